@@ -44,6 +44,36 @@ fn main() {
     }
     println!("cargo:rustc-env=EXT4_FS_IMAGE={}", ext4_embed_img.display());
 
+    // 启动自检用的用户态程序，由 kernel::selftest 嵌入
+    let selftest_src = PathBuf::from(&manifest_dir)
+        .join("selftest")
+        .join("stub.rs");
+    let selftest_elf = PathBuf::from(&out_dir).join("selftest_stub.elf");
+    println!("cargo:rerun-if-changed={}", selftest_src.display());
+    build_user_elf(&target, &selftest_src, &selftest_elf);
+    println!(
+        "cargo:rustc-env=SELFTEST_STUB_ELF={}",
+        selftest_elf.display()
+    );
+
+    // 随机系统调用模糊测试程序，由 kernel::fuzz 嵌入
+    let fuzz_src = project_root
+        .join("user")
+        .join("fuzz")
+        .join("src")
+        .join("main.rs");
+    let fuzz_elf = PathBuf::from(&out_dir).join("fuzz.elf");
+    println!("cargo:rerun-if-changed={}", fuzz_src.display());
+    build_user_elf(&target, &fuzz_src, &fuzz_elf);
+    println!("cargo:rustc-env=FUZZ_ELF={}", fuzz_elf.display());
+
+    // 系统调用一致性测试程序与期望输出，由 kernel::conformance 嵌入
+    let conformance_cases = build_conformance(&target, &project_root, Path::new(&out_dir));
+    println!(
+        "cargo:rustc-env=CONFORMANCE_CASES={}",
+        conformance_cases.display()
+    );
+
     // 检测是否为测试模式（用于跳过 3.2 的运行时镜像生成）
     let is_test = env::var("TEST").is_ok()
         || env::var("CARGO_CFG_TEST").is_ok()
@@ -195,6 +225,101 @@ fn create_empty_image(path: &PathBuf) {
     }
 }
 
+/// 把不依赖用户库的单文件程序 `src` 编译为目标架构的静态 ELF
+///
+/// 用于启动自检、一致性测试和模糊测试。非目标架构或编译失败时写入空文件，
+/// 内核在运行时会报告程序缺失。
+fn build_user_elf(target: &str, src: &Path, out: &Path) {
+    let is_target_arch = target.contains("riscv64") || target.contains("loongarch");
+    if is_target_arch {
+        let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let status = Command::new(rustc)
+            .args([
+                "--target",
+                target,
+                "--edition",
+                "2024",
+                "--crate-type",
+                "bin",
+            ])
+            .args(["-C", "panic=abort", "-C", "opt-level=s"])
+            .arg("-o")
+            .arg(out)
+            .arg(src)
+            .status();
+        match status {
+            Ok(s) if s.success() => return,
+            Ok(s) => println!(
+                "cargo:warning=[build.rs] Failed to build {}: rustc exited with {}",
+                src.display(),
+                s
+            ),
+            Err(e) => println!(
+                "cargo:warning=[build.rs] Failed to build {}: {}",
+                src.display(),
+                e
+            ),
+        }
+    }
+    if let Err(e) = fs::write(out, []) {
+        println!(
+            "cargo:warning=[build.rs] Failed to create empty {}: {}",
+            out.display(),
+            e
+        );
+    }
+}
+
+/// 编译 `user/conformance` 下的一致性测试程序，生成 `kernel::conformance` 的用例表
+///
+/// `src/bin/<name>.rs` 必须有对应的 `expected/<name>.out`。返回生成的用例表路径，
+/// 其内容是一个 `&[ConformanceCase]` 表达式，按程序名排序。
+fn build_conformance(target: &str, project_root: &Path, out_dir: &Path) -> PathBuf {
+    let root = project_root.join("user").join("conformance");
+    let bin_dir = root.join("src").join("bin");
+    let expected_dir = root.join("expected");
+    println!("cargo:rerun-if-changed={}", root.join("src").display());
+    println!("cargo:rerun-if-changed={}", expected_dir.display());
+
+    let mut names: Vec<String> = fs::read_dir(&bin_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+                .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+
+    let mut cases = String::from("&[\n");
+    for name in &names {
+        let expected = expected_dir.join(format!("{}.out", name));
+        if !expected.exists() {
+            println!(
+                "cargo:warning=[build.rs] Conformance program {} has no {}",
+                name,
+                expected.display()
+            );
+            continue;
+        }
+        let elf = out_dir.join(format!("conformance_{}.elf", name));
+        build_user_elf(target, &bin_dir.join(format!("{}.rs", name)), &elf);
+        cases.push_str(&format!(
+            "    ConformanceCase {{ name: {:?}, elf: include_bytes!({:?}), expected: include_str!({:?}) }},\n",
+            name,
+            elf.display().to_string(),
+            expected.display().to_string()
+        ));
+    }
+    cases.push_str("]\n");
+
+    let path = out_dir.join("conformance_cases.rs");
+    fs::write(&path, cases).expect("Failed to write conformance case table");
+    path
+}
+
 /// 创建 ext4 测试镜像 (8MB)
 fn create_ext4_test_image(path: &PathBuf) {
     create_empty_ext4_image(path, 8);
@@ -0,0 +1,371 @@
+//! 内核启动自检用的用户态程序
+//!
+//! 由 `build.rs` 单独编译为静态 ELF 并嵌入内核，在命令行带 `selftest` 时由
+//! `kernel::selftest` 在启动 /sbin/init 之前运行。程序不依赖任何用户库，只通过
+//! 系统调用检查内核的用户态 ABI：
+//!
+//! - 初始栈：sp 对齐、argc/argv/envp 内容与 NULL 结尾、auxv 对齐与各项取值
+//! - 信号：`rt_sigaction` + `kill` 自身后处理函数被调用，`rt_sigreturn` 恢复现场与屏蔽字
+//! - 基本系统调用：getpid、brk、mmap/munmap、clock_gettime、uname、write
+//!
+//! 全部通过时以 0 退出，否则以第一个失败检查项的编号退出（见 [`Check`]）。
+//! argv/envp 必须与 `kernel::selftest` 传入的一致。
+//!
+//! 系统调用号取自 Linux 通用表（asm-generic/unistd.h），RISC-V 与 LoongArch 相同。
+
+#![no_std]
+#![no_main]
+
+use core::arch::{asm, global_asm};
+use core::ptr;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(target_arch = "riscv64")]
+global_asm!(".globl _start", "_start:", "mv a0, sp", "call main");
+#[cfg(target_arch = "loongarch64")]
+global_asm!(".globl _start", "_start:", "move $a0, $sp", "bl main");
+
+const SYS_WRITE: usize = 64;
+const SYS_EXIT_GROUP: usize = 94;
+const SYS_CLOCK_GETTIME: usize = 113;
+const SYS_KILL: usize = 129;
+const SYS_RT_SIGACTION: usize = 134;
+const SYS_RT_SIGPROCMASK: usize = 135;
+const SYS_UNAME: usize = 160;
+const SYS_GETPID: usize = 172;
+const SYS_BRK: usize = 214;
+const SYS_MUNMAP: usize = 215;
+const SYS_MMAP: usize = 222;
+
+const AT_NULL: usize = 0;
+const AT_PHDR: usize = 3;
+const AT_PHENT: usize = 4;
+const AT_PHNUM: usize = 5;
+const AT_PAGESZ: usize = 6;
+const AT_ENTRY: usize = 9;
+const AT_RANDOM: usize = 25;
+const AT_EXECFN: usize = 31;
+
+const SIGUSR1: usize = 10;
+const SA_SIGINFO: usize = 4;
+const SIG_BLOCK: usize = 0;
+const CLOCK_MONOTONIC: usize = 1;
+const PROT_READ_WRITE: usize = 0x3;
+const MAP_PRIVATE_ANONYMOUS: usize = 0x22;
+const PAGE_SIZE: usize = 4096;
+
+/// 与 `kernel::selftest` 约定的参数
+const EXPECTED_ARGV: [&[u8]; 2] = [b"selftest", b"arg1"];
+const EXPECTED_ENVP: [&[u8]; 1] = [b"SELFTEST=1"];
+
+/// 检查项编号，即失败时的退出码
+#[derive(Clone, Copy)]
+#[repr(i32)]
+enum Check {
+    StackAlign = 1,
+    Argc = 2,
+    Argv = 3,
+    Envp = 4,
+    AuxvAlign = 5,
+    AuxvPagesz = 6,
+    AuxvEntry = 7,
+    AuxvPhdr = 8,
+    AuxvRandom = 9,
+    AuxvExecfn = 10,
+    Sigaction = 11,
+    SignalDelivery = 12,
+    SignalInfo = 13,
+    SignalReturn = 14,
+    Getpid = 15,
+    Brk = 16,
+    Mmap = 17,
+    ClockGettime = 18,
+    Uname = 19,
+    Write = 20,
+}
+
+#[repr(C)]
+struct SigAction {
+    handler: usize,
+    flags: usize,
+    restorer: usize,
+    mask: u64,
+}
+
+#[repr(C)]
+struct TimeSpec {
+    sec: i64,
+    nsec: i64,
+}
+
+/// 处理函数被调用的次数
+static SIGNAL_COUNT: AtomicUsize = AtomicUsize::new(0);
+/// 处理函数看到的 signo、siginfo.si_signo 与 ucontext 地址
+static SIGNAL_SIGNO: AtomicUsize = AtomicUsize::new(0);
+static SIGNAL_SI_SIGNO: AtomicUsize = AtomicUsize::new(0);
+static SIGNAL_UCONTEXT: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(target_arch = "riscv64")]
+unsafe fn syscall(id: usize, args: [usize; 6]) -> isize {
+    let ret: isize;
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") args[0] => ret,
+            in("a1") args[1],
+            in("a2") args[2],
+            in("a3") args[3],
+            in("a4") args[4],
+            in("a5") args[5],
+            in("a7") id,
+        );
+    }
+    ret
+}
+
+#[cfg(target_arch = "loongarch64")]
+unsafe fn syscall(id: usize, args: [usize; 6]) -> isize {
+    let ret: isize;
+    unsafe {
+        asm!(
+            "syscall 0",
+            inlateout("$a0") args[0] => ret,
+            in("$a1") args[1],
+            in("$a2") args[2],
+            in("$a3") args[3],
+            in("$a4") args[4],
+            in("$a5") args[5],
+            in("$a7") id,
+        );
+    }
+    ret
+}
+
+fn write(fd: usize, buf: &[u8]) -> isize {
+    unsafe { syscall(SYS_WRITE, [fd, buf.as_ptr() as usize, buf.len(), 0, 0, 0]) }
+}
+
+fn exit(code: i32) -> ! {
+    unsafe { syscall(SYS_EXIT_GROUP, [code as usize, 0, 0, 0, 0, 0]) };
+    loop {}
+}
+
+fn fail(check: Check) -> ! {
+    let code = check as i32;
+    let msg = *b"selftest: check 00 failed\n";
+    let mut msg = msg;
+    msg[16] = b'0' + (code / 10) as u8;
+    msg[17] = b'0' + (code % 10) as u8;
+    write(2, &msg);
+    exit(code)
+}
+
+fn ensure(cond: bool, check: Check) {
+    if !cond {
+        fail(check);
+    }
+}
+
+/// 比较以 NUL 结尾的 C 字符串与 `expected`
+unsafe fn cstr_eq(p: *const u8, expected: &[u8]) -> bool {
+    if p.is_null() {
+        return false;
+    }
+    for (i, &b) in expected.iter().enumerate() {
+        if unsafe { p.add(i).read() } != b {
+            return false;
+        }
+    }
+    unsafe { p.add(expected.len()).read() == 0 }
+}
+
+extern "C" fn on_sigusr1(signo: usize, info: *const i32, ucontext: usize) {
+    SIGNAL_SIGNO.store(signo, Ordering::Relaxed);
+    if !info.is_null() {
+        SIGNAL_SI_SIGNO.store(unsafe { info.read() } as usize, Ordering::Relaxed);
+    }
+    SIGNAL_UCONTEXT.store(ucontext, Ordering::Relaxed);
+    SIGNAL_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 检查初始栈与 auxv
+unsafe fn check_initial_stack(sp: *const usize) {
+    ensure(sp as usize % 16 == 0, Check::StackAlign);
+
+    let argc = unsafe { sp.read() };
+    ensure(argc == EXPECTED_ARGV.len(), Check::Argc);
+    let argv = unsafe { sp.add(1) } as *const *const u8;
+    for (i, expected) in EXPECTED_ARGV.iter().enumerate() {
+        ensure(unsafe { cstr_eq(argv.add(i).read(), expected) }, Check::Argv);
+    }
+    ensure(unsafe { argv.add(argc).read() }.is_null(), Check::Argv);
+
+    let envp = unsafe { argv.add(argc + 1) };
+    for (i, expected) in EXPECTED_ENVP.iter().enumerate() {
+        ensure(unsafe { cstr_eq(envp.add(i).read(), expected) }, Check::Envp);
+    }
+    ensure(
+        unsafe { envp.add(EXPECTED_ENVP.len()).read() }.is_null(),
+        Check::Envp,
+    );
+
+    let mut auxv = unsafe { envp.add(EXPECTED_ENVP.len() + 1) } as *const usize;
+    ensure(auxv as usize % 8 == 0, Check::AuxvAlign);
+    let (mut pagesz, mut entry, mut phdr, mut phent, mut phnum, mut random, mut execfn) =
+        (0, 0, 0, 0, 0, 0, 0);
+    loop {
+        let (key, value) = unsafe { (auxv.read(), auxv.add(1).read()) };
+        match key {
+            AT_NULL => break,
+            AT_PAGESZ => pagesz = value,
+            AT_ENTRY => entry = value,
+            AT_PHDR => phdr = value,
+            AT_PHENT => phent = value,
+            AT_PHNUM => phnum = value,
+            AT_RANDOM => random = value,
+            AT_EXECFN => execfn = value,
+            _ => {}
+        }
+        auxv = unsafe { auxv.add(2) };
+    }
+
+    unsafe extern "C" {
+        fn _start();
+    }
+    ensure(pagesz == PAGE_SIZE, Check::AuxvPagesz);
+    ensure(entry == _start as usize, Check::AuxvEntry);
+    // 程序头表必须可读，且第一项是装入了 ELF 头之后的某个段
+    ensure(phdr != 0 && phent == 56 && phnum > 0, Check::AuxvPhdr);
+    let p_type = unsafe { (phdr as *const u32).read() };
+    ensure(p_type != 0, Check::AuxvPhdr);
+    ensure(random != 0, Check::AuxvRandom);
+    let random = unsafe { ptr::read_unaligned(random as *const [u8; 16]) };
+    ensure(random.iter().any(|&b| b != 0), Check::AuxvRandom);
+    ensure(
+        unsafe { cstr_eq(execfn as *const u8, EXPECTED_ARGV[0]) },
+        Check::AuxvExecfn,
+    );
+}
+
+/// 向自身发送 SIGUSR1，检查处理函数的参数与 sigreturn 后的现场
+fn check_signal() {
+    let act = SigAction {
+        handler: on_sigusr1 as usize,
+        flags: SA_SIGINFO,
+        restorer: 0,
+        mask: 0,
+    };
+    let ret = unsafe {
+        syscall(
+            SYS_RT_SIGACTION,
+            [SIGUSR1, &act as *const _ as usize, 0, 8, 0, 0],
+        )
+    };
+    ensure(ret == 0, Check::Sigaction);
+
+    // 信号返回后栈上的值和后续代码都应不受影响
+    let canary = core::hint::black_box(0x5a5a_a5a5_usize);
+    let pid = unsafe { syscall(SYS_GETPID, [0; 6]) } as usize;
+    let ret = unsafe { syscall(SYS_KILL, [pid, SIGUSR1, 0, 0, 0, 0]) };
+    ensure(ret == 0, Check::SignalDelivery);
+    ensure(
+        SIGNAL_COUNT.load(Ordering::Relaxed) == 1,
+        Check::SignalDelivery,
+    );
+    ensure(
+        SIGNAL_SIGNO.load(Ordering::Relaxed) == SIGUSR1
+            && SIGNAL_SI_SIGNO.load(Ordering::Relaxed) == SIGUSR1,
+        Check::SignalInfo,
+    );
+    let ucontext = SIGNAL_UCONTEXT.load(Ordering::Relaxed);
+    ensure(ucontext != 0 && ucontext % 8 == 0, Check::SignalInfo);
+    ensure(core::hint::black_box(canary) == 0x5a5a_a5a5, Check::SignalReturn);
+
+    // 处理期间被屏蔽的 SIGUSR1 应在 rt_sigreturn 时解除
+    let mut old: u64 = u64::MAX;
+    let ret = unsafe {
+        syscall(
+            SYS_RT_SIGPROCMASK,
+            [SIG_BLOCK, 0, &mut old as *mut u64 as usize, 8, 0, 0],
+        )
+    };
+    ensure(
+        ret == 0 && old & (1 << (SIGUSR1 - 1)) == 0,
+        Check::SignalReturn,
+    );
+}
+
+fn check_basic_syscalls() {
+    let pid = unsafe { syscall(SYS_GETPID, [0; 6]) };
+    ensure(pid > 1, Check::Getpid);
+
+    let cur = unsafe { syscall(SYS_BRK, [0; 6]) };
+    ensure(cur > 0, Check::Brk);
+    let want = cur as usize + PAGE_SIZE;
+    let new = unsafe { syscall(SYS_BRK, [want, 0, 0, 0, 0, 0]) };
+    ensure(new as usize == want, Check::Brk);
+    unsafe {
+        (cur as *mut u8).write_volatile(0xa5);
+        ensure((cur as *const u8).read_volatile() == 0xa5, Check::Brk);
+    }
+
+    let addr = unsafe {
+        syscall(
+            SYS_MMAP,
+            [
+                0,
+                PAGE_SIZE,
+                PROT_READ_WRITE,
+                MAP_PRIVATE_ANONYMOUS,
+                usize::MAX,
+                0,
+            ],
+        )
+    };
+    ensure(addr > 0 && addr as usize % PAGE_SIZE == 0, Check::Mmap);
+    unsafe {
+        // 匿名映射初始为零
+        ensure((addr as *const u64).read_volatile() == 0, Check::Mmap);
+        (addr as *mut u64).write_volatile(0x1234_5678);
+        ensure(
+            (addr as *const u64).read_volatile() == 0x1234_5678,
+            Check::Mmap,
+        );
+    }
+    let ret = unsafe { syscall(SYS_MUNMAP, [addr as usize, PAGE_SIZE, 0, 0, 0, 0]) };
+    ensure(ret == 0, Check::Mmap);
+
+    let mut ts = TimeSpec { sec: -1, nsec: -1 };
+    let ret = unsafe {
+        syscall(
+            SYS_CLOCK_GETTIME,
+            [CLOCK_MONOTONIC, &mut ts as *mut TimeSpec as usize, 0, 0, 0, 0],
+        )
+    };
+    ensure(
+        ret == 0 && ts.sec >= 0 && (0..1_000_000_000).contains(&ts.nsec),
+        Check::ClockGettime,
+    );
+
+    // struct utsname: 6 个 65 字节的字段，第一个是 sysname
+    let mut uts = [0u8; 65 * 6];
+    let ret = unsafe { syscall(SYS_UNAME, [uts.as_mut_ptr() as usize, 0, 0, 0, 0, 0]) };
+    ensure(ret == 0 && uts[0] != 0 && uts[64] == 0, Check::Uname);
+
+    let msg = b"selftest: all checks passed\n";
+    ensure(write(1, msg) == msg.len() as isize, Check::Write);
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn main(sp: *const usize) -> ! {
+    // SAFETY: `_start` 传入的是进程的初始栈指针
+    unsafe { check_initial_stack(sp) };
+    check_signal();
+    check_basic_syscalls();
+    exit(0)
+}
+
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    exit(-1)
+}
@@ -65,24 +65,3 @@ pub fn resolve_relocation_value(
     };
     (base as isize + addend) as usize
 }
-
-/// Static `/proc/cpuinfo` content for the active target.
-pub fn proc_cpuinfo_bytes() -> &'static [u8] {
-    #[cfg(target_arch = "riscv64")]
-    {
-        b"processor\t: 0\n\
-hart\t\t: 0\n\
-isa\t\t: rv64imafdcsu\n\
-mmu\t\t: sv39\n\
-uarch\t\t: qemu,virt\n\n"
-    }
-    #[cfg(target_arch = "loongarch64")]
-    {
-        b"processor\t: 0\n\
-arch\t\t: loongarch64\n\n"
-    }
-    #[cfg(not(any(target_arch = "riscv64", target_arch = "loongarch64")))]
-    {
-        b"processor\t: 0\narch\t\t: mock\n\n"
-    }
-}
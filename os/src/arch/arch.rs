@@ -35,6 +35,12 @@ pub trait HwTrapFrame: Copy + Debug + Send + Sync + SyscallFrame + 'static {
     );
     unsafe fn set_fork_trap_frame(&mut self, parent_frame: &Self);
     fn get_sp(&self) -> usize;
+    /// 帧指针（riscv 的 s0，loongarch 的 $fp）
+    fn get_fp(&self) -> usize;
+    /// 返回地址寄存器
+    fn get_ra(&self) -> usize;
+    /// 按寄存器编号排列的 32 个通用寄存器，0 号恒为 0
+    fn gprs(&self) -> [usize; 32];
     fn set_sp(&mut self, val: usize);
     fn set_a0(&mut self, val: usize);
     fn set_a1(&mut self, val: usize);
@@ -54,8 +54,8 @@ macro_rules! impl_arch {
                 if len != 0 && dst.is_null() {
                     return Err(PagingError::InvalidAddress);
                 }
-                let _guard = trap::SumGuard::new();
-                unsafe { core::ptr::copy_nonoverlapping(src as *const u8, dst, len) };
+                let guard = $crate::arch::uaccess::UserAccessGuard::new();
+                unsafe { $crate::arch::uaccess::copy_from_user_raw(&guard, src, dst, len) };
                 Ok(())
             }
 
@@ -77,8 +77,8 @@ macro_rules! impl_arch {
                 if len != 0 && src.is_null() {
                     return Err(PagingError::InvalidAddress);
                 }
-                let _guard = trap::SumGuard::new();
-                unsafe { core::ptr::copy_nonoverlapping(src, dst as *mut u8, len) };
+                let guard = $crate::arch::uaccess::UserAccessGuard::new();
+                unsafe { $crate::arch::uaccess::copy_to_user_raw(&guard, src, dst, len) };
                 Ok(())
             }
 
@@ -94,12 +94,12 @@ macro_rules! impl_arch {
                 if max_len != 0 && dst.is_null() {
                     return Err(PagingError::InvalidAddress);
                 }
-                let _guard = trap::SumGuard::new();
+                let guard = $crate::arch::uaccess::UserAccessGuard::new();
                 let mut i = 0;
                 while i < max_len {
                     let cur = src.checked_add(i).ok_or(PagingError::InvalidAddress)?;
                     validate_user_copy_range(cur, 1, false)?;
-                    let byte = unsafe { core::ptr::read_volatile(cur as *const u8) };
+                    let byte = unsafe { $crate::arch::uaccess::read_user_byte_raw(&guard, cur) };
                     unsafe { *dst.add(i) = byte };
                     if byte == 0 {
                         return Ok(i);
@@ -160,11 +160,17 @@ macro_rules! impl_arch {
             }
 
             let space = $crate::kernel::current_memory_space();
-            let guard = space.lock();
+            let mut guard = space.lock();
             let mut cur = start;
             while cur < end {
                 let vpn = Vpn::from_addr_floor(VA::from_usize(cur));
-                let (_, _, flags) = guard.page_table().walk(vpn)?;
+                // 被换出的页先换入
+                let (_, _, flags) = match guard.page_table().walk(vpn) {
+                    Err(PagingError::NotMapped) if guard.swap_in(vpn) => {
+                        guard.page_table().walk(vpn)?
+                    }
+                    walked => walked?,
+                };
                 let required = UniversalPTEFlag::VALID | UniversalPTEFlag::USER_ACCESSIBLE;
                 if !flags.contains(required) {
                     return Err(PagingError::PermissionDenied);
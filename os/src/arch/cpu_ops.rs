@@ -1,6 +1,6 @@
 //! CpuOps — 最底层架构抽象 trait
 //!
-//! 将架构相关操作缩小到最少 6 个方法，使得 sync/memory 等模块完全可移植。
+//! 将架构相关操作缩小到最少 7 个方法，使得 sync/memory 等模块完全可移植。
 
 /// CPU 操作抽象 trait。
 ///
@@ -9,15 +9,25 @@
 ///
 /// # 移植要点
 ///
-/// 这是移植新架构时第一个需要实现的 trait。只需 6 个方法，实现后即可编译
+/// 这是移植新架构时第一个需要实现的 trait。只需 7 个方法，实现后即可编译
 /// 同步原语和内存分配器等核心模块。
 pub trait CpuOps: 'static {
     /// 获取当前 CPU 核心 ID
     fn id() -> usize;
 
     /// 停止 CPU，永不返回
+    ///
+    /// idle 循环改用 [`CpuOps::wait_for_interrupt`] 以便统计空闲时间，
+    /// 这里保留给不需要返回的停机路径。
+    #[allow(dead_code)]
     fn halt() -> !;
 
+    /// 让 CPU 进入低功耗等待，直到有中断挂起后返回
+    ///
+    /// 即使当前全局中断处于禁用状态，挂起的中断也会唤醒 CPU；
+    /// 中断本身要等调用者重新开中断后才会被处理。
+    fn wait_for_interrupt();
+
     /// 禁用中断并返回之前的中断状态
     ///
     /// 返回的 `usize` 值可用于 `restore_interrupt_state` 恢复之前的状态。
@@ -42,4 +52,11 @@ pub trait CpuOps: 'static {
     fn interrupt_was_enabled(flags: usize) -> bool {
         flags & 1 != 0
     }
+
+    /// 读取当前帧指针，用于栈回溯
+    ///
+    /// 默认返回 0，表示不支持回溯。
+    fn frame_pointer() -> usize {
+        0
+    }
 }
@@ -39,6 +39,16 @@ impl CpuOps for LoongArch64 {
         }
     }
 
+    /// 执行一次 `idle 0`
+    ///
+    /// ECFG 中已使能的中断挂起时即被唤醒，与 CRMD.IE 无关。
+    #[inline]
+    fn wait_for_interrupt() {
+        unsafe {
+            core::arch::asm!("idle 0", options(nomem, nostack));
+        }
+    }
+
     /// 原子地禁用中断并返回之前的中断状态
     ///
     /// 通过修改 CRMD 寄存器的 IE 位来实现。
@@ -111,4 +121,16 @@ impl CpuOps for LoongArch64 {
     fn interrupt_was_enabled(flags: usize) -> bool {
         flags & CSR_CRMD_IE != 0
     }
+
+    /// 读取帧指针寄存器
+    ///
+    /// 内核以 `force-frame-pointers` 编译，内联到调用者后得到的是调用者的帧。
+    #[inline(always)]
+    fn frame_pointer() -> usize {
+        let fp: usize;
+        unsafe {
+            core::arch::asm!("move {}, $fp", out(reg) fp, options(nomem, nostack));
+        }
+        fp
+    }
 }
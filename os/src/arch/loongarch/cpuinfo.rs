@@ -0,0 +1,211 @@
+//! LoongArch 的 `/proc/cpuinfo`
+//!
+//! 处理器特性来自 CPUCFG 指令，型号名来自设备树 CPU 节点的 `compatible`。
+//! CPUCFG 只能读取执行它的 CPU，各核按同构处理，都使用当前 CPU 的结果。
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::device::device_tree::{self, DtCpu};
+
+/// CPUCFG 1：ISA 位宽、地址位数与杂项特性
+const CPUCFG1_ISGR32: u32 = 1 << 0;
+const CPUCFG1_ISGR64: u32 = 1 << 1;
+const CPUCFG1_UAL: u32 = 1 << 20;
+const CPUCFG1_CRC32: u32 = 1 << 25;
+
+/// CPUCFG 2：浮点、向量与虚拟化相关特性
+const CPUCFG2_FP: u32 = 1 << 0;
+const CPUCFG2_LSX: u32 = 1 << 6;
+const CPUCFG2_LASX: u32 = 1 << 7;
+const CPUCFG2_COMPLEX: u32 = 1 << 8;
+const CPUCFG2_CRYPTO: u32 = 1 << 9;
+const CPUCFG2_LVZP: u32 = 1 << 10;
+const CPUCFG2_X86BT: u32 = 1 << 18;
+const CPUCFG2_ARMBT: u32 = 1 << 19;
+const CPUCFG2_MIPSBT: u32 = 1 << 20;
+const CPUCFG2_LSPW: u32 = 1 << 21;
+const CPUCFG2_LAM: u32 = 1 << 22;
+const CPUCFG2_PTW: u32 = 1 << 24;
+
+/// Features 行的内容：(配置字编号, 位, 名称)，按 Linux 的输出顺序
+const FEATURES: &[(u32, u32, &str)] = &[
+    (2, CPUCFG2_LAM, "lam"),
+    (1, CPUCFG1_UAL, "ual"),
+    (2, CPUCFG2_FP, "fpu"),
+    (2, CPUCFG2_LSX, "lsx"),
+    (2, CPUCFG2_LASX, "lasx"),
+    (1, CPUCFG1_CRC32, "crc32"),
+    (2, CPUCFG2_COMPLEX, "complex"),
+    (2, CPUCFG2_CRYPTO, "crypto"),
+    (2, CPUCFG2_PTW, "ptw"),
+    (2, CPUCFG2_LSPW, "lspw"),
+    (2, CPUCFG2_LVZP, "lvz"),
+    (2, CPUCFG2_X86BT, "lbt_x86"),
+    (2, CPUCFG2_ARMBT, "lbt_arm"),
+    (2, CPUCFG2_MIPSBT, "lbt_mips"),
+];
+
+/// 执行 `cpucfg` 读取配置字
+fn cpucfg(word: u32) -> u32 {
+    let value: usize;
+    // SAFETY: cpucfg 只读取处理器配置信息，没有副作用
+    unsafe {
+        core::arch::asm!(
+            "cpucfg {value}, {word}",
+            value = out(reg) value,
+            word = in(reg) word as usize,
+            options(nomem, nostack, preserves_flags)
+        );
+    }
+    value as u32
+}
+
+/// 解码后的 CPUCFG 信息
+struct CpuConfig {
+    prid: u32,
+    cfg1: u32,
+    cfg2: u32,
+    /// 恒定频率计时器（rdtime 的时基）频率，单位 Hz；为 0 表示未提供
+    counter_freq: u64,
+}
+
+impl CpuConfig {
+    fn read() -> Self {
+        Self::decode([cpucfg(0), cpucfg(1), cpucfg(2), cpucfg(4), cpucfg(5)])
+    }
+
+    /// 由 CPUCFG 0、1、2、4、5 号配置字构造
+    fn decode([prid, cfg1, cfg2, cfg4, cfg5]: [u32; 5]) -> Self {
+        // CPUCFG5 的低 16 位是倍频，高 16 位是分频
+        let mul = (cfg5 & 0xffff) as u64;
+        let div = (cfg5 >> 16) as u64;
+        let counter_freq = if mul == 0 || div == 0 {
+            cfg4 as u64
+        } else {
+            cfg4 as u64 * mul / div
+        };
+        Self {
+            prid,
+            cfg1,
+            cfg2,
+            counter_freq,
+        }
+    }
+
+    /// 物理地址位数（CPUCFG1[11:4] + 1）
+    fn pa_bits(&self) -> u32 {
+        ((self.cfg1 >> 4) & 0xff) + 1
+    }
+
+    /// 虚拟地址位数（CPUCFG1[19:12] + 1）
+    fn va_bits(&self) -> u32 {
+        ((self.cfg1 >> 12) & 0xff) + 1
+    }
+
+    /// 浮点单元版本（CPUCFG2[5:3]）
+    fn fpu_revision(&self) -> u32 {
+        (self.cfg2 >> 3) & 0x7
+    }
+
+    fn isa(&self) -> Vec<&'static str> {
+        let mut isa = Vec::new();
+        if self.cfg1 & CPUCFG1_ISGR32 != 0 {
+            isa.push("loongarch32");
+        }
+        if self.cfg1 & CPUCFG1_ISGR64 != 0 {
+            isa.push("loongarch64");
+        }
+        isa
+    }
+
+    /// 特性名，与 Linux `/proc/cpuinfo` 的 Features 行一致
+    fn features(&self) -> Vec<&'static str> {
+        let mut features = alloc::vec!["cpucfg"];
+        features.extend(FEATURES.iter().filter_map(|&(word, bit, name)| {
+            let cfg = if word == 1 { self.cfg1 } else { self.cfg2 };
+            (cfg & bit != 0).then_some(name)
+        }));
+        features
+    }
+}
+
+/// 生成 `/proc/cpuinfo` 的内容，每个 CPU 一段
+pub fn cpuinfo() -> String {
+    let mut cpus = device_tree::cpu_nodes();
+    if cpus.is_empty() {
+        cpus = (0..crate::kernel::num_cpu())
+            .map(|core| DtCpu {
+                hw_id: core,
+                ..DtCpu::default()
+            })
+            .collect();
+    }
+    let config = CpuConfig::read();
+    let system_type =
+        device_tree::model().unwrap_or_else(|| String::from("Generic Loongson64 System"));
+
+    let mut out = String::new();
+    for (processor, cpu) in cpus.iter().enumerate() {
+        let _ = write_cpu(&mut out, &system_type, processor, cpu, &config);
+    }
+    out
+}
+
+fn write_cpu(
+    out: &mut String,
+    system_type: &str,
+    processor: usize,
+    cpu: &DtCpu,
+    config: &CpuConfig,
+) -> core::fmt::Result {
+    writeln!(out, "system type\t\t: {}", system_type)?;
+    writeln!(out, "processor\t\t: {}", processor)?;
+    writeln!(out, "package\t\t\t: 0")?;
+    writeln!(out, "core\t\t\t: {}", cpu.hw_id)?;
+    writeln!(out, "CPU Family\t\t: Loongson-64bit")?;
+    writeln!(
+        out,
+        "Model Name\t\t: {}",
+        cpu.compatible.first().map_or("unknown", String::as_str)
+    )?;
+    writeln!(out, "PRID\t\t\t: {:#010x}", config.prid)?;
+    writeln!(out, "CPU Revision\t\t: 0x{:02x}", config.prid & 0xff)?;
+    writeln!(out, "FPU Revision\t\t: 0x{:02x}", config.fpu_revision())?;
+    writeln!(
+        out,
+        "Address Sizes\t\t: {} bits physical, {} bits virtual",
+        config.pa_bits(),
+        config.va_bits()
+    )?;
+    writeln!(out, "ISA\t\t\t: {}", config.isa().join(" "))?;
+    writeln!(out, "Features\t\t: {}", config.features().join(" "))?;
+    writeln!(out, "Timebase\t\t: {}", config.counter_freq)?;
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_loongarch_cpucfg_decode, {
+        // QEMU la464：LA64，48 位物理/虚拟地址，UAL、CRC32，FP、LSX、LASX、LAM
+        let cfg1 = CPUCFG1_ISGR64 | (47 << 4) | (47 << 12) | CPUCFG1_UAL | CPUCFG1_CRC32;
+        let cfg2 = CPUCFG2_FP | (1 << 3) | CPUCFG2_LSX | CPUCFG2_LASX | CPUCFG2_LAM;
+        let config = CpuConfig::decode([0x14c010, cfg1, cfg2, 100_000_000, (1 << 16) | 1]);
+
+        kassert!(config.pa_bits() == 48);
+        kassert!(config.va_bits() == 48);
+        kassert!(config.fpu_revision() == 1);
+        kassert!(config.isa() == ["loongarch64"]);
+        kassert!(config.features() == ["cpucfg", "lam", "ual", "fpu", "lsx", "lasx", "crc32"]);
+        kassert!(config.counter_freq == 100_000_000);
+
+        // 倍频/分频为 0 时直接使用基准频率
+        let config = CpuConfig::decode([0, 0, 0, 25_000_000, 0]);
+        kassert!(config.counter_freq == 25_000_000);
+        kassert!(config.features() == ["cpucfg"]);
+    });
+}
@@ -73,6 +73,13 @@ pub unsafe fn enable_timer_interrupt() {
     unsafe { update_ecfg(TIMER_LIE_BIT, true) };
 }
 
+/// 禁用定时器中断（仅清除本地定时器使能位）
+/// # Safety
+/// 直接操作 CSR，调用者负责之后重新启用定时器中断
+pub unsafe fn disable_timer_interrupt() {
+    unsafe { update_ecfg(TIMER_LIE_BIT, false) };
+}
+
 /// 启用全局中断
 /// # Safety
 /// 直接操作 CSR 寄存器
@@ -117,16 +124,97 @@ pub unsafe fn restore_interrupts(flags: usize) {
     }
 }
 
-/// 启用指定 IRQ（LoongArch 目前仅处理本地中断位，其他由中断控制器驱动负责）
-#[allow(dead_code)]
-pub fn enable_irq(_irq: usize) {
-    // 留给外部中断控制器驱动实现
+/// 中断号到 ECFG.LIE 位的转换
+///
+/// 根中断管理器使用 trap cause 编码（最高位置位，低位为 ESTAT.IS 中的位号），
+/// 例如 `SUPERVISOR_EXTERNAL` 对应 HWI0。
+fn irq_lie_bit(irq: usize) -> Option<usize> {
+    let bit = irq.checked_sub(usize::MAX / 2 + 1)?;
+    let mask = 1usize.checked_shl(bit as u32)?;
+    (mask & CSR_ECFG_LIE_MASK != 0).then_some(mask)
+}
+
+/// 启用指定 IRQ（只处理本地中断位，外设中断由中断控制器驱动负责）
+pub fn enable_irq(irq: usize) {
+    if let Some(mask) = irq_lie_bit(irq) {
+        unsafe { update_ecfg(mask, true) };
+    }
 }
 
 /// 禁用指定 IRQ
 #[allow(dead_code)]
-pub fn disable_irq(_irq: usize) {
-    // 留给外部中断控制器驱动实现
+pub fn disable_irq(irq: usize) {
+    if let Some(mask) = irq_lie_bit(irq) {
+        unsafe { update_ecfg(mask, false) };
+    }
+}
+
+/// 读取 32 位 IOCSR 寄存器
+///
+/// # Safety
+/// `reg` 必须是有效的 IOCSR 地址
+#[inline(always)]
+pub unsafe fn iocsr_read32(reg: usize) -> u32 {
+    let value: u32;
+    unsafe {
+        core::arch::asm!(
+            "iocsrrd.w {value}, {reg}",
+            value = out(reg) value,
+            reg = in(reg) reg,
+            options(nostack, preserves_flags)
+        );
+    }
+    value
+}
+
+/// 写入 32 位 IOCSR 寄存器
+///
+/// # Safety
+/// `reg` 必须是有效的 IOCSR 地址，写入值符合寄存器语义
+#[inline(always)]
+pub unsafe fn iocsr_write32(reg: usize, value: u32) {
+    unsafe {
+        core::arch::asm!(
+            "iocsrwr.w {value}, {reg}",
+            value = in(reg) value,
+            reg = in(reg) reg,
+            options(nostack, preserves_flags)
+        );
+    }
+}
+
+/// 读取 64 位 IOCSR 寄存器
+///
+/// # Safety
+/// `reg` 必须是有效的 IOCSR 地址
+#[inline(always)]
+pub unsafe fn iocsr_read64(reg: usize) -> u64 {
+    let value: u64;
+    unsafe {
+        core::arch::asm!(
+            "iocsrrd.d {value}, {reg}",
+            value = out(reg) value,
+            reg = in(reg) reg,
+            options(nostack, preserves_flags)
+        );
+    }
+    value
+}
+
+/// 写入 64 位 IOCSR 寄存器
+///
+/// # Safety
+/// `reg` 必须是有效的 IOCSR 地址，写入值符合寄存器语义
+#[inline(always)]
+pub unsafe fn iocsr_write64(reg: usize, value: u64) {
+    unsafe {
+        core::arch::asm!(
+            "iocsrwr.d {value}, {reg}",
+            value = in(reg) value,
+            reg = in(reg) reg,
+            options(nostack, preserves_flags)
+        );
+    }
 }
 
 /// 软中断模块
@@ -135,3 +223,18 @@ pub mod softirq {
     #[allow(dead_code)]
     pub fn init() {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arch::constant::{SUPERVISOR_EXTERNAL, TIMER};
+    use crate::{kassert, test_case};
+
+    test_case!(test_irq_lie_bit, {
+        kassert!(irq_lie_bit(SUPERVISOR_EXTERNAL) == Some(1 << 2));
+        kassert!(irq_lie_bit(TIMER) == Some(1 << 11));
+        // 非中断编码或超出 LIE 范围的号码被忽略
+        kassert!(irq_lie_bit(3).is_none());
+        kassert!(irq_lie_bit(usize::MAX / 2 + 1 + 13).is_none());
+    });
+}
@@ -278,7 +278,10 @@ pub unsafe fn prepare_user_restore(
         crate::pr_err!("[kernel_execve] trap_frame_ptr is null");
         panic!("kernel_execve: null trap_frame_ptr");
     }
-    crate::pr_debug!("[kernel_execve] trap_frame_ptr={:#x}", tfp as usize);
+    crate::pr_debug!(
+        "[kernel_execve] trap_frame_ptr={}",
+        crate::log::hashed_ptr(tfp as usize)
+    );
     unsafe {
         crate::pr_debug!(
             "[kernel_execve] trapframe: era={:#x}, sp={:#x}, prmd={:#x}, crmd={:#x}, a0={:#x}, a1={:#x}, a2={:#x}",
@@ -370,10 +373,10 @@ pub unsafe fn prepare_user_restore(
         tlbrelo1
     );
     crate::pr_debug!(
-        "[kernel_execve] tlb_refill_entry: vaddr={:#x}, paddr={:#x}, dm_vaddr={:#x}",
-        tlbr_entry_vaddr,
+        "[kernel_execve] tlb_refill_entry: vaddr={}, paddr={:#x}, dm_vaddr={}",
+        crate::log::hashed_ptr(tlbr_entry_vaddr),
         tlbr_entry_paddr,
-        tlbr_entry_dm_vaddr
+        crate::log::hashed_ptr(tlbr_entry_dm_vaddr)
     );
     unsafe {
         core::arch::asm!("csrwr {0}, 0x30", in(reg) tfp as usize, options(nostack, preserves_flags));
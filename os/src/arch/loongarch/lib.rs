@@ -75,6 +75,17 @@ pub fn shutdown(_failure: bool) -> ! {
     }
 }
 
+/// 停机实现：关中断后停在 `idle`，不写 GED 寄存器，保持上电
+pub fn halt() -> ! {
+    // SAFETY: 停机后不再返回，关闭中断不会破坏任何临界区
+    unsafe { super::intr::disable_interrupts() };
+    loop {
+        unsafe {
+            core::arch::asm!("idle 0");
+        }
+    }
+}
+
 /// 重启实现
 pub fn restart() -> ! {
     let base_vaddr = VIRT_GED_REG_ADDR | DMW0_BASE;
@@ -84,3 +84,34 @@ pub const unsafe fn va_to_pa(va: VA) -> PA {
 pub const fn pa_to_va(pa: PA) -> VA {
     VA::from_usize(pa.as_usize() | VADDR_START)
 }
+
+/// DMA 传输前把 `[pa, pa + len)` 的 CPU 缓存行写回内存
+///
+/// QEMU virt 平台的设备访问与 CPU 缓存一致（DMW1 为一致可缓存窗口），只需
+/// 一道内存屏障保证写入先于通知设备；缓存不一致的硬件应在此按行执行 `cacop`。
+pub fn dma_cache_wback(_pa: PA, _len: usize) {
+    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// 设备写入 `[pa, pa + len)` 后丢弃 CPU 中的旧缓存行
+///
+/// 缓存一致的平台上只需内存屏障；缓存不一致的硬件应在此按行执行 `cacop`。
+pub fn dma_cache_inv(_pa: PA, _len: usize) {
+    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// 把 `[va, va + len)` 的数据缓存写回到取指可见的层级
+///
+/// LoongArch 的数据缓存由硬件维护一致，`dbar 0` 保证之前的写入全部完成。
+pub fn flush_dcache_range(_va: VA, _len: usize) {
+    unsafe { core::arch::asm!("dbar 0") };
+}
+
+/// 写入 `[va, va + len)` 的代码后同步指令缓存
+///
+/// 指令缓存与数据写入之间没有硬件保证：先用 `dbar 0` 完成写入，再用 `ibar 0`
+/// 让之后的取指看到新代码。当前只启动一个核，不需要通知其他核。
+pub fn sync_icache_range(va: VA, len: usize) {
+    flush_dcache_range(va, len);
+    unsafe { core::arch::asm!("ibar 0") };
+}
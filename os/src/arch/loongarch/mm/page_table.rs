@@ -383,6 +383,10 @@ impl PageTableInnerTrait<PageTableEntry> for PageTableInner {
             }
 
             if level == 0 {
+                // 叶子没有 present 位（如交换项）时不是有效映射
+                if !pte.is_valid() {
+                    return Err(PagingError::NotMapped);
+                }
                 let page_size = PageSize::Size4K; // 当前仅启用 4K 页路径
                 return Ok((pte.ppn(), page_size, pte.flags()));
             }
@@ -392,6 +396,34 @@ impl PageTableInnerTrait<PageTableEntry> for PageTableInner {
 
         Err(PagingError::NotMapped)
     }
+
+    /// 把已映射的叶子改写为交换项
+    fn set_swap_entry(&mut self, vpn: Vpn, slot: usize) -> PagingResult<()> {
+        let (table, idx) = self.leaf_slot(vpn).ok_or(PagingError::NotMapped)?;
+        if !Self::read_pte(table, idx).is_valid() {
+            return Err(PagingError::NotMapped);
+        }
+        Self::write_pte(table, idx, PageTableEntry::new_swap(slot));
+        Self::tlb_flush(vpn);
+        Ok(())
+    }
+
+    /// 读取叶子上的交换槽号
+    fn swap_entry(&self, vpn: Vpn) -> Option<usize> {
+        let (table, idx) = self.leaf_slot(vpn)?;
+        Self::read_pte(table, idx).swap_slot()
+    }
+
+    /// 清除叶子上的交换项
+    fn clear_swap_entry(&mut self, vpn: Vpn) -> PagingResult<()> {
+        let (table, idx) = self.leaf_slot(vpn).ok_or(PagingError::NotMapped)?;
+        if Self::read_pte(table, idx).swap_slot().is_none() {
+            return Err(PagingError::NotMapped);
+        }
+        Self::write_pte(table, idx, PageTableEntry::empty());
+        Self::tlb_flush(vpn);
+        Ok(())
+    }
 }
 
 impl PageTableInner {
@@ -486,6 +518,20 @@ impl PageTableInner {
         (vpn >> (9 * level)) & 0x1ff
     }
 
+    /// 找到 `vpn` 的叶子所在的页表页与索引（不检查叶子本身）
+    fn leaf_slot(&self, vpn: Vpn) -> Option<(Ppn, usize)> {
+        let mut ppn = self.root_ppn;
+        let vpn_value = vpn.as_usize();
+        for level in (1..Self::LEVELS).rev() {
+            let pte = Self::read_pte(ppn, Self::vpn_index(vpn_value, level));
+            if pte.is_empty() {
+                return None;
+            }
+            ppn = pte.ppn();
+        }
+        Some((ppn, Self::vpn_index(vpn_value, 0)))
+    }
+
     /// 读取页表项
     #[inline]
     fn read_pte(ppn: Ppn, index: usize) -> PageTableEntry {
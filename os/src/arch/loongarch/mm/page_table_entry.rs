@@ -133,7 +133,7 @@ impl UniversalConvertableFlag for LAPTEFlags {
     /// 从通用标志位转换为 LoongArch 标志位
     ///
     /// # 翻译规则
-    /// - VALID → VALID + MAT_CC
+    /// - VALID → VALID + MAT_CC（带 DEVICE 时为 MAT_SUC）
     /// - READABLE → 不设置 NR (NR=0 表示可读)
     /// - WRITEABLE → DIRTY (D 位控制写权限)
     /// - EXECUTABLE → 不设置 NX (NX=0 表示可执行)
@@ -146,7 +146,11 @@ impl UniversalConvertableFlag for LAPTEFlags {
         if flag.contains(UniversalPTEFlag::VALID) {
             // 标记软件 present，并启用一致性缓存。
             // 这里同时设置 VALID 位（不做 accessed/dirty 跟踪）。
-            result |= LAPTEFlags::PRESENT | LAPTEFlags::VALID | LAPTEFlags::MAT_CC;
+            result |= LAPTEFlags::PRESENT | LAPTEFlags::VALID;
+            // 设备内存使用强序非缓存访问（MAT_SUC 为 0，不需要置位）
+            if !flag.contains(UniversalPTEFlag::DEVICE) {
+                result |= LAPTEFlags::MAT_CC;
+            }
         }
 
         // READABLE: 不设置 NR 位（默认 NR=0 表示可读）
@@ -192,11 +196,15 @@ impl UniversalConvertableFlag for LAPTEFlags {
     /// - NX=0 → EXECUTABLE
     /// - PLV=3 → USER_ACCESSIBLE
     /// - GLOBAL → GLOBAL
+    /// - MAT_SUC → DEVICE
     fn to_universal(&self) -> UniversalPTEFlag {
         let mut result = UniversalPTEFlag::empty();
 
         if self.contains(LAPTEFlags::PRESENT) {
             result |= UniversalPTEFlag::VALID;
+            if !self.intersects(LAPTEFlags::MAT_BIT0 | LAPTEFlags::MAT_BIT1) {
+                result |= UniversalPTEFlag::DEVICE;
+            }
         }
 
         // NR=0 表示可读
@@ -284,6 +292,19 @@ impl PageTableEntryTrait for PageTableEntry {
         self.0 == 0
     }
 
+    /// 创建交换项：V 与 P 均为 0，交换槽号存放在 PPN 字段
+    fn new_swap(slot: usize) -> Self {
+        PageTableEntry(((slot as u64) << LA64_PTE_PPN_OFFSET) & LA64_PTE_PPN_MASK)
+    }
+
+    /// 获取交换项中的交换槽号
+    fn swap_slot(&self) -> Option<usize> {
+        if self.is_valid() || self.0 & LAPTEFlags::VALID.bits() != 0 || self.is_empty() {
+            return None;
+        }
+        Some(((self.0 & LA64_PTE_PPN_MASK) >> LA64_PTE_PPN_OFFSET) as usize)
+    }
+
     /// 获取物理页号 (PPN)
     fn ppn(&self) -> Ppn {
         let ppn = (self.0 & LA64_PTE_PPN_MASK) >> LA64_PTE_PPN_OFFSET;
@@ -368,5 +389,16 @@ mod tests {
         kassert!(converted_back.contains(UniversalPTEFlag::READABLE));
         kassert!(converted_back.contains(UniversalPTEFlag::WRITEABLE));
         kassert!(converted_back.contains(UniversalPTEFlag::USER_ACCESSIBLE));
+        kassert!(!converted_back.contains(UniversalPTEFlag::DEVICE));
+    });
+
+    // 4. 设备内存使用非缓存访问
+    test_case!(test_flag_conversion_device, {
+        let universal = UniversalPTEFlag::user_rw() | UniversalPTEFlag::DEVICE;
+        let la_flags = LAPTEFlags::from_universal(universal);
+
+        kassert!(la_flags.contains(LAPTEFlags::VALID));
+        kassert!(!la_flags.intersects(LAPTEFlags::MAT_BIT0 | LAPTEFlags::MAT_BIT1));
+        kassert!(la_flags.to_universal().contains(UniversalPTEFlag::DEVICE));
     });
 }
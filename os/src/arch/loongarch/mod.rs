@@ -4,6 +4,7 @@ pub mod boot;
 pub mod compiler_builtins;
 pub mod constant;
 pub mod cpu_ops;
+pub mod cpuinfo;
 pub mod intr;
 pub mod ipi;
 pub mod kernel;
@@ -56,6 +56,8 @@ pub fn mmio_of(device: VirtDevice) -> Option<(usize, usize)> {
 pub fn init() {
     crate::device::serial::uart16550::driver_init();
     crate::device::bus::virtio_mmio::driver_init();
+    crate::device::irq::eiointc::driver_init();
+    crate::device::irq::pch_pic::driver_init();
     crate::device::rtc::rtc_goldfish::driver_init();
     crate::device::device_tree::init();
     crate::device::bus::pcie::init_virtio_pci();
@@ -10,7 +10,8 @@ pub static TIMER_TICKS: AtomicUsize = AtomicUsize::new(0);
 /// 时钟频率 (Hz)
 static CLOCK_FREQ: AtomicUsize = AtomicUsize::new(100_000_000); // 默认 100MHz，可在 init 中覆写
 
-/// 每秒滴答数
+/// 默认每秒滴答数，也是用户态看到的时钟节拍（USER_HZ）
+/// 实际节拍频率见 `kernel::time::TICK_CONFIG`，可由 `hz=` 参数修改
 pub const TICKS_PER_SEC: usize = 100;
 
 // LoongArch 定时器相关 CSR 编号
@@ -27,12 +28,16 @@ pub fn init() {
 }
 
 /// 读取当前时间（硬件计数器）
+///
+/// 启用 `virtual_time` 时返回 `kernel::vclock` 换算后的虚拟时间。
 pub fn get_time() -> usize {
     let time: usize;
     unsafe {
         // LoongArch rdtime.d 需要显式提供第二操作数（通常为 $zero）。
         core::arch::asm!("rdtime.d {time}, $zero", time = out(reg) time, options(nostack, preserves_flags));
     }
+    #[cfg(feature = "virtual_time")]
+    let time = crate::kernel::vclock::now(time);
     time
 }
 
@@ -48,7 +53,7 @@ pub fn clock_freq() -> usize {
 
 /// 设置下一次定时器中断
 pub fn set_next_trigger() {
-    let delta = (clock_freq() / TICKS_PER_SEC).max(1);
+    let delta = crate::kernel::time::TICK_CONFIG.tick_cycles();
 
     unsafe {
         // 1. 先彻底关闭定时器并清除周期模式 (TCFG bit 0 and 1 = 0) 防止配置过程中的竞争
@@ -65,6 +70,25 @@ pub fn set_next_trigger() {
     }
 }
 
+/// 设置一次性定时器中断，在硬件时间到达 `deadline` 时触发
+///
+/// 中断处理程序会调用 `set_next_trigger` 恢复周期模式。
+pub fn set_oneshot_trigger(deadline: usize) {
+    let delta = deadline.saturating_sub(get_time()).max(4);
+
+    unsafe {
+        core::arch::asm!("csrwr $r0, {tcfg}", tcfg = const CSR_TCFG);
+
+        // 只置 En 位，不置 Periodic 位
+        let cfg = (delta & !0b11usize) | 0b01usize;
+        core::arch::asm!(
+            "csrwr {val}, {tcfg}",
+            val = in(reg) cfg,
+            tcfg = const CSR_TCFG
+        );
+    }
+}
+
 /// 确认/清除定时器中断挂起位
 pub fn ack_timer_interrupt() {
     unsafe {
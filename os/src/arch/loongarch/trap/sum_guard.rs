@@ -1,32 +1,80 @@
-//! SUM 位保护器（LoongArch 占位实现）
+//! 用户内存访问窗口（LoongArch）
 //!
-//! LoongArch 平台目前未实现用户内存访问的硬件开关，本守卫提供与 RISC-V
-//! 版本一致的接口以便上层代码编译。未来接入实际 CSR 控制时可在此处完善。
+//! LoongArch 没有与 RISC-V `sstatus.SUM` 对应的硬件开关：PLV0 总能访问 PLV3 页。
+//! 这里用每 CPU 的软件窗口代替：守卫存在期间窗口打开，内核态在窗口之外
+//! 访问用户地址触发的异常会被陷阱处理程序报告为越权访问。
+//!
+//! 守卫持有期间禁止抢占，保证窗口计数始终记在同一个 CPU 上。
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::config::MAX_CPU_COUNT;
+use crate::sync::PreemptGuard;
 
-/// RAII 样式的 SUM 位守卫
-#[derive(Debug, Default, Clone)]
+/// 每个 CPU 上打开的用户访问窗口层数
+static USER_ACCESS_DEPTH: [AtomicUsize; MAX_CPU_COUNT] =
+    [const { AtomicUsize::new(0) }; MAX_CPU_COUNT];
+
+/// RAII 样式的用户访问窗口守卫
+///
+/// 与 RISC-V 版本一样支持嵌套：只有最外层守卫销毁时窗口才关闭。
 pub struct SumGuard {
-    was_enabled: bool,
+    /// 创建守卫前窗口是否已经打开
+    was_set: bool,
+    _preempt: PreemptGuard,
 }
 
 impl SumGuard {
-    /// 创建新的守卫。
-    ///
-    /// 当前实现仅记录占位状态，未对硬件寄存器进行修改。
+    /// 打开当前 CPU 的用户访问窗口
     #[inline]
     pub fn new() -> Self {
-        Self { was_enabled: true }
+        let preempt = PreemptGuard::new();
+        let was_set = USER_ACCESS_DEPTH[crate::arch::cpu_id()].fetch_add(1, Ordering::Relaxed) != 0;
+        Self {
+            was_set,
+            _preempt: preempt,
+        }
+    }
+
+    /// 检查在创建此守卫前，窗口是否已经打开
+    #[allow(dead_code)]
+    pub fn was_set(&self) -> bool {
+        self.was_set
     }
 
-    /// 返回守卫创建前是否认为 SUM 已开启。
+    /// 当前 CPU 的用户访问窗口是否打开
     #[inline]
-    pub fn was_enabled(&self) -> bool {
-        self.was_enabled
+    pub fn active() -> bool {
+        USER_ACCESS_DEPTH[crate::arch::cpu_id()].load(Ordering::Relaxed) != 0
     }
 }
 
 impl Drop for SumGuard {
-    /// 作用域结束时恢复占位状态。
+    /// 关闭一层窗口；抢占在字段析构时才重新开启
     #[inline]
-    fn drop(&mut self) {}
+    fn drop(&mut self) {
+        USER_ACCESS_DEPTH[crate::arch::cpu_id()].fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_guard_nested, {
+        kassert!(!SumGuard::active());
+        {
+            let outer = SumGuard::new();
+            kassert!(SumGuard::active());
+            kassert!(!outer.was_set());
+            {
+                let inner = SumGuard::new();
+                kassert!(inner.was_set());
+            }
+            // 内层守卫销毁后窗口仍然打开
+            kassert!(SumGuard::active());
+        }
+        kassert!(!SumGuard::active());
+    });
 }
@@ -28,6 +28,10 @@ pub struct TrapFrame {
     pub fcsr: u64,
     /// 浮点条件码寄存器 fcc0-fcc7，按 8-bit lane 打包。
     pub fcc: u64,
+    /// 系统调用入口时的 a0，a0 随后会被返回值覆盖，重启系统调用时用它恢复参数
+    pub orig_a0: usize,
+    /// 是否正从系统调用返回，仅在此时才需要考虑系统调用重启
+    pub in_syscall: bool,
 }
 
 impl TrapFrame {
@@ -44,6 +48,8 @@ impl TrapFrame {
             fregs: [0; 32],
             fcsr: 0,
             fcc: 0,
+            orig_a0: 0,
+            in_syscall: false,
         }
     }
 
@@ -217,6 +223,7 @@ impl TrapFrame {
             );
         }
         self.regs[4] = 0; // a0 = 0，子进程返回 0
+        self.in_syscall = false;
         self.kernel_sp = kernel_sp;
         if user_sp != 0 {
             self.regs[3] = user_sp;
@@ -233,6 +240,7 @@ impl TrapFrame {
             );
         }
         self.regs[4] = 0; // a0 = 0
+        self.in_syscall = false;
     }
 
     /// 将 TrapFrame 转换为 MContextT
@@ -256,6 +264,7 @@ impl TrapFrame {
 
     /// 从 MContextT 恢复 TrapFrame
     pub fn restore_from_mcontext(&mut self, mcontext: &MContextT) {
+        self.in_syscall = false;
         self.era = mcontext.gregs[0] as usize;
         for i in 1..32 {
             self.regs[i] = mcontext.gregs[i] as usize;
@@ -312,6 +321,22 @@ impl crate::kernel::syscall::syscall_frame::SyscallFrame for TrapFrame {
     fn set_ret(&mut self, val: usize) {
         self.regs[4] = val;
     }
+
+    fn enter_syscall(&mut self) {
+        self.orig_a0 = self.regs[4];
+        self.in_syscall = true;
+    }
+
+    fn leave_syscall(&mut self) -> Option<usize> {
+        core::mem::take(&mut self.in_syscall).then_some(self.regs[4])
+    }
+
+    fn restart(&mut self, syscall_id: usize) {
+        // 系统调用指令定长 4 字节
+        self.era -= 4;
+        self.regs[4] = self.orig_a0;
+        self.regs[11] = syscall_id;
+    }
 }
 
 impl crate::arch::HwTrapFrame for TrapFrame {
@@ -349,6 +374,20 @@ impl crate::arch::HwTrapFrame for TrapFrame {
         TrapFrame::get_sp(self)
     }
 
+    fn get_fp(&self) -> usize {
+        self.regs[22] // $fp = $r22
+    }
+
+    fn get_ra(&self) -> usize {
+        self.regs[1]
+    }
+
+    fn gprs(&self) -> [usize; 32] {
+        let mut regs = self.regs;
+        regs[0] = 0;
+        regs
+    }
+
     fn set_sp(&mut self, val: usize) {
         TrapFrame::set_sp(self, val)
     }
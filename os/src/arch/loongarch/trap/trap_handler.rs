@@ -8,13 +8,11 @@ use core::{
 use crate::arch::constant::{
     CSR_BADI, CSR_BADV, CSR_CRMD_PLV_MASK, CSR_EENTRY, CSR_ESTAT_IS_MASK, CSR_TLBRENT,
 };
-use crate::arch::timer::{
-    TIMER_TICKS, ack_timer_interrupt, clock_freq, get_time, set_next_trigger,
-};
+use crate::arch::timer::{TIMER_TICKS, ack_timer_interrupt, get_time, set_next_trigger};
 use crate::arch::trap::restore;
 use crate::ipc::check_signal;
+use crate::kernel::schedule;
 use crate::kernel::syscall::dispatch::dispatch_syscall;
-use crate::kernel::{TIMER, TIMER_QUEUE, schedule, send_signal_process, wake_up_task};
 
 use super::TrapFrame;
 
@@ -49,7 +47,17 @@ static FIRST_USER_TIMER_LOGGED: AtomicBool = AtomicBool::new(false);
 static USER_SYSCALL_LOG_BUDGET: AtomicUsize = AtomicUsize::new(16);
 
 const ECODE_SYSCALL: usize = 0xb; // LoongArch syscall 异常码
+const ECODE_PIL: usize = 0x1; // load 操作页无效
+const ECODE_PIF: usize = 0x3; // 取指操作页无效
+const ECODE_PPI: usize = 0x7; // 页特权等级不合规
+const ECODE_ADE: usize = 0x8; // 取指或访存地址错
+const ECODE_ALE: usize = 0x9; // 地址非对齐
+const ECODE_BCE: usize = 0xa; // 边界检查错
+const ECODE_BRK: usize = 0xc; // 断点
+const ECODE_FPE: usize = 0x12; // 浮点异常
+const ECODE_WPE: usize = 0x13; // 监测点
 const TIMER_INT_BIT: usize = 1 << 11; // ESTAT.IS 中的本地定时器位
+const HWI0_INT_BIT: usize = 1 << 2; // ESTAT.IS 中的 HWI0，外部中断控制器接在这里
 
 unsafe extern "C" {
     unsafe fn __restore(tf: &TrapFrame);
@@ -90,15 +98,20 @@ pub extern "C" fn trap_handler(trap_frame: &mut TrapFrame) {
 
     if (prmd & CSR_CRMD_PLV_MASK) != 0 {
         user_trap(estat, era, trap_frame);
+        // 仅在返回用户态时检查信号：内核态陷阱返回时当前任务的系统调用尚未结束
+        check_signal();
     } else {
         kernel_trap(estat, era, trap_frame);
     }
 
-    check_signal();
-
     // 恢复“当前任务”的陷阱帧；若没有当前任务，回退到入口参数。
+    // 离开内核前顺带检查内核栈金丝雀（仅 debug 构建）
     let tf_ptr = crate::kernel::try_current_task()
-        .map(|t| t.lock().trap_frame_ptr.load(Ordering::SeqCst) as usize)
+        .map(|t| {
+            let t = t.lock();
+            t.check_stack_canary();
+            t.trap_frame_ptr.load(Ordering::SeqCst) as usize
+        })
         .unwrap_or(trap_frame as *mut _ as usize);
     // Safety: 指针来源于当前任务保存的 trap_frame_ptr 或回退到入口参数。
     unsafe { restore(&*(tf_ptr as *const TrapFrame)) };
@@ -195,11 +208,12 @@ fn install_trap_entry() {
 }
 
 fn user_trap(estat: usize, era: usize, trap_frame: &mut TrapFrame) {
+    crate::kernel::stall::touch_progress();
     if estat & CSR_ESTAT_IS_MASK != 0 {
         if (estat & TIMER_INT_BIT) != 0 && !FIRST_USER_TIMER_LOGGED.swap(true, Ordering::Relaxed) {
             crate::pr_debug!("[user_trap] first user timer interrupt, era={:#x}", era);
         }
-        handle_interrupt(estat);
+        handle_interrupt(estat, true);
         return;
     }
 
@@ -218,13 +232,32 @@ fn user_trap(estat: usize, era: usize, trap_frame: &mut TrapFrame) {
             trap_frame.era = era.wrapping_add(4);
             dispatch_syscall(trap_frame);
         }
-        _ => user_panic(estat, era, trap_frame),
+        // 访问了被换出的页，换入后重新执行
+        ECODE_PIL..=ECODE_PIF if crate::mm::swap::handle_page_fault(read_badv()) => {}
+        _ => user_exception(estat, era, trap_frame),
+    }
+}
+
+/// 用户态异常对应的信号，与 Linux LoongArch 的处理一致
+fn user_exception_signal(ecode: usize) -> usize {
+    use crate::uapi::signal::{NUM_SIGBUS, NUM_SIGFPE, NUM_SIGILL, NUM_SIGSEGV, NUM_SIGTRAP};
+    match ecode {
+        ECODE_PIL..=ECODE_PPI | ECODE_BCE => NUM_SIGSEGV,
+        ECODE_ADE | ECODE_ALE => NUM_SIGBUS,
+        ECODE_BRK | ECODE_WPE => NUM_SIGTRAP,
+        ECODE_FPE => NUM_SIGFPE,
+        // 指令不存在、特权指令、浮点/向量单元未使能等
+        _ => NUM_SIGILL,
     }
 }
 
 fn kernel_trap(estat: usize, era: usize, tf: &TrapFrame) {
     if estat & CSR_ESTAT_IS_MASK != 0 {
-        handle_interrupt(estat);
+        handle_interrupt(estat, false);
+        // 被打断的内核代码长时间没有进展时打印现场
+        if estat & TIMER_INT_BIT != 0 {
+            crate::kernel::stall::check_stall(tf);
+        }
         return;
     }
 
@@ -235,6 +268,24 @@ fn kernel_trap(estat: usize, era: usize, tf: &TrapFrame) {
         core::arch::asm!("csrrd {0}, {csr}", out(reg) badv, csr = const CSR_BADV, options(nostack, preserves_flags));
         core::arch::asm!("csrrd {0}, {csr}", out(reg) badi, csr = const CSR_BADI, options(nostack, preserves_flags));
     }
+    // 用户拷贝访问了被换出的页
+    if (ECODE_PIL..=ECODE_PIF).contains(&ecode)
+        && badv <= crate::arch::constant::USER_TOP
+        && crate::arch::uaccess::UserAccessGuard::active()
+        && crate::mm::swap::handle_page_fault(badv)
+    {
+        return;
+    }
+    // 页异常落在用户地址上：窗口之外说明内核绕过了 UserAccessGuard
+    if (ECODE_PIL..=ECODE_PPI).contains(&ecode)
+        && badv <= crate::arch::constant::USER_TOP
+        && !crate::arch::uaccess::UserAccessGuard::active()
+    {
+        panic!(
+            "Kernel accessed user address {:#x} outside UserAccessGuard: ecode={:#x}, era={:#x}",
+            badv, ecode, era
+        );
+    }
     panic!(
         "Unexpected trap in kernel: ecode={:#x}, estat={:#x}, era={:#x}, badv={:#x}, badi={:#x}, crmd={:#x}, prmd={:#x}, a0={:#x}, a1={:#x}",
         ecode,
@@ -249,15 +300,37 @@ fn kernel_trap(estat: usize, era: usize, tf: &TrapFrame) {
     );
 }
 
-fn handle_interrupt(estat: usize) {
+fn read_badv() -> usize {
+    let badv: usize;
+    unsafe {
+        core::arch::asm!("csrrd {0}, {csr}", out(reg) badv, csr = const CSR_BADV, options(nostack, preserves_flags));
+    }
+    badv
+}
+
+fn handle_interrupt(estat: usize, user: bool) {
+    if estat & HWI0_INT_BIT != 0 {
+        check_device();
+    }
     if estat & TIMER_INT_BIT != 0 {
         ack_timer_interrupt();
         set_next_trigger();
-        check_timer();
+        check_timer(user);
     }
 }
 
-fn user_panic(estat: usize, era: usize, trap_frame: &TrapFrame) {
+/// 处理设备中断
+fn check_device() {
+    crate::device::IRQ_MANAGER
+        .lock()
+        .try_handle_interrupt(Some(crate::arch::constant::SUPERVISOR_EXTERNAL));
+}
+
+/// 用户态异常：打印现场后向当前线程强制投递对应信号
+///
+/// 与 RISC-V 相同不让内核 panic，返回用户态前按信号的处理方式
+/// （默认为终止并 core dump）处理。
+fn user_exception(estat: usize, era: usize, trap_frame: &TrapFrame) {
     let ecode = (estat >> 16) & 0x3f;
     let badv: usize;
     let badi: usize;
@@ -265,9 +338,17 @@ fn user_panic(estat: usize, era: usize, trap_frame: &TrapFrame) {
         core::arch::asm!("csrrd {0}, {csr}", out(reg) badv, csr = const CSR_BADV, options(nostack, preserves_flags));
         core::arch::asm!("csrrd {0}, {csr}", out(reg) badi, csr = const CSR_BADI, options(nostack, preserves_flags));
     }
+    let task = crate::kernel::current_task();
+    let (pid, tid, exe_path) = {
+        let t = task.lock();
+        (t.pid, t.tid, t.exe_path.clone())
+    };
+    let sig = user_exception_signal(ecode);
     emergency_println!("\n===============================================");
     emergency_println!("   UNEXPECTED TRAP IN USER MODE (PLV>0)");
     emergency_println!("===============================================");
+    emergency_println!("pid  : {}  tid: {}", pid, tid);
+    emergency_println!("exe  : {}", exe_path.as_deref().unwrap_or("<unknown>"));
     emergency_println!("estat: {:#x}", estat);
     emergency_println!("ecode: {:#x}", ecode);
     emergency_println!("era  : {:#x}", era);
@@ -279,30 +360,28 @@ fn user_panic(estat: usize, era: usize, trap_frame: &TrapFrame) {
         trap_frame.regs[3]
     );
     emergency_println!("regs : {:#x?}", trap_frame.regs);
-    panic!(
-        "Unexpected trap in user mode: estat={:#x}, era={:#x}, badv={:#x}, badi={:#x}",
-        estat, era, badv, badi
-    );
+    emergency_println!("signal: {}", sig);
+    emergency_println!("===============================================");
+    crate::ipc::force_signal(&task, sig);
 }
 
 /// 处理时钟中断
-fn check_timer() {
+/// # 参数
+/// - `user`: 中断是否发生在用户态，用于 CPU 时间定时器的记账
+fn check_timer(user: bool) {
     let _ticks = TIMER_TICKS.fetch_add(1, Ordering::Relaxed);
+    crate::kernel::time::timekeeping_tick();
+    crate::kernel::loadavg::loadavg_tick();
+    crate::kernel::watchdog::watchdog_tick();
+    crate::kernel::account_cpu_tick(user);
 
     // Loopback/null-net paths need periodic progress, but smoltcp should not run
     // directly in hard interrupt context.
     crate::net::socket::request_network_poll();
 
-    while let Some(task) = TIMER_QUEUE.lock().pop_due_task(get_time()) {
-        wake_up_task(task);
-    }
-    while let Some(entry) = TIMER.lock().pop_due_entry(get_time()) {
-        send_signal_process(&entry.task, entry.sig);
-        if !entry.it_interval.is_zero() {
-            let next_trigger = get_time() + entry.it_interval.into_freq(clock_freq());
-            TIMER.lock().push(next_trigger, entry);
-        }
-    }
+    let now = get_time();
+    crate::kernel::run_timer_queue(now);
+    crate::kernel::run_itimers(now);
     let should_preempt = {
         let mut sched = crate::kernel::current_scheduler().lock();
         sched.update_time_slice() && !sched.is_empty()
@@ -318,3 +397,21 @@ pub(super) fn restore_context(trap_frame: &TrapFrame) {
 }
 
 // 信号返回跳板由汇编提供（sigreturn.S）
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uapi::signal::{NUM_SIGBUS, NUM_SIGFPE, NUM_SIGILL, NUM_SIGSEGV, NUM_SIGTRAP};
+    use crate::{kassert, test_case};
+
+    test_case!(test_user_exception_signal, {
+        kassert!(user_exception_signal(ECODE_PIL) == NUM_SIGSEGV);
+        kassert!(user_exception_signal(ECODE_PPI) == NUM_SIGSEGV);
+        kassert!(user_exception_signal(ECODE_ADE) == NUM_SIGBUS);
+        kassert!(user_exception_signal(ECODE_ALE) == NUM_SIGBUS);
+        kassert!(user_exception_signal(ECODE_BRK) == NUM_SIGTRAP);
+        kassert!(user_exception_signal(ECODE_FPE) == NUM_SIGFPE);
+        // INE
+        kassert!(user_exception_signal(0xd) == NUM_SIGILL);
+    });
+}
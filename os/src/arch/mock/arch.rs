@@ -38,6 +38,10 @@ impl CpuOps for MockCpuOps {
         }
     }
 
+    fn wait_for_interrupt() {
+        core::hint::spin_loop();
+    }
+
     fn disable_interrupts() -> usize {
         0
     }
@@ -176,6 +180,9 @@ impl CpuOps for MockArch {
     fn halt() -> ! {
         MockCpuOps::halt()
     }
+    fn wait_for_interrupt() {
+        MockCpuOps::wait_for_interrupt()
+    }
     fn disable_interrupts() -> usize {
         MockCpuOps::disable_interrupts()
     }
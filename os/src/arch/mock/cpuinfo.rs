@@ -0,0 +1,11 @@
+//! 宿主测试用的 `/proc/cpuinfo`
+
+use alloc::format;
+use alloc::string::String;
+
+/// 生成 `/proc/cpuinfo` 的内容，每个 CPU 一段
+pub fn cpuinfo() -> String {
+    (0..crate::kernel::num_cpu())
+        .map(|cpu| format!("processor\t: {}\narch\t\t: mock\n\n", cpu))
+        .collect()
+}
@@ -20,4 +20,6 @@ pub fn enable_irq(_irq: usize) {}
 
 pub fn enable_software_interrupt() {}
 
-pub fn enable_timer_interrupt() {}
+pub unsafe fn enable_timer_interrupt() {}
+
+pub unsafe fn disable_timer_interrupt() {}
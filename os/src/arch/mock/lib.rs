@@ -10,6 +10,12 @@ pub fn restart() -> ! {
     }
 }
 
+pub fn halt() -> ! {
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
 pub fn console_putchar(_c: u8) {}
 
 pub fn console_getchar() -> usize {
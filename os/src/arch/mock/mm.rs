@@ -12,6 +12,14 @@ pub unsafe fn va_to_pa(va: VA) -> PA {
     PA::from_usize(va.as_usize() - super::constant::SV39_BOT_HALF_TOP)
 }
 
+pub fn dma_cache_wback(_pa: PA, _len: usize) {}
+
+pub fn dma_cache_inv(_pa: PA, _len: usize) {}
+
+pub fn flush_dcache_range(_va: VA, _len: usize) {}
+
+pub fn sync_icache_range(_va: VA, _len: usize) {}
+
 // ---- Mock PageTableEntry ----
 
 #[derive(Debug, Clone, Copy)]
@@ -54,6 +62,19 @@ impl PageTableEntryTrait for PageTableEntry {
         self.bits == 0
     }
 
+    fn new_swap(slot: usize) -> Self {
+        Self {
+            bits: (slot as u64) << 10,
+        }
+    }
+
+    fn swap_slot(&self) -> Option<usize> {
+        if self.is_valid() || self.is_empty() {
+            return None;
+        }
+        Some((self.bits >> 10) as usize)
+    }
+
     fn ppn(&self) -> Ppn {
         Ppn::from_usize(((self.bits >> 10) & ((1u64 << 44) - 1)) as usize)
     }
@@ -181,6 +202,18 @@ impl PageTableInnerTrait<PageTableEntry> for PageTableInner {
     fn walk(&self, _vpn: Vpn) -> PagingResult<(Ppn, PageSize, UniversalPTEFlag)> {
         Err(PagingError::NotMapped)
     }
+
+    fn set_swap_entry(&mut self, _vpn: Vpn, _slot: usize) -> PagingResult<()> {
+        Err(PagingError::NotMapped)
+    }
+
+    fn swap_entry(&self, _vpn: Vpn) -> Option<usize> {
+        None
+    }
+
+    fn clear_swap_entry(&mut self, _vpn: Vpn) -> PagingResult<()> {
+        Err(PagingError::NotMapped)
+    }
 }
 
 // Batch methods (non-trait, architecture-specific helpers)
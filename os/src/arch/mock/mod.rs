@@ -6,6 +6,7 @@
 pub mod arch;
 pub mod boot;
 pub mod constant;
+pub mod cpuinfo;
 pub mod intr;
 pub mod ipi;
 pub mod kernel;
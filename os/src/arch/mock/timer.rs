@@ -22,4 +22,6 @@ pub fn clock_freq() -> usize {
 
 pub fn set_next_trigger() {}
 
+pub fn set_oneshot_trigger(_deadline: usize) {}
+
 pub fn init() {}
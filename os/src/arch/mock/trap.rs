@@ -43,6 +43,8 @@ pub struct TrapFrame {
     pub sstatus: usize,
     pub kernel_sp: usize,
     pub cpu_ptr: usize,
+    pub orig_a0: usize,
+    pub in_syscall: bool,
 }
 
 impl TrapFrame {
@@ -83,6 +85,8 @@ impl TrapFrame {
             sstatus: 0,
             kernel_sp: 0,
             cpu_ptr: 0,
+            orig_a0: 0,
+            in_syscall: false,
         }
     }
 
@@ -137,6 +141,7 @@ impl TrapFrame {
     ) {
         *self = *parent_frame;
         self.x10_a0 = 0;
+        self.in_syscall = false;
         self.kernel_sp = kernel_sp;
         if user_sp != 0 {
             self.x2_sp = user_sp;
@@ -184,6 +189,7 @@ impl TrapFrame {
     pub unsafe fn set_fork_trap_frame(&mut self, parent_frame: &TrapFrame) {
         *self = *parent_frame;
         self.x10_a0 = 0;
+        self.in_syscall = false;
     }
 
     pub fn to_mcontext(&self) -> MContextT {
@@ -193,7 +199,9 @@ impl TrapFrame {
         }
     }
 
-    pub fn restore_from_mcontext(&mut self, _mcontext: &MContextT) {}
+    pub fn restore_from_mcontext(&mut self, _mcontext: &MContextT) {
+        self.in_syscall = false;
+    }
 }
 
 impl crate::kernel::syscall::syscall_frame::SyscallFrame for TrapFrame {
@@ -223,6 +231,22 @@ impl crate::kernel::syscall::syscall_frame::SyscallFrame for TrapFrame {
     fn set_ret(&mut self, val: usize) {
         self.x10_a0 = val;
     }
+
+    fn enter_syscall(&mut self) {
+        self.orig_a0 = self.x10_a0;
+        self.in_syscall = true;
+    }
+
+    fn leave_syscall(&mut self) -> Option<usize> {
+        core::mem::take(&mut self.in_syscall).then_some(self.x10_a0)
+    }
+
+    fn restart(&mut self, syscall_id: usize) {
+        // 系统调用指令定长 4 字节
+        self.sepc -= 4;
+        self.x10_a0 = self.orig_a0;
+        self.x17_a7 = syscall_id;
+    }
 }
 
 impl crate::arch::HwTrapFrame for TrapFrame {
@@ -260,6 +284,51 @@ impl crate::arch::HwTrapFrame for TrapFrame {
         TrapFrame::get_sp(self)
     }
 
+    fn get_fp(&self) -> usize {
+        self.x8_s0
+    }
+
+    fn get_ra(&self) -> usize {
+        self.x1_ra
+    }
+
+    fn gprs(&self) -> [usize; 32] {
+        [
+            0,
+            self.x1_ra,
+            self.x2_sp,
+            self.x3_gp,
+            self.x4_tp,
+            self.x5_t0,
+            self.x6_t1,
+            self.x7_t2,
+            self.x8_s0,
+            self.x9_s1,
+            self.x10_a0,
+            self.x11_a1,
+            self.x12_a2,
+            self.x13_a3,
+            self.x14_a4,
+            self.x15_a5,
+            self.x16_a6,
+            self.x17_a7,
+            self.x18_s2,
+            self.x19_s3,
+            self.x20_s4,
+            self.x21_s5,
+            self.x22_s6,
+            self.x23_s7,
+            self.x24_s8,
+            self.x25_s9,
+            self.x26_s10,
+            self.x27_s11,
+            self.x28_t3,
+            self.x29_t4,
+            self.x30_t5,
+            self.x31_t6,
+        ]
+    }
+
     fn set_sp(&mut self, val: usize) {
         TrapFrame::set_sp(self, val)
     }
@@ -306,6 +375,10 @@ impl SumGuard {
     pub fn new() -> Self {
         Self
     }
+
+    pub fn active() -> bool {
+        true
+    }
 }
 
 pub unsafe fn restore(_trap_frame: &TrapFrame) {}
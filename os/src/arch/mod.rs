@@ -18,6 +18,7 @@ pub mod arch;
 pub mod cpu_ops;
 pub mod plat;
 pub mod task;
+pub mod uaccess;
 pub mod virtual_memory;
 
 pub use arch::{Arch, HwTrapFrame};
@@ -40,15 +41,15 @@ mod riscv;
 #[cfg(target_arch = "loongarch64")]
 #[allow(unused_imports)]
 pub use loongarch::{
-    boot, compiler_builtins, constant, cpu_ops as target_cpu_ops, intr, ipi, kernel, lib, memory,
-    mm, platform, timer, trap,
+    boot, compiler_builtins, constant, cpu_ops as target_cpu_ops, cpuinfo, intr, ipi, kernel, lib,
+    memory, mm, platform, timer, trap,
 };
 
 #[cfg(target_arch = "riscv64")]
 #[allow(unused_imports)]
 pub use riscv::{
-    boot, constant, cpu_ops as target_cpu_ops, intr, ipi, kernel, lib, memory, mm, platform, timer,
-    trap,
+    boot, constant, cpu_ops as target_cpu_ops, cpuinfo, intr, ipi, kernel, lib, memory, mm,
+    platform, sbi, timer, trap,
 };
 
 // ---- 非目标架构（宿主测试）：Mock Stubs ----
@@ -59,8 +60,8 @@ mod mock;
 #[cfg(not(any(target_arch = "riscv64", target_arch = "loongarch64")))]
 #[allow(unused_imports)]
 pub use mock::{
-    MockAddressSpace, MockArch, MockCpuOps, boot, constant, intr, ipi, kernel, lib, mm, platform,
-    timer, trap,
+    MockAddressSpace, MockArch, MockCpuOps, boot, constant, cpuinfo, intr, ipi, kernel, lib, mm,
+    platform, timer, trap,
 };
 
 pub type TrapFrame = <ArchImpl as Arch>::TrapFrame;
@@ -178,6 +179,33 @@ pub fn clock_freq() -> usize {
     ArchImpl::clock_freq()
 }
 
+/// 让当前 CPU 等待中断（WFI / idle）
+#[inline]
+pub fn wait_for_interrupt() {
+    ArchImpl::wait_for_interrupt()
+}
+
+/// 把本 CPU 的下一次时钟中断推迟到硬件时间 `deadline`
+///
+/// 用于 idle 时停掉周期节拍；下一次时钟中断会自动恢复周期节拍。
+#[inline]
+pub fn set_oneshot_trigger(deadline: usize) {
+    timer::set_oneshot_trigger(deadline)
+}
+
+/// 停止本 CPU 的时钟中断
+#[inline]
+pub fn stop_tick() {
+    unsafe { intr::disable_timer_interrupt() }
+}
+
+/// 恢复本 CPU 的周期时钟中断
+#[inline]
+pub fn restart_tick() {
+    timer::set_next_trigger();
+    unsafe { intr::enable_timer_interrupt() }
+}
+
 /// 发送重调度 IPI
 #[inline]
 pub fn send_reschedule_ipi(target: usize) {
@@ -273,6 +301,33 @@ pub unsafe fn va_to_pa(va: address::VA) -> address::PA {
     unsafe { PlatformImpl::va_to_pa(va) }
 }
 
+/// DMA 传输前写回 `[pa, pa + len)` 的 CPU 缓存
+#[inline]
+pub fn dma_cache_wback(pa: address::PA, len: usize) {
+    mm::dma_cache_wback(pa, len)
+}
+
+/// 设备写入后丢弃 `[pa, pa + len)` 的 CPU 缓存
+#[inline]
+pub fn dma_cache_inv(pa: address::PA, len: usize) {
+    mm::dma_cache_inv(pa, len)
+}
+
+/// 把 `[va, va + len)` 的数据缓存写回到取指可见的层级
+#[inline]
+#[allow(dead_code)]
+pub fn flush_dcache_range(va: address::VA, len: usize) {
+    mm::flush_dcache_range(va, len)
+}
+
+/// 写入 `[va, va + len)` 的代码后同步所有在线 CPU 的指令缓存
+///
+/// 加载 ELF、写入信号 trampoline 等把代码写进内存之后、执行之前必须调用。
+#[inline]
+pub fn sync_icache_range(va: address::VA, len: usize) {
+    mm::sync_icache_range(va, len)
+}
+
 /// 判断虚拟地址是否位于直接映射区域。
 #[inline]
 pub fn is_direct_mapped_va(va: address::VA) -> bool {
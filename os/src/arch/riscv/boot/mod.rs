@@ -70,7 +70,7 @@ unsafe extern "C" {
 
 /// 从核入口
 ///
-/// 与主核对称：创建 idle → switch_task → trap::init → 启用中断 → idle_loop。
+/// 与主核对称：创建 idle → switch_task → trap::init → 切换到 idle 任务（开中断进入 idle_loop）。
 #[unsafe(no_mangle)]
 pub extern "C" fn secondary_start(hartid: usize) -> ! {
     trap::init_boot_trap();
@@ -93,7 +93,7 @@ pub extern "C" fn secondary_start(hartid: usize) -> ! {
         let _guard = PreemptGuard::new();
         let cpu = current_cpu();
         cpu.idle_task = Some(idle_task.clone());
-        cpu.switch_task(idle_task);
+        cpu.switch_task(idle_task.clone());
     }
 
     // 切换到全局内核页表
@@ -118,10 +118,9 @@ pub extern "C" fn secondary_start(hartid: usize) -> ! {
     // 完整陷阱 + 定时器 + 中断
     trap::init();
     timer::init();
-    crate::arch::enable_interrupts();
 
     pr_debug!("[SMP] CPU {} entering idle loop", hartid);
-    kernel::boot::idle_loop();
+    kernel::boot::enter_idle_task(idle_task);
 }
 
 /// 启动从核（由主核调用）
@@ -139,6 +138,12 @@ pub fn boot_secondary_cpus(num_cpus: usize) {
 
     CPU_ONLINE_MASK.fetch_or(1, Ordering::Release);
 
+    if !crate::arch::sbi::SBI_INFO.has_extension(crate::arch::sbi::eid::HSM) {
+        pr_warn!("[SMP] SBI HSM extension not available; falling back to single-core");
+        set_num_cpu(1);
+        return;
+    }
+
     let mut expected_mask: usize = 1;
     for hartid in 1..num_cpus {
         let start_vaddr = secondary_sbi_entry as usize;
@@ -146,19 +151,15 @@ pub fn boot_secondary_cpus(num_cpus: usize) {
             unsafe { crate::arch::mm::va_to_pa(crate::arch::address::VA::from_usize(start_vaddr)) }
                 .as_usize();
         pr_info!(
-            "[SMP] Starting hart {} at vaddr=0x{:x}, paddr=0x{:x}",
+            "[SMP] Starting hart {} at vaddr={}, paddr=0x{:x}",
             hartid,
-            start_vaddr,
+            crate::log::hashed_ptr(start_vaddr),
             start_paddr
         );
 
-        let ret = crate::arch::lib::hart_start(hartid, start_paddr, hartid);
-        if ret.error != 0 {
-            pr_err!(
-                "[SMP] Failed to start hart {}: SBI error {}",
-                hartid,
-                ret.error
-            );
+        let ret = crate::arch::sbi::hsm::hart_start(hartid, start_paddr, hartid);
+        if let Err(e) = ret.into_result() {
+            pr_err!("[SMP] Failed to start hart {}: SBI error {:?}", hartid, e);
             continue;
         }
         expected_mask |= 1 << hartid;
@@ -47,6 +47,16 @@ impl CpuOps for Riscv64 {
         }
     }
 
+    /// 执行一次 WFI
+    ///
+    /// sie 中已使能的中断挂起时 WFI 即返回，与 sstatus.SIE 无关。
+    #[inline]
+    fn wait_for_interrupt() {
+        unsafe {
+            core::arch::asm!("wfi", options(nomem, nostack));
+        }
+    }
+
     /// 原子地禁用中断并返回之前的中断状态
     ///
     /// 通过 CSRRC 指令原子地清除 sstatus.SIE 位。
@@ -93,4 +103,16 @@ impl CpuOps for Riscv64 {
     fn interrupt_was_enabled(flags: usize) -> bool {
         flags & crate::arch::constant::SSTATUS_SIE != 0
     }
+
+    /// 读取帧指针寄存器
+    ///
+    /// 内核以 `force-frame-pointers` 编译，内联到调用者后得到的是调用者的帧。
+    #[inline(always)]
+    fn frame_pointer() -> usize {
+        let fp: usize;
+        unsafe {
+            core::arch::asm!("mv {}, s0", out(reg) fp, options(nomem, nostack));
+        }
+        fp
+    }
 }
@@ -0,0 +1,203 @@
+//! RISC-V 的 `/proc/cpuinfo`
+//!
+//! ISA 来自设备树 CPU 节点的 `riscv,isa`，或较新的 `riscv,isa-base` 加
+//! `riscv,isa-extensions`。misa 只能在 M 态读取，S 态下改用 SBI 基础扩展
+//! 取得 mvendorid/marchid/mimpid。
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::device::device_tree::{self, DtCpu};
+
+/// 内核使用的分页模式
+const MMU_MODE: &str = "sv39";
+
+/// 生成 `/proc/cpuinfo` 的内容，每个 hart 一段
+pub fn cpuinfo() -> String {
+    let mut cpus = device_tree::cpu_nodes();
+    if cpus.is_empty() {
+        cpus = (0..crate::kernel::num_cpu())
+            .map(|hart| DtCpu {
+                hw_id: hart,
+                ..DtCpu::default()
+            })
+            .collect();
+    }
+    let sbi = &*super::sbi::SBI_INFO;
+    let ids = [sbi.mvendorid, sbi.marchid, sbi.mimpid];
+
+    let mut out = String::new();
+    for (processor, cpu) in cpus.iter().enumerate() {
+        let _ = write_hart(&mut out, processor, cpu, ids);
+    }
+    out
+}
+
+fn write_hart(
+    out: &mut String,
+    processor: usize,
+    cpu: &DtCpu,
+    [mvendorid, marchid, mimpid]: [usize; 3],
+) -> core::fmt::Result {
+    writeln!(out, "processor\t: {}", processor)?;
+    writeln!(out, "hart\t\t: {}", cpu.hw_id)?;
+    match isa_string(cpu) {
+        Some(isa) => {
+            writeln!(out, "isa\t\t: {}", isa)?;
+            writeln!(out, "isa-ext\t\t: {}", isa_extensions(&isa).join(" "))?;
+        }
+        None => writeln!(out, "isa\t\t: unknown")?,
+    }
+    writeln!(out, "mmu\t\t: {}", MMU_MODE)?;
+    // 通用的 "riscv" 不说明微架构
+    if let Some(uarch) = cpu.compatible.iter().find(|c| c.as_str() != "riscv") {
+        writeln!(out, "uarch\t\t: {}", uarch)?;
+    }
+    writeln!(out, "mvendorid\t: {:#x}", mvendorid)?;
+    writeln!(out, "marchid\t\t: {:#x}", marchid)?;
+    writeln!(out, "mimpid\t\t: {:#x}", mimpid)?;
+    writeln!(out, "timebase\t: {}", crate::kernel::clock_freq())?;
+    writeln!(out)
+}
+
+/// 设备树给出的 ISA 字符串（小写）
+///
+/// 只有 `riscv,isa-base` 和 `riscv,isa-extensions` 时按它们拼出等价的字符串：
+/// 单字母扩展紧跟在基础 ISA 之后，多字母扩展以 `_` 分隔。
+fn isa_string(cpu: &DtCpu) -> Option<String> {
+    if let Some(isa) = &cpu.isa {
+        return Some(isa.to_ascii_lowercase());
+    }
+    let mut isa = cpu.isa_base.as_ref()?.to_ascii_lowercase();
+    let (single, multi): (Vec<&String>, Vec<&String>) =
+        cpu.isa_extensions.iter().partition(|ext| ext.len() == 1);
+    for ext in single {
+        let ext = ext.to_ascii_lowercase();
+        // 基础 ISA 本身已含 `i` 或 `e`
+        if !isa
+            .get(4..)
+            .is_some_and(|letters| letters.contains(ext.as_str()))
+        {
+            isa.push_str(&ext);
+        }
+    }
+    for ext in multi {
+        isa.push('_');
+        isa.push_str(&ext.to_ascii_lowercase());
+    }
+    Some(isa)
+}
+
+/// 把 ISA 字符串拆成扩展名列表，如 `rv64gc_zba` → `i m a f d zicsr zifencei c zba`
+///
+/// 忽略版本号（`i2p1`），`g` 展开为 `imafd_zicsr_zifencei`。单字母部分中出现的
+/// `z`、`s`、`x` 开始一个多字母扩展，直到下一个 `_`。
+fn isa_extensions(isa: &str) -> Vec<String> {
+    let isa = isa.to_ascii_lowercase();
+    let Some(rest) = isa
+        .strip_prefix("rv64")
+        .or_else(|| isa.strip_prefix("rv32"))
+    else {
+        return Vec::new();
+    };
+
+    let mut exts: Vec<String> = Vec::new();
+    let push = |exts: &mut Vec<String>, ext: &str| {
+        if !ext.is_empty() && !exts.iter().any(|e| e == ext) {
+            exts.push(String::from(ext));
+        }
+    };
+    for (index, part) in rest.split('_').enumerate() {
+        if index > 0 {
+            push(&mut exts, strip_version(part));
+            continue;
+        }
+        let bytes = part.as_bytes();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b'z' | b's' | b'x' => {
+                    push(&mut exts, strip_version(&part[pos..]));
+                    break;
+                }
+                b'g' => {
+                    for ext in ["i", "m", "a", "f", "d", "zicsr", "zifencei"] {
+                        push(&mut exts, ext);
+                    }
+                }
+                b'a'..=b'z' => push(&mut exts, &part[pos..pos + 1]),
+                _ => {}
+            }
+            pos += 1 + version_len(&bytes[pos + 1..]);
+        }
+    }
+    exts
+}
+
+/// `bytes` 开头的版本号（`2` 或 `2p1`）的长度
+fn version_len(bytes: &[u8]) -> usize {
+    let digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+    let major = digits(0);
+    if major == 0 {
+        return 0;
+    }
+    if bytes.get(major) == Some(&b'p') {
+        let minor = digits(major + 1);
+        if minor > 0 {
+            return major + 1 + minor;
+        }
+    }
+    major
+}
+
+/// 去掉多字母扩展末尾的版本号，如 `zicsr2p0` → `zicsr`
+fn strip_version(ext: &str) -> &str {
+    let bytes = ext.as_bytes();
+    (1..bytes.len())
+        .find(|&pos| version_len(&bytes[pos..]) == bytes.len() - pos)
+        .map_or(ext, |pos| &ext[..pos])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_riscv_isa_extensions, {
+        let exts = isa_extensions("rv64imafdch_zicsr_zifencei_zihintpause_sstc");
+        kassert!(
+            exts == [
+                "i",
+                "m",
+                "a",
+                "f",
+                "d",
+                "c",
+                "h",
+                "zicsr",
+                "zifencei",
+                "zihintpause",
+                "sstc"
+            ]
+        );
+        kassert!(isa_extensions("RV64GC") == ["i", "m", "a", "f", "d", "zicsr", "zifencei", "c"]);
+        kassert!(isa_extensions("rv64i2p1m2p0_zicsr2p0") == ["i", "m", "zicsr"]);
+        kassert!(isa_extensions("x86_64").is_empty());
+
+        let cpu = DtCpu {
+            isa_base: Some(String::from("rv64i")),
+            isa_extensions: ["i", "m", "a", "zicsr"]
+                .iter()
+                .map(|s| String::from(*s))
+                .collect(),
+            ..DtCpu::default()
+        };
+        kassert!(isa_string(&cpu).as_deref() == Some("rv64ima_zicsr"));
+    });
+}
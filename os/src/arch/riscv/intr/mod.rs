@@ -18,6 +18,15 @@ pub unsafe fn enable_timer_interrupt() {
     unsafe { sie::set_stimer() };
 }
 
+/// 禁用定时器中断
+///
+/// # Safety
+///
+/// 该函数直接操作 CPU 寄存器，调用者负责之后重新启用定时器中断
+pub unsafe fn disable_timer_interrupt() {
+    unsafe { sie::clear_stimer() };
+}
+
 /// 启用软件中断（用于 IPI）
 ///
 /// # Safety
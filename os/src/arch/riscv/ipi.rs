@@ -4,7 +4,7 @@
 //!
 //! # 设计说明
 //!
-//! - 使用 sbi-rt crate 的 IPI 扩展发送中断
+//! - 通过 [`super::sbi`] 的 IPI 扩展发送中断，不支持时回退到 legacy 调用
 //! - Per-CPU 原子标志位存储待处理的 IPI 类型
 //! - 在软件中断处理程序中处理 IPI
 //!
@@ -30,6 +30,8 @@ pub enum IpiType {
     TlbFlush = 1 << 1,
     /// 停止 CPU（系统关机）
     Stop = 1 << 2,
+    /// 同步指令缓存（代码写入后，SBI 不支持 RFENCE 时使用）
+    FenceI = 1 << 3,
 }
 
 /// Per-CPU 待处理 IPI 标志
@@ -150,9 +152,20 @@ pub fn handle_ipi() {
         }
     }
 
-    // 处理停止 IPI
+    // 处理指令缓存同步 IPI
+    if pending & (IpiType::FenceI as u32) != 0 {
+        // SAFETY: fence.i 只同步本 hart 的取指，没有副作用
+        unsafe {
+            core::arch::asm!("fence.i");
+        }
+    }
+
+    // 处理停止 IPI：有 HSM 扩展时交还给 SBI，否则停在 wfi
     if pending & (IpiType::Stop as u32) != 0 {
         crate::pr_debug!("[IPI] CPU {} stopping", cpu);
+        if super::sbi::SBI_INFO.has_extension(super::sbi::eid::HSM) {
+            super::sbi::hsm::hart_stop();
+        }
         loop {
             // SAFETY: wfi 是安全的 RISC-V 指令，用于等待中断
             unsafe {
@@ -3,7 +3,6 @@ use core::mem::size_of;
 use core::ptr;
 
 use alloc::vec::Vec;
-use riscv::register::sstatus;
 
 use crate::arch::constant::STACK_ALIGN_MASK;
 use crate::arch::{
@@ -33,9 +32,8 @@ pub fn setup_stack_layout(
     let mut sp = sp;
     let mut arg_ptrs: Vec<usize> = Vec::with_capacity(argv.len());
     let mut env_ptrs: Vec<usize> = Vec::with_capacity(envp.len());
-    unsafe {
-        sstatus::set_sum();
-    }
+    // 直接写入新地址空间的用户栈
+    let user_access = crate::arch::uaccess::UserAccessGuard::new();
 
     for &env in envp.iter().rev() {
         let bytes = env.as_bytes();
@@ -180,10 +178,8 @@ pub fn setup_stack_layout(
         ptr::write(sp as *mut usize, argc);
     }
 
-    // 拷贝完成，恢复 SUM
-    unsafe {
-        sstatus::clear_sum();
-    }
+    // 拷贝完成，关闭用户访问
+    drop(user_access);
 
     // 6. 最终 sp 应该已经是 16 字节对齐的
     // sp &= !STACK_ALIGN_MASK;
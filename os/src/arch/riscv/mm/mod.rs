@@ -69,3 +69,51 @@ pub const unsafe fn va_to_pa(va: VA) -> PA {
 pub const fn pa_to_va(pa: PA) -> VA {
     VA::from_usize(pa.as_usize() | VADDR_START)
 }
+
+/// DMA 传输前把 `[pa, pa + len)` 的 CPU 缓存行写回内存
+///
+/// QEMU virt 平台的设备访问与 CPU 缓存一致，只需一道内存屏障保证写入先于
+/// 通知设备；缓存不一致的硬件应在此执行 Zicbom `cbo.clean`。
+pub fn dma_cache_wback(_pa: PA, _len: usize) {
+    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// 设备写入 `[pa, pa + len)` 后丢弃 CPU 中的旧缓存行
+///
+/// 缓存一致的平台上只需内存屏障；缓存不一致的硬件应在此执行 Zicbom `cbo.inval`。
+pub fn dma_cache_inv(_pa: PA, _len: usize) {
+    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// 把 `[va, va + len)` 的数据缓存写回到取指可见的层级
+///
+/// RISC-V 的数据缓存在 hart 之间保持一致，只需 `fence rw, rw` 让之前的写入对所有
+/// hart 可见；缓存不一致的硬件应在此执行 Zicbom `cbo.flush`。
+pub fn flush_dcache_range(_va: VA, _len: usize) {
+    unsafe { core::arch::asm!("fence rw, rw") };
+}
+
+/// 写入 `[va, va + len)` 的代码后同步指令缓存
+///
+/// `fence.i` 不接受地址且只作用于执行它的 hart：本地执行一次，其余在线 hart 通过
+/// SBI RFENCE 扩展的 `remote_fence_i` 同步，不支持时发送 IPI 让它们各自执行。
+pub fn sync_icache_range(va: VA, len: usize) {
+    use crate::arch::ipi::{IpiType, send_ipi_many};
+    use crate::arch::sbi::{SBI_INFO, eid, remote_fence_i};
+
+    flush_dcache_range(va, len);
+    // 关抢占，保证本地 hart 与“其余 hart”的划分在发送期间不变
+    let _guard = crate::sync::PreemptGuard::new();
+    unsafe { core::arch::asm!("fence.i") };
+
+    let num_cpu = crate::kernel::num_cpu();
+    let others = ((1usize << num_cpu) - 1) & !(1 << crate::arch::kernel::cpu::cpu_id());
+    if others == 0 {
+        return;
+    }
+    if SBI_INFO.has_extension(eid::RFENCE) {
+        remote_fence_i(others, 0);
+    } else {
+        send_ipi_many(others, IpiType::FenceI);
+    }
+}
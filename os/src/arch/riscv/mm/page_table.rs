@@ -369,10 +369,58 @@ impl PageTableInnerTrait<PageTableEntry> for PageTableInner {
 
         Err(PagingError::NotMapped) // 未找到映射
     }
+
+    // 把已映射的叶子改写为交换项
+    fn set_swap_entry(&mut self, vpn: Vpn, slot: usize) -> PagingResult<()> {
+        let pte = self.leaf_pte(vpn).ok_or(PagingError::NotMapped)?;
+        if !pte.is_valid() {
+            return Err(PagingError::NotMapped);
+        }
+        *pte = PageTableEntry::new_swap(slot);
+        Self::tlb_flush_all_cpus(vpn);
+        Ok(())
+    }
+
+    // 读取叶子上的交换槽号
+    fn swap_entry(&self, vpn: Vpn) -> Option<usize> {
+        let pte = self.leaf_pte(vpn)?;
+        pte.swap_slot()
+    }
+
+    // 清除叶子上的交换项 (V=0 的页表项不会进入 TLB，无需刷新)
+    fn clear_swap_entry(&mut self, vpn: Vpn) -> PagingResult<()> {
+        let pte = self.leaf_pte(vpn).ok_or(PagingError::NotMapped)?;
+        if pte.swap_slot().is_none() {
+            return Err(PagingError::NotMapped);
+        }
+        pte.clear();
+        Ok(())
+    }
 }
 
 // PageTableInner 的额外实现（非 trait 方法）
 impl PageTableInner {
+    /// 找到 `vpn` 的叶子页表项（不检查叶子本身是否有效）
+    #[allow(clippy::mut_from_ref)]
+    fn leaf_pte(&self, vpn: Vpn) -> Option<&mut PageTableEntry> {
+        let mut ppn = self.root;
+        let vpn_value = vpn.as_usize();
+        for level in (1..Self::LEVELS).rev() {
+            let idx = (vpn_value >> (9 * level)) & 0x1ff;
+            // Unsafe: 页表页由本页表持有，索引不超过 512
+            let pte = unsafe {
+                &*(ppn.start_addr().to_va().as_usize() as *const PageTableEntry).add(idx)
+            };
+            if !pte.is_valid() {
+                return None;
+            }
+            ppn = pte.ppn();
+        }
+        let idx = vpn_value & 0x1ff;
+        // Unsafe: 同上；调用者通过 &mut self 或只读使用保证不产生别名写
+        unsafe { Some(&mut *(ppn.start_addr().to_va().as_usize() as *mut PageTableEntry).add(idx)) }
+    }
+
     /// 刷新所有 CPU 的 TLB（多核 TLB Shootdown）
     ///
     /// 此函数执行以下操作：
@@ -100,6 +100,19 @@ impl PageTableEntryTrait for PageTableEntry {
         self.0 == 0
     }
 
+    // 交换项：V=0，交换槽号存放在 PPN 字段 (硬件忽略 V=0 的页表项的其余位)
+    fn new_swap(slot: usize) -> Self {
+        PageTableEntry((slot as u64) << SV39_PTE_PPN_OFFSET)
+    }
+
+    // 获取交换项中的交换槽号
+    fn swap_slot(&self) -> Option<usize> {
+        if self.is_valid() || self.is_empty() {
+            return None;
+        }
+        Some((self.0 >> SV39_PTE_PPN_OFFSET) as usize)
+    }
+
     // 获取页表项中的物理页号 (PPN)
     fn ppn(&self) -> Ppn {
         // 提取 PPN 位，并右移 10 位
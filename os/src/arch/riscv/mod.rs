@@ -2,6 +2,7 @@
 pub mod boot;
 pub mod constant;
 pub mod cpu_ops;
+pub mod cpuinfo;
 pub mod intr;
 pub mod ipi;
 pub mod kernel;
@@ -9,6 +10,7 @@ pub mod lib;
 pub mod memory;
 pub mod mm;
 pub mod platform;
+pub mod sbi;
 pub mod timer;
 pub mod trap;
 
@@ -0,0 +1,410 @@
+//! SBI 客户端
+//!
+//! 按扩展封装 S 态到 SBI 实现（OpenSBI、RustSBI 等）的调用：
+//! - BASE：规范版本、实现信息与扩展探测
+//! - SRST：带类型和原因的系统复位
+//! - HSM：hart 启动、停止、挂起与状态查询，供 SMP 启动使用
+//! - IPI：按 hart 掩码发送核间中断
+//!
+//! 首次使用时通过 BASE 的 `probe_extension` 探测一次，结果缓存在 [`SBI_INFO`] 中；
+//! 调用方据此选择新扩展或回退到 legacy 调用。探测结果通过 `/proc/sbi` 输出。
+
+use alloc::string::String;
+use core::fmt::Write;
+
+/// SBI 扩展 ID
+pub mod eid {
+    pub const LEGACY_SEND_IPI: usize = 0x04;
+    pub const LEGACY_SHUTDOWN: usize = 0x08;
+    pub const BASE: usize = 0x10;
+    pub const TIME: usize = 0x5449_4D45;
+    pub const IPI: usize = 0x73_5049;
+    pub const RFENCE: usize = 0x5246_4E43;
+    pub const HSM: usize = 0x48_534D;
+    pub const SRST: usize = 0x5352_5354;
+    pub const PMU: usize = 0x50_4D55;
+    pub const DBCN: usize = 0x4442_434E;
+    pub const SUSP: usize = 0x5355_5350;
+}
+
+/// `/proc/sbi` 中列出的扩展
+const KNOWN_EXTENSIONS: [(&str, usize); 9] = [
+    ("BASE", eid::BASE),
+    ("TIME", eid::TIME),
+    ("IPI", eid::IPI),
+    ("RFENCE", eid::RFENCE),
+    ("HSM", eid::HSM),
+    ("SRST", eid::SRST),
+    ("PMU", eid::PMU),
+    ("DBCN", eid::DBCN),
+    ("SUSP", eid::SUSP),
+];
+
+/// SBI 调用返回值
+#[derive(Debug, Clone, Copy)]
+pub struct SbiRet {
+    pub error: isize,
+    pub value: usize,
+}
+
+impl SbiRet {
+    /// 转换为 `Result`，成功时返回 `value`
+    pub fn into_result(self) -> Result<usize, SbiError> {
+        match self.error {
+            0 => Ok(self.value),
+            error => Err(SbiError::from_code(error)),
+        }
+    }
+}
+
+/// SBI 标准错误码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbiError {
+    Failed,
+    NotSupported,
+    InvalidParam,
+    Denied,
+    InvalidAddress,
+    AlreadyAvailable,
+    AlreadyStarted,
+    AlreadyStopped,
+    /// 规范之外的错误码
+    Unknown(isize),
+}
+
+impl SbiError {
+    fn from_code(code: isize) -> Self {
+        match code {
+            -1 => Self::Failed,
+            -2 => Self::NotSupported,
+            -3 => Self::InvalidParam,
+            -4 => Self::Denied,
+            -5 => Self::InvalidAddress,
+            -6 => Self::AlreadyAvailable,
+            -7 => Self::AlreadyStarted,
+            -8 => Self::AlreadyStopped,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// 执行 SBI 调用
+#[inline(always)]
+fn sbi_call(eid: usize, fid: usize, arg0: usize, arg1: usize, arg2: usize) -> SbiRet {
+    let error: isize;
+    let value: usize;
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in("a7") eid,
+            in("a6") fid,
+            inlateout("a0") arg0 => error,
+            inlateout("a1") arg1 => value,
+            in("a2") arg2,
+        );
+    }
+    SbiRet { error, value }
+}
+
+/// BASE 扩展（规范 v0.2 起必须实现）
+pub mod base {
+    use super::{SbiRet, eid, sbi_call};
+
+    fn call(fid: usize) -> SbiRet {
+        sbi_call(eid::BASE, fid, 0, 0, 0)
+    }
+
+    /// 规范版本，bit[30:24] 为主版本号，bit[23:0] 为次版本号
+    pub fn spec_version() -> SbiRet {
+        call(0)
+    }
+
+    pub fn impl_id() -> SbiRet {
+        call(1)
+    }
+
+    pub fn impl_version() -> SbiRet {
+        call(2)
+    }
+
+    /// 扩展可用时 `value` 非零
+    pub fn probe_extension(extension: usize) -> SbiRet {
+        sbi_call(eid::BASE, 3, extension, 0, 0)
+    }
+
+    pub fn mvendorid() -> SbiRet {
+        call(4)
+    }
+
+    pub fn marchid() -> SbiRet {
+        call(5)
+    }
+
+    pub fn mimpid() -> SbiRet {
+        call(6)
+    }
+}
+
+/// SRST 复位类型
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+#[repr(usize)]
+pub enum ResetType {
+    Shutdown = 0,
+    ColdReboot = 1,
+    WarmReboot = 2,
+}
+
+/// SRST 复位原因
+#[derive(Debug, Clone, Copy)]
+#[repr(usize)]
+pub enum ResetReason {
+    NoReason = 0,
+    SystemFailure = 1,
+}
+
+/// SRST 扩展：系统复位，成功时不返回
+pub fn system_reset(reset_type: ResetType, reason: ResetReason) -> SbiRet {
+    sbi_call(eid::SRST, 0, reset_type as usize, reason as usize, 0)
+}
+
+/// Legacy 关机（SBI v0.1），成功时不返回
+pub fn legacy_shutdown() {
+    sbi_call(eid::LEGACY_SHUTDOWN, 0, 0, 0, 0);
+}
+
+/// HSM 扩展：hart 状态管理
+pub mod hsm {
+    use super::{SbiRet, eid, sbi_call};
+
+    /// `hart_get_status` 返回的 hart 状态
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum HartState {
+        Started,
+        Stopped,
+        StartPending,
+        StopPending,
+        Suspended,
+        SuspendPending,
+        ResumePending,
+    }
+
+    impl HartState {
+        fn from_value(value: usize) -> Option<Self> {
+            Some(match value {
+                0 => Self::Started,
+                1 => Self::Stopped,
+                2 => Self::StartPending,
+                3 => Self::StopPending,
+                4 => Self::Suspended,
+                5 => Self::SuspendPending,
+                6 => Self::ResumePending,
+                _ => return None,
+            })
+        }
+
+        pub fn as_str(self) -> &'static str {
+            match self {
+                Self::Started => "started",
+                Self::Stopped => "stopped",
+                Self::StartPending => "start-pending",
+                Self::StopPending => "stop-pending",
+                Self::Suspended => "suspended",
+                Self::SuspendPending => "suspend-pending",
+                Self::ResumePending => "resume-pending",
+            }
+        }
+    }
+
+    /// 启动 `hartid`，其从物理地址 `start_addr` 开始执行，`a1` 为 `opaque`
+    pub fn hart_start(hartid: usize, start_addr: usize, opaque: usize) -> SbiRet {
+        sbi_call(eid::HSM, 0, hartid, start_addr, opaque)
+    }
+
+    /// 停止当前 hart，成功时不返回
+    pub fn hart_stop() -> SbiRet {
+        sbi_call(eid::HSM, 1, 0, 0, 0)
+    }
+
+    /// 查询 `hartid` 的状态
+    pub fn hart_get_status(hartid: usize) -> Result<HartState, super::SbiError> {
+        let value = sbi_call(eid::HSM, 2, hartid, 0, 0).into_result()?;
+        HartState::from_value(value).ok_or(super::SbiError::Unknown(value as isize))
+    }
+
+    /// 挂起当前 hart
+    ///
+    /// `suspend_type` 为 0 时是默认的保持型挂起，唤醒后从调用处返回；
+    /// 非保持型挂起从 `resume_addr` 恢复，`a1` 为 `opaque`。
+    #[allow(dead_code)]
+    pub fn hart_suspend(suspend_type: u32, resume_addr: usize, opaque: usize) -> SbiRet {
+        sbi_call(eid::HSM, 3, suspend_type as usize, resume_addr, opaque)
+    }
+}
+
+/// RFENCE 扩展：让 `hart_mask_base` 起的 `hart_mask` 中的 hart 执行 `fence.i`
+pub fn remote_fence_i(hart_mask: usize, hart_mask_base: usize) -> SbiRet {
+    sbi_call(eid::RFENCE, 0, hart_mask, hart_mask_base, 0)
+}
+
+/// IPI 扩展：向 `hart_mask_base` 起的 `hart_mask` 中的 hart 发送 IPI
+pub fn send_ipi(hart_mask: usize, hart_mask_base: usize) -> SbiRet {
+    sbi_call(eid::IPI, 0, hart_mask, hart_mask_base, 0)
+}
+
+/// Legacy IPI（SBI v0.1），hart 掩码通过指针传递
+pub fn legacy_send_ipi(hart_mask: usize) {
+    sbi_call(
+        eid::LEGACY_SEND_IPI,
+        0,
+        &hart_mask as *const _ as usize,
+        0,
+        0,
+    );
+}
+
+/// 探测得到的 SBI 实现信息
+#[derive(Debug, Clone, Copy)]
+pub struct SbiInfo {
+    /// 规范版本 (主, 次)，没有 BASE 扩展的 v0.1 实现为 (0, 1)
+    pub spec_version: (usize, usize),
+    pub impl_id: usize,
+    pub impl_version: usize,
+    pub mvendorid: usize,
+    pub marchid: usize,
+    pub mimpid: usize,
+    /// 与 [`KNOWN_EXTENSIONS`] 一一对应
+    extensions: [bool; KNOWN_EXTENSIONS.len()],
+}
+
+impl SbiInfo {
+    fn probe() -> Self {
+        let Ok(version) = base::spec_version().into_result() else {
+            return Self {
+                spec_version: (0, 1),
+                impl_id: 0,
+                impl_version: 0,
+                mvendorid: 0,
+                marchid: 0,
+                mimpid: 0,
+                extensions: [false; KNOWN_EXTENSIONS.len()],
+            };
+        };
+        let value = |ret: SbiRet| ret.into_result().unwrap_or(0);
+        Self {
+            spec_version: ((version >> 24) & 0x7f, version & 0xff_ffff),
+            impl_id: value(base::impl_id()),
+            impl_version: value(base::impl_version()),
+            mvendorid: value(base::mvendorid()),
+            marchid: value(base::marchid()),
+            mimpid: value(base::mimpid()),
+            extensions: KNOWN_EXTENSIONS.map(|(_, eid)| value(base::probe_extension(eid)) != 0),
+        }
+    }
+
+    /// 扩展 `eid` 是否可用
+    pub fn has_extension(&self, eid: usize) -> bool {
+        KNOWN_EXTENSIONS
+            .iter()
+            .zip(self.extensions)
+            .any(|(&(_, known), present)| known == eid && present)
+    }
+
+    /// 实现名称，见 SBI 规范的实现 ID 表
+    pub fn impl_name(&self) -> &'static str {
+        match self.impl_id {
+            0 => "BBL",
+            1 => "OpenSBI",
+            2 => "Xvisor",
+            3 => "KVM",
+            4 => "RustSBI",
+            5 => "Diosix",
+            6 => "Coffer",
+            7 => "Xen Project",
+            8 => "PolarFire Hart Software Services",
+            9 => "coreboot",
+            10 => "oreboot",
+            11 => "bhyve",
+            _ => "unknown",
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    /// 首次访问时探测的 SBI 信息
+    pub static ref SBI_INFO: SbiInfo = SbiInfo::probe();
+}
+
+/// 生成 `/proc/sbi` 的内容：实现信息、扩展探测结果和各 hart 的 HSM 状态
+pub fn sbi_info() -> String {
+    let info = *SBI_INFO;
+    let mut out = String::new();
+    let _ = write_info(&mut out, &info);
+    out
+}
+
+fn write_info(out: &mut String, info: &SbiInfo) -> core::fmt::Result {
+    writeln!(
+        out,
+        "spec_version\t: {}.{}",
+        info.spec_version.0, info.spec_version.1
+    )?;
+    writeln!(out, "impl_id\t\t: {} ({})", info.impl_id, info.impl_name())?;
+    writeln!(out, "impl_version\t: {:#x}", info.impl_version)?;
+    writeln!(out, "mvendorid\t: {:#x}", info.mvendorid)?;
+    writeln!(out, "marchid\t\t: {:#x}", info.marchid)?;
+    writeln!(out, "mimpid\t\t: {:#x}", info.mimpid)?;
+    writeln!(out, "extensions\t:")?;
+    for (&(name, eid), present) in KNOWN_EXTENSIONS.iter().zip(info.extensions) {
+        writeln!(
+            out,
+            "  {:<8}{:#012x}  {}",
+            name,
+            eid,
+            if present { "yes" } else { "no" }
+        )?;
+    }
+    if info.has_extension(eid::HSM) {
+        writeln!(out, "harts\t\t:")?;
+        for hart in 0..crate::kernel::num_cpu() {
+            match hsm::hart_get_status(hart) {
+                Ok(state) => writeln!(out, "  hart{}\t{}", hart, state.as_str())?,
+                Err(e) => writeln!(out, "  hart{}\terror {:?}", hart, e)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_sbi_error_codes, {
+        kassert!(SbiRet { error: 0, value: 7 }.into_result() == Ok(7));
+        kassert!(
+            SbiRet {
+                error: -2,
+                value: 0
+            }
+            .into_result()
+                == Err(SbiError::NotSupported)
+        );
+        kassert!(SbiError::from_code(-100) == SbiError::Unknown(-100));
+    });
+
+    test_case!(test_sbi_probe, {
+        let info = *SBI_INFO;
+        if info.spec_version != (0, 1) {
+            kassert!(info.has_extension(eid::BASE));
+        }
+        kassert!(!info.has_extension(0x1234_5678));
+        kassert!(sbi_info().starts_with("spec_version"));
+        // 当前 hart 正在运行
+        if info.has_extension(eid::HSM) {
+            let hart = crate::arch::kernel::cpu::cpu_id();
+            kassert!(hsm::hart_get_status(hart) == Ok(hsm::HartState::Started));
+        }
+    });
+}
@@ -6,8 +6,8 @@ use core::sync::atomic::{AtomicUsize, Ordering};
 use crate::{arch::lib::set_timer, kernel};
 use riscv::register::time;
 
-/// 每秒的时钟中断次数
-/// 决定内核每秒想要多少次时钟中断
+/// 默认的每秒时钟中断次数，也是用户态看到的时钟节拍（USER_HZ）
+/// 实际节拍频率见 `kernel::time::TICK_CONFIG`，可由 `hz=` 参数修改
 pub const TICKS_PER_SEC: usize = 100;
 /// 每秒的毫秒数
 pub const MSEC_PER_SEC: usize = 1000;
@@ -22,24 +22,42 @@ pub fn get_ticks() -> usize {
 }
 
 /// 获取当前硬件时钟周期数时间
+///
+/// 启用 `virtual_time` 时返回 `kernel::vclock` 换算后的虚拟时间。
 #[inline]
 pub fn get_time() -> usize {
-    time::read()
+    #[cfg(feature = "virtual_time")]
+    {
+        kernel::vclock::now(time::read())
+    }
+    #[cfg(not(feature = "virtual_time"))]
+    {
+        time::read()
+    }
 }
 
 /// 获取当前时间（以毫秒为单位）
 #[inline]
 pub fn get_time_ms() -> usize {
-    (time::read() as u128 * MSEC_PER_SEC as u128 / clock_freq() as u128) as usize
+    (get_time() as u128 * MSEC_PER_SEC as u128 / clock_freq() as u128) as usize
 }
 
 /// 设置定时器中断
 #[inline]
 pub fn set_next_trigger() {
-    let next = get_time() + clock_freq() / TICKS_PER_SEC;
+    let next = time::read() + kernel::time::TICK_CONFIG.tick_cycles();
     set_timer(next);
 }
 
+/// 设置一次性定时器中断，在 `get_time` 到达 `deadline` 时触发
+///
+/// 中断处理程序会调用 `set_next_trigger` 恢复周期节拍。
+#[inline]
+pub fn set_oneshot_trigger(deadline: usize) {
+    // SBI 按硬件计数器比较，换算时扣除虚拟时钟的偏移
+    set_timer(time::read() + deadline.saturating_sub(get_time()));
+}
+
 /// 初始化定时器
 pub fn init() {
     set_next_trigger();
@@ -71,6 +71,12 @@ impl SumGuard {
     pub fn was_set(&self) -> bool {
         self.was_set
     }
+
+    /// 当前是否允许内核访问用户空间内存（SUM 位是否置位）
+    #[inline]
+    pub fn active() -> bool {
+        sstatus::read().sum()
+    }
 }
 
 impl Drop for SumGuard {
@@ -45,6 +45,10 @@ pub struct TrapFrame {
     /// 指向当前 CPU 结构体的指针
     /// 用于在 trap entry 时快速获取 CPU 信息并设置 tp
     pub cpu_ptr: usize, // 272(sp)
+    /// 系统调用入口时的 a0，a0 随后会被返回值覆盖，重启系统调用时用它恢复参数
+    pub orig_a0: usize,
+    /// 是否正从系统调用返回，仅在此时才需要考虑系统调用重启
+    pub in_syscall: bool,
 }
 
 impl TrapFrame {
@@ -95,6 +99,8 @@ impl TrapFrame {
             sstatus: 0,
             kernel_sp: 0,
             cpu_ptr,
+            orig_a0: 0,
+            in_syscall: false,
         }
     }
 
@@ -219,6 +225,7 @@ impl TrapFrame {
         }
         // 子进程返回 0
         self.x10_a0 = 0;
+        self.in_syscall = false;
         self.kernel_sp = kernel_sp;
         // 如果提供了新栈，使用新栈；否则使用父进程的栈
         if user_sp != 0 {
@@ -316,6 +323,7 @@ impl TrapFrame {
         }
         // 子进程返回值为0
         self.x10_a0 = 0;
+        self.in_syscall = false;
     }
 
     /// 将 TrapFrame 转换为 MContextT 结构体
@@ -361,6 +369,7 @@ impl TrapFrame {
 
     /// 从 MContextT 恢复 TrapFrame
     pub fn restore_from_mcontext(&mut self, mcontext: &MContextT) {
+        self.in_syscall = false;
         self.sepc = mcontext.gregs[0] as usize;
         self.x1_ra = mcontext.gregs[1] as usize;
         self.x2_sp = mcontext.gregs[2] as usize;
@@ -423,6 +432,22 @@ impl crate::kernel::syscall::syscall_frame::SyscallFrame for TrapFrame {
     fn set_ret(&mut self, val: usize) {
         self.x10_a0 = val;
     }
+
+    fn enter_syscall(&mut self) {
+        self.orig_a0 = self.x10_a0;
+        self.in_syscall = true;
+    }
+
+    fn leave_syscall(&mut self) -> Option<usize> {
+        core::mem::take(&mut self.in_syscall).then_some(self.x10_a0)
+    }
+
+    fn restart(&mut self, syscall_id: usize) {
+        // 系统调用指令定长 4 字节
+        self.sepc -= 4;
+        self.x10_a0 = self.orig_a0;
+        self.x17_a7 = syscall_id;
+    }
 }
 
 impl crate::arch::HwTrapFrame for TrapFrame {
@@ -460,6 +485,51 @@ impl crate::arch::HwTrapFrame for TrapFrame {
         TrapFrame::get_sp(self)
     }
 
+    fn get_fp(&self) -> usize {
+        self.x8_s0
+    }
+
+    fn get_ra(&self) -> usize {
+        self.x1_ra
+    }
+
+    fn gprs(&self) -> [usize; 32] {
+        [
+            0,
+            self.x1_ra,
+            self.x2_sp,
+            self.x3_gp,
+            self.x4_tp,
+            self.x5_t0,
+            self.x6_t1,
+            self.x7_t2,
+            self.x8_s0,
+            self.x9_s1,
+            self.x10_a0,
+            self.x11_a1,
+            self.x12_a2,
+            self.x13_a3,
+            self.x14_a4,
+            self.x15_a5,
+            self.x16_a6,
+            self.x17_a7,
+            self.x18_s2,
+            self.x19_s3,
+            self.x20_s4,
+            self.x21_s5,
+            self.x22_s6,
+            self.x23_s7,
+            self.x24_s8,
+            self.x25_s9,
+            self.x26_s10,
+            self.x27_s11,
+            self.x28_t3,
+            self.x29_t4,
+            self.x30_t5,
+            self.x31_t6,
+        ]
+    }
+
     fn set_sp(&mut self, val: usize) {
         TrapFrame::set_sp(self, val)
     }
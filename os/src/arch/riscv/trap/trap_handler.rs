@@ -10,11 +10,11 @@ use riscv::register::sstatus::SPP;
 use riscv::register::{sepc, sscratch, sstatus, stval};
 
 use crate::arch::constant::SUPERVISOR_EXTERNAL;
-use crate::arch::timer::{TIMER_TICKS, clock_freq, get_time};
+use crate::arch::timer::{TIMER_TICKS, get_time};
 use crate::arch::trap::restore;
 use crate::device::IRQ_MANAGER;
+use crate::kernel::schedule;
 use crate::kernel::syscall::dispatch::dispatch_syscall;
-use crate::kernel::{TIMER, TIMER_QUEUE, schedule, send_signal_process, wake_up_task};
 
 macro_rules! emergency_println {
     ($fmt: literal $(, $($arg: tt)+)?) => {
@@ -50,16 +50,17 @@ pub extern "C" fn trap_handler(trap_frame: &mut super::TrapFrame) {
             // 仅在返回用户态时检查信号
             check_signal();
         }
-        SPP::Supervisor => kernel_trap(scause, sepc_old, sstatus_old),
+        SPP::Supervisor => kernel_trap(scause, sepc_old, sstatus_old, trap_frame),
     }
     // 恢复“当前任务”的陷阱帧。
     // 注意：在陷阱处理中可能发生了调度（例如用户态定时器中断），
     // 这时需要恢复到新任务的 TrapFrame，而不是入口参数 trap_frame。
+    // 离开内核前顺带检查内核栈金丝雀（仅 debug 构建）
     let tf_ptr = crate::kernel::try_current_task()
         .map(|t| {
-            t.lock()
-                .trap_frame_ptr
-                .load(core::sync::atomic::Ordering::SeqCst) as usize
+            let t = t.lock();
+            t.check_stack_canary();
+            t.trap_frame_ptr.load(core::sync::atomic::Ordering::SeqCst) as usize
         })
         .unwrap_or(trap_frame as *mut _ as usize);
     // SAFETY: 指针来源于当前任务保存的 trap_frame_ptr 或回退到入口参数。
@@ -74,6 +75,7 @@ pub fn user_trap(
     trap_frame: &mut super::TrapFrame,
 ) {
     crate::pr_debug!("[user_trap] scause: {:?}", scause.cause());
+    crate::kernel::stall::touch_progress();
     match scause.cause() {
         Trap::Exception(8) => {
             // 设置返回地址为下一个指令
@@ -84,7 +86,7 @@ pub fn user_trap(
         Trap::Interrupt(5) => {
             // 处理时钟中断
             crate::arch::timer::set_next_trigger();
-            check_timer();
+            check_timer(true);
         }
         Trap::Interrupt(1) => {
             // 软件中断（IPI）：仅当有待运行任务时才调度，避免空转
@@ -101,6 +103,11 @@ pub fn user_trap(
             // 外部中断（设备）
             check_device();
         }
+        Trap::Exception(12) | Trap::Exception(13) | Trap::Exception(15)
+            if crate::mm::swap::handle_page_fault(stval::read()) =>
+        {
+            // 访问了被换出的页，换入后重新执行
+        }
         _ => {
             // 立即读取相关寄存器的当前值
             let stval_val = stval::read();
@@ -269,22 +276,28 @@ pub fn user_trap(
             }
             emergency_println!("");
             emergency_println!("===============================================");
-            // 不要因为用户态异常让内核 panic；仿照 Linux 行为，终止当前任务即可。
-            // TODO: 进一步完善为向进程投递对应信号（SIGILL/SIGSEGV/...），并支持 core dump 等。
+            // 不要因为用户态异常让内核 panic；仿照 Linux 行为，向当前线程强制投递对应信号，
+            // 返回用户态前按其处理方式（默认为终止并 core dump）处理。
             let sig = match scause.cause() {
                 Trap::Exception(2) => crate::uapi::signal::NUM_SIGILL, // Illegal Instruction
+                Trap::Exception(3) => crate::uapi::signal::NUM_SIGTRAP, // Breakpoint
                 Trap::Exception(12) | Trap::Exception(13) | Trap::Exception(15) => {
                     crate::uapi::signal::NUM_SIGSEGV
                 }
                 _ => crate::uapi::signal::NUM_SIGILL,
             };
-            crate::kernel::terminate_task(128 + sig);
+            crate::ipc::force_signal(&crate::kernel::current_task(), sig);
         }
     }
 }
 
 /// 处理来自内核态的陷阱（中断、异常）
-pub fn kernel_trap(scause: scause::Scause, sepc_old: usize, sstatus_old: sstatus::Sstatus) {
+pub fn kernel_trap(
+    scause: scause::Scause,
+    sepc_old: usize,
+    sstatus_old: sstatus::Sstatus,
+    trap_frame: &super::TrapFrame,
+) {
     match scause.cause() {
         Trap::Interrupt(5) => {
             // 时钟中断（内核态）
@@ -294,7 +307,9 @@ pub fn kernel_trap(scause: scause::Scause, sepc_old: usize, sstatus_old: sstatus
             crate::arch::timer::set_next_trigger();
 
             // 驱动 TIMER/TIMER_QUEUE，唤醒超时任务
-            check_timer();
+            check_timer(false);
+            // 被打断的内核代码长时间没有进展时打印现场
+            crate::kernel::stall::check_stall(trap_frame);
 
             // 是否需要在内核态进行一次调度：
             // - 运行队列非空；或
@@ -330,6 +345,11 @@ pub fn kernel_trap(scause: scause::Scause, sepc_old: usize, sstatus_old: sstatus
             // 外部中断（设备）
             check_device();
         }
+        // 用户拷贝访问了被换出的页
+        Trap::Exception(12) | Trap::Exception(13) | Trap::Exception(15)
+            if stval::read() <= crate::arch::constant::USER_TOP
+                && crate::arch::uaccess::UserAccessGuard::active()
+                && crate::mm::swap::handle_page_fault(stval::read()) => {}
         // 中断处理时发生异常一般是致命的
         Trap::Exception(e) => {
             // 立即读取 sscratch 和 stval 寄存器的当前值
@@ -347,6 +367,11 @@ pub fn kernel_trap(scause: scause::Scause, sepc_old: usize, sstatus_old: sstatus
             emergency_println!("  Faulting PC (sepc):  {:#x}", sepc_old);
             emergency_println!("  sstatus:             {:#x}", sstatus_old.bits());
             emergency_println!("  sscratch:            {:#x}", sscratch_val);
+            if stval_val <= crate::arch::constant::USER_TOP
+                && !crate::arch::uaccess::UserAccessGuard::active()
+            {
+                emergency_println!("  User address accessed outside UserAccessGuard");
+            }
             emergency_println!("==============================================");
             // sbi::shutdown(true);
             panic!("Kernel exception in S-Mode");
@@ -361,22 +386,21 @@ pub fn kernel_trap(scause: scause::Scause, sepc_old: usize, sstatus_old: sstatus
 }
 
 /// 处理时钟中断
-pub fn check_timer() {
+/// # 参数
+/// - `user`: 中断是否发生在用户态，用于 CPU 时间定时器的记账
+pub fn check_timer(user: bool) {
     let _ticks = TIMER_TICKS.fetch_add(1, Ordering::Relaxed);
+    crate::kernel::time::timekeeping_tick();
+    crate::kernel::loadavg::loadavg_tick();
+    crate::kernel::watchdog::watchdog_tick();
+    crate::kernel::account_cpu_tick(user);
 
     // 推进网络栈的请求放到 kworker 中执行，避免在硬中断上下文里持有网络栈锁。
     crate::net::socket::request_network_poll();
 
-    while let Some(task) = TIMER_QUEUE.lock().pop_due_task(get_time()) {
-        wake_up_task(task);
-    }
-    while let Some(entry) = TIMER.lock().pop_due_entry(get_time()) {
-        send_signal_process(&entry.task, entry.sig);
-        if !entry.it_interval.is_zero() {
-            let next_trigger = get_time() + entry.it_interval.into_freq(clock_freq());
-            TIMER.lock().push(next_trigger, entry);
-        }
-    }
+    let now = get_time();
+    crate::kernel::run_timer_queue(now);
+    crate::kernel::run_itimers(now);
     // 仅在时间片用尽且运行队列非空时才触发调度，避免空转日志刷屏
     let do_sched = {
         let mut sched = crate::kernel::current_scheduler().lock();
@@ -0,0 +1,72 @@
+//! 内核访问用户内存的统一守卫
+//!
+//! [`UserAccessGuard`] 是各架构用户访问开关的统一外观：
+//! - RISC-V：置位 `sstatus.SUM`，允许 S 态访问 U 态页；
+//! - LoongArch：打开每 CPU 的软件访问窗口（硬件没有对应开关），
+//!   窗口之外的内核态用户地址异常会被报告为越权访问。
+//!
+//! 本模块的原始拷贝函数都要求调用者出示守卫的引用，
+//! 因此无法在未打开用户访问的情况下触碰用户内存。
+
+use core::marker::PhantomData;
+
+use super::trap::SumGuard;
+
+/// 用户内存访问守卫
+///
+/// 守卫存在期间允许内核访问用户空间内存，离开作用域时自动恢复。
+/// 守卫不能跨线程传递，也不应长时间持有。
+pub struct UserAccessGuard {
+    _inner: SumGuard,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl UserAccessGuard {
+    /// 打开用户内存访问
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            _inner: SumGuard::new(),
+            _not_send: PhantomData,
+        }
+    }
+
+    /// 当前 CPU 是否允许访问用户内存
+    #[inline]
+    pub fn active() -> bool {
+        SumGuard::active()
+    }
+}
+
+impl Default for UserAccessGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 在守卫保护下从用户空间复制 `len` 字节
+///
+/// # Safety
+/// 调用者必须已校验 `src .. src + len` 位于用户地址范围内，且 `dst` 可写 `len` 字节。
+#[inline]
+pub unsafe fn copy_from_user_raw(_guard: &UserAccessGuard, src: usize, dst: *mut u8, len: usize) {
+    unsafe { core::ptr::copy_nonoverlapping(src as *const u8, dst, len) };
+}
+
+/// 在守卫保护下向用户空间复制 `len` 字节
+///
+/// # Safety
+/// 调用者必须已校验 `dst .. dst + len` 位于用户地址范围内，且 `src` 可读 `len` 字节。
+#[inline]
+pub unsafe fn copy_to_user_raw(_guard: &UserAccessGuard, src: *const u8, dst: usize, len: usize) {
+    unsafe { core::ptr::copy_nonoverlapping(src, dst as *mut u8, len) };
+}
+
+/// 在守卫保护下读取一个用户字节
+///
+/// # Safety
+/// 调用者必须已校验 `src` 位于用户地址范围内。
+#[inline]
+pub unsafe fn read_user_byte_raw(_guard: &UserAccessGuard, src: usize) -> u8 {
+    unsafe { core::ptr::read_volatile(src as *const u8) }
+}
@@ -8,6 +8,10 @@ pub const MAX_CPU_COUNT: usize = 8;
 // about memory management
 pub const PAGE_SIZE: usize = 4096;
 pub const KERNEL_HEAP_SIZE: usize = 32 * 1024 * 1024; // 32MB(临时扩容, 原16MB)
+/// 内核堆（初始区域加上从帧分配器扩展的部分）的总上限
+pub const KERNEL_HEAP_MAX_SIZE: usize = 512 * 1024 * 1024;
+/// 内核堆每次扩展的最小字节数
+pub const KERNEL_HEAP_GROW_CHUNK: usize = 2 * 1024 * 1024;
 pub const USER_STACK_SIZE: usize = 4 * 1024 * 1024; // 4MB
 
 pub const MAX_ARGV: usize = 256;
@@ -36,8 +40,20 @@ pub const USER_SIGRETURN_TRAMPOLINE: usize =
 /// Maximum heap size (prevent OOM)
 pub const MAX_USER_HEAP_SIZE: usize = 128 * 1024 * 1024; // 128MB
 
+/// mmap 新映射与堆、程序映像等关键区域之间保留的间隔
+pub const USER_GUARD_GAP: usize = PAGE_SIZE;
+
+/// mmap 区与用户栈之间保留的间隔
+pub const USER_STACK_GUARD_GAP: usize = 1024 * 1024; // 1MB
+
+/// `/proc/sys/vm/mmap_min_addr` 的默认值
+pub const DEFAULT_MMAP_MIN_ADDR: usize = PAGE_SIZE;
+
 pub const DEFAULT_MAX_FDS: usize = 256;
 
+/// 全系统已打开的文件描述符总数上限（/proc/sys/fs/file-max）
+pub const FILE_MAX: usize = 65536;
+
 // Ext4 filesystem constants
 /// Ext4 文件系统块大小 (必须与 mkfs.ext4 -b 参数一致)
 pub const EXT4_BLOCK_SIZE: usize = 4096;
@@ -45,3 +61,58 @@ pub const EXT4_BLOCK_SIZE: usize = 4096;
 pub const VIRTIO_BLK_SECTOR_SIZE: usize = 512;
 /// 文件系统镜像大小 (与 qemu-run.sh 中的 fs.img 大小一致)
 pub const FS_IMAGE_SIZE: usize = 1024 * 1024 * 1024; // 1 GB
+
+mod options;
+
+pub use options::*;
+
+options::option! {
+    /// 目标架构
+    CONFIG_ARCH: str = if cfg!(target_arch = "riscv64") {
+        "riscv64"
+    } else if cfg!(target_arch = "loongarch64") {
+        "loongarch64"
+    } else {
+        "unknown"
+    };
+    /// 同步原语（feature `sync`）
+    CONFIG_SYNC: bool = cfg!(feature = "sync");
+    /// 内核堆分配器（feature `alloc`）
+    CONFIG_ALLOC: bool = cfg!(feature = "alloc");
+    /// 分页与地址空间（feature `paging`）
+    CONFIG_PAGING: bool = cfg!(feature = "paging");
+    /// 进程管理与 ELF 加载（feature `proc`）
+    CONFIG_PROC: bool = cfg!(feature = "proc");
+    /// 文件系统（feature `fs`）
+    CONFIG_FS: bool = cfg!(feature = "fs");
+    /// 进程地址空间的文件映射（feature `proc_vm`）
+    CONFIG_PROC_VM: bool = cfg!(feature = "proc_vm");
+    /// 设备驱动（feature `device`）
+    CONFIG_DEVICE: bool = cfg!(feature = "device");
+    /// 网络协议栈（feature `net`）
+    CONFIG_NET: bool = cfg!(feature = "net");
+    /// IPv6 socket 地址（feature `proto-ipv6`）
+    CONFIG_IPV6: bool = cfg!(feature = "proto-ipv6");
+    /// 交换文件，关闭时 swapon 返回 ENOSYS（feature `swap`）
+    CONFIG_SWAP: bool = cfg!(feature = "swap");
+    /// SysRq 魔术键与 /proc/sysrq-trigger（feature `sysrq`）
+    CONFIG_SYSRQ: bool = cfg!(feature = "sysrq");
+    /// 测试用的虚拟时钟（feature `virtual_time`）
+    CONFIG_VIRTUAL_TIME: bool = cfg!(feature = "virtual_time");
+    /// 调试构建
+    CONFIG_DEBUG: bool = cfg!(debug_assertions);
+    /// 最大 CPU 数
+    CONFIG_NR_CPUS: usize = MAX_CPU_COUNT;
+    /// 初始内核堆大小（字节）
+    CONFIG_KERNEL_HEAP_SIZE: usize = KERNEL_HEAP_SIZE;
+    /// 内核堆上限（字节）
+    CONFIG_KERNEL_HEAP_MAX_SIZE: usize = KERNEL_HEAP_MAX_SIZE;
+    /// 用户栈大小（字节）
+    CONFIG_USER_STACK_SIZE: usize = USER_STACK_SIZE;
+    /// 默认的 mmap 最低地址（字节）
+    CONFIG_DEFAULT_MMAP_MIN_ADDR: usize = DEFAULT_MMAP_MIN_ADDR;
+    /// 每个进程默认的文件描述符上限
+    CONFIG_DEFAULT_MAX_FDS: usize = DEFAULT_MAX_FDS;
+    /// 全系统打开文件数上限
+    CONFIG_FILE_MAX: usize = FILE_MAX;
+}
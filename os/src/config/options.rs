@@ -0,0 +1,145 @@
+//! 内核构建配置
+//!
+//! 把 cargo feature 和构建期常量登记为 Linux 风格的 `CONFIG_*` 选项：
+//! - [`option!`] 为每个选项生成一个同名常量，子系统用 `if CONFIG_X { ... }`
+//!   判断，关闭时整段代码在编译期被消除；需要连同依赖一起裁剪的模块仍用
+//!   `#[cfg(feature = "...")]`；
+//! - 同时生成选项表 [`OPTIONS`](super::OPTIONS)，运行时可按名字查询，
+//!   启动时打印启用的选项，全部内容通过 `/proc/config` 导出。
+//!
+//! 新增可裁剪子系统时，在 `os/Cargo.toml` 中加 feature，再在 `config.rs` 的
+//! `option!` 中登记一行。
+
+use core::fmt;
+
+/// 选项的值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigValue {
+    /// 开关，对应一个 cargo feature
+    Bool(bool),
+    /// 数值
+    Int(usize),
+    /// 字符串
+    Str(&'static str),
+}
+
+/// 一个配置选项
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigOption {
+    /// 选项名，如 `CONFIG_NET`
+    pub name: &'static str,
+    /// 选项的值
+    pub value: ConfigValue,
+    /// 一行说明
+    pub help: &'static str,
+}
+
+impl ConfigOption {
+    /// 开关是否打开；非开关选项总是返回 `true`
+    pub fn enabled(&self) -> bool {
+        !matches!(self.value, ConfigValue::Bool(false))
+    }
+}
+
+/// 按 Linux `.config` 的格式输出一行
+impl fmt::Display for ConfigOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            ConfigValue::Bool(true) => write!(f, "{}=y", self.name),
+            ConfigValue::Bool(false) => write!(f, "# {} is not set", self.name),
+            ConfigValue::Int(value) => write!(f, "{}={}", self.name, value),
+            ConfigValue::Str(value) => write!(f, "{}=\"{}\"", self.name, value),
+        }
+    }
+}
+
+/// 登记配置选项
+///
+/// 每行形如 `/// 说明` 加 `NAME: 类型 = 值;`，类型为 `bool`、`usize` 或 `str`
+/// （`&'static str`）。生成同名的 `pub const` 和按书写顺序排列的 `OPTIONS` 表。
+macro_rules! option {
+    (@ty bool) => { bool };
+    (@ty usize) => { usize };
+    (@ty str) => { &'static str };
+    (@value bool, $value:expr) => { $crate::config::ConfigValue::Bool($value) };
+    (@value usize, $value:expr) => { $crate::config::ConfigValue::Int($value) };
+    (@value str, $value:expr) => { $crate::config::ConfigValue::Str($value) };
+    ($(#[doc = $help:literal] $name:ident: $kind:ident = $value:expr;)*) => {
+        $(
+            #[doc = $help]
+            pub const $name: $crate::config::option!(@ty $kind) = $value;
+        )*
+
+        /// 所有配置选项，按登记顺序排列
+        pub static OPTIONS: &[$crate::config::ConfigOption] = &[
+            $($crate::config::ConfigOption {
+                name: stringify!($name),
+                value: $crate::config::option!(@value $kind, $name),
+                help: $help.trim_ascii_start(),
+            },)*
+        ];
+    };
+}
+
+pub(crate) use option;
+
+/// 按名字查询选项，`CONFIG_` 前缀可以省略
+pub fn lookup(name: &str) -> Option<&'static ConfigOption> {
+    super::OPTIONS
+        .iter()
+        .find(|option| option.name == name || option.name.strip_prefix("CONFIG_") == Some(name))
+}
+
+/// 启动时打印构建配置：架构和所有打开的开关
+///
+/// 在堆初始化之前调用，逐项打印，不分配内存。
+pub fn print_build_config() {
+    crate::print!("[Boot] Config: {}", super::CONFIG_ARCH);
+    for option in super::OPTIONS
+        .iter()
+        .filter(|option| option.value == ConfigValue::Bool(true))
+    {
+        let name = option.name.strip_prefix("CONFIG_").unwrap_or(option.name);
+        crate::print!(" +{}", name);
+    }
+    crate::println!(" (see /proc/config)");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+    use alloc::format;
+
+    test_case!(test_config_options, {
+        let nr_cpus = lookup("CONFIG_NR_CPUS").map(|o| o.value);
+        kassert!(nr_cpus == Some(ConfigValue::Int(crate::config::MAX_CPU_COUNT)));
+        kassert!(lookup("NR_CPUS").map(|o| o.name) == Some("CONFIG_NR_CPUS"));
+        kassert!(lookup("NO_SUCH_OPTION").is_none());
+
+        // 开关与 cargo feature 一致
+        kassert!(lookup("ALLOC").map(|o| o.enabled()) == Some(cfg!(feature = "alloc")));
+
+        let on = ConfigOption {
+            name: "CONFIG_A",
+            value: ConfigValue::Bool(true),
+            help: "",
+        };
+        let off = ConfigOption {
+            value: ConfigValue::Bool(false),
+            ..on
+        };
+        let int = ConfigOption {
+            value: ConfigValue::Int(8),
+            ..on
+        };
+        let s = ConfigOption {
+            value: ConfigValue::Str("riscv64"),
+            ..on
+        };
+        kassert!(format!("{}", on) == "CONFIG_A=y");
+        kassert!(format!("{}", off) == "# CONFIG_A is not set");
+        kassert!(format!("{}", int) == "CONFIG_A=8");
+        kassert!(format!("{}", s) == "CONFIG_A=\"riscv64\"");
+    });
+}
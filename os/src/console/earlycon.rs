@@ -0,0 +1,174 @@
+//! 早期控制台（earlycon）
+//!
+//! 设备驱动初始化之前的输出都经过这里。earlycon 的目标设备来自：
+//! 1. 命令行参数 `earlycon=<驱动>,<mmio|mmio32>,<物理地址>`；
+//! 2. 否则为设备树 `/chosen/stdout-path` 指向的 ns16550 兼容串口。
+//!
+//! 两者都没有时退回到架构默认输出（RISC-V 为 SBI，LoongArch 为固定 UART）。
+//!
+//! LoongArch 可以通过 DMW 窗口在页表建立之前直接访问串口寄存器；RISC-V
+//! 在驱动映射 MMIO 之前无法触碰串口，因此仍经 SBI 输出，而 SBI 固件写的正是
+//! `stdout-path` 指向的串口。无论哪种方式，这里都记录 earlycon 对应的物理串口，
+//! 供切换到运行时控制台时判断早期输出是否已经出现在同一设备上。
+
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+
+use crate::arch::Platform;
+
+/// 早期控制台配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EarlyconConfig {
+    /// 串口寄存器的物理基地址
+    pub paddr: usize,
+    /// 寄存器间距为 `1 << reg_shift` 字节
+    pub reg_shift: u8,
+}
+
+/// earlycon 串口物理地址，0 表示未配置
+static EARLYCON_PADDR: AtomicUsize = AtomicUsize::new(0);
+/// earlycon 寄存器间距
+static EARLYCON_SHIFT: AtomicU8 = AtomicU8::new(0);
+/// 是否直接通过 MMIO 驱动 earlycon 串口
+static EARLYCON_MMIO: AtomicBool = AtomicBool::new(false);
+
+/// 16550 寄存器：发送保持寄存器
+const UART_THR: usize = 0;
+/// 16550 寄存器：线路状态寄存器
+const UART_LSR: usize = 5;
+/// LSR: 发送保持寄存器空
+const LSR_THRE: u8 = 1 << 5;
+
+/// ns16550 兼容的 compatible 字符串
+const NS16550_COMPATIBLE: &[&str] = &["ns16550a", "ns16550", "snps,dw-apb-uart"];
+
+/// 解析 `earlycon=` 命令行参数
+///
+/// 支持 `earlycon=uart8250,mmio,0x10000000` 与 `earlycon=ns16550a,mmio32,0x...`；
+/// 仅写 `earlycon` 或缺少地址时返回 `None`，由设备树决定。
+pub fn parse_param(cmdline: &str) -> Option<EarlyconConfig> {
+    let value = cmdline
+        .split_ascii_whitespace()
+        .find_map(|arg| arg.strip_prefix("earlycon="))?;
+    let mut fields = value.split(',');
+    let _driver = fields.next()?;
+    let (reg_shift, addr) = match fields.next()? {
+        "mmio" => (0, fields.next()?),
+        "mmio32" => (2, fields.next()?),
+        addr => (0, addr),
+    };
+    let addr = addr
+        .strip_prefix("0x")
+        .or_else(|| addr.strip_prefix("0X"))?;
+    let paddr = usize::from_str_radix(addr, 16).ok()?;
+    (paddr != 0).then_some(EarlyconConfig { paddr, reg_shift })
+}
+
+/// 去掉 `stdout-path` 中 `:` 之后的串口参数（如 `:115200n8`）
+pub fn stdout_path_node(path: &str) -> &str {
+    let path = path.trim_end_matches('\0');
+    path.split(':').next().unwrap_or(path)
+}
+
+/// 从设备树 `/chosen/stdout-path` 得到 earlycon 配置
+#[cfg(feature = "device")]
+fn from_device_tree(fdt: &fdt::Fdt) -> Option<EarlyconConfig> {
+    let chosen = fdt.find_node("/chosen")?;
+    let path = chosen.property("stdout-path")?;
+    let path = stdout_path_node(core::str::from_utf8(path.value).ok()?);
+    let node = if path.starts_with('/') {
+        fdt.find_node(path)?
+    } else {
+        fdt.find_node(fdt.aliases()?.resolve(path)?)?
+    };
+
+    let compatible = node.compatible()?;
+    if !compatible.all().any(|c| NS16550_COMPATIBLE.contains(&c)) {
+        return None;
+    }
+    let paddr = node.reg()?.next()?.starting_address as usize;
+    let reg_shift = node
+        .property("reg-shift")
+        .and_then(|p| p.as_usize())
+        .unwrap_or(0) as u8;
+    Some(EarlyconConfig { paddr, reg_shift })
+}
+
+/// 根据命令行和设备树配置 earlycon
+///
+/// 在启动最早期调用（清零 BSS 之后、第一条输出之前），不使用堆。
+pub fn init() {
+    #[cfg(feature = "device")]
+    {
+        let fdt = &*crate::device::device_tree::FDT;
+        let config = fdt
+            .chosen()
+            .bootargs()
+            .and_then(parse_param)
+            .or_else(|| from_device_tree(fdt));
+        if let Some(config) = config {
+            setup(config);
+        }
+    }
+}
+
+/// 启用指定的 earlycon
+pub fn setup(config: EarlyconConfig) {
+    EARLYCON_SHIFT.store(config.reg_shift, Ordering::Relaxed);
+    EARLYCON_PADDR.store(config.paddr, Ordering::Relaxed);
+    // 只有 LoongArch 能在驱动初始化之前直接访问设备寄存器
+    EARLYCON_MMIO.store(cfg!(target_arch = "loongarch64"), Ordering::Release);
+}
+
+/// earlycon 对应的串口物理地址
+pub fn device() -> Option<usize> {
+    match EARLYCON_PADDR.load(Ordering::Acquire) {
+        0 => None,
+        paddr => Some(paddr),
+    }
+}
+
+/// 通过 earlycon 输出一个字节
+#[inline]
+pub fn putchar(c: u8) {
+    if !EARLYCON_MMIO.load(Ordering::Acquire) {
+        crate::arch::ArchImpl::console_putchar(c);
+        return;
+    }
+
+    let paddr = EARLYCON_PADDR.load(Ordering::Relaxed);
+    let shift = EARLYCON_SHIFT.load(Ordering::Relaxed);
+    let base = crate::arch::mmio_pa_to_va(crate::mm::address::PA::from_usize(paddr)).as_usize();
+    let thr = base + (UART_THR << shift);
+    let lsr = base + (UART_LSR << shift);
+    unsafe {
+        while core::ptr::read_volatile(lsr as *const u8) & LSR_THRE == 0 {}
+        core::ptr::write_volatile(thr as *mut u8, c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_parse_earlycon_param, {
+        let config = parse_param("console=ttyS0 earlycon=uart8250,mmio,0x10000000 quiet");
+        kassert!(
+            config
+                == Some(EarlyconConfig {
+                    paddr: 0x1000_0000,
+                    reg_shift: 0
+                })
+        );
+        let config = parse_param("earlycon=ns16550a,mmio32,0x1fe001e0");
+        kassert!(config.map(|c| c.reg_shift) == Some(2));
+        // 只写 earlycon 时由设备树决定
+        kassert!(parse_param("earlycon").is_none());
+        kassert!(parse_param("earlycon=uart8250,mmio,zz").is_none());
+    });
+
+    test_case!(test_stdout_path_node, {
+        kassert!(stdout_path_node("/soc/serial@10000000:115200n8") == "/soc/serial@10000000");
+        kassert!(stdout_path_node("serial0\0") == "serial0");
+    });
+}
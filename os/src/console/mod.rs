@@ -1,14 +1,20 @@
 //! 统一的控制台抽象
 //!
 //! 提供两阶段控制台：
-//! - 早期阶段：使用 arch::sbi 直接输出
-//! - 运行时阶段：使用 device::console::MAIN_CONSOLE
+//! - 早期阶段：经 [`earlycon`] 输出，同时记入启动缓冲区
+//! - 运行时阶段：写到 device::console 的全部活动控制台，输入来自主控制台
+//!
+//! 切换时，启动缓冲区中的早期输出会重放到与 earlycon 不是同一物理设备的
+//! 活动控制台，保证启动日志不丢失。
+
+pub mod earlycon;
 
 use core::cell::UnsafeCell;
 use core::fmt::{self, Write};
 use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use crate::arch::Platform;
+use crate::log::LogLevel;
 use crate::sync::SpinLock;
 
 /// 控制台是否已切换到运行时模式
@@ -43,29 +49,43 @@ impl BootConsoleBuffer {
         }
     }
 
-    fn replay_to_runtime_console(&self) {
-        #[cfg(feature = "device")]
-        if let Some(console) = crate::device::console::MAIN_CONSOLE.read().as_ref() {
-            let write_seq = self.write_seq.load(Ordering::Acquire);
-            let oldest = write_seq.saturating_sub(BOOT_CONSOLE_BUFFER_SIZE);
-            let start = self.replayed_seq.load(Ordering::Acquire).max(oldest);
-
-            for seq in start..write_seq {
-                let byte = unsafe { *self.bytes[seq % BOOT_CONSOLE_BUFFER_SIZE].get() };
-                console.write_bytes(&[byte]);
-            }
+    /// 把尚未重放的早期输出写到 `console`
+    #[cfg(feature = "device")]
+    fn replay_to(&self, console: &dyn crate::device::console::Console) {
+        let write_seq = self.write_seq.load(Ordering::Acquire);
+        let oldest = write_seq.saturating_sub(BOOT_CONSOLE_BUFFER_SIZE);
+        let start = self.replayed_seq.load(Ordering::Acquire).max(oldest);
 
-            self.replayed_seq.store(write_seq, Ordering::Release);
+        for seq in start..write_seq {
+            let byte = unsafe { *self.bytes[seq % BOOT_CONSOLE_BUFFER_SIZE].get() };
+            console.write_bytes(&[byte]);
         }
     }
+
+    /// 早期输出已经出现在运行时控制台上，标记为已重放
+    fn mark_replayed(&self) {
+        self.replayed_seq
+            .store(self.write_seq.load(Ordering::Acquire), Ordering::Release);
+    }
 }
 
 static BOOT_CONSOLE_BUFFER: BootConsoleBuffer = BootConsoleBuffer::new();
 
 /// 切换到运行时控制台（设备初始化完成后调用）
+///
+/// 早期输出重放到每个活动控制台；earlycon 所用的串口已经显示过这些输出，跳过。
 pub fn init() {
     CONSOLE_RUNTIME.store(true, Ordering::Release);
-    BOOT_CONSOLE_BUFFER.replay_to_runtime_console();
+    #[cfg(feature = "device")]
+    for active in crate::device::console::ACTIVE_CONSOLES.read().iter() {
+        let console = active.console.as_ref();
+        let is_earlycon =
+            console.phys_base().is_some() && console.phys_base() == earlycon::device();
+        if !is_earlycon {
+            BOOT_CONSOLE_BUFFER.replay_to(console);
+        }
+    }
+    BOOT_CONSOLE_BUFFER.mark_replayed();
 }
 
 pub fn is_runtime() -> bool {
@@ -74,17 +94,21 @@ pub fn is_runtime() -> bool {
 
 #[inline]
 fn write_str_unlocked(s: &str) {
+    write_filtered_unlocked(s, None);
+}
+
+/// 写到所有活动控制台；`filter` 为 `(日志级别, 全局控制台级别)` 时按各控制台阈值过滤
+fn write_filtered_unlocked(s: &str, filter: Option<(LogLevel, LogLevel)>) {
     #[cfg(feature = "device")]
-    if CONSOLE_RUNTIME.load(Ordering::Acquire)
-        && let Some(console) = crate::device::console::MAIN_CONSOLE.read().as_ref()
-    {
-        console.write_str(s);
+    if CONSOLE_RUNTIME.load(Ordering::Acquire) && crate::device::console::write_active(s, filter) {
         return;
     }
+    #[cfg(not(feature = "device"))]
+    let _ = filter;
 
     for b in s.bytes() {
         BOOT_CONSOLE_BUFFER.push(b);
-        crate::arch::ArchImpl::console_putchar(b);
+        earlycon::putchar(b);
     }
 }
 
@@ -97,14 +121,14 @@ fn putchar_unlocked(c: u8) {
         if let Some(console) = crate::device::console::MAIN_CONSOLE.read().as_ref() {
             console.write_bytes(&[c]);
         } else {
-            // 降级到 SBI
-            crate::arch::ArchImpl::console_putchar(c);
+            // 降级到 earlycon
+            earlycon::putchar(c);
         }
         return;
     }
-    // 早期或无 device 功能：使用 arch console
+    // 早期或无 device 功能：使用 earlycon
     BOOT_CONSOLE_BUFFER.push(c);
-    crate::arch::ArchImpl::console_putchar(c);
+    earlycon::putchar(c);
 }
 
 /// 无锁的单字符输入（内部使用）
@@ -143,6 +167,19 @@ pub fn putchar(c: u8) {
     with_console_lock_or_fallback(|| putchar_unlocked(c));
 }
 
+/// 等待运行时控制台有输入可读
+///
+/// 可能睡眠，必须在不持有控制台锁时调用；早期阶段直接返回，由 [`getchar`] 轮询。
+pub fn wait_for_input() {
+    #[cfg(feature = "device")]
+    if CONSOLE_RUNTIME.load(Ordering::Acquire) {
+        let console = crate::device::console::MAIN_CONSOLE.read().clone();
+        if let Some(console) = console {
+            console.wait_input();
+        }
+    }
+}
+
 /// 带锁的单字符输入（公开接口）
 pub fn getchar() -> Option<u8> {
     if let Some(_guard) = CONSOLE_LOCK.try_lock() {
@@ -177,6 +214,51 @@ impl Write for Stdout {
     }
 }
 
+/// 是否有控制台会立即输出 `level` 级别的日志
+///
+/// 运行时按各活动控制台的阈值判断（未单独设置的跟随 `default`），早期阶段只看 `default`。
+pub fn level_enabled(level: LogLevel, default: LogLevel) -> bool {
+    #[cfg(feature = "device")]
+    if CONSOLE_RUNTIME.load(Ordering::Acquire)
+        && let Some(enabled) = crate::device::console::any_accepts(level, default)
+    {
+        return enabled;
+    }
+    level as u8 <= default as u8
+}
+
+/// 按日志级别过滤的控制台输出（供日志系统使用）
+pub struct LevelStdout {
+    /// 本次输出的日志级别
+    pub level: LogLevel,
+    /// 全局控制台级别，作为未单独设置阈值的控制台的阈值
+    pub default: LogLevel,
+}
+
+impl Write for LevelStdout {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let filter = Some((self.level, self.default));
+        with_console_lock_or_fallback(|| write_filtered_unlocked(s, filter));
+        Ok(())
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments) -> fmt::Result {
+        // 整条日志在同一次加锁内格式化输出，锁被占用时与 write_str 一样降级为不加锁
+        let filter = Some((self.level, self.default));
+        let mut result = Ok(());
+        with_console_lock_or_fallback(|| result = FilteredWriter(filter).write_fmt(args));
+        result
+    }
+}
+
+struct FilteredWriter(Option<(LogLevel, LogLevel)>);
+impl Write for FilteredWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        write_filtered_unlocked(s, self.0);
+        Ok(())
+    }
+}
+
 // 创建一个临时的 writer，它使用 write_str_unlocked（不加锁）
 struct UnlockedWriter;
 impl Write for UnlockedWriter {
@@ -0,0 +1,713 @@
+//! 块设备请求队列与 I/O 调度器
+//!
+//! [`BlkQueue`] 包装一个块设备驱动，同一时刻只允许一个请求在设备上执行。
+//! 设备忙时到来的请求进入队列，发起请求的任务睡眠，直到调度器选中它为止。
+//! 调度器（deadline-lite 电梯）按以下顺序选择下一个请求：
+//!
+//! 1. 已超过期限的请求，期限最早的优先。读请求的期限远短于写请求，
+//!    因此同步读不会被大量流式写饿死；
+//! 2. 否则在 I/O 优先级最高的请求中，从上一个请求结束的位置开始按块号递增
+//!    （C-SCAN）选择，到末尾后回绕到最小块号，使一批请求按块号排序后下发。
+//!
+//! 任务的 I/O 优先级由 `ioprio_set` 设置，未设置时按调度策略推导：
+//! realtime 策略对应 RT 类，SCHED_IDLE 对应 IDLE 类，其余为 BE 类，级别均为 4。
+//!
+//! 请求开始执行时，队列中紧接其后、方向相同的请求会被合并进来，与它一起作为一个
+//! 多段（scatter-gather）请求下发，段数不超过驱动的 `max_segments()`，
+//! 合并后的总长度不超过 `max_sectors_kb`（可通过 `/sys/block/<dev>/queue/max_sectors_kb` 调整）。
+//! 本身超过该长度的请求不拆分，原样下发。
+//! 被合并请求的任务不再单独占用设备，由执行者完成后直接把结果交给它们。
+//!
+//! 调度效果由 kstat 指标体现：`blk_dispatched`（下发的请求数）、`blk_queued`
+//! （因设备忙而排队的请求数）、`blk_reordered`（没有按到达顺序下发的请求数）、
+//! `blk_deadline_expired`（因超期而被优先下发的请求数）、`blk_contiguous`
+//! （紧接上一个同方向请求的请求数）和 `blk_merged`（被合并进其他请求的请求数）。
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use super::super::{DeviceType, Driver};
+use super::{BlockDriver, DEFAULT_MAX_SECTORS_KB, QueueStats};
+use crate::kernel::kstat::{self, Counter, Unit};
+use crate::kernel::{TaskStruct, try_current_task};
+use crate::sync::{Completion, SpinLock};
+use crate::uapi::ioprio::{
+    IOPRIO_CLASS_BE, IOPRIO_CLASS_IDLE, IOPRIO_CLASS_NONE, IOPRIO_CLASS_RT, ioprio_prio_class,
+    ioprio_prio_level, ioprio_prio_value,
+};
+use crate::uapi::sched::{SCHED_FIFO, SCHED_IDLE, SCHED_RR};
+
+/// 读请求的期限（毫秒），与 Linux deadline 调度器的默认值相同
+pub const READ_EXPIRE_MS: usize = 500;
+
+/// 写请求的期限（毫秒）
+pub const WRITE_EXPIRE_MS: usize = 5000;
+
+/// 未设置 I/O 优先级的任务使用的类别内级别（nice 0 对应的级别）
+pub const IOPRIO_DEFAULT_LEVEL: u16 = 4;
+
+/// 下发到设备的请求数
+static BLK_DISPATCHED: Counter = Counter::new();
+/// 因设备忙而排队的请求数
+static BLK_QUEUED: Counter = Counter::new();
+/// 没有按到达顺序下发的请求数
+static BLK_REORDERED: Counter = Counter::new();
+/// 因超期而被优先下发的请求数
+static BLK_DEADLINE_EXPIRED: Counter = Counter::new();
+/// 紧接上一个同方向请求的请求数
+static BLK_CONTIGUOUS: Counter = Counter::new();
+/// 被合并进其他请求的请求数
+static BLK_MERGED: Counter = Counter::new();
+
+/// 登记块设备调度相关的统计指标
+pub fn register_iosched_metrics() {
+    kstat::register_counter("blk_dispatched", Unit::Count, &BLK_DISPATCHED);
+    kstat::register_counter("blk_queued", Unit::Count, &BLK_QUEUED);
+    kstat::register_counter("blk_reordered", Unit::Count, &BLK_REORDERED);
+    kstat::register_counter("blk_deadline_expired", Unit::Count, &BLK_DEADLINE_EXPIRED);
+    kstat::register_counter("blk_contiguous", Unit::Count, &BLK_CONTIGUOUS);
+    kstat::register_counter("blk_merged", Unit::Count, &BLK_MERGED);
+}
+
+/// 请求方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoDirection {
+    Read,
+    Write,
+}
+
+impl IoDirection {
+    fn expire_ms(self) -> usize {
+        match self {
+            Self::Read => READ_EXPIRE_MS,
+            Self::Write => WRITE_EXPIRE_MS,
+        }
+    }
+}
+
+/// 任务的有效 I/O 优先级
+pub fn task_io_priority(task: &TaskStruct) -> u16 {
+    if ioprio_prio_class(task.io_priority) != IOPRIO_CLASS_NONE {
+        return task.io_priority;
+    }
+    let class = match task.sched_policy {
+        SCHED_FIFO | SCHED_RR => IOPRIO_CLASS_RT,
+        SCHED_IDLE => IOPRIO_CLASS_IDLE,
+        _ => IOPRIO_CLASS_BE,
+    };
+    ioprio_prio_value(class, IOPRIO_DEFAULT_LEVEL)
+}
+
+/// 优先级的排序键，越小越先服务
+fn prio_rank(ioprio: u16) -> (u16, u16) {
+    let class = match ioprio_prio_class(ioprio) {
+        IOPRIO_CLASS_RT => 0,
+        IOPRIO_CLASS_IDLE => 2,
+        _ => 1,
+    };
+    (class, ioprio_prio_level(ioprio))
+}
+
+/// 请求的数据缓冲区
+///
+/// 发起请求的任务在请求完成前一直阻塞在 [`BlkQueue::submit`] 中，
+/// 因此缓冲区在请求排队和执行期间始终有效，可以交给合并它的任务访问。
+#[derive(Clone, Copy)]
+struct RawBuf {
+    ptr: *mut u8,
+    len: usize,
+}
+
+// SAFETY: 缓冲区同一时刻只被执行请求的任务访问，见 `RawBuf` 的说明
+unsafe impl Send for RawBuf {}
+
+impl RawBuf {
+    const fn empty() -> Self {
+        Self {
+            ptr: core::ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn from_mut(buf: &mut [u8]) -> Self {
+        Self {
+            ptr: buf.as_mut_ptr(),
+            len: buf.len(),
+        }
+    }
+
+    fn from_ref(buf: &[u8]) -> Self {
+        Self {
+            ptr: buf.as_ptr() as *mut u8,
+            len: buf.len(),
+        }
+    }
+}
+
+/// 一个块请求；`nr_blocks` 为 0 表示刷新
+#[derive(Clone, Copy)]
+struct BlkRequest {
+    dir: IoDirection,
+    block: usize,
+    nr_blocks: usize,
+    buf: RawBuf,
+}
+
+impl BlkRequest {
+    fn end(&self) -> usize {
+        self.block.saturating_add(self.nr_blocks)
+    }
+
+    /// 能否作为多段请求中的一段：缓冲区恰好覆盖请求的块
+    fn segmentable(&self, block_size: usize) -> bool {
+        self.nr_blocks > 0 && self.buf.len == self.nr_blocks * block_size
+    }
+}
+
+/// 排队中的请求
+struct PendingRequest {
+    /// 到达序号，越小越早
+    ticket: u64,
+    req: BlkRequest,
+    ioprio: u16,
+    /// 期限（启动以来的毫秒数）
+    deadline: usize,
+    /// 请求被选中或被合并执行完毕时完成
+    done: Arc<Completion>,
+}
+
+/// deadline-lite 电梯
+pub struct Elevator {
+    pending: Vec<PendingRequest>,
+    next_ticket: u64,
+    /// 上一个下发的请求结束的块号
+    head: usize,
+    /// 上一个下发的请求的方向
+    last_dir: Option<IoDirection>,
+}
+
+impl Elevator {
+    pub const fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            next_ticket: 0,
+            head: 0,
+            last_dir: None,
+        }
+    }
+
+    /// 排队中的请求数
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 请求加入队列，返回其到达序号
+    fn add(&mut self, req: BlkRequest, ioprio: u16, now_ms: usize, done: Arc<Completion>) -> u64 {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        self.pending.push(PendingRequest {
+            ticket,
+            req,
+            ioprio,
+            deadline: now_ms.saturating_add(req.dir.expire_ms()),
+            done,
+        });
+        ticket
+    }
+
+    /// 记录一个请求开始在设备上执行
+    fn start(&mut self, dir: IoDirection, block: usize, nr_blocks: usize) {
+        BLK_DISPATCHED.inc();
+        if self.last_dir == Some(dir) && block == self.head {
+            BLK_CONTIGUOUS.inc();
+        }
+        self.head = block.saturating_add(nr_blocks);
+        self.last_dir = Some(dir);
+    }
+
+    /// 选出下一个要下发的请求
+    fn pick(&mut self, now_ms: usize) -> Option<PendingRequest> {
+        let oldest = self.pending.iter().map(|r| r.ticket).min()?;
+
+        let expired = self
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.deadline <= now_ms)
+            .min_by_key(|(_, r)| (r.deadline, r.ticket))
+            .map(|(idx, _)| idx);
+
+        let idx = match expired {
+            Some(idx) => {
+                BLK_DEADLINE_EXPIRED.inc();
+                idx
+            }
+            None => {
+                let best = self.pending.iter().map(|r| prio_rank(r.ioprio)).min()?;
+                let candidates = || {
+                    self.pending
+                        .iter()
+                        .enumerate()
+                        .filter(move |(_, r)| prio_rank(r.ioprio) == best)
+                };
+                candidates()
+                    .filter(|(_, r)| r.req.block >= self.head)
+                    .min_by_key(|(_, r)| (r.req.block, r.ticket))
+                    .or_else(|| candidates().min_by_key(|(_, r)| (r.req.block, r.ticket)))
+                    .map(|(idx, _)| idx)?
+            }
+        };
+
+        let req = self.pending.swap_remove(idx);
+        if req.ticket != oldest {
+            BLK_REORDERED.inc();
+        }
+        Some(req)
+    }
+
+    /// 取出从 `end` 开始首尾相接、方向为 `dir` 的排队请求，最多 `max` 个，
+    /// 总长度不超过 `max_bytes`
+    fn take_mergeable(
+        &mut self,
+        dir: IoDirection,
+        mut end: usize,
+        max: usize,
+        mut max_bytes: usize,
+        block_size: usize,
+    ) -> Vec<PendingRequest> {
+        let mut merged = Vec::new();
+        while merged.len() < max {
+            let Some(idx) = self.pending.iter().position(|r| {
+                r.req.dir == dir
+                    && r.req.block == end
+                    && r.req.segmentable(block_size)
+                    && r.req.buf.len <= max_bytes
+            }) else {
+                break;
+            };
+            let next = self.pending.swap_remove(idx);
+            BLK_MERGED.inc();
+            end = next.req.end();
+            max_bytes -= next.req.buf.len;
+            merged.push(next);
+        }
+        if !merged.is_empty() {
+            self.head = end;
+        }
+        merged
+    }
+}
+
+impl Default for Elevator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct QueueState {
+    elevator: Elevator,
+    /// 是否有请求正在设备上执行
+    busy: bool,
+    /// 已被选中、等待其任务醒来执行的请求
+    granted: Option<u64>,
+    /// 被合并执行完毕的请求及其结果，等待其任务醒来取走
+    completed: BTreeMap<u64, bool>,
+}
+
+/// 带 I/O 调度的块设备请求队列
+pub struct BlkQueue {
+    dev: Arc<dyn BlockDriver>,
+    state: SpinLock<QueueState>,
+    /// 被合并进其他请求的请求数
+    merged: AtomicU64,
+    /// 合并后单个请求的长度上限（KiB）
+    max_sectors_kb: AtomicUsize,
+}
+
+impl BlkQueue {
+    /// 为块设备创建请求队列
+    pub fn new(dev: Arc<dyn BlockDriver>) -> Arc<Self> {
+        Arc::new(Self {
+            dev,
+            state: SpinLock::new(QueueState {
+                elevator: Elevator::new(),
+                busy: false,
+                granted: None,
+                completed: BTreeMap::new(),
+            }),
+            merged: AtomicU64::new(0),
+            max_sectors_kb: AtomicUsize::new(DEFAULT_MAX_SECTORS_KB),
+        })
+    }
+
+    /// 经过调度后在设备上执行一个请求
+    fn submit(&self, req: BlkRequest) -> bool {
+        if let Some(result) = self.acquire(req) {
+            return result;
+        }
+        let merged = self.take_merged(&req);
+        let result = self.execute(&req, &merged);
+        self.release(merged, result);
+        result
+    }
+
+    /// 等待设备空闲并轮到该请求
+    ///
+    /// 请求被合并进其他请求执行完毕时返回其结果。
+    fn acquire(&self, req: BlkRequest) -> Option<bool> {
+        let task = try_current_task();
+        let ioprio = match &task {
+            Some(t) => task_io_priority(&t.lock()),
+            None => ioprio_prio_value(IOPRIO_CLASS_BE, IOPRIO_DEFAULT_LEVEL),
+        };
+
+        let done = Arc::new(Completion::new());
+        let ticket = {
+            let mut st = self.state.lock();
+            if !st.busy {
+                st.busy = true;
+                st.elevator.start(req.dir, req.block, req.nr_blocks);
+                return None;
+            }
+            BLK_QUEUED.inc();
+            let now = crate::arch::get_time_ms();
+            st.elevator.add(req, ioprio, now, done.clone())
+        };
+
+        done.wait_for_completion();
+        let mut st = self.state.lock();
+        if let Some(result) = st.completed.remove(&ticket) {
+            return Some(result);
+        }
+        // 设备在该请求执行完之前一直忙，其它请求不会覆盖 granted
+        debug_assert_eq!(st.granted, Some(ticket));
+        st.granted = None;
+        None
+    }
+
+    /// 取出可以合并到 `req` 之后一起执行的排队请求
+    fn take_merged(&self, req: &BlkRequest) -> Vec<PendingRequest> {
+        let block_size = self.dev.block_size();
+        let max_segments = self.dev.max_segments();
+        if max_segments <= 1 || !req.segmentable(block_size) {
+            return Vec::new();
+        }
+        let max_bytes = self.max_sectors_kb.load(Ordering::Relaxed) * 1024;
+        let merged = self.state.lock().elevator.take_mergeable(
+            req.dir,
+            req.end(),
+            max_segments - 1,
+            max_bytes.saturating_sub(req.buf.len),
+            block_size,
+        );
+        self.merged
+            .fetch_add(merged.len() as u64, Ordering::Relaxed);
+        merged
+    }
+
+    /// 在设备上执行请求及合并进来的请求
+    fn execute(&self, req: &BlkRequest, merged: &[PendingRequest]) -> bool {
+        if req.nr_blocks == 0 {
+            return self.dev.flush();
+        }
+        let bufs = core::iter::once(req.buf).chain(merged.iter().map(|r| r.req.buf));
+        // SAFETY: 各缓冲区的所有者都阻塞在 `submit` 中等待结果，互不重叠，见 `RawBuf`
+        match req.dir {
+            IoDirection::Read => {
+                let mut segs: Vec<&mut [u8]> = bufs
+                    .map(|b| unsafe { core::slice::from_raw_parts_mut(b.ptr, b.len) })
+                    .collect();
+                if let [seg] = segs.as_mut_slice() {
+                    return self.dev.read_blocks(req.block, seg);
+                }
+                self.dev.read_segments(req.block, &mut segs)
+            }
+            IoDirection::Write => {
+                let segs: Vec<&[u8]> = bufs
+                    .map(|b| unsafe { core::slice::from_raw_parts(b.ptr as *const u8, b.len) })
+                    .collect();
+                if let [seg] = segs.as_slice() {
+                    return self.dev.write_blocks(req.block, seg);
+                }
+                self.dev.write_segments(req.block, &segs)
+            }
+        }
+    }
+
+    /// 请求完成：交付被合并请求的结果，选出下一个请求并唤醒相关任务
+    fn release(&self, merged: Vec<PendingRequest>, result: bool) {
+        let mut finished = Vec::new();
+        {
+            let mut st = self.state.lock();
+            for r in merged {
+                st.completed.insert(r.ticket, result);
+                finished.push(r.done);
+            }
+            match st.elevator.pick(crate::arch::get_time_ms()) {
+                Some(next) => {
+                    st.elevator
+                        .start(next.req.dir, next.req.block, next.req.nr_blocks);
+                    st.granted = Some(next.ticket);
+                    finished.push(next.done);
+                }
+                None => st.busy = false,
+            }
+        }
+        for done in finished {
+            done.complete();
+        }
+    }
+
+    fn nr_blocks(&self, len: usize) -> usize {
+        len / self.dev.block_size().max(1)
+    }
+}
+
+impl Driver for BlkQueue {
+    fn try_handle_interrupt(&self, irq: Option<usize>) -> bool {
+        self.dev.try_handle_interrupt(irq)
+    }
+
+    fn device_type(&self) -> DeviceType {
+        self.dev.device_type()
+    }
+
+    fn get_id(&self) -> String {
+        self.dev.get_id()
+    }
+
+    fn as_block(&self) -> Option<&dyn BlockDriver> {
+        Some(self)
+    }
+
+    fn as_block_arc(self: Arc<Self>) -> Option<Arc<dyn BlockDriver>> {
+        Some(self)
+    }
+
+    fn suspend(&self) {
+        self.dev.suspend();
+    }
+
+    fn resume(&self) {
+        self.dev.resume();
+    }
+}
+
+impl BlockDriver for BlkQueue {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) -> bool {
+        self.submit(BlkRequest {
+            dir: IoDirection::Read,
+            block: block_id,
+            nr_blocks: 1,
+            buf: RawBuf::from_mut(buf),
+        })
+    }
+
+    fn read_blocks(&self, start_block: usize, buf: &mut [u8]) -> bool {
+        self.submit(BlkRequest {
+            dir: IoDirection::Read,
+            block: start_block,
+            nr_blocks: self.nr_blocks(buf.len()),
+            buf: RawBuf::from_mut(buf),
+        })
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) -> bool {
+        self.submit(BlkRequest {
+            dir: IoDirection::Write,
+            block: block_id,
+            nr_blocks: 1,
+            buf: RawBuf::from_ref(buf),
+        })
+    }
+
+    fn write_blocks(&self, start_block: usize, buf: &[u8]) -> bool {
+        self.submit(BlkRequest {
+            dir: IoDirection::Write,
+            block: start_block,
+            nr_blocks: self.nr_blocks(buf.len()),
+            buf: RawBuf::from_ref(buf),
+        })
+    }
+
+    fn max_segments(&self) -> usize {
+        self.dev.max_segments()
+    }
+
+    fn queue_stats(&self) -> QueueStats {
+        QueueStats {
+            merged: self.merged.load(Ordering::Relaxed),
+            ..self.dev.queue_stats()
+        }
+    }
+
+    fn max_sectors_kb(&self) -> usize {
+        self.max_sectors_kb.load(Ordering::Relaxed)
+    }
+
+    fn set_max_sectors_kb(&self, kb: usize) -> bool {
+        self.max_sectors_kb.store(kb, Ordering::Relaxed);
+        true
+    }
+
+    fn flush(&self) -> bool {
+        // 刷新没有位置，排在当前位置上，按写请求的期限保证不被饿死
+        let head = self.state.lock().elevator.head;
+        self.submit(BlkRequest {
+            dir: IoDirection::Write,
+            block: head,
+            nr_blocks: 0,
+            buf: RawBuf::empty(),
+        })
+    }
+
+    fn block_size(&self) -> usize {
+        self.dev.block_size()
+    }
+
+    fn total_blocks(&self) -> usize {
+        self.dev.total_blocks()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    fn done() -> Arc<Completion> {
+        Arc::new(Completion::new())
+    }
+
+    fn be(level: u16) -> u16 {
+        ioprio_prio_value(IOPRIO_CLASS_BE, level)
+    }
+
+    fn read(block: usize, nr_blocks: usize) -> BlkRequest {
+        BlkRequest {
+            dir: IoDirection::Read,
+            block,
+            nr_blocks,
+            buf: RawBuf::empty(),
+        }
+    }
+
+    fn write(block: usize, nr_blocks: usize) -> BlkRequest {
+        BlkRequest {
+            dir: IoDirection::Write,
+            ..read(block, nr_blocks)
+        }
+    }
+
+    fn pick_block(e: &mut Elevator, now: usize) -> usize {
+        let r = e.pick(now).unwrap();
+        e.start(r.req.dir, r.req.block, r.req.nr_blocks);
+        r.req.block
+    }
+
+    // 同优先级按块号从磁头位置向上扫描，到末尾后回绕
+    test_case!(test_elevator_cscan, {
+        let mut e = Elevator::new();
+        e.start(IoDirection::Read, 40, 10);
+        for block in [70, 10, 55, 30] {
+            e.add(read(block, 1), be(4), 0, done());
+        }
+        kassert!(pick_block(&mut e, 0) == 55);
+        kassert!(pick_block(&mut e, 0) == 70);
+        kassert!(pick_block(&mut e, 0) == 10);
+        kassert!(pick_block(&mut e, 0) == 30);
+        kassert!(e.pick(0).is_none());
+    });
+
+    // 超期请求优先，读的期限短于写
+    test_case!(test_elevator_deadline, {
+        let mut e = Elevator::new();
+        e.add(write(100, 8), be(4), 0, done());
+        e.add(read(900, 1), be(4), 0, done());
+        e.add(write(200, 8), be(4), 0, done());
+        kassert!(pick_block(&mut e, READ_EXPIRE_MS) == 900);
+        kassert!(pick_block(&mut e, READ_EXPIRE_MS) == 100);
+        kassert!(e.len() == 1);
+    });
+
+    // 优先级高的类别先服务，未设置时按调度策略推导
+    test_case!(test_elevator_ioprio, {
+        let mut e = Elevator::new();
+        e.add(
+            read(1, 1),
+            ioprio_prio_value(IOPRIO_CLASS_IDLE, 0),
+            0,
+            done(),
+        );
+        e.add(read(2, 1), be(7), 0, done());
+        e.add(read(3, 1), be(0), 0, done());
+        e.add(read(4, 1), ioprio_prio_value(IOPRIO_CLASS_RT, 7), 0, done());
+        kassert!(pick_block(&mut e, 0) == 4);
+        kassert!(pick_block(&mut e, 0) == 3);
+        kassert!(pick_block(&mut e, 0) == 2);
+        kassert!(pick_block(&mut e, 0) == 1);
+
+        let mut task = TaskStruct::new_dummy_task(1);
+        kassert!(task_io_priority(&task) == be(IOPRIO_DEFAULT_LEVEL));
+        task.sched_policy = SCHED_IDLE;
+        kassert!(ioprio_prio_class(task_io_priority(&task)) == IOPRIO_CLASS_IDLE);
+        task.io_priority = be(1);
+        kassert!(task_io_priority(&task) == be(1));
+    });
+
+    // 合并首尾相接、方向相同且缓冲区完整的请求，并推进磁头位置
+    test_case!(test_elevator_merge, {
+        let mut buf = [0u8; 4 * 512];
+        let (a, rest) = buf.split_at_mut(512);
+        let (b, rest) = rest.split_at_mut(2 * 512);
+        let mut e = Elevator::new();
+        e.add(
+            BlkRequest {
+                buf: RawBuf::from_mut(b),
+                ..read(11, 2)
+            },
+            be(4),
+            0,
+            done(),
+        );
+        e.add(
+            BlkRequest {
+                buf: RawBuf::from_mut(a),
+                ..read(10, 1)
+            },
+            be(4),
+            0,
+            done(),
+        );
+        e.add(
+            BlkRequest {
+                buf: RawBuf::from_mut(rest),
+                ..write(13, 1)
+            },
+            be(4),
+            0,
+            done(),
+        );
+        // 缓冲区为空的请求不能作为段
+        e.add(read(13, 1), be(4), 0, done());
+
+        let merged = e.take_mergeable(IoDirection::Read, 11, 4, usize::MAX, 512);
+        kassert!(merged.len() == 1);
+        kassert!(merged[0].req.block == 11);
+        kassert!(e.head == 13);
+        kassert!(
+            e.take_mergeable(IoDirection::Write, 13, 0, usize::MAX, 512)
+                .is_empty()
+        );
+        // 合并后总长度不能超过 max_sectors_kb
+        kassert!(
+            e.take_mergeable(IoDirection::Write, 13, 4, 511, 512)
+                .is_empty()
+        );
+        kassert!(e.take_mergeable(IoDirection::Write, 13, 4, 512, 512).len() == 1);
+        kassert!(e.len() == 2);
+    });
+}
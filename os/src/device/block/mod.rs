@@ -1,13 +1,31 @@
 //! 块设备模块
 //!
-//! 包含块设备相关的驱动接口和实现
+//! 包含块设备相关的驱动接口和实现。物理块设备登记到 `BLK_DRIVERS` 前
+//! 包装为 [`iosched::BlkQueue`]，所有请求经过 I/O 调度器下发。
 
 use super::Driver;
 
+pub mod iosched;
 pub mod partition;
 pub mod ram_disk;
+pub mod verity;
 pub mod virtio_blk;
 
+/// 单个请求长度上限的默认值（KiB），与 Linux 相同
+pub const DEFAULT_MAX_SECTORS_KB: usize = 1280;
+
+/// 单个请求长度上限可设置的最大值（KiB）
+pub const MAX_HW_SECTORS_KB: usize = 32767;
+
+/// 块设备请求队列统计
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueueStats {
+    /// 提交给设备的请求数（virtio 上每个请求对应一次 vring 通知）
+    pub vring_kicks: u64,
+    /// 被合并进相邻请求、没有单独提交给设备的请求数
+    pub merged: u64,
+}
+
 /// 块设备驱动程序接口
 pub trait BlockDriver: Driver {
     /// 读取块设备数据
@@ -60,6 +78,63 @@ pub trait BlockDriver: Driver {
         true
     }
 
+    /// 单个设备请求最多包含的段数，1 表示不合并请求
+    fn max_segments(&self) -> usize {
+        1
+    }
+
+    /// 把从 `start_block` 开始的连续块依次读入多个缓冲区（scatter）。
+    ///
+    /// 每段长度都必须是块大小的整数倍。默认实现对每段分别调用 `read_blocks`，
+    /// 支持 scatter-gather 的驱动可以覆盖为一次设备请求。
+    fn read_segments(&self, start_block: usize, segs: &mut [&mut [u8]]) -> bool {
+        let block_size = self.block_size();
+        if block_size == 0 {
+            return false;
+        }
+        let mut block = start_block;
+        for seg in segs.iter_mut() {
+            if !self.read_blocks(block, seg) {
+                return false;
+            }
+            block += seg.len() / block_size;
+        }
+        true
+    }
+
+    /// 把多个缓冲区依次写入从 `start_block` 开始的连续块（gather）。
+    ///
+    /// 默认实现对每段分别调用 `write_blocks`。
+    fn write_segments(&self, start_block: usize, segs: &[&[u8]]) -> bool {
+        let block_size = self.block_size();
+        if block_size == 0 {
+            return false;
+        }
+        let mut block = start_block;
+        for seg in segs {
+            if !self.write_blocks(block, seg) {
+                return false;
+            }
+            block += seg.len() / block_size;
+        }
+        true
+    }
+
+    /// 请求队列统计，见 `/sys/block/<dev>/queue`
+    fn queue_stats(&self) -> QueueStats {
+        QueueStats::default()
+    }
+
+    /// 合并后单个设备请求的长度上限（KiB），见 `/sys/block/<dev>/queue/max_sectors_kb`
+    fn max_sectors_kb(&self) -> usize {
+        DEFAULT_MAX_SECTORS_KB
+    }
+
+    /// 设置合并后单个设备请求的长度上限，不支持调整时返回 false
+    fn set_max_sectors_kb(&self, _kb: usize) -> bool {
+        false
+    }
+
     /// 刷新到磁盘
     /// # 返回值：
     /// 如果刷新成功则返回 true，否则返回 false
@@ -1,6 +1,6 @@
 //! Partition block-device wrapper and simple MBR discovery.
 
-use super::BlockDriver;
+use super::{BlockDriver, QueueStats};
 use crate::device::{DeviceType, Driver};
 use alloc::string::String;
 use alloc::sync::Arc;
@@ -150,6 +150,22 @@ impl BlockDriver for PartitionBlockDevice {
         self.inner.write_blocks(inner_block, buf)
     }
 
+    fn max_segments(&self) -> usize {
+        self.inner.max_segments()
+    }
+
+    fn queue_stats(&self) -> QueueStats {
+        self.inner.queue_stats()
+    }
+
+    fn max_sectors_kb(&self) -> usize {
+        self.inner.max_sectors_kb()
+    }
+
+    fn set_max_sectors_kb(&self, kb: usize) -> bool {
+        self.inner.set_max_sectors_kb(kb)
+    }
+
     fn flush(&self) -> bool {
         self.inner.flush()
     }
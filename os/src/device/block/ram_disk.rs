@@ -1,14 +1,18 @@
 use super::super::{DeviceType, Driver};
 use super::BlockDriver;
 use crate::sync::SpinLock;
+use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
 
 /// 内存模拟的块设备
 ///
-/// 用于测试和开发
+/// 用于测试和开发。可以模拟带易失写缓存的磁盘（[`RamDisk::enable_write_cache`]、
+/// [`RamDisk::crash`]）和读写失败（[`RamDisk::set_fail_reads`]、
+/// [`RamDisk::set_fail_writes`]），用于崩溃一致性和错误处理测试。
 pub struct RamDisk {
     /// 存储数据
     data: SpinLock<Vec<u8>>,
@@ -18,16 +22,21 @@ pub struct RamDisk {
 
     /// 设备 ID
     device_id: usize,
+
+    /// 模拟的易失写缓存：已写入但尚未 flush 的块；为 None 时直接写入 `data`
+    write_cache: SpinLock<Option<BTreeMap<usize, Vec<u8>>>>,
+
+    /// 读操作是否失败
+    fail_reads: AtomicBool,
+
+    /// 写操作是否失败
+    fail_writes: AtomicBool,
 }
 
 impl RamDisk {
     /// 创建指定大小的内存磁盘
     pub fn new(size: usize, block_size: usize, device_id: usize) -> Arc<Self> {
-        Arc::new(Self {
-            data: SpinLock::new(vec![0u8; size]),
-            block_size,
-            device_id,
-        })
+        Self::from_bytes(vec![0u8; size], block_size, device_id)
     }
 
     /// 从字节数组创建
@@ -36,14 +45,44 @@ impl RamDisk {
             data: SpinLock::new(data),
             block_size,
             device_id,
+            write_cache: SpinLock::new(None),
+            fail_reads: AtomicBool::new(false),
+            fail_writes: AtomicBool::new(false),
         })
     }
 
     /// 获取原始数据（用于调试）
+    ///
+    /// 不包含写缓存中尚未 flush 的块，即掉电后仍然存在的内容。
     pub fn raw_data(&self) -> Vec<u8> {
         self.data.lock().clone()
     }
 
+    /// 启用易失写缓存：之后的写入在 flush 之前只保存在缓存中
+    pub fn enable_write_cache(&self) {
+        let mut cache = self.write_cache.lock();
+        if cache.is_none() {
+            *cache = Some(BTreeMap::new());
+        }
+    }
+
+    /// 模拟掉电：丢弃所有尚未 flush 的写入
+    pub fn crash(&self) {
+        if let Some(pending) = self.write_cache.lock().as_mut() {
+            pending.clear();
+        }
+    }
+
+    /// 设置读操作是否失败
+    pub fn set_fail_reads(&self, fail: bool) {
+        self.fail_reads.store(fail, Ordering::Relaxed);
+    }
+
+    /// 设置写操作是否失败
+    pub fn set_fail_writes(&self, fail: bool) {
+        self.fail_writes.store(fail, Ordering::Relaxed);
+    }
+
     /// 获取设备 ID
     pub fn device_id(&self) -> usize {
         self.device_id
@@ -71,10 +110,17 @@ impl Driver for RamDisk {
 // 实现 BlockDriver trait
 impl BlockDriver for RamDisk {
     fn read_block(&self, block_id: usize, buf: &mut [u8]) -> bool {
-        if buf.len() != self.block_size {
+        if buf.len() != self.block_size || self.fail_reads.load(Ordering::Relaxed) {
             return false;
         }
 
+        if let Some(pending) = self.write_cache.lock().as_ref()
+            && let Some(block) = pending.get(&block_id)
+        {
+            buf.copy_from_slice(block);
+            return true;
+        }
+
         let data = self.data.lock();
         let offset = block_id * self.block_size;
 
@@ -87,23 +133,40 @@ impl BlockDriver for RamDisk {
     }
 
     fn write_block(&self, block_id: usize, buf: &[u8]) -> bool {
-        if buf.len() != self.block_size {
+        if buf.len() != self.block_size || self.fail_writes.load(Ordering::Relaxed) {
             return false;
         }
 
-        let mut data = self.data.lock();
         let offset = block_id * self.block_size;
-
-        if offset + self.block_size > data.len() {
+        if offset + self.block_size > self.data.lock().len() {
             return false;
         }
 
-        data[offset..offset + self.block_size].copy_from_slice(buf);
+        if let Some(pending) = self.write_cache.lock().as_mut() {
+            pending.insert(block_id, buf.to_vec());
+            return true;
+        }
+
+        self.data.lock()[offset..offset + self.block_size].copy_from_slice(buf);
         true
     }
 
     fn flush(&self) -> bool {
-        true // 内存设备无需 flush
+        if self.fail_writes.load(Ordering::Relaxed) {
+            return false;
+        }
+
+        // 持有缓存锁直到写入完成，避免并发读看到提交了一半的状态
+        let mut cache = self.write_cache.lock();
+        let Some(pending) = cache.as_mut() else {
+            return true;
+        };
+        let mut data = self.data.lock();
+        for (block_id, block) in core::mem::take(pending) {
+            let offset = block_id * self.block_size;
+            data[offset..offset + self.block_size].copy_from_slice(&block);
+        }
+        true
     }
 
     fn block_size(&self) -> usize {
@@ -0,0 +1,481 @@
+//! 只读完整性校验层（简化版 dm-verity）
+//!
+//! 启动参数 `verity=<设备名>[,<根哈希>]` 为指定块设备（如 `vda1`）套上校验层：
+//! 每次读取都按数据块计算 SHA-256，并沿哈希树逐级校验到根哈希，
+//! 不一致时读取失败（上层得到 EIO）并记录坏块号。写入一律拒绝。
+//!
+//! 哈希树由 `scripts/append_verity_tree.py` 追加在文件系统镜像之后：
+//!
+//! ```text
+//! | 数据块 0..N | 哈希块（顶层在前，叶子层在后） | 超级块（最后 512 字节） |
+//! ```
+//!
+//! 叶子层依次存放各数据块的摘要，上一层存放下一层各哈希块的摘要，顶层只有一个块，
+//! 其摘要即根哈希。命令行没有给出根哈希时使用超级块中记录的值，此时只能发现
+//! 意外损坏，不能防篡改。已校验过的哈希块内容缓存在内存中，之后直接使用缓存，
+//! 不再从设备重读，因此校验之后对底层设备的改动不会绕过校验。
+
+use super::{BlockDriver, QueueStats};
+use crate::device::{CMDLINE, DeviceType, Driver};
+use crate::sync::SpinLock;
+use crate::util::sha256::{DIGEST_LEN, sha256};
+use crate::{pr_err, pr_info};
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// 超级块魔数
+pub const VERITY_MAGIC: &[u8; 8] = b"CVERITY1";
+/// 超级块格式版本
+pub const VERITY_VERSION: u32 = 1;
+/// 超级块大小，位于设备最后
+pub const VERITY_SUPERBLOCK_SIZE: usize = 512;
+
+lazy_static::lazy_static! {
+    /// 已打开的校验设备，按设备名缓存，保留已校验哈希块的记录
+    static ref VERITY_DEVICES: SpinLock<BTreeMap<String, Arc<VerityBlockDevice>>> =
+        SpinLock::new(BTreeMap::new());
+}
+
+/// `verity=` 启动参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerityParam {
+    /// 受保护的块设备名
+    pub device: String,
+    /// 可信根哈希；`None` 时使用超级块中记录的值
+    pub root_hash: Option<[u8; DIGEST_LEN]>,
+}
+
+/// 解析 `verity=<设备名>[,<64 位十六进制根哈希>]`
+pub fn parse_param(cmdline: &str) -> Option<VerityParam> {
+    let value = cmdline
+        .split_ascii_whitespace()
+        .find_map(|arg| arg.strip_prefix("verity="))?;
+    let mut fields = value.split(',');
+    let device = fields.next()?;
+    let device = device.strip_prefix("/dev/").unwrap_or(device);
+    if device.is_empty() {
+        return None;
+    }
+    let root_hash = match fields.next() {
+        Some(hex) => Some(parse_digest(hex)?),
+        None => None,
+    };
+    Some(VerityParam {
+        device: device.to_string(),
+        root_hash,
+    })
+}
+
+fn parse_digest(hex: &str) -> Option<[u8; DIGEST_LEN]> {
+    if hex.len() != DIGEST_LEN * 2 {
+        return None;
+    }
+    let mut digest = [0u8; DIGEST_LEN];
+    for (byte, pair) in digest.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(core::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(digest)
+}
+
+/// 哈希树的一层
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashLevel {
+    /// 该层第一个哈希块在哈希区中的序号
+    pub start: usize,
+    /// 该层哈希块数
+    pub count: usize,
+}
+
+/// 计算哈希树布局，`levels[0]` 为叶子层
+///
+/// 各层在哈希区中顶层在前。返回 `(各层, 哈希块总数)`。
+pub fn tree_layout(data_blocks: usize, block_size: usize) -> (Vec<HashLevel>, usize) {
+    let per_block = block_size / DIGEST_LEN;
+    let mut counts = Vec::new();
+    let mut count = data_blocks;
+    loop {
+        count = count.div_ceil(per_block);
+        counts.push(count);
+        if count <= 1 {
+            break;
+        }
+    }
+
+    let total = counts.iter().sum();
+    let mut start = total;
+    let levels = counts
+        .iter()
+        .map(|&count| {
+            start -= count;
+            HashLevel { start, count }
+        })
+        .collect();
+    (levels, total)
+}
+
+/// 带哈希树校验的只读块设备
+pub struct VerityBlockDevice {
+    inner: Arc<dyn BlockDriver>,
+    name: String,
+    /// 校验块大小（字节），是底层块大小的整数倍
+    block_size: usize,
+    data_blocks: usize,
+    levels: Vec<HashLevel>,
+    root_hash: [u8; DIGEST_LEN],
+    /// 已通过校验的哈希块：序号 -> 内容
+    verified: SpinLock<BTreeMap<usize, Arc<[u8]>>>,
+}
+
+impl VerityBlockDevice {
+    /// 读取超级块并打开校验层
+    ///
+    /// 超级块缺失或与设备大小不符时返回 `None`。
+    pub fn open(
+        inner: Arc<dyn BlockDriver>,
+        name: String,
+        root_hash: Option<[u8; DIGEST_LEN]>,
+    ) -> Option<Arc<Self>> {
+        let sector = inner.block_size();
+        if sector == 0 || !VERITY_SUPERBLOCK_SIZE.is_multiple_of(sector) {
+            return None;
+        }
+        let device_bytes = inner.total_blocks().checked_mul(sector)?;
+        let sb_offset = device_bytes.checked_sub(VERITY_SUPERBLOCK_SIZE)?;
+        let mut sb = [0u8; VERITY_SUPERBLOCK_SIZE];
+        if !inner.read_blocks(sb_offset / sector, &mut sb) {
+            return None;
+        }
+
+        if &sb[0..8] != VERITY_MAGIC || le_u32(&sb[8..12]) != VERITY_VERSION {
+            return None;
+        }
+        let block_size = le_u32(&sb[12..16]) as usize;
+        let data_blocks = usize::try_from(le_u64(&sb[16..24])).ok()?;
+        let hash_blocks = usize::try_from(le_u64(&sb[24..32])).ok()?;
+        if !block_size.is_power_of_two()
+            || block_size < sector.max(DIGEST_LEN * 2)
+            || data_blocks == 0
+        {
+            return None;
+        }
+        let (levels, expected_hash_blocks) = tree_layout(data_blocks, block_size);
+        let tree_end = data_blocks
+            .checked_add(hash_blocks)?
+            .checked_mul(block_size)?
+            .checked_add(VERITY_SUPERBLOCK_SIZE)?;
+        if hash_blocks != expected_hash_blocks || tree_end > device_bytes {
+            return None;
+        }
+
+        let mut stored_root = [0u8; DIGEST_LEN];
+        stored_root.copy_from_slice(&sb[32..32 + DIGEST_LEN]);
+        if root_hash.is_some_and(|hash| hash != stored_root) {
+            pr_err!("[Verity] {}: root hash does not match the hash tree", name);
+        }
+
+        Some(Arc::new(Self {
+            inner,
+            name,
+            block_size,
+            data_blocks,
+            levels,
+            root_hash: root_hash.unwrap_or(stored_root),
+            verified: SpinLock::new(BTreeMap::new()),
+        }))
+    }
+
+    /// 读取校验块 `block`（数据区和哈希区统一编号）
+    fn read_raw(&self, block: usize, buf: &mut [u8]) -> bool {
+        let sector = self.inner.block_size();
+        self.inner
+            .read_blocks(block * (self.block_size / sector), buf)
+    }
+
+    /// 读取并校验第 `level` 层的第 `index` 个哈希块
+    ///
+    /// 已校验过的块直接返回缓存的内容。
+    fn verified_hash_block(&self, level: usize, index: usize) -> Option<Arc<[u8]>> {
+        let hash_block = self.levels[level].start + index;
+        if let Some(block) = self.verified.lock().get(&hash_block) {
+            return Some(block.clone());
+        }
+        let mut block = vec![0u8; self.block_size];
+        if !self.read_raw(self.data_blocks + hash_block, &mut block) {
+            return None;
+        }
+
+        let expected = if level + 1 == self.levels.len() {
+            self.root_hash
+        } else {
+            let per_block = self.block_size / DIGEST_LEN;
+            let parent = self.verified_hash_block(level + 1, index / per_block)?;
+            digest_at(&parent, index % per_block)
+        };
+        if sha256(&block) != expected {
+            pr_err!(
+                "[Verity] {}: hash block {} (level {}) is corrupted",
+                self.name,
+                hash_block,
+                level
+            );
+            return None;
+        }
+        let block: Arc<[u8]> = block.into();
+        self.verified.lock().insert(hash_block, block.clone());
+        Some(block)
+    }
+
+    /// 读取并校验数据块
+    fn read_verified(&self, block: usize, buf: &mut [u8]) -> bool {
+        if !self.read_raw(block, buf) {
+            return false;
+        }
+        let per_block = self.block_size / DIGEST_LEN;
+        let Some(leaf) = self.verified_hash_block(0, block / per_block) else {
+            pr_err!("[Verity] {}: cannot verify data block {}", self.name, block);
+            return false;
+        };
+        if sha256(buf) != digest_at(&leaf, block % per_block) {
+            pr_err!("[Verity] {}: data block {} is corrupted", self.name, block);
+            return false;
+        }
+        true
+    }
+}
+
+impl Driver for VerityBlockDevice {
+    fn try_handle_interrupt(&self, _irq: Option<usize>) -> bool {
+        false
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Block
+    }
+
+    fn get_id(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_block(&self) -> Option<&dyn BlockDriver> {
+        Some(self)
+    }
+
+    fn as_block_arc(self: Arc<Self>) -> Option<Arc<dyn BlockDriver>> {
+        Some(self)
+    }
+}
+
+impl BlockDriver for VerityBlockDevice {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) -> bool {
+        let sector = self.block_size();
+        if buf.len() < sector {
+            return false;
+        }
+        self.read_blocks(block_id, &mut buf[..sector])
+    }
+
+    fn read_blocks(&self, start_block: usize, buf: &mut [u8]) -> bool {
+        let sector = self.block_size();
+        if !buf.len().is_multiple_of(sector) {
+            return false;
+        }
+        let Some(end_block) = start_block.checked_add(buf.len() / sector) else {
+            return false;
+        };
+        if end_block > self.total_blocks() {
+            return false;
+        }
+
+        let mut data = vec![0u8; self.block_size];
+        let mut offset = start_block * sector;
+        let mut done = 0;
+        while done < buf.len() {
+            let block = offset / self.block_size;
+            let in_block = offset % self.block_size;
+            let len = (self.block_size - in_block).min(buf.len() - done);
+            if !self.read_verified(block, &mut data) {
+                return false;
+            }
+            buf[done..done + len].copy_from_slice(&data[in_block..in_block + len]);
+            done += len;
+            offset += len;
+        }
+        true
+    }
+
+    fn write_block(&self, _block_id: usize, _buf: &[u8]) -> bool {
+        false
+    }
+
+    fn write_blocks(&self, _start_block: usize, _buf: &[u8]) -> bool {
+        false
+    }
+
+    fn max_segments(&self) -> usize {
+        self.inner.max_segments()
+    }
+
+    fn queue_stats(&self) -> QueueStats {
+        self.inner.queue_stats()
+    }
+
+    fn max_sectors_kb(&self) -> usize {
+        self.inner.max_sectors_kb()
+    }
+
+    fn set_max_sectors_kb(&self, kb: usize) -> bool {
+        self.inner.set_max_sectors_kb(kb)
+    }
+
+    fn flush(&self) -> bool {
+        true
+    }
+
+    fn block_size(&self) -> usize {
+        self.inner.block_size()
+    }
+
+    fn total_blocks(&self) -> usize {
+        self.data_blocks * (self.block_size / self.inner.block_size())
+    }
+}
+
+/// 如果 `verity=` 指定了 `name`，返回套上校验层的设备，否则原样返回
+///
+/// 超级块无效时记录错误并不做校验。
+pub fn wrap(name: &str, device: Arc<dyn BlockDriver>) -> Arc<dyn BlockDriver> {
+    let Some(param) = parse_param(&CMDLINE.read()) else {
+        return device;
+    };
+    if param.device != name {
+        return device;
+    }
+    if let Some(verity) = VERITY_DEVICES.lock().get(name) {
+        return verity.clone();
+    }
+
+    // 打开需要读盘，不在持锁时进行
+    let Some(verity) = VerityBlockDevice::open(device.clone(), name.to_string(), param.root_hash)
+    else {
+        pr_err!(
+            "[Verity] {}: no valid hash tree, verification disabled",
+            name
+        );
+        return device;
+    };
+    pr_info!(
+        "[Verity] {}: verifying {} blocks of {} bytes",
+        name,
+        verity.data_blocks,
+        verity.block_size
+    );
+    VERITY_DEVICES
+        .lock()
+        .entry(name.to_string())
+        .or_insert(verity)
+        .clone()
+}
+
+fn digest_at(block: &[u8], index: usize) -> [u8; DIGEST_LEN] {
+    let mut digest = [0u8; DIGEST_LEN];
+    digest.copy_from_slice(&block[index * DIGEST_LEN..(index + 1) * DIGEST_LEN]);
+    digest
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes.try_into().unwrap())
+}
+
+fn le_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes.try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::block::ram_disk::RamDisk;
+    use crate::{kassert, test_case};
+
+    const BLOCK: usize = 512;
+
+    /// 按 `scripts/append_verity_tree.py` 的格式在数据后追加哈希树和超级块
+    fn build_image(data_blocks: usize) -> Vec<u8> {
+        let mut image: Vec<u8> = (0..data_blocks * BLOCK).map(|i| (i / 7) as u8).collect();
+        let (levels, hash_blocks) = tree_layout(data_blocks, BLOCK);
+        let mut hashes = vec![0u8; hash_blocks * BLOCK];
+        let mut below: Vec<[u8; DIGEST_LEN]> = image.chunks_exact(BLOCK).map(sha256).collect();
+        for level in &levels {
+            let area = &mut hashes[level.start * BLOCK..(level.start + level.count) * BLOCK];
+            for (i, digest) in below.iter().enumerate() {
+                area[i * DIGEST_LEN..(i + 1) * DIGEST_LEN].copy_from_slice(digest);
+            }
+            below = area.chunks_exact(BLOCK).map(sha256).collect();
+        }
+        image.extend_from_slice(&hashes);
+
+        let mut sb = [0u8; VERITY_SUPERBLOCK_SIZE];
+        sb[0..8].copy_from_slice(VERITY_MAGIC);
+        sb[8..12].copy_from_slice(&VERITY_VERSION.to_le_bytes());
+        sb[12..16].copy_from_slice(&(BLOCK as u32).to_le_bytes());
+        sb[16..24].copy_from_slice(&(data_blocks as u64).to_le_bytes());
+        sb[24..32].copy_from_slice(&(hash_blocks as u64).to_le_bytes());
+        sb[32..64].copy_from_slice(&below[0]);
+        image.extend_from_slice(&sb);
+        image
+    }
+
+    test_case!(test_verity_param_and_layout, {
+        let param = parse_param("root=/dev/vda1 verity=/dev/vda1").unwrap();
+        kassert!(param.device == "vda1" && param.root_hash.is_none());
+        let hex = "00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff";
+        let param = parse_param(&alloc::format!("verity=vdb2,{}", hex)).unwrap();
+        kassert!(param.root_hash.map(|h| h[1]) == Some(0x11));
+        kassert!(parse_param("verity=vda1,abc").is_none());
+
+        // 每块 16 个摘要：40 个数据块需要 3 个叶子块和 1 个顶层块
+        let (levels, total) = tree_layout(40, BLOCK);
+        kassert!(total == 4);
+        kassert!(levels[0] == HashLevel { start: 1, count: 3 });
+        kassert!(levels[1] == HashLevel { start: 0, count: 1 });
+    });
+
+    test_case!(test_verity_detects_corruption, {
+        let image = build_image(40);
+        let disk = RamDisk::from_bytes(image, BLOCK, 0);
+        let inner: Arc<dyn BlockDriver> = disk.clone();
+        let verity = VerityBlockDevice::open(inner, "vda1".to_string(), None).unwrap();
+        kassert!(verity.total_blocks() == 40);
+
+        let mut buf = vec![0u8; 3 * BLOCK];
+        kassert!(verity.read_blocks(20, &mut buf));
+        kassert!(buf[0] == ((20 * BLOCK) / 7) as u8);
+        kassert!(!verity.write_block(0, &buf[..BLOCK]));
+
+        // 篡改第 33 块后读取失败，其他块不受影响
+        let mut block = [0u8; BLOCK];
+        kassert!(disk.read_block(33, &mut block));
+        block[100] ^= 0xFF;
+        kassert!(disk.write_block(33, &block));
+        kassert!(!verity.read_block(33, &mut buf[..BLOCK]));
+        kassert!(verity.read_block(32, &mut buf[..BLOCK]));
+
+        // 已校验的叶子块（覆盖第 16..32 块）被同步篡改后仍以缓存为准
+        kassert!(disk.read_block(21, &mut block));
+        block[0] ^= 0xFF;
+        kassert!(disk.write_block(21, &block));
+        let forged = sha256(&block);
+        let leaf = 40 + tree_layout(40, BLOCK).0[0].start + 1;
+        kassert!(disk.read_block(leaf, &mut block));
+        block[5 * DIGEST_LEN..6 * DIGEST_LEN].copy_from_slice(&forged);
+        kassert!(disk.write_block(leaf, &block));
+        kassert!(!verity.read_block(21, &mut buf[..BLOCK]));
+
+        // 命令行根哈希不一致时所有读取失败
+        let inner: Arc<dyn BlockDriver> = disk;
+        let wrong = VerityBlockDevice::open(inner, "vda1".to_string(), Some([0; 32])).unwrap();
+        kassert!(!wrong.read_block(0, &mut buf[..BLOCK]));
+    });
+}
@@ -1,9 +1,26 @@
-use alloc::{string::String, sync::Arc};
-use virtio_drivers::device::blk::VirtIOBlk;
-use virtio_drivers::transport::InterruptStatus;
+//! VirtIO 块设备驱动
+//!
+//! 块设备请求队列（[`BlkQueue`]）会把块号相邻的排队请求合并为一个多段请求，
+//! 段数上限取自设备配置空间的 `seg_max`（设备未提供 `VIRTIO_BLK_F_SEG_MAX` 时不合并）。
+//! `virtio-drivers` 的请求接口只接受一个连续的数据缓冲区，因此多段请求先经由
+//! 回弹缓冲区聚合，再作为一个 virtio 请求提交：合并的请求不再单独触发 vring 通知。
+//! 提交的请求数见 `/sys/block/<dev>/queue/vring_kicks`。
+//!
+//! 读写请求的请求头和状态字节放在设备私有的 DMA32 页中，数据缓冲区超出 DMA32 区域时
+//! 由驱动先分配 DMA32 反弹缓冲区中转：HAL 的 `share` 无法报告错误，DMA32 内存耗尽时
+//! 在这里分配失败，请求以 EIO 失败，而不会在共享缓冲区时 panic。
+
+use alloc::{string::String, sync::Arc, vec};
+use core::sync::atomic::{AtomicU64, Ordering};
+use virtio_drivers::device::blk::{BlkReq, BlkResp, VirtIOBlk};
+use virtio_drivers::transport::{InterruptStatus, Transport};
 use virtio_drivers::transport::{mmio::MmioTransport, pci::PciTransport};
 
+use crate::config::PAGE_SIZE;
+use crate::device::dma::{DmaBuffer, dma_alloc};
 use crate::device::virtio_hal::VirtIOHal;
+use crate::mm::address::VA;
+use crate::mm::frame_allocator::DMA32_LIMIT;
 
 use crate::device::{BLK_DRIVERS, DRIVERS, IRQ_MANAGER, NetDevice};
 use crate::pr_info;
@@ -11,11 +28,185 @@ use crate::sync::Mutex;
 
 use super::{
     super::{DeviceType, Driver},
-    BlockDriver,
+    BlockDriver, QueueStats,
+    iosched::BlkQueue,
 };
 
+/// 设备特性位 VIRTIO_BLK_F_SEG_MAX
+const VIRTIO_BLK_F_SEG_MAX: u64 = 1 << 2;
+
+/// 配置空间中 `seg_max` 字段的偏移
+const CONFIG_SEG_MAX_OFFSET: usize = 12;
+
+/// 单个请求最多合并的段数，限制回弹缓冲区的大小
+const MAX_SEGMENTS_LIMIT: usize = 32;
+
+/// 请求头所在页中状态字节的偏移
+const RESP_OFFSET: usize = 64;
+
+/// 缓冲区是否整个位于 DMA32 区域，可以直接交给设备
+fn dma32_reachable(buf: &[u8]) -> bool {
+    let paddr = unsafe { crate::arch::va_to_pa(VA::from_usize(buf.as_ptr() as usize)) };
+    paddr.as_usize() + buf.len() <= DMA32_LIMIT
+}
+
+/// 读取设备配置空间中的最大段数，须在设备初始化之前调用
+fn read_max_segments(transport: &mut impl Transport) -> usize {
+    if transport.read_device_features() & VIRTIO_BLK_F_SEG_MAX == 0 {
+        return 1;
+    }
+    transport
+        .read_config_space::<u32>(CONFIG_SEG_MAX_OFFSET)
+        .map_or(1, |seg_max| (seg_max as usize).clamp(1, MAX_SEGMENTS_LIMIT))
+}
+
+/// MMIO 与 PCI 两种传输方式共用的设备状态
+struct VirtIOBlkInner<T: Transport> {
+    blk: Mutex<VirtIOBlk<VirtIOHal, T>>,
+    /// 读写请求的请求头和状态字节，位于 DMA32 区域，只在持有 `blk` 锁时访问
+    headers: DmaBuffer,
+    /// 单个请求最多包含的段数
+    max_segments: usize,
+    /// 提交给设备的请求数
+    kicks: AtomicU64,
+}
+
+impl<T: Transport> VirtIOBlkInner<T> {
+    fn new(mut transport: T) -> virtio_drivers::Result<Self> {
+        let max_segments = read_max_segments(&mut transport);
+        let headers = dma_alloc(1).ok_or(virtio_drivers::Error::DmaError)?;
+        Ok(Self {
+            blk: Mutex::new(VirtIOBlk::new(transport)?),
+            headers,
+            max_segments,
+            kicks: AtomicU64::new(0),
+        })
+    }
+
+    fn ack_interrupt(&self) -> bool {
+        let status = self.blk.lock().ack_interrupt();
+        status.contains(InterruptStatus::QUEUE_INTERRUPT)
+    }
+
+    /// 请求头和状态字节
+    ///
+    /// # Safety
+    /// 调用者须持有 `blk` 锁，并且在请求完成前不再次调用。
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn header_slots(&self) -> (&mut BlkReq, &mut BlkResp) {
+        // dma_alloc 返回清零的页，全零是合法的 BlkReq 和 BlkResp
+        let mut base = self.headers.vaddr();
+        let mut resp = base + RESP_OFFSET;
+        unsafe {
+            (
+                &mut *base.as_mut_ptr::<BlkReq>(),
+                &mut *resp.as_mut_ptr::<BlkResp>(),
+            )
+        }
+    }
+
+    /// 提交读请求并轮询等待完成，`buf` 须位于 DMA32 区域
+    fn submit_read(&self, start_block: usize, buf: &mut [u8]) -> bool {
+        let mut blk = self.blk.lock();
+        unsafe {
+            let (req, resp) = self.header_slots();
+            let Ok(token) = blk.read_blocks_nb(start_block, req, buf, resp) else {
+                return false;
+            };
+            while blk.peek_used() != Some(token) {
+                core::hint::spin_loop();
+            }
+            blk.complete_read_blocks(token, req, buf, resp).is_ok()
+        }
+    }
+
+    /// 提交写请求并轮询等待完成，`buf` 须位于 DMA32 区域
+    fn submit_write(&self, start_block: usize, buf: &[u8]) -> bool {
+        let mut blk = self.blk.lock();
+        unsafe {
+            let (req, resp) = self.header_slots();
+            let Ok(token) = blk.write_blocks_nb(start_block, req, buf, resp) else {
+                return false;
+            };
+            while blk.peek_used() != Some(token) {
+                core::hint::spin_loop();
+            }
+            blk.complete_write_blocks(token, req, buf, resp).is_ok()
+        }
+    }
+
+    fn read_blocks(&self, start_block: usize, buf: &mut [u8]) -> bool {
+        self.kicks.fetch_add(1, Ordering::Relaxed);
+        if dma32_reachable(buf) {
+            return self.submit_read(start_block, buf);
+        }
+        let Some(mut bounce) = dma_alloc(buf.len().div_ceil(PAGE_SIZE)) else {
+            return false;
+        };
+        let data = &mut bounce.as_mut_slice()[..buf.len()];
+        if !self.submit_read(start_block, data) {
+            return false;
+        }
+        buf.copy_from_slice(data);
+        true
+    }
+
+    fn write_blocks(&self, start_block: usize, buf: &[u8]) -> bool {
+        self.kicks.fetch_add(1, Ordering::Relaxed);
+        if dma32_reachable(buf) {
+            return self.submit_write(start_block, buf);
+        }
+        let Some(mut bounce) = dma_alloc(buf.len().div_ceil(PAGE_SIZE)) else {
+            return false;
+        };
+        let data = &mut bounce.as_mut_slice()[..buf.len()];
+        data.copy_from_slice(buf);
+        self.submit_write(start_block, data)
+    }
+
+    fn read_segments(&self, start_block: usize, segs: &mut [&mut [u8]]) -> bool {
+        if let [seg] = segs {
+            return self.read_blocks(start_block, seg);
+        }
+        let total = segs.iter().map(|seg| seg.len()).sum();
+        let mut bounce = vec![0u8; total];
+        if !self.read_blocks(start_block, &mut bounce) {
+            return false;
+        }
+        let mut offset = 0;
+        for seg in segs.iter_mut() {
+            seg.copy_from_slice(&bounce[offset..offset + seg.len()]);
+            offset += seg.len();
+        }
+        true
+    }
+
+    fn write_segments(&self, start_block: usize, segs: &[&[u8]]) -> bool {
+        if let [seg] = segs {
+            return self.write_blocks(start_block, seg);
+        }
+        self.write_blocks(start_block, &segs.concat())
+    }
+
+    fn flush(&self) -> bool {
+        self.kicks.fetch_add(1, Ordering::Relaxed);
+        self.blk.lock().flush().is_ok()
+    }
+
+    fn queue_stats(&self) -> QueueStats {
+        QueueStats {
+            vring_kicks: self.kicks.load(Ordering::Relaxed),
+            ..QueueStats::default()
+        }
+    }
+
+    fn total_blocks(&self) -> usize {
+        self.blk.lock().capacity() as usize
+    }
+}
+
 /// VirtIO 块设备驱动结构体
-pub struct VirtIOBlkDriver(Mutex<VirtIOBlk<VirtIOHal, MmioTransport<'static>>>);
+pub struct VirtIOBlkDriver(VirtIOBlkInner<MmioTransport<'static>>);
 
 impl VirtIOBlkDriver {
     const ID: &'static str = "virtio_block";
@@ -23,8 +214,7 @@ impl VirtIOBlkDriver {
 
 impl Driver for VirtIOBlkDriver {
     fn try_handle_interrupt(&self, _irq: Option<usize>) -> bool {
-        let status = self.0.lock().ack_interrupt();
-        status.contains(InterruptStatus::QUEUE_INTERRUPT)
+        self.0.ack_interrupt()
     }
 
     fn device_type(&self) -> DeviceType {
@@ -54,23 +244,39 @@ impl Driver for VirtIOBlkDriver {
 
 impl BlockDriver for VirtIOBlkDriver {
     fn read_block(&self, block_id: usize, buf: &mut [u8]) -> bool {
-        self.0.lock().read_blocks(block_id, buf).is_ok()
+        self.0.read_blocks(block_id, buf)
     }
 
     fn read_blocks(&self, start_block: usize, buf: &mut [u8]) -> bool {
-        self.0.lock().read_blocks(start_block, buf).is_ok()
+        self.0.read_blocks(start_block, buf)
     }
 
     fn write_block(&self, block_id: usize, buf: &[u8]) -> bool {
-        self.0.lock().write_blocks(block_id, buf).is_ok()
+        self.0.write_blocks(block_id, buf)
     }
 
     fn write_blocks(&self, start_block: usize, buf: &[u8]) -> bool {
-        self.0.lock().write_blocks(start_block, buf).is_ok()
+        self.0.write_blocks(start_block, buf)
+    }
+
+    fn max_segments(&self) -> usize {
+        self.0.max_segments
+    }
+
+    fn read_segments(&self, start_block: usize, segs: &mut [&mut [u8]]) -> bool {
+        self.0.read_segments(start_block, segs)
+    }
+
+    fn write_segments(&self, start_block: usize, segs: &[&[u8]]) -> bool {
+        self.0.write_segments(start_block, segs)
+    }
+
+    fn queue_stats(&self) -> QueueStats {
+        self.0.queue_stats()
     }
 
     fn flush(&self) -> bool {
-        self.0.lock().flush().is_ok()
+        self.0.flush()
     }
 
     fn block_size(&self) -> usize {
@@ -78,17 +284,16 @@ impl BlockDriver for VirtIOBlkDriver {
     }
 
     fn total_blocks(&self) -> usize {
-        self.0.lock().capacity() as usize
+        self.0.total_blocks()
     }
 }
 
 /// VirtIO 块设备驱动结构体（PCI）
-pub struct VirtIOBlkPciDriver(Mutex<VirtIOBlk<VirtIOHal, PciTransport>>);
+pub struct VirtIOBlkPciDriver(VirtIOBlkInner<PciTransport>);
 
 impl Driver for VirtIOBlkPciDriver {
     fn try_handle_interrupt(&self, _irq: Option<usize>) -> bool {
-        let status = self.0.lock().ack_interrupt();
-        status.contains(InterruptStatus::QUEUE_INTERRUPT)
+        self.0.ack_interrupt()
     }
 
     fn device_type(&self) -> DeviceType {
@@ -118,23 +323,39 @@ impl Driver for VirtIOBlkPciDriver {
 
 impl BlockDriver for VirtIOBlkPciDriver {
     fn read_block(&self, block_id: usize, buf: &mut [u8]) -> bool {
-        self.0.lock().read_blocks(block_id, buf).is_ok()
+        self.0.read_blocks(block_id, buf)
     }
 
     fn read_blocks(&self, start_block: usize, buf: &mut [u8]) -> bool {
-        self.0.lock().read_blocks(start_block, buf).is_ok()
+        self.0.read_blocks(start_block, buf)
     }
 
     fn write_block(&self, block_id: usize, buf: &[u8]) -> bool {
-        self.0.lock().write_blocks(block_id, buf).is_ok()
+        self.0.write_blocks(block_id, buf)
     }
 
     fn write_blocks(&self, start_block: usize, buf: &[u8]) -> bool {
-        self.0.lock().write_blocks(start_block, buf).is_ok()
+        self.0.write_blocks(start_block, buf)
+    }
+
+    fn max_segments(&self) -> usize {
+        self.0.max_segments
+    }
+
+    fn read_segments(&self, start_block: usize, segs: &mut [&mut [u8]]) -> bool {
+        self.0.read_segments(start_block, segs)
+    }
+
+    fn write_segments(&self, start_block: usize, segs: &[&[u8]]) -> bool {
+        self.0.write_segments(start_block, segs)
+    }
+
+    fn queue_stats(&self) -> QueueStats {
+        self.0.queue_stats()
     }
 
     fn flush(&self) -> bool {
-        self.0.lock().flush().is_ok()
+        self.0.flush()
     }
 
     fn block_size(&self) -> usize {
@@ -142,28 +363,34 @@ impl BlockDriver for VirtIOBlkPciDriver {
     }
 
     fn total_blocks(&self) -> usize {
-        self.0.lock().capacity() as usize
+        self.0.total_blocks()
     }
 }
 
 /// 初始化 VirtIO 块设备驱动
 pub fn init(transport: MmioTransport<'static>) {
-    let blk = VirtIOBlk::new(transport).expect("failed to init blk driver");
-    let driver = Arc::new(VirtIOBlkDriver(Mutex::new(blk)));
+    let inner = VirtIOBlkInner::new(transport).expect("failed to init blk driver");
+    let driver = Arc::new(VirtIOBlkDriver(inner));
     DRIVERS.write().push(driver.clone());
     IRQ_MANAGER.lock().register_all(driver.clone());
-    BLK_DRIVERS.write().push(driver);
-    pr_info!("[Device] Block driver (virtio-blk) is initialized");
+    pr_info!(
+        "[Device] Block driver (virtio-blk) is initialized, max {} segments per request",
+        driver.0.max_segments
+    );
+    BLK_DRIVERS.write().push(BlkQueue::new(driver));
 }
 
 /// 初始化 VirtIO 块设备驱动（PCI）
 pub fn init_pci(transport: PciTransport) {
-    let blk = VirtIOBlk::new(transport).expect("failed to init pci blk driver");
-    let driver = Arc::new(VirtIOBlkPciDriver(Mutex::new(blk)));
+    let inner = VirtIOBlkInner::new(transport).expect("failed to init pci blk driver");
+    let driver = Arc::new(VirtIOBlkPciDriver(inner));
     DRIVERS.write().push(driver.clone());
     IRQ_MANAGER.lock().register_all(driver.clone());
-    BLK_DRIVERS.write().push(driver);
-    pr_info!("[Device] Block driver (virtio-blk-pci) is initialized");
+    pr_info!(
+        "[Device] Block driver (virtio-blk-pci) is initialized, max {} segments per request",
+        driver.0.max_segments
+    );
+    BLK_DRIVERS.write().push(BlkQueue::new(driver));
 }
 
 #[cfg(test)]
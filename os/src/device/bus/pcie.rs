@@ -2,7 +2,15 @@
 
 use crate::{
     arch::platform::{VirtDevice, mmio_of},
-    device::{block::virtio_blk, device_tree::FDT, net::virtio_net},
+    device::{
+        block::virtio_blk,
+        device_tree::FDT,
+        net::virtio_net,
+        watchdog::{
+            self,
+            i6300esb::{I6300ESB_DEVICE_ID, I6300ESB_VENDOR_ID, I6300Esb},
+        },
+    },
     kernel::current_memory_space,
     mm::{
         address::{ConvertablePA, PA, VA},
@@ -407,6 +415,10 @@ pub fn init_virtio_pci() {
 
     for bus in host.bus_start..=host.bus_end {
         for (df, info) in root.enumerate_bus(bus) {
+            if info.vendor_id == I6300ESB_VENDOR_ID && info.device_id == I6300ESB_DEVICE_ID {
+                init_i6300esb(&mut root, df, ecam_vaddr, &mut next_mmio, mmio_end);
+                continue;
+            }
             let dev_type = match virtio_device_type(&info) {
                 Some(t) => t,
                 None => continue,
@@ -444,6 +456,35 @@ pub fn init_virtio_pci() {
     }
 }
 
+/// 初始化 i6300esb 看门狗：分配 BAR0 并注册为硬件看门狗
+fn init_i6300esb(
+    root: &mut PciRoot<MmioCam<'_>>,
+    df: virtio_drivers::transport::pci::bus::DeviceFunction,
+    ecam_vaddr: usize,
+    next_mmio: &mut PA,
+    mmio_end: PA,
+) {
+    let (_status, command) = root.get_status_command(df);
+    root.set_command(df, command | Command::MEMORY_SPACE);
+    allocate_bars(root, df, next_mmio, mmio_end);
+
+    let Ok(Some(BarInfo::Memory { address, .. })) = root.bar_info(df, 0) else {
+        pr_warn!("[PCIe] i6300esb {}: BAR0 is not a memory BAR", df);
+        return;
+    };
+    if address == 0 {
+        pr_warn!("[PCIe] i6300esb {}: BAR0 not assigned", df);
+        return;
+    }
+    let config = ecam_vaddr
+        + (((df.bus as usize) << 20)
+            | ((df.device as usize) << 15)
+            | ((df.function as usize) << 12));
+    let regs = crate::arch::mmio_pa_to_va(PA::from_usize(address as usize)).as_usize();
+    let esb = unsafe { I6300Esb::new(config, regs) };
+    watchdog::register(alloc::sync::Arc::new(esb));
+}
+
 fn allocate_bars(
     root: &mut PciRoot<MmioCam<'_>>,
     df: virtio_drivers::transport::pci::bus::DeviceFunction,
@@ -1,15 +1,154 @@
-// Console -> TextBuffer -> FrameBuffer
-
-/// 初始化控制台设备
-pub fn init() {
-    // if cfg!(feature = "consolegraphic") {
-    //     if let Some(fb) = FRAME_BUFFER.write().take() {
-    //         // TODO: now take FrameBuffer out of global variable, then move into Console
-    //         let console = Console::on_frame_buffer(fb.fb_info.xres, fb.fb_info.yres, fb);
-    //         *CONSOLE.lock() = Some(console);
-    //         pr_info!("console: init end");
-    //     } else {
-    //         pr_warn!("console: init failed");
-    //     }
-    // }
+//! 帧缓冲控制台
+//!
+//! Console -> 字符网格 -> FrameBuffer
+//!
+//! 名为 `tty0`。没有可用串口或 `console=tty0` 时启用：用 8x8 点阵字体把文本画到帧缓冲上，
+//! 写到最后一行后整屏上移一行。帧缓冲没有输入来源，读取总是立即返回空字符。
+
+use alloc::{string::String, sync::Arc};
+
+use super::font8x8::{GLYPH_HEIGHT, GLYPH_WIDTH, glyph};
+use super::{CONSOLES, Console};
+use crate::device::gpu::{FRAME_BUFFER, FrameBuffer};
+use crate::sync::SpinLock;
+
+/// 前景色（浅灰）
+const FG_COLOR: u32 = 0xFFAA_AAAA;
+/// 背景色（黑）
+const BG_COLOR: u32 = 0xFF00_0000;
+/// 制表位宽度
+const TAB_WIDTH: usize = 8;
+
+/// 光标位置（以字符为单位）
+struct Cursor {
+    col: usize,
+    row: usize,
+}
+
+struct FrameConsole {
+    fb: Arc<FrameBuffer>,
+    cols: usize,
+    rows: usize,
+    cursor: SpinLock<Cursor>,
+}
+
+impl FrameConsole {
+    fn new(fb: Arc<FrameBuffer>) -> Self {
+        let info = fb.info();
+        Self {
+            cols: info.xres as usize / GLYPH_WIDTH,
+            rows: info.yres as usize / GLYPH_HEIGHT,
+            fb,
+            cursor: SpinLock::new(Cursor { col: 0, row: 0 }),
+        }
+    }
+
+    /// 在 (`col`, `row`) 处绘制一个字符
+    fn draw_char(&self, col: usize, row: usize, ch: char) {
+        let bitmap = glyph(ch).or_else(|| glyph('?')).unwrap();
+        let x0 = (col * GLYPH_WIDTH) as u32;
+        let y0 = (row * GLYPH_HEIGHT) as u32;
+        for (dy, bits) in bitmap.iter().enumerate() {
+            for dx in 0..GLYPH_WIDTH {
+                let color = if bits & (1 << dx) != 0 {
+                    FG_COLOR
+                } else {
+                    BG_COLOR
+                };
+                self.fb.put_pixel(x0 + dx as u32, y0 + dy as u32, color);
+            }
+        }
+    }
+
+    /// 整屏上移一行文本，并清空最后一行
+    fn scroll_up(&self) {
+        let line_bytes = self.fb.info().line_length as usize * GLYPH_HEIGHT;
+        let keep = line_bytes * (self.rows - 1);
+        self.fb.copy_within(line_bytes, 0, keep);
+        self.fb.fill(keep, line_bytes, BG_COLOR);
+    }
+
+    fn newline(&self, cursor: &mut Cursor) {
+        cursor.col = 0;
+        if cursor.row + 1 < self.rows {
+            cursor.row += 1;
+        } else {
+            self.scroll_up();
+        }
+    }
+
+    fn put_char(&self, cursor: &mut Cursor, ch: char) {
+        match ch {
+            '\n' => self.newline(cursor),
+            '\r' => cursor.col = 0,
+            '\t' => {
+                cursor.col = (cursor.col / TAB_WIDTH + 1) * TAB_WIDTH;
+                if cursor.col >= self.cols {
+                    self.newline(cursor);
+                }
+            }
+            '\x08' => cursor.col = cursor.col.saturating_sub(1),
+            _ => {
+                if cursor.col >= self.cols {
+                    self.newline(cursor);
+                }
+                self.draw_char(cursor.col, cursor.row, ch);
+                cursor.col += 1;
+            }
+        }
+    }
+}
+
+impl Console for FrameConsole {
+    fn name(&self) -> &str {
+        "tty0"
+    }
+
+    fn write_str(&self, s: &str) {
+        if self.cols == 0 || self.rows == 0 {
+            return;
+        }
+        {
+            let mut cursor = self.cursor.lock();
+            for ch in s.chars() {
+                self.put_char(&mut cursor, ch);
+            }
+        }
+        self.fb.flush();
+    }
+
+    fn read_char(&self) -> char {
+        '\0'
+    }
+
+    fn read_line(&self, _buf: &mut String) {}
+
+    fn flush(&self) {
+        self.fb.flush();
+    }
+}
+
+/// 在帧缓冲上注册后备控制台
+///
+/// 返回是否注册成功（没有帧缓冲时返回 false）。
+pub fn init() -> bool {
+    let Some(fb) = FRAME_BUFFER.read().clone() else {
+        return false;
+    };
+    CONSOLES.write().push(Arc::new(FrameConsole::new(fb)));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::font8x8::{FONT8X8, glyph};
+    use crate::{kassert, test_case};
+
+    test_case!(test_font_glyph_lookup, {
+        kassert!(glyph(' ') == Some(&FONT8X8[0]));
+        kassert!(glyph('~') == Some(&FONT8X8[94]));
+        kassert!(glyph('A').is_some_and(|g| g.iter().any(|&row| row != 0)));
+        kassert!(glyph('\n').is_none());
+        kassert!(glyph('\u{7f}').is_none());
+    });
 }
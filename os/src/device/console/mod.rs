@@ -1,19 +1,41 @@
 //! 控制台驱动模块
+//!
+//! 所有控制台注册到 [`CONSOLES`]；启动参数 `console=` 从中选出活动控制台：
+//! 内核输出写到每个活动控制台，最后一个 `console=` 指定的是主控制台，
+//! 负责输入并作为 `/dev/console`。每个活动控制台可以带自己的日志级别阈值：
+//!
+//! ```text
+//! console=tty0 console=ttyS0,115200,loglevel:7
+//! ```
+//!
+//! 没有 `console=` 时只启用第一个已注册的控制台（通常是串口）。
 
+pub mod font8x8;
 pub mod frame_console;
 pub mod uart_console;
 
+use crate::device::{CMDLINE, DeviceType, Driver, serial::SerialDriver};
+use crate::log::LogLevel;
 use crate::sync::RwLock;
-use alloc::{string::String, sync::Arc, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
 
 lazy_static::lazy_static! {
     /// 全局控制台列表
     pub static ref CONSOLES: RwLock<Vec<Arc<dyn Console>>> = RwLock::new(Vec::new());
     /// 全局主控制台
     pub static ref MAIN_CONSOLE: RwLock<Option<Arc<dyn Console>>> = RwLock::new(None);
+    /// 活动控制台列表（包含主控制台）
+    pub static ref ACTIVE_CONSOLES: RwLock<Vec<ActiveConsole>> = RwLock::new(Vec::new());
 }
 
 pub trait Console: Send + Sync {
+    /// 控制台名称，与 `console=` 参数和 `/dev` 节点名一致，如 `ttyS0`、`tty0`
+    fn name(&self) -> &str;
+
     /// 向控制台写入字符串
     fn write_str(&self, s: &str);
 
@@ -32,19 +54,224 @@ pub trait Console: Send + Sync {
 
     /// 刷新控制台输出缓冲区
     fn flush(&self);
+
+    /// 控制台所在设备的物理基地址，用于与 earlycon 比对
+    fn phys_base(&self) -> Option<usize> {
+        None
+    }
+
+    /// 睡眠等待输入到达（不持有控制台锁时调用）
+    fn wait_input(&self) {}
+
+    /// 控制台底层的串口驱动
+    fn serial(&self) -> Option<Arc<dyn SerialDriver>> {
+        None
+    }
+}
+
+/// 活动控制台
+#[derive(Clone)]
+pub struct ActiveConsole {
+    pub console: Arc<dyn Console>,
+    /// 日志级别阈值；`None` 表示跟随全局控制台级别
+    pub level: Option<LogLevel>,
+}
+
+impl ActiveConsole {
+    /// 该控制台是否输出 `level` 级别的日志
+    pub fn accepts(&self, level: LogLevel, default: LogLevel) -> bool {
+        level as u8 <= self.level.unwrap_or(default) as u8
+    }
+}
+
+/// 一个 `console=` 参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleParam {
+    pub name: String,
+    pub level: Option<LogLevel>,
+}
+
+/// 解析命令行中的全部 `console=` 参数
+///
+/// 格式为 `console=<名称>[,<选项>...]`，选项中的 `loglevel:<0-7>` 设置该控制台的
+/// 日志级别阈值，波特率等其他选项忽略。
+pub fn parse_console_params(cmdline: &str) -> Vec<ConsoleParam> {
+    cmdline
+        .split_ascii_whitespace()
+        .filter_map(|arg| arg.strip_prefix("console="))
+        .filter_map(|value| {
+            let mut fields = value.split(',');
+            let name = fields.next().filter(|n| !n.is_empty())?;
+            let level = fields
+                .filter_map(|opt| opt.strip_prefix("loglevel:"))
+                .filter_map(|v| v.parse::<u8>().ok())
+                .find(|v| *v <= LogLevel::Debug as u8)
+                .map(LogLevel::from_u8);
+            Some(ConsoleParam {
+                name: name.to_string(),
+                level,
+            })
+        })
+        .collect()
+}
+
+/// 按名称查找已注册的控制台
+pub fn find_console(name: &str) -> Option<Arc<dyn Console>> {
+    CONSOLES.read().iter().find(|c| c.name() == name).cloned()
+}
+
+/// 控制台对应的字符设备驱动
+///
+/// 串口控制台直接使用串口驱动；其他控制台（如帧缓冲）包装成只写的 tty。
+pub fn tty_driver(console: &Arc<dyn Console>) -> Arc<dyn Driver> {
+    match console.serial() {
+        Some(serial) => serial as Arc<dyn Driver>,
+        None => Arc::new(ConsoleTty {
+            console: console.clone(),
+        }),
+    }
+}
+
+/// `/dev/console` 对应的驱动：主控制台
+pub fn console_driver() -> Option<Arc<dyn Driver>> {
+    MAIN_CONSOLE.read().as_ref().map(tty_driver)
+}
+
+/// 把没有串口驱动的控制台适配为 tty
+struct ConsoleTty {
+    console: Arc<dyn Console>,
+}
+
+impl Driver for ConsoleTty {
+    fn try_handle_interrupt(&self, _irq: Option<usize>) -> bool {
+        false
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Serial
+    }
+
+    fn get_id(&self) -> String {
+        self.console.name().to_string()
+    }
+
+    fn as_serial(&self) -> Option<&dyn SerialDriver> {
+        Some(self)
+    }
+}
+
+impl SerialDriver for ConsoleTty {
+    fn read(&self) -> u8 {
+        self.console.read_char() as u8
+    }
+
+    fn write(&self, data: &[u8]) {
+        self.console.write_bytes(data);
+    }
+
+    fn try_read(&self) -> Option<u8> {
+        None
+    }
+
+    fn rx_ready(&self) -> bool {
+        false
+    }
+}
+
+/// 向活动控制台写入字符串
+///
+/// `filter` 为 `(日志级别, 全局控制台级别)` 时只写到接受该级别的控制台。
+/// 返回是否存在活动控制台。
+pub fn write_active(s: &str, filter: Option<(LogLevel, LogLevel)>) -> bool {
+    let active = ACTIVE_CONSOLES.read();
+    for entry in active.iter() {
+        if filter.is_none_or(|(level, default)| entry.accepts(level, default)) {
+            entry.console.write_str(s);
+        }
+    }
+    !active.is_empty()
+}
+
+/// 是否有活动控制台会输出 `level` 级别的日志
+pub fn any_accepts(level: LogLevel, default: LogLevel) -> Option<bool> {
+    let active = ACTIVE_CONSOLES.read();
+    if active.is_empty() {
+        return None;
+    }
+    Some(active.iter().any(|c| c.accepts(level, default)))
+}
+
+/// 帧缓冲控制台这类虚拟终端的名称（`tty<N>`，不含串口 `ttyS<N>`）
+fn is_virtual_terminal(name: &str) -> bool {
+    name.strip_prefix("tty")
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
 }
 
 /// 初始化控制台设备
+///
+/// 按 `console=` 选择活动控制台和主控制台；没有参数时使用第一个已注册的串口，
+/// 没有串口时退回到帧缓冲控制台。
 pub fn init() {
-    let Some(console) = CONSOLES.read().first().cloned() else {
-        crate::println!("[Console] No runtime console registered, keeping early console");
-        return;
-    };
+    let params = parse_console_params(&CMDLINE.read());
+    let no_serial = CONSOLES.read().is_empty();
+    let wants_vt = params.iter().any(|p| is_virtual_terminal(&p.name));
+    if (no_serial || wants_vt) && frame_console::init() && no_serial {
+        crate::println!("[Console] No serial console, falling back to framebuffer console");
+    }
 
-    MAIN_CONSOLE.write().replace(console);
-    // frame_console::init();
+    let mut active: Vec<ActiveConsole> = Vec::new();
+    for param in &params {
+        let Some(console) = find_console(&param.name) else {
+            crate::pr_warn!("[Console] console={} not found, ignored", param.name);
+            continue;
+        };
+        // 同一控制台出现多次时以最后一次为准，并移到末尾成为主控制台
+        active.retain(|c| !Arc::ptr_eq(&c.console, &console));
+        active.push(ActiveConsole {
+            console,
+            level: param.level,
+        });
+    }
+    if active.is_empty() {
+        let Some(console) = CONSOLES.read().first().cloned() else {
+            crate::println!("[Console] No runtime console registered, keeping early console");
+            return;
+        };
+        active.push(ActiveConsole {
+            console,
+            level: None,
+        });
+    }
+
+    let primary = active.last().unwrap().console.clone();
+    MAIN_CONSOLE.write().replace(primary.clone());
+    *ACTIVE_CONSOLES.write() = active;
 
     // 切换到运行时控制台
     crate::console::init();
-    crate::pr_info!("[Console] Switched to runtime console");
+    crate::pr_info!("[Console] Switched to runtime console {}", primary.name());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_parse_console_params, {
+        let params =
+            parse_console_params("root=/dev/vda console=tty0 console=ttyS0,115200,loglevel:7");
+        kassert!(params.len() == 2);
+        kassert!(params[0].name == "tty0" && params[0].level.is_none());
+        kassert!(params[1].name == "ttyS0");
+        kassert!(params[1].level == Some(LogLevel::Debug));
+        // 超出范围的级别被忽略
+        let params = parse_console_params("console=ttyS1,loglevel:9 console=");
+        kassert!(params.len() == 1 && params[0].level.is_none());
+    });
+
+    test_case!(test_virtual_terminal_name, {
+        kassert!(is_virtual_terminal("tty0"));
+        kassert!(!is_virtual_terminal("ttyS0"));
+        kassert!(!is_virtual_terminal("tty"));
+    });
 }
@@ -1,28 +1,43 @@
 //! UART 控制台驱动模块
 
-use alloc::{string::String, sync::Arc};
+use alloc::{format, string::String, sync::Arc};
 
 use crate::device::{
+    SERIAL_DRIVERS,
     console::{CONSOLES, Console},
     serial::SerialDriver,
 };
 
 struct UARTConsole {
+    /// `ttyS<N>`，N 为串口在 SERIAL_DRIVERS 中的序号
+    name: String,
     uart: Arc<dyn SerialDriver>,
+    /// 串口寄存器的物理地址
+    paddr: Option<usize>,
 }
 
 impl Console for UARTConsole {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
     fn write_str(&self, s: &str) {
-        self.uart.write(s.as_bytes());
+        self.uart.write_sync(s.as_bytes());
     }
 
     fn write_bytes(&self, bytes: &[u8]) {
-        self.uart.write(bytes);
+        self.uart.write_sync(bytes);
     }
 
     fn read_char(&self) -> char {
-        let byte = self.uart.read();
-        self.uart.write(&[byte]); // 回显
+        // 调用者持有控制台锁，不能睡眠；先用 wait_input 等到输入再读
+        let byte = loop {
+            if let Some(byte) = self.uart.try_read() {
+                break byte;
+            }
+            core::hint::spin_loop();
+        };
+        self.uart.write_sync(&[byte]); // 回显
         byte as char
     }
 
@@ -39,9 +54,30 @@ impl Console for UARTConsole {
     fn flush(&self) {
         // UART 通常不需要显式刷新
     }
+
+    fn phys_base(&self) -> Option<usize> {
+        self.paddr
+    }
+
+    fn wait_input(&self) {
+        self.uart.wait_readable();
+    }
+
+    fn serial(&self) -> Option<Arc<dyn SerialDriver>> {
+        Some(self.uart.clone())
+    }
 }
 
-pub fn init(uart: Arc<dyn SerialDriver>) {
-    let console = Arc::new(UARTConsole { uart });
+pub fn init(uart: Arc<dyn SerialDriver>, paddr: Option<usize>) {
+    let index = SERIAL_DRIVERS
+        .lock()
+        .iter()
+        .position(|d| Arc::ptr_eq(d, &uart))
+        .unwrap_or(0);
+    let console = Arc::new(UARTConsole {
+        name: format!("ttyS{}", index),
+        uart,
+        paddr,
+    });
     CONSOLES.write().push(console);
 }
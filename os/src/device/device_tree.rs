@@ -7,7 +7,12 @@ use crate::{
     pr_info, pr_warn,
     sync::SpinLock,
 };
-use alloc::{collections::btree_map::BTreeMap, string::String, sync::Arc};
+use alloc::{
+    collections::btree_map::BTreeMap,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
 use core::sync::atomic::{AtomicUsize, Ordering};
 use fdt::{Fdt, node::FdtNode};
 /// 指向设备树的指针，在启动时由引导程序设置
@@ -44,6 +49,40 @@ lazy_static::lazy_static! {
         SpinLock::new(BTreeMap::new());
 }
 
+/// 设备树 `/cpus` 下的一个 CPU 节点
+///
+/// 在 [`init`] 中从设备树复制出来，供 `/proc/cpuinfo` 等在启动后使用。
+#[derive(Debug, Clone, Default)]
+pub struct DtCpu {
+    /// `reg` 属性：RISC-V 的 hart ID，LoongArch 的物理核号
+    pub hw_id: usize,
+    /// `compatible` 字符串列表
+    pub compatible: Vec<String>,
+    /// `riscv,isa`（旧式 ISA 字符串，如 `rv64imafdc_zicsr`）
+    pub isa: Option<String>,
+    /// `riscv,isa-base`（如 `rv64i`）
+    pub isa_base: Option<String>,
+    /// `riscv,isa-extensions` 字符串列表
+    pub isa_extensions: Vec<String>,
+}
+
+/// 启动时记录的 CPU 节点，按设备树中的顺序排列
+static CPU_NODES: SpinLock<Vec<DtCpu>> = SpinLock::new(Vec::new());
+
+/// 启动时记录的根节点 `model`
+static MODEL: SpinLock<Option<String>> = SpinLock::new(None);
+
+/// 设备树中的 CPU 节点
+pub fn cpu_nodes() -> Vec<DtCpu> {
+    CPU_NODES.lock().clone()
+}
+
+/// 设备树根节点的 `model`，如 `linux,dummy-loongson3`
+#[allow(dead_code)]
+pub fn model() -> Option<String> {
+    MODEL.lock().clone()
+}
+
 /// 早期初始化: 只解析 CPU 数量和时钟频率
 ///
 /// 此函数在堆分配器初始化之前调用,因此不能使用任何需要堆分配的操作。
@@ -73,6 +112,40 @@ fn cpu_clock_frequency(cpu: fdt::standard_nodes::Cpu<'_, '_>) -> Option<usize> {
         .and_then(|prop| prop.as_usize())
 }
 
+/// 复制 `/cpus` 下各 CPU 节点中 `/proc/cpuinfo` 需要的属性
+fn collect_cpu_nodes() -> Vec<DtCpu> {
+    let string = |cpu: &fdt::standard_nodes::Cpu<'_, '_>, name: &str| {
+        cpu.property(name)
+            .and_then(|p| p.as_str())
+            .map(ToString::to_string)
+    };
+    let string_list = |cpu: &fdt::standard_nodes::Cpu<'_, '_>, name: &str| {
+        cpu.property(name)
+            .map(|p| {
+                p.value
+                    .split(|b| *b == 0)
+                    .filter(|s| !s.is_empty())
+                    .filter_map(|s| core::str::from_utf8(s).ok())
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    FDT.cpus()
+        .enumerate()
+        .map(|(index, cpu)| DtCpu {
+            hw_id: cpu
+                .property("reg")
+                .and_then(|p| p.as_usize())
+                .unwrap_or(index),
+            compatible: string_list(&cpu, "compatible"),
+            isa: string(&cpu, "riscv,isa"),
+            isa_base: string(&cpu, "riscv,isa-base"),
+            isa_extensions: string_list(&cpu, "riscv,isa-extensions"),
+        })
+        .collect()
+}
+
 /// 初始化设备树
 pub fn init() {
     let model = FDT
@@ -87,6 +160,10 @@ pub fn init() {
     early_init();
     pr_info!("[Device] now has {} CPU(s)", num_cpu());
     pr_info!("[Device] CLOCK_FREQ set to {} Hz", clock_freq());
+    *CPU_NODES.lock() = collect_cpu_nodes();
+    if model != "unknown" {
+        *MODEL.lock() = Some(String::from(model));
+    }
 
     FDT.memory().regions().for_each(|region| {
         pr_info!(
@@ -113,45 +190,62 @@ pub fn init() {
 /// * `fdt` - 设备树对象
 fn walk_dt(fdt: &Fdt, intc_only: bool) {
     for node in fdt.all_nodes() {
-        if let Some(compatible) = node.compatible()
-            && node.property("interrupt-controller").is_some() == intc_only
+        if node.property("interrupt-controller").is_some() != intc_only {
+            continue;
+        }
+        // 中断控制器可能已被其子控制器提前探测
+        if intc_only && phandle_of(&node).is_some_and(|p| DEVICE_TREE_INTC.lock().contains_key(&p))
         {
-            pr_info!("[Device] Found device: {}", node.name);
-            let registry = DEVICE_TREE_REGISTRY.lock();
-            for c in compatible.all() {
-                if let Some(f) = registry.get(c) {
-                    f(&node);
-                }
-            }
+            continue;
         }
+        probe_node(&node);
     }
 }
 
-/// 返回 DRAM 的起始物理地址与总大小（合并所有 memory.regions）
-/// # 返回值
-/// * `Option<(usize, usize)>` - 返回起始地址和大小的元组，如果没有有效的内存区域则返回 None
-pub fn dram_info() -> Option<(usize, usize)> {
-    let mut start = usize::MAX;
-    let mut end = 0usize;
-
-    for region in FDT.memory().regions() {
-        let s = region.starting_address as usize;
-        let size = region.size.unwrap_or(0);
-        let e = s.saturating_add(size);
-        if size == 0 {
-            continue;
-        }
-        if s < start {
-            start = s;
-        }
-        if e > end {
-            end = e;
+/// 按 compatible 调用已注册的探测函数
+///
+/// 调用前释放注册表锁，探测函数内部可以递归探测其他节点。
+fn probe_node(node: &FdtNode) {
+    let Some(compatible) = node.compatible() else {
+        return;
+    };
+    pr_info!("[Device] Found device: {}", node.name);
+    for c in compatible.all() {
+        let probe = DEVICE_TREE_REGISTRY.lock().get(c).copied();
+        if let Some(f) = probe {
+            f(node);
         }
     }
+}
+
+fn phandle_of(node: &FdtNode) -> Option<u32> {
+    node.property("phandle")
+        .and_then(|p| p.as_usize())
+        .map(|p| p as u32)
+}
 
-    if start < end {
-        Some((start, end - start))
-    } else {
-        None
+/// 节点 `interrupts` 属性中的第一个中断号
+///
+/// 只取第一个 cell：RISC-V PLIC 为单 cell，LoongArch PCH-PIC 的第二个 cell 是触发方式。
+pub fn first_interrupt(node: &FdtNode) -> Option<usize> {
+    let value = node.property("interrupts")?.value;
+    let cell = value.get(..4)?;
+    Some(u32::from_be_bytes([cell[0], cell[1], cell[2], cell[3]]) as usize)
+}
+
+/// 节点的中断父控制器驱动
+///
+/// 节点自身没有 `interrupt-parent` 时使用根节点的；父控制器尚未探测时先探测它。
+pub fn intc_of(node: &FdtNode) -> Option<Arc<dyn IntcDriver>> {
+    let parent = node.interrupt_parent().or_else(|| {
+        FDT.root()
+            .property("interrupt-parent")
+            .and_then(|p| FDT.find_phandle(p.as_usize()? as u32))
+    })?;
+    let phandle = phandle_of(&parent)?;
+    if let Some(intc) = DEVICE_TREE_INTC.lock().get(&phandle) {
+        return Some(intc.clone());
     }
+    probe_node(&parent);
+    DEVICE_TREE_INTC.lock().get(&phandle).cloned()
 }
@@ -0,0 +1,223 @@
+//! DMA 映射接口
+//!
+//! 驱动通过本模块获得设备可访问的物理地址，不再自行做地址转换：
+//! - [`dma_alloc`] 分配一致性 DMA 缓冲区（物理连续、位于 DMA32 区域、已清零），
+//!   由 [`DmaBuffer`] 持有，drop 时释放；
+//! - [`dma_map_single`] 把驱动已有的缓冲区交给设备，返回 [`DmaMapping`]；
+//!   缓冲区超出 DMA32 区域时改用反弹缓冲区中转；
+//! - [`dma_unmap`]（或直接 drop 映射）结束一次传输，把数据交还给 CPU。
+//!
+//! 映射和解除映射时按传输方向调用架构的缓存维护钩子
+//! （[`crate::arch::dma_cache_wback`] / [`crate::arch::dma_cache_inv`]）。
+//! QEMU virt 平台缓存一致，钩子只是内存屏障。
+
+use crate::config::PAGE_SIZE;
+use crate::mm::address::{ConvertablePA, PA, PageNum, VA};
+use crate::mm::frame_allocator::{AllocFlags, DMA32_LIMIT, FrameRangeTracker};
+
+/// DMA 传输方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmaDirection {
+    /// 设备只读取缓冲区
+    ToDevice,
+    /// 设备只写入缓冲区
+    FromDevice,
+    /// 设备既读又写
+    Bidirectional,
+}
+
+impl DmaDirection {
+    /// 设备是否会读取缓冲区
+    fn device_reads(self) -> bool {
+        self != DmaDirection::FromDevice
+    }
+
+    /// 设备是否会写入缓冲区
+    fn device_writes(self) -> bool {
+        self != DmaDirection::ToDevice
+    }
+}
+
+/// 把 `[pa, pa + len)` 交给设备前的缓存维护
+fn sync_for_device(pa: PA, len: usize, dir: DmaDirection) {
+    if dir.device_reads() {
+        crate::arch::dma_cache_wback(pa, len);
+    }
+    if dir.device_writes() {
+        // 丢弃可能在传输期间被写回、覆盖设备数据的脏行
+        crate::arch::dma_cache_inv(pa, len);
+    }
+}
+
+/// 设备完成对 `[pa, pa + len)` 的访问后的缓存维护
+fn sync_for_cpu(pa: PA, len: usize, dir: DmaDirection) {
+    if dir.device_writes() {
+        crate::arch::dma_cache_inv(pa, len);
+    }
+}
+
+/// 一致性 DMA 缓冲区
+///
+/// CPU 与设备可以同时访问，无需逐次同步；drop 时释放物理帧。
+pub struct DmaBuffer {
+    frames: FrameRangeTracker,
+}
+
+impl DmaBuffer {
+    /// 设备看到的起始物理地址
+    pub fn paddr(&self) -> PA {
+        self.frames.start_ppn().start_addr()
+    }
+
+    /// CPU 访问用的起始虚拟地址（直接映射区）
+    pub fn vaddr(&self) -> VA {
+        self.paddr().to_va()
+    }
+
+    /// 页数
+    pub fn pages(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// 字节数
+    pub fn len(&self) -> usize {
+        self.pages() * PAGE_SIZE
+    }
+
+    /// 是否为空缓冲区
+    pub fn is_empty(&self) -> bool {
+        self.pages() == 0
+    }
+
+    /// 缓冲区内容
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.vaddr().as_ptr::<u8>(), self.len()) }
+    }
+
+    /// 可变的缓冲区内容
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.vaddr().as_mut_ptr::<u8>(), self.len()) }
+    }
+
+    /// `vaddr` 是否落在缓冲区内
+    pub fn contains(&self, vaddr: VA) -> bool {
+        let start = self.vaddr().as_usize();
+        (start..start + self.len()).contains(&vaddr.as_usize())
+    }
+}
+
+/// 分配 `pages` 页已清零的一致性 DMA 缓冲区
+pub fn dma_alloc(pages: usize) -> Option<DmaBuffer> {
+    let frames = crate::mm::frame_allocator::alloc_contig_frames_with(pages, AllocFlags::DMA32)?;
+    let mut buf = DmaBuffer { frames };
+    buf.as_mut_slice().fill(0);
+    sync_for_device(buf.paddr(), buf.len(), DmaDirection::Bidirectional);
+    Some(buf)
+}
+
+/// 一次流式 DMA 映射
+///
+/// 映射期间缓冲区归设备所有，CPU 不应访问；drop 时解除映射。
+pub struct DmaMapping {
+    /// 驱动缓冲区的虚拟地址
+    vaddr: VA,
+    /// 缓冲区长度（字节）
+    len: usize,
+    /// 传输方向
+    dir: DmaDirection,
+    /// 缓冲区超出 DMA32 区域时使用的反弹缓冲区
+    bounce: Option<DmaBuffer>,
+}
+
+impl DmaMapping {
+    /// 交给设备的物理地址
+    pub fn paddr(&self) -> PA {
+        match &self.bounce {
+            Some(bounce) => bounce.paddr(),
+            None => unsafe { crate::arch::va_to_pa(self.vaddr) },
+        }
+    }
+
+    /// 是否经反弹缓冲区中转
+    pub fn is_bounced(&self) -> bool {
+        self.bounce.is_some()
+    }
+}
+
+impl Drop for DmaMapping {
+    fn drop(&mut self) {
+        sync_for_cpu(self.paddr(), self.len, self.dir);
+        if let Some(bounce) = &self.bounce
+            && self.dir.device_writes()
+        {
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    bounce.vaddr().as_ptr::<u8>(),
+                    self.vaddr.as_mut_ptr::<u8>(),
+                    self.len,
+                );
+            }
+        }
+    }
+}
+
+/// 把位于直接映射区的缓冲区 `[vaddr, vaddr + len)` 映射给设备
+///
+/// 缓冲区超出 DMA32 区域时分配反弹缓冲区，DMA32 内存耗尽时返回 `None`。
+///
+/// # Safety
+/// 调用者需保证缓冲区位于直接映射区且物理连续，并在映射解除前一直有效。
+pub unsafe fn dma_map_single(vaddr: VA, len: usize, dir: DmaDirection) -> Option<DmaMapping> {
+    let paddr = unsafe { crate::arch::va_to_pa(vaddr) };
+    let bounce = if paddr.as_usize() + len > DMA32_LIMIT {
+        let bounce = dma_alloc(len.div_ceil(PAGE_SIZE).max(1))?;
+        if dir.device_reads() {
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    vaddr.as_ptr::<u8>(),
+                    bounce.vaddr().as_mut_ptr::<u8>(),
+                    len,
+                );
+            }
+        }
+        Some(bounce)
+    } else {
+        None
+    };
+    let mapping = DmaMapping {
+        vaddr,
+        len,
+        dir,
+        bounce,
+    };
+    sync_for_device(mapping.paddr(), len, dir);
+    Some(mapping)
+}
+
+/// 解除映射，把缓冲区交还给 CPU
+///
+/// 设备写入的数据在返回后对 CPU 可见（反弹缓冲区的内容会被复制回来）。
+pub fn dma_unmap(mapping: DmaMapping) {
+    drop(mapping);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_dma_alloc_and_map, {
+        let mut buf = dma_alloc(2).expect("dma_alloc failed");
+        kassert!(buf.len() == 2 * PAGE_SIZE);
+        kassert!(buf.paddr().as_usize() + buf.len() <= DMA32_LIMIT);
+        kassert!(buf.as_slice().iter().all(|b| *b == 0));
+
+        buf.as_mut_slice()[..4].copy_from_slice(&[1, 2, 3, 4]);
+        let mapping = unsafe { dma_map_single(buf.vaddr(), 4, DmaDirection::ToDevice) }
+            .expect("dma_map_single failed");
+        kassert!(!mapping.is_bounced());
+        kassert!(mapping.paddr() == buf.paddr());
+        dma_unmap(mapping);
+        kassert!(buf.as_slice()[..4] == [1, 2, 3, 4]);
+    });
+}
@@ -1,5 +1,224 @@
 //! GPU 设备模块
 //!
 //! 包含 GPU 设备相关的驱动接口和实现
+//!
+//! GPU 驱动在初始化时建立一块线性帧缓冲（[`FrameBuffer`]），登记到全局
+//! [`FRAME_BUFFER`]。`/dev/fb0` 和帧缓冲控制台都只通过它访问显存；
+//! 像素写入后需要调用 [`FrameBuffer::flush`] 把内容提交给宿主显示。
 
 pub mod virtio_gpu;
+
+use alloc::sync::Arc;
+use core::fmt;
+
+use super::Driver;
+use crate::config::PAGE_SIZE;
+use crate::mm::address::{Ppn, UsizeConvert};
+use crate::mm::memory_space::mapping_area::SharedPages;
+use crate::sync::RwLock;
+use crate::uapi::fb::{
+    FB_TYPE_PACKED_PIXELS, FB_VISUAL_TRUECOLOR, FbBitfield, FbFixScreeninfo, FbVarScreeninfo,
+};
+
+lazy_static::lazy_static! {
+    /// 全局帧缓冲（目前只支持一个显示输出）
+    pub static ref FRAME_BUFFER: RwLock<Option<Arc<FrameBuffer>>> = RwLock::new(None);
+}
+
+/// GPU 驱动接口
+pub trait GpuDriver: Driver {
+    /// 把帧缓冲内容提交到显示输出
+    fn flush(&self);
+}
+
+/// 帧缓冲显示参数
+///
+/// 像素格式固定为 32 位 B8G8R8A8（小端下即 `0xAARRGGBB`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameBufferInfo {
+    /// 水平分辨率
+    pub xres: u32,
+    /// 垂直分辨率
+    pub yres: u32,
+    /// 每像素位数
+    pub bits_per_pixel: u32,
+    /// 每行字节数
+    pub line_length: u32,
+}
+
+/// 线性帧缓冲
+///
+/// 显存是一段物理连续、位于直接映射区的内存，既可由内核直接读写，
+/// 也可通过 [`SharedPages`] 按页映射进用户地址空间。
+pub struct FrameBuffer {
+    info: FrameBufferInfo,
+    /// 显存起始虚拟地址（直接映射区）
+    vaddr: usize,
+    /// 显存起始物理地址，页对齐
+    paddr: usize,
+    /// 显存长度（字节）
+    size: usize,
+    driver: Arc<dyn GpuDriver>,
+}
+
+impl FrameBuffer {
+    /// 创建帧缓冲
+    ///
+    /// # Safety
+    /// 调用者需保证 `[vaddr, vaddr + size)` 是有效的显存映射，物理上从页对齐的
+    /// `paddr` 开始连续，并在帧缓冲存活期间一直有效。
+    pub unsafe fn new(
+        info: FrameBufferInfo,
+        vaddr: usize,
+        paddr: usize,
+        size: usize,
+        driver: Arc<dyn GpuDriver>,
+    ) -> Self {
+        Self {
+            info,
+            vaddr,
+            paddr,
+            size,
+            driver,
+        }
+    }
+
+    /// 显示参数
+    pub fn info(&self) -> FrameBufferInfo {
+        self.info
+    }
+
+    /// 显存长度（字节）
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 从显存 `offset` 处读取，返回实际读取的字节数
+    pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        if offset >= self.size {
+            return 0;
+        }
+        let len = buf.len().min(self.size - offset);
+        // SAFETY: 范围已限制在显存内
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                (self.vaddr + offset) as *const u8,
+                buf.as_mut_ptr(),
+                len,
+            );
+        }
+        len
+    }
+
+    /// 向显存 `offset` 处写入，返回实际写入的字节数（不会自动刷新）
+    pub fn write_at(&self, offset: usize, buf: &[u8]) -> usize {
+        if offset >= self.size {
+            return 0;
+        }
+        let len = buf.len().min(self.size - offset);
+        // SAFETY: 范围已限制在显存内
+        unsafe {
+            core::ptr::copy_nonoverlapping(buf.as_ptr(), (self.vaddr + offset) as *mut u8, len);
+        }
+        len
+    }
+
+    /// 在显存内部搬移数据（允许重叠），用于滚屏
+    pub fn copy_within(&self, src: usize, dst: usize, len: usize) {
+        if src.saturating_add(len) > self.size || dst.saturating_add(len) > self.size {
+            return;
+        }
+        // SAFETY: 源和目的范围均在显存内
+        unsafe {
+            core::ptr::copy(
+                (self.vaddr + src) as *const u8,
+                (self.vaddr + dst) as *mut u8,
+                len,
+            );
+        }
+    }
+
+    /// 用 32 位像素值填充 `[offset, offset + len)`，`offset` 和 `len` 需按 4 字节对齐
+    pub fn fill(&self, offset: usize, len: usize, pixel: u32) {
+        if offset.saturating_add(len) > self.size {
+            return;
+        }
+        let base = (self.vaddr + offset) as *mut u32;
+        for i in 0..len / 4 {
+            // SAFETY: 范围已限制在显存内，显存起始页对齐
+            unsafe { base.add(i).write_volatile(pixel) };
+        }
+    }
+
+    /// 写入位于 (`x`, `y`) 的像素，越界时忽略
+    pub fn put_pixel(&self, x: u32, y: u32, pixel: u32) {
+        if x >= self.info.xres || y >= self.info.yres {
+            return;
+        }
+        let offset = y as usize * self.info.line_length as usize + x as usize * 4;
+        self.fill(offset, 4, pixel);
+    }
+
+    /// 把显存内容提交到显示输出
+    pub fn flush(&self) {
+        self.driver.flush();
+    }
+
+    /// 可变屏幕信息（FBIOGET_VSCREENINFO）
+    pub fn var_screeninfo(&self) -> FbVarScreeninfo {
+        let channel = |offset| FbBitfield {
+            offset,
+            length: 8,
+            msb_right: 0,
+        };
+        FbVarScreeninfo {
+            xres: self.info.xres,
+            yres: self.info.yres,
+            xres_virtual: self.info.xres,
+            yres_virtual: self.info.yres,
+            bits_per_pixel: self.info.bits_per_pixel,
+            // B8G8R8A8：蓝色在最低字节
+            blue: channel(0),
+            green: channel(8),
+            red: channel(16),
+            transp: channel(24),
+            ..FbVarScreeninfo::default()
+        }
+    }
+
+    /// 固定屏幕信息（FBIOGET_FSCREENINFO）
+    pub fn fix_screeninfo(&self) -> FbFixScreeninfo {
+        let mut id = [0u8; 16];
+        let name = b"virtio_gpu";
+        id[..name.len()].copy_from_slice(name);
+        FbFixScreeninfo {
+            id,
+            smem_start: self.paddr,
+            smem_len: self.size as u32,
+            type_: FB_TYPE_PACKED_PIXELS,
+            visual: FB_VISUAL_TRUECOLOR,
+            line_length: self.info.line_length,
+            ..FbFixScreeninfo::default()
+        }
+    }
+}
+
+impl fmt::Debug for FrameBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameBuffer")
+            .field("info", &self.info)
+            .field("paddr", &self.paddr)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl SharedPages for FrameBuffer {
+    fn ppn_at(&self, page_idx: usize) -> Option<Ppn> {
+        (page_idx < self.page_count()).then(|| Ppn::from_usize(self.paddr / PAGE_SIZE + page_idx))
+    }
+
+    fn page_count(&self) -> usize {
+        self.size.div_ceil(PAGE_SIZE)
+    }
+}
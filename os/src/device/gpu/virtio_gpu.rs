@@ -1,7 +1,103 @@
-use virtio_drivers::transport::mmio::MmioTransport;
+//! VirtIO GPU 设备驱动
+//!
+//! 初始化时读取首个 scanout 的分辨率并建立帧缓冲，之后只在刷新时与设备交互。
+//! 显存是 virtio HAL 分配的一致性 DMA 缓冲区（物理连续、位于直接映射区），
+//! 因此可以直接按页映射给用户态。
 
-use crate::pr_info;
+use alloc::{string::String, sync::Arc};
+use virtio_drivers::device::gpu::VirtIOGpu;
+use virtio_drivers::transport::{InterruptStatus, mmio::MmioTransport};
 
-pub fn init(_transport: MmioTransport<'static>) {
-    pr_info!("[Device] GPU driver (virtio-gpu) is initialized");
+use super::{FRAME_BUFFER, FrameBuffer, FrameBufferInfo, GpuDriver};
+use crate::device::virtio_hal::VirtIOHal;
+use crate::device::{DRIVERS, DeviceType, Driver};
+use crate::mm::address::VA;
+use crate::sync::SpinLock;
+use crate::{pr_info, pr_warn};
+
+/// VirtIO GPU 驱动结构体
+pub struct VirtIOGpuDriver {
+    inner: SpinLock<VirtIOGpu<VirtIOHal, MmioTransport<'static>>>,
+}
+
+impl Driver for VirtIOGpuDriver {
+    fn try_handle_interrupt(&self, _irq: Option<usize>) -> bool {
+        // 请求均以轮询方式完成，这里只负责应答
+        let status = self.inner.lock().ack_interrupt();
+        status.contains(InterruptStatus::QUEUE_INTERRUPT)
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Gpu
+    }
+
+    fn get_id(&self) -> String {
+        String::from("virtio_gpu")
+    }
+
+    fn suspend(&self) {
+        // 挂起前把帧缓冲内容推到屏幕上
+        GpuDriver::flush(self);
+    }
+}
+
+impl GpuDriver for VirtIOGpuDriver {
+    fn flush(&self) {
+        if let Err(e) = self.inner.lock().flush() {
+            pr_warn!("[Device] virtio-gpu flush failed: {:?}", e);
+        }
+    }
+}
+
+/// 初始化 VirtIO GPU 设备驱动
+pub fn init(transport: MmioTransport<'static>) {
+    if FRAME_BUFFER.read().is_some() {
+        pr_warn!("[Device] Extra virtio-gpu ignored, only one framebuffer is supported");
+        return;
+    }
+    let mut gpu = match VirtIOGpu::<VirtIOHal, _>::new(transport) {
+        Ok(gpu) => gpu,
+        Err(e) => {
+            pr_warn!("[Device] Failed to init virtio-gpu: {:?}", e);
+            return;
+        }
+    };
+    let (xres, yres) = match gpu.resolution() {
+        Ok(res) => res,
+        Err(e) => {
+            pr_warn!("[Device] Failed to query virtio-gpu resolution: {:?}", e);
+            return;
+        }
+    };
+    let (vaddr, size) = match gpu.setup_framebuffer() {
+        Ok(buf) => (buf.as_mut_ptr() as usize, buf.len()),
+        Err(e) => {
+            pr_warn!("[Device] Failed to set up virtio-gpu framebuffer: {:?}", e);
+            return;
+        }
+    };
+    let Some(paddr) = VirtIOHal::dma_paddr(VA::from_usize(vaddr)) else {
+        pr_warn!("[Device] virtio-gpu framebuffer is not a DMA buffer");
+        return;
+    };
+    let paddr = paddr.as_usize();
+
+    let info = FrameBufferInfo {
+        xres,
+        yres,
+        bits_per_pixel: 32,
+        line_length: xres * 4,
+    };
+    let driver = Arc::new(VirtIOGpuDriver {
+        inner: SpinLock::new(gpu),
+    });
+    // SAFETY: 显存随 VirtIOGpu 一起由驱动持有，驱动永不释放
+    let fb = unsafe { FrameBuffer::new(info, vaddr, paddr, size, driver.clone()) };
+    DRIVERS.write().push(driver);
+    FRAME_BUFFER.write().replace(Arc::new(fb));
+    pr_info!(
+        "[Device] GPU driver (virtio-gpu) is initialized: {}x{}",
+        xres,
+        yres
+    );
 }
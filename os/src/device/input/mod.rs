@@ -1,5 +1,114 @@
 //! 输入设备模块
 //!
 //! 包含输入设备相关的驱动接口和实现
+//!
+//! 驱动在中断（或读者主动轮询）时把硬件事件转换为 evdev 格式的
+//! [`InputEvent`]，写入每个设备独立的 [`EventRing`]；`/dev/input/eventN`
+//! 字符设备文件从环中取出事件交给用户态。
+//!
+//! 环满时丢弃最旧的事件，并在队尾补一个 `SYN_DROPPED`，与 Linux evdev 一致，
+//! 让客户端知道需要重新同步设备状态。
 
 pub mod virtio_input;
+
+use alloc::{collections::vec_deque::VecDeque, string::String, vec::Vec};
+
+use super::Driver;
+use crate::uapi::input::{EV_SYN, InputEvent, InputId, SYN_DROPPED};
+
+/// 每个输入设备缓冲的事件数（Linux evdev 默认值为 64 的倍数）
+pub const INPUT_EVENT_RING_SIZE: usize = 256;
+
+/// 输入设备驱动接口
+pub trait InputDriver: Driver {
+    /// 设备名称（EVIOCGNAME）
+    fn name(&self) -> String;
+
+    /// 设备标识（EVIOCGID）
+    fn input_id(&self) -> InputId;
+
+    /// 指定事件类型支持的编码位图（EVIOCGBIT）
+    ///
+    /// `ev_type == 0` 时返回支持的事件类型位图。
+    fn event_bits(&self, ev_type: u8) -> Vec<u8>;
+
+    /// 取出一个待处理事件，没有事件时返回 None
+    fn pop_event(&self) -> Option<InputEvent>;
+
+    /// 是否有待读取的事件
+    fn has_events(&self) -> bool;
+}
+
+/// 输入事件环形缓冲区
+pub struct EventRing {
+    events: VecDeque<InputEvent>,
+    capacity: usize,
+}
+
+impl EventRing {
+    /// 创建指定容量的事件环
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(2),
+        }
+    }
+
+    /// 写入一个事件，环满时丢弃最旧的事件并记录 SYN_DROPPED
+    pub fn push(&mut self, event: InputEvent) {
+        if self.events.len() >= self.capacity {
+            // 腾出两个位置：一个给 SYN_DROPPED，一个给新事件
+            self.events.pop_front();
+            self.events.pop_front();
+            self.events
+                .push_back(InputEvent::new(event.time, EV_SYN, SYN_DROPPED, 0));
+        }
+        self.events.push_back(event);
+    }
+
+    /// 取出最旧的事件
+    pub fn pop(&mut self) -> Option<InputEvent> {
+        self.events.pop_front()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uapi::{input::EV_KEY, time::timeval};
+    use crate::{kassert, test_case};
+
+    fn key(code: u16) -> InputEvent {
+        InputEvent::new(timeval::zero(), EV_KEY, code, 1)
+    }
+
+    test_case!(test_event_ring_fifo, {
+        let mut ring = EventRing::new(8);
+        kassert!(ring.is_empty());
+        ring.push(key(1));
+        ring.push(key(2));
+        kassert!(!ring.is_empty());
+        kassert!(ring.pop().map(|e| e.code) == Some(1));
+        kassert!(ring.pop().map(|e| e.code) == Some(2));
+        kassert!(ring.pop().is_none());
+    });
+
+    test_case!(test_event_ring_overflow_marks_dropped, {
+        let mut ring = EventRing::new(4);
+        for code in 0..4 {
+            ring.push(key(code));
+        }
+        ring.push(key(10));
+        kassert!(ring.pop().map(|e| e.code) == Some(2));
+        kassert!(ring.pop().map(|e| e.code) == Some(3));
+        let dropped = ring.pop().unwrap();
+        kassert!(dropped.type_ == EV_SYN && dropped.code == SYN_DROPPED);
+        kassert!(ring.pop().map(|e| e.code) == Some(10));
+        kassert!(ring.is_empty());
+    });
+}
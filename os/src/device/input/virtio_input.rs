@@ -1,7 +1,165 @@
-use virtio_drivers::transport::mmio::MmioTransport;
+//! VirtIO 输入设备驱动（键盘 / 鼠标 / 数位板）
+//!
+//! 设备配置（名称、ID、事件位图）在初始化时一次性读出并缓存，
+//! 运行时只需处理事件队列。事件在中断中搬运到 [`EventRing`]，
+//! 读者在环为空时也会主动轮询一次设备，保证关中断或未接通中断线时仍能取到事件。
 
+use alloc::{format, string::String, sync::Arc, vec, vec::Vec};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use virtio_drivers::device::input::VirtIOInput;
+use virtio_drivers::transport::{InterruptStatus, mmio::MmioTransport};
+
+use super::{EventRing, INPUT_EVENT_RING_SIZE, InputDriver};
+use crate::device::virtio_hal::VirtIOHal;
+use crate::device::{DRIVERS, DeviceType, Driver, INPUT_DRIVERS, IRQ_MANAGER};
 use crate::pr_info;
+use crate::sync::SpinLock;
+use crate::uapi::input::{BUS_VIRTUAL, EV_MAX, EV_SYN, InputEvent, InputId};
+use crate::uapi::time::TimeSpec;
+
+/// 已初始化的 virtio-input 设备计数，用于生成唯一 ID
+static INPUT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// VirtIO 输入设备驱动结构体
+pub struct VirtIOInputDriver {
+    inner: SpinLock<VirtIOInput<VirtIOHal, MmioTransport<'static>>>,
+    events: SpinLock<EventRing>,
+    id: String,
+    name: String,
+    input_id: InputId,
+    /// 按事件类型索引的编码位图，下标 0 为支持的事件类型位图
+    ev_bits: Vec<Vec<u8>>,
+}
+
+impl VirtIOInputDriver {
+    /// 把设备队列中的事件全部搬运到事件环
+    ///
+    /// 返回是否搬运了至少一个事件。
+    fn drain_device(&self) -> bool {
+        let mut inner = self.inner.lock();
+        let mut moved = false;
+        let now = TimeSpec::now().to_timeval();
+        while let Some(ev) = inner.pop_pending_event() {
+            self.events.lock().push(InputEvent::new(
+                now,
+                ev.event_type,
+                ev.code,
+                ev.value as i32,
+            ));
+            moved = true;
+        }
+        moved
+    }
+}
+
+impl Driver for VirtIOInputDriver {
+    fn try_handle_interrupt(&self, _irq: Option<usize>) -> bool {
+        let status = self.inner.lock().ack_interrupt();
+        if !status.contains(InterruptStatus::QUEUE_INTERRUPT) {
+            return false;
+        }
+        if self.drain_device() {
+            crate::kernel::syscall::io::wake_poll_waiters();
+        }
+        true
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Input
+    }
+
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn as_input(&self) -> Option<&dyn InputDriver> {
+        Some(self)
+    }
+}
+
+impl InputDriver for VirtIOInputDriver {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn input_id(&self) -> InputId {
+        self.input_id
+    }
+
+    fn event_bits(&self, ev_type: u8) -> Vec<u8> {
+        self.ev_bits
+            .get(ev_type as usize)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn pop_event(&self) -> Option<InputEvent> {
+        if let Some(ev) = self.events.lock().pop() {
+            return Some(ev);
+        }
+        self.drain_device();
+        self.events.lock().pop()
+    }
+
+    fn has_events(&self) -> bool {
+        !self.events.lock().is_empty() || self.drain_device()
+    }
+}
+
+/// 读取设备支持的事件位图，并合成 EV_SYN 下标处的事件类型位图
+fn query_ev_bits(input: &mut VirtIOInput<VirtIOHal, MmioTransport<'static>>) -> Vec<Vec<u8>> {
+    let mut bits = vec![Vec::new(); EV_MAX as usize + 1];
+    let mut types = vec![0u8; (EV_MAX as usize + 1).div_ceil(8)];
+    types[0] |= 1 << EV_SYN;
+    for ev_type in 1..=EV_MAX as u8 {
+        let Ok(map) = input.ev_bits(ev_type) else {
+            continue;
+        };
+        if map.iter().any(|&b| b != 0) {
+            types[ev_type as usize / 8] |= 1 << (ev_type % 8);
+            bits[ev_type as usize] = map.into_vec();
+        }
+    }
+    bits[0] = types;
+    bits
+}
+
+/// 初始化 VirtIO 输入设备驱动
+pub fn init(transport: MmioTransport<'static>) {
+    let mut input =
+        VirtIOInput::<VirtIOHal, _>::new(transport).expect("failed to init input driver");
+    let index = INPUT_COUNT.fetch_add(1, Ordering::Relaxed);
+
+    let name = input
+        .name()
+        .unwrap_or_else(|_| format!("virtio-input{}", index));
+    let input_id = match input.ids() {
+        Ok(ids) => InputId {
+            bustype: ids.bustype,
+            vendor: ids.vendor,
+            product: ids.product,
+            version: ids.version,
+        },
+        Err(_) => InputId {
+            bustype: BUS_VIRTUAL,
+            ..InputId::default()
+        },
+    };
+    let ev_bits = query_ev_bits(&mut input);
 
-pub fn init(_transport: MmioTransport<'static>) {
-    pr_info!("[Device] Input driver (virtio-input) is initialized");
+    let driver = Arc::new(VirtIOInputDriver {
+        inner: SpinLock::new(input),
+        events: SpinLock::new(EventRing::new(INPUT_EVENT_RING_SIZE)),
+        id: format!("virtio_input{}", index),
+        name,
+        input_id,
+        ev_bits,
+    });
+    DRIVERS.write().push(driver.clone());
+    IRQ_MANAGER.lock().register_all(driver.clone());
+    INPUT_DRIVERS.write().push(driver.clone());
+    pr_info!(
+        "[Device] Input driver (virtio-input) is initialized: {}",
+        driver.name
+    );
 }
@@ -0,0 +1,134 @@
+//! LoongArch 扩展 I/O 中断控制器（EIOINTC）驱动
+//!
+//! EIOINTC 通过 IOCSR 访问，把最多 256 个中断向量汇聚到 CPU 的 HWI 引脚。
+//! 本驱动把所有向量路由到 CPU 0 的 HWI0，挂在根中断管理器的
+//! `SUPERVISOR_EXTERNAL` 上；子中断管理器中的中断号就是向量号。
+
+use super::{super::DRIVERS, IrqManager};
+use crate::arch::SUPERVISOR_EXTERNAL;
+use crate::arch::intr::{iocsr_read32, iocsr_read64, iocsr_write32, iocsr_write64};
+use crate::device::device_tree::{DEVICE_TREE_INTC, DEVICE_TREE_REGISTRY};
+use crate::device::irq::IntcDriver;
+use crate::device::{DeviceType, Driver, IRQ_MANAGER};
+use crate::sync::SpinLock;
+use crate::{pr_info, pr_warn};
+use alloc::string::String;
+use alloc::sync::Arc;
+use fdt::node::FdtNode;
+
+/// 杂项功能寄存器
+const IOCSR_MISC_FUNC: usize = 0x420;
+/// MISC_FUNC: 扩展 I/O 中断使能
+const MISC_FUNC_EXT_IOI_EN: u64 = 1 << 48;
+
+/// 每 32 个向量一组映射到 CPU 中断引脚，每组 1 字节
+const EIOINTC_REG_IPMAP: usize = 0x14c0;
+/// 向量使能位图
+const EIOINTC_REG_ENABLE: usize = 0x1600;
+/// 向量轮转分发位图
+const EIOINTC_REG_BOUNCE: usize = 0x1680;
+/// 当前 CPU 的向量挂起位图，写 1 清除
+const EIOINTC_REG_ISR: usize = 0x1800;
+/// 每个向量 1 字节的目标 CPU 路由
+const EIOINTC_REG_ROUTE: usize = 0x1c00;
+
+/// 向量数量
+const EIOINTC_VECTORS: usize = 256;
+/// IPMAP 字节：路由到 CPU 中断引脚 0（HWI0）
+const IPMAP_HWI0: u32 = 0x0101_0101;
+/// ROUTE 字节：路由到 CPU 0
+const ROUTE_CPU0: u32 = 0x0101_0101;
+
+/// 扩展 I/O 中断控制器
+pub struct Eiointc {
+    manager: SpinLock<IrqManager>,
+}
+
+impl Driver for Eiointc {
+    /// 逐组读取挂起位图，清除后分发给对应向量的驱动
+    fn try_handle_interrupt(&self, _irq: Option<usize>) -> bool {
+        let mut handled = false;
+        for group in 0..EIOINTC_VECTORS / 64 {
+            let reg = EIOINTC_REG_ISR + group * 8;
+            let mut pending = unsafe { iocsr_read64(reg) };
+            if pending == 0 {
+                continue;
+            }
+            unsafe { iocsr_write64(reg, pending) };
+
+            let manager = self.manager.lock();
+            while pending != 0 {
+                let bit = pending.trailing_zeros() as usize;
+                pending &= pending - 1;
+                handled |= manager.try_handle_interrupt(Some(group * 64 + bit));
+            }
+        }
+        handled
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Intc
+    }
+
+    fn get_id(&self) -> String {
+        String::from("eiointc")
+    }
+}
+
+impl IntcDriver for Eiointc {
+    /// 使能向量 `irq` 并注册其处理程序
+    fn register_local_irq(&self, irq: usize, driver: Arc<dyn Driver>) {
+        if irq >= EIOINTC_VECTORS {
+            pr_warn!("[Device] EIOINTC vector {} out of range", irq);
+            return;
+        }
+        let reg = EIOINTC_REG_ENABLE + irq / 32 * 4;
+        unsafe { iocsr_write32(reg, iocsr_read32(reg) | (1 << (irq % 32))) };
+        self.manager.lock().register_irq(irq, driver);
+    }
+}
+
+/// 初始化设备树中的 EIOINTC
+/// # 参数：
+/// * `dt` - 设备树节点
+pub fn init_dt(dt: &FdtNode) {
+    unsafe {
+        iocsr_write64(
+            IOCSR_MISC_FUNC,
+            iocsr_read64(IOCSR_MISC_FUNC) | MISC_FUNC_EXT_IOI_EN,
+        );
+        for i in 0..EIOINTC_VECTORS / 32 / 4 {
+            iocsr_write32(EIOINTC_REG_IPMAP + i * 4, IPMAP_HWI0);
+        }
+        for i in 0..EIOINTC_VECTORS / 4 {
+            iocsr_write32(EIOINTC_REG_ROUTE + i * 4, ROUTE_CPU0);
+        }
+        // 向量默认全部关闭，注册时再逐个打开
+        for i in 0..EIOINTC_VECTORS / 32 {
+            iocsr_write32(EIOINTC_REG_ENABLE + i * 4, 0);
+            iocsr_write32(EIOINTC_REG_BOUNCE + i * 4, 0);
+        }
+    }
+
+    let eiointc = Arc::new(Eiointc {
+        manager: SpinLock::new(IrqManager::new(false)),
+    });
+    DRIVERS.write().push(eiointc.clone());
+    IRQ_MANAGER
+        .lock()
+        .register_irq(SUPERVISOR_EXTERNAL, eiointc.clone());
+    if let Some(phandle) = dt.property("phandle").and_then(|p| p.as_usize()) {
+        DEVICE_TREE_INTC.lock().insert(phandle as u32, eiointc);
+    }
+    pr_info!(
+        "[Device] EIOINTC initialized from device tree node {}",
+        dt.name
+    );
+}
+
+/// 注册 EIOINTC 驱动初始化函数
+pub fn driver_init() {
+    let mut registry = DEVICE_TREE_REGISTRY.lock();
+    registry.insert("loongson,ls2k2000-eiointc", init_dt);
+    registry.insert("loongson,ls2k0500-eiointc", init_dt);
+}
@@ -8,6 +8,10 @@ use alloc::{
     vec::Vec,
 };
 
+#[cfg(target_arch = "loongarch64")]
+pub mod eiointc;
+#[cfg(target_arch = "loongarch64")]
+pub mod pch_pic;
 pub mod plic;
 
 use crate::{arch::enable_irq, device::Driver};
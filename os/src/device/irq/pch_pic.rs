@@ -0,0 +1,141 @@
+//! LoongArch 平台中断控制器（LS7A PCH-PIC）驱动
+//!
+//! PCH-PIC 收集 64 条外设中断线，按 `HTMSI_VEC` 把每条线转换为上级 EIOINTC 的
+//! 中断向量（`loongson,pic-base-vec` + 线号）。本驱动只使用电平触发、高有效。
+
+use super::{super::DRIVERS, IrqManager};
+use crate::device::device_tree::{DEVICE_TREE_INTC, DEVICE_TREE_REGISTRY, intc_of};
+use crate::device::irq::IntcDriver;
+use crate::device::{DeviceType, Driver};
+use crate::kernel::current_memory_space;
+use crate::mm::address::{PA, VA};
+use crate::{pr_info, pr_warn};
+use crate::{sync::SpinLock, util::read, util::write};
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use fdt::node::FdtNode;
+
+/// 中断屏蔽位图，置 1 屏蔽
+const PCH_PIC_MASK: usize = 0x20;
+/// HT 消息中断使能位图
+const PCH_PIC_HTMSI_EN: usize = 0x40;
+/// 触发方式位图，置 1 为边沿触发
+const PCH_PIC_EDGE: usize = 0x60;
+/// 每条中断线 1 字节的向量号
+const PCH_PIC_HTMSI_VEC: usize = 0x200;
+/// 极性位图，置 1 为低电平有效
+const PCH_PIC_POL: usize = 0x3e0;
+
+/// 中断线数量
+const PCH_PIC_IRQS: usize = 64;
+
+/// 平台中断控制器
+pub struct PchPic {
+    base: VA,
+    /// 第 0 条中断线对应的上级向量
+    vec_base: usize,
+    manager: SpinLock<IrqManager>,
+}
+
+impl PchPic {
+    /// 修改 64 位位图寄存器中 `irq` 对应的位（按 32 位访问）
+    fn update_bit(&self, reg: usize, irq: usize, set: bool) {
+        let addr = self.base.as_usize() + reg + irq / 32 * 4;
+        let bit = 1u32 << (irq % 32);
+        let value: u32 = read(addr);
+        write(addr, if set { value | bit } else { value & !bit });
+    }
+}
+
+impl Driver for PchPic {
+    /// 上级传入的是向量号，换算回中断线后分发
+    fn try_handle_interrupt(&self, irq: Option<usize>) -> bool {
+        let Some(line) = irq.and_then(|vec| vec.checked_sub(self.vec_base)) else {
+            return false;
+        };
+        self.manager.lock().try_handle_interrupt(Some(line))
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Intc
+    }
+
+    fn get_id(&self) -> String {
+        format!("pch_pic_{:#x}", self.base.as_usize())
+    }
+}
+
+impl IntcDriver for PchPic {
+    /// 把中断线 `irq` 配置为电平触发并解除屏蔽
+    fn register_local_irq(&self, irq: usize, driver: Arc<dyn Driver>) {
+        if irq >= PCH_PIC_IRQS {
+            pr_warn!("[Device] PCH-PIC irq {} out of range", irq);
+            return;
+        }
+        write::<u8>(
+            self.base.as_usize() + PCH_PIC_HTMSI_VEC + irq,
+            (self.vec_base + irq) as u8,
+        );
+        self.update_bit(PCH_PIC_EDGE, irq, false);
+        self.update_bit(PCH_PIC_POL, irq, false);
+        self.update_bit(PCH_PIC_HTMSI_EN, irq, true);
+        self.manager.lock().register_irq(irq, driver);
+        self.update_bit(PCH_PIC_MASK, irq, false);
+    }
+}
+
+/// 初始化设备树中的 PCH-PIC
+/// # 参数：
+/// * `dt` - 设备树节点
+pub fn init_dt(dt: &FdtNode) {
+    let Some(reg) = dt.reg().and_then(|mut reg| reg.next()) else {
+        pr_warn!(
+            "[Device] PCH-PIC device tree node {} has no 'reg' property",
+            dt.name
+        );
+        return;
+    };
+    let Some(parent) = intc_of(dt) else {
+        pr_warn!("[Device] PCH-PIC {} has no interrupt parent", dt.name);
+        return;
+    };
+    let size = reg.size.unwrap_or(0x400);
+    let vaddr = current_memory_space()
+        .lock()
+        .map_mmio(PA::from_usize(reg.starting_address as usize), size)
+        .ok()
+        .expect("Failed to map MMIO region");
+    let vec_base = dt
+        .property("loongson,pic-base-vec")
+        .and_then(|p| p.as_usize())
+        .unwrap_or(0);
+
+    // 初始全部屏蔽，注册时再逐条打开
+    write::<u32>(vaddr.as_usize() + PCH_PIC_MASK, u32::MAX);
+    write::<u32>(vaddr.as_usize() + PCH_PIC_MASK + 4, u32::MAX);
+
+    let pic = Arc::new(PchPic {
+        base: vaddr,
+        vec_base,
+        manager: SpinLock::new(IrqManager::new(false)),
+    });
+    DRIVERS.write().push(pic.clone());
+    for line in 0..PCH_PIC_IRQS {
+        parent.register_local_irq(vec_base + line, pic.clone());
+    }
+    if let Some(phandle) = dt.property("phandle").and_then(|p| p.as_usize()) {
+        DEVICE_TREE_INTC.lock().insert(phandle as u32, pic);
+    }
+    pr_info!(
+        "[Device] PCH-PIC initialized from device tree node {}",
+        dt.name
+    );
+}
+
+/// 注册 PCH-PIC 驱动初始化函数
+pub fn driver_init() {
+    DEVICE_TREE_REGISTRY
+        .lock()
+        .insert("loongson,pch-pic-1.0", init_dt);
+}
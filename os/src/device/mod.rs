@@ -4,6 +4,7 @@
 pub mod bus;
 pub mod block;
 pub mod console;
+pub mod dma;
 pub mod gpu;
 pub mod input;
 pub mod irq;
@@ -11,6 +12,7 @@ pub mod net;
 pub mod rtc;
 pub mod serial;
 pub mod virtio_hal;
+pub mod watchdog;
 
 pub mod device_tree;
 
@@ -19,10 +21,12 @@ use crate::sync::SpinLock;
 use alloc::sync::Arc;
 pub use block::ram_disk::RamDisk;
 
+use crate::device::input::InputDriver;
 use crate::device::rtc::RtcDriver;
 
 use crate::device::serial::SerialDriver;
 use crate::device::{block::BlockDriver, net::net_device::NetDevice};
+use crate::mm::memory_space::mapping_area::SharedPages;
 
 use alloc::{string::String, vec::Vec};
 use lazy_static::lazy_static;
@@ -91,10 +95,28 @@ pub trait Driver: Send + Sync {
         None
     }
 
+    /// 将驱动程序转换为输入设备驱动程序（如果适用）
+    fn as_input(&self) -> Option<&dyn InputDriver> {
+        None
+    }
+
     /// 将驱动程序转换为串口驱动程序（如果适用）
     fn as_serial(&self) -> Option<&dyn serial::SerialDriver> {
         None
     }
+
+    /// 可以通过设备文件 mmap 到用户空间的设备内存（MMIO 寄存器、显存等）
+    ///
+    /// MMIO 区域通常用 [`PhysPages`](crate::mm::memory_space::mapping_area::PhysPages) 描述。
+    fn mmap_region(&self) -> Option<Arc<dyn SharedPages>> {
+        None
+    }
+
+    /// 进入 suspend-to-idle 前调用，驱动应完成所有未决的输出
+    fn suspend(&self) {}
+
+    /// 从 suspend-to-idle 唤醒后调用
+    fn resume(&self) {}
 }
 
 lazy_static! {
@@ -102,6 +124,7 @@ lazy_static! {
     pub static ref DRIVERS: RwLock<Vec<Arc<dyn Driver>>> = RwLock::new(Vec::new());
     pub static ref BLK_DRIVERS: RwLock<Vec<Arc<dyn BlockDriver>>> = RwLock::new(Vec::new());
     pub static ref RTC_DRIVERS: RwLock<Vec<Arc<dyn RtcDriver>>> = RwLock::new(Vec::new());
+    pub static ref INPUT_DRIVERS: RwLock<Vec<Arc<dyn InputDriver>>> = RwLock::new(Vec::new());
     pub static ref SERIAL_DRIVERS: SpinLock<Vec<Arc<dyn SerialDriver>>> = SpinLock::new(Vec::new());
     pub static ref IRQ_MANAGER: SpinLock<irq::IrqManager> = SpinLock::new(irq::IrqManager::new(true));
 }
@@ -1,10 +1,14 @@
 use alloc::collections::VecDeque;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::sync::SpinLock;
 
-use super::net_device::{NetDevice, NetDeviceError};
+use super::net_device::{NetDevice, NetDeviceError, check_mtu};
+
+/// 回环设备的最大 MTU
+const MAX_MTU: usize = 65535;
 
 /// Explicit loopback network device.
 ///
@@ -14,7 +18,7 @@ pub struct LoopbackNetDevice {
     device_id: usize,
     rx_queue: SpinLock<VecDeque<Vec<u8>>>,
     mac: [u8; 6],
-    mtu: usize,
+    mtu: AtomicUsize,
 }
 
 impl LoopbackNetDevice {
@@ -23,7 +27,7 @@ impl LoopbackNetDevice {
             device_id,
             rx_queue: SpinLock::new(VecDeque::new()),
             mac: [0x02, 0x00, 0x00, 0x00, 0x00, 0x7f],
-            mtu: 65535,
+            mtu: AtomicUsize::new(MAX_MTU),
         })
     }
 }
@@ -48,7 +52,17 @@ impl NetDevice for LoopbackNetDevice {
     }
 
     fn mtu(&self) -> usize {
-        self.mtu
+        self.mtu.load(Ordering::Relaxed)
+    }
+
+    fn max_mtu(&self) -> usize {
+        MAX_MTU
+    }
+
+    fn set_mtu(&self, mtu: usize) -> Result<(), NetDeviceError> {
+        check_mtu(mtu, MAX_MTU)?;
+        self.mtu.store(mtu, Ordering::Relaxed);
+        Ok(())
     }
 
     fn name(&self) -> &str {
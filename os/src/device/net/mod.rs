@@ -7,9 +7,12 @@ use crate::sync::SpinLock;
 use alloc::{sync::Arc, vec::Vec};
 
 pub mod loopback;
+pub mod napi;
 pub mod net_device;
 pub mod null_net;
 pub mod virtio_net;
+pub mod virtio_net_mq;
+pub mod virtqueue;
 
 use lazy_static::lazy_static;
 lazy_static! {
@@ -0,0 +1,83 @@
+//! NAPI 风格的收包轮询
+//!
+//! 网卡中断只负责应答并屏蔽后续中断，随后把收包交给工作队列中的网络轮询（下半部）。
+//! 下半部每轮至多从网卡收取 [`NAPI_WEIGHT`] 个数据包；一轮结束时接收队列仍非空则保持
+//! 中断屏蔽并调度下一轮，排空后才重新打开中断。高负载下网卡因此退化为轮询，
+//! 不会每个数据包触发一次中断。
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::get_net_devices;
+
+/// 每轮轮询从一块网卡收取的最大数据包数
+pub const NAPI_WEIGHT: usize = 64;
+
+/// 单块网卡的轮询调度状态
+pub struct Napi {
+    scheduled: AtomicBool,
+}
+
+impl Napi {
+    /// 创建未调度的状态
+    pub const fn new() -> Self {
+        Self {
+            scheduled: AtomicBool::new(false),
+        }
+    }
+
+    /// 标记为已调度，返回此前是否未调度
+    ///
+    /// 返回 `true` 时调用者应屏蔽网卡中断并请求一次网络轮询。
+    pub fn schedule(&self) -> bool {
+        self.scheduled
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// 结束调度，返回此前是否已调度
+    ///
+    /// 返回 `true` 时调用者应重新打开网卡中断。
+    pub fn complete(&self) -> bool {
+        self.scheduled.swap(false, Ordering::AcqRel)
+    }
+
+    /// 是否已调度
+    pub fn is_scheduled(&self) -> bool {
+        self.scheduled.load(Ordering::Acquire)
+    }
+}
+
+impl Default for Napi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 一轮网络轮询结束后调用：仍有数据包待收的网卡继续轮询，其余重新打开中断
+pub fn poll_complete() {
+    let mut again = false;
+    for dev in get_net_devices() {
+        again |= dev.napi_complete();
+    }
+    if again {
+        crate::net::socket::request_network_poll();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_napi_schedule_complete, {
+        let napi = Napi::new();
+        kassert!(!napi.is_scheduled());
+        kassert!(napi.schedule());
+        // 重复的中断不会再次调度
+        kassert!(!napi.schedule());
+        kassert!(napi.is_scheduled());
+        kassert!(napi.complete());
+        kassert!(!napi.complete());
+        kassert!(!napi.is_scheduled());
+    });
+}
@@ -7,6 +7,19 @@ pub enum NetDeviceError {
     QueueFull,
     QueueEmpty,
     AllocationFailed,
+    InvalidArgument,
+}
+
+/// 以太网允许的最小 MTU
+pub const ETH_MIN_MTU: usize = 68;
+
+/// 检查 `mtu` 是否在 [`ETH_MIN_MTU`] 到 `max_mtu` 之间
+pub fn check_mtu(mtu: usize, max_mtu: usize) -> Result<(), NetDeviceError> {
+    if (ETH_MIN_MTU..=max_mtu).contains(&mtu) {
+        Ok(())
+    } else {
+        Err(NetDeviceError::InvalidArgument)
+    }
 }
 
 /// 网络设备接口
@@ -23,30 +36,91 @@ pub trait NetDevice: Send + Sync {
     /// 获取最大传输单元(MTU)
     fn mtu(&self) -> usize;
 
+    /// 设备支持的最大 MTU
+    fn max_mtu(&self) -> usize {
+        self.mtu()
+    }
+
+    /// 设置 MTU，取值须在 [`ETH_MIN_MTU`] 到 [`Self::max_mtu`] 之间
+    fn set_mtu(&self, _mtu: usize) -> Result<(), NetDeviceError> {
+        Err(NetDeviceError::NotSupported)
+    }
+
     /// 获取设备名称
     fn name(&self) -> &str;
 
     /// 获取MAC地址
     fn mac_address(&self) -> [u8; 6];
+
+    /// 处理设备中断，返回中断是否属于本设备
+    ///
+    /// 有新数据包时屏蔽后续中断并调度 NAPI 轮询，见 [`super::napi`]。
+    fn handle_interrupt(&self) -> bool {
+        false
+    }
+
+    /// 一轮 NAPI 轮询结束
+    ///
+    /// 接收队列仍有数据包时返回 `true` 要求继续轮询，否则重新打开中断并返回 `false`。
+    fn napi_complete(&self) -> bool {
+        false
+    }
 }
 
+use super::napi::Napi;
+use super::virtio_net_mq::{self, MultiQueueNet};
 use crate::device::virtio_hal::VirtIOHal;
 use crate::sync::SpinLock;
 use alloc::boxed::Box;
 use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use virtio_drivers::{
     device::net::{TxBuffer, VirtIONet},
-    transport::Transport,
+    transport::{InterruptStatus, Transport},
 };
 
-/// 使用 virtio-drivers 0.12.0 实现的 Virtio 网络设备
+/// 收发队列深度
+const QUEUE_SIZE: usize = 256;
+
+/// 接收缓冲区大小，需容纳 virtio-net 头部和完整的以太网帧
+const RX_BUFFER_LEN: usize = 2048;
+
+/// 以太网头部长度
+const ETH_HEADER_LEN: usize = 14;
+
+/// virtio-net 数据包头部长度
+const VIRTIO_NET_HDR_LEN: usize = 12;
+
+/// 默认以太网 MTU
+const DEFAULT_MTU: usize = 1500;
+
+/// VIRTIO_NET_F_MTU：配置空间提供 MTU
+const VIRTIO_NET_F_MTU: u64 = 1 << 3;
+
+/// 配置空间中 `mtu` 的偏移
+const CONFIG_MTU: usize = 10;
+
+/// 收发队列的实现
+enum Backend<T: Transport> {
+    /// virtio-drivers 驱动的第 0 对收发队列
+    Single(SpinLock<Box<VirtIONet<VirtIOHal, T, QUEUE_SIZE>>>),
+    /// 协商了 VIRTIO_NET_F_MQ 的多对收发队列，见 [`super::virtio_net_mq`]
+    Multi(MultiQueueNet<T>),
+}
+
+/// Virtio 网络设备
+///
+/// 设备支持多队列且系统有多个 CPU 时启用多对收发队列，否则（或多队列初始化失败时）
+/// 退回 virtio-drivers 0.12.0 的单队列驱动。
 pub struct VirtioNetDevice<T: Transport + Send + Sync> {
-    // 使用SpinLock包装UnsafeCell以实现线程安全的内部可变性
-    virtio_net: SpinLock<Option<Box<VirtIONet<VirtIOHal, T, 256>>>>,
+    backend: Backend<T>,
+    napi: Napi,
     device_id: usize,
     name: &'static str,
     mac: [u8; 6],
-    mtu: usize,
+    mtu: AtomicUsize,
+    /// 设备提供的 MTU，也是可设置的上限
+    max_mtu: usize,
 }
 
 impl<T: Transport + Send + Sync> VirtioNetDevice<T> {
@@ -55,61 +129,109 @@ impl<T: Transport + Send + Sync> VirtioNetDevice<T> {
     /// # 参数
     /// * `transport` - VirtIO 设备传输层
     /// * `device_id` - 设备标识符
-    pub fn new(transport: T, device_id: usize) -> Result<Arc<Self>, NetDeviceError> {
-        let virtio_net = VirtIONet::<VirtIOHal, T, 256>::new(transport, 0)
-            .map_err(|_| NetDeviceError::DeviceNotReady)?;
+    pub fn new(mut transport: T, device_id: usize) -> Result<Arc<Self>, NetDeviceError> {
+        // 初始化前从配置空间读取可选参数
+        let features = transport.read_device_features();
+        let mtu = if features & VIRTIO_NET_F_MTU != 0 {
+            transport
+                .read_config_space::<u16>(CONFIG_MTU)
+                .map(usize::from)
+                .unwrap_or(DEFAULT_MTU)
+        } else {
+            DEFAULT_MTU
+        };
+        // 接收缓冲区放不下完整帧时退回默认 MTU
+        let mtu = mtu.min(RX_BUFFER_LEN - ETH_HEADER_LEN - VIRTIO_NET_HDR_LEN);
 
-        // 从 VirtIONet 中获取 MAC 地址和 MTU
+        if virtio_net_mq::supports_multiqueue(features) {
+            let max_pairs = virtio_net_mq::max_queue_pairs(&transport);
+            let pairs = virtio_net_mq::wanted_queue_pairs(max_pairs);
+            if pairs > 1 {
+                match MultiQueueNet::new(transport, pairs, max_pairs, RX_BUFFER_LEN) {
+                    Ok(multi) => {
+                        let mac = multi.mac_address();
+                        let backend = Backend::Multi(multi);
+                        return Ok(Self::with_backend(backend, device_id, mac, mtu));
+                    }
+                    Err((t, e)) => {
+                        crate::pr_warn!(
+                            "[Device] virtio-net multiqueue setup failed ({:?}), using 1 queue pair",
+                            e
+                        );
+                        transport = t;
+                    }
+                }
+            }
+        }
+
+        // 单队列驱动的初始化会先复位设备，多队列失败留下的状态随之清除
+        let virtio_net = VirtIONet::<VirtIOHal, T, QUEUE_SIZE>::new(transport, RX_BUFFER_LEN)
+            .map_err(|_| NetDeviceError::DeviceNotReady)?;
         let mac = virtio_net.mac_address();
-        let mtu = 1500; // 默认以太网 MTU
+        let backend = Backend::Single(SpinLock::new(Box::new(virtio_net)));
+        Ok(Self::with_backend(backend, device_id, mac, mtu))
+    }
 
-        Ok(Arc::new(Self {
-            virtio_net: SpinLock::new(Some(Box::new(virtio_net))),
+    fn with_backend(backend: Backend<T>, device_id: usize, mac: [u8; 6], mtu: usize) -> Arc<Self> {
+        Arc::new(Self {
+            backend,
+            napi: Napi::new(),
             device_id,
             name: "virtio-net",
             mac,
-            mtu,
-        }))
+            mtu: AtomicUsize::new(mtu),
+            max_mtu: mtu,
+        })
+    }
+
+    /// 正在使用的收发队列对数
+    pub fn queue_pairs(&self) -> usize {
+        match &self.backend {
+            Backend::Single(_) => 1,
+            Backend::Multi(multi) => multi.queue_pairs(),
+        }
     }
 }
 
 impl<T: Transport + Send + Sync> NetDevice for VirtioNetDevice<T> {
     /// 发送数据包
     fn send(&self, packet: &[u8]) -> Result<(), NetDeviceError> {
-        if packet.len() > self.mtu {
-            // 18 字节用于以太网头部和尾部
+        if packet.len() > self.mtu() + ETH_HEADER_LEN {
             return Err(NetDeviceError::QueueFull);
         }
 
-        // 使用SpinLock的RAII模式，自动处理锁的获取和释放
-        if let Some(ref mut virtio_net) = *self.virtio_net.lock() {
-            let tx_buffer = TxBuffer::from(packet);
-            match virtio_net.send(tx_buffer) {
-                Ok(_) => Ok(()),
-                Err(_) => Err(NetDeviceError::QueueFull),
+        match &self.backend {
+            Backend::Single(virtio_net) => {
+                let tx_buffer = TxBuffer::from(packet);
+                virtio_net
+                    .lock()
+                    .send(tx_buffer)
+                    .map_err(|_| NetDeviceError::QueueFull)
             }
-        } else {
-            Err(NetDeviceError::DeviceNotReady)
+            Backend::Multi(multi) => multi.send(packet),
         }
     }
 
     /// 接收数据包
+    ///
+    /// 数据包复制出来后立即把接收缓冲区交还给设备。
     fn receive(&self, buf: &mut [u8]) -> Result<usize, NetDeviceError> {
-        // 使用SpinLock的RAII模式，自动处理锁的获取和释放
-        if let Some(ref mut virtio_net) = *self.virtio_net.lock() {
-            match virtio_net.receive() {
-                Ok(rx_buffer) => {
-                    // 使用packet()方法获取实际的网络数据包（不包括头部）
-                    let packet = rx_buffer.packet();
-                    let actual_len = core::cmp::min(packet.len(), buf.len());
-                    buf[..actual_len].copy_from_slice(&packet[..actual_len]);
-                    Ok(actual_len)
-                }
-                Err(_) => Err(NetDeviceError::QueueEmpty),
-            }
-        } else {
-            Err(NetDeviceError::DeviceNotReady)
+        let virtio_net = match &self.backend {
+            Backend::Single(virtio_net) => virtio_net,
+            Backend::Multi(multi) => return multi.receive(buf),
+        };
+        let mut virtio_net = virtio_net.lock();
+        let rx_buffer = virtio_net
+            .receive()
+            .map_err(|_| NetDeviceError::QueueEmpty)?;
+        // 使用packet()方法获取实际的网络数据包（不包括头部）
+        let packet = rx_buffer.packet();
+        let actual_len = core::cmp::min(packet.len(), buf.len());
+        buf[..actual_len].copy_from_slice(&packet[..actual_len]);
+        if let Err(e) = virtio_net.recycle_rx_buffer(rx_buffer) {
+            crate::pr_warn!("[Device] virtio-net failed to recycle rx buffer: {:?}", e);
         }
+        Ok(actual_len)
     }
 
     /// 获取设备标识符
@@ -119,7 +241,17 @@ impl<T: Transport + Send + Sync> NetDevice for VirtioNetDevice<T> {
 
     /// 获取最大传输单元(MTU)
     fn mtu(&self) -> usize {
-        self.mtu
+        self.mtu.load(Ordering::Relaxed)
+    }
+
+    fn max_mtu(&self) -> usize {
+        self.max_mtu
+    }
+
+    fn set_mtu(&self, mtu: usize) -> Result<(), NetDeviceError> {
+        check_mtu(mtu, self.max_mtu)?;
+        self.mtu.store(mtu, Ordering::Relaxed);
+        Ok(())
     }
 
     /// 获取设备名称
@@ -131,4 +263,47 @@ impl<T: Transport + Send + Sync> NetDevice for VirtioNetDevice<T> {
     fn mac_address(&self) -> [u8; 6] {
         self.mac
     }
+
+    fn handle_interrupt(&self) -> bool {
+        let virtio_net = match &self.backend {
+            Backend::Single(virtio_net) => virtio_net,
+            Backend::Multi(multi) => return multi.handle_interrupt(&self.napi),
+        };
+        let mut virtio_net = virtio_net.lock();
+        let status = virtio_net.ack_interrupt();
+        if !status.contains(InterruptStatus::QUEUE_INTERRUPT) {
+            return !status.is_empty();
+        }
+        let scheduled = self.napi.schedule();
+        if scheduled {
+            virtio_net.disable_interrupts();
+        }
+        drop(virtio_net);
+        if scheduled {
+            crate::net::socket::request_network_poll();
+        }
+        true
+    }
+
+    fn napi_complete(&self) -> bool {
+        let virtio_net = match &self.backend {
+            Backend::Single(virtio_net) => virtio_net,
+            Backend::Multi(multi) => return multi.napi_complete(&self.napi),
+        };
+        let mut virtio_net = virtio_net.lock();
+        if !self.napi.is_scheduled() {
+            return false;
+        }
+        if virtio_net.can_recv() {
+            return true;
+        }
+        self.napi.complete();
+        virtio_net.enable_interrupts();
+        // 重新打开中断前到达的数据包不会再触发中断
+        if virtio_net.can_recv() && self.napi.schedule() {
+            virtio_net.disable_interrupts();
+            return true;
+        }
+        false
+    }
 }
@@ -1,6 +1,10 @@
 use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
-use super::net_device::{NetDevice, NetDeviceError};
+use super::net_device::{NetDevice, NetDeviceError, check_mtu};
+
+/// 空设备的最大 MTU（标准以太网）
+const MAX_MTU: usize = 1500;
 
 /// 一个“空”网络设备：不收包、不发包（发送直接丢弃），仅用于让 smoltcp 能初始化起来。
 ///
@@ -10,7 +14,7 @@ pub struct NullNetDevice {
     device_id: usize,
     name: &'static str,
     mac: [u8; 6],
-    mtu: usize,
+    mtu: AtomicUsize,
 }
 
 impl NullNetDevice {
@@ -20,7 +24,7 @@ impl NullNetDevice {
             name: "null-net",
             // locally administered MAC
             mac: [0x02, 0x00, 0x00, 0x00, 0x00, 0x01],
-            mtu: 1500,
+            mtu: AtomicUsize::new(MAX_MTU),
         })
     }
 }
@@ -40,7 +44,17 @@ impl NetDevice for NullNetDevice {
     }
 
     fn mtu(&self) -> usize {
-        self.mtu
+        self.mtu.load(Ordering::Relaxed)
+    }
+
+    fn max_mtu(&self) -> usize {
+        MAX_MTU
+    }
+
+    fn set_mtu(&self, mtu: usize) -> Result<(), NetDeviceError> {
+        check_mtu(mtu, MAX_MTU)?;
+        self.mtu.store(mtu, Ordering::Relaxed);
+        Ok(())
     }
 
     fn name(&self) -> &str {
@@ -1,4 +1,4 @@
-use virtio_drivers::transport::{mmio::MmioTransport, pci::PciTransport};
+use virtio_drivers::transport::{Transport, mmio::MmioTransport, pci::PciTransport};
 
 use crate::{
     device::net::net_device::{NetDeviceError, VirtioNetDevice},
@@ -13,39 +13,17 @@ lazy_static! {
 
 pub fn init(transport: MmioTransport<'static>) {
     pr_info!("[Device] Initializing network driver (virtio-net)");
-
-    // 获取设备ID
-    let device_id = {
-        let mut count = NET_DEVICE_COUNT.lock();
-        let id = *count;
-        *count += 1;
-        id
-    };
-    pr_info!("[Device] Find VirtioNetDevice with ID: {}", device_id);
-
-    // 创建VirtioNetDevice
-    match VirtioNetDevice::new(transport, device_id) {
-        Ok(virtio_device) => {
-            pr_info!("[Device] VirtioNetDevice created with ID: {}", device_id);
-            let network_interface = crate::net::register_net_device(virtio_device);
-
-            pr_info!(
-                "[Device] Network interface {} initialized successfully",
-                network_interface.name()
-            );
-        }
-        Err(NetDeviceError::DeviceNotReady) => {
-            pr_info!("[Device] VirtioNetDevice not ready; skipping");
-        }
-        Err(e) => {
-            pr_warn!("[Device] Failed to initialize VirtioNetDevice: {:?}", e);
-        }
-    }
+    probe(transport);
 }
 
 pub fn init_pci(transport: PciTransport) {
     pr_info!("[Device] Initializing network driver (virtio-net-pci)");
+    probe(transport);
+}
 
+/// 创建设备并注册到网络子系统
+fn probe<T: Transport + Send + Sync + 'static>(transport: T) {
+    // 获取设备ID
     let device_id = {
         let mut count = NET_DEVICE_COUNT.lock();
         let id = *count;
@@ -56,7 +34,11 @@ pub fn init_pci(transport: PciTransport) {
 
     match VirtioNetDevice::new(transport, device_id) {
         Ok(virtio_device) => {
-            pr_info!("[Device] VirtioNetDevice created with ID: {}", device_id);
+            pr_info!(
+                "[Device] VirtioNetDevice created with ID: {}, {} queue pair(s)",
+                device_id,
+                virtio_device.queue_pairs()
+            );
             let network_interface = crate::net::register_net_device(virtio_device);
 
             pr_info!(
@@ -0,0 +1,334 @@
+//! 多队列 virtio-net
+//!
+//! 设备同时提供 VIRTIO_NET_F_MQ 和 VIRTIO_NET_F_CTRL_VQ 时，驱动自行初始化设备：
+//! 建立多对收发队列和控制队列，再用 `VIRTIO_NET_CTRL_MQ_VQ_PAIRS_SET` 命令启用这些队列对。
+//! 发送按当前 CPU 选择队列对，接收在各队列对之间轮流取包。
+//! 初始化在设备进入 DRIVER_OK 之前失败时，调用者改用 virtio-drivers 的单队列驱动重新初始化；
+//! 控制命令被设备拒绝时只使用第 0 对队列。
+
+use super::napi::Napi;
+use super::net_device::NetDeviceError;
+use super::virtqueue::SplitQueue;
+use crate::sync::SpinLock;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use virtio_drivers::transport::{DeviceStatus, InterruptStatus, Transport};
+
+bitflags::bitflags! {
+    /// 多队列模式下协商的特性
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Features: u64 {
+        /// 配置空间提供 MTU
+        const MTU = 1 << 3;
+        /// 配置空间提供 MAC 地址
+        const MAC = 1 << 5;
+        /// 设备提供控制队列
+        const CTRL_VQ = 1 << 17;
+        /// 设备支持多队列
+        const MQ = 1 << 22;
+        /// 新式设备
+        const VERSION_1 = 1 << 32;
+    }
+}
+
+/// 配置空间中 `mac` 的偏移
+const CONFIG_MAC: usize = 0;
+
+/// 配置空间中 `max_virtqueue_pairs` 的偏移
+const CONFIG_MAX_VIRTQUEUE_PAIRS: usize = 8;
+
+/// 最多启用的队列对数
+const MAX_QUEUE_PAIRS: usize = 4;
+
+/// 每个收发队列的深度
+const QUEUE_SIZE: u16 = 64;
+
+/// 控制队列深度，一条命令占两个描述符
+const CTRL_QUEUE_SIZE: u16 = 2;
+
+/// 控制队列描述符缓冲区大小
+const CTRL_BUFFER_LEN: usize = 8;
+
+/// VIRTIO_NET_CTRL_MQ 命令类
+const VIRTIO_NET_CTRL_MQ: u8 = 4;
+
+/// VIRTIO_NET_CTRL_MQ_VQ_PAIRS_SET 命令
+const VIRTIO_NET_CTRL_MQ_VQ_PAIRS_SET: u8 = 0;
+
+/// 控制命令执行成功
+const VIRTIO_NET_OK: u8 = 0;
+
+/// 等待控制命令完成的最大轮询次数
+const CTRL_SPIN_LIMIT: usize = 10_000_000;
+
+/// 设备是否提供多队列所需的特性
+pub fn supports_multiqueue(device_features: u64) -> bool {
+    Features::from_bits_truncate(device_features).contains(Features::MQ | Features::CTRL_VQ)
+}
+
+/// 设备提供的收发队列对数
+pub fn max_queue_pairs<T: Transport>(transport: &T) -> usize {
+    transport
+        .read_config_space::<u16>(CONFIG_MAX_VIRTQUEUE_PAIRS)
+        .map(|pairs| usize::from(pairs).max(1))
+        .unwrap_or(1)
+}
+
+/// 计划启用的队列对数：不超过设备上限、CPU 数和 [`MAX_QUEUE_PAIRS`]
+pub fn wanted_queue_pairs(max_pairs: usize) -> usize {
+    max_pairs
+        .min(crate::kernel::num_cpu())
+        .min(MAX_QUEUE_PAIRS)
+        .max(1)
+}
+
+/// 一对收发队列
+struct QueuePair {
+    rx: SplitQueue,
+    tx: SplitQueue,
+    /// 发送队列中空闲的描述符
+    tx_free: Vec<u16>,
+}
+
+impl QueuePair {
+    /// 回收设备已发送完的描述符
+    fn reclaim_tx(&mut self) {
+        while let Some((id, _)) = self.tx.pop() {
+            self.tx_free.push(id);
+        }
+    }
+}
+
+/// 多队列 virtio-net 的收发队列
+pub struct MultiQueueNet<T: Transport> {
+    /// 通知设备、应答中断时使用；加锁顺序在队列对之前
+    transport: SpinLock<T>,
+    pairs: Vec<SpinLock<QueuePair>>,
+    /// 设备接受的队列对数，不超过 `pairs.len()`
+    active: usize,
+    /// 控制队列，设备可能仍会访问，随设备一起保留
+    _ctrl: SplitQueue,
+    /// virtio-net 数据包头部长度
+    hdr_len: usize,
+    /// 接收缓冲区大小
+    buf_len: usize,
+    mac: [u8; 6],
+    /// 下一次接收从哪个队列对开始，轮流取包以免后面的队列饿死
+    next_rx: AtomicUsize,
+}
+
+impl<T: Transport> MultiQueueNet<T> {
+    /// 初始化设备并启用 `pairs` 对收发队列
+    ///
+    /// 失败时设备尚未进入 DRIVER_OK，连同错误一起交还 `transport`，调用者可以重新初始化为单队列。
+    pub fn new(
+        mut transport: T,
+        pairs: usize,
+        max_pairs: usize,
+        buf_len: usize,
+    ) -> Result<Self, (T, NetDeviceError)> {
+        let negotiated = transport.begin_init(
+            Features::MTU | Features::MAC | Features::CTRL_VQ | Features::MQ | Features::VERSION_1,
+        );
+        if !transport.get_status().contains(DeviceStatus::FEATURES_OK) {
+            return Err((transport, NetDeviceError::NotSupported));
+        }
+        let queues = match Self::setup_queues(&mut transport, pairs, max_pairs, buf_len) {
+            Ok(queues) => queues,
+            Err(e) => return Err((transport, e)),
+        };
+        let (mut queue_pairs, mut ctrl) = queues;
+        let mac = if negotiated.contains(Features::MAC) {
+            transport
+                .read_config_space::<[u8; 6]>(CONFIG_MAC)
+                .unwrap_or_default()
+        } else {
+            [0; 6]
+        };
+        transport.finish_init();
+
+        for pair in queue_pairs.iter_mut() {
+            for id in 0..pair.rx.size() {
+                pair.rx.push(&[(id, buf_len, true)]);
+            }
+            // 发送完成由下一次发送顺带回收，不需要中断
+            pair.tx.set_interrupts(false);
+            transport.notify(pair.rx.index());
+        }
+
+        let active = if send_vq_pairs_set(&mut transport, &mut ctrl, pairs) {
+            pairs
+        } else {
+            crate::pr_warn!(
+                "[Device] virtio-net rejected {} queue pairs, using 1",
+                pairs
+            );
+            1
+        };
+
+        Ok(Self {
+            transport: SpinLock::new(transport),
+            pairs: queue_pairs.into_iter().map(SpinLock::new).collect(),
+            active,
+            _ctrl: ctrl,
+            hdr_len: if negotiated.contains(Features::VERSION_1) {
+                12
+            } else {
+                10
+            },
+            buf_len,
+            mac,
+            next_rx: AtomicUsize::new(0),
+        })
+    }
+
+    /// 建立 `pairs` 对收发队列和控制队列
+    fn setup_queues(
+        transport: &mut T,
+        pairs: usize,
+        max_pairs: usize,
+        buf_len: usize,
+    ) -> Result<(Vec<QueuePair>, SplitQueue), NetDeviceError> {
+        let mut queue_pairs = Vec::with_capacity(pairs);
+        for i in 0..pairs as u16 {
+            let rx = SplitQueue::new(transport, 2 * i, QUEUE_SIZE, buf_len)?;
+            let tx = SplitQueue::new(transport, 2 * i + 1, QUEUE_SIZE, buf_len)?;
+            queue_pairs.push(QueuePair {
+                rx,
+                tx,
+                tx_free: (0..QUEUE_SIZE).collect(),
+            });
+        }
+        // 控制队列排在设备全部队列对之后；合法的队列对数至多 0x8000，编号不会溢出
+        let ctrl_index = 2 * max_pairs as u16;
+        let ctrl = SplitQueue::new(transport, ctrl_index, CTRL_QUEUE_SIZE, CTRL_BUFFER_LEN)?;
+        Ok((queue_pairs, ctrl))
+    }
+
+    /// 启用的队列对数
+    pub fn queue_pairs(&self) -> usize {
+        self.active
+    }
+
+    /// MAC 地址
+    pub fn mac_address(&self) -> [u8; 6] {
+        self.mac
+    }
+
+    /// 从当前 CPU 对应的发送队列发出一帧
+    pub fn send(&self, packet: &[u8]) -> Result<(), NetDeviceError> {
+        if self.hdr_len + packet.len() > self.buf_len {
+            return Err(NetDeviceError::InvalidArgument);
+        }
+        let index = {
+            let mut pair = self.pairs[crate::arch::cpu_id() % self.active].lock();
+            pair.reclaim_tx();
+            let id = pair.tx_free.pop().ok_or(NetDeviceError::QueueFull)?;
+            let buf = pair.tx.buffer(id);
+            buf[..self.hdr_len].fill(0);
+            buf[self.hdr_len..self.hdr_len + packet.len()].copy_from_slice(packet);
+            pair.tx.push(&[(id, self.hdr_len + packet.len(), false)]);
+            pair.tx.index()
+        };
+        self.transport.lock().notify(index);
+        Ok(())
+    }
+
+    /// 轮流从各接收队列取出一帧，复制后立即把缓冲区交还给设备
+    pub fn receive(&self, buf: &mut [u8]) -> Result<usize, NetDeviceError> {
+        let start = self.next_rx.fetch_add(1, Ordering::Relaxed);
+        for i in 0..self.active {
+            let (len, index) = {
+                let mut pair = self.pairs[(start + i) % self.active].lock();
+                let Some((id, used)) = pair.rx.pop() else {
+                    continue;
+                };
+                let used = used.clamp(self.hdr_len, self.buf_len);
+                let packet = &pair.rx.buffer(id)[self.hdr_len..used];
+                let len = packet.len().min(buf.len());
+                buf[..len].copy_from_slice(&packet[..len]);
+                pair.rx.push(&[(id, self.buf_len, true)]);
+                (len, pair.rx.index())
+            };
+            self.transport.lock().notify(index);
+            return Ok(len);
+        }
+        Err(NetDeviceError::QueueEmpty)
+    }
+
+    /// 是否有接收队列中有待取的数据包
+    fn can_recv(&self) -> bool {
+        self.pairs[..self.active]
+            .iter()
+            .any(|pair| pair.lock().rx.can_pop())
+    }
+
+    /// 打开或屏蔽所有接收队列的中断
+    fn set_rx_interrupts(&self, enable: bool) {
+        for pair in &self.pairs[..self.active] {
+            pair.lock().rx.set_interrupts(enable);
+        }
+    }
+
+    /// 应答中断，有新数据包时屏蔽接收中断并调度 NAPI 轮询
+    ///
+    /// 与 [`Self::napi_complete`] 在 `transport` 锁内切换中断，避免两者交错。
+    pub fn handle_interrupt(&self, napi: &Napi) -> bool {
+        let mut transport = self.transport.lock();
+        let status = transport.ack_interrupt();
+        if !status.contains(InterruptStatus::QUEUE_INTERRUPT) {
+            return !status.is_empty();
+        }
+        let scheduled = napi.schedule();
+        if scheduled {
+            self.set_rx_interrupts(false);
+        }
+        drop(transport);
+        if scheduled {
+            crate::net::socket::request_network_poll();
+        }
+        true
+    }
+
+    /// 一轮 NAPI 轮询结束，语义同 [`super::net_device::NetDevice::napi_complete`]
+    pub fn napi_complete(&self, napi: &Napi) -> bool {
+        let _transport = self.transport.lock();
+        if !napi.is_scheduled() {
+            return false;
+        }
+        if self.can_recv() {
+            return true;
+        }
+        napi.complete();
+        self.set_rx_interrupts(true);
+        // 重新打开中断前到达的数据包不会再触发中断
+        if self.can_recv() && napi.schedule() {
+            self.set_rx_interrupts(false);
+            return true;
+        }
+        false
+    }
+}
+
+/// 通过控制队列发送 VQ_PAIRS_SET，返回设备是否接受
+fn send_vq_pairs_set<T: Transport>(transport: &mut T, ctrl: &mut SplitQueue, pairs: usize) -> bool {
+    // 命令完成靠轮询，不需要中断
+    ctrl.set_interrupts(false);
+    let [lo, hi] = (pairs as u16).to_le_bytes();
+    ctrl.buffer(0)[..4].copy_from_slice(&[
+        VIRTIO_NET_CTRL_MQ,
+        VIRTIO_NET_CTRL_MQ_VQ_PAIRS_SET,
+        lo,
+        hi,
+    ]);
+    ctrl.buffer(1)[0] = !VIRTIO_NET_OK;
+    ctrl.push(&[(0, 4, false), (1, 1, true)]);
+    transport.notify(ctrl.index());
+    for _ in 0..CTRL_SPIN_LIMIT {
+        if ctrl.pop().is_some() {
+            return ctrl.buffer(1)[0] == VIRTIO_NET_OK;
+        }
+        core::hint::spin_loop();
+    }
+    false
+}
@@ -0,0 +1,216 @@
+//! 驱动自行管理的 split virtqueue
+//!
+//! virtio-drivers 0.12 不导出 virtqueue，多队列 virtio-net 的额外队列对和控制队列由本模块提供。
+//! 用法刻意保持简单：描述符与数据缓冲区一一对应（第 `i` 个描述符总是指向第 `i` 块缓冲区），
+//! 缓冲区由队列直接从 DMA32 区域分配，不经过 HAL 的 `share`；不使用间接描述符和 EVENT_IDX。
+//!
+//! 内存按传统设备的布局排列：描述符表、可用环，已用环从下一页开始。
+
+use super::net_device::NetDeviceError;
+use crate::config::PAGE_SIZE;
+use crate::device::dma::{DmaBuffer, dma_alloc};
+use core::ptr::{read_volatile, write_volatile};
+use core::sync::atomic::{Ordering, fence};
+use virtio_drivers::transport::Transport;
+
+/// 描述符后面还链着下一个描述符
+const VIRTQ_DESC_F_NEXT: u16 = 1;
+
+/// 设备写入该描述符指向的缓冲区
+const VIRTQ_DESC_F_WRITE: u16 = 2;
+
+/// 驱动不需要已用缓冲区通知
+const VIRTQ_AVAIL_F_NO_INTERRUPT: u16 = 1;
+
+/// 描述符表项
+#[repr(C)]
+struct Descriptor {
+    addr: u64,
+    len: u32,
+    flags: u16,
+    next: u16,
+}
+
+/// 已用环表项
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UsedElem {
+    id: u32,
+    len: u32,
+}
+
+/// 队列深度为 `size` 时可用环和已用环的偏移，以及整个环区的字节数
+fn ring_layout(size: u16) -> (usize, usize, usize) {
+    let size = usize::from(size);
+    let avail = size_of::<Descriptor>() * size;
+    // flags、idx、ring[size]、used_event
+    let used = (avail + size_of::<u16>() * (3 + size)).next_multiple_of(PAGE_SIZE);
+    // flags、idx、ring[size]、avail_event
+    let total = used + size_of::<u16>() * 3 + size_of::<UsedElem>() * size;
+    (avail, used, total)
+}
+
+/// split virtqueue
+pub struct SplitQueue {
+    /// 队列编号
+    index: u16,
+    /// 队列深度
+    size: u16,
+    /// 描述符表、可用环和已用环
+    ring: DmaBuffer,
+    /// 每个描述符对应一块 `buf_len` 字节的数据缓冲区
+    bufs: DmaBuffer,
+    buf_len: usize,
+    /// 可用环偏移
+    avail: usize,
+    /// 已用环偏移
+    used: usize,
+    /// 驱动下一次写入可用环的位置
+    avail_idx: u16,
+    /// 驱动下一次读取已用环的位置
+    last_used: u16,
+}
+
+impl SplitQueue {
+    /// 分配第 `index` 号队列并告知设备
+    ///
+    /// 队列已被占用、设备不支持 `size` 深度或 DMA32 内存不足时返回错误。
+    pub fn new<T: Transport>(
+        transport: &mut T,
+        index: u16,
+        size: u16,
+        buf_len: usize,
+    ) -> Result<Self, NetDeviceError> {
+        if transport.queue_used(index) || (transport.max_queue_size(index) as usize) < size.into() {
+            return Err(NetDeviceError::DeviceNotReady);
+        }
+        let (avail, used, total) = ring_layout(size);
+        let ring = dma_alloc(total.div_ceil(PAGE_SIZE)).ok_or(NetDeviceError::AllocationFailed)?;
+        let bufs = dma_alloc((usize::from(size) * buf_len).div_ceil(PAGE_SIZE))
+            .ok_or(NetDeviceError::AllocationFailed)?;
+        let base = ring.paddr().as_usize() as u64;
+        transport.queue_set(
+            index,
+            size.into(),
+            base,
+            base + avail as u64,
+            base + used as u64,
+        );
+        Ok(Self {
+            index,
+            size,
+            ring,
+            bufs,
+            buf_len,
+            avail,
+            used,
+            avail_idx: 0,
+            last_used: 0,
+        })
+    }
+
+    /// 队列编号
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// 队列深度
+    pub fn size(&self) -> u16 {
+        self.size
+    }
+
+    /// 第 `id` 个描述符对应的数据缓冲区
+    pub fn buffer(&mut self, id: u16) -> &mut [u8] {
+        let start = usize::from(id) * self.buf_len;
+        &mut self.bufs.as_mut_slice()[start..start + self.buf_len]
+    }
+
+    /// 环区内偏移 `offset` 处的指针
+    fn ring_ptr<U>(&self, offset: usize) -> *mut U {
+        (self.ring.vaddr().as_usize() + offset) as *mut U
+    }
+
+    /// 把描述符链交给设备
+    ///
+    /// `chain` 的每一项为 `(描述符编号, 长度, 设备是否写入)`，长度不超过缓冲区大小。
+    pub fn push(&mut self, chain: &[(u16, usize, bool)]) {
+        let bufs = self.bufs.paddr().as_usize() as u64;
+        for (i, &(id, len, writable)) in chain.iter().enumerate() {
+            debug_assert!(id < self.size && len <= self.buf_len);
+            let mut flags = if writable { VIRTQ_DESC_F_WRITE } else { 0 };
+            let next = match chain.get(i + 1) {
+                Some(&(next, ..)) => {
+                    flags |= VIRTQ_DESC_F_NEXT;
+                    next
+                }
+                None => 0,
+            };
+            let desc = Descriptor {
+                addr: bufs + (usize::from(id) * self.buf_len) as u64,
+                len: len as u32,
+                flags,
+                next,
+            };
+            unsafe {
+                write_volatile(
+                    self.ring_ptr(size_of::<Descriptor>() * usize::from(id)),
+                    desc,
+                )
+            };
+        }
+        let slot = usize::from(self.avail_idx % self.size);
+        unsafe { write_volatile(self.ring_ptr(self.avail + 4 + 2 * slot), chain[0].0) };
+        // 设备看到新的 idx 前必须先看到描述符和环表项
+        fence(Ordering::SeqCst);
+        self.avail_idx = self.avail_idx.wrapping_add(1);
+        unsafe { write_volatile(self.ring_ptr(self.avail + 2), self.avail_idx) };
+        fence(Ordering::SeqCst);
+    }
+
+    /// 已用环中是否有未取走的表项
+    pub fn can_pop(&self) -> bool {
+        fence(Ordering::SeqCst);
+        unsafe { read_volatile(self.ring_ptr::<u16>(self.used + 2)) != self.last_used }
+    }
+
+    /// 取出一个设备已处理完的描述符链，返回链头编号和设备写入的字节数
+    pub fn pop(&mut self) -> Option<(u16, usize)> {
+        if !self.can_pop() {
+            return None;
+        }
+        let slot = usize::from(self.last_used % self.size);
+        let elem: UsedElem =
+            unsafe { read_volatile(self.ring_ptr(self.used + 4 + size_of::<UsedElem>() * slot)) };
+        self.last_used = self.last_used.wrapping_add(1);
+        Some((elem.id as u16, elem.len as usize))
+    }
+
+    /// 打开或屏蔽设备的已用缓冲区通知
+    pub fn set_interrupts(&mut self, enable: bool) {
+        let flags = if enable {
+            0
+        } else {
+            VIRTQ_AVAIL_F_NO_INTERRUPT
+        };
+        unsafe { write_volatile(self.ring_ptr(self.avail), flags) };
+        fence(Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_ring_layout, {
+        // 与 virtio 规范传统布局的例子一致：深度 256 时已用环从第 2 页开始
+        let (avail, used, total) = ring_layout(256);
+        kassert!(avail == 4096);
+        kassert!(used == 2 * PAGE_SIZE);
+        kassert!(total == used + 6 + 8 * 256);
+
+        let (avail, used, _) = ring_layout(16);
+        kassert!(avail == 256);
+        kassert!(used == PAGE_SIZE);
+    });
+}
@@ -1,10 +1,41 @@
 //! RTC 设备驱动模块
+//!
+//! RTC 保存 UTC 时间，内核中的时间（墙上时钟、文件时间戳、`RTC_RD_TIME`）都不做
+//! 时区换算，本地时间由用户态按 TZ 计算。只有 sysfs 的 `date`/`time` 属性按
+//! `/proc/sys/dev/rtc/display_offset_minutes` 加上显示偏移，默认为 0，即 UTC。
+
+use core::ffi::c_int;
+use core::ops::RangeInclusive;
+use core::sync::atomic::{AtomicI32, Ordering};
 
 use super::Driver;
-use chrono::{DateTime as ChronoDateTime, Datelike, FixedOffset, TimeZone, Timelike, Utc};
+use crate::uapi::errno::EINVAL;
+use chrono::{Datelike, TimeZone, Timelike, Utc};
+
+/// 显示偏移允许的范围（分钟），与实际存在的时区一致：UTC-12 到 UTC+14
+pub const DISPLAY_OFFSET_RANGE: RangeInclusive<i32> = -12 * 60..=14 * 60;
+
+/// sysfs 显示 RTC 日期时间时加上的偏移（分钟，东正西负）
+static DISPLAY_OFFSET_MINUTES: AtomicI32 = AtomicI32::new(0);
+
+/// 当前的显示偏移（分钟）
+pub fn display_offset_minutes() -> i32 {
+    DISPLAY_OFFSET_MINUTES.load(Ordering::Relaxed)
+}
 
-/// 简化的日期时间结构（用于 sysfs 显示）
-#[derive(Debug, Clone, Copy)]
+/// 设置显示偏移（分钟）
+/// # 返回值
+/// 超出 [`DISPLAY_OFFSET_RANGE`] 时返回 `Err(EINVAL)`
+pub fn set_display_offset_minutes(minutes: i32) -> Result<(), c_int> {
+    if !DISPLAY_OFFSET_RANGE.contains(&minutes) {
+        return Err(EINVAL);
+    }
+    DISPLAY_OFFSET_MINUTES.store(minutes, Ordering::Relaxed);
+    Ok(())
+}
+
+/// 简化的日期时间结构（用于 sysfs 显示和 `RTC_RD_TIME`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DateTime {
     pub year: i32,
     pub month: u32,
@@ -15,35 +46,35 @@ pub struct DateTime {
 }
 
 impl DateTime {
-    /// 从 Unix 时间戳(秒)转换为北京时间 (UTC+8)
+    /// 从 Unix 时间戳(秒)转换为 UTC 日期时间
     pub fn from_epoch(epoch: u64) -> Self {
-        // 创建 UTC 时间
-        let utc_time = match Utc.timestamp_opt(epoch as i64, 0) {
-            chrono::LocalResult::Single(t) => t,
-            _ => {
-                // 如果时间戳无效，返回一个默认值
-                return Self {
-                    year: 1970,
-                    month: 1,
-                    day: 1,
-                    hour: 0,
-                    minute: 0,
-                    second: 0,
-                };
-            }
-        };
+        Self::from_epoch_offset(epoch, 0)
+    }
 
-        // 转换为北京时间 (UTC+8)
-        let beijing_offset = FixedOffset::east_opt(8 * 3600).unwrap();
-        let beijing_time: ChronoDateTime<FixedOffset> = utc_time.with_timezone(&beijing_offset);
+    /// 从 Unix 时间戳(秒)转换为 UTC 加上 `offset_secs` 秒后的日期时间
+    pub fn from_epoch_offset(epoch: u64, offset_secs: i64) -> Self {
+        let Some(time) = (epoch as i64)
+            .checked_add(offset_secs)
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+        else {
+            // 如果时间戳无效，返回一个默认值
+            return Self {
+                year: 1970,
+                month: 1,
+                day: 1,
+                hour: 0,
+                minute: 0,
+                second: 0,
+            };
+        };
 
         Self {
-            year: beijing_time.year(),
-            month: beijing_time.month(),
-            day: beijing_time.day(),
-            hour: beijing_time.hour(),
-            minute: beijing_time.minute(),
-            second: beijing_time.second(),
+            year: time.year(),
+            month: time.month(),
+            day: time.day(),
+            hour: time.hour(),
+            minute: time.minute(),
+            second: time.second(),
         }
     }
 }
@@ -55,8 +86,41 @@ pub trait RtcDriver: Driver {
     /// 读取自纪元以来的秒数
     fn read_epoch(&self) -> u64;
 
-    /// 读取日期时间（北京时间，默认实现）
+    /// 写入自纪元以来的秒数（用于 clock_settime 持久化墙上时钟）
+    fn write_epoch(&self, epoch: u64);
+
+    /// 读取日期时间（UTC，默认实现）
     fn read_datetime(&self) -> DateTime {
         DateTime::from_epoch(self.read_epoch())
     }
+
+    /// 读取用于显示的日期时间：UTC 加上显示偏移
+    fn read_display_datetime(&self) -> DateTime {
+        let offset_secs = display_offset_minutes() as i64 * 60;
+        DateTime::from_epoch_offset(self.read_epoch(), offset_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_rtc_datetime_utc_and_offset, {
+        // 2024-02-29 23:30:00 UTC
+        let epoch = 1_709_249_400;
+        let utc = DateTime::from_epoch(epoch);
+        kassert!((utc.year, utc.month, utc.day) == (2024, 2, 29));
+        kassert!((utc.hour, utc.minute, utc.second) == (23, 30, 0));
+
+        // 东八区显示为次日，西五区显示为当日 18:30
+        let east = DateTime::from_epoch_offset(epoch, 8 * 3600);
+        kassert!((east.month, east.day, east.hour) == (3, 1, 7));
+        let west = DateTime::from_epoch_offset(epoch, -5 * 3600);
+        kassert!((west.day, west.hour, west.minute) == (29, 18, 30));
+
+        kassert!(set_display_offset_minutes(15 * 60).is_err());
+        kassert!(set_display_offset_minutes(-13 * 60).is_err());
+        kassert!(display_offset_minutes() == 0);
+    });
 }
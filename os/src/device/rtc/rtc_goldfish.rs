@@ -2,11 +2,15 @@ use alloc::{string::String, sync::Arc};
 use fdt::node::FdtNode;
 
 use crate::{
+    config::PAGE_SIZE,
     device::{
         DRIVERS, DeviceType, Driver, RTC_DRIVERS, device_tree::DEVICE_TREE_REGISTRY, rtc::RtcDriver,
     },
     kernel::current_memory_space,
-    mm::address::{PA, VA},
+    mm::{
+        address::{PA, Ppn, UsizeConvert, VA},
+        memory_space::mapping_area::{PhysPages, SharedPages},
+    },
     pr_info, pr_warn,
     util::{read, write},
 };
@@ -14,6 +18,8 @@ use crate::{
 const GOLDFISH_TIME_LOW: usize = 0x00;
 const GOLDFISH_TIME_HIGH: usize = 0x04;
 
+const LS7A_TOYWRITE0: usize = 0x24;
+const LS7A_TOYWRITE1: usize = 0x28;
 const LS7A_TOYREAD0: usize = 0x2c;
 const LS7A_TOYREAD1: usize = 0x30;
 const LS7A_RTCCTRL: usize = 0x40;
@@ -29,6 +35,9 @@ enum RtcBackend {
 
 pub struct RtcGoldfish {
     base: VA,
+    /// 寄存器的物理地址与长度
+    paddr: usize,
+    size: usize,
     backend: RtcBackend,
 }
 
@@ -51,6 +60,17 @@ impl Driver for RtcGoldfish {
     fn as_rtc(&self) -> Option<&dyn RtcDriver> {
         Some(self)
     }
+
+    fn mmap_region(&self) -> Option<Arc<dyn SharedPages>> {
+        // 寄存器不独占整页时，映射会暴露同一页中的其它设备
+        if !self.paddr.is_multiple_of(PAGE_SIZE) || !self.size.is_multiple_of(PAGE_SIZE) {
+            return None;
+        }
+        Some(PhysPages::new(
+            Ppn::from_usize(self.paddr / PAGE_SIZE),
+            self.size / PAGE_SIZE,
+        ))
+    }
 }
 
 impl RtcDriver for RtcGoldfish {
@@ -61,6 +81,13 @@ impl RtcDriver for RtcGoldfish {
             RtcBackend::Ls7a => self.read_ls7a_epoch(),
         }
     }
+
+    fn write_epoch(&self, epoch: u64) {
+        match self.backend {
+            RtcBackend::Goldfish => self.write_goldfish_epoch(epoch),
+            RtcBackend::Ls7a => self.write_ls7a_epoch(epoch),
+        }
+    }
 }
 
 impl RtcGoldfish {
@@ -72,6 +99,14 @@ impl RtcGoldfish {
         ns / 1_000_000_000u64
     }
 
+    fn write_goldfish_epoch(&self, epoch: u64) {
+        let base = self.base.as_usize();
+        let ns = epoch.saturating_mul(1_000_000_000u64);
+        // 设备在写 TIME_LOW 时才合并高低位生效，必须先写高位
+        write(base + GOLDFISH_TIME_HIGH, (ns >> 32) as u32);
+        write(base + GOLDFISH_TIME_LOW, ns as u32);
+    }
+
     fn read_ls7a_epoch(&self) -> u64 {
         let base = self.base.as_usize();
 
@@ -93,6 +128,14 @@ impl RtcGoldfish {
 
         utc_to_epoch(1900 + year as i32, month, day, hour, minute, second).unwrap_or(0)
     }
+
+    fn write_ls7a_epoch(&self, epoch: u64) {
+        let base = self.base.as_usize();
+        let (year, month, day, hour, minute, second) = epoch_to_utc(epoch);
+        let toy0 = (month << 26) | (day << 21) | (hour << 16) | (minute << 10) | (second << 4);
+        write(base + LS7A_TOYWRITE0, toy0);
+        write(base + LS7A_TOYWRITE1, (year - 1900) as u32);
+    }
 }
 
 fn init_dt(dt: &FdtNode, backend: RtcBackend) {
@@ -121,6 +164,8 @@ fn init_dt(dt: &FdtNode, backend: RtcBackend) {
 
     let rtc = Arc::new(RtcGoldfish {
         base: vaddr,
+        paddr,
+        size,
         backend,
     });
     DRIVERS.write().push(rtc.clone());
@@ -172,6 +217,40 @@ fn utc_to_epoch(
     Some(days * 86_400 + hour as u64 * 3_600 + minute as u64 * 60 + second.min(59) as u64)
 }
 
+/// 把自纪元以来的秒数转换为 UTC (年, 月, 日, 时, 分, 秒)
+fn epoch_to_utc(epoch: u64) -> (i32, u32, u32, u32, u32, u32) {
+    let mut days = epoch / 86_400;
+    let rem = epoch % 86_400;
+    let (hour, minute, second) = (rem / 3_600, rem % 3_600 / 60, rem % 60);
+
+    let mut year = 1970;
+    loop {
+        let year_days = if is_leap_year(year) { 366 } else { 365 };
+        if days < year_days {
+            break;
+        }
+        days -= year_days;
+        year += 1;
+    }
+
+    let mut month = 1;
+    while let Some(dim) = days_in_month(year, month)
+        && days >= dim as u64
+    {
+        days -= dim as u64;
+        month += 1;
+    }
+
+    (
+        year,
+        month,
+        days as u32 + 1,
+        hour as u32,
+        minute as u32,
+        second as u32,
+    )
+}
+
 fn days_in_month(year: i32, month: u32) -> Option<u32> {
     Some(match month {
         1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
@@ -194,3 +273,24 @@ pub fn driver_init() {
         .lock()
         .insert("loongson,ls7a-rtc", init_ls7a_dt);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_epoch_to_utc_known_dates, {
+        kassert!(epoch_to_utc(0) == (1970, 1, 1, 0, 0, 0));
+        // 2000-02-29 12:34:56 UTC
+        kassert!(epoch_to_utc(951_827_696) == (2000, 2, 29, 12, 34, 56));
+        // 2024-12-31 23:59:59 UTC
+        kassert!(epoch_to_utc(1_735_689_599) == (2024, 12, 31, 23, 59, 59));
+    });
+
+    test_case!(test_epoch_utc_round_trip, {
+        for epoch in [0u64, 86_399, 951_827_696, 1_709_210_096, 4_102_444_800] {
+            let (y, mo, d, h, mi, s) = epoch_to_utc(epoch);
+            kassert!(utc_to_epoch(y, mo, d, h, mi, s) == Some(epoch));
+        }
+    });
+}
@@ -20,4 +20,17 @@ pub trait SerialDriver: Driver {
     fn try_read(&self) -> Option<u8> {
         Some(self.read())
     }
+
+    /// 同步写入：不睡眠，数据送出后才返回（控制台日志、panic 路径使用）
+    fn write_sync(&self, data: &[u8]) {
+        self.write(data);
+    }
+
+    /// 是否有已到达的输入（用于 poll/select）
+    fn rx_ready(&self) -> bool {
+        true
+    }
+
+    /// 阻塞到有输入可读；不能睡眠时立即返回，由调用者轮询
+    fn wait_readable(&self) {}
 }
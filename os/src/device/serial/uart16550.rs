@@ -1,28 +1,234 @@
 //! 16550 UART 串行端口驱动程序模块
+//!
+//! 接收由中断驱动：中断处理程序把 RBR 中的字节搬进接收环，tty 层从环中取数据，
+//! 环为空时读者睡眠等待。发送经过软件 FIFO：写者把数据放进 FIFO 后由发送空中断
+//! 逐批送入硬件，FIFO 满时写者睡眠。
+//!
+//! 串口没有挂上中断控制器、或调用者不能睡眠（中断上下文、禁止抢占、控制台日志）时
+//! 退回到轮询收发，因此内核日志和 panic 输出不依赖中断。
+//!
+//! 开启 `sysrq` feature 时，接收路径解析 SysRq 转义（Ctrl-A 加命令字母，见
+//! `crate::kernel::sysrq`），命令在中断处理程序中直接执行，不进入接收环，
+//! 系统卡在用户态或 tty 层时也能触发。
+
+use core::sync::atomic::{AtomicBool, Ordering};
 
 use alloc::sync::Arc;
 use fdt::node::FdtNode;
-use uart_16550::MmioSerialPort;
 
+#[cfg(feature = "sysrq")]
+use crate::kernel::sysrq::{SysrqEscape, SysrqInput, handle_sysrq};
 use crate::{
     device::{
-        DRIVERS, DeviceType, Driver, SERIAL_DRIVERS, console::uart_console,
-        device_tree::DEVICE_TREE_REGISTRY, serial::SerialDriver,
+        DRIVERS, DeviceType, Driver, SERIAL_DRIVERS,
+        console::uart_console,
+        device_tree::{DEVICE_TREE_REGISTRY, first_interrupt, intc_of},
+        serial::SerialDriver,
     },
-    kernel::current_memory_space,
+    kernel::{WaitQueue, current_memory_space, current_task, schedule, try_current_task},
     mm::address::PA,
     pr_info, pr_warn,
-    sync::SpinLock,
+    sync::{SpinLock, preempt_disabled},
+    util::ring_buffer::RingBuffer,
 };
 
+/// 接收缓冲寄存器 / 发送保持寄存器（DLAB=0）
+const UART_RBR_THR: usize = 0;
+/// 中断使能寄存器
+const UART_IER: usize = 1;
+/// 中断标识寄存器（读）/ FIFO 控制寄存器（写）
+const UART_IIR_FCR: usize = 2;
+/// 线路控制寄存器
+const UART_LCR: usize = 3;
+/// Modem 控制寄存器
+const UART_MCR: usize = 4;
+/// 线路状态寄存器
+const UART_LSR: usize = 5;
+
+/// IER: 接收数据可用中断
+const IER_RDI: u8 = 1 << 0;
+/// IER: 发送保持寄存器空中断
+const IER_THRI: u8 = 1 << 1;
+/// IIR: 没有挂起的中断
+const IIR_NO_INT: u8 = 1 << 0;
+/// LSR: 接收数据就绪
+const LSR_DR: u8 = 1 << 0;
+/// LSR: 发送保持寄存器（及硬件 FIFO）空
+const LSR_THRE: u8 = 1 << 5;
+/// LCR: 除数锁存访问位
+const LCR_DLAB: u8 = 1 << 7;
+
+/// 硬件发送 FIFO 深度
+const UART_TX_FIFO_DEPTH: usize = 16;
+
+/// 16550 寄存器窗口
+struct Uart16550Regs {
+    base: usize,
+    reg_shift: usize,
+}
+
+impl Uart16550Regs {
+    fn read(&self, reg: usize) -> u8 {
+        unsafe { core::ptr::read_volatile((self.base + (reg << self.reg_shift)) as *const u8) }
+    }
+
+    fn write(&self, reg: usize, value: u8) {
+        unsafe {
+            core::ptr::write_volatile((self.base + (reg << self.reg_shift)) as *mut u8, value)
+        }
+    }
+
+    /// 8N1、启用 FIFO，中断先保持关闭
+    fn init(&self) {
+        self.write(UART_IER, 0);
+        self.write(UART_LCR, LCR_DLAB);
+        self.write(UART_RBR_THR, 0x03); // 除数低字节：38400 波特
+        self.write(UART_IER, 0x00); // 除数高字节
+        self.write(UART_LCR, 0x03); // 8 位数据，无校验，1 位停止位
+        self.write(UART_IIR_FCR, 0xC7); // 启用并清空 FIFO，14 字节触发阈值
+        self.write(UART_MCR, 0x0B); // DTR、RTS、OUT2
+    }
+}
+
 /// 16550 UART 串行端口驱动程序结构体
 pub struct Uart16550 {
-    serial_port: SpinLock<MmioSerialPort>,
+    regs: Uart16550Regs,
+    /// 接收环，由中断处理程序填充
+    rx: SpinLock<RingBuffer>,
+    /// 软件发送 FIFO；持有此锁时才能写发送保持寄存器
+    tx: SpinLock<RingBuffer>,
+    /// 等待接收数据的任务
+    rx_waiters: SpinLock<WaitQueue>,
+    /// 等待发送 FIFO 空间的任务
+    tx_waiters: SpinLock<WaitQueue>,
+    /// 是否已挂上中断控制器
+    irq_enabled: AtomicBool,
+    /// 接收字节流中的 SysRq 转义状态
+    #[cfg(feature = "sysrq")]
+    sysrq: SysrqEscape,
+}
+
+impl Uart16550 {
+    fn new(regs: Uart16550Regs) -> Self {
+        Self {
+            regs,
+            rx: SpinLock::new(RingBuffer::new()),
+            tx: SpinLock::new(RingBuffer::new()),
+            rx_waiters: SpinLock::new(WaitQueue::new()),
+            tx_waiters: SpinLock::new(WaitQueue::new()),
+            irq_enabled: AtomicBool::new(false),
+            #[cfg(feature = "sysrq")]
+            sysrq: SysrqEscape::new(),
+        }
+    }
+
+    /// 中断注册完成后打开接收中断
+    fn enable_interrupts(&self) {
+        self.irq_enabled.store(true, Ordering::Release);
+        self.regs.write(UART_IER, IER_RDI);
+    }
+
+    /// 当前上下文能否睡眠等待中断
+    fn may_sleep(&self) -> bool {
+        self.irq_enabled.load(Ordering::Acquire)
+            && !preempt_disabled()
+            && try_current_task().is_some()
+    }
+
+    /// 把硬件中已到达的字节全部搬进接收环，返回是否搬运了数据
+    ///
+    /// 接收环满时丢弃新到达的字节。SysRq 转义和命令字母不进入接收环。
+    fn drain_rx(&self) -> bool {
+        let mut rx = self.rx.lock();
+        let mut moved = false;
+        #[cfg(feature = "sysrq")]
+        let mut sysrq = None;
+        while self.regs.read(UART_LSR) & LSR_DR != 0 {
+            let byte = self.regs.read(UART_RBR_THR);
+            #[cfg(feature = "sysrq")]
+            let byte = match self.sysrq.feed(byte) {
+                SysrqInput::Byte(byte) => byte,
+                SysrqInput::Escape => continue,
+                SysrqInput::Command(key) => {
+                    sysrq = Some(key);
+                    continue;
+                }
+            };
+            let _ = rx.write_byte(byte);
+            moved = true;
+        }
+        drop(rx);
+        // 转储输出会经过本串口，释放接收环后再执行
+        #[cfg(feature = "sysrq")]
+        if let Some(key) = sysrq {
+            handle_sysrq(key);
+        }
+        moved
+    }
+
+    /// 在发送保持寄存器空时从软件 FIFO 送出一批数据，返回是否送出了数据
+    ///
+    /// FIFO 排空后关闭发送空中断，避免中断风暴。
+    fn push_tx(&self, tx: &mut RingBuffer) -> bool {
+        let mut sent = false;
+        if self.regs.read(UART_LSR) & LSR_THRE != 0 {
+            for _ in 0..UART_TX_FIFO_DEPTH {
+                let Some(byte) = tx.read_byte() else {
+                    break;
+                };
+                self.regs.write(UART_RBR_THR, byte);
+                sent = true;
+            }
+        }
+        if self.irq_enabled.load(Ordering::Acquire) {
+            let ier = if tx.is_empty() {
+                IER_RDI
+            } else {
+                IER_RDI | IER_THRI
+            };
+            self.regs.write(UART_IER, ier);
+        }
+        sent
+    }
+
+    /// 轮询发送一个字节
+    fn send_polled(&self, byte: u8) {
+        while self.regs.read(UART_LSR) & LSR_THRE == 0 {
+            core::hint::spin_loop();
+        }
+        self.regs.write(UART_RBR_THR, byte);
+    }
+
+    /// 轮询发送：先按序排空软件 FIFO，再直接写出数据，不睡眠
+    fn write_polled(&self, data: &[u8]) {
+        let mut tx = self.tx.lock();
+        while let Some(byte) = tx.read_byte() {
+            self.send_polled(byte);
+        }
+        for &byte in data {
+            self.send_polled(byte);
+        }
+        self.push_tx(&mut tx);
+        drop(tx);
+        self.tx_waiters.lock().wake_up_all();
+    }
 }
 
 impl Driver for Uart16550 {
     fn try_handle_interrupt(&self, _irq: Option<usize>) -> bool {
-        false
+        if self.regs.read(UART_IIR_FCR) & IIR_NO_INT != 0 {
+            return false;
+        }
+
+        if self.drain_rx() {
+            self.rx_waiters.lock().wake_up_all();
+            crate::kernel::syscall::io::wake_poll_waiters();
+        }
+        let sent = self.push_tx(&mut self.tx.lock());
+        if sent {
+            self.tx_waiters.lock().wake_up_all();
+        }
+        true
     }
 
     fn device_type(&self) -> crate::device::DeviceType {
@@ -40,18 +246,76 @@ impl Driver for Uart16550 {
 
 impl SerialDriver for Uart16550 {
     fn read(&self) -> u8 {
-        // Now the serial port is ready to be used. To send a byte:
-        self.serial_port.lock().receive()
+        loop {
+            if let Some(byte) = self.try_read() {
+                return byte;
+            }
+            self.wait_readable();
+            core::hint::spin_loop();
+        }
     }
 
     fn write(&self, data: &[u8]) {
-        for &byte in data {
-            self.serial_port.lock().send(byte);
+        if !self.may_sleep() {
+            self.write_polled(data);
+            return;
+        }
+
+        let mut rest = data;
+        while !rest.is_empty() {
+            {
+                let mut tx = self.tx.lock();
+                while let Some((&byte, tail)) = rest.split_first() {
+                    if tx.write_byte(byte).is_err() {
+                        break;
+                    }
+                    rest = tail;
+                }
+                self.push_tx(&mut tx);
+            }
+            if rest.is_empty() {
+                break;
+            }
+
+            // 发送 FIFO 已满：睡眠到发送空中断腾出空间
+            let slept = self
+                .tx_waiters
+                .lock()
+                .sleep_if(current_task(), || self.tx.lock().available_space() > 0);
+            if slept {
+                schedule();
+            }
         }
     }
 
+    fn write_sync(&self, data: &[u8]) {
+        self.write_polled(data);
+    }
+
     fn try_read(&self) -> Option<u8> {
-        self.serial_port.lock().try_receive().ok()
+        if let Some(byte) = self.rx.lock().read_byte() {
+            return Some(byte);
+        }
+        // 未启用中断或中断尚未送达时直接检查硬件
+        self.drain_rx();
+        self.rx.lock().read_byte()
+    }
+
+    fn rx_ready(&self) -> bool {
+        !self.rx.lock().is_empty() || self.drain_rx()
+    }
+
+    fn wait_readable(&self) {
+        if !self.may_sleep() {
+            return;
+        }
+        let slept = self
+            .rx_waiters
+            .lock()
+            .sleep_if(current_task(), || self.rx_ready());
+        if slept {
+            schedule();
+        }
     }
 }
 
@@ -74,14 +338,32 @@ pub fn init(node: &FdtNode) {
         .map_mmio(PA::from_usize(paddr), size)
         .ok()
         .expect("Failed to map MMIO region");
-    let mut serial_port = unsafe { MmioSerialPort::new(vaddr.as_usize()) };
-    serial_port.init();
-    let driver = Arc::new(Uart16550 {
-        serial_port: SpinLock::new(serial_port),
-    });
+    let reg_shift = node
+        .property("reg-shift")
+        .and_then(|p| p.as_usize())
+        .unwrap_or(0);
+    let regs = Uart16550Regs {
+        base: vaddr.as_usize(),
+        reg_shift,
+    };
+    regs.init();
+    let driver = Arc::new(Uart16550::new(regs));
     DRIVERS.write().push(driver.clone());
     SERIAL_DRIVERS.lock().push(driver.clone());
-    uart_console::init(driver);
+
+    match (first_interrupt(node), intc_of(node)) {
+        (Some(irq), Some(intc)) => {
+            intc.register_local_irq(irq, driver.clone());
+            driver.enable_interrupts();
+            pr_info!("[Device] uart16550 at {:#x} uses irq {}", paddr, irq);
+        }
+        _ => pr_warn!(
+            "[Device] uart16550 at {:#x} has no usable interrupt, falling back to polling",
+            paddr
+        ),
+    }
+
+    uart_console::init(driver, Some(paddr));
     pr_info!("[Device] Serial driver (uart16550) is initialized");
 }
 
@@ -1,17 +1,38 @@
 //! HAL (硬件抽象层) 实现，用于适配 virtio-drivers 0.12.0 库
+//!
+//! 内存分配与共享均通过 [`crate::device::dma`] 完成，由其负责缓冲区生命周期和缓存维护。
+//! 传统（legacy）virtio 设备只能访问 4GiB 以下的物理地址：队列从 DMA32 区域分配；
+//! 位于 4GiB 以上的驱动缓冲区在共享给设备时经 DMA32 反弹缓冲区中转。
 
-use crate::mm::address::{ConvertablePA, PA, PageNum, VA};
-use crate::mm::frame_allocator::FrameRangeTracker;
+use crate::device::dma::{DmaBuffer, DmaDirection, DmaMapping, dma_alloc, dma_map_single};
+use crate::mm::address::{PA, VA};
 use crate::sync::SpinLock;
 use alloc::collections::btree_map::BTreeMap;
 use core::ptr::NonNull;
 use lazy_static::lazy_static;
 use virtio_drivers::{BufferDirection, Hal, PhysAddr};
 
-// 全局映射表，用于跟踪物理地址到分配的帧范围的映射
+// 全局映射表，按交给设备的物理地址跟踪 DMA 缓冲区和流式映射
+// 注意：必须先释放这些锁，再 drop 其中的 DmaBuffer / DmaMapping，
+// 因为释放物理帧会获取 FRAME_ALLOCATOR 锁
+// 锁顺序要求：FRAME_ALLOCATOR(层级0) 必须在 DMA_ALLOCATIONS(层级7) 之前
 lazy_static! {
-    static ref DMA_ALLOCATIONS: SpinLock<BTreeMap<PhysAddr, FrameRangeTracker>> =
+    static ref DMA_ALLOCATIONS: SpinLock<BTreeMap<PhysAddr, DmaBuffer>> =
         SpinLock::new(BTreeMap::new());
+
+    /// 共享给设备的驱动缓冲区
+    static ref DMA_MAPPINGS: SpinLock<BTreeMap<PhysAddr, DmaMapping>> =
+        SpinLock::new(BTreeMap::new());
+}
+
+impl From<BufferDirection> for DmaDirection {
+    fn from(direction: BufferDirection) -> Self {
+        match direction {
+            BufferDirection::DriverToDevice => DmaDirection::ToDevice,
+            BufferDirection::DeviceToDriver => DmaDirection::FromDevice,
+            BufferDirection::Both => DmaDirection::Bidirectional,
+        }
+    }
 }
 
 /// virtio-drivers 0.12.0 库使用的 HAL 实现
@@ -20,54 +41,22 @@ pub struct VirtIOHal;
 unsafe impl Hal for VirtIOHal {
     /// 分配并清零指定数量的连续物理页用于DMA
     fn dma_alloc(pages: usize, _direction: BufferDirection) -> (PhysAddr, NonNull<u8>) {
-        // 使用系统的连续物理帧分配器
-        let frame_range = match crate::mm::frame_allocator::alloc_contig_frames(pages) {
-            Some(range) => range,
-            None => {
-                // 返回空指针，让上层代码处理错误
-                return (PhysAddr::from(0u64), NonNull::dangling());
-            }
+        // 队列和 DMA 缓冲区必须位于 DMA32 区域
+        let Some(buf) = dma_alloc(pages) else {
+            // 返回空指针，让上层代码处理错误
+            return (PhysAddr::from(0u64), NonNull::dangling());
         };
-
-        // 获取起始物理页号
-        let start_ppn = frame_range.start_ppn();
-
-        // 计算物理地址
-        let phys_addr = PhysAddr::from(start_ppn.start_addr().as_usize() as u64);
-
-        // 将物理地址转换为虚拟地址
-        let virt_addr = start_ppn.start_addr().to_va().as_mut_ptr::<u8>();
-        let virt_ptr = NonNull::new(virt_addr).unwrap();
-
-        // 清零 DMA 缓冲区（VirtIO HAL trait 要求）
-        unsafe {
-            for page_idx in 0..pages {
-                let page_start = virt_addr.add(page_idx * crate::config::PAGE_SIZE);
-
-                // 逐字节清零整个页面
-                for offset in 0..crate::config::PAGE_SIZE {
-                    core::ptr::write_volatile(page_start.add(offset), 0);
-                }
-            }
-        }
-
-        // 将帧范围存储到全局映射表中
-        DMA_ALLOCATIONS.lock().insert(phys_addr, frame_range);
-
+        let phys_addr = PhysAddr::from(buf.paddr().as_usize() as u64);
+        let virt_ptr = NonNull::new(buf.vaddr().as_mut_ptr::<u8>()).unwrap();
+        DMA_ALLOCATIONS.lock().insert(phys_addr, buf);
         (phys_addr, virt_ptr)
     }
 
     /// 释放之前分配的DMA内存
     unsafe fn dma_dealloc(paddr: PhysAddr, _vaddr: NonNull<u8>, _pages: usize) -> i32 {
-        // 从全局映射表中查找并移除对应的帧范围
-        // 注意：必须先释放DMA_ALLOCATIONS锁，再drop FrameRangeTracker
-        // 因为FrameRangeTracker::drop()会获取FRAME_ALLOCATOR锁
-        // 锁顺序要求：FRAME_ALLOCATOR(层级0) 必须在 DMA_ALLOCATIONS(层级7) 之前
-        let frame_range = DMA_ALLOCATIONS.lock().remove(&paddr);
-        // DMA_ALLOCATIONS锁已释放
-
-        // 现在可以安全地drop frame_range，它会获取FRAME_ALLOCATOR锁
-        if frame_range.is_some() {
+        let buf = DMA_ALLOCATIONS.lock().remove(&paddr);
+        // DMA_ALLOCATIONS 锁已释放，可以安全地 drop 缓冲区
+        if buf.is_some() {
             0 // 成功释放
         } else {
             -1 // 未找到对应的分配记录
@@ -86,16 +75,29 @@ unsafe impl Hal for VirtIOHal {
     }
 
     /// 共享内存区域给设备，并返回设备可访问的物理地址
-    unsafe fn share(buffer: NonNull<[u8]>, _direction: BufferDirection) -> PhysAddr {
-        let vaddr = buffer.as_ptr() as *const u8 as usize;
-        let paddr = unsafe { crate::arch::va_to_pa(VA::from_usize(vaddr)) };
-
-        PhysAddr::from(paddr.as_usize() as u64)
+    ///
+    /// 缓冲区超出 DMA32 区域时改为共享一块 DMA32 反弹缓冲区。
+    /// DMA32 内存耗尽时与 `dma_alloc` 一样返回空地址，设备访问失败，请求以错误完成。
+    unsafe fn share(buffer: NonNull<[u8]>, direction: BufferDirection) -> PhysAddr {
+        let vaddr = VA::from_usize(buffer.as_ptr() as *const u8 as usize);
+        let Some(mapping) = (unsafe { dma_map_single(vaddr, buffer.len(), direction.into()) })
+        else {
+            crate::pr_warn!("[virtio] out of DMA32 memory for bounce buffer");
+            return PhysAddr::from(0u64);
+        };
+        let paddr = PhysAddr::from(mapping.paddr().as_usize() as u64);
+        let stale = DMA_MAPPINGS.lock().insert(paddr, mapping);
+        drop(stale);
+        paddr
     }
 
     /// 取消共享内存区域，并在必要时将数据复制回原始缓冲区
-    unsafe fn unshare(_paddr: PhysAddr, _buffer: NonNull<[u8]>, _direction: BufferDirection) {
-        // 简化实现，由于使用直接映射，不需要额外操作
+    unsafe fn unshare(paddr: PhysAddr, _buffer: NonNull<[u8]>, _direction: BufferDirection) {
+        let mapping = DMA_MAPPINGS.lock().remove(&paddr);
+        // DMA_MAPPINGS 锁已释放，解除映射时会释放反弹缓冲区
+        if let Some(mapping) = mapping {
+            crate::device::dma::dma_unmap(mapping);
+        }
     }
 }
 
@@ -104,4 +106,15 @@ impl VirtIOHal {
     pub fn new() -> Self {
         Self
     }
+
+    /// `vaddr` 所在的 DMA 缓冲区（由 [`Hal::dma_alloc`] 分配）对应的物理地址
+    pub fn dma_paddr(vaddr: VA) -> Option<PA> {
+        DMA_ALLOCATIONS
+            .lock()
+            .values()
+            .find(|buf| buf.contains(vaddr))
+            .map(|buf| {
+                PA::from_usize(buf.paddr().as_usize() + vaddr.as_usize() - buf.vaddr().as_usize())
+            })
+    }
 }
@@ -0,0 +1,122 @@
+//! Intel 6300ESB 看门狗驱动
+//!
+//! QEMU 以 `-device i6300esb` 提供的 PCI 看门狗。寄存器布局参考 Linux
+//! drivers/watchdog/i6300esb.c：
+//! - 配置空间 `0x60` 选择输出模式与时钟，`0x68` 的 ENABLE 位启动计时；
+//! - BAR0 中两级计时器的预置值，以及写入前需要解锁的 RELOAD 寄存器。
+//!
+//! 计时器使用约 1 kHz 的时钟，预置值为 `秒数 << 9`。第一级超时后进入第二级，
+//! 第二级超时时复位系统。
+
+use core::ptr::write_volatile;
+
+use super::WatchdogDriver;
+use crate::sync::SpinLock;
+
+/// PCI 厂商号
+pub const I6300ESB_VENDOR_ID: u16 = 0x8086;
+/// PCI 设备号
+pub const I6300ESB_DEVICE_ID: u16 = 0x25ab;
+
+/// 配置空间：看门狗配置
+const ESB_CONFIG_REG: usize = 0x60;
+/// 配置空间：看门狗锁定/使能
+const ESB_LOCK_REG: usize = 0x68;
+
+/// BAR0：第一级计时器预置值
+const ESB_TIMER1_REG: usize = 0x00;
+/// BAR0：第二级计时器预置值
+const ESB_TIMER2_REG: usize = 0x04;
+/// BAR0：重载寄存器
+const ESB_RELOAD_REG: usize = 0x0c;
+
+/// 关闭看门狗中断，1 kHz 时钟，超时时输出复位信号
+const ESB_WDT_INTTYPE_DISABLED: u16 = 0x03;
+const ESB_WDT_ENABLE: u8 = 0x02;
+const ESB_WDT_RELOAD: u16 = 1 << 8;
+const ESB_WDT_TIMEOUT: u16 = 1 << 9;
+const ESB_UNLOCK1: u16 = 0x80;
+const ESB_UNLOCK2: u16 = 0x86;
+
+/// 预置值寄存器为 20 位
+const ESB_MAX_TIMEOUT: u32 = 0x000f_ffff >> 9;
+
+/// i6300esb 看门狗
+pub struct I6300Esb {
+    /// 配置空间的内核虚拟地址
+    config: usize,
+    /// BAR0 的内核虚拟地址
+    regs: usize,
+    /// 串行化解锁序列与寄存器写入
+    lock: SpinLock<()>,
+}
+
+impl I6300Esb {
+    /// 初始化设备：关闭计时，清除超时标志
+    ///
+    /// # Safety
+    /// `config` 必须是该设备配置空间的映射，`regs` 必须是已映射的 BAR0。
+    pub unsafe fn new(config: usize, regs: usize) -> Self {
+        let esb = Self {
+            config,
+            regs,
+            lock: SpinLock::new(()),
+        };
+        unsafe {
+            write_volatile(
+                (config + ESB_CONFIG_REG) as *mut u16,
+                ESB_WDT_INTTYPE_DISABLED,
+            );
+            write_volatile((config + ESB_LOCK_REG) as *mut u8, 0);
+        }
+        esb.unlock_registers();
+        esb.write_reg16(ESB_RELOAD_REG, ESB_WDT_TIMEOUT | ESB_WDT_RELOAD);
+        esb
+    }
+
+    fn unlock_registers(&self) {
+        self.write_reg16(ESB_RELOAD_REG, ESB_UNLOCK1);
+        self.write_reg16(ESB_RELOAD_REG, ESB_UNLOCK2);
+    }
+
+    fn write_reg16(&self, offset: usize, val: u16) {
+        unsafe { write_volatile((self.regs + offset) as *mut u16, val) }
+    }
+
+    fn write_reg32(&self, offset: usize, val: u32) {
+        unsafe { write_volatile((self.regs + offset) as *mut u32, val) }
+    }
+}
+
+impl WatchdogDriver for I6300Esb {
+    fn identity(&self) -> &'static str {
+        "i6300ESB timer"
+    }
+
+    fn max_timeout(&self) -> u32 {
+        ESB_MAX_TIMEOUT
+    }
+
+    fn start(&self, timeout: u32) {
+        let val = timeout.clamp(1, ESB_MAX_TIMEOUT) << 9;
+        let _guard = self.lock.lock();
+        self.unlock_registers();
+        self.write_reg32(ESB_TIMER1_REG, val);
+        self.unlock_registers();
+        self.write_reg32(ESB_TIMER2_REG, val);
+        self.unlock_registers();
+        self.write_reg16(ESB_RELOAD_REG, ESB_WDT_RELOAD);
+        unsafe { write_volatile((self.config + ESB_LOCK_REG) as *mut u8, ESB_WDT_ENABLE) }
+    }
+
+    fn stop(&self) {
+        let _guard = self.lock.lock();
+        unsafe { write_volatile((self.config + ESB_LOCK_REG) as *mut u8, 0) }
+    }
+
+    fn keepalive(&self) {
+        let _guard = self.lock.lock();
+        self.unlock_registers();
+        self.write_reg16(ESB_RELOAD_REG, ESB_WDT_RELOAD);
+    }
+}
@@ -0,0 +1,45 @@
+//! 硬件看门狗驱动模块
+//!
+//! 硬件看门狗由 [`crate::kernel::watchdog`] 的 watchdogd 线程定期喂狗，
+//! 在所有 CPU 都停止响应（例如关中断死循环）时由硬件复位系统。
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use lazy_static::lazy_static;
+
+use crate::sync::RwLock;
+
+pub mod i6300esb;
+
+/// 硬件看门狗驱动接口
+pub trait WatchdogDriver: Send + Sync {
+    /// 设备名，用于日志和 `WDIOC_GETSUPPORT`
+    fn identity(&self) -> &'static str;
+
+    /// 支持的最长超时（秒）
+    fn max_timeout(&self) -> u32;
+
+    /// 以 `timeout` 秒的超时启动看门狗
+    fn start(&self, timeout: u32);
+
+    /// 停止看门狗
+    fn stop(&self);
+
+    /// 喂狗，重新开始计时
+    fn keepalive(&self);
+}
+
+lazy_static! {
+    /// 已探测到的硬件看门狗
+    pub static ref WATCHDOG_DRIVERS: RwLock<Vec<Arc<dyn WatchdogDriver>>> = RwLock::new(Vec::new());
+}
+
+/// 注册硬件看门狗
+pub fn register(driver: Arc<dyn WatchdogDriver>) {
+    crate::pr_info!(
+        "[Watchdog] registered hardware watchdog {}",
+        driver.identity()
+    );
+    WATCHDOG_DRIVERS.write().push(driver);
+}
@@ -7,6 +7,7 @@ use crate::device::block::BlockDriver;
 use crate::sync::SpinLock;
 use alloc::vec::Vec;
 use alloc::{collections::BTreeMap, sync::Arc};
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 const READ_CACHE_BLOCKS: usize = 1024;
 
@@ -81,6 +82,11 @@ pub struct BlockDeviceAdapter {
     sector_size: usize,
     /// 小型读缓存，避免动态加载器和目录遍历重复触发慢速 VirtIO 读。
     read_cache: SpinLock<BlockReadCache>,
+    /// 累计的读写失败次数
+    ///
+    /// ext4_rs 的 `BlockDevice` 接口无法返回错误，读失败时得到全零块、写失败时静默丢弃，
+    /// 由上层在每次操作后比较该计数发现错误（见 [`super::error`]）。
+    io_errors: AtomicUsize,
 }
 
 impl BlockDeviceAdapter {
@@ -103,8 +109,23 @@ impl BlockDeviceAdapter {
             block_size,
             sector_size,
             read_cache: SpinLock::new(BlockReadCache::new()),
+            io_errors: AtomicUsize::new(0),
         }
     }
+
+    /// 累计的读写失败次数
+    pub fn io_errors(&self) -> usize {
+        self.io_errors.load(Ordering::Relaxed)
+    }
+
+    /// 刷新底层设备的写缓存
+    pub fn flush(&self) -> bool {
+        self.inner.flush()
+    }
+
+    fn io_error(&self) {
+        self.io_errors.fetch_add(1, Ordering::Relaxed);
+    }
 }
 
 impl ext4_rs::BlockDevice for BlockDeviceAdapter {
@@ -136,6 +157,7 @@ impl ext4_rs::BlockDevice for BlockDeviceAdapter {
                 sectors_needed,
                 offset
             );
+            self.io_error();
             return alloc::vec![0u8; self.block_size];
         }
 
@@ -165,6 +187,7 @@ impl ext4_rs::BlockDevice for BlockDeviceAdapter {
 
         let Some(write_end) = offset.checked_add(bytes_to_write) else {
             crate::pr_err!("[Ext4Adapter] Write offset overflow at offset {}", offset);
+            self.io_error();
             return;
         };
 
@@ -180,6 +203,7 @@ impl ext4_rs::BlockDevice for BlockDeviceAdapter {
                     sectors_needed,
                     offset
                 );
+                self.io_error();
             }
             return;
         }
@@ -203,6 +227,7 @@ impl ext4_rs::BlockDevice for BlockDeviceAdapter {
                         sector_id,
                         offset
                     );
+                    self.io_error();
                 }
                 continue;
             }
@@ -214,6 +239,7 @@ impl ext4_rs::BlockDevice for BlockDeviceAdapter {
                     sector_id,
                     offset
                 );
+                self.io_error();
                 return;
             }
             let sector_buf_start = overlap_start - sector_start;
@@ -227,6 +253,7 @@ impl ext4_rs::BlockDevice for BlockDeviceAdapter {
                     sector_id,
                     offset
                 );
+                self.io_error();
             }
         }
     }
@@ -0,0 +1,148 @@
+//! Ext4 错误处理策略
+//!
+//! ext4_rs 没有日志，元数据更新写到一半出错时磁盘结构可能已经不一致。与 Linux 的
+//! `errors=` 挂载选项相同，检测到错误后把超级块标记为 `EXT4_ERROR_FS`、记录出错的
+//! inode 和时间，再按超级块 `s_errors` 字段选择处理方式：
+//!
+//! - `EXT4_ERRORS_PANIC`：内核 panic；
+//! - 其余取值：文件系统转为只读，之后的写操作返回 `ReadOnlyFs`。
+//!
+//! `errors=continue` 同样按只读处理：没有日志可供回放，继续写入只会扩大损坏。
+//!
+//! 错误有两个来源：ext4_rs 返回的 `EIO`，以及块设备适配器记录的读写失败
+//! （ext4_rs 的 `BlockDevice` 接口无法向上传递错误，只能事后比较计数）。
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use ext4_rs::{Errno, Ext4Error};
+
+use super::BlockDeviceAdapter;
+use crate::uapi::time::TimeSpec;
+use crate::vfs::FsError;
+
+/// `s_state`：文件系统检测到过错误
+pub const EXT4_ERROR_FS: u16 = 0x0002;
+
+/// `s_errors`：出错时 panic
+const EXT4_ERRORS_PANIC: u16 = 3;
+
+/// 检测到错误后的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// 转为只读（`errors=remount-ro`）
+    RemountRo,
+    /// 内核 panic（`errors=panic`）
+    Panic,
+}
+
+impl ErrorPolicy {
+    /// 由超级块的 `s_errors` 字段得到处理方式
+    pub fn from_super_block(errors: u16) -> Self {
+        match errors {
+            EXT4_ERRORS_PANIC => Self::Panic,
+            _ => Self::RemountRo,
+        }
+    }
+}
+
+/// 一个已挂载 ext4 实例的错误状态
+pub struct Ext4ErrorState {
+    adapter: Arc<BlockDeviceAdapter>,
+    policy: ErrorPolicy,
+    read_only: AtomicBool,
+    /// 已经处理过的适配器读写失败次数
+    seen_io_errors: AtomicUsize,
+}
+
+impl Ext4ErrorState {
+    pub fn new(adapter: Arc<BlockDeviceAdapter>, policy: ErrorPolicy) -> Self {
+        let seen_io_errors = AtomicUsize::new(adapter.io_errors());
+        Self {
+            adapter,
+            policy,
+            read_only: AtomicBool::new(false),
+            seen_io_errors,
+        }
+    }
+
+    /// 文件系统是否已因错误转为只读
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Acquire)
+    }
+
+    /// 写操作前调用，只读时返回 `ReadOnlyFs`
+    pub fn check_writable(&self) -> Result<(), FsError> {
+        if self.is_read_only() {
+            Err(FsError::ReadOnlyFs)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 检查自上次检查以来块设备是否出错，出错时执行错误策略
+    pub fn check_io(&self, fs: &mut ext4_rs::Ext4, ino: u32, op: &str) -> Result<(), FsError> {
+        if self.adapter.io_errors() == self.seen_io_errors.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        self.handle(fs, ino, op, "block device I/O error");
+        Err(FsError::IoError)
+    }
+
+    /// 转换 ext4_rs 返回的错误
+    ///
+    /// `EIO` 或期间块设备出错时执行错误策略并返回 `IoError`；其余错误（如空间不足、
+    /// 目录非空）不影响文件系统一致性，返回调用者给出的 `fallback`。
+    pub fn map_err(
+        &self,
+        fs: &mut ext4_rs::Ext4,
+        ino: u32,
+        op: &str,
+        err: Ext4Error,
+        fallback: FsError,
+    ) -> FsError {
+        if err.error() == Errno::EIO {
+            self.handle(fs, ino, op, "metadata I/O error");
+            return FsError::IoError;
+        }
+        match self.check_io(fs, ino, op) {
+            Ok(()) => fallback,
+            Err(e) => e,
+        }
+    }
+
+    /// 刷新块设备的写缓存
+    pub fn flush(&self) -> Result<(), FsError> {
+        if self.adapter.flush() {
+            Ok(())
+        } else {
+            Err(FsError::IoError)
+        }
+    }
+
+    fn handle(&self, fs: &mut ext4_rs::Ext4, ino: u32, op: &str, what: &str) {
+        crate::pr_err!("EXT4-fs error: {}: inode #{}: {}", op, ino, what);
+
+        if !self.is_read_only() {
+            // 尽力把错误记录到超级块，fsck 据此知道需要完整检查
+            let now = TimeSpec::now().tv_sec as u32;
+            fs.update_super_block(|sb| {
+                sb.set_state(sb.state() | EXT4_ERROR_FS);
+                sb.record_error(now, ino);
+            });
+            self.adapter.flush();
+        }
+        // 记录超级块本身也可能失败，这些失败归入本次错误
+        self.seen_io_errors
+            .store(self.adapter.io_errors(), Ordering::Release);
+
+        match self.policy {
+            ErrorPolicy::Panic => panic!("EXT4-fs (errors=panic): {}: inode #{}", op, ino),
+            ErrorPolicy::RemountRo => {
+                if !self.read_only.swap(true, Ordering::AcqRel) {
+                    crate::pr_err!("EXT4-fs: Remounting filesystem read-only");
+                }
+            }
+        }
+    }
+}
@@ -5,8 +5,13 @@
 //! 设计要点：
 //! - 使用 Dentry 引用而非存储路径，消除与 VFS 的冗余
 //! - 需要路径时动态从 Dentry.full_path() 获取
+//! - 修改操作在文件系统因错误转为只读后返回 `ReadOnlyFs`，
+//!   ext4_rs 或块设备出错时交给 [`Ext4ErrorState`] 按错误策略处理
 
-use crate::sync::{Mutex, SpinLock};
+use super::error::Ext4ErrorState;
+use super::orphan::{self, OrphanTracker};
+use crate::config::EXT4_BLOCK_SIZE;
+use crate::sync::{Mutex, MutexGuard, SpinLock};
 use crate::uapi::time::TimeSpec;
 use alloc::collections::BTreeMap;
 use alloc::string::String;
@@ -19,10 +24,16 @@ use crate::vfs::dev::{
     major as dev_major, minor as dev_minor,
 };
 use crate::vfs::page_cache::{PAGE_CACHE_PAGE_SIZE, PageCache, PageCacheObjectId};
+use crate::vfs::timestamps::atime_needs_update;
 use crate::vfs::{Dentry, DirEntry, FileMode, FsError, Inode, InodeMetadata, InodeType};
 
 const LOOKUP_CACHE_MAX_ENTRIES: usize = 4096;
 
+/// 时间戳的 extra 字段：`(nanosec << 2) | epoch_bits`，epoch_bits 固定为 0
+fn time_extra(time: &TimeSpec) -> u32 {
+    (time.tv_nsec as u32) << 2
+}
+
 struct LookupCache {
     entries: BTreeMap<u32, BTreeMap<String, u32>>,
     len: usize,
@@ -85,14 +96,36 @@ impl LookupCache {
     }
 }
 
+/// 同一文件系统实例的所有 inode 共享的状态
 pub struct Ext4InodeCaches {
     lookup: SpinLock<LookupCache>,
+    pub(super) errors: Ext4ErrorState,
+    orphans: SpinLock<OrphanTracker>,
 }
 
 impl Ext4InodeCaches {
-    pub const fn new() -> Self {
+    pub fn new(errors: Ext4ErrorState) -> Self {
         Self {
             lookup: SpinLock::new(LookupCache::new()),
+            errors,
+            orphans: SpinLock::new(OrphanTracker::new()),
+        }
+    }
+
+    /// 释放最后一个引用已经消失的孤儿 inode
+    ///
+    /// `Ext4Inode` 在 drop 时不能获取 ext4_rs 的睡眠锁（调用者可能正持有它），
+    /// 只把 inode 号记下，由下一个持锁者在这里释放。只读时保留在链表上，留给下次挂载。
+    pub(super) fn reap_orphans(&self, fs: &mut ext4_rs::Ext4) {
+        if self.errors.is_read_only() {
+            return;
+        }
+        let pending = self.orphans.lock().take_pending();
+        for ino in pending {
+            if let Err(e) = orphan::release_orphan(fs, ino) {
+                self.errors
+                    .map_err(fs, ino, "orphan cleanup", e, FsError::IoError);
+            }
         }
     }
 }
@@ -130,6 +163,7 @@ impl Ext4Inode {
         fs_id: u64,
         ino: u32,
     ) -> Self {
+        caches.orphans.lock().get(ino);
         Self {
             fs,
             ino,
@@ -140,6 +174,108 @@ impl Ext4Inode {
         }
     }
 
+    /// 获取 ext4_rs 锁，顺带释放等待回收的孤儿 inode
+    fn lock_fs(&self) -> MutexGuard<'_, ext4_rs::Ext4> {
+        let mut fs = self.fs.lock();
+        self.caches.reap_orphans(&mut fs);
+        fs
+    }
+
+    fn check_writable(&self) -> Result<(), FsError> {
+        self.caches.errors.check_writable()
+    }
+
+    /// 内容被修改：把 mtime 和 ctime 设为当前时间并写回 inode
+    fn touch_modified(&self, fs: &mut ext4_rs::Ext4) {
+        let now = TimeSpec::now();
+        let mut inode_ref = fs.get_inode_ref(self.ino);
+        let inode = &mut inode_ref.inode;
+        inode.mtime = now.tv_sec as u32;
+        inode.i_mtime_extra = time_extra(&now);
+        inode.ctime = now.tv_sec as u32;
+        inode.i_ctime_extra = time_extra(&now);
+        fs.write_back_inode(&mut inode_ref);
+    }
+
+    /// 持有文件系统锁时读取文件大小
+    fn size_locked(&self, fs: &ext4_rs::Ext4) -> usize {
+        let inode = fs.get_inode_ref(self.ino).inode;
+        ((inode.size as u64) | ((inode.size_hi as u64) << 32)) as usize
+    }
+
+    /// 持有文件系统锁时写入数据并刷新页缓存
+    ///
+    /// 写入前的文件大小在锁内读取，与并发的写入和截断保持一致。
+    fn write_locked(
+        &self,
+        fs: &mut ext4_rs::Ext4,
+        offset: usize,
+        buf: &[u8],
+    ) -> Result<usize, FsError> {
+        let old_size = self.size_locked(fs);
+
+        // ext4_rs 的 write_at 签名: pub fn write_at(&self, inode: u32, offset: usize, write_buf: &[u8])
+        let written = fs
+            .write_at(self.ino, offset, buf)
+            .map_err(|e| self.fail(fs, "write", e, FsError::IoError))?;
+        if let Err(e) = self.check_io(fs, "write") {
+            // 缓存中可能已是部分写入的数据
+            self.invalidate_read_cache();
+            return Err(e);
+        }
+        if written > 0 {
+            self.touch_modified(fs);
+            if offset > old_size {
+                self.refresh_zero_cache_range(old_size, offset - old_size);
+            }
+            self.page_cache.refresh_clean_range(
+                self.cache_object_id(),
+                offset,
+                &buf[..written.min(buf.len())],
+            );
+        }
+        Ok(written)
+    }
+
+    /// 操作结束前检查期间块设备是否出错
+    fn check_io(&self, fs: &mut ext4_rs::Ext4, op: &str) -> Result<(), FsError> {
+        self.caches.errors.check_io(fs, self.ino, op)
+    }
+
+    /// 转换 ext4_rs 返回的错误，I/O 错误按错误策略处理
+    fn fail(
+        &self,
+        fs: &mut ext4_rs::Ext4,
+        op: &str,
+        err: ext4_rs::Ext4Error,
+        fallback: FsError,
+    ) -> FsError {
+        self.caches.errors.map_err(fs, self.ino, op, err, fallback)
+    }
+
+    /// 链接数减一；降为 0 时仍被打开则挂上孤儿链表，否则立即释放
+    fn drop_link(&self, fs: &mut ext4_rs::Ext4, ino: u32) -> Result<(), FsError> {
+        let mut inode_ref = fs.get_inode_ref(ino);
+        let links = inode_ref.inode.links_count().saturating_sub(1);
+        inode_ref.inode.set_links_count(links);
+        inode_ref.inode.set_ctime(TimeSpec::now().tv_sec as u32);
+        fs.write_back_inode(&mut inode_ref);
+        if links > 0 {
+            return Ok(());
+        }
+
+        if self.caches.orphans.lock().defer(ino) {
+            orphan::add_orphan(fs, ino);
+            Ok(())
+        } else {
+            orphan::release_orphan(fs, ino).map_err(|e| {
+                self.caches
+                    .errors
+                    .map_err(fs, ino, "unlink", e, FsError::IoError)
+            })
+        }
+    }
+
     fn invalidate_read_cache(&self) {
         self.page_cache.invalidate_inode(self.cache_object_id());
     }
@@ -274,7 +410,7 @@ impl Ext4Inode {
             return Ok(None);
         }
 
-        let fs = self.fs.lock();
+        let fs = self.lock_fs();
         let inode_ref = fs.get_inode_ref(self.ino);
         let inode = &inode_ref.inode;
 
@@ -292,7 +428,7 @@ impl Ext4Inode {
 
 impl Inode for Ext4Inode {
     fn metadata(&self) -> Result<InodeMetadata, FsError> {
-        let fs = self.fs.lock();
+        let fs = self.lock_fs();
 
         let inode_ref = fs.get_inode_ref(self.ino);
         let inode = &inode_ref.inode;
@@ -351,7 +487,21 @@ impl Inode for Ext4Inode {
     }
 
     fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, FsError> {
-        if buf.is_empty() {
+        let mut copied = 0;
+        self.read_pages(offset, buf.len(), &mut |data| {
+            buf[copied..copied + data.len()].copy_from_slice(data);
+            copied += data.len();
+            Ok(())
+        })
+    }
+
+    fn read_pages(
+        &self,
+        offset: usize,
+        len: usize,
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), FsError>,
+    ) -> Result<usize, FsError> {
+        if len == 0 {
             return Ok(0);
         }
 
@@ -365,7 +515,7 @@ impl Inode for Ext4Inode {
             return Ok(0);
         }
 
-        let target_len = buf.len().min(metadata.size - offset);
+        let target_len = len.min(metadata.size - offset);
         let object = self.cache_object_id();
         let mut copied = 0;
         while copied < target_len {
@@ -374,36 +524,31 @@ impl Inode for Ext4Inode {
             let page_offset = current_offset % PAGE_CACHE_PAGE_SIZE;
             let chunk_len = (PAGE_CACHE_PAGE_SIZE - page_offset).min(target_len - copied);
 
-            if let Some(n) = self.page_cache.read_hit(
-                object,
-                current_offset,
-                &mut buf[copied..copied + chunk_len],
-            ) {
-                copied += n;
-                if n == 0 {
-                    break;
-                }
-                continue;
-            }
-
+            // 命中时直接返回缓存页；未命中时把磁盘数据读入新的缓存页
             let page_start = page_index * PAGE_CACHE_PAGE_SIZE;
             let page_len = PAGE_CACHE_PAGE_SIZE.min(metadata.size.saturating_sub(page_start));
             let page =
                 self.page_cache
                     .get_or_insert_clean_page(object, page_index, |page_buf| {
-                        let fs = self.fs.lock();
-                        fs.read_at(self.ino, page_start, &mut page_buf[..page_len])
-                            .map_err(|_| FsError::IoError)
+                        let mut fs = self.lock_fs();
+                        let read = fs
+                            .read_at(self.ino, page_start, &mut page_buf[..page_len])
+                            .map_err(|e| self.fail(&mut fs, "read", e, FsError::IoError))?;
+                        self.check_io(&mut fs, "read")?;
+                        Ok(read)
                     })?;
 
-            if page_offset >= page.data().len() {
+            let data = page.data();
+            if page_offset >= data.len() {
                 break;
             }
-
-            let n = page.copy_out(page_offset, &mut buf[copied..copied + chunk_len]);
+            let n = (data.len() - page_offset).min(chunk_len);
+            if let Err(e) = sink(&data[page_offset..page_offset + n]) {
+                return if copied > 0 { Ok(copied) } else { Err(e) };
+            }
             copied += n;
 
-            if n == 0 {
+            if n < chunk_len {
                 break;
             }
         }
@@ -418,23 +563,23 @@ impl Inode for Ext4Inode {
             return Err(FsError::IsDirectory);
         }
 
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
+        self.write_locked(&mut fs, offset, buf)
+    }
 
-        // ext4_rs 的 write_at 签名: pub fn write_at(&self, inode: u32, offset: usize, write_buf: &[u8])
-        let written = fs
-            .write_at(self.ino, offset, buf)
-            .map_err(|_| FsError::IoError)?;
-        if written > 0 {
-            if offset > metadata.size {
-                self.refresh_zero_cache_range(metadata.size, offset - metadata.size);
-            }
-            self.page_cache.refresh_clean_range(
-                self.cache_object_id(),
-                offset,
-                &buf[..written.min(buf.len())],
-            );
+    fn append(&self, buf: &[u8]) -> Result<(usize, usize), FsError> {
+        let metadata = self.metadata()?;
+        if metadata.inode_type == InodeType::Directory {
+            return Err(FsError::IsDirectory);
         }
-        Ok(written)
+
+        self.check_writable()?;
+        // 同一把文件系统锁内读取大小并写入，其它会话的写入和截断不会插入其间
+        let mut fs = self.lock_fs();
+        let offset = self.size_locked(&fs);
+        let written = self.write_locked(&mut fs, offset, buf)?;
+        Ok((offset, written))
     }
 
     fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>, FsError> {
@@ -456,7 +601,7 @@ impl Inode for Ext4Inode {
 
         // 类似 create,lookup 也应该使用相对路径
         // 直接在当前目录下查找指定名称的文件
-        let fs = self.fs.lock();
+        let fs = self.lock_fs();
         let mut parent = self.ino;
         let mut name_off = 0;
 
@@ -488,14 +633,16 @@ impl Inode for Ext4Inode {
             return Err(FsError::AlreadyExists);
         }
 
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
         let file_mode = Self::regular_file_mode(mode);
 
         let mut child_inode = fs
             .create(self.ino, name, file_mode)
-            .map_err(|_| FsError::IoError)?;
+            .map_err(|e| self.fail(&mut fs, "create", e, FsError::IoError))?;
         child_inode.inode.set_mode(file_mode);
         fs.write_back_inode(&mut child_inode);
+        self.check_io(&mut fs, "create")?;
 
         self.drop_lookup_cache_entry(name);
         self.invalidate_inode_no(child_inode.inode_num);
@@ -520,7 +667,8 @@ impl Inode for Ext4Inode {
             return Err(FsError::AlreadyExists);
         }
 
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
         let dir_mode = Self::directory_mode(mode);
 
         let mut parent = self.ino;
@@ -530,11 +678,12 @@ impl Inode for Ext4Inode {
             .generic_open(name, &mut parent, true, dir_mode, &mut name_off)
             .map_err(|e| {
                 crate::pr_debug!("[Ext4Inode::mkdir] generic_open failed: {:?}", e);
-                FsError::NoSpace
+                self.fail(&mut fs, "mkdir", e, FsError::NoSpace)
             })?;
         let mut inode_ref = fs.get_inode_ref(inode_id);
         inode_ref.inode.set_mode(dir_mode);
         fs.write_back_inode(&mut inode_ref);
+        self.check_io(&mut fs, "mkdir")?;
 
         self.drop_lookup_cache_entry(name);
         self.invalidate_inode_no(inode_id);
@@ -556,11 +705,12 @@ impl Inode for Ext4Inode {
 
         let parent = self.ino;
         let inode_mod = InodeFileType::S_IFLNK.bits() | 0o777;
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
 
         let new_inode = fs
             .create(parent, name, inode_mod)
-            .map_err(|_| FsError::NoSpace)?;
+            .map_err(|e| self.fail(&mut fs, "symlink", e, FsError::NoSpace))?;
 
         const FAST_SYMLINK_MAX_LEN: usize = 15 * core::mem::size_of::<u32>();
         let target_bytes = target.as_bytes();
@@ -579,8 +729,9 @@ impl Inode for Ext4Inode {
             fs.write_back_inode(&mut inode_ref);
         } else {
             fs.write_at(new_inode.inode_num, 0, target_bytes)
-                .map_err(|_| FsError::IoError)?;
+                .map_err(|e| self.fail(&mut fs, "symlink", e, FsError::IoError))?;
         }
+        self.check_io(&mut fs, "symlink")?;
 
         self.drop_lookup_cache_entry(name);
         self.invalidate_inode_no(new_inode.inode_num);
@@ -609,11 +760,19 @@ impl Inode for Ext4Inode {
             return Err(FsError::CrossDeviceLink);
         }
 
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
         let mut self_ref = fs.get_inode_ref(self.ino);
         let mut target_ref = fs.get_inode_ref(ext4_inode.ino);
+        // 已删除（在孤儿链表上）的 inode 不能再获得链接
+        if target_ref.inode.links_count() == 0 {
+            return Err(FsError::NotFound);
+        }
         fs.link(&mut self_ref, &mut target_ref, name)
-            .map_err(|_| FsError::NoSpace)?;
+            .map_err(|e| self.fail(&mut fs, "link", e, FsError::NoSpace))?;
+        // ext4_rs 只在内存中增加链接数，需要写回，否则删除其中一个名字就会释放 inode
+        fs.write_back_inode(&mut target_ref);
+        self.check_io(&mut fs, "link")?;
 
         self.drop_lookup_cache_entry(name);
         self.invalidate_inode_no(ext4_inode.ino);
@@ -632,12 +791,16 @@ impl Inode for Ext4Inode {
         let child_metadata = child.metadata()?;
 
         // 获取 child 的 inode 号
-        let child_ext4 = child
+        let child_ino = child
             .as_any()
             .downcast_ref::<Ext4Inode>()
-            .ok_or(FsError::InvalidArgument)?;
+            .ok_or(FsError::InvalidArgument)?
+            .ino;
+        // 本次查找得到的引用不算作“仍被打开”
+        drop(child);
 
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
 
         // Workaround for ext4_rs bug: dir_remove() 无条件调用 dir_has_entry()
         // 但 dir_has_entry() 内部 assert child 必须是目录
@@ -646,24 +809,22 @@ impl Inode for Ext4Inode {
         if child_metadata.inode_type == InodeType::Directory {
             // 对于目录，使用 dir_remove（它会检查目录是否为空）
             fs.dir_remove(self.ino, name)
-                .map_err(|_| FsError::IoError)?;
+                .map_err(|e| self.fail(&mut fs, "unlink", e, FsError::IoError))?;
         } else {
-            // 对于普通文件，使用底层 API 手动删除
+            // 对于普通文件，只删除目录项并减少链接数。
+            // ext4_rs 的 unlink 会无条件释放 inode，不适用于硬链接和仍被打开的文件
             let mut parent_ref = fs.get_inode_ref(self.ino);
-            let mut child_ref = fs.get_inode_ref(child_ext4.ino);
-
-            // 调用底层的 unlink，它会：
-            // 1. 删除目录项（dir_remove_entry）
-            // 2. 释放 inode（ialloc_free_inode）
-            fs.unlink(&mut parent_ref, &mut child_ref, name)
-                .map_err(|_| FsError::IoError)?;
+            fs.dir_remove_entry(&mut parent_ref, name)
+                .map_err(|e| self.fail(&mut fs, "unlink", e, FsError::IoError))?;
 
             // 写回 parent inode
             fs.write_back_inode(&mut parent_ref);
+            self.drop_link(&mut fs, child_ino)?;
         }
+        self.check_io(&mut fs, "unlink")?;
 
         self.drop_lookup_cache_entry(name);
-        self.invalidate_inode_no(child_ext4.ino);
+        self.invalidate_inode_no(child_ino);
         Ok(())
     }
 
@@ -674,15 +835,16 @@ impl Inode for Ext4Inode {
             return Err(FsError::NotDirectory);
         }
 
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
         let parent = self.ino;
 
         fs.dir_remove(parent, name)
-            .map(|_| {
-                self.drop_lookup_cache_entry(name);
-                self.page_cache.invalidate_fs(self.fs_id);
-            })
-            .map_err(|_| FsError::NotFound)
+            .map_err(|e| self.fail(&mut fs, "rmdir", e, FsError::NotFound))?;
+        self.check_io(&mut fs, "rmdir")?;
+        self.drop_lookup_cache_entry(name);
+        self.page_cache.invalidate_fs(self.fs_id);
+        Ok(())
     }
 
     /// 重命名或移动文件/目录
@@ -761,7 +923,8 @@ impl Inode for Ext4Inode {
         }
 
         // 持有锁直到操作完成
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
 
         // ========== 阶段 2: 检查目标是否存在 ==========
 
@@ -796,14 +959,13 @@ impl Inode for Ext4Inode {
 
                 // 删除空目录
                 fs.dir_remove(new_parent_ext4.ino, new_name)
-                    .map_err(|_| FsError::IoError)?;
+                    .map_err(|e| self.fail(&mut fs, "rename", e, FsError::IoError))?;
             } else {
-                // 删除普通文件
+                // 删除普通文件的目录项；链接数在重命名成功后再减少，回滚时无需恢复 inode
                 let mut new_parent_ref = fs.get_inode_ref(new_parent_ext4.ino);
-                let mut existing_ref = fs.get_inode_ref(existing_ino);
 
-                fs.unlink(&mut new_parent_ref, &mut existing_ref, new_name)
-                    .map_err(|_| FsError::IoError)?;
+                fs.dir_remove_entry(&mut new_parent_ref, new_name)
+                    .map_err(|e| self.fail(&mut fs, "rename", e, FsError::IoError))?;
 
                 fs.write_back_inode(&mut new_parent_ref);
             }
@@ -904,6 +1066,13 @@ impl Inode for Ext4Inode {
         fs.write_back_inode(&mut old_parent_ref);
         fs.write_back_inode(&mut new_parent_ref);
 
+        if let Some(replaced_ino) = replaced_inode
+            && !fs.get_inode_ref(replaced_ino).inode.is_dir()
+        {
+            self.drop_link(&mut fs, replaced_ino)?;
+        }
+        self.check_io(&mut fs, "rename")?;
+
         self.drop_lookup_cache_entry(old_name);
         new_parent_ext4.drop_lookup_cache_entry(new_name);
         self.invalidate_inode_no(old_child_ext4.ino);
@@ -920,7 +1089,7 @@ impl Inode for Ext4Inode {
             return Err(FsError::NotDirectory);
         }
 
-        let fs = self.fs.lock();
+        let fs = self.lock_fs();
 
         // ext4_rs 的 dir_get_entries 签名: pub fn dir_get_entries(&self, inode: u32) -> Vec<Ext4DirEntry>
         // 直接返回 Vec，不需要 map_err
@@ -949,44 +1118,57 @@ impl Inode for Ext4Inode {
     }
 
     fn truncate(&self, size: usize) -> Result<(), FsError> {
-        let metadata = self.metadata()?;
-        let old_size = metadata.size;
+        // 原大小在锁内读取，截断不会与其它会话的写入或追加交错
+        let mut fs = self.lock_fs();
+        let old_size = self.size_locked(&fs);
 
         if size == old_size {
             // 大小不变，直接返回
             return Ok(());
         }
+        self.check_writable()?;
 
         if size < old_size {
             // 缩小文件：使用 ext4_rs 的 truncate_inode
-            let fs = self.fs.lock();
             let mut inode_ref = fs.get_inode_ref(self.ino);
-            fs.truncate_inode(&mut inode_ref, size as u64)
-                .map_err(|_| FsError::IoError)?;
+            let result = fs
+                .truncate_inode(&mut inode_ref, size as u64)
+                .map_err(|e| self.fail(&mut fs, "truncate", e, FsError::IoError))
+                .and_then(|_| {
+                    self.touch_modified(&mut fs);
+                    self.check_io(&mut fs, "truncate")
+                });
             let invalidate_start = (size / PAGE_CACHE_PAGE_SIZE) * PAGE_CACHE_PAGE_SIZE;
             self.page_cache.invalidate_range(
                 self.cache_object_id(),
                 invalidate_start,
                 old_size - invalidate_start,
             );
+            result?;
         } else {
-            // 扩展文件：ext4_rs 的 truncate_inode 不支持扩展（有 assert）
-            // Workaround: 在文件末尾写入零字节来扩展
-            // 这是安全的，因为：
-            // 1. 写入位置从 old_size 开始，在现有数据之后
-            // 2. write_at 会分配新块并更新文件大小
-            // 3. 符合 POSIX truncate 语义（新增部分填充零）
+            // 扩展文件：ext4_rs 的 truncate_inode 不支持扩展（有 assert）。
+            // 扩展部分作为空洞不分配块，只清零原末尾块中 old_size 之后的残留数据，
+            // 再直接修改 inode 大小
             let extend_size = size - old_size;
-            let zero_buf = alloc::vec![0u8; extend_size.min(4096)]; // 使用 4KB 缓冲区
-
-            let fs = self.fs.lock();
-            let mut written = 0;
-            while written < extend_size {
-                let to_write = (extend_size - written).min(zero_buf.len());
-                fs.write_at(self.ino, old_size + written, &zero_buf[..to_write])
-                    .map_err(|_| FsError::IoError)?;
-                written += to_write;
+
+            let tail = old_size % EXT4_BLOCK_SIZE;
+            if tail != 0 {
+                let inode_ref = fs.get_inode_ref(self.ino);
+                if fs
+                    .get_pblock_idx(&inode_ref, (old_size / EXT4_BLOCK_SIZE) as u32)
+                    .is_ok()
+                {
+                    let zero_len = (EXT4_BLOCK_SIZE - tail).min(extend_size);
+                    let zero_buf = alloc::vec![0u8; zero_len];
+                    fs.write_at(self.ino, old_size, &zero_buf)
+                        .map_err(|e| self.fail(&mut fs, "truncate", e, FsError::IoError))?;
+                }
             }
+            let mut inode_ref = fs.get_inode_ref(self.ino);
+            inode_ref.inode.set_size(size as u64);
+            fs.write_back_inode(&mut inode_ref);
+            self.touch_modified(&mut fs);
+            self.check_io(&mut fs, "truncate")?;
             self.refresh_zero_cache_range(old_size, extend_size);
         }
 
@@ -994,8 +1176,56 @@ impl Inode for Ext4Inode {
     }
 
     fn sync(&self) -> Result<(), FsError> {
-        // ext4_rs 会自动同步数据到 BlockDevice
-        Ok(())
+        // ext4_rs 写直达 BlockDevice，数据块和 inode 在每次修改时已经写出；
+        // 这里再写回一次 inode，确认期间没有写失败，然后刷新设备写缓存
+        let mut fs = self.lock_fs();
+        if !self.caches.errors.is_read_only() {
+            let mut inode_ref = fs.get_inode_ref(self.ino);
+            fs.write_back_inode(&mut inode_ref);
+        }
+        self.check_io(&mut fs, "fsync")?;
+        drop(fs);
+        self.caches.errors.flush()
+    }
+
+    fn next_data(&self, offset: usize) -> Result<Option<(usize, usize)>, FsError> {
+        let size = self.metadata()?.size;
+        if offset >= size {
+            return Ok(None);
+        }
+
+        const EXT4_INODE_FLAG_EXTENTS: u32 = 0x0008_0000;
+        let fs = self.lock_fs();
+        let inode_ref = fs.get_inode_ref(self.ino);
+        if inode_ref.inode.flags & EXT4_INODE_FLAG_EXTENTS == 0 {
+            // 非 extent 文件（如快速符号链接）视为没有空洞
+            return Ok(Some((offset, size)));
+        }
+        let extents = fs.inode_extents(&inode_ref).map_err(|_| FsError::IoError)?;
+        drop(fs);
+
+        // extent 按逻辑块有序，找出第一个结束于 offset 之后的
+        Ok(extents
+            .iter()
+            .map(|extent| {
+                let start = extent.get_first_block() as usize * EXT4_BLOCK_SIZE;
+                (
+                    start,
+                    start + extent.get_actual_len() as usize * EXT4_BLOCK_SIZE,
+                )
+            })
+            .find(|&(_, end)| end > offset)
+            .map(|(start, end)| (start, end.min(size)))
+            .filter(|&(start, _)| start < size))
+    }
+
+    fn swap_backing(&self) -> Result<Arc<dyn crate::mm::swap::SwapBacking>, FsError> {
+        let meta = self.metadata()?;
+        if meta.inode_type != InodeType::File {
+            return Err(FsError::InvalidArgument);
+        }
+        let backing = super::swap::Ext4SwapBacking::new(&self.fs, self.ino, meta.size)?;
+        Ok(Arc::new(backing))
     }
 
     fn set_dentry(&self, dentry: Weak<Dentry>) {
@@ -1011,7 +1241,8 @@ impl Inode for Ext4Inode {
     }
 
     fn set_times(&self, atime: Option<TimeSpec>, mtime: Option<TimeSpec>) -> Result<(), FsError> {
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
 
         // 获取 inode 引用（可变）
         let mut inode_ref = fs.get_inode_ref(self.ino);
@@ -1020,30 +1251,48 @@ impl Inode for Ext4Inode {
         // 更新访问时间
         if let Some(at) = atime {
             inode.atime = at.tv_sec as u32;
-            // 设置纳秒精度: (nanosec << 2) | epoch_bits
-            // epoch_bits 通常为 0，所以直接左移 2 位
-            inode.i_atime_extra = ((at.tv_nsec as u32) << 2) & 0xFFFFFFFC;
+            inode.i_atime_extra = time_extra(&at);
         }
 
         // 更新修改时间
         if let Some(mt) = mtime {
             inode.mtime = mt.tv_sec as u32;
-            inode.i_mtime_extra = ((mt.tv_nsec as u32) << 2) & 0xFFFFFFFC;
-
-            // 修改时间改变时，也更新 ctime
-            let now = TimeSpec::now();
-            inode.ctime = now.tv_sec as u32;
-            inode.i_ctime_extra = ((now.tv_nsec as u32) << 2) & 0xFFFFFFFC;
+            inode.i_mtime_extra = time_extra(&mt);
         }
 
+        // 时间戳改变属于状态改变，更新 ctime
+        let now = TimeSpec::now();
+        inode.ctime = now.tv_sec as u32;
+        inode.i_ctime_extra = time_extra(&now);
+
         // 写回 inode 到磁盘
         fs.write_back_inode(&mut inode_ref);
 
-        Ok(())
+        self.check_io(&mut fs, "set_times")
+    }
+
+    fn touch_atime(&self) {
+        // 只读时跳过
+        if self.check_writable().is_err() {
+            return;
+        }
+        let Ok(metadata) = self.metadata() else {
+            return;
+        };
+        let now = TimeSpec::now();
+        if !atime_needs_update(metadata.atime, metadata.mtime, metadata.ctime, now) {
+            return;
+        }
+        let fs = self.lock_fs();
+        let mut inode_ref = fs.get_inode_ref(self.ino);
+        inode_ref.inode.atime = now.tv_sec as u32;
+        inode_ref.inode.i_atime_extra = time_extra(&now);
+        fs.write_back_inode(&mut inode_ref);
     }
 
     fn chown(&self, uid: u32, gid: u32) -> Result<(), FsError> {
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
 
         // 获取 inode 引用（可变）
         let mut inode_ref = fs.get_inode_ref(self.ino);
@@ -1062,16 +1311,17 @@ impl Inode for Ext4Inode {
         // 更新 ctime（状态改变时间）
         let now = TimeSpec::now();
         inode.ctime = now.tv_sec as u32;
-        inode.i_ctime_extra = ((now.tv_nsec as u32) << 2) & 0xFFFFFFFC;
+        inode.i_ctime_extra = time_extra(&now);
 
         // 写回 inode 到磁盘
         fs.write_back_inode(&mut inode_ref);
 
-        Ok(())
+        self.check_io(&mut fs, "chown")
     }
 
     fn chmod(&self, mode: FileMode) -> Result<(), FsError> {
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
 
         // 获取 inode 引用（可变）
         let mut inode_ref = fs.get_inode_ref(self.ino);
@@ -1085,12 +1335,12 @@ impl Inode for Ext4Inode {
         // 更新 ctime（状态改变时间）
         let now = TimeSpec::now();
         inode.ctime = now.tv_sec as u32;
-        inode.i_ctime_extra = ((now.tv_nsec as u32) << 2) & 0xFFFFFFFC;
+        inode.i_ctime_extra = time_extra(&now);
 
         // 写回 inode 到磁盘
         fs.write_back_inode(&mut inode_ref);
 
-        Ok(())
+        self.check_io(&mut fs, "chmod")
     }
 
     fn readlink(&self) -> Result<String, FsError> {
@@ -1111,7 +1361,7 @@ impl Inode for Ext4Inode {
         }
 
         // 读取长符号链接目标。短符号链接已在上面从 inode 的 i_block 内联区读取。
-        let fs = self.fs.lock();
+        let fs = self.lock_fs();
         let mut buf = alloc::vec![0u8; size];
 
         let bytes_read = fs
@@ -1137,11 +1387,12 @@ impl Inode for Ext4Inode {
 
         let file_type = Self::mode_file_type(mode)?;
         let inode_mode = Self::special_file_mode(mode, file_type);
-        let fs = self.fs.lock();
+        self.check_writable()?;
+        let mut fs = self.lock_fs();
 
         let mut new_inode = fs
             .create(self.ino, name, inode_mode)
-            .map_err(|_| FsError::IoError)?;
+            .map_err(|e| self.fail(&mut fs, "mknod", e, FsError::IoError))?;
 
         new_inode.inode.set_mode(inode_mode);
         new_inode.inode.set_size(0);
@@ -1152,6 +1403,7 @@ impl Inode for Ext4Inode {
         }
 
         fs.write_back_inode(&mut new_inode);
+        self.check_io(&mut fs, "mknod")?;
 
         self.drop_lookup_cache_entry(name);
         self.invalidate_inode_no(new_inode.inode_num);
@@ -1164,3 +1416,9 @@ impl Inode for Ext4Inode {
         )))
     }
 }
+
+impl Drop for Ext4Inode {
+    fn drop(&mut self) {
+        self.caches.orphans.lock().put(self.ino);
+    }
+}
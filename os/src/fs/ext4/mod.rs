@@ -26,12 +26,22 @@
 //!
 //! ## 支持的操作
 //!
-//! - **文件操作**：read、write、truncate、sync
+//! - **文件操作**：read、write、truncate、sync（写回 inode 并刷新设备写缓存）
 //! - **目录操作**：lookup、create、mkdir、readdir、rmdir
 //! - **链接操作**：symlink、link、unlink、readlink
 //! - **元数据**：chmod、chown、set_times
 //! - **重命名**：rename（支持跨目录移动）
 //!
+//! ## 错误处理
+//!
+//! 检测到 ext4_rs 或块设备的 I/O 错误时，在超级块中记录错误，并按 `s_errors`
+//! 转为只读或 panic（见 [`error`]）。
+//!
+//! ## 孤儿 inode
+//!
+//! 链接数降为 0 但仍被打开的文件挂到超级块的孤儿链表上，最后一个引用释放后才释放；
+//! 崩溃后在下次挂载时回收（见 [`orphan`]）。
+//!
 //! # 使用示例
 //!
 //! ```rust
@@ -52,19 +62,26 @@
 //! # 限制
 //!
 //! - `mknod` 未实现（设备文件创建）
-//! - 非日志模式，崩溃可能导致不一致
+//! - ext4_rs 不支持扩展属性：xattr 系统调用返回 `EOPNOTSUPP`，listxattr 返回空列表
+//! - 非日志模式：未 sync 的元数据更新在崩溃后可能不一致，需要 fsck 修复；
+//!   已 sync 的数据和元数据不受影响
 pub mod adpaters;
+pub mod error;
 pub mod inode;
+mod orphan;
+mod swap;
 
 pub use adpaters::BlockDeviceAdapter;
 pub use inode::{Ext4Inode, Ext4InodeCaches};
 
 use crate::device::block::BlockDriver;
-use crate::pr_info;
 use crate::sync::Mutex;
+use crate::uapi::fs::FileSystemType;
 use crate::vfs::page_cache::PageCache;
 use crate::vfs::{FileSystem, FsError, Inode, StatFs};
+use crate::{pr_info, pr_warn};
 use alloc::sync::Arc;
+use error::{EXT4_ERROR_FS, ErrorPolicy, Ext4ErrorState};
 use ext4_rs::BlockDevice;
 
 /// Ext4 文件系统
@@ -90,6 +107,9 @@ pub struct Ext4FileSystem {
     /// Shared clean file page cache.
     page_cache: Arc<PageCache>,
 
+    /// 各 inode 共享的缓存、错误状态与孤儿记录
+    inode_caches: Arc<Ext4InodeCaches>,
+
     /// 根 inode
     root: Arc<dyn Inode>,
 }
@@ -127,19 +147,29 @@ impl Ext4FileSystem {
         // 使用 ext4_rs 打开文件系统
         // 注意：ext4_rs::Ext4::open 直接返回 Ext4，不返回 Result
         pr_info!("[Ext4] Calling ext4_rs::Ext4::open...");
-        let ext4 = ext4_rs::Ext4::open(adapter);
+        let mut ext4 = ext4_rs::Ext4::open(adapter.clone());
         pr_info!("[Ext4] ext4_rs returned successfully");
 
+        if ext4.super_block.state() & EXT4_ERROR_FS != 0 {
+            pr_warn!("[Ext4] Filesystem has recorded errors, running fsck is recommended");
+        }
+        let recovered = orphan::recover_orphans(&mut ext4);
+        if recovered > 0 {
+            pr_info!("[Ext4] Recovered {} orphan inode(s)", recovered);
+        }
+        let policy = ErrorPolicy::from_super_block(ext4.super_block.errors());
+        let errors = Ext4ErrorState::new(adapter, policy);
+
         let ext4 = Arc::new(Mutex::new(ext4));
 
-        let inode_caches = Arc::new(Ext4InodeCaches::new());
+        let inode_caches = Arc::new(Ext4InodeCaches::new(errors));
         let fs_id = device_id as u64;
         let page_cache = Arc::new(PageCache::new());
 
         // 创建根 inode (inode 号 2 是 Ext4 的根目录)
         let root = Arc::new(Ext4Inode::new(
             ext4.clone(),
-            inode_caches,
+            inode_caches.clone(),
             page_cache.clone(),
             fs_id,
             2,
@@ -153,12 +183,25 @@ impl Ext4FileSystem {
             fs_id,
             ext4,
             page_cache,
+            inode_caches,
             root,
         });
 
         pr_info!("[Ext4] Filesystem opened successfully");
         Ok(fs)
     }
+
+    /// 文件系统是否已因错误转为只读
+    #[cfg(test)]
+    pub(crate) fn is_read_only(&self) -> bool {
+        self.inode_caches.errors.is_read_only()
+    }
+
+    /// 超级块孤儿链表上的 inode
+    #[cfg(test)]
+    pub(crate) fn orphan_list(&self) -> alloc::vec::Vec<u32> {
+        orphan::orphan_list(&self.ext4.lock())
+    }
 }
 
 fn validate_superblock(adapter: &BlockDeviceAdapter) -> Result<(), FsError> {
@@ -200,6 +243,7 @@ impl FileSystem for Ext4FileSystem {
     }
 
     fn sync(&self) -> Result<(), FsError> {
+        self.inode_caches.reap_orphans(&mut self.ext4.lock());
         // 调用底层块设备的 flush 方法，将缓存刷新到磁盘
         if self.device.flush() {
             Ok(())
@@ -213,6 +257,7 @@ impl FileSystem for Ext4FileSystem {
         let sb = &ext4.super_block;
 
         Ok(StatFs {
+            fs_type: FileSystemType::Ext4,
             block_size: self.block_size,
             total_blocks: self.total_blocks,
             free_blocks: sb.free_blocks_count() as usize,
@@ -0,0 +1,160 @@
+//! Ext4 孤儿 inode
+//!
+//! 链接数降为 0 但仍被打开的 inode 不能立即释放。与 Linux 相同，这类 inode 挂到以超级块
+//! `s_last_orphan` 为头的单链表上，next 指针复用 inode 的 `i_dtime` 字段；最后一个
+//! [`Ext4Inode`](super::Ext4Inode) 释放后再截断数据、摘链并释放 inode。若在此之前崩溃，
+//! 下次挂载时由 [`recover_orphans`] 沿链表回收。
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use ext4_rs::{Ext4, Ext4Error};
+
+use crate::uapi::time::TimeSpec;
+
+const EXT4_INODE_FLAG_EXTENTS: u32 = 0x0008_0000;
+
+/// 内存中各 inode 的引用计数与待回收的孤儿
+pub struct OrphanTracker {
+    /// 每个 inode 号当前存活的 `Ext4Inode` 数
+    live: BTreeMap<u32, usize>,
+    /// 已挂上孤儿链表、仍有引用的 inode
+    orphans: BTreeSet<u32>,
+    /// 最后一个引用已释放、等待回收的孤儿
+    pending: Vec<u32>,
+}
+
+impl OrphanTracker {
+    pub const fn new() -> Self {
+        Self {
+            live: BTreeMap::new(),
+            orphans: BTreeSet::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// 新建了一个指向 `ino` 的 `Ext4Inode`
+    pub fn get(&mut self, ino: u32) {
+        *self.live.entry(ino).or_insert(0) += 1;
+    }
+
+    /// 释放了一个指向 `ino` 的 `Ext4Inode`
+    pub fn put(&mut self, ino: u32) {
+        let Some(count) = self.live.get_mut(&ino) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.live.remove(&ino);
+            if self.orphans.remove(&ino) {
+                self.pending.push(ino);
+            }
+        }
+    }
+
+    /// 链接数降为 0 的 inode 仍有引用时记为孤儿并返回 true；否则可以立即释放
+    pub fn defer(&mut self, ino: u32) -> bool {
+        if self.live.contains_key(&ino) {
+            self.orphans.insert(ino);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 取出等待回收的孤儿
+    pub fn take_pending(&mut self) -> Vec<u32> {
+        core::mem::take(&mut self.pending)
+    }
+}
+
+/// 把 inode 挂到孤儿链表头部
+pub fn add_orphan(fs: &mut Ext4, ino: u32) {
+    let mut inode_ref = fs.get_inode_ref(ino);
+    inode_ref.inode.set_dtime(fs.super_block.last_orphan());
+    fs.write_back_inode(&mut inode_ref);
+    fs.update_super_block(|sb| sb.set_last_orphan(ino));
+}
+
+/// 把 inode 从孤儿链表摘下
+fn remove_orphan(fs: &mut Ext4, ino: u32) {
+    let next = match fs.get_inode_ref(ino).inode.dtime() {
+        next if next == ino => 0,
+        next => next,
+    };
+    let head = fs.super_block.last_orphan();
+    if head == ino {
+        fs.update_super_block(|sb| sb.set_last_orphan(next));
+        return;
+    }
+
+    let mut prev = head;
+    for _ in 0..fs.super_block.total_inodes() {
+        if !valid_ino(fs, prev) {
+            return;
+        }
+        let mut prev_ref = fs.get_inode_ref(prev);
+        let prev_next = prev_ref.inode.dtime();
+        if prev_next == ino {
+            prev_ref.inode.set_dtime(next);
+            fs.write_back_inode(&mut prev_ref);
+            return;
+        }
+        prev = prev_next;
+    }
+}
+
+/// 截断并释放链接数为 0 的 inode，同时把它从孤儿链表摘下
+pub fn release_orphan(fs: &mut Ext4, ino: u32) -> Result<(), Ext4Error> {
+    let mut inode_ref = fs.get_inode_ref(ino);
+    // 快速符号链接和设备文件的 i_block 不是 extent 树
+    if inode_ref.inode.flags & EXT4_INODE_FLAG_EXTENTS != 0 && inode_ref.inode.size() > 0 {
+        fs.truncate_inode(&mut inode_ref, 0)?;
+    }
+    // 截断完成后再摘链，中途崩溃时下次挂载仍能找到它
+    remove_orphan(fs, ino);
+
+    let mut inode_ref = fs.get_inode_ref(ino);
+    inode_ref.inode.set_links_count(0);
+    inode_ref.inode.set_dtime(TimeSpec::now().tv_sec as u32);
+    fs.write_back_inode(&mut inode_ref);
+    fs.ialloc_free_inode(ino, inode_ref.inode.is_dir());
+    Ok(())
+}
+
+/// 挂载时回收上次未能释放的孤儿 inode，返回回收的个数
+pub fn recover_orphans(fs: &mut Ext4) -> usize {
+    let mut recovered = 0;
+    while recovered < fs.super_block.total_inodes() as usize {
+        let ino = fs.super_block.last_orphan();
+        if ino == 0 {
+            break;
+        }
+        if !valid_ino(fs, ino) || release_orphan(fs, ino).is_err() {
+            // 链表已损坏：清空表头，剩下的交给 fsck
+            crate::pr_warn!(
+                "[Ext4] Corrupted orphan list at inode {}, truncating it",
+                ino
+            );
+            fs.update_super_block(|sb| sb.set_last_orphan(0));
+            break;
+        }
+        recovered += 1;
+    }
+    recovered
+}
+
+/// 按链表顺序列出孤儿 inode
+#[cfg(test)]
+pub fn orphan_list(fs: &Ext4) -> Vec<u32> {
+    let mut list = Vec::new();
+    let mut ino = fs.super_block.last_orphan();
+    while valid_ino(fs, ino) && list.len() < fs.super_block.total_inodes() as usize {
+        list.push(ino);
+        ino = fs.get_inode_ref(ino).inode.dtime();
+    }
+    list
+}
+
+fn valid_ino(fs: &Ext4, ino: u32) -> bool {
+    ino != 0 && ino <= fs.super_block.total_inodes()
+}
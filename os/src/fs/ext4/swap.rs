@@ -0,0 +1,67 @@
+//! Ext4 交换文件后端
+//!
+//! `swapon` 时一次性解析交换文件每一页对应的物理块，之后的换入换出直接读写块设备，
+//! 不再获取文件系统锁。要求文件不含空洞且文件系统块大小等于页大小。
+
+use alloc::{sync::Arc, vec::Vec};
+
+use crate::config::PAGE_SIZE;
+use crate::mm::swap::SwapBacking;
+use crate::sync::Mutex;
+use crate::vfs::FsError;
+
+/// 预先解析好物理块的交换文件
+pub struct Ext4SwapBacking {
+    device: Arc<dyn ext4_rs::BlockDevice>,
+    /// 第 i 页所在的物理块号
+    blocks: Vec<u64>,
+}
+
+impl Ext4SwapBacking {
+    /// 解析 inode `ino` 的前 `size / PAGE_SIZE` 页
+    pub fn new(fs: &Arc<Mutex<ext4_rs::Ext4>>, ino: u32, size: usize) -> Result<Self, FsError> {
+        let fs = fs.lock();
+        if fs.super_block.block_size() as usize != PAGE_SIZE {
+            return Err(FsError::InvalidArgument);
+        }
+        let inode_ref = fs.get_inode_ref(ino);
+        let blocks = (0..size / PAGE_SIZE)
+            .map(|lblock| {
+                fs.get_pblock_idx(&inode_ref, lblock as u32)
+                    .ok()
+                    .filter(|&pblock| pblock != 0)
+                    .ok_or(FsError::InvalidArgument)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            device: fs.block_device.clone(),
+            blocks,
+        })
+    }
+}
+
+impl SwapBacking for Ext4SwapBacking {
+    fn page_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    fn read_page(&self, page: usize, buf: &mut [u8]) -> bool {
+        let Some(&pblock) = self.blocks.get(page) else {
+            return false;
+        };
+        let data = self.device.read_offset(pblock as usize * PAGE_SIZE);
+        if data.len() < buf.len() {
+            return false;
+        }
+        buf.copy_from_slice(&data[..buf.len()]);
+        true
+    }
+
+    fn write_page(&self, page: usize, buf: &[u8]) -> bool {
+        let Some(&pblock) = self.blocks.get(page) else {
+            return false;
+        };
+        self.device.write_offset(pblock as usize * PAGE_SIZE, buf);
+        true
+    }
+}
@@ -47,10 +47,10 @@
 //!
 //! // 3. 挂载伪文件系统
 //! init_procfs()?;
-//! init_sysfs()?;
+//! init_sysfs(MountFlags::empty())?;
 //!
 //! // 4. 挂载tmpfs
-//! mount_tmpfs("/tmp", 64)?;  // 64MB
+//! mount_tmpfs("/tmp", 64, MountFlags::empty())?;  // 64MB
 //!
 //! // 5. 初始化设备文件
 //! init_dev()?;
@@ -94,7 +94,7 @@ use crate::fs::tmpfs::TmpFs;
 // use crate::fs::smfs::SimpleMemoryFileSystem;
 use crate::pr_info;
 use crate::vfs::dev::makedev;
-use crate::vfs::devno::{chrdev_major, misc_minor};
+use crate::vfs::devno::{chrdev_major, input_minor, misc_minor};
 use crate::vfs::{FileMode, FsError, MOUNT_TABLE, MountFlags, vfs_lookup};
 
 // lazy_static! {
@@ -260,19 +260,34 @@ fn is_partition_device_name(name: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// 解析命令行中的 `root=` 参数，返回块设备名（如 `vda1`）
+///
+/// 接受 `root=/dev/vda1` 与 `root=vda1` 两种写法。
+pub fn root_device_param(cmdline: &str) -> Option<&str> {
+    let value = cmdline
+        .split_ascii_whitespace()
+        .find_map(|arg| arg.strip_prefix("root="))?;
+    let name = value.strip_prefix("/dev/").unwrap_or(value);
+    (!name.is_empty()).then_some(name)
+}
+
 /// 探测并挂载分区盘 rootfs。
 ///
 /// QEMU 挂载一个 MBR raw disk，`vda1` 是 ext4 rootfs，`vda2` 是 FAT32/VFAT
 /// 测试分区。这里遍历已发现的整盘和分区块设备，优先尝试分区设备，
 /// 选择能打开 ext4 且含 `/bin/sh` 或 `/bin/ash` 的设备挂载为 `/`。
+/// 命令行指定了 `root=` 时最先尝试该设备，失败再退回探测。
 pub fn init_rootfs_from_discovered_block_devices() -> Result<(), FsError> {
     use crate::config::EXT4_BLOCK_SIZE;
 
     pr_info!("[RootFS][Ext4] Probing discovered block devices for rootfs");
 
+    let cmdline = crate::device::CMDLINE.read().clone();
+    let requested = root_device_param(&cmdline);
     let mut devices = list_block_devices();
     devices.sort_by_key(|dev| {
         (
+            Some(dev.name.as_str()) != requested,
             !is_partition_device_name(&dev.name),
             dev.minor,
             dev.name.clone(),
@@ -282,6 +297,14 @@ pub fn init_rootfs_from_discovered_block_devices() -> Result<(), FsError> {
         pr_info!("[RootFS][Ext4] No block device found");
         return Err(FsError::NoDevice);
     }
+    if let Some(name) = requested
+        && !devices.iter().any(|dev| dev.name == name)
+    {
+        crate::pr_warn!(
+            "[RootFS] root=/dev/{} not found, probing all block devices",
+            name
+        );
+    }
 
     // 探测并挂载 rootfs 到 "/"（认 /bin/sh 或 /bin/ash）。
     let mut root_device: Option<String> = None;
@@ -344,7 +367,12 @@ pub fn init_rootfs_from_discovered_block_devices() -> Result<(), FsError> {
 ///
 /// - `mount_point`: 挂载点路径（如 "/tmp"）
 /// - `max_size_mb`: 最大容量（MB），0 表示无限制
-pub fn mount_tmpfs(mount_point: &str, max_size_mb: usize) -> Result<(), crate::vfs::FsError> {
+/// - `flags`: 挂载标志
+pub fn mount_tmpfs(
+    mount_point: &str,
+    max_size_mb: usize,
+    flags: MountFlags,
+) -> Result<(), crate::vfs::FsError> {
     use alloc::string::ToString;
 
     pr_info!(
@@ -360,12 +388,7 @@ pub fn mount_tmpfs(mount_point: &str, max_size_mb: usize) -> Result<(), crate::v
     let tmpfs = TmpFs::new(max_size_mb);
 
     // 挂载到指定路径
-    MOUNT_TABLE.mount(
-        tmpfs,
-        mount_point,
-        MountFlags::empty(),
-        Some(String::from("tmpfs")),
-    )?;
+    MOUNT_TABLE.mount(tmpfs, mount_point, flags, Some(String::from("tmpfs")))?;
 
     pr_info!("[Tmpfs] Tmpfs mounted at {}", mount_point);
 
@@ -389,6 +412,10 @@ fn create_devices() -> Result<(), FsError> {
     // 字符设备：0666 权限
     let char_mode = FileMode::S_IFCHR | FileMode::from_bits_truncate(0o666);
 
+    // /dev/mem (1, 1) - 物理地址空间，仅 root 可访问
+    let mem_mode = FileMode::S_IFCHR | FileMode::from_bits_truncate(0o600);
+    dev_inode.mknod("mem", mem_mode, makedev(chrdev_major::MEM, 1))?;
+
     // /dev/null (1, 3)
     dev_inode.mknod("null", char_mode, makedev(chrdev_major::MEM, 3))?;
 
@@ -437,6 +464,42 @@ fn create_devices() -> Result<(), FsError> {
         Err(err) => return Err(err),
     }
 
+    // /dev/watchdog (10, 130)
+    match dev_inode.mknod(
+        "watchdog",
+        char_mode,
+        makedev(chrdev_major::MISC, misc_minor::WATCHDOG),
+    ) {
+        Ok(_) | Err(FsError::AlreadyExists) => {}
+        Err(err) => return Err(err),
+    }
+
+    // /dev/input/eventN (13, 64+N)
+    let input_count = crate::device::INPUT_DRIVERS.read().len();
+    if input_count > 0 {
+        match dev_inode.mkdir("input", dir_mode) {
+            Ok(_) | Err(FsError::AlreadyExists) => {}
+            Err(err) => return Err(err),
+        }
+        let input_dentry = vfs_lookup("/dev/input")?;
+        for idx in 0..input_count.min(input_minor::EVDEV_MAX as usize) {
+            input_dentry.inode.mknod(
+                &alloc::format!("event{}", idx),
+                FileMode::S_IFCHR | FileMode::from_bits_truncate(0o660),
+                makedev(chrdev_major::INPUT, input_minor::EVDEV_BASE + idx as u32),
+            )?;
+        }
+    }
+
+    // /dev/fb0 (29, 0)
+    if crate::device::gpu::FRAME_BUFFER.read().is_some() {
+        dev_inode.mknod(
+            "fb0",
+            FileMode::S_IFCHR | FileMode::from_bits_truncate(0o660),
+            makedev(chrdev_major::FB, 0),
+        )?;
+    }
+
     // 块设备：0660 权限
     let block_mode = FileMode::S_IFBLK | FileMode::from_bits_truncate(0o660);
 
@@ -467,24 +530,29 @@ fn create_devices() -> Result<(), FsError> {
 
 /// 初始化并挂载 procfs 到 /proc
 pub fn init_procfs() -> Result<(), crate::vfs::FsError> {
+    init_procfs_with_options(
+        crate::fs::proc::ProcMountOptions::default(),
+        MountFlags::empty(),
+    )
+}
+
+/// 按挂载选项初始化并挂载 procfs 到 /proc
+pub fn init_procfs_with_options(
+    options: crate::fs::proc::ProcMountOptions,
+    flags: MountFlags,
+) -> Result<(), crate::vfs::FsError> {
     use crate::fs::proc::ProcFS;
-    use crate::vfs::MountFlags;
 
     pr_info!("[ProcFS] Initializing procfs");
 
     // 创建 procfs
-    let procfs = ProcFS::new();
+    let procfs = ProcFS::with_options(options);
 
     // 初始化文件系统树
     procfs.init_tree()?;
 
     // 挂载到 /proc
-    MOUNT_TABLE.mount(
-        procfs,
-        "/proc",
-        MountFlags::empty(),
-        Some(String::from("proc")),
-    )?;
+    MOUNT_TABLE.mount(procfs, "/proc", flags, Some(String::from("proc")))?;
 
     pr_info!("[ProcFS] Procfs mounted at /proc");
 
@@ -492,9 +560,8 @@ pub fn init_procfs() -> Result<(), crate::vfs::FsError> {
 }
 
 /// 初始化并挂载 sysfs 到 /sys
-pub fn init_sysfs() -> Result<(), crate::vfs::FsError> {
+pub fn init_sysfs(flags: MountFlags) -> Result<(), crate::vfs::FsError> {
     use crate::fs::sysfs::SysFS;
-    use crate::vfs::MountFlags;
 
     pr_info!("[SysFS] Initializing sysfs");
 
@@ -505,12 +572,7 @@ pub fn init_sysfs() -> Result<(), crate::vfs::FsError> {
     sysfs.init_tree()?;
 
     // 挂载到 /sys
-    MOUNT_TABLE.mount(
-        sysfs,
-        "/sys",
-        MountFlags::empty(),
-        Some(String::from("sysfs")),
-    )?;
+    MOUNT_TABLE.mount(sysfs, "/sys", flags, Some(String::from("sysfs")))?;
 
     pr_info!("[SysFS] Sysfs mounted at /sys");
 
@@ -0,0 +1,13 @@
+use alloc::vec::Vec;
+
+use crate::fs::proc::inode::ContentGenerator;
+use crate::vfs::FsError;
+
+/// `/proc/bootinfo`：启动里程碑及其时间戳，格式见 `kernel::bootinfo::format_report`
+pub struct BootinfoGenerator;
+
+impl ContentGenerator for BootinfoGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        Ok(crate::kernel::bootinfo::report().into_bytes())
+    }
+}
@@ -0,0 +1,17 @@
+use alloc::{format, vec::Vec};
+
+use crate::fs::proc::inode::ContentGenerator;
+use crate::vfs::FsError;
+
+/// /proc/config：构建配置，每行一个选项，格式同 Linux `.config`
+pub struct ConfigGenerator;
+
+impl ContentGenerator for ConfigGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let mut content = Vec::new();
+        for option in crate::config::OPTIONS {
+            content.extend_from_slice(format!("{}\n", option).as_bytes());
+        }
+        Ok(content)
+    }
+}
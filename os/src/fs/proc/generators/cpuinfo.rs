@@ -7,6 +7,6 @@ pub struct CpuinfoGenerator;
 
 impl ContentGenerator for CpuinfoGenerator {
     fn generate(&self) -> Result<Vec<u8>, FsError> {
-        Ok(crate::arch::abi::proc_cpuinfo_bytes().to_vec())
+        Ok(crate::arch::cpuinfo::cpuinfo().into_bytes())
     }
 }
@@ -0,0 +1,27 @@
+use alloc::vec::Vec;
+
+use crate::fs::proc::inode::{ContentGenerator, ContentWriter};
+use crate::kernel::syscall::enosys;
+use crate::vfs::FsError;
+
+/// `/proc/enosys`：未实现系统调用的直方图，格式见 `kernel::syscall::enosys::EnosysStats::format`
+pub struct EnosysGenerator;
+
+impl ContentGenerator for EnosysGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        Ok(enosys::report().into_bytes())
+    }
+}
+
+/// `/proc/enosys` 写端：写入 `0` 清空直方图
+pub struct EnosysWriter;
+
+impl ContentWriter for EnosysWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        if buf.trim_ascii() != b"0" {
+            return Err(FsError::InvalidArgument);
+        }
+        enosys::reset();
+        Ok(buf.len())
+    }
+}
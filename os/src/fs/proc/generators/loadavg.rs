@@ -0,0 +1,35 @@
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::fs::proc::inode::ContentGenerator;
+use crate::kernel::loadavg::{load_int_frac, loadavg};
+use crate::kernel::{TASK_MANAGER, TaskManagerTrait, nr_running};
+use crate::vfs::FsError;
+
+/// /proc/loadavg：1/5/15 分钟平均负载、可运行/总任务数和最近分配的 PID
+pub struct LoadavgGenerator;
+
+impl ContentGenerator for LoadavgGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let [l1, l5, l15] = loadavg().map(load_int_frac);
+        let (nr_threads, last_pid) = {
+            let tm = TASK_MANAGER.lock();
+            (tm.task_count(), tm.last_tid())
+        };
+
+        let content = format!(
+            "{}.{:02} {}.{:02} {}.{:02} {}/{} {}\n",
+            l1.0,
+            l1.1,
+            l5.0,
+            l5.1,
+            l15.0,
+            l15.1,
+            nr_running(),
+            nr_threads,
+            last_pid
+        );
+
+        Ok(content.into_bytes())
+    }
+}
@@ -1,24 +1,18 @@
 use alloc::{format, vec::Vec};
 
-use crate::{
-    config::PAGE_SIZE,
-    fs::proc::ContentGenerator,
-    mm::frame_allocator::{get_free_frames, get_total_frames},
-    vfs::FsError,
-};
+use crate::{fs::proc::ContentGenerator, kernel::kstat, vfs::FsError};
 
+/// /proc/meminfo：内存和交换区的使用情况，数据来自 [`kstat`] 注册表
 pub struct MeminfoGenerator;
 
 impl ContentGenerator for MeminfoGenerator {
     fn generate(&self) -> Result<Vec<u8>, FsError> {
-        // 从内存管理器获取真实数据
-        let total_frames = get_total_frames();
-        let free_frames = get_free_frames();
-
-        // 转换为 kB（1024 字节）
-        let total_kb = (total_frames * PAGE_SIZE) / 1024;
-        let free_kb = (free_frames * PAGE_SIZE) / 1024;
+        let stats = kstat::snapshot();
+        let total_kb = stats.kb("nr_total_pages");
+        let free_kb = stats.kb("nr_free_pages");
         let available_kb = free_kb; // 简化实现：可用内存 = 空闲内存
+        let swap_total_kb = stats.kb("nr_swap_pages");
+        let swap_free_kb = stats.kb("nr_swap_free_pages");
 
         // 注意：格式严格遵循 Linux ABI
         let content = format!(
@@ -43,8 +37,35 @@ Writeback:      {:>8} kB
 AnonPages:      {:>8} kB
 Mapped:         {:>8} kB
 Shmem:          {:>8} kB
+KHeapTotal:     {:>8} kB
+KHeapGrown:     {:>8} kB
+KHeapLimit:     {:>8} kB
 ",
-            total_kb, free_kb, available_kb, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+            total_kb,
+            free_kb,
+            available_kb,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            swap_total_kb,
+            swap_free_kb,
+            0,
+            0,
+            0,
+            0,
+            0,
+            // 内核堆：初始区域加上从帧分配器扩展的部分，扩展的帧已计入 MemTotal - MemFree
+            stats.kb("kheap_bytes"),
+            stats.kb("kheap_grown_bytes"),
+            stats.kb("kheap_limit_bytes")
         );
 
         Ok(content.into_bytes())
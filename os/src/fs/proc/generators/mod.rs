@@ -1,15 +1,53 @@
+pub mod bootinfo;
 pub mod cmdline;
+pub mod config;
 pub mod cpuinfo;
+pub mod enosys;
+pub mod loadavg;
 pub mod meminfo;
 pub mod mounts;
+pub mod net;
+pub mod power;
 pub mod process;
 pub mod psmem;
+#[cfg(target_arch = "riscv64")]
+pub mod sbi;
+pub mod stat;
+pub mod swaps;
+pub mod sysctl;
+#[cfg(feature = "sysrq")]
+pub mod sysrq;
+pub mod test_result;
+pub mod timekeeping;
 pub mod uptime;
+pub mod vmstat;
 
+pub use bootinfo::BootinfoGenerator;
 pub use cmdline::KernelCmdlineGenerator;
+pub use config::ConfigGenerator;
 pub use cpuinfo::CpuinfoGenerator;
+pub use enosys::{EnosysGenerator, EnosysWriter};
+pub use loadavg::LoadavgGenerator;
 pub use meminfo::MeminfoGenerator;
 pub use mounts::MountsGenerator;
+pub use net::{NetDevGenerator, NetRouteGenerator, NetTcpGenerator, NetUdpGenerator};
+pub use power::{PowerStateGenerator, PowerStateWriter, PowerStatsGenerator};
 pub use process::{CmdlineGenerator, MapsGenerator, StatGenerator, StatusGenerator};
 pub use psmem::PsmemGenerator;
+#[cfg(target_arch = "riscv64")]
+pub use sbi::SbiGenerator;
+pub use stat::SystemStatGenerator;
+pub use swaps::SwapsGenerator;
+pub use sysctl::{
+    AtimePolicyGenerator, AtimePolicyWriter, FileMaxGenerator, FileNrGenerator,
+    HashPointersGenerator, HashPointersWriter, IpForwardGenerator, IpForwardWriter,
+    MmapMinAddrGenerator, MmapMinAddrWriter, RrTimesliceGenerator, RrTimesliceWriter,
+    RtcDisplayOffsetGenerator, RtcDisplayOffsetWriter, SchedstatsVerboseGenerator,
+    SchedstatsVerboseWriter, WxPolicyGenerator, WxPolicyWriter,
+};
+#[cfg(feature = "sysrq")]
+pub use sysrq::{SysrqTriggerGenerator, SysrqTriggerWriter};
+pub use test_result::{TestResultGenerator, TestResultWriter};
+pub use timekeeping::TimekeepingGenerator;
 pub use uptime::UptimeGenerator;
+pub use vmstat::VmstatGenerator;
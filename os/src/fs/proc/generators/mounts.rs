@@ -3,44 +3,106 @@ use alloc::string::String;
 use alloc::vec::Vec;
 
 use crate::fs::proc::inode::ContentGenerator;
-use crate::vfs::{FsError, MOUNT_TABLE};
+use crate::vfs::{FsError, MOUNT_TABLE, MountFlags};
 
+/// /proc/mounts 与 /proc/\[pid\]/mounts：挂载点列表
+///
+/// 每行依次为设备、挂载路径、文件系统类型、挂载选项以及两个固定为 0 的
+/// dump/pass 字段，与 Linux 的 fstab 格式一致。没有后备设备的文件系统以类型名作为设备名。
 pub struct MountsGenerator;
 
+/// 按 fstab 的规则转义空白与反斜杠（八进制 `\ooo`）
+fn escape_field(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            ' ' | '\t' | '\n' | '\\' => out.push_str(&format!("\\{:03o}", c as u32)),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// 把挂载标志渲染为选项字符串
+fn mount_options(flags: &MountFlags) -> String {
+    let mut options = Vec::new();
+    options.push(if flags.contains(MountFlags::READ_ONLY) {
+        "ro"
+    } else {
+        "rw"
+    });
+    if flags.contains(MountFlags::SYNC) {
+        options.push("sync");
+    }
+    if flags.contains(MountFlags::NO_SUID) {
+        options.push("nosuid");
+    }
+    if flags.contains(MountFlags::NO_DEV) {
+        options.push("nodev");
+    }
+    if flags.contains(MountFlags::NO_EXEC) {
+        options.push("noexec");
+    }
+    if flags.contains(MountFlags::NO_ATIME) {
+        options.push("noatime");
+    }
+    if flags.contains(MountFlags::NO_DIRATIME) {
+        options.push("nodiratime");
+    }
+    if !flags.contains(MountFlags::NO_ATIME) {
+        options.push("relatime");
+    }
+    options.join(",")
+}
+
+/// 生成一行挂载记录
+fn mount_line(device: Option<&str>, path: &str, fs_type: &str, flags: &MountFlags) -> String {
+    format!(
+        "{} {} {} {} 0 0\n",
+        escape_field(device.unwrap_or(fs_type)),
+        escape_field(path),
+        fs_type,
+        mount_options(flags)
+    )
+}
+
 impl ContentGenerator for MountsGenerator {
     fn generate(&self) -> Result<Vec<u8>, FsError> {
         let mut content = String::new();
-
-        // 获取所有挂载点
-        let mounts = MOUNT_TABLE.list_all();
-
-        for (path, mount_point) in mounts {
-            let device = mount_point.device.as_deref().unwrap_or("none");
-            let fs_type = mount_point.fs.fs_type();
-
-            // 构建挂载选项
-            let mut options = Vec::new();
-            if mount_point
-                .flags
-                .contains(crate::vfs::MountFlags::READ_ONLY)
-            {
-                options.push("ro");
-            } else {
-                options.push("rw");
-            }
-            options.push("relatime");
-
-            let line = format!(
-                "{} {} {} {} 0 0\n",
-                device,
-                path,
-                fs_type,
-                options.join(",")
-            );
-
-            content.push_str(&line);
+        for (path, mount_point) in MOUNT_TABLE.list_all() {
+            content.push_str(&mount_line(
+                mount_point.device.as_deref(),
+                &path,
+                mount_point.fs.fs_type(),
+                &mount_point.flags,
+            ));
         }
-
         Ok(content.into_bytes())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_proc_mounts_line, {
+        kassert!(
+            mount_line(Some("/dev/vda"), "/", "ext4", &MountFlags::empty())
+                == "/dev/vda / ext4 rw,relatime 0 0\n"
+        );
+        kassert!(
+            mount_line(None, "/mnt/my disk", "tmpfs", &MountFlags::READ_ONLY)
+                == "tmpfs /mnt/my\\040disk tmpfs ro,relatime 0 0\n"
+        );
+        kassert!(
+            mount_options(&(MountFlags::NO_SUID | MountFlags::NO_DEV | MountFlags::NO_EXEC))
+                == "rw,nosuid,nodev,noexec,relatime"
+        );
+        kassert!(
+            mount_options(
+                &(MountFlags::READ_ONLY | MountFlags::NO_ATIME | MountFlags::NO_DIRATIME)
+            ) == "ro,noatime,nodiratime"
+        );
+    });
+}
@@ -0,0 +1,161 @@
+use alloc::{format, string::String, vec::Vec};
+use smoltcp::wire::{IpAddress, Ipv4Address};
+
+use crate::fs::proc::inode::ContentGenerator;
+use crate::net::stack::InetSockInfo;
+use crate::vfs::FsError;
+
+/// /proc/net/route 与 /proc/net/udp 每行（含表头）填充到的宽度
+const ROUTE_LINE_WIDTH: usize = 127;
+
+/// /proc/net/tcp 每行（含表头）填充到的宽度
+const TCP_LINE_WIDTH: usize = 149;
+
+/// 按 Linux 的格式把 IPv4 地址输出为主机字节序的十六进制
+fn hex_addr(addr: Ipv4Address) -> u32 {
+    u32::from_le_bytes(addr.octets())
+}
+
+/// `地址:端口` 形式的十六进制端点，未指定的地址输出为 0
+fn hex_endpoint(addr: Option<IpAddress>, port: u16) -> String {
+    let addr = match addr {
+        Some(IpAddress::Ipv4(v4)) => hex_addr(v4),
+        _ => 0,
+    };
+    format!("{:08X}:{:04X}", addr, port)
+}
+
+/// 按 /proc/net/{tcp,udp} 的格式输出套接字表
+fn format_sock_table(
+    header: &str,
+    rows: &[InetSockInfo],
+    width: usize,
+    sl_width: usize,
+) -> Vec<u8> {
+    let mut content = format!("{:<width$}\n", header, width = width);
+    for (sl, row) in rows.iter().enumerate() {
+        let line = format!(
+            "{:>sl_width$}: {} {} {:02X} {:08X}:{:08X} 00:00000000 00000000 {:>5} {:>8} {} 1 0000000000000000",
+            sl,
+            hex_endpoint(row.local.addr, row.local.port),
+            match row.remote {
+                Some(remote) => hex_endpoint(Some(remote.addr), remote.port),
+                None => hex_endpoint(None, 0),
+            },
+            row.state,
+            row.tx_queue,
+            row.rx_queue,
+            row.uid,
+            0,
+            row.ino,
+            sl_width = sl_width,
+        );
+        content.push_str(&format!("{:<width$}\n", line, width = width));
+    }
+    content.into_bytes()
+}
+
+/// /proc/net/route：IPv4 路由表（不含回环接口上的路由）
+pub struct NetRouteGenerator;
+
+impl ContentGenerator for NetRouteGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let routes = crate::net::route::ROUTING_TABLE.lock().routes().to_vec();
+        let loopback: Vec<String> = crate::net::interface::NETWORK_INTERFACE_MANAGER
+            .lock()
+            .get_interfaces()
+            .iter()
+            .filter(|iface| iface.is_loopback())
+            .map(|iface| String::from(iface.name()))
+            .collect();
+
+        let mut content = format!(
+            "{:<width$}\n",
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT",
+            width = ROUTE_LINE_WIDTH
+        );
+        for route in routes.iter().filter(|r| !loopback.contains(&r.iface)) {
+            let line = format!(
+                "{}\t{:08X}\t{:08X}\t{:04X}\t0\t0\t{}\t{:08X}\t0\t0\t0",
+                route.iface,
+                hex_addr(route.dest.address()),
+                hex_addr(route.gateway.unwrap_or(Ipv4Address::UNSPECIFIED)),
+                route.flags(),
+                route.metric,
+                hex_addr(route.dest.netmask()),
+            );
+            content.push_str(&format!("{:<width$}\n", line, width = ROUTE_LINE_WIDTH));
+        }
+        Ok(content.into_bytes())
+    }
+}
+
+/// /proc/net/tcp：IPv4 TCP 套接字
+pub struct NetTcpGenerator;
+
+impl ContentGenerator for NetTcpGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let rows = crate::net::stack::network_stack().tcp_sock_info();
+        Ok(format_sock_table(
+            "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode",
+            &rows,
+            TCP_LINE_WIDTH,
+            4,
+        ))
+    }
+}
+
+/// /proc/net/udp：IPv4 UDP 套接字
+pub struct NetUdpGenerator;
+
+impl ContentGenerator for NetUdpGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let rows = crate::net::stack::network_stack().udp_sock_info();
+        Ok(format_sock_table(
+            "   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops",
+            &rows,
+            ROUTE_LINE_WIDTH,
+            5,
+        ))
+    }
+}
+
+/// /proc/net/dev：各接口的收发统计
+pub struct NetDevGenerator;
+
+impl ContentGenerator for NetDevGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let interfaces = crate::net::interface::NETWORK_INTERFACE_MANAGER
+            .lock()
+            .get_interfaces()
+            .to_vec();
+        let mut content = String::from(
+            "Inter-|   Receive                                                |  Transmit\n \
+             face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed\n",
+        );
+        for iface in &interfaces {
+            let stats = iface.stats().snapshot();
+            content.push_str(&format!(
+                "{:>6}:{:>8} {:>7} {:>4} {:>4} {:>4} {:>5} {:>10} {:>9} {:>8} {:>7} {:>4} {:>4} {:>4} {:>5} {:>7} {:>10}\n",
+                iface.name(),
+                stats.rx_bytes,
+                stats.rx_packets,
+                stats.rx_errors,
+                stats.rx_dropped,
+                0,
+                0,
+                0,
+                0,
+                stats.tx_bytes,
+                stats.tx_packets,
+                stats.tx_errors,
+                stats.tx_dropped,
+                0,
+                0,
+                0,
+                0,
+            ));
+        }
+        Ok(content.into_bytes())
+    }
+}
@@ -0,0 +1,85 @@
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::fs::proc::inode::{ContentGenerator, ContentWriter};
+use crate::vfs::FsError;
+
+/// 支持的睡眠状态
+const SUPPORTED_STATES: &[u8] = b"freeze\n";
+
+/// 解析写入 /proc/sys/power/state 的内容
+///
+/// 目前只支持 `freeze`（suspend-to-idle），允许末尾的换行和 NUL。
+fn parse_power_state(buf: &[u8]) -> Result<(), FsError> {
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    let s = core::str::from_utf8(&buf[..end]).map_err(|_| FsError::InvalidArgument)?;
+    match s.trim() {
+        "freeze" => Ok(()),
+        _ => Err(FsError::InvalidArgument),
+    }
+}
+
+/// /proc/sys/power/state 读端：列出支持的睡眠状态
+pub struct PowerStateGenerator;
+
+impl ContentGenerator for PowerStateGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        Ok(SUPPORTED_STATES.to_vec())
+    }
+}
+
+/// /proc/sys/power/stats：每 CPU 空闲状态统计和 suspend-to-idle 统计（时间单位毫秒）
+pub struct PowerStatsGenerator;
+
+impl ContentGenerator for PowerStatsGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        use crate::kernel::idle::{idle_snapshot, suspend_stats};
+
+        let freq = crate::arch::clock_freq() as u128;
+        let to_ms = |cycles: u64| cycles as u128 * 1000 / freq;
+
+        let mut content = alloc::string::String::from("cpu\tidle_ms\tshallow\ttickless\n");
+        for cpu in 0..crate::kernel::num_cpu() {
+            let snap = idle_snapshot(cpu);
+            content.push_str(&format!(
+                "cpu{}\t{}\t{}\t{}\n",
+                cpu,
+                to_ms(snap.idle_cycles),
+                snap.shallow_entries,
+                snap.tickless_entries
+            ));
+        }
+        let (count, cycles) = suspend_stats();
+        content.push_str(&format!(
+            "suspend_count:\t{}\nsuspend_ms:\t{}\n",
+            count,
+            to_ms(cycles)
+        ));
+
+        Ok(content.into_bytes())
+    }
+}
+
+/// /proc/sys/power/state 写端：写入 `freeze` 进入 suspend-to-idle
+pub struct PowerStateWriter;
+
+impl ContentWriter for PowerStateWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        parse_power_state(buf)?;
+        crate::kernel::idle::suspend_to_idle();
+        Ok(buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_power_state;
+    use crate::{kassert, test_case};
+
+    test_case!(test_parse_power_state, {
+        kassert!(parse_power_state(b"freeze").is_ok());
+        kassert!(parse_power_state(b"freeze\n\0").is_ok());
+        kassert!(parse_power_state(b"mem").is_err());
+        kassert!(parse_power_state(b"").is_err());
+    });
+}
@@ -11,6 +11,8 @@ use crate::{
 };
 
 /// `/proc/[pid]/maps` (simplified): list user VMAs and their sizes.
+///
+/// File-backed areas show the path of the mapped file instead of a label.
 pub struct MapsGenerator {
     task: Weak<SpinLock<TaskStruct>>,
 }
@@ -21,19 +23,6 @@ impl MapsGenerator {
     }
 }
 
-fn area_label(at: AreaType) -> &'static str {
-    match at {
-        AreaType::UserText => "[text]",
-        AreaType::UserRodata => "[rodata]",
-        AreaType::UserData => "[data]",
-        AreaType::UserBss => "[bss]",
-        AreaType::UserHeap => "[heap]",
-        AreaType::UserStack => "[stack]",
-        AreaType::UserMmap => "[mmap]",
-        _ => "[kernel]",
-    }
-}
-
 impl ContentGenerator for MapsGenerator {
     fn generate(&self) -> Result<Vec<u8>, FsError> {
         let task_arc = self.task.upgrade().ok_or(FsError::NotFound)?;
@@ -98,6 +87,11 @@ impl ContentGenerator for MapsGenerator {
             };
             let pages = a.vpn_range().len();
             let rss_pages = a.mapped_pages();
+            // 文件映射显示后备文件的路径
+            let label = match a.mmap_file().map(|f| f.file.dentry()) {
+                Some(Ok(dentry)) => dentry.d_path(),
+                _ => String::from(a.area_type().label()),
+            };
 
             out.push_str(&format!(
                 "{:016x}-{:016x} {}{}{}{} {:>7}kB rss {:>7}kB {:>8} {}\n",
@@ -110,7 +104,7 @@ impl ContentGenerator for MapsGenerator {
                 (pages * PAGE_SIZE) / 1024,
                 (rss_pages * PAGE_SIZE) / 1024,
                 map_type,
-                area_label(a.area_type()),
+                label,
             ));
         }
 
@@ -3,6 +3,9 @@ pub mod maps;
 pub mod memory;
 pub mod oom_score;
 pub mod oom_score_adj;
+pub mod pagemap;
+pub mod sched;
+pub mod schedstat;
 pub mod stat;
 pub mod status;
 
@@ -11,5 +14,8 @@ pub use maps::MapsGenerator;
 pub use memory::collect_user_vm_stats;
 pub use oom_score::OomScoreGenerator;
 pub use oom_score_adj::{OomScoreAdjGenerator, OomScoreAdjWriter};
+pub use pagemap::PagemapGenerator;
+pub use sched::SchedGenerator;
+pub use schedstat::SchedstatGenerator;
 pub use stat::StatGenerator;
 pub use status::StatusGenerator;
@@ -0,0 +1,72 @@
+use alloc::{string::String, sync::Weak, vec::Vec};
+use core::fmt::Write;
+
+use crate::{
+    fs::proc::ContentGenerator,
+    kernel::TaskStruct,
+    mm::address::PageNum,
+    mm::memory_space::{DisplayPteFlags, MemorySpace},
+    sync::SpinLock,
+    vfs::FsError,
+};
+
+/// `/proc/[pid]/pagemap`：逐页翻译用户 VMA，显示物理页号和页表项标志
+///
+/// 与 Linux 的二进制格式不同，这里输出文本：每个 VMA 一行标题（地址范围、VMA 权限和名称），
+/// 其下每行一段虚拟地址连续、物理页号（或交换槽号）也连续且标志相同的页：
+///
+/// ```text
+/// 0000000000010000-0000000000012000 vr-xu--- [text]
+///   0000000000010000-0000000000012000      2 pfn 0x80412 vr-xu-a-
+/// ```
+pub struct PagemapGenerator {
+    task: Weak<SpinLock<TaskStruct>>,
+}
+
+impl PagemapGenerator {
+    pub fn new(task: Weak<SpinLock<TaskStruct>>) -> Self {
+        Self { task }
+    }
+}
+
+/// 生成地址空间的 pagemap 文本
+pub fn format_pagemap(ms: &MemorySpace) -> String {
+    let mut areas: Vec<_> = ms
+        .areas()
+        .iter()
+        .filter(|a| a.area_type().is_user())
+        .collect();
+    areas.sort_by_key(|a| a.vpn_range().start());
+
+    let mut out = String::new();
+    for a in areas {
+        let label = match a.mmap_file().map(|f| f.file.dentry()) {
+            Some(Ok(dentry)) => dentry.d_path(),
+            _ => String::from(a.area_type().label()),
+        };
+        let _ = writeln!(
+            out,
+            "{:016x}-{:016x} {} {}",
+            a.vpn_range().start().start_addr().as_usize(),
+            a.vpn_range().end().start_addr().as_usize(),
+            DisplayPteFlags(a.permission()),
+            label
+        );
+        ms.walk_pages(a.vpn_range(), |run| {
+            let _ = writeln!(out, "  {}", run);
+        });
+    }
+    out
+}
+
+impl ContentGenerator for PagemapGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let task_arc = self.task.upgrade().ok_or(FsError::NotFound)?;
+        let memory_space = task_arc.lock().memory_space.clone();
+        let Some(ms) = memory_space else {
+            return Ok(Vec::new());
+        };
+        let ms = ms.lock();
+        Ok(format_pagemap(&ms).into_bytes())
+    }
+}
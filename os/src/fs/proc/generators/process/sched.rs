@@ -0,0 +1,83 @@
+use alloc::{format, string::String, sync::Weak, vec::Vec};
+
+use crate::{
+    fs::proc::ContentGenerator,
+    kernel::{TaskStruct, fair::task_weight},
+    sync::SpinLock,
+    uapi::sched::{SCHED_FIFO, SCHED_RR},
+    vfs::FsError,
+};
+
+/// 为指定任务生成 /proc/\[pid\]/sched 内容的生成器
+///
+/// 格式仿照 Linux：时间以毫秒为单位，保留 6 位小数。
+pub struct SchedGenerator {
+    task: Weak<SpinLock<TaskStruct>>,
+}
+
+impl SchedGenerator {
+    pub fn new(task: Weak<SpinLock<TaskStruct>>) -> Self {
+        Self { task }
+    }
+}
+
+/// 纳秒格式化为 `毫秒.小数` 一行
+fn ns_line(name: &str, ns: u64) -> String {
+    format!(
+        "{:<45}:{:>21}.{:06}\n",
+        name,
+        ns / 1_000_000,
+        ns % 1_000_000
+    )
+}
+
+fn int_line(name: &str, value: i64) -> String {
+    format!("{:<45}:{:>21}\n", name, value)
+}
+
+/// Linux 内核视角的优先级：realtime 为 99 - rt_priority，普通任务为 120
+fn kernel_prio(policy: i32, rt_priority: i32) -> i64 {
+    match policy {
+        SCHED_FIFO | SCHED_RR if rt_priority > 0 => 99 - rt_priority as i64,
+        _ => 120,
+    }
+}
+
+impl ContentGenerator for SchedGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let task_arc = self.task.upgrade().ok_or(FsError::NotFound)?;
+        let task = task_arc.lock();
+
+        let mut out = format!("{} ({}, #threads: 1)\n", task.comm(), task.tid);
+        out.push_str(&"-".repeat(67));
+        out.push('\n');
+        out.push_str(&ns_line("se.exec_start", task.exec_start));
+        out.push_str(&ns_line("se.vruntime", task.vruntime));
+        out.push_str(&ns_line("se.sum_exec_runtime", task.sum_exec_runtime));
+        out.push_str(&int_line(
+            "se.load.weight",
+            task_weight(task.sched_policy) as i64,
+        ));
+        out.push_str(&int_line("policy", task.sched_policy as i64));
+        out.push_str(&int_line(
+            "prio",
+            kernel_prio(task.sched_policy, task.sched_priority),
+        ));
+        Ok(out.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case, uapi::sched::SCHED_NORMAL};
+
+    test_case!(test_proc_sched_format, {
+        kassert!(
+            ns_line("se.vruntime", 1_234_567_890).ends_with(":                 1234.567890\n")
+        );
+        kassert!(kernel_prio(SCHED_NORMAL, 0) == 120);
+        kassert!(kernel_prio(SCHED_FIFO, 10) == 89);
+        kassert!(kernel_prio(SCHED_RR, 0) == 120);
+    });
+}
@@ -0,0 +1,29 @@
+use alloc::{sync::Weak, vec::Vec};
+
+use crate::{
+    fs::proc::ContentGenerator,
+    kernel::{TaskStruct, schedstat::schedstat_line},
+    sync::SpinLock,
+    vfs::FsError,
+};
+
+/// 为指定任务生成 /proc/\[pid\]/schedstat 内容的生成器
+///
+/// 一行四列（纳秒）：运行时间、运行队列等待时间、切入 CPU 次数、阻塞时间。
+pub struct SchedstatGenerator {
+    task: Weak<SpinLock<TaskStruct>>,
+}
+
+impl SchedstatGenerator {
+    pub fn new(task: Weak<SpinLock<TaskStruct>>) -> Self {
+        Self { task }
+    }
+}
+
+impl ContentGenerator for SchedstatGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let task_arc = self.task.upgrade().ok_or(FsError::NotFound)?;
+        let task = task_arc.lock();
+        Ok(schedstat_line(task.sum_exec_runtime, &task.sched_stat).into_bytes())
+    }
+}
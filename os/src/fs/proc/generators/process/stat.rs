@@ -32,8 +32,7 @@ impl ContentGenerator for StatGenerator {
             TaskState::Zombie => 'Z',
         };
 
-        // 获取进程名称（简化实现）
-        let name = format!("task_{}", task.tid);
+        let name = task.comm();
 
         // Linux /proc/\[pid\]/stat 格式（简化版）
         // 格式参考: man 5 proc
@@ -1,4 +1,4 @@
-use alloc::{format, sync::Weak, vec::Vec};
+use alloc::{format, string::String, sync::Weak, vec::Vec};
 
 use crate::{
     fs::proc::ContentGenerator,
@@ -23,15 +23,29 @@ impl StatusGenerator {
 impl ContentGenerator for StatusGenerator {
     fn generate(&self) -> Result<Vec<u8>, FsError> {
         let task_arc = self.task.upgrade().ok_or(FsError::NotFound)?;
-        let (pid, tid, ppid, state, name, mem_stats) = {
+        let (pid, tid, ppid, state, name, mem_stats, cred) = {
             let task = task_arc.lock();
-            let name = format!("task_{}", task.tid);
+            let name = task.comm();
             let mem_stats = task.memory_space.as_ref().map(|ms| {
                 let ms = ms.lock();
                 collect_user_vm_stats(&ms)
             });
-            (task.pid, task.tid, task.ppid, task.state, name, mem_stats)
+            (
+                task.pid,
+                task.tid,
+                task.ppid,
+                task.state,
+                name,
+                mem_stats,
+                task.credential,
+            )
         };
+        let groups: String = cred
+            .groups
+            .as_slice()
+            .iter()
+            .map(|gid| format!("{} ", gid))
+            .collect();
 
         // 状态字符串映射
         let state_char = match state {
@@ -67,8 +81,9 @@ impl ContentGenerator for StatusGenerator {
              Pid:\t{}\n\
              PPid:\t{}\n\
              TracerPid:\t0\n\
-             Uid:\t0\t0\t0\t0\n\
-             Gid:\t0\t0\t0\t0\n\
+             Uid:\t{}\t{}\t{}\t{}\n\
+             Gid:\t{}\t{}\t{}\t{}\n\
+             Groups:\t{}\n\
              VmSize:\t{:>8} kB\n\
              VmRSS:\t{:>8} kB\n\
              VmStk:\t{:>8} kB\n\
@@ -84,6 +99,15 @@ impl ContentGenerator for StatusGenerator {
             pid,
             tid,
             ppid,
+            cred.uid,
+            cred.euid,
+            cred.suid,
+            cred.fsuid,
+            cred.gid,
+            cred.egid,
+            cred.sgid,
+            cred.fsgid,
+            groups,
             vm_size_kb,
             rss_kb,
             stack_kb,
@@ -0,0 +1,13 @@
+use alloc::vec::Vec;
+
+use crate::fs::proc::inode::ContentGenerator;
+use crate::vfs::FsError;
+
+/// /proc/sbi：SBI 实现信息与扩展探测结果（仅 RISC-V）
+pub struct SbiGenerator;
+
+impl ContentGenerator for SbiGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        Ok(crate::arch::sbi::sbi_info().into_bytes())
+    }
+}
@@ -0,0 +1,73 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::fs::proc::inode::ContentGenerator;
+use crate::kernel::idle::idle_snapshot;
+use crate::kernel::time::realtime_now;
+use crate::kernel::{kstat, num_cpu};
+use crate::vfs::FsError;
+
+/// 用户态时钟节拍频率（USER_HZ）
+const USER_HZ: u64 = 100;
+
+/// 硬件时钟周期换算为 USER_HZ 节拍
+fn cycles_to_clock_ticks(cycles: u64, freq: u64) -> u64 {
+    (cycles as u128 * USER_HZ as u128 / freq.max(1) as u128) as u64
+}
+
+/// 生成一行 cpu 统计
+///
+/// 内核尚未区分用户态/内核态时间，非空闲时间全部计入 system。
+fn cpu_line(name: &str, total: u64, idle: u64) -> String {
+    let idle = idle.min(total);
+    format!("{} 0 0 {} {} 0 0 0 0 0 0\n", name, total - idle, idle)
+}
+
+/// /proc/stat：每 CPU 的忙碌/空闲时间，上下文切换次数和任务统计
+///
+/// 每 CPU 时间按 CPU 直接读取空闲统计，其余各行来自 [`kstat`] 注册表。
+pub struct SystemStatGenerator;
+
+impl ContentGenerator for SystemStatGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let freq = crate::arch::clock_freq() as u64;
+        let uptime = cycles_to_clock_ticks(crate::arch::get_time() as u64, freq);
+        let ncpu = num_cpu();
+
+        let idle: Vec<u64> = (0..ncpu)
+            .map(|cpu| cycles_to_clock_ticks(idle_snapshot(cpu).idle_cycles, freq))
+            .collect();
+
+        let mut content = cpu_line("cpu ", uptime * ncpu as u64, idle.iter().sum());
+        for (cpu, idle) in idle.iter().enumerate() {
+            content.push_str(&cpu_line(&format!("cpu{}", cpu), uptime, *idle));
+        }
+
+        let stats = kstat::snapshot();
+        let btime = realtime_now().tv_sec - (stats.get("uptime_ms") / 1000) as i64;
+        content.push_str(&format!(
+            "ctxt {}\nbtime {}\nprocesses {}\nprocs_running {}\nprocs_blocked {}\n",
+            stats.get("ctxt"),
+            btime,
+            stats.get("processes"),
+            stats.get("procs_running"),
+            stats.get("procs_blocked")
+        ));
+
+        Ok(content.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_stat_cpu_line_format, {
+        kassert!(cpu_line("cpu0", 500, 300) == "cpu0 0 0 200 300 0 0 0 0 0 0\n");
+        // 空闲时间不会超过总时间
+        kassert!(cpu_line("cpu0", 100, 150) == "cpu0 0 0 0 100 0 0 0 0 0 0\n");
+        kassert!(cycles_to_clock_ticks(25_000_000, 12_500_000) == 200);
+    });
+}
@@ -0,0 +1,24 @@
+use alloc::{format, string::String, vec::Vec};
+
+use crate::config::PAGE_SIZE;
+use crate::fs::proc::inode::ContentGenerator;
+use crate::mm::swap::swap_info;
+use crate::vfs::FsError;
+
+/// /proc/swaps：已启用的交换区
+pub struct SwapsGenerator;
+
+impl ContentGenerator for SwapsGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let mut content = String::from("Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority\n");
+        if let Some(info) = swap_info() {
+            content.push_str(&format!(
+                "{:<40}file\t\t{}\t\t{}\t\t-2\n",
+                info.path,
+                info.total * PAGE_SIZE / 1024,
+                info.used * PAGE_SIZE / 1024
+            ));
+        }
+        Ok(content.into_bytes())
+    }
+}
@@ -0,0 +1,246 @@
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::fs::proc::inode::{ContentGenerator, ContentWriter};
+use crate::vfs::FsError;
+
+/// 解析写入布尔型 sysctl 的内容（`0` 或 `1`，允许末尾的换行和 NUL）
+fn parse_bool_sysctl(buf: &[u8]) -> Result<bool, FsError> {
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    let s = core::str::from_utf8(&buf[..end]).map_err(|_| FsError::InvalidArgument)?;
+    match s.trim() {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(FsError::InvalidArgument),
+    }
+}
+
+/// /proc/sys/kernel/hash_pointers：日志中的内核指针是否打印为哈希值
+pub struct HashPointersGenerator;
+
+impl ContentGenerator for HashPointersGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let enabled = crate::log::hash_pointers_enabled() as u8;
+        Ok(format!("{}\n", enabled).into_bytes())
+    }
+}
+
+/// /proc/sys/kernel/hash_pointers 写端：写入 0 关闭哈希，1 开启
+pub struct HashPointersWriter;
+
+impl ContentWriter for HashPointersWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        crate::log::set_hash_pointers(parse_bool_sysctl(buf)?);
+        Ok(buf.len())
+    }
+}
+
+/// /proc/sys/kernel/sched_schedstats_verbose：任务退出时是否打印调度统计
+pub struct SchedstatsVerboseGenerator;
+
+impl ContentGenerator for SchedstatsVerboseGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let enabled = crate::kernel::schedstat::schedstats_verbose() as u8;
+        Ok(format!("{}\n", enabled).into_bytes())
+    }
+}
+
+/// /proc/sys/kernel/sched_schedstats_verbose 写端：写入 0 关闭，1 开启
+pub struct SchedstatsVerboseWriter;
+
+impl ContentWriter for SchedstatsVerboseWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        crate::kernel::schedstat::set_schedstats_verbose(parse_bool_sysctl(buf)?);
+        Ok(buf.len())
+    }
+}
+
+/// /proc/sys/kernel/sched_rr_timeslice_ms：SCHED_RR 任务的时间片（毫秒）
+pub struct RrTimesliceGenerator;
+
+impl ContentGenerator for RrTimesliceGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let ms = crate::kernel::time::TICK_CONFIG.rr_timeslice_ms();
+        Ok(format!("{}\n", ms).into_bytes())
+    }
+}
+
+/// /proc/sys/kernel/sched_rr_timeslice_ms 写端，从下一次切换任务起生效
+pub struct RrTimesliceWriter;
+
+impl ContentWriter for RrTimesliceWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        let s = core::str::from_utf8(&buf[..end]).map_err(|_| FsError::InvalidArgument)?;
+        let ms = s
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| FsError::InvalidArgument)?;
+        crate::kernel::time::TICK_CONFIG
+            .set_rr_timeslice_ms(ms)
+            .map_err(|_| FsError::InvalidArgument)?;
+        Ok(buf.len())
+    }
+}
+
+/// /proc/sys/dev/rtc/display_offset_minutes：sysfs 显示 RTC 日期时间时相对 UTC 的偏移（分钟）
+pub struct RtcDisplayOffsetGenerator;
+
+impl ContentGenerator for RtcDisplayOffsetGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let minutes = crate::device::rtc::display_offset_minutes();
+        Ok(format!("{}\n", minutes).into_bytes())
+    }
+}
+
+/// /proc/sys/dev/rtc/display_offset_minutes 写端，东正西负，不影响内核中的时间
+pub struct RtcDisplayOffsetWriter;
+
+impl ContentWriter for RtcDisplayOffsetWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        let s = core::str::from_utf8(&buf[..end]).map_err(|_| FsError::InvalidArgument)?;
+        let minutes = s
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| FsError::InvalidArgument)?;
+        crate::device::rtc::set_display_offset_minutes(minutes)
+            .map_err(|_| FsError::InvalidArgument)?;
+        Ok(buf.len())
+    }
+}
+
+/// /proc/sys/fs/file-nr：已打开的文件描述符数、空闲数（恒为 0）和上限
+pub struct FileNrGenerator;
+
+impl ContentGenerator for FileNrGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let nr = crate::vfs::nr_open_files();
+        Ok(format!("{}\t0\t{}\n", nr, crate::config::FILE_MAX).into_bytes())
+    }
+}
+
+/// /proc/sys/fs/file-max：全系统已打开文件描述符的上限
+pub struct FileMaxGenerator;
+
+impl ContentGenerator for FileMaxGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        Ok(format!("{}\n", crate::config::FILE_MAX).into_bytes())
+    }
+}
+
+/// /proc/sys/fs/atime_policy：读取时的 atime 更新策略（0 从不，1 relatime，2 每次）
+pub struct AtimePolicyGenerator;
+
+impl ContentGenerator for AtimePolicyGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let policy = crate::vfs::timestamps::atime_policy() as u8;
+        Ok(format!("{}\n", policy).into_bytes())
+    }
+}
+
+/// /proc/sys/fs/atime_policy 写端
+pub struct AtimePolicyWriter;
+
+impl ContentWriter for AtimePolicyWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        let s = core::str::from_utf8(&buf[..end]).map_err(|_| FsError::InvalidArgument)?;
+        let policy = s
+            .trim()
+            .parse::<u8>()
+            .ok()
+            .and_then(crate::vfs::timestamps::AtimePolicy::from_u8)
+            .ok_or(FsError::InvalidArgument)?;
+        crate::vfs::timestamps::set_atime_policy(policy);
+        Ok(buf.len())
+    }
+}
+
+/// /proc/sys/net/ipv4/ip_forward：是否在接口之间转发 IPv4 数据包
+pub struct IpForwardGenerator;
+
+impl ContentGenerator for IpForwardGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let enabled = crate::net::route::ip_forward_enabled() as u8;
+        Ok(format!("{}\n", enabled).into_bytes())
+    }
+}
+
+/// /proc/sys/net/ipv4/ip_forward 写端：写入 0 关闭转发，1 开启
+pub struct IpForwardWriter;
+
+impl ContentWriter for IpForwardWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        crate::net::route::set_ip_forward(parse_bool_sysctl(buf)?);
+        Ok(buf.len())
+    }
+}
+
+/// /proc/sys/vm/wx_policy：同时可写可执行映射的处理策略（0 放行，1 告警，2 拒绝）
+pub struct WxPolicyGenerator;
+
+impl ContentGenerator for WxPolicyGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let policy = crate::mm::wx_policy::wx_policy() as u8;
+        Ok(format!("{}\n", policy).into_bytes())
+    }
+}
+
+/// /proc/sys/vm/wx_policy 写端
+pub struct WxPolicyWriter;
+
+impl ContentWriter for WxPolicyWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        let s = core::str::from_utf8(&buf[..end]).map_err(|_| FsError::InvalidArgument)?;
+        let policy = s
+            .trim()
+            .parse::<u8>()
+            .ok()
+            .and_then(crate::mm::wx_policy::WxPolicy::from_u8)
+            .ok_or(FsError::InvalidArgument)?;
+        crate::mm::wx_policy::set_wx_policy(policy);
+        Ok(buf.len())
+    }
+}
+
+/// /proc/sys/vm/mmap_min_addr：用户 mmap 可使用的最低地址
+pub struct MmapMinAddrGenerator;
+
+impl ContentGenerator for MmapMinAddrGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let addr = crate::mm::memory_space::mmap_min_addr();
+        Ok(format!("{}\n", addr).into_bytes())
+    }
+}
+
+/// /proc/sys/vm/mmap_min_addr 写端：写入十进制地址，向上取整到页
+pub struct MmapMinAddrWriter;
+
+impl ContentWriter for MmapMinAddrWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        let s = core::str::from_utf8(&buf[..end]).map_err(|_| FsError::InvalidArgument)?;
+        let addr = s
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| FsError::InvalidArgument)?;
+        crate::mm::memory_space::set_mmap_min_addr(addr);
+        Ok(buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_bool_sysctl;
+    use crate::{kassert, test_case};
+
+    test_case!(test_parse_bool_sysctl, {
+        kassert!(parse_bool_sysctl(b"0\n").ok() == Some(false));
+        kassert!(parse_bool_sysctl(b"1").ok() == Some(true));
+        kassert!(parse_bool_sysctl(b"1\0").ok() == Some(true));
+        kassert!(parse_bool_sysctl(b"2").is_err());
+        kassert!(parse_bool_sysctl(b"").is_err());
+    });
+}
@@ -0,0 +1,24 @@
+use alloc::vec::Vec;
+
+use crate::fs::proc::inode::{ContentGenerator, ContentWriter};
+use crate::vfs::FsError;
+
+/// /proc/sysrq-trigger：只写，读取为空
+pub struct SysrqTriggerGenerator;
+
+impl ContentGenerator for SysrqTriggerGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        Ok(Vec::new())
+    }
+}
+
+/// /proc/sysrq-trigger 写端：按写入的第一个字节执行 SysRq 命令，见 [`crate::kernel::sysrq`]
+pub struct SysrqTriggerWriter;
+
+impl ContentWriter for SysrqTriggerWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        let &key = buf.first().ok_or(FsError::InvalidArgument)?;
+        crate::kernel::sysrq::handle_sysrq(key);
+        Ok(buf.len())
+    }
+}
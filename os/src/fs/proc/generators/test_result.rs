@@ -0,0 +1,27 @@
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::fs::proc::inode::{ContentGenerator, ContentWriter};
+use crate::test::user_result::{self, UserTestResult};
+use crate::vfs::FsError;
+
+/// /proc/test-result：用户态测试报告的整体结果（`none`、`pass` 或 `fail`）
+pub struct TestResultGenerator;
+
+impl ContentGenerator for TestResultGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let result = user_result::result().map_or("none", |r| r.as_str());
+        Ok(format!("{}\n", result).into_bytes())
+    }
+}
+
+/// /proc/test-result 写端：写入 `pass` 或 `fail`，关机时决定模拟器的退出码
+pub struct TestResultWriter;
+
+impl ContentWriter for TestResultWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        let result = UserTestResult::parse(buf).ok_or(FsError::InvalidArgument)?;
+        user_result::report(result);
+        Ok(buf.len())
+    }
+}
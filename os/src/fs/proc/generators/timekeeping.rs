@@ -0,0 +1,65 @@
+use alloc::vec::Vec;
+use alloc::{format, string::String};
+
+use crate::device::RTC_DRIVERS;
+use crate::fs::proc::inode::ContentGenerator;
+use crate::kernel::time::{NTP, boottime_now, realtime_now};
+use crate::vfs::{FsError, TimeSpec};
+
+/// /proc/timekeeping：时钟状态、adjtimex 参数和相对 RTC 的漂移统计
+pub struct TimekeepingGenerator;
+
+impl ContentGenerator for TimekeepingGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let realtime = realtime_now();
+        let monotonic = TimeSpec::monotonic_now();
+        let boottime = boottime_now();
+        let ntp = *NTP.lock();
+
+        let rtc = match RTC_DRIVERS.read().first() {
+            Some(rtc) => {
+                let epoch = rtc.read_epoch() as i64;
+                // RTC 只有秒级精度，漂移以软件时钟减去 RTC 计
+                let drift_ns = (realtime.tv_sec - epoch) * 1_000_000_000 + realtime.tv_nsec;
+                format!("rtc:\t\t\t{}\nrtc_drift_ns:\t\t{}\n", epoch, drift_ns)
+            }
+            None => String::from("rtc:\t\t\tnone\n"),
+        };
+
+        let content = format!(
+            "realtime:\t\t{}.{:09}\n\
+             monotonic:\t\t{}.{:09}\n\
+             boottime:\t\t{}.{:09}\n\
+             {}\
+             freq:\t\t\t{}\n\
+             tick_usec:\t\t{}\n\
+             offset_remaining_ns:\t{}\n\
+             status:\t\t\t{:#x}\n\
+             maxerror:\t\t{}\n\
+             esterror:\t\t{}\n\
+             freq_adjusted_ns:\t{}\n\
+             slewed_ns:\t\t{}\n\
+             stepped_ns:\t\t{}\n\
+             adjustments:\t\t{}\n",
+            realtime.tv_sec,
+            realtime.tv_nsec,
+            monotonic.tv_sec,
+            monotonic.tv_nsec,
+            boottime.tv_sec,
+            boottime.tv_nsec,
+            rtc,
+            ntp.freq,
+            ntp.tick_usec,
+            ntp.offset_ns,
+            ntp.status,
+            ntp.maxerror,
+            ntp.esterror,
+            ntp.freq_adjusted_ns,
+            ntp.slewed_ns,
+            ntp.stepped_ns,
+            ntp.adjustments,
+        );
+
+        Ok(content.into_bytes())
+    }
+}
@@ -13,9 +13,11 @@ impl ContentGenerator for UptimeGenerator {
         let uptime_sec = uptime_ms / 1000;
         let uptime_frac = (uptime_ms % 1000) / 10; // 保留2位小数
 
-        // TODO: 获取空闲时间
-        let idle_sec = 0;
-        let idle_frac = 0;
+        // 空闲时间为所有 CPU 之和，与 Linux 一致
+        let idle_ms = (crate::kernel::idle::total_idle_cycles() as u128 * 1000
+            / crate::arch::clock_freq() as u128) as usize;
+        let idle_sec = idle_ms / 1000;
+        let idle_frac = (idle_ms % 1000) / 10;
 
         let content = format!(
             "{}.{:02} {}.{:02}\n",
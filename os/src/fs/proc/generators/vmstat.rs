@@ -0,0 +1,22 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::fs::proc::inode::ContentGenerator;
+use crate::kernel::kstat;
+use crate::vfs::FsError;
+
+/// /proc/vmstat：[`kstat`] 注册表中的全部指标，每行 `名字 值`
+///
+/// 值保持登记时的单位（页数、字节数或次数），不做换算。
+pub struct VmstatGenerator;
+
+impl ContentGenerator for VmstatGenerator {
+    fn generate(&self) -> Result<Vec<u8>, FsError> {
+        let mut content = String::new();
+        for (name, _, value) in kstat::snapshot().iter() {
+            let _ = writeln!(content, "{} {}", name, value);
+        }
+        Ok(content.into_bytes())
+    }
+}
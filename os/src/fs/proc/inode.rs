@@ -1,9 +1,16 @@
 use core::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::{
+    fs::proc::options::{HidePid, ProcMountOptions},
+    kernel::{Credential, SharedTask, try_current_task},
     sync::{Mutex, SpinLock},
-    uapi::time::TimeSpec,
-    vfs::{DirEntry, FileMode, FsError, Inode, InodeMetadata, InodeType},
+    uapi::{
+        fs::{R_OK, W_OK, X_OK},
+        time::TimeSpec,
+    },
+    vfs::{
+        DirEntry, FileMode, FsError, Inode, InodeMetadata, InodeType, perm::check_current_access,
+    },
 };
 use alloc::{
     collections::BTreeMap,
@@ -15,8 +22,13 @@ use alloc::{
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ProcInodeKind {
     Generic,
-    Root,
-    PidDir(u32),
+    /// `/proc` 根目录，带挂载选项
+    Root(ProcMountOptions),
+    /// `/proc/[pid]`；`denied` 表示 `hidepid=noaccess` 下调用者无权访问其内容
+    PidDir {
+        pid: u32,
+        denied: bool,
+    },
 }
 
 const PROC_PID_INO_BASE: usize = 1_000_000_000;
@@ -30,6 +42,28 @@ fn proc_pid_child_inode_no(pid: u32, offset: usize) -> usize {
     proc_pid_dir_inode_no(pid).saturating_add(offset)
 }
 
+/// 普通文件的完整模式：补上 `S_IFREG`，没有写入器的文件去掉所有写权限位
+fn proc_file_mode(mode: FileMode, writable: bool) -> FileMode {
+    let mut mode = mode.difference(FileMode::S_IFMT) | FileMode::S_IFREG;
+    if !writable {
+        mode.remove(FileMode::S_IWUSR | FileMode::S_IWGRP | FileMode::S_IWOTH);
+    }
+    mode
+}
+
+/// 当前任务的凭证，内核上下文中为 `None`
+fn caller_credential() -> Option<Credential> {
+    try_current_task().map(|task| task.lock().credential)
+}
+
+/// 调用者能否访问 `task` 的 `/proc/[pid]`，内核上下文总是可以
+fn pid_visible(options: &ProcMountOptions, caller: Option<&Credential>, task: &SharedTask) -> bool {
+    match caller {
+        Some(cred) => options.pid_visible(cred, &task.lock().credential),
+        None => true,
+    }
+}
+
 /// 动态内容生成器 trait
 pub trait ContentGenerator: Send + Sync {
     /// 生成文件内容（每次调用时重新生成）
@@ -79,8 +113,9 @@ pub enum ProcInodeContent {
 static NEXT_INODE_NO: AtomicUsize = AtomicUsize::new(1);
 
 impl ProcInode {
-    pub fn new_proc_root_directory(mode: FileMode) -> Arc<Self> {
-        Self::new_directory_with_inode_no(mode, None, ProcInodeKind::Root)
+    /// 创建 `/proc` 根目录 inode，`options` 决定其他用户的进程目录是否可见
+    pub fn new_proc_root_directory(mode: FileMode, options: ProcMountOptions) -> Arc<Self> {
+        Self::new_directory_with_inode_no(mode, None, ProcInodeKind::Root(options))
     }
 
     /// 创建静态文件 inode
@@ -93,7 +128,7 @@ impl ProcInode {
             metadata: SpinLock::new(InodeMetadata {
                 inode_no,
                 inode_type: InodeType::File,
-                mode,
+                mode: proc_file_mode(mode, false),
                 uid: 0,
                 gid: 0,
                 size: 0, // proc 文件总是返回 size = 0
@@ -130,7 +165,7 @@ impl ProcInode {
             metadata: SpinLock::new(InodeMetadata {
                 inode_no,
                 inode_type: InodeType::File,
-                mode,
+                mode: proc_file_mode(mode, false),
                 uid: 0,
                 gid: 0,
                 size: 0, // proc 文件总是返回 size = 0
@@ -169,7 +204,7 @@ impl ProcInode {
             metadata: SpinLock::new(InodeMetadata {
                 inode_no,
                 inode_type: InodeType::File,
-                mode,
+                mode: proc_file_mode(mode, true),
                 uid: 0,
                 gid: 0,
                 size: 0, // proc 文件总是返回 size = 0
@@ -288,23 +323,59 @@ impl ProcInode {
         }
     }
 
+    /// 设置属主，目录连同所有子节点一起设置
+    fn set_owner(&self, uid: u32, gid: u32) {
+        {
+            let mut meta = self.metadata.lock();
+            meta.uid = uid;
+            meta.gid = gid;
+        }
+        if let ProcInodeContent::Directory(children) = &self.content {
+            for child in children.lock().values() {
+                child.set_owner(uid, gid);
+            }
+        }
+    }
+
+    /// 以当前任务的凭证检查对本节点的访问权限
+    fn check_access(&self, mask: i32) -> Result<(), FsError> {
+        let meta = self.metadata.lock().clone();
+        check_current_access(&meta, mask)
+    }
+
     /// 为指定 PID 创建进程目录
-    fn create_process_dir(&self, pid: u32) -> Option<Arc<ProcInode>> {
+    ///
+    /// 目录及其中的文件属于进程的有效用户和组。按 `hidepid` 选项，调用者无权查看该进程时：
+    /// `invisible` 返回 `None`，`noaccess` 返回一个不能访问的空目录。
+    fn create_process_dir(&self, pid: u32, options: &ProcMountOptions) -> Option<Arc<ProcInode>> {
         use crate::fs::proc::generators::{
-            CmdlineGenerator, MapsGenerator, StatGenerator, StatusGenerator,
-            process::{OomScoreAdjGenerator, OomScoreAdjWriter, OomScoreGenerator},
+            CmdlineGenerator, MapsGenerator, MountsGenerator, StatGenerator, StatusGenerator,
+            process::{
+                OomScoreAdjGenerator, OomScoreAdjWriter, OomScoreGenerator, PagemapGenerator,
+                SchedGenerator, SchedstatGenerator,
+            },
         };
         use crate::kernel::{TASK_MANAGER, TaskManagerTrait};
 
         // 获取任务
         let task = TASK_MANAGER.lock().get_task(pid)?;
+        let cred = task.lock().credential;
+
+        let denied = !pid_visible(options, caller_credential().as_ref(), &task);
+        if denied && options.hidepid == HidePid::Invisible {
+            return None;
+        }
 
         // 创建进程目录
         let proc_dir = Self::new_directory_with_inode_no(
             FileMode::from_bits_truncate(0o555 | FileMode::S_IFDIR.bits()),
             Some(proc_pid_dir_inode_no(pid)),
-            ProcInodeKind::PidDir(pid),
+            ProcInodeKind::PidDir { pid, denied },
         );
+        if denied {
+            proc_dir.set_owner(cred.euid, cred.egid);
+            return Some(proc_dir);
+        }
 
         // 创建 status 文件
         let status = Self::new_dynamic_file_with_inode_no(
@@ -351,6 +422,14 @@ impl ProcInode {
         );
         let _ = proc_dir.add_child("maps", maps);
 
+        // 创建 pagemap 文件：逐页显示物理页号和页表项标志
+        let pagemap = Self::new_dynamic_file_with_inode_no(
+            Arc::new(PagemapGenerator::new(Arc::downgrade(&task))),
+            FileMode::from_bits_truncate(0o400),
+            Some(proc_pid_child_inode_no(pid, 12)),
+        );
+        let _ = proc_dir.add_child("pagemap", pagemap);
+
         let oom_score_adj = Self::new_writable_dynamic_file_with_inode_no(
             Arc::new(OomScoreAdjGenerator::new(Arc::downgrade(&task))),
             Arc::new(OomScoreAdjWriter::new(Arc::downgrade(&task))),
@@ -366,6 +445,45 @@ impl ProcInode {
         );
         let _ = proc_dir.add_child("oom_score", oom_score);
 
+        // 创建 cwd 符号链接：读取时从 cwd dentry 重建路径，已删除的目录带 " (deleted)"
+        let task_weak = Arc::downgrade(&task);
+        let cwd = Self::new_dynamic_symlink_with_inode_no(
+            move || {
+                task_weak
+                    .upgrade()
+                    .and_then(|t| t.lock().fs.lock().cwd.clone())
+                    .map(|cwd| cwd.d_path())
+                    .unwrap_or_else(|| "/".to_string())
+            },
+            Some(proc_pid_child_inode_no(pid, 8)),
+        );
+        let _ = proc_dir.add_child("cwd", cwd);
+
+        // 创建 sched 文件：调度统计
+        let sched = Self::new_dynamic_file_with_inode_no(
+            Arc::new(SchedGenerator::new(Arc::downgrade(&task))),
+            FileMode::from_bits_truncate(0o444),
+            Some(proc_pid_child_inode_no(pid, 9)),
+        );
+        let _ = proc_dir.add_child("sched", sched);
+
+        // 创建 schedstat 文件：运行、等待和阻塞时间
+        let schedstat = Self::new_dynamic_file_with_inode_no(
+            Arc::new(SchedstatGenerator::new(Arc::downgrade(&task))),
+            FileMode::from_bits_truncate(0o444),
+            Some(proc_pid_child_inode_no(pid, 11)),
+        );
+        let _ = proc_dir.add_child("schedstat", schedstat);
+
+        // 创建 mounts 文件：没有挂载命名空间，所有进程看到同一张挂载表
+        let mounts = Self::new_dynamic_file_with_inode_no(
+            Arc::new(MountsGenerator),
+            FileMode::from_bits_truncate(0o444),
+            Some(proc_pid_child_inode_no(pid, 10)),
+        );
+        let _ = proc_dir.add_child("mounts", mounts);
+
+        proc_dir.set_owner(cred.euid, cred.egid);
         Some(proc_dir)
     }
 }
@@ -383,6 +501,9 @@ impl Inode for ProcInode {
     }
 
     fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, FsError> {
+        if !matches!(self.content, ProcInodeContent::Directory(_)) {
+            self.check_access(R_OK)?;
+        }
         match &self.content {
             ProcInodeContent::Static(data) => {
                 if offset >= data.len() {
@@ -416,7 +537,10 @@ impl Inode for ProcInode {
 
     fn write_at(&self, _offset: usize, buf: &[u8]) -> Result<usize, FsError> {
         match &self.content {
-            ProcInodeContent::WritableDynamic { writer, .. } => writer.write(buf),
+            ProcInodeContent::WritableDynamic { writer, .. } => {
+                self.check_access(W_OK)?;
+                writer.write(buf)
+            }
             _ => Err(FsError::PermissionDenied),
         }
     }
@@ -424,17 +548,22 @@ impl Inode for ProcInode {
     fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>, FsError> {
         match &self.content {
             ProcInodeContent::Directory(children) => {
+                if matches!(self.kind, ProcInodeKind::PidDir { denied: true, .. }) {
+                    return Err(FsError::PermissionDenied);
+                }
+                self.check_access(X_OK)?;
+
                 // 先从已有的子节点中查找
                 if let Some(child) = children.lock().get(name).cloned() {
                     return Ok(child as Arc<dyn Inode>);
                 }
 
                 // 仅对 /proc 根目录：检查是否为进程目录（数字命名）
-                if self.kind == ProcInodeKind::Root
+                if let ProcInodeKind::Root(options) = &self.kind
                     && let Ok(pid) = name.parse::<u32>()
                 {
                     // 动态创建进程目录（不缓存，避免 stale PID 与 dentry 缓存问题）
-                    if let Some(proc_dir) = self.create_process_dir(pid) {
+                    if let Some(proc_dir) = self.create_process_dir(pid, options) {
                         return Ok(proc_dir as Arc<dyn Inode>);
                     }
                 }
@@ -481,7 +610,12 @@ impl Inode for ProcInode {
     fn readdir(&self) -> Result<Vec<DirEntry>, FsError> {
         match &self.content {
             ProcInodeContent::Directory(children) => {
-                let metadata = self.metadata.lock();
+                if matches!(self.kind, ProcInodeKind::PidDir { denied: true, .. }) {
+                    return Err(FsError::PermissionDenied);
+                }
+                self.check_access(R_OK)?;
+
+                let metadata = self.metadata.lock().clone();
                 let mut entries = Vec::new();
 
                 entries.push(DirEntry {
@@ -508,8 +642,9 @@ impl Inode for ProcInode {
                     guard.keys().cloned().collect()
                 };
 
-                if self.kind == ProcInodeKind::Root {
+                if let ProcInodeKind::Root(options) = &self.kind {
                     use crate::kernel::{TASK_MANAGER, TaskManagerTrait};
+                    let caller = caller_credential();
                     let pids = TASK_MANAGER.lock().list_process_pids_snapshot();
                     for pid in pids {
                         let name = pid.to_string();
@@ -517,6 +652,13 @@ impl Inode for ProcInode {
                         if child_names.binary_search(&name).is_ok() {
                             continue;
                         }
+                        // hidepid=invisible：不列出调用者无权查看的进程
+                        if options.hidepid == HidePid::Invisible {
+                            let task = TASK_MANAGER.lock().get_task(pid);
+                            if !task.is_some_and(|t| pid_visible(options, caller.as_ref(), &t)) {
+                                continue;
+                            }
+                        }
                         entries.push(DirEntry {
                             name,
                             inode_no: proc_pid_dir_inode_no(pid),
@@ -533,7 +675,7 @@ impl Inode for ProcInode {
 
     fn cacheable(&self) -> bool {
         // /proc/[pid] 目录不缓存：避免进程退出后仍可通过 dentry cache 访问（幽灵 PID）。
-        !matches!(self.kind, ProcInodeKind::PidDir(_))
+        !matches!(self.kind, ProcInodeKind::PidDir { .. })
     }
 
     fn truncate(&self, size: usize) -> Result<(), FsError> {
@@ -7,6 +7,7 @@
 //! - [`ProcFS`] - 文件系统结构，管理 /proc 目录树
 //! - [`ProcInode`] - Inode 实现，支持静态和动态内容
 //! - [`ContentGenerator`] - 动态内容生成器 trait
+//! - [`ProcMountOptions`] - 挂载选项（`hidepid=`、`gid=`）
 //! - [`generators`] - 内置生成器（meminfo、cpuinfo、uptime 等）
 //!
 //! # 设计概览
@@ -28,20 +29,31 @@
 //! - **动态符号链接**：目标路径动态计算（如 `/proc/self`）
 //! - **进程目录**：为每个进程创建 `/proc/[pid]/` 子目录
 //!
+//! ## 访问控制
+//!
+//! 每个节点带完整的文件模式，读写、查找时按调用者凭证检查权限位。
+//! 没有写入器的文件总是只读。`/proc/[pid]/` 及其文件属于该进程的有效用户和组，
+//! `hidepid=` 挂载选项可以对其他用户隐藏进程目录，见 [`options`]。
+//!
 //! # 导出的信息
 //!
 //! ## 系统信息
 //!
 //! - `/proc/meminfo` - 内存使用情况
+//! - `/proc/vmstat` - 内核统计指标（见 [`crate::kernel::kstat`]）
 //! - `/proc/cpuinfo` - CPU 信息
+//! - `/proc/sbi` - SBI 实现信息与扩展探测结果（仅 RISC-V）
 //! - `/proc/uptime` - 系统运行时间
-//! - `/proc/mounts` - 挂载点列表
+//! - `/proc/mounts` - 挂载点列表（fstab 格式）
+//! - `/proc/test-result` - 用户态测试结果，写入 `pass`/`fail`，关机时决定模拟器退出码
 //!
 //! ## 进程信息
 //!
 //! - `/proc/[pid]/stat` - 进程状态（单行格式）
 //! - `/proc/[pid]/status` - 详细状态（键值对格式）
 //! - `/proc/[pid]/cmdline` - 命令行参数
+//! - `/proc/[pid]/sched` - 调度统计（vruntime、累计运行时间等）
+//! - `/proc/[pid]/mounts` - 进程可见的挂载点列表，与 `/proc/mounts` 相同
 //!
 //! # 使用示例
 //!
@@ -58,7 +70,9 @@
 
 pub mod generators;
 pub mod inode;
+pub mod options;
 pub mod proc;
 
 pub use inode::{ContentGenerator, ProcInode};
+pub use options::ProcMountOptions;
 pub use proc::ProcFS;
@@ -0,0 +1,64 @@
+//! procfs 挂载选项
+//!
+//! 支持 Linux 的 `hidepid=` 和 `gid=`，用于隐藏其他用户的 `/proc/[pid]` 目录：
+//! - `hidepid=0`/`off`：不限制（默认）；
+//! - `hidepid=1`/`noaccess`：目录可见，但不能访问其中的文件；
+//! - `hidepid=2`/`invisible`：目录不可见，`lookup` 返回 `ENOENT`，`readdir` 不列出；
+//! - `gid=N`：属于该组的调用者不受 `hidepid` 限制。
+//!
+//! 能否看到某个进程的判定见 [`Credential::may_inspect`]。
+
+use crate::kernel::Credential;
+use crate::vfs::FsError;
+
+/// `/proc/[pid]` 的隐藏级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HidePid {
+    #[default]
+    Off,
+    NoAccess,
+    Invisible,
+}
+
+/// procfs 挂载选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProcMountOptions {
+    pub hidepid: HidePid,
+    /// 不受 `hidepid` 限制的组
+    pub gid: Option<u32>,
+}
+
+impl ProcMountOptions {
+    /// 解析 mount(2) 的 data 字符串，如 `hidepid=2,gid=10`
+    ///
+    /// # 返回
+    /// - `Err(FsError::InvalidArgument)`: 未知选项或取值非法
+    pub fn parse(data: &str) -> Result<Self, FsError> {
+        let mut options = Self::default();
+        for opt in data.split(',').map(str::trim).filter(|opt| !opt.is_empty()) {
+            let (key, value) = opt.split_once('=').ok_or(FsError::InvalidArgument)?;
+            match key {
+                "hidepid" => {
+                    options.hidepid = match value {
+                        "0" | "off" => HidePid::Off,
+                        "1" | "noaccess" => HidePid::NoAccess,
+                        "2" | "invisible" => HidePid::Invisible,
+                        _ => return Err(FsError::InvalidArgument),
+                    }
+                }
+                "gid" => {
+                    options.gid = Some(value.parse().map_err(|_| FsError::InvalidArgument)?);
+                }
+                _ => return Err(FsError::InvalidArgument),
+            }
+        }
+        Ok(options)
+    }
+
+    /// 凭证为 `cred` 的调用者能否访问凭证为 `target` 的进程的 `/proc/[pid]`
+    pub fn pid_visible(&self, cred: &Credential, target: &Credential) -> bool {
+        self.hidepid == HidePid::Off
+            || self.gid.is_some_and(|gid| cred.in_group(gid))
+            || cred.may_inspect(target)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    fs::proc::ProcInode,
+    fs::proc::{ProcInode, ProcMountOptions},
+    uapi::fs::FileSystemType,
     vfs::{FileMode, FileSystem, FsError, Inode, StatFs},
 };
 use alloc::sync::Arc;
@@ -11,10 +12,16 @@ pub struct ProcFS {
 impl ProcFS {
     /// 创建新的 ProcFS 实例
     pub fn new() -> Arc<Self> {
+        Self::with_options(ProcMountOptions::default())
+    }
+
+    /// 按挂载选项（如 `hidepid=`）创建 ProcFS 实例
+    pub fn with_options(options: ProcMountOptions) -> Arc<Self> {
         // 创建根目录
-        let root = ProcInode::new_proc_root_directory(FileMode::from_bits_truncate(
-            0o555 | FileMode::S_IFDIR.bits(),
-        ));
+        let root = ProcInode::new_proc_root_directory(
+            FileMode::from_bits_truncate(0o555 | FileMode::S_IFDIR.bits()),
+            options,
+        );
 
         Arc::new(Self { root_inode: root })
     }
@@ -22,8 +29,17 @@ impl ProcFS {
     /// 初始化 proc 文件系统树结构
     pub fn init_tree(self: &Arc<Self>) -> Result<(), FsError> {
         use crate::fs::proc::generators::{
-            CpuinfoGenerator, KernelCmdlineGenerator, MeminfoGenerator, MountsGenerator,
-            UptimeGenerator,
+            AtimePolicyGenerator, AtimePolicyWriter, BootinfoGenerator, CpuinfoGenerator,
+            EnosysGenerator, EnosysWriter, FileMaxGenerator, FileNrGenerator,
+            HashPointersGenerator, HashPointersWriter, IpForwardGenerator, IpForwardWriter,
+            KernelCmdlineGenerator, LoadavgGenerator, MeminfoGenerator, MmapMinAddrGenerator,
+            MmapMinAddrWriter, MountsGenerator, NetDevGenerator, NetRouteGenerator,
+            NetTcpGenerator, NetUdpGenerator, PowerStateGenerator, PowerStateWriter,
+            PowerStatsGenerator, RrTimesliceGenerator, RrTimesliceWriter,
+            RtcDisplayOffsetGenerator, RtcDisplayOffsetWriter, SchedstatsVerboseGenerator,
+            SchedstatsVerboseWriter, SwapsGenerator, SystemStatGenerator, TestResultGenerator,
+            TestResultWriter, TimekeepingGenerator, UptimeGenerator, VmstatGenerator,
+            WxPolicyGenerator, WxPolicyWriter,
         };
         use crate::kernel::current_task;
 
@@ -45,6 +61,14 @@ impl ProcFS {
         );
         root.add_child("meminfo", meminfo)?;
 
+        // 创建 /proc/swaps
+        let swaps = ProcInode::new_dynamic_file(
+            "swaps",
+            alloc::sync::Arc::new(SwapsGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        root.add_child("swaps", swaps)?;
+
         // 创建 /proc/uptime
         let uptime = ProcInode::new_dynamic_file(
             "uptime",
@@ -53,6 +77,55 @@ impl ProcFS {
         );
         root.add_child("uptime", uptime)?;
 
+        // 创建 /proc/bootinfo
+        let bootinfo = ProcInode::new_dynamic_file(
+            "bootinfo",
+            alloc::sync::Arc::new(BootinfoGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        root.add_child("bootinfo", bootinfo)?;
+
+        // 创建 /proc/enosys - 未实现系统调用的直方图，写入 0 清空
+        let enosys = ProcInode::new_writable_dynamic_file(
+            "enosys",
+            alloc::sync::Arc::new(EnosysGenerator),
+            alloc::sync::Arc::new(EnosysWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        root.add_child("enosys", enosys)?;
+
+        // 创建 /proc/stat
+        let stat = ProcInode::new_dynamic_file(
+            "stat",
+            alloc::sync::Arc::new(SystemStatGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        root.add_child("stat", stat)?;
+
+        // 创建 /proc/vmstat
+        let vmstat = ProcInode::new_dynamic_file(
+            "vmstat",
+            alloc::sync::Arc::new(VmstatGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        root.add_child("vmstat", vmstat)?;
+
+        // 创建 /proc/loadavg
+        let loadavg = ProcInode::new_dynamic_file(
+            "loadavg",
+            alloc::sync::Arc::new(LoadavgGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        root.add_child("loadavg", loadavg)?;
+
+        // 创建 /proc/timekeeping
+        let timekeeping = ProcInode::new_dynamic_file(
+            "timekeeping",
+            alloc::sync::Arc::new(TimekeepingGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        root.add_child("timekeeping", timekeeping)?;
+
         // 创建 /proc/cpuinfo
         let cpuinfo = ProcInode::new_dynamic_file(
             "cpuinfo",
@@ -61,6 +134,25 @@ impl ProcFS {
         );
         root.add_child("cpuinfo", cpuinfo)?;
 
+        // 创建 /proc/sbi - SBI 实现信息与扩展探测结果
+        #[cfg(target_arch = "riscv64")]
+        {
+            let sbi = ProcInode::new_dynamic_file(
+                "sbi",
+                alloc::sync::Arc::new(crate::fs::proc::generators::SbiGenerator),
+                FileMode::from_bits_truncate(0o444), // r--r--r--
+            );
+            root.add_child("sbi", sbi)?;
+        }
+
+        // 创建 /proc/config - 构建配置
+        let config = ProcInode::new_dynamic_file(
+            "config",
+            alloc::sync::Arc::new(crate::fs::proc::generators::ConfigGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        root.add_child("config", config)?;
+
         // 创建 /proc/mounts
         let mounts = ProcInode::new_dynamic_file(
             "mounts",
@@ -77,6 +169,192 @@ impl ProcFS {
         );
         root.add_child("psmem", psmem)?;
 
+        // 创建 /proc/test-result - 用户态测试报告结果，关机时转为模拟器退出码
+        let test_result = ProcInode::new_writable_dynamic_file(
+            "test-result",
+            alloc::sync::Arc::new(TestResultGenerator),
+            alloc::sync::Arc::new(TestResultWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        root.add_child("test-result", test_result)?;
+
+        // 创建 /proc/sysrq-trigger - 写入命令字母把内核状态转储到日志
+        #[cfg(feature = "sysrq")]
+        {
+            use crate::fs::proc::generators::{SysrqTriggerGenerator, SysrqTriggerWriter};
+            let sysrq_trigger = ProcInode::new_writable_dynamic_file(
+                "sysrq-trigger",
+                alloc::sync::Arc::new(SysrqTriggerGenerator),
+                alloc::sync::Arc::new(SysrqTriggerWriter),
+                FileMode::from_bits_truncate(0o200), // -w-------
+            );
+            root.add_child("sysrq-trigger", sysrq_trigger)?;
+        }
+
+        // 创建 /proc/sys/power/{state,stats} - 写入 freeze 进入 suspend-to-idle
+        let sys = ProcInode::new_directory(FileMode::from_bits_truncate(
+            0o555 | FileMode::S_IFDIR.bits(),
+        ));
+        let power = ProcInode::new_directory(FileMode::from_bits_truncate(
+            0o555 | FileMode::S_IFDIR.bits(),
+        ));
+        let state = ProcInode::new_writable_dynamic_file(
+            "state",
+            alloc::sync::Arc::new(PowerStateGenerator),
+            alloc::sync::Arc::new(PowerStateWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        power.add_child("state", state)?;
+        let stats = ProcInode::new_dynamic_file(
+            "stats",
+            alloc::sync::Arc::new(PowerStatsGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        power.add_child("stats", stats)?;
+        sys.add_child("power", power)?;
+
+        // 创建 /proc/sys/kernel/hash_pointers - 日志指针哈希开关
+        let kernel = ProcInode::new_directory(FileMode::from_bits_truncate(
+            0o555 | FileMode::S_IFDIR.bits(),
+        ));
+        let hash_pointers = ProcInode::new_writable_dynamic_file(
+            "hash_pointers",
+            alloc::sync::Arc::new(HashPointersGenerator),
+            alloc::sync::Arc::new(HashPointersWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        kernel.add_child("hash_pointers", hash_pointers)?;
+
+        // 创建 /proc/sys/kernel/sched_rr_timeslice_ms - SCHED_RR 时间片
+        let rr_timeslice = ProcInode::new_writable_dynamic_file(
+            "sched_rr_timeslice_ms",
+            alloc::sync::Arc::new(RrTimesliceGenerator),
+            alloc::sync::Arc::new(RrTimesliceWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        kernel.add_child("sched_rr_timeslice_ms", rr_timeslice)?;
+
+        // 创建 /proc/sys/kernel/sched_schedstats_verbose - 任务退出时打印调度统计
+        let schedstats_verbose = ProcInode::new_writable_dynamic_file(
+            "sched_schedstats_verbose",
+            alloc::sync::Arc::new(SchedstatsVerboseGenerator),
+            alloc::sync::Arc::new(SchedstatsVerboseWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        kernel.add_child("sched_schedstats_verbose", schedstats_verbose)?;
+        sys.add_child("kernel", kernel)?;
+
+        // 创建 /proc/sys/dev/rtc/display_offset_minutes - sysfs 中 RTC 时间的显示偏移
+        let dev = ProcInode::new_directory(FileMode::from_bits_truncate(
+            0o555 | FileMode::S_IFDIR.bits(),
+        ));
+        let rtc = ProcInode::new_directory(FileMode::from_bits_truncate(
+            0o555 | FileMode::S_IFDIR.bits(),
+        ));
+        let rtc_display_offset = ProcInode::new_writable_dynamic_file(
+            "display_offset_minutes",
+            alloc::sync::Arc::new(RtcDisplayOffsetGenerator),
+            alloc::sync::Arc::new(RtcDisplayOffsetWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        rtc.add_child("display_offset_minutes", rtc_display_offset)?;
+        dev.add_child("rtc", rtc)?;
+        sys.add_child("dev", dev)?;
+
+        // 创建 /proc/sys/fs/{file-nr,file-max,atime_policy} - 文件描述符计数和 atime 策略
+        let fs = ProcInode::new_directory(FileMode::from_bits_truncate(
+            0o555 | FileMode::S_IFDIR.bits(),
+        ));
+        let file_nr = ProcInode::new_dynamic_file(
+            "file-nr",
+            alloc::sync::Arc::new(FileNrGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        fs.add_child("file-nr", file_nr)?;
+        let file_max = ProcInode::new_dynamic_file(
+            "file-max",
+            alloc::sync::Arc::new(FileMaxGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        fs.add_child("file-max", file_max)?;
+        let atime_policy = ProcInode::new_writable_dynamic_file(
+            "atime_policy",
+            alloc::sync::Arc::new(AtimePolicyGenerator),
+            alloc::sync::Arc::new(AtimePolicyWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        fs.add_child("atime_policy", atime_policy)?;
+        sys.add_child("fs", fs)?;
+
+        // 创建 /proc/sys/vm/wx_policy - W^X 策略
+        let vm = ProcInode::new_directory(FileMode::from_bits_truncate(
+            0o555 | FileMode::S_IFDIR.bits(),
+        ));
+        let wx_policy = ProcInode::new_writable_dynamic_file(
+            "wx_policy",
+            alloc::sync::Arc::new(WxPolicyGenerator),
+            alloc::sync::Arc::new(WxPolicyWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        vm.add_child("wx_policy", wx_policy)?;
+        // 创建 /proc/sys/vm/mmap_min_addr - 用户 mmap 最低地址
+        let mmap_min_addr = ProcInode::new_writable_dynamic_file(
+            "mmap_min_addr",
+            alloc::sync::Arc::new(MmapMinAddrGenerator),
+            alloc::sync::Arc::new(MmapMinAddrWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        vm.add_child("mmap_min_addr", mmap_min_addr)?;
+        sys.add_child("vm", vm)?;
+
+        // 创建 /proc/sys/net/ipv4/ip_forward - IPv4 转发开关
+        let net = ProcInode::new_directory(FileMode::from_bits_truncate(
+            0o555 | FileMode::S_IFDIR.bits(),
+        ));
+        let ipv4 = ProcInode::new_directory(FileMode::from_bits_truncate(
+            0o555 | FileMode::S_IFDIR.bits(),
+        ));
+        let ip_forward = ProcInode::new_writable_dynamic_file(
+            "ip_forward",
+            alloc::sync::Arc::new(IpForwardGenerator),
+            alloc::sync::Arc::new(IpForwardWriter),
+            FileMode::from_bits_truncate(0o644), // rw-r--r--
+        );
+        ipv4.add_child("ip_forward", ip_forward)?;
+        net.add_child("ipv4", ipv4)?;
+        sys.add_child("net", net)?;
+        root.add_child("sys", sys)?;
+
+        // 创建 /proc/net/{route,tcp,udp,dev} - 路由表、套接字表与接口统计
+        let net = ProcInode::new_directory(FileMode::from_bits_truncate(
+            0o555 | FileMode::S_IFDIR.bits(),
+        ));
+        let route = ProcInode::new_dynamic_file(
+            "route",
+            alloc::sync::Arc::new(NetRouteGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        net.add_child("route", route)?;
+        let tcp = ProcInode::new_dynamic_file(
+            "tcp",
+            alloc::sync::Arc::new(NetTcpGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        net.add_child("tcp", tcp)?;
+        let udp = ProcInode::new_dynamic_file(
+            "udp",
+            alloc::sync::Arc::new(NetUdpGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        net.add_child("udp", udp)?;
+        let dev = ProcInode::new_dynamic_file(
+            "dev",
+            alloc::sync::Arc::new(NetDevGenerator),
+            FileMode::from_bits_truncate(0o444), // r--r--r--
+        );
+        net.add_child("dev", dev)?;
+        root.add_child("net", net)?;
+
         // 创建 /proc/self - 动态符号链接，指向当前进程
         let self_link = ProcInode::new_dynamic_symlink("self", || {
             use alloc::string::ToString;
@@ -106,6 +384,7 @@ impl FileSystem for ProcFS {
 
     fn statfs(&self) -> Result<StatFs, FsError> {
         Ok(StatFs {
+            fs_type: FileSystemType::Proc,
             block_size: 4096,
             total_blocks: 0,
             free_blocks: 0,
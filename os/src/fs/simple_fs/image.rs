@@ -0,0 +1,455 @@
+//! SimpleFS 镜像格式
+//!
+//! 镜像的解析、序列化、格式化（mkfs）与一致性检查（fsck）。格式与用户态
+//! `mkfs_simplefs` / `fsck_simplefs` 一致。当前为第 2 版：
+//!
+//! ```text
+//! 镜像头   (32B)  "RAMDISK\0" + 文件数量 (u32) + 版本 (u32) + 校验和 (u32) + 保留
+//! 文件头   (64B)  魔数 "FILE" + 名称长度 + 数据长度 + 文件类型 + 权限 + 保留
+//!                 + atime/mtime/ctime 秒 (i64 ×3) + 纳秒 (u32 ×3) + 保留
+//! 文件名          相对根目录的路径（如 `bin/hello`），UTF-8，4 字节对齐
+//! 文件数据        512 字节对齐
+//! ```
+//!
+//! 校验和是把校验和字段置 0 后整个镜像头的 CRC-32。路径的每个分量不超过
+//! [`NAME_MAX`] 字节，目录可以任意嵌套。
+//!
+//! 第 1 版（`build.rs` 与 `scripts/make_init_simple_fs.py` 生成的镜像）的镜像头只有
+//! 16 字节，版本字段位置为保留的 0，没有校验和；文件头只有 32 字节，没有时间戳。
+//! 两个版本都可以读取，时间戳按 0 处理；写回时总是使用第 2 版。
+//!
+//! 所有整数均为小端序。目录条目必须出现在其子项之前，中间目录可以省略。
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::device::block::BlockDriver;
+use crate::uapi::time::TimeSpec;
+use crate::util::crc32::crc32;
+use crate::vfs::FsError;
+
+/// 镜像头魔数
+pub const IMAGE_MAGIC: &[u8; 8] = b"RAMDISK\0";
+/// 文件头魔数（"FILE"）
+pub const ENTRY_MAGIC: u32 = 0x46494C45;
+/// 第 1 版：没有校验和与时间戳
+pub const VERSION_1: u32 = 1;
+/// 第 2 版：带校验和的镜像头，文件头含时间戳
+pub const VERSION_2: u32 = 2;
+/// 写入镜像时使用的版本
+pub const CURRENT_VERSION: u32 = VERSION_2;
+/// 镜像头长度，第一个文件头紧随其后
+pub const HEADER_SIZE: usize = 32;
+/// 第 1 版的镜像头长度
+pub const HEADER_SIZE_V1: usize = 16;
+/// 文件头长度
+pub const ENTRY_HEADER_SIZE: usize = 64;
+/// 第 1 版的文件头长度
+pub const ENTRY_HEADER_SIZE_V1: usize = 32;
+/// 路径分量的长度上限
+pub const NAME_MAX: usize = 255;
+/// 文件类型：普通文件
+pub const FILE_TYPE_FILE: u32 = 0;
+/// 文件类型：目录
+pub const FILE_TYPE_DIR: u32 = 1;
+
+const NAME_ALIGN: usize = 4;
+const DATA_ALIGN: usize = 512;
+/// 路径长度上限，超过即视为文件头损坏
+const MAX_PATH_LEN: usize = 4096;
+/// 镜像头中版本与校验和字段的偏移
+const VERSION_OFFSET: usize = 12;
+const CHECKSUM_OFFSET: usize = 16;
+
+/// 镜像中的一个文件或目录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageEntry {
+    /// 相对根目录的路径
+    pub path: String,
+    pub is_dir: bool,
+    /// 权限位（不含文件类型）
+    pub mode: u32,
+    /// 文件内容，目录为空
+    pub data: Vec<u8>,
+    pub atime: TimeSpec,
+    pub mtime: TimeSpec,
+    pub ctime: TimeSpec,
+}
+
+impl ImageEntry {
+    /// 该条目在（当前版本的）镜像中占用的字节数
+    fn encoded_len(&self) -> usize {
+        ENTRY_HEADER_SIZE
+            + self.path.len().next_multiple_of(NAME_ALIGN)
+            + self.data.len().next_multiple_of(DATA_ALIGN)
+    }
+}
+
+/// 镜像总长度
+pub fn image_len(entries: &[ImageEntry]) -> usize {
+    HEADER_SIZE + entries.iter().map(ImageEntry::encoded_len).sum::<usize>()
+}
+
+/// 设备容量（字节）
+pub fn capacity(device: &dyn BlockDriver) -> usize {
+    device.block_size() * device.total_blocks()
+}
+
+/// 解析镜像中的全部条目
+///
+/// 只做解析所需的最少检查，魔数、校验和或边界错误返回 `IoError`；完整检查见 [`check`]。
+pub fn read_image(device: &dyn BlockDriver) -> Result<Vec<ImageEntry>, FsError> {
+    let header = parse_header(&read_block0(device)?).ok_or(FsError::IoError)?;
+    let mut entries = Vec::new();
+    let mut offset = header.size();
+    for _ in 0..header.file_count {
+        let (raw, next) = read_entry(device, &header, offset)?;
+        let path = String::from_utf8(raw.name).map_err(|_| FsError::IoError)?;
+        entries.push(ImageEntry {
+            path,
+            is_dir: raw.file_type != FILE_TYPE_FILE,
+            mode: raw.mode,
+            data: raw.data,
+            atime: raw.times[0],
+            mtime: raw.times[1],
+            ctime: raw.times[2],
+        });
+        offset = next;
+    }
+    Ok(entries)
+}
+
+/// 把条目序列化写入设备并刷新
+///
+/// 镜像头所在的块最后写入，写到一半失败时旧的文件数量不会指向不完整的条目之外。
+pub fn write_image(device: &dyn BlockDriver, entries: &[ImageEntry]) -> Result<(), FsError> {
+    let block_size = device.block_size();
+    let len = image_len(entries);
+    if len > capacity(device) {
+        return Err(FsError::NoSpace);
+    }
+
+    let mut image = Vec::with_capacity(len.next_multiple_of(block_size));
+    image.extend_from_slice(IMAGE_MAGIC);
+    image.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    image.extend_from_slice(&CURRENT_VERSION.to_le_bytes());
+    image.resize(HEADER_SIZE, 0);
+    let checksum = crc32(&image);
+    image[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4].copy_from_slice(&checksum.to_le_bytes());
+    for entry in entries {
+        let file_type = if entry.is_dir {
+            FILE_TYPE_DIR
+        } else {
+            FILE_TYPE_FILE
+        };
+        for field in [
+            ENTRY_MAGIC,
+            entry.path.len() as u32,
+            entry.data.len() as u32,
+            file_type,
+            entry.mode,
+        ] {
+            image.extend_from_slice(&field.to_le_bytes());
+        }
+        image.resize(image.len() + 4, 0);
+        let times = [entry.atime, entry.mtime, entry.ctime];
+        for time in &times {
+            image.extend_from_slice(&time.tv_sec.to_le_bytes());
+        }
+        for time in &times {
+            image.extend_from_slice(&(time.tv_nsec as u32).to_le_bytes());
+        }
+        image.resize(image.len() + 4, 0);
+        image.extend_from_slice(entry.path.as_bytes());
+        image.resize(image.len().next_multiple_of(NAME_ALIGN), 0);
+        image.extend_from_slice(&entry.data);
+        image.resize(image.len().next_multiple_of(DATA_ALIGN), 0);
+    }
+    image.resize(image.len().next_multiple_of(block_size), 0);
+
+    for (block, chunk) in image.chunks(block_size).enumerate().skip(1) {
+        if !device.write_block(block, chunk) {
+            return Err(FsError::IoError);
+        }
+    }
+    if !device.write_block(0, &image[..block_size]) || !device.flush() {
+        return Err(FsError::IoError);
+    }
+    Ok(())
+}
+
+/// 在设备上创建空的 SimpleFS，与用户态 `mkfs_simplefs` 写出的镜像相同
+#[cfg(test)]
+pub fn format(device: &dyn BlockDriver) -> Result<(), FsError> {
+    write_image(device, &[])
+}
+
+/// 一致性检查的结果
+#[derive(Debug, Default)]
+pub struct FsckReport {
+    /// 镜像格式版本
+    pub version: u32,
+    pub files: usize,
+    pub dirs: usize,
+    /// 文件数据总字节数
+    pub bytes: usize,
+    /// 镜像实际占用的字节数（到最后一个可解析条目为止）
+    pub image_len: usize,
+    /// 发现的问题，每项一行
+    pub errors: Vec<String>,
+}
+
+impl FsckReport {
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// 检查设备上的 SimpleFS（fsck）
+///
+/// 镜像头无法读取时返回 `IoError`，头部魔数、版本或校验和错误返回 `InvalidArgument`；
+/// 其余问题记录在报告中。条目结构损坏（魔数、长度、越界）后无法定位后续条目，检查就此停止。
+pub fn check(device: &dyn BlockDriver) -> Result<FsckReport, FsError> {
+    let header = parse_header(&read_block0(device)?).ok_or(FsError::InvalidArgument)?;
+    let file_count = header.file_count;
+
+    let mut report = FsckReport {
+        version: header.version,
+        image_len: header.size(),
+        ..FsckReport::default()
+    };
+    // 路径 -> 是否为目录
+    let mut seen: BTreeMap<String, bool> = BTreeMap::new();
+    let mut offset = header.size();
+
+    for index in 0..file_count as usize {
+        let (raw, next) = match read_entry(device, &header, offset) {
+            Ok(entry) => entry,
+            Err(_) => {
+                report.errors.push(format!(
+                    "entry {}/{} at offset {}: bad header or extends past end of device",
+                    index, file_count, offset
+                ));
+                break;
+            }
+        };
+        let entry_offset = offset;
+        offset = next;
+        report.image_len = next;
+
+        let Ok(path) = String::from_utf8(raw.name) else {
+            report.errors.push(format!(
+                "entry {} at offset {}: name is not UTF-8",
+                index, entry_offset
+            ));
+            continue;
+        };
+        let components: Vec<&str> = path.split('/').collect();
+        if components
+            .iter()
+            .any(|c| c.is_empty() || *c == "." || *c == "..")
+        {
+            report
+                .errors
+                .push(format!("entry {}: invalid path '{}'", index, path));
+            continue;
+        }
+        if components.iter().any(|c| c.len() > NAME_MAX) {
+            report.errors.push(format!(
+                "entry {}: name longer than {} bytes in '{}'",
+                index, NAME_MAX, path
+            ));
+            continue;
+        }
+        let is_dir = match raw.file_type {
+            FILE_TYPE_FILE => false,
+            FILE_TYPE_DIR => true,
+            other => {
+                report.errors.push(format!(
+                    "entry {}: '{}' has unknown type {}",
+                    index, path, other
+                ));
+                continue;
+            }
+        };
+        if is_dir && !raw.data.is_empty() {
+            report
+                .errors
+                .push(format!("entry {}: directory '{}' has data", index, path));
+        }
+        if seen.contains_key(&path) {
+            report
+                .errors
+                .push(format!("entry {}: duplicate path '{}'", index, path));
+            continue;
+        }
+        for depth in 1..components.len() {
+            let parent = components[..depth].join("/");
+            if seen.get(&parent) == Some(&false) {
+                report.errors.push(format!(
+                    "entry {}: parent '{}' of '{}' is a regular file",
+                    index, parent, path
+                ));
+                break;
+            }
+        }
+
+        seen.insert(path, is_dir);
+        if is_dir {
+            report.dirs += 1;
+        } else {
+            report.files += 1;
+            report.bytes += raw.data.len();
+        }
+    }
+
+    Ok(report)
+}
+
+/// 解析出的原始文件头
+struct RawEntry {
+    name: Vec<u8>,
+    file_type: u32,
+    mode: u32,
+    data: Vec<u8>,
+    /// atime、mtime、ctime
+    times: [TimeSpec; 3],
+}
+
+/// 解析出的镜像头
+struct Header {
+    version: u32,
+    file_count: u32,
+}
+
+impl Header {
+    /// 镜像头长度
+    fn size(&self) -> usize {
+        match self.version {
+            VERSION_1 => HEADER_SIZE_V1,
+            _ => HEADER_SIZE,
+        }
+    }
+
+    /// 文件头长度
+    fn entry_header_size(&self) -> usize {
+        match self.version {
+            VERSION_1 => ENTRY_HEADER_SIZE_V1,
+            _ => ENTRY_HEADER_SIZE,
+        }
+    }
+}
+
+fn read_block0(device: &dyn BlockDriver) -> Result<Vec<u8>, FsError> {
+    let mut block = vec![0u8; device.block_size()];
+    if !device.read_block(0, &mut block) {
+        return Err(FsError::IoError);
+    }
+    Ok(block)
+}
+
+/// 校验镜像头
+///
+/// 第 1 版的版本字段是保留的 0。第 2 版要求校验和匹配。
+fn parse_header(block: &[u8]) -> Option<Header> {
+    if block.len() < HEADER_SIZE || &block[0..8] != IMAGE_MAGIC {
+        return None;
+    }
+    let le32 = |offset: usize| u32::from_le_bytes(block[offset..offset + 4].try_into().unwrap());
+    let version = match le32(VERSION_OFFSET) {
+        0 => VERSION_1,
+        VERSION_2 => {
+            let mut header = [0u8; HEADER_SIZE];
+            header.copy_from_slice(&block[..HEADER_SIZE]);
+            header[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4].fill(0);
+            if crc32(&header) != le32(CHECKSUM_OFFSET) {
+                return None;
+            }
+            VERSION_2
+        }
+        _ => return None,
+    };
+    Some(Header {
+        version,
+        file_count: le32(8),
+    })
+}
+
+/// 解析 `offset` 处的条目，返回条目和下一个条目的偏移
+fn read_entry(
+    device: &dyn BlockDriver,
+    image: &Header,
+    offset: usize,
+) -> Result<(RawEntry, usize), FsError> {
+    let entry_header_size = image.entry_header_size();
+    let mut header = [0u8; ENTRY_HEADER_SIZE];
+    let header = &mut header[..entry_header_size];
+    read_at(device, offset, header)?;
+    let field = |i: usize| u32::from_le_bytes(header[i * 4..i * 4 + 4].try_into().unwrap());
+    if field(0) != ENTRY_MAGIC {
+        return Err(FsError::IoError);
+    }
+    let name_len = field(1) as usize;
+    let data_len = field(2) as usize;
+    if name_len == 0 || name_len > MAX_PATH_LEN {
+        return Err(FsError::IoError);
+    }
+
+    let name_offset = offset + entry_header_size;
+    let data_offset = name_offset + name_len.next_multiple_of(NAME_ALIGN);
+    let next = data_offset + data_len.next_multiple_of(DATA_ALIGN);
+    // 先检查边界，避免为损坏的长度字段分配巨大的缓冲区
+    if next > capacity(device) {
+        return Err(FsError::IoError);
+    }
+
+    let mut name = vec![0u8; name_len];
+    read_at(device, name_offset, &mut name)?;
+    let mut data = vec![0u8; data_len];
+    read_at(device, data_offset, &mut data)?;
+
+    let mut times = [TimeSpec::zero(); 3];
+    if image.version >= VERSION_2 {
+        for (i, time) in times.iter_mut().enumerate() {
+            let sec = i64::from_le_bytes(header[24 + i * 8..32 + i * 8].try_into().unwrap());
+            time.tv_sec = sec as _;
+            time.tv_nsec = field(12 + i) as _;
+        }
+    }
+
+    Ok((
+        RawEntry {
+            name,
+            file_type: field(3),
+            mode: field(4),
+            data,
+            times,
+        },
+        next,
+    ))
+}
+
+/// 从设备的任意偏移位置读取数据（支持跨块读取）
+fn read_at(device: &dyn BlockDriver, offset: usize, buf: &mut [u8]) -> Result<(), FsError> {
+    let block_size = device.block_size();
+    let mut block_buf = vec![0u8; block_size];
+    let mut buf_offset = 0;
+
+    while buf_offset < buf.len() {
+        let current_offset = offset + buf_offset;
+        let block_offset = current_offset % block_size;
+        if !device.read_block(current_offset / block_size, &mut block_buf) {
+            return Err(FsError::IoError);
+        }
+
+        let copy_len = (block_size - block_offset).min(buf.len() - buf_offset);
+        buf[buf_offset..buf_offset + copy_len]
+            .copy_from_slice(&block_buf[block_offset..block_offset + copy_len]);
+        buf_offset += copy_len;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,680 @@
+//! SimpleFS - 简单测试文件系统
+//!
+//! 该模块提供了一个**轻量级的内存文件系统**，用于测试和调试。镜像可以在编译时嵌入内核，
+//! 也可以在运行时由 `mkfs_simplefs` 写到任意块设备上再挂载。
+//!
+//! # 设计概览
+//!
+//! ## 镜像格式
+//!
+//! ```text
+//! +------------------+
+//! | Header (32B)     |  Magic: "RAMDISK\0", File count, Version, Checksum
+//! +------------------+
+//! | File Entry 1     |  Header (64B) + Name + Data
+//! +------------------+
+//! | File Entry 2     |
+//! | ...              |
+//! +------------------+
+//! ```
+//!
+//! 详细布局见 [`image`]。第 1 版镜像（编译时由 `build.rs` 生成）仍可读取，写回时升级为第 2 版。
+//!
+//! ## 加载流程
+//!
+//! 1. 编译时 `build.rs` 生成镜像并嵌入，或运行时用 `mkfs_simplefs` 格式化块设备
+//! 2. [`SimpleFs::from_ramdisk`] 只加载到内存；[`SimpleFs::open`]（`mount -t simplefs`）
+//!    先做一致性检查，之后 `sync`/卸载时把整个目录树重新写回设备
+//! 3. 解析镜像构建目录树
+//!
+//! # 组件
+//!
+//! - [`SimpleFs`] - 文件系统结构，实现 `FileSystem` trait
+//! - `SimpleFsInode` - 内部 Inode 实现
+//! - [`image`] - 镜像读写、mkfs 与 fsck
+//!
+//! # 使用示例
+//!
+//! ```rust
+//! use crate::fs::init_simple_fs;
+//!
+//! // 从编译时嵌入的镜像加载
+//! init_simple_fs()?;
+//!
+//! // 读取预加载的文件
+//! let hello = vfs_load_file("/bin/hello")?;
+//! ```
+//!
+//! # 特点
+//!
+//! - **整体回写**：修改只在内存中进行，`sync` 时重写整个镜像；不支持符号链接和硬链接
+//! - **时间戳**：记录 atime/mtime/ctime 并随镜像保存，第 1 版镜像中的条目时间戳为 0
+//! - **快速启动**：无需磁盘 I/O
+//! - **测试友好**：提供一致的测试环境
+//! - **自动路径创建**：支持多级路径（如 `bin/hello`），文件名最长 255 字节
+
+pub mod image;
+
+use crate::sync::{Mutex, SpinLock};
+use crate::uapi::fs::FileSystemType;
+use crate::vfs::timestamps::atime_needs_update;
+use crate::vfs::*;
+use crate::{device::block::BlockDriver, uapi::time::TimeSpec};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use image::{ImageEntry, NAME_MAX};
+
+/// 简单的内存文件系统（用于测试）
+pub struct SimpleFs {
+    device: Option<Arc<dyn BlockDriver>>, // 回写的块设备，None 表示纯内存
+    root: Arc<SimpleFsInode>,
+    /// 串行化回写，避免两次 sync 的块交错
+    sync_lock: Mutex<()>,
+}
+
+impl SimpleFs {
+    /// 创建新的简单文件系统
+    pub fn new() -> Arc<Self> {
+        let root = Arc::new(SimpleFsInode::new_dir(1, Self::default_dir_mode()));
+        Arc::new(Self {
+            device: None,
+            root,
+            sync_lock: Mutex::new(()),
+        })
+    }
+}
+
+impl FileSystem for SimpleFs {
+    fn fs_type(&self) -> &'static str {
+        "simplefs"
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone() as Arc<dyn Inode>
+    }
+
+    fn sync(&self) -> Result<(), FsError> {
+        let Some(device) = &self.device else {
+            return Ok(()); // 内存文件系统无需同步
+        };
+        let _guard = self.sync_lock.lock();
+        image::write_image(device.as_ref(), &self.entries())
+    }
+
+    fn statfs(&self) -> Result<StatFs, FsError> {
+        let Some(device) = &self.device else {
+            return Ok(StatFs {
+                fs_type: FileSystemType::Ramfs,
+                block_size: 4096,
+                total_blocks: 0,
+                free_blocks: 0,
+                available_blocks: 0,
+                total_inodes: 0,
+                free_inodes: 0,
+                fsid: 0,
+                max_filename_len: NAME_MAX,
+            });
+        };
+        // 以 512 字节为单位统计镜像占用
+        let total_blocks = image::capacity(device.as_ref()) / 512;
+        let used_blocks = image::image_len(&self.entries()).div_ceil(512);
+        let free_blocks = total_blocks.saturating_sub(used_blocks);
+        Ok(StatFs {
+            fs_type: FileSystemType::Ramfs,
+            block_size: 512,
+            total_blocks,
+            free_blocks,
+            available_blocks: free_blocks,
+            total_inodes: 0,
+            free_inodes: 0,
+            fsid: 0,
+            max_filename_len: NAME_MAX,
+        })
+    }
+}
+
+impl SimpleFs {
+    /// 把镜像加载到内存，之后的修改不回写设备
+    ///
+    /// 用于编译时嵌入的只读镜像。
+    pub fn from_ramdisk(device: Arc<dyn BlockDriver>) -> Result<Self, FsError> {
+        let root = Self::load(device.as_ref())?;
+        Ok(Self {
+            device: None,
+            root,
+            sync_lock: Mutex::new(()),
+        })
+    }
+
+    /// 打开块设备上的 SimpleFS，`sync` 时把目录树写回设备
+    ///
+    /// 挂载前先做一致性检查，镜像损坏时返回 `InvalidArgument`。
+    pub fn open(device: Arc<dyn BlockDriver>) -> Result<Arc<Self>, FsError> {
+        let report = image::check(device.as_ref())?;
+        if !report.is_clean() {
+            for error in &report.errors {
+                crate::pr_warn!("[SimpleFS] {}", error);
+            }
+            return Err(FsError::InvalidArgument);
+        }
+        if report.version < image::CURRENT_VERSION {
+            crate::pr_info!(
+                "[SimpleFS] version {} image, will be upgraded to version {} on sync",
+                report.version,
+                image::CURRENT_VERSION
+            );
+        }
+        let root = Self::load(device.as_ref())?;
+        Ok(Arc::new(Self {
+            device: Some(device),
+            root,
+            sync_lock: Mutex::new(()),
+        }))
+    }
+
+    /// 解析镜像，构建目录树
+    fn load(device: &dyn BlockDriver) -> Result<Arc<SimpleFsInode>, FsError> {
+        let root = Arc::new(SimpleFsInode::new_dir(1, Self::default_dir_mode()));
+        for entry in image::read_image(device)? {
+            let mode = FileMode::from_bits_truncate(entry.mode);
+            let inode = if entry.is_dir {
+                SimpleFsInode::new_dir(root.next_inode_no(), mode)
+            } else {
+                let inode = SimpleFsInode::new_file(root.next_inode_no(), mode);
+                *inode.data.lock() = entry.data;
+                inode
+            };
+            *inode.times.lock() = Timestamps {
+                atime: entry.atime,
+                mtime: entry.mtime,
+                ctime: entry.ctime,
+            };
+            // 处理多级路径 (如 "bin/hello")
+            Self::insert_inode_by_path(&entry.path, Arc::new(inode), root.clone())?;
+        }
+        Ok(root)
+    }
+
+    /// 按先序遍历收集目录树，目录总在其子项之前
+    fn collect_entries(dir: &SimpleFsInode, prefix: &str, entries: &mut Vec<ImageEntry>) {
+        let children: Vec<(String, Arc<SimpleFsInode>)> = dir
+            .children
+            .lock()
+            .iter()
+            .map(|(name, child)| (name.clone(), child.clone()))
+            .collect();
+        for (name, child) in children {
+            let path = if prefix.is_empty() {
+                name
+            } else {
+                format!("{}/{}", prefix, name)
+            };
+            let is_dir = child.inode_type == InodeType::Directory;
+            let times = *child.times.lock();
+            entries.push(ImageEntry {
+                path: path.clone(),
+                is_dir,
+                mode: child.mode.lock().bits() & 0o7777,
+                data: child.data.lock().clone(),
+                atime: times.atime,
+                mtime: times.mtime,
+                ctime: times.ctime,
+            });
+            if is_dir {
+                Self::collect_entries(&child, &path, entries);
+            }
+        }
+    }
+
+    /// 当前目录树的镜像
+    fn entries(&self) -> Vec<ImageEntry> {
+        let mut entries = Vec::new();
+        Self::collect_entries(&self.root, "", &mut entries);
+        entries
+    }
+
+    /// 0o755 = rwxr-xr-x
+    fn default_dir_mode() -> FileMode {
+        FileMode::S_IRUSR
+            | FileMode::S_IWUSR
+            | FileMode::S_IXUSR
+            | FileMode::S_IRGRP
+            | FileMode::S_IXGRP
+            | FileMode::S_IROTH
+            | FileMode::S_IXOTH
+    }
+
+    /// 按路径插入 inode，自动创建不存在的中间目录
+    fn insert_inode_by_path(
+        path: &str,
+        inode: Arc<SimpleFsInode>,
+        root: Arc<SimpleFsInode>,
+    ) -> Result<(), FsError> {
+        let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        if parts.is_empty() {
+            return Err(FsError::InvalidArgument);
+        }
+
+        let mut current = root;
+
+        // 遍历到倒数第二级，确保父目录存在
+        for part in &parts[..parts.len() - 1] {
+            let child = {
+                let children = current.children.lock();
+                children.get(*part).cloned()
+            };
+
+            if let Some(child) = child {
+                current = child;
+            } else {
+                // 创建中间目录 (0o755 = rwxr-xr-x)
+                let new_dir = Arc::new(SimpleFsInode::new_dir(
+                    current.next_inode_no(),
+                    FileMode::S_IRUSR
+                        | FileMode::S_IWUSR
+                        | FileMode::S_IXUSR
+                        | FileMode::S_IRGRP
+                        | FileMode::S_IXGRP
+                        | FileMode::S_IROTH
+                        | FileMode::S_IXOTH,
+                ));
+                current
+                    .children
+                    .lock()
+                    .insert(String::from(*part), new_dir.clone());
+                current = new_dir;
+            }
+        }
+
+        // 插入最终的文件/目录
+        let final_name = parts[parts.len() - 1];
+        current
+            .children
+            .lock()
+            .insert(String::from(final_name), inode);
+
+        Ok(())
+    }
+}
+
+/// 检查文件名长度
+fn check_name(name: &str) -> Result<(), FsError> {
+    if name.len() > NAME_MAX {
+        return Err(FsError::NameTooLong);
+    }
+    Ok(())
+}
+
+/// inode 的时间戳
+#[derive(Clone, Copy)]
+struct Timestamps {
+    atime: TimeSpec,
+    mtime: TimeSpec,
+    ctime: TimeSpec,
+}
+
+impl Timestamps {
+    fn now() -> Self {
+        let now = TimeSpec::now();
+        Self {
+            atime: now,
+            mtime: now,
+            ctime: now,
+        }
+    }
+}
+
+/// 简单文件系统的 Inode
+struct SimpleFsInode {
+    inode_no: usize,
+    inode_type: InodeType,
+    mode: SpinLock<FileMode>,
+    data: SpinLock<Vec<u8>>,
+    children: SpinLock<BTreeMap<String, Arc<SimpleFsInode>>>,
+    times: SpinLock<Timestamps>,
+}
+
+impl SimpleFsInode {
+    /// 创建文件 inode（带权限）
+    fn new_file(inode_no: u64, mode: FileMode) -> Self {
+        let file_mode = FileMode::S_IFREG | mode;
+
+        Self {
+            inode_no: inode_no as usize,
+            inode_type: InodeType::File,
+            mode: SpinLock::new(file_mode),
+            data: SpinLock::new(Vec::new()),
+            children: SpinLock::new(BTreeMap::new()),
+            times: SpinLock::new(Timestamps::now()),
+        }
+    }
+
+    /// 创建目录 inode（带权限）
+    fn new_dir(inode_no: u64, mode: FileMode) -> Self {
+        let dir_mode = FileMode::S_IFDIR | mode;
+
+        Self {
+            inode_no: inode_no as usize,
+            inode_type: InodeType::Directory,
+            mode: SpinLock::new(dir_mode),
+            data: SpinLock::new(Vec::new()),
+            children: SpinLock::new(BTreeMap::new()),
+            times: SpinLock::new(Timestamps::now()),
+        }
+    }
+
+    /// 获取下一个 inode 编号
+    fn next_inode_no(&self) -> u64 {
+        static NEXT_INODE: AtomicU64 = AtomicU64::new(2);
+        NEXT_INODE.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// 内容被修改：更新 mtime 与 ctime
+    fn touch_modified(&self) {
+        let now = TimeSpec::now();
+        let mut times = self.times.lock();
+        times.mtime = now;
+        times.ctime = now;
+    }
+
+    /// `target` 是否是自身或位于自身的子树中
+    fn contains(&self, target: &SimpleFsInode) -> bool {
+        if core::ptr::eq(self, target) {
+            return true;
+        }
+        let children: Vec<Arc<SimpleFsInode>> = self.children.lock().values().cloned().collect();
+        children
+            .iter()
+            .any(|child| child.inode_type == InodeType::Directory && child.contains(target))
+    }
+}
+
+impl Inode for SimpleFsInode {
+    fn metadata(&self) -> Result<InodeMetadata, FsError> {
+        let data = self.data.lock();
+        let times = *self.times.lock();
+        Ok(InodeMetadata {
+            inode_no: self.inode_no,
+            inode_type: self.inode_type,
+            mode: *self.mode.lock(),
+            uid: 0,
+            gid: 0,
+            size: data.len(),
+            atime: times.atime,
+            mtime: times.mtime,
+            ctime: times.ctime,
+            nlinks: 1,
+            blocks: data.len().div_ceil(512),
+            rdev: 0,
+        })
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, FsError> {
+        if self.inode_type == InodeType::Directory {
+            return Err(FsError::IsDirectory);
+        }
+
+        let data = self.data.lock();
+        if offset >= data.len() {
+            return Ok(0);
+        }
+        let len = core::cmp::min(buf.len(), data.len() - offset);
+
+        buf[..len].copy_from_slice(&data[offset..offset + len]);
+        Ok(len)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, FsError> {
+        if self.inode_type == InodeType::Directory {
+            return Err(FsError::IsDirectory);
+        }
+
+        let mut data = self.data.lock();
+        if offset + buf.len() > data.len() {
+            data.resize(offset + buf.len(), 0);
+        }
+        data[offset..offset + buf.len()].copy_from_slice(buf);
+        drop(data);
+        self.touch_modified();
+        Ok(buf.len())
+    }
+
+    fn append(&self, buf: &[u8]) -> Result<(usize, usize), FsError> {
+        if self.inode_type == InodeType::Directory {
+            return Err(FsError::IsDirectory);
+        }
+
+        // 文件大小就是数据长度，在同一把锁内取得写入位置
+        let mut data = self.data.lock();
+        let offset = data.len();
+        data.extend_from_slice(buf);
+        drop(data);
+        self.touch_modified();
+        Ok((offset, buf.len()))
+    }
+
+    fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>, FsError> {
+        if self.inode_type != InodeType::Directory {
+            return Err(FsError::NotDirectory);
+        }
+        self.children
+            .lock()
+            .get(name)
+            .cloned()
+            .ok_or(FsError::NotFound)
+            .map(|inode| inode as Arc<dyn Inode>)
+    }
+
+    fn create(&self, name: &str, mode: FileMode) -> Result<Arc<dyn Inode>, FsError> {
+        if self.inode_type != InodeType::Directory {
+            return Err(FsError::NotDirectory);
+        }
+        check_name(name)?;
+
+        let mut children = self.children.lock();
+        if children.contains_key(name) {
+            return Err(FsError::AlreadyExists);
+        }
+
+        let new_inode = Arc::new(SimpleFsInode::new_file((children.len() + 2) as u64, mode));
+        children.insert(String::from(name), new_inode.clone());
+        drop(children);
+        self.touch_modified();
+
+        Ok(new_inode as Arc<dyn Inode>)
+    }
+
+    fn mkdir(&self, name: &str, mode: FileMode) -> Result<Arc<dyn Inode>, FsError> {
+        if self.inode_type != InodeType::Directory {
+            return Err(FsError::NotDirectory);
+        }
+        check_name(name)?;
+
+        let mut children = self.children.lock();
+        if children.contains_key(name) {
+            return Err(FsError::AlreadyExists);
+        }
+
+        let new_inode = Arc::new(SimpleFsInode::new_dir((children.len() + 2) as u64, mode));
+        children.insert(String::from(name), new_inode.clone());
+        drop(children);
+        self.touch_modified();
+
+        Ok(new_inode as Arc<dyn Inode>)
+    }
+
+    fn unlink(&self, name: &str) -> Result<(), FsError> {
+        if self.inode_type != InodeType::Directory {
+            return Err(FsError::NotDirectory);
+        }
+
+        let mut children = self.children.lock();
+        children.remove(name).ok_or(FsError::NotFound)?;
+        drop(children);
+        self.touch_modified();
+        Ok(())
+    }
+
+    fn readdir(&self) -> Result<Vec<DirEntry>, FsError> {
+        if self.inode_type != InodeType::Directory {
+            return Err(FsError::NotDirectory);
+        }
+
+        let children = self.children.lock();
+        let mut entries = Vec::new();
+
+        for (name, child) in children.iter() {
+            entries.push(DirEntry {
+                name: name.clone(),
+                inode_no: child.inode_no,
+                inode_type: child.inode_type,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    fn truncate(&self, size: usize) -> Result<(), FsError> {
+        self.data.lock().resize(size, 0);
+        self.touch_modified();
+        Ok(())
+    }
+
+    fn sync(&self) -> Result<(), FsError> {
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self as &dyn core::any::Any
+    }
+
+    fn symlink(&self, _name: &str, _target: &str) -> Result<Arc<dyn Inode>, FsError> {
+        Err(FsError::NotSupported)
+    }
+
+    fn link(&self, _name: &str, _target: &Arc<dyn Inode>) -> Result<(), FsError> {
+        Err(FsError::NotSupported)
+    }
+
+    fn rmdir(&self, name: &str) -> Result<(), FsError> {
+        if self.inode_type != InodeType::Directory {
+            return Err(FsError::NotDirectory);
+        }
+
+        let mut children = self.children.lock();
+        let child = children.get(name).ok_or(FsError::NotFound)?;
+        if child.inode_type != InodeType::Directory {
+            return Err(FsError::NotDirectory);
+        }
+        if !child.children.lock().is_empty() {
+            return Err(FsError::DirectoryNotEmpty);
+        }
+        children.remove(name);
+        drop(children);
+        self.touch_modified();
+        Ok(())
+    }
+
+    fn rename(
+        &self,
+        old_name: &str,
+        new_parent: Arc<dyn Inode>,
+        new_name: &str,
+    ) -> Result<(), FsError> {
+        if self.inode_type != InodeType::Directory {
+            return Err(FsError::NotDirectory);
+        }
+        let new_parent = new_parent
+            .as_any()
+            .downcast_ref::<SimpleFsInode>()
+            .ok_or(FsError::CrossDeviceLink)?;
+        if new_parent.inode_type != InodeType::Directory {
+            return Err(FsError::NotDirectory);
+        }
+        check_name(new_name)?;
+
+        let inode = self
+            .children
+            .lock()
+            .get(old_name)
+            .cloned()
+            .ok_or(FsError::NotFound)?;
+        let same_parent = core::ptr::eq(self, new_parent);
+        if same_parent && old_name == new_name {
+            return Ok(());
+        }
+        // 目录不能移动到自己的子树中
+        if inode.inode_type == InodeType::Directory && inode.contains(new_parent) {
+            return Err(FsError::InvalidArgument);
+        }
+
+        // 先检查目标，再摘下源项：两个目录的锁不同时持有
+        if let Some(existing) = new_parent.children.lock().get(new_name) {
+            match (inode.inode_type, existing.inode_type) {
+                (InodeType::Directory, InodeType::Directory) => {
+                    if !existing.children.lock().is_empty() {
+                        return Err(FsError::DirectoryNotEmpty);
+                    }
+                }
+                (InodeType::Directory, _) => return Err(FsError::NotDirectory),
+                (_, InodeType::Directory) => return Err(FsError::IsDirectory),
+                _ => {}
+            }
+        }
+        self.children.lock().remove(old_name);
+        inode.times.lock().ctime = TimeSpec::now();
+        new_parent
+            .children
+            .lock()
+            .insert(String::from(new_name), inode);
+        self.touch_modified();
+        if !same_parent {
+            new_parent.touch_modified();
+        }
+        Ok(())
+    }
+
+    fn set_times(&self, atime: Option<TimeSpec>, mtime: Option<TimeSpec>) -> Result<(), FsError> {
+        let mut times = self.times.lock();
+        if let Some(atime) = atime {
+            times.atime = atime;
+        }
+        if let Some(mtime) = mtime {
+            times.mtime = mtime;
+        }
+        times.ctime = TimeSpec::now();
+        Ok(())
+    }
+
+    fn touch_atime(&self) {
+        let mut times = self.times.lock();
+        let now = TimeSpec::now();
+        if atime_needs_update(times.atime, times.mtime, times.ctime, now) {
+            times.atime = now;
+        }
+    }
+
+    fn readlink(&self) -> Result<String, FsError> {
+        Err(FsError::NotSupported)
+    }
+
+    fn mknod(&self, _name: &str, _mode: FileMode, _dev: u64) -> Result<Arc<dyn Inode>, FsError> {
+        Err(FsError::NotSupported)
+    }
+
+    fn chmod(&self, mode: FileMode) -> Result<(), FsError> {
+        let mut current = self.mode.lock();
+        *current = (*current & FileMode::S_IFMT) | (mode & !FileMode::S_IFMT);
+        self.times.lock().ctime = TimeSpec::now();
+        Ok(())
+    }
+
+    fn chown(&self, _uid: u32, _gid: u32) -> Result<(), FsError> {
+        Err(FsError::NotSupported)
+    }
+}
@@ -6,8 +6,11 @@ use alloc::format;
 use alloc::string::ToString;
 use alloc::sync::Arc;
 
+use crate::device::block::MAX_HW_SECTORS_KB;
+use crate::device::net::net_device::{ETH_MIN_MTU, NetDevice};
 use crate::fs::sysfs::device_registry;
-use crate::fs::sysfs::inode::{SysfsAttr, SysfsInode};
+use crate::fs::sysfs::inode::{SysfsAttr, SysfsInode, parse_in_range};
+use crate::net::interface::{InterfaceStatsSnapshot, NETWORK_INTERFACE_MANAGER, OperState};
 use crate::vfs::{FileMode, FsError, Inode};
 
 /// 构建 /sys/devices/ 层次结构
@@ -186,18 +189,42 @@ fn build_queue_directory(
         SysfsInode::new_attribute(hw_sector_size_attr),
     )?;
 
-    // max_sectors_kb
-    let max_sectors_kb_attr = SysfsAttr {
-        name: "max_sectors_kb".to_string(),
-        mode: FileMode::from_bits_truncate(0o644),
-        show: Arc::new(|| Ok("1280\n".to_string())), // 默认值
-        store: None,
-    };
+    // max_sectors_kb: 合并后单个请求的长度上限，至少一页
+    let max_sectors_kb_attr = SysfsAttr::writable(
+        "max_sectors_kb",
+        {
+            let dev = device.clone();
+            move || Ok(format!("{}\n", dev.max_sectors_kb()))
+        },
+        |value| parse_in_range(value, crate::config::PAGE_SIZE / 1024, MAX_HW_SECTORS_KB),
+        {
+            let dev = device.clone();
+            move |kb| {
+                if dev.set_max_sectors_kb(kb) {
+                    Ok(())
+                } else {
+                    Err(FsError::NotSupported)
+                }
+            }
+        },
+    );
     queue_dir.add_child(
         "max_sectors_kb",
         SysfsInode::new_attribute(max_sectors_kb_attr),
     )?;
 
+    // max_hw_sectors_kb: max_sectors_kb 可设置的上限
+    let max_hw_sectors_kb_attr = SysfsAttr {
+        name: "max_hw_sectors_kb".to_string(),
+        mode: FileMode::from_bits_truncate(0o444),
+        show: Arc::new(|| Ok(format!("{}\n", MAX_HW_SECTORS_KB))),
+        store: None,
+    };
+    queue_dir.add_child(
+        "max_hw_sectors_kb",
+        SysfsInode::new_attribute(max_hw_sectors_kb_attr),
+    )?;
+
     // rotational (0 = SSD, 1 = HDD)
     let rotational_attr = SysfsAttr {
         name: "rotational".to_string(),
@@ -207,6 +234,42 @@ fn build_queue_directory(
     };
     queue_dir.add_child("rotational", SysfsInode::new_attribute(rotational_attr))?;
 
+    // max_segments: 单个请求最多包含的段数
+    let max_segments_attr = SysfsAttr {
+        name: "max_segments".to_string(),
+        mode: FileMode::from_bits_truncate(0o444),
+        show: {
+            let dev = device.clone();
+            Arc::new(move || Ok(format!("{}\n", dev.max_segments())))
+        },
+        store: None,
+    };
+    queue_dir.add_child("max_segments", SysfsInode::new_attribute(max_segments_attr))?;
+
+    // vring_kicks: 提交给设备的请求数
+    let vring_kicks_attr = SysfsAttr {
+        name: "vring_kicks".to_string(),
+        mode: FileMode::from_bits_truncate(0o444),
+        show: {
+            let dev = device.clone();
+            Arc::new(move || Ok(format!("{}\n", dev.queue_stats().vring_kicks)))
+        },
+        store: None,
+    };
+    queue_dir.add_child("vring_kicks", SysfsInode::new_attribute(vring_kicks_attr))?;
+
+    // merged: 被合并进相邻请求的请求数
+    let merged_attr = SysfsAttr {
+        name: "merged".to_string(),
+        mode: FileMode::from_bits_truncate(0o444),
+        show: {
+            let dev = device.clone();
+            Arc::new(move || Ok(format!("{}\n", dev.queue_stats().merged)))
+        },
+        store: None,
+    };
+    queue_dir.add_child("merged", SysfsInode::new_attribute(merged_attr))?;
+
     dev_dir.add_child("queue", queue_dir)?;
     Ok(())
 }
@@ -248,25 +311,55 @@ fn build_platform_net_devices(platform_dir: &Arc<SysfsInode>) -> Result<(), FsEr
         };
         dev_dir.add_child("address", SysfsInode::new_attribute(address_attr))?;
 
-        // mtu 文件
-        let mtu_attr = SysfsAttr {
-            name: "mtu".to_string(),
-            mode: FileMode::from_bits_truncate(0o644),
-            show: {
+        // mtu 文件: 取值范围为 ETH_MIN_MTU 到设备支持的最大 MTU
+        let mtu_attr = SysfsAttr::writable(
+            "mtu",
+            {
                 let dev = dev_info.device.clone();
-                Arc::new(move || Ok(format!("{}\n", dev.mtu())))
+                move || Ok(format!("{}\n", dev.mtu()))
             },
-            store: None,
-        };
+            {
+                let dev = dev_info.device.clone();
+                move |value| parse_in_range(value, ETH_MIN_MTU, dev.max_mtu())
+            },
+            {
+                let dev = dev_info.device.clone();
+                move |mtu| dev.set_mtu(mtu).map_err(|_| FsError::InvalidArgument)
+            },
+        );
         dev_dir.add_child("mtu", SysfsInode::new_attribute(mtu_attr))?;
 
-        // operstate 文件
-        let operstate_attr = SysfsAttr {
-            name: "operstate".to_string(),
-            mode: FileMode::from_bits_truncate(0o444),
-            show: Arc::new(|| Ok("up\n".to_string())),
-            store: None,
-        };
+        // operstate 文件: 写入状态名覆盖运行状态，写入 auto 恢复为按 IFF_UP 推出
+        let operstate_attr = SysfsAttr::writable(
+            "operstate",
+            {
+                let dev = dev_info.device.clone();
+                move || {
+                    let state = NETWORK_INTERFACE_MANAGER
+                        .lock()
+                        .find_interface_by_device(&dev)
+                        .map_or(OperState::Unknown, |iface| iface.operstate());
+                    Ok(format!("{}\n", state.as_str()))
+                }
+            },
+            |value| match value {
+                "auto" => Ok(None),
+                _ => OperState::from_name(value)
+                    .map(Some)
+                    .ok_or(FsError::InvalidArgument),
+            },
+            {
+                let dev = dev_info.device.clone();
+                move |state| {
+                    NETWORK_INTERFACE_MANAGER
+                        .lock()
+                        .find_interface_by_device(&dev)
+                        .ok_or(FsError::NoDevice)?
+                        .set_operstate_override(state);
+                    Ok(())
+                }
+            },
+        );
         dev_dir.add_child("operstate", SysfsInode::new_attribute(operstate_attr))?;
 
         // carrier 文件: 物理链接状态
@@ -299,6 +392,9 @@ fn build_platform_net_devices(platform_dir: &Arc<SysfsInode>) -> Result<(), FsEr
         };
         dev_dir.add_child("type", SysfsInode::new_attribute(type_attr))?;
 
+        // statistics 目录: 收发计数
+        dev_dir.add_child("statistics", build_net_statistics(&dev_info.device)?)?;
+
         // 添加到 platform 目录
         platform_dir.add_child(&dev_info.name, dev_dir)?;
     }
@@ -306,6 +402,45 @@ fn build_platform_net_devices(platform_dir: &Arc<SysfsInode>) -> Result<(), FsEr
     Ok(())
 }
 
+/// 构建网络设备的 statistics 目录
+///
+/// 计数保存在设备所属的网络接口上，读取时按设备查找接口。
+fn build_net_statistics(device: &Arc<dyn NetDevice>) -> Result<Arc<SysfsInode>, FsError> {
+    type StatField = fn(&InterfaceStatsSnapshot) -> u64;
+    const FIELDS: [(&str, StatField); 8] = [
+        ("rx_bytes", |s| s.rx_bytes),
+        ("rx_packets", |s| s.rx_packets),
+        ("rx_errors", |s| s.rx_errors),
+        ("rx_dropped", |s| s.rx_dropped),
+        ("tx_bytes", |s| s.tx_bytes),
+        ("tx_packets", |s| s.tx_packets),
+        ("tx_errors", |s| s.tx_errors),
+        ("tx_dropped", |s| s.tx_dropped),
+    ];
+
+    let stats_dir = SysfsInode::new_directory(FileMode::from_bits_truncate(0o040000 | 0o555));
+    for (name, field) in FIELDS {
+        let attr = SysfsAttr {
+            name: name.to_string(),
+            mode: FileMode::from_bits_truncate(0o444),
+            show: {
+                let dev = device.clone();
+                Arc::new(move || {
+                    let snapshot = NETWORK_INTERFACE_MANAGER
+                        .lock()
+                        .find_interface_by_device(&dev)
+                        .map(|iface| iface.stats().snapshot())
+                        .unwrap_or_default();
+                    Ok(format!("{}\n", field(&snapshot)))
+                })
+            },
+            store: None,
+        };
+        stats_dir.add_child(name, SysfsInode::new_attribute(attr))?;
+    }
+    Ok(stats_dir)
+}
+
 /// 构建平台 TTY 设备
 fn build_platform_tty_devices(platform_dir: &Arc<SysfsInode>) -> Result<(), FsError> {
     for dev_info in device_registry::list_tty_devices() {
@@ -413,14 +548,14 @@ fn build_platform_rtc_devices(platform_dir: &Arc<SysfsInode>) -> Result<(), FsEr
         };
         dev_dir.add_child("name", SysfsInode::new_attribute(name_attr))?;
 
-        // date 文件: 读取当前 RTC 日期（北京时间）
+        // date 文件: 读取当前 RTC 日期（UTC 加显示偏移）
         let date_attr = SysfsAttr {
             name: "date".to_string(),
             mode: FileMode::from_bits_truncate(0o444),
             show: {
                 let rtc = dev_info.device.clone();
                 Arc::new(move || {
-                    let dt = rtc.read_datetime();
+                    let dt = rtc.read_display_datetime();
                     Ok(format!("{:04}-{:02}-{:02}\n", dt.year, dt.month, dt.day))
                 })
             },
@@ -428,14 +563,14 @@ fn build_platform_rtc_devices(platform_dir: &Arc<SysfsInode>) -> Result<(), FsEr
         };
         dev_dir.add_child("date", SysfsInode::new_attribute(date_attr))?;
 
-        // time 文件: 读取当前 RTC 时间（北京时间）
+        // time 文件: 读取当前 RTC 时间（UTC 加显示偏移）
         let time_attr = SysfsAttr {
             name: "time".to_string(),
             mode: FileMode::from_bits_truncate(0o444),
             show: {
                 let rtc = dev_info.device.clone();
                 Arc::new(move || {
-                    let dt = rtc.read_datetime();
+                    let dt = rtc.read_display_datetime();
                     Ok(format!(
                         "{:02}:{:02}:{:02}\n",
                         dt.hour, dt.minute, dt.second
@@ -3,7 +3,9 @@
 use alloc::string::ToString;
 use alloc::sync::Arc;
 
-use crate::fs::sysfs::inode::{SysfsAttr, SysfsInode};
+use crate::fs::sysfs::inode::{SysfsAttr, SysfsInode, parse_in_range};
+use crate::log::{self, LogLevel, pstore};
+use crate::mm::ksm;
 use crate::uapi::uts_namespace::{UTS_RELEASE, UTS_VERSION};
 use crate::vfs::{FileMode, FsError, Inode};
 
@@ -32,5 +34,114 @@ pub fn build_kernel_info(root: &Arc<SysfsInode>) -> Result<(), FsError> {
     };
     kernel_dir.add_child("osrelease", SysfsInode::new_attribute(osrelease_attr))?;
 
+    // /sys/kernel/loglevel: 控制台输出级别 0-7，数值越大输出越多
+    let loglevel_attr = SysfsAttr::writable(
+        "loglevel",
+        || Ok(alloc::format!("{}\n", log::get_console_level().to_u8())),
+        |value| parse_in_range(value, 0u8, LogLevel::Debug.to_u8()),
+        |level| {
+            log::set_console_level(LogLevel::from_u8(level));
+            Ok(())
+        },
+    );
+    kernel_dir.add_child("loglevel", SysfsInode::new_attribute(loglevel_attr))?;
+
+    build_ksm_info(kernel_dir)?;
+
+    Ok(())
+}
+
+/// 构建 /sys/kernel/mm/ksm
+fn build_ksm_info(kernel_dir: &SysfsInode) -> Result<(), FsError> {
+    let mm_dir = SysfsInode::new_directory(FileMode::from_bits_truncate(0o040000 | 0o555));
+    kernel_dir.add_child("mm", mm_dir.clone())?;
+    let ksm_dir = SysfsInode::new_directory(FileMode::from_bits_truncate(0o040000 | 0o555));
+    mm_dir.add_child("ksm", ksm_dir.clone())?;
+
+    // 可写的控制参数
+    let controls: [(&str, fn() -> usize, fn(usize) -> Result<(), FsError>); 3] = [
+        (
+            "run",
+            || ksm::is_running() as usize,
+            |v| match v {
+                0 | 1 => {
+                    ksm::set_running(v == 1);
+                    Ok(())
+                }
+                _ => Err(FsError::InvalidArgument),
+            },
+        ),
+        ("pages_to_scan", ksm::pages_to_scan, |v| {
+            ksm::set_pages_to_scan(v);
+            Ok(())
+        }),
+        ("sleep_millisecs", ksm::sleep_millisecs, |v| {
+            ksm::set_sleep_millisecs(v);
+            Ok(())
+        }),
+    ];
+    for (name, show, store) in controls {
+        let attr = SysfsAttr {
+            name: name.to_string(),
+            mode: FileMode::from_bits_truncate(0o644),
+            show: Arc::new(move || Ok(alloc::format!("{}\n", show()))),
+            store: Some(Arc::new(move |value: &str| {
+                let value = value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| FsError::InvalidArgument)?;
+                store(value)
+            })),
+        };
+        ksm_dir.add_child(name, SysfsInode::new_attribute(attr))?;
+    }
+
+    // 只读的统计信息
+    let counters: [(&str, fn(&ksm::KsmStats) -> usize); 4] = [
+        ("pages_shared", |s| s.pages_shared),
+        ("pages_sharing", |s| s.pages_sharing),
+        ("pages_unshared", |s| s.pages_unshared),
+        ("full_scans", |s| s.full_scans),
+    ];
+    for (name, field) in counters {
+        let attr = SysfsAttr {
+            name: name.to_string(),
+            mode: FileMode::from_bits_truncate(0o444),
+            show: Arc::new(move || Ok(alloc::format!("{}\n", field(&ksm::stats())))),
+            store: None,
+        };
+        ksm_dir.add_child(name, SysfsInode::new_attribute(attr))?;
+    }
+
+    Ok(())
+}
+
+/// 构建 /sys/fs/pstore
+///
+/// 上一次启动留下了崩溃日志时创建 `dmesg-blk-0`，向其写入任意内容清除记录。
+pub fn build_pstore(root: &Arc<SysfsInode>) -> Result<(), FsError> {
+    if pstore::last_record().is_none() {
+        return Ok(());
+    }
+    let fs_inode = root.lookup("fs")?;
+    let pstore_inode = fs_inode.lookup("pstore")?;
+    let pstore_dir = pstore_inode
+        .downcast_ref::<SysfsInode>()
+        .ok_or(FsError::InvalidArgument)?;
+
+    let attr = SysfsAttr {
+        name: "dmesg-blk-0".to_string(),
+        mode: FileMode::from_bits_truncate(0o644),
+        show: Arc::new(|| {
+            let record = pstore::last_record().unwrap_or_default();
+            Ok(alloc::string::String::from_utf8_lossy(&record).into_owned())
+        }),
+        store: Some(Arc::new(|_value: &str| {
+            pstore::erase();
+            Ok(())
+        })),
+    };
+    pstore_dir.add_child("dmesg-blk-0", SysfsInode::new_attribute(attr))?;
+
     Ok(())
 }
@@ -9,6 +9,7 @@ use alloc::vec::Vec;
 
 use crate::device::block::BlockDriver;
 use crate::device::block::partition::{PartitionBlockDevice, discover_partitions};
+use crate::device::block::verity;
 use crate::device::net::net_device::NetDevice;
 use crate::device::rtc::RtcDriver;
 use crate::device::{BLK_DRIVERS, DRIVERS, DeviceType};
@@ -68,7 +69,7 @@ pub fn list_block_devices() -> Vec<BlockDeviceInfo> {
             name: disk_name.clone(),
             major: blkdev_major::VIRTIO_BLK,
             minor: disk_minor,
-            device: driver.clone(),
+            device: verity::wrap(&disk_name, driver.clone()),
         });
 
         for entry in discover_partitions(driver) {
@@ -84,10 +85,10 @@ pub fn list_block_devices() -> Vec<BlockDeviceInfo> {
             };
 
             devices.push(BlockDeviceInfo {
+                device: verity::wrap(&partition_name, device),
                 name: partition_name,
                 major: blkdev_major::VIRTIO_BLK,
                 minor: partition_minor,
-                device,
             });
         }
     }
@@ -4,6 +4,9 @@
 //! - 目录 (Directory)
 //! - 属性文件 (Attribute) - 动态生成内容
 //! - 符号链接 (Symlink)
+//!
+//! 属性文件只有注册了 `store` 才可写，否则创建时去掉所有写权限位。
+//! 读写属性、查找和列出目录时按调用者凭证检查权限位。
 
 use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
@@ -13,7 +16,9 @@ use core::any::Any;
 use core::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::sync::Mutex;
+use crate::uapi::fs::{R_OK, W_OK, X_OK};
 use crate::uapi::time::TimeSpec;
+use crate::vfs::perm::check_current_access;
 use crate::vfs::{DirEntry, FileMode, FsError, Inode, InodeMetadata, InodeType};
 
 /// Sysfs 属性生成器
@@ -25,11 +30,44 @@ pub type AttrStoreFn = dyn Fn(&str) -> Result<(), FsError> + Send + Sync;
 /// Sysfs 属性
 pub struct SysfsAttr {
     pub name: String,
+    /// 权限位；没有 `store` 时写权限位被忽略
     pub mode: FileMode,
     pub show: Arc<AttrShowFn>,
     pub store: Option<Arc<AttrStoreFn>>,
 }
 
+impl SysfsAttr {
+    /// 创建可写属性（`rw-r--r--`）
+    ///
+    /// 写入内容去掉首尾空白后先交给 `parse` 校验，通过后把结果交给 `store` 生效。
+    /// 校验失败时返回 `parse` 的错误，`store` 不会被调用。
+    pub fn writable<T, S, P, W>(name: &str, show: S, parse: P, store: W) -> Self
+    where
+        S: Fn() -> Result<String, FsError> + Send + Sync + 'static,
+        P: Fn(&str) -> Result<T, FsError> + Send + Sync + 'static,
+        W: Fn(T) -> Result<(), FsError> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            mode: FileMode::from_bits_truncate(0o644),
+            show: Arc::new(show),
+            store: Some(Arc::new(move |value: &str| store(parse(value.trim())?))),
+        }
+    }
+}
+
+/// 把写入内容解析为 `min..=max` 内的整数，否则返回 `EINVAL`
+pub fn parse_in_range<T>(value: &str, min: T, max: T) -> Result<T, FsError>
+where
+    T: core::str::FromStr + PartialOrd,
+{
+    value
+        .parse::<T>()
+        .ok()
+        .filter(|v| *v >= min && *v <= max)
+        .ok_or(FsError::InvalidArgument)
+}
+
 /// Sysfs Inode 内容类型
 pub enum SysfsInodeContent {
     /// 目录 (子节点)
@@ -81,7 +119,10 @@ impl SysfsInode {
     /// 创建属性文件 inode
     pub fn new_attribute(attr: SysfsAttr) -> Arc<Self> {
         let inode_no = NEXT_INODE_NO.fetch_add(1, Ordering::Relaxed);
-        let mode = attr.mode;
+        let mut mode = attr.mode.difference(FileMode::S_IFMT) | FileMode::S_IFREG;
+        if attr.store.is_none() {
+            mode.remove(FileMode::S_IWUSR | FileMode::S_IWGRP | FileMode::S_IWOTH);
+        }
         let now = TimeSpec::now();
         Arc::new(Self {
             inode_no,
@@ -140,6 +181,12 @@ impl SysfsInode {
         }
     }
 
+    /// 以当前任务的凭证检查对本节点的访问权限
+    fn check_access(&self, mask: i32) -> Result<(), FsError> {
+        let meta = self.metadata.lock().clone();
+        check_current_access(&meta, mask)
+    }
+
     /// 读取符号链接目标
     pub fn readlink(&self) -> Result<String, FsError> {
         match &self.content {
@@ -157,6 +204,7 @@ impl Inode for SysfsInode {
     fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, FsError> {
         match &self.content {
             SysfsInodeContent::Attribute(attr) => {
+                self.check_access(R_OK)?;
                 // 调用 show 函数生成内容
                 let content = (attr.show)()?;
                 let data = content.as_bytes();
@@ -178,6 +226,7 @@ impl Inode for SysfsInode {
             SysfsInodeContent::Attribute(attr) => {
                 // 检查是否支持写入
                 if let Some(store) = &attr.store {
+                    self.check_access(W_OK)?;
                     if offset != 0 {
                         return Err(FsError::InvalidArgument);
                     }
@@ -199,6 +248,7 @@ impl Inode for SysfsInode {
     fn readdir(&self) -> Result<Vec<DirEntry>, FsError> {
         match &self.content {
             SysfsInodeContent::Directory(children) => {
+                self.check_access(R_OK)?;
                 let mut entries = Vec::new();
 
                 entries.push(DirEntry {
@@ -229,12 +279,15 @@ impl Inode for SysfsInode {
 
     fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>, FsError> {
         match &self.content {
-            SysfsInodeContent::Directory(children) => children
-                .lock()
-                .get(name)
-                .cloned()
-                .map(|inode| inode as Arc<dyn Inode>)
-                .ok_or(FsError::NotFound),
+            SysfsInodeContent::Directory(children) => {
+                self.check_access(X_OK)?;
+                children
+                    .lock()
+                    .get(name)
+                    .cloned()
+                    .map(|inode| inode as Arc<dyn Inode>)
+                    .ok_or(FsError::NotFound)
+            }
             _ => Err(FsError::NotDirectory),
         }
     }
@@ -311,3 +364,65 @@ impl Inode for SysfsInode {
         Err(FsError::NotSupported)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_sysfs_attribute_writable_only_with_store, {
+        // 声明为 0644 但没有 store：去掉写权限位，写入被拒绝
+        let readonly = SysfsInode::new_attribute(SysfsAttr {
+            name: "uevent".to_string(),
+            mode: FileMode::from_bits_truncate(0o644),
+            show: Arc::new(|| Ok("x\n".to_string())),
+            store: None,
+        });
+        let meta = readonly.metadata().unwrap();
+        kassert!(meta.mode.bits() & 0o777 == 0o444);
+        kassert!(meta.mode.contains(FileMode::S_IFREG));
+        kassert!(readonly.write_at(0, b"1") == Err(FsError::PermissionDenied));
+
+        let writable = SysfsInode::new_attribute(SysfsAttr {
+            name: "knob".to_string(),
+            mode: FileMode::from_bits_truncate(0o644),
+            show: Arc::new(|| Ok("0\n".to_string())),
+            store: Some(Arc::new(|_value: &str| Ok(()))),
+        });
+        kassert!(writable.metadata().unwrap().mode.bits() & 0o777 == 0o644);
+        kassert!(writable.write_at(0, b"1") == Ok(1));
+    });
+
+    test_case!(test_sysfs_writable_attribute_validation, {
+        use core::sync::atomic::AtomicUsize;
+
+        let value = Arc::new(AtomicUsize::new(4));
+        let attr = SysfsAttr::writable(
+            "knob",
+            {
+                let value = value.clone();
+                move || Ok(alloc::format!("{}\n", value.load(Ordering::Relaxed)))
+            },
+            |input| parse_in_range(input, 1usize, 8),
+            {
+                let value = value.clone();
+                move |v| {
+                    value.store(v, Ordering::Relaxed);
+                    Ok(())
+                }
+            },
+        );
+        let inode = SysfsInode::new_attribute(attr);
+
+        // 首尾空白被忽略，校验失败的写入不生效
+        kassert!(inode.write_at(0, b"6\n") == Ok(2));
+        kassert!(value.load(Ordering::Relaxed) == 6);
+        kassert!(inode.write_at(0, b"9") == Err(FsError::InvalidArgument));
+        kassert!(inode.write_at(0, b"abc") == Err(FsError::InvalidArgument));
+        kassert!(value.load(Ordering::Relaxed) == 6);
+
+        let mut buf = [0u8; 8];
+        kassert!(inode.read_at(0, &mut buf) == Ok(2));
+        kassert!(&buf[..2] == b"6\n");
+    });
+}
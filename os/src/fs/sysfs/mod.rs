@@ -42,6 +42,9 @@
 //! │   └── net/
 //! │       └── eth0 -> ../../devices/platform/eth0
 //! ├── block -> class/block/  # 向后兼容
+//! ├── fs/
+//! │   └── pstore/
+//! │       └── dmesg-blk-0   # 上一次启动的崩溃日志 (存在时)
 //! └── kernel/           # 内核信息
 //!     ├── version
 //!     └── osrelease
@@ -63,6 +66,9 @@
 //! - `queue/hw_sector_size`: 硬件扇区大小
 //! - `queue/max_sectors_kb`: 最大传输大小
 //! - `queue/rotational`: 旋转设备标志
+//! - `queue/max_segments`: 单个请求最多包含的段数
+//! - `queue/vring_kicks`: 提交给设备的请求数（vring 通知次数）
+//! - `queue/merged`: 被合并进相邻请求的请求数
 //!
 //! ## 网络设备
 //! - `uevent`: udev 事件文件
@@ -5,6 +5,7 @@
 use alloc::string::ToString;
 use alloc::sync::Arc;
 
+use crate::uapi::fs::FileSystemType;
 use crate::vfs::{FileMode, FileSystem, FsError, Inode, StatFs};
 
 use super::inode::SysfsInode;
@@ -66,6 +67,12 @@ impl SysFS {
         let kernel_dir = SysfsInode::new_directory(FileMode::from_bits_truncate(0o040000 | 0o555));
         root.add_child("kernel", kernel_dir)?;
 
+        // /sys/fs/pstore/
+        let fs_dir = SysfsInode::new_directory(FileMode::from_bits_truncate(0o040000 | 0o555));
+        root.add_child("fs", fs_dir.clone())?;
+        let pstore_dir = SysfsInode::new_directory(FileMode::from_bits_truncate(0o040000 | 0o755));
+        fs_dir.add_child("pstore", pstore_dir)?;
+
         // /sys/devices/
         let devices_dir = SysfsInode::new_directory(FileMode::from_bits_truncate(0o040000 | 0o555));
         root.add_child("devices", devices_dir)?;
@@ -92,6 +99,7 @@ impl SysFS {
 
         // 3. 构建内核信息树
         builders::kernel::build_kernel_info(&self.root_inode)?;
+        builders::kernel::build_pstore(&self.root_inode)?;
 
         Ok(())
     }
@@ -113,6 +121,7 @@ impl FileSystem for SysFS {
 
     fn statfs(&self) -> Result<StatFs, FsError> {
         Ok(StatFs {
+            fs_type: FileSystemType::Sysfs,
             block_size: 4096,
             total_blocks: 0,
             free_blocks: 0,
@@ -0,0 +1,175 @@
+use super::*;
+use crate::config::EXT4_BLOCK_SIZE;
+use crate::{kassert, test_case};
+use alloc::vec;
+
+// 崩溃一致性、孤儿 inode 与错误策略测试
+
+/// 在同一块 RamDisk 上重新挂载，模拟重启
+fn mount(ramdisk: &Arc<RamDisk>) -> Arc<Ext4FileSystem> {
+    let total_blocks = ramdisk.total_blocks();
+    let device_id = ramdisk.device_id();
+    let block_driver: Arc<dyn BlockDriver> = ramdisk.clone();
+    Ext4FileSystem::open(block_driver, EXT4_BLOCK_SIZE, total_blocks, device_id)
+        .expect("Failed to mount Ext4FileSystem")
+}
+
+fn read_all(inode: &Arc<dyn Inode>) -> alloc::vec::Vec<u8> {
+    let size = inode.metadata().unwrap().size;
+    let mut buf = vec![0u8; size];
+    let n = inode.read_at(0, &mut buf).unwrap();
+    buf.truncate(n);
+    buf
+}
+
+test_case!(test_ext4_fsync_survives_crash, {
+    let ramdisk = create_test_ramdisk();
+    ramdisk.enable_write_cache();
+    let fs = mount(&ramdisk);
+    let root = fs.root_inode();
+
+    let durable = root
+        .create("durable.txt", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    durable.write_at(0, b"committed data").unwrap();
+    kassert!(durable.sync().is_ok());
+
+    // 之后的修改没有 fsync，掉电后应当丢失
+    let volatile = root
+        .create("volatile.txt", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    volatile.write_at(0, b"lost").unwrap();
+    ramdisk.crash();
+
+    let fs = mount(&ramdisk);
+    let root = fs.root_inode();
+    let durable = root.lookup("durable.txt").unwrap();
+    kassert!(read_all(&durable) == b"committed data");
+    kassert!(matches!(
+        root.lookup("volatile.txt"),
+        Err(FsError::NotFound)
+    ));
+});
+
+test_case!(test_ext4_crash_without_fsync_is_mountable, {
+    let ramdisk = create_test_ramdisk();
+    ramdisk.enable_write_cache();
+    let fs = mount(&ramdisk);
+    let root = fs.root_inode();
+
+    let dir = root
+        .mkdir("unsynced", FileMode::from_bits_truncate(0o755))
+        .unwrap();
+    for name in ["a", "b", "c"] {
+        let file = dir
+            .create(name, FileMode::from_bits_truncate(0o644))
+            .unwrap();
+        file.write_at(0, &[0x5a; 8192]).unwrap();
+    }
+    ramdisk.crash();
+
+    // 崩溃后仍能挂载并继续使用
+    let fs = mount(&ramdisk);
+    let root = fs.root_inode();
+    kassert!(root.readdir().is_ok());
+    let file = root
+        .create("after_crash.txt", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    file.write_at(0, b"ok").unwrap();
+    kassert!(read_all(&file) == b"ok");
+});
+
+test_case!(test_ext4_unlinked_open_file_is_orphaned, {
+    let fs = create_test_ext4();
+    let root = fs.root_inode();
+
+    let file = root
+        .create("orphan.txt", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    file.write_at(0, b"still readable").unwrap();
+    let ino = file.metadata().unwrap().inode_no as u32;
+
+    root.unlink("orphan.txt").unwrap();
+    kassert!(matches!(root.lookup("orphan.txt"), Err(FsError::NotFound)));
+
+    // 仍被打开的文件保留数据，并挂在孤儿链表上
+    kassert!(file.metadata().unwrap().nlinks == 0);
+    kassert!(read_all(&file) == b"still readable");
+    kassert!(fs.orphan_list() == [ino]);
+
+    // 最后一个引用释放后由下一次 sync 回收
+    drop(file);
+    fs.sync().unwrap();
+    kassert!(fs.orphan_list().is_empty());
+});
+
+test_case!(test_ext4_hard_link_survives_unlink, {
+    let fs = create_test_ext4();
+    let root = fs.root_inode();
+
+    let file = root
+        .create("first.txt", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    file.write_at(0, b"shared").unwrap();
+    root.link("second.txt", &file).unwrap();
+    drop(file);
+
+    root.unlink("first.txt").unwrap();
+    let second = root.lookup("second.txt").unwrap();
+    kassert!(second.metadata().unwrap().nlinks == 1);
+    kassert!(read_all(&second) == b"shared");
+    kassert!(fs.orphan_list().is_empty());
+});
+
+test_case!(test_ext4_orphan_recovered_after_crash, {
+    let ramdisk = create_test_ramdisk();
+    ramdisk.enable_write_cache();
+    let fs = mount(&ramdisk);
+    let root = fs.root_inode();
+
+    let file = root
+        .create("tmpfile", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    file.write_at(0, &[0xa5; 3 * 4096]).unwrap();
+    root.unlink("tmpfile").unwrap();
+    fs.sync().unwrap();
+
+    // 文件仍被打开时掉电：孤儿链表已经落盘
+    kassert!(fs.orphan_list().len() == 1);
+    ramdisk.crash();
+
+    let fs2 = mount(&ramdisk);
+    kassert!(fs2.orphan_list().is_empty());
+    kassert!(matches!(
+        fs2.root_inode().lookup("tmpfile"),
+        Err(FsError::NotFound)
+    ));
+    drop(file);
+});
+
+test_case!(test_ext4_write_error_remounts_read_only, {
+    let ramdisk = create_test_ramdisk();
+    let fs = mount(&ramdisk);
+    let root = fs.root_inode();
+
+    let file = root
+        .create("victim.txt", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    kassert!(!fs.is_read_only());
+
+    ramdisk.set_fail_writes(true);
+    kassert!(matches!(file.write_at(0, b"data"), Err(FsError::IoError)));
+    ramdisk.set_fail_writes(false);
+
+    // errors=remount-ro：之后的修改一律拒绝，读取仍然可用
+    kassert!(fs.is_read_only());
+    kassert!(matches!(
+        root.create("new.txt", FileMode::from_bits_truncate(0o644)),
+        Err(FsError::ReadOnlyFs)
+    ));
+    kassert!(matches!(
+        root.unlink("victim.txt"),
+        Err(FsError::ReadOnlyFs)
+    ));
+    kassert!(root.lookup("victim.txt").is_ok());
+});
@@ -1,6 +1,6 @@
 use super::*;
 use crate::vfs::file_system::FileSystem;
-use crate::{kassert, test_case};
+use crate::{kassert, println, test_case};
 use alloc::vec;
 
 // P1 重要功能测试
@@ -476,6 +476,89 @@ test_case!(test_ext4_cached_read_cross_page_partial_eof, {
     kassert!(&cached[..17] == &data[4096 - 8..]);
 });
 
+test_case!(test_ext4_read_pages_matches_read_at, {
+    const TOTAL_SIZE: usize = 3 * 4096 + 100;
+    let fs = create_test_ext4();
+    let data: alloc::vec::Vec<u8> = (0..TOTAL_SIZE).map(|i| (i % 253) as u8).collect();
+    let inode = create_test_file_with_content(&fs, "read-pages.bin", &data).unwrap();
+
+    // 从页内偏移开始，跨页读到文件末尾；每次交给 sink 的数据不跨页
+    let mut out = alloc::vec::Vec::new();
+    let mut chunks = 0;
+    let n = inode
+        .read_pages(100, TOTAL_SIZE, &mut |chunk| {
+            kassert!(chunk.len() <= 4096);
+            out.extend_from_slice(chunk);
+            chunks += 1;
+            Ok(())
+        })
+        .unwrap();
+    kassert!(n == TOTAL_SIZE - 100);
+    kassert!(chunks == 4);
+    kassert!(&out[..] == &data[100..]);
+
+    // sink 第一次就出错时返回错误
+    let result = inode.read_pages(0, TOTAL_SIZE, &mut |_| Err(FsError::BadAddress));
+    kassert!(matches!(result, Err(FsError::BadAddress)));
+
+    // 已交出部分数据后出错时返回已交出的字节数
+    let mut calls = 0;
+    let result = inode.read_pages(100, TOTAL_SIZE, &mut |_| {
+        calls += 1;
+        if calls > 2 {
+            Err(FsError::BadAddress)
+        } else {
+            Ok(())
+        }
+    });
+    kassert!(matches!(result, Ok(n) if n == 2 * 4096 - 100));
+});
+
+// 基准：整页对齐的缓存命中读取，比较经中间缓冲区的 read_at 与直接交给目的地的 read_pages
+test_case!(test_ext4_bench_cached_full_page_read, {
+    const PAGES: usize = 16;
+    const ROUNDS: usize = 32;
+    let fs = create_test_ext4();
+    let data: alloc::vec::Vec<u8> = (0..PAGES * 4096).map(|i| (i % 251) as u8).collect();
+    let inode = create_test_file_with_content(&fs, "bench.bin", &data).unwrap();
+    let mut dst = vec![0u8; PAGES * 4096];
+    // 预热页缓存
+    kassert!(inode.read_at(0, &mut dst).unwrap() == dst.len());
+
+    // 旧路径：读入中间缓冲区，再拷贝到目的地（对应 copy_to_user）
+    let start = crate::arch::get_time();
+    for _ in 0..ROUNDS {
+        let mut bounce = vec![0u8; dst.len()];
+        let n = inode.read_at(0, &mut bounce).unwrap();
+        dst[..n].copy_from_slice(&bounce[..n]);
+    }
+    let bounce_ticks = crate::arch::get_time() - start;
+
+    // 快速路径：每个缓存页直接拷贝到目的地
+    dst.fill(0);
+    let start = crate::arch::get_time();
+    for _ in 0..ROUNDS {
+        let mut pos = 0;
+        inode
+            .read_pages(0, dst.len(), &mut |chunk| {
+                dst[pos..pos + chunk.len()].copy_from_slice(chunk);
+                pos += chunk.len();
+                Ok(())
+            })
+            .unwrap();
+    }
+    let direct_ticks = crate::arch::get_time() - start;
+    kassert!(dst == data);
+
+    println!(
+        "[bench] cached full-page read, {} x {} KiB: bounce {} ticks, direct {} ticks",
+        ROUNDS,
+        PAGES * 4,
+        bounce_ticks,
+        direct_ticks
+    );
+});
+
 test_case!(test_ext4_empty_file_read, {
     // 创建空文件
     let fs = create_test_ext4();
@@ -514,3 +597,50 @@ test_case!(test_ext4_truncate_extend, {
     let metadata = inode.metadata().unwrap();
     kassert!(metadata.size == 20);
 });
+
+test_case!(test_ext4_sparse_write_leaves_hole, {
+    let fs = create_test_ext4();
+    let inode = create_test_file_with_content(&fs, "sparse.dat", b"head").unwrap();
+    let blocks_before = inode.metadata().unwrap().blocks;
+
+    // 跳过中间 3 个块写入，空洞不分配块
+    let offset = 4 * 4096;
+    kassert!(inode.write_at(offset, b"tail").unwrap() == 4);
+    let metadata = inode.metadata().unwrap();
+    kassert!(metadata.size == offset + 4);
+    kassert!(metadata.blocks == blocks_before + 8);
+
+    let mut hole = vec![0xffu8; 4096];
+    kassert!(inode.read_at(4096, &mut hole).unwrap() == 4096);
+    kassert!(hole.iter().all(|&b| b == 0));
+
+    kassert!(inode.next_data(0).unwrap() == Some((0, 4096)));
+    kassert!(inode.next_data(4096).unwrap() == Some((offset, offset + 4)));
+    kassert!(inode.next_data(offset + 4).unwrap().is_none());
+
+    // 填补空洞只分配被写入的块
+    kassert!(inode.write_at(2 * 4096 + 10, b"mid").unwrap() == 3);
+    kassert!(inode.metadata().unwrap().blocks == blocks_before + 16);
+    kassert!(inode.next_data(4096).unwrap() == Some((2 * 4096, 3 * 4096)));
+    let mut buf = [0u8; 3];
+    inode.read_at(2 * 4096 + 10, &mut buf).unwrap();
+    kassert!(&buf == b"mid");
+});
+
+test_case!(test_ext4_truncate_extend_is_sparse, {
+    let fs = create_test_ext4();
+    let inode = create_test_file_with_content(&fs, "grow.dat", b"Hello").unwrap();
+    inode.truncate(3).unwrap();
+    let blocks_before = inode.metadata().unwrap().blocks;
+
+    kassert!(inode.truncate(1024 * 1024).is_ok());
+    let metadata = inode.metadata().unwrap();
+    kassert!(metadata.size == 1024 * 1024);
+    kassert!(metadata.blocks == blocks_before);
+
+    // 原末尾块中截断掉的数据被清零
+    let mut buf = [0xffu8; 8];
+    kassert!(inode.read_at(0, &mut buf).unwrap() == 8);
+    kassert!(&buf == b"Hel\0\0\0\0\0");
+    kassert!(inode.next_data(4096).unwrap().is_none());
+});
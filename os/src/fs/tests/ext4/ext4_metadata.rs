@@ -173,3 +173,14 @@ test_case!(test_ext4_mknod_duplicate, {
     );
     kassert!(matches!(result, Err(FsError::AlreadyExists)));
 });
+
+test_case!(test_ext4_xattr_not_supported, {
+    let fs = create_test_ext4();
+    let inode = create_test_file_with_content(&fs, "xattr.txt", b"x").unwrap();
+
+    let flags = crate::uapi::xattr::XattrFlags::empty();
+    kassert!(inode.setxattr("user.a", b"1", flags) == Err(FsError::NotSupported));
+    kassert!(inode.getxattr("user.a") == Err(FsError::NotSupported));
+    kassert!(inode.removexattr("user.a") == Err(FsError::NotSupported));
+    kassert!(inode.listxattr().unwrap().is_empty());
+});
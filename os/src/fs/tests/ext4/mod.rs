@@ -169,6 +169,7 @@ pub fn create_test_file(fs: &Arc<Ext4FileSystem>, name: &str) -> Result<Arc<dyn
 // Export test modules
 pub mod ext4_basic;
 pub mod ext4_directory;
+pub mod ext4_durability;
 pub mod ext4_error;
 pub mod ext4_integration;
 pub mod ext4_io;
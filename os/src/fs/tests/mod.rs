@@ -1,5 +1,6 @@
 mod ext4;
 mod proc;
+mod rootfs;
 mod simple_fs;
 mod sysfs;
 mod tmpfs;
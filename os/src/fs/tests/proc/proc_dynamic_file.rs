@@ -63,6 +63,34 @@ test_case!(test_procfs_uptime_read, {
     kassert!(content.is_ok());
 });
 
+test_case!(test_procfs_bootinfo_read, {
+    let procfs = create_test_procfs_with_tree().unwrap();
+    let root = procfs.root_inode();
+    let bootinfo = root.lookup("bootinfo").unwrap();
+    kassert!(bootinfo.metadata().unwrap().mode.bits() & 0o777 == 0o444);
+
+    let mut buf = [0u8; 1024];
+    let bytes_read = bootinfo.read_at(0, &mut buf).unwrap();
+    let content = core::str::from_utf8(&buf[..bytes_read]).unwrap();
+    // 至少有表头，已登记的里程碑（如 mm_init）紧随其后
+    kassert!(content.lines().next().unwrap().contains("milestone"));
+});
+
+test_case!(test_procfs_enosys, {
+    let procfs = create_test_procfs_with_tree().unwrap();
+    let root = procfs.root_inode();
+    let enosys = root.lookup("enosys").unwrap();
+    kassert!(enosys.metadata().unwrap().mode.bits() & 0o777 == 0o644);
+
+    // 只接受 0，清空后只剩表头
+    kassert!(enosys.write_at(0, b"1\n").is_err());
+    kassert!(enosys.write_at(0, b"0\n").is_ok());
+    let mut buf = [0u8; 256];
+    let bytes_read = enosys.read_at(0, &mut buf).unwrap();
+    let content = core::str::from_utf8(&buf[..bytes_read]).unwrap();
+    kassert!(content == "nr\tname\tcount\tbinaries\n");
+});
+
 test_case!(test_procfs_cpuinfo_exists, {
     let procfs = create_test_procfs_with_tree().unwrap();
     let root = procfs.root_inode();
@@ -138,3 +166,18 @@ test_case!(test_procfs_dynamic_file_regenerate, {
     kassert!(read1 > 0);
     kassert!(read2 > 0);
 });
+
+test_case!(test_procfs_file_modes, {
+    let procfs = create_test_procfs_with_tree().unwrap();
+    let root = procfs.root_inode();
+
+    // 只读文件带普通文件类型位
+    let meminfo = root.lookup("meminfo").unwrap().metadata().unwrap();
+    kassert!(meminfo.mode.contains(FileMode::S_IFREG));
+    kassert!(meminfo.mode.bits() & 0o222 == 0);
+
+    // 有写入器的文件保留注册时的写权限位
+    let test_result = root.lookup("test-result").unwrap().metadata().unwrap();
+    kassert!(test_result.mode.bits() & 0o7777 == 0o644);
+    kassert!(test_result.mode.contains(FileMode::S_IFREG));
+});
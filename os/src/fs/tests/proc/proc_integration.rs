@@ -115,3 +115,23 @@ test_case!(test_procfs_readdir_all_entries, {
 //     let is_numeric = target.chars().all(|c: char| c.is_numeric());
 //     kassert!(is_numeric);
 // });
+
+test_case!(test_procfs_mount_options, {
+    use crate::fs::proc::{ProcMountOptions, options::HidePid};
+
+    kassert!(ProcMountOptions::parse("") == Ok(ProcMountOptions::default()));
+
+    let options = ProcMountOptions::parse("hidepid=2,gid=10").unwrap();
+    kassert!(options.hidepid == HidePid::Invisible);
+    kassert!(options.gid == Some(10));
+    kassert!(ProcMountOptions::parse("hidepid=noaccess").unwrap().hidepid == HidePid::NoAccess);
+
+    kassert!(ProcMountOptions::parse("hidepid=3") == Err(FsError::InvalidArgument));
+    kassert!(ProcMountOptions::parse("gid=abc") == Err(FsError::InvalidArgument));
+    kassert!(ProcMountOptions::parse("uid=0") == Err(FsError::InvalidArgument));
+
+    // 使用选项创建的 procfs 仍然包含静态节点
+    let procfs = ProcFS::with_options(options);
+    kassert!(procfs.init_tree().is_ok());
+    kassert!(procfs.root_inode().lookup("meminfo").is_ok());
+});
@@ -0,0 +1,9 @@
+use crate::fs::root_device_param;
+use crate::{kassert, test_case};
+
+test_case!(test_root_device_param, {
+    kassert!(root_device_param("console=ttyS0 root=/dev/vda1 rw") == Some("vda1"));
+    kassert!(root_device_param("root=vdb2") == Some("vdb2"));
+    kassert!(root_device_param("root=/dev/").is_none());
+    kassert!(root_device_param("quiet").is_none());
+});
@@ -51,6 +51,7 @@ pub fn create_test_dir(fs: &Arc<SimpleFs>, path: &str) -> Result<Arc<dyn Inode>,
 
 pub mod simple_fs_basic;
 pub mod simple_fs_dir;
+pub mod simple_fs_image;
 pub mod simple_fs_integration;
 pub mod simple_fs_permission;
 pub mod simple_fs_ramdisk;
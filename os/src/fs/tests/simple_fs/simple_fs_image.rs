@@ -0,0 +1,293 @@
+use super::*;
+use crate::device::block::BlockDriver;
+use crate::fs::simple_fs::image::{self, HEADER_SIZE, ImageEntry};
+use crate::uapi::time::TimeSpec;
+use crate::{kassert, test_case};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+// mkfs / fsck 与回写测试
+
+/// 创建一块全零的 RamDisk
+fn blank_disk(blocks: usize) -> Arc<RamDisk> {
+    RamDisk::from_bytes(vec![0u8; blocks * 512], 512, 0)
+}
+
+fn entry(path: &str, is_dir: bool, data: &[u8]) -> ImageEntry {
+    ImageEntry {
+        path: String::from(path),
+        is_dir,
+        mode: if is_dir { 0o755 } else { 0o644 },
+        data: Vec::from(data),
+        atime: TimeSpec::zero(),
+        mtime: TimeSpec::zero(),
+        ctime: TimeSpec::zero(),
+    }
+}
+
+fn read_all(inode: &Arc<dyn Inode>) -> Vec<u8> {
+    let mut buf = vec![0u8; inode.metadata().unwrap().size];
+    let n = inode.read_at(0, &mut buf).unwrap();
+    buf.truncate(n);
+    buf
+}
+
+test_case!(test_simplefs_mkfs_then_fsck_clean, {
+    let disk = blank_disk(16);
+    kassert!(matches!(
+        image::check(disk.as_ref()),
+        Err(FsError::InvalidArgument)
+    ));
+
+    image::format(disk.as_ref()).unwrap();
+    let report = image::check(disk.as_ref()).unwrap();
+    kassert!(report.is_clean());
+    kassert!(report.files == 0 && report.dirs == 0);
+    kassert!(report.image_len == HEADER_SIZE);
+
+    let fs = SimpleFs::open(disk).unwrap();
+    kassert!(fs.root_inode().readdir().unwrap().is_empty());
+    let statfs = fs.statfs().unwrap();
+    kassert!(statfs.total_blocks == 16);
+    kassert!(statfs.free_blocks == 15);
+});
+
+test_case!(test_simplefs_sync_roundtrip, {
+    let disk = blank_disk(64);
+    image::format(disk.as_ref()).unwrap();
+
+    let fs = SimpleFs::open(disk.clone()).unwrap();
+    let root = fs.root_inode();
+    let dir = root
+        .mkdir("etc", FileMode::from_bits_truncate(0o750))
+        .unwrap();
+    let file = dir
+        .create("motd", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    file.write_at(0, &[0x42; 1000]).unwrap();
+    file.chmod(FileMode::from_bits_truncate(0o600)).unwrap();
+    fs.sync().unwrap();
+
+    let report = image::check(disk.as_ref()).unwrap();
+    kassert!(report.is_clean());
+    kassert!(report.files == 1 && report.dirs == 1 && report.bytes == 1000);
+
+    // 重新挂载后内容与权限保持不变
+    let fs = SimpleFs::open(disk).unwrap();
+    let dir = fs.root_inode().lookup("etc").unwrap();
+    let dir_mode = dir.metadata().unwrap().mode;
+    kassert!(dir_mode.contains(FileMode::S_IFDIR));
+    kassert!(dir_mode.bits() & 0o777 == 0o750);
+    let file = dir.lookup("motd").unwrap();
+    kassert!(file.metadata().unwrap().mode.bits() & 0o777 == 0o600);
+    kassert!(read_all(&file) == [0x42; 1000]);
+});
+
+test_case!(test_simplefs_rename_and_rmdir_persist, {
+    let disk = blank_disk(64);
+    image::format(disk.as_ref()).unwrap();
+
+    let fs = SimpleFs::open(disk.clone()).unwrap();
+    let root = fs.root_inode();
+    let a = root
+        .mkdir("a", FileMode::from_bits_truncate(0o755))
+        .unwrap();
+    let b = root
+        .mkdir("b", FileMode::from_bits_truncate(0o755))
+        .unwrap();
+    root.mkdir("empty", FileMode::from_bits_truncate(0o755))
+        .unwrap();
+    a.create("f", FileMode::from_bits_truncate(0o644))
+        .unwrap()
+        .write_at(0, b"moved")
+        .unwrap();
+
+    kassert!(a.rename("f", b.clone(), "g").is_ok());
+    kassert!(matches!(root.rmdir("b"), Err(FsError::DirectoryNotEmpty)));
+    kassert!(root.rmdir("empty").is_ok());
+    // 目录不能移动到自身的子树中
+    kassert!(matches!(
+        root.rename("a", a.clone(), "inner"),
+        Err(FsError::InvalidArgument)
+    ));
+    fs.sync().unwrap();
+
+    let fs = SimpleFs::open(disk).unwrap();
+    let root = fs.root_inode();
+    kassert!(matches!(root.lookup("empty"), Err(FsError::NotFound)));
+    kassert!(root.lookup("a").unwrap().readdir().unwrap().is_empty());
+    let g = root.lookup("b").unwrap().lookup("g").unwrap();
+    kassert!(read_all(&g) == b"moved");
+});
+
+test_case!(test_simplefs_fsck_detects_corruption, {
+    let disk = blank_disk(16);
+    image::write_image(disk.as_ref(), &[
+        entry("bin", true, &[]),
+        entry("bin/sh", false, b"elf"),
+        entry("bin/sh", false, b"dup"),
+        entry("bin/sh/x", false, b""),
+    ])
+    .unwrap();
+
+    let report = image::check(disk.as_ref()).unwrap();
+    kassert!(report.errors.len() == 2);
+    kassert!(report.errors[0].contains("duplicate"));
+    kassert!(report.errors[1].contains("regular file"));
+    kassert!(matches!(
+        SimpleFs::open(disk.clone()),
+        Err(FsError::InvalidArgument)
+    ));
+
+    // 破坏第一个文件头的魔数：后续条目无法定位
+    image::write_image(disk.as_ref(), &[entry("a", false, b"x")]).unwrap();
+    let mut block = vec![0u8; 512];
+    disk.read_block(0, &mut block);
+    block[HEADER_SIZE..HEADER_SIZE + 4].copy_from_slice(&0u32.to_le_bytes());
+    disk.write_block(0, &block);
+    let report = image::check(disk.as_ref()).unwrap();
+    kassert!(!report.is_clean() && report.files == 0);
+
+    // 超出设备末尾的数据长度
+    image::write_image(disk.as_ref(), &[entry("a", false, b"x")]).unwrap();
+    disk.read_block(0, &mut block);
+    let len_offset = HEADER_SIZE + 8;
+    block[len_offset..len_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    disk.write_block(0, &block);
+    kassert!(!image::check(disk.as_ref()).unwrap().is_clean());
+});
+
+test_case!(test_simplefs_sync_reports_no_space, {
+    let disk = blank_disk(4);
+    image::format(disk.as_ref()).unwrap();
+
+    let fs = SimpleFs::open(disk.clone()).unwrap();
+    let file = fs
+        .root_inode()
+        .create("big", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    file.write_at(0, &[1u8; 4 * 512]).unwrap();
+    kassert!(matches!(fs.sync(), Err(FsError::NoSpace)));
+
+    // 失败的 sync 不破坏设备上原有的镜像
+    kassert!(image::check(disk.as_ref()).unwrap().is_clean());
+    file.truncate(100).unwrap();
+    kassert!(fs.sync().is_ok());
+});
+
+test_case!(test_simplefs_reads_v1_image_and_upgrades, {
+    // 第 1 版镜像：16 字节镜像头 + 32 字节文件头，与 build.rs 的生成结果相同
+    let mut raw = vec![0u8; 16 * 512];
+    raw[..8].copy_from_slice(image::IMAGE_MAGIC);
+    raw[8..12].copy_from_slice(&2u32.to_le_bytes());
+    let mut offset = image::HEADER_SIZE_V1;
+    for (path, file_type, data) in [("etc", 1u32, &b""[..]), ("etc/hostname", 0, b"comix")] {
+        for field in [
+            image::ENTRY_MAGIC,
+            path.len() as u32,
+            data.len() as u32,
+            file_type,
+            0o644,
+        ] {
+            raw[offset..offset + 4].copy_from_slice(&field.to_le_bytes());
+            offset += 4;
+        }
+        offset += image::ENTRY_HEADER_SIZE_V1 - 20;
+        raw[offset..offset + path.len()].copy_from_slice(path.as_bytes());
+        offset += path.len().next_multiple_of(4);
+        raw[offset..offset + data.len()].copy_from_slice(data);
+        offset += data.len().next_multiple_of(512);
+    }
+    let disk = RamDisk::from_bytes(raw, 512, 0);
+
+    let report = image::check(disk.as_ref()).unwrap();
+    kassert!(report.is_clean());
+    kassert!(report.version == image::VERSION_1);
+    kassert!(report.files == 1 && report.dirs == 1);
+
+    let fs = SimpleFs::open(disk.clone()).unwrap();
+    let file = fs
+        .root_inode()
+        .lookup("etc")
+        .unwrap()
+        .lookup("hostname")
+        .unwrap();
+    kassert!(read_all(&file) == b"comix");
+    kassert!(file.metadata().unwrap().mtime == TimeSpec::zero());
+
+    // 回写后升级为第 2 版
+    fs.sync().unwrap();
+    let report = image::check(disk.as_ref()).unwrap();
+    kassert!(report.is_clean() && report.version == image::CURRENT_VERSION);
+    kassert!(report.files == 1 && report.dirs == 1);
+});
+
+test_case!(test_simplefs_header_checksum, {
+    let disk = blank_disk(16);
+    image::format(disk.as_ref()).unwrap();
+    kassert!(image::check(disk.as_ref()).unwrap().version == image::VERSION_2);
+
+    // 篡改文件数量后校验和不再匹配
+    let mut block = vec![0u8; 512];
+    disk.read_block(0, &mut block);
+    block[8] = 1;
+    disk.write_block(0, &block);
+    kassert!(matches!(
+        image::check(disk.as_ref()),
+        Err(FsError::InvalidArgument)
+    ));
+    kassert!(matches!(
+        image::read_image(disk.as_ref()),
+        Err(FsError::IoError)
+    ));
+});
+
+test_case!(test_simplefs_long_names_and_timestamps, {
+    let disk = blank_disk(64);
+    image::format(disk.as_ref()).unwrap();
+
+    let fs = SimpleFs::open(disk.clone()).unwrap();
+    let root = fs.root_inode();
+    let long_name = "n".repeat(image::NAME_MAX);
+    let dir = root
+        .mkdir(&long_name, FileMode::from_bits_truncate(0o755))
+        .unwrap();
+    let nested = dir
+        .mkdir("a", FileMode::from_bits_truncate(0o755))
+        .unwrap()
+        .mkdir("b", FileMode::from_bits_truncate(0o755))
+        .unwrap();
+    kassert!(matches!(
+        nested.create(
+            &"x".repeat(image::NAME_MAX + 1),
+            FileMode::from_bits_truncate(0o644)
+        ),
+        Err(FsError::NameTooLong)
+    ));
+    let file = nested
+        .create(&long_name, FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    let mtime = TimeSpec {
+        tv_sec: 1_700_000_000,
+        tv_nsec: 123_456_789,
+    };
+    file.set_times(Some(TimeSpec::zero()), Some(mtime)).unwrap();
+    fs.sync().unwrap();
+    kassert!(image::check(disk.as_ref()).unwrap().is_clean());
+
+    let fs = SimpleFs::open(disk).unwrap();
+    let file = fs
+        .root_inode()
+        .lookup(&long_name)
+        .unwrap()
+        .lookup("a")
+        .unwrap()
+        .lookup("b")
+        .unwrap()
+        .lookup(&long_name)
+        .unwrap();
+    let meta = file.metadata().unwrap();
+    kassert!(meta.mtime == mtime);
+    kassert!(meta.atime == TimeSpec::zero());
+});
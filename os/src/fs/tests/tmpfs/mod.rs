@@ -45,4 +45,5 @@ pub mod tmpfs_error;
 pub mod tmpfs_integration;
 pub mod tmpfs_io;
 pub mod tmpfs_metadata;
+pub mod tmpfs_mmap;
 pub mod tmpfs_sparse;
@@ -1,6 +1,8 @@
 //! Tmpfs 元数据和时间戳测试
 
 use super::*;
+use crate::uapi::xattr::XattrFlags;
+use crate::vfs::TimeSpec;
 use crate::{kassert, test_case};
 
 test_case!(test_tmpfs_metadata_initial, {
@@ -116,6 +118,71 @@ test_case!(test_tmpfs_metadata_timestamps_file, {
     kassert!(meta2.mtime.tv_sec >= mtime1.tv_sec);
 });
 
+test_case!(test_tmpfs_metadata_set_times_and_atime, {
+    let fs = create_test_tmpfs();
+    let root = fs.root_inode();
+    let file = root
+        .create("utimes.txt", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    kassert!(file.write_at(0, b"data").is_ok());
+
+    // 只设置 atime：mtime 不变，ctime 更新为当前时间
+    let before = file.metadata().unwrap();
+    let past = TimeSpec {
+        tv_sec: 1_000,
+        tv_nsec: 0,
+    };
+    file.set_times(Some(past), None).unwrap();
+    let meta = file.metadata().unwrap();
+    kassert!(meta.atime == past);
+    kassert!(meta.mtime == before.mtime);
+    kassert!(meta.ctime >= before.ctime);
+
+    // inode 的读取本身不更新 atime，由 VFS 在读取后调用 touch_atime
+    let mut buf = [0u8; 4];
+    kassert!(file.read_at(0, &mut buf).is_ok());
+    kassert!(file.metadata().unwrap().atime == past);
+
+    // relatime：atime 早于 mtime，访问后更新；再次访问不再更新
+    file.touch_atime();
+    let read_atime = file.metadata().unwrap().atime;
+    kassert!(read_atime > meta.mtime);
+    file.touch_atime();
+    kassert!(file.metadata().unwrap().atime == read_atime);
+});
+
+test_case!(test_tmpfs_metadata_xattr, {
+    let fs = create_test_tmpfs();
+    let root = fs.root_inode();
+    let file = root
+        .create("xattr.txt", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    kassert!(file.listxattr().unwrap().is_empty());
+    kassert!(file.getxattr("user.mime") == Err(FsError::NoData));
+
+    // 设置属性更新 ctime
+    let past = TimeSpec {
+        tv_sec: 1_000,
+        tv_nsec: 0,
+    };
+    file.set_times(Some(past), Some(past)).unwrap();
+    let before = file.metadata().unwrap().ctime;
+    kassert!(
+        file.setxattr("user.mime", b"text/plain", XattrFlags::CREATE)
+            .is_ok()
+    );
+    kassert!(file.metadata().unwrap().ctime >= before);
+    kassert!(file.getxattr("user.mime") == Ok(b"text/plain".to_vec()));
+    let result = file.setxattr("user.mime", b"x", XattrFlags::CREATE);
+    kassert!(result == Err(FsError::AlreadyExists));
+    kassert!(file.setxattr("trusted.a", b"", XattrFlags::empty()).is_ok());
+    kassert!(file.listxattr().unwrap() == ["trusted.a", "user.mime"]);
+
+    kassert!(file.removexattr("user.mime").is_ok());
+    kassert!(file.removexattr("user.mime") == Err(FsError::NoData));
+    kassert!(file.listxattr().unwrap() == ["trusted.a"]);
+});
+
 test_case!(test_tmpfs_metadata_timestamps_dir, {
     let fs = create_test_tmpfs();
     let root = fs.root_inode();
@@ -0,0 +1,142 @@
+//! Tmpfs 共享映射与 memfd 测试
+
+use super::*;
+use crate::config::PAGE_SIZE;
+use crate::fs::tmpfs::create_memfd;
+use crate::mm::address::{ConvertablePA, PageNum};
+use crate::{kassert, test_case};
+
+/// 通过物理页的内核直接映射读取一个字节
+fn read_page_byte(ppn: crate::mm::address::Ppn, offset: usize) -> u8 {
+    let va = ppn.start_addr().to_va().as_usize() + offset;
+    unsafe { core::ptr::read_volatile(va as *const u8) }
+}
+
+test_case!(test_tmpfs_mmap_pages_share_file_data, {
+    let fs = create_test_tmpfs();
+    let file = create_test_file_with_content(&fs, "shared.dat", b"hello").unwrap();
+    file.truncate(2 * PAGE_SIZE).unwrap();
+
+    // 第二页原本是空洞，映射时被补齐
+    let pages = file.mmap_pages().unwrap();
+    kassert!(pages.page_count() == 2);
+    kassert!(read_page_byte(pages.ppn_at(0).unwrap(), 0) == b'h');
+    kassert!(read_page_byte(pages.ppn_at(1).unwrap(), 0) == 0);
+
+    // 之后的 write 写入同一批物理页
+    file.write_at(PAGE_SIZE, b"x").unwrap();
+    kassert!(read_page_byte(pages.ppn_at(1).unwrap(), 0) == b'x');
+
+    // 再次映射得到相同的物理页
+    let again = file.mmap_pages().unwrap();
+    kassert!(again.ppn_at(0) == pages.ppn_at(0));
+    kassert!(again.ppn_at(1) == pages.ppn_at(1));
+});
+
+test_case!(test_tmpfs_mmap_pages_rejects_directory, {
+    let fs = create_test_tmpfs();
+    let dir = create_test_dir(&fs, "dir").unwrap();
+    kassert!(matches!(dir.mmap_pages(), Err(FsError::IsDirectory)));
+});
+
+test_case!(test_memfd_is_detached_tmpfs_file, {
+    let dentry = create_memfd("buffer").unwrap();
+    kassert!(dentry.name() == "memfd:buffer");
+
+    let inode = dentry.inode.clone();
+    let meta = inode.metadata().unwrap();
+    kassert!(meta.inode_type == InodeType::File);
+    kassert!(meta.nlinks == 0);
+    kassert!(meta.size == 0);
+
+    inode.truncate(PAGE_SIZE).unwrap();
+    kassert!(inode.write_at(0, b"memfd").unwrap() == 5);
+    let pages = inode.mmap_pages().unwrap();
+    kassert!(pages.page_count() == 1);
+    kassert!(read_page_byte(pages.ppn_at(0).unwrap(), 4) == b'd');
+
+    // 每次创建都是独立的文件
+    let other = create_memfd("buffer").unwrap();
+    kassert!(!Arc::ptr_eq(&other.inode, &dentry.inode));
+});
+
+/// 描述从文件开头映射 `pages` 页的请求
+fn mmap_desc(pages: usize, flags: crate::uapi::mm::MapFlags) -> crate::vfs::MmapDesc {
+    crate::vfs::MmapDesc {
+        offset: 0,
+        len: pages * PAGE_SIZE,
+        prot: crate::uapi::mm::ProtFlags::READ | crate::uapi::mm::ProtFlags::WRITE,
+        flags,
+    }
+}
+
+test_case!(test_file_mmap_policy, {
+    use crate::uapi::mm::MapFlags;
+    use crate::vfs::{File, MmapPolicy, OpenFlags, RegFile};
+
+    // memfd 的共享映射直接映射文件页，私有映射与越过文件末尾的共享映射走拷贝路径
+    let dentry = create_memfd("policy").unwrap();
+    dentry.inode.truncate(PAGE_SIZE).unwrap();
+    let file = RegFile::new(dentry, OpenFlags::O_RDWR);
+    kassert!(matches!(
+        file.mmap(&mmap_desc(1, MapFlags::SHARED)),
+        Ok(MmapPolicy::Direct { first_page: 0, .. })
+    ));
+    kassert!(matches!(
+        file.mmap(&mmap_desc(1, MapFlags::PRIVATE)),
+        Ok(MmapPolicy::CopyIn)
+    ));
+    kassert!(matches!(
+        file.mmap(&mmap_desc(2, MapFlags::SHARED)),
+        Ok(MmapPolicy::CopyIn)
+    ));
+});
+
+test_case!(test_dev_file_mmap_policy, {
+    use crate::uapi::mm::MapFlags;
+    use crate::vfs::dev::makedev;
+    use crate::vfs::devno::chrdev_major;
+    use crate::vfs::impls::CharDeviceFile;
+    use crate::vfs::{Dentry, File, MmapPolicy, OpenFlags};
+
+    let fs = create_test_tmpfs();
+    let root = fs.root_inode();
+    let mode = FileMode::S_IFCHR | FileMode::from_bits_truncate(0o666);
+    let open = |name: &str, minor: u32| {
+        let inode = root
+            .mknod(name, mode, makedev(chrdev_major::MEM, minor))
+            .unwrap();
+        CharDeviceFile::new(Dentry::new(name.into(), inode), OpenFlags::O_RDWR).unwrap()
+    };
+
+    // /dev/zero 映射为匿名内存，/dev/null 不支持映射
+    let zero = open("zero", 5);
+    kassert!(matches!(
+        zero.mmap(&mmap_desc(4, MapFlags::SHARED)),
+        Ok(MmapPolicy::Anonymous)
+    ));
+    let null = open("null", 3);
+    kassert!(matches!(
+        null.mmap(&mmap_desc(1, MapFlags::SHARED)),
+        Err(FsError::NotSupported)
+    ));
+});
+
+test_case!(test_mmap_policy_direct_checks_range, {
+    use crate::mm::memory_space::mapping_area::{AnonSharedPages, SharedPages};
+    use crate::uapi::mm::MapFlags;
+    use crate::vfs::MmapPolicy;
+
+    let pages: Arc<dyn SharedPages> = AnonSharedPages::new(2).unwrap();
+    let mut desc = mmap_desc(1, MapFlags::SHARED);
+    desc.offset = PAGE_SIZE;
+    kassert!(matches!(
+        MmapPolicy::direct(pages.clone(), &desc),
+        Ok(MmapPolicy::Direct { first_page: 1, .. })
+    ));
+    desc.len = 2 * PAGE_SIZE;
+    kassert!(matches!(
+        MmapPolicy::direct(pages, &desc),
+        Err(FsError::InvalidArgument)
+    ));
+});
@@ -180,3 +180,26 @@ test_case!(test_tmpfs_sparse_empty_truncate, {
         kassert!(*byte == 0);
     }
 });
+
+test_case!(test_tmpfs_sparse_blocks_and_next_data, {
+    let fs = create_test_tmpfs();
+    let root = fs.root_inode();
+    let file = root
+        .create("extents.dat", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+
+    // 只有写入的页计入 blocks
+    file.write_at(0, b"A").unwrap();
+    file.write_at(3 * 4096, b"B").unwrap();
+    kassert!(file.metadata().unwrap().blocks == 2 * 4096 / 512);
+    kassert!(file.next_data(0).unwrap() == Some((0, 4096)));
+    kassert!(file.next_data(4096).unwrap() == Some((3 * 4096, 3 * 4096 + 1)));
+
+    // 扩展不分配页，缩小释放页
+    file.truncate(1024 * 1024).unwrap();
+    kassert!(file.metadata().unwrap().blocks == 2 * 4096 / 512);
+    kassert!(file.next_data(3 * 4096 + 1).unwrap().is_none());
+    file.truncate(100).unwrap();
+    kassert!(file.metadata().unwrap().blocks == 4096 / 512);
+    kassert!(file.next_data(0).unwrap() == Some((0, 100)));
+});
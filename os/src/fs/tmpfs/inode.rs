@@ -8,11 +8,17 @@ use alloc::sync::{Arc, Weak};
 use alloc::vec::Vec;
 
 use crate::config::PAGE_SIZE;
-use crate::mm::address::{ConvertablePA, PageNum};
+use crate::mm::address::{ConvertablePA, PageNum, Ppn};
 use crate::mm::frame_allocator::{FrameTracker, alloc_frame};
+use crate::mm::memory_space::mapping_area::SharedPages;
 use crate::sync::{Mutex, SpinLock};
 use crate::uapi::time::TimeSpec;
-use crate::vfs::{DirEntry, FileMode, FsError, Inode, InodeMetadata, InodeType};
+use crate::uapi::xattr::XattrFlags;
+use crate::vfs::timestamps::atime_needs_update;
+use crate::vfs::xattr::XattrMap;
+use crate::vfs::{
+    DirEntry, FileMode, FsError, Inode, InodeMetadata, InodeType, MmapDesc, MmapPolicy,
+};
 
 /// Tmpfs Inode 实现
 ///
@@ -37,6 +43,28 @@ pub struct TmpfsInode {
 
     /// 指向自身的弱引用（用于 lookup "." 和作为子节点的父节点）
     self_ref: Mutex<Weak<TmpfsInode>>,
+
+    /// 扩展属性
+    xattrs: Mutex<XattrMap>,
+}
+
+/// 映射期间的 tmpfs 文件数据页快照
+///
+/// 与文件共享同一批物理帧：通过映射写入的数据对 read 可见，反之亦然。
+/// 之后文件被截断时，快照仍持有被截掉的帧，直到映射解除。
+#[derive(Debug)]
+struct TmpfsMappedPages {
+    frames: Vec<Arc<FrameTracker>>,
+}
+
+impl SharedPages for TmpfsMappedPages {
+    fn ppn_at(&self, page_idx: usize) -> Option<Ppn> {
+        self.frames.get(page_idx).map(|frame| frame.ppn())
+    }
+
+    fn page_count(&self) -> usize {
+        self.frames.len()
+    }
 }
 
 /// Tmpfs 统计信息
@@ -103,6 +131,7 @@ impl TmpfsInode {
             children: Mutex::new(BTreeMap::new()),
             stats,
             self_ref: Mutex::new(Weak::new()),
+            xattrs: Mutex::new(XattrMap::new()),
         })
     }
 
@@ -150,12 +179,6 @@ impl TmpfsInode {
         stats.allocated_pages = stats.allocated_pages.saturating_sub(num);
     }
 
-    /// 更新访问时间
-    fn update_atime(&self) {
-        let mut meta = self.metadata.lock();
-        meta.atime = TimeSpec::now();
-    }
-
     /// 更新修改时间
     fn update_mtime(&self) {
         let mut meta = self.metadata.lock();
@@ -224,6 +247,94 @@ impl TmpfsInode {
         self.dec_allocated_pages(num_pages);
     }
 
+    /// 为 `[start_page, end_page)` 中的空洞分配物理页，返回新分配的页数
+    fn fill_holes(
+        &self,
+        data: &mut Vec<Option<Arc<FrameTracker>>>,
+        start_page: usize,
+        end_page: usize,
+    ) -> Result<usize, FsError> {
+        if end_page > data.len() {
+            data.resize(end_page, None);
+        }
+
+        let pages_needed = data[start_page..end_page]
+            .iter()
+            .filter(|page| page.is_none())
+            .count();
+        self.reserve_pages(pages_needed)?;
+
+        let mut allocated = Vec::with_capacity(pages_needed);
+        for index in start_page..end_page {
+            if data[index].is_some() {
+                continue;
+            }
+            match Self::alloc_data_frame() {
+                Ok(frame) => {
+                    data[index] = Some(frame);
+                    allocated.push(index);
+                }
+                Err(err) => {
+                    // 回滚本次分配的页，使文件占用与 `blocks` 保持一致
+                    for index in allocated {
+                        data[index] = None;
+                    }
+                    self.cancel_page_reservations(pages_needed);
+                    return Err(err);
+                }
+            }
+        }
+        Ok(pages_needed)
+    }
+
+    /// 写入只对普通文件有效
+    fn check_regular(&self) -> Result<(), FsError> {
+        if self.metadata.lock().inode_type != InodeType::File {
+            return Err(FsError::IsDirectory);
+        }
+        Ok(())
+    }
+
+    /// 持有数据锁时写入 `buf` 并更新文件大小
+    ///
+    /// 文件大小只在持有数据锁时修改，写入、追加和截断因此互相串行，
+    /// 任何会话读到的大小都与数据页一致。
+    fn write_locked(
+        &self,
+        data: &mut Vec<Option<Arc<FrameTracker>>>,
+        offset: usize,
+        buf: &[u8],
+    ) -> Result<usize, FsError> {
+        let mut allocated = 0;
+        if let Some((start_page, end_page)) = Self::page_range(offset, buf.len()) {
+            allocated = self.fill_holes(data, start_page, end_page)?;
+        }
+
+        let mut bytes_written = 0;
+
+        while bytes_written < buf.len() {
+            let page_index = (offset + bytes_written) / PAGE_SIZE;
+            let page_offset = (offset + bytes_written) % PAGE_SIZE;
+            let write_len = (PAGE_SIZE - page_offset).min(buf.len() - bytes_written);
+
+            // 通过内核直接映射写入
+            let frame = data[page_index].as_ref().unwrap();
+            Self::copy_to_page(
+                frame,
+                page_offset,
+                &buf[bytes_written..bytes_written + write_len],
+            );
+
+            bytes_written += write_len;
+        }
+
+        // 更新文件大小
+        let mut meta = self.metadata.lock();
+        meta.size = meta.size.max(offset + bytes_written);
+        meta.blocks += allocated * (PAGE_SIZE / 512); // 以 512B 为单位，空洞不占用
+        Ok(bytes_written)
+    }
+
     fn alloc_data_frame() -> Result<Arc<FrameTracker>, FsError> {
         alloc_frame().map(Arc::new).ok_or(FsError::NoSpace)
     }
@@ -336,76 +447,29 @@ impl Inode for TmpfsInode {
             bytes_read += read_len;
         }
 
-        self.update_atime();
         Ok(bytes_read)
     }
 
     fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, FsError> {
-        let meta = self.metadata.lock();
-
-        if meta.inode_type != InodeType::File {
-            return Err(FsError::IsDirectory);
-        }
-
-        drop(meta);
-
+        self.check_regular()?;
         let mut data = self.data.lock();
-        if let Some((start_page, end_page)) = Self::page_range(offset, buf.len()) {
-            if end_page > data.len() {
-                data.resize(end_page, None);
-            }
-
-            let pages_needed = data[start_page..end_page]
-                .iter()
-                .filter(|page| page.is_none())
-                .count();
-            self.reserve_pages(pages_needed)?;
-
-            let mut allocated = 0;
-            for page in &mut data[start_page..end_page] {
-                if page.is_none() {
-                    match Self::alloc_data_frame() {
-                        Ok(frame) => {
-                            *page = Some(frame);
-                            allocated += 1;
-                        }
-                        Err(err) => {
-                            self.cancel_page_reservations(pages_needed - allocated);
-                            return Err(err);
-                        }
-                    }
-                }
-            }
-        }
-
-        let mut bytes_written = 0;
-
-        while bytes_written < buf.len() {
-            let page_index = (offset + bytes_written) / PAGE_SIZE;
-            let page_offset = (offset + bytes_written) % PAGE_SIZE;
-            let write_len = (PAGE_SIZE - page_offset).min(buf.len() - bytes_written);
-
-            // 通过内核直接映射写入
-            let frame = data[page_index].as_ref().unwrap();
-            Self::copy_to_page(
-                frame,
-                page_offset,
-                &buf[bytes_written..bytes_written + write_len],
-            );
+        let bytes_written = self.write_locked(&mut data, offset, buf)?;
+        drop(data);
 
-            bytes_written += write_len;
-        }
+        self.update_mtime();
+        Ok(bytes_written)
+    }
 
+    fn append(&self, buf: &[u8]) -> Result<(usize, usize), FsError> {
+        self.check_regular()?;
+        // 持有数据锁读取大小，其它会话的写入和截断不会插入其间
+        let mut data = self.data.lock();
+        let offset = self.metadata.lock().size;
+        let bytes_written = self.write_locked(&mut data, offset, buf)?;
         drop(data);
 
-        // 更新文件大小和时间
-        let mut meta = self.metadata.lock();
-        meta.size = meta.size.max(offset + bytes_written);
-        meta.blocks = meta.size.div_ceil(512); // 以 512B 为单位
-        drop(meta);
-
         self.update_mtime();
-        Ok(bytes_written)
+        Ok((offset, bytes_written))
     }
 
     fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>, FsError> {
@@ -635,26 +699,16 @@ impl Inode for TmpfsInode {
     }
 
     fn truncate(&self, new_size: usize) -> Result<(), FsError> {
-        let mut meta = self.metadata.lock();
-
-        if meta.inode_type != InodeType::File {
-            return Err(FsError::IsDirectory);
-        }
+        self.check_regular()?;
 
-        let old_size = meta.size;
+        // 与写入一样先取数据锁，截断不会与其它会话的写入或追加交错
+        let mut data = self.data.lock();
+        let old_size = self.metadata.lock().size;
 
+        let mut pages_to_free = 0;
         if new_size < old_size {
             // 缩小：释放多余的页
             let new_page_count = new_size.div_ceil(PAGE_SIZE);
-            let old_page_count = old_size.div_ceil(PAGE_SIZE);
-
-            let mut data = self.data.lock();
-
-            // 计算要释放的页数
-            let pages_to_free = data[new_page_count..old_page_count.min(data.len())]
-                .iter()
-                .filter(|f| f.is_some())
-                .count();
 
             if new_page_count > 0 {
                 let tail_offset = new_size % PAGE_SIZE;
@@ -665,16 +719,23 @@ impl Inode for TmpfsInode {
                 }
             }
 
+            pages_to_free = data
+                .iter()
+                .skip(new_page_count)
+                .filter(|f| f.is_some())
+                .count();
             data.truncate(new_page_count);
-            drop(data);
-
-            self.dec_allocated_pages(pages_to_free);
         }
 
-        meta.size = new_size;
-        meta.blocks = new_size.div_ceil(512);
-        drop(meta);
+        // 扩展部分是空洞，不分配页
+        {
+            let mut meta = self.metadata.lock();
+            meta.size = new_size;
+            meta.blocks -= pages_to_free * (PAGE_SIZE / 512);
+        }
+        drop(data);
 
+        self.dec_allocated_pages(pages_to_free);
         self.update_mtime();
         Ok(())
     }
@@ -684,6 +745,59 @@ impl Inode for TmpfsInode {
         Ok(())
     }
 
+    fn next_data(&self, offset: usize) -> Result<Option<(usize, usize)>, FsError> {
+        let size = self.metadata.lock().size;
+        if offset >= size {
+            return Ok(None);
+        }
+
+        // 已分配的页即数据，其余均为空洞
+        let data = self.data.lock();
+        let page_count = size.div_ceil(PAGE_SIZE).min(data.len());
+        let Some(start) = (offset / PAGE_SIZE..page_count).find(|&i| data[i].is_some()) else {
+            return Ok(None);
+        };
+        let end = (start..page_count)
+            .find(|&i| data[i].is_none())
+            .unwrap_or(page_count);
+        Ok(Some((start * PAGE_SIZE, (end * PAGE_SIZE).min(size))))
+    }
+
+    fn mmap_pages(&self) -> Result<Arc<dyn SharedPages>, FsError> {
+        let meta = self.metadata.lock();
+        if meta.inode_type != InodeType::File {
+            return Err(FsError::IsDirectory);
+        }
+        let page_count = meta.size.div_ceil(PAGE_SIZE);
+        drop(meta);
+
+        // 映射要求每一页都有物理帧，先补齐空洞
+        let mut data = self.data.lock();
+        let allocated = self.fill_holes(&mut data, 0, page_count)?;
+        let frames = data[..page_count]
+            .iter()
+            .map(|page| page.clone().unwrap())
+            .collect();
+        drop(data);
+        if allocated > 0 {
+            self.metadata.lock().blocks += allocated * (PAGE_SIZE / 512);
+        }
+        Ok(Arc::new(TmpfsMappedPages { frames }))
+    }
+
+    fn mmap(&self, desc: &MmapDesc) -> Result<MmapPolicy, FsError> {
+        // 共享映射直接映射文件页，memfd 经 fd 传递后各进程映射到同一块内存；
+        // 私有映射和越过文件末尾的共享映射走拷贝路径
+        if desc.is_shared()
+            && let Ok(policy) = self
+                .mmap_pages()
+                .and_then(|pages| MmapPolicy::direct(pages, desc))
+        {
+            return Ok(policy);
+        }
+        Ok(MmapPolicy::CopyIn)
+    }
+
     fn as_any(&self) -> &dyn core::any::Any {
         self
     }
@@ -895,6 +1009,35 @@ impl Inode for TmpfsInode {
         if let Some(mtime) = mtime {
             metadata.mtime = mtime;
         }
+        metadata.ctime = TimeSpec::now();
+        Ok(())
+    }
+
+    fn touch_atime(&self) {
+        let mut meta = self.metadata.lock();
+        let now = TimeSpec::now();
+        if atime_needs_update(meta.atime, meta.mtime, meta.ctime, now) {
+            meta.atime = now;
+        }
+    }
+
+    fn getxattr(&self, name: &str) -> Result<Vec<u8>, FsError> {
+        self.xattrs.lock().get(name)
+    }
+
+    fn setxattr(&self, name: &str, value: &[u8], flags: XattrFlags) -> Result<(), FsError> {
+        self.xattrs.lock().set(name, value, flags)?;
+        self.metadata.lock().ctime = TimeSpec::now();
+        Ok(())
+    }
+
+    fn listxattr(&self) -> Result<Vec<String>, FsError> {
+        Ok(self.xattrs.lock().list())
+    }
+
+    fn removexattr(&self, name: &str) -> Result<(), FsError> {
+        self.xattrs.lock().remove(name)?;
+        self.metadata.lock().ctime = TimeSpec::now();
         Ok(())
     }
 
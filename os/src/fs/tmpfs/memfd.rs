@@ -0,0 +1,36 @@
+//! memfd 支持
+//!
+//! memfd 是一个不出现在任何目录中的匿名 tmpfs 文件：
+//! 所有 memfd 都建立在内核私有的 [`TmpFs`] 实例上，创建后立即从其根目录摘除，
+//! 文件生命周期完全由打开它的 fd（以及共享映射）决定。
+//! 通过 fd 传递（fork 继承、exec 后保留等）即可在进程间共享同一块内存。
+
+use alloc::format;
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::vfs::{Dentry, FileMode, FileSystem, FsError};
+
+use super::TmpFs;
+
+lazy_static::lazy_static! {
+    /// 承载所有 memfd 的内核私有 tmpfs（不挂载，不限容量）
+    static ref MEMFD_FS: Arc<TmpFs> = TmpFs::new(0);
+}
+
+/// 内部文件名序号，保证临时目录项不重名
+static MEMFD_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// 创建一个 memfd，返回其目录项
+///
+/// 目录项名为 `memfd:<name>`，与 Linux 在 `/proc/<pid>/fd` 中显示的名字一致。
+pub fn create_memfd(name: &str) -> Result<Arc<Dentry>, FsError> {
+    let root = MEMFD_FS.root_inode();
+    let seq = MEMFD_SEQ.fetch_add(1, Ordering::Relaxed);
+    let tmp_name = format!("{}", seq);
+
+    let inode = root.create(&tmp_name, FileMode::from_bits_truncate(0o777))?;
+    root.unlink(&tmp_name)?;
+
+    Ok(Dentry::new(format!("memfd:{}", name), inode))
+}
@@ -6,6 +6,7 @@
 //!
 //! - [`TmpFs`] - 文件系统结构，实现 `FileSystem` trait
 //! - [`TmpfsInode`](inode::TmpfsInode) - Inode 实现，管理文件/目录数据
+//! - [`create_memfd`] - 在内核私有 tmpfs 上创建匿名文件（memfd_create）
 //!
 //! # 设计概览
 //!
@@ -27,10 +28,10 @@
 //! use crate::fs::mount_tmpfs;
 //!
 //! // 挂载 64MB tmpfs 到 /tmp
-//! mount_tmpfs("/tmp", 64)?;
+//! mount_tmpfs("/tmp", 64, MountFlags::empty())?;
 //!
 //! // 无限制大小
-//! mount_tmpfs("/run", 0)?;
+//! mount_tmpfs("/run", 0, MountFlags::empty())?;
 //! ```
 //!
 //! # 性能特点
@@ -40,6 +41,8 @@
 //! - **并发安全**：使用 `SpinLock` 保护共享数据
 
 mod inode;
+mod memfd;
 mod tmpfs;
 
+pub use memfd::create_memfd;
 pub use tmpfs::TmpFs;
@@ -4,6 +4,7 @@ use alloc::sync::Arc;
 
 use crate::config::PAGE_SIZE;
 use crate::sync::Mutex;
+use crate::uapi::fs::FileSystemType;
 use crate::vfs::{FileSystem, FsError, Inode, StatFs};
 
 use super::inode::{TmpfsInode, TmpfsStats};
@@ -89,6 +90,7 @@ impl FileSystem for TmpFs {
         };
 
         Ok(StatFs {
+            fs_type: FileSystemType::Tmpfs,
             block_size: PAGE_SIZE,
             total_blocks,
             free_blocks,
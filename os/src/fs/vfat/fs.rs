@@ -6,6 +6,7 @@ use crate::device::block::BlockDriver;
 use crate::fs::vfat::adapter::{FatBlockDevice, VfatIoError};
 use crate::fs::vfat::inode::VfatInode;
 use crate::sync::Mutex;
+use crate::uapi::fs::FileSystemType;
 use crate::vfs::{FileSystem, FsError, Inode, StatFs};
 
 pub(super) type FatFs = fatfs::FileSystem<FatBlockDevice>;
@@ -101,6 +102,7 @@ impl FileSystem for VfatFileSystem {
         self.state.with_fs(|fs| {
             let stats = fs.stats().map_err(map_fat_error)?;
             Ok(StatFs {
+                fs_type: FileSystemType::Msdos,
                 block_size: stats.cluster_size() as usize,
                 total_blocks: stats.total_clusters() as usize,
                 free_blocks: stats.free_clusters() as usize,
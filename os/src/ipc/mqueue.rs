@@ -0,0 +1,514 @@
+//! POSIX 消息队列
+//!
+//! 队列按名字登记在全局注册表中，`mq_open` 返回一个 [`MqFile`] 作为消息队列描述符：
+//! - 每个队列有独立的 `mq_maxmsg` / `mq_msgsize` 上限，创建时确定；
+//! - 接收总是取优先级最高的消息，同优先级按发送顺序；
+//! - 队列从空变为非空且没有阻塞中的接收者时，触发一次 `mq_notify` 注册的信号通知；
+//! - `mq_unlink` 只从注册表摘除名字，已打开的描述符仍可继续收发，最后一个描述符关闭后队列释放。
+//!
+//! 阻塞等待由 syscall 层完成：本模块提供非阻塞的收发原语，以及在队列满 / 空时
+//! 登记到队列自身等待队列上的 [`PosixMq::prepare_wait`]，发送和接收成功后唤醒对侧的等待者。
+
+use alloc::{
+    collections::{btree_map::BTreeMap, vec_deque::VecDeque},
+    string::String,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use core::ffi::c_int;
+
+use lazy_static::lazy_static;
+
+use crate::{
+    kernel::{
+        Capabilities, SharedTask, TaskStruct, WaitQueue, current_task, send_signal_process,
+        sleep_task_prepare,
+    },
+    sync::SpinLock,
+    uapi::{
+        errno::{EACCES, EAGAIN, EBUSY, EEXIST, EINVAL, EMSGSIZE, ENAMETOOLONG, ENOENT, ENOMEM},
+        mqueue::{
+            DFLT_MSGMAX, DFLT_MSGSIZEMAX, MQ_BYTES_LIMIT, MQ_MAXMSG_LIMIT, MQ_MSGSIZE_LIMIT,
+            MQ_NAME_MAX, MQ_PRIO_MAX, MqAttr,
+        },
+        signal::NSIG,
+        time::TimeSpec,
+    },
+    vfs::{File, FileMode, FsError, InodeMetadata, InodeType, OpenFlags},
+};
+
+/// 已登记的通知
+#[derive(Debug)]
+struct MqNotify {
+    /// 注册通知的进程（线程组 leader 或注册线程）
+    task: Weak<SpinLock<TaskStruct>>,
+    /// 注册进程的 pid
+    pid: u32,
+    /// 通知信号；`SIGEV_NONE` 时为 None
+    signo: Option<usize>,
+}
+
+#[derive(Debug)]
+struct MqState {
+    /// 优先级 -> 该优先级的消息（FIFO）
+    messages: BTreeMap<u32, VecDeque<Vec<u8>>>,
+    /// 当前消息数
+    curmsgs: usize,
+    /// 已登记的通知
+    notify: Option<MqNotify>,
+    /// 阻塞在接收中的任务数；有接收者等待时不发送通知
+    waiting_receivers: usize,
+    /// 等待队列变为非满的发送者
+    senders: WaitQueue,
+    /// 等待队列变为非空的接收者
+    receivers: WaitQueue,
+}
+
+/// POSIX 消息队列对象
+#[derive(Debug)]
+pub struct PosixMq {
+    /// 最大消息数
+    maxmsg: usize,
+    /// 单条消息最大字节数
+    msgsize: usize,
+    /// 权限位
+    mode: u32,
+    /// 属主
+    uid: u32,
+    /// 属组
+    gid: u32,
+    state: SpinLock<MqState>,
+}
+
+impl PosixMq {
+    fn new(maxmsg: usize, msgsize: usize, mode: u32) -> Self {
+        let cred = current_task().lock().credential;
+        Self {
+            maxmsg,
+            msgsize,
+            mode: mode & 0o777,
+            uid: cred.euid,
+            gid: cred.egid,
+            state: SpinLock::new(MqState {
+                messages: BTreeMap::new(),
+                curmsgs: 0,
+                notify: None,
+                waiting_receivers: 0,
+                senders: WaitQueue::new(),
+                receivers: WaitQueue::new(),
+            }),
+        }
+    }
+
+    /// 最大消息数
+    pub fn maxmsg(&self) -> usize {
+        self.maxmsg
+    }
+
+    /// 单条消息最大字节数
+    pub fn msgsize(&self) -> usize {
+        self.msgsize
+    }
+
+    /// 当前消息数
+    pub fn curmsgs(&self) -> usize {
+        self.state.lock().curmsgs
+    }
+
+    /// 非阻塞发送；队列满时返回 `EAGAIN`
+    pub fn try_send(&self, data: &[u8], prio: u32) -> Result<(), c_int> {
+        if prio >= MQ_PRIO_MAX {
+            return Err(EINVAL);
+        }
+        if data.len() > self.msgsize {
+            return Err(EMSGSIZE);
+        }
+
+        let mut st = self.state.lock();
+        if st.curmsgs >= self.maxmsg {
+            return Err(EAGAIN);
+        }
+        let was_empty = st.curmsgs == 0;
+        st.messages
+            .entry(prio)
+            .or_default()
+            .push_back(data.to_vec());
+        st.curmsgs += 1;
+        st.receivers.wake_up_all();
+        let notify = if was_empty && st.waiting_receivers == 0 {
+            st.notify.take()
+        } else {
+            None
+        };
+        drop(st);
+
+        crate::kernel::syscall::io::wake_poll_waiters();
+        if let Some(notify) = notify
+            && let Some(signo) = notify.signo
+            && let Some(task) = notify.task.upgrade()
+        {
+            send_signal_process(&task, signo);
+        }
+        Ok(())
+    }
+
+    /// 非阻塞接收优先级最高的消息；队列空时返回 `EAGAIN`
+    ///
+    /// `buf_len` 小于队列的 `mq_msgsize` 时返回 `EMSGSIZE`（与 Linux 相同，与消息实际长度无关）。
+    pub fn try_receive(&self, buf_len: usize) -> Result<(Vec<u8>, u32), c_int> {
+        if buf_len < self.msgsize {
+            return Err(EMSGSIZE);
+        }
+
+        let mut st = self.state.lock();
+        let mut entry = st.messages.last_entry().ok_or(EAGAIN)?;
+        let prio = *entry.key();
+        let data = entry.get_mut().pop_front().unwrap();
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+        st.curmsgs -= 1;
+        st.senders.wake_up_all();
+        drop(st);
+
+        crate::kernel::syscall::io::wake_poll_waiters();
+        Ok((data, prio))
+    }
+
+    /// 标记一个接收者开始 / 结束阻塞等待
+    pub fn set_receiver_waiting(&self, waiting: bool) {
+        let mut st = self.state.lock();
+        if waiting {
+            st.waiting_receivers += 1;
+        } else {
+            st.waiting_receivers -= 1;
+        }
+    }
+
+    /// 队列仍满（`sender` 为 true）或仍空时，把 `task` 登记到对应的等待队列并准备睡眠
+    ///
+    /// 检查与登记在同一把锁下完成，不会错过其间的收发。返回 true 时调用者应让出 CPU，
+    /// 醒来后调用 [`Self::finish_wait`]；返回 false 表示状态已变化或有待处理的信号，无需睡眠。
+    pub fn prepare_wait(&self, task: &SharedTask, sender: bool) -> bool {
+        let mut st = self.state.lock();
+        let blocked = if sender {
+            st.curmsgs >= self.maxmsg
+        } else {
+            st.curmsgs == 0
+        };
+        if !blocked {
+            return false;
+        }
+        let waitq = if sender {
+            &mut st.senders
+        } else {
+            &mut st.receivers
+        };
+        waitq.add_task(task.clone());
+        if !sleep_task_prepare(task.clone(), true, |t| crate::ipc::sig_pending(t)) {
+            waitq.remove_task(task);
+            return false;
+        }
+        true
+    }
+
+    /// 结束一次等待，把 `task` 从等待队列摘除（超时或被信号唤醒时仍在队列中）
+    pub fn finish_wait(&self, task: &SharedTask) {
+        let mut st = self.state.lock();
+        st.senders.remove_task(task);
+        st.receivers.remove_task(task);
+    }
+
+    /// 为当前进程登记通知；`signo` 为 None 表示 `SIGEV_NONE`
+    ///
+    /// 已有其它登记时返回 `EBUSY`。
+    pub fn register_notify(&self, task: &SharedTask, signo: Option<usize>) -> Result<(), c_int> {
+        if signo.is_some_and(|sig| sig == 0 || sig > NSIG) {
+            return Err(EINVAL);
+        }
+        let pid = task.lock().pid;
+        let mut st = self.state.lock();
+        if let Some(notify) = &st.notify
+            && notify.task.strong_count() > 0
+        {
+            return Err(EBUSY);
+        }
+        st.notify = Some(MqNotify {
+            task: Arc::downgrade(task),
+            pid,
+            signo,
+        });
+        Ok(())
+    }
+
+    /// 撤销当前进程的通知登记；不是登记者时静默忽略
+    pub fn unregister_notify(&self, pid: u32) {
+        let mut st = self.state.lock();
+        if st.notify.as_ref().is_some_and(|n| n.pid == pid) {
+            st.notify = None;
+        }
+    }
+
+    fn check_access(&self, requested: u32) -> Result<(), c_int> {
+        if requested == 0 {
+            return Ok(());
+        }
+        let cred = current_task().lock().credential;
+        if cred.capabilities.has(Capabilities::DAC_OVERRIDE) {
+            return Ok(());
+        }
+        let available = if cred.euid == self.uid {
+            (self.mode >> 6) & 0o7
+        } else if cred.in_egroup(self.gid) {
+            (self.mode >> 3) & 0o7
+        } else {
+            self.mode & 0o7
+        };
+        if available & requested == requested {
+            Ok(())
+        } else {
+            Err(EACCES)
+        }
+    }
+}
+
+lazy_static! {
+    /// 队列名 -> 队列
+    static ref MQ_REGISTRY: SpinLock<BTreeMap<String, Arc<PosixMq>>> =
+        SpinLock::new(BTreeMap::new());
+}
+
+/// 校验队列名（用户态库已去掉开头的 `/`）
+fn check_mq_name(name: &str) -> Result<(), c_int> {
+    if name.is_empty() {
+        return Err(ENOENT);
+    }
+    if name.len() > MQ_NAME_MAX {
+        return Err(ENAMETOOLONG);
+    }
+    if name.contains('/') {
+        return Err(EACCES);
+    }
+    Ok(())
+}
+
+/// 按属性确定新队列的容量
+fn queue_limits(attr: Option<&MqAttr>) -> Result<(usize, usize), c_int> {
+    let Some(attr) = attr else {
+        return Ok((DFLT_MSGMAX, DFLT_MSGSIZEMAX));
+    };
+    if attr.mq_maxmsg <= 0 || attr.mq_msgsize <= 0 {
+        return Err(EINVAL);
+    }
+    let (maxmsg, msgsize) = (attr.mq_maxmsg as usize, attr.mq_msgsize as usize);
+    if maxmsg > MQ_MAXMSG_LIMIT || msgsize > MQ_MSGSIZE_LIMIT {
+        return Err(EINVAL);
+    }
+    if maxmsg * msgsize > MQ_BYTES_LIMIT {
+        return Err(ENOMEM);
+    }
+    Ok((maxmsg, msgsize))
+}
+
+/// 打开（必要时创建）名为 `name` 的队列
+///
+/// `mode` 应已去掉 umask 屏蔽的位。
+pub fn mq_open_queue(
+    name: &str,
+    oflag: OpenFlags,
+    mode: u32,
+    attr: Option<&MqAttr>,
+) -> Result<Arc<PosixMq>, c_int> {
+    check_mq_name(name)?;
+
+    let mut registry = MQ_REGISTRY.lock();
+    if let Some(queue) = registry.get(name) {
+        if oflag.contains(OpenFlags::O_CREAT) && oflag.contains(OpenFlags::O_EXCL) {
+            return Err(EEXIST);
+        }
+        let mut requested = 0;
+        if oflag.readable() {
+            requested |= 0o4;
+        }
+        if oflag.writable() {
+            requested |= 0o2;
+        }
+        queue.check_access(requested)?;
+        return Ok(queue.clone());
+    }
+
+    if !oflag.contains(OpenFlags::O_CREAT) {
+        return Err(ENOENT);
+    }
+    let (maxmsg, msgsize) = queue_limits(attr)?;
+    let queue = Arc::new(PosixMq::new(maxmsg, msgsize, mode));
+    registry.insert(String::from(name), queue.clone());
+    Ok(queue)
+}
+
+/// 从注册表中删除队列名
+pub fn mq_unlink_queue(name: &str) -> Result<(), c_int> {
+    check_mq_name(name)?;
+    MQ_REGISTRY.lock().remove(name).map(|_| ()).ok_or(ENOENT)
+}
+
+/// 消息队列描述符
+pub struct MqFile {
+    queue: Arc<PosixMq>,
+    /// 打开标志（访问模式与 O_NONBLOCK）
+    flags: SpinLock<OpenFlags>,
+}
+
+impl MqFile {
+    pub fn new(queue: Arc<PosixMq>, flags: OpenFlags) -> Self {
+        Self {
+            queue,
+            flags: SpinLock::new(flags),
+        }
+    }
+
+    /// 关联的队列
+    pub fn queue(&self) -> &Arc<PosixMq> {
+        &self.queue
+    }
+}
+
+impl File for MqFile {
+    fn readable(&self) -> bool {
+        self.flags.lock().readable()
+    }
+
+    fn writable(&self) -> bool {
+        self.flags.lock().writable()
+    }
+
+    fn read_ready(&self) -> bool {
+        self.readable() && self.queue.curmsgs() > 0
+    }
+
+    fn write_ready(&self) -> bool {
+        self.writable() && self.queue.curmsgs() < self.queue.maxmsg
+    }
+
+    fn read(&self, _buf: &mut [u8]) -> Result<usize, FsError> {
+        // 消息通过 mq_timedreceive 接收
+        Err(FsError::InvalidArgument)
+    }
+
+    fn write(&self, _buf: &[u8]) -> Result<usize, FsError> {
+        // 消息通过 mq_timedsend 发送
+        Err(FsError::InvalidArgument)
+    }
+
+    fn metadata(&self) -> Result<InodeMetadata, FsError> {
+        Ok(InodeMetadata {
+            inode_no: 0,
+            inode_type: InodeType::File,
+            size: 0,
+            mode: FileMode::S_IFREG | FileMode::from_bits_truncate(self.queue.mode),
+            uid: self.queue.uid,
+            gid: self.queue.gid,
+            atime: TimeSpec::zero(),
+            mtime: TimeSpec::zero(),
+            ctime: TimeSpec::zero(),
+            nlinks: 1,
+            blocks: 0,
+            rdev: 0,
+        })
+    }
+
+    fn flags(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_status_flags(&self, new_flags: OpenFlags) -> Result<(), FsError> {
+        let mut flags = self.flags.lock();
+        flags.set(
+            OpenFlags::O_NONBLOCK,
+            new_flags.contains(OpenFlags::O_NONBLOCK),
+        );
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+    use alloc::vec;
+
+    fn attr(maxmsg: usize, msgsize: usize) -> MqAttr {
+        MqAttr {
+            mq_maxmsg: maxmsg as _,
+            mq_msgsize: msgsize as _,
+            ..MqAttr::default()
+        }
+    }
+
+    test_case!(test_mq_priority_order, {
+        let queue = PosixMq::new(4, 16, 0o600);
+        queue.try_send(&[1], 1).unwrap();
+        queue.try_send(&[2], 5).unwrap();
+        queue.try_send(&[3], 1).unwrap();
+
+        kassert!(queue.try_receive(16) == Ok((vec![2], 5)));
+        kassert!(queue.try_receive(16) == Ok((vec![1], 1)));
+        kassert!(queue.try_receive(16) == Ok((vec![3], 1)));
+        kassert!(queue.try_receive(16) == Err(EAGAIN));
+    });
+
+    test_case!(test_mq_limits, {
+        let queue = PosixMq::new(2, 4, 0o600);
+        kassert!(queue.try_send(&[0; 5], 0) == Err(EMSGSIZE));
+        kassert!(queue.try_send(&[0], MQ_PRIO_MAX) == Err(EINVAL));
+        queue.try_send(&[0; 4], 0).unwrap();
+        queue.try_send(&[0; 4], 0).unwrap();
+        kassert!(queue.try_send(&[0], 0) == Err(EAGAIN));
+        kassert!(queue.curmsgs() == 2);
+
+        // 接收缓冲区必须不小于 mq_msgsize
+        kassert!(queue.try_receive(3) == Err(EMSGSIZE));
+
+        kassert!(queue_limits(Some(&attr(0, 4))) == Err(EINVAL));
+        kassert!(queue_limits(Some(&attr(MQ_MAXMSG_LIMIT + 1, 4))) == Err(EINVAL));
+        kassert!(queue_limits(None) == Ok((DFLT_MSGMAX, DFLT_MSGSIZEMAX)));
+    });
+
+    test_case!(test_mq_prepare_wait_rechecks_state, {
+        // 队列状态已允许收发时不登记等待
+        let queue = PosixMq::new(1, 4, 0o600);
+        let task = current_task();
+        kassert!(!queue.prepare_wait(&task, true));
+        queue.try_send(&[0], 0).unwrap();
+        kassert!(!queue.prepare_wait(&task, false));
+
+        let st = queue.state.lock();
+        kassert!(!st.senders.contains(&task) && !st.receivers.contains(&task));
+    });
+
+    test_case!(test_mq_registry_open_and_unlink, {
+        let name = "comix-test-mq";
+        let create = OpenFlags::O_CREAT | OpenFlags::O_EXCL | OpenFlags::O_RDWR;
+        kassert!(mq_open_queue(name, OpenFlags::O_RDWR, 0o600, None).err() == Some(ENOENT));
+
+        let queue = mq_open_queue(name, create, 0o600, Some(&attr(3, 8))).unwrap();
+        kassert!(queue.maxmsg() == 3 && queue.msgsize() == 8);
+        kassert!(mq_open_queue(name, create, 0o600, None).err() == Some(EEXIST));
+
+        let again = mq_open_queue(name, OpenFlags::O_RDONLY, 0, None).unwrap();
+        kassert!(Arc::ptr_eq(&queue, &again));
+
+        // 删除名字后已打开的队列仍然可用
+        mq_unlink_queue(name).unwrap();
+        kassert!(mq_unlink_queue(name) == Err(ENOENT));
+        queue.try_send(&[7], 0).unwrap();
+        kassert!(again.try_receive(8) == Ok((vec![7], 0)));
+
+        kassert!(check_mq_name("a/b") == Err(EACCES));
+        kassert!(check_mq_name("") == Err(ENOENT));
+    });
+}
@@ -11,6 +11,7 @@ use crate::{
     mm::{
         address::{PageNum, Ppn},
         frame_allocator::{FrameTracker, alloc_frames},
+        memory_space::mapping_area::SharedPages,
     },
     sync::SpinLock,
     uapi::{
@@ -141,6 +142,16 @@ impl ShmSegment {
     }
 }
 
+impl SharedPages for ShmSegment {
+    fn ppn_at(&self, page_idx: usize) -> Option<Ppn> {
+        ShmSegment::ppn_at(self, page_idx)
+    }
+
+    fn page_count(&self) -> usize {
+        self.pages()
+    }
+}
+
 #[derive(Debug)]
 struct ShmRegistry {
     next_id: c_int,
@@ -279,7 +290,7 @@ fn shm_check_mode_access(segment: &ShmSegment, requested: u32) -> Result<(), c_i
 
     let available = if cred.euid == segment.uid || cred.euid == segment.cuid {
         (segment.mode >> 6) & 0o7
-    } else if cred.egid == segment.gid || cred.egid == segment.cgid {
+    } else if cred.in_egroup(segment.gid) || cred.in_egroup(segment.cgid) {
         (segment.mode >> 3) & 0o7
     } else {
         segment.mode & 0o7
@@ -8,23 +8,218 @@
 //! - **返回：** 内核退出，任务在用户态执行信号处理函数。
 //! - **循环：** 当信号处理函数执行完毕，通过 `rt_sigreturn` 返回内核后，内核会**再次**进入检查流程。
 //!             此时，它可能会发现队列中还有第二个未决信号，然后开始第二次单次投递。
+//!
+//! # 作业控制
+//! - 停止信号（SIGSTOP/SIGTSTP/SIGTTIN/SIGTTOU）按默认动作投递时，向线程组的所有线程
+//!   发出停止请求，各线程在返回用户态前停下（Stopped 状态不参与调度）；
+//!   线程组 leader 记下停止事件并通知父进程，父进程用 `wait4(WUNTRACED)` 取走。
+//! - SIGCONT 在**产生**时（[`prepare_signal`]）立即恢复已停止的线程，不论它是否被屏蔽
+//!   或捕获；leader 记下继续事件，父进程用 `wait4(WCONTINUED)` 取走。
+//! - 停止期间其它信号照常挂起，SIGKILL 会恢复线程使其退出。
+//!
+//! # 默认动作
+//! 处理方式为 SIG_DFL 时按 [`default_action`]（与 signal(7) 一致）终止、终止并 core dump、
+//! 忽略、停止或继续。被忽略且未被屏蔽的信号在产生时即被丢弃（[`sig_ignored`]）；
+//! 故障信号由 [`force_signal`] 产生，不能被屏蔽或忽略。execve 后捕获的信号恢复为
+//! SIG_DFL，忽略的信号保持忽略（[`SignalHandlerTable::for_exec`]）。
+//! SIGKILL 和 SIGSTOP 不能被捕获、忽略或屏蔽。
+//!
+//! # 系统调用重启
+//! 可被信号打断的阻塞系统调用返回内核内部错误码 `ERESTART*`，返回用户态前由
+//! [`check_signal`] 按投递结果决定（[`syscall_restart_action`]）：回退 PC 重新执行
+//! 系统调用，或向用户态返回 `EINTR`。`ERESTART_RESTARTBLOCK` 改为重新执行
+//! `restart_syscall`，后者按任务保存的 [`RestartBlock`] 继续剩余的工作。
 
+use alloc::collections::VecDeque;
 use bitflags::bitflags;
 
 use crate::{
-    arch::{HwTrapFrame, TrapFrame},
+    arch::{HwTrapFrame, TrapFrame, address::UA},
     kernel::{
-        SharedTask, TASK_MANAGER, TaskExitStatus, TaskManagerTrait, TaskState,
-        cleanup_process_resources_on_exit, current_cpu, current_task, exit_process_with_status,
-        exit_task, schedule, sleep_task, task_group_leader, wake_up_task, yield_task,
+        SharedTask, TASK_MANAGER, TaskExitStatus, TaskManagerTrait, TaskState, TaskStruct,
+        cleanup_process_resources_on_exit, continue_task, current_cpu, current_task,
+        exit_process_with_status, exit_task, notify_parent_jobctl, schedule, stop_task_prepare,
+        syscall::{numbers::SYS_RESTART_SYSCALL, syscall_frame::SyscallFrame},
+        task_group_leader,
     },
     pr_err,
-    uapi::signal::*,
+    uapi::{
+        errno::{
+            EAGAIN, EINTR, EINVAL, ERESTART_RESTARTBLOCK, ERESTARTNOHAND, ERESTARTNOINTR,
+            ERESTARTSYS,
+        },
+        resource::ResourceId,
+        signal::*,
+    },
     util::{address::align_down, user_buffer::write_to_user},
 };
 
 const USER_STACK_GROWTH_GUARD: usize = 128;
 
+/// 线程组的作业控制状态变化，等待父进程通过 wait4 取走
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobCtlReport {
+    /// 被指定信号停止
+    Stopped(usize),
+    /// 被 SIGCONT 恢复
+    Continued,
+}
+
+/// 任务的作业控制状态
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JobCtl {
+    /// 线程组正在停止，本线程须在返回用户态前停下
+    pub stop_pending: bool,
+    /// 尚未被父进程取走的状态变化，只记录在线程组 leader 上
+    pub report: Option<JobCtlReport>,
+}
+
+/// 信号处理方式为 SIG_DFL 时的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigDefaultAction {
+    /// 终止进程
+    Terminate,
+    /// 终止进程并生成 core dump
+    CoreDump,
+    /// 忽略
+    Ignore,
+    /// 停止线程组
+    Stop,
+    /// 恢复已停止的线程组，未停止时等同于忽略
+    Continue,
+}
+
+/// 信号的默认动作，与 Linux 的 signal(7) 一致
+pub fn default_action(sig: usize) -> SigDefaultAction {
+    match sig {
+        NUM_SIGQUIT | NUM_SIGILL | NUM_SIGTRAP | NUM_SIGABRT | NUM_SIGBUS | NUM_SIGFPE
+        | NUM_SIGSEGV | NUM_SIGXCPU | NUM_SIGXFSZ | NUM_SIGSYS => SigDefaultAction::CoreDump,
+        NUM_SIGCHLD | NUM_SIGURG | NUM_SIGWINCH => SigDefaultAction::Ignore,
+        NUM_SIGSTOP | NUM_SIGTSTP | NUM_SIGTTIN | NUM_SIGTTOU => SigDefaultAction::Stop,
+        NUM_SIGCONT => SigDefaultAction::Continue,
+        // SIGHUP、SIGINT、SIGKILL、SIGPIPE、SIGALRM、SIGTERM、SIGUSR1/2、SIGSTKFLT、
+        // SIGVTALRM、SIGPROF、SIGIO、SIGPWR 以及所有实时信号
+        _ => SigDefaultAction::Terminate,
+    }
+}
+
+/// 处理动作 `action` 是否使信号 `sig` 被忽略
+///
+/// SIG_IGN，或 SIG_DFL 且默认动作为忽略/继续（继续已在产生时由 [`prepare_signal`] 完成）。
+pub fn action_ignores(action: &SignalAction, sig: usize) -> bool {
+    match unsafe { action.sa_handler() } as isize {
+        SIG_IGN => true,
+        SIG_DFL => matches!(
+            default_action(sig),
+            SigDefaultAction::Ignore | SigDefaultAction::Continue
+        ),
+        _ => false,
+    }
+}
+
+/// 信号产生时是否直接丢弃，对应 Linux 的 `sig_ignored`
+///
+/// 被屏蔽的信号即使被忽略也要挂起，之后可能被解除屏蔽前改为捕获，或被 sigtimedwait 取走。
+pub fn sig_ignored(task: &TaskStruct, sig: usize) -> bool {
+    let Some(flag) = SignalFlags::from_signal_num(sig) else {
+        return false;
+    };
+    if task.blocked.contains(flag) {
+        return false;
+    }
+    action_ignores(&task.signal_handlers.lock().actions[sig], sig)
+}
+
+/// 任务可排队的信号实例数上限（RLIMIT_SIGPENDING 软限制）
+pub fn sigpending_limit(task: &TaskStruct) -> usize {
+    task.rlimit.lock().limits[ResourceId::Sigpending as usize].rlim_cur
+}
+
+/// 同步产生的故障信号（SIGSEGV、SIGILL 等），对应 Linux 的 `force_sig`
+///
+/// 故障信号不能被屏蔽或忽略：当前被屏蔽或忽略时恢复为 SIG_DFL 并解除屏蔽，
+/// 然后挂到线程私有的 pending 上，在返回用户态前投递。
+pub fn force_signal(task: &SharedTask, sig: usize) {
+    let Some(flag) = SignalFlags::from_signal_num(sig) else {
+        return;
+    };
+    let mut t = task.lock();
+    {
+        let mut handlers = t.signal_handlers.lock();
+        let ignored = unsafe { handlers.actions[sig].sa_handler() } as isize == SIG_IGN;
+        if ignored || t.blocked.contains(flag) {
+            handlers.set_action(sig, SignalAction::default());
+        }
+    }
+    t.blocked.remove(flag);
+    let _ = t
+        .pending
+        .enqueue(QueuedSignal::new(sig, SI_KERNEL, 0, 0), usize::MAX);
+}
+
+/// 默认动作为停止进程的信号
+pub fn is_stop_signal(sig: usize) -> bool {
+    default_action(sig) == SigDefaultAction::Stop
+}
+
+/// 产生时需要 [`prepare_signal`] 处理的信号
+pub fn is_job_control_signal(sig: usize) -> bool {
+    is_stop_signal(sig) || sig == NUM_SIGCONT || sig == NUM_SIGKILL
+}
+
+/// 信号产生时的作业控制处理，对应 Linux 的 `prepare_signal`
+///
+/// 在信号挂入 pending 之前调用，`threads` 是目标线程组的全部线程：
+/// - 停止信号丢弃挂起的 SIGCONT；
+/// - SIGCONT 丢弃挂起的停止信号，撤销停止请求并恢复已停止的线程；
+/// - SIGKILL 恢复已停止的线程，使其能够处理 SIGKILL 退出。
+///
+/// SIGCONT 使线程组从停止状态恢复时返回 leader，调用者须通知其父进程。
+pub fn prepare_signal(sig: usize, threads: &[SharedTask]) -> Option<SharedTask> {
+    if is_stop_signal(sig) {
+        discard_pending(threads, SignalFlags::from_signal_num(NUM_SIGCONT).unwrap());
+        return None;
+    }
+    if sig != NUM_SIGCONT && sig != NUM_SIGKILL {
+        return None;
+    }
+    if sig == NUM_SIGCONT {
+        let stop_mask = [NUM_SIGSTOP, NUM_SIGTSTP, NUM_SIGTTIN, NUM_SIGTTOU]
+            .into_iter()
+            .filter_map(SignalFlags::from_signal_num)
+            .fold(SignalFlags::empty(), |mask, flag| mask | flag);
+        discard_pending(threads, stop_mask);
+    }
+
+    let mut was_stopped = false;
+    for thread in threads {
+        let stopped = {
+            let mut t = thread.lock();
+            was_stopped |= t.jobctl.stop_pending || t.state == TaskState::Stopped;
+            t.jobctl.stop_pending = false;
+            t.state == TaskState::Stopped
+        };
+        if stopped {
+            continue_task(thread.clone());
+        }
+    }
+    if sig != NUM_SIGCONT || !was_stopped {
+        return None;
+    }
+    let leader = threads.iter().find(|t| t.lock().is_process())?.clone();
+    leader.lock().jobctl.report = Some(JobCtlReport::Continued);
+    Some(leader)
+}
+
+/// 从线程组的私有和共享 pending 中移除 `mask`
+pub fn discard_pending(threads: &[SharedTask], mask: SignalFlags) {
+    for thread in threads {
+        let mut t = thread.lock();
+        t.pending.discard(mask);
+        t.shared_pending.lock().discard(mask);
+    }
+}
+
 /// 信号的动作表
 /// 每个进程拥有一个独立的信号处理动作表，
 /// 用于存储每个信号的处理函数、屏蔽字和标志。
@@ -56,6 +251,22 @@ impl SignalHandlerTable {
         }
         self.actions[sig] = action;
     }
+
+    /// execve 后的新动作表
+    ///
+    /// 旧程序的处理函数在新地址空间中无意义：捕获的信号恢复为 SIG_DFL，
+    /// 忽略的信号保持 SIG_IGN；标志和屏蔽字一律清空。
+    pub fn for_exec(&self) -> Self {
+        Self {
+            actions: core::array::from_fn(|sig| {
+                if unsafe { self.actions[sig].sa_handler() } as isize == SIG_IGN {
+                    SignalAction::new(SIG_IGN as *mut _, SaFlags::empty(), SignalFlags::empty())
+                } else {
+                    SignalAction::default()
+                }
+            }),
+        }
+    }
 }
 
 /// 找出第一个可投递的信号（未被屏蔽且挂起的信号中编号最小的）
@@ -79,29 +290,28 @@ pub fn first_deliverable_signal(
 }
 
 #[inline]
-fn handle_one_signal(sig_flag: SignalFlags, action: SignalAction, task: &SharedTask) {
-    let sig_num = signal_from_flag(sig_flag).unwrap();
+fn handle_one_signal(info: QueuedSignal, action: SignalAction, task: &SharedTask) {
+    let sig_num = info.signo;
 
     match unsafe { action.sa_handler() } as isize {
-        SIG_DFL => match sig_num {
-            NUM_SIGQUIT | NUM_SIGILL | NUM_SIGABRT | NUM_SIGBUS | NUM_SIGFPE | NUM_SIGSEGV
-            | NUM_SIGSYS | NUM_SIGXCPU | NUM_SIGXFSZ => sig_dump(sig_num), // 致命错误，调用退出系统调用或内核退出函数
-
-            NUM_SIGHUP | NUM_SIGINT | NUM_SIGPIPE | NUM_SIGALRM | NUM_SIGTERM | NUM_SIGUSR1
-            | NUM_SIGUSR2 | NUM_SIGSTKFLT | NUM_SIGPROF | NUM_SIGPWR => sig_terminate(sig_num), // 默认终止
-            NUM_SIGKILL => sig_terminate(sig_num), // SIGKILL 总是终止
-            NUM_SIGSTOP | NUM_SIGTSTP | NUM_SIGTTIN | NUM_SIGTTOU => sig_stop(sig_num),
-            NUM_SIGCONT => sig_continue(sig_num),
-            NUM_SIGCHLD | NUM_SIGURG | NUM_SIGWINCH | NUM_SIGIO => sig_ignore(sig_num),
-            NUM_SIGRTMIN..=NUM_SIGRTMAX => sig_terminate(sig_num),
-            _ => sig_terminate(sig_num),
+        SIG_DFL => match default_action(sig_num) {
+            SigDefaultAction::Terminate => sig_terminate(sig_num),
+            SigDefaultAction::CoreDump => sig_dump(sig_num),
+            SigDefaultAction::Ignore => sig_ignore(sig_num),
+            SigDefaultAction::Stop => sig_stop(sig_num),
+            SigDefaultAction::Continue => sig_continue(sig_num),
         },
         SIG_IGN => sig_ignore(sig_num),
         handler_addr => {
-            // 自定义处理器：构造用户栈上下文并跳转
-            install_user_signal_trap_frame(task, sig_num, handler_addr, action);
+            // 自定义处理器：先决定被打断的系统调用是否重启，再构造用户栈上下文并跳转，
+            // 这样 ucontext 中保存的就是重启后的 PC
+            let flags = SaFlags::from_bits_truncate(action.sa_flags as u32);
+            restart_interrupted_syscall(task, Some(flags));
+            install_user_signal_trap_frame(task, info, handler_addr, action);
+            return;
         }
     }
+    restart_interrupted_syscall(task, None);
 }
 
 /// 在返回用户态前检查信号并处理
@@ -111,48 +321,96 @@ fn handle_one_signal(sig_flag: SignalFlags, action: SignalAction, task: &SharedT
 /// 如果没有可投递的信号，则直接返回。
 pub fn check_signal() {
     let task = current_task();
-    let (sig_flag, action) = {
+    if task.lock().jobctl.stop_pending {
+        do_signal_stop(&task);
+    }
+    let next = 'next: {
         let mut t = task.lock();
-        let pending_copy = t.pending.clone();
-        let shared_pending_copy = t.shared_pending.lock().clone();
-        let blocked_copy = t.blocked;
-        if let Some(flag) = first_deliverable_signal(pending_copy.signals, blocked_copy) {
-            let num = signal_from_flag(flag).unwrap();
-            let action = {
-                let handlers = t.signal_handlers.lock();
-                handlers.actions[num]
-            };
-            t.pending.signals.remove(flag);
-            (flag, action)
-        } else if let Some(flag) =
-            first_deliverable_signal(shared_pending_copy.signals, blocked_copy)
-        {
-            let num = signal_from_flag(flag).unwrap();
-            let action = {
-                let handlers = t.signal_handlers.lock();
-                handlers.actions[num]
-            };
-            t.shared_pending.lock().signals.remove(flag);
-            (flag, action)
+        let blocked = t.blocked;
+        let info = if let Some(flag) = t.pending.first_deliverable_signal(blocked) {
+            t.pending.dequeue(flag)
         } else {
-            return;
-        }
+            let mut shared = t.shared_pending.lock();
+            let Some(flag) = shared.first_deliverable_signal(blocked) else {
+                break 'next None;
+            };
+            shared.dequeue(flag)
+        };
+        let action = t.signal_handlers.lock().actions[info.signo];
+        Some((info, action))
     };
 
-    handle_one_signal(sig_flag, action, &task);
+    match next {
+        Some((info, action)) => handle_one_signal(info, action, &task),
+        // 没有可投递的信号（如已被其它线程取走）时照常重启
+        None => restart_interrupted_syscall(&task, None),
+    }
+}
+
+/// 被信号打断的系统调用返回用户态前的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyscallRestart {
+    /// 不是重启错误码，返回值原样交给用户态
+    None,
+    /// 向用户态返回 EINTR
+    Interrupt,
+    /// 回退 PC，以原系统调用号和参数重新执行
+    Restart,
+    /// 回退 PC，改为执行 restart_syscall
+    RestartBlock,
 }
 
-/// 为信号创建 siginfo_t 结构体
+/// 根据系统调用返回值和本次投递结果决定是否重启系统调用
 /// # 参数:
-/// * `flag`: 信号标志
-/// TODO: 填充更多字段
-pub fn create_siginfo_for_signal(flag: SignalFlags) -> SigInfoT {
-    let sig_num = flag.to_signal_number();
-    let mut sig_info = SigInfoT::new();
-    sig_info.si_signo = sig_num as i32;
-    sig_info.si_code = 0;
-    sig_info.si_errno = 0;
-    sig_info
+/// * `ret`: 系统调用的返回值
+/// * `handler`: 投递了用户处理函数时为其 sa_flags，默认处理、忽略或没有投递信号时为 None
+pub fn syscall_restart_action(ret: isize, handler: Option<SaFlags>) -> SyscallRestart {
+    let Some(err) = ret.checked_neg().and_then(|e| i32::try_from(e).ok()) else {
+        return SyscallRestart::None;
+    };
+    match (err, handler) {
+        (ERESTARTNOINTR, _) => SyscallRestart::Restart,
+        (ERESTARTSYS, Some(flags)) if flags.contains(SaFlags::RESTART) => SyscallRestart::Restart,
+        (ERESTARTSYS | ERESTARTNOHAND | ERESTART_RESTARTBLOCK, Some(_)) => {
+            SyscallRestart::Interrupt
+        }
+        (ERESTARTSYS | ERESTARTNOHAND, None) => SyscallRestart::Restart,
+        (ERESTART_RESTARTBLOCK, None) => SyscallRestart::RestartBlock,
+        _ => SyscallRestart::None,
+    }
+}
+
+/// 以 `ERESTART_RESTARTBLOCK` 返回的系统调用留给 restart_syscall 的续作信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartBlock {
+    /// 相对时间的 nanosleep/clock_nanosleep
+    Nanosleep {
+        /// 原定的唤醒时刻（时钟周期）
+        deadline: usize,
+        /// 剩余时间写回的用户地址
+        rem: Option<UA>,
+    },
+}
+
+/// 若当前正从系统调用返回，按 [`syscall_restart_action`] 改写 trap frame
+///
+/// 每次系统调用只处理一次，之后的检查点（如停止后再次检查信号）不再生效。
+fn restart_interrupted_syscall(task: &SharedTask, handler: Option<SaFlags>) {
+    let tp = task
+        .lock()
+        .trap_frame_ptr
+        .load(core::sync::atomic::Ordering::SeqCst);
+    // SAFETY: trap_frame_ptr 指向当前任务的 trap frame，只有当前任务会在返回用户态前修改它
+    let tf = unsafe { &mut *tp };
+    let Some(ret) = tf.leave_syscall() else {
+        return;
+    };
+    match syscall_restart_action(ret as isize, handler) {
+        SyscallRestart::None => {}
+        SyscallRestart::Interrupt => tf.set_ret(-(EINTR as isize) as usize),
+        SyscallRestart::Restart => tf.restart(tf.syscall_id()),
+        SyscallRestart::RestartBlock => tf.restart(SYS_RESTART_SYSCALL),
+    }
 }
 
 /// 设置信号用户态处理栈帧
@@ -165,20 +423,21 @@ pub fn create_siginfo_for_signal(flag: SignalFlags) -> SigInfoT {
 /// 内核接收到这个调用后，会从栈上加载 ucontext_t 结构体，恢复所有保存的寄存器状态，从而使程序恢复到被中断时的执行点。
 /// # 参数:
 /// * `task`: 目标任务
-/// * `sig_num`: 信号编号
+/// * `info`: 投递的信号实例
 /// * `entry`: 用户信号处理函数入口地址
 /// * `action_mask`: 信号处理函数的屏蔽字
 fn install_user_signal_trap_frame(
     task: &SharedTask,
-    sig_num: usize,
+    info: QueuedSignal,
     entry: isize,
     action: SignalAction,
 ) {
+    let sig_num = info.signo;
     let mut t = task.lock();
     let tp = t.trap_frame_ptr.load(core::sync::atomic::Ordering::SeqCst);
     unsafe {
         let tf = &mut *tp;
-        let siginfo = create_siginfo_for_signal(SignalFlags::from_signal_num(sig_num).unwrap());
+        let siginfo = info.to_siginfo();
         let sa_flags = SaFlags::from_bits_truncate(action.sa_flags as u32);
         let uc = UContextT::new(
             0,                     // TODO: flags未实现
@@ -246,16 +505,6 @@ fn on_signal_stack(sp: usize, stack: SignalStack) -> bool {
     sp >= start && sp < end
 }
 
-fn signal_from_flag(flag: SignalFlags) -> Option<usize> {
-    let bit_index = flag.bits().trailing_zeros() as usize;
-
-    if bit_index >= NSIG {
-        return None;
-    }
-
-    Some(bit_index + 1)
-}
-
 /* 默认信号处理函数 */
 /// 默认行为：进程中止
 fn sig_terminate(sig_num: usize) -> ! {
@@ -282,53 +531,118 @@ fn sig_dump(sig_num: usize) -> ! {
 }
 
 /// 默认行为：停止进程
+///
+/// 向线程组的所有线程发出停止请求，通知父进程后停下当前线程；
+/// 其它线程在各自返回用户态时停下。
 fn sig_stop(sig_num: usize) {
-    let pid = current_task().lock().pid;
-    let tasks = TASK_MANAGER.lock().get_process_threads(current_task());
-    for task in tasks {
-        {
-            let mut t = task.lock();
-            if t.state == TaskState::Zombie {
-                continue;
-            }
-            t.state = TaskState::Stopped;
+    let task = current_task();
+    let threads = TASK_MANAGER.lock().get_process_threads(task.clone());
+    for thread in threads.iter() {
+        let mut t = thread.lock();
+        if t.state != TaskState::Zombie {
+            t.jobctl.stop_pending = true;
         }
+    }
+    if let Some(leader) = threads.iter().find(|t| t.lock().is_process()) {
+        leader.lock().jobctl.report = Some(JobCtlReport::Stopped(sig_num));
+        notify_parent_jobctl(&TASK_MANAGER.lock(), leader);
+    }
 
-        // 从运行队列移除（不可被信号唤醒，外部需用 SIGCONT）
-        sleep_task(task, false);
+    do_signal_stop(&task);
+    // 停止期间挂起的信号（如 SIGKILL）
+    check_signal();
+}
+
+/// 响应停止请求：停下当前线程，直到被 SIGCONT 或 SIGKILL 恢复
+///
+/// 检查与状态转换在 task 锁内完成，请求在此之前已被 SIGCONT 撤销时不停止。
+fn do_signal_stop(task: &SharedTask) {
+    if stop_task_prepare(task.clone(), |t| {
+        core::mem::take(&mut t.jobctl.stop_pending)
+    }) {
+        schedule();
     }
-    yield_task();
 }
 
 /// 默认行为：继续进程
-fn sig_continue(sig_num: usize) {
-    let pid = current_task().lock().pid;
-    let tasks = TASK_MANAGER.lock().get_process_threads(current_task());
-    for task in tasks {
-        let mut resume = false;
-        {
-            let mut t = task.lock();
-            if t.state == TaskState::Stopped {
-                t.state = TaskState::Running;
-                resume = true;
-            }
-        }
-        if resume {
-            wake_up_task(task);
+///
+/// 已停止的线程在信号产生时就被 [`prepare_signal`] 恢复，投递时无事可做。
+fn sig_continue(_sig_num: usize) {}
+
+/// 默认行为：忽略信号
+fn sig_ignore(sig_num: usize) {}
+
+/// 排队中的一个信号实例，投递时转换为 siginfo_t
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuedSignal {
+    /// 信号编号
+    pub signo: usize,
+    /// 信号来源（`SI_USER`、`SI_QUEUE` 等）
+    pub si_code: i32,
+    /// 发送者的 PID
+    pub pid: u32,
+    /// 发送者的真实 UID
+    pub uid: u32,
+    /// sigqueue 附带的值
+    pub value: usize,
+}
+
+impl QueuedSignal {
+    /// 内核产生的信号，不携带发送者信息
+    pub fn kernel(signo: usize) -> Self {
+        Self::new(signo, SI_USER, 0, 0)
+    }
+
+    /// 由用户任务发送的信号
+    pub fn new(signo: usize, si_code: i32, pid: u32, uid: u32) -> Self {
+        Self {
+            signo,
+            si_code,
+            pid,
+            uid,
+            value: 0,
         }
     }
+
+    /// 转换为用户态的 siginfo_t
+    pub fn to_siginfo(&self) -> SigInfoT {
+        let mut info = SigInfoT::new();
+        info.si_signo = self.signo as i32;
+        info.si_code = self.si_code;
+        info.__si_fields.__si_common = __SiCommon {
+            __first: __FirstCommon {
+                __piduid: __PidUid {
+                    si_pid: self.pid as _,
+                    si_uid: self.uid as _,
+                },
+            },
+            __second: __SecondCommon {
+                si_value: Sigval {
+                    sival_ptr: self.value as *mut _,
+                },
+            },
+        };
+        info
+    }
 }
 
-/// 默认行为：忽略信号
-fn sig_ignore(sig_num: usize) {}
+/// 实时信号（SIGRTMIN..=SIGRTMAX）可重复排队，普通信号最多挂起一个
+#[inline]
+pub fn is_rt_signal(sig: usize) -> bool {
+    (NUM_SIGRTMIN..=NUM_SIGRTMAX).contains(&sig)
+}
 
 /// 待处理信号结构体
+///
+/// `signals` 是挂起信号的位图，`queue` 按到达顺序保存各实例的 siginfo。
+/// 同一实时信号的多个实例依次投递；队列中没有对应实例的挂起位（如超出
+/// RLIMIT_SIGPENDING 时由 kill 发出的信号）投递时只带信号编号。
 #[derive(Debug, Clone)]
 pub struct SignalPending {
-    /// 待处理非实时信号集合
+    /// 待处理信号集合
     pub signals: SignalFlags,
-    // /// 待处理实时信号队列
-    // pub rt_signals: RtSignalQueue,
+    /// 待处理信号实例队列
+    pub queue: VecDeque<QueuedSignal>,
 }
 
 impl SignalPending {
@@ -336,8 +650,50 @@ impl SignalPending {
     pub fn empty() -> Self {
         Self {
             signals: SignalFlags::empty(),
-            // rt_signals: RtSignalQueue::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// 挂起一个信号实例，`limit` 为可排队的实例数上限（RLIMIT_SIGPENDING）
+    ///
+    /// 已挂起的普通信号直接合并。队列已满时，sigqueue 等发出的实时信号返回
+    /// `EAGAIN`；其余信号仍会挂起，只是丢失 siginfo，与 Linux 一致。
+    pub fn enqueue(&mut self, info: QueuedSignal, limit: usize) -> Result<(), i32> {
+        let Some(flag) = SignalFlags::from_signal_num(info.signo) else {
+            return Err(EINVAL);
+        };
+        let rt = is_rt_signal(info.signo);
+        if !rt && self.signals.contains(flag) {
+            return Ok(());
+        }
+        if !rt || self.queue.len() < limit {
+            self.queue.push_back(info);
+        } else if info.si_code != SI_USER {
+            return Err(EAGAIN);
+        }
+        self.signals.insert(flag);
+        Ok(())
+    }
+
+    /// 取出信号 `flag` 最早到达的实例，同一信号没有剩余实例时清除挂起位
+    pub fn dequeue(&mut self, flag: SignalFlags) -> QueuedSignal {
+        let signo = flag.to_signal_number();
+        let info = match self.queue.iter().position(|q| q.signo == signo) {
+            Some(idx) => self.queue.remove(idx).unwrap(),
+            None => QueuedSignal::kernel(signo),
+        };
+        if !self.queue.iter().any(|q| q.signo == signo) {
+            self.signals.remove(flag);
         }
+        info
+    }
+
+    /// 丢弃 `mask` 中的所有挂起信号及其排队实例
+    pub fn discard(&mut self, mask: SignalFlags) {
+        self.signals.remove(mask);
+        self.queue.retain(|q| {
+            SignalFlags::from_signal_num(q.signo).is_some_and(|flag| !mask.contains(flag))
+        });
     }
 
     /// 检查是否有可投递的信号
@@ -358,18 +714,7 @@ impl SignalPending {
     /// # 参数:
     /// * `target`: 目标信号集合
     pub fn first_target_signal(&self, target: SignalFlags) -> Option<SignalFlags> {
-        let intersect = self.signals & target;
-        if intersect.is_empty() {
-            return None;
-        }
-
-        let signal_bit_index = intersect.bits().trailing_zeros();
-        if signal_bit_index < NSIG as u32 {
-            let first_sig = SignalFlags::from_bits(1 << signal_bit_index).unwrap();
-            return Some(first_sig);
-        }
-
-        None
+        first_deliverable_signal(self.signals, target.complement())
     }
 }
 
@@ -384,7 +729,11 @@ pub fn do_sigpending() -> SignalFlags {
 /// # 参数:
 /// * `task`: 目标任务
 pub fn signal_pending(task: &SharedTask) -> bool {
-    let t = task.lock();
+    sig_pending(&task.lock())
+}
+
+/// 同 [`signal_pending`]，供已持有任务锁的调用者（如睡眠前的条件检查）使用
+pub fn sig_pending(t: &TaskStruct) -> bool {
     t.pending.has_deliverable_signal(t.blocked)
         || t.shared_pending.lock().has_deliverable_signal(t.blocked)
 }
@@ -408,7 +757,8 @@ pub fn signal_interrupts_syscall(task: &SharedTask) -> bool {
         // Practical Linux-compat behavior:
         // netserver/netperf often rely on SIGCHLD for child reaping; on Linux this usually does
         // not surface as EINTR to select()/poll() because handlers are installed with SA_RESTART.
-        // We don't fully implement SA_RESTART yet, so never use SIGCHLD to interrupt syscalls.
+        // Only syscalls returning ERESTARTSYS honour SA_RESTART here, so never use SIGCHLD to
+        // interrupt syscalls.
         if sig_num == NUM_SIGCHLD {
             continue;
         }
@@ -419,16 +769,165 @@ pub fn signal_interrupts_syscall(task: &SharedTask) -> bool {
             continue;
         }
 
-        let action = handlers.actions[sig_num];
-        match unsafe { action.sa_handler() } as isize {
-            SIG_IGN => continue,
-            SIG_DFL => match sig_num {
-                NUM_SIGCHLD | NUM_SIGURG | NUM_SIGWINCH | NUM_SIGIO => continue, // default ignore
-                _ => return true,
-            },
-            _ => return true, // caught
+        if !action_ignores(&handlers.actions[sig_num], sig_num) {
+            return true;
         }
     }
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    fn handler_of(action: &SignalAction) -> isize {
+        unsafe { action.sa_handler() as isize }
+    }
+
+    test_case!(test_default_action_table, {
+        kassert!(default_action(NUM_SIGSEGV) == SigDefaultAction::CoreDump);
+        kassert!(default_action(NUM_SIGQUIT) == SigDefaultAction::CoreDump);
+        kassert!(default_action(NUM_SIGKILL) == SigDefaultAction::Terminate);
+        kassert!(default_action(NUM_SIGIO) == SigDefaultAction::Terminate);
+        kassert!(default_action(NUM_SIGRTMIN) == SigDefaultAction::Terminate);
+        kassert!(default_action(NUM_SIGCHLD) == SigDefaultAction::Ignore);
+        kassert!(default_action(NUM_SIGTSTP) == SigDefaultAction::Stop);
+        kassert!(default_action(NUM_SIGCONT) == SigDefaultAction::Continue);
+    });
+
+    test_case!(test_action_ignores, {
+        let dfl = SignalAction::default();
+        let ign = SignalAction::new(SIG_IGN as *mut _, SaFlags::empty(), SignalFlags::empty());
+        let caught = SignalAction::new(0x1000 as *mut _, SaFlags::empty(), SignalFlags::empty());
+
+        kassert!(action_ignores(&dfl, NUM_SIGCHLD));
+        kassert!(action_ignores(&dfl, NUM_SIGCONT));
+        kassert!(!action_ignores(&dfl, NUM_SIGTERM));
+        kassert!(action_ignores(&ign, NUM_SIGTERM));
+        kassert!(!action_ignores(&caught, NUM_SIGCHLD));
+    });
+
+    test_case!(test_handler_table_for_exec, {
+        let mut table = SignalHandlerTable::new();
+        table.set_action(
+            NUM_SIGINT,
+            SignalAction::new(SIG_IGN as *mut _, SaFlags::SIGINFO, SignalFlags::all()),
+        );
+        table.set_action(
+            NUM_SIGUSR1,
+            SignalAction::new(0x1000 as *mut _, SaFlags::ONSTACK, SignalFlags::empty()),
+        );
+
+        let exec = table.for_exec();
+        kassert!(handler_of(&exec.actions[NUM_SIGINT]) == SIG_IGN);
+        kassert!(exec.actions[NUM_SIGINT].sa_flags == 0);
+        kassert!(handler_of(&exec.actions[NUM_SIGUSR1]) == SIG_DFL);
+        kassert!(exec.actions[NUM_SIGUSR1].sa_flags == 0);
+        kassert!(handler_of(&exec.actions[NUM_SIGTERM]) == SIG_DFL);
+    });
+
+    fn queued(signo: usize, value: usize) -> QueuedSignal {
+        let mut info = QueuedSignal::new(signo, SI_QUEUE, 1, 0);
+        info.value = value;
+        info
+    }
+
+    test_case!(test_pending_rt_signals_queue_fifo, {
+        let mut pending = SignalPending::empty();
+        let rt = SignalFlags::from_signal_num(NUM_SIGRTMIN + 1).unwrap();
+        for value in 1..=3 {
+            kassert!(pending.enqueue(queued(NUM_SIGRTMIN + 1, value), 8).is_ok());
+        }
+
+        for value in 1..=3 {
+            kassert!(pending.signals.contains(rt));
+            kassert!(pending.dequeue(rt).value == value);
+        }
+        kassert!(!pending.signals.contains(rt));
+        kassert!(pending.queue.is_empty());
+    });
+
+    test_case!(test_pending_standard_signals_merge, {
+        let mut pending = SignalPending::empty();
+        let usr1 = SignalFlags::from_signal_num(NUM_SIGUSR1).unwrap();
+        kassert!(pending.enqueue(queued(NUM_SIGUSR1, 1), 8).is_ok());
+        kassert!(pending.enqueue(queued(NUM_SIGUSR1, 2), 8).is_ok());
+        kassert!(pending.queue.len() == 1);
+
+        kassert!(pending.dequeue(usr1).value == 1);
+        kassert!(!pending.signals.contains(usr1));
+    });
+
+    test_case!(test_pending_rt_limit, {
+        let mut pending = SignalPending::empty();
+        let rt = SignalFlags::from_signal_num(NUM_SIGRTMIN).unwrap();
+        kassert!(pending.enqueue(queued(NUM_SIGRTMIN, 1), 1).is_ok());
+        kassert!(pending.enqueue(queued(NUM_SIGRTMIN, 2), 1) == Err(EAGAIN));
+        // kill 发出的信号超出上限时仍然挂起，只是不再排队
+        kassert!(
+            pending
+                .enqueue(QueuedSignal::kernel(NUM_SIGRTMIN), 1)
+                .is_ok()
+        );
+        kassert!(pending.queue.len() == 1);
+
+        kassert!(pending.dequeue(rt).value == 1);
+        kassert!(!pending.signals.contains(rt));
+    });
+
+    test_case!(test_pending_discard, {
+        let mut pending = SignalPending::empty();
+        let rt = SignalFlags::from_signal_num(NUM_SIGRTMIN).unwrap();
+        kassert!(pending.enqueue(queued(NUM_SIGRTMIN, 1), 8).is_ok());
+        kassert!(pending.enqueue(queued(NUM_SIGUSR2, 2), 8).is_ok());
+
+        pending.discard(rt);
+        kassert!(!pending.signals.contains(rt));
+        kassert!(pending.queue.len() == 1);
+        kassert!(pending.queue[0].signo == NUM_SIGUSR2);
+    });
+
+    test_case!(test_queued_signal_siginfo, {
+        let mut info = QueuedSignal::new(NUM_SIGRTMIN, SI_QUEUE, 42, 1000);
+        info.value = 0xdead;
+        let siginfo = info.to_siginfo();
+        kassert!(siginfo.si_signo == NUM_SIGRTMIN as i32);
+        kassert!(siginfo.si_code == SI_QUEUE);
+        unsafe {
+            let common = siginfo.__si_fields.__si_common;
+            kassert!(common.__first.__piduid.si_pid == 42);
+            kassert!(common.__first.__piduid.si_uid == 1000);
+            kassert!(common.__second.si_value.sival_ptr as usize == 0xdead);
+        }
+    });
+
+    test_case!(test_syscall_restart_action, {
+        let err = |e: i32| -(e as isize);
+        let plain = Some(SaFlags::empty());
+        let restart = Some(SaFlags::RESTART);
+
+        kassert!(syscall_restart_action(0, None) == SyscallRestart::None);
+        kassert!(syscall_restart_action(err(EAGAIN), plain) == SyscallRestart::None);
+        kassert!(syscall_restart_action(isize::MIN, plain) == SyscallRestart::None);
+
+        kassert!(syscall_restart_action(err(ERESTARTSYS), None) == SyscallRestart::Restart);
+        kassert!(syscall_restart_action(err(ERESTARTSYS), plain) == SyscallRestart::Interrupt);
+        kassert!(syscall_restart_action(err(ERESTARTSYS), restart) == SyscallRestart::Restart);
+
+        kassert!(syscall_restart_action(err(ERESTARTNOINTR), plain) == SyscallRestart::Restart);
+
+        kassert!(syscall_restart_action(err(ERESTARTNOHAND), None) == SyscallRestart::Restart);
+        kassert!(syscall_restart_action(err(ERESTARTNOHAND), restart) == SyscallRestart::Interrupt);
+
+        kassert!(
+            syscall_restart_action(err(ERESTART_RESTARTBLOCK), None)
+                == SyscallRestart::RestartBlock
+        );
+        kassert!(
+            syscall_restart_action(err(ERESTART_RESTARTBLOCK), restart)
+                == SyscallRestart::Interrupt
+        );
+    });
+}
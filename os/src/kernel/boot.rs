@@ -6,12 +6,13 @@
 use alloc::sync::Arc;
 
 use crate::{
-    arch::{CpuOps, platform, timer, trap},
+    arch::{Arch, ArchImpl, kernel::context::Context, platform, timer, trap},
     ipc::{SignalHandlerTable, SignalPending},
     kernel::{
-        FsStruct, Scheduler, TASK_MANAGER, TaskManagerTrait, TaskStruct, current_cpu,
-        current_memory_space, current_task, kernel_execve, kthread_spawn, kworker, scheduler_of,
-        sleep_task, time, yield_task,
+        FsStruct, Scheduler, TASK_MANAGER, TaskManagerTrait, TaskStruct,
+        bootinfo::{self, Milestone},
+        current_cpu, current_memory_space, current_task, kernel_execve, kthread_run, kthread_spawn,
+        kworker, scheduler_of, set_kthreadd_pid, sleep_task, time, yield_task,
     },
     mm,
     mm::frame_allocator::{alloc_contig_frames, alloc_frame},
@@ -19,6 +20,7 @@ use crate::{
     sync::{PreemptGuard, SpinLock},
     test::run_early_tests,
     uapi::{
+        cred::ROOT_GID,
         resource::{INIT_RLIMITS, RlimitStruct},
         signal::SignalFlags,
         uts_namespace::UtsNamespace,
@@ -61,6 +63,8 @@ pub fn run_primary_boot(hartid: usize, ops: PrimaryBootOps) -> ! {
 
     (ops.after_clear_bss)(hartid);
 
+    crate::console::earlycon::init();
+
     run_early_tests();
 
     crate::println!("[Boot] Hello, world!");
@@ -70,8 +74,10 @@ pub fn run_primary_boot(hartid: usize, ops: PrimaryBootOps) -> ! {
         ops.cpu_label,
         hartid
     );
+    crate::config::print_build_config();
 
     let kernel_space = mm::init();
+    crate::kernel::kstat::init();
 
     (ops.after_mm_init)(hartid);
 
@@ -80,15 +86,26 @@ pub fn run_primary_boot(hartid: usize, ops: PrimaryBootOps) -> ! {
         current_cpu().switch_space(kernel_space);
         crate::println!("[Boot] Activated kernel address space");
     }
+    bootinfo::record(Milestone::MmInit, "kernel address space active");
 
     #[cfg(test)]
     crate::test_main();
 
     trap::init_boot_trap();
     platform::init();
+    record_devices_probed();
     time::init();
+    crate::kernel::syscall::legacy::init();
+    bootinfo::record(
+        Milestone::TimeInit,
+        &alloc::format!("clock {} Hz", crate::arch::clock_freq()),
+    );
 
     (ops.after_time_init)(hartid);
+    bootinfo::record(
+        Milestone::SmpUp,
+        &alloc::format!("{} cpu(s)", crate::kernel::num_cpu()),
+    );
 
     timer::init();
 
@@ -96,26 +113,57 @@ pub fn run_primary_boot(hartid: usize, ops: PrimaryBootOps) -> ! {
     {
         let _guard = PreemptGuard::new();
         current_cpu().idle_task = Some(idle.clone());
-        current_cpu().switch_task(idle);
+        current_cpu().switch_task(idle.clone());
     }
 
     trap::init();
     rest_init();
+    bootinfo::record(Milestone::SchedReady, "pid 1 queued on cpu 0");
 
-    crate::arch::enable_interrupts();
-    idle_loop();
+    enter_idle_task(idle);
+}
+
+/// 登记设备探测完成，说明中给出各类设备的数量
+fn record_devices_probed() {
+    let detail = alloc::format!(
+        "{} drivers, {} block, {} net",
+        crate::device::DRIVERS.read().len(),
+        crate::device::BLK_DRIVERS.read().len(),
+        crate::device::net::NETWORK_DEVICES.lock().len()
+    );
+    bootinfo::record(Milestone::DevicesProbed, &detail);
+}
+
+/// 离开启动上下文，切换到本 CPU 的 idle 任务，永不返回
+///
+/// 调用前须已把 `idle` 设为本 CPU 的 idle 任务和当前任务。启动上下文保存到一个
+/// 随即丢弃的 `Context` 中，不会再被调度，因此启动栈不会成为任何任务的栈；
+/// idle 任务经 forkret 在自己的内核栈上开中断进入 `idle_loop`，
+/// 之后由调度器在时钟中断中切换到 rest_init 创建的 init 任务。
+pub fn enter_idle_task(idle: crate::kernel::SharedTask) -> ! {
+    crate::arch::disable_interrupts();
+    let mut boot_context = Context::zero_init();
+    let idle_context: *const Context = {
+        let g = idle.lock();
+        &g.context as *const _
+    };
+    drop(idle);
+    // SAFETY: idle 任务由 TASK_MANAGER 和 Cpu::idle_task 持有，其 context 在切换后仍然有效
+    unsafe { ArchImpl::context_switch(&mut boot_context, idle_context) };
+    unreachable!("enter_idle_task: boot context resumed");
 }
 
 /// 架构无关的 idle 循环
 ///
-/// 确保中断开启后持续等待中断，唤醒后立即重新等待。
-/// 使用 `ArchImpl::halt()` 执行具体的 halt 指令（wfi / idle 0）。
-#[allow(clippy::never_loop)]
+/// 确保中断开启后反复调用 `idle::cpu_idle()`：由空闲调控器决定是否停掉
+/// 周期节拍，在 wfi / idle 0 中等待中断并统计空闲时间。
 pub fn idle_loop() -> ! {
     if !crate::arch::interrupts_enabled() {
         crate::arch::enable_interrupts();
     }
-    crate::arch::ArchImpl::halt();
+    loop {
+        crate::kernel::idle::cpu_idle();
+    }
 }
 
 /// 清除 BSS 段
@@ -140,8 +188,8 @@ pub fn clear_bss() {
 
 /// 创建 init 任务 (PID=1) 并加入调度队列
 ///
-/// 调用后 init 任务在运行队列中就绪，调用者应进入 idle_loop 等待
-/// 下一次时钟中断触发调度，由调度器自动选中 init 并切换上下文。
+/// 调用后 init 任务在运行队列中就绪，调用者应通过 [`enter_idle_task`] 进入 idle 任务，
+/// 由下一次时钟中断触发的调度自动选中 init 并切换上下文。
 pub fn rest_init() {
     let tid = 1;
     let kstack_tracker = alloc_contig_frames(4).expect("rest_init: failed to alloc kstack");
@@ -204,29 +252,80 @@ fn init() {
     create_kthreadd();
 
     // 默认启动形态是 MBR raw disk：vda1 为 ext4 rootfs，vda2 为 VFAT 测试分区。
-    if let Err(e) = crate::fs::init_rootfs_from_discovered_block_devices() {
-        pr_err!(
-            "[Init] Warning: Failed to initialize root filesystem: {:?}",
-            e
-        );
-        pr_info!("[Init] Continuing without filesystem...");
+    match crate::fs::init_rootfs_from_discovered_block_devices() {
+        Ok(()) => bootinfo::record(Milestone::RootfsMounted, "/"),
+        Err(e) => {
+            pr_err!(
+                "[Init] Warning: Failed to initialize root filesystem: {:?}",
+                e
+            );
+            pr_info!("[Init] Continuing without filesystem...");
+            bootinfo::record(Milestone::RootfsMounted, &alloc::format!("failed: {:?}", e));
+        }
+    }
+    init_groups();
+    crate::log::pstore::init();
+
+    match crate::net::config::NetworkConfigManager::init_default_interface() {
+        Ok(_) => bootinfo::record(Milestone::NetworkUp, "default interface"),
+        Err(e) => {
+            pr_warn!(
+                "[Init] Warning: Failed to init default network interface: {:?}",
+                e
+            );
+            bootinfo::record(Milestone::NetworkUp, &alloc::format!("failed: {:?}", e));
+        }
     }
 
-    if let Err(e) = crate::net::config::NetworkConfigManager::init_default_interface() {
-        pr_warn!(
-            "[Init] Warning: Failed to init default network interface: {:?}",
-            e
-        );
+    if crate::kernel::selftest::enabled() && !crate::kernel::selftest::run() {
+        crate::test::user_result::report(crate::test::user_result::UserTestResult::Fail);
+    }
+    if crate::kernel::conformance::enabled() && !crate::kernel::conformance::run() {
+        crate::test::user_result::report(crate::test::user_result::UserTestResult::Fail);
+    }
+    if crate::kernel::fuzz::enabled() && !crate::kernel::fuzz::run() {
+        crate::test::user_result::report(crate::test::user_result::UserTestResult::Fail);
     }
 
+    bootinfo::record(Milestone::InitStarted, "/sbin/init");
+    bootinfo::print_summary();
+    bootinfo::enforce_budget();
+
     kernel_execve("/sbin/init", &["/sbin/init"], &[]);
 }
 
+/// `/etc/group` 读取上限，超过部分忽略
+const GROUP_FILE_MAX: usize = 64 * 1024;
+
+/// 按 `/etc/group` 设置 init 的附加组，相当于 `initgroups("root", 0)`
+///
+/// 没有 `/etc/group` 时附加组只含 root 组。附加组随 fork 继承给所有用户进程。
+fn init_groups() {
+    let content = crate::vfs::vfs_lookup("/etc/group")
+        .ok()
+        .and_then(|dentry| {
+            let size = dentry.inode.metadata().ok()?.size.min(GROUP_FILE_MAX);
+            let mut buf = alloc::vec![0u8; size];
+            let n = dentry.inode.read_at(0, &mut buf).ok()?;
+            buf.truncate(n);
+            alloc::string::String::from_utf8(buf).ok()
+        })
+        .unwrap_or_default();
+    let groups = crate::kernel::initgroups(&content, "root", ROOT_GID);
+    pr_info!("[Init] Supplementary groups: {:?}", groups);
+    current_task().lock().credential.groups = groups;
+}
+
 /// 内核守护线程 PID = 2
 ///
 /// 负责创建内核任务，回收僵尸任务等工作
 fn kthreadd() {
     kthread_spawn(kworker);
+    crate::kernel::executor::spawn(crate::mm::ksm::ksmd());
+    kthread_run("watchdogd", crate::kernel::watchdog::watchdogd, 0);
+    for _ in 0..crate::kernel::syscall::IO_WQ_WORKERS {
+        kthread_spawn(crate::kernel::syscall::io_wq_worker);
+    }
     loop {
         sleep_task(current_task(), true);
         yield_task();
@@ -276,6 +375,7 @@ fn create_kthreadd() {
         );
     }
     let task = task.into_shared();
+    set_kthreadd_pid(tid);
     TASK_MANAGER.lock().add_task(task.clone());
     task.lock().on_cpu = Some(0);
     scheduler_of(0).lock().add_task(task);
@@ -283,11 +383,13 @@ fn create_kthreadd() {
 
 /// 为指定 CPU 创建 idle 任务
 ///
-/// idle 任务使用 `idle_fn` 作为入口（各架构自行提供 wfi/idle 0 循环）。
+/// idle 任务使用 `idle_fn` 作为入口（各架构自行提供 wfi/idle 0 循环），
+/// 不进入运行队列，只在没有可运行任务时被调度器选中。
+/// 它在自己的内核栈上运行，中断也在这个栈上处理，见 [`enter_idle_task`]。
 pub fn create_idle_task(cpu_id: usize, idle_fn: fn() -> !) -> crate::kernel::SharedTask {
     let tid = TASK_MANAGER.lock().allocate_tid();
     let kstack_tracker =
-        alloc_contig_frames(1).expect("Failed to allocate kernel stack for idle task");
+        alloc_contig_frames(4).expect("Failed to allocate kernel stack for idle task");
     let trap_frame_tracker = alloc_frame().expect("Failed to allocate trap frame for idle task");
 
     let mut task = TaskStruct::ktask_create(
@@ -0,0 +1,363 @@
+//! 启动报告
+//!
+//! 启动路径在关键节点调用 [`record`] 登记里程碑（内存管理就绪、设备探测完成、
+//! 根文件系统挂载、init 启动等），每条记录带硬件时间戳和一行说明。
+//! 启动结束（执行 `/sbin/init` 之前）由 [`print_summary`] 打印对齐的汇总表，
+//! 之后记录一直保留，供 `/proc/bootinfo` 查看，便于跟踪启动耗时的回归。
+//!
+//! 时间戳保存原始时钟周期数，在输出时才按 `clock_freq` 换算成微秒，
+//! 因此在时钟频率从设备树确定之前登记的节点也能得到正确的时间。
+//!
+//! 相邻两个里程碑之间是一个启动阶段，阶段以结束它的里程碑命名，报告中同时给出
+//! 阶段的毫秒数和时钟周期数。命令行带 `boot_budget_ms=` 时，[`enforce_budget`]
+//! 在启动结束时逐阶段比较耗时与预算，任何阶段超出预算都会打印超限的阶段并以
+//! 失败状态关机，让 CI 式的运行在启动变慢时直接失败。参数格式为逗号分隔的
+//! `<毫秒>`（所有阶段的默认预算）或 `<阶段>:<毫秒>`（单个阶段的预算），如
+//! `boot_budget_ms=200,rootfs_mounted:2000`。
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::sync::SpinLock;
+use crate::{pr_emerg, pr_info, pr_warn};
+
+/// 启动里程碑
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Milestone {
+    /// 内存管理初始化完成，内核地址空间可用
+    MmInit,
+    /// 平台设备探测完成
+    DevicesProbed,
+    /// 时间子系统初始化完成
+    TimeInit,
+    /// 从核上线
+    SmpUp,
+    /// 调度就绪，PID 1 已创建
+    SchedReady,
+    /// 根文件系统挂载完成（或失败）
+    RootfsMounted,
+    /// 默认网络接口配置完成（或失败）
+    NetworkUp,
+    /// 即将执行用户态 init
+    InitStarted,
+}
+
+impl Milestone {
+    const ALL: [Milestone; 8] = [
+        Milestone::MmInit,
+        Milestone::DevicesProbed,
+        Milestone::TimeInit,
+        Milestone::SmpUp,
+        Milestone::SchedReady,
+        Milestone::RootfsMounted,
+        Milestone::NetworkUp,
+        Milestone::InitStarted,
+    ];
+
+    /// 按名字查找里程碑，用于解析 `boot_budget_ms=`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.name() == name)
+    }
+
+    /// 在汇总表和 `/proc/bootinfo` 中显示的名字
+    pub fn name(self) -> &'static str {
+        match self {
+            Milestone::MmInit => "mm_init",
+            Milestone::DevicesProbed => "devices_probed",
+            Milestone::TimeInit => "time_init",
+            Milestone::SmpUp => "smp_up",
+            Milestone::SchedReady => "sched_ready",
+            Milestone::RootfsMounted => "rootfs_mounted",
+            Milestone::NetworkUp => "network_up",
+            Milestone::InitStarted => "init_started",
+        }
+    }
+}
+
+/// 一条启动记录
+#[derive(Debug, Clone)]
+pub struct BootRecord {
+    pub milestone: Milestone,
+    /// 登记时的硬件时钟周期数
+    pub cycles: u64,
+    pub detail: String,
+}
+
+/// 名字列的宽度，取最长的里程碑名
+const NAME_WIDTH: usize = 14;
+
+static RECORDS: SpinLock<Vec<BootRecord>> = SpinLock::new(Vec::new());
+
+/// 登记一个启动里程碑
+///
+/// 需要堆分配，只能在 `mm::init()` 之后调用。同一里程碑可以登记多次（如多次挂载），
+/// 报告中按登记顺序全部列出。
+pub fn record(milestone: Milestone, detail: &str) {
+    let cycles = crate::arch::get_time() as u64;
+    RECORDS.lock().push(BootRecord {
+        milestone,
+        cycles,
+        detail: String::from(detail),
+    });
+}
+
+/// 返回已登记记录的副本
+pub fn records() -> Vec<BootRecord> {
+    RECORDS.lock().clone()
+}
+
+fn cycles_to_us(cycles: u64, clock_freq: u64) -> u64 {
+    if clock_freq == 0 {
+        return 0;
+    }
+    (cycles as u128 * 1_000_000 / clock_freq as u128) as u64
+}
+
+/// 以各记录结束的阶段耗时（时钟周期数），第一个阶段从上电开始计
+fn phase_cycles(records: &[BootRecord]) -> impl Iterator<Item = (&BootRecord, u64)> {
+    let mut prev = 0;
+    records.iter().map(move |rec| {
+        let delta = rec.cycles.saturating_sub(prev);
+        prev = rec.cycles;
+        (rec, delta)
+    })
+}
+
+/// 把记录格式化为对齐的表格
+///
+/// 每行依次为：自上电起的时间、阶段耗时、阶段耗时的时钟周期数、里程碑名和说明，
+/// 时间单位为毫秒，保留三位小数。`/proc/bootinfo` 与启动汇总使用同一格式。
+pub fn format_report(records: &[BootRecord], clock_freq: u64) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:>12} {:>12} {:>14}  {:<width$}  detail",
+        "time_ms",
+        "delta_ms",
+        "delta_cycles",
+        "milestone",
+        width = NAME_WIDTH
+    );
+    for (rec, delta) in phase_cycles(records) {
+        let us = cycles_to_us(rec.cycles, clock_freq);
+        let delta_us = cycles_to_us(delta, clock_freq);
+        let _ = writeln!(
+            out,
+            "{:>8}.{:03} {:>8}.{:03} {:>14}  {:<width$}  {}",
+            us / 1000,
+            us % 1000,
+            delta_us / 1000,
+            delta_us % 1000,
+            delta,
+            rec.milestone.name(),
+            rec.detail,
+            width = NAME_WIDTH
+        );
+    }
+    out
+}
+
+/// 生成当前的启动报告
+pub fn report() -> String {
+    format_report(&records(), crate::arch::clock_freq() as u64)
+}
+
+/// 在控制台打印启动汇总
+pub fn print_summary() {
+    let report = report();
+    crate::println!("[Boot] Boot report:");
+    for line in report.lines() {
+        crate::println!("[Boot]   {}", line);
+    }
+}
+
+/// `boot_budget_ms=` 给出的阶段耗时预算
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BootBudget {
+    /// 未单独指定预算的阶段使用的预算
+    pub default_ms: Option<u64>,
+    /// 单个阶段的预算，优先于默认预算
+    pub phases: Vec<(Milestone, u64)>,
+}
+
+impl BootBudget {
+    /// 解析 `boot_budget_ms=` 的值，任何一项无法识别都返回 `None`
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut budget = BootBudget::default();
+        for item in value.split(',') {
+            match item.split_once(':') {
+                Some((name, ms)) => {
+                    let milestone = Milestone::from_name(name)?;
+                    let ms = ms.parse().ok()?;
+                    budget.phases.retain(|(m, _)| *m != milestone);
+                    budget.phases.push((milestone, ms));
+                }
+                None => budget.default_ms = Some(item.parse().ok()?),
+            }
+        }
+        Some(budget)
+    }
+
+    /// 指定阶段的预算（毫秒），没有预算时为 `None`
+    pub fn limit_ms(&self, milestone: Milestone) -> Option<u64> {
+        self.phases
+            .iter()
+            .find(|(m, _)| *m == milestone)
+            .map(|(_, ms)| *ms)
+            .or(self.default_ms)
+    }
+}
+
+/// 一个超出预算的阶段
+#[derive(Debug, PartialEq, Eq)]
+pub struct BudgetViolation {
+    pub milestone: Milestone,
+    pub elapsed_cycles: u64,
+    pub budget_ms: u64,
+}
+
+/// 逐阶段比较耗时与预算，返回所有超出预算的阶段
+///
+/// 预算换算为时钟周期后与阶段的周期数比较，不受微秒取整影响。
+pub fn check_budget(
+    records: &[BootRecord],
+    clock_freq: u64,
+    budget: &BootBudget,
+) -> Vec<BudgetViolation> {
+    phase_cycles(records)
+        .filter_map(|(rec, delta)| {
+            let budget_ms = budget.limit_ms(rec.milestone)?;
+            let limit = (budget_ms as u128 * clock_freq as u128 / 1000) as u64;
+            (delta > limit).then_some(BudgetViolation {
+                milestone: rec.milestone,
+                elapsed_cycles: delta,
+                budget_ms,
+            })
+        })
+        .collect()
+}
+
+/// 从命令行取 `boot_budget_ms=` 的值，多次给出时以最后一次为准
+fn budget_param(cmdline: &str) -> Option<&str> {
+    cmdline
+        .split_ascii_whitespace()
+        .filter_map(|arg| arg.strip_prefix("boot_budget_ms="))
+        .next_back()
+}
+
+/// 按 `boot_budget_ms=` 检查启动耗时，有阶段超出预算时以失败状态关机
+///
+/// 没有给出参数时什么也不做；参数无法解析时只打印警告。应在登记
+/// [`Milestone::InitStarted`] 之后调用。
+pub fn enforce_budget() {
+    let cmdline = crate::device::CMDLINE.read().clone();
+    let Some(value) = budget_param(&cmdline) else {
+        return;
+    };
+    let Some(budget) = BootBudget::parse(value) else {
+        pr_warn!("[Boot] boot_budget_ms={} is malformed, ignored", value);
+        return;
+    };
+    let clock_freq = crate::arch::clock_freq() as u64;
+    let violations = check_budget(&records(), clock_freq, &budget);
+    if violations.is_empty() {
+        pr_info!("[Boot] All boot phases within budget");
+        return;
+    }
+    for v in &violations {
+        let us = cycles_to_us(v.elapsed_cycles, clock_freq);
+        pr_emerg!(
+            "[Boot] Boot phase {} took {}.{:03} ms ({} cycles), budget {} ms",
+            v.milestone.name(),
+            us / 1000,
+            us % 1000,
+            v.elapsed_cycles,
+            v.budget_ms
+        );
+    }
+    pr_emerg!(
+        "[Boot] Boot budget exceeded in {} phase(s), powering off",
+        violations.len()
+    );
+    crate::arch::lib::shutdown(true);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_bootinfo_format_report, {
+        let records = [
+            BootRecord {
+                milestone: Milestone::MmInit,
+                cycles: 1_500,
+                detail: String::from("kernel space ready"),
+            },
+            BootRecord {
+                milestone: Milestone::InitStarted,
+                cycles: 2_250_000,
+                detail: String::from("/sbin/init"),
+            },
+        ];
+        // 1 MHz 时钟：1 周期 = 1 微秒
+        let report = format_report(&records, 1_000_000);
+        let lines: Vec<&str> = report.lines().collect();
+        kassert!(lines.len() == 3);
+        kassert!(lines[0].starts_with("     time_ms     delta_ms   delta_cycles  milestone"));
+        kassert!(
+            lines[1]
+                == "       1.500        1.500           1500  mm_init         kernel space ready"
+        );
+        kassert!(
+            lines[2] == "    2250.000     2248.500        2248500  init_started    /sbin/init"
+        );
+
+        // 时钟频率未知时不除零
+        kassert!(format_report(&records, 0).lines().count() == 3);
+    });
+
+    test_case!(test_bootinfo_budget, {
+        kassert!(BootBudget::parse("abc").is_none());
+        kassert!(BootBudget::parse("nosuch_phase:10").is_none());
+        kassert!(BootBudget::parse("mm_init:").is_none());
+
+        let budget = BootBudget::parse("100,rootfs_mounted:2000,rootfs_mounted:3000").unwrap();
+        kassert!(budget.limit_ms(Milestone::MmInit) == Some(100));
+        kassert!(budget.limit_ms(Milestone::RootfsMounted) == Some(3000));
+        kassert!(
+            BootBudget::parse("mm_init:5")
+                .unwrap()
+                .limit_ms(Milestone::SmpUp)
+                == None
+        );
+
+        kassert!(budget_param("quiet boot_budget_ms=1 boot_budget_ms=2") == Some("2"));
+        kassert!(budget_param("quiet").is_none());
+
+        // 1 MHz 时钟：mm_init 阶段 100 ms 恰好不超，rootfs_mounted 阶段 3000.001 ms 超出
+        let records = [
+            BootRecord {
+                milestone: Milestone::MmInit,
+                cycles: 100_000,
+                detail: String::new(),
+            },
+            BootRecord {
+                milestone: Milestone::RootfsMounted,
+                cycles: 3_100_001,
+                detail: String::new(),
+            },
+        ];
+        let violations = check_budget(&records, 1_000_000, &budget);
+        kassert!(
+            violations
+                == [BudgetViolation {
+                    milestone: Milestone::RootfsMounted,
+                    elapsed_cycles: 3_000_001,
+                    budget_ms: 3000,
+                }]
+        );
+        kassert!(check_budget(&records, 1_000_000, &BootBudget::default()).is_empty());
+    });
+}
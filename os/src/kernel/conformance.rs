@@ -0,0 +1,257 @@
+//! 系统调用一致性测试
+//!
+//! `user/conformance` 中的每个程序覆盖一类系统调用（文件、进程、信号、内存、时间），
+//! 逐项输出 `名字 = 结果`；`expected/<程序名>.out` 是按 Linux 行为编写的期望输出。
+//! build.rs 编译这些程序，与期望输出一起生成 [`CASES`] 嵌入内核。
+//!
+//! 命令行带 `conformance` 时，init 在 exec /sbin/init 之前依次运行各程序。每个程序以
+//! 一个新建的空 tmpfs 为根目录和当前目录，看不到也改不了真正的根文件系统；标准输出和
+//! 标准错误写入沙箱之外的文件。程序退出后逐行比较输出与期望，差异写入内核日志。
+//! 任何用例失败都记为用户态测试失败，但不阻止启动。
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    fs::tmpfs::TmpFs,
+    kernel::{
+        Credential, ExecImageError, FsStruct, TaskExitStatus,
+        embedded::{self, UserProgram},
+    },
+    pr_err, pr_info,
+    vfs::{Dentry, FDTable, File, FileMode, FileSystem, Inode, OpenFlags, RegFile},
+};
+
+/// 一个一致性测试用例
+pub struct ConformanceCase {
+    /// 程序名，即 `user/conformance/src/bin/` 下的文件名
+    pub name: &'static str,
+    /// 编译好的程序，非目标架构上为空
+    pub elf: &'static [u8],
+    /// 期望输出
+    pub expected: &'static str,
+}
+
+/// 由 build.rs 生成的用例表，按程序名排序
+static CASES: &[ConformanceCase] = include!(env!("CONFORMANCE_CASES"));
+
+/// 单个程序超过该时间仍未退出则杀死它
+const TIMEOUT_MS: usize = 30_000;
+
+/// 命令行是否开启了一致性测试
+pub fn enabled() -> bool {
+    cmdline_has_conformance(&crate::device::CMDLINE.read())
+}
+
+fn cmdline_has_conformance(cmdline: &str) -> bool {
+    cmdline
+        .split_ascii_whitespace()
+        .any(|arg| arg == "conformance")
+}
+
+/// 输出与期望的一处差异，行号是期望文件中的行号
+#[derive(Debug, PartialEq, Eq)]
+pub enum Mismatch<'a> {
+    /// 结果不同
+    Differs {
+        line: usize,
+        expected: &'a str,
+        actual: &'a str,
+    },
+    /// 输出在这一行之前就结束了
+    Missing { line: usize, expected: &'a str },
+    /// 输出比期望多出的行
+    Unexpected { actual: &'a str },
+}
+
+impl fmt::Display for Mismatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Differs {
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "line {}: expected `{}`, got `{}`",
+                line, expected, actual
+            ),
+            Mismatch::Missing { line, expected } => {
+                write!(f, "line {}: expected `{}`, got nothing", line, expected)
+            }
+            Mismatch::Unexpected { actual } => write!(f, "unexpected `{}`", actual),
+        }
+    }
+}
+
+/// 逐行比较输出与期望
+///
+/// 期望中的空行和以 `#` 开头的注释行被跳过，其余各行须与输出的对应行完全相同。
+pub fn diff<'a>(expected: &'a str, actual: &'a str) -> Vec<Mismatch<'a>> {
+    let mut actual_lines = actual.lines();
+    let mut mismatches = Vec::new();
+    for (i, line) in expected.lines().enumerate() {
+        let expected = line.trim_end();
+        if expected.is_empty() || expected.starts_with('#') {
+            continue;
+        }
+        match actual_lines.next() {
+            Some(actual) if actual == expected => {}
+            Some(actual) => mismatches.push(Mismatch::Differs {
+                line: i + 1,
+                expected,
+                actual,
+            }),
+            None => mismatches.push(Mismatch::Missing {
+                line: i + 1,
+                expected,
+            }),
+        }
+    }
+    mismatches.extend(actual_lines.map(|actual| Mismatch::Unexpected { actual }));
+    mismatches
+}
+
+/// 运行所有用例，返回是否全部通过
+///
+/// 必须由 init 任务调用，程序作为其子进程运行并由本函数回收。
+pub fn run() -> bool {
+    let failed = CASES.iter().filter(|case| !run_case(case)).count();
+    if failed == 0 {
+        pr_info!("[Conformance] PASSED: {} programs", CASES.len());
+    } else {
+        pr_err!(
+            "[Conformance] FAILED: {} of {} programs",
+            failed,
+            CASES.len()
+        );
+    }
+    failed == 0
+}
+
+fn run_case(case: &ConformanceCase) -> bool {
+    if case.elf.is_empty() {
+        pr_err!(
+            "[Conformance] {}: program not embedded in this build",
+            case.name
+        );
+        return false;
+    }
+    let (status, output) = match execute(case) {
+        Ok(result) => result,
+        Err(e) => {
+            pr_err!("[Conformance] {}: failed to run: {:?}", case.name, e);
+            return false;
+        }
+    };
+
+    let mismatches = diff(case.expected, &output);
+    for mismatch in &mismatches {
+        pr_err!("[Conformance] {}: {}", case.name, mismatch);
+    }
+    let exited = status == Some(TaskExitStatus::Exited(0));
+    if !exited {
+        pr_err!("[Conformance] {}: exit status {:?}", case.name, status);
+    }
+    let passed = exited && mismatches.is_empty();
+    if passed {
+        pr_info!("[Conformance] {}: ok", case.name);
+    }
+    passed
+}
+
+/// 在新的 tmpfs 沙箱中运行程序，返回退出状态和它写到标准输出的内容
+fn execute(case: &ConformanceCase) -> Result<(Option<TaskExitStatus>, String), ExecImageError> {
+    let image = embedded::load(case.name, case.elf)?;
+
+    // 标准输入为空文件，标准输出和标准错误共享一个追加写的文件，都不在沙箱里
+    let stdio = TmpFs::new(0);
+    let stdin = stdio
+        .root_inode()
+        .create("stdin", FileMode::from_bits_truncate(0o644))?;
+    let stdout = stdio
+        .root_inode()
+        .create("stdout", FileMode::from_bits_truncate(0o644))?;
+    let open = |name: &str, inode: &Arc<dyn Inode>, flags| -> Arc<dyn File> {
+        Arc::new(RegFile::new(
+            Dentry::new(name.to_string(), inode.clone()),
+            flags,
+        ))
+    };
+    let fd_table = FDTable::new();
+    let out = open("stdout", &stdout, OpenFlags::O_WRONLY | OpenFlags::O_APPEND);
+    fd_table.install_at(0, open("stdin", &stdin, OpenFlags::O_RDONLY))?;
+    fd_table.install_at(1, out.clone())?;
+    fd_table.install_at(2, out)?;
+
+    let sandbox = TmpFs::new(0);
+    let root = Dentry::new(String::from("/"), sandbox.root_inode());
+    let fs = FsStruct::new(Some(root.clone()), Some(root));
+
+    let program = UserProgram {
+        image,
+        argv: vec![case.name.to_string()],
+        envp: Vec::new(),
+        credential: Credential::root(),
+    };
+    let child = embedded::spawn(program, fd_table, fs);
+    let status = embedded::wait_and_reap(child, TIMEOUT_MS);
+
+    let mut output = vec![0u8; stdout.metadata()?.size];
+    let len = stdout.read_at(0, &mut output)?;
+    output.truncate(len);
+    let output = String::from_utf8(output)
+        .unwrap_or_else(|e| format!("<{} bytes of non-UTF-8 output>", e.as_bytes().len()));
+    Ok((status, output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_conformance_cmdline, {
+        kassert!(cmdline_has_conformance("console=ttyS0 conformance"));
+        kassert!(!cmdline_has_conformance("console=ttyS0 selftest"));
+        kassert!(!cmdline_has_conformance("conformance=1"));
+    });
+
+    test_case!(test_conformance_diff, {
+        let expected = "# comment\nopen = 3\n\nread = 5\nclose = 0\n";
+        kassert!(diff(expected, "open = 3\nread = 5\nclose = 0\n").is_empty());
+
+        let mismatches = diff(expected, "open = 3\nread = EBADF\n");
+        kassert!(
+            mismatches
+                == [
+                    Mismatch::Differs {
+                        line: 4,
+                        expected: "read = 5",
+                        actual: "read = EBADF",
+                    },
+                    Mismatch::Missing {
+                        line: 5,
+                        expected: "close = 0",
+                    },
+                ]
+        );
+
+        let mismatches = diff("open = 3\n", "open = 3\npanic\n");
+        kassert!(mismatches == [Mismatch::Unexpected { actual: "panic" }]);
+        kassert!(mismatches[0].to_string() == "unexpected `panic`");
+    });
+
+    // 每个嵌入的程序都有非空的期望输出
+    test_case!(test_conformance_cases_embedded, {
+        kassert!(!CASES.is_empty());
+        for case in CASES {
+            kassert!(case.expected.lines().any(|line| line.contains(" = ")));
+        }
+    });
+}
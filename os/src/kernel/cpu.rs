@@ -5,12 +5,7 @@ use alloc::sync::Arc;
 use core::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::mm::activate;
-use crate::{
-    kernel::task::SharedTask,
-    mm::memory_space::MemorySpace,
-    sync::{PerCpu, SpinLock},
-};
-use lazy_static::lazy_static;
+use crate::{kernel::task::SharedTask, mm::memory_space::MemorySpace, sync::SpinLock};
 
 pub static NUM_CPU: AtomicUsize = AtomicUsize::new(1);
 pub static CLOCK_FREQ: AtomicUsize = AtomicUsize::new(12_500_000);
@@ -35,17 +30,12 @@ pub fn set_clock_freq(clock_freq: usize) {
     CLOCK_FREQ.store(clock_freq, Ordering::Release);
 }
 
-lazy_static! {
+crate::percpu! {
     /// Per-CPU 数据: 每个 CPU 的状态
     ///
-    /// 使用 PerCpu 容器自动实现缓存行对齐，避免伪共享。
+    /// 编译期初始化的缓存行对齐数组，不依赖堆，tp 指向本 CPU 的元素。
     /// 每个 CPU 只访问自己的 Cpu 实例，不需要锁保护。
-    ///
-    /// 注意：使用 MAX_CPU_COUNT 而不是 NUM_CPU，避免 lazy_static 初始化时机问题
-    pub static ref CPUS: PerCpu<Cpu> = {
-        use crate::config::MAX_CPU_COUNT;
-        PerCpu::new_with_id_and_count(MAX_CPU_COUNT, Cpu::new_with_id)
-    };
+    pub static CPUS: Cpu = |cpu_id| Cpu::new_with_id(cpu_id);
 }
 
 /// CPU 结构体
@@ -61,26 +51,34 @@ pub struct Cpu {
     /// 本 CPU 的 idle 任务（永远可用的兜底任务）
     /// 不在运行队列中，当没有可运行任务时切换到它并在其中 WFI。
     pub idle_task: Option<SharedTask>,
+    /// 当前任务的 pid 缓存，切换任务时更新，供快速路径免锁读取
+    pub current_pid: u32,
+    /// 当前任务的 tid 缓存
+    pub current_tid: u32,
 }
 
 impl Cpu {
     /// 创建一个新的 CPU 实例
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Cpu {
             cpu_id: 0,
             current_task: None,
             current_memory_space: None,
             idle_task: None,
+            current_pid: 0,
+            current_tid: 0,
         }
     }
 
     /// 创建一个新的 CPU 实例 (指定 CPU ID)
-    pub fn new_with_id(cpu_id: usize) -> Self {
+    pub const fn new_with_id(cpu_id: usize) -> Self {
         Cpu {
             cpu_id,
             current_task: None,
             current_memory_space: None,
             idle_task: None,
+            current_pid: 0,
+            current_tid: 0,
         }
     }
 
@@ -88,8 +86,14 @@ impl Cpu {
     /// # 参数
     /// * `task` - 要切换到的任务
     pub fn switch_task(&mut self, task: SharedTask) {
+        let to_idle = self
+            .idle_task
+            .as_ref()
+            .is_some_and(|idle| Arc::ptr_eq(idle, &task));
+        crate::kernel::account_switch(self.cpu_id, to_idle);
+
         // 切换当前任务，并在必要时切换到其地址空间
-        self.current_task = Some(task.clone());
+        self.set_current_task(task.clone());
         if !task.lock().is_kernel_thread() {
             self.current_memory_space = task.lock().memory_space.clone();
             activate(
@@ -109,6 +113,17 @@ impl Cpu {
         crate::arch::on_task_switch(tf_usize, self as *const _ as usize);
     }
 
+    /// 设置当前任务并刷新 pid/tid 缓存，不切换地址空间
+    ///
+    /// 任务的 pid/tid 创建后不再改变，因此缓存只需在这里更新。
+    pub fn set_current_task(&mut self, task: SharedTask) {
+        (self.current_pid, self.current_tid) = {
+            let t = task.lock();
+            (t.pid, t.tid)
+        };
+        self.current_task = Some(task);
+    }
+
     /// 切换当前内存空间
     /// # 参数
     /// * `space` - 要切换到的内存空间
@@ -127,7 +142,20 @@ impl Cpu {
 #[rustfmt::skip]
 pub fn current_cpu() -> &'static
 mut Cpu {
-    CPUS.get_mut()
+    // SAFETY: 由调用者保证访问期间不会迁移，见上
+    unsafe { CPUS.current_unchecked() }
+}
+
+/// 当前任务的 pid，不获取锁也不增加任务的引用计数
+#[inline]
+pub fn current_pid() -> u32 {
+    CPUS.with(|cpu| cpu.current_pid)
+}
+
+/// 当前任务的 tid，不获取锁也不增加任务的引用计数
+#[inline]
+pub fn current_tid() -> u32 {
+    CPUS.with(|cpu| cpu.current_tid)
 }
 
 /// 获取指定 CPU 的引用 (只读)
@@ -182,6 +210,35 @@ mod tests {
         }
     });
 
+    /// 测试设置当前任务时刷新 pid/tid 缓存
+    test_case!(test_set_current_task_caches_ids, {
+        use crate::kernel::TaskStruct;
+        use crate::sync::{PreemptGuard, lock_acquisitions};
+
+        let task = TaskStruct::new_dummy_task(4321).into_shared();
+        let (prev_task, prev_ids) = {
+            let _guard = PreemptGuard::new();
+            let cpu = current_cpu();
+            let prev_ids = (cpu.current_pid, cpu.current_tid);
+            let prev_task = cpu.current_task.take();
+            cpu.set_current_task(task.clone());
+            (prev_task, prev_ids)
+        };
+
+        let before = lock_acquisitions();
+        kassert!(current_pid() == 4321);
+        kassert!(current_tid() == 4321);
+        kassert!(lock_acquisitions() == before);
+
+        // 恢复原来的当前任务，不影响后续测试
+        {
+            let _guard = PreemptGuard::new();
+            let cpu = current_cpu();
+            cpu.current_task = prev_task;
+            (cpu.current_pid, cpu.current_tid) = prev_ids;
+        }
+    });
+
     /// 测试 PerCpu 数据独立性（多核场景）
     test_case!(test_per_cpu_independence, {
         use crate::sync::{PerCpu, PreemptGuard};
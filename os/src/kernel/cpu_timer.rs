@@ -0,0 +1,302 @@
+//! 进程 CPU 时间间隔定时器
+//!
+//! `ITIMER_VIRTUAL` 只在进程运行用户态代码时递减，到期发送 SIGVTALRM；
+//! `ITIMER_PROF` 在进程运行用户态或内核态（代表进程执行系统调用等）时都递减，
+//! 到期发送 SIGPROF。与按墙上时钟触发的 `ITIMER_REAL`（见 [`TIMER`](super::TIMER)）
+//! 不同，这两个定时器由时钟中断按节拍采样驱动：每个节拍把一个节拍长度记到被打断的
+//! 任务所在进程上，中断发生在用户态时同时计入两者，发生在内核态时只计入 PROF，
+//! idle 任务不记账。
+//!
+//! 与 Linux 一样，定时器属于进程（线程组），线程组内所有线程消耗的 CPU 时间都会
+//! 推进同一组定时器；三个定时器互相独立，fork 出的子进程不继承。
+
+use alloc::{collections::btree_map::BTreeMap, sync::Arc};
+
+use crate::{
+    kernel::{
+        ITIMER_EXIT_NOTIFIER, SharedTask, current_cpu, register_exit_notifier, send_signal_process,
+        time::TICK_CONFIG,
+    },
+    sync::SpinLock,
+    uapi::{
+        signal::{NUM_SIGPROF, NUM_SIGVTALRM},
+        time::{Itimerval, timeval},
+    },
+};
+
+/// 由 CPU 时间驱动的定时器种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuClock {
+    /// 仅用户态时间（ITIMER_VIRTUAL）
+    Virtual,
+    /// 用户态与内核态时间（ITIMER_PROF）
+    Prof,
+}
+
+impl CpuClock {
+    /// 到期时发送的信号
+    pub fn signal(self) -> usize {
+        match self {
+            CpuClock::Virtual => NUM_SIGVTALRM,
+            CpuClock::Prof => NUM_SIGPROF,
+        }
+    }
+}
+
+/// 单个 CPU 时间定时器，时间单位为纳秒
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuItimer {
+    /// 距到期剩余的 CPU 时间，0 表示未启用
+    pub value_ns: u64,
+    /// 到期后重新装载的周期，0 表示一次性
+    pub interval_ns: u64,
+}
+
+impl CpuItimer {
+    /// 由用户传入的 `Itimerval` 构造
+    pub fn from_itimerval(val: &Itimerval) -> Self {
+        Self {
+            value_ns: timeval_to_ns(&val.it_value),
+            interval_ns: timeval_to_ns(&val.it_interval),
+        }
+    }
+
+    /// 转换为返回给用户的 `Itimerval`
+    pub fn to_itimerval(self) -> Itimerval {
+        Itimerval {
+            it_interval: ns_to_timeval(self.interval_ns),
+            it_value: ns_to_timeval(self.value_ns),
+        }
+    }
+
+    /// 是否处于启用状态
+    pub fn is_armed(&self) -> bool {
+        self.value_ns != 0
+    }
+
+    /// 消耗 `delta_ns` 的 CPU 时间，返回是否到期
+    ///
+    /// 到期时按周期重新装载；一个节拍跨过多个周期时只报告一次到期，
+    /// 与 Linux 一样不累计错过的信号。
+    pub fn charge(&mut self, delta_ns: u64) -> bool {
+        if !self.is_armed() {
+            return false;
+        }
+        if delta_ns < self.value_ns {
+            self.value_ns -= delta_ns;
+            return false;
+        }
+        self.value_ns = if self.interval_ns == 0 {
+            0
+        } else {
+            let overrun = (delta_ns - self.value_ns) % self.interval_ns;
+            self.interval_ns - overrun
+        };
+        true
+    }
+}
+
+fn timeval_to_ns(tv: &timeval) -> u64 {
+    (tv.tv_sec.max(0) as u64)
+        .saturating_mul(1_000_000_000)
+        .saturating_add(tv.tv_usec.max(0) as u64 * 1000)
+}
+
+fn ns_to_timeval(ns: u64) -> timeval {
+    // 与 Linux 一样向上取整到微秒，剩余时间不为 0 的定时器不会被读成未启用
+    let us = ns.div_ceil(1000);
+    timeval::new((us / 1_000_000) as _, (us % 1_000_000) as _)
+}
+
+/// 一个进程的 CPU 时间定时器
+pub struct ProcessCpuTimers {
+    /// 线程组 leader，到期信号发给它所在的进程
+    owner: SharedTask,
+    virt: CpuItimer,
+    prof: CpuItimer,
+}
+
+impl ProcessCpuTimers {
+    fn new(owner: SharedTask) -> Self {
+        Self {
+            owner,
+            virt: CpuItimer::default(),
+            prof: CpuItimer::default(),
+        }
+    }
+
+    fn timer(&self, clock: CpuClock) -> &CpuItimer {
+        match clock {
+            CpuClock::Virtual => &self.virt,
+            CpuClock::Prof => &self.prof,
+        }
+    }
+
+    fn timer_mut(&mut self, clock: CpuClock) -> &mut CpuItimer {
+        match clock {
+            CpuClock::Virtual => &mut self.virt,
+            CpuClock::Prof => &mut self.prof,
+        }
+    }
+
+    /// 记一段 CPU 时间，返回需要发送的信号
+    fn charge(&mut self, delta_ns: u64, user: bool) -> [Option<usize>; 2] {
+        let virt = (user && self.virt.charge(delta_ns)).then_some(CpuClock::Virtual.signal());
+        let prof = self
+            .prof
+            .charge(delta_ns)
+            .then_some(CpuClock::Prof.signal());
+        [virt, prof]
+    }
+
+    fn is_idle(&self) -> bool {
+        !self.virt.is_armed() && !self.prof.is_armed()
+    }
+}
+
+lazy_static::lazy_static! {
+    /// 已启用 CPU 时间定时器的进程，以线程组 id 为键
+    static ref CPU_ITIMERS: SpinLock<BTreeMap<u32, ProcessCpuTimers>> =
+        SpinLock::new(BTreeMap::new());
+}
+
+/// 读取进程的 CPU 时间定时器
+pub fn get_cpu_itimer(pid: u32, clock: CpuClock) -> CpuItimer {
+    CPU_ITIMERS
+        .lock()
+        .get(&pid)
+        .map(|timers| *timers.timer(clock))
+        .unwrap_or_default()
+}
+
+/// 设置进程的 CPU 时间定时器，返回旧值
+/// # 参数
+/// - `owner`: 线程组 leader
+/// - `pid`: 线程组 id
+/// - `clock`: 定时器种类
+/// - `new`: 新的定时器值，`value_ns` 为 0 表示撤销
+pub fn set_cpu_itimer(owner: &SharedTask, pid: u32, clock: CpuClock, new: CpuItimer) -> CpuItimer {
+    let mut timers = CPU_ITIMERS.lock();
+    if !new.is_armed() {
+        let Some(entry) = timers.get_mut(&pid) else {
+            return CpuItimer::default();
+        };
+        let old = core::mem::take(entry.timer_mut(clock));
+        if entry.is_idle() {
+            timers.remove(&pid);
+        }
+        return old;
+    }
+    register_exit_notifier(owner, &ITIMER_EXIT_NOTIFIER);
+    let entry = timers
+        .entry(pid)
+        .or_insert_with(|| ProcessCpuTimers::new(owner.clone()));
+    core::mem::replace(entry.timer_mut(clock), new)
+}
+
+/// 撤销进程的所有 CPU 时间定时器
+pub fn remove_cpu_itimers(owner: &SharedTask) {
+    CPU_ITIMERS
+        .lock()
+        .retain(|_, timers| !Arc::ptr_eq(&timers.owner, owner));
+}
+
+/// 时钟中断中调用：把一个节拍记到被打断的任务所在进程上
+/// # 参数
+/// - `user`: 中断是否发生在用户态
+pub fn account_cpu_tick(user: bool) {
+    let pid = {
+        let cpu = current_cpu();
+        let Some(task) = cpu.current_task.as_ref() else {
+            return;
+        };
+        if cpu
+            .idle_task
+            .as_ref()
+            .is_some_and(|idle| Arc::ptr_eq(idle, task))
+        {
+            return;
+        }
+        task.lock().pid
+    };
+    let tick_ns = 1_000_000_000 / TICK_CONFIG.hz() as u64;
+    let (owner, signals) = {
+        let mut timers = CPU_ITIMERS.lock();
+        let Some(entry) = timers.get_mut(&pid) else {
+            return;
+        };
+        let signals = entry.charge(tick_ns, user);
+        let owner = entry.owner.clone();
+        if entry.is_idle() {
+            timers.remove(&pid);
+        }
+        (owner, signals)
+    };
+    for sig in signals.into_iter().flatten() {
+        send_signal_process(&owner, sig);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, kernel::TaskStruct, test_case};
+
+    test_case!(test_cpu_itimer_charge, {
+        let mut t = CpuItimer {
+            value_ns: 25,
+            interval_ns: 0,
+        };
+        kassert!(!t.charge(10));
+        kassert!(t.value_ns == 15);
+        kassert!(t.charge(10));
+        kassert!(!t.is_armed());
+        kassert!(!t.charge(10));
+
+        // 周期定时器到期后重新装载，跨过的部分从下一个周期中扣除
+        let mut t = CpuItimer {
+            value_ns: 10,
+            interval_ns: 30,
+        };
+        kassert!(t.charge(15));
+        kassert!(t.value_ns == 25);
+        kassert!(t.charge(100));
+        kassert!(t.value_ns == 15);
+    });
+
+    // 用户态节拍推进 VIRTUAL 和 PROF，内核态节拍只推进 PROF
+    test_case!(test_process_cpu_timers_independent, {
+        let owner = TaskStruct::new_dummy_task(1).into_shared();
+        let mut timers = ProcessCpuTimers::new(owner);
+        timers.virt = CpuItimer {
+            value_ns: 20,
+            interval_ns: 0,
+        };
+        timers.prof = CpuItimer {
+            value_ns: 30,
+            interval_ns: 30,
+        };
+
+        kassert!(timers.charge(10, false) == [None, None]);
+        kassert!(timers.virt.value_ns == 20);
+        kassert!(timers.prof.value_ns == 20);
+
+        kassert!(timers.charge(10, true) == [None, None]);
+        kassert!(timers.charge(10, true) == [Some(NUM_SIGVTALRM), Some(NUM_SIGPROF)]);
+        kassert!(!timers.virt.is_armed());
+        kassert!(timers.prof.value_ns == 30);
+        kassert!(!timers.is_idle());
+    });
+
+    test_case!(test_cpu_itimer_timeval_round_trip, {
+        let val = Itimerval {
+            it_interval: timeval::new(1, 500),
+            it_value: timeval::new(0, 250_000),
+        };
+        let t = CpuItimer::from_itimerval(&val);
+        kassert!(t.value_ns == 250_000_000);
+        kassert!(t.interval_ns == 1_000_500_000);
+        kassert!(t.to_itimerval() == val);
+        kassert!(ns_to_timeval(1).tv_usec == 1);
+    });
+}
@@ -0,0 +1,143 @@
+//! 运行嵌入内核的用户态程序
+//!
+//! 启动自检和一致性测试都把 build.rs 编译的 ELF 作为 init 的子进程运行：调用者准备好
+//! 映像、fd 表和文件系统信息，本模块创建进程、等待它退出并回收。
+//! 必须由 init 任务调用，同一时间只能运行一个程序。
+
+use alloc::{string::String, sync::Arc, vec::Vec};
+
+use lazy_static::lazy_static;
+
+use crate::{
+    ipc::{SignalHandlerTable, SignalPending},
+    kernel::{
+        Credential, ExecImageError, FsStruct, PreparedExecImage, Scheduler, SharedTask,
+        TASK_MANAGER, TaskExitStatus, TaskManagerTrait, TaskState, TaskStruct, current_task,
+        kernel_execve_prepared, prepare_exec_image, scheduler_of, send_signal_process, yield_task,
+    },
+    mm::frame_allocator::{alloc_contig_frames, alloc_frame},
+    pr_err,
+    sync::SpinLock,
+    uapi::signal::{NUM_SIGKILL, SignalFlags},
+    vfs::{FDTable, FileMode, FileSystem},
+};
+
+/// 等待子进程 exec 的程序
+pub struct UserProgram {
+    pub image: PreparedExecImage,
+    /// argv[0] 同时用作进程名
+    pub argv: Vec<String>,
+    pub envp: Vec<String>,
+    /// 进程的凭证
+    pub credential: Credential,
+}
+
+lazy_static! {
+    static ref PENDING_PROGRAM: SpinLock<Option<UserProgram>> = SpinLock::new(None);
+}
+
+/// 把 ELF 写进一个私有 tmpfs 并加载，不依赖根文件系统
+pub fn load(name: &str, elf: &[u8]) -> Result<PreparedExecImage, ExecImageError> {
+    let tmpfs = crate::fs::tmpfs::TmpFs::new(0);
+    let inode = tmpfs
+        .root_inode()
+        .create(name, FileMode::from_bits_truncate(0o755))?;
+    inode.write_at(0, elf)?;
+    prepare_exec_image(inode.as_ref())
+}
+
+/// 以当前任务为父进程创建执行 `program` 的新进程
+pub fn spawn(program: UserProgram, fd_table: FDTable, fs: FsStruct) -> SharedTask {
+    let credential = program.credential;
+    *PENDING_PROGRAM.lock() = Some(program);
+
+    let tid = TASK_MANAGER.lock().allocate_tid();
+    let kstack_tracker = alloc_contig_frames(4).expect("embedded: failed to alloc kstack");
+    let trap_frame_tracker = alloc_frame().expect("embedded: failed to alloc trap_frame");
+    let parent = current_task();
+    let (ppid, uts, rlimit) = {
+        let t = parent.lock();
+        (t.pid, t.uts_namespace.clone(), t.rlimit.clone())
+    };
+    let mut task = TaskStruct::ktask_create(
+        tid,
+        tid,
+        ppid,
+        TaskStruct::empty_children(),
+        kstack_tracker,
+        trap_frame_tracker,
+        Arc::new(SpinLock::new(SignalHandlerTable::new())),
+        SignalFlags::empty(),
+        Arc::new(SpinLock::new(SignalPending::empty())),
+        uts,
+        rlimit,
+        Arc::new(fd_table),
+        Arc::new(SpinLock::new(fs)),
+    );
+    task.credential = credential;
+
+    let task_frame = task
+        .trap_frame_ptr
+        .load(core::sync::atomic::Ordering::SeqCst);
+    unsafe {
+        crate::arch::init_kernel_trap_frame(
+            task_frame,
+            program_entry as usize,
+            0,
+            task.kstack_base.as_usize(),
+        );
+    }
+    let task = task.into_shared();
+    parent.lock().children.lock().push(task.clone());
+    TASK_MANAGER.lock().add_task(task.clone());
+    task.lock().on_cpu = Some(0);
+    scheduler_of(0).lock().add_task(task.clone());
+    task
+}
+
+/// 子进程入口：exec 事先准备好的程序
+fn program_entry() {
+    let program = PENDING_PROGRAM
+        .lock()
+        .take()
+        .expect("embedded: no pending program");
+    let argv: Vec<&str> = program.argv.iter().map(String::as_str).collect();
+    let envp: Vec<&str> = program.envp.iter().map(String::as_str).collect();
+    kernel_execve_prepared(argv[0], program.image, &argv, &envp);
+}
+
+/// 等待子进程成为僵尸并回收，超过 `timeout_ms` 则先杀死它
+pub fn wait_and_reap(child: SharedTask, timeout_ms: usize) -> Option<TaskExitStatus> {
+    let start = crate::arch::get_time_ms();
+    let mut killed = false;
+    loop {
+        if child.lock().state == TaskState::Zombie {
+            break;
+        }
+        if !killed && crate::arch::get_time_ms() - start > timeout_ms {
+            pr_err!(
+                "[{}] User program timed out after {} ms",
+                child.lock().comm(),
+                timeout_ms
+            );
+            send_signal_process(&child, NUM_SIGKILL);
+            killed = true;
+        }
+        yield_task();
+    }
+
+    let (tid, status) = {
+        let t = child.lock();
+        (t.tid, t.exit_status)
+    };
+    let parent = current_task();
+    parent
+        .lock()
+        .children
+        .lock()
+        .retain(|c| c.lock().tid != tid);
+    TASK_MANAGER.lock().release_task(child);
+    // 子进程退出时给 init 发送的 SIGCHLD 已无意义，不要留给 /sbin/init
+    parent.lock().pending.signals.remove(SignalFlags::SIGCHLD);
+    status
+}
@@ -0,0 +1,205 @@
+//! 随机系统调用模糊测试
+//!
+//! `user/fuzz` 以对抗性参数（野指针、内核地址、越过映射末尾的缓冲区、超大长度、无效 fd）
+//! 反复发起随机系统调用，检查各系统调用对用户指针和参数的校验。build.rs 把它编译后
+//! 嵌入内核。
+//!
+//! 命令行带 `fuzz` 或 `fuzz=<种子>` 时，init 在 exec /sbin/init 之前运行它：进程以无特权
+//! 用户 nobody 运行，不带任何能力，根目录和当前目录是一个新建的空 tmpfs，标准输入输出
+//! 继承自 init。种子在运行前写入内核日志，用同一种子可以重放同一串调用；`fuzz_calls=<n>`
+//! 修改调用次数，`fuzz_verbose` 让程序在每次调用前输出调用名和参数。
+//!
+//! 通过的标准是内核没有 panic、程序在限时内正常退出。程序被信号杀死或超时都记为
+//! 用户态测试失败，但不阻止启动。
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::{
+    fs::tmpfs::TmpFs,
+    kernel::{
+        CapabilitySet, Credential, ExecImageError, FsStruct, TaskExitStatus, current_task,
+        embedded::{self, UserProgram},
+    },
+    pr_err, pr_info,
+    vfs::{Dentry, FileSystem},
+};
+
+/// 由 build.rs 编译的模糊测试程序，非目标架构上为空
+static FUZZ_ELF: &[u8] = include_bytes!(env!("FUZZ_ELF"));
+
+/// 默认的调用次数
+const DEFAULT_CALLS: usize = 20_000;
+
+/// 超过该时间仍未退出则认为内核在某个调用中挂起
+const TIMEOUT_MS: usize = 120_000;
+
+/// 沙箱 tmpfs 的容量，防止超大的 ftruncate 和 write 耗尽内存
+const SANDBOX_SIZE_MB: usize = 16;
+
+/// 运行模糊测试的用户，即 Linux 的 nobody
+const FUZZ_UID: u32 = 65534;
+const FUZZ_GID: u32 = 65534;
+
+/// 命令行中的模糊测试参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FuzzConfig {
+    /// 未指定时按启动时间生成
+    seed: Option<u64>,
+    calls: usize,
+    verbose: bool,
+}
+
+/// 命令行是否开启了模糊测试
+pub fn enabled() -> bool {
+    parse_cmdline(&crate::device::CMDLINE.read()).is_some()
+}
+
+fn parse_cmdline(cmdline: &str) -> Option<FuzzConfig> {
+    let mut config = None;
+    let mut calls = DEFAULT_CALLS;
+    let mut verbose = false;
+    for arg in cmdline.split_ascii_whitespace() {
+        match arg.split_once('=') {
+            None if arg == "fuzz" => config = Some(None),
+            Some(("fuzz", seed)) => config = Some(parse_number(seed)),
+            Some(("fuzz_calls", n)) => {
+                calls = parse_number(n).map_or(DEFAULT_CALLS, |n| n as usize);
+            }
+            None if arg == "fuzz_verbose" => verbose = true,
+            _ => {}
+        }
+    }
+    config.map(|seed| FuzzConfig {
+        seed,
+        calls,
+        verbose,
+    })
+}
+
+/// 解析十进制或 0x 开头的十六进制数
+fn parse_number(s: &str) -> Option<u64> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// 无特权的 nobody 凭证，不带任何能力
+fn unprivileged() -> Credential {
+    Credential {
+        uid: FUZZ_UID,
+        gid: FUZZ_GID,
+        euid: FUZZ_UID,
+        egid: FUZZ_GID,
+        suid: FUZZ_UID,
+        sgid: FUZZ_GID,
+        fsuid: FUZZ_UID,
+        fsgid: FUZZ_GID,
+        capabilities: CapabilitySet::empty(),
+        ..Credential::root()
+    }
+}
+
+/// 按命令行运行模糊测试，返回程序是否正常退出
+///
+/// 必须由 init 任务调用，程序作为其子进程运行并由本函数回收。
+pub fn run() -> bool {
+    let Some(config) = parse_cmdline(&crate::device::CMDLINE.read()) else {
+        return true;
+    };
+    if FUZZ_ELF.is_empty() {
+        pr_err!("[Fuzz] No fuzzer embedded in this build");
+        return false;
+    }
+    let seed = config
+        .seed
+        .unwrap_or_else(|| crate::arch::get_time() as u64);
+    pr_info!(
+        "[Fuzz] Seed {:#x}, {} calls (reproduce with fuzz={:#x} fuzz_calls={})",
+        seed,
+        config.calls,
+        seed,
+        config.calls
+    );
+
+    let status = match execute(seed, &config) {
+        Ok(status) => status,
+        Err(e) => {
+            pr_err!("[Fuzz] Failed to load fuzzer: {:?}", e);
+            return false;
+        }
+    };
+    let passed = status == Some(TaskExitStatus::Exited(0));
+    if passed {
+        pr_info!("[Fuzz] PASSED: seed {:#x}", seed);
+    } else {
+        pr_err!("[Fuzz] FAILED: seed {:#x}, exit status {:?}", seed, status);
+    }
+    passed
+}
+
+fn execute(seed: u64, config: &FuzzConfig) -> Result<Option<TaskExitStatus>, ExecImageError> {
+    let image = embedded::load("fuzz", FUZZ_ELF)?;
+
+    // 沙箱根目录属于 nobody，程序可以在其中创建文件
+    let sandbox = TmpFs::new(SANDBOX_SIZE_MB);
+    sandbox.root_inode().chown(FUZZ_UID, FUZZ_GID)?;
+    let root = Dentry::new(String::from("/"), sandbox.root_inode());
+    let fs = FsStruct::new(Some(root.clone()), Some(root));
+    let fd_table = current_task().lock().fd_table.clone_table();
+
+    let mut argv = vec![
+        String::from("fuzz"),
+        format!("{:#x}", seed),
+        format!("{}", config.calls),
+    ];
+    if config.verbose {
+        argv.push(String::from("-v"));
+    }
+    let program = UserProgram {
+        image,
+        argv,
+        envp: Vec::new(),
+        credential: unprivileged(),
+    };
+    let child = embedded::spawn(program, fd_table, fs);
+    Ok(embedded::wait_and_reap(child, TIMEOUT_MS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_fuzz_cmdline, {
+        kassert!(parse_cmdline("console=ttyS0").is_none());
+        kassert!(parse_cmdline("fuzz_calls=10").is_none());
+        kassert!(
+            parse_cmdline("fuzz")
+                == Some(FuzzConfig {
+                    seed: None,
+                    calls: DEFAULT_CALLS,
+                    verbose: false,
+                })
+        );
+        kassert!(
+            parse_cmdline("fuzz=0x2a fuzz_calls=100 fuzz_verbose")
+                == Some(FuzzConfig {
+                    seed: Some(42),
+                    calls: 100,
+                    verbose: true,
+                })
+        );
+        kassert!(parse_cmdline("fuzz=42").unwrap().seed == Some(42));
+        // 种子写错时退回到按时间生成，而不是静默关闭
+        kassert!(parse_cmdline("fuzz=abc").unwrap().seed.is_none());
+    });
+
+    test_case!(test_fuzz_credential, {
+        let cred = unprivileged();
+        kassert!(!cred.is_root());
+        kassert!(cred.fsuid == FUZZ_UID && cred.fsgid == FUZZ_GID);
+        kassert!(cred.capabilities.effective.is_empty());
+        kassert!(cred.capabilities.permitted.is_empty());
+    });
+}
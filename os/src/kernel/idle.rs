@@ -0,0 +1,259 @@
+//! CPU 空闲管理模块
+//!
+//! 每个 CPU 的 idle 任务循环调用 [`cpu_idle`]：
+//! - 空闲调控器（governor）根据最近的定时器截止时间选择空闲状态：
+//!   - `Shallow`：保留周期节拍，等待下一个中断；
+//!   - `Tickless`：把时钟中断推迟到最近的截止时间，中途不再产生节拍。
+//! - 进入和离开 WFI 之间的硬件时钟周期计入该 CPU 的空闲时间，
+//!   供 `/proc/stat`、`/proc/uptime` 使用。
+//!
+//! 此外提供 suspend-to-idle（[`suspend_to_idle`]），由 `/proc/sys/power/state`
+//! 写入 `freeze` 触发：同步块设备、通知驱动挂起、停掉节拍后等待唤醒中断。
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::arch::timer::TIMER_TICKS;
+use crate::config::MAX_CPU_COUNT;
+use crate::kernel::{TIMER, TIMER_QUEUE, current_scheduler};
+
+/// 截止时间不足这么多个节拍时不值得停掉周期节拍
+const MIN_TICKLESS_TICKS: usize = 2;
+
+/// 空闲状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleState {
+    /// 保留周期节拍等待中断
+    Shallow,
+    /// 停掉周期节拍，直到硬件时间 `deadline` 再唤醒
+    Tickless(usize),
+}
+
+/// 单个 CPU 的空闲统计
+struct IdleStats {
+    /// 在 WFI 中度过的硬件时钟周期（含 suspend-to-idle）
+    idle_cycles: AtomicU64,
+    /// 进入 Shallow 状态的次数
+    shallow_entries: AtomicU64,
+    /// 进入 Tickless 状态的次数
+    tickless_entries: AtomicU64,
+}
+
+impl IdleStats {
+    const fn new() -> Self {
+        Self {
+            idle_cycles: AtomicU64::new(0),
+            shallow_entries: AtomicU64::new(0),
+            tickless_entries: AtomicU64::new(0),
+        }
+    }
+}
+
+static IDLE_STATS: [IdleStats; MAX_CPU_COUNT] = [const { IdleStats::new() }; MAX_CPU_COUNT];
+
+/// suspend-to-idle 完成次数
+static SUSPEND_COUNT: AtomicU64 = AtomicU64::new(0);
+/// 累计在 suspend-to-idle 中度过的硬件时钟周期
+static SUSPEND_CYCLES: AtomicU64 = AtomicU64::new(0);
+
+/// 单个 CPU 空闲统计的快照
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdleSnapshot {
+    /// 空闲时间（硬件时钟周期）
+    pub idle_cycles: u64,
+    /// 进入 Shallow 状态的次数
+    pub shallow_entries: u64,
+    /// 进入 Tickless 状态的次数
+    pub tickless_entries: u64,
+}
+
+/// 读取指定 CPU 的空闲统计
+pub fn idle_snapshot(cpu: usize) -> IdleSnapshot {
+    let stats = &IDLE_STATS[cpu];
+    IdleSnapshot {
+        idle_cycles: stats.idle_cycles.load(Ordering::Relaxed),
+        shallow_entries: stats.shallow_entries.load(Ordering::Relaxed),
+        tickless_entries: stats.tickless_entries.load(Ordering::Relaxed),
+    }
+}
+
+/// 所有 CPU 空闲时间之和（硬件时钟周期）
+pub fn total_idle_cycles() -> u64 {
+    (0..crate::kernel::num_cpu())
+        .map(|cpu| IDLE_STATS[cpu].idle_cycles.load(Ordering::Relaxed))
+        .sum()
+}
+
+/// suspend-to-idle 的完成次数与累计时长（硬件时钟周期）
+pub fn suspend_stats() -> (u64, u64) {
+    (
+        SUSPEND_COUNT.load(Ordering::Relaxed),
+        SUSPEND_CYCLES.load(Ordering::Relaxed),
+    )
+}
+
+/// 一个节拍对应的硬件时钟周期数
+#[inline]
+fn tick_cycles() -> usize {
+    crate::kernel::time::TICK_CONFIG.tick_cycles()
+}
+
+/// 按空闲时长补记错过的节拍
+///
+/// `observed` 为期间已由时钟中断记下的节拍数。
+fn catch_up_ticks(elapsed: usize, observed: usize) {
+    let missed = (elapsed / tick_cycles()).saturating_sub(observed);
+    if missed > 0 {
+        TIMER_TICKS.fetch_add(missed, Ordering::Relaxed);
+    }
+}
+
+/// 空闲调控器：给出下一次空闲应进入的状态
+///
+/// 只要有任务可能依赖周期节拍（可运行任务、poll 等待者、打开的套接字），
+/// 就保留节拍；否则停到最近的定时器截止时间（睡眠任务的截止时间计入其
+/// timer slack，相近的唤醒合并为一次中断）。即使没有任何定时器，
+/// 也至少每秒醒来一次，限制节拍计数的误差。
+pub fn select_state(now: usize) -> IdleState {
+    if !current_scheduler().lock().is_empty()
+        || crate::kernel::syscall::io::has_poll_waiters()
+        || crate::net::socket::has_open_sockets()
+    {
+        return IdleState::Shallow;
+    }
+
+    let period = tick_cycles();
+    let limit = now + crate::kernel::time::TICK_CONFIG.hz() * period;
+    let deadline = [
+        TIMER_QUEUE.lock().next_expiry(),
+        TIMER.lock().next_deadline(),
+        crate::kernel::executor::next_async_timer(),
+    ]
+    .into_iter()
+    .flatten()
+    .fold(limit, usize::min);
+
+    governor_decide(now, deadline, period)
+}
+
+/// 根据截止时间决定空闲状态（与全局状态无关，便于测试）
+fn governor_decide(now: usize, deadline: usize, period: usize) -> IdleState {
+    if deadline <= now + MIN_TICKLESS_TICKS * period {
+        IdleState::Shallow
+    } else {
+        IdleState::Tickless(deadline)
+    }
+}
+
+/// 执行一次空闲
+///
+/// 在关中断状态下选择状态并 WFI，唤醒后先完成计时再开中断，
+/// 这样中断处理程序中发生的调度不会被算作空闲时间。
+pub fn cpu_idle() {
+    let flags = crate::arch::disable_interrupts();
+    let stats = &IDLE_STATS[crate::arch::cpu_id()];
+    crate::kernel::stall::touch_progress();
+
+    let start = crate::arch::get_time();
+    let state = select_state(start);
+    match state {
+        IdleState::Shallow => {
+            stats.shallow_entries.fetch_add(1, Ordering::Relaxed);
+        }
+        IdleState::Tickless(deadline) => {
+            stats.tickless_entries.fetch_add(1, Ordering::Relaxed);
+            crate::kernel::TIMER_ONESHOT_EVENTS.inc();
+            crate::arch::set_oneshot_trigger(deadline);
+        }
+    }
+
+    crate::arch::wait_for_interrupt();
+
+    let end = crate::arch::get_time();
+    let elapsed = end.saturating_sub(start);
+    stats
+        .idle_cycles
+        .fetch_add(elapsed as u64, Ordering::Relaxed);
+
+    if let IdleState::Tickless(deadline) = state {
+        if end < deadline {
+            // 被其它中断提前唤醒，一次性定时器尚未到期，恢复周期节拍
+            crate::arch::timer::set_next_trigger();
+        }
+        // 到期的时钟中断开中断后才会记下 1 个节拍
+        catch_up_ticks(elapsed, 1);
+    }
+
+    crate::arch::restore_interrupt_state(flags);
+}
+
+/// 进入 suspend-to-idle，直到有唤醒中断（设备中断或 IPI）到来
+///
+/// 只挂起调用者所在的 CPU；其它 CPU 照常在各自的 idle 循环中等待。
+pub fn suspend_to_idle() {
+    use crate::device::{BLK_DRIVERS, DRIVERS};
+
+    crate::pr_info!("[PM] suspend-to-idle: entering");
+
+    // 与 sync 相同：刷新块设备写缓存
+    for blk in BLK_DRIVERS.read().iter() {
+        blk.flush();
+    }
+    let drivers = DRIVERS.read().clone();
+    for driver in drivers.iter() {
+        driver.suspend();
+    }
+    crate::kernel::watchdog::suspend();
+
+    let flags = crate::arch::disable_interrupts();
+    crate::arch::stop_tick();
+
+    let start = crate::arch::get_time();
+    crate::arch::wait_for_interrupt();
+    let end = crate::arch::get_time();
+
+    crate::arch::restart_tick();
+    let elapsed = end.saturating_sub(start);
+    catch_up_ticks(elapsed, 0);
+    IDLE_STATS[crate::arch::cpu_id()]
+        .idle_cycles
+        .fetch_add(elapsed as u64, Ordering::Relaxed);
+    SUSPEND_CYCLES.fetch_add(elapsed as u64, Ordering::Relaxed);
+    SUSPEND_COUNT.fetch_add(1, Ordering::Relaxed);
+
+    // 开中断后唤醒中断才会被处理
+    crate::kernel::stall::touch_progress();
+    crate::arch::restore_interrupt_state(flags);
+
+    crate::kernel::watchdog::resume();
+    for driver in drivers.iter().rev() {
+        driver.resume();
+    }
+
+    crate::pr_info!(
+        "[PM] suspend-to-idle: resumed after {} ms",
+        elapsed as u128 * 1000 / crate::arch::clock_freq() as u128
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_governor_keeps_tick_for_near_deadline, {
+        kassert!(governor_decide(1000, 1000, 100) == IdleState::Shallow);
+        kassert!(governor_decide(1000, 1200, 100) == IdleState::Shallow);
+        kassert!(governor_decide(1000, 1201, 100) == IdleState::Tickless(1201));
+    });
+
+    test_case!(test_idle_time_accumulates, {
+        let cpu = {
+            let _guard = crate::sync::PreemptGuard::new();
+            crate::arch::cpu_id()
+        };
+        let before = idle_snapshot(cpu);
+        IDLE_STATS[cpu].idle_cycles.fetch_add(5, Ordering::Relaxed);
+        kassert!(idle_snapshot(cpu).idle_cycles >= before.idle_cycles + 5);
+        kassert!(total_idle_cycles() >= before.idle_cycles + 5);
+    });
+}
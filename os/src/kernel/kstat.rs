@@ -0,0 +1,237 @@
+//! 内核统计指标注册表
+//!
+//! 各子系统把自己的统计量以指标名登记到全局注册表，`sysinfo`、`/proc/meminfo`、
+//! `/proc/vmstat` 和 `/proc/stat` 都只从注册表取数，同一个量在各接口中保持一致。
+//!
+//! 指标有三种来源：
+//! - [`Counter`]：只增不减的事件计数，如换入/换出的页数；
+//! - [`Gauge`]：由子系统设置的当前值，如初始堆大小；
+//! - 探针（`fn() -> u64`）：读取时现场计算，适合已由子系统自行维护的量，
+//!   如帧分配器的空闲帧数。
+//!
+//! 计数器和仪表只做原子更新，可以在任意上下文（包括中断和分配路径）中使用；
+//! 注册表本身只在注册和读取快照时加锁，探针在锁外求值。
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::config::PAGE_SIZE;
+use crate::sync::SpinLock;
+
+/// 事件计数器，只增不减
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    pub const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 当前值，由所属子系统整体设置
+pub struct Gauge(AtomicU64);
+
+impl Gauge {
+    pub const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    pub fn set(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for Gauge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 指标的单位，决定渲染时如何换算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// 无量纲计数
+    Count,
+    /// 页数
+    Pages,
+    /// 字节数
+    Bytes,
+}
+
+/// 指标的取值来源
+#[derive(Clone, Copy)]
+pub enum Source {
+    Counter(&'static Counter),
+    Gauge(&'static Gauge),
+    Probe(fn() -> u64),
+}
+
+impl Source {
+    fn read(&self) -> u64 {
+        match self {
+            Source::Counter(counter) => counter.get(),
+            Source::Gauge(gauge) => gauge.get(),
+            Source::Probe(probe) => probe(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Metric {
+    unit: Unit,
+    source: Source,
+}
+
+/// 指标名到指标的映射，按名字排序
+static REGISTRY: SpinLock<BTreeMap<&'static str, Metric>> = SpinLock::new(BTreeMap::new());
+
+/// 登记一个指标；同名指标由后登记者覆盖
+pub fn register(name: &'static str, unit: Unit, source: Source) {
+    REGISTRY.lock().insert(name, Metric { unit, source });
+}
+
+/// 登记一个计数器
+pub fn register_counter(name: &'static str, unit: Unit, counter: &'static Counter) {
+    register(name, unit, Source::Counter(counter));
+}
+
+/// 登记一个仪表
+pub fn register_gauge(name: &'static str, unit: Unit, gauge: &'static Gauge) {
+    register(name, unit, Source::Gauge(gauge));
+}
+
+/// 登记一个读取时计算的探针
+pub fn register_probe(name: &'static str, unit: Unit, probe: fn() -> u64) {
+    register(name, unit, Source::Probe(probe));
+}
+
+/// 某一时刻所有指标的取值
+///
+/// 渲染一个接口时先取一次快照，同一份输出中的各个数字来自同一次读取。
+pub struct Snapshot {
+    values: Vec<(&'static str, Unit, u64)>,
+}
+
+impl Snapshot {
+    fn find(&self, name: &str) -> Option<&(&'static str, Unit, u64)> {
+        self.values
+            .binary_search_by(|(n, _, _)| (*n).cmp(name))
+            .ok()
+            .map(|index| &self.values[index])
+    }
+
+    /// 指标的原始值；未登记时为 0
+    pub fn get(&self, name: &str) -> u64 {
+        self.find(name).map_or(0, |&(_, _, value)| value)
+    }
+
+    /// 指标换算为字节；计数类指标原样返回
+    pub fn bytes(&self, name: &str) -> u64 {
+        match self.find(name) {
+            Some(&(_, Unit::Pages, value)) => value * PAGE_SIZE as u64,
+            Some(&(_, _, value)) => value,
+            None => 0,
+        }
+    }
+
+    /// 指标换算为 kB
+    pub fn kb(&self, name: &str) -> u64 {
+        match self.find(name) {
+            Some(&(_, Unit::Count, value)) => value,
+            Some(_) => self.bytes(name) / 1024,
+            None => 0,
+        }
+    }
+
+    /// 按名字顺序遍历 (名字, 单位, 值)
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, Unit, u64)> + '_ {
+        self.values.iter().copied()
+    }
+}
+
+/// 读取所有指标
+pub fn snapshot() -> Snapshot {
+    // 探针可能获取帧分配器、任务管理器等锁，先复制出来在注册表锁外求值
+    let metrics: Vec<(&'static str, Metric)> = REGISTRY
+        .lock()
+        .iter()
+        .map(|(name, metric)| (*name, *metric))
+        .collect();
+    Snapshot {
+        values: metrics
+            .into_iter()
+            .map(|(name, metric)| (name, metric.unit, metric.source.read()))
+            .collect(),
+    }
+}
+
+/// 登记各子系统的指标，在堆分配器可用后调用一次
+pub fn init() {
+    crate::mm::register_metrics();
+    crate::kernel::register_sched_metrics();
+    crate::kernel::time::register_metrics();
+    crate::kernel::register_timer_metrics();
+    crate::kernel::executor::register_executor_metrics();
+    crate::device::block::iosched::register_iosched_metrics();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    static TEST_COUNTER: Counter = Counter::new();
+    static TEST_GAUGE: Gauge = Gauge::new();
+
+    test_case!(
+        #[retains_state]
+        test_kstat_registry,
+        {
+            register_counter("test_kstat_events", Unit::Count, &TEST_COUNTER);
+            register_gauge("test_kstat_pages", Unit::Pages, &TEST_GAUGE);
+            register_probe("test_kstat_bytes", Unit::Bytes, || 3 * 1024);
+
+            TEST_COUNTER.add(2);
+            TEST_COUNTER.inc();
+            TEST_GAUGE.set(5);
+            TEST_GAUGE.set(2);
+
+            let snap = snapshot();
+            kassert!(snap.get("test_kstat_events") == 3);
+            kassert!(snap.kb("test_kstat_events") == 3);
+            kassert!(snap.bytes("test_kstat_pages") == 2 * PAGE_SIZE as u64);
+            kassert!(snap.kb("test_kstat_bytes") == 3);
+            kassert!(snap.get("test_kstat_missing") == 0);
+
+            // 快照按名字排序，与登记顺序无关
+            let names: Vec<&str> = snap.iter().map(|(name, _, _)| name).collect();
+            kassert!(names.windows(2).all(|w| w[0] < w[1]));
+
+            // 各子系统的指标在启动时登记
+            kassert!(snap.get("nr_total_pages") > 0);
+            kassert!(snap.get("nr_free_pages") <= snap.get("nr_total_pages"));
+        }
+    );
+}
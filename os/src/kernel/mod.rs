@@ -5,15 +5,30 @@
 //! 实现内核的核心功能
 
 pub mod boot;
+pub mod bootinfo;
+pub mod conformance;
 mod cpu;
+mod cpu_timer;
+mod embedded;
+pub mod fuzz;
+pub mod idle;
+pub mod kstat;
 mod scheduler;
+pub mod selftest;
 mod task;
 mod timer;
 
+pub mod stall;
 pub mod syscall;
+#[cfg(feature = "sysrq")]
+pub mod sysrq;
 pub mod time;
+#[cfg(feature = "virtual_time")]
+pub mod vclock;
+pub mod watchdog;
 
 pub use cpu::*;
+pub use cpu_timer::*;
 pub use scheduler::*;
 pub use task::*;
 pub use timer::*;
@@ -0,0 +1,216 @@
+//! fair 调度类
+//!
+//! 仿照 Linux CFS：任务按权重累计虚拟运行时间（vruntime，纳秒），权重越大增长越慢。
+//! 可运行任务以 (vruntime, 入队序号) 为键存放在 BTreeMap 中，总是选择 vruntime
+//! 最小的任务，同 vruntime 保持 FIFO。SCHED_NORMAL / SCHED_BATCH 使用 nice 0 的权重，
+//! SCHED_IDLE 使用极小的权重，几乎只在其他任务都不可运行时才会被选中。
+//!
+//! 队列维护单调不减的 `min_vruntime`。入队任务的 vruntime 至少为
+//! `min_vruntime - SLEEPER_CREDIT_NS`：睡眠较久的交互任务醒来后能很快得到 CPU，
+//! 但不会因为积攒了很小的 vruntime 而长时间独占 CPU。
+
+use alloc::{collections::BTreeMap, sync::Arc};
+
+use crate::{kernel::task::SharedTask, uapi::sched::SCHED_IDLE};
+
+/// nice 0 任务的权重
+pub const NICE_0_LOAD: u64 = 1024;
+
+/// SCHED_IDLE 任务的权重，与 Linux 的 WEIGHT_IDLEPRIO 相同
+pub const WEIGHT_IDLEPRIO: u64 = 3;
+
+/// 醒来的任务相对 `min_vruntime` 最多获得的补偿（纳秒）
+pub const SLEEPER_CREDIT_NS: u64 = 40_000_000;
+
+/// 调度策略对应的 fair 类权重
+pub fn task_weight(policy: i32) -> u64 {
+    match policy {
+        SCHED_IDLE => WEIGHT_IDLEPRIO,
+        _ => NICE_0_LOAD,
+    }
+}
+
+/// 把实际运行时间按权重换算为虚拟运行时间
+pub fn calc_delta_fair(delta_ns: u64, weight: u64) -> u64 {
+    if weight == NICE_0_LOAD {
+        return delta_ns;
+    }
+    let delta = delta_ns as u128 * NICE_0_LOAD as u128 / weight.max(1) as u128;
+    delta.min(u64::MAX as u128) as u64
+}
+
+/// 调度时钟：启动以来的纳秒数
+pub fn sched_clock() -> u64 {
+    let freq = crate::arch::clock_freq().max(1) as u128;
+    (crate::arch::get_time() as u128 * 1_000_000_000 / freq) as u64
+}
+
+/// 以任务对象地址作为队列内的身份
+fn task_id(task: &SharedTask) -> usize {
+    Arc::as_ptr(task) as *const () as usize
+}
+
+/// fair 类运行队列
+pub struct FairRunQueue {
+    /// (vruntime, 入队序号) -> 任务
+    tree: BTreeMap<(u64, u64), SharedTask>,
+    /// 任务身份 -> 在 `tree` 中的键
+    keys: BTreeMap<usize, (u64, u64)>,
+    /// 下一个入队序号
+    next_seq: u64,
+    /// 单调不减的最小 vruntime
+    min_vruntime: u64,
+}
+
+impl FairRunQueue {
+    /// 创建一个空队列（const 版本），用于静态数组初始化
+    pub const fn empty() -> Self {
+        Self {
+            tree: BTreeMap::new(),
+            keys: BTreeMap::new(),
+            next_seq: 0,
+            min_vruntime: 0,
+        }
+    }
+
+    /// 队列中的任务数量
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// 队列是否为空
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// 检查任务是否在队列中
+    pub fn contains(&self, task: &SharedTask) -> bool {
+        self.keys.contains_key(&task_id(task))
+    }
+
+    /// 当前的 `min_vruntime`
+    pub fn min_vruntime(&self) -> u64 {
+        self.min_vruntime
+    }
+
+    /// 队列中最小的 vruntime
+    pub fn leftmost_vruntime(&self) -> Option<u64> {
+        self.tree.keys().next().map(|&(vruntime, _)| vruntime)
+    }
+
+    /// 任务入队，vruntime 过小时先按 `min_vruntime` 放置；已在队列中则忽略
+    pub fn enqueue(&mut self, task: SharedTask) {
+        let id = task_id(&task);
+        if self.keys.contains_key(&id) {
+            return;
+        }
+        let vruntime = {
+            let mut t = task.lock();
+            let floor = self.min_vruntime.saturating_sub(SLEEPER_CREDIT_NS);
+            if t.vruntime < floor {
+                t.vruntime = floor;
+            }
+            t.vruntime
+        };
+        let key = (vruntime, self.next_seq);
+        self.next_seq = self.next_seq.wrapping_add(1);
+        self.keys.insert(id, key);
+        self.tree.insert(key, task);
+    }
+
+    /// 把任务移出队列，返回它是否在队列中
+    pub fn dequeue(&mut self, task: &SharedTask) -> bool {
+        match self.keys.remove(&task_id(task)) {
+            Some(key) => {
+                self.tree.remove(&key);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 弹出 vruntime 最小的任务
+    pub fn pop_first(&mut self) -> Option<SharedTask> {
+        let ((vruntime, _), task) = self.tree.pop_first()?;
+        self.keys.remove(&task_id(&task));
+        self.update_min_vruntime(Some(vruntime));
+        Some(task)
+    }
+
+    /// 推进 `min_vruntime`
+    ///
+    /// 取正在运行的 fair 任务（`curr_vruntime`）与队列最左任务中较小的 vruntime，
+    /// 并保持单调不减。
+    pub fn update_min_vruntime(&mut self, curr_vruntime: Option<u64>) {
+        let candidate = match (curr_vruntime, self.leftmost_vruntime()) {
+            (Some(curr), Some(leftmost)) => curr.min(leftmost),
+            (Some(curr), None) => curr,
+            (None, Some(leftmost)) => leftmost,
+            (None, None) => return,
+        };
+        self.min_vruntime = self.min_vruntime.max(candidate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, kernel::task::TaskStruct, test_case};
+
+    fn mk_task(tid: u32, vruntime: u64) -> SharedTask {
+        let mut task = TaskStruct::new_dummy_task(tid);
+        task.vruntime = vruntime;
+        task.into_shared()
+    }
+
+    // 按 vruntime 从小到大出队，同 vruntime 保持 FIFO
+    test_case!(test_fair_rq_pick_order, {
+        let mut rq = FairRunQueue::empty();
+        let t1 = mk_task(1, 300);
+        let t2 = mk_task(2, 100);
+        let t3 = mk_task(3, 100);
+        rq.enqueue(t1.clone());
+        rq.enqueue(t2.clone());
+        rq.enqueue(t3.clone());
+        rq.enqueue(t2.clone());
+        kassert!(rq.len() == 3);
+
+        kassert!(Arc::ptr_eq(&rq.pop_first().unwrap(), &t2));
+        kassert!(Arc::ptr_eq(&rq.pop_first().unwrap(), &t3));
+        kassert!(Arc::ptr_eq(&rq.pop_first().unwrap(), &t1));
+        kassert!(rq.is_empty());
+        kassert!(rq.min_vruntime() == 300);
+    });
+
+    // 出队与身份：不同实例即使 tid 相同也不相等
+    test_case!(test_fair_rq_dequeue, {
+        let mut rq = FairRunQueue::empty();
+        let t1 = mk_task(10, 0);
+        let t1_other = mk_task(10, 0);
+        rq.enqueue(t1.clone());
+        kassert!(rq.contains(&t1));
+        kassert!(!rq.contains(&t1_other));
+        kassert!(!rq.dequeue(&t1_other));
+        kassert!(rq.dequeue(&t1));
+        kassert!(rq.is_empty());
+    });
+
+    // 醒来的任务至多落后 min_vruntime 一个补偿量
+    test_case!(test_fair_rq_sleeper_placement, {
+        let mut rq = FairRunQueue::empty();
+        rq.update_min_vruntime(Some(SLEEPER_CREDIT_NS * 10));
+        let sleeper = mk_task(20, 0);
+        rq.enqueue(sleeper.clone());
+        kassert!(sleeper.lock().vruntime == SLEEPER_CREDIT_NS * 9);
+
+        // min_vruntime 不会倒退
+        rq.update_min_vruntime(Some(0));
+        kassert!(rq.min_vruntime() == SLEEPER_CREDIT_NS * 10);
+    });
+
+    test_case!(test_fair_weight, {
+        kassert!(calc_delta_fair(1000, NICE_0_LOAD) == 1000);
+        kassert!(calc_delta_fair(3, task_weight(SCHED_IDLE)) == 1024);
+        kassert!(task_weight(crate::uapi::sched::SCHED_BATCH) == NICE_0_LOAD);
+    });
+}
@@ -0,0 +1,123 @@
+//! 系统平均负载
+//!
+//! 与 Linux 相同，每 5 秒在时钟中断中采样一次可运行任务数，
+//! 按定点指数加权移动平均更新 1/5/15 分钟负载。
+//! 采样按硬件时间而不是节拍计数触发，idle 停掉节拍后也不会积累误差。
+//!
+//! 目前只统计可运行任务（运行队列中的任务和正在运行的任务），
+//! 不包含处于不可中断睡眠中的任务。
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// 定点数小数位数
+pub const FSHIFT: u32 = 11;
+/// 定点数 1.0
+pub const FIXED_1: usize = 1 << FSHIFT;
+/// 1/exp(5s/1min)
+const EXP_1: usize = 1884;
+/// 1/exp(5s/5min)
+const EXP_5: usize = 2014;
+/// 1/exp(5s/15min)
+const EXP_15: usize = 2037;
+
+/// 采样间隔（秒）
+const LOAD_FREQ_SEC: usize = 5;
+
+/// 1/5/15 分钟平均负载（定点数）
+static AVENRUN: [AtomicUsize; 3] = [const { AtomicUsize::new(0) }; 3];
+
+/// 下一次采样的硬件时间；0 表示尚未开始
+static NEXT_SAMPLE: AtomicUsize = AtomicUsize::new(0);
+
+/// 单次指数衰减：`load * exp + active * (1 - exp)`
+///
+/// 负载上升时向上取整，保证持续满载时能收敛到整数值。
+pub fn calc_load(load: usize, exp: usize, active: usize) -> usize {
+    let mut newload = load * exp + active * (FIXED_1 - exp);
+    if active >= load {
+        newload += FIXED_1 - 1;
+    }
+    newload / FIXED_1
+}
+
+/// 用一次采样更新三个平均值
+fn update_avenrun(active: usize) {
+    let active = active * FIXED_1;
+    for (avg, exp) in AVENRUN.iter().zip([EXP_1, EXP_5, EXP_15]) {
+        let load = avg.load(Ordering::Relaxed);
+        avg.store(calc_load(load, exp, active), Ordering::Relaxed);
+    }
+}
+
+/// 时钟中断钩子：到达采样时间时更新平均负载
+///
+/// 只在 CPU 0 上采样，避免多核重复计算。
+pub fn loadavg_tick() {
+    if crate::arch::cpu_id() != 0 {
+        return;
+    }
+
+    let now = crate::arch::get_time();
+    let period = crate::arch::clock_freq() * LOAD_FREQ_SEC;
+    let next = NEXT_SAMPLE.load(Ordering::Relaxed);
+    if next == 0 {
+        NEXT_SAMPLE.store(now + period, Ordering::Relaxed);
+        return;
+    }
+    if now < next {
+        return;
+    }
+
+    update_avenrun(super::nr_running());
+
+    // 错过多个周期（如长时间 suspend）时从当前时刻重新计时
+    let next = if now >= next + period {
+        now + period
+    } else {
+        next + period
+    };
+    NEXT_SAMPLE.store(next, Ordering::Relaxed);
+}
+
+/// 读取 1/5/15 分钟平均负载（定点数，小数位数为 [`FSHIFT`]）
+pub fn loadavg() -> [usize; 3] {
+    [
+        AVENRUN[0].load(Ordering::Relaxed),
+        AVENRUN[1].load(Ordering::Relaxed),
+        AVENRUN[2].load(Ordering::Relaxed),
+    ]
+}
+
+/// 定点负载拆成整数部分和两位小数
+pub fn load_int_frac(load: usize) -> (usize, usize) {
+    let int = load >> FSHIFT;
+    let frac = ((load & (FIXED_1 - 1)) * 100) >> FSHIFT;
+    (int, frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_calc_load_converges, {
+        // 持续 1 个可运行任务，1 分钟负载逐渐逼近 1.00
+        let mut load = 0;
+        for _ in 0..120 {
+            load = calc_load(load, EXP_1, FIXED_1);
+        }
+        kassert!(load_int_frac(load) == (1, 0));
+
+        // 负载消失后逐渐衰减
+        for _ in 0..12 {
+            load = calc_load(load, EXP_1, 0);
+        }
+        let (int, frac) = load_int_frac(load);
+        kassert!(int == 0 && frac > 30 && frac < 40);
+    });
+
+    test_case!(test_load_int_frac, {
+        kassert!(load_int_frac(0) == (0, 0));
+        kassert!(load_int_frac(FIXED_1 * 3 + FIXED_1 / 2) == (3, 50));
+    });
+}
@@ -1,11 +1,14 @@
 //！ 调度器模块
 //!
 //！ 定义了调度器接口和相关功能
+pub mod fair;
+pub mod loadavg;
 mod rr_scheduler;
+pub mod schedstat;
 mod task_queue;
 mod wait_queue;
 
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 
 use crate::{
     arch::Arch,
@@ -27,6 +30,61 @@ static SCHEDULERS: [SpinLock<RRScheduler>; MAX_CPU_COUNT] =
 /// 用于简单轮转选择目标 CPU
 static NEXT_CPU: AtomicUsize = AtomicUsize::new(0);
 
+/// 每 CPU 的上下文切换次数
+static NR_SWITCHES: [AtomicU64; MAX_CPU_COUNT] = [const { AtomicU64::new(0) }; MAX_CPU_COUNT];
+
+/// 每 CPU 当前是否在运行非 idle 任务
+static CPU_BUSY: [AtomicBool; MAX_CPU_COUNT] = [const { AtomicBool::new(false) }; MAX_CPU_COUNT];
+
+/// 记录一次任务切换（由 `Cpu::switch_task` 调用）
+pub(crate) fn account_switch(cpu_id: usize, to_idle: bool) {
+    NR_SWITCHES[cpu_id].fetch_add(1, Ordering::Relaxed);
+    CPU_BUSY[cpu_id].store(!to_idle, Ordering::Relaxed);
+}
+
+/// 所有 CPU 的上下文切换总次数
+pub fn nr_context_switches() -> u64 {
+    (0..crate::kernel::num_cpu())
+        .map(|cpu| NR_SWITCHES[cpu].load(Ordering::Relaxed))
+        .sum()
+}
+
+/// 可运行任务数：各 CPU 运行队列中的任务加上正在运行的非 idle 任务
+pub fn nr_running() -> usize {
+    (0..crate::kernel::num_cpu())
+        .map(|cpu| {
+            let queued = scheduler_of(cpu).lock().task_count();
+            queued + CPU_BUSY[cpu].load(Ordering::Relaxed) as usize
+        })
+        .sum()
+}
+
+/// 登记调度相关的统计指标
+///
+/// 平均负载以定点数登记（小数位数为 [`loadavg::FSHIFT`]）。
+pub fn register_sched_metrics() {
+    use crate::kernel::kstat::{Unit, register_probe};
+    use crate::kernel::{TASK_MANAGER, TaskManagerTrait};
+
+    register_probe("ctxt", Unit::Count, nr_context_switches);
+    register_probe("procs_running", Unit::Count, || nr_running() as u64);
+    register_probe("procs_blocked", Unit::Count, || {
+        TASK_MANAGER
+            .lock()
+            .get_task_cond(|t| t.lock().state == TaskState::Uninterruptible)
+            .len() as u64
+    });
+    register_probe("processes", Unit::Count, || {
+        TASK_MANAGER.lock().last_tid() as u64
+    });
+    register_probe("nr_threads", Unit::Count, || {
+        TASK_MANAGER.lock().task_count() as u64
+    });
+    register_probe("loadavg_1", Unit::Count, || loadavg::loadavg()[0] as u64);
+    register_probe("loadavg_5", Unit::Count, || loadavg::loadavg()[1] as u64);
+    register_probe("loadavg_15", Unit::Count, || loadavg::loadavg()[2] as u64);
+}
+
 /// 上下文切换计划结构体
 pub(crate) struct SwitchPlan {
     pub old: *mut Context,
@@ -79,6 +137,16 @@ pub trait Scheduler {
         receive_signal: bool,
         prepare: impl FnOnce(&mut crate::kernel::TaskStruct) -> bool,
     ) -> bool;
+    /// 原子地检查条件并停止任务（作业控制）
+    ///
+    /// 在持有调度器锁和 task 锁的情况下执行 `prepare` 闭包。
+    /// 如果 `prepare` 返回 `true`，则将任务设为 Stopped 并从运行队列中移除，
+    /// 返回 `true`；否则任务状态不变，返回 `false`。
+    fn stop_task_prepare(
+        &mut self,
+        task: SharedTask,
+        prepare: impl FnOnce(&mut crate::kernel::TaskStruct) -> bool,
+    ) -> bool;
 }
 
 /// 获取当前 CPU 的调度器
@@ -159,6 +227,7 @@ pub fn schedule() {
         }; // 调度器锁在这里释放
 
         if let Some(plan) = plan {
+            crate::kernel::stall::touch_progress();
             // SAFETY: next_task 生成的上下文指针有效
             unsafe { crate::arch::ArchImpl::context_switch(plan.old, plan.new) };
             // 通常不会立即返回；返回时再继续当前上下文后续逻辑
@@ -277,3 +346,30 @@ pub fn sleep_task_prepare(
         .lock()
         .sleep_task_prepare(task, receive_signal, prepare)
 }
+
+/// 原子地检查条件并停止任务（SIGSTOP 等作业控制信号）
+///
+/// `prepare` 返回 `true` 时任务被设为 Stopped 并从运行队列移除，返回 `true`，
+/// 调用者随后须调用 `schedule()` 让出 CPU。Stopped 的任务不会被 [`wake_up_task`]
+/// 唤醒，只能由 [`continue_task`] 恢复。
+pub fn stop_task_prepare(task: SharedTask, prepare: impl FnOnce(&mut TaskStruct) -> bool) -> bool {
+    let cpu_id = {
+        let t = task.lock();
+        t.on_cpu.unwrap_or_else(crate::arch::cpu_id)
+    };
+    scheduler_of(cpu_id).lock().stop_task_prepare(task, prepare)
+}
+
+/// 恢复被停止的任务（SIGCONT / SIGKILL）
+///
+/// 任务不处于 Stopped 时不做任何事。
+pub fn continue_task(task: SharedTask) {
+    {
+        let mut t = task.lock();
+        if t.state != TaskState::Stopped {
+            return;
+        }
+        t.state = TaskState::Uninterruptible;
+    }
+    wake_up_task(task);
+}
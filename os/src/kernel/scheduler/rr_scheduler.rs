@@ -1,23 +1,34 @@
 //! 轮转调度器模块
 //!
 //! 实现了一个简单的轮转调度器（Round-Robin Scheduler）
+//!
+//! 调度类按优先级从高到低依次为 realtime（SCHED_FIFO / SCHED_RR，按优先级轮转）
+//! 和 fair（SCHED_NORMAL / SCHED_BATCH / SCHED_IDLE，按加权 vruntime 选择，见
+//! [`super::fair`]）。只有 realtime 队列为空时才会选择 fair 任务。
+use alloc::sync::Arc;
+
 use crate::{
     arch::kernel::context::Context,
     kernel::{
         TaskState,
         cpu::current_cpu,
-        scheduler::{Scheduler, SwitchPlan, TaskQueue},
+        scheduler::{
+            Scheduler, SwitchPlan, TaskQueue,
+            fair::{FairRunQueue, calc_delta_fair, sched_clock, task_weight},
+            schedstat::report_exit,
+        },
         task::SharedTask,
+        time::TICK_CONFIG,
     },
     uapi::sched::{SCHED_BATCH, SCHED_FIFO, SCHED_IDLE, SCHED_NORMAL, SCHED_RR},
 };
 
-const DEFAULT_TIME_SLICE: usize = 1; // fair 类默认时间片长度
-const RT_RR_TIME_SLICE: usize = 4;
-const FAIR_TARGET_LATENCY: usize = 8;
-const FAIR_MIN_GRANULARITY: usize = 2;
-const FAIR_BATCH_GRANULARITY: usize = 4;
-const FAIR_IDLE_GRANULARITY: usize = 8;
+const DEFAULT_TIME_SLICE: usize = 1; // fair 类默认时间片长度（节拍）
+// fair 类的调度周期与最小时间片（毫秒），按当前节拍频率换算为节拍数
+const FAIR_TARGET_LATENCY_MS: usize = 80;
+const FAIR_MIN_GRANULARITY_MS: usize = 20;
+const FAIR_BATCH_GRANULARITY_MS: usize = 40;
+const FAIR_IDLE_GRANULARITY_MS: usize = 80;
 
 /// 简单的轮转调度器实现
 /// 每个任务按顺序轮流获得 CPU 时间片
@@ -28,7 +39,7 @@ pub struct RRScheduler {
     // realtime 运行队列：SCHED_FIFO / SCHED_RR
     rt_queue: TaskQueue,
     // fair 运行队列：SCHED_NORMAL / SCHED_BATCH / SCHED_IDLE
-    fair_queue: TaskQueue,
+    fair_queue: FairRunQueue,
     // 时间片长度（以时钟中断滴答数为单位）
     time_slice: usize,
     // 当前时间片剩余时间
@@ -41,7 +52,7 @@ impl RRScheduler {
     pub const fn empty() -> Self {
         RRScheduler {
             rt_queue: TaskQueue::empty(),
-            fair_queue: TaskQueue::empty(),
+            fair_queue: FairRunQueue::empty(),
             time_slice: DEFAULT_TIME_SLICE,
             current_slice: DEFAULT_TIME_SLICE,
         }
@@ -62,9 +73,13 @@ impl RRScheduler {
     /// 返回 true 表示当前任务应该让调度器重新选择。SCHED_FIFO 不因
     /// 普通时钟 tick 被轮转；SCHED_RR 和 fair 类任务仍使用时间片。
     pub fn update_time_slice(&mut self) -> bool {
-        Self::charge_current_fair_task();
+        if let Some(current_task) = current_cpu().current_task.as_ref() {
+            let mut task = current_task.lock();
+            task.exec_ticks = task.exec_ticks.saturating_add(1);
+        }
+        self.update_curr();
 
-        if self.should_preempt_for_rt() {
+        if self.should_preempt_for_rt() || self.should_preempt_for_fair() {
             return true;
         }
 
@@ -89,23 +104,20 @@ impl RRScheduler {
     }
 
     fn enqueue_task(&mut self, task: SharedTask) {
-        let policy = { task.lock().sched_policy };
+        let policy = {
+            let mut t = task.lock();
+            t.sched_stat.on_enqueue(sched_clock());
+            t.sched_policy
+        };
         match policy {
             SCHED_FIFO | SCHED_RR if Self::is_realtime_task(&task) => self.rt_queue.add_task(task),
-            SCHED_NORMAL | SCHED_BATCH | SCHED_IDLE | SCHED_FIFO | SCHED_RR => {
-                self.clamp_fair_vruntime(&task);
-                self.fair_queue.add_task(task)
-            }
-            _ => {
-                self.clamp_fair_vruntime(&task);
-                self.fair_queue.add_task(task)
-            }
+            _ => self.fair_queue.enqueue(task),
         }
     }
 
     fn remove_queued_task(&mut self, task: &SharedTask) {
         self.rt_queue.remove_task(task);
-        self.fair_queue.remove_task(task);
+        self.fair_queue.dequeue(task);
     }
 
     fn contains_queued_task(&self, task: &SharedTask) -> bool {
@@ -115,7 +127,7 @@ impl RRScheduler {
     fn pop_next_queued_task(&mut self) -> Option<SharedTask> {
         self.rt_queue
             .pop_highest_priority_task()
-            .or_else(|| self.fair_queue.pop_min_vruntime_task())
+            .or_else(|| self.fair_queue.pop_first())
     }
 
     fn should_preempt_for_rt(&self) -> bool {
@@ -144,42 +156,74 @@ impl RRScheduler {
 
     fn task_time_slice(&self, task: &SharedTask) -> usize {
         match task.lock().sched_policy {
-            SCHED_RR => RT_RR_TIME_SLICE,
-            SCHED_NORMAL => self.fair_time_slice(FAIR_MIN_GRANULARITY),
-            SCHED_BATCH => self.fair_time_slice(FAIR_BATCH_GRANULARITY),
-            SCHED_IDLE => self.fair_time_slice(FAIR_IDLE_GRANULARITY),
+            SCHED_RR => TICK_CONFIG.ms_to_ticks(TICK_CONFIG.rr_timeslice_ms()),
+            SCHED_NORMAL => self.fair_time_slice(FAIR_MIN_GRANULARITY_MS),
+            SCHED_BATCH => self.fair_time_slice(FAIR_BATCH_GRANULARITY_MS),
+            SCHED_IDLE => self.fair_time_slice(FAIR_IDLE_GRANULARITY_MS),
             _ => DEFAULT_TIME_SLICE,
         }
     }
 
-    fn fair_time_slice(&self, min_granularity: usize) -> usize {
+    fn fair_time_slice(&self, min_granularity_ms: usize) -> usize {
         let runnable = (self.fair_queue.len() + 1).max(1);
-        let slice = FAIR_TARGET_LATENCY / runnable;
-        slice.max(min_granularity)
+        let slice = TICK_CONFIG.ms_to_ticks(FAIR_TARGET_LATENCY_MS) / runnable;
+        slice.max(TICK_CONFIG.ms_to_ticks(min_granularity_ms))
     }
 
-    fn clamp_fair_vruntime(&self, task: &SharedTask) {
-        let Some(min_vruntime) = self.fair_queue.min_vruntime() else {
-            return;
-        };
-        let mut task = task.lock();
-        if task.vruntime < min_vruntime {
-            task.vruntime = min_vruntime;
-        }
+    fn is_fair_policy(policy: i32) -> bool {
+        matches!(policy, SCHED_NORMAL | SCHED_BATCH | SCHED_IDLE)
     }
 
-    fn charge_current_fair_task() {
+    /// fair 类的当前任务领先队列最左任务超过一个最小时间片时让出 CPU
+    fn should_preempt_for_fair(&self) -> bool {
+        let Some(leftmost) = self.fair_queue.leftmost_vruntime() else {
+            return false;
+        };
         let Some(current_task) = current_cpu().current_task.as_ref().cloned() else {
+            return false;
+        };
+        let task = current_task.lock();
+        Self::is_fair_policy(task.sched_policy)
+            && task.vruntime > leftmost.saturating_add(FAIR_MIN_GRANULARITY_MS as u64 * 1_000_000)
+    }
+
+    /// 记账当前任务自上次记账以来的运行时间
+    ///
+    /// 累加到 `sum_exec_runtime`；fair 类任务同时按权重推进 vruntime，
+    /// 并据此推进运行队列的 `min_vruntime`。idle 任务不记账。
+    fn update_curr(&mut self) {
+        let cpu = current_cpu();
+        let Some(current_task) = cpu.current_task.as_ref().cloned() else {
             return;
         };
-        let mut task = current_task.lock();
-        let vruntime_delta = match task.sched_policy {
-            SCHED_NORMAL | SCHED_BATCH => 1,
-            SCHED_IDLE => 8,
-            _ => 0,
+        if cpu
+            .idle_task
+            .as_ref()
+            .is_some_and(|idle| Arc::ptr_eq(idle, &current_task))
+        {
+            return;
+        }
+
+        let now = sched_clock();
+        let curr_vruntime = {
+            let mut task = current_task.lock();
+            // exec_start 为 0 表示尚未经调度器切入，没有可记账的时间
+            let delta = if task.exec_start == 0 {
+                0
+            } else {
+                now.saturating_sub(task.exec_start)
+            };
+            task.exec_start = now;
+            task.sum_exec_runtime = task.sum_exec_runtime.saturating_add(delta);
+            if Self::is_fair_policy(task.sched_policy) {
+                let delta_fair = calc_delta_fair(delta, task_weight(task.sched_policy));
+                task.vruntime = task.vruntime.saturating_add(delta_fair);
+                Some(task.vruntime)
+            } else {
+                None
+            }
         };
-        task.exec_ticks = task.exec_ticks.saturating_add(1);
-        task.vruntime = task.vruntime.saturating_add(vruntime_delta);
+        self.fair_queue.update_min_vruntime(curr_vruntime);
     }
 }
 
@@ -187,7 +231,7 @@ impl Scheduler for RRScheduler {
     fn new() -> Self {
         RRScheduler {
             rt_queue: TaskQueue::new(),
-            fair_queue: TaskQueue::new(),
+            fair_queue: FairRunQueue::empty(),
             time_slice: DEFAULT_TIME_SLICE,
             current_slice: DEFAULT_TIME_SLICE,
         }
@@ -203,6 +247,9 @@ impl Scheduler for RRScheduler {
             self.task_count()
         );
 
+        // 先给当前任务记账，使它放回队列时的 vruntime 是最新的
+        self.update_curr();
+
         // 选择下一个可运行任务
         let next_task = match self.pop_next_queued_task() {
             Some(t) => t,
@@ -268,18 +315,26 @@ impl Scheduler for RRScheduler {
             &mut g.context as *mut _
         };
 
-        // 轮转：旧任务若仍可运行，放回运行队列尾
+        // 轮转：旧任务若仍可运行，放回运行队列尾；idle 任务永远不进入运行队列
         {
+            let is_idle = current_cpu()
+                .idle_task
+                .as_ref()
+                .is_some_and(|idle| Arc::ptr_eq(idle, &prev_task));
             let still_running = { prev_task.lock().state == TaskState::Running };
-            if still_running {
+            if still_running && !is_idle {
                 self.enqueue_task(prev_task.clone());
             }
         }
 
-        // 更新 on_cpu 字段和时间片
+        // 更新 on_cpu 字段、记账起点和时间片
         {
             let cpu_id = crate::arch::cpu_id();
-            next_task.lock().on_cpu = Some(cpu_id);
+            let mut t = next_task.lock();
+            let now = sched_clock();
+            t.on_cpu = Some(cpu_id);
+            t.exec_start = now;
+            t.sched_stat.on_arrive(now);
         }
         self.reset_time_slice(&next_task);
 
@@ -311,11 +366,13 @@ impl Scheduler for RRScheduler {
 
     fn sleep_task(&mut self, task: SharedTask, receive_signal: bool) {
         {
-            task.lock().state = if receive_signal {
+            let mut t = task.lock();
+            t.state = if receive_signal {
                 TaskState::Interruptible
             } else {
                 TaskState::Uninterruptible
             };
+            t.sched_stat.on_block(sched_clock());
         }
 
         self.remove_queued_task(&task);
@@ -333,7 +390,9 @@ impl Scheduler for RRScheduler {
 
     fn exit_task(&mut self, task: SharedTask) {
         {
-            task.lock().state = TaskState::Zombie;
+            let mut t = task.lock();
+            t.state = TaskState::Zombie;
+            report_exit(&t);
         }
 
         self.remove_queued_task(&task);
@@ -354,9 +413,25 @@ impl Scheduler for RRScheduler {
         } else {
             TaskState::Uninterruptible
         };
+        t.sched_stat.on_block(sched_clock());
         self.remove_queued_task(&task);
         true // 已进入睡眠
     }
+
+    fn stop_task_prepare(
+        &mut self,
+        task: SharedTask,
+        prepare: impl FnOnce(&mut crate::kernel::TaskStruct) -> bool,
+    ) -> bool {
+        let mut t = task.lock();
+        if !prepare(&mut t) {
+            return false;
+        }
+        t.state = TaskState::Stopped;
+        t.sched_stat.on_block(sched_clock());
+        self.remove_queued_task(&task);
+        true
+    }
 }
 
 #[cfg(test)]
@@ -484,4 +559,34 @@ mod tests {
         kassert!(expired);
         kassert!(rr.current_slice == 0);
     });
+
+    // fair 类当前任务领先队列最左任务超过最小粒度（20ms）时被抢占，运行时间由虚拟时钟给出
+    #[cfg(feature = "virtual_time")]
+    test_case!(test_rr_fair_preempt_on_virtual_time, {
+        use crate::kernel::vclock;
+        use core::time::Duration;
+
+        let _clock = vclock::freeze();
+        let curr = mk_task(50);
+        curr.lock().exec_start = sched_clock();
+        let prev = {
+            let _guard = crate::sync::PreemptGuard::new();
+            current_cpu().current_task.replace(curr.clone())
+        };
+
+        let mut rr = RRScheduler::new();
+        rr.add_task(mk_task(51));
+        rr.current_slice = 100;
+
+        vclock::advance_time(Duration::from_millis(15));
+        kassert!(!rr.update_time_slice());
+        vclock::advance_time(Duration::from_millis(10));
+        kassert!(rr.update_time_slice());
+        kassert!(curr.lock().vruntime > 20_000_000);
+
+        {
+            let _guard = crate::sync::PreemptGuard::new();
+            current_cpu().current_task = prev;
+        }
+    });
 }
@@ -0,0 +1,139 @@
+//! 任务调度统计
+//!
+//! 仿照 Linux schedstats，在任务状态转换时用调度时钟（由周期计数器换算，见
+//! [`sched_clock`](crate::kernel::fair::sched_clock)）记录三类时间：运行（即
+//! `sum_exec_runtime`）、可运行但在运行队列中等待（`run_delay`）和阻塞（睡眠或停止，
+//! `blocked_time`）。
+//!
+//! - 入队（唤醒或被抢占放回队列）：结束阻塞区间，开始等待区间
+//! - 被选中切入 CPU：结束等待区间，`pcount` 加一
+//! - 睡眠或停止：结束可能未结束的等待区间，开始阻塞区间
+//!
+//! 统计通过 /proc/\[pid\]/schedstat 导出；写 `/proc/sys/kernel/sched_schedstats_verbose`
+//! 为 1 后，任务退出时打印其统计。
+
+use alloc::{format, string::String};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::kernel::TaskStruct;
+
+/// 任务退出时是否打印调度统计
+static SCHEDSTATS_VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// 开启或关闭退出时的调度统计打印
+pub fn set_schedstats_verbose(enabled: bool) {
+    SCHEDSTATS_VERBOSE.store(enabled, Ordering::Relaxed);
+}
+
+/// 退出时的调度统计打印是否开启
+pub fn schedstats_verbose() -> bool {
+    SCHEDSTATS_VERBOSE.load(Ordering::Relaxed)
+}
+
+/// 单个任务的调度统计，时间单位均为纳秒
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SchedStat {
+    /// 在运行队列中等待 CPU 的累计时间
+    pub run_delay: u64,
+    /// 睡眠或停止的累计时间
+    pub blocked_time: u64,
+    /// 被切入 CPU 的次数
+    pub pcount: u64,
+    /// 当前等待区间的起点，0 表示不在运行队列中
+    wait_start: u64,
+    /// 当前阻塞区间的起点，0 表示未阻塞
+    block_start: u64,
+}
+
+impl SchedStat {
+    /// 任务进入运行队列
+    pub fn on_enqueue(&mut self, now: u64) {
+        if self.block_start != 0 {
+            self.blocked_time = self
+                .blocked_time
+                .saturating_add(now.saturating_sub(self.block_start));
+            self.block_start = 0;
+        }
+        if self.wait_start == 0 {
+            self.wait_start = now;
+        }
+    }
+
+    /// 任务被切入 CPU
+    pub fn on_arrive(&mut self, now: u64) {
+        self.end_wait(now);
+        self.pcount = self.pcount.saturating_add(1);
+    }
+
+    /// 任务睡眠或停止
+    pub fn on_block(&mut self, now: u64) {
+        self.end_wait(now);
+        if self.block_start == 0 {
+            self.block_start = now;
+        }
+    }
+
+    fn end_wait(&mut self, now: u64) {
+        if self.wait_start != 0 {
+            self.run_delay = self
+                .run_delay
+                .saturating_add(now.saturating_sub(self.wait_start));
+            self.wait_start = 0;
+        }
+    }
+}
+
+/// /proc/\[pid\]/schedstat 的内容
+///
+/// 前三列与 Linux 相同：运行时间、运行队列等待时间、切入次数；第四列为阻塞时间。
+pub fn schedstat_line(sum_exec_runtime: u64, stat: &SchedStat) -> String {
+    format!(
+        "{} {} {} {}\n",
+        sum_exec_runtime, stat.run_delay, stat.pcount, stat.blocked_time
+    )
+}
+
+/// 任务退出时按 sysctl 打印调度统计
+pub(super) fn report_exit(task: &TaskStruct) {
+    if !schedstats_verbose() {
+        return;
+    }
+    crate::pr_info!(
+        "[schedstat] {} (tid {}) exited: run {} ns, wait {} ns, blocked {} ns, {} switches",
+        task.comm(),
+        task.tid,
+        task.sum_exec_runtime,
+        task.sched_stat.run_delay,
+        task.sched_stat.blocked_time,
+        task.sched_stat.pcount
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_schedstat_transitions, {
+        let mut s = SchedStat::default();
+        // 创建后入队，等待 100 后运行
+        s.on_enqueue(1_000);
+        s.on_arrive(1_100);
+        // 运行后睡眠 500，唤醒后再等待 30
+        s.on_block(1_400);
+        s.on_enqueue(1_900);
+        s.on_arrive(1_930);
+        kassert!(s.run_delay == 130);
+        kassert!(s.blocked_time == 500);
+        kassert!(s.pcount == 2);
+
+        // 被抢占放回队列后在运行前被阻塞，等待时间仍计入 run_delay
+        s.on_enqueue(2_000);
+        s.on_block(2_050);
+        s.on_enqueue(2_250);
+        kassert!(s.run_delay == 180);
+        kassert!(s.blocked_time == 700);
+
+        kassert!(schedstat_line(42, &s) == "42 180 2 700\n");
+    });
+}
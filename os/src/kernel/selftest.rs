@@ -0,0 +1,104 @@
+//! 启动自检
+//!
+//! 命令行带 `selftest` 时，init 在 exec /sbin/init 之前运行嵌入内核的用户态程序
+//! （`selftest/stub.rs`，由 build.rs 编译），检查初始栈与 auxv 布局、信号投递
+//! 以及基本系统调用。程序以第一个失败检查项的编号退出，结果写入内核日志。
+//! 自检失败不会阻止启动。
+
+use alloc::string::String;
+
+use crate::{
+    kernel::{
+        Credential, TaskExitStatus, current_task,
+        embedded::{self, UserProgram},
+    },
+    pr_err, pr_info,
+};
+
+/// 由 build.rs 编译的用户态程序，非目标架构上为空
+static STUB_ELF: &[u8] = include_bytes!(env!("SELFTEST_STUB_ELF"));
+
+/// 传给用户态程序的参数，须与 `selftest/stub.rs` 中的期望值一致
+const STUB_ARGV: [&str; 2] = ["selftest", "arg1"];
+const STUB_ENVP: [&str; 1] = ["SELFTEST=1"];
+
+/// 超过该时间仍未退出则杀死用户态程序
+const TIMEOUT_MS: usize = 10_000;
+
+/// 命令行是否开启了自检
+pub fn enabled() -> bool {
+    cmdline_has_selftest(&crate::device::CMDLINE.read())
+}
+
+fn cmdline_has_selftest(cmdline: &str) -> bool {
+    cmdline
+        .split_ascii_whitespace()
+        .any(|arg| arg == "selftest")
+}
+
+/// 运行启动自检并等待其结束，返回是否全部通过
+///
+/// 必须由 init 任务调用，用户态程序作为其子进程运行并由本函数回收。
+pub fn run() -> bool {
+    if STUB_ELF.is_empty() {
+        pr_err!("[Selftest] No user stub embedded in this build");
+        return false;
+    }
+    let image = match embedded::load(STUB_ARGV[0], STUB_ELF) {
+        Ok(image) => image,
+        Err(e) => {
+            pr_err!("[Selftest] Failed to load user stub: {:?}", e);
+            return false;
+        }
+    };
+    let program = UserProgram {
+        image,
+        argv: STUB_ARGV.map(String::from).to_vec(),
+        envp: STUB_ENVP.map(String::from).to_vec(),
+        credential: Credential::root(),
+    };
+    // 自检程序继承 init 的标准输入输出和根目录
+    let parent = current_task();
+    let (fd_table, fs) = {
+        let t = parent.lock();
+        (t.fd_table.clone_table(), t.fs.lock().clone())
+    };
+
+    let child = embedded::spawn(program, fd_table, fs);
+    let pid = child.lock().pid;
+    pr_info!("[Selftest] Running user stub as pid {}", pid);
+
+    let status = embedded::wait_and_reap(child, TIMEOUT_MS);
+
+    match status {
+        Some(TaskExitStatus::Exited(0)) => {
+            pr_info!("[Selftest] PASSED");
+            true
+        }
+        Some(TaskExitStatus::Exited(code)) => {
+            pr_err!("[Selftest] FAILED: check {} failed", code);
+            false
+        }
+        Some(TaskExitStatus::Signaled { signal, .. }) => {
+            pr_err!("[Selftest] FAILED: killed by signal {}", signal);
+            false
+        }
+        None => {
+            pr_err!("[Selftest] FAILED: no exit status");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_selftest_cmdline, {
+        kassert!(cmdline_has_selftest("console=ttyS0 selftest"));
+        kassert!(cmdline_has_selftest("selftest"));
+        kassert!(!cmdline_has_selftest("console=ttyS0 root=/dev/vda1"));
+        kassert!(!cmdline_has_selftest("selftest=1 noselftest"));
+    });
+}
@@ -0,0 +1,335 @@
+//! 软死锁检测 (soft lockup detector)
+//!
+//! 与 Linux 的 softlockup watchdog 类似，在 [`watchdog`](super::watchdog) 复位之前
+//! 把卡住的现场打印到控制台：
+//! - 每个 CPU 在发生上下文切换、进入 idle 或从用户态陷入时记录一次“进展”；
+//!   时钟中断打断内核态代码时，若本 CPU 超过 [`STALL_THRESH_SECS`] 秒没有进展，
+//!   打印被打断处的寄存器和沿帧指针链的栈回溯；
+//! - 自旋锁等待超过同样的时间时，打印锁地址、持有者 CPU 和等待者自身的回溯。
+//!   持有者通常关着中断，没有 NMI 时无法打断它取现场，只能报告是哪个 CPU；
+//!   该 CPU 的节拍停止后由看门狗的硬死锁检测处理。
+//!
+//! 自旋等待超过一个检查间隔的锁记录在本 CPU 的状态中，可由 [`spin_wait`] 查询，
+//! sysrq 的锁转储据此列出各 CPU 正在等待的锁和持有者。
+//!
+//! 每次卡住只报告一次，重新取得进展后复位。报告只经由 `emergency_print` 输出，
+//! 不获取控制台锁和任务锁，在持有任意自旋锁时调用也不会死锁。
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::arch::{ArchImpl, CpuOps, HwTrapFrame, TrapFrame};
+use crate::kernel::clock_freq;
+
+/// CPU 或自旋锁等待者超过这么多秒没有进展即报告
+///
+/// 小于看门狗的软死锁复位阈值，保证复位前能看到现场。
+pub const STALL_THRESH_SECS: usize = 10;
+
+/// 回溯的最大深度
+const MAX_BACKTRACE_DEPTH: usize = 32;
+
+/// 回溯时帧指针允许高出栈指针的范围
+///
+/// 内核栈位于线性映射区，范围内的地址总是可读的。
+const MAX_STACK_SPAN: usize = 64 * 1024;
+
+/// 单个 CPU 的卡住检测状态
+struct StallState {
+    /// 最近一次取得进展的时间，0 表示还没有开始调度
+    progress: AtomicUsize,
+    /// 本次卡住是否已经报告过
+    reported: AtomicBool,
+    /// 正在自旋等待的锁地址，0 表示没有；等待超过一个检查间隔后才记录
+    spin_lock: AtomicUsize,
+    /// 该锁持有者的 CPU 号加 1，0 表示未知
+    spin_owner: AtomicUsize,
+    /// 开始等待该锁的时间
+    spin_start: AtomicUsize,
+}
+
+impl StallState {
+    const fn new() -> Self {
+        Self {
+            progress: AtomicUsize::new(0),
+            reported: AtomicBool::new(false),
+            spin_lock: AtomicUsize::new(0),
+            spin_owner: AtomicUsize::new(0),
+            spin_start: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// 某个 CPU 正在自旋等待的锁，见 [`spin_wait`]
+#[derive(Debug, Clone, Copy)]
+pub struct SpinWait {
+    /// 锁地址
+    pub lock: usize,
+    /// 持有者的 CPU 号
+    pub owner: Option<usize>,
+    /// 开始等待的时间（时钟周期）
+    pub since: usize,
+}
+
+/// `cpu` 正在自旋等待的锁；没有等待或等待尚未超过一个检查间隔时为 `None`
+///
+/// 只读原子变量，可以在任意上下文中调用（如 sysrq 转储）。
+pub fn spin_wait(cpu: usize) -> Option<SpinWait> {
+    let state = STALL.get_of(cpu);
+    let lock = state.spin_lock.load(Ordering::Relaxed);
+    if lock == 0 {
+        return None;
+    }
+    Some(SpinWait {
+        lock,
+        owner: state.spin_owner.load(Ordering::Relaxed).checked_sub(1),
+        since: state.spin_start.load(Ordering::Relaxed),
+    })
+}
+
+crate::percpu! {
+    static STALL: StallState = StallState::new();
+}
+
+macro_rules! stall_print {
+    ($($arg: tt)*) => {
+        crate::console::emergency_print(format_args!($($arg)*))
+    };
+}
+
+/// 记录当前 CPU 取得了进展
+#[inline]
+pub fn touch_progress() {
+    STALL.with(|state| {
+        state
+            .progress
+            .store(crate::arch::get_time(), Ordering::Relaxed);
+        state.reported.store(false, Ordering::Relaxed);
+    });
+}
+
+/// 内核态时钟中断调用：本 CPU 卡住时打印被打断处的现场
+pub fn check_stall(frame: &TrapFrame) {
+    let cpu = crate::arch::cpu_id();
+    let now = crate::arch::get_time();
+    let state = STALL.get_of(cpu);
+    let stamp = state.progress.load(Ordering::Relaxed);
+    if !is_stalled(stamp, now, clock_freq()) || state.reported.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    let tid = crate::kernel::current_cpu()
+        .current_task
+        .as_ref()
+        .and_then(|task| task.try_lock().map(|t| t.tid));
+    stall_print!(
+        "BUG: soft lockup - CPU#{} stuck for {}s! [tid {}]\n",
+        cpu,
+        (now - stamp) / clock_freq(),
+        DisplayTid(tid)
+    );
+    dump_registers(frame);
+    print_backtrace(frame.get_fp(), frame.get_sp());
+}
+
+/// `stamp` 距 `now` 是否超过阈值；`stamp` 为 0 时尚未开始调度，不算卡住
+fn is_stalled(stamp: usize, now: usize, freq: usize) -> bool {
+    stamp != 0 && now.saturating_sub(stamp) > STALL_THRESH_SECS * freq
+}
+
+/// 自旋锁等待者的计时状态，由 `RawSpinLock` 在自旋循环中定期检查
+pub struct SpinWatch {
+    start: usize,
+    reported: bool,
+}
+
+impl SpinWatch {
+    pub const fn new() -> Self {
+        Self {
+            start: 0,
+            reported: false,
+        }
+    }
+
+    /// 第一次调用开始计时，之后等待超过阈值时报告一次
+    ///
+    /// `owner` 是持有者的 CPU 号，锁刚被释放或尚未记录持有者时为 `None`。
+    pub fn check(&mut self, lock: usize, owner: Option<usize>) {
+        let now = crate::arch::get_time();
+        // 自旋期间中断已关闭，不会迁移到其它 CPU
+        let state = STALL.get_of(crate::arch::cpu_id());
+        state
+            .spin_owner
+            .store(owner.map_or(0, |cpu| cpu + 1), Ordering::Relaxed);
+        if self.start == 0 {
+            self.start = now;
+            state.spin_start.store(now, Ordering::Relaxed);
+            state.spin_lock.store(lock, Ordering::Relaxed);
+            return;
+        }
+        if self.reported || !is_stalled(self.start, now, clock_freq()) {
+            return;
+        }
+        self.reported = true;
+
+        let cpu = crate::arch::cpu_id();
+        stall_print!(
+            "BUG: spinlock lockup suspected on CPU#{}, lock {:#x} held by CPU#{} for {}s\n",
+            cpu,
+            lock,
+            DisplayCpu(owner),
+            (now - self.start) / clock_freq()
+        );
+        if owner == Some(cpu) {
+            stall_print!("  lock owner is this CPU: recursive locking?\n");
+        }
+        let fp = ArchImpl::frame_pointer();
+        print_backtrace(fp, fp.wrapping_sub(16));
+    }
+}
+
+impl Default for SpinWatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SpinWatch {
+    fn drop(&mut self) {
+        if self.start != 0 {
+            STALL
+                .get_of(crate::arch::cpu_id())
+                .spin_lock
+                .store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+struct DisplayTid(Option<u32>);
+
+impl fmt::Display for DisplayTid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(tid) => write!(f, "{}", tid),
+            None => f.write_str("?"),
+        }
+    }
+}
+
+struct DisplayCpu(Option<usize>);
+
+impl fmt::Display for DisplayCpu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(cpu) => write!(f, "{}", cpu),
+            None => f.write_str("?"),
+        }
+    }
+}
+
+/// 打印陷阱帧中的 pc 和 32 个通用寄存器（按寄存器编号）
+fn dump_registers(frame: &TrapFrame) {
+    stall_print!(
+        "pc : {:#018x} ra : {:#018x} sp : {:#018x}\n",
+        frame.get_sepc(),
+        frame.get_ra(),
+        frame.get_sp()
+    );
+    for (i, row) in frame.gprs().chunks(4).enumerate() {
+        stall_print!(
+            "r{:02}: {:#018x} {:#018x} {:#018x} {:#018x}\n",
+            i * 4,
+            row[0],
+            row[1],
+            row[2],
+            row[3]
+        );
+    }
+}
+
+/// 从 `fp` 开始沿帧指针链打印返回地址
+fn print_backtrace(fp: usize, sp: usize) {
+    stall_print!("Call trace:\n");
+    let mut depth = 0;
+    // SAFETY: walk_frames 只读取 [sp, sp + MAX_STACK_SPAN] 内对齐的地址，
+    // 该范围位于线性映射的内核栈上。
+    walk_frames(
+        fp,
+        sp,
+        |addr| unsafe { (addr as *const usize).read_volatile() },
+        |ra| {
+            stall_print!("  #{:<2} {:#018x}\n", depth, ra);
+            depth += 1;
+        },
+    );
+    if depth == 0 {
+        stall_print!("  (no frames)\n");
+    }
+}
+
+/// 沿帧指针链回溯，对每一帧的返回地址调用 `f`
+///
+/// riscv 与 loongarch 以 `force-frame-pointers` 编译，fp 指向本帧的栈顶，
+/// `fp - 8` 处保存返回地址，`fp - 16` 处保存调用者的 fp。只接受落在
+/// `[sp + 16, sp + MAX_STACK_SPAN]` 内、8 字节对齐且严格递增的 fp，
+/// 栈损坏时提前停止而不是越界访问。
+fn walk_frames(mut fp: usize, sp: usize, read: impl Fn(usize) -> usize, mut f: impl FnMut(usize)) {
+    let top = sp.saturating_add(MAX_STACK_SPAN);
+    for _ in 0..MAX_BACKTRACE_DEPTH {
+        if !fp.is_multiple_of(8) || fp < sp.saturating_add(16) || fp > top {
+            break;
+        }
+        let ra = read(fp - 8);
+        if ra == 0 {
+            break;
+        }
+        f(ra);
+        let prev = read(fp - 16);
+        if prev <= fp {
+            break;
+        }
+        fp = prev;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+    use alloc::vec::Vec;
+
+    test_case!(test_walk_frames, {
+        // 模拟一段栈：三帧，fp 依次为 base+32、base+64、base+96
+        let mut stack = [0usize; 16];
+        let base = stack.as_ptr() as usize;
+        let set = |stack: &mut [usize; 16], addr: usize, val: usize| {
+            stack[(addr - base) / 8] = val;
+        };
+        set(&mut stack, base + 32 - 8, 0x1000);
+        set(&mut stack, base + 32 - 16, base + 64);
+        set(&mut stack, base + 64 - 8, 0x2000);
+        set(&mut stack, base + 64 - 16, base + 96);
+        set(&mut stack, base + 96 - 8, 0x3000);
+        set(&mut stack, base + 96 - 16, 0);
+
+        let read = |addr: usize| stack[(addr - base) / 8];
+        let mut ras = Vec::new();
+        walk_frames(base + 32, base, read, |ra| ras.push(ra));
+        kassert!(ras == [0x1000, 0x2000, 0x3000]);
+
+        // fp 低于栈指针或未对齐时不读取
+        let mut count = 0;
+        walk_frames(base + 8, base, read, |_| count += 1);
+        walk_frames(base + 33, base, read, |_| count += 1);
+        kassert!(count == 0);
+    });
+
+    test_case!(test_is_stalled, {
+        let freq = 1000;
+        kassert!(!is_stalled(0, 100_000, freq));
+        kassert!(!is_stalled(5000, 5000 + STALL_THRESH_SECS * freq, freq));
+        kassert!(is_stalled(5000, 5001 + STALL_THRESH_SECS * freq, freq));
+        // 时间戳来自其它 CPU、略晚于 now 时不算卡住
+        kassert!(!is_stalled(5000, 4000, freq));
+    });
+}
@@ -1,11 +1,19 @@
 //! 用户凭证和权限相关的系统调用
 //!
-//! 在单 root 用户系统中，这些系统调用存储值但不实际限制权限
+//! 在单 root 用户系统中，uid/gid 相关调用存储值但不实际限制权限；
+//! 能力集可以通过 capset 收缩，特权系统调用按能力检查。
 
-use crate::kernel::task::current_task;
-use crate::uapi::cred::{GID_UNCHANGED, ROOT_GID, ROOT_UID, UID_UNCHANGED};
-use crate::uapi::errno::{EFAULT, EPERM};
-use crate::util::user_buffer::{validate_user_ptr_mut, write_to_user};
+use crate::arch::{Arch, ArchImpl, address::UA};
+use crate::kernel::task::{Capabilities, GroupList, TASK_MANAGER, TaskManagerTrait, current_task};
+use crate::uapi::cred::{
+    CapUserData, CapUserHeader, GID_UNCHANGED, LINUX_CAPABILITY_VERSION_1,
+    LINUX_CAPABILITY_VERSION_2, LINUX_CAPABILITY_VERSION_3, NGROUPS_MAX, ROOT_GID, ROOT_UID,
+    UID_UNCHANGED,
+};
+use crate::uapi::errno::{EFAULT, EINVAL, EPERM, ESRCH};
+use crate::util::user_buffer::{
+    read_from_user, validate_user_ptr, validate_user_ptr_mut, write_to_user,
+};
 
 /// 获取真实用户 ID
 ///
@@ -211,6 +219,102 @@ pub fn getresgid(rgid: *mut u32, egid: *mut u32, sgid: *mut u32) -> isize {
     0
 }
 
+/// 获取附加组
+///
+/// # 参数
+/// * `size` - `list` 的容量；为 0 时只返回组的个数，不写 `list`
+/// * `list` - 输出数组，组 ID 按升序排列
+///
+/// # 返回值
+/// * 组的个数
+/// * -EINVAL - `size` 为负或小于组的个数
+/// * -EFAULT - `list` 无效
+pub fn getgroups(size: i32, list: *mut u32) -> isize {
+    if size < 0 {
+        return -(EINVAL as isize);
+    }
+    let groups = current_task().lock().credential.groups;
+    if size == 0 {
+        return groups.len() as isize;
+    }
+    if (size as usize) < groups.len() {
+        return -(EINVAL as isize);
+    }
+    if groups.is_empty() {
+        return 0;
+    }
+    if !validate_user_ptr_mut(list) || !validate_user_ptr_mut(list.wrapping_add(groups.len() - 1)) {
+        return -(EFAULT as isize);
+    }
+    let gids = groups.as_slice();
+    // SAFETY: 目标范围已检查在用户空间内，未映射的页由 copy_to_user 报错
+    if unsafe {
+        ArchImpl::copy_to_user(
+            gids.as_ptr() as *const u8,
+            UA::from_usize(list as usize),
+            core::mem::size_of_val(gids),
+        )
+    }
+    .is_err()
+    {
+        return -(EFAULT as isize);
+    }
+    groups.len() as isize
+}
+
+/// 设置附加组
+///
+/// 需要 `CAP_SETGID`。与 Linux 相同，只修改调用线程的凭证，由 libc 负责同步到其它线程。
+///
+/// # 参数
+/// * `size` - 组的个数，最多 `NGROUPS_MAX`
+/// * `list` - 组 ID 数组；`size` 为 0 时清空附加组
+///
+/// # 返回值
+/// * 0 - 成功
+/// * -EINVAL - `size` 为负或超过 `NGROUPS_MAX`
+/// * -EPERM - 没有 `CAP_SETGID`
+/// * -EFAULT - `list` 无效
+pub fn setgroups(size: i32, list: *const u32) -> isize {
+    if size < 0 || size as usize > NGROUPS_MAX {
+        return -(EINVAL as isize);
+    }
+    let size = size as usize;
+    let task = current_task();
+    if !task
+        .lock()
+        .credential
+        .capabilities
+        .has(Capabilities::SETGID)
+    {
+        return -(EPERM as isize);
+    }
+
+    let mut gids = [0u32; NGROUPS_MAX];
+    if size > 0 {
+        if !validate_user_ptr(list) || !validate_user_ptr(list.wrapping_add(size - 1)) {
+            return -(EFAULT as isize);
+        }
+        // SAFETY: 源范围已检查在用户空间内，未映射的页由 copy_from_user 报错
+        if unsafe {
+            ArchImpl::copy_from_user(
+                UA::from_usize(list as usize),
+                gids.as_mut_ptr() as *mut u8,
+                size * core::mem::size_of::<u32>(),
+            )
+        }
+        .is_err()
+        {
+            return -(EFAULT as isize);
+        }
+    }
+    let Some(groups) = GroupList::from_slice(&gids[..size]) else {
+        return -(EINVAL as isize);
+    };
+    task.lock().credential.groups = groups;
+    0
+}
+
 /// 设置文件创建掩码
 ///
 /// # 参数
@@ -220,11 +324,128 @@ pub fn getresgid(rgid: *mut u32, egid: *mut u32, sgid: *mut u32) -> isize {
 /// 返回之前的 umask 值
 ///
 /// # 注意
-/// 在当前方案中，umask 值会被存储但不会实际应用到文件创建过程z
+/// umask 保存在 `FsStruct` 中，与 cwd 一样只在带 `CLONE_FS` 创建的任务之间共享
 pub fn umask(mask: u32) -> isize {
-    let task = current_task();
-    let mut task_inner = task.lock();
-    let old_umask = task_inner.umask;
-    task_inner.umask = mask & 0o777; // 只保留权限位
+    let fs = current_task().lock().fs.clone();
+    let mut fs = fs.lock();
+    let old_umask = fs.umask;
+    fs.umask = mask & 0o777; // 只保留权限位
     old_umask as isize
 }
+
+/// 校验 capget/capset 头部的版本，返回数据项个数
+///
+/// 版本不支持时把内核偏好的版本写回头部并返回 EINVAL。
+fn cap_data_count(hdrp: *mut CapUserHeader) -> Result<usize, isize> {
+    if !validate_user_ptr_mut(hdrp) {
+        return Err(-(EFAULT as isize));
+    }
+    let header = read_from_user(hdrp as *const CapUserHeader);
+    match header.version {
+        LINUX_CAPABILITY_VERSION_1 => Ok(1),
+        LINUX_CAPABILITY_VERSION_2 | LINUX_CAPABILITY_VERSION_3 => Ok(2),
+        _ => {
+            let preferred = CapUserHeader {
+                version: LINUX_CAPABILITY_VERSION_3,
+                ..header
+            };
+            write_to_user(hdrp, preferred);
+            Err(-(EINVAL as isize))
+        }
+    }
+}
+
+/// 获取任务能力集
+///
+/// # 参数
+/// * `hdrp` - 版本和目标 pid（0 表示调用者）
+/// * `datap` - 输出数组，版本 1 一项，版本 2/3 两项；为 NULL 时只探测版本
+pub fn capget(hdrp: *mut CapUserHeader, datap: *mut CapUserData) -> isize {
+    let count = match cap_data_count(hdrp) {
+        Ok(n) => n,
+        Err(e) => return e,
+    };
+    if datap.is_null() {
+        return 0;
+    }
+    if !validate_user_ptr_mut(datap) || !validate_user_ptr_mut(datap.wrapping_add(count - 1)) {
+        return -(EFAULT as isize);
+    }
+
+    let pid = read_from_user(hdrp as *const CapUserHeader).pid;
+    let task = if pid == 0 {
+        current_task()
+    } else {
+        match TASK_MANAGER.lock().get_task(pid as u32) {
+            Some(t) => t,
+            None => return -(ESRCH as isize),
+        }
+    };
+    let caps = task.lock().credential.capabilities;
+
+    for i in 0..count {
+        let shift = 32 * i;
+        let data = CapUserData {
+            effective: (caps.effective.bits() >> shift) as u32,
+            permitted: (caps.permitted.bits() >> shift) as u32,
+            inheritable: (caps.inheritable.bits() >> shift) as u32,
+        };
+        write_to_user(datap.wrapping_add(i), data);
+    }
+    0
+}
+
+/// 设置调用线程的能力集
+///
+/// 只能修改调用者自身（pid 为 0 或自己的 tid），规则见
+/// [`CapabilitySet::try_capset`](crate::kernel::task::CapabilitySet::try_capset)。
+pub fn capset(hdrp: *mut CapUserHeader, datap: *const CapUserData) -> isize {
+    let count = match cap_data_count(hdrp) {
+        Ok(n) => n,
+        Err(e) => return e,
+    };
+    if !validate_user_ptr(datap) || !validate_user_ptr(datap.wrapping_add(count - 1)) {
+        return -(EFAULT as isize);
+    }
+
+    let task = current_task();
+    let pid = read_from_user(hdrp as *const CapUserHeader).pid;
+    if pid != 0 && pid as u32 != task.lock().tid {
+        return -(EPERM as isize);
+    }
+
+    let (mut effective, mut permitted, mut inheritable) = (0u64, 0u64, 0u64);
+    for i in 0..count {
+        let data = read_from_user(datap.wrapping_add(i));
+        let shift = 32 * i;
+        effective |= (data.effective as u64) << shift;
+        permitted |= (data.permitted as u64) << shift;
+        inheritable |= (data.inheritable as u64) << shift;
+    }
+
+    let mut t = task.lock();
+    let ok = t.credential.capabilities.try_capset(
+        Capabilities::from_bits_truncate(effective),
+        Capabilities::from_bits_truncate(permitted),
+        Capabilities::from_bits_truncate(inheritable),
+    );
+    if ok { 0 } else { -(EPERM as isize) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        arch::{ArchImpl, virtual_memory::VirtualMemory},
+        kassert, test_case,
+    };
+
+    // 无效的头部指针在读取前返回 EFAULT
+    test_case!(test_capget_capset_bad_header, {
+        let kernel_ptr = (<ArchImpl as VirtualMemory>::USER_TOP + 1) as *mut CapUserHeader;
+        kassert!(capget(core::ptr::null_mut(), core::ptr::null_mut()) == -(EFAULT as isize));
+        kassert!(capget(kernel_ptr, core::ptr::null_mut()) == -(EFAULT as isize));
+        kassert!(capset(core::ptr::null_mut(), core::ptr::null()) == -(EFAULT as isize));
+        kassert!(capset(kernel_ptr, core::ptr::null()) == -(EFAULT as isize));
+    });
+}
@@ -0,0 +1,238 @@
+//! 未实现系统调用的统计
+//!
+//! 分发时找不到处理函数的调用号返回 `ENOSYS`，同时记入两处：
+//!
+//! - 全局直方图：按调用号汇总次数，并按发起调用的程序（任务名）细分，
+//!   通过 `/proc/enosys` 读出，写入 `0` 清空；
+//! - 任务自己的计数：任务退出时在日志中打印一行汇总，随后丢弃。
+//!
+//! 用于移植新的用户程序时一次性看到缺失的系统调用及其调用频率。
+//! 为防止随机调用号撑满内存，各表的条目数有上限，超出的调用只计入 `dropped`。
+
+use alloc::{collections::btree_map::BTreeMap, format, string::String, vec::Vec};
+use core::fmt::Write;
+
+use crate::{
+    kernel::{
+        EXIT_PRIO_KERNEL, ExitNotifier, current_task, register_exit_notifier,
+        syscall::{legacy, linux_abi},
+    },
+    sync::SpinLock,
+};
+
+/// 全局直方图最多记录的调用号数
+const MAX_SYSCALLS: usize = 256;
+/// 每个调用号最多细分的程序数
+const MAX_BINARIES: usize = 16;
+/// 每个任务最多记录的调用号数
+const MAX_TASK_SYSCALLS: usize = 64;
+
+/// 单个调用号的统计
+#[derive(Debug, Default)]
+struct SyscallMisses {
+    count: u64,
+    /// 按程序细分的次数
+    binaries: BTreeMap<String, u64>,
+}
+
+/// 未实现系统调用的全局直方图
+#[derive(Debug, Default)]
+pub struct EnosysStats {
+    syscalls: BTreeMap<usize, SyscallMisses>,
+    /// 因条目数达到上限而未能细分记录的调用次数
+    dropped: u64,
+}
+
+impl EnosysStats {
+    /// 记录一次调用，返回该程序是否第一次调用这个调用号
+    pub fn record(&mut self, nr: usize, binary: &str) -> bool {
+        if !self.syscalls.contains_key(&nr) && self.syscalls.len() >= MAX_SYSCALLS {
+            self.dropped += 1;
+            return false;
+        }
+        let entry = self.syscalls.entry(nr).or_default();
+        entry.count += 1;
+        if let Some(n) = entry.binaries.get_mut(binary) {
+            *n += 1;
+            return false;
+        }
+        if entry.binaries.len() >= MAX_BINARIES {
+            self.dropped += 1;
+            return false;
+        }
+        entry.binaries.insert(String::from(binary), 1);
+        true
+    }
+
+    /// 调用号 `nr` 被请求的总次数
+    pub fn count(&self, nr: usize) -> u64 {
+        self.syscalls.get(&nr).map_or(0, |entry| entry.count)
+    }
+
+    /// 清空统计
+    pub fn clear(&mut self) {
+        self.syscalls.clear();
+        self.dropped = 0;
+    }
+
+    /// 格式化为 `/proc/enosys` 的内容
+    ///
+    /// 每行一个调用号，按次数从多到少排列，次数相同时按调用号排列：
+    /// `nr name count binary:count ...`，Linux 中也不存在的调用号名称为 `?`。
+    /// 有丢弃的调用时末尾追加一行 `dropped N`。
+    pub fn format(&self) -> String {
+        let mut rows: Vec<(&usize, &SyscallMisses)> = self.syscalls.iter().collect();
+        rows.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(b.0)));
+
+        let mut out = String::from("nr\tname\tcount\tbinaries\n");
+        for (&nr, entry) in rows {
+            let _ = write!(
+                out,
+                "{}\t{}\t{}\t",
+                nr,
+                syscall_name(nr).unwrap_or("?"),
+                entry.count
+            );
+            let binaries: Vec<String> = entry
+                .binaries
+                .iter()
+                .map(|(name, n)| format!("{}:{}", name, n))
+                .collect();
+            out.push_str(&binaries.join(" "));
+            out.push('\n');
+        }
+        if self.dropped > 0 {
+            let _ = writeln!(out, "dropped {}", self.dropped);
+        }
+        out
+    }
+}
+
+/// 调用号在 Linux 中的名字，包括 asm-generic 的旧调用号
+pub fn syscall_name(nr: usize) -> Option<&'static str> {
+    linux_abi::name_of(nr).or_else(|| legacy::name_of(nr))
+}
+
+/// 任务自己的未实现系统调用计数，以调用号为键
+type TaskMisses = BTreeMap<usize, u64>;
+
+/// 格式化任务退出时的汇总：`name(nr)xN ...`，按调用号排列
+fn format_task_misses(misses: &TaskMisses) -> String {
+    let items: Vec<String> = misses
+        .iter()
+        .map(|(&nr, n)| format!("{}({})x{}", syscall_name(nr).unwrap_or("?"), nr, n))
+        .collect();
+    items.join(" ")
+}
+
+lazy_static::lazy_static! {
+    /// 全局直方图
+    static ref ENOSYS_STATS: SpinLock<EnosysStats> = SpinLock::new(EnosysStats::default());
+    /// 各任务的计数，以 tid 为键，任务退出时移除
+    static ref TASK_MISSES: SpinLock<BTreeMap<u32, TaskMisses>> =
+        SpinLock::new(BTreeMap::new());
+}
+
+/// 任务第一次调用未实现的系统调用时注册在该任务上，退出时打印汇总
+static ENOSYS_EXIT_NOTIFIER: ExitNotifier = ExitNotifier {
+    name: "enosys",
+    priority: EXIT_PRIO_KERNEL,
+    call: |task| {
+        let (tid, comm) = {
+            let t = task.lock();
+            (t.tid, t.comm())
+        };
+        let Some(misses) = TASK_MISSES.lock().remove(&tid) else {
+            return;
+        };
+        crate::pr_info!(
+            "[enosys] task {} ({}) exited, unimplemented syscalls: {}",
+            tid,
+            comm,
+            format_task_misses(&misses)
+        );
+    },
+};
+
+/// 记录当前任务的一次未实现系统调用
+///
+/// 返回该程序是否第一次调用这个调用号，调用者据此只在第一次时打印警告。
+pub fn record(nr: usize) -> bool {
+    let task = current_task();
+    let (tid, comm) = {
+        let t = task.lock();
+        (t.tid, t.comm())
+    };
+    let first = ENOSYS_STATS.lock().record(nr, &comm);
+
+    let mut tasks = TASK_MISSES.lock();
+    let misses = tasks.entry(tid).or_default();
+    let new_task = misses.is_empty();
+    if let Some(n) = misses.get_mut(&nr) {
+        *n += 1;
+    } else if misses.len() < MAX_TASK_SYSCALLS {
+        misses.insert(nr, 1);
+    }
+    drop(tasks);
+    if new_task {
+        register_exit_notifier(&task, &ENOSYS_EXIT_NOTIFIER);
+    }
+    first
+}
+
+/// `/proc/enosys` 的内容
+pub fn report() -> String {
+    ENOSYS_STATS.lock().format()
+}
+
+/// 清空全局直方图，各任务自己的计数不受影响
+pub fn reset() {
+    ENOSYS_STATS.lock().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_enosys_stats, {
+        let mut stats = EnosysStats::default();
+        kassert!(stats.record(437, "busybox"));
+        kassert!(!stats.record(437, "busybox"));
+        kassert!(stats.record(437, "ls"));
+        kassert!(stats.record(9999, "ls"));
+        kassert!(stats.count(437) == 3);
+        kassert!(stats.count(1) == 0);
+
+        let text = stats.format();
+        let lines: Vec<&str> = text.lines().collect();
+        kassert!(lines.len() == 3);
+        kassert!(lines[1] == "437\topenat2\t3\tbusybox:2 ls:1");
+        kassert!(lines[2] == "9999\t?\t1\tls:1");
+
+        stats.clear();
+        kassert!(stats.count(437) == 0);
+        kassert!(stats.format().lines().count() == 1);
+    });
+
+    test_case!(test_enosys_stats_limits, {
+        let mut stats = EnosysStats::default();
+        for i in 0..MAX_BINARIES + 2 {
+            stats.record(500, &format!("bin{}", i));
+        }
+        kassert!(stats.count(500) == (MAX_BINARIES + 2) as u64);
+        kassert!(stats.dropped == 2);
+        for nr in 1000..1000 + MAX_SYSCALLS {
+            stats.record(nr, "fuzz");
+        }
+        kassert!(stats.syscalls.len() == MAX_SYSCALLS);
+        kassert!(stats.format().ends_with("dropped 3\n"));
+    });
+
+    test_case!(test_enosys_task_summary, {
+        let mut misses = TaskMisses::new();
+        misses.insert(437, 2);
+        misses.insert(9999, 1);
+        kassert!(format_task_misses(&misses) == "openat2(437)x2 ?(9999)x1");
+    });
+}
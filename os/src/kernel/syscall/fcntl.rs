@@ -1,10 +1,10 @@
 //! fcntl 系统调用实现
 
-use crate::kernel::current_task;
+use crate::kernel::{current_task, register_exit_notifier, task_group_leader};
 use crate::uapi::errno::EINVAL;
 use crate::uapi::fcntl::{FcntlCmd, FdFlags, FileStatusFlags, Flock, LockType};
 use crate::util::user_buffer::{read_from_user, write_to_user};
-use crate::vfs::{FsError, OpenFlags, file_lock_manager};
+use crate::vfs::{FILE_LOCK_EXIT_NOTIFIER, FsError, OpenFlags, file_lock_manager};
 use alloc::sync::Arc;
 
 /// fcntl - 文件描述符操作
@@ -212,7 +212,13 @@ pub fn fcntl(fd: usize, cmd_raw: i32, arg: usize) -> isize {
             let dev = 0;
             let ino = metadata.inode_no as u64;
             match file_lock_manager().set_lock(dev, ino, start, len, lock_type, pid, blocking) {
-                Ok(()) => 0,
+                Ok(()) => {
+                    if lock_type != LockType::Unlock {
+                        let leader = task_group_leader(&task).unwrap_or(task);
+                        register_exit_notifier(&leader, &FILE_LOCK_EXIT_NOTIFIER);
+                    }
+                    0
+                }
                 Err(e) => e.to_errno(),
             }
         }
@@ -74,3 +74,47 @@ pub fn ftruncate(fd: usize, length: i64) -> isize {
         Err(e) => e.to_errno(),
     }
 }
+
+/// memfd_create - 创建匿名内存文件
+///
+/// # 语义（与 Linux 对齐的子集）
+/// - 返回可读写的 fd，初始长度为 0，用 ftruncate 设置大小。
+/// - `MAP_SHARED` 映射直接共享文件的物理页，通过 fd 传递即可跨进程共享。
+/// - 支持 `MFD_CLOEXEC`；`MFD_ALLOW_SEALING` 被接受但封印尚未实现。
+/// - `MFD_HUGETLB` 及未知标志返回 EINVAL，名称超过 249 字节返回 EINVAL。
+pub fn memfd_create(name: *const c_char, flags: u32) -> isize {
+    use crate::uapi::mm::{MFD_NAME_MAX_LEN, MemfdFlags};
+    use crate::vfs::RegFile;
+
+    let Some(memfd_flags) = MemfdFlags::from_bits(flags) else {
+        return FsError::InvalidArgument.to_errno();
+    };
+    if memfd_flags.contains(MemfdFlags::HUGETLB) {
+        return FsError::InvalidArgument.to_errno();
+    }
+
+    let name = match get_path_safe(name as usize) {
+        Ok(n) => n,
+        Err(e) => return e.to_errno(),
+    };
+    if name.len() > MFD_NAME_MAX_LEN {
+        return FsError::InvalidArgument.to_errno();
+    }
+
+    let dentry = match crate::fs::tmpfs::create_memfd(&name) {
+        Ok(d) => d,
+        Err(e) => return e.to_errno(),
+    };
+    let file = alloc::sync::Arc::new(RegFile::new(dentry, OpenFlags::O_RDWR));
+
+    let fd_flags = if memfd_flags.contains(MemfdFlags::CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+    let task = current_task();
+    match task.lock().fd_table.alloc_with_flags(file, fd_flags) {
+        Ok(fd) => fd as isize,
+        Err(e) => e.to_errno(),
+    }
+}
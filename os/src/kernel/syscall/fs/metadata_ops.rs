@@ -1,4 +1,5 @@
 use super::*;
+use crate::vfs::perm::{check_chmod, check_chown};
 use alloc::string::String;
 
 fn split_creating_path(dirfd: i32, path: &str) -> Result<(String, String), FsError> {
@@ -28,6 +29,20 @@ fn split_creating_path(dirfd: i32, path: &str) -> Result<(String, String), FsErr
     split_parent_preserving_basename(path)
 }
 
+fn chown_checked(dentry: &Dentry, owner: u32, group: u32) -> Result<(), FsError> {
+    check_mount_writable(dentry)?;
+    let cred = current_task().lock().credential;
+    check_chown(&cred, &dentry.inode.metadata()?, owner, group)?;
+    dentry.inode.chown(owner, group)
+}
+
+fn chmod_checked(dentry: &Dentry, mode: FileMode) -> Result<(), FsError> {
+    check_mount_writable(dentry)?;
+    let cred = current_task().lock().credential;
+    let mode = check_chmod(&cred, &dentry.inode.metadata()?, mode)?;
+    dentry.inode.chmod(mode)
+}
+
 /// fchownat - 修改文件所有者和组
 ///
 /// # 参数
@@ -41,8 +56,8 @@ fn split_creating_path(dirfd: i32, path: &str) -> Result<(String, String), FsErr
 /// * 0 - 成功
 /// * -errno - 失败
 ///
-/// # 在单 root 用户系统中的行为
-/// 所有调用都会成功并更新 inode 的 uid/gid 字段，不进行权限检查
+/// # 权限
+/// 改变属主需要 `CAP_CHOWN`，属主可以把属组改为自己的有效组（见 [`check_chown`]）
 pub fn fchownat(dirfd: i32, pathname: *const c_char, owner: u32, group: u32, flags: u32) -> isize {
     use crate::uapi::fs::AtFlags;
 
@@ -82,7 +97,7 @@ pub fn fchownat(dirfd: i32, pathname: *const c_char, owner: u32, group: u32, fla
             Err(e) => return e.to_errno(),
         };
 
-        return match dentry.inode.chown(owner, group) {
+        return match chown_checked(&dentry, owner, group) {
             Ok(()) => 0,
             Err(e) => e.to_errno(),
         };
@@ -97,8 +112,7 @@ pub fn fchownat(dirfd: i32, pathname: *const c_char, owner: u32, group: u32, fla
         Err(e) => return e.to_errno(),
     };
 
-    // 调用 inode 的 chown 方法
-    match dentry.inode.chown(owner, group) {
+    match chown_checked(&dentry, owner, group) {
         Ok(()) => 0,
         Err(e) => e.to_errno(),
     }
@@ -116,8 +130,8 @@ pub fn fchownat(dirfd: i32, pathname: *const c_char, owner: u32, group: u32, fla
 /// * 0 - 成功
 /// * -errno - 失败
 ///
-/// # 在单 root 用户系统中的行为
-/// 所有调用都会成功并更新 inode 的 mode 字段，不进行权限检查
+/// # 权限
+/// 需要是属主或拥有 `CAP_FOWNER`（见 [`check_chmod`]）
 pub fn fchmodat(dirfd: i32, pathname: *const c_char, mode: u32, flags: u32) -> isize {
     use crate::uapi::fs::AtFlags;
 
@@ -164,7 +178,7 @@ pub fn fchmodat(dirfd: i32, pathname: *const c_char, mode: u32, flags: u32) -> i
             Err(e) => return e.to_errno(),
         };
 
-        return match dentry.inode.chmod(file_mode) {
+        return match chmod_checked(&dentry, file_mode) {
             Ok(()) => 0,
             Err(e) => e.to_errno(),
         };
@@ -179,8 +193,7 @@ pub fn fchmodat(dirfd: i32, pathname: *const c_char, mode: u32, flags: u32) -> i
         Err(e) => return e.to_errno(),
     };
 
-    // 调用 inode 的 chmod 方法
-    match dentry.inode.chmod(file_mode) {
+    match chmod_checked(&dentry, file_mode) {
         Ok(()) => 0,
         Err(e) => e.to_errno(),
     }
@@ -227,8 +240,12 @@ pub fn mknodat(dirfd: i32, pathname: *const c_char, mode: u32, dev: u64) -> isiz
         return FsError::AlreadyExists.to_errno();
     }
 
+    if let Err(e) = check_mount_writable(&parent_dentry) {
+        return e.to_errno();
+    }
+
     // mknod without an explicit file type creates a regular file.
-    let mut file_mode = FileMode::from_bits_truncate(mode);
+    let mut file_mode = FileMode::from_bits_truncate(apply_umask(mode));
     if file_mode & FileMode::S_IFMT == FileMode::empty() {
         file_mode |= FileMode::S_IFREG;
     }
@@ -298,6 +315,10 @@ pub fn symlinkat(target: *const c_char, newdirfd: i32, linkpath: *const c_char)
         return FsError::AlreadyExists.to_errno();
     }
 
+    if let Err(e) = check_mount_writable(&parent_dentry) {
+        return e.to_errno();
+    }
+
     // 创建符号链接
     match parent_dentry.inode.symlink(&link_name, &target_str) {
         Ok(symlink_inode) => {
@@ -368,6 +389,9 @@ pub fn linkat(
     if is_special_basename(&new_name) {
         return FsError::AlreadyExists.to_errno();
     }
+    if let Err(e) = check_mount_writable(&new_parent) {
+        return e.to_errno();
+    }
 
     match new_parent.inode.link(&new_name, &old_dentry.inode) {
         Ok(()) => {
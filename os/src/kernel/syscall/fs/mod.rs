@@ -1,5 +1,6 @@
 //! 文件系统相关的系统调用实现
 
+use alloc::sync::Arc;
 use core::ffi::c_char;
 
 use crate::arch::Arch;
@@ -8,20 +9,20 @@ use crate::{
     kernel::{
         current_task,
         syscall::util::{
-            create_file_at, create_file_from_dentry, get_path_safe, is_special_basename,
-            resolve_at_path, resolve_at_path_string, resolve_at_path_with_flags,
-            split_parent_preserving_basename,
+            apply_umask, create_file_at, create_file_from_dentry, get_path_safe,
+            is_special_basename, resolve_at_path, resolve_at_path_string,
+            resolve_at_path_with_flags, split_parent_preserving_basename,
         },
     },
     uapi::{
         errno::{EACCES, EINVAL, ENOENT},
-        fs::{AtFlags, F_OK, FileSystemType, LinuxStatFs, R_OK, W_OK, X_OK},
+        fs::{AtFlags, F_OK, LinuxStatFs, R_OK, W_OK, X_OK},
         time::TimeSpec,
     },
     util::user_buffer::write_to_user,
     vfs::{
         DENTRY_CACHE, Dentry, FdFlags, FileMode, FsError, InodeType, OpenFlags, SeekWhence, Stat,
-        Statx, vfs_lookup,
+        Statx, check_mount_writable, get_root_dentry, vfs_lookup,
     },
 };
 
@@ -30,23 +31,81 @@ pub const AT_SYMLINK_NOFOLLOW: u32 = 0x100;
 pub const AT_REMOVEDIR: u32 = 0x200;
 pub const O_CLOEXEC: u32 = 0o2000000;
 
-fn drop_cached_child(parent: &Dentry, name: &str) {
+fn cached_child_path(parent: &Dentry, name: &str) -> alloc::string::String {
     let parent_path = parent.full_path();
-    let child_path = if parent_path == "/" {
+    if parent_path == "/" {
         alloc::format!("/{}", name)
     } else {
         alloc::format!("{}/{}", parent_path, name)
-    };
+    }
+}
+
+fn drop_cached_child(parent: &Dentry, name: &str) {
+    let child_path = cached_child_path(parent, name);
     parent.remove_child(name);
     DENTRY_CACHE.remove_tree(&child_path);
 }
 
+/// unlink/rmdir 成功后移除子 dentry，并标记为已删除（getcwd 据此返回 ENOENT）
+fn drop_deleted_child(parent: &Dentry, name: &str) {
+    if let Some(child) = parent.lookup_child(name) {
+        child.mark_deleted();
+    }
+    drop_cached_child(parent, name);
+}
+
+/// rename 成功后更新 dentry 树
+///
+/// 源 dentry 移动到新位置而不是丢弃，持有它的 cwd 和打开的文件随之看到新路径；
+/// 被覆盖的目标标记为已删除。
+fn move_cached_child(
+    old_parent: &Arc<Dentry>,
+    old_name: &str,
+    new_parent: &Arc<Dentry>,
+    new_name: &str,
+) {
+    let moved = old_parent.lookup_child(old_name);
+    if let Some(replaced) = new_parent.lookup_child(new_name)
+        && !moved.as_ref().is_some_and(|d| Arc::ptr_eq(d, &replaced))
+    {
+        replaced.mark_deleted();
+    }
+    drop_cached_child(new_parent, new_name);
+    DENTRY_CACHE.remove_tree(&cached_child_path(old_parent, old_name));
+    match moved {
+        Some(dentry) => dentry.move_to(new_parent, new_name),
+        None => drop_cached_child(old_parent, old_name),
+    }
+}
+
+/// RENAME_EXCHANGE 成功后交换两个子 dentry 的位置
+fn exchange_cached_children(
+    old_parent: &Arc<Dentry>,
+    old_name: &str,
+    new_parent: &Arc<Dentry>,
+    new_name: &str,
+) {
+    let old = old_parent.lookup_child(old_name);
+    let new = new_parent.lookup_child(new_name);
+    DENTRY_CACHE.remove_tree(&cached_child_path(old_parent, old_name));
+    DENTRY_CACHE.remove_tree(&cached_child_path(new_parent, new_name));
+    old_parent.remove_child(old_name);
+    new_parent.remove_child(new_name);
+    if let Some(old) = old {
+        old.move_to(new_parent, new_name);
+    }
+    if let Some(new) = new {
+        new.move_to(old_parent, old_name);
+    }
+}
+
 mod fd_ops;
 mod metadata_ops;
 mod mount_ops;
 mod path_ops;
 mod rename_ops;
 mod stat_ops;
+mod xattr_ops;
 
 pub use fd_ops::*;
 pub use metadata_ops::*;
@@ -54,3 +113,4 @@ pub use mount_ops::*;
 pub use path_ops::*;
 pub use rename_ops::*;
 pub use stat_ops::*;
+pub use xattr_ops::*;
@@ -1,4 +1,14 @@
 use super::*;
+use crate::{kernel::Capabilities, uapi::errno::EPERM};
+
+/// 挂载和卸载需要 `CAP_SYS_ADMIN`
+fn has_sys_admin() -> bool {
+    current_task()
+        .lock()
+        .credential
+        .capabilities
+        .has(Capabilities::SYS_ADMIN)
+}
 
 /// mount - 挂载文件系统
 ///
@@ -6,25 +16,33 @@ use super::*;
 /// 40 (SYS_MOUNT)
 ///
 /// # 简化实现说明
-/// - 支持 ext4 与 FAT/VFAT 块设备文件系统
-/// - 忽略所有 mountflags（但保留以保持 ABI 兼容）
-/// - 忽略 data 参数
+/// - 支持 ext4、FAT/VFAT 与 SimpleFS 块设备文件系统
+/// - mountflags 与 data 中的通用选项（`ro`、`nosuid`、`nodev`、`noatime` 等）合并为
+///   挂载点的标志；MS_REMOUNT、MS_BIND 等改变挂载方式的标志被忽略
+/// - data 中其余的选项仅 procfs 解析（`hidepid=`、`gid=`），其它文件系统忽略
 pub fn mount(
     source: *const c_char,
     target: *const c_char,
     filesystemtype: *const c_char,
-    _mountflags: u64,
-    _data: *const core::ffi::c_void,
+    mountflags: u64,
+    data: *const core::ffi::c_void,
 ) -> isize {
     use crate::config::EXT4_BLOCK_SIZE;
     use crate::fs::ext4::Ext4FileSystem;
+    use crate::fs::proc::ProcMountOptions;
+    use crate::fs::simple_fs::SimpleFs;
     use crate::fs::sysfs::find_block_device;
     use crate::fs::vfat::VfatFileSystem;
-    use crate::fs::{init_dev, init_procfs, init_sysfs, mount_tmpfs};
+    use crate::fs::{init_dev, init_procfs_with_options, init_sysfs, mount_tmpfs};
     use crate::kernel::syscall::fs::AT_FDCWD;
-    use crate::vfs::{MOUNT_TABLE, MountFlags as VfsMountFlags};
+    use crate::uapi::fs::SysMountFlags;
+    use crate::vfs::{MOUNT_TABLE, MountFlags as VfsMountFlags, parse_mount_options};
     use alloc::string::String;
 
+    if !has_sys_admin() {
+        return -(EPERM as isize);
+    }
+
     // 解析目标路径
     let target_str = match get_path_safe(target as usize) {
         Ok(s) => s,
@@ -51,11 +69,26 @@ pub fn mount(
         String::new()
     };
 
+    // data 为逗号分隔的选项字符串，如 "ro,nodev,hidepid=2"
+    let data_str = if !data.is_null() {
+        match get_path_safe(data as usize) {
+            Ok(s) => s,
+            Err(e) => return e.to_errno(),
+        }
+    } else {
+        String::new()
+    };
+    let (flags, fs_options) = parse_mount_options(
+        &data_str,
+        VfsMountFlags::from_syscall(SysMountFlags::from_bits_truncate(mountflags)),
+    );
+
     crate::pr_debug!(
-        "[SYSCALL] mount: source='{}', target='{}', type='{}'",
+        "[SYSCALL] mount: source='{}', target='{}', type='{}', flags={:?}",
         source_str,
         target_str,
-        fstype_str
+        fstype_str,
+        flags
     );
 
     fn ensure_dir_exists(path: &str) -> Result<(), FsError> {
@@ -118,26 +151,28 @@ pub fn mount(
     // 特殊挂载点处理
     match target_path.as_str() {
         "/proc" => {
-            return match init_procfs() {
+            return match ProcMountOptions::parse(&fs_options)
+                .and_then(|options| init_procfs_with_options(options, flags))
+            {
                 Ok(_) => 0,
                 Err(e) => e.to_errno(),
             };
         }
         "/sys" => {
-            return match init_sysfs() {
+            return match init_sysfs(flags) {
                 Ok(_) => 0,
                 Err(e) => e.to_errno(),
             };
         }
         "/tmp" => {
-            return match mount_tmpfs("/tmp", 0) {
+            return match mount_tmpfs("/tmp", 0, flags) {
                 Ok(_) => 0,
                 Err(e) => e.to_errno(),
             };
         }
         "/dev" => {
             // 先挂载 tmpfs 到 /dev
-            if let Err(e) = mount_tmpfs("/dev", 0) {
+            if let Err(e) = mount_tmpfs("/dev", 0, flags) {
                 return e.to_errno();
             }
             // 然后初始化设备节点
@@ -150,7 +185,7 @@ pub fn mount(
     }
 
     if fstype_str == "tmpfs" {
-        return match mount_tmpfs(&target_path, 0) {
+        return match mount_tmpfs(&target_path, 0, flags) {
             Ok(_) => 0,
             Err(e) => e.to_errno(),
         };
@@ -197,12 +232,7 @@ pub fn mount(
         };
 
         // 挂载文件系统
-        match MOUNT_TABLE.mount(
-            ext4_fs,
-            &target_path,
-            VfsMountFlags::empty(),
-            Some(source_str),
-        ) {
+        match MOUNT_TABLE.mount(ext4_fs, &target_path, flags, Some(source_str)) {
             Ok(()) => {
                 crate::pr_info!(
                     "[SYSCALL] mount: successfully mounted ext4 at '{}'",
@@ -234,12 +264,7 @@ pub fn mount(
             }
         };
 
-        match MOUNT_TABLE.mount(
-            vfat_fs,
-            &target_path,
-            VfsMountFlags::empty(),
-            Some(source_str),
-        ) {
+        match MOUNT_TABLE.mount(vfat_fs, &target_path, flags, Some(source_str)) {
             Ok(()) => {
                 crate::pr_info!(
                     "[SYSCALL] mount: successfully mounted vfat at '{}'",
@@ -254,6 +279,38 @@ pub fn mount(
         }
     }
 
+    if fstype_str == "simplefs" {
+        let dev_info = match find_source_block_device() {
+            Some(info) => info,
+            None => {
+                crate::pr_err!("[SYSCALL] mount: block device '{}' not found", source_str);
+                return -(ENOENT as isize);
+            }
+        };
+
+        let simple_fs = match SimpleFs::open(dev_info.device.clone()) {
+            Ok(fs) => fs,
+            Err(e) => {
+                crate::pr_err!("[SYSCALL] mount: failed to open simplefs: {:?}", e);
+                return e.to_errno();
+            }
+        };
+
+        match MOUNT_TABLE.mount(simple_fs, &target_path, flags, Some(source_str)) {
+            Ok(()) => {
+                crate::pr_info!(
+                    "[SYSCALL] mount: successfully mounted simplefs at '{}'",
+                    target_path
+                );
+                return 0;
+            }
+            Err(e) => {
+                crate::pr_err!("[SYSCALL] mount: failed: {:?}", e);
+                return e.to_errno();
+            }
+        }
+    }
+
     crate::pr_err!(
         "[SYSCALL] mount: unsupported filesystem type '{}' or target '{}'",
         fstype_str,
@@ -274,6 +331,10 @@ pub fn mount(
 pub fn umount2(target: *const c_char, _flags: i32) -> isize {
     use crate::vfs::MOUNT_TABLE;
 
+    if !has_sys_admin() {
+        return -(EPERM as isize);
+    }
+
     // 解析目标路径
     let target_str = match get_path_safe(target as usize) {
         Ok(s) => s,
@@ -333,15 +394,29 @@ pub fn syncfs(fd: usize) -> isize {
 /// fsync - 同步文件数据和元数据
 ///
 /// # 实现说明
-/// 在写直达架构下,等同于 syncfs
+/// 调用文件 inode 的 `sync`，由文件系统写回该 inode 并刷新设备写缓存；
+/// 管道、套接字等没有 inode 的文件返回 EINVAL
 pub fn fsync(fd: usize) -> isize {
-    syncfs(fd)
+    use crate::uapi::errno::EINVAL;
+
+    let file = match current_task().lock().fd_table.get(fd) {
+        Ok(file) => file,
+        Err(e) => return e.to_errno(),
+    };
+    let inode = match file.inode() {
+        Ok(inode) => inode,
+        Err(_) => return -EINVAL as isize,
+    };
+    match inode.sync() {
+        Ok(()) => 0,
+        Err(e) => e.to_errno(),
+    }
 }
 
 /// fdatasync - 同步文件数据(元数据可选)
 ///
 /// # 实现说明
-/// 在写直达架构下,完全等同于 fsync
+/// 写直达架构下数据块已随写入落到设备，与 fsync 相同
 pub fn fdatasync(fd: usize) -> isize {
     fsync(fd)
 }
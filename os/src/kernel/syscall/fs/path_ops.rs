@@ -70,6 +70,17 @@ pub fn openat(dirfd: i32, pathname: *const c_char, flags: u32, mode: u32) -> isi
         return FsError::NotDirectory.to_errno();
     }
 
+    // 只读挂载上不能以写方式打开文件系统自己的对象，设备节点和 FIFO 不受影响
+    if (open_flags.writable() || open_flags.contains(OpenFlags::O_TRUNC))
+        && matches!(
+            meta.inode_type,
+            InodeType::File | InodeType::Directory | InodeType::Symlink
+        )
+        && let Err(e) = check_mount_writable(&dentry)
+    {
+        return e.to_errno();
+    }
+
     // 处理 O_TRUNC (截断文件)
     if open_flags.contains(OpenFlags::O_TRUNC)
         && open_flags.writable()
@@ -122,8 +133,12 @@ pub fn mkdirat(dirfd: i32, pathname: *const c_char, mode: u32) -> isize {
         return FsError::AlreadyExists.to_errno();
     }
 
+    if let Err(e) = check_mount_writable(&parent_dentry) {
+        return e.to_errno();
+    }
+
     // 创建目录
-    let dir_mode = FileMode::from_bits_truncate(mode) | FileMode::S_IFDIR;
+    let dir_mode = FileMode::from_bits_truncate(apply_umask(mode)) | FileMode::S_IFDIR;
     match parent_dentry.inode.mkdir(&dirname, dir_mode) {
         Ok(_) => 0,
         Err(e) => e.to_errno(),
@@ -184,6 +199,10 @@ pub fn unlinkat(dirfd: i32, pathname: *const c_char, flags: u32) -> isize {
         }
     }
 
+    if let Err(e) = check_mount_writable(&parent_dentry) {
+        return e.to_errno();
+    }
+
     // 删除目录项
     let result = if is_rmdir {
         parent_dentry.inode.rmdir(&filename)
@@ -194,7 +213,7 @@ pub fn unlinkat(dirfd: i32, pathname: *const c_char, flags: u32) -> isize {
     match result {
         Ok(()) => {
             // 从缓存中移除
-            drop_cached_child(&parent_dentry, &filename);
+            drop_deleted_child(&parent_dentry, &filename);
             0
         }
         Err(e) => e.to_errno(),
@@ -214,38 +233,72 @@ pub fn chdir(path: *const c_char) -> isize {
         Err(e) => return e.to_errno(),
     };
 
-    // 检查是否为目录
-    let meta = match dentry.inode.metadata() {
-        Ok(m) => m,
+    match set_cwd(dentry) {
+        Ok(()) => 0,
+        Err(e) => e.to_errno(),
+    }
+}
+
+pub fn fchdir(fd: usize) -> isize {
+    let file = match current_task().lock().fd_table.get(fd) {
+        Ok(f) => f,
         Err(e) => return e.to_errno(),
     };
+    let dentry = match file.dentry() {
+        Ok(d) => d,
+        Err(_) => return FsError::NotDirectory.to_errno(),
+    };
+
+    match set_cwd(dentry) {
+        Ok(()) => 0,
+        Err(e) => e.to_errno(),
+    }
+}
 
+/// 检查 `dentry` 是目录后设为当前工作目录
+///
+/// cwd 保存的是 dentry 本身，getcwd 时沿父节点重建路径，不受路径长度和祖先目录重命名的影响。
+fn set_cwd(dentry: Arc<Dentry>) -> Result<(), FsError> {
+    let meta = dentry.inode.metadata()?;
     if meta.inode_type != InodeType::Directory {
-        return FsError::NotDirectory.to_errno();
+        return Err(FsError::NotDirectory);
     }
 
-    // 更新当前工作目录
     current_task().lock().fs.lock().cwd = Some(dentry);
-    0
+    Ok(())
 }
 
 pub fn getcwd(buf: *mut u8, size: usize) -> isize {
-    // 获取当前工作目录dentry
-    let cwd_dentry = match current_task().lock().fs.lock().cwd.clone() {
-        Some(d) => d,
-        None => return FsError::IoError.to_errno(),
+    // 获取当前工作目录和根目录 dentry
+    let (cwd_dentry, root_dentry) = {
+        let task = current_task();
+        let task = task.lock();
+        let fs = task.fs.lock();
+        (fs.cwd.clone(), fs.root.clone())
+    };
+    let Some(cwd_dentry) = cwd_dentry else {
+        return FsError::IoError.to_errno();
     };
 
-    // 获取完整路径
-    let path = cwd_dentry.full_path();
-    let path_bytes = path.as_bytes();
+    // 沿父节点重建路径（跨越挂载点），已删除的目录返回 ENOENT
+    let path = match root_dentry.or_else(|| get_root_dentry().ok()) {
+        Some(root) => cwd_dentry.path_from(&root),
+        None if cwd_dentry.is_deleted() => Err(FsError::NotFound),
+        None => Ok(cwd_dentry.full_path()),
+    };
+    let path = match path {
+        Ok(p) => p,
+        Err(e) => return e.to_errno(),
+    };
+    let mut path_bytes = path.into_bytes();
+    path_bytes.push(0);
 
     // 检查缓冲区大小
-    if path_bytes.len() + 1 > size {
+    if path_bytes.len() > size {
         return -(crate::uapi::errno::ERANGE as isize);
     }
 
-    // 复制到用户态缓冲区
+    // 复制到用户态缓冲区（含结尾的 NUL）
     if unsafe {
         crate::arch::ArchImpl::copy_to_user(
             path_bytes.as_ptr(),
@@ -257,18 +310,7 @@ pub fn getcwd(buf: *mut u8, size: usize) -> isize {
     {
         return FsError::BadAddress.to_errno();
     }
-    let nul = [0u8];
-    if unsafe {
-        crate::arch::ArchImpl::copy_to_user(
-            nul.as_ptr(),
-            crate::arch::address::UA::from_usize(buf as usize + path_bytes.len()),
-            nul.len(),
-        )
-    }
-    .is_err()
-    {
-        return FsError::BadAddress.to_errno();
-    }
 
-    buf as isize
+    // 与 Linux 一样返回写入的长度（含 NUL）
+    path_bytes.len() as isize
 }
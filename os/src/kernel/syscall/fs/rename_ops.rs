@@ -109,6 +109,11 @@ pub fn renameat2(
     if new_parent_meta.inode_type != InodeType::Directory {
         return -(ENOTDIR as isize);
     }
+    if let Err(e) =
+        check_mount_writable(&old_parent).and_then(|_| check_mount_writable(&new_parent))
+    {
+        return e.to_errno();
+    }
 
     if is_special_basename(&old_path.name) {
         return -(crate::uapi::errno::EBUSY as isize);
@@ -347,9 +352,8 @@ pub fn renameat2(
         );
 
         // 更新 dentry 缓存
-        drop_cached_child(&old_parent, &old_name);
         drop_cached_child(&old_parent, &temp_name);
-        drop_cached_child(&new_parent, &new_name);
+        exchange_cached_children(&old_parent, &old_name, &new_parent, &new_name);
     } else if rename_flags.contains(RenameFlags::NOREPLACE) {
         // 目标存在时失败
         if new_parent.inode.lookup(&new_name).is_ok() {
@@ -368,7 +372,7 @@ pub fn renameat2(
         }
 
         // 更新 dentry 缓存
-        drop_cached_child(&old_parent, &old_name);
+        move_cached_child(&old_parent, &old_name, &new_parent, &new_name);
     } else if rename_flags.contains(RenameFlags::WHITEOUT) {
         // WHITEOUT 暂不支持(需要 Union FS 支持)
         return FsError::NotSupported.to_errno();
@@ -400,8 +404,7 @@ pub fn renameat2(
         }
 
         // 更新 dentry 缓存
-        drop_cached_child(&old_parent, &old_name);
-        drop_cached_child(&new_parent, &new_name);
+        move_cached_child(&old_parent, &old_name, &new_parent, &new_name);
     }
 
     0
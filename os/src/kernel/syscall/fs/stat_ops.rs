@@ -59,8 +59,13 @@ pub fn fstat(fd: usize, statbuf: *mut Stat) -> isize {
     0
 }
 
+/// getdents64 - 读取目录项
+///
+/// 从目录偏移（目录项下标）开始，把放得下的目录项编码到内核缓冲区后一次拷贝到用户空间，
+/// 返回写入的字节数，目录读完时返回 0。缓冲区连下一条记录都放不下时返回 EINVAL，
+/// 放不下的条目留到下一次调用，从更新后的偏移继续。
 pub fn getdents64(fd: usize, dirp: *mut u8, count: usize) -> isize {
-    use crate::vfs::{LinuxDirent64, inode_type_to_d_type};
+    use crate::vfs::encode_dirents;
 
     // 检查参数有效性
     if dirp.is_null() || count == 0 {
@@ -81,72 +86,46 @@ pub fn getdents64(fd: usize, dirp: *mut u8, count: usize) -> isize {
         Err(e) => return e.to_errno(),
     };
 
-    // 获取当前文件偏移量 (作为 entry 索引)
-    // 假设目录的 offset 就是 entry 的 index
+    // 目录的 offset 就是下一个 entry 的下标
     let start_index = match file.lseek(0, SeekWhence::Cur) {
         Ok(pos) => pos,
         Err(e) => return e.to_errno(),
     };
+    if start_index >= entries.len() {
+        return 0;
+    }
 
-    // 写入目录项到用户空间
-    let mut written = 0usize;
-    let mut items_written = 0usize;
-
-    for entry in entries.iter().skip(start_index) {
-        // 计算这个 dirent 需要的空间
-        let dirent_len = LinuxDirent64::total_len(&entry.name);
-
-        // 检查缓冲区是否还有足够空间
-        if written + dirent_len > count {
-            break;
-        }
-
-        // 计算下一个 entry 的 offset (index + 1)
-        let current_off = (start_index + items_written + 1) as i64;
-
-        // 写入 dirent 头部
-        unsafe {
-            write_to_user(dirp.add(written) as *mut LinuxDirent64, LinuxDirent64 {
-                d_ino: entry.inode_no as u64,
-                d_off: current_off,
-                d_reclen: dirent_len as u16,
-                d_type: inode_type_to_d_type(entry.inode_type),
-            });
-        }
-
-        // 写入文件名到 d_name 字段（从 LinuxDirent64 offset 19 开始）
-        let name_offset = written + 19;
-        let name_bytes = entry.name.as_bytes();
-        unsafe {
-            crate::arch::ArchImpl::copy_to_user(
-                name_bytes.as_ptr(),
-                crate::arch::address::UA::from_usize(dirp as usize + name_offset),
-                name_bytes.len(),
-            )
-            .ok();
-            write_to_user(dirp.add(name_offset + name_bytes.len()), 0u8);
-        }
+    let mut buf = alloc::vec::Vec::new();
+    let items_written = encode_dirents(&entries, start_index, count, &mut buf);
+    if items_written == 0 {
+        // 缓冲区放不下下一条记录
+        return FsError::InvalidArgument.to_errno();
+    }
 
-        written += dirent_len;
-        items_written += 1;
+    let copied = unsafe {
+        crate::arch::ArchImpl::copy_to_user(
+            buf.as_ptr(),
+            crate::arch::address::UA::from_usize(dirp as usize),
+            buf.len(),
+        )
+    };
+    if copied.is_err() {
+        return FsError::BadAddress.to_errno();
     }
 
     // 更新文件偏移量
-    if items_written > 0 {
-        // 更新为新的 index
-        if let Err(e) = file.lseek((start_index + items_written) as isize, SeekWhence::Set) {
-            crate::pr_warn!(
-                "[getdents64] failed to update file offset for fd {}: {:?}",
-                fd,
-                e
-            );
-            // 即使更新 offset 失败，我们已经写入了数据，返回 written 更合理。
-            // 下一次 getdents64 调用可能会重复读取一些条目，但这比丢失数据或为部分成功的读取返回错误要好。
-        }
+    if let Err(e) = file.lseek((start_index + items_written) as isize, SeekWhence::Set) {
+        crate::pr_warn!(
+            "[getdents64] failed to update file offset for fd {}: {:?}",
+            fd,
+            e
+        );
+        // 即使更新 offset 失败，我们已经写入了数据，返回 written 更合理。
+        // 下一次 getdents64 调用可能会重复读取一些条目，但这比丢失数据或为部分成功的读取返回错误要好。
     }
 
     // 返回写入的字节数
-    written as isize
+    buf.len() as isize
 }
 
 pub fn statfs(path: *const c_char, buf: *mut LinuxStatFs) -> isize {
@@ -180,10 +159,8 @@ pub fn statfs(path: *const c_char, buf: *mut LinuxStatFs) -> isize {
     };
 
     // 转换为 Linux statfs 结构
-    let fs_type = FileSystemType::from_str(mount_point.fs.fs_type());
-
     let statfs_buf = LinuxStatFs {
-        f_type: fs_type.magic(),
+        f_type: fs_stat.fs_type.magic(),
         f_bsize: fs_stat.block_size as i64,
         f_blocks: fs_stat.total_blocks as u64,
         f_bfree: fs_stat.free_blocks as u64,
@@ -475,11 +452,11 @@ pub fn utimensat(dirfd: i32, pathname: *const c_char, times: *const TimeSpec, fl
         }
     };
 
-    // 解析时间参数
-    let (atime_opt, mtime_opt) = if times.is_null() {
+    // 解析时间参数；`explicit` 表示至少有一个时间戳由调用者指定
+    let (atime_opt, mtime_opt, explicit) = if times.is_null() {
         // NULL 表示将两个时间都设置为当前时间
         let now = TimeSpec::now();
-        (Some(now), Some(now))
+        (Some(now), Some(now), false)
     } else {
         unsafe {
             use crate::util::user_buffer::read_from_user;
@@ -511,10 +488,31 @@ pub fn utimensat(dirfd: i32, pathname: *const c_char, times: *const TimeSpec, fl
                 Some(ts1)
             };
 
-            (atime_opt, mtime_opt)
+            let explicit = [ts0, ts1].iter().any(|ts| !ts.is_omit() && !ts.is_now());
+            (atime_opt, mtime_opt, explicit)
         }
     };
 
+    // 两个时间都是 UTIME_OMIT 时不做任何修改，ctime 也不变
+    if atime_opt.is_none() && mtime_opt.is_none() {
+        return 0;
+    }
+    if let Err(e) = check_mount_writable(&dentry) {
+        return e.to_errno();
+    }
+
+    // 显式设置时间戳需要是属主或拥有 CAP_FOWNER
+    if explicit {
+        let cred = current_task().lock().credential;
+        let result = dentry
+            .inode
+            .metadata()
+            .and_then(|meta| crate::vfs::perm::check_set_times(&cred, &meta));
+        if let Err(e) = result {
+            return e.to_errno();
+        }
+    }
+
     // 设置时间戳
     if let Err(e) = dentry.inode.set_times(atime_opt, mtime_opt) {
         return e.to_errno();
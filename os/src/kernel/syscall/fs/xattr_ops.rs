@@ -0,0 +1,260 @@
+//! 扩展属性系统调用
+//!
+//! `*xattr` 按路径操作并跟随符号链接，`l*xattr` 不跟随，`f*xattr` 按文件描述符操作。
+//! 长度语义与 Linux 相同：`size` 为 0 时只返回所需长度，缓冲区不够时返回 ERANGE，
+//! 属性值超过 `XATTR_SIZE_MAX` 时返回 E2BIG。
+
+use super::*;
+use crate::arch::address::UA;
+use crate::uapi::xattr::{XATTR_LIST_MAX, XATTR_SIZE_MAX, XattrFlags};
+use crate::vfs::xattr::{check_xattr_permission, xattr_listable};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+fn xattr_ret(result: Result<usize, FsError>) -> isize {
+    match result {
+        Ok(n) => n as isize,
+        Err(e) => e.to_errno(),
+    }
+}
+
+/// 按路径查找文件，`follow` 为 false 时不跟随最后一级符号链接
+fn xattr_path_dentry(pathname: *const c_char, follow: bool) -> Result<Arc<Dentry>, FsError> {
+    let path = get_path_safe(pathname as usize)?;
+    if path.is_empty() {
+        return Err(FsError::NotFound);
+    }
+    resolve_at_path_with_flags(AT_FDCWD, &path, follow)
+}
+
+fn xattr_fd_dentry(fd: usize) -> Result<Arc<Dentry>, FsError> {
+    let file = current_task().lock().fd_table.get(fd)?;
+    file.dentry()
+}
+
+/// 读取属性名，过长时返回 ERANGE
+fn read_xattr_name(name: *const c_char) -> Result<String, FsError> {
+    get_path_safe(name as usize).map_err(|e| match e {
+        FsError::NameTooLong => FsError::OutOfRange,
+        e => e,
+    })
+}
+
+/// 把属性值或名字列表拷贝到用户缓冲区，返回其长度
+fn copy_xattr_out(buf: *mut u8, size: usize, data: &[u8]) -> Result<usize, FsError> {
+    if size == 0 || data.is_empty() {
+        return Ok(data.len());
+    }
+    if data.len() > size {
+        return Err(FsError::OutOfRange);
+    }
+    unsafe {
+        crate::arch::ArchImpl::copy_to_user(data.as_ptr(), UA::from_usize(buf as usize), data.len())
+    }
+    .map_err(|_| FsError::BadAddress)?;
+    Ok(data.len())
+}
+
+fn do_getxattr(
+    dentry: &Dentry,
+    name: *const c_char,
+    value: *mut u8,
+    size: usize,
+) -> Result<usize, FsError> {
+    let name = read_xattr_name(name)?;
+    let cred = current_task().lock().credential;
+    check_xattr_permission(&cred, &dentry.inode.metadata()?, &name, false)?;
+    let data = dentry.inode.getxattr(&name)?;
+    copy_xattr_out(value, size, &data)
+}
+
+fn do_setxattr(
+    dentry: &Dentry,
+    name: *const c_char,
+    value: *const u8,
+    size: usize,
+    flags: i32,
+) -> Result<usize, FsError> {
+    let flags = XattrFlags::from_bits(flags as u32).ok_or(FsError::InvalidArgument)?;
+    let name = read_xattr_name(name)?;
+    if size > XATTR_SIZE_MAX {
+        return Err(FsError::TooBig);
+    }
+    let mut data = alloc::vec![0u8; size];
+    if size > 0 {
+        unsafe {
+            crate::arch::ArchImpl::copy_from_user(
+                UA::from_usize(value as usize),
+                data.as_mut_ptr(),
+                size,
+            )
+        }
+        .map_err(|_| FsError::BadAddress)?;
+    }
+    let cred = current_task().lock().credential;
+    check_xattr_permission(&cred, &dentry.inode.metadata()?, &name, true)?;
+    check_mount_writable(dentry)?;
+    dentry.inode.setxattr(&name, &data, flags)?;
+    Ok(0)
+}
+
+fn do_listxattr(dentry: &Dentry, list: *mut u8, size: usize) -> Result<usize, FsError> {
+    let cred = current_task().lock().credential;
+    let mut names = Vec::new();
+    for name in dentry
+        .inode
+        .listxattr()?
+        .iter()
+        .filter(|name| xattr_listable(&cred, name))
+    {
+        names.extend_from_slice(name.as_bytes());
+        names.push(0);
+    }
+    if names.len() > XATTR_LIST_MAX {
+        return Err(FsError::TooBig);
+    }
+    copy_xattr_out(list, size, &names)
+}
+
+fn do_removexattr(dentry: &Dentry, name: *const c_char) -> Result<usize, FsError> {
+    let name = read_xattr_name(name)?;
+    let cred = current_task().lock().credential;
+    check_xattr_permission(&cred, &dentry.inode.metadata()?, &name, true)?;
+    check_mount_writable(dentry)?;
+    dentry.inode.removexattr(&name)?;
+    Ok(0)
+}
+
+/// setxattr - 设置扩展属性
+///
+/// # 参数
+/// * `pathname` - 文件路径（跟随符号链接）
+/// * `name` - 属性名，如 `user.mime_type`
+/// * `value`/`size` - 属性值
+/// * `flags` - `XATTR_CREATE`（已存在时失败）或 `XATTR_REPLACE`（不存在时失败）
+///
+/// # 返回值
+/// * 0 - 成功
+/// * -errno - 失败
+pub fn setxattr(
+    pathname: *const c_char,
+    name: *const c_char,
+    value: *const u8,
+    size: usize,
+    flags: i32,
+) -> isize {
+    xattr_ret(
+        xattr_path_dentry(pathname, true)
+            .and_then(|dentry| do_setxattr(&dentry, name, value, size, flags)),
+    )
+}
+
+/// lsetxattr - 设置扩展属性，不跟随符号链接
+pub fn lsetxattr(
+    pathname: *const c_char,
+    name: *const c_char,
+    value: *const u8,
+    size: usize,
+    flags: i32,
+) -> isize {
+    xattr_ret(
+        xattr_path_dentry(pathname, false)
+            .and_then(|dentry| do_setxattr(&dentry, name, value, size, flags)),
+    )
+}
+
+/// fsetxattr - 设置打开文件的扩展属性
+pub fn fsetxattr(
+    fd: usize,
+    name: *const c_char,
+    value: *const u8,
+    size: usize,
+    flags: i32,
+) -> isize {
+    xattr_ret(xattr_fd_dentry(fd).and_then(|dentry| do_setxattr(&dentry, name, value, size, flags)))
+}
+
+/// getxattr - 读取扩展属性
+///
+/// # 参数
+/// * `pathname` - 文件路径（跟随符号链接）
+/// * `name` - 属性名
+/// * `value`/`size` - 用户缓冲区；`size` 为 0 时只返回属性值的长度
+///
+/// # 返回值
+/// * 属性值的长度 - 成功
+/// * -ENODATA - 属性不存在
+/// * -ERANGE - 缓冲区太小
+pub fn getxattr(
+    pathname: *const c_char,
+    name: *const c_char,
+    value: *mut u8,
+    size: usize,
+) -> isize {
+    xattr_ret(
+        xattr_path_dentry(pathname, true)
+            .and_then(|dentry| do_getxattr(&dentry, name, value, size)),
+    )
+}
+
+/// lgetxattr - 读取扩展属性，不跟随符号链接
+pub fn lgetxattr(
+    pathname: *const c_char,
+    name: *const c_char,
+    value: *mut u8,
+    size: usize,
+) -> isize {
+    xattr_ret(
+        xattr_path_dentry(pathname, false)
+            .and_then(|dentry| do_getxattr(&dentry, name, value, size)),
+    )
+}
+
+/// fgetxattr - 读取打开文件的扩展属性
+pub fn fgetxattr(fd: usize, name: *const c_char, value: *mut u8, size: usize) -> isize {
+    xattr_ret(xattr_fd_dentry(fd).and_then(|dentry| do_getxattr(&dentry, name, value, size)))
+}
+
+/// listxattr - 列出扩展属性名
+///
+/// 名字以 `\0` 结尾依次排列；没有 `CAP_SYS_ADMIN` 时不列出 `trusted.*`。
+///
+/// # 返回值
+/// * 名字列表的总长度 - 成功（`size` 为 0 时只返回长度）
+/// * -ERANGE - 缓冲区太小
+pub fn listxattr(pathname: *const c_char, list: *mut u8, size: usize) -> isize {
+    xattr_ret(
+        xattr_path_dentry(pathname, true).and_then(|dentry| do_listxattr(&dentry, list, size)),
+    )
+}
+
+/// llistxattr - 列出扩展属性名，不跟随符号链接
+pub fn llistxattr(pathname: *const c_char, list: *mut u8, size: usize) -> isize {
+    xattr_ret(
+        xattr_path_dentry(pathname, false).and_then(|dentry| do_listxattr(&dentry, list, size)),
+    )
+}
+
+/// flistxattr - 列出打开文件的扩展属性名
+pub fn flistxattr(fd: usize, list: *mut u8, size: usize) -> isize {
+    xattr_ret(xattr_fd_dentry(fd).and_then(|dentry| do_listxattr(&dentry, list, size)))
+}
+
+/// removexattr - 删除扩展属性
+///
+/// # 返回值
+/// * 0 - 成功
+/// * -ENODATA - 属性不存在
+pub fn removexattr(pathname: *const c_char, name: *const c_char) -> isize {
+    xattr_ret(xattr_path_dentry(pathname, true).and_then(|dentry| do_removexattr(&dentry, name)))
+}
+
+/// lremovexattr - 删除扩展属性，不跟随符号链接
+pub fn lremovexattr(pathname: *const c_char, name: *const c_char) -> isize {
+    xattr_ret(xattr_path_dentry(pathname, false).and_then(|dentry| do_removexattr(&dentry, name)))
+}
+
+/// fremovexattr - 删除打开文件的扩展属性
+pub fn fremovexattr(fd: usize, name: *const c_char) -> isize {
+    xattr_ret(xattr_fd_dentry(fd).and_then(|dentry| do_removexattr(&dentry, name)))
+}
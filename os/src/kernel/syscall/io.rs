@@ -3,15 +3,15 @@
 use crate::arch::Arch;
 use crate::arch::address::UA;
 use crate::kernel::current_task;
+use crate::net::socket::SockTimeout;
 use crate::uapi::errno::EFAULT;
 use crate::uapi::errno::EINVAL;
-use crate::uapi::iovec::IoVec;
+use crate::uapi::iovec::{IOV_MAX, IoVec};
 use crate::uapi::select::FdSet;
-use crate::util::user_buffer::{
-    read_from_user, validate_user_ptr, validate_user_ptr_mut, write_to_user,
-};
-use crate::vfs::File;
+use crate::util::user_buffer::{UserBuffer, read_from_user, validate_user_ptr, write_to_user};
+use crate::vfs::{File, FsError};
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 
 fn empty_iovec() -> IoVec {
     IoVec {
@@ -20,6 +20,30 @@ fn empty_iovec() -> IoVec {
     }
 }
 
+/// 经由 `read_pages` 把文件数据从页缓存直接拷贝到用户缓冲区
+///
+/// 每个缓存页只经过一次 `copy_to_user`，不分配中间缓冲区。
+/// 文件不支持该快速路径时返回 `None`，调用者回退到普通的读取路径。
+fn read_pages_to_user(
+    buf: *mut u8,
+    read: impl FnOnce(&mut dyn FnMut(&[u8]) -> Result<(), FsError>) -> Result<usize, FsError>,
+) -> Option<isize> {
+    let mut dst = buf as usize;
+    let result = read(&mut |data| {
+        unsafe {
+            crate::arch::ArchImpl::copy_to_user(data.as_ptr(), UA::from_usize(dst), data.len())
+        }
+        .map_err(|_| FsError::BadAddress)?;
+        dst += data.len();
+        Ok(())
+    });
+    match result {
+        Err(FsError::NotSupported) => None,
+        Ok(n) => Some(n as isize),
+        Err(e) => Some(e.to_errno()),
+    }
+}
+
 fn copy_user_bytes(src: *const u8, len: usize) -> Result<alloc::vec::Vec<u8>, isize> {
     let mut buf = alloc::vec![0u8; len];
     unsafe {
@@ -49,11 +73,95 @@ fn copy_user_array<T: Copy>(
     Ok(buf)
 }
 
+/// 经由 uaccess 读入用户的 iovec 数组
+///
+/// - `iovcnt` 超过 [`IOV_MAX`] 或总长度超出 ssize_t 范围时返回 EINVAL；
+/// - 数组本身不可访问时返回 EFAULT；
+/// - `iovcnt` 为 0 时返回空数组，不访问 `iov`。
+fn import_iovec(iov: *const IoVec, iovcnt: usize) -> Result<Vec<IoVec>, isize> {
+    if iovcnt > IOV_MAX {
+        return Err(-(EINVAL as isize));
+    }
+    if iovcnt == 0 {
+        return Ok(Vec::new());
+    }
+    if !validate_user_ptr(iov) {
+        return Err(-(EFAULT as isize));
+    }
+    let iovecs = copy_user_array(iov, iovcnt, empty_iovec())?;
+    if IoVec::total_len(&iovecs).is_none() {
+        return Err(-(EINVAL as isize));
+    }
+    Ok(iovecs)
+}
+
+/// 依次处理每个 iovec，所有向量 I/O 系统调用共用
+///
+/// `op` 返回该段实际传输的字节数。遇到短传输即停止；某段出错时，
+/// 若之前已有数据完成传输则返回已完成的字节数，否则返回该错误。
+fn iov_for_each(iovecs: &[IoVec], mut op: impl FnMut(&IoVec) -> Result<usize, isize>) -> isize {
+    let mut done = 0usize;
+    for vec in iovecs {
+        if vec.iov_len == 0 {
+            continue;
+        }
+        match op(vec) {
+            Ok(n) => {
+                done += n;
+                if n < vec.iov_len {
+                    break;
+                }
+            }
+            Err(e) => return if done > 0 { done as isize } else { e },
+        }
+    }
+    done as isize
+}
+
+/// 读取一段数据到 iovec 描述的用户缓冲区
+fn read_segment(
+    vec: &IoVec,
+    read: impl FnOnce(&mut [u8]) -> Result<usize, FsError>,
+) -> Result<usize, isize> {
+    if !user_range_ok(vec) {
+        return Err(-(EFAULT as isize));
+    }
+    let mut kernel_buf = alloc::vec![0u8; vec.iov_len];
+    let n = read(&mut kernel_buf).map_err(|e| e.to_errno())?;
+    unsafe {
+        crate::arch::ArchImpl::copy_to_user(
+            kernel_buf.as_ptr(),
+            UA::from_usize(vec.iov_base as usize),
+            n,
+        )
+    }
+    .map_err(|_| -(EFAULT as isize))?;
+    Ok(n)
+}
+
+/// 把 iovec 描述的用户缓冲区写出
+fn write_segment(
+    vec: &IoVec,
+    write: impl FnOnce(&[u8]) -> Result<usize, FsError>,
+) -> Result<usize, isize> {
+    if !user_range_ok(vec) {
+        return Err(-(EFAULT as isize));
+    }
+    let kernel_buf = copy_user_bytes(vec.iov_base, vec.iov_len)?;
+    write(&kernel_buf).map_err(|e| e.to_errno())
+}
+
+/// iovec 描述的缓冲区是否完整落在用户地址范围内
+fn user_range_ok(vec: &IoVec) -> bool {
+    !vec.iov_base.is_null() && UserBuffer::new(vec.iov_base, vec.iov_len).range_sane()
+}
+
 fn should_retry_would_block(file: &Arc<dyn File>) -> bool {
     use crate::net::socket::SocketFile;
     use crate::net::unix_socket::UnixSocketFile;
     use crate::uapi::fcntl::OpenFlags;
     use crate::vfs::PipeFile;
+    use crate::vfs::impls::CharDeviceFile;
 
     if let Some(socket_file) = file.as_any().downcast_ref::<SocketFile>() {
         return !socket_file.flags().contains(OpenFlags::O_NONBLOCK);
@@ -67,40 +175,37 @@ fn should_retry_would_block(file: &Arc<dyn File>) -> bool {
         return !pipe_file.flags().contains(OpenFlags::O_NONBLOCK);
     }
 
+    if let Some(char_file) = file.as_any().downcast_ref::<CharDeviceFile>() {
+        return !char_file.flags().contains(OpenFlags::O_NONBLOCK);
+    }
+
     false
 }
 
-fn wait_for_would_block(file: Arc<dyn File>, task: crate::kernel::SharedTask) -> Result<(), isize> {
+fn wait_for_would_block(
+    file: Arc<dyn File>,
+    task: crate::kernel::SharedTask,
+    dir: crate::net::socket::SockTimeout,
+    start: usize,
+) -> Result<(), isize> {
     if file.as_any().is::<crate::net::socket::SocketFile>() {
-        crate::net::socket::poll_network_and_dispatch();
+        return crate::net::socket::wait_socket_io(file, &task, dir, start);
     }
 
     drop(file);
     crate::kernel::yield_task();
 
+    // 管道和字符设备上的阻塞读写尚未传输数据，可以按 SA_RESTART 重启
     if crate::ipc::signal_interrupts_syscall(&task) {
-        return Err(-(crate::uapi::errno::EINTR as isize));
+        return Err(-(crate::uapi::errno::ERESTARTSYS as isize));
     }
 
     Ok(())
 }
 
-fn file_read_ready(file: &Arc<dyn File>) -> bool {
-    if let Some(pipe_file) = file.as_any().downcast_ref::<crate::vfs::PipeFile>() {
-        return pipe_file.read_ready();
-    }
-    file.readable()
-}
-
-fn file_write_ready(file: &Arc<dyn File>) -> bool {
-    if let Some(pipe_file) = file.as_any().downcast_ref::<crate::vfs::PipeFile>() {
-        return pipe_file.write_ready();
-    }
-    file.writable()
-}
-
 /// 向文件描述符写入数据
 pub fn write(fd: usize, buf: *const u8, count: usize) -> isize {
+    let start = crate::arch::get_time();
     loop {
         let task = current_task();
         let file = match task.lock().fd_table.get(fd) {
@@ -120,7 +225,7 @@ pub fn write(fd: usize, buf: *const u8, count: usize) -> isize {
 
         if result == -11 {
             if should_retry_would_block(&file) {
-                if let Err(e) = wait_for_would_block(file, task) {
+                if let Err(e) = wait_for_would_block(file, task, SockTimeout::Send, start) {
                     return e;
                 }
                 continue;
@@ -133,6 +238,7 @@ pub fn write(fd: usize, buf: *const u8, count: usize) -> isize {
 
 /// 从文件描述符读取数据
 pub fn read(fd: usize, buf: *mut u8, count: usize) -> isize {
+    let start = crate::arch::get_time();
     loop {
         let task = current_task();
         let file = match task.lock().fd_table.get(fd) {
@@ -140,6 +246,10 @@ pub fn read(fd: usize, buf: *mut u8, count: usize) -> isize {
             Err(e) => return e.to_errno(),
         };
 
+        if let Some(result) = read_pages_to_user(buf, |sink| file.read_pages(count, sink)) {
+            return result;
+        }
+
         let mut kernel_buf = alloc::vec![0u8; count];
         let result = match file.read(&mut kernel_buf) {
             Ok(n) => {
@@ -158,7 +268,7 @@ pub fn read(fd: usize, buf: *mut u8, count: usize) -> isize {
 
         if result == -11 {
             if should_retry_would_block(&file) {
-                if let Err(e) = wait_for_would_block(file, task) {
+                if let Err(e) = wait_for_would_block(file, task, SockTimeout::Recv, start) {
                     return e;
                 }
                 continue;
@@ -171,15 +281,7 @@ pub fn read(fd: usize, buf: *mut u8, count: usize) -> isize {
 
 /// 向量化读取：从文件描述符读取数据到多个缓冲区
 pub fn readv(fd: usize, iov: *const IoVec, iovcnt: usize) -> isize {
-    if iov.is_null() || iovcnt == 0 || iovcnt > 1024 {
-        return -(EINVAL as isize);
-    }
-
-    if !validate_user_ptr(iov) {
-        return -(EFAULT as isize);
-    }
-
-    let iovec_array = match copy_user_array(iov, iovcnt, empty_iovec()) {
+    let iovecs = match import_iovec(iov, iovcnt) {
         Ok(iovecs) => iovecs,
         Err(e) => return e,
     };
@@ -190,60 +292,12 @@ pub fn readv(fd: usize, iov: *const IoVec, iovcnt: usize) -> isize {
         Err(e) => return e.to_errno(),
     };
 
-    let mut total_read = 0usize;
-    for vec in &iovec_array {
-        if vec.iov_base.is_null() || vec.iov_len == 0 {
-            continue;
-        }
-
-        if !validate_user_ptr_mut(vec.iov_base) {
-            return if total_read > 0 {
-                total_read as isize
-            } else {
-                -(EFAULT as isize)
-            };
-        }
-
-        let mut kernel_buf = alloc::vec![0u8; vec.iov_len];
-        match file.read(&mut kernel_buf) {
-            Ok(n) => {
-                unsafe {
-                    crate::arch::ArchImpl::copy_to_user(
-                        kernel_buf.as_ptr(),
-                        crate::arch::address::UA::from_usize(vec.iov_base as usize),
-                        n,
-                    )
-                    .ok();
-                }
-                total_read += n;
-                if n < vec.iov_len {
-                    break;
-                }
-            }
-            Err(e) => {
-                return if total_read > 0 {
-                    total_read as isize
-                } else {
-                    e.to_errno()
-                };
-            }
-        }
-    }
-
-    total_read as isize
+    iov_for_each(&iovecs, |vec| read_segment(vec, |kbuf| file.read(kbuf)))
 }
 
 /// 向量化写入：将多个缓冲区的数据写入文件描述符
 pub fn writev(fd: usize, iov: *const IoVec, iovcnt: usize) -> isize {
-    if iov.is_null() || iovcnt == 0 || iovcnt > 1024 {
-        return -(EINVAL as isize);
-    }
-
-    if !validate_user_ptr(iov) {
-        return -(EFAULT as isize);
-    }
-
-    let iovec_array = match copy_user_array(iov, iovcnt, empty_iovec()) {
+    let iovecs = match import_iovec(iov, iovcnt) {
         Ok(iovecs) => iovecs,
         Err(e) => return e,
     };
@@ -254,48 +308,7 @@ pub fn writev(fd: usize, iov: *const IoVec, iovcnt: usize) -> isize {
         Err(e) => return e.to_errno(),
     };
 
-    let mut total_written = 0usize;
-    for vec in &iovec_array {
-        if vec.iov_base.is_null() || vec.iov_len == 0 {
-            continue;
-        }
-
-        if !validate_user_ptr(vec.iov_base) {
-            return if total_written > 0 {
-                total_written as isize
-            } else {
-                -(EFAULT as isize)
-            };
-        }
-
-        let kernel_buf = match copy_user_bytes(vec.iov_base, vec.iov_len) {
-            Ok(buf) => buf,
-            Err(e) => {
-                return if total_written > 0 {
-                    total_written as isize
-                } else {
-                    e
-                };
-            }
-        };
-        match file.write(&kernel_buf) {
-            Ok(n) => {
-                total_written += n;
-                if n < vec.iov_len {
-                    break;
-                }
-            }
-            Err(e) => {
-                return if total_written > 0 {
-                    total_written as isize
-                } else {
-                    e.to_errno()
-                };
-            }
-        }
-    }
-
-    total_written as isize
+    iov_for_each(&iovecs, |vec| write_segment(vec, |kbuf| file.write(kbuf)))
 }
 
 /// 位置读取：从指定位置读取数据，不改变文件偏移量
@@ -315,6 +328,12 @@ pub fn pread64(fd: usize, buf: *mut u8, count: usize, offset: i64) -> isize {
         Err(e) => return e.to_errno(),
     };
 
+    if let Some(result) =
+        read_pages_to_user(buf, |sink| file.read_pages_at(offset as usize, count, sink))
+    {
+        return result;
+    }
+
     let mut kernel_buf = alloc::vec![0u8; count];
     match file.read_at(offset as usize, &mut kernel_buf) {
         Ok(n) => {
@@ -366,14 +385,14 @@ pub fn pwrite64(fd: usize, buf: *const u8, count: usize, offset: i64) -> isize {
 /// - `iovcnt`: iovec 数组元素个数
 /// - `offset`: 文件偏移量
 pub fn preadv(fd: usize, iov: *const IoVec, iovcnt: usize, offset: i64) -> isize {
-    if iov.is_null() || iovcnt == 0 || iovcnt > 1024 || offset < 0 {
+    if offset < 0 {
         return -(EINVAL as isize);
     }
 
-    // 验证 iovec 数组指针
-    if !validate_user_ptr(iov) {
-        return -(EFAULT as isize);
-    }
+    let iovecs = match import_iovec(iov, iovcnt) {
+        Ok(iovecs) => iovecs,
+        Err(e) => return e,
+    };
 
     let task = current_task();
     let file = match task.lock().fd_table.get(fd) {
@@ -381,54 +400,12 @@ pub fn preadv(fd: usize, iov: *const IoVec, iovcnt: usize, offset: i64) -> isize
         Err(e) => return e.to_errno(),
     };
 
-    let iovec_array = match copy_user_array(iov, iovcnt, empty_iovec()) {
-        Ok(iovecs) => iovecs,
-        Err(e) => return e,
-    };
-
-    let mut total_read = 0usize;
-    let mut current_offset = offset as usize;
-    for vec in &iovec_array {
-        if vec.iov_base.is_null() || vec.iov_len == 0 {
-            continue;
-        }
-
-        if !validate_user_ptr_mut(vec.iov_base) {
-            return if total_read > 0 {
-                total_read as isize
-            } else {
-                -(EFAULT as isize)
-            };
-        }
-
-        let mut kernel_buf = alloc::vec![0u8; vec.iov_len];
-        match file.read_at(current_offset, &mut kernel_buf) {
-            Ok(n) => {
-                unsafe {
-                    crate::arch::ArchImpl::copy_to_user(
-                        kernel_buf.as_ptr(),
-                        crate::arch::address::UA::from_usize(vec.iov_base as usize),
-                        n,
-                    )
-                    .ok();
-                }
-                total_read += n;
-                current_offset += n;
-                if n < vec.iov_len {
-                    break;
-                }
-            }
-            Err(e) => {
-                return if total_read > 0 {
-                    total_read as isize
-                } else {
-                    e.to_errno()
-                };
-            }
-        }
-    }
-
-    total_read as isize
+    let mut pos = offset as usize;
+    iov_for_each(&iovecs, |vec| {
+        let n = read_segment(vec, |kbuf| file.read_at(pos, kbuf))?;
+        pos += n;
+        Ok(n)
+    })
 }
 
 /// 向量化位置写入：将多个缓冲区的数据写入指定位置，不改变文件偏移量
@@ -438,14 +415,14 @@ pub fn preadv(fd: usize, iov: *const IoVec, iovcnt: usize, offset: i64) -> isize
 /// - `iovcnt`: iovec 数组元素个数
 /// - `offset`: 文件偏移量
 pub fn pwritev(fd: usize, iov: *const IoVec, iovcnt: usize, offset: i64) -> isize {
-    if iov.is_null() || iovcnt == 0 || iovcnt > 1024 || offset < 0 {
+    if offset < 0 {
         return -(EINVAL as isize);
     }
 
-    // 验证 iovec 数组指针
-    if !validate_user_ptr(iov) {
-        return -(EFAULT as isize);
-    }
+    let iovecs = match import_iovec(iov, iovcnt) {
+        Ok(iovecs) => iovecs,
+        Err(e) => return e,
+    };
 
     let task = current_task();
     let file = match task.lock().fd_table.get(fd) {
@@ -453,55 +430,12 @@ pub fn pwritev(fd: usize, iov: *const IoVec, iovcnt: usize, offset: i64) -> isiz
         Err(e) => return e.to_errno(),
     };
 
-    let iovec_array = match copy_user_array(iov, iovcnt, empty_iovec()) {
-        Ok(iovecs) => iovecs,
-        Err(e) => return e,
-    };
-
-    let mut total_written = 0usize;
-    let mut current_offset = offset as usize;
-    for vec in &iovec_array {
-        if vec.iov_base.is_null() || vec.iov_len == 0 {
-            continue;
-        }
-
-        if !validate_user_ptr(vec.iov_base) {
-            return if total_written > 0 {
-                total_written as isize
-            } else {
-                -(EFAULT as isize)
-            };
-        }
-
-        let kernel_buf = match copy_user_bytes(vec.iov_base, vec.iov_len) {
-            Ok(buf) => buf,
-            Err(e) => {
-                return if total_written > 0 {
-                    total_written as isize
-                } else {
-                    e
-                };
-            }
-        };
-        match file.write_at(current_offset, &kernel_buf) {
-            Ok(n) => {
-                total_written += n;
-                current_offset += n;
-                if n < vec.iov_len {
-                    break;
-                }
-            }
-            Err(e) => {
-                return if total_written > 0 {
-                    total_written as isize
-                } else {
-                    e.to_errno()
-                };
-            }
-        }
-    }
-
-    total_written as isize
+    let mut pos = offset as usize;
+    iov_for_each(&iovecs, |vec| {
+        let n = write_segment(vec, |kbuf| file.write_at(pos, kbuf))?;
+        pos += n;
+        Ok(n)
+    })
 }
 
 /// 零拷贝文件传输：从一个文件描述符传输数据到另一个
@@ -630,6 +564,24 @@ pub fn wake_poll_waiters() {
     POLL_WAIT_QUEUE.lock().wake_up_all();
 }
 
+/// 在 poll 等待队列上睡眠，直到被唤醒或到达 `deadline`（时钟周期）
+///
+/// 网络轮询和套接字读写会唤醒 poll 等待者，到期时由 TIMER_QUEUE 唤醒。
+pub fn sleep_poll_waiter(task: &crate::kernel::SharedTask, deadline: usize) {
+    use crate::kernel::timer::TIMER_QUEUE;
+    TIMER_QUEUE.lock().push(deadline, task.clone());
+    POLL_WAIT_QUEUE.lock().sleep(task.clone());
+    crate::kernel::schedule();
+    TIMER_QUEUE.lock().remove_task(task);
+}
+
+/// 是否有任务阻塞在 poll/select 中
+///
+/// 这些任务依赖时钟节拍周期性唤醒重查，有等待者时 idle 不能停掉节拍。
+pub fn has_poll_waiters() -> bool {
+    !POLL_WAIT_QUEUE.lock().is_empty()
+}
+
 fn poll_with_timeout(
     fds: usize,
     nfds: usize,
@@ -686,11 +638,11 @@ fn poll_with_timeout(
                     }
                 };
 
-                if (pollfd.events & POLLIN) != 0 && file_read_ready(&file) {
+                if (pollfd.events & POLLIN) != 0 && file.read_ready() {
                     pollfd.revents |= POLLIN;
                 }
 
-                if (pollfd.events & POLLOUT) != 0 && file_write_ready(&file) {
+                if (pollfd.events & POLLOUT) != 0 && file.write_ready() {
                     pollfd.revents |= POLLOUT;
                 }
 
@@ -935,14 +887,14 @@ fn select_common(
 
             let mut fd_ready = false;
             if check_read
-                && file_read_ready(&file)
+                && file.read_ready()
                 && let Some(ref mut set) = read_set
             {
                 set.set(fd);
                 fd_ready = true;
             }
             if check_write
-                && file_write_ready(&file)
+                && file.write_ready()
                 && let Some(ref mut set) = write_set
             {
                 set.set(fd);
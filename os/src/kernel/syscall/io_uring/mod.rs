@@ -0,0 +1,233 @@
+//! io_uring 相关的系统调用实现
+//!
+//! 实现 Linux io_uring 的一个子集：
+//! - `io_uring_setup` 创建一对共享内存环（见 [`ring`]）并返回实例的 fd，
+//!   用户以 Linux 的偏移 `mmap` 该 fd 即可直接读写 SQ/CQ；
+//! - `io_uring_enter` 一次提交一批 SQE，并可等待若干完成项；
+//! - 支持 NOP、READ、WRITE、ACCEPT。NOP 在提交时完成，其余操作交给
+//!   io-wq 内核线程（见 [`wq`]）执行，完成项由内核线程直接写入 CQ，
+//!   用户轮询 CQ tail 即可取得结果，不需要逐个操作地进入内核。
+//!
+//! 不支持 SQPOLL、固定文件/缓冲区、链接请求等特性，SQE 的 `flags` 非 0 时以
+//! EINVAL 完成。关闭实例的 fd 会取消尚未完成的请求。
+
+mod ring;
+mod wq;
+
+use ring::IoRing;
+pub use wq::{IO_WQ_WORKERS, io_wq_worker};
+
+use alloc::sync::Arc;
+
+use crate::arch::Arch;
+use crate::arch::address::UA;
+use crate::kernel::{current_task, yield_task};
+use crate::mm::memory_space::mapping_area::SharedPages;
+use crate::uapi::errno::{EBADF, EFAULT, EINTR, EINVAL, ENOMEM, EOPNOTSUPP};
+use crate::uapi::io_uring::{
+    IORING_ENTER_GETEVENTS, IORING_FEAT_SINGLE_MMAP, IORING_MAX_CQ_ENTRIES, IORING_MAX_ENTRIES,
+    IORING_SETUP_CLAMP, IORING_SETUP_CQSIZE, IoUringParams,
+};
+use crate::uapi::time::TimeSpec;
+use crate::vfs::{FdFlags, File, FileMode, FsError, InodeMetadata, InodeType};
+
+/// io_uring 实例的文件
+pub struct IoUringFile {
+    ring: Arc<IoRing>,
+}
+
+impl IoUringFile {
+    pub fn ring(&self) -> &Arc<IoRing> {
+        &self.ring
+    }
+}
+
+impl File for IoUringFile {
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    /// CQ 非空时可读，与 Linux 的 io_uring fd 一样可以用 poll 等待完成项
+    fn read_ready(&self) -> bool {
+        self.ring.cq_ready() > 0
+    }
+
+    fn read(&self, _buf: &mut [u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn write(&self, _buf: &[u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn metadata(&self) -> Result<InodeMetadata, FsError> {
+        // 与 Linux 的匿名 inode 一样没有文件类型位
+        Ok(InodeMetadata {
+            inode_no: 0,
+            inode_type: InodeType::File,
+            size: 0,
+            mode: FileMode::from_bits_truncate(0o600),
+            uid: 0,
+            gid: 0,
+            atime: TimeSpec::zero(),
+            mtime: TimeSpec::zero(),
+            ctime: TimeSpec::zero(),
+            nlinks: 1,
+            blocks: 0,
+            rdev: 0,
+        })
+    }
+
+    fn mmap_pages(&self) -> Result<Arc<dyn SharedPages>, FsError> {
+        Ok(self.ring.pages())
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+/// 按 `flags` 把用户请求的条目数规整为 2 的幂
+fn ring_entries(requested: u32, max: u32, flags: u32) -> Result<u32, isize> {
+    if requested == 0 {
+        return Err(-(EINVAL as isize));
+    }
+    let entries = if requested > max {
+        if flags & IORING_SETUP_CLAMP == 0 {
+            return Err(-(EINVAL as isize));
+        }
+        max
+    } else {
+        requested
+    };
+    Ok(entries.next_power_of_two())
+}
+
+/// io_uring_setup - 创建 io_uring 实例
+///
+/// # 语义（与 Linux 对齐的子集）
+/// - 支持 `IORING_SETUP_CQSIZE` 和 `IORING_SETUP_CLAMP`，其余标志返回 EINVAL。
+/// - SQ 条目数上取整为 2 的幂，CQ 默认是 SQ 的两倍。
+/// - 总是报告 `IORING_FEAT_SINGLE_MMAP`；返回的 fd 带 `O_CLOEXEC`。
+pub fn io_uring_setup(entries: u32, params: *mut IoUringParams) -> isize {
+    let mut p = IoUringParams::default();
+    unsafe {
+        if crate::arch::ArchImpl::copy_from_user(
+            UA::from_usize(params as usize),
+            &mut p as *mut IoUringParams as *mut u8,
+            core::mem::size_of::<IoUringParams>(),
+        )
+        .is_err()
+        {
+            return -(EFAULT as isize);
+        }
+    }
+    if p.flags & !(IORING_SETUP_CQSIZE | IORING_SETUP_CLAMP) != 0 || p.resv.iter().any(|&r| r != 0)
+    {
+        return -(EINVAL as isize);
+    }
+
+    let sq_entries = match ring_entries(entries, IORING_MAX_ENTRIES, p.flags) {
+        Ok(n) => n,
+        Err(e) => return e,
+    };
+    let cq_entries = if p.flags & IORING_SETUP_CQSIZE != 0 {
+        match ring_entries(p.cq_entries, IORING_MAX_CQ_ENTRIES, p.flags) {
+            Ok(n) if n >= sq_entries => n,
+            Ok(_) => return -(EINVAL as isize),
+            Err(e) => return e,
+        }
+    } else {
+        2 * sq_entries
+    };
+
+    let Some(ring) = IoRing::new(sq_entries, cq_entries) else {
+        return -(ENOMEM as isize);
+    };
+    p.sq_entries = sq_entries;
+    p.cq_entries = cq_entries;
+    p.features = IORING_FEAT_SINGLE_MMAP;
+    p.sq_off = ring.sq_offsets();
+    p.cq_off = ring.cq_offsets();
+    unsafe {
+        if crate::arch::ArchImpl::copy_to_user(
+            &p as *const IoUringParams as *const u8,
+            UA::from_usize(params as usize),
+            core::mem::size_of::<IoUringParams>(),
+        )
+        .is_err()
+        {
+            return -(EFAULT as isize);
+        }
+    }
+
+    let file = Arc::new(IoUringFile {
+        ring: Arc::new(ring),
+    });
+    match current_task()
+        .lock()
+        .fd_table
+        .alloc_with_flags(file, FdFlags::CLOEXEC)
+    {
+        Ok(fd) => fd as isize,
+        Err(e) => e.to_errno(),
+    }
+}
+
+/// io_uring_enter - 提交 SQE 并等待完成项
+///
+/// # 语义（与 Linux 对齐的子集）
+/// - 从 SQ 取出至多 `to_submit` 项提交，返回提交的项数。
+/// - 带 `IORING_ENTER_GETEVENTS` 时等待 CQ 中至少有 `min_complete` 项；
+///   等待被信号打断时，若已提交过请求则返回提交数，否则返回 EINTR。
+/// - `sig` 暂不支持，等待期间的信号掩码不变。
+pub fn io_uring_enter(
+    fd: u32,
+    to_submit: u32,
+    min_complete: u32,
+    flags: u32,
+    _sig: usize,
+    _sigsz: usize,
+) -> isize {
+    if flags & !IORING_ENTER_GETEVENTS != 0 {
+        return -(EINVAL as isize);
+    }
+    let task = current_task();
+    let file = match task.lock().fd_table.get(fd as usize) {
+        Ok(f) => f,
+        Err(_) => return -(EBADF as isize),
+    };
+    let Some(uring) = file.as_any().downcast_ref::<IoUringFile>() else {
+        return -(EOPNOTSUPP as isize);
+    };
+    let ring = uring.ring().clone();
+    drop(file);
+
+    let submitted = if to_submit > 0 {
+        wq::submit(&ring, &task, to_submit)
+    } else {
+        0
+    };
+
+    if flags & IORING_ENTER_GETEVENTS != 0 {
+        let min = min_complete.min(ring.cq_entries());
+        while ring.cq_ready() < min {
+            if crate::ipc::signal_interrupts_syscall(&task) {
+                return if submitted > 0 {
+                    submitted as isize
+                } else {
+                    -(EINTR as isize)
+                };
+            }
+            if ring.wait_cq(task.clone(), min) {
+                yield_task();
+            }
+        }
+    }
+
+    submitted as isize
+}
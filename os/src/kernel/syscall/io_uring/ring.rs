@@ -0,0 +1,330 @@
+//! 共享内存中的提交/完成队列
+//!
+//! 一个 io_uring 实例占用两段连续物理页，都以 `MAP_SHARED` 方式映射给用户：
+//!
+//! ```text
+//! 环区域（IORING_OFF_SQ_RING / IORING_OFF_CQ_RING）
+//!   0    SQ head / tail / ring_mask / ring_entries / flags / dropped
+//!   64   CQ head / tail / ring_mask / ring_entries / overflow / flags
+//!   128  CQE 数组（cq_entries 项，每项 16 字节）
+//!   ...  SQ 索引数组（sq_entries 个 u32）
+//! SQE 区域（IORING_OFF_SQES）
+//!   0    SQE 数组（sq_entries 项，每项 64 字节）
+//! ```
+//!
+//! SQ tail 和 CQ head 由用户推进，SQ head 和 CQ tail 由内核推进；内核自己的
+//! 游标另存一份，用户改写共享内存中的值不会让内核越界访问。
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::config::PAGE_SIZE;
+use crate::kernel::WaitQueue;
+use crate::mm::address::{PageNum, Ppn, UsizeConvert};
+use crate::mm::frame_allocator::{FrameRangeTracker, alloc_contig_frames};
+use crate::mm::memory_space::mapping_area::SharedPages;
+use crate::sync::SpinLock;
+use crate::uapi::io_uring::{
+    IORING_OFF_CQ_RING, IORING_OFF_SQES, IoCqringOffsets, IoSqringOffsets, IoUringCqe, IoUringSqe,
+};
+
+const SQ_HEAD: usize = 0;
+const SQ_TAIL: usize = 4;
+const SQ_RING_MASK: usize = 8;
+const SQ_RING_ENTRIES: usize = 12;
+const SQ_FLAGS: usize = 16;
+const SQ_DROPPED: usize = 20;
+const CQ_HEAD: usize = 64;
+const CQ_TAIL: usize = 68;
+const CQ_RING_MASK: usize = 72;
+const CQ_RING_ENTRIES: usize = 76;
+const CQ_OVERFLOW: usize = 80;
+const CQ_FLAGS: usize = 84;
+const CQES: usize = 128;
+
+const CQE_SIZE: usize = core::mem::size_of::<IoUringCqe>();
+const SQE_SIZE: usize = core::mem::size_of::<IoUringSqe>();
+
+/// 一段连续物理页及其内核虚拟地址
+#[derive(Debug)]
+struct RingRegion {
+    frames: FrameRangeTracker,
+    base: usize,
+}
+
+impl RingRegion {
+    fn new(bytes: usize) -> Option<Self> {
+        let frames = alloc_contig_frames(bytes.div_ceil(PAGE_SIZE))?;
+        let base = crate::arch::pa_to_va(frames.start_ppn().start_addr()).as_usize();
+        unsafe {
+            core::ptr::write_bytes(base as *mut u8, 0, frames.len() * PAGE_SIZE);
+        }
+        Some(Self { frames, base })
+    }
+
+    fn pages(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn ppn_at(&self, page_idx: usize) -> Option<Ppn> {
+        (page_idx < self.pages())
+            .then(|| Ppn::from_usize(self.frames.start_ppn().as_usize() + page_idx))
+    }
+
+    fn u32_at(&self, off: usize) -> &AtomicU32 {
+        unsafe { &*((self.base + off) as *const AtomicU32) }
+    }
+}
+
+/// 环的物理页，按 Linux 的 mmap 偏移排布
+///
+/// 环区域同时出现在 `IORING_OFF_SQ_RING` 和 `IORING_OFF_CQ_RING` 处，
+/// 其间的页号没有对应的物理页，映射到那里会失败。
+#[derive(Debug)]
+pub struct IoUringPages {
+    ring: RingRegion,
+    sqes: RingRegion,
+}
+
+const CQ_RING_PAGE: usize = IORING_OFF_CQ_RING as usize / PAGE_SIZE;
+const SQES_PAGE: usize = IORING_OFF_SQES as usize / PAGE_SIZE;
+
+impl SharedPages for IoUringPages {
+    fn ppn_at(&self, page_idx: usize) -> Option<Ppn> {
+        if page_idx >= SQES_PAGE {
+            self.sqes.ppn_at(page_idx - SQES_PAGE)
+        } else if page_idx >= CQ_RING_PAGE {
+            self.ring.ppn_at(page_idx - CQ_RING_PAGE)
+        } else {
+            self.ring.ppn_at(page_idx)
+        }
+    }
+
+    fn page_count(&self) -> usize {
+        SQES_PAGE + self.sqes.pages()
+    }
+}
+
+/// 一个 io_uring 实例
+pub struct IoRing {
+    pages: Arc<IoUringPages>,
+    sq_entries: u32,
+    cq_entries: u32,
+    /// SQ 索引数组在环区域中的偏移
+    sq_array: usize,
+    /// 内核的 SQ head，同时串行化提交
+    sq_head: SpinLock<u32>,
+    /// 内核的 CQ tail，同时串行化完成项的写入
+    cq_tail: SpinLock<u32>,
+    /// 等待完成项的 io_uring_enter 调用者
+    cq_wait: SpinLock<WaitQueue>,
+}
+
+impl IoRing {
+    /// 创建条目数为 `sq_entries` / `cq_entries`（均为 2 的幂）的实例
+    pub fn new(sq_entries: u32, cq_entries: u32) -> Option<Self> {
+        let sq_array = CQES + cq_entries as usize * CQE_SIZE;
+        let ring = RingRegion::new(sq_array + sq_entries as usize * 4)?;
+        let sqes = RingRegion::new(sq_entries as usize * SQE_SIZE)?;
+        ring.u32_at(SQ_RING_MASK)
+            .store(sq_entries - 1, Ordering::Relaxed);
+        ring.u32_at(SQ_RING_ENTRIES)
+            .store(sq_entries, Ordering::Relaxed);
+        ring.u32_at(CQ_RING_MASK)
+            .store(cq_entries - 1, Ordering::Relaxed);
+        ring.u32_at(CQ_RING_ENTRIES)
+            .store(cq_entries, Ordering::Relaxed);
+        Some(Self {
+            pages: Arc::new(IoUringPages { ring, sqes }),
+            sq_entries,
+            cq_entries,
+            sq_array,
+            sq_head: SpinLock::new(0),
+            cq_tail: SpinLock::new(0),
+            cq_wait: SpinLock::new(WaitQueue::new()),
+        })
+    }
+
+    pub fn sq_entries(&self) -> u32 {
+        self.sq_entries
+    }
+
+    pub fn cq_entries(&self) -> u32 {
+        self.cq_entries
+    }
+
+    /// 供 mmap 使用的物理页
+    pub fn pages(&self) -> Arc<IoUringPages> {
+        self.pages.clone()
+    }
+
+    /// 返回给用户的 SQ 字段偏移
+    pub fn sq_offsets(&self) -> IoSqringOffsets {
+        IoSqringOffsets {
+            head: SQ_HEAD as u32,
+            tail: SQ_TAIL as u32,
+            ring_mask: SQ_RING_MASK as u32,
+            ring_entries: SQ_RING_ENTRIES as u32,
+            flags: SQ_FLAGS as u32,
+            dropped: SQ_DROPPED as u32,
+            array: self.sq_array as u32,
+            ..Default::default()
+        }
+    }
+
+    /// 返回给用户的 CQ 字段偏移
+    pub fn cq_offsets(&self) -> IoCqringOffsets {
+        IoCqringOffsets {
+            head: CQ_HEAD as u32,
+            tail: CQ_TAIL as u32,
+            ring_mask: CQ_RING_MASK as u32,
+            ring_entries: CQ_RING_ENTRIES as u32,
+            overflow: CQ_OVERFLOW as u32,
+            cqes: CQES as u32,
+            flags: CQ_FLAGS as u32,
+            ..Default::default()
+        }
+    }
+
+    fn ring(&self) -> &RingRegion {
+        &self.pages.ring
+    }
+
+    /// 从 SQ 取出至多 `max` 个提交项，逐个交给 `f`，返回取出的有效项数
+    ///
+    /// 索引越界的项计入 `dropped` 后跳过。
+    pub fn consume_sqes(&self, max: u32, mut f: impl FnMut(IoUringSqe)) -> u32 {
+        let ring = self.ring();
+        let mut head = self.sq_head.lock();
+        let tail = ring.u32_at(SQ_TAIL).load(Ordering::Acquire);
+        let count = tail.wrapping_sub(*head).min(max).min(self.sq_entries);
+        let mut consumed = 0;
+        for _ in 0..count {
+            let slot = (*head & (self.sq_entries - 1)) as usize;
+            *head = head.wrapping_add(1);
+            let idx = ring
+                .u32_at(self.sq_array + slot * 4)
+                .load(Ordering::Relaxed);
+            if idx >= self.sq_entries {
+                ring.u32_at(SQ_DROPPED).fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            let sqe = unsafe {
+                core::ptr::read_volatile(
+                    (self.pages.sqes.base + idx as usize * SQE_SIZE) as *const IoUringSqe,
+                )
+            };
+            f(sqe);
+            consumed += 1;
+        }
+        ring.u32_at(SQ_HEAD).store(*head, Ordering::Release);
+        consumed
+    }
+
+    /// 写入一个完成项并唤醒等待者
+    ///
+    /// CQ 已满时丢弃并增加 `overflow` 计数。
+    pub fn post_cqe(&self, user_data: u64, res: i32) {
+        let ring = self.ring();
+        {
+            let mut tail = self.cq_tail.lock();
+            let head = ring.u32_at(CQ_HEAD).load(Ordering::Acquire);
+            if tail.wrapping_sub(head) >= self.cq_entries {
+                ring.u32_at(CQ_OVERFLOW).fetch_add(1, Ordering::Relaxed);
+            } else {
+                let slot = (*tail & (self.cq_entries - 1)) as usize;
+                let cqe = IoUringCqe {
+                    user_data,
+                    res,
+                    flags: 0,
+                };
+                unsafe {
+                    core::ptr::write_volatile(
+                        (ring.base + CQES + slot * CQE_SIZE) as *mut IoUringCqe,
+                        cqe,
+                    );
+                }
+                *tail = tail.wrapping_add(1);
+                ring.u32_at(CQ_TAIL).store(*tail, Ordering::Release);
+            }
+        }
+        self.cq_wait.lock().wake_up_all();
+        crate::kernel::syscall::io::wake_poll_waiters();
+    }
+
+    /// CQ 中尚未被用户取走的完成项数
+    pub fn cq_ready(&self) -> u32 {
+        let tail = *self.cq_tail.lock();
+        tail.wrapping_sub(self.ring().u32_at(CQ_HEAD).load(Ordering::Acquire))
+            .min(self.cq_entries)
+    }
+
+    /// 睡眠直到 CQ 中至少有 `min` 个完成项；返回 `false` 表示已经满足、没有睡眠
+    pub fn wait_cq(&self, task: crate::kernel::SharedTask, min: u32) -> bool {
+        self.cq_wait
+            .lock()
+            .sleep_if(task, || self.cq_ready() >= min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uapi::io_uring::IORING_OP_NOP;
+    use crate::{kassert, test_case};
+
+    fn push_sqe(ring: &IoRing, tail: u32, idx: u32, user_data: u64) {
+        let r = ring.ring();
+        let slot = (tail & (ring.sq_entries - 1)) as usize;
+        r.u32_at(ring.sq_array + slot * 4)
+            .store(idx, Ordering::Relaxed);
+        if idx < ring.sq_entries {
+            let sqe = IoUringSqe {
+                opcode: IORING_OP_NOP,
+                user_data,
+                ..Default::default()
+            };
+            unsafe {
+                core::ptr::write(
+                    (ring.pages.sqes.base + idx as usize * SQE_SIZE) as *mut IoUringSqe,
+                    sqe,
+                );
+            }
+        }
+        r.u32_at(SQ_TAIL).store(tail + 1, Ordering::Release);
+    }
+
+    test_case!(test_io_uring_rings, {
+        let ring = IoRing::new(4, 8).expect("alloc ring failed");
+        kassert!(ring.ring().u32_at(SQ_RING_MASK).load(Ordering::Relaxed) == 3);
+        kassert!(ring.ring().u32_at(CQ_RING_ENTRIES).load(Ordering::Relaxed) == 8);
+
+        // 环区域在 SQ/CQ 两个偏移处指向同一批页，SQE 数组在 IORING_OFF_SQES 处
+        let pages = ring.pages();
+        kassert!(pages.ppn_at(0) == pages.ppn_at(CQ_RING_PAGE));
+        kassert!(pages.ppn_at(SQES_PAGE).is_some());
+        kassert!(pages.ppn_at(1).is_none());
+        kassert!(pages.page_count() == SQES_PAGE + 1);
+
+        // 越界的索引计入 dropped
+        push_sqe(&ring, 0, 2, 7);
+        push_sqe(&ring, 1, 9, 8);
+        let mut seen = alloc::vec::Vec::new();
+        kassert!(ring.consume_sqes(8, |sqe| seen.push(sqe.user_data)) == 1);
+        kassert!(seen == [7]);
+        kassert!(ring.ring().u32_at(SQ_HEAD).load(Ordering::Relaxed) == 2);
+        kassert!(ring.ring().u32_at(SQ_DROPPED).load(Ordering::Relaxed) == 1);
+
+        // CQ 满后新的完成项计入 overflow
+        for i in 0..9 {
+            ring.post_cqe(i, -(i as i32));
+        }
+        kassert!(ring.cq_ready() == 8);
+        kassert!(ring.ring().u32_at(CQ_OVERFLOW).load(Ordering::Relaxed) == 1);
+        let cqe =
+            unsafe { core::ptr::read((ring.ring().base + CQES + CQE_SIZE) as *const IoUringCqe) };
+        kassert!(cqe.user_data == 1 && cqe.res == -1);
+        ring.ring().u32_at(CQ_HEAD).store(8, Ordering::Release);
+        kassert!(ring.cq_ready() == 0);
+    });
+}
@@ -0,0 +1,321 @@
+//! io-wq：执行 io_uring 请求的内核线程
+//!
+//! `io_uring_enter` 在提交者上下文中解析 fd，把请求连同提交者的 fd 表和地址空间
+//! 放入全局队列。内核线程不切换页表，读写用户缓冲区都经由提交者的 `MemorySpace`
+//! 按页拷贝。
+//!
+//! 文件的 [`File::read_ready`] / [`File::write_ready`] 表明操作会阻塞时请求留在队列中；
+//! 一轮下来没有任何请求完成时，线程在 poll 等待队列上睡眠，由网络事件、新的提交
+//! 或超时唤醒后重试。以 `O_NONBLOCK` 打开的文件不等待，直接以 EAGAIN 完成。
+//!
+//! 请求只持有实例、fd 表和地址空间的弱引用，实例被关闭或提交进程退出后，
+//! 剩余请求被丢弃。
+
+use alloc::collections::VecDeque;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+
+use lazy_static::lazy_static;
+
+use super::ring::IoRing;
+use crate::kernel::syscall::io::{sleep_poll_waiter, wake_poll_waiters};
+use crate::kernel::syscall::{accepted_socket, tcp_try_accept};
+use crate::kernel::{SharedTask, WaitQueue, current_task, yield_task};
+use crate::mm::memory_space::MemorySpace;
+use crate::net::socket::{
+    SOCKADDR_IN_SIZE, SocketFile, SocketHandle, register_socket_fd, write_sockaddr_in_to_buf,
+};
+use crate::sync::SpinLock;
+use crate::uapi::errno::{EBADF, ECANCELED, EFAULT, EINVAL, ENOTSOCK, EOPNOTSUPP};
+use crate::uapi::io_uring::{
+    IORING_OP_ACCEPT, IORING_OP_NOP, IORING_OP_READ, IORING_OP_WRITE, IoUringSqe,
+};
+use crate::uapi::socket::{SOCK_CLOEXEC, SOCK_NONBLOCK};
+use crate::vfs::{FDTable, FdFlags, File, FsError, OpenFlags};
+
+/// io-wq 线程数
+pub const IO_WQ_WORKERS: usize = 2;
+
+/// 阻塞的请求没有被唤醒时的重试间隔（毫秒）
+const IO_WQ_RETRY_MS: usize = 10;
+
+/// 单个读写请求一次传输的最大字节数，超出部分按短读写处理
+const IO_RW_MAX: usize = 1 << 20;
+
+lazy_static! {
+    /// 等待执行的请求
+    static ref IO_WQ: SpinLock<VecDeque<IoWork>> = SpinLock::new(VecDeque::new());
+    /// 队列为空时 io-wq 线程在此等待
+    static ref IO_WQ_WAIT: SpinLock<WaitQueue> = SpinLock::new(WaitQueue::new());
+}
+
+enum IoOp {
+    Read {
+        addr: usize,
+        len: usize,
+        off: u64,
+    },
+    Write {
+        addr: usize,
+        len: usize,
+        off: u64,
+    },
+    Accept {
+        addr: usize,
+        addrlen: usize,
+        flags: i32,
+    },
+}
+
+/// 一个交给 io-wq 的请求
+struct IoWork {
+    ring: Weak<IoRing>,
+    user_data: u64,
+    op: IoOp,
+    file: Arc<dyn File>,
+    fd: usize,
+    tid: usize,
+    fd_table: Weak<FDTable>,
+    space: Weak<SpinLock<MemorySpace>>,
+}
+
+/// 从 `ring` 的 SQ 取出至多 `to_submit` 项提交，返回提交的项数
+pub(super) fn submit(ring: &Arc<IoRing>, task: &SharedTask, to_submit: u32) -> u32 {
+    let (fd_table, space, tid) = {
+        let t = task.lock();
+        (
+            t.fd_table.clone(),
+            t.memory_space
+                .as_ref()
+                .map(Arc::downgrade)
+                .unwrap_or_default(),
+            t.tid as usize,
+        )
+    };
+    let mut works = Vec::new();
+    let submitted = ring.consume_sqes(to_submit, |sqe| {
+        match prepare(ring, &sqe, &fd_table, &space, tid) {
+            Ok(Some(work)) => works.push(work),
+            Ok(None) => ring.post_cqe(sqe.user_data, 0),
+            Err(errno) => ring.post_cqe(sqe.user_data, -errno),
+        }
+    });
+    if !works.is_empty() {
+        IO_WQ.lock().extend(works);
+        IO_WQ_WAIT.lock().wake_up_all();
+        // 正在等待文件就绪的线程也睡在 poll 等待队列上
+        wake_poll_waiters();
+    }
+    submitted
+}
+
+/// 把 SQE 转换为交给 io-wq 的请求；NOP 返回 `Ok(None)`，可立即判定的错误返回 errno
+fn prepare(
+    ring: &Arc<IoRing>,
+    sqe: &IoUringSqe,
+    fd_table: &Arc<FDTable>,
+    space: &Weak<SpinLock<MemorySpace>>,
+    tid: usize,
+) -> Result<Option<IoWork>, i32> {
+    if sqe.flags != 0 {
+        return Err(EINVAL);
+    }
+    let op = match sqe.opcode {
+        IORING_OP_NOP => return Ok(None),
+        IORING_OP_READ => IoOp::Read {
+            addr: sqe.addr as usize,
+            len: sqe.len as usize,
+            off: sqe.off,
+        },
+        IORING_OP_WRITE => IoOp::Write {
+            addr: sqe.addr as usize,
+            len: sqe.len as usize,
+            off: sqe.off,
+        },
+        IORING_OP_ACCEPT => {
+            let flags = sqe.op_flags as i32;
+            if flags & !(SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 {
+                return Err(EINVAL);
+            }
+            IoOp::Accept {
+                addr: sqe.addr as usize,
+                addrlen: sqe.off as usize,
+                flags,
+            }
+        }
+        _ => return Err(EINVAL),
+    };
+    if sqe.fd < 0 {
+        return Err(EBADF);
+    }
+    let file = fd_table.get(sqe.fd as usize).map_err(|_| EBADF)?;
+    Ok(Some(IoWork {
+        ring: Arc::downgrade(ring),
+        user_data: sqe.user_data,
+        op,
+        file,
+        fd: sqe.fd as usize,
+        tid,
+        fd_table: Arc::downgrade(fd_table),
+        space: space.clone(),
+    }))
+}
+
+impl IoWork {
+    /// 尝试执行；操作会阻塞时返回 `None`，否则返回完成项的 `res`
+    fn try_complete(&self) -> Option<i32> {
+        let Some(space) = self.space.upgrade() else {
+            return Some(-ECANCELED);
+        };
+        let blocking = !self.file.flags().contains(OpenFlags::O_NONBLOCK);
+        let res = match self.op {
+            IoOp::Read { addr, len, off } => {
+                if blocking && self.file.readable() && !self.file.read_ready() {
+                    return None;
+                }
+                let mut buf = alloc::vec![0u8; len.min(IO_RW_MAX)];
+                let result = if off == u64::MAX {
+                    self.file.read(&mut buf)
+                } else {
+                    self.file.read_at(off as usize, &mut buf)
+                };
+                match result {
+                    Ok(n) => match space.lock().write_user_bytes_at(addr, &buf[..n]) {
+                        Ok(()) => n as isize,
+                        Err(_) => -(EFAULT as isize),
+                    },
+                    Err(FsError::WouldBlock) if blocking => return None,
+                    Err(e) => e.to_errno(),
+                }
+            }
+            IoOp::Write { addr, len, off } => {
+                if blocking && self.file.writable() && !self.file.write_ready() {
+                    return None;
+                }
+                let mut buf = alloc::vec![0u8; len.min(IO_RW_MAX)];
+                if space.lock().read_user_bytes_at(addr, &mut buf).is_err() {
+                    return Some(-EFAULT);
+                }
+                let result = if off == u64::MAX {
+                    self.file.write(&buf)
+                } else {
+                    self.file.write_at(off as usize, &buf)
+                };
+                match result {
+                    Ok(n) => n as isize,
+                    Err(FsError::WouldBlock) if blocking => return None,
+                    Err(e) => e.to_errno(),
+                }
+            }
+            IoOp::Accept {
+                addr,
+                addrlen,
+                flags,
+            } => match self.accept(&space, addr, addrlen, flags) {
+                Ok(fd) => fd as isize,
+                Err(-11) if blocking => return None, // EAGAIN
+                Err(e) => e,
+            },
+        };
+        Some(res as i32)
+    }
+
+    fn accept(
+        &self,
+        space: &SpinLock<MemorySpace>,
+        addr: usize,
+        addrlen: usize,
+        flags: i32,
+    ) -> Result<usize, isize> {
+        let Some(listener) = self.file.as_any().downcast_ref::<SocketFile>() else {
+            return Err(-(ENOTSOCK as isize));
+        };
+        if !matches!(listener.handle(), SocketHandle::Tcp(_)) {
+            return Err(-(EOPNOTSUPP as isize));
+        }
+        if !listener.is_listener() {
+            return Err(-(EINVAL as isize));
+        }
+        let Some(conn_handle) = tcp_try_accept(&self.file, listener, self.tid, self.fd)? else {
+            return Err(-11); // EAGAIN
+        };
+        let (conn, remote) = accepted_socket(listener, conn_handle)?;
+
+        if addr != 0 && addrlen != 0 {
+            let mut space = space.lock();
+            let mut len = [0u8; 4];
+            space
+                .read_user_bytes_at(addrlen, &mut len)
+                .map_err(|_| -(EFAULT as isize))?;
+            let mut sockaddr = [0u8; SOCKADDR_IN_SIZE];
+            let _ = write_sockaddr_in_to_buf(&mut sockaddr, remote);
+            let n = (u32::from_ne_bytes(len) as usize).min(SOCKADDR_IN_SIZE);
+            space
+                .write_user_bytes_at(addr, &sockaddr[..n])
+                .and_then(|()| {
+                    space.write_user_bytes_at(addrlen, &(SOCKADDR_IN_SIZE as u32).to_ne_bytes())
+                })
+                .map_err(|_| -(EFAULT as isize))?;
+        }
+
+        if flags & SOCK_NONBLOCK != 0 {
+            let _ = conn.set_status_flags(conn.flags() | OpenFlags::O_NONBLOCK);
+        }
+        let fd_flags = if flags & SOCK_CLOEXEC != 0 {
+            FdFlags::CLOEXEC
+        } else {
+            FdFlags::empty()
+        };
+        let fd_table = self.fd_table.upgrade().ok_or(-(ECANCELED as isize))?;
+        let fd = fd_table
+            .alloc_with_flags(conn, fd_flags)
+            .map_err(|e| e.to_errno())?;
+        register_socket_fd(self.tid, fd, SocketHandle::Tcp(conn_handle));
+        Ok(fd)
+    }
+}
+
+/// io-wq 内核线程主函数
+pub fn io_wq_worker() {
+    let task = current_task();
+    loop {
+        let batch = core::mem::take(&mut *IO_WQ.lock());
+        if batch.is_empty() {
+            if IO_WQ_WAIT
+                .lock()
+                .sleep_if(task.clone(), || !IO_WQ.lock().is_empty())
+            {
+                yield_task();
+            }
+            continue;
+        }
+
+        // 与阻塞的 socket 系统调用一样，先推进网络栈再检查就绪状态
+        crate::net::socket::poll_network_and_dispatch();
+
+        let mut pending = VecDeque::new();
+        let mut progressed = false;
+        for work in batch {
+            let Some(ring) = work.ring.upgrade() else {
+                continue;
+            };
+            match work.try_complete() {
+                Some(res) => {
+                    ring.post_cqe(work.user_data, res);
+                    progressed = true;
+                }
+                None => pending.push_back(work),
+            }
+        }
+
+        if pending.is_empty() {
+            continue;
+        }
+        IO_WQ.lock().extend(pending);
+        if !progressed {
+            let deadline =
+                crate::arch::get_time() + IO_WQ_RETRY_MS * crate::arch::clock_freq() / 1000;
+            sleep_poll_waiter(&task, deadline);
+        }
+    }
+}
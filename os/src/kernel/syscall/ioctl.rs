@@ -2,12 +2,23 @@
 //!
 //! ioctl (input/output control) 是一个多功能的系统调用，用于设备特定的控制操作。
 
-use crate::kernel::current_task;
-use crate::uapi::errno::{EBADF, EINVAL, ENOTTY, EOPNOTSUPP};
+use crate::device::net::net_device::NetDeviceError;
+use crate::kernel::{Capabilities, current_task};
+use crate::net::interface::{NETWORK_INTERFACE_MANAGER, NetworkInterface};
+use crate::net::route::{ROUTING_TABLE, Route, RouteError};
+use crate::net::stack::network_stack;
+use crate::uapi::errno::{
+    EADDRNOTAVAIL, EBADF, EEXIST, EFAULT, EINVAL, ENETUNREACH, ENODEV, ENOTTY, EOPNOTSUPP, EPERM,
+    ESRCH,
+};
 use crate::uapi::ioctl::*;
-use crate::util::user_buffer::{read_from_user, write_to_user};
+use crate::uapi::socket::AF_INET;
+use crate::util::user_buffer::{
+    read_from_user, validate_user_ptr, validate_user_ptr_mut, write_to_user,
+};
 use crate::vfs::FsError;
 use crate::{pr_debug, pr_err, pr_warn};
+use smoltcp::wire::{Ipv4Address, Ipv4Cidr};
 
 /// ioctl - 设备特定的输入/输出控制
 ///
@@ -37,6 +48,8 @@ use crate::{pr_debug, pr_err, pr_warn};
 /// - `SIOCGIFCONF` - 获取网络接口列表
 /// - `SIOCGIFADDR` - 获取接口地址
 /// - `SIOCGIFFLAGS` - 获取接口标志
+/// - `SIOCSIFADDR` / `SIOCSIFNETMASK` / `SIOCSIFFLAGS` - 配置接口（需要 CAP_NET_ADMIN）
+/// - `SIOCADDRT` / `SIOCDELRT` - 添加/删除路由（需要 CAP_NET_ADMIN）
 /// - 等等（详见 uapi/ioctl.rs）
 ///
 /// # 注意
@@ -99,8 +112,9 @@ pub fn ioctl(fd: i32, request: u32, arg: usize) -> isize {
         //  网络 Socket 控制
         SIOCGIFCONF => handle_siocgifconf(arg),
         SIOCGIFADDR | SIOCSIFADDR | SIOCGIFFLAGS | SIOCSIFFLAGS | SIOCGIFNETMASK
-        | SIOCSIFNETMASK | SIOCGIFMTU | SIOCSIFMTU | SIOCGIFHWADDR | SIOCSIFHWADDR
-        | SIOCGIFINDEX => handle_ifreq(&file, request, arg),
+        | SIOCSIFNETMASK | SIOCGIFBRDADDR | SIOCSIFBRDADDR | SIOCGIFMTU | SIOCSIFMTU
+        | SIOCGIFHWADDR | SIOCSIFHWADDR | SIOCGIFINDEX => handle_ifreq(&file, request, arg),
+        SIOCADDRT | SIOCDELRT => handle_rtentry(request, arg),
 
         //  设备特定
         // 尝试委托给文件对象的 ioctl 方法
@@ -267,42 +281,304 @@ fn handle_vt_openqry(arg: usize) -> isize {
 //  网络控制处理函数
 
 /// SIOCGIFCONF - 获取网络接口列表
+///
+/// 与 Linux 一致，只列出配置了 IPv4 地址的接口；`ifc_buf` 为空时只返回所需长度。
 fn handle_siocgifconf(arg: usize) -> isize {
     let ifconf_ptr = arg as *mut Ifconf;
     if ifconf_ptr.is_null() {
         return -EINVAL as isize;
     }
+    if !validate_user_ptr_mut(ifconf_ptr) {
+        return -EFAULT as isize;
+    }
 
-    let ifconf = unsafe { read_from_user(ifconf_ptr as *const Ifconf) };
-
-    // TODO: 填充实际的网络接口列表
-    let mut new_ifconf = ifconf;
-    new_ifconf.ifc_len = 0;
-    unsafe { write_to_user(ifconf_ptr, new_ifconf) };
+    let mut ifconf = read_from_user(ifconf_ptr as *const Ifconf);
+    let interfaces = NETWORK_INTERFACE_MANAGER.lock().get_interfaces().to_vec();
+    let entries: alloc::vec::Vec<Ifreq> = interfaces
+        .iter()
+        .filter_map(|iface| {
+            let cidr = iface.primary_ipv4()?;
+            let mut ifreq = ifreq_with_name(iface.name());
+            ifreq.ifr_ifru.ifru_addr = sockaddr_in_bytes(cidr.address());
+            Some(ifreq)
+        })
+        .collect();
+
+    let entry_size = core::mem::size_of::<Ifreq>();
+    if ifconf.ifc_buf == 0 {
+        ifconf.ifc_len = (entries.len() * entry_size) as i32;
+    } else {
+        let capacity = (ifconf.ifc_len.max(0) as usize) / entry_size;
+        let count = entries.len().min(capacity);
+        let buf = ifconf.ifc_buf as *mut Ifreq;
+        if count > 0
+            && (!validate_user_ptr_mut(buf) || !validate_user_ptr_mut(buf.wrapping_add(count - 1)))
+        {
+            return -EFAULT as isize;
+        }
+        for (i, entry) in entries.iter().take(count).enumerate() {
+            let dst = (ifconf.ifc_buf + i * entry_size) as *mut Ifreq;
+            write_to_user(dst, *entry);
+        }
+        ifconf.ifc_len = (count * entry_size) as i32;
+    }
+    write_to_user(ifconf_ptr, ifconf);
 
-    pr_debug!("ioctl: SIOCGIFCONF returned 0 interfaces");
+    pr_debug!("ioctl: SIOCGIFCONF returned {} interfaces", entries.len());
     0
 }
 
+/// 构造只填了接口名的 ifreq
+fn ifreq_with_name(name: &str) -> Ifreq {
+    let mut ifr_name = [0u8; IFNAMSIZ];
+    let len = name.len().min(IFNAMSIZ - 1);
+    ifr_name[..len].copy_from_slice(&name.as_bytes()[..len]);
+    Ifreq {
+        ifr_name,
+        ifr_ifru: IfreqIfru { ifru_map: [0; 24] },
+    }
+}
+
+/// ifreq 中的接口名
+fn ifreq_name(ifreq: &Ifreq) -> &str {
+    let len = ifreq
+        .ifr_name
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(IFNAMSIZ);
+    core::str::from_utf8(&ifreq.ifr_name[..len]).unwrap_or("")
+}
+
+/// 把 IPv4 地址编码为 sockaddr_in
+fn sockaddr_in_bytes(addr: Ipv4Address) -> [u8; 16] {
+    let mut sa = [0u8; 16];
+    sa[0..2].copy_from_slice(&(AF_INET as u16).to_ne_bytes());
+    sa[4..8].copy_from_slice(&addr.octets());
+    sa
+}
+
+/// 从 sockaddr_in 中取出 IPv4 地址，地址族不是 AF_INET 时返回 `None`
+fn sockaddr_in_addr(sa: &[u8; 16]) -> Option<Ipv4Address> {
+    if u16::from_ne_bytes([sa[0], sa[1]]) != AF_INET as u16 {
+        return None;
+    }
+    Some(Ipv4Address::new(sa[4], sa[5], sa[6], sa[7]))
+}
+
+/// 调用者是否具备 CAP_NET_ADMIN
+fn has_net_admin() -> bool {
+    current_task()
+        .lock()
+        .credential
+        .capabilities
+        .has(Capabilities::NET_ADMIN)
+}
+
 /// 处理网络接口请求（ifreq 结构）
 fn handle_ifreq(_file: &alloc::sync::Arc<dyn crate::vfs::File>, request: u32, arg: usize) -> isize {
     let ifreq_ptr = arg as *mut Ifreq;
     if ifreq_ptr.is_null() {
         return -EINVAL as isize;
     }
+    if !validate_user_ptr_mut(ifreq_ptr) {
+        return -EFAULT as isize;
+    }
 
-    let _ifreq = unsafe { read_from_user(ifreq_ptr as *const Ifreq) };
+    let mut ifreq = read_from_user(ifreq_ptr as *const Ifreq);
+    let Some(iface) = NETWORK_INTERFACE_MANAGER
+        .lock()
+        .find_interface_by_name(ifreq_name(&ifreq))
+        .cloned()
+    else {
+        return -ENODEV as isize;
+    };
 
-    // TODO: 实现实际的网络接口操作
     match request {
-        SIOCGIFADDR | SIOCGIFFLAGS | SIOCGIFNETMASK | SIOCGIFMTU | SIOCGIFHWADDR | SIOCGIFINDEX => {
-            pr_debug!("ioctl: network get request {:#x} not implemented", request);
-            -EOPNOTSUPP as isize
+        SIOCGIFADDR | SIOCGIFNETMASK | SIOCGIFBRDADDR => {
+            let Some(cidr) = iface.primary_ipv4() else {
+                return -EADDRNOTAVAIL as isize;
+            };
+            let addr = match request {
+                SIOCGIFADDR => cidr.address(),
+                SIOCGIFNETMASK => cidr.netmask(),
+                _ => cidr.broadcast().unwrap_or(Ipv4Address::UNSPECIFIED),
+            };
+            ifreq.ifr_ifru.ifru_addr = sockaddr_in_bytes(addr);
         }
-        SIOCSIFADDR | SIOCSIFFLAGS | SIOCSIFNETMASK | SIOCSIFMTU | SIOCSIFHWADDR => {
-            pr_debug!("ioctl: network set request {:#x} not implemented", request);
-            -EOPNOTSUPP as isize
+        SIOCGIFFLAGS => ifreq.ifr_ifru.ifru_flags = iface.flags() as i16,
+        SIOCGIFMTU => ifreq.ifr_ifru.ifru_mtu = iface.device().mtu() as i32,
+        SIOCGIFHWADDR => {
+            let family = if iface.is_loopback() {
+                ARPHRD_LOOPBACK
+            } else {
+                ARPHRD_ETHER
+            };
+            let mut hwaddr = [0u8; 16];
+            hwaddr[0..2].copy_from_slice(&family.to_ne_bytes());
+            hwaddr[2..8].copy_from_slice(iface.mac_address().as_bytes());
+            ifreq.ifr_ifru.ifru_hwaddr = hwaddr;
+        }
+        SIOCGIFINDEX => {
+            let index = NETWORK_INTERFACE_MANAGER
+                .lock()
+                .interface_index(iface.name())
+                .unwrap_or(0);
+            ifreq.ifr_ifru.ifru_ivalue = index as i32;
+        }
+        SIOCSIFADDR | SIOCSIFNETMASK | SIOCSIFBRDADDR | SIOCSIFFLAGS => {
+            if !has_net_admin() {
+                return -EPERM as isize;
+            }
+            return handle_ifreq_set(&iface, request, &ifreq);
         }
-        _ => -EINVAL as isize,
+        SIOCSIFMTU => {
+            if !has_net_admin() {
+                return -EPERM as isize;
+            }
+            let mtu = unsafe { ifreq.ifr_ifru.ifru_mtu };
+            return match usize::try_from(mtu).map(|mtu| iface.device().set_mtu(mtu)) {
+                Ok(Ok(())) => 0,
+                Ok(Err(NetDeviceError::NotSupported)) => -EOPNOTSUPP as isize,
+                _ => -EINVAL as isize,
+            };
+        }
+        SIOCSIFHWADDR => {
+            pr_debug!("ioctl: network set request {:#x} not supported", request);
+            return -EOPNOTSUPP as isize;
+        }
+        _ => return -EINVAL as isize,
     }
+
+    write_to_user(ifreq_ptr, ifreq);
+    0
+}
+
+/// 修改接口配置，并同步到协议栈
+fn handle_ifreq_set(iface: &NetworkInterface, request: u32, ifreq: &Ifreq) -> isize {
+    match request {
+        SIOCSIFADDR => {
+            let Some(addr) = sockaddr_in_addr(unsafe { &ifreq.ifr_ifru.ifru_addr }) else {
+                return -EINVAL as isize;
+            };
+            iface.set_primary_ipv4(addr);
+        }
+        SIOCSIFNETMASK => {
+            let Some(mask) = sockaddr_in_addr(unsafe { &ifreq.ifr_ifru.ifru_netmask }) else {
+                return -EINVAL as isize;
+            };
+            let Ok(cidr) = Ipv4Cidr::from_netmask(Ipv4Address::UNSPECIFIED, mask) else {
+                return -EINVAL as isize;
+            };
+            if !iface.set_primary_ipv4_prefix(cidr.prefix_len()) {
+                return -EADDRNOTAVAIL as isize;
+            }
+        }
+        SIOCSIFBRDADDR => {
+            // 广播地址总是由地址和掩码推出，只做校验
+            if sockaddr_in_addr(unsafe { &ifreq.ifr_ifru.ifru_broadaddr }).is_none() {
+                return -EINVAL as isize;
+            }
+            return 0;
+        }
+        SIOCSIFFLAGS => iface.set_flags(unsafe { ifreq.ifr_ifru.ifru_flags } as u16 as u32),
+        _ => return -EINVAL as isize,
+    }
+    pr_debug!(
+        "ioctl: {} reconfigured: addr={:?}, flags={:#x}",
+        iface.name(),
+        iface.primary_ipv4(),
+        iface.flags()
+    );
+    network_stack().sync_interface_config();
+    0
+}
+
+/// SIOCADDRT / SIOCDELRT - 添加或删除路由
+///
+/// 带 RTF_HOST 的路由忽略掩码、按 /32 处理；目的地址不能带有掩码以外的主机位。
+/// 未指定设备时，经网关的路由从网关所在的直连网络推出出口接口。
+fn handle_rtentry(request: u32, arg: usize) -> isize {
+    let rtentry_ptr = arg as *const Rtentry;
+    if rtentry_ptr.is_null() {
+        return -EINVAL as isize;
+    }
+    if !has_net_admin() {
+        return -EPERM as isize;
+    }
+    if !validate_user_ptr(rtentry_ptr) {
+        return -EFAULT as isize;
+    }
+
+    let rtentry = read_from_user(rtentry_ptr);
+    let Some(dst) = sockaddr_in_addr(&rtentry.rt_dst) else {
+        return -EINVAL as isize;
+    };
+    let dest = if rtentry.rt_flags & RTF_HOST != 0 {
+        Ipv4Cidr::new(dst, 32)
+    } else {
+        let mask = sockaddr_in_addr(&rtentry.rt_genmask).unwrap_or(Ipv4Address::UNSPECIFIED);
+        match Ipv4Cidr::from_netmask(dst, mask) {
+            Ok(cidr) if cidr.network().address() == dst => cidr,
+            _ => return -EINVAL as isize,
+        }
+    };
+    let gateway = if rtentry.rt_flags & RTF_GATEWAY != 0 {
+        match sockaddr_in_addr(&rtentry.rt_gateway) {
+            Some(gw) if !gw.is_unspecified() => Some(gw),
+            _ => return -EINVAL as isize,
+        }
+    } else {
+        None
+    };
+    let dev = if rtentry.rt_dev != 0 {
+        match crate::kernel::syscall::network::copy_c_str_from_user(
+            rtentry.rt_dev as *const core::ffi::c_char,
+        ) {
+            Some(dev) => Some(dev),
+            None => return -EFAULT as isize,
+        }
+    } else {
+        None
+    };
+
+    if request == SIOCADDRT {
+        let iface = match (dev, gateway) {
+            (Some(dev), _) => {
+                if NETWORK_INTERFACE_MANAGER
+                    .lock()
+                    .find_interface_by_name(&dev)
+                    .is_none()
+                {
+                    return -ENODEV as isize;
+                }
+                dev
+            }
+            // 网关必须位于某个直连网络中
+            (None, Some(gw)) => match ROUTING_TABLE.lock().lookup(gw) {
+                Some(route) if route.connected => route.iface.clone(),
+                _ => return -ENETUNREACH as isize,
+            },
+            (None, None) => return -ENODEV as isize,
+        };
+        // Linux 中用户态的 metric 比内核记录的大 1
+        let metric = (rtentry.rt_metric.max(1) - 1) as u32;
+        let route = Route::new(dest, gateway, iface, metric);
+        if ROUTING_TABLE.lock().add(route) == Err(RouteError::Exists) {
+            return -EEXIST as isize;
+        }
+    } else if ROUTING_TABLE
+        .lock()
+        .delete(dest, gateway, dev.as_deref())
+        .is_err()
+    {
+        return -ESRCH as isize;
+    }
+    pr_debug!(
+        "ioctl: route {} {} via {:?}",
+        if request == SIOCADDRT { "add" } else { "del" },
+        dest,
+        gateway
+    );
+    network_stack().sync_interface_config();
+    0
 }
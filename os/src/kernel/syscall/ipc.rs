@@ -1,23 +1,38 @@
 //! IPC 相关的系统调用实现
 
 use alloc::sync::Arc;
+use core::ffi::c_char;
 
 use crate::{
-    arch::{ArchImpl, virtual_memory::VirtualMemory},
+    arch::{
+        Arch, ArchImpl,
+        address::UA,
+        timer::{clock_freq, get_time},
+        virtual_memory::VirtualMemory,
+    },
     config::PAGE_SIZE,
-    ipc::{shm_check_access, shm_detach_segment, shm_mark_removed, shm_segment, shmget_segment},
-    kernel::{ShmAttachment, current_memory_space, current_task},
+    ipc::{
+        MqFile, PosixMq, mq_open_queue, mq_unlink_queue, shm_check_access, shm_detach_segment,
+        shm_mark_removed, shm_segment, shmget_segment, signal_interrupts_syscall,
+    },
+    kernel::{
+        SHM_EXIT_NOTIFIER, SharedTask, ShmAttachment, TIMER_QUEUE, current_memory_space,
+        current_task, register_exit_notifier, syscall::util::get_path_safe, task_group_leader,
+        time::realtime_now, yield_task,
+    },
     mm::{
         address::{PageNum, VA, Vpn, VpnRange},
         page_table::UniversalPTEFlag,
     },
     uapi::{
-        errno::{EFAULT, EINVAL, ENOMEM},
+        errno::{EAGAIN, EBADF, EFAULT, EINTR, EINVAL, EMSGSIZE, ENOMEM, ETIMEDOUT},
         ipc::{
             IPC_RMID, IPC_STAT, KeyT, SHM_EXEC, SHM_RDONLY, SHM_REMAP, SHM_RND, SHMLBA, ShmIdDs,
         },
+        mqueue::{MqAttr, SIGEV_NONE, SIGEV_SIGNAL, SigEvent},
+        time::TimeSpec,
     },
-    util::user_buffer::write_to_user,
+    util::user_buffer::{read_from_user, validate_user_ptr, validate_user_ptr_mut, write_to_user},
     vfs::{FdFlags, File, FsError, OpenFlags, PipeFile},
 };
 
@@ -47,6 +62,17 @@ pub fn dup3(oldfd: usize, newfd: usize, flags: u32) -> isize {
     }
 }
 
+/// dup2 - 旧接口，与 dup3 不同，`oldfd == newfd` 时只检查 oldfd 有效并返回它
+pub fn dup2(oldfd: usize, newfd: usize) -> isize {
+    if oldfd == newfd {
+        return match current_task().lock().fd_table.get(oldfd) {
+            Ok(_) => newfd as isize,
+            Err(e) => e.to_errno(),
+        };
+    }
+    dup3(oldfd, newfd, 0)
+}
+
 pub fn pipe2(pipefd: *mut i32, flags: u32) -> isize {
     if pipefd.is_null() {
         return FsError::InvalidArgument.to_errno();
@@ -198,7 +224,7 @@ pub fn shmat(shmid: i32, shmaddr: *const u8, shmflg: i32) -> isize {
         return -EINVAL as isize;
     }
     if space
-        .insert_shared_area(range, flags, segment.clone())
+        .insert_shared_area(range, flags, segment.clone(), 0)
         .is_err()
     {
         if let Some(old) = old_attachment {
@@ -217,6 +243,8 @@ pub fn shmat(shmid: i32, shmaddr: *const u8, shmflg: i32) -> isize {
         len,
         segment,
     });
+    let leader = task_group_leader(&task).unwrap_or(task);
+    register_exit_notifier(&leader, &SHM_EXIT_NOTIFIER);
 
     start as isize
 }
@@ -251,3 +279,292 @@ pub fn shmdt(shmaddr: *const u8) -> isize {
     shm_detach_segment(&attachment.segment, pid);
     0
 }
+
+/// 取出 fd 对应的消息队列描述符；不是消息队列时返回 EBADF
+fn mq_file(mqdes: i32) -> Result<Arc<dyn File>, isize> {
+    let file = current_task()
+        .lock()
+        .fd_table
+        .get(mqdes as usize)
+        .map_err(|e| e.to_errno())?;
+    if !file.as_any().is::<MqFile>() {
+        return Err(-EBADF as isize);
+    }
+    Ok(file)
+}
+
+/// 读取 mq_timedsend/mq_timedreceive 的绝对超时（CLOCK_REALTIME），换算为单调时钟周期
+fn mq_deadline(abs_timeout: *const TimeSpec) -> Result<Option<usize>, isize> {
+    if abs_timeout.is_null() {
+        return Ok(None);
+    }
+    if !validate_user_ptr(abs_timeout) {
+        return Err(-EFAULT as isize);
+    }
+    let ts = read_from_user(abs_timeout);
+    if ts.tv_sec < 0 || ts.tv_nsec < 0 || ts.tv_nsec >= 1_000_000_000 {
+        return Err(-EINVAL as isize);
+    }
+    let rel = ts
+        .into_freq(clock_freq())
+        .saturating_sub(realtime_now().into_freq(clock_freq()));
+    Ok(Some(get_time().saturating_add(rel)))
+}
+
+/// 阻塞等待一轮：睡眠在队列的发送 / 接收等待队列上，直到对侧收发、超时或信号
+///
+/// 超时返回 ETIMEDOUT，被信号打断返回 EINTR；其余情况返回 Ok，由调用者重试收发。
+fn mq_wait(
+    mq: &PosixMq,
+    task: &SharedTask,
+    deadline: Option<usize>,
+    sender: bool,
+) -> Result<(), isize> {
+    if deadline.is_some_and(|d| d <= get_time()) {
+        return Err(-ETIMEDOUT as isize);
+    }
+    if mq.prepare_wait(task, sender) {
+        if let Some(deadline) = deadline {
+            TIMER_QUEUE.lock().push(deadline, task.clone());
+        }
+        yield_task();
+        if deadline.is_some() {
+            TIMER_QUEUE.lock().remove_task(task);
+        }
+        mq.finish_wait(task);
+    }
+    if signal_interrupts_syscall(task) {
+        return Err(-EINTR as isize);
+    }
+    Ok(())
+}
+
+/// mq_open - 打开或创建 POSIX 消息队列
+///
+/// `name` 不含开头的 `/`（由用户态库去掉）。
+pub fn mq_open(name: *const c_char, oflag: i32, mode: u32, attr: *const MqAttr) -> isize {
+    let name = match get_path_safe(name as usize) {
+        Ok(n) => n,
+        Err(e) => return e.to_errno(),
+    };
+    let flags = OpenFlags::from_bits_truncate(oflag as u32);
+    let attr = if flags.contains(OpenFlags::O_CREAT) && !attr.is_null() {
+        if !validate_user_ptr(attr) {
+            return -EFAULT as isize;
+        }
+        Some(read_from_user(attr))
+    } else {
+        None
+    };
+
+    let task = current_task();
+    let mode = task.lock().fs.lock().apply_umask(mode);
+    let queue = match mq_open_queue(&name, flags, mode, attr.as_ref()) {
+        Ok(q) => q,
+        Err(errno) => return -errno as isize,
+    };
+
+    let file_flags = flags & (OpenFlags::O_ACCMODE | OpenFlags::O_NONBLOCK);
+    let file = Arc::new(MqFile::new(queue, file_flags)) as Arc<dyn File>;
+    let fd_table = task.lock().fd_table.clone();
+    match fd_table.alloc_with_flags(file, FdFlags::from_open_flags(flags)) {
+        Ok(fd) => fd as isize,
+        Err(e) => e.to_errno(),
+    }
+}
+
+/// mq_unlink - 删除消息队列名
+pub fn mq_unlink(name: *const c_char) -> isize {
+    let name = match get_path_safe(name as usize) {
+        Ok(n) => n,
+        Err(e) => return e.to_errno(),
+    };
+    match mq_unlink_queue(&name) {
+        Ok(()) => 0,
+        Err(errno) => -errno as isize,
+    }
+}
+
+/// mq_timedsend - 发送消息，队列满时阻塞到 `abs_timeout`
+pub fn mq_timedsend(
+    mqdes: i32,
+    msg_ptr: *const u8,
+    msg_len: usize,
+    msg_prio: u32,
+    abs_timeout: *const TimeSpec,
+) -> isize {
+    let file = match mq_file(mqdes) {
+        Ok(f) => f,
+        Err(e) => return e,
+    };
+    if !file.writable() {
+        return -EBADF as isize;
+    }
+    let mq = file.as_any().downcast_ref::<MqFile>().unwrap();
+
+    let mut data = alloc::vec![0u8; msg_len.min(mq.queue().msgsize() + 1)];
+    unsafe {
+        if ArchImpl::copy_from_user(
+            UA::from_usize(msg_ptr as usize),
+            data.as_mut_ptr(),
+            data.len(),
+        )
+        .is_err()
+        {
+            return -EFAULT as isize;
+        }
+    }
+    if msg_len > data.len() {
+        return -EMSGSIZE as isize;
+    }
+    let deadline = match mq_deadline(abs_timeout) {
+        Ok(d) => d,
+        Err(e) => return e,
+    };
+
+    let task = current_task();
+    loop {
+        match mq.queue().try_send(&data, msg_prio) {
+            Ok(()) => return 0,
+            Err(EAGAIN) if !mq.flags().contains(OpenFlags::O_NONBLOCK) => {
+                if let Err(e) = mq_wait(mq.queue(), &task, deadline, true) {
+                    return e;
+                }
+            }
+            Err(errno) => return -errno as isize,
+        }
+    }
+}
+
+/// mq_timedreceive - 接收优先级最高的消息，队列空时阻塞到 `abs_timeout`
+pub fn mq_timedreceive(
+    mqdes: i32,
+    msg_ptr: *mut u8,
+    msg_len: usize,
+    msg_prio: *mut u32,
+    abs_timeout: *const TimeSpec,
+) -> isize {
+    let file = match mq_file(mqdes) {
+        Ok(f) => f,
+        Err(e) => return e,
+    };
+    if !file.readable() {
+        return -EBADF as isize;
+    }
+    let mq = file.as_any().downcast_ref::<MqFile>().unwrap();
+    // 取出消息前检查，避免消息出队后才发现优先级写不回去
+    if !msg_prio.is_null() && !validate_user_ptr_mut(msg_prio) {
+        return -EFAULT as isize;
+    }
+    let deadline = match mq_deadline(abs_timeout) {
+        Ok(d) => d,
+        Err(e) => return e,
+    };
+
+    let task = current_task();
+    let mut waiting = false;
+    let result = loop {
+        match mq.queue().try_receive(msg_len) {
+            Ok(msg) => break Ok(msg),
+            Err(EAGAIN) if !mq.flags().contains(OpenFlags::O_NONBLOCK) => {
+                if !waiting {
+                    mq.queue().set_receiver_waiting(true);
+                    waiting = true;
+                }
+                if let Err(e) = mq_wait(mq.queue(), &task, deadline, false) {
+                    break Err(e);
+                }
+            }
+            Err(errno) => break Err(-errno as isize),
+        }
+    };
+    if waiting {
+        mq.queue().set_receiver_waiting(false);
+    }
+
+    let (data, prio) = match result {
+        Ok(msg) => msg,
+        Err(e) => return e,
+    };
+    unsafe {
+        if ArchImpl::copy_to_user(data.as_ptr(), UA::from_usize(msg_ptr as usize), data.len())
+            .is_err()
+        {
+            return -EFAULT as isize;
+        }
+    }
+    if !msg_prio.is_null() {
+        write_to_user(msg_prio, prio);
+    }
+    data.len() as isize
+}
+
+/// mq_notify - 登记或撤销消息到达通知
+///
+/// 支持 `SIGEV_SIGNAL` 和 `SIGEV_NONE`；`SIGEV_THREAD` 由用户态库实现，这里返回 EINVAL。
+pub fn mq_notify(mqdes: i32, sevp: *const SigEvent) -> isize {
+    let file = match mq_file(mqdes) {
+        Ok(f) => f,
+        Err(e) => return e,
+    };
+    let queue = file.as_any().downcast_ref::<MqFile>().unwrap().queue();
+    let task = current_task();
+
+    if sevp.is_null() {
+        queue.unregister_notify(task.lock().pid);
+        return 0;
+    }
+
+    if !validate_user_ptr(sevp) {
+        return -EFAULT as isize;
+    }
+    let sev = read_from_user(sevp);
+    let signo = match sev.sigev_notify {
+        SIGEV_NONE => None,
+        SIGEV_SIGNAL => Some(sev.sigev_signo as usize),
+        _ => return -EINVAL as isize,
+    };
+    match queue.register_notify(&task, signo) {
+        Ok(()) => 0,
+        Err(errno) => -errno as isize,
+    }
+}
+
+/// mq_getsetattr - 读取队列属性，并可修改描述符的 O_NONBLOCK
+pub fn mq_getsetattr(mqdes: i32, newattr: *const MqAttr, oldattr: *mut MqAttr) -> isize {
+    let file = match mq_file(mqdes) {
+        Ok(f) => f,
+        Err(e) => return e,
+    };
+    let mq = file.as_any().downcast_ref::<MqFile>().unwrap();
+    if (!newattr.is_null() && !validate_user_ptr(newattr))
+        || (!oldattr.is_null() && !validate_user_ptr_mut(oldattr))
+    {
+        return -EFAULT as isize;
+    }
+
+    let nonblock = OpenFlags::O_NONBLOCK.bits() as core::ffi::c_long;
+    let old = MqAttr {
+        mq_flags: mq.flags().bits() as core::ffi::c_long & nonblock,
+        mq_maxmsg: mq.queue().maxmsg() as _,
+        mq_msgsize: mq.queue().msgsize() as _,
+        mq_curmsgs: mq.queue().curmsgs() as _,
+        ..MqAttr::default()
+    };
+
+    if !newattr.is_null() {
+        let new = read_from_user(newattr);
+        if new.mq_flags & !nonblock != 0 {
+            return -EINVAL as isize;
+        }
+        let mut flags = mq.flags();
+        flags.set(OpenFlags::O_NONBLOCK, new.mq_flags & nonblock != 0);
+        if let Err(e) = mq.set_status_flags(flags) {
+            return e.to_errno();
+        }
+    }
+    if !oldattr.is_null() {
+        write_to_user(oldattr, old);
+    }
+    0
+}
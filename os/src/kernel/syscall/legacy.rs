@@ -0,0 +1,309 @@
+//! 旧系统调用兼容层
+//!
+//! asm-generic 早期为没有 `*at` 和带标志版本的调用保留了 1024 起的旧调用号
+//! （`__ARCH_WANT_SYSCALL_NO_AT`、`__ARCH_WANT_SYSCALL_NO_FLAGS`、
+//! `__ARCH_WANT_SYSCALL_DEPRECATED`），一些旧的静态链接程序和 libc 的回退路径
+//! 仍会使用。这些调用号不在 [`numbers`](super::numbers) 的系统调用表中，
+//! `dispatch_syscall` 在表中找不到时交给本模块：
+//!
+//! - 能直接改写为新调用的（`open` → `openat(AT_FDCWD, ..)`、`fork` → `clone(SIGCHLD)` 等）
+//!   改写参数后按新调用号重新分发；
+//! - 语义与新调用不同的（`dup2`、`poll`）直接调用内核实现。
+//!
+//! 启用哪些旧调用由内核参数 `legacy_syscalls=` 配置：`all`（默认）、`none`，
+//! 或逗号分隔的调用名列表。未启用的旧调用与其它未知调用一样返回 `ENOSYS`。
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::numbers::{
+    self, SYS_CLONE, SYS_FACCESSAT, SYS_FCHMODAT, SYS_FCHOWNAT, SYS_FSTATAT, SYS_GETPGID,
+    SYS_LINKAT, SYS_MKDIRAT, SYS_OPENAT, SYS_PIPE2, SYS_READLINKAT, SYS_RENAMEAT2, SYS_SYMLINKAT,
+    SYS_UNLINKAT,
+};
+use super::syscall_frame::SyscallFrame;
+use super::{dup2, poll};
+use crate::uapi::{
+    fcntl::OpenFlags,
+    fs::{AT_FDCWD, AtFlags},
+    signal::NUM_SIGCHLD,
+};
+
+/// 旧调用号的起点
+const LEGACY_BASE: usize = 1024;
+
+/// 旧调用的处理方式
+#[derive(Clone, Copy)]
+enum LegacyAction {
+    /// 改写参数后按新的调用号分发
+    Forward(usize, fn(&[usize; 6]) -> [usize; 6]),
+    /// 直接调用内核实现，返回值写回用户态
+    Call(fn(&[usize; 6]) -> isize),
+}
+
+/// 旧调用表项
+#[derive(Clone, Copy)]
+pub struct LegacySyscall {
+    /// 旧调用号
+    pub nr: usize,
+    /// Linux 中的系统调用名
+    pub name: &'static str,
+    action: LegacyAction,
+}
+
+const fn forward(
+    nr: usize,
+    name: &'static str,
+    target: usize,
+    args: fn(&[usize; 6]) -> [usize; 6],
+) -> LegacySyscall {
+    LegacySyscall {
+        nr,
+        name,
+        action: LegacyAction::Forward(target, args),
+    }
+}
+
+const fn call(nr: usize, name: &'static str, handler: fn(&[usize; 6]) -> isize) -> LegacySyscall {
+    LegacySyscall {
+        nr,
+        name,
+        action: LegacyAction::Call(handler),
+    }
+}
+
+/// `AT_FDCWD` 作为寄存器值
+const CWD: usize = AT_FDCWD as isize as usize;
+const NOFOLLOW: usize = AtFlags::SYMLINK_NOFOLLOW.bits() as usize;
+const REMOVEDIR: usize = AtFlags::REMOVEDIR.bits() as usize;
+const CREAT_FLAGS: usize =
+    (OpenFlags::O_CREAT.bits() | OpenFlags::O_WRONLY.bits() | OpenFlags::O_TRUNC.bits()) as usize;
+
+/// 支持的旧调用，调用号与 asm-generic 一致
+pub static LEGACY_SYSCALLS: &[LegacySyscall] = &[
+    forward(1024, "open", SYS_OPENAT, |a| [CWD, a[0], a[1], a[2], 0, 0]),
+    forward(1025, "link", SYS_LINKAT, |a| [CWD, a[0], CWD, a[1], 0, 0]),
+    forward(1026, "unlink", SYS_UNLINKAT, |a| [CWD, a[0], 0, 0, 0, 0]),
+    forward(1028, "chmod", SYS_FCHMODAT, |a| [CWD, a[0], a[1], 0, 0, 0]),
+    forward(1029, "chown", SYS_FCHOWNAT, |a| {
+        [CWD, a[0], a[1], a[2], 0, 0]
+    }),
+    forward(1030, "mkdir", SYS_MKDIRAT, |a| [CWD, a[0], a[1], 0, 0, 0]),
+    forward(1031, "rmdir", SYS_UNLINKAT, |a| {
+        [CWD, a[0], REMOVEDIR, 0, 0, 0]
+    }),
+    forward(1032, "lchown", SYS_FCHOWNAT, |a| {
+        [CWD, a[0], a[1], a[2], NOFOLLOW, 0]
+    }),
+    forward(1033, "access", SYS_FACCESSAT, |a| {
+        [CWD, a[0], a[1], 0, 0, 0]
+    }),
+    forward(1034, "rename", SYS_RENAMEAT2, |a| {
+        [CWD, a[0], CWD, a[1], 0, 0]
+    }),
+    forward(1035, "readlink", SYS_READLINKAT, |a| {
+        [CWD, a[0], a[1], a[2], 0, 0]
+    }),
+    forward(1036, "symlink", SYS_SYMLINKAT, |a| {
+        [a[0], CWD, a[1], 0, 0, 0]
+    }),
+    forward(1038, "stat", SYS_FSTATAT, |a| [CWD, a[0], a[1], 0, 0, 0]),
+    forward(1039, "lstat", SYS_FSTATAT, |a| {
+        [CWD, a[0], a[1], NOFOLLOW, 0, 0]
+    }),
+    forward(1040, "pipe", SYS_PIPE2, |a| [a[0], 0, 0, 0, 0, 0]),
+    call(1041, "dup2", |a| dup2(a[0], a[1])),
+    forward(1060, "getpgrp", SYS_GETPGID, |_| [0; 6]),
+    forward(1064, "creat", SYS_OPENAT, |a| {
+        [CWD, a[0], CREAT_FLAGS, a[1], 0, 0]
+    }),
+    call(1068, "poll", |a| poll(a[0], a[1], a[2] as i32)),
+    forward(1079, "fork", SYS_CLONE, |_| [NUM_SIGCHLD, 0, 0, 0, 0, 0]),
+];
+
+/// 启用的旧调用，第 `nr - LEGACY_BASE` 位对应调用号 `nr`
+static ENABLED: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// 按调用号查找旧调用
+pub fn lookup(nr: usize) -> Option<&'static LegacySyscall> {
+    LEGACY_SYSCALLS.iter().find(|legacy| legacy.nr == nr)
+}
+
+/// 旧调用号在 Linux 中的名字
+pub fn name_of(nr: usize) -> Option<&'static str> {
+    lookup(nr).map(|legacy| legacy.name)
+}
+
+/// 调用号在启用位图中的位
+fn bit(nr: usize) -> u64 {
+    1 << (nr - LEGACY_BASE)
+}
+
+/// 旧调用是否启用，`nr` 必须是 [`LEGACY_SYSCALLS`] 中的调用号
+fn is_enabled(nr: usize) -> bool {
+    ENABLED.load(Ordering::Relaxed) & bit(nr) != 0
+}
+
+/// 解析 `legacy_syscalls=` 的值，返回启用位图；不认识的调用名放入 `unknown`
+fn parse_enabled<'a>(value: &'a str, unknown: &mut alloc::vec::Vec<&'a str>) -> u64 {
+    match value {
+        "all" => return u64::MAX,
+        "none" | "" => return 0,
+        _ => {}
+    }
+    let mut mask = 0;
+    for name in value.split(',').filter(|name| !name.is_empty()) {
+        match LEGACY_SYSCALLS.iter().find(|legacy| legacy.name == name) {
+            Some(legacy) => mask |= bit(legacy.nr),
+            None => unknown.push(name),
+        }
+    }
+    mask
+}
+
+/// 按内核参数 `legacy_syscalls=` 设置启用的旧调用，多次给出时以最后一次为准
+pub fn init() {
+    let cmdline = crate::device::CMDLINE.read();
+    let Some(value) = cmdline
+        .split_ascii_whitespace()
+        .filter_map(|arg| arg.strip_prefix("legacy_syscalls="))
+        .next_back()
+    else {
+        return;
+    };
+    let mut unknown = alloc::vec::Vec::new();
+    ENABLED.store(parse_enabled(value, &mut unknown), Ordering::Relaxed);
+    for name in unknown {
+        crate::pr_warn!("legacy_syscalls: unknown syscall '{}' ignored", name);
+    }
+}
+
+/// 以改写后的调用号和参数呈现给处理函数的系统调用帧
+///
+/// 返回值、重启等操作转发给原始帧；信号导致的重启使用原始帧中的旧调用号，
+/// 重新进入时再次改写。
+struct ForwardFrame<'a, F: SyscallFrame> {
+    inner: &'a mut F,
+    nr: usize,
+    args: [usize; 6],
+}
+
+impl<F: SyscallFrame> SyscallFrame for ForwardFrame<'_, F> {
+    fn syscall_id(&self) -> usize {
+        self.nr
+    }
+    fn arg0(&self) -> usize {
+        self.args[0]
+    }
+    fn arg1(&self) -> usize {
+        self.args[1]
+    }
+    fn arg2(&self) -> usize {
+        self.args[2]
+    }
+    fn arg3(&self) -> usize {
+        self.args[3]
+    }
+    fn arg4(&self) -> usize {
+        self.args[4]
+    }
+    fn arg5(&self) -> usize {
+        self.args[5]
+    }
+    fn set_ret(&mut self, val: usize) {
+        self.inner.set_ret(val);
+    }
+    fn enter_syscall(&mut self) {
+        self.inner.enter_syscall();
+    }
+    fn leave_syscall(&mut self) -> Option<usize> {
+        self.inner.leave_syscall()
+    }
+    fn restart(&mut self, syscall_id: usize) {
+        self.inner.restart(syscall_id);
+    }
+}
+
+/// 处理旧调用 `nr`，未知或未启用的调用号返回 `false`
+pub fn invoke(nr: usize, frame: &mut impl SyscallFrame) -> bool {
+    let Some(legacy) = lookup(nr).filter(|legacy| is_enabled(legacy.nr)) else {
+        return false;
+    };
+    let args = [
+        frame.arg0(),
+        frame.arg1(),
+        frame.arg2(),
+        frame.arg3(),
+        frame.arg4(),
+        frame.arg5(),
+    ];
+    match legacy.action {
+        LegacyAction::Forward(target, rewrite) => {
+            let mut forward = ForwardFrame {
+                inner: frame,
+                nr: target,
+                args: rewrite(&args),
+            };
+            numbers::invoke(target, &mut forward)
+        }
+        LegacyAction::Call(handler) => {
+            frame.set_ret(handler(&args) as usize);
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, kernel::syscall::linux_abi, test_case};
+
+    fn rewrite(name: &str, args: [usize; 6]) -> (usize, [usize; 6]) {
+        let legacy = LEGACY_SYSCALLS.iter().find(|l| l.name == name).unwrap();
+        match legacy.action {
+            LegacyAction::Forward(target, f) => (target, f(&args)),
+            LegacyAction::Call(_) => panic!("{} is not forwarded", name),
+        }
+    }
+
+    test_case!(test_legacy_table, {
+        for legacy in LEGACY_SYSCALLS {
+            kassert!(legacy.nr >= LEGACY_BASE && legacy.nr < LEGACY_BASE + 64);
+            // 不与现有调用号冲突，改写目标必须在系统调用表中
+            kassert!(linux_abi::name_of(legacy.nr).is_none());
+            kassert!(numbers::describe(legacy.nr).is_none());
+            if let LegacyAction::Forward(target, _) = legacy.action {
+                kassert!(numbers::describe(target).is_some());
+            }
+            kassert!(LEGACY_SYSCALLS.iter().filter(|l| l.nr == legacy.nr).count() == 1);
+        }
+        kassert!(name_of(1024) == Some("open"));
+        kassert!(name_of(1027).is_none());
+    });
+
+    test_case!(test_legacy_rewrite, {
+        let (nr, args) = rewrite("open", [0x1000, 2, 0o644, 9, 9, 9]);
+        kassert!(nr == SYS_OPENAT);
+        kassert!(args == [CWD, 0x1000, 2, 0o644, 0, 0]);
+
+        let (nr, args) = rewrite("rmdir", [0x2000, 0, 0, 0, 0, 0]);
+        kassert!(nr == SYS_UNLINKAT && args[2] == 0x200);
+
+        let (nr, args) = rewrite("symlink", [0x1000, 0x2000, 0, 0, 0, 0]);
+        kassert!(nr == SYS_SYMLINKAT && args[..3] == [0x1000, CWD, 0x2000]);
+
+        let (nr, args) = rewrite("fork", [7; 6]);
+        kassert!(nr == SYS_CLONE && args == [17, 0, 0, 0, 0, 0]);
+
+        let (_, args) = rewrite("creat", [0x1000, 0o600, 0, 0, 0, 0]);
+        kassert!(args[2] == 0o1101 && args[3] == 0o600);
+    });
+
+    test_case!(test_legacy_parse_enabled, {
+        let mut unknown = alloc::vec::Vec::new();
+        kassert!(parse_enabled("all", &mut unknown) == u64::MAX);
+        kassert!(parse_enabled("none", &mut unknown) == 0);
+        let mask = parse_enabled("open,dup2,,bogus", &mut unknown);
+        kassert!(mask == bit(1024) | bit(1041));
+        kassert!(unknown == ["bogus"]);
+    });
+}
@@ -1,15 +1,21 @@
-use core::ffi::{c_int, c_void};
+use alloc::sync::Arc;
+use core::ffi::{c_char, c_int, c_void};
 
 use crate::config::PAGE_SIZE;
 use crate::kernel::{current_memory_space, current_task};
 use crate::mm::address::{PageNum, VA, Vpn, VpnRange};
-use crate::mm::memory_space::MmapFile;
-use crate::mm::memory_space::mapping_area::AreaType;
-use crate::mm::page_table::UniversalPTEFlag;
-use crate::uapi::errno::{EACCES, EAGAIN, EBADF, EEXIST, EFAULT, EINVAL, EIO, ENOMEM};
+use crate::mm::memory_space::mapping_area::{AnonSharedPages, AreaType, SharedPages};
+use crate::mm::memory_space::{MmapFile, mmap_min_addr};
+use crate::mm::page_table::{PagingError, UniversalPTEFlag};
+use crate::mm::swap;
+use crate::mm::wx_policy::{USER_PROT_MASK, user_pte_flags, wx_permitted};
+use crate::uapi::errno::{
+    EACCES, EAGAIN, EBADF, EEXIST, EFAULT, EINVAL, EIO, ENODEV, ENOENT, ENOMEM, ENOSYS, EPERM,
+};
 use crate::uapi::mm::{MAP_FAILED, MapFlags, ProtFlags};
 use crate::uapi::resource::ResourceId;
 use crate::util::user_buffer::{validate_user_ptr_mut, write_to_user};
+use crate::vfs::{FsError, MmapDesc, MmapPolicy};
 use crate::{pr_err, pr_warn};
 
 /// brk - 改变数据段的结束地址（堆顶）
@@ -66,8 +72,8 @@ pub fn brk(new_brk: usize) -> isize {
 /// - `len`: 映射的长度（字节）
 /// - `prot`: 内存保护标志（PROT_READ | PROT_WRITE | PROT_EXEC）
 /// - `flags`: 映射标志（MAP_SHARED | MAP_PRIVATE | MAP_ANONYMOUS 等）
-/// - `fd`: 文件描述符（目前仅支持匿名映射，必须为 -1）
-/// - `offset`: 文件内偏移量（目前仅支持匿名映射，必须为 0）
+/// - `fd`: 文件描述符（匿名映射时必须为 -1）
+/// - `offset`: 文件内偏移量（匿名映射时必须为 0，文件映射时必须页对齐）
 ///
 /// # 返回值
 /// - 成功: 返回映射区域的起始地址
@@ -76,12 +82,16 @@ pub fn brk(new_brk: usize) -> isize {
 /// # 支持的特性
 /// - ✅ MAP_ANONYMOUS - 匿名映射
 /// - ✅ MAP_PRIVATE / MAP_SHARED - 私有/共享映射
-/// - ✅ MAP_FIXED - 固定地址映射（覆盖现有）
+///   （共享匿名映射和 tmpfs/memfd 文件的共享映射直接映射物理页，跨 fork 共享）
+/// - ✅ 文件映射：映射方式由 [`File::mmap`](crate::vfs::File::mmap) 决定
+///   （`/dev/zero` 为匿名内存，设备文件直接映射设备内存，不支持映射的文件返回 ENODEV）
+/// - ✅ MAP_FIXED - 固定地址映射（覆盖现有，栈、堆等关键区域除外，返回 EEXIST）
 /// - ✅ MAP_FIXED_NOREPLACE - 固定地址映射（不覆盖）
 /// - ✅ 地址 hint 机制
+/// - ✅ mmap_min_addr（`/proc/sys/vm/mmap_min_addr`，固定映射低于它返回 EPERM）
+/// - ✅ W^X 策略（`/proc/sys/vm/wx_policy`）
 ///
 /// # 当前限制
-/// - ❌ 文件映射（需要 VFS 支持）
 /// - ❌ MAP_POPULATE（预分配，当前默认立即分配）
 /// - ❌ MAP_NORESERVE（延迟分配，当前默认立即分配）
 /// - ❌ 大页 (MAP_HUGETLB)
@@ -122,6 +132,40 @@ pub fn mmap(addr: *mut c_void, len: usize, prot: i32, flags: i32, fd: i32, offse
         return -EINVAL as isize;
     }
 
+    // mmap_min_addr：固定映射不能低于该地址，普通 hint 被抬高到该地址
+    let min_addr = mmap_min_addr();
+    if map_flags.intersects(MapFlags::FIXED | MapFlags::FIXED_NOREPLACE) && hint < min_addr {
+        pr_warn!(
+            "mmap: fixed mapping at {:#x} below mmap_min_addr {:#x}",
+            hint,
+            min_addr
+        );
+        return -EPERM as isize;
+    }
+    let hint = if hint != 0 && hint < min_addr {
+        min_addr
+    } else {
+        hint
+    };
+
+    // W^X 策略
+    let requested = user_pte_flags(
+        prot_flags.contains(ProtFlags::READ),
+        prot_flags.contains(ProtFlags::WRITE),
+        prot_flags.contains(ProtFlags::EXEC),
+    );
+    if !wx_permitted(requested, "mmap", hint) {
+        return -EACCES as isize;
+    }
+
+    // 之后 mprotect 可以授予的最大权限
+    let mut max_prot = USER_PROT_MASK;
+
+    // 直接映射的物理页：(物理页集合, 起始页下标)
+    let mut shared_pages = None;
+    // 不关联文件内容的映射
+    let mut anonymous = map_flags.contains(MapFlags::ANONYMOUS);
+
     // 创建 MmapFile（如果是文件映射）
     let mmap_file = if !map_flags.contains(MapFlags::ANONYMOUS) {
         // 文件映射：验证文件描述符和偏移量
@@ -152,14 +196,43 @@ pub fn mmap(addr: *mut c_void, len: usize, prot: i32, flags: i32, fd: i32, offse
             pr_err!("mmap: file not writable but PROT_WRITE + MAP_SHARED requested");
             return -EACCES as isize;
         }
+        // 只读打开的文件做共享映射，之后也不能通过 mprotect 改为可写
+        if map_flags.contains(MapFlags::SHARED) && !file.writable() {
+            max_prot.remove(UniversalPTEFlag::WRITEABLE);
+        }
 
-        Some(MmapFile {
-            file,
+        // 由文件决定映射方式：直接映射物理页、匿名内存或按内容拷贝
+        let desc = MmapDesc {
             offset: offset as usize,
             len,
             prot: prot_flags,
             flags: map_flags,
-        })
+        };
+        match file.mmap(&desc) {
+            Ok(MmapPolicy::Direct { pages, first_page }) => {
+                shared_pages = Some((pages, first_page));
+                None
+            }
+            Ok(MmapPolicy::Anonymous) => {
+                anonymous = true;
+                None
+            }
+            Ok(MmapPolicy::CopyIn) => Some(MmapFile {
+                file,
+                offset: offset as usize,
+                len,
+                prot: prot_flags,
+                flags: map_flags,
+            }),
+            Err(FsError::NotSupported) => {
+                pr_err!("mmap: file does not support mapping");
+                return -ENODEV as isize;
+            }
+            Err(e) => {
+                pr_err!("mmap: file refused mapping: {:?}", e);
+                return e.to_errno();
+            }
+        }
     } else {
         // 匿名映射验证
         if fd != -1 {
@@ -173,12 +246,35 @@ pub fn mmap(addr: *mut c_void, len: usize, prot: i32, flags: i32, fd: i32, offse
         None
     };
 
+    // 共享匿名映射（含 /dev/zero）：物理页由 AnonSharedPages 持有，fork 后父子进程共享
+    if anonymous && map_flags.contains(MapFlags::SHARED) {
+        match AnonSharedPages::new(len.div_ceil(PAGE_SIZE)) {
+            Some(pages) => shared_pages = Some((pages as Arc<dyn SharedPages>, 0)),
+            None => {
+                pr_err!("mmap: out of memory for shared anonymous mapping");
+                return -ENOMEM as isize;
+            }
+        }
+    }
+
     // 确定映射地址
     let memory_space = current_memory_space();
     let mut space = memory_space.lock();
 
     let start_addr = if map_flags.contains(MapFlags::FIXED) {
-        // MAP_FIXED: 强制使用指定地址，覆盖现有映射
+        // MAP_FIXED: 强制使用指定地址，覆盖现有映射，但不替换栈、堆等关键区域
+        let range = VpnRange::new(
+            Vpn::from_addr_floor(VA::from_usize(hint)),
+            Vpn::from_addr_ceil(VA::from_usize(hint + len)),
+        );
+        if space.overlaps_critical(range) {
+            pr_warn!(
+                "mmap: MAP_FIXED at {:#x} (len {:#x}) would replace a critical mapping",
+                hint,
+                len
+            );
+            return -EEXIST as isize;
+        }
         match space.munmap(VA::from_usize(hint), len) {
             Ok(_) => hint,
             Err(e) => {
@@ -221,13 +317,7 @@ pub fn mmap(addr: *mut c_void, len: usize, prot: i32, flags: i32, fd: i32, offse
             // hint != 0: 尝试使用 hint，失败则内核选择
             let aligned_hint = hint & !(PAGE_SIZE - 1);
 
-            let start_vpn = Vpn::from_addr_floor(VA::from_usize(aligned_hint));
-            let end_vpn = Vpn::from_addr_ceil(VA::from_usize(aligned_hint + len));
-            let range = VpnRange::new(start_vpn, end_vpn);
-
-            let hint_available = !space.areas().iter().any(|a| a.vpn_range().overlaps(&range));
-
-            if hint_available {
+            if space.hint_fits(aligned_hint, len) {
                 aligned_hint
             } else {
                 // hint 不可用，内核选择
@@ -266,14 +356,22 @@ pub fn mmap(addr: *mut c_void, len: usize, prot: i32, flags: i32, fd: i32, offse
     if prot_flags.contains(ProtFlags::EXEC) {
         pte_flags |= UniversalPTEFlag::EXECUTABLE;
     }
+    if shared_pages
+        .as_ref()
+        .is_some_and(|(pages, _)| pages.is_device_memory())
+    {
+        pte_flags |= UniversalPTEFlag::DEVICE;
+    }
 
     // 创建映射
     let start_vpn = Vpn::from_addr_floor(VA::from_usize(start_addr));
     let end_vpn = Vpn::from_addr_ceil(VA::from_usize(start_addr + len));
     let vpn_range = VpnRange::new(start_vpn, end_vpn);
 
-    // 插入映射区域（PROT_NONE 用 Reserved 占位，不建立页表映射）
-    let insert_result = if wants_mapping {
+    // 插入映射区域（共享物理页用 Shared 直接映射，PROT_NONE 用 Reserved 占位，不建立页表映射）
+    let insert_result = if wants_mapping && let Some((pages, first_page)) = shared_pages {
+        space.insert_shared_area(vpn_range, pte_flags, pages, first_page)
+    } else if wants_mapping {
         space.insert_framed_area(vpn_range, AreaType::UserMmap, pte_flags, None, mmap_file)
     } else {
         space.insert_reserved_area(vpn_range, AreaType::UserMmap, pte_flags, mmap_file)
@@ -290,6 +388,9 @@ pub fn mmap(addr: *mut c_void, len: usize, prot: i32, flags: i32, fd: i32, offse
         );
         return MAP_FAILED;
     }
+    if let Some(area) = space.areas_mut().last_mut() {
+        area.set_max_permission(max_prot);
+    }
 
     // 如果是文件映射，立即加载数据
     if let Some(area) = space.areas_mut().last_mut()
@@ -370,6 +471,7 @@ pub fn munmap(addr: *mut c_void, len: usize) -> isize {
 /// - 地址必须页对齐，否则返回 EINVAL
 /// - 范围必须完全在现有映射区域内，否则返回 ENOMEM
 /// - 只能修改通过 mmap 或 brk 创建的用户空间映射
+/// - 超出映射的最大权限（如只读文件的共享映射改为可写），或被 W^X 策略拒绝时返回 EACCES
 ///
 /// # 支持的特性
 /// - ✅ PROT_NONE - 不可访问
@@ -412,12 +514,17 @@ pub fn mprotect(addr: *mut c_void, len: usize, prot: i32) -> isize {
     // PROT_NONE: 不添加任何权限标志（只保留 VALID 和 USER_ACCESSIBLE）
     // 注意：RISC-V 中，如果没有 R/W/X 权限，访问会触发页面故障
 
+    if !wx_permitted(pte_flags, "mprotect", start) {
+        return -EACCES as isize;
+    }
+
     // 获取内存空间并执行权限修改
     let memory_space = current_memory_space();
     let mut space = memory_space.lock();
 
     match space.mprotect(VA::from_usize(start), len, pte_flags) {
         Ok(()) => 0,
+        Err(PagingError::PermissionDenied) => -EACCES as isize,
         Err(e) => {
             pr_err!(
                 "mprotect failed: {:?}, addr=0x{:x}, len=0x{:x}, prot=0x{:x}",
@@ -469,8 +576,9 @@ fn memlock_limit_allows(len: usize) -> bool {
 
 /// mlock - lock a user address range in memory.
 ///
-/// CCYOS does not swap user pages out, so the lock operation is a Linux-compatible no-op after
-/// validating the range and RLIMIT_MEMLOCK. This still matters for userland feature probing.
+/// Locked ranges are not tracked, so the lock operation is a Linux-compatible no-op after
+/// validating the range and RLIMIT_MEMLOCK; the pages may still be swapped out under pressure.
+/// This still matters for userland feature probing.
 pub fn mlock(addr: *const c_void, len: usize) -> isize {
     if len == 0 {
         return 0;
@@ -622,3 +730,83 @@ pub fn get_mempolicy(
 
     0
 }
+
+/// swapon/swapoff 需要 `CAP_SYS_ADMIN`
+fn has_sys_admin() -> bool {
+    current_task()
+        .lock()
+        .credential
+        .capabilities
+        .has(crate::kernel::Capabilities::SYS_ADMIN)
+}
+
+/// swapon - 启用交换文件
+///
+/// 文件需由 `mkswap` 初始化，且所在文件系统支持交换（目前只有 ext4）。
+/// 同一时刻只能启用一个交换区。`flags`（优先级、discard）被忽略。
+/// 未开启 `swap` feature 时返回 `ENOSYS`。
+pub fn swapon(path: *const c_char, _flags: i32) -> isize {
+    use crate::kernel::syscall::fs::AT_FDCWD;
+    use crate::kernel::syscall::util::{get_path_safe, resolve_at_path};
+
+    if !crate::config::CONFIG_SWAP {
+        return -(ENOSYS as isize);
+    }
+
+    if !has_sys_admin() {
+        return -(EPERM as isize);
+    }
+    let path = match get_path_safe(path as usize) {
+        Ok(p) => p,
+        Err(e) => return e.to_errno(),
+    };
+    let dentry = match resolve_at_path(AT_FDCWD, &path) {
+        Ok(Some(d)) => d,
+        Ok(None) => return -ENOENT as isize,
+        Err(e) => return e.to_errno(),
+    };
+    let backing = match dentry.inode.swap_backing() {
+        Ok(b) => b,
+        Err(FsError::NotSupported) => return -EINVAL as isize,
+        Err(e) => return e.to_errno(),
+    };
+
+    let full_path = dentry.full_path();
+    match swap::swapon(full_path.clone(), backing) {
+        Ok(pages) => {
+            crate::pr_info!(
+                "[Swap] Adding {}k swap on {}",
+                pages * PAGE_SIZE / 1024,
+                full_path
+            );
+            0
+        }
+        Err(e) => e.to_errno(),
+    }
+}
+
+/// swapoff - 停用交换文件
+///
+/// 所有被换出的页先换回内存；内存不足时失败并返回 `ENOMEM`，交换区保持启用。
+pub fn swapoff(path: *const c_char) -> isize {
+    use crate::kernel::syscall::fs::AT_FDCWD;
+    use crate::kernel::syscall::util::{get_path_safe, resolve_at_path};
+
+    if !has_sys_admin() {
+        return -(EPERM as isize);
+    }
+    let path = match get_path_safe(path as usize) {
+        Ok(p) => p,
+        Err(e) => return e.to_errno(),
+    };
+    let dentry = match resolve_at_path(AT_FDCWD, &path) {
+        Ok(Some(d)) => d,
+        Ok(None) => return -ENOENT as isize,
+        Err(e) => return e.to_errno(),
+    };
+    match swap::swapoff(&dentry.full_path()) {
+        Ok(()) => 0,
+        Err(FsError::NoMemory) => -ENOMEM as isize,
+        Err(e) => e.to_errno(),
+    }
+}
@@ -5,11 +5,15 @@
 #![allow(dead_code)]
 mod cred;
 pub mod dispatch;
+pub mod enosys;
 mod fcntl;
 mod fs;
 pub mod io;
+mod io_uring;
 mod ioctl;
 mod ipc;
+pub mod legacy;
+pub mod linux_abi;
 mod mm;
 mod network;
 pub mod numbers;
@@ -24,15 +28,17 @@ use core::ffi::{c_char, c_int, c_uint, c_ulong, c_void};
 use crate::{
     impl_syscall,
     uapi::{
+        cred::{CapUserData, CapUserHeader},
         fs::LinuxStatFs,
         futex::RobustListHead,
         iovec::IoVec,
         ipc::{KeyT, ShmIdDs},
+        mqueue::{MqAttr, SigEvent},
         resource::{Rlimit, Rusage},
         sched::SchedParam,
         signal::{SigInfoT, SignalAction},
         sysinfo::SysInfo,
-        time::{Itimerval, TimeSpec, Tms, timeval, timezone},
+        time::{Itimerval, TimeSpec, Timex, Tms, timeval, timezone},
         types::{SigSetT, SizeT, StackT},
         uts_namespace::UtsNamespace,
     },
@@ -42,6 +48,7 @@ use cred::*;
 use fcntl::*;
 use fs::*;
 use io::*;
+use io_uring::*;
 use ioctl::*;
 use ipc::*;
 use mm::*;
@@ -50,12 +57,56 @@ use signal::*;
 use sys::*;
 use task::*;
 
+pub use io_uring::{IO_WQ_WORKERS, io_wq_worker};
+
 // 系统调用实现注册
-// 分类顺序与 arch/riscv/syscall/syscall_number.rs 保持一致
+// 分类顺序与 numbers.rs 中的系统调用表保持一致
 
 // 文件系统/目录操作 (Filesystem/Directory Operations)
 impl_syscall!(sys_getcwd, getcwd, (*mut u8, usize));
 
+// 扩展属性 (Extended Attributes)
+impl_syscall!(
+    sys_setxattr,
+    setxattr,
+    (*const c_char, *const c_char, *const u8, usize, i32)
+);
+impl_syscall!(
+    sys_lsetxattr,
+    lsetxattr,
+    (*const c_char, *const c_char, *const u8, usize, i32)
+);
+impl_syscall!(
+    sys_fsetxattr,
+    fsetxattr,
+    (usize, *const c_char, *const u8, usize, i32)
+);
+impl_syscall!(
+    sys_getxattr,
+    getxattr,
+    (*const c_char, *const c_char, *mut u8, usize)
+);
+impl_syscall!(
+    sys_lgetxattr,
+    lgetxattr,
+    (*const c_char, *const c_char, *mut u8, usize)
+);
+impl_syscall!(
+    sys_fgetxattr,
+    fgetxattr,
+    (usize, *const c_char, *mut u8, usize)
+);
+impl_syscall!(sys_listxattr, listxattr, (*const c_char, *mut u8, usize));
+impl_syscall!(sys_llistxattr, llistxattr, (*const c_char, *mut u8, usize));
+impl_syscall!(sys_flistxattr, flistxattr, (usize, *mut u8, usize));
+impl_syscall!(sys_removexattr, removexattr, (*const c_char, *const c_char));
+impl_syscall!(
+    sys_lremovexattr,
+    lremovexattr,
+    (*const c_char, *const c_char)
+);
+impl_syscall!(sys_fremovexattr, fremovexattr, (usize, *const c_char));
+
 // Epoll & Duplication
 impl_syscall!(sys_dup, dup, (usize));
 impl_syscall!(sys_dup3, dup3, (usize, usize, u32));
@@ -83,6 +134,7 @@ impl_syscall!(sys_statfs, statfs, (*const c_char, *mut LinuxStatFs));
 // 文件大小/权限/所有权 (File Size/Permissions/Ownership)
 impl_syscall!(sys_faccessat, faccessat, (i32, *const c_char, i32, u32));
 impl_syscall!(sys_chdir, chdir, (*const c_char));
+impl_syscall!(sys_fchdir, fchdir, (usize));
 impl_syscall!(sys_fchmodat, fchmodat_legacy, (i32, *const c_char, u32));
 impl_syscall!(sys_fchownat, fchownat, (i32, *const c_char, u32, u32, u32));
 
@@ -169,6 +221,7 @@ impl_syscall!(
 impl_syscall!(sys_clock_settime, clock_settime, (c_int, *const TimeSpec));
 impl_syscall!(sys_clock_gettime, clock_gettime, (c_int, *mut TimeSpec));
 impl_syscall!(sys_clock_getres, clock_getres, (c_int, *mut TimeSpec));
+impl_syscall!(sys_clock_adjtime, clock_adjtime, (c_int, *mut Timex));
 impl_syscall!(
     sys_clock_nanosleep,
     clock_nanosleep,
@@ -197,9 +250,12 @@ impl_syscall!(
     (c_int, usize, *mut u8)
 );
 impl_syscall!(sys_sched_yield, sched_yield, ());
+impl_syscall!(sys_ioprio_set, ioprio_set, (c_int, c_int, c_int));
+impl_syscall!(sys_ioprio_get, ioprio_get, (c_int, c_int));
 impl_syscall!(sys_syslog, syslog, (i32, *mut u8, i32));
 
 // 信号 (Signals)
+impl_syscall!(sys_restart_syscall, restart_syscall, ());
 impl_syscall!(sys_kill, kill, (c_int, c_int));
 impl_syscall!(sys_tkill, tkill, (c_int, c_int));
 impl_syscall!(sys_tgkill, tgkill, (c_int, c_int, c_int));
@@ -221,6 +277,11 @@ impl_syscall!(
     rt_sigtimedwait,
     (*const SigSetT, *mut SigInfoT, *const TimeSpec, c_uint)
 );
+impl_syscall!(
+    sys_rt_sigqueueinfo,
+    rt_sigqueueinfo,
+    (c_int, c_int, *const SigInfoT)
+);
 impl_syscall!(sys_rt_sigreturn, rt_sigreturn, noreturn, ());
 
 // 进程属性 (Process Attributes)
@@ -231,6 +292,10 @@ impl_syscall!(sys_setresuid, setresuid, (u32, u32, u32));
 impl_syscall!(sys_getresuid, getresuid, (*mut u32, *mut u32, *mut u32));
 impl_syscall!(sys_setresgid, setresgid, (u32, u32, u32));
 impl_syscall!(sys_getresgid, getresgid, (*mut u32, *mut u32, *mut u32));
+impl_syscall!(sys_getgroups, getgroups, (i32, *mut u32));
+impl_syscall!(sys_setgroups, setgroups, (i32, *const u32));
+impl_syscall!(sys_capget, capget, (*mut CapUserHeader, *mut CapUserData));
+impl_syscall!(sys_capset, capset, (*mut CapUserHeader, *const CapUserData));
 impl_syscall!(sys_times, times, (*mut Tms));
 impl_syscall!(sys_setsid, setsid, ());
 impl_syscall!(sys_setpgid, set_pgid, (c_int, c_int));
@@ -242,11 +307,22 @@ impl_syscall!(sys_getrlimit, getrlimit, (c_int, *mut Rlimit));
 impl_syscall!(sys_getrusage, getrusage, (c_int, *mut Rusage));
 impl_syscall!(sys_setrlimit, setrlimit, (c_int, *const Rlimit));
 impl_syscall!(sys_umask, umask, (u32));
+impl_syscall!(
+    sys_prctl,
+    prctl,
+    (c_int, c_ulong, c_ulong, c_ulong, c_ulong)
+);
 impl_syscall!(
     sys_gettimeofday,
     gettimeofday,
     (*mut timeval, *mut timezone)
 );
+impl_syscall!(
+    sys_settimeofday,
+    settimeofday,
+    (*const timeval, *const timezone)
+);
+impl_syscall!(sys_adjtimex, adjtimex, (*mut Timex));
 impl_syscall!(sys_getpid, get_pid, ());
 impl_syscall!(sys_getppid, get_ppid, ());
 impl_syscall!(sys_getpgid, get_pgid, (c_int));
@@ -257,6 +333,30 @@ impl_syscall!(sys_getegid, getegid, ());
 impl_syscall!(sys_gettid, gettid, ());
 impl_syscall!(sys_sysinfo, sysinfo, (*mut SysInfo));
 
+// POSIX 消息队列
+impl_syscall!(
+    sys_mq_open,
+    mq_open,
+    (*const c_char, i32, u32, *const MqAttr)
+);
+impl_syscall!(sys_mq_unlink, mq_unlink, (*const c_char));
+impl_syscall!(
+    sys_mq_timedsend,
+    mq_timedsend,
+    (i32, *const u8, usize, u32, *const TimeSpec)
+);
+impl_syscall!(
+    sys_mq_timedreceive,
+    mq_timedreceive,
+    (i32, *mut u8, usize, *mut u32, *const TimeSpec)
+);
+impl_syscall!(sys_mq_notify, mq_notify, (i32, *const SigEvent));
+impl_syscall!(
+    sys_mq_getsetattr,
+    mq_getsetattr,
+    (i32, *const MqAttr, *mut MqAttr)
+);
+
 // System V IPC
 impl_syscall!(sys_shmget, shmget, (KeyT, usize, i32));
 impl_syscall!(sys_shmctl, shmctl, (i32, i32, *mut ShmIdDs));
@@ -323,6 +423,8 @@ impl_syscall!(
 impl_syscall!(sys_brk, brk, (usize));
 impl_syscall!(sys_mmap, mmap, (*mut c_void, usize, i32, i32, i32, i64));
 impl_syscall!(sys_munmap, munmap, (*mut c_void, usize));
+impl_syscall!(sys_swapon, swapon, (*const c_char, i32));
+impl_syscall!(sys_swapoff, swapoff, (*const c_char));
 impl_syscall!(sys_mprotect, mprotect, (*mut c_void, usize, i32));
 impl_syscall!(sys_mlock, mlock, (*const c_void, usize));
 impl_syscall!(sys_munlock, munlock, (*const c_void, usize));
@@ -361,6 +463,19 @@ impl_syscall!(
 
 // 随机数与内存文件
 impl_syscall!(sys_getrandom, getrandom, (*mut c_void, SizeT, c_uint));
+impl_syscall!(sys_memfd_create, memfd_create, (*const c_char, u32));
+
+// io_uring
+impl_syscall!(
+    sys_io_uring_setup,
+    io_uring_setup,
+    (u32, *mut crate::uapi::io_uring::IoUringParams)
+);
+impl_syscall!(
+    sys_io_uring_enter,
+    io_uring_enter,
+    (u32, u32, u32, u32, usize, usize)
+);
 
 // 获取网络接口地址列表 (非标准系统调用)
 impl_syscall!(sys_getifaddrs, getifaddrs, (*mut *mut u8));
@@ -371,3 +486,74 @@ impl_syscall!(sys_send, send, (i32, *const u8, usize, i32));
 impl_syscall!(sys_recv, recv, (i32, *mut u8, usize, i32));
 impl_syscall!(sys_seteuid, seteuid, (u32));
 impl_syscall!(sys_setegid, setegid, (u32));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arch::timer::get_time;
+    use crate::kernel::{TaskStruct, current_cpu, current_task};
+    use crate::sync::{PreemptGuard, lock_acquisitions};
+    use crate::uapi::time::clock_id::{CLOCK_BOOTTIME, CLOCK_MONOTONIC, CLOCK_REALTIME};
+    use crate::{kassert, test_case};
+
+    const ITERS: usize = 1000;
+
+    /// 关中断连续调用 `f` [`ITERS`] 次，返回平均每次的计时器周期数和期间获取锁的次数
+    fn bench(mut f: impl FnMut()) -> (usize, usize) {
+        let _guard = PreemptGuard::new();
+        let flags = crate::arch::disable_interrupts();
+        let locks = lock_acquisitions();
+        let start = get_time();
+        for _ in 0..ITERS {
+            f();
+        }
+        let cycles = (get_time() - start) / ITERS;
+        let locks = lock_acquisitions() - locks;
+        crate::arch::restore_interrupt_state(flags);
+        (cycles, locks)
+    }
+
+    // getpid/gettid/clock_gettime 的快速路径不获取锁（也就不会进入堆分配器）；
+    // 各路径的周期数只输出作对比，不设阈值
+    test_case!(test_fast_syscall_bench, {
+        let task = TaskStruct::new_dummy_task(2433).into_shared();
+        let (prev_task, prev_ids) = {
+            let _guard = PreemptGuard::new();
+            let cpu = current_cpu();
+            let prev_ids = (cpu.current_pid, cpu.current_tid);
+            let prev_task = cpu.current_task.take();
+            cpu.set_current_task(task);
+            (prev_task, prev_ids)
+        };
+
+        let (pid_cycles, pid_locks) = bench(|| kassert!(get_pid() == 2433));
+        let (tid_cycles, tid_locks) = bench(|| kassert!(gettid() == 2433));
+        let (clock_cycles, clock_locks) = bench(|| {
+            for clk_id in [CLOCK_REALTIME, CLOCK_MONOTONIC, CLOCK_BOOTTIME] {
+                kassert!(read_clock(clk_id).is_ok());
+            }
+        });
+        let (locked_cycles, locked_locks) = bench(|| kassert!(current_task().lock().pid == 2433));
+        crate::println!(
+            "[bench] cycles/call: getpid {}, gettid {}, clock_gettime x3 {}, locked getpid {}",
+            pid_cycles,
+            tid_cycles,
+            clock_cycles,
+            locked_cycles
+        );
+
+        kassert!(pid_locks == 0);
+        kassert!(tid_locks == 0);
+        kassert!(clock_locks == 0);
+        // 对照组确实被计数
+        kassert!(locked_locks == ITERS);
+
+        // 恢复原来的当前任务，不影响后续测试
+        {
+            let _guard = PreemptGuard::new();
+            let cpu = current_cpu();
+            cpu.current_task = prev_task;
+            (cpu.current_pid, cpu.current_tid) = prev_ids;
+        }
+    });
+}
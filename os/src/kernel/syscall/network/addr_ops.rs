@@ -106,7 +106,7 @@ pub fn sendto(
                     Err(e) => return e.to_errno(),
                 }
             }
-            SocketHandle::Tcp(_) => handle,
+            SocketHandle::Tcp(_) | SocketHandle::Icmp(_) | SocketHandle::Raw(_) => handle,
         };
         socket_sendto(handle, &kernel_buf, endpoint)
     };
@@ -144,6 +144,7 @@ pub fn recvfrom(
     addrlen: *mut u32,
 ) -> isize {
     pr_debug!("recvfrom: sockfd={}, len={}", sockfd, len);
+    let start = crate::arch::get_time();
     loop {
         let task = current_task();
         let file = match task.lock().fd_table.get(sockfd as usize) {
@@ -210,11 +211,13 @@ pub fn recvfrom(
                             .flags()
                             .contains(crate::uapi::fcntl::OpenFlags::O_NONBLOCK)
                     {
-                        drop(file);
-                        crate::net::socket::poll_network_and_dispatch();
-                        crate::kernel::yield_task();
-                        if crate::ipc::signal_interrupts_syscall(&task) {
-                            return -(crate::uapi::errno::EINTR as isize);
+                        if let Err(e) = crate::net::socket::wait_socket_io(
+                            file,
+                            &task,
+                            SockTimeout::Recv,
+                            start,
+                        ) {
+                            return e;
                         }
                         continue;
                     }
@@ -356,8 +359,8 @@ pub fn getpeername(sockfd: i32, addr: *mut u8, addrlen: *mut u32) -> isize {
 
     let remote_endpoint = match handle {
         SocketHandle::Tcp(_) => network_stack().socket_remote_endpoint(handle),
-        SocketHandle::Udp(_) => {
-            // UDP doesn't have a peer, use stored endpoint
+        SocketHandle::Udp(_) | SocketHandle::Icmp(_) | SocketHandle::Raw(_) => {
+            // Datagram sockets don't have a peer, use stored endpoint
             let file = match task.lock().fd_table.get(sockfd as usize) {
                 Ok(f) => f,
                 Err(_) => return -9, // EBADF
@@ -111,9 +111,12 @@ pub fn connect(sockfd: i32, addr: *const u8, addrlen: u32) -> isize {
                 return e.to_errno();
             }
 
+            let socket_file = file.as_any().downcast_ref::<SocketFile>();
+
             // For blocking sockets, wait until connection is established
             if !is_nonblock {
                 pr_debug!("connect: handle={:?}, entering wait loop", h);
+                let start = crate::arch::get_time();
                 loop {
                     // Poll until all loopback packets are processed
                     if is_loopback {
@@ -134,9 +137,21 @@ pub fn connect(sockfd: i32, addr: *const u8, addrlen: u32) -> isize {
                         return -111; // ECONNREFUSED
                     }
 
-                    crate::kernel::yield_task();
-                    if crate::ipc::signal_interrupts_syscall(&task) {
-                        return -(crate::uapi::errno::EINTR as isize);
+                    // On SO_SNDTIMEO expiry or a signal the handshake goes on in the
+                    // background and its outcome is reported through SO_ERROR.
+                    if let Err(e) = crate::net::socket::wait_socket_io(
+                        file.clone(),
+                        &task,
+                        SockTimeout::Send,
+                        start,
+                    ) {
+                        if let Some(sf) = socket_file {
+                            sf.set_connecting(true);
+                        }
+                        if e == -(crate::uapi::errno::EAGAIN as isize) {
+                            return -(crate::uapi::errno::EINPROGRESS as isize);
+                        }
+                        return e;
                     }
                 }
             }
@@ -149,6 +164,9 @@ pub fn connect(sockfd: i32, addr: *const u8, addrlen: u32) -> isize {
             );
 
             if is_nonblock {
+                if let Some(sf) = socket_file {
+                    sf.set_connecting(true);
+                }
                 return -115; // EINPROGRESS
             }
         }
@@ -160,6 +178,10 @@ pub fn connect(sockfd: i32, addr: *const u8, addrlen: u32) -> isize {
             }
             pr_debug!("connect: sockfd={} UDP -> success", sockfd);
         }
+        SocketHandle::Icmp(_) | SocketHandle::Raw(_) => {
+            // Only the default destination is recorded.
+            pr_debug!("connect: sockfd={} ICMP/RAW -> success", sockfd);
+        }
     }
 
     pr_debug!("connect: sockfd={} -> success", sockfd);
@@ -168,6 +190,7 @@ pub fn connect(sockfd: i32, addr: *const u8, addrlen: u32) -> isize {
 
 /// 发送数据
 pub fn send(sockfd: i32, buf: *const u8, len: usize, _flags: i32) -> isize {
+    let start = crate::arch::get_time();
     loop {
         let task = current_task();
         let (_tid, file) = {
@@ -217,11 +240,13 @@ pub fn send(sockfd: i32, buf: *const u8, len: usize, _flags: i32) -> isize {
                     if let Some(socket_file) = file.as_any().downcast_ref::<SocketFile>()
                         && !socket_file.flags().contains(OpenFlags::O_NONBLOCK)
                     {
-                        drop(file);
-                        crate::net::socket::poll_network_and_dispatch();
-                        crate::kernel::yield_task();
-                        if crate::ipc::signal_interrupts_syscall(&task) {
-                            return -(crate::uapi::errno::EINTR as isize);
+                        if let Err(e) = crate::net::socket::wait_socket_io(
+                            file,
+                            &task,
+                            SockTimeout::Send,
+                            start,
+                        ) {
+                            return e;
                         }
                         continue;
                     }
@@ -242,6 +267,7 @@ pub fn send(sockfd: i32, buf: *const u8, len: usize, _flags: i32) -> isize {
 
 /// 接收数据
 pub fn recv(sockfd: i32, buf: *mut u8, len: usize, _flags: i32) -> isize {
+    let start = crate::arch::get_time();
     loop {
         let task = current_task();
         let (_tid, file) = {
@@ -301,11 +327,13 @@ pub fn recv(sockfd: i32, buf: *mut u8, len: usize, _flags: i32) -> isize {
                     if let Some(socket_file) = file.as_any().downcast_ref::<SocketFile>()
                         && !socket_file.flags().contains(OpenFlags::O_NONBLOCK)
                     {
-                        drop(file);
-                        crate::net::socket::poll_network_and_dispatch();
-                        crate::kernel::yield_task();
-                        if crate::ipc::signal_interrupts_syscall(&task) {
-                            return -(crate::uapi::errno::EINTR as isize);
+                        if let Err(e) = crate::net::socket::wait_socket_io(
+                            file,
+                            &task,
+                            SockTimeout::Recv,
+                            start,
+                        ) {
+                            return e;
                         }
                         continue;
                     }
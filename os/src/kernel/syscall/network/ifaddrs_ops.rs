@@ -186,7 +186,7 @@ pub fn getifaddrs(ifap: *mut *mut u8) -> isize {
             // ifa_name: usize (offset 8)
             ifa_slice[8..16].copy_from_slice(&name_ua.to_ne_bytes());
             // ifa_flags: u32 (offset 16)
-            ifa_slice[16..20].copy_from_slice(&get_interface_flags(iface.name()).to_ne_bytes());
+            ifa_slice[16..20].copy_from_slice(&iface.flags().to_ne_bytes());
             // ifa_addr: usize (offset 24)
             ifa_slice[24..32].copy_from_slice(&addr_ua.to_ne_bytes());
             // ifa_netmask: usize (offset 32)
@@ -249,19 +249,6 @@ pub fn getifaddrs(ifap: *mut *mut u8) -> isize {
     0 // 成功
 }
 
-/// 获取接口标志
-fn get_interface_flags(iface_name: &str) -> u32 {
-    let mut flags = IFF_UP | IFF_RUNNING | IFF_MULTICAST;
-
-    if iface_name.starts_with("lo") {
-        flags |= IFF_LOOPBACK;
-    } else {
-        flags |= IFF_BROADCAST;
-    }
-
-    flags
-}
-
 /// 从 IP 地址填充 sockaddr_in 到字节缓冲区
 fn fill_sockaddr_from_ip(buf: &mut [u8], ip: smoltcp::wire::IpAddress) {
     use smoltcp::wire::IpAddress;
@@ -29,13 +29,6 @@ struct SockAddrIn {
     sin_zero: [u8; 8],
 }
 
-/// 接口标志 (Linux IFF_* flags)
-const IFF_UP: u32 = 1 << 0; // 接口已启用
-const IFF_BROADCAST: u32 = 1 << 1; // 支持广播
-const IFF_LOOPBACK: u32 = 1 << 3; // 回环接口
-const IFF_RUNNING: u32 = 1 << 6; // 接口正在运行
-const IFF_MULTICAST: u32 = 1 << 12; // 支持多播
-
 const AF_INET: u16 = 2;
 
 const EPHEMERAL_PORT_START: u16 = 49152;
@@ -65,20 +58,39 @@ macro_rules! set_sockopt_bool {
     };
 }
 
-macro_rules! set_sockopt_int {
-    ($optval:expr, $optlen:expr, $field:expr) => {
+macro_rules! set_sockopt_bufsize {
+    ($optval:expr, $optlen:expr, $field:expr, $min:expr) => {
         if $optlen >= 4 {
-            let val: i32 = read_from_user($optval as *const i32);
-            if val < 0 {
-                return -(EINVAL as isize);
+            // Linux behavior: the value is capped, then doubled to leave room for
+            // bookkeeping overhead; negative values count as very large.
+            if !crate::util::user_buffer::validate_user_ptr($optval as *const u32) {
+                return -(crate::uapi::errno::EFAULT as isize);
             }
-            // Clamp to reasonable range: min 4KB, max 16MB
-            let val = (val as usize).max(4096).min(16 * 1024 * 1024);
-            $field = val;
+            let val: u32 = read_from_user($optval as *const u32);
+            $field = (val.min(SOCK_BUF_MAX) as usize * 2).max($min);
         }
     };
 }
 
+macro_rules! set_sockopt_timeval {
+    ($optval:expr, $optlen:expr, $field:expr) => {{
+        if ($optlen as usize) < core::mem::size_of::<crate::uapi::time::timeval>() {
+            return -(EINVAL as isize);
+        }
+        if !crate::util::user_buffer::validate_user_ptr(
+            $optval as *const crate::uapi::time::timeval,
+        ) {
+            return -(crate::uapi::errno::EFAULT as isize);
+        }
+        let tv: crate::uapi::time::timeval =
+            read_from_user($optval as *const crate::uapi::time::timeval);
+        if !(0..1_000_000).contains(&tv.tv_usec) {
+            return -(crate::uapi::errno::EDOM as isize);
+        }
+        $field = tv;
+    }};
+}
+
 macro_rules! get_sockopt_bool {
     ($optval:expr, $avail:expr, $field:expr, $written:expr) => {
         if $avail >= 4 {
@@ -88,6 +100,22 @@ macro_rules! get_sockopt_bool {
     };
 }
 
+macro_rules! get_sockopt_timeval {
+    ($optval:expr, $avail:expr, $field:expr, $written:expr) => {{
+        let size = core::mem::size_of::<crate::uapi::time::timeval>();
+        if $avail < size {
+            return -(EINVAL as isize);
+        }
+        if !crate::util::user_buffer::validate_user_ptr_mut(
+            $optval as *mut crate::uapi::time::timeval,
+        ) {
+            return -(crate::uapi::errno::EFAULT as isize);
+        }
+        write_to_user($optval as *mut crate::uapi::time::timeval, $field);
+        $written = size;
+    }};
+}
+
 macro_rules! get_sockopt_int {
     ($optval:expr, $avail:expr, $field:expr, $written:expr) => {
         if $avail >= 4 {
@@ -99,13 +127,13 @@ macro_rules! get_sockopt_int {
 
 use crate::vfs::File;
 use crate::{
-    kernel::current_task,
+    kernel::{Capabilities, current_task},
     net::{
         interface::NETWORK_INTERFACE_MANAGER,
         socket::{
-            SocketFile, SocketHandle, create_tcp_socket, create_udp_socket, get_socket_handle,
-            parse_sockaddr_in, read_sockaddr_family, register_socket_fd, unregister_socket_fd,
-            write_sockaddr_in,
+            SockTimeout, SocketFile, SocketHandle, create_icmp_socket, create_raw_socket,
+            create_tcp_socket, create_udp_socket, get_socket_handle, parse_sockaddr_in,
+            read_sockaddr_family, register_socket_fd, unregister_socket_fd, write_sockaddr_in,
         },
         stack::{TcpConnectionState, TcpListenState, network_stack},
         unix_socket::{
@@ -116,7 +144,10 @@ use crate::{
     pr_debug, println,
     uapi::{
         fcntl::{FdFlags, OpenFlags},
-        socket::{AF_UNIX, SOCK_CLOEXEC, SOCK_DGRAM, SOCK_NONBLOCK, SOCK_STREAM, SOCK_TYPE_MASK},
+        socket::{
+            AF_UNIX, IPPROTO_ICMP, SOCK_CLOEXEC, SOCK_DGRAM, SOCK_NONBLOCK, SOCK_RAW, SOCK_STREAM,
+            SOCK_TYPE_MASK,
+        },
     },
 };
 use alloc::sync::Arc;
@@ -177,7 +208,8 @@ fn ensure_udp_bound_for_peer(
 }
 
 /// 安全地从用户空间拷贝C字符串
-fn copy_c_str_from_user(ptr: *const c_char) -> Option<String> {
+/// 从用户空间复制以 NUL 结尾的字符串（最长 255 字节）
+pub(crate) fn copy_c_str_from_user(ptr: *const c_char) -> Option<String> {
     if ptr.is_null() {
         return None;
     }
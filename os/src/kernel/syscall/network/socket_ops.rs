@@ -2,6 +2,7 @@ use super::*;
 
 const TCP_LISTENER_POOL_LIMIT: usize = 16;
 const AF_INET_U16: u16 = 2;
+const EPHEMERAL_BIND_ATTEMPTS: usize = 32;
 
 fn is_local_bind_address(addr: IpAddress) -> bool {
     match addr {
@@ -32,8 +33,27 @@ fn is_local_bind_address(addr: IpAddress) -> bool {
     }
 }
 
+/// 为 bind() 选定本地端点并检查地址冲突
+///
+/// 端口为 0 时分配一个未被占用的临时端口。
+fn bind_endpoint(file: &SocketFile, endpoint: IpEndpoint) -> Result<IpEndpoint, isize> {
+    use crate::net::socket::check_bind_conflict;
+
+    if endpoint.port != 0 {
+        check_bind_conflict(file, endpoint).map_err(|e| e.to_errno())?;
+        return Ok(endpoint);
+    }
+    for _ in 0..EPHEMERAL_BIND_ATTEMPTS {
+        let candidate = IpEndpoint::new(endpoint.addr, alloc_ephemeral_port());
+        if check_bind_conflict(file, candidate).is_ok() {
+            return Ok(candidate);
+        }
+    }
+    Err(crate::net::NetworkError::AddressInUse.to_errno())
+}
+
 /// 创建套接字
-pub fn socket(domain: i32, socket_type: i32, _protocol: i32) -> isize {
+pub fn socket(domain: i32, socket_type: i32, protocol: i32) -> isize {
     let base_type = socket_type & SOCK_TYPE_MASK;
     let extra_flags = socket_type & !SOCK_TYPE_MASK;
     let supported_flags = SOCK_NONBLOCK | SOCK_CLOEXEC;
@@ -74,14 +94,36 @@ pub fn socket(domain: i32, socket_type: i32, _protocol: i32) -> isize {
             Ok(h) => h,
             Err(e) => return e.to_errno(),
         },
+        SOCK_DGRAM if protocol == IPPROTO_ICMP => match create_icmp_socket() {
+            Ok(h) => h,
+            Err(e) => return e.to_errno(),
+        },
         SOCK_DGRAM => match create_udp_socket() {
             Ok(h) => h,
             Err(e) => return e.to_errno(),
         },
+        SOCK_RAW => {
+            if protocol != IPPROTO_ICMP {
+                return -(crate::uapi::errno::EPROTONOSUPPORT as isize);
+            }
+            if !current_task()
+                .lock()
+                .credential
+                .capabilities
+                .has(Capabilities::NET_RAW)
+            {
+                return -(crate::uapi::errno::EPERM as isize);
+            }
+            match create_raw_socket() {
+                Ok(h) => h,
+                Err(e) => return e.to_errno(),
+            }
+        }
         _ => return -94, // ESOCKTNOSUPPORT
     };
 
     let socket_file = Arc::new(SocketFile::new_with_flags(handle, open_flags));
+    crate::net::socket::register_inet_socket(&socket_file);
     let task = current_task();
 
     let task_lock = task.lock();
@@ -92,6 +134,8 @@ pub fn socket(domain: i32, socket_type: i32, _protocol: i32) -> isize {
             let handle_type = match handle {
                 SocketHandle::Tcp(_) => "TCP",
                 SocketHandle::Udp(_) => "UDP",
+                SocketHandle::Icmp(_) => "ICMP",
+                SocketHandle::Raw(_) => "RAW",
             };
             pr_debug!(
                 "[SOCKET] Created {} socket: tid={}, fd={}, domain={}, type={}",
@@ -214,50 +258,49 @@ pub fn bind(sockfd: i32, addr: *const u8, addrlen: u32) -> isize {
     // For UDP: bind immediately
     match handle {
         SocketHandle::Tcp(_) => {
-            use crate::net::socket::set_socket_local_endpoint;
-            // Linux behavior: bind(..., port=0) asks the kernel to choose an ephemeral port.
-            let endpoint = if endpoint.port == 0 {
-                IpEndpoint::new(endpoint.addr, alloc_ephemeral_port())
-            } else {
-                endpoint
+            let Some(sf) = file.as_any().downcast_ref::<SocketFile>() else {
+                return -88; // ENOTSOCK
             };
 
             // Linux behavior: binding an already-bound TCP socket is invalid.
-            if let Some(sf) = file.as_any().downcast_ref::<SocketFile>()
-                && let Some(old) = sf.get_local_endpoint()
+            if let Some(old) = sf.get_local_endpoint()
                 && old.port != 0
             {
                 return -22; // EINVAL
             }
 
-            if let Err(e) = set_socket_local_endpoint(&file, endpoint) {
+            let endpoint = match bind_endpoint(sf, endpoint) {
+                Ok(endpoint) => endpoint,
+                Err(e) => return e,
+            };
+            if let Err(e) = crate::net::socket::set_socket_local_endpoint(&file, endpoint) {
                 return e.to_errno();
             }
         }
         SocketHandle::Udp(h) => {
-            // Linux allows binding multiple UDP sockets to the same port (with SO_REUSEADDR).
+            // Linux allows binding multiple UDP sockets to the same port with SO_REUSEADDR
+            // or SO_REUSEPORT (see `check_bind_conflict`).
             // smoltcp's UDP demux only matches by dst_port, so we implement a per-port dispatcher:
             // one smoltcp UDP socket per local port, and per-fd queues filtered by remote endpoint.
 
+            let Some(sf) = file.as_any().downcast_ref::<SocketFile>() else {
+                return -88; // ENOTSOCK
+            };
+
             // Linux behavior: binding an already-bound UDP socket is invalid.
-            if let Some(sf) = file.as_any().downcast_ref::<SocketFile>()
-                && let Some(old) = sf.get_local_endpoint()
+            if let Some(old) = sf.get_local_endpoint()
                 && old.port != 0
             {
                 return -22; // EINVAL
             }
 
-            // Bind port 0 => allocate an ephemeral port.
-            let endpoint = if endpoint.port == 0 {
-                IpEndpoint::new(endpoint.addr, alloc_ephemeral_port())
-            } else {
-                endpoint
+            let endpoint = match bind_endpoint(sf, endpoint) {
+                Ok(endpoint) => endpoint,
+                Err(e) => return e,
             };
 
             // Persist local endpoint on the SocketFile for dispatch matching.
-            if let Some(sf) = file.as_any().downcast_ref::<SocketFile>() {
-                sf.set_local_endpoint(endpoint);
-            }
+            sf.set_local_endpoint(endpoint);
 
             let bind_addr = match endpoint.addr {
                 IpAddress::Ipv4(a) if a.is_unspecified() => None,
@@ -281,6 +324,21 @@ pub fn bind(sockfd: i32, addr: *const u8, addrlen: u32) -> isize {
                 return e.to_errno();
             }
         }
+        SocketHandle::Icmp(h) => {
+            // Ping sockets use the port as the echo identifier.
+            let ident = match network_stack().icmp_bind(h, endpoint.port) {
+                Ok(ident) => ident,
+                Err(e) => return e.to_errno(),
+            };
+            if let Some(sf) = file.as_any().downcast_ref::<SocketFile>() {
+                sf.set_local_endpoint(IpEndpoint::new(endpoint.addr, ident));
+            }
+        }
+        SocketHandle::Raw(_) => {
+            if let Some(sf) = file.as_any().downcast_ref::<SocketFile>() {
+                sf.set_local_endpoint(IpEndpoint::new(endpoint.addr, 0));
+            }
+        }
     }
 
     0
@@ -388,8 +446,8 @@ pub fn listen(sockfd: i32, backlog: i32) -> isize {
             }
             0
         }
-        SocketHandle::Udp(_) => {
-            -95 // EOPNOTSUPP - UDP doesn't support listen
+        SocketHandle::Udp(_) | SocketHandle::Icmp(_) | SocketHandle::Raw(_) => {
+            -95 // EOPNOTSUPP - datagram sockets don't support listen
         }
     }
 }
@@ -436,8 +494,8 @@ pub fn accept(sockfd: i32, addr: *mut u8, addrlen: *mut u32) -> isize {
         None => return -88, // ENOTSOCK
     };
 
-    if matches!(socket_file.handle(), SocketHandle::Udp(_)) {
-        return -95; // EOPNOTSUPP - UDP doesn't support accept
+    if !matches!(socket_file.handle(), SocketHandle::Tcp(_)) {
+        return -95; // EOPNOTSUPP - datagram sockets don't support accept
     }
 
     if !socket_file.is_listener() {
@@ -448,6 +506,45 @@ pub fn accept(sockfd: i32, addr: *mut u8, addrlen: *mut u32) -> isize {
     let is_nonblock = socket_file
         .flags()
         .contains(crate::uapi::fcntl::OpenFlags::O_NONBLOCK);
+    let start = crate::arch::get_time();
+
+    loop {
+        match tcp_try_accept(&file, socket_file, tid as usize, sockfd as usize) {
+            Ok(Some(conn_handle)) => {
+                return accept_return_conn(
+                    task.clone(),
+                    tid as usize,
+                    socket_file,
+                    conn_handle,
+                    addr,
+                    addrlen,
+                );
+            }
+            Ok(None) => {}
+            Err(e) => return e,
+        }
+
+        if is_nonblock {
+            return -11; // EAGAIN
+        }
+        if let Err(e) =
+            crate::net::socket::wait_socket_io(file.clone(), &task, SockTimeout::Recv, start)
+        {
+            return e;
+        }
+    }
+}
+
+/// 不阻塞地从监听套接字取出一个已完成握手的连接
+///
+/// 没有可接受的连接时返回 `Ok(None)`。`tid` 和 `sockfd` 是监听套接字在
+/// fd 到 socket handle 映射中的键，监听 handle 被换出时据此更新。
+pub fn tcp_try_accept(
+    file: &Arc<dyn File>,
+    socket_file: &SocketFile,
+    tid: usize,
+    sockfd: usize,
+) -> Result<Option<smoltcp::iface::SocketHandle>, isize> {
     let backlog = socket_file.listen_backlog().clamp(1, 128);
 
     loop {
@@ -458,80 +555,56 @@ pub fn accept(sockfd: i32, addr: *mut u8, addrlen: *mut u32) -> isize {
         if let Some(SocketHandle::Tcp(conn_handle)) =
             socket_file.take_established_from_listen_queue()
         {
-            return accept_return_conn(task.clone(), tid as usize, conn_handle, addr, addrlen);
+            return Ok(Some(conn_handle));
         }
 
         // 2) 检查当前监听 handle 是否已经进入握手/已建立状态；
         //    如果进入了（state != Listen），就立刻创建一个新的 listener 继续监听，
         //    把旧 handle 放入队列（Established 直接返回，SynReceived 等待后续成熟）。
-        let listen_handle = match get_socket_handle(tid as usize, sockfd as usize) {
+        let listen_handle = match get_socket_handle(tid, sockfd) {
             Some(SocketHandle::Tcp(h)) => h,
-            Some(SocketHandle::Udp(_)) => return -95, // EOPNOTSUPP
-            None => return -88,                       // ENOTSOCK
+            Some(_) => return Err(-95), // EOPNOTSUPP
+            None => return Err(-88),    // ENOTSOCK
         };
 
         let (state, listen_endpoint) =
             match network_stack().tcp_listener_state_endpoint(listen_handle) {
                 Some(v) => v,
-                None => return -88, // ENOTSOCK
+                None => return Err(-88), // ENOTSOCK
             };
 
         if state != TcpListenState::Listen {
-            if let Err(e) = replenish_tcp_listeners(socket_file, listen_endpoint, backlog) {
-                return e;
-            }
+            replenish_tcp_listeners(socket_file, listen_endpoint, backlog)?;
 
             let Some(new_listen_handle) =
                 network_stack().take_spare_tcp_listener(socket_file, listen_endpoint)
             else {
-                return -11; // EAGAIN
+                return Err(-11); // EAGAIN
             };
 
             use crate::net::socket::{update_socket_file_handle, update_socket_handle};
-            update_socket_handle(
-                tid as usize,
-                sockfd as usize,
-                SocketHandle::Tcp(new_listen_handle),
-            );
-            if let Err(e) = update_socket_file_handle(&file, SocketHandle::Tcp(new_listen_handle)) {
-                return e.to_errno();
-            }
+            update_socket_handle(tid, sockfd, SocketHandle::Tcp(new_listen_handle));
+            update_socket_file_handle(file, SocketHandle::Tcp(new_listen_handle))
+                .map_err(|e| e.to_errno())?;
 
             // Established / CloseWait: this handle is ready to return right away.
             if matches!(
                 state,
                 TcpListenState::Established | TcpListenState::CloseWait
             ) {
-                return accept_return_conn(
-                    task.clone(),
-                    tid as usize,
-                    listen_handle,
-                    addr,
-                    addrlen,
-                );
+                return Ok(Some(listen_handle));
             }
 
             // Otherwise: keep it as pending (SynReceived, etc).
             if !socket_file.has_listen_socket(SocketHandle::Tcp(listen_handle)) {
                 socket_file.add_listen_socket(SocketHandle::Tcp(listen_handle));
             }
-            if let Err(e) = replenish_tcp_listeners(socket_file, listen_endpoint, backlog) {
-                return e;
-            }
+            replenish_tcp_listeners(socket_file, listen_endpoint, backlog)?;
             continue;
         }
 
-        if let Err(e) = replenish_tcp_listeners(socket_file, listen_endpoint, backlog) {
-            return e;
-        }
-
-        if is_nonblock {
-            return -11; // EAGAIN
-        }
-        crate::kernel::yield_task();
-        if crate::ipc::signal_interrupts_syscall(&task) {
-            return -(crate::uapi::errno::EINTR as isize);
-        }
+        replenish_tcp_listeners(socket_file, listen_endpoint, backlog)?;
+        return Ok(None);
     }
 }
 
@@ -550,8 +623,9 @@ fn replenish_tcp_listeners(
                 }
                 return Err(e.to_errno());
             }
-            Ok(SocketHandle::Udp(_)) => return Err(-(crate::uapi::errno::EINVAL as isize)),
+            Ok(_) => return Err(-(crate::uapi::errno::EINVAL as isize)),
         };
+        network_stack().tcp_apply_options(new_listen_handle, &socket_file.get_socket_options());
 
         if let Err(e) = network_stack().tcp_listen(new_listen_handle, listen_endpoint) {
             network_stack().remove_tcp_socket(new_listen_handle);
@@ -569,16 +643,14 @@ fn replenish_tcp_listeners(
 fn accept_return_conn(
     task: crate::kernel::SharedTask,
     tid: usize,
+    listener: &SocketFile,
     conn_handle: smoltcp::iface::SocketHandle,
     addr: *mut u8,
     addrlen: *mut u32,
 ) -> isize {
-    use crate::net::socket::SocketFile;
-
-    let (remote_endpoint, local_endpoint) = match network_stack().tcp_accept_endpoints(conn_handle)
-    {
-        Some(v) => v,
-        None => return -11, // EAGAIN
+    let (conn, remote_endpoint) = match accepted_socket(listener, conn_handle) {
+        Ok(v) => v,
+        Err(e) => return e,
     };
 
     if !addr.is_null() && !addrlen.is_null() {
@@ -586,12 +658,6 @@ fn accept_return_conn(
         let _ = write_sockaddr_in(addr, addrlen, remote_endpoint);
     }
 
-    let conn = Arc::new(SocketFile::new(SocketHandle::Tcp(conn_handle)));
-    if let Some(local_ep) = local_endpoint {
-        conn.set_local_endpoint(local_ep);
-    }
-    conn.set_remote_endpoint(remote_endpoint);
-
     match task.lock().fd_table.alloc(conn) {
         Ok(fd) => {
             register_socket_fd(tid, fd, SocketHandle::Tcp(conn_handle));
@@ -600,3 +666,23 @@ fn accept_return_conn(
         Err(_) => -24, // EMFILE
     }
 }
+
+/// 为 `tcp_try_accept` 取出的连接创建套接字文件，返回文件和对端地址
+pub fn accepted_socket(
+    listener: &SocketFile,
+    conn_handle: smoltcp::iface::SocketHandle,
+) -> Result<(Arc<SocketFile>, IpEndpoint), isize> {
+    let (remote_endpoint, local_endpoint) = network_stack()
+        .tcp_accept_endpoints(conn_handle)
+        .ok_or(-11_isize)?; // EAGAIN
+
+    let conn = Arc::new(SocketFile::new(SocketHandle::Tcp(conn_handle)));
+    crate::net::socket::register_inet_socket(&conn);
+    // Linux behavior: accepted sockets inherit the listener's options.
+    conn.set_socket_options(listener.get_socket_options());
+    if let Some(local_ep) = local_endpoint {
+        conn.set_local_endpoint(local_ep);
+    }
+    conn.set_remote_endpoint(remote_endpoint);
+    Ok((conn, remote_endpoint))
+}
@@ -58,10 +58,19 @@ pub fn setsockopt(sockfd: i32, level: i32, optname: i32, optval: *const u8, optl
             SO_REUSEPORT => set_sockopt_bool!(optval, optlen, opts.reuse_port),
             SO_KEEPALIVE => set_sockopt_bool!(optval, optlen, opts.keepalive),
             SO_DONTROUTE | SO_BROADCAST | SO_OOBINLINE => { /* ignore */ }
-            SO_SNDBUF => set_sockopt_int!(optval, optlen, opts.send_buffer_size),
-            SO_RCVBUF => set_sockopt_int!(optval, optlen, opts.recv_buffer_size),
+            SO_SNDBUF => {
+                set_sockopt_bufsize!(optval, optlen, opts.send_buffer_size, SOCK_MIN_SNDBUF)
+            }
+            SO_RCVBUF => {
+                set_sockopt_bufsize!(optval, optlen, opts.recv_buffer_size, SOCK_MIN_RCVBUF)
+            }
             SO_RCVLOWAT | SO_SNDLOWAT => { /* ignore */ }
-            SO_RCVTIMEO_OLD | SO_SNDTIMEO_OLD => { /* Ignore timeout options */ }
+            SO_RCVTIMEO_OLD | SO_RCVTIMEO_NEW => {
+                set_sockopt_timeval!(optval, optlen, opts.recv_timeout)
+            }
+            SO_SNDTIMEO_OLD | SO_SNDTIMEO_NEW => {
+                set_sockopt_timeval!(optval, optlen, opts.send_timeout)
+            }
             _ => return -(ENOPROTOOPT as isize),
         },
         _ if matches!(target, SocketOptionTarget::Unix(_)) => return -(ENOPROTOOPT as isize),
@@ -105,10 +114,10 @@ pub fn getsockopt(
         Err(_) => return -(EBADF as isize),
     };
 
-    let opts = if let Some(sf) = file
+    let inet = file
         .as_any()
-        .downcast_ref::<crate::net::socket::SocketFile>()
-    {
+        .downcast_ref::<crate::net::socket::SocketFile>();
+    let opts = if let Some(sf) = inet {
         sf.get_socket_options()
     } else if let Some(sf) = file
         .as_any()
@@ -148,6 +157,16 @@ pub fn getsockopt(
             SO_RCVBUF => {
                 get_sockopt_int!(optval, available_len, opts.recv_buffer_size, written_len)
             }
+            SO_ERROR => {
+                let error = inet.map_or(0, |sf| sf.take_error());
+                get_sockopt_int!(optval, available_len, error, written_len)
+            }
+            SO_RCVTIMEO_OLD | SO_RCVTIMEO_NEW => {
+                get_sockopt_timeval!(optval, available_len, opts.recv_timeout, written_len)
+            }
+            SO_SNDTIMEO_OLD | SO_SNDTIMEO_NEW => {
+                get_sockopt_timeval!(optval, available_len, opts.send_timeout, written_len)
+            }
             _ => return -(ENOPROTOOPT as isize),
         },
         IPPROTO_TCP => match optname {
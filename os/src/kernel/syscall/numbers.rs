@@ -1,182 +1,355 @@
-//! Linux 系统调用号定义（架构无关）
+//! 系统调用表
 //!
-//! 系统调用号遵循 Linux 通用 ABI，在所有架构上数值一致。
-//! 本模块仅包含内核实际处理的系统调用。
-
-use core::usize;
-
-// ---- 文件系统/目录操作 ----
-pub const SYS_GETCWD: usize = 17;
-pub const SYS_DUP: usize = 23;
-pub const SYS_DUP3: usize = 24;
-pub const SYS_FCNTL: usize = 25;
-pub const SYS_IOCTL: usize = 29;
-pub const SYS_MKNODAT: usize = 33;
-pub const SYS_MKDIRAT: usize = 34;
-pub const SYS_UNLINKAT: usize = 35;
-pub const SYS_SYMLINKAT: usize = 36;
-pub const SYS_LINKAT: usize = 37;
-pub const SYS_MOUNT: usize = 40;
-pub const SYS_UMOUNT2: usize = 39;
-pub const SYS_STATFS: usize = 43;
-pub const SYS_FACCESSAT: usize = 48;
-pub const SYS_CHDIR: usize = 49;
-pub const SYS_FCHMODAT: usize = 53;
-pub const SYS_FCHOWNAT: usize = 54;
-pub const SYS_OPENAT: usize = 56;
-pub const SYS_CLOSE: usize = 57;
-pub const SYS_PIPE2: usize = 59;
-pub const SYS_GETDENTS64: usize = 61;
-pub const SYS_LSEEK: usize = 62;
-pub const SYS_FTRUNCATE: usize = 46;
-
-// ---- I/O 操作 ----
-pub const SYS_READ: usize = 63;
-pub const SYS_WRITE: usize = 64;
-pub const SYS_READV: usize = 65;
-pub const SYS_WRITEV: usize = 66;
-pub const SYS_PREAD64: usize = 67;
-pub const SYS_PWRITE64: usize = 68;
-pub const SYS_PREADV: usize = 69;
-pub const SYS_PWRITEV: usize = 70;
-pub const SYS_SENDFILE: usize = 71;
-
-// ---- I/O 多路复用 ----
-pub const SYS_PSELECT6: usize = 72;
-pub const SYS_PPOLL: usize = 73;
-
-// ---- 文件元数据与同步 ----
-pub const SYS_READLINKAT: usize = 78;
-pub const SYS_FSTATAT: usize = 79;
-pub const SYS_FSTAT: usize = 80;
-pub const SYS_SYNC: usize = 81;
-pub const SYS_FSYNC: usize = 82;
-pub const SYS_FDATASYNC: usize = 83;
-
-// ---- 时间 ----
-pub const SYS_UTIMENSAT: usize = 88;
-
-// ---- 进程与控制 ----
-pub const SYS_EXIT: usize = 93;
-pub const SYS_EXIT_GROUP: usize = 94;
-pub const SYS_SET_TID_ADDRESS: usize = 96;
-
-// ---- 同步/休眠 ----
-pub const SYS_FUTEX: usize = 98;
-pub const SYS_SET_ROBUST_LIST: usize = 99;
-pub const SYS_GET_ROBUST_LIST: usize = 100;
-pub const SYS_NANOSLEEP: usize = 101;
-pub const SYS_GETITIMER: usize = 102;
-pub const SYS_SETITIMER: usize = 103;
-
-// ---- 时钟 ----
-pub const SYS_CLOCK_SETTIME: usize = 112;
-pub const SYS_CLOCK_GETTIME: usize = 113;
-pub const SYS_CLOCK_GETRES: usize = 114;
-pub const SYS_CLOCK_NANOSLEEP: usize = 115;
-pub const SYS_SYSLOG: usize = 116;
-
-// ---- 调度 ----
-pub const SYS_SCHED_SETPARAM: usize = 118;
-pub const SYS_SCHED_SETSCHEDULER: usize = 119;
-pub const SYS_SCHED_GETSCHEDULER: usize = 120;
-pub const SYS_SCHED_GETPARAM: usize = 121;
-pub const SYS_SCHED_SETAFFINITY: usize = 122;
-pub const SYS_SCHED_GETAFFINITY: usize = 123;
-pub const SYS_SCHED_YIELD: usize = 124;
-
-// ---- 信号 ----
-pub const SYS_KILL: usize = 129;
-pub const SYS_TKILL: usize = 130;
-pub const SYS_TGKILL: usize = 131;
-pub const SYS_SIGALTSTACK: usize = 132;
-pub const SYS_RT_SIGSUSPEND: usize = 133;
-pub const SYS_RT_SIGACTION: usize = 134;
-pub const SYS_RT_SIGPROCMASK: usize = 135;
-pub const SYS_RT_SIGPENDING: usize = 136;
-pub const SYS_RT_SIGTIMEDWAIT: usize = 137;
-pub const SYS_RT_SIGRETURN: usize = 139;
-
-// ---- 进程属性 ----
-pub const SYS_REBOOT: usize = 142;
-pub const SYS_SETGID: usize = 144;
-pub const SYS_SETUID: usize = 146;
-pub const SYS_SETRESUID: usize = 147;
-pub const SYS_GETRESUID: usize = 148;
-pub const SYS_SETRESGID: usize = 149;
-pub const SYS_GETRESGID: usize = 150;
-pub const SYS_TIMES: usize = 153;
-pub const SYS_SETPGID: usize = 154;
-pub const SYS_GETPGID: usize = 155;
-pub const SYS_SETSID: usize = 157;
-pub const SYS_UNAME: usize = 160;
-pub const SYS_SETHOSTNAME: usize = 161;
-pub const SYS_GETRLIMIT: usize = 163;
-pub const SYS_SETRLIMIT: usize = 164;
-pub const SYS_GETRUSAGE: usize = 165;
-pub const SYS_UMASK: usize = 166;
-pub const SYS_GETTIMEOFDAY: usize = 169;
-pub const SYS_GETPID: usize = 172;
-pub const SYS_GETPPID: usize = 173;
-pub const SYS_GETUID: usize = 174;
-pub const SYS_GETEUID: usize = 175;
-pub const SYS_GETGID: usize = 176;
-pub const SYS_GETEGID: usize = 177;
-pub const SYS_GETTID: usize = 178;
-pub const SYS_SYSINFO: usize = 179;
-
-// ---- System V IPC ----
-pub const SYS_SHMGET: usize = 194;
-pub const SYS_SHMCTL: usize = 195;
-pub const SYS_SHMAT: usize = 196;
-pub const SYS_SHMDT: usize = 197;
-
-// ---- 网络/Socket ----
-pub const SYS_SOCKET: usize = 198;
-pub const SYS_SOCKETPAIR: usize = 199;
-pub const SYS_BIND: usize = 200;
-pub const SYS_LISTEN: usize = 201;
-pub const SYS_ACCEPT: usize = 202;
-pub const SYS_CONNECT: usize = 203;
-pub const SYS_GETSOCKNAME: usize = 204;
-pub const SYS_GETPEERNAME: usize = 205;
-pub const SYS_SENDTO: usize = 206;
-pub const SYS_RECVFROM: usize = 207;
-pub const SYS_SETSOCKOPT: usize = 208;
-pub const SYS_GETSOCKOPT: usize = 209;
-pub const SYS_SHUTDOWN: usize = 210;
-
-// ---- 进程创建/执行 ----
-pub const SYS_CLONE: usize = 220;
-pub const SYS_EXECVE: usize = 221;
-
-// ---- 内存管理 ----
-pub const SYS_BRK: usize = 214;
-pub const SYS_MUNMAP: usize = 215;
-pub const SYS_MMAP: usize = 222;
-pub const SYS_MPROTECT: usize = 226;
-pub const SYS_MLOCK: usize = 228;
-pub const SYS_MUNLOCK: usize = 229;
-pub const SYS_MLOCKALL: usize = 230;
-pub const SYS_MUNLOCKALL: usize = 231;
-pub const SYS_MADVISE: usize = 233;
-pub const SYS_GET_MEMPOLICY: usize = 236;
-
-// ---- 网络 (续) ----
-pub const SYS_ACCEPT4: usize = 242;
-
-// ---- 进程与控制 (续) ----
-pub const SYS_WAIT4: usize = 260;
-pub const SYS_PRLIMIT64: usize = 261;
-
-// ---- 文件系统 (续) ----
-pub const SYS_SYNCFS: usize = 267;
-
-// ---- 其他 ----
-pub const SYS_RENAMEAT2: usize = 276;
-pub const SYS_GETRANDOM: usize = 278;
-pub const SYS_STATX: usize = 291;
-
-// ---- 自定义内核扩展 ----
-// 注意：此调用号在不同架构上可能不同
-pub const SYS_GETIFADDRS: usize = crate::arch::abi::SYS_GETIFADDRS;
+//! 内核实现的每个系统调用在本文件的 `syscall_table!` 中声明一行：
+//!
+//! ```text
+//! SYS_READ = 63 => read(3) sys_read;
+//! ```
+//!
+//! 依次为调用号常量、调用号、Linux 中的名字、参数个数和 `impl_syscall!` 生成的包装函数。
+//! 同一份声明生成：
+//! - `SYS_*` 调用号常量；
+//! - [`SYSCALL_TABLE`]：调用号、名字和参数个数，供诊断和统计使用；
+//! - [`invoke`]：`dispatch_syscall` 使用的分发；
+//! - 编译期检查：每个调用号必须与 [`linux_abi`](super::linux_abi) 中当前架构的
+//!   `__NR_<名字>` 一致，表中不能有重复的调用号。
+//!
+//! riscv64 与 loongarch64 共用 asm-generic 调用号，只在某一架构上存在的调用用
+//! `#[cfg(...)]` 标注，例如 loongarch64 没有 `getrlimit`/`setrlimit`。
+//! 不属于 Linux 的内核扩展在行尾加 `nonstandard`，此时改为检查它不占用任何 Linux 调用号。
+
+use super::linux_abi;
+use super::syscall_frame::SyscallFrame;
+use super::*;
+
+/// 系统调用表项
+#[derive(Debug, Clone, Copy)]
+pub struct SyscallDesc {
+    /// 调用号
+    pub nr: usize,
+    /// Linux 中的系统调用名
+    pub name: &'static str,
+    /// 参数个数
+    pub nargs: usize,
+}
+
+macro_rules! syscall_table {
+    ($(
+        $(#[$attr:meta])*
+        $konst:ident = $nr:expr => $name:ident($nargs:literal) $handler:ident $($ext:ident)?;
+    )*) => {
+        $(
+            $(#[$attr])*
+            pub const $konst: usize = $nr;
+        )*
+
+        const TABLE: &[SyscallDesc] = &[
+            $(
+                $(#[$attr])*
+                SyscallDesc {
+                    nr: $konst,
+                    name: stringify!($name),
+                    nargs: $nargs,
+                },
+            )*
+        ];
+
+        /// 内核实现的全部系统调用，按声明顺序排列
+        pub static SYSCALL_TABLE: &[SyscallDesc] = TABLE;
+
+        /// 调用 `nr` 对应的处理函数，未实现的调用号返回 `false`
+        pub fn invoke(nr: usize, frame: &mut impl SyscallFrame) -> bool {
+            match nr {
+                $(
+                    $(#[$attr])*
+                    $konst => $handler(frame),
+                )*
+                _ => return false,
+            }
+            true
+        }
+
+        $(
+            syscall_table!(@check $(#[$attr])* $konst $name $($ext)?);
+        )*
+    };
+    (@check $(#[$attr:meta])* $konst:ident $name:ident) => {
+        paste::paste! {
+            $(#[$attr])*
+            const _: () = assert!(
+                $konst == linux_abi::[<__NR_ $name>],
+                concat!(stringify!($konst), " does not match Linux __NR_", stringify!($name))
+            );
+        }
+    };
+    (@check $(#[$attr:meta])* $konst:ident $name:ident nonstandard) => {
+        $(#[$attr])*
+        const _: () = assert!(
+            linux_abi::name_of($konst).is_none(),
+            concat!(stringify!($konst), " collides with a Linux syscall number")
+        );
+    };
+}
+
+/// 编译期检查表中没有重复的调用号
+const fn check_unique(table: &[SyscallDesc]) {
+    let mut i = 0;
+    while i < table.len() {
+        let mut j = i + 1;
+        while j < table.len() {
+            assert!(table[i].nr != table[j].nr, "duplicate syscall number");
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+const _: () = check_unique(TABLE);
+
+/// 按调用号查找表项
+pub fn describe(nr: usize) -> Option<&'static SyscallDesc> {
+    SYSCALL_TABLE.iter().find(|desc| desc.nr == nr)
+}
+
+syscall_table! {
+    // ---- 文件系统/目录操作 ----
+    SYS_SETXATTR = 5 => setxattr(5) sys_setxattr;
+    SYS_LSETXATTR = 6 => lsetxattr(5) sys_lsetxattr;
+    SYS_FSETXATTR = 7 => fsetxattr(5) sys_fsetxattr;
+    SYS_GETXATTR = 8 => getxattr(4) sys_getxattr;
+    SYS_LGETXATTR = 9 => lgetxattr(4) sys_lgetxattr;
+    SYS_FGETXATTR = 10 => fgetxattr(4) sys_fgetxattr;
+    SYS_LISTXATTR = 11 => listxattr(3) sys_listxattr;
+    SYS_LLISTXATTR = 12 => llistxattr(3) sys_llistxattr;
+    SYS_FLISTXATTR = 13 => flistxattr(3) sys_flistxattr;
+    SYS_REMOVEXATTR = 14 => removexattr(2) sys_removexattr;
+    SYS_LREMOVEXATTR = 15 => lremovexattr(2) sys_lremovexattr;
+    SYS_FREMOVEXATTR = 16 => fremovexattr(2) sys_fremovexattr;
+    SYS_GETCWD = 17 => getcwd(2) sys_getcwd;
+    SYS_DUP = 23 => dup(1) sys_dup;
+    SYS_DUP3 = 24 => dup3(3) sys_dup3;
+    SYS_FCNTL = 25 => fcntl(3) sys_fcntl;
+    SYS_IOCTL = 29 => ioctl(3) sys_ioctl;
+    SYS_IOPRIO_SET = 30 => ioprio_set(3) sys_ioprio_set;
+    SYS_IOPRIO_GET = 31 => ioprio_get(2) sys_ioprio_get;
+    SYS_MKNODAT = 33 => mknodat(4) sys_mknodat;
+    SYS_MKDIRAT = 34 => mkdirat(3) sys_mkdirat;
+    SYS_UNLINKAT = 35 => unlinkat(3) sys_unlinkat;
+    SYS_SYMLINKAT = 36 => symlinkat(3) sys_symlinkat;
+    SYS_LINKAT = 37 => linkat(5) sys_linkat;
+    SYS_MOUNT = 40 => mount(5) sys_mount;
+    SYS_UMOUNT2 = 39 => umount2(2) sys_umount2;
+    SYS_STATFS = 43 => statfs(2) sys_statfs;
+    SYS_FACCESSAT = 48 => faccessat(4) sys_faccessat;
+    SYS_CHDIR = 49 => chdir(1) sys_chdir;
+    SYS_FCHDIR = 50 => fchdir(1) sys_fchdir;
+    SYS_FCHMODAT = 53 => fchmodat(3) sys_fchmodat;
+    SYS_FCHOWNAT = 54 => fchownat(5) sys_fchownat;
+    SYS_OPENAT = 56 => openat(4) sys_openat;
+    SYS_CLOSE = 57 => close(1) sys_close;
+    SYS_PIPE2 = 59 => pipe2(2) sys_pipe2;
+    SYS_GETDENTS64 = 61 => getdents64(3) sys_getdents64;
+    SYS_LSEEK = 62 => lseek(3) sys_lseek;
+    SYS_FTRUNCATE = 46 => ftruncate(2) sys_ftruncate;
+
+    // ---- I/O 操作 ----
+    SYS_READ = 63 => read(3) sys_read;
+    SYS_WRITE = 64 => write(3) sys_write;
+    SYS_READV = 65 => readv(3) sys_readv;
+    SYS_WRITEV = 66 => writev(3) sys_writev;
+    SYS_PREAD64 = 67 => pread64(4) sys_pread64;
+    SYS_PWRITE64 = 68 => pwrite64(4) sys_pwrite64;
+    SYS_PREADV = 69 => preadv(4) sys_preadv;
+    SYS_PWRITEV = 70 => pwritev(4) sys_pwritev;
+    SYS_SENDFILE = 71 => sendfile(4) sys_sendfile;
+
+    // ---- I/O 多路复用 ----
+    SYS_PSELECT6 = 72 => pselect6(6) sys_pselect6;
+    SYS_PPOLL = 73 => ppoll(4) sys_ppoll;
+
+    // ---- 文件元数据与同步 ----
+    SYS_READLINKAT = 78 => readlinkat(4) sys_readlinkat;
+    SYS_FSTATAT = 79 => newfstatat(4) sys_newfstatat;
+    SYS_FSTAT = 80 => fstat(2) sys_fstat;
+    SYS_SYNC = 81 => sync(0) sys_sync;
+    SYS_FSYNC = 82 => fsync(1) sys_fsync;
+    SYS_FDATASYNC = 83 => fdatasync(1) sys_fdatasync;
+
+    // ---- 时间 ----
+    SYS_UTIMENSAT = 88 => utimensat(4) sys_utimensat;
+
+    // ---- 进程与控制 ----
+    SYS_CAPGET = 90 => capget(2) sys_capget;
+    SYS_CAPSET = 91 => capset(2) sys_capset;
+    SYS_EXIT = 93 => exit(1) sys_exit;
+    SYS_EXIT_GROUP = 94 => exit_group(1) sys_exit_group;
+    SYS_SET_TID_ADDRESS = 96 => set_tid_address(1) sys_set_tid_address;
+
+    // ---- 同步/休眠 ----
+    SYS_FUTEX = 98 => futex(6) sys_futex;
+    SYS_SET_ROBUST_LIST = 99 => set_robust_list(2) sys_set_robust_list;
+    SYS_GET_ROBUST_LIST = 100 => get_robust_list(3) sys_get_robust_list;
+    SYS_NANOSLEEP = 101 => nanosleep(2) sys_nanosleep;
+    SYS_GETITIMER = 102 => getitimer(2) sys_getitimmer;
+    SYS_SETITIMER = 103 => setitimer(3) sys_setitimmer;
+
+    // ---- 时钟 ----
+    SYS_CLOCK_SETTIME = 112 => clock_settime(2) sys_clock_settime;
+    SYS_CLOCK_GETTIME = 113 => clock_gettime(2) sys_clock_gettime;
+    SYS_CLOCK_GETRES = 114 => clock_getres(2) sys_clock_getres;
+    SYS_CLOCK_NANOSLEEP = 115 => clock_nanosleep(4) sys_clock_nanosleep;
+    SYS_SYSLOG = 116 => syslog(3) sys_syslog;
+
+    // ---- 调度 ----
+    SYS_SCHED_SETPARAM = 118 => sched_setparam(2) sys_sched_setparam;
+    SYS_SCHED_SETSCHEDULER = 119 => sched_setscheduler(3) sys_sched_setscheduler;
+    SYS_SCHED_GETSCHEDULER = 120 => sched_getscheduler(1) sys_sched_getscheduler;
+    SYS_SCHED_GETPARAM = 121 => sched_getparam(2) sys_sched_getparam;
+    SYS_SCHED_SETAFFINITY = 122 => sched_setaffinity(3) sys_sched_setaffinity;
+    SYS_SCHED_GETAFFINITY = 123 => sched_getaffinity(3) sys_sched_getaffinity;
+    SYS_SCHED_YIELD = 124 => sched_yield(0) sys_sched_yield;
+
+    // ---- 信号 ----
+    SYS_RESTART_SYSCALL = 128 => restart_syscall(0) sys_restart_syscall;
+    SYS_KILL = 129 => kill(2) sys_kill;
+    SYS_TKILL = 130 => tkill(2) sys_tkill;
+    SYS_TGKILL = 131 => tgkill(3) sys_tgkill;
+    SYS_SIGALTSTACK = 132 => sigaltstack(2) sys_sigaltstack;
+    SYS_RT_SIGSUSPEND = 133 => rt_sigsuspend(2) sys_rt_sigsuspend;
+    SYS_RT_SIGACTION = 134 => rt_sigaction(4) sys_rt_sigaction;
+    SYS_RT_SIGPROCMASK = 135 => rt_sigprocmask(4) sys_rt_sigprocmask;
+    SYS_RT_SIGPENDING = 136 => rt_sigpending(2) sys_rt_sigpending;
+    SYS_RT_SIGTIMEDWAIT = 137 => rt_sigtimedwait(4) sys_rt_sigtimedwait;
+    SYS_RT_SIGQUEUEINFO = 138 => rt_sigqueueinfo(3) sys_rt_sigqueueinfo;
+    SYS_RT_SIGRETURN = 139 => rt_sigreturn(0) sys_rt_sigreturn;
+
+    // ---- 进程属性 ----
+    SYS_REBOOT = 142 => reboot(4) sys_reboot;
+    SYS_SETGID = 144 => setgid(1) sys_setgid;
+    SYS_SETUID = 146 => setuid(1) sys_setuid;
+    SYS_SETRESUID = 147 => setresuid(3) sys_setresuid;
+    SYS_GETRESUID = 148 => getresuid(3) sys_getresuid;
+    SYS_SETRESGID = 149 => setresgid(3) sys_setresgid;
+    SYS_GETRESGID = 150 => getresgid(3) sys_getresgid;
+    SYS_TIMES = 153 => times(1) sys_times;
+    SYS_SETPGID = 154 => setpgid(2) sys_setpgid;
+    SYS_GETPGID = 155 => getpgid(1) sys_getpgid;
+    SYS_SETSID = 157 => setsid(0) sys_setsid;
+    SYS_GETGROUPS = 158 => getgroups(2) sys_getgroups;
+    SYS_SETGROUPS = 159 => setgroups(2) sys_setgroups;
+    SYS_UNAME = 160 => uname(1) sys_uname;
+    SYS_SETHOSTNAME = 161 => sethostname(2) sys_sethostname;
+    #[cfg(not(target_arch = "loongarch64"))]
+    SYS_GETRLIMIT = 163 => getrlimit(2) sys_getrlimit;
+    #[cfg(not(target_arch = "loongarch64"))]
+    SYS_SETRLIMIT = 164 => setrlimit(2) sys_setrlimit;
+    SYS_GETRUSAGE = 165 => getrusage(2) sys_getrusage;
+    SYS_UMASK = 166 => umask(1) sys_umask;
+    SYS_PRCTL = 167 => prctl(5) sys_prctl;
+    SYS_GETTIMEOFDAY = 169 => gettimeofday(2) sys_gettimeofday;
+    SYS_SETTIMEOFDAY = 170 => settimeofday(2) sys_settimeofday;
+    SYS_ADJTIMEX = 171 => adjtimex(1) sys_adjtimex;
+    SYS_GETPID = 172 => getpid(0) sys_getpid;
+    SYS_GETPPID = 173 => getppid(0) sys_getppid;
+    SYS_GETUID = 174 => getuid(0) sys_getuid;
+    SYS_GETEUID = 175 => geteuid(0) sys_geteuid;
+    SYS_GETGID = 176 => getgid(0) sys_getgid;
+    SYS_GETEGID = 177 => getegid(0) sys_getegid;
+    SYS_GETTID = 178 => gettid(0) sys_gettid;
+    SYS_SYSINFO = 179 => sysinfo(1) sys_sysinfo;
+
+    // ---- POSIX 消息队列 ----
+    SYS_MQ_OPEN = 180 => mq_open(4) sys_mq_open;
+    SYS_MQ_UNLINK = 181 => mq_unlink(1) sys_mq_unlink;
+    SYS_MQ_TIMEDSEND = 182 => mq_timedsend(5) sys_mq_timedsend;
+    SYS_MQ_TIMEDRECEIVE = 183 => mq_timedreceive(5) sys_mq_timedreceive;
+    SYS_MQ_NOTIFY = 184 => mq_notify(2) sys_mq_notify;
+    SYS_MQ_GETSETATTR = 185 => mq_getsetattr(3) sys_mq_getsetattr;
+
+    // ---- System V IPC ----
+    SYS_SHMGET = 194 => shmget(3) sys_shmget;
+    SYS_SHMCTL = 195 => shmctl(3) sys_shmctl;
+    SYS_SHMAT = 196 => shmat(3) sys_shmat;
+    SYS_SHMDT = 197 => shmdt(1) sys_shmdt;
+
+    // ---- 网络/Socket ----
+    SYS_SOCKET = 198 => socket(3) sys_socket;
+    SYS_SOCKETPAIR = 199 => socketpair(4) sys_socketpair;
+    SYS_BIND = 200 => bind(3) sys_bind;
+    SYS_LISTEN = 201 => listen(2) sys_listen;
+    SYS_ACCEPT = 202 => accept(3) sys_accept;
+    SYS_CONNECT = 203 => connect(3) sys_connect;
+    SYS_GETSOCKNAME = 204 => getsockname(3) sys_getsockname;
+    SYS_GETPEERNAME = 205 => getpeername(3) sys_getpeername;
+    SYS_SENDTO = 206 => sendto(6) sys_sendto;
+    SYS_RECVFROM = 207 => recvfrom(6) sys_recvfrom;
+    SYS_SETSOCKOPT = 208 => setsockopt(5) sys_setsockopt;
+    SYS_GETSOCKOPT = 209 => getsockopt(5) sys_getsockopt;
+    SYS_SHUTDOWN = 210 => shutdown(2) sys_shutdown;
+
+    // ---- 进程创建/执行 ----
+    SYS_CLONE = 220 => clone(5) sys_clone;
+    SYS_EXECVE = 221 => execve(3) sys_execve;
+
+    // ---- 内存管理 ----
+    SYS_BRK = 214 => brk(1) sys_brk;
+    SYS_MUNMAP = 215 => munmap(2) sys_munmap;
+    SYS_MMAP = 222 => mmap(6) sys_mmap;
+    SYS_SWAPON = 224 => swapon(2) sys_swapon;
+    SYS_SWAPOFF = 225 => swapoff(1) sys_swapoff;
+    SYS_MPROTECT = 226 => mprotect(3) sys_mprotect;
+    SYS_MLOCK = 228 => mlock(2) sys_mlock;
+    SYS_MUNLOCK = 229 => munlock(2) sys_munlock;
+    SYS_MLOCKALL = 230 => mlockall(1) sys_mlockall;
+    SYS_MUNLOCKALL = 231 => munlockall(0) sys_munlockall;
+    SYS_MADVISE = 233 => madvise(3) sys_madvise;
+    SYS_GET_MEMPOLICY = 236 => get_mempolicy(5) sys_get_mempolicy;
+
+    // ---- 网络 (续) ----
+    SYS_ACCEPT4 = 242 => accept4(4) sys_accept4;
+
+    // ---- 进程与控制 (续) ----
+    SYS_WAIT4 = 260 => wait4(4) sys_wait4;
+    SYS_PRLIMIT64 = 261 => prlimit64(4) sys_prlimit;
+
+    // ---- 时钟 (续) ----
+    SYS_CLOCK_ADJTIME = 266 => clock_adjtime(2) sys_clock_adjtime;
+
+    // ---- 文件系统 (续) ----
+    SYS_SYNCFS = 267 => syncfs(1) sys_syncfs;
+
+    // ---- 其他 ----
+    SYS_RENAMEAT2 = 276 => renameat2(5) sys_renameat2;
+    SYS_GETRANDOM = 278 => getrandom(3) sys_getrandom;
+    SYS_MEMFD_CREATE = 279 => memfd_create(2) sys_memfd_create;
+    SYS_STATX = 291 => statx(5) sys_statx;
+
+    // ---- io_uring ----
+    SYS_IO_URING_SETUP = 425 => io_uring_setup(2) sys_io_uring_setup;
+    SYS_IO_URING_ENTER = 426 => io_uring_enter(6) sys_io_uring_enter;
+
+    // ---- 自定义内核扩展 ----
+    SYS_GETIFADDRS = crate::arch::abi::SYS_GETIFADDRS => getifaddrs(1) sys_getifaddrs nonstandard;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_syscall_table, {
+        let read = describe(SYS_READ).unwrap();
+        kassert!(read.name == "read" && read.nargs == 3);
+        kassert!(describe(SYS_FSTATAT).unwrap().name == "newfstatat");
+        kassert!(describe(SYS_GETIFADDRS).unwrap().name == "getifaddrs");
+        kassert!(describe(38).is_none());
+
+        // 除内核扩展外，表中的名字与上游一致
+        for desc in SYSCALL_TABLE {
+            kassert!(desc.nargs <= 6);
+            if desc.nr != SYS_GETIFADDRS {
+                kassert!(linux_abi::name_of(desc.nr) == Some(desc.name));
+            }
+        }
+    });
+}
@@ -9,25 +9,27 @@ use alloc::{sync::Arc, vec::Vec};
 
 use crate::{
     arch::{HwTrapFrame, TrapFrame, timer::clock_freq},
-    ipc::{create_siginfo_for_signal, do_sigpending},
+    ipc::{QueuedSignal, RestartBlock, action_ignores, discard_pending, do_sigpending},
     kernel::{
-        SharedTask, TASK_MANAGER, TIMER_QUEUE, TaskManagerTrait, current_task, sleep_task_prepare,
-        yield_task,
+        SharedTask, TASK_MANAGER, TIMER_QUEUE, TaskManagerTrait, current_task,
+        send_signal_process_info, sleep_task_prepare, yield_task,
     },
     sync::SpinLock,
     uapi::{
-        errno::{EAGAIN, EINTR, EINVAL, ENOMEM, ENOSYS, ESRCH},
+        errno::{EAGAIN, EFAULT, EINTR, EINVAL, ENOMEM, ENOSYS, EPERM, ESRCH},
         signal::{
-            MINSIGSTKSZ, NSIG, NUM_SIGKILL, NUM_SIGSTOP, RtSigFrame, SIG_BLOCK, SIG_SETMASK,
-            SIG_UNBLOCK, SIGSET_SIZE, SS_AUTODISARM, SS_DISABLE, SaFlags, SigInfoT, SignalAction,
-            SignalFlags, UContextT,
+            MINSIGSTKSZ, NSIG, NUM_SIGKILL, NUM_SIGSTOP, RtSigFrame, SI_TKILL, SI_USER, SIG_BLOCK,
+            SIG_SETMASK, SIG_UNBLOCK, SIGSET_SIZE, SS_AUTODISARM, SS_DISABLE, SaFlags, SigInfoT,
+            SignalAction, SignalFlags, UContextT,
         },
         time::TimeSpec,
         types::{SigSetT, StackT},
     },
-    util::user_buffer::{read_from_user, write_to_user},
+    util::user_buffer::{read_from_user, validate_user_ptr, write_to_user},
 };
 
+use super::task::nanosleep_restart;
+
 fn unblockable_signals() -> SignalFlags {
     SignalFlags::from_signal_num(NUM_SIGKILL).unwrap()
         | SignalFlags::from_signal_num(NUM_SIGSTOP).unwrap()
@@ -149,6 +151,17 @@ pub fn rt_sigaction(
         t.signal_handlers
             .lock()
             .set_action(signum as usize, new_action);
+
+        // POSIX：改为忽略时丢弃线程组中挂起的该信号，不论是否被屏蔽
+        if action_ignores(&new_action, signum as usize) {
+            let pid = t.pid;
+            drop(t);
+            let threads = TASK_MANAGER.lock().get_task_cond(|t| t.lock().pid == pid);
+            discard_pending(
+                &threads,
+                SignalFlags::from_signal_num(signum as usize).unwrap(),
+            );
+        }
     }
 
     0
@@ -174,8 +187,9 @@ pub fn rt_sigtimedwait(
     }
 
     let wait_set_bits = unsafe { read_from_user(set) };
+    // SIGKILL 和 SIGSTOP 不能被等待取走
     let wait_set = if let Some(flags) = SignalFlags::from_bits(wait_set_bits as usize) {
-        flags
+        flags & !unblockable_signals()
     } else {
         return -EINVAL;
     };
@@ -188,13 +202,13 @@ pub fn rt_sigtimedwait(
     };
 
     match wait_for_signal(current_task(), wait_set, timeout_opt) {
-        Ok((sig_num, sig_info)) => {
+        Ok(queued) => {
             if !info.is_null() {
                 unsafe {
-                    write_to_user(info, sig_info);
+                    write_to_user(info, queued.to_siginfo());
                 }
             }
-            sig_num as c_int
+            queued.signo as c_int
         }
         Err(err_code) => err_code,
     }
@@ -249,6 +263,8 @@ pub fn rt_sigreturn() -> ! {
         let task = current_task();
         let mut t = task.lock();
         t.blocked = normalize_signal_mask(ucontext.uc_sigmask);
+        // 处理函数打断的系统调用已返回 EINTR，其重启块作废
+        t.restart_block = None;
     }
 
     <TrapFrame as HwTrapFrame>::restore_from_mcontext(tf, &ucontext.uc_mcontext);
@@ -256,6 +272,19 @@ pub fn rt_sigreturn() -> ! {
     unreachable!("rt_sigreturn should not return");
 }
 
+/// 继续被信号打断、以 ERESTART_RESTARTBLOCK 返回的系统调用
+///
+/// 由内核在返回用户态前改写系统调用号发起，用户态不应直接调用。
+/// # 返回值：
+/// * 按任务保存的重启块执行剩余的工作，没有重启块时返回 -EINTR
+pub fn restart_syscall() -> c_int {
+    let block = current_task().lock().restart_block.take();
+    match block {
+        Some(RestartBlock::Nanosleep { deadline, rem }) => nanosleep_restart(deadline, rem),
+        None => -EINTR,
+    }
+}
+
 /// 设置或获取备用信号处理栈的信息
 /// # 参数：
 /// * `uss` - 指向用户空间缓冲区的指针，包含新的信号栈信息（如果不为 NULL）
@@ -304,6 +333,11 @@ pub fn kill(pid: c_int, sig: c_int) -> c_int {
     if sig < 0 || sig as usize > NSIG {
         return -EINVAL;
     }
+    let (sender_pid, sender) = {
+        let current = current_task();
+        let t = current.lock();
+        (t.pid, t.credential)
+    };
     let task_manager = TASK_MANAGER.lock();
     let target_tasks: Vec<SharedTask> = match pid {
         0 => {
@@ -337,10 +371,17 @@ pub fn kill(pid: c_int, sig: c_int) -> c_int {
         return -ESRCH;
     }
 
+    // 没有权限的目标被跳过；一个都发不出去时返回 EPERM
+    let mut sent = false;
     for task in target_tasks {
-        task_manager.send_signal(task, sig as usize);
+        if !sender.can_signal(&task.lock().credential) {
+            continue;
+        }
+        let info = QueuedSignal::new(sig as usize, SI_USER, sender_pid, sender.uid);
+        let _ = task_manager.send_signal_info(task, info);
+        sent = true;
     }
-    0
+    if sent { 0 } else { -EPERM }
 }
 
 /// 向线程组 tgid 中线程 ID 为 tid 的线程发送信号 sig.
@@ -355,7 +396,11 @@ pub fn tkill(tid: c_int, sig: c_int) -> c_int {
     if tid <= 0 || sig < 0 || sig as usize > NSIG {
         return -EINVAL;
     }
-    let current_pid = current_task().lock().pid;
+    let (current_pid, sender) = {
+        let current = current_task();
+        let t = current.lock();
+        (t.pid, t.credential)
+    };
     let task_manager = TASK_MANAGER.lock();
     let task = if let Some(task) = task_manager.get_task(tid as u32) {
         task
@@ -365,7 +410,11 @@ pub fn tkill(tid: c_int, sig: c_int) -> c_int {
     if task.lock().pid != current_pid {
         return -EINVAL;
     }
-    task_manager.send_signal(task, sig as usize);
+    if !sender.can_signal(&task.lock().credential) {
+        return -EPERM;
+    }
+    let info = QueuedSignal::new(sig as usize, SI_TKILL, current_pid, sender.uid);
+    let _ = task_manager.send_signal_info(task, info);
     0
 }
 
@@ -381,6 +430,11 @@ pub fn tgkill(tgid: c_int, tid: c_int, sig: c_int) -> c_int {
     if tgid <= 0 || tid <= 0 || sig < 0 || sig as usize > NSIG {
         return -EINVAL;
     }
+    let (sender_pid, sender) = {
+        let current = current_task();
+        let t = current.lock();
+        (t.pid, t.credential)
+    };
     let task_manager = TASK_MANAGER.lock();
     let task = if let Some(task) = task_manager.get_task(tid as u32) {
         task
@@ -390,39 +444,82 @@ pub fn tgkill(tgid: c_int, tid: c_int, sig: c_int) -> c_int {
     if task.lock().pid != tgid as u32 {
         return -EINVAL;
     }
-    task_manager.send_signal(task, sig as usize);
+    if !sender.can_signal(&task.lock().credential) {
+        return -EPERM;
+    }
+    let info = QueuedSignal::new(sig as usize, SI_TKILL, sender_pid, sender.uid);
+    let _ = task_manager.send_signal_info(task, info);
     0
 }
 
+/// 将带附加值的信号排队发送给进程
+/// # 参数：
+/// * `pid` - 目标进程的 ID
+/// * `sig` - 要发送的信号编号，为 0 时只做存在性和权限检查
+/// * `uinfo` - 指向用户空间的 siginfo_t，提供 si_code 和 si_value
+/// # 返回值：
+/// * 成功时返回 0
+/// * 失败时返回负的错误码；实时信号队列已满时返回 EAGAIN
+pub fn rt_sigqueueinfo(pid: c_int, sig: c_int, uinfo: *const SigInfoT) -> c_int {
+    if pid <= 0 || sig < 0 || sig as usize > NSIG {
+        return -EINVAL;
+    }
+    if !validate_user_ptr(uinfo) {
+        return -EFAULT;
+    }
+    let uinfo = unsafe { read_from_user(uinfo) };
+    let (sender_pid, sender) = {
+        let current = current_task();
+        let t = current.lock();
+        (t.pid, t.credential)
+    };
+    // 不允许伪造 kill/tkill 或内核产生的信号，除非发给自己
+    if (uinfo.si_code >= 0 || uinfo.si_code == SI_TKILL) && pid as u32 != sender_pid {
+        return -EPERM;
+    }
+
+    let task = {
+        let task_manager = TASK_MANAGER.lock();
+        match task_manager.get_task(pid as u32) {
+            Some(task) if task.lock().is_process() => task,
+            _ => return -ESRCH,
+        }
+    };
+    if !sender.can_signal(&task.lock().credential) {
+        return -EPERM;
+    }
+    if sig == 0 {
+        return 0;
+    }
+
+    let mut info = QueuedSignal::new(sig as usize, uinfo.si_code, sender_pid, sender.uid);
+    info.value = unsafe { uinfo.__si_fields.__si_common.__second.si_value.sival_ptr } as usize;
+    match send_signal_process_info(&task, info) {
+        Ok(()) => 0,
+        Err(e) => -e,
+    }
+}
+
 /// 在任务中等待指定信号的到来
 /// # 参数
 /// * `task` - 任务引用
 /// * `signal` - 要等待的信号集合
 /// * `timeout` - 可选的超时时间
 /// # 返回值
-/// * 成功时返回收到的信号编号及其信息
+/// * 成功时返回收到的信号实例
 /// * 失败时返回负的错误码
 fn wait_for_signal(
     task: SharedTask,
     signal: SignalFlags,
     timeout: Option<TimeSpec>,
-) -> Result<(usize, SigInfoT), i32> {
+) -> Result<QueuedSignal, i32> {
     if let Some(timeout) = timeout {
         if timeout.tv_sec < 0 || timeout.tv_nsec < 0 || timeout.tv_nsec >= 1_000_000_000 {
             return Err(-EINVAL);
         }
         if timeout.tv_sec == 0 && timeout.tv_nsec == 0 {
             // 轮询, 不阻塞
-            let mut t = task.lock();
-            if t.pending.has_deliverable_signal(signal)
-                || t.shared_pending.lock().has_deliverable_signal(signal)
-            {
-                let flag = take_first_deliverable(&mut t, signal).unwrap();
-                let sig_num = flag.to_signal_number();
-                Ok((sig_num, create_siginfo_for_signal(flag)))
-            } else {
-                Err(-EAGAIN)
-            }
+            take_first_target(&mut task.lock(), signal).ok_or(-EAGAIN)
         } else {
             // 带超时的阻塞等待
             let start = TimeSpec::now();
@@ -432,9 +529,7 @@ fn wait_for_signal(
                     return Err(-EAGAIN); // 超时返回
                 }
                 let slept = sleep_task_prepare(task.clone(), true, |t| {
-                    if t.pending.has_deliverable_signal(signal)
-                        || t.shared_pending.lock().has_deliverable_signal(signal)
-                    {
+                    if has_target_signal(t, signal) {
                         return true; // 信号已到达，不睡眠
                     }
                     TIMER_QUEUE
@@ -444,10 +539,7 @@ fn wait_for_signal(
                 });
                 if !slept {
                     TIMER_QUEUE.lock().remove_task(&task);
-                    let mut t = task.lock();
-                    let flag = take_first_deliverable(&mut t, signal).unwrap();
-                    let sig_num = flag.to_signal_number();
-                    return Ok((sig_num, create_siginfo_for_signal(flag)));
+                    return Ok(take_first_target(&mut task.lock(), signal).unwrap());
                 }
                 yield_task();
             }
@@ -456,46 +548,51 @@ fn wait_for_signal(
         // 阻塞等待
         loop {
             let slept = sleep_task_prepare(task.clone(), true, |t| {
-                t.pending
-                    .first_target_signal(signal)
-                    .or_else(|| t.shared_pending.lock().first_target_signal(signal))
-                    .is_some() // 信号已到达则不睡眠
+                has_target_signal(t, signal) // 信号已到达则不睡眠
             });
             if !slept {
-                let mut t = task.lock();
-                let flag = take_first_target(&mut t, signal).unwrap();
-                let sig_num = flag.to_signal_number();
-                return Ok((sig_num, create_siginfo_for_signal(flag)));
+                return Ok(take_first_target(&mut task.lock(), signal).unwrap());
             }
             yield_task();
         }
     }
 }
 
-fn take_first_deliverable(
-    t: &mut crate::kernel::task::TaskStruct,
-    signal: SignalFlags,
-) -> Option<SignalFlags> {
-    if let Some(flag) = t.pending.first_deliverable_signal(signal) {
-        t.pending.signals.remove(flag);
-        return Some(flag);
-    }
-    let mut shared = t.shared_pending.lock();
-    let flag = shared.first_deliverable_signal(signal)?;
-    shared.signals.remove(flag);
-    Some(flag)
+fn has_target_signal(t: &crate::kernel::task::TaskStruct, signal: SignalFlags) -> bool {
+    t.pending.first_target_signal(signal).is_some()
+        || t.shared_pending
+            .lock()
+            .first_target_signal(signal)
+            .is_some()
 }
 
+/// 取出 `signal` 中编号最小的挂起信号，同一信号的多个实例按到达顺序取出
 fn take_first_target(
     t: &mut crate::kernel::task::TaskStruct,
     signal: SignalFlags,
-) -> Option<SignalFlags> {
+) -> Option<QueuedSignal> {
     if let Some(flag) = t.pending.first_target_signal(signal) {
-        t.pending.signals.remove(flag);
-        return Some(flag);
+        return Some(t.pending.dequeue(flag));
     }
     let mut shared = t.shared_pending.lock();
     let flag = shared.first_target_signal(signal)?;
-    shared.signals.remove(flag);
-    Some(flag)
+    Some(shared.dequeue(flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        arch::{ArchImpl, virtual_memory::VirtualMemory},
+        kassert, test_case,
+    };
+
+    // 无效的 siginfo 指针在读取前返回 EFAULT
+    test_case!(test_rt_sigqueueinfo_bad_pointer, {
+        kassert!(rt_sigqueueinfo(1, 10, core::ptr::null()) == -EFAULT);
+        let kernel_ptr = (<ArchImpl as VirtualMemory>::USER_TOP + 1) as *const SigInfoT;
+        kassert!(rt_sigqueueinfo(1, 10, kernel_ptr) == -EFAULT);
+        // 参数检查仍先于指针检查
+        kassert!(rt_sigqueueinfo(0, 10, core::ptr::null()) == -EINVAL);
+    });
 }
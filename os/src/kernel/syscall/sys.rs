@@ -1,40 +1,40 @@
 //! 系统相关系统调用实现
 
-use crate::arch::{Arch, address::UA};
-use core::{
-    ffi::{c_char, c_int, c_long, c_uint, c_ulong, c_void},
-    sync::atomic::Ordering,
-};
+use crate::arch::{Arch, ArchImpl, address::UA};
+use core::ffi::{c_char, c_int, c_long, c_uint, c_ulong, c_void};
 
 use crate::{
     arch::{
-        lib::{restart, shutdown},
-        timer::{TICKS_PER_SEC, TIMER_TICKS, clock_freq, get_time},
+        lib::{halt, restart, shutdown},
+        timer::{TICKS_PER_SEC, clock_freq, get_time},
     },
     kernel::{
-        current_task,
-        syscall::util::{check_syslog_permission, validate_syslog_args},
-        time::update_realtime,
+        current_task, kstat,
+        loadavg::FSHIFT,
+        syscall::util::{check_syslog_permission, flush_all_block_devices, validate_syslog_args},
+        task::Capabilities,
+        time::{SYS_TZ, boottime_now, do_adjtimex, set_sys_tz, update_realtime},
     },
     log::{
         DEFAULT_CONSOLE_LEVEL, LogLevel, format_log_entry, get_console_level, read_log,
         set_console_level,
     },
-    security::{BiogasPoll, EntropyPool},
+    security::get_random_bytes,
     uapi::{
-        errno::{EFAULT, EINVAL, ENOSYS},
+        errno::{EFAULT, EINVAL, ENOSYS, EPERM},
         log::SyslogAction,
         reboot::{
             REBOOT_CMD_CAD_OFF, REBOOT_CMD_CAD_ON, REBOOT_CMD_HALT, REBOOT_CMD_POWER_OFF,
-            REBOOT_CMD_RESTART, REBOOT_MAGIC1, REBOOT_MAGIC2, REBOOT_MAGIC2A, REBOOT_MAGIC2B,
-            REBOOT_MAGIC2C,
+            REBOOT_CMD_RESTART, REBOOT_CMD_RESTART2, REBOOT_MAGIC1, REBOOT_MAGIC2, REBOOT_MAGIC2A,
+            REBOOT_MAGIC2B, REBOOT_MAGIC2C,
         },
-        sysinfo::SysInfo,
+        sysinfo::{SI_LOAD_SHIFT, SysInfo},
         time::{
-            Tms,
+            Timex, Tms,
+            adjtimex::ADJ_OFFSET_SS_READ,
             clock_id::{
-                CLOCK_MONOTONIC, CLOCK_MONOTONIC_COARSE, CLOCK_MONOTONIC_RAW, CLOCK_REALTIME,
-                CLOCK_REALTIME_COARSE, MAX_CLOCKS,
+                CLOCK_BOOTTIME, CLOCK_MONOTONIC, CLOCK_MONOTONIC_COARSE, CLOCK_MONOTONIC_RAW,
+                CLOCK_REALTIME, CLOCK_REALTIME_COARSE, MAX_CLOCKS,
             },
             timeval, timezone,
         },
@@ -43,9 +43,11 @@ use crate::{
     },
     util::{
         cstr_copy,
-        user_buffer::{UserBuffer, write_to_user},
+        user_buffer::{
+            UserBuffer, read_from_user, validate_user_ptr, validate_user_ptr_mut, write_to_user,
+        },
     },
-    vfs::TimeSpec,
+    vfs::{MOUNT_TABLE, TimeSpec},
 };
 
 /// 重启系统调用
@@ -53,11 +55,18 @@ use crate::{
 /// - `magic`: 第一个魔数，必须为 REBOOT_MAGIC1
 /// - `magic2`: 第二个魔数，必须为 REBOOT_MAGIC2 或 REBOOT_MAGIC2A/B/C
 /// - `op`: 重启操作码，指定重启类型
-/// - `arg`: 可选参数，取决于操作码
+/// - `arg`: 可选参数，取决于操作码；RESTART2 时为用户空间的重启命令字符串
 /// # 返回值
 /// 成功返回 0，失败返回负错误码
-/// 对于重启或关机操作，函数不会返回
-pub fn reboot(magic: c_int, magic2: c_int, op: c_int, _arg: *mut c_void) -> c_int {
+/// 对于重启、停机或关机操作，函数不会返回；没有 `CAP_SYS_BOOT` 时返回 EPERM
+/// # 说明
+/// - RESTART / RESTART2：冷重启（SBI SRST ColdReboot / LoongArch GED reset）
+/// - POWER_OFF：切断电源（SBI SRST Shutdown / LoongArch GED poweroff），
+///   若 /proc/test-result 记录了失败则以失败原因关机
+/// - HALT：停止所有 CPU 但保持上电
+///
+/// 三者执行前都会写回所有已挂载的文件系统并刷新块设备写缓存。
+pub fn reboot(magic: c_int, magic2: c_int, op: c_int, arg: *mut c_void) -> c_int {
     if magic as u32 != REBOOT_MAGIC1 {
         return -EINVAL;
     }
@@ -68,14 +77,77 @@ pub fn reboot(magic: c_int, magic2: c_int, op: c_int, _arg: *mut c_void) -> c_in
     {
         return -EINVAL;
     }
+    if !current_task()
+        .lock()
+        .credential
+        .capabilities
+        .has(Capabilities::SYS_BOOT)
+    {
+        return -EPERM;
+    }
     match op as u32 {
         REBOOT_CMD_CAD_OFF | REBOOT_CMD_CAD_ON => 0,
-        REBOOT_CMD_RESTART => restart(),
-        REBOOT_CMD_HALT | REBOOT_CMD_POWER_OFF => shutdown(false),
+        REBOOT_CMD_RESTART => {
+            reboot_prepare();
+            crate::pr_emerg!("reboot: Restarting system");
+            restart()
+        }
+        REBOOT_CMD_RESTART2 => {
+            let mut cmd = [0u8; REBOOT_CMD_MAX];
+            // SAFETY: copy_strn_from_user 校验用户地址，越界访问返回错误
+            let len = match unsafe {
+                ArchImpl::copy_strn_from_user(
+                    UA::from_usize(arg as usize),
+                    cmd.as_mut_ptr(),
+                    cmd.len(),
+                )
+            } {
+                Ok(len) => len.min(cmd.len() - 1),
+                Err(_) => return -EFAULT,
+            };
+            reboot_prepare();
+            crate::pr_emerg!(
+                "reboot: Restarting system with command '{}'",
+                core::str::from_utf8(&cmd[..len]).unwrap_or("?")
+            );
+            restart()
+        }
+        REBOOT_CMD_HALT => {
+            reboot_prepare();
+            crate::pr_emerg!("reboot: System halted");
+            halt()
+        }
+        REBOOT_CMD_POWER_OFF => {
+            reboot_prepare();
+            let failed = crate::test::user_result::failed();
+            if failed {
+                crate::pr_emerg!("reboot: Power down (user tests failed)");
+            } else {
+                crate::pr_emerg!("reboot: Power down");
+            }
+            shutdown(failed)
+        }
         _ => -EINVAL,
     }
 }
 
+/// RESTART2 命令字符串的最大长度（含结尾 NUL），与 Linux 一致
+const REBOOT_CMD_MAX: usize = 256;
+
+/// 重启、停机或关机前的准备：写回所有已挂载的文件系统，再刷新块设备写缓存
+///
+/// 失败只记录日志，不阻止后续操作。
+fn reboot_prepare() {
+    for (path, mount) in MOUNT_TABLE.list_all() {
+        if let Err(e) = mount.fs.sync() {
+            crate::pr_err!("reboot: failed to sync {}: {:?}", path, e);
+        }
+    }
+    if flush_all_block_devices().is_err() {
+        crate::pr_err!("reboot: failed to flush block devices");
+    }
+}
+
 /// 获取系统信息系统调用
 /// # 参数
 /// - `buf`: 指向用户空间缓冲区的指针，用于存储系统信息
@@ -92,7 +164,7 @@ pub fn uname(buf: *mut UtsNamespace) -> c_int {
         write_to_user(buf, uts_lock.clone());
     }
     0
-    // TODO: EPERM 和 EFAULT
+    // TODO: EFAULT
 }
 
 /// 设置主机名系统调用
@@ -108,6 +180,9 @@ pub fn set_hostname(name: *const c_char, len: usize) -> c_int {
     let uts = {
         let task = current_task();
         let t = task.lock();
+        if !t.credential.capabilities.has(Capabilities::SYS_ADMIN) {
+            return -EPERM;
+        }
         t.uts_namespace.clone()
     };
     let name_buf = UserBuffer::new(name as *mut _, len);
@@ -127,16 +202,46 @@ pub fn set_hostname(name: *const c_char, len: usize) -> c_int {
 /// * **成功**：返回 0，`info` 被填充系统信息
 /// * **失败**：返回负的 errno
 pub fn sysinfo(info: *mut SysInfo) -> c_int {
-    // TODO: 填充更多系统信息字段
+    // 与 /proc/meminfo、/proc/loadavg 取自同一个指标注册表
+    let stats = kstat::snapshot();
     let mut sys_info = SysInfo::new();
-    sys_info.uptime = (TIMER_TICKS.load(Ordering::SeqCst) / TICKS_PER_SEC) as c_ulong;
+    sys_info.uptime = (stats.get("uptime_ms") / 1000) as c_ulong;
+    // 定点平均负载换算为 sysinfo 使用的 16 位小数
+    let load = |name| (stats.get(name) << (SI_LOAD_SHIFT - FSHIFT)) as c_ulong;
+    sys_info.loads = [load("loadavg_1"), load("loadavg_5"), load("loadavg_15")];
+    sys_info.totalram = stats.bytes("nr_total_pages") as c_ulong;
+    sys_info.freeram = stats.bytes("nr_free_pages") as c_ulong;
+    sys_info.totalswap = stats.bytes("nr_swap_pages") as c_ulong;
+    sys_info.freeswap = stats.bytes("nr_swap_free_pages") as c_ulong;
+    sys_info.procs = stats.get("nr_threads").min(u16::MAX as u64) as u16;
+    sys_info.mem_unit = 1;
     unsafe {
         write_to_user(info, sys_info);
     }
     0
 }
 
+/// 读取指定时钟的当前时间
+///
+/// 只读计时器和 REALTIME 顺序锁保护的偏移量，不获取锁也不分配内存。
+/// # 返回值
+/// * **成功**：当前时间
+/// * **失败**：errno（未实现的时钟为 ENOSYS，无效时钟为 EINVAL）
+pub(super) fn read_clock(clk_id: c_int) -> Result<TimeSpec, c_int> {
+    match clk_id {
+        CLOCK_REALTIME | CLOCK_REALTIME_COARSE => Ok(TimeSpec::now()),
+        CLOCK_MONOTONIC | CLOCK_MONOTONIC_COARSE | CLOCK_MONOTONIC_RAW => {
+            Ok(TimeSpec::monotonic_now())
+        }
+        CLOCK_BOOTTIME => Ok(boottime_now()),
+        id if id < MAX_CLOCKS as c_int && id >= 0 => Err(ENOSYS),
+        _ => Err(EINVAL),
+    }
+}
+
 /// 获取指定时钟的时间系统调用
+///
+/// 时钟读取见 [`read_clock`]；写回用户空间时仍需在地址空间锁下校验目标页。
 /// # 参数
 /// * `clk_id` - 时钟 ID（如 CLOCK_REALTIME）
 /// * `tp` - 指向用户空间 TimeSpec 结构体的指针，用于存储时间
@@ -144,15 +249,9 @@ pub fn sysinfo(info: *mut SysInfo) -> c_int {
 /// * **成功**：返回 0，`tp` 被填充当前时间
 /// * **失败**：返回负的 errno
 pub fn clock_gettime(clk_id: c_int, tp: *mut TimeSpec) -> c_int {
-    let ts = match clk_id {
-        CLOCK_REALTIME | CLOCK_REALTIME_COARSE => TimeSpec::now(),
-        CLOCK_MONOTONIC | CLOCK_MONOTONIC_COARSE | CLOCK_MONOTONIC_RAW => TimeSpec::monotonic_now(),
-        id if id < MAX_CLOCKS as c_int && id >= 0 => {
-            return -ENOSYS;
-        }
-        _ => {
-            return -EINVAL;
-        }
+    let ts = match read_clock(clk_id) {
+        Ok(ts) => ts,
+        Err(errno) => return -errno,
     };
 
     unsafe {
@@ -167,17 +266,31 @@ pub fn clock_gettime(clk_id: c_int, tp: *mut TimeSpec) -> c_int {
 /// * `clk_id` - 时钟 ID（如 CLOCK_REALTIME）
 /// * `tp` - 指向用户空间 TimeSpec 结构体的指针，包含要设置的时间
 /// # 返回值
-/// * **成功**：返回 0，时钟时间被更新
+/// * **成功**：返回 0，时钟时间被更新（CLOCK_REALTIME 同时写回 RTC）
 /// * **失败**：返回负的 errno
 pub fn clock_settime(clk_id: c_int, tp: *const TimeSpec) -> c_int {
     match clk_id {
-        CLOCK_REALTIME | CLOCK_REALTIME_COARSE => {
-            let ts: TimeSpec = unsafe { core::ptr::read(tp) };
+        CLOCK_REALTIME => {
+            if !validate_user_ptr(tp) {
+                return -EFAULT;
+            }
+            let ts: TimeSpec = read_from_user(tp);
+            if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+                return -EINVAL;
+            }
+            let cred = current_task().lock().credential;
+            if !cred.capabilities.has(Capabilities::SYS_TIME) {
+                return -EPERM;
+            }
             update_realtime(&ts);
             0
         }
-        CLOCK_MONOTONIC | CLOCK_MONOTONIC_COARSE | CLOCK_MONOTONIC_RAW => {
-            // 单调时钟不可设置
+        CLOCK_REALTIME_COARSE
+        | CLOCK_MONOTONIC
+        | CLOCK_MONOTONIC_COARSE
+        | CLOCK_MONOTONIC_RAW
+        | CLOCK_BOOTTIME => {
+            // 粗粒度时钟和单调时钟不可设置
             -EINVAL
         }
         id if id < MAX_CLOCKS as c_int && id >= 0 => -ENOSYS,
@@ -199,10 +312,12 @@ pub fn clock_getres(clk_id: c_int, tp: *mut TimeSpec) -> c_int {
             tv_sec: 0,
             tv_nsec: nsec,
         },
-        CLOCK_MONOTONIC | CLOCK_MONOTONIC_COARSE | CLOCK_MONOTONIC_RAW => TimeSpec {
-            tv_sec: 0,
-            tv_nsec: nsec,
-        },
+        CLOCK_MONOTONIC | CLOCK_MONOTONIC_COARSE | CLOCK_MONOTONIC_RAW | CLOCK_BOOTTIME => {
+            TimeSpec {
+                tv_sec: 0,
+                tv_nsec: nsec,
+            }
+        }
         id if id < MAX_CLOCKS as c_int && id >= 0 => {
             return -ENOSYS;
         }
@@ -218,16 +333,85 @@ pub fn clock_getres(clk_id: c_int, tp: *mut TimeSpec) -> c_int {
     0
 }
 
-/// 获取当前墙上时间。
+/// 调整内核时钟系统调用
+/// # 参数
+/// * `tx` - 指向用户空间 Timex 结构体的指针，`modes` 指定要修改的字段
+/// # 返回值
+/// * **成功**：返回时钟状态（TIME_OK / TIME_ERROR），`tx` 被填充当前参数
+/// * **失败**：返回负的 errno
+pub fn adjtimex(tx: *mut Timex) -> c_int {
+    clock_adjtime(CLOCK_REALTIME, tx)
+}
+
+/// 调整指定时钟系统调用，目前只支持 CLOCK_REALTIME
+/// # 参数
+/// * `clk_id` - 时钟 ID
+/// * `tx` - 指向用户空间 Timex 结构体的指针
+/// # 返回值
+/// * **成功**：返回时钟状态（TIME_OK / TIME_ERROR）
+/// * **失败**：返回负的 errno
+pub fn clock_adjtime(clk_id: c_int, tx: *mut Timex) -> c_int {
+    if clk_id != CLOCK_REALTIME {
+        return -EINVAL;
+    }
+    // 先读后写，两个方向都要检查
+    if !validate_user_ptr(tx) || !validate_user_ptr_mut(tx) {
+        return -EFAULT;
+    }
+    let mut timex: Timex = read_from_user(tx);
+    // 只读查询不需要权限
+    if timex.modes != 0 && timex.modes != ADJ_OFFSET_SS_READ {
+        let cred = current_task().lock().credential;
+        if !cred.capabilities.has(Capabilities::SYS_TIME) {
+            return -EPERM;
+        }
+    }
+    match do_adjtimex(&mut timex) {
+        Ok(state) => {
+            write_to_user(tx, timex);
+            state
+        }
+        Err(errno) => -errno,
+    }
+}
+
+/// 获取当前墙上时间（UTC）和 settimeofday 保存的时区。
 pub fn gettimeofday(tv: *mut timeval, tz: *mut timezone) -> c_int {
     if !tv.is_null() {
         write_to_user(tv, TimeSpec::now().to_timeval());
     }
     if !tz.is_null() {
-        write_to_user(tz, timezone {
-            tz_minuteswest: 0,
-            tz_dsttime: 0,
-        });
+        write_to_user(tz, *SYS_TZ.lock());
+    }
+    0
+}
+
+/// 设置墙上时间和时区，需要 `CAP_SYS_TIME`
+///
+/// `tv` 是 UTC 时间，与 `clock_settime(CLOCK_REALTIME)` 相同；`tz` 只被保存并由
+/// gettimeofday 返回，内核不按时区换算任何时间，也不把 RTC 当作本地时间。
+pub fn settimeofday(tv: *const timeval, tz: *const timezone) -> c_int {
+    if (!tv.is_null() && !validate_user_ptr(tv)) || (!tz.is_null() && !validate_user_ptr(tz)) {
+        return -EFAULT;
+    }
+    let tv = (!tv.is_null()).then(|| read_from_user(tv));
+    let tz = (!tz.is_null()).then(|| read_from_user(tz));
+    if let Some(tv) = tv
+        && (tv.tv_sec < 0 || !(0..1_000_000).contains(&tv.tv_usec))
+    {
+        return -EINVAL;
+    }
+    let cred = current_task().lock().credential;
+    if !cred.capabilities.has(Capabilities::SYS_TIME) {
+        return -EPERM;
+    }
+    if let Some(tz) = tz
+        && let Err(errno) = set_sys_tz(tz)
+    {
+        return -errno;
+    }
+    if let Some(tv) = tv {
+        update_realtime(&tv.to_timespec());
     }
     0
 }
@@ -474,14 +658,13 @@ pub fn getrandom(buf: *mut c_void, len: SizeT, _flags: c_uint) -> c_int {
         return -EINVAL;
     }
 
-    let mut pool = BiogasPoll::new();
     let len = len as usize;
     let mut done = 0usize;
     let mut chunk = [0u8; 64];
 
     while done < len {
         let take = core::cmp::min(chunk.len(), len - done);
-        if pool.try_fill(&mut chunk[..take]).is_err() {
+        if get_random_bytes(&mut chunk[..take]).is_err() {
             return -EINVAL;
         }
         let dst = match (buf as usize).checked_add(done) {
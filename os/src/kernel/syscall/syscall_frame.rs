@@ -13,4 +13,11 @@ pub trait SyscallFrame {
     fn arg4(&self) -> usize;
     fn arg5(&self) -> usize;
     fn set_ret(&mut self, val: usize);
+
+    /// 进入系统调用：保存原始 arg0 并标记正处于系统调用中
+    fn enter_syscall(&mut self);
+    /// 离开系统调用：若当前正从系统调用返回，清除标记并返回系统调用的返回值
+    fn leave_syscall(&mut self) -> Option<usize>;
+    /// 回退到系统调用指令并恢复原始 arg0，返回用户态后重新发起 `syscall_id` 号系统调用
+    fn restart(&mut self, syscall_id: usize);
 }
@@ -47,11 +47,16 @@ pub fn clone(
         uts,
         rlimit,
         exe_path,
+        comm,
         sched_policy,
         sched_priority,
         sched_reset_on_fork,
+        vruntime,
+        timer_slack_ns,
+        io_priority,
         oom_score_adj,
         cpu_affinity,
+        credential,
         shm_attachments,
     ) = {
         let _guard = crate::sync::PreemptGuard::new();
@@ -74,11 +79,16 @@ pub fn clone(
             task.uts_namespace.clone(),
             task.rlimit.clone(),
             task.exe_path.clone(),
+            task.comm,
             task.sched_policy,
             task.sched_priority,
             task.sched_reset_on_fork,
+            task.vruntime,
+            task.timer_slack_ns,
+            task.io_priority,
             task.oom_score_adj,
             task.cpu_affinity,
+            task.credential,
             if requested_flags.contains(CloneFlags::THREAD) {
                 task.shm_attachments.clone()
             } else {
@@ -104,11 +114,7 @@ pub fn clone(
     } else {
         Arc::new(fd_table.clone_table())
     };
-    let fs = if requested_flags.contains(CloneFlags::FS) {
-        fs
-    } else {
-        Arc::new(SpinLock::new(fs.lock().clone()))
-    };
+    let fs = FsStruct::clone_for_child(&fs, requested_flags.contains(CloneFlags::FS));
     let ppid = if requested_flags.contains(CloneFlags::PARENT) {
         c_ppid
     } else {
@@ -151,6 +157,7 @@ pub fn clone(
         fs,
     );
     child_task.exe_path = exe_path;
+    child_task.comm = comm;
     if sched_reset_on_fork {
         child_task.sched_policy = crate::uapi::sched::SCHED_NORMAL;
         child_task.sched_priority = 0;
@@ -160,16 +167,29 @@ pub fn clone(
         child_task.sched_priority = sched_priority;
         child_task.sched_reset_on_fork = false;
     }
+    // 子任务从父任务的 vruntime 开始，不能靠 fork 获得比父任务更多的 CPU
+    child_task.vruntime = vruntime;
+    child_task.timer_slack_ns = timer_slack_ns;
+    child_task.default_timer_slack_ns = timer_slack_ns;
+    child_task.io_priority = io_priority;
     child_task.oom_score_adj = oom_score_adj;
     child_task.cpu_affinity = cpu_affinity & crate::kernel::online_cpu_mask();
     if child_task.cpu_affinity == 0 {
         child_task.cpu_affinity = crate::kernel::online_cpu_mask();
     }
+    child_task.credential = credential;
     child_task.shm_attachments = shm_attachments;
     if !requested_flags.contains(CloneFlags::THREAD) {
-        for attachment in child_task.shm_attachments.lock().values() {
+        let attachments = child_task.shm_attachments.clone();
+        let attachments = attachments.lock();
+        for attachment in attachments.values() {
             attachment.segment.mark_attached(pid as c_int);
         }
+        if !attachments.is_empty() {
+            child_task
+                .exit_notifiers
+                .register(&crate::kernel::SHM_EXIT_NOTIFIER);
+        }
     }
 
     if requested_flags.contains(CloneFlags::PARENT_SETTID) {
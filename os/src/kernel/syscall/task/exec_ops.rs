@@ -210,6 +210,9 @@ fn do_execve_prepare(
         Err(crate::kernel::task::ExecImageError::Paging(
             crate::mm::page_table::PagingError::OutOfMemory,
         )) => return Err(-ENOMEM),
+        Err(crate::kernel::task::ExecImageError::Paging(
+            crate::mm::page_table::PagingError::PermissionDenied,
+        )) => return Err(-EACCES),
         Err(_) => return Err(-ENOEXEC),
     };
 
@@ -246,6 +249,7 @@ fn do_execve_switch(
     let task = current_task();
 
     task.lock().fd_table.close_exec();
+    crate::kernel::task::exit_robust_list(&task);
     crate::kernel::task::detach_all_shm(task.clone());
 
     // 换掉当前任务的地址空间，e.g. 切换 satp
@@ -262,6 +266,7 @@ fn do_execve_switch(
         let envp_refs: Vec<&str> = envp.iter().map(|s| s.as_str()).collect();
 
         let mut t = task.lock();
+        t.set_exec_comm(&exe_path);
         t.exe_path = Some(exe_path);
         t.execve(
             space.clone(),
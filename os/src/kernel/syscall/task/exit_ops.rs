@@ -10,15 +10,16 @@ use super::*;
 /// # 参数
 /// - `code`: 退出代码
 pub fn exit(code: c_int) -> c_int {
-    // TODO: 处理 tid_addr 和 robust_list
     clear_child_tid_and_wake();
     let task = current_task();
-    // Linux 语义：退出时释放用户地址空间并关闭打开文件。
-    // 注意：必须先切换到内核页表，再释放当前进程页表资源，避免释放“正在使用的 satp”。
-    crate::kernel::task::cleanup_current_process_resources_on_exit();
     if task.lock().is_process() {
+        // Linux 语义：退出时调用退出回调链、释放用户地址空间并关闭打开文件。
+        // 注意：必须先切换到内核页表，再释放当前进程页表资源，避免释放“正在使用的 satp”。
+        crate::kernel::task::cleanup_current_process_resources_on_exit();
         exit_process(task, code & 0xFF);
     } else {
+        // 线程只回收自身注册的资源（如健壮列表），进程级资源随线程组一起回收。
+        crate::kernel::task::exit_notify(&task);
         TASK_MANAGER.lock().exit_task(task, code & 0xFF);
     }
     schedule();
@@ -36,7 +37,6 @@ pub fn exit(code: c_int) -> c_int {
 /// # 参数
 /// - `code`: 退出代码
 pub fn exit_group(code: c_int) -> ! {
-    // TODO: 处理 tid_addr 和 robust_list
     clear_child_tid_and_wake();
     let task = current_task();
     let leader = crate::kernel::task::task_group_leader(&task).unwrap_or(task);
@@ -72,12 +72,9 @@ fn clear_child_tid_and_wake() {
     }
 
     // 2) futex wake
-    let Some(paddr) = memory_space
-        .lock()
-        .translate(VA::from_usize(clear_addr.as_usize()))
-        .map(|p| p.as_usize())
-    else {
+    // 与 Linux 相同按不带 PRIVATE 标志的 futex 唤醒
+    let Some(key) = futex_key(&memory_space, clear_addr.as_usize(), false) else {
         return;
     };
-    FUTEX_MANAGER.lock().get_wait_queue(paddr).wake_up_all();
+    FUTEX_MANAGER.lock().get_wait_queue(key).wake_up_all();
 }
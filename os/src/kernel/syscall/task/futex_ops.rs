@@ -1,15 +1,17 @@
 use super::*;
-use crate::{arch::Arch, kernel::WaitQueue};
+use crate::{
+    arch::Arch,
+    kernel::{ROBUST_LIST_EXIT_NOTIFIER, WaitQueue, register_exit_notifier},
+};
 
-fn futex_paddr(uaddr: *mut u32) -> Result<usize, c_int> {
+fn futex_key_of(uaddr: *mut u32, private: bool) -> Result<FutexKey, c_int> {
+    if uaddr as usize % core::mem::align_of::<u32>() != 0 {
+        return Err(-EINVAL);
+    }
     let Some(memory_space) = current_task().lock().memory_space.clone() else {
         return Err(-EFAULT);
     };
-    memory_space
-        .lock()
-        .translate(VA::from_usize(uaddr as usize))
-        .map(|pa| pa.as_usize())
-        .ok_or(-EFAULT)
+    futex_key(&memory_space, uaddr as usize, private).ok_or(-EFAULT)
 }
 
 fn read_futex_word(uaddr: *mut u32) -> Result<u32, c_int> {
@@ -62,17 +64,18 @@ fn futex_wait_common(
     timeout: *const TimeSpec,
     absolute_timeout: bool,
     realtime: bool,
+    private: bool,
 ) -> c_int {
     let task = current_task();
-    let paddr = match futex_paddr(uaddr) {
-        Ok(paddr) => paddr,
-        Err(e) => return e,
-    };
-
+    // 先读取用户字，让尚未建立映射的共享页在查询物理地址前完成缺页
     let user_val = match read_futex_word(uaddr) {
         Ok(v) => v,
         Err(e) => return e,
     };
+    let key = match futex_key_of(uaddr, private) {
+        Ok(key) => key,
+        Err(e) => return e,
+    };
     if user_val != val {
         return -EAGAIN;
     }
@@ -87,16 +90,20 @@ fn futex_wait_common(
         return -ETIMEDOUT;
     }
 
+    // 没有超时的等待可以原样重启；带超时的等待重启后会重新计时，因此返回 EINTR
+    let interrupted = if trigger.is_none() {
+        -ERESTARTSYS
+    } else {
+        -EINTR
+    };
+
     {
         let mut fm = FUTEX_MANAGER.lock();
-        fm.get_wait_queue(paddr).add_task(task.clone());
-        let slept = sleep_task_prepare(task.clone(), true, |t| {
-            t.pending.has_deliverable_signal(t.blocked)
-                || t.shared_pending.lock().has_deliverable_signal(t.blocked)
-        });
+        fm.get_wait_queue(key).add_task(task.clone());
+        let slept = sleep_task_prepare(task.clone(), true, |t| sig_pending(t));
         if !slept {
-            fm.get_wait_queue(paddr).remove_task(&task);
-            return -EINTR;
+            fm.get_wait_queue(key).remove_task(&task);
+            return interrupted;
         }
     }
 
@@ -114,7 +121,7 @@ fn futex_wait_common(
 
     let still_waiting = {
         let mut fm = FUTEX_MANAGER.lock();
-        let waitq = fm.get_wait_queue(paddr);
+        let waitq = fm.get_wait_queue(key);
         let still_waiting = waitq.contains(&task);
         if still_waiting {
             waitq.remove_task(&task);
@@ -123,7 +130,7 @@ fn futex_wait_common(
     };
 
     if signal_pending(&task) {
-        return -EINTR;
+        return interrupted;
     }
 
     if trigger.is_some() && still_waiting && !timer_was_pending {
@@ -133,13 +140,13 @@ fn futex_wait_common(
     0
 }
 
-fn futex_wake_common(uaddr: *mut u32, val: u32) -> c_int {
-    let paddr = match futex_paddr(uaddr) {
-        Ok(paddr) => paddr,
+fn futex_wake_common(uaddr: *mut u32, val: u32, private: bool) -> c_int {
+    let key = match futex_key_of(uaddr, private) {
+        Ok(key) => key,
         Err(e) => return e,
     };
     let mut fm = FUTEX_MANAGER.lock();
-    let waitq = fm.get_wait_queue(paddr);
+    let waitq = fm.get_wait_queue(key);
     let mut wake_count = 0;
     for _ in 0..val {
         if waitq.is_empty() {
@@ -181,33 +188,33 @@ pub fn futex(
     uaddr2: *mut u32,
     val3: u32,
 ) -> c_int {
-    let _private = (op & FUTEX_PRIVATE as c_int) != 0; // TODO: 目前不区分 PRIVATE 和 SHARED
+    let private = (op & FUTEX_PRIVATE as c_int) != 0;
     let realtime = (op & FUTEX_CLOCK_REALTIME as c_int) != 0;
     let op = op & !(FUTEX_PRIVATE as c_int) & !(FUTEX_CLOCK_REALTIME as c_int);
     match op as u32 {
-        FUTEX_WAIT => futex_wait_common(uaddr, val, timeout, false, realtime),
+        FUTEX_WAIT => futex_wait_common(uaddr, val, timeout, false, realtime, private),
         FUTEX_WAIT_BITSET => {
             if val3 == 0 {
                 return -EINVAL;
             }
-            futex_wait_common(uaddr, val, timeout, true, realtime)
+            futex_wait_common(uaddr, val, timeout, true, realtime, private)
         }
         FUTEX_WAKE | FUTEX_WAKE_BITSET => {
             if op as u32 == FUTEX_WAKE_BITSET && val3 == 0 {
                 return -EINVAL;
             }
-            futex_wake_common(uaddr, val)
+            futex_wake_common(uaddr, val, private)
         }
         FUTEX_REQUEUE | FUTEX_CMP_REQUEUE => {
             if uaddr2.is_null() {
                 return -EFAULT;
             }
-            let paddr1 = match futex_paddr(uaddr) {
-                Ok(paddr) => paddr,
+            let key1 = match futex_key_of(uaddr, private) {
+                Ok(key) => key,
                 Err(e) => return e,
             };
-            let paddr2 = match futex_paddr(uaddr2) {
-                Ok(paddr) => paddr,
+            let key2 = match futex_key_of(uaddr2, private) {
+                Ok(key) => key,
                 Err(e) => return e,
             };
             let requeue_count = timeout as usize as u32;
@@ -221,8 +228,8 @@ pub fn futex(
             }
 
             let mut fm = FUTEX_MANAGER.lock();
-            if paddr1 == paddr2 {
-                let waitq = fm.get_wait_queue(paddr1);
+            if key1 == key2 {
+                let waitq = fm.get_wait_queue(key1);
                 let mut changed = 0;
                 for _ in 0..val {
                     if waitq.is_empty() {
@@ -234,8 +241,8 @@ pub fn futex(
                 return changed;
             }
 
-            let mut src = fm.take_wait_queue(paddr1);
-            let dst = fm.get_wait_queue(paddr2);
+            let mut src = fm.take_wait_queue(key1);
+            let dst = fm.get_wait_queue(key2);
             let mut changed = 0;
             for _ in 0..val {
                 if src.is_empty() {
@@ -245,7 +252,7 @@ pub fn futex(
                 changed += 1;
             }
             changed += requeue_waiters(&mut src, dst, requeue_count);
-            fm.put_wait_queue(paddr1, src);
+            fm.put_wait_queue(key1, src);
             changed
         }
         _ => -ENOSYS,
@@ -313,5 +320,6 @@ pub fn set_robust_list(head: *const RobustListHead, size: SizeT) -> c_int {
     }
     let task = current_task();
     task.lock().robust_list = Some(UA::from_usize(head as usize));
+    register_exit_notifier(&task, &ROBUST_LIST_EXIT_NOTIFIER);
     0
 }
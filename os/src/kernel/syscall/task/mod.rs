@@ -13,11 +13,12 @@ use crate::{
         address::UA,
         timer::{clock_freq, get_time},
     },
-    ipc::{SignalHandlerTable, SignalPending, signal_pending},
+    ipc::{RestartBlock, SignalHandlerTable, SignalPending, sig_pending, signal_pending},
     kernel::{
-        FUTEX_MANAGER, Scheduler, SharedTask, TASK_MANAGER, TIMER, TIMER_QUEUE, TaskExitStatus,
-        TaskManagerTrait, TaskState, TaskStruct, TimerEntry, current_cpu, current_task,
-        exit_process, schedule, sleep_task, sleep_task_prepare,
+        CpuClock, CpuItimer, FUTEX_MANAGER, FsStruct, FutexKey, ITIMER_EXIT_NOTIFIER, Scheduler,
+        SharedTask, TASK_MANAGER, TIMER, TIMER_QUEUE, TaskExitStatus, TaskManagerTrait, TaskState,
+        TaskStruct, TimerEntry, current_cpu, current_task, exit_process, futex_key, get_cpu_itimer,
+        register_exit_notifier, schedule, set_cpu_itimer, sleep_task_prepare,
         syscall::util::{get_args_safe, get_path_safe},
         time::realtime_now,
         yield_task,
@@ -31,7 +32,7 @@ use crate::{
     uapi::{
         errno::{
             EACCES, EAGAIN, EFAULT, EINTR, EINVAL, EIO, EISDIR, ENOENT, ENOEXEC, ENOMEM, ENOSYS,
-            EPERM, ESRCH, ETIMEDOUT,
+            EPERM, ERESTART_RESTARTBLOCK, ERESTARTNOHAND, ERESTARTSYS, ESRCH, ETIMEDOUT,
         },
         futex::{
             FUTEX_CLOCK_REALTIME, FUTEX_CMP_REQUEUE, FUTEX_PRIVATE, FUTEX_REQUEUE, FUTEX_WAIT,
@@ -39,7 +40,7 @@ use crate::{
         },
         resource::{RLIM_NLIMITS, ResourceId, Rlimit, Rusage},
         sched::CloneFlags,
-        signal::{NUM_SIGALRM, NUM_SIGPROF, NUM_SIGVTALRM},
+        signal::NUM_SIGALRM,
         time::{
             Itimerval, TimeSpec,
             clock_flags::TIMER_ABSTIME,
@@ -1,13 +1,22 @@
 use super::*;
+use crate::arch::{Arch, ArchImpl};
+use crate::kernel::task::TASK_COMM_LEN;
 use crate::uapi::errno::{EFAULT, EINVAL};
+use crate::uapi::prctl::{
+    PR_GET_NAME, PR_GET_PDEATHSIG, PR_GET_TIMERSLACK, PR_SET_NAME, PR_SET_PDEATHSIG,
+    PR_SET_TIMERSLACK,
+};
 use crate::uapi::resource::{RUSAGE_CHILDREN, RUSAGE_SELF, RUSAGE_THREAD, Rusage};
+use crate::uapi::signal::NSIG;
 use crate::util::user_buffer::validate_user_ptr_mut;
 
 /// 获取当前任务的进程 ID
+///
+/// 读取 per-CPU 缓存，不获取锁也不分配内存。
 /// # 返回值:
 /// - 进程 ID
 pub fn get_pid() -> c_int {
-    current_task().lock().pid as c_int
+    crate::kernel::current_pid() as c_int
 }
 
 /// 获取当前任务的父进程 ID
@@ -202,3 +211,77 @@ pub fn prlimit(
     0
     // TODO: EPERM, EPERM 和 EFAULT
 }
+
+/// 进程控制
+/// # 参数
+/// - `option`: 操作，支持 `PR_SET_NAME`、`PR_GET_NAME`、`PR_SET_PDEATHSIG`、`PR_GET_PDEATHSIG`、
+///   `PR_SET_TIMERSLACK`、`PR_GET_TIMERSLACK`
+/// - `arg2`: 名字缓冲区地址、信号编号、存放信号编号的 int 地址或 slack 纳秒数，依操作而定
+/// - 其余参数未使用
+/// # 返回值
+/// - 成功返回 0（`PR_GET_TIMERSLACK` 返回当前 slack），失败返回负错误码
+pub fn prctl(
+    option: c_int,
+    arg2: c_ulong,
+    _arg3: c_ulong,
+    _arg4: c_ulong,
+    _arg5: c_ulong,
+) -> c_int {
+    match option {
+        PR_SET_NAME => {
+            // 只读到 '\0' 为止，名字后面的内存可以不可访问；超长的名字截断到 15 字节
+            let mut name = [0u8; TASK_COMM_LEN];
+            // SAFETY: copy_strn_from_user 逐字节校验用户地址，越界访问返回错误
+            let len = match unsafe {
+                ArchImpl::copy_strn_from_user(
+                    UA::from_usize(arg2 as usize),
+                    name.as_mut_ptr(),
+                    TASK_COMM_LEN - 1,
+                )
+            } {
+                Ok(len) => len,
+                Err(_) => return -EFAULT,
+            };
+            current_task().lock().set_comm(&name[..len]);
+            0
+        }
+        PR_GET_NAME => {
+            let ptr = arg2 as *mut [u8; TASK_COMM_LEN];
+            if !validate_user_ptr_mut(ptr) {
+                return -EFAULT;
+            }
+            let name = current_task().lock().comm;
+            write_to_user(ptr, name);
+            0
+        }
+        PR_SET_PDEATHSIG => {
+            let sig = arg2 as usize;
+            if sig > NSIG {
+                return -EINVAL;
+            }
+            current_task().lock().pdeath_signal = sig as u8;
+            0
+        }
+        PR_GET_PDEATHSIG => {
+            let ptr = arg2 as *mut c_int;
+            if !validate_user_ptr_mut(ptr) {
+                return -EFAULT;
+            }
+            let sig = current_task().lock().pdeath_signal as c_int;
+            write_to_user(ptr, sig);
+            0
+        }
+        PR_SET_TIMERSLACK => {
+            let task = current_task();
+            let mut t = task.lock();
+            t.timer_slack_ns = if arg2 == 0 {
+                t.default_timer_slack_ns
+            } else {
+                arg2 as u64
+            };
+            0
+        }
+        PR_GET_TIMERSLACK => current_task().lock().timer_slack_ns.min(c_int::MAX as u64) as c_int,
+        _ => -EINVAL,
+    }
+}
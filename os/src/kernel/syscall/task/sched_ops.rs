@@ -4,6 +4,11 @@ use crate::{
     arch::{Arch, ArchImpl, address::UA},
     kernel::task::Capabilities,
     uapi::{
+        ioprio::{
+            IOPRIO_CLASS_BE, IOPRIO_CLASS_IDLE, IOPRIO_CLASS_NONE, IOPRIO_CLASS_RT,
+            IOPRIO_NR_LEVELS, IOPRIO_WHO_PGRP, IOPRIO_WHO_PROCESS, IOPRIO_WHO_USER,
+            ioprio_prio_class, ioprio_prio_level,
+        },
         resource::ResourceId,
         sched::{
             SCHED_BATCH, SCHED_DEADLINE, SCHED_EXT, SCHED_FIFO, SCHED_IDLE, SCHED_NORMAL,
@@ -241,3 +246,96 @@ pub fn sched_getaffinity(pid: c_int, cpusetsize: usize, mask: *mut u8) -> c_int
     }
     CPU_SET_BYTES as c_int
 }
+
+/// 检查 ioprio_set 的优先级值是否合法
+fn validate_ioprio(ioprio: c_int) -> Result<u16, c_int> {
+    if !(0..=u16::MAX as c_int).contains(&ioprio) {
+        return Err(EINVAL);
+    }
+    let ioprio = ioprio as u16;
+    let level = ioprio_prio_level(ioprio);
+    match ioprio_prio_class(ioprio) {
+        IOPRIO_CLASS_RT | IOPRIO_CLASS_BE if level < IOPRIO_NR_LEVELS => Ok(ioprio),
+        IOPRIO_CLASS_IDLE => Ok(ioprio),
+        IOPRIO_CLASS_NONE if level == 0 => Ok(ioprio),
+        _ => Err(EINVAL),
+    }
+}
+
+/// 按 `which`/`who` 找出 ioprio_set/ioprio_get 作用的任务
+fn ioprio_targets(which: c_int, who: c_int) -> Result<Vec<SharedTask>, c_int> {
+    if who < 0 {
+        return Err(EINVAL);
+    }
+    let (cur_pgid, cur_uid) = {
+        let cur = current_task();
+        let cur = cur.lock();
+        (cur.pgid, cur.credential.uid)
+    };
+    let tasks = match which {
+        IOPRIO_WHO_PROCESS => {
+            return get_target_task(who).map(|task| alloc::vec![task]);
+        }
+        IOPRIO_WHO_PGRP => {
+            let pgid = if who == 0 { cur_pgid } else { who as u32 };
+            TASK_MANAGER.lock().get_task_cond(|t| t.lock().pgid == pgid)
+        }
+        IOPRIO_WHO_USER => {
+            let uid = if who == 0 { cur_uid } else { who as u32 };
+            TASK_MANAGER
+                .lock()
+                .get_task_cond(|t| t.lock().credential.uid == uid)
+        }
+        _ => return Err(EINVAL),
+    };
+    if tasks.is_empty() {
+        return Err(ESRCH);
+    }
+    Ok(tasks)
+}
+
+pub fn ioprio_set(which: c_int, who: c_int, ioprio: c_int) -> c_int {
+    let ioprio = match validate_ioprio(ioprio) {
+        Ok(v) => v,
+        Err(errno) => return -errno,
+    };
+    let tasks = match ioprio_targets(which, who) {
+        Ok(tasks) => tasks,
+        Err(errno) => return -errno,
+    };
+
+    let cred = current_task().lock().credential;
+    let privileged = cred.capabilities.has(Capabilities::SYS_NICE)
+        || cred.capabilities.has(Capabilities::SYS_ADMIN);
+    if ioprio_prio_class(ioprio) == IOPRIO_CLASS_RT && !privileged {
+        return -EPERM;
+    }
+
+    for task in tasks {
+        let mut t = task.lock();
+        if !privileged && cred.euid != t.credential.euid && cred.euid != t.credential.uid {
+            return -EPERM;
+        }
+        t.io_priority = ioprio;
+    }
+    0
+}
+
+pub fn ioprio_get(which: c_int, who: c_int) -> c_int {
+    let tasks = match ioprio_targets(which, who) {
+        Ok(tasks) => tasks,
+        Err(errno) => return -errno,
+    };
+    // 多个任务时返回其中优先级最高的：类别值越小、级别越小越优先（NONE 视为最低）
+    tasks
+        .iter()
+        .map(|task| task.lock().io_priority)
+        .min_by_key(|&ioprio| {
+            let class = match ioprio_prio_class(ioprio) {
+                IOPRIO_CLASS_NONE => IOPRIO_CLASS_IDLE + 1,
+                class => class,
+            };
+            (class, ioprio_prio_level(ioprio))
+        })
+        .map_or(-ESRCH, |ioprio| ioprio as c_int)
+}
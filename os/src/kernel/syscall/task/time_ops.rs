@@ -1,4 +1,5 @@
 use super::*;
+use crate::util::user_buffer::{validate_user_ptr, validate_user_ptr_mut};
 
 /// 高精度睡眠（纳秒级别）
 /// # 参数
@@ -6,6 +7,7 @@ use super::*;
 /// - `rem`: 指向 TimeSpec 结构体的指针, 用于存储剩余的睡眠时间, 可为 NULL
 /// # 返回值
 /// - 成功返回 0, 失败返回负错误码
+/// - 被信号打断返回 ERESTART_RESTARTBLOCK，没有执行用户处理函数时由 restart_syscall 睡完剩余时间
 pub fn nanosleep(duration: *const TimeSpec, rem: *mut TimeSpec) -> c_int {
     let req = unsafe { read_from_user(duration) };
     if req.tv_sec == 0 && req.tv_nsec == 0 {
@@ -14,42 +16,73 @@ pub fn nanosleep(duration: *const TimeSpec, rem: *mut TimeSpec) -> c_int {
     if req.tv_sec < 0 || req.tv_nsec < 0 || req.tv_nsec > 999999999 {
         return -EINVAL;
     }
-    let mut result = 0;
-    let task = current_task();
-    let trigger = get_time() + req.into_freq(clock_freq());
+    let deadline = get_time() + req.into_freq(clock_freq());
+    relative_sleep(deadline, user_rem(rem))
+    // TODO: EFAULT
+}
 
-    let mut timer_q = TIMER_QUEUE.lock();
-    timer_q.push(trigger, task.clone());
-    sleep_task(task.clone(), true);
-    drop(timer_q);
-    yield_task();
+/// 被打断的相对睡眠经 restart_syscall 继续：睡到原定的唤醒时刻
+/// # 参数
+/// - `deadline`: 原定的唤醒时刻（时钟周期）
+/// - `rem`: 剩余时间写回的用户地址
+pub fn nanosleep_restart(deadline: usize, rem: Option<UA>) -> c_int {
+    relative_sleep(deadline, rem)
+}
 
-    // 被唤醒后（可能是超时，也可能是信号），必须确保将任务从定时器队列中清理掉
-    // 如果是超时唤醒，pop_due_task 已经移除了
-    // 如果是信号唤醒，任务还在队列中，需要手动移除以避免 Arc 泄漏
-    TIMER_QUEUE.lock().remove_task(&task);
-
-    if !rem.is_null() {
-        let dur = trigger.saturating_sub(get_time());
-        let remaining_ticks = if dur > 0 {
-            // XXX: 提前唤醒是否一定是因为信号？
-            result = -EINTR;
-            dur
-        } else {
-            0
-        };
-        let rem_ts = TimeSpec::from_freq(remaining_ticks, clock_freq());
+fn user_rem(rem: *mut TimeSpec) -> Option<UA> {
+    (!rem.is_null()).then(|| UA::from_usize(rem as usize))
+}
+
+/// 睡眠到 `deadline`，被信号打断时写回剩余时间并登记重启块
+fn relative_sleep(deadline: usize, rem: Option<UA>) -> c_int {
+    if sleep_until(deadline) {
+        return 0;
+    }
+    if let Some(rem) = rem {
+        let remaining = deadline.saturating_sub(get_time());
         unsafe {
-            write_to_user(rem, rem_ts);
+            write_to_user(
+                rem.as_usize() as *mut TimeSpec,
+                TimeSpec::from_freq(remaining, clock_freq()),
+            );
         }
     }
+    current_task().lock().restart_block = Some(RestartBlock::Nanosleep { deadline, rem });
+    -ERESTART_RESTARTBLOCK
+}
 
-    result
-    // TODO: EFAULT
+/// 可被信号打断地睡眠到 `deadline`（时钟周期）
+/// # 返回值
+/// - 睡到 `deadline` 返回 true，被信号打断返回 false
+fn sleep_until(deadline: usize) -> bool {
+    let task = current_task();
+    loop {
+        if get_time() >= deadline {
+            return true;
+        }
+        let slept = sleep_task_prepare(task.clone(), true, |t| {
+            if sig_pending(t) {
+                return true;
+            }
+            TIMER_QUEUE.lock().push(deadline, task.clone());
+            false
+        });
+        if !slept {
+            return false;
+        }
+        yield_task();
+        // 被唤醒后（可能是超时，也可能是信号），必须确保将任务从定时器队列中清理掉
+        // 如果是超时唤醒，pop_due_task 已经移除了
+        // 如果是信号唤醒，任务还在队列中，需要手动移除以避免 Arc 泄漏
+        TIMER_QUEUE.lock().remove_task(&task);
+    }
 }
 
+/// 获取当前线程 ID
+///
+/// 读取 per-CPU 缓存，不获取锁也不分配内存。
 pub fn gettid() -> c_int {
-    current_task().lock().tid as c_int
+    crate::kernel::current_tid() as c_int
 }
 
 pub fn sched_yield() -> c_int {
@@ -65,6 +98,7 @@ pub fn sched_yield() -> c_int {
 /// - `rem`: 指向 TimeSpec 结构体的指针, 用于存储剩余的睡眠时间, 可为 NULL
 /// # 返回值
 /// - 成功返回 0, 失败返回负错误码
+/// - 被信号打断时相对睡眠同 nanosleep，绝对睡眠返回 ERESTARTNOHAND
 pub fn clock_nanosleep(
     clk_id: c_int,
     flags: c_int,
@@ -76,47 +110,38 @@ pub fn clock_nanosleep(
     let sleep_ticks = time_req.into_freq(clock_freq());
     let trigger = if is_abstime {
         match clk_id {
-            CLOCK_REALTIME => sleep_ticks,
-            CLOCK_MONOTONIC => sleep_ticks,
-            CLOCK_TAI | CLOCK_BOOTTIME | CLOCK_PROCESS_CPUTIME_ID => return -ENOSYS,
+            CLOCK_REALTIME => {
+                // 绝对墙上时间需先扣除 REALTIME 偏移量，换算到单调时钟
+                let mono = time_req - crate::kernel::time::REALTIME.read();
+                if mono.tv_sec < 0 {
+                    0
+                } else {
+                    mono.into_freq(clock_freq())
+                }
+            }
+            CLOCK_MONOTONIC | CLOCK_BOOTTIME => sleep_ticks,
+            CLOCK_TAI | CLOCK_PROCESS_CPUTIME_ID => return -ENOSYS,
             _ => return -EINVAL,
         }
     } else {
         match clk_id {
-            CLOCK_REALTIME | CLOCK_MONOTONIC => get_time().saturating_add(sleep_ticks),
-            CLOCK_TAI | CLOCK_BOOTTIME | CLOCK_PROCESS_CPUTIME_ID => return -ENOSYS,
+            CLOCK_REALTIME | CLOCK_MONOTONIC | CLOCK_BOOTTIME => {
+                get_time().saturating_add(sleep_ticks)
+            }
+            CLOCK_TAI | CLOCK_PROCESS_CPUTIME_ID => return -ENOSYS,
             _ => return -EINVAL,
         }
     };
 
-    let mut result = 0;
-    let task = current_task();
-
-    let mut timer_q = TIMER_QUEUE.lock();
-    timer_q.push(trigger, task.clone());
-    sleep_task(task.clone(), true);
-    drop(timer_q);
-    yield_task();
-
-    // 同 sys_nanosleep，防止信号唤醒导致的 Arc 泄漏
-    TIMER_QUEUE.lock().remove_task(&task);
-
-    if !rem.is_null() {
-        let dur = trigger.saturating_sub(get_time());
-        let remaining_ticks = if dur > 0 {
-            // XXX: 提前唤醒是否一定是因为信号？
-            result = -EINTR;
-            dur
-        } else {
-            0
-        };
-        let rem_ts = TimeSpec::from_freq(remaining_ticks, clock_freq());
-        unsafe {
-            write_to_user(rem, rem_ts);
-        }
+    if !is_abstime {
+        return relative_sleep(trigger, user_rem(rem));
+    }
+    // 绝对时间的睡眠可以原样重启，也不写回剩余时间
+    if sleep_until(trigger) {
+        0
+    } else {
+        -ERESTARTNOHAND
     }
-
-    result
 }
 
 /// 获取间隔定时器的当前值
@@ -126,15 +151,17 @@ pub fn clock_nanosleep(
 /// # 返回值
 /// - 成功返回 0, 失败返回负错误码
 pub fn getitimer(which: c_int, curr_value: *mut Itimerval) -> c_int {
-    match which {
-        ITIMER_REAL | ITIMER_VIRTUAL | ITIMER_PROF => {}
-        _ => return -EINVAL,
-    }
     let sig = match which {
         ITIMER_REAL => NUM_SIGALRM,
-        ITIMER_VIRTUAL => NUM_SIGVTALRM,
-        ITIMER_PROF => NUM_SIGPROF,
-        _ => unreachable!("getitimer: unreachable which case."),
+        ITIMER_VIRTUAL | ITIMER_PROF => {
+            let pid = current_task().lock().pid;
+            let val = get_cpu_itimer(pid, cpu_clock(which)).to_itimerval();
+            unsafe {
+                write_to_user(curr_value, val);
+            }
+            return 0;
+        }
+        _ => return -EINVAL,
     };
     // Linux semantics: ITIMER_* are per-process (thread group), not per-thread.
     // Use the thread-group leader (pid) as the timer owner.
@@ -170,26 +197,39 @@ pub fn getitimer(which: c_int, curr_value: *mut Itimerval) -> c_int {
 /// # 返回值
 /// - 成功返回 0, 失败返回负错误码
 pub fn setitimer(which: c_int, new_value: *const Itimerval, old_value: *mut Itimerval) -> c_int {
-    match which {
-        ITIMER_REAL | ITIMER_VIRTUAL | ITIMER_PROF => {}
-        _ => return -EINVAL,
+    if !matches!(which, ITIMER_REAL | ITIMER_VIRTUAL | ITIMER_PROF) {
+        return -EINVAL;
+    }
+    // 修改定时器前检查两个指针，避免设置了新值却写不回旧值
+    if !validate_user_ptr(new_value) || (!old_value.is_null() && !validate_user_ptr_mut(old_value))
+    {
+        return -EFAULT;
     }
-    let sig = match which {
-        ITIMER_REAL => NUM_SIGALRM,
-        ITIMER_VIRTUAL => NUM_SIGVTALRM,
-        ITIMER_PROF => NUM_SIGPROF,
-        _ => unreachable!("setitimer: unreachable which case."),
-    };
 
     // Linux semantics: ITIMER_* are per-process (thread group), not per-thread.
-    let owner = {
+    let (pid, owner) = {
         let pid = current_task().lock().pid;
-        TASK_MANAGER
+        let owner = TASK_MANAGER
             .lock()
             .get_task(pid)
-            .unwrap_or_else(current_task)
+            .unwrap_or_else(current_task);
+        (pid, owner)
     };
 
+    // VIRTUAL/PROF 由进程消耗的 CPU 时间驱动，与 REAL 互不影响
+    if which != ITIMER_REAL {
+        let new_itimer = unsafe { read_from_user(new_value) };
+        let new = CpuItimer::from_itimerval(&new_itimer);
+        let old = set_cpu_itimer(&owner, pid, cpu_clock(which), new);
+        if !old_value.is_null() {
+            unsafe {
+                write_to_user(old_value, old.to_itimerval());
+            }
+        }
+        return 0;
+    }
+    let sig = NUM_SIGALRM;
+
     let mut binding = TIMER.lock();
 
     // Linux semantics: return the previous timer value, then replace it with the new one.
@@ -212,6 +252,7 @@ pub fn setitimer(which: c_int, new_value: *const Itimerval, old_value: *mut Itim
     if !new_itimer.it_value.is_zero() {
         let trigger = get_time() + new_itimer.it_value.into_freq(clock_freq());
         let interval = new_itimer.it_interval.to_timespec();
+        register_exit_notifier(&owner, &ITIMER_EXIT_NOTIFIER);
         let entry = TimerEntry {
             task: owner,
             sig,
@@ -227,3 +268,12 @@ pub fn setitimer(which: c_int, new_value: *const Itimerval, old_value: *mut Itim
 
     0
 }
+
+/// ITIMER_VIRTUAL/ITIMER_PROF 对应的 CPU 时间定时器
+fn cpu_clock(which: c_int) -> CpuClock {
+    if which == ITIMER_VIRTUAL {
+        CpuClock::Virtual
+    } else {
+        CpuClock::Prof
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::ipc::JobCtlReport;
 use crate::uapi::errno::ECHILD;
 
 /// 等待子进程状态变化（wait4）
@@ -19,10 +20,10 @@ use crate::uapi::errno::ECHILD;
 /// - `rusage`: 指向存储资源使用情况的 rusage 结构体指针
 /// # 返回值
 /// - 成功返回子进程 ID, 如果设置了 NOHANG 标志且没有满足条件的子进程，则立即返回 0，失败返回负错误码
+/// - 等待期间被信号打断返回 ERESTARTSYS，由信号投递决定重启或返回 EINTR
 /// TODO:
 /// 1. rusage 参数的处理
-/// 2. 信号处理
-/// 3. 错误处理
+/// 2. 错误处理
 pub fn wait4(pid: c_int, wstatus: *mut c_int, options: c_int, _rusage: *mut Rusage) -> c_int {
     // 阻塞当前任务,直到指定的子任务结束
     let cur_task = current_task();
@@ -44,34 +45,28 @@ pub fn wait4(pid: c_int, wstatus: *mut c_int, options: c_int, _rusage: *mut Rusa
     let check_exited = opt.contains(WaitFlags::EXITED)
         || (!opt.contains(WaitFlags::STOPPED) && !opt.contains(WaitFlags::CONTINUED));
 
-    let zombie: fn(TaskState) -> bool = if check_exited {
-        |ch| ch == TaskState::Zombie
-    } else {
-        |_ch| false
-    };
-    let continued: fn(TaskState) -> bool = if opt.contains(WaitFlags::CONTINUED) {
-        |ch| ch == TaskState::Running
-    } else {
-        |_ch| false
-    };
-    let stopped: fn(TaskState) -> bool = if opt.contains(WaitFlags::STOPPED) {
-        |ch| ch == TaskState::Stopped || ch == TaskState::Zombie
-    } else {
-        |_ch| false
-    };
+    let check_stopped = opt.contains(WaitFlags::STOPPED);
+    let check_continued = opt.contains(WaitFlags::CONTINUED);
+    // 停止/继续事件记录在子进程 leader 的 jobctl 上，被取走后不再重复报告
     let cond = |ch: &SharedTask| {
         if !match_pid(ch) {
             return false;
         }
-        let state = ch.lock().state;
-        zombie(state) || continued(state) || stopped(state)
+        let t = ch.lock();
+        match (t.state, t.jobctl.report) {
+            (TaskState::Zombie, _) => check_exited,
+            (_, Some(JobCtlReport::Stopped(_))) => check_stopped,
+            (_, Some(JobCtlReport::Continued)) => check_continued,
+            _ => false,
+        }
     };
     let has_matching_child = |children: &[SharedTask]| children.iter().any(match_pid);
 
-    let task = loop {
+    let (task, report) = loop {
         let mut found: Option<SharedTask> = None;
         let mut nohang = false;
         let mut no_child = false;
+        let mut interrupted = false;
 
         let slept = sleep_task_prepare(cur_task.clone(), true, |t| {
             if let Some(res) = t.check_child(cond, !opt.contains(WaitFlags::NOWAIT)) {
@@ -88,6 +83,11 @@ pub fn wait4(pid: c_int, wstatus: *mut c_int, options: c_int, _rusage: *mut Rusa
                 return true;
             }
             let mut wc = t.wait_child.lock();
+            if sig_pending(t) {
+                wc.remove_task(&cur_task);
+                interrupted = true;
+                return true;
+            }
             if !wc.contains(&cur_task) {
                 wc.add_task(cur_task.clone());
             }
@@ -96,7 +96,20 @@ pub fn wait4(pid: c_int, wstatus: *mut c_int, options: c_int, _rusage: *mut Rusa
 
         if !slept {
             if let Some(res) = found {
-                break res;
+                // 同一事件可能已被另一个等待者取走，此时重新查找
+                let (zombie, report) = {
+                    let mut t = res.lock();
+                    let report = if opt.contains(WaitFlags::NOWAIT) {
+                        t.jobctl.report
+                    } else {
+                        t.jobctl.report.take()
+                    };
+                    (t.state == TaskState::Zombie, report)
+                };
+                if zombie || report.is_some() {
+                    break (res, report);
+                }
+                continue;
             }
             if no_child {
                 return -ECHILD;
@@ -104,6 +117,9 @@ pub fn wait4(pid: c_int, wstatus: *mut c_int, options: c_int, _rusage: *mut Rusa
             if nohang {
                 return 0;
             }
+            if interrupted {
+                return -ERESTARTSYS;
+            }
         }
         yield_task();
     };
@@ -113,21 +129,17 @@ pub fn wait4(pid: c_int, wstatus: *mut c_int, options: c_int, _rusage: *mut Rusa
         (t.tid, t.state, t.exit_status)
     };
 
-    let status = match state {
-        TaskState::Zombie => match exit_status.expect("Zombie must set exit status.") {
+    let status = match (state, report) {
+        (TaskState::Zombie, _) => match exit_status.expect("Zombie must set exit status.") {
             TaskExitStatus::Exited(code) => WaitStatus::exit_code(code as u8, 0),
             TaskExitStatus::Signaled {
                 signal,
                 core_dumped,
             } => WaitStatus::signaled(signal as u8, core_dumped),
         },
-        TaskState::Stopped => {
-            WaitStatus::stop_code(0) // TODO: 停止信号
-        }
-        TaskState::Running => WaitStatus::continued_code(),
-        _ => {
-            unreachable!("wait4: unexpected task state.")
-        }
+        (_, Some(JobCtlReport::Stopped(sig))) => WaitStatus::stop_code(sig as u8),
+        (_, Some(JobCtlReport::Continued)) => WaitStatus::continued_code(),
+        (_, None) => unreachable!("wait4: child has no state change to report."),
     };
 
     // wstatus 允许为 NULL（例如 waitpid(-1, NULL, 0)），此时不写回状态
@@ -11,12 +11,15 @@ use alloc::{
 
 use crate::{
     config::MAX_ARGV,
-    kernel::current_task,
+    kernel::{current_task, watchdog::WatchdogFile},
     uapi::{errno::EINVAL, log::SyslogAction},
     vfs::{
-        DENTRY_CACHE, Dentry, File, FileMode, FsError, InodeType, OpenFlags,
+        DENTRY_CACHE, Dentry, File, FileMode, FsError, InodeType, MountFlags, OpenFlags,
+        check_mount_writable,
+        dev::makedev,
+        devno::{chrdev_major, misc_minor},
         impls::{BlockDeviceFile, CharDeviceFile, PipeFile, RegFile},
-        normalize_path, vfs_lookup_from,
+        mount_flags, normalize_path, vfs_lookup_from,
     },
 };
 
@@ -243,8 +246,9 @@ pub fn create_file_at(dirfd: i32, path: &str, mode: u32) -> Result<Arc<Dentry>,
     if is_special_basename(&filename) {
         return Err(FsError::AlreadyExists);
     }
+    check_mount_writable(&parent_dentry)?;
 
-    let file_mode = FileMode::from_bits_truncate(mode) | FileMode::S_IFREG;
+    let file_mode = FileMode::from_bits_truncate(apply_umask(mode)) | FileMode::S_IFREG;
     let child_inode = parent_dentry.inode.create(&filename, file_mode)?;
 
     let child_dentry = Dentry::new(filename.clone(), child_inode);
@@ -254,6 +258,12 @@ pub fn create_file_at(dirfd: i32, path: &str, mode: u32) -> Result<Arc<Dentry>,
     Ok(child_dentry)
 }
 
+/// 按当前任务的 umask 屏蔽新建文件的权限位
+pub fn apply_umask(mode: u32) -> u32 {
+    let fs = current_task().lock().fs.clone();
+    fs.lock().apply_umask(mode)
+}
+
 /// 验证 syslog 系统调用参数
 ///
 /// 根据操作类型检查参数的有效性。
@@ -524,14 +534,26 @@ pub fn create_file_from_dentry(
 ) -> Result<Arc<dyn File>, FsError> {
     let inode_type = dentry.inode.metadata()?.inode_type;
 
+    // nodev 挂载上的设备节点不能打开
+    if matches!(inode_type, InodeType::CharDevice | InodeType::BlockDevice)
+        && mount_flags(&dentry).contains(MountFlags::NO_DEV)
+    {
+        return Err(FsError::PermissionDenied);
+    }
+
     let file: Arc<dyn File> = match inode_type {
         InodeType::File | InodeType::Directory | InodeType::Symlink => {
             // 普通文件、目录、符号链接
             Arc::new(RegFile::new(dentry, flags))
         }
         InodeType::CharDevice => {
-            // 字符设备
-            Arc::new(CharDeviceFile::new(dentry, flags)?)
+            // 字符设备；/dev/watchdog 的打开和关闭有独立的语义
+            let rdev = dentry.inode.metadata()?.rdev;
+            if rdev == makedev(chrdev_major::MISC, misc_minor::WATCHDOG) {
+                Arc::new(WatchdogFile::open(dentry, flags)?)
+            } else {
+                Arc::new(CharDeviceFile::new(dentry, flags)?)
+            }
         }
         InodeType::BlockDevice => {
             // 块设备
@@ -0,0 +1,311 @@
+//! 魔术键（SysRq）
+//!
+//! 系统卡住时按需把内核状态转储到日志，类似 Linux 的 Magic SysRq。两种触发方式：
+//! - 串口控制台上按 Ctrl-A 再按命令字母，由 UART 接收中断解析，不经过 tty 层；
+//!   连按两次 Ctrl-A 向 tty 输入一个 Ctrl-A。QEMU `-nographic` 自身占用 Ctrl-A，
+//!   需要按 Ctrl-A Ctrl-A 才能把一个 Ctrl-A 送进虚拟机；
+//! - 向 `/proc/sysrq-trigger` 写入命令字母。
+//!
+//! | 字母 | 转储内容 |
+//! |------|----------|
+//! | `t`  | 所有任务的状态、所在 CPU、保存的 sp/ra 和内核栈基址 |
+//! | `m`  | 帧分配器和内核堆的使用情况 |
+//! | `d`  | 各 CPU 正在自旋等待的锁及其持有者 |
+//! | `f`  | 各进程的文件描述符表 |
+//! | `v`  | 各进程的用户映射：VMA 权限及其中驻留、换出和未映射的页数 |
+//!
+//! 其它字母打印帮助。
+//!
+//! 转储以 Warning 级别写入日志（默认同时打印到控制台），只使用栈上的格式化缓冲区
+//! 和预先分配的日志环形缓冲区，不分配内存。任务表、任务、fd 表等用 `try_lock`
+//! 访问，锁被占用时打印 `<locked>` 跳过，因此在内存耗尽或某个锁被永久持有时也能输出。
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::kernel::{TASK_MANAGER, TaskManagerTrait, TaskState, TaskStruct, clock_freq, num_cpu};
+use crate::mm::address::PageNum;
+use crate::mm::memory_space::{DisplayPteFlags, MemorySpace, PageState};
+
+/// 串口上的 SysRq 转义字符（Ctrl-A）
+pub const SYSRQ_ESCAPE: u8 = 0x01;
+
+macro_rules! sysrq_print {
+    ($($arg: tt)*) => {
+        crate::pr_warn!($($arg)*)
+    };
+}
+
+/// 串口输入经过 [`SysrqEscape`] 后的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SysrqInput {
+    /// 普通输入字节，照常交给 tty
+    Byte(u8),
+    /// 转义字符，等待下一个字节
+    Escape,
+    /// SysRq 命令字母
+    Command(u8),
+}
+
+/// 串口输入中的 SysRq 转义状态
+///
+/// 由串口驱动在接收路径上逐字节调用 [`SysrqEscape::feed`]。
+pub struct SysrqEscape {
+    armed: AtomicBool,
+}
+
+impl SysrqEscape {
+    pub const fn new() -> Self {
+        Self {
+            armed: AtomicBool::new(false),
+        }
+    }
+
+    /// 处理一个接收到的字节
+    pub fn feed(&self, byte: u8) -> SysrqInput {
+        if self.armed.swap(false, Ordering::Relaxed) {
+            return match byte {
+                SYSRQ_ESCAPE => SysrqInput::Byte(byte),
+                _ => SysrqInput::Command(byte),
+            };
+        }
+        if byte == SYSRQ_ESCAPE {
+            self.armed.store(true, Ordering::Relaxed);
+            return SysrqInput::Escape;
+        }
+        SysrqInput::Byte(byte)
+    }
+}
+
+impl Default for SysrqEscape {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 执行一个 SysRq 命令
+///
+/// 可以在中断上下文中调用。
+pub fn handle_sysrq(key: u8) {
+    match key {
+        b't' => show_tasks(),
+        b'm' => show_mem(),
+        b'd' => show_locks(),
+        b'f' => show_files(),
+        b'v' => show_vm(),
+        _ => sysrq_print!(
+            "sysrq: HELP : show-tasks(t) show-memory(m) show-locks(d) show-files(f) show-vm(v)"
+        ),
+    }
+}
+
+/// 任务名，不分配内存；不是合法 UTF-8 时打印 `?`
+fn comm_str(task: &TaskStruct) -> &str {
+    let len = task
+        .comm
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(task.comm.len());
+    core::str::from_utf8(&task.comm[..len]).unwrap_or("?")
+}
+
+/// 与 /proc/\[pid\]/stat 相同的状态字符
+fn state_char(state: TaskState) -> char {
+    match state {
+        TaskState::Running => 'R',
+        TaskState::Interruptible => 'S',
+        TaskState::Uninterruptible => 'D',
+        TaskState::Stopped => 'T',
+        TaskState::Zombie => 'Z',
+    }
+}
+
+struct DisplayCpu(Option<usize>);
+
+impl fmt::Display for DisplayCpu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(cpu) => write!(f, "{}", cpu),
+            None => f.write_str("-"),
+        }
+    }
+}
+
+fn show_tasks() {
+    sysrq_print!("sysrq: Show State");
+    let Some(tm) = TASK_MANAGER.try_lock() else {
+        sysrq_print!("  task table <locked>");
+        return;
+    };
+    sysrq_print!("    tid   pid comm            S cpu sp / ra / kstack");
+    tm.for_each_task(|tid, task| match task.try_lock() {
+        Some(t) => sysrq_print!(
+            "  {:>5} {:>5} {:<15} {} {:>3} {:#x} / {:#x} / {:#x}",
+            tid,
+            t.pid,
+            comm_str(&t),
+            state_char(t.state),
+            DisplayCpu(t.on_cpu),
+            t.context.sp,
+            t.context.ra,
+            t.kstack_base.as_usize()
+        ),
+        None => sysrq_print!("  {:>5} <locked>", tid),
+    });
+}
+
+fn show_mem() {
+    sysrq_print!("sysrq: Show Memory");
+    match crate::mm::frame_allocator::try_get_stats() {
+        Some((_, _, _, allocated, free)) => sysrq_print!(
+            "  frames: {} total, {} allocated, {} free",
+            allocated + free,
+            allocated,
+            free
+        ),
+        None => sysrq_print!("  frames: allocator <locked>"),
+    }
+    #[cfg(feature = "alloc")]
+    sysrq_print!(
+        "  kernel heap: {} bytes",
+        crate::mm::global_allocator::heap_bytes()
+    );
+    sysrq_print!("  open files: {}", crate::vfs::nr_open_files());
+}
+
+fn show_locks() {
+    sysrq_print!("sysrq: Show Locks");
+    let now = crate::arch::get_time();
+    let freq = clock_freq().max(1);
+    let mut waiting = false;
+    for cpu in 0..num_cpu() {
+        if let Some(wait) = crate::kernel::stall::spin_wait(cpu) {
+            waiting = true;
+            sysrq_print!(
+                "  CPU#{} spinning on lock {:#x} held by CPU#{} for {} ms",
+                cpu,
+                wait.lock,
+                DisplayCpu(wait.owner),
+                now.saturating_sub(wait.since) * 1000 / freq
+            );
+        }
+    }
+    if !waiting {
+        sysrq_print!("  no CPU is spinning on a lock");
+    }
+    if TASK_MANAGER.try_lock().is_none() {
+        sysrq_print!("  task table lock is held");
+    }
+}
+
+fn show_files() {
+    sysrq_print!("sysrq: Show Files");
+    let Some(tm) = TASK_MANAGER.try_lock() else {
+        sysrq_print!("  task table <locked>");
+        return;
+    };
+    tm.for_each_task(|tid, task| {
+        let Some(t) = task.try_lock() else {
+            sysrq_print!("  {:>5} <locked>", tid);
+            return;
+        };
+        // 同一线程组共享 fd 表，只打印线程组 leader
+        if t.pid != tid {
+            return;
+        }
+        sysrq_print!("  pid {} ({}):", tid, comm_str(&t));
+        let complete = t.fd_table.try_for_each(|fd, file, fd_flags| {
+            let flags = file.flags().bits();
+            let cloexec = fd_flags.contains(crate::vfs::FdFlags::CLOEXEC);
+            let shown = file.dentry().ok().and_then(|dentry| {
+                dentry.try_with_name(|name| {
+                    sysrq_print!(
+                        "    fd {:>3} flags {:#o}{} {}",
+                        fd,
+                        flags,
+                        if cloexec { " cloexec" } else { "" },
+                        name
+                    )
+                })
+            });
+            if shown.is_none() {
+                sysrq_print!(
+                    "    fd {:>3} flags {:#o}{} <anon>",
+                    fd,
+                    flags,
+                    if cloexec { " cloexec" } else { "" }
+                );
+            }
+        });
+        if !complete {
+            sysrq_print!("    fd table <locked>");
+        }
+    });
+}
+
+/// 打印一个地址空间的用户映射，逐页统计驻留、换出和未映射的页
+fn show_mappings(ms: &MemorySpace) {
+    for area in ms.areas().iter().filter(|a| a.area_type().is_user()) {
+        let (mut present, mut swapped, mut none) = (0, 0, 0);
+        ms.walk_pages(area.vpn_range(), |run| match run.first {
+            PageState::Present { .. } => present += run.pages,
+            PageState::Swapped(_) => swapped += run.pages,
+            PageState::NotPresent => none += run.pages,
+        });
+        sysrq_print!(
+            "    {:016x}-{:016x} {} {:<8} present {} swapped {} none {}",
+            area.vpn_range().start().start_addr().as_usize(),
+            area.vpn_range().end().start_addr().as_usize(),
+            DisplayPteFlags(area.permission()),
+            area.area_type().label(),
+            present,
+            swapped,
+            none
+        );
+    }
+}
+
+fn show_vm() {
+    sysrq_print!("sysrq: Show VM");
+    let Some(tm) = TASK_MANAGER.try_lock() else {
+        sysrq_print!("  task table <locked>");
+        return;
+    };
+    tm.for_each_task(|tid, task| {
+        let Some(t) = task.try_lock() else {
+            sysrq_print!("  {:>5} <locked>", tid);
+            return;
+        };
+        // 同一线程组共享地址空间，只打印线程组 leader
+        if t.pid != tid {
+            return;
+        }
+        let Some(space) = t.memory_space.as_ref() else {
+            return;
+        };
+        sysrq_print!("  pid {} ({}):", tid, comm_str(&t));
+        match space.try_lock() {
+            Some(ms) => show_mappings(&ms),
+            None => sysrq_print!("    memory space <locked>"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_sysrq_escape, {
+        let esc = SysrqEscape::new();
+        kassert!(esc.feed(b'a') == SysrqInput::Byte(b'a'));
+        kassert!(esc.feed(SYSRQ_ESCAPE) == SysrqInput::Escape);
+        kassert!(esc.feed(b't') == SysrqInput::Command(b't'));
+        kassert!(esc.feed(b't') == SysrqInput::Byte(b't'));
+
+        // 连按两次转义字符输入一个转义字符
+        kassert!(esc.feed(SYSRQ_ESCAPE) == SysrqInput::Escape);
+        kassert!(esc.feed(SYSRQ_ESCAPE) == SysrqInput::Byte(SYSRQ_ESCAPE));
+        kassert!(esc.feed(b'x') == SysrqInput::Byte(b'x'));
+    });
+}
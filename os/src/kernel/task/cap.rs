@@ -170,6 +170,40 @@ impl CapabilitySet {
     pub fn remove(&mut self, cap: Capabilities) {
         self.effective.remove(cap);
     }
+
+    /// 按 capset(2) 的规则替换 effective/permitted/inheritable
+    ///
+    /// - permitted 只能缩小；
+    /// - effective 必须是新 permitted 的子集；
+    /// - inheritable 不能超出原 inheritable 与 bounding 之并，
+    ///   没有 `CAP_SETPCAP` 时也不能超出原 inheritable 与 permitted 之并。
+    ///
+    /// ambient 随之收缩到新 permitted 与 inheritable 的交集。
+    /// 不满足规则时返回 `false`，能力集保持不变。
+    pub fn try_capset(
+        &mut self,
+        effective: Capabilities,
+        permitted: Capabilities,
+        inheritable: Capabilities,
+    ) -> bool {
+        if !self.permitted.contains(permitted) || !permitted.contains(effective) {
+            return false;
+        }
+        if !(self.inheritable | self.bounding).contains(inheritable) {
+            return false;
+        }
+        if !self.has(Capabilities::SETPCAP)
+            && !(self.inheritable | self.permitted).contains(inheritable)
+        {
+            return false;
+        }
+
+        self.effective = effective;
+        self.permitted = permitted;
+        self.inheritable = inheritable;
+        self.ambient &= permitted & inheritable;
+        true
+    }
 }
 
 // 为了与 Linux 系统调用兼容，提供能力位的数值常量
@@ -223,3 +257,50 @@ pub fn capability_from_u32(cap_index: u32) -> Option<Capabilities> {
     }
     Some(Capabilities::from_bits_truncate(1u64 << cap_index))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_capset_drops_capabilities, {
+        let mut caps = CapabilitySet::full();
+        let kept = Capabilities::CHOWN | Capabilities::KILL;
+        kassert!(caps.try_capset(Capabilities::CHOWN, kept, kept));
+        kassert!(caps.has(Capabilities::CHOWN));
+        kassert!(!caps.has(Capabilities::KILL));
+        kassert!(!caps.has(Capabilities::SYS_ADMIN));
+        kassert!(caps.ambient == kept);
+
+        // permitted 中的能力可以重新放进 effective
+        kassert!(caps.try_capset(kept, kept, kept));
+        kassert!(caps.has(Capabilities::KILL));
+    });
+
+    test_case!(test_capset_rejects_escalation, {
+        let mut caps = CapabilitySet::empty();
+        caps.bounding = Capabilities::full();
+        caps.permitted = Capabilities::CHOWN;
+        caps.effective = Capabilities::CHOWN;
+
+        // permitted 不能扩大，effective 不能超出 permitted
+        kassert!(!caps.try_capset(
+            Capabilities::KILL,
+            Capabilities::KILL,
+            Capabilities::empty()
+        ));
+        kassert!(!caps.try_capset(
+            Capabilities::KILL,
+            Capabilities::CHOWN,
+            Capabilities::empty()
+        ));
+        // 没有 CAP_SETPCAP 时 inheritable 不能超出原 permitted
+        kassert!(!caps.try_capset(Capabilities::CHOWN, Capabilities::CHOWN, Capabilities::KILL));
+        kassert!(caps.effective == Capabilities::CHOWN);
+        kassert!(caps.try_capset(
+            Capabilities::empty(),
+            Capabilities::CHOWN,
+            Capabilities::CHOWN
+        ));
+    });
+}
@@ -1,5 +1,98 @@
-use crate::kernel::task::CapabilitySet;
-use crate::uapi::cred::{ROOT_GID, ROOT_UID};
+use core::fmt;
+
+use crate::kernel::task::{Capabilities, CapabilitySet};
+use crate::uapi::cred::{NGROUPS_MAX, ROOT_GID, ROOT_UID};
+
+/// 附加组列表
+///
+/// 定长存储，使 [`Credential`] 保持 `Copy`。与 Linux 相同，组 ID 按升序保存，
+/// getgroups 按此顺序返回；不去重。
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct GroupList {
+    len: usize,
+    gids: [u32; NGROUPS_MAX],
+}
+
+impl GroupList {
+    /// 空列表
+    pub const fn empty() -> Self {
+        Self {
+            len: 0,
+            gids: [0; NGROUPS_MAX],
+        }
+    }
+
+    /// 由组 ID 列表构造，超过 `NGROUPS_MAX` 时返回 `None`
+    pub fn from_slice(gids: &[u32]) -> Option<Self> {
+        if gids.len() > NGROUPS_MAX {
+            return None;
+        }
+        let mut list = Self::empty();
+        list.gids[..gids.len()].copy_from_slice(gids);
+        list.len = gids.len();
+        list.gids[..list.len].sort_unstable();
+        Some(list)
+    }
+
+    /// 按升序排列的组 ID
+    pub fn as_slice(&self) -> &[u32] {
+        &self.gids[..self.len]
+    }
+
+    /// 组的个数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 列表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 列表中是否有 `gid`
+    pub fn contains(&self, gid: u32) -> bool {
+        self.as_slice().binary_search(&gid).is_ok()
+    }
+}
+
+impl fmt::Debug for GroupList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+/// 按 `/etc/group` 的内容计算用户的附加组，相当于 initgroups(3)
+///
+/// 结果包含 `gid` 本身和成员列表中含有 `user` 的所有组。格式不对的行被跳过，
+/// 超过 `NGROUPS_MAX` 的部分被丢弃。
+pub fn initgroups(group_file: &str, user: &str, gid: u32) -> GroupList {
+    let mut gids = [0u32; NGROUPS_MAX];
+    gids[0] = gid;
+    let mut len = 1;
+    for line in group_file.lines() {
+        if len == NGROUPS_MAX {
+            break;
+        }
+        // name:password:gid:member1,member2
+        let mut fields = line.split(':');
+        let (Some(_), Some(_), Some(group_gid), Some(members)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let Ok(group_gid) = group_gid.trim().parse::<u32>() else {
+            continue;
+        };
+        if gids[..len].contains(&group_gid) {
+            continue;
+        }
+        if members.trim().split(',').any(|member| member == user) {
+            gids[len] = group_gid;
+            len += 1;
+        }
+    }
+    GroupList::from_slice(&gids[..len]).unwrap_or(GroupList::empty())
+}
 
 /// 进程凭证结构
 #[derive(Clone, Copy, Debug)]
@@ -21,6 +114,9 @@ pub struct Credential {
     /// 文件系统组 ID
     pub fsgid: u32,
 
+    /// 附加组
+    pub groups: GroupList,
+
     /// 能力集合
     pub capabilities: CapabilitySet,
 }
@@ -37,6 +133,7 @@ impl Credential {
             sgid: ROOT_GID,
             fsuid: ROOT_UID,
             fsgid: ROOT_GID,
+            groups: GroupList::empty(),
             capabilities: CapabilitySet::full(),
         }
     }
@@ -45,4 +142,108 @@ impl Credential {
     pub fn is_root(&self) -> bool {
         self.euid == ROOT_UID
     }
+
+    /// 文件系统组 ID 或附加组中是否有 `gid`，用于文件权限的属组判定
+    pub fn in_group(&self, gid: u32) -> bool {
+        self.fsgid == gid || self.groups.contains(gid)
+    }
+
+    /// 有效组 ID 或附加组中是否有 `gid`，用于 IPC 对象的属组判定
+    pub fn in_egroup(&self, gid: u32) -> bool {
+        self.egid == gid || self.groups.contains(gid)
+    }
+
+    /// 是否允许向凭证为 `target` 的任务发送信号
+    ///
+    /// 与 Linux 相同：发送者的真实或有效 UID 等于目标的真实或保存的 UID，
+    /// 或者发送者拥有 `CAP_KILL`。
+    pub fn can_signal(&self, target: &Credential) -> bool {
+        self.capabilities.has(Capabilities::KILL)
+            || self.euid == target.suid
+            || self.euid == target.uid
+            || self.uid == target.suid
+            || self.uid == target.uid
+    }
+
+    /// 是否允许查看凭证为 `target` 的任务的 `/proc/[pid]` 信息
+    ///
+    /// 与 Linux 以文件系统凭证做 ptrace 读检查相同：拥有 `CAP_SYS_PTRACE`，
+    /// 或者 fsuid/fsgid 与目标的真实、有效、保存的 UID/GID 都相同。
+    pub fn may_inspect(&self, target: &Credential) -> bool {
+        if self.capabilities.has(Capabilities::SYS_PTRACE) {
+            return true;
+        }
+        [target.uid, target.euid, target.suid]
+            .iter()
+            .all(|&uid| uid == self.fsuid)
+            && [target.gid, target.egid, target.sgid]
+                .iter()
+                .all(|&gid| gid == self.fsgid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    fn user(uid: u32) -> Credential {
+        let mut cred = Credential::root();
+        cred.uid = uid;
+        cred.euid = uid;
+        cred.suid = uid;
+        cred.fsuid = uid;
+        cred.capabilities = CapabilitySet::empty();
+        cred
+    }
+
+    test_case!(test_can_signal, {
+        kassert!(user(1000).can_signal(&user(1000)));
+        kassert!(!user(1000).can_signal(&user(1001)));
+        kassert!(!user(1000).can_signal(&Credential::root()));
+
+        // 目标的 saved UID 匹配也允许
+        let mut target = user(1001);
+        target.suid = 1000;
+        kassert!(user(1000).can_signal(&target));
+
+        kassert!(Credential::root().can_signal(&user(1001)));
+    });
+
+    test_case!(test_may_inspect, {
+        kassert!(user(1000).may_inspect(&user(1000)));
+        kassert!(!user(1000).may_inspect(&user(1001)));
+
+        // setuid 程序：保存的 UID 不同，不能查看
+        let mut target = user(1000);
+        target.suid = 0;
+        kassert!(!user(1000).may_inspect(&target));
+
+        kassert!(Credential::root().may_inspect(&user(1001)));
+    });
+
+    test_case!(test_group_list, {
+        kassert!(GroupList::from_slice(&[0; NGROUPS_MAX + 1]).is_none());
+        let list = GroupList::from_slice(&[20, 10, 30]).unwrap();
+        kassert!(list.as_slice() == [10, 20, 30]);
+        kassert!(list.contains(20) && !list.contains(15));
+
+        let mut cred = user(1000);
+        cred.fsgid = 100;
+        cred.egid = 100;
+        cred.groups = list;
+        kassert!(cred.in_group(100) && cred.in_group(30) && !cred.in_group(40));
+        kassert!(cred.in_egroup(100) && cred.in_egroup(10));
+    });
+
+    test_case!(test_initgroups, {
+        let group_file = "root:x:0:\n\
+                          bin:x:1:root,bin,daemon\n\
+                          wheel:x:10:alice,root\n\
+                          broken line\n\
+                          users:x:100:alice\n";
+        kassert!(initgroups(group_file, "root", 0).as_slice() == [0, 1, 10]);
+        kassert!(initgroups(group_file, "alice", 1000).as_slice() == [10, 100, 1000]);
+        kassert!(initgroups("", "root", 0).as_slice() == [0]);
+    });
 }
@@ -8,6 +8,7 @@ use crate::mm::address::{PageNum, VA, Vpn};
 use crate::mm::memory_space::MemorySpace;
 use crate::mm::memory_space::mapping_area::AreaType;
 use crate::mm::page_table::{PagingError, UniversalPTEFlag};
+use crate::mm::wx_policy::{user_pte_flags, wx_permitted};
 use crate::vfs::{FsError, Inode, InodeType};
 
 #[derive(Debug)]
@@ -305,15 +306,14 @@ fn load_segments_into_space(
             Vpn::from_addr_ceil(VA::from_usize(end_va)),
         );
 
-        let mut perm = UniversalPTEFlag::USER_ACCESSIBLE | UniversalPTEFlag::VALID;
-        if (ph.p_flags & PF_R) != 0 {
-            perm |= UniversalPTEFlag::READABLE;
-        }
-        if (ph.p_flags & PF_W) != 0 {
-            perm |= UniversalPTEFlag::WRITEABLE;
-        }
-        if (ph.p_flags & PF_X) != 0 {
-            perm |= UniversalPTEFlag::EXECUTABLE;
+        // 按程序头给出的最小权限映射；同时要求 W 和 X 的段交给 W^X 策略裁决
+        let perm = user_pte_flags(
+            (ph.p_flags & PF_R) != 0,
+            (ph.p_flags & PF_W) != 0,
+            (ph.p_flags & PF_X) != 0,
+        );
+        if !wx_permitted(perm, "exec", start_va) {
+            return Err(PagingError::PermissionDenied.into());
         }
 
         let area_type = if as_mmap_area {
@@ -355,6 +355,11 @@ fn load_segments_into_space(
             zero_va += take;
             zero_remain -= take;
         }
+
+        // 代码段写入完成，执行前同步指令缓存
+        if (ph.p_flags & PF_X) != 0 {
+            crate::arch::sync_icache_range(VA::from_usize(start_va), end_va - start_va);
+        }
     }
 
     // Compute PHDR runtime address (for auxv AT_PHDR)
@@ -462,15 +467,19 @@ fn apply_static_pie_relocs(
 
 pub fn prepare_exec_image_from_path(path: &str) -> Result<PreparedExecImage, ExecImageError> {
     let dentry = crate::vfs::vfs_lookup(path).map_err(ExecImageError::from)?;
-    let inode = dentry.inode.clone();
+    prepare_exec_image(dentry.inode.as_ref())
+}
+
+/// 从已打开的 inode 准备可执行映像，不要求它挂在目录树中
+pub fn prepare_exec_image(inode: &dyn Inode) -> Result<PreparedExecImage, ExecImageError> {
     let meta = inode.metadata().map_err(ExecImageError::from)?;
     if meta.inode_type != InodeType::File {
         return Err(ExecImageError::NotRegular(meta.inode_type));
     }
 
-    let eh = parse_elf_header(inode.as_ref())?;
-    let phdrs = parse_program_headers(inode.as_ref(), &eh)?;
-    let interp = find_interp_path(inode.as_ref(), &phdrs)?;
+    let eh = parse_elf_header(inode)?;
+    let phdrs = parse_program_headers(inode, &eh)?;
+    let interp = find_interp_path(inode, &phdrs)?;
 
     let mut space = MemorySpace::new_user_with_kernel_mappings()?;
 
@@ -480,14 +489,8 @@ pub fn prepare_exec_image_from_path(path: &str) -> Result<PreparedExecImage, Exe
     } else {
         None
     };
-    let (main_bias, main_entry, phdr_addr, phnum, phent, main_max_end) = load_segments_into_space(
-        &mut space,
-        inode.as_ref(),
-        &eh,
-        &phdrs,
-        main_base_hint,
-        false,
-    )?;
+    let (main_bias, main_entry, phdr_addr, phnum, phent, main_max_end) =
+        load_segments_into_space(&mut space, inode, &eh, &phdrs, main_base_hint, false)?;
     let tls = find_tls_template(&phdrs, main_bias)?;
 
     // Heap starts after end of main segments
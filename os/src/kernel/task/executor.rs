@@ -0,0 +1,421 @@
+//! 内核异步执行器
+//!
+//! 让后台状态机以 `async fn` 的形式编写，共享 kworker 线程，而不是各占一个内核线程和内核栈。
+//!
+//! [`spawn`] 把 future 包装为异步任务放入就绪队列，任务被唤醒时重新入队。就绪队列非空时
+//! 向全局工作队列提交一个工作项，由 kworker 依次 poll 就绪的任务。与
+//! `request_network_poll` 相同，工作项已提交但尚未执行时不会重复提交，因此 waker
+//! 可以在中断上下文中调用。
+//!
+//! 唤醒来源：
+//! - 定时器：[`sleep`] / [`sleep_until`]，到期由时钟中断中的 [`run_async_timers`] 唤醒，
+//!   tickless 空闲会把最早的到期时间（[`next_async_timer`]）计入一次性定时器；
+//! - 等待队列：[`AsyncWaitQueue::wait_until`] 等待条件成立，条件可能改变时由
+//!   `wake_one` / `wake_all` 唤醒。
+//!
+//! 执行器是协作式的：poll 期间不应长时间占用 kworker，较长的工作应分段并在其间
+//! `yield_now().await`。kstat 指标 `async_spawned`、`async_polls` 和 `async_timer_wakeups`
+//! 分别统计创建的异步任务数、poll 次数和由定时器唤醒的次数。
+
+use alloc::{
+    boxed::Box,
+    collections::{btree_map::BTreeMap, vec_deque::VecDeque},
+    sync::Arc,
+    task::Wake,
+    vec::Vec,
+};
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use crate::{
+    kernel::{
+        GLOBAL_WORK_QUEUE, WorkItem,
+        kstat::{self, Counter, Unit},
+    },
+    sync::{Mutex, SpinLock},
+};
+
+/// 一个工作项最多 poll 的任务数，超过后重新提交，让其他工作项有机会执行
+const POLL_BUDGET: usize = 32;
+
+/// 创建的异步任务数
+static ASYNC_SPAWNED: Counter = Counter::new();
+/// poll 次数
+static ASYNC_POLLS: Counter = Counter::new();
+/// 由定时器唤醒的次数
+static ASYNC_TIMER_WAKEUPS: Counter = Counter::new();
+
+/// 登记异步执行器相关的统计指标
+pub fn register_executor_metrics() {
+    kstat::register_counter("async_spawned", Unit::Count, &ASYNC_SPAWNED);
+    kstat::register_counter("async_polls", Unit::Count, &ASYNC_POLLS);
+    kstat::register_counter("async_timer_wakeups", Unit::Count, &ASYNC_TIMER_WAKEUPS);
+}
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// 异步任务
+struct AsyncTask {
+    /// 尚未完成的 future，完成后置为 None
+    ///
+    /// 任务可能在 poll 期间被唤醒并由另一个 kworker 取出，互斥锁保证同一时刻只有一方 poll。
+    future: Mutex<Option<BoxFuture>>,
+    /// 是否已在就绪队列中
+    queued: AtomicBool,
+}
+
+impl AsyncTask {
+    fn poll(self: &Arc<Self>) {
+        let mut slot = self.future.lock();
+        let Some(future) = slot.as_mut() else {
+            return;
+        };
+        ASYNC_POLLS.inc();
+        let waker = Waker::from(self.clone());
+        let mut cx = Context::from_waker(&waker);
+        if future.as_mut().poll(&mut cx).is_ready() {
+            *slot = None;
+        }
+    }
+}
+
+impl Wake for AsyncTask {
+    fn wake(self: Arc<Self>) {
+        enqueue(self);
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        enqueue(self.clone());
+    }
+}
+
+lazy_static::lazy_static! {
+    /// 就绪队列
+    static ref READY: SpinLock<VecDeque<Arc<AsyncTask>>> = SpinLock::new(VecDeque::new());
+    /// 异步定时器：(到期时间, 序号) -> waker，时间单位为硬件时钟周期
+    static ref ASYNC_TIMERS: SpinLock<BTreeMap<(usize, u64), Waker>> =
+        SpinLock::new(BTreeMap::new());
+}
+
+/// 是否已提交 poll 工作项且尚未开始执行
+static RUN_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// 异步定时器与等待队列登记项的序号
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+fn next_seq() -> u64 {
+    NEXT_SEQ.fetch_add(1, Ordering::Relaxed)
+}
+
+/// 创建一个异步任务，由 kworker 执行
+pub fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+    ASYNC_SPAWNED.inc();
+    enqueue(Arc::new(AsyncTask {
+        future: Mutex::new(Some(Box::pin(future))),
+        queued: AtomicBool::new(false),
+    }));
+}
+
+/// 把任务放入就绪队列（已在队列中则忽略）
+fn enqueue(task: Arc<AsyncTask>) {
+    if task.queued.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    READY.lock().push_back(task);
+    request_run();
+}
+
+/// 提交 poll 工作项
+fn request_run() {
+    if RUN_PENDING
+        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+        .is_ok()
+    {
+        GLOBAL_WORK_QUEUE
+            .lock()
+            .schedule_work(WorkItem::new(run_ready_tasks));
+    }
+}
+
+/// 工作项：poll 就绪队列中的任务
+fn run_ready_tasks() {
+    // 先清除标志：poll 期间被唤醒的任务会提交新的工作项
+    RUN_PENDING.store(false, Ordering::Release);
+    for _ in 0..POLL_BUDGET {
+        let Some(task) = READY.lock().pop_front() else {
+            return;
+        };
+        task.queued.store(false, Ordering::Release);
+        task.poll();
+    }
+    if !READY.lock().is_empty() {
+        request_run();
+    }
+}
+
+/// 时钟中断中调用：唤醒到期的异步定时器
+pub fn run_async_timers(now: usize) {
+    let mut due = Vec::new();
+    {
+        let mut timers = ASYNC_TIMERS.lock();
+        while let Some(entry) = timers.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            due.push(entry.remove());
+        }
+    }
+    ASYNC_TIMER_WAKEUPS.add(due.len() as u64);
+    for waker in due {
+        waker.wake();
+    }
+}
+
+/// 最早的异步定时器到期时间
+pub fn next_async_timer() -> Option<usize> {
+    ASYNC_TIMERS.lock().keys().next().map(|&(time, _)| time)
+}
+
+/// 在时间点 `deadline`（硬件时钟周期）之前挂起的 future
+pub struct Sleep {
+    deadline: usize,
+    /// 已登记的定时器键
+    key: Option<(usize, u64)>,
+}
+
+/// 睡眠到时间点 `deadline`（硬件时钟周期）
+pub fn sleep_until(deadline: usize) -> Sleep {
+    Sleep {
+        deadline,
+        key: None,
+    }
+}
+
+/// 睡眠 `duration`
+pub fn sleep(duration: Duration) -> Sleep {
+    let cycles = duration.as_nanos() * crate::arch::clock_freq() as u128 / 1_000_000_000;
+    sleep_until(crate::arch::get_time().saturating_add(cycles as usize))
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if crate::arch::get_time() >= self.deadline {
+            if let Some(key) = self.key.take() {
+                ASYNC_TIMERS.lock().remove(&key);
+            }
+            return Poll::Ready(());
+        }
+        let deadline = self.deadline;
+        let key = *self.key.get_or_insert_with(|| (deadline, next_seq()));
+        ASYNC_TIMERS.lock().insert(key, cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            ASYNC_TIMERS.lock().remove(&key);
+        }
+    }
+}
+
+/// 让出 kworker 一次：本次 poll 返回 Pending，任务立即重新入队
+#[allow(dead_code)]
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// 异步等待队列
+///
+/// 与 [`crate::kernel::WaitQueue`] 对应，等待者是 future 而不是任务。
+pub struct AsyncWaitQueue {
+    waiters: SpinLock<BTreeMap<u64, Waker>>,
+}
+
+impl AsyncWaitQueue {
+    pub const fn new() -> Self {
+        Self {
+            waiters: SpinLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// 等待 `cond` 成立
+    ///
+    /// 登记 waker 之后会再检查一次条件，条件在两次检查之间变为成立并调用
+    /// `wake_*` 时不会丢失唤醒。
+    pub fn wait_until<F: FnMut() -> bool>(&self, cond: F) -> WaitUntil<'_, F> {
+        WaitUntil {
+            queue: self,
+            cond,
+            seq: None,
+        }
+    }
+
+    /// 唤醒最早登记的一个等待者
+    #[allow(dead_code)]
+    pub fn wake_one(&self) {
+        let waker = self.waiters.lock().pop_first().map(|(_, waker)| waker);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// 唤醒所有等待者
+    pub fn wake_all(&self) {
+        let waiters = core::mem::take(&mut *self.waiters.lock());
+        for waker in waiters.into_values() {
+            waker.wake();
+        }
+    }
+
+    /// 登记的等待者数量
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.waiters.lock().len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.waiters.lock().is_empty()
+    }
+}
+
+impl Default for AsyncWaitQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// [`AsyncWaitQueue::wait_until`] 返回的 future
+pub struct WaitUntil<'a, F> {
+    queue: &'a AsyncWaitQueue,
+    cond: F,
+    /// 已登记的序号
+    seq: Option<u64>,
+}
+
+impl<F: FnMut() -> bool + Unpin> Future for WaitUntil<'_, F> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = &mut *self;
+        if (this.cond)() {
+            this.unregister();
+            return Poll::Ready(());
+        }
+        let seq = *this.seq.get_or_insert_with(next_seq);
+        this.queue.waiters.lock().insert(seq, cx.waker().clone());
+        if (this.cond)() {
+            this.unregister();
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
+
+impl<F> WaitUntil<'_, F> {
+    fn unregister(&mut self) {
+        if let Some(seq) = self.seq.take() {
+            self.queue.waiters.lock().remove(&seq);
+        }
+    }
+}
+
+impl<F> Drop for WaitUntil<'_, F> {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+    use core::sync::atomic::AtomicUsize;
+
+    /// 记录被唤醒次数的 waker
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn poll_once<F: Future + Unpin>(future: &mut F, waker: &Waker) -> Poll<F::Output> {
+        Pin::new(future).poll(&mut Context::from_waker(waker))
+    }
+
+    // 条件不成立时登记，wake_all 唤醒并移除登记，条件成立后完成
+    test_case!(test_async_wait_queue, {
+        let queue = AsyncWaitQueue::new();
+        let flag = AtomicBool::new(false);
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+
+        let mut wait = queue.wait_until(|| flag.load(Ordering::SeqCst));
+        kassert!(poll_once(&mut wait, &waker).is_pending());
+        kassert!(queue.len() == 1);
+
+        queue.wake_all();
+        kassert!(counter.0.load(Ordering::SeqCst) == 1);
+        kassert!(queue.is_empty());
+
+        flag.store(true, Ordering::SeqCst);
+        kassert!(poll_once(&mut wait, &waker).is_ready());
+
+        // 未完成即被丢弃的等待者不会留在队列中
+        let mut wait = queue.wait_until(|| false);
+        kassert!(poll_once(&mut wait, &waker).is_pending());
+        drop(wait);
+        kassert!(queue.is_empty());
+    });
+
+    // 定时器到期前挂起，到期处理唤醒 waker，之后完成
+    test_case!(test_async_sleep_timer, {
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let deadline = crate::arch::get_time() + crate::arch::clock_freq() * 3600;
+
+        let mut sleep = sleep_until(deadline);
+        kassert!(poll_once(&mut sleep, &waker).is_pending());
+        kassert!(next_async_timer().is_some_and(|t| t <= deadline));
+
+        run_async_timers(deadline);
+        kassert!(counter.0.load(Ordering::SeqCst) == 1);
+        kassert!(ASYNC_TIMERS.lock().keys().all(|&(t, _)| t > deadline));
+
+        let mut expired = sleep_until(0);
+        kassert!(poll_once(&mut expired, &waker).is_ready());
+
+        let mut yielded = yield_now();
+        kassert!(poll_once(&mut yielded, &waker).is_pending());
+        kassert!(counter.0.load(Ordering::SeqCst) == 2);
+        kassert!(poll_once(&mut yielded, &waker).is_ready());
+    });
+}
@@ -0,0 +1,186 @@
+//! 任务退出通知链
+//!
+//! 任务退出时需要回收的内核资源（健壮 futex 列表、POSIX 记录锁、间隔定时器、
+//! SysV 共享内存映射等）不在退出路径中逐个处理：子系统在任务第一次持有某类资源时，
+//! 把对应的 [`ExitNotifier`] 注册到任务的 [`ExitNotifierChain`] 上，退出路径只负责
+//! 按确定的顺序调用链上的回调。
+//!
+//! - 线程级资源（健壮列表）注册在线程自身上；进程级资源（记录锁、间隔定时器、
+//!   共享内存）注册在线程组 leader 上。
+//! - 回调按优先级从小到大调用，优先级相同时按注册顺序；同一回调重复注册只保留一份。
+//! - 调用前整条链被取走，每个回调最多执行一次。
+//! - 整个线程组退出时先调用其它线程的链（按 tid 顺序），最后调用 leader 的链。
+//!   此时仍在进程的用户地址空间中，回调可以访问用户内存。
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::kernel::{SharedTask, TASK_MANAGER, TaskManagerTrait};
+
+/// 需要读写用户内存的回调，如遍历健壮列表
+pub const EXIT_PRIO_USER_MEMORY: i32 = 0;
+/// 只涉及内核对象的回调，如释放记录锁、撤销定时器
+pub const EXIT_PRIO_KERNEL: i32 = 10;
+/// 修改用户地址空间的回调，如分离共享内存
+pub const EXIT_PRIO_ADDRESS_SPACE: i32 = 20;
+
+/// 退出回调
+pub struct ExitNotifier {
+    /// 名称，用于调试输出
+    pub name: &'static str,
+    /// 优先级，小的先调用
+    pub priority: i32,
+    /// 回调函数，参数为退出的任务
+    pub call: fn(&SharedTask),
+}
+
+/// 任务的退出回调链
+#[derive(Default)]
+pub struct ExitNotifierChain {
+    notifiers: Vec<&'static ExitNotifier>,
+}
+
+impl ExitNotifierChain {
+    pub const fn new() -> Self {
+        Self {
+            notifiers: Vec::new(),
+        }
+    }
+
+    /// 注册回调，已注册时不做任何事
+    pub fn register(&mut self, notifier: &'static ExitNotifier) {
+        if self.contains(notifier) {
+            return;
+        }
+        let pos = self
+            .notifiers
+            .iter()
+            .position(|n| n.priority > notifier.priority)
+            .unwrap_or(self.notifiers.len());
+        self.notifiers.insert(pos, notifier);
+    }
+
+    /// 注销回调
+    /// # 返回值
+    /// 回调已注册返回 `true`，否则返回 `false`
+    pub fn unregister(&mut self, notifier: &'static ExitNotifier) -> bool {
+        let len = self.notifiers.len();
+        self.notifiers.retain(|n| !core::ptr::eq(*n, notifier));
+        self.notifiers.len() != len
+    }
+
+    /// 回调是否已注册
+    pub fn contains(&self, notifier: &'static ExitNotifier) -> bool {
+        self.notifiers.iter().any(|n| core::ptr::eq(*n, notifier))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+
+    /// 按顺序调用所有回调
+    fn call(self, task: &SharedTask) {
+        for notifier in self.notifiers {
+            crate::pr_debug!("exit_notify: {}", notifier.name);
+            (notifier.call)(task);
+        }
+    }
+}
+
+impl fmt::Debug for ExitNotifierChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.notifiers.iter().map(|n| n.name))
+            .finish()
+    }
+}
+
+/// 向任务注册退出回调
+pub fn register_exit_notifier(task: &SharedTask, notifier: &'static ExitNotifier) {
+    task.lock().exit_notifiers.register(notifier);
+}
+
+/// 调用任务的退出回调链
+///
+/// 链在调用前被取走，重复调用不会再次执行回调。
+pub fn exit_notify(task: &SharedTask) {
+    let chain = core::mem::take(&mut task.lock().exit_notifiers);
+    chain.call(task);
+}
+
+/// 调用线程组中所有任务的退出回调链，leader 最后调用
+pub fn exit_notify_group(leader: &SharedTask) {
+    let threads = TASK_MANAGER.lock().get_process_threads(leader.clone());
+    for thread in threads
+        .iter()
+        .filter(|thread| !alloc::sync::Arc::ptr_eq(thread, leader))
+    {
+        exit_notify(thread);
+    }
+    exit_notify(leader);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, kernel::TaskStruct, test_case};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 按调用顺序记录回调编号，每个编号占 4 位
+    static TRACE: AtomicUsize = AtomicUsize::new(0);
+
+    fn record(id: usize) {
+        let trace = TRACE.load(Ordering::Relaxed);
+        TRACE.store((trace << 4) | id, Ordering::Relaxed);
+    }
+
+    static FIRST: ExitNotifier = ExitNotifier {
+        name: "first",
+        priority: EXIT_PRIO_USER_MEMORY,
+        call: |_| record(1),
+    };
+    static SECOND: ExitNotifier = ExitNotifier {
+        name: "second",
+        priority: EXIT_PRIO_KERNEL,
+        call: |_| record(2),
+    };
+    static THIRD: ExitNotifier = ExitNotifier {
+        name: "third",
+        priority: EXIT_PRIO_KERNEL,
+        call: |_| record(3),
+    };
+    static LAST: ExitNotifier = ExitNotifier {
+        name: "last",
+        priority: EXIT_PRIO_ADDRESS_SPACE,
+        call: |_| record(4),
+    };
+
+    test_case!(test_exit_notifier_order_and_once, {
+        TRACE.store(0, Ordering::Relaxed);
+        let task = TaskStruct::new_dummy_task(1).into_shared();
+        register_exit_notifier(&task, &LAST);
+        register_exit_notifier(&task, &SECOND);
+        register_exit_notifier(&task, &THIRD);
+        register_exit_notifier(&task, &FIRST);
+        register_exit_notifier(&task, &SECOND);
+        kassert!(task.lock().exit_notifiers.contains(&THIRD));
+
+        exit_notify(&task);
+        kassert!(TRACE.load(Ordering::Relaxed) == 0x1234);
+        kassert!(task.lock().exit_notifiers.is_empty());
+
+        // 链已被取走，再次调用不会重复执行
+        exit_notify(&task);
+        kassert!(TRACE.load(Ordering::Relaxed) == 0x1234);
+    });
+
+    test_case!(test_exit_notifier_unregister, {
+        let mut chain = ExitNotifierChain::new();
+        chain.register(&FIRST);
+        chain.register(&LAST);
+        kassert!(chain.unregister(&FIRST));
+        kassert!(!chain.unregister(&FIRST));
+        kassert!(!chain.contains(&FIRST));
+        kassert!(chain.contains(&LAST));
+    });
+}
@@ -1,17 +1,77 @@
 //! Futex 相关功能
+//!
+//! 等待队列按 [`FutexKey`] 索引：
+//! - 私有 futex（`FUTEX_PRIVATE_FLAG`）按 (地址空间, 虚拟地址) 索引，无需查页表；
+//! - 共享 futex 落在共享映射中时按物理地址（物理帧 + 页内偏移）索引，
+//!   不同进程映射同一共享页时能找到同一条等待队列；
+//!   落在私有映射中时与私有 futex 相同，保证与不带 PRIVATE 标志的唤醒
+//!   （如 `clear_child_tid`）互相匹配。
 
+use alloc::sync::Arc;
+use core::mem::MaybeUninit;
 use hashbrown::HashMap;
 
-use crate::{kernel::WaitQueue, sync::SpinLock};
+use crate::{
+    arch::{Arch, ArchImpl},
+    kernel::{EXIT_PRIO_USER_MEMORY, ExitNotifier, SharedTask, WaitQueue, try_current_task},
+    mm::{
+        address::{PageNum, UA, VA, Vpn},
+        memory_space::MemorySpace,
+    },
+    sync::SpinLock,
+    uapi::futex::{
+        FUTEX_OWNER_DIED, FUTEX_TID_MASK, FUTEX_WAITERS, ROBUST_LIST_LIMIT, RobustListHead,
+    },
+};
 
 lazy_static::lazy_static! {
     /// 全局 Futex 管理器实例
     pub static ref FUTEX_MANAGER: SpinLock<FutexManager> = SpinLock::new(FutexManager::new());
 }
 
+/// Futex 等待队列的索引
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FutexKey {
+    /// 进程私有：地址空间标识与用户虚拟地址
+    Private { mm: usize, uaddr: usize },
+    /// 跨进程共享：物理地址
+    Shared { paddr: usize },
+}
+
+/// 计算 `uaddr` 处 futex 的索引
+///
+/// `private` 对应 `FUTEX_PRIVATE_FLAG`。共享 futex 所在页尚未映射时返回 `None`。
+pub fn futex_key(
+    memory_space: &Arc<SpinLock<MemorySpace>>,
+    uaddr: usize,
+    private: bool,
+) -> Option<FutexKey> {
+    let private_key = FutexKey::Private {
+        mm: Arc::as_ptr(memory_space) as usize,
+        uaddr,
+    };
+    if private {
+        return Some(private_key);
+    }
+
+    let space = memory_space.lock();
+    let vpn = Vpn::from_addr_floor(VA::from_usize(uaddr));
+    match space.find_area(vpn) {
+        Some(area) if area.is_shared() => {
+            space
+                .translate(VA::from_usize(uaddr))
+                .map(|pa| FutexKey::Shared {
+                    paddr: pa.as_usize(),
+                })
+        }
+        Some(_) => Some(private_key),
+        None => None,
+    }
+}
+
 /// Futex 管理器，负责管理所有的 Futex 对象
 pub struct FutexManager {
-    futexes: HashMap<usize, WaitQueue>,
+    futexes: HashMap<FutexKey, WaitQueue>,
 }
 
 impl FutexManager {
@@ -22,18 +82,238 @@ impl FutexManager {
         }
     }
 
-    /// 根据用户空间地址获取对应的 Futex 等待队列
-    pub fn get_wait_queue(&mut self, uaddr: usize) -> &mut WaitQueue {
-        self.futexes.entry(uaddr).or_insert_with(WaitQueue::new)
+    /// 根据索引获取对应的 Futex 等待队列
+    pub fn get_wait_queue(&mut self, key: FutexKey) -> &mut WaitQueue {
+        self.futexes.entry(key).or_insert_with(WaitQueue::new)
     }
 
-    pub fn take_wait_queue(&mut self, uaddr: usize) -> WaitQueue {
-        self.futexes.remove(&uaddr).unwrap_or_else(WaitQueue::new)
+    pub fn take_wait_queue(&mut self, key: FutexKey) -> WaitQueue {
+        self.futexes.remove(&key).unwrap_or_else(WaitQueue::new)
     }
 
-    pub fn put_wait_queue(&mut self, uaddr: usize, waitq: WaitQueue) {
+    pub fn put_wait_queue(&mut self, key: FutexKey, waitq: WaitQueue) {
         if !waitq.is_empty() {
-            self.futexes.insert(uaddr, waitq);
+            self.futexes.insert(key, waitq);
         }
     }
 }
+
+/// 处理健壮列表的退出回调，线程调用 set_robust_list 时注册
+pub static ROBUST_LIST_EXIT_NOTIFIER: ExitNotifier = ExitNotifier {
+    name: "robust_list",
+    priority: EXIT_PRIO_USER_MEMORY,
+    call: exit_robust_list,
+};
+
+/// 处理退出线程的健壮 futex 列表
+///
+/// 与 Linux 相同：列表中以及 `pending` 指向的锁若仍由该线程持有，在 futex 字中置
+/// `FUTEX_OWNER_DIED`，有等待者时唤醒一个，使其加锁时得到 `EOWNERDEAD`。
+/// 线程退出时经 [`ROBUST_LIST_EXIT_NOTIFIER`] 调用；execve 换掉地址空间前也要调用，
+/// 旧映像中的列表地址在新映像中已失效。处理后清除任务的健壮列表头。
+///
+/// 用户内存经当前页表访问，只在当前任务与该线程共享地址空间时处理；
+/// 读写用户内存失败时停止遍历。
+pub fn exit_robust_list(task: &SharedTask) {
+    let (tid, head, memory_space) = {
+        let mut t = task.lock();
+        (t.tid, t.robust_list.take(), t.memory_space.clone())
+    };
+    let (Some(head), Some(memory_space)) = (head, memory_space) else {
+        return;
+    };
+    let in_space = try_current_task().is_some_and(|current| {
+        current
+            .lock()
+            .memory_space
+            .as_ref()
+            .is_some_and(|space| Arc::ptr_eq(space, &memory_space))
+    });
+    if !in_space {
+        return;
+    }
+
+    let head = head.as_usize();
+    let Some(list) = read_user::<RobustListHead>(head) else {
+        return;
+    };
+    let offset = list.off as isize;
+    let (pending, pending_pi) = robust_entry(list.pending as usize);
+    let (mut entry, mut pi) = robust_entry(list.head as usize);
+    let mut limit = ROBUST_LIST_LIMIT;
+    while entry != head && limit > 0 {
+        // 先取下一项：处理当前项后它可能被其它线程取得并移出列表
+        let Some(next) = read_user::<usize>(entry) else {
+            return;
+        };
+        if entry != pending {
+            handle_futex_death(
+                &memory_space,
+                entry.wrapping_add_signed(offset),
+                tid,
+                pi,
+                false,
+            );
+        }
+        (entry, pi) = robust_entry(next);
+        limit -= 1;
+    }
+    if pending != 0 {
+        handle_futex_death(
+            &memory_space,
+            pending.wrapping_add_signed(offset),
+            tid,
+            pending_pi,
+            true,
+        );
+    }
+}
+
+/// 拆分健壮列表项指针，最低位标记 PI futex
+fn robust_entry(ptr: usize) -> (usize, bool) {
+    (ptr & !1, ptr & 1 != 0)
+}
+
+/// 持有者 `tid` 退出后 futex 字的新值：保留等待者位并置持有者已退出位
+///
+/// 锁不由 `tid` 持有时返回 `None`。
+fn owner_died_value(uval: u32, tid: u32) -> Option<u32> {
+    (uval & FUTEX_TID_MASK == tid).then_some((uval & FUTEX_WAITERS) | FUTEX_OWNER_DIED)
+}
+
+/// 处理 `uaddr` 处的一个健壮 futex，`pending` 表示它来自 `list_op_pending`
+fn handle_futex_death(
+    memory_space: &Arc<SpinLock<MemorySpace>>,
+    uaddr: usize,
+    tid: u32,
+    pi: bool,
+    pending: bool,
+) {
+    if !uaddr.is_multiple_of(core::mem::align_of::<u32>()) {
+        return;
+    }
+    let Some(uval) = read_user::<u32>(uaddr) else {
+        return;
+    };
+    // 线程在用户态解锁后、唤醒等待者前退出，或被唤醒的等待者在取得锁前退出时，
+    // 锁已空闲但可能仍有等待者睡眠，此时按 Linux 的做法唤醒一个
+    if pending && !pi && uval == 0 {
+        futex_wake_one(memory_space, uaddr);
+        return;
+    }
+    let Some(mval) = owner_died_value(uval, tid) else {
+        return;
+    };
+    // 没有用户内存上的原子比较交换原语，按读-改-写处理
+    let written = unsafe {
+        ArchImpl::copy_to_user(
+            &mval as *const u32 as *const u8,
+            UA::from_usize(uaddr),
+            core::mem::size_of::<u32>(),
+        )
+    };
+    if written.is_ok() && !pi && uval & FUTEX_WAITERS != 0 {
+        futex_wake_one(memory_space, uaddr);
+    }
+}
+
+/// 与 Linux 相同按不带 PRIVATE 标志的 futex 唤醒一个等待者
+fn futex_wake_one(memory_space: &Arc<SpinLock<MemorySpace>>, uaddr: usize) {
+    if let Some(key) = futex_key(memory_space, uaddr, false) {
+        FUTEX_MANAGER.lock().get_wait_queue(key).wake_up_one();
+    }
+}
+
+fn read_user<T>(uaddr: usize) -> Option<T> {
+    let mut val = MaybeUninit::<T>::uninit();
+    unsafe {
+        ArchImpl::copy_from_user(
+            UA::from_usize(uaddr),
+            val.as_mut_ptr() as *mut u8,
+            core::mem::size_of::<T>(),
+        )
+    }
+    .ok()?;
+    Some(unsafe { val.assume_init() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::PAGE_SIZE,
+        kassert,
+        mm::{
+            address::{UsizeConvert, VpnRange},
+            memory_space::mapping_area::{AnonSharedPages, AreaType},
+            page_table::UniversalPTEFlag,
+        },
+        test_case,
+    };
+
+    fn space_with_areas() -> Arc<SpinLock<MemorySpace>> {
+        let mut space = MemorySpace::new().expect("create memory space failed");
+        space
+            .insert_framed_area(
+                VpnRange::new(Vpn::from_usize(0x2000), Vpn::from_usize(0x2001)),
+                AreaType::UserMmap,
+                UniversalPTEFlag::user_rw(),
+                None,
+                None,
+            )
+            .expect("insert private area failed");
+        let pages = AnonSharedPages::new(1).expect("alloc shared pages failed");
+        space
+            .insert_shared_area(
+                VpnRange::new(Vpn::from_usize(0x3000), Vpn::from_usize(0x3001)),
+                UniversalPTEFlag::user_rw(),
+                pages,
+                0,
+            )
+            .expect("insert shared area failed");
+        Arc::new(SpinLock::new(space))
+    }
+
+    test_case!(test_futex_key_shared_mapping_across_fork, {
+        let parent = space_with_areas();
+        let child = Arc::new(SpinLock::new(
+            parent.lock().clone_for_fork().expect("fork failed"),
+        ));
+        let uaddr = 0x3000 * PAGE_SIZE + 8;
+
+        let key = futex_key(&parent, uaddr, false);
+        kassert!(matches!(key, Some(FutexKey::Shared { .. })));
+        kassert!(futex_key(&child, uaddr, false) == key);
+
+        // PRIVATE 标志下即使在共享映射中也按地址空间区分
+        kassert!(futex_key(&parent, uaddr, true) != futex_key(&child, uaddr, true));
+    });
+
+    test_case!(test_robust_futex_owner_died, {
+        let tid = 42;
+        kassert!(owner_died_value(tid, tid) == Some(FUTEX_OWNER_DIED));
+        kassert!(
+            owner_died_value(tid | FUTEX_WAITERS, tid) == Some(FUTEX_WAITERS | FUTEX_OWNER_DIED)
+        );
+        // 已标记过持有者退出的锁被重新取得后仍按 TID 判断
+        kassert!(owner_died_value(tid | FUTEX_OWNER_DIED, tid) == Some(FUTEX_OWNER_DIED));
+        kassert!(owner_died_value(43 | FUTEX_WAITERS, tid).is_none());
+        kassert!(owner_died_value(0, tid).is_none());
+
+        kassert!(robust_entry(0x1000) == (0x1000, false));
+        kassert!(robust_entry(0x1001) == (0x1000, true));
+    });
+
+    test_case!(test_futex_key_private_mapping, {
+        let parent = space_with_areas();
+        let child = Arc::new(SpinLock::new(
+            parent.lock().clone_for_fork().expect("fork failed"),
+        ));
+        let uaddr = 0x2000 * PAGE_SIZE + 4;
+
+        // 私有映射中不带 PRIVATE 标志的 futex 与私有 futex 是同一个
+        kassert!(futex_key(&parent, uaddr, false) == futex_key(&parent, uaddr, true));
+        kassert!(futex_key(&parent, uaddr, false) != futex_key(&child, uaddr, false));
+        kassert!(futex_key(&parent, 0x5000 * PAGE_SIZE, false).is_none());
+    });
+}
@@ -3,20 +3,33 @@
 //! 包括内核线程创建、等待、执行用户程序等功能
 //! 内核任务不具备用户态任务的内存空间和权限
 //! 仅在内核态运行
-use core::{hint, sync::atomic::Ordering};
+//!
+//! [`kthread_spawn`] 创建的线程属于调用者的线程组，启动后无法控制；
+//! [`kthread_create`] 创建的线程是 kthreadd 的子进程，带有名字（在 /proc 和 ps
+//! 中可见），并支持协作式的停止（[`kthread_stop`]）与暂停（[`kthread_park`]）。
+use core::{
+    hint,
+    sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering},
+};
 
+use alloc::collections::btree_map::BTreeMap;
 use alloc::string::ToString;
 use alloc::sync::Arc;
 
+use lazy_static::lazy_static;
+
 use crate::{
+    ipc::{SignalHandlerTable, SignalPending},
     kernel::{
-        TaskState,
+        SharedTask, TaskState,
         cpu::current_cpu,
-        scheduler::Scheduler,
+        current_task,
+        scheduler::{Scheduler, schedule, sleep_task_prepare, wake_up_task},
         task::{TASK_MANAGER, TaskStruct, task_manager::TaskManagerTrait},
     },
     mm::frame_allocator::{alloc_contig_frames, alloc_frame},
-    sync::SpinLock,
+    sync::{Completion, SpinLock},
+    uapi::{errno::EINTR, signal::SignalFlags},
 };
 
 /// 创建一个新的内核线程并返回其 Arc 包装
@@ -110,6 +123,258 @@ pub fn kthread_spawn(entry_point: fn()) -> u32 {
     tid
 }
 
+/// kthreadd 的 PID，[`kthread_create`] 创建的线程以它为父进程
+static KTHREADD_PID: AtomicU32 = AtomicU32::new(0);
+
+/// 记录 kthreadd 的 PID，由 boot 在创建 kthreadd 时调用
+pub fn set_kthreadd_pid(pid: u32) {
+    KTHREADD_PID.store(pid, Ordering::Relaxed);
+}
+
+/// [`kthread_create`] 创建的内核线程的控制块
+pub struct Kthread {
+    func: fn(usize) -> i32,
+    arg: usize,
+    task: SharedTask,
+    /// 已请求停止
+    should_stop: AtomicBool,
+    /// 已请求暂停
+    should_park: AtomicBool,
+    /// 线程已在 [`kthread_parkme`] 中暂停或已退出，[`kthread_unpark`] 时清除
+    parked: Completion,
+    /// 线程函数已返回且线程已进入僵尸状态
+    exited: Completion,
+    /// 线程函数的返回值
+    exit_code: AtomicI32,
+}
+
+impl Kthread {
+    fn new(func: fn(usize) -> i32, arg: usize, task: SharedTask) -> Self {
+        Self {
+            func,
+            arg,
+            task,
+            should_stop: AtomicBool::new(false),
+            should_park: AtomicBool::new(false),
+            parked: Completion::new(),
+            exited: Completion::new(),
+            exit_code: AtomicI32::new(0),
+        }
+    }
+
+    fn request_stop(&self) {
+        self.should_stop.store(true, Ordering::Release);
+        self.should_park.store(false, Ordering::Release);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.should_stop.load(Ordering::Acquire)
+    }
+
+    fn should_park(&self) -> bool {
+        self.should_park.load(Ordering::Acquire)
+    }
+
+    /// 线程已进入僵尸状态：唤醒等待回收或等待暂停的任务
+    fn finish(&self) {
+        self.exited.complete_all();
+        self.parked.complete_all();
+    }
+}
+
+lazy_static! {
+    /// tid -> 控制块，线程被 [`kthread_stop`] 回收时移除
+    static ref KTHREADS: SpinLock<BTreeMap<u32, Arc<Kthread>>> = SpinLock::new(BTreeMap::new());
+}
+
+/// [`kthread_create`] 返回的句柄
+///
+/// 句柄被丢弃不会停止线程；线程函数自行返回后会保持僵尸状态，
+/// 直到 [`kthread_stop`] 回收并取得返回值。
+pub struct KthreadHandle {
+    kthread: Arc<Kthread>,
+}
+
+#[allow(dead_code)]
+impl KthreadHandle {
+    /// 线程的 tid（同时也是其 PID）
+    pub fn tid(&self) -> u32 {
+        self.kthread.task.lock().tid
+    }
+
+    /// 线程对应的任务
+    pub fn task(&self) -> SharedTask {
+        self.kthread.task.clone()
+    }
+
+    /// 启动新创建的线程；线程已在运行时只是唤醒它
+    pub fn wake_up(&self) {
+        wake_up_task(self.kthread.task.clone());
+    }
+}
+
+/// 创建一个带名字的内核线程，但不启动它
+///
+/// 线程是 kthreadd 的子进程（pid == tid），`name` 会截断到 `TASK_COMM_LEN - 1`
+/// 字节后作为进程名。调用 [`KthreadHandle::wake_up`] 后线程才开始执行 `func(arg)`；
+/// 若在此之前已被 [`kthread_stop`]，`func` 不会执行，返回值为 `-EINTR`。
+pub fn kthread_create(name: &str, func: fn(usize) -> i32, arg: usize) -> KthreadHandle {
+    let tid = TASK_MANAGER.lock().allocate_tid();
+    let (uts, rlimit, fd_table, fs) = {
+        let task = current_task();
+        let t = task.lock();
+        (
+            t.uts_namespace.clone(),
+            t.rlimit.clone(),
+            t.fd_table.clone_table(),
+            t.fs.lock().clone(),
+        )
+    };
+
+    let kstack_tracker = alloc_contig_frames(4).expect("kthread_create: failed to alloc kstack");
+    let trap_frame_tracker = alloc_frame().expect("kthread_create: failed to alloc trap_frame");
+
+    let mut task = TaskStruct::ktask_create(
+        tid,
+        tid,
+        KTHREADD_PID.load(Ordering::Relaxed),
+        TaskStruct::empty_children(),
+        kstack_tracker,
+        trap_frame_tracker,
+        Arc::new(SpinLock::new(SignalHandlerTable::new())),
+        SignalFlags::empty(),
+        Arc::new(SpinLock::new(SignalPending::empty())),
+        uts,
+        rlimit,
+        Arc::new(fd_table),
+        Arc::new(SpinLock::new(fs)),
+    );
+    task.set_comm(name.as_bytes());
+    // 在第一次 wake_up 之前不进入运行队列
+    task.state = TaskState::Uninterruptible;
+
+    let tf = task.trap_frame_ptr.load(Ordering::SeqCst);
+    // SAFETY: 此时 trap_frame_tracker 已经分配完毕且不可变更，所有权在 task 中，指针有效
+    unsafe {
+        crate::arch::init_kernel_trap_frame(
+            tf,
+            kthread_main as usize,
+            0,
+            task.kstack_base.as_usize(),
+        );
+    }
+    let task = task.into_shared();
+    let kthread = Arc::new(Kthread::new(func, arg, task.clone()));
+    KTHREADS.lock().insert(tid, kthread.clone());
+    TASK_MANAGER.lock().add_task(task);
+
+    crate::pr_debug!("[kthread] Created {} (tid {})", name, tid);
+    KthreadHandle { kthread }
+}
+
+/// 创建并立即启动一个带名字的内核线程
+pub fn kthread_run(name: &str, func: fn(usize) -> i32, arg: usize) -> KthreadHandle {
+    let handle = kthread_create(name, func, arg);
+    handle.wake_up();
+    handle
+}
+
+/// 当前任务的控制块，不是 [`kthread_create`] 创建的线程时返回 `None`
+fn current_kthread() -> Option<Arc<Kthread>> {
+    let tid = current_task().lock().tid;
+    KTHREADS.lock().get(&tid).cloned()
+}
+
+/// [`kthread_create`] 创建的线程的入口
+fn kthread_main() {
+    let kthread = current_kthread().expect("kthread_main: not a kthread");
+    let code = if kthread.should_stop() {
+        -EINTR
+    } else {
+        (kthread.func)(kthread.arg)
+    };
+    kthread.exit_code.store(code, Ordering::Release);
+
+    // 只结束本线程：进入僵尸状态等待 kthread_stop 回收，不通知 kthreadd
+    TASK_MANAGER.lock().exit_task(kthread.task.clone(), code);
+    kthread.finish();
+    drop(kthread);
+    schedule();
+    unreachable!("kthread_main: should not return after exit");
+}
+
+/// 当前内核线程是否已被请求停止
+///
+/// 线程函数应在主循环中检查它，返回 `true` 时尽快返回。
+/// 不是 [`kthread_create`] 创建的线程时总是返回 `false`。
+pub fn kthread_should_stop() -> bool {
+    current_kthread().is_some_and(|k| k.should_stop())
+}
+
+/// 当前内核线程是否已被请求暂停，返回 `true` 时应调用 [`kthread_parkme`]
+#[allow(dead_code)]
+pub fn kthread_should_park() -> bool {
+    current_kthread().is_some_and(|k| k.should_park())
+}
+
+/// 暂停当前内核线程，直到 [`kthread_unpark`] 或 [`kthread_stop`]
+#[allow(dead_code)]
+pub fn kthread_parkme() {
+    let Some(kthread) = current_kthread() else {
+        return;
+    };
+    let task = current_task();
+    while kthread.should_park() {
+        kthread.parked.complete_all();
+        // 检查与睡眠在锁内完成，不会错过 unpark 的唤醒
+        if sleep_task_prepare(task.clone(), false, |_| !kthread.should_park()) {
+            schedule();
+        }
+    }
+}
+
+/// 请求线程暂停并等待它进入 [`kthread_parkme`]
+///
+/// 线程须配合检查 [`kthread_should_park`]；线程已退出时立即返回。
+#[allow(dead_code)]
+pub fn kthread_park(handle: &KthreadHandle) {
+    let kthread = &handle.kthread;
+    kthread.should_park.store(true, Ordering::Release);
+    wake_up_task(kthread.task.clone());
+    kthread.parked.wait_for_completion();
+}
+
+/// 让暂停的线程继续运行
+#[allow(dead_code)]
+pub fn kthread_unpark(handle: &KthreadHandle) {
+    let kthread = &handle.kthread;
+    if kthread.exited.is_done() {
+        return;
+    }
+    kthread.parked.reinit();
+    kthread.should_park.store(false, Ordering::Release);
+    wake_up_task(kthread.task.clone());
+}
+
+/// 请求线程停止，等待线程函数返回并回收线程
+///
+/// 线程须配合检查 [`kthread_should_stop`]。暂停或睡眠中的线程会被唤醒。
+/// # 返回值
+/// 线程函数的返回值；线程从未启动时为 `-EINTR`
+#[allow(dead_code)]
+pub fn kthread_stop(handle: KthreadHandle) -> i32 {
+    let kthread = handle.kthread;
+    kthread.request_stop();
+    wake_up_task(kthread.task.clone());
+    kthread.exited.wait_for_completion();
+
+    let tid = kthread.task.lock().tid;
+    KTHREADS.lock().remove(&tid);
+    TASK_MANAGER.lock().release_task(kthread.task.clone());
+    crate::pr_debug!("[kthread] Stopped tid {}", tid);
+    kthread.exit_code.load(Ordering::Acquire)
+}
+
 /// 等待指定 tid 的任务结束
 /// 该函数会阻塞调用者直到目标任务状态变为 Stopped
 /// 如果目标任务不存在则立即返回错误码
@@ -159,6 +424,19 @@ pub fn kernel_execve(path: &str, argv: &[&str], envp: &[&str]) -> ! {
     crate::pr_info!("[kernel_execve] Loading: {}", path);
     let prepared =
         super::prepare_exec_image_from_path(path).expect("kernel_execve: failed to prepare image");
+    kernel_execve_prepared(path, prepared, argv, envp)
+}
+
+/// 在内核任务中执行已准备好的可执行映像
+///
+/// 与 [`kernel_execve`] 相同，但映像由调用者通过 `prepare_exec_image` 等方式
+/// 事先加载，`path` 只用于设置进程名和 exe 路径。
+pub fn kernel_execve_prepared(
+    path: &str,
+    prepared: super::PreparedExecImage,
+    argv: &[&str],
+    envp: &[&str],
+) -> ! {
     crate::pr_info!(
         "[kernel_execve] Prepared image, pc=0x{:x}, at_base=0x{:x}, at_entry=0x{:x}",
         prepared.initial_pc.as_usize(),
@@ -187,6 +465,7 @@ pub fn kernel_execve(path: &str, argv: &[&str], envp: &[&str]) -> ! {
     crate::arch::disable_interrupts();
     {
         let mut t = task.lock();
+        t.set_exec_comm(path);
         t.exe_path = Some(path.to_string());
         t.execve(
             space,
@@ -288,6 +567,27 @@ mod tests {
     //     kassert!(TASK_MANAGER.lock().get_task(tid).is_none());
     // });
 
+    fn dummy_kthread(_arg: usize) -> i32 {
+        0
+    }
+
+    // 测试 Kthread 控制标志：停止请求会取消暂停请求，返回值在 finish 后可见
+    test_case!(test_kthread_flags, {
+        let kthread = Kthread::new(dummy_kthread, 0, mk_task(0xFFFF_FFF0));
+        kassert!(!kthread.should_stop() && !kthread.should_park());
+        kthread.should_park.store(true, Ordering::Release);
+        kassert!(kthread.should_park());
+        kthread.request_stop();
+        kassert!(kthread.should_stop());
+        kassert!(!kthread.should_park());
+        kassert!(!kthread.exited.is_done());
+        kthread.exit_code.store(-EINTR, Ordering::Release);
+        kthread.finish();
+        kassert!(kthread.exited.is_done());
+        kassert!(kthread.parked.is_done());
+        kassert!(kthread.exit_code.load(Ordering::Acquire) == -EINTR);
+    });
+
     // 测试 kthread_join 失败路径：不存在的 tid
     test_case!(test_kthread_join_not_found, {
         // 选择一个极小概率已存在的高 tid（或先确保不存在）
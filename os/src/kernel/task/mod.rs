@@ -8,6 +8,8 @@ mod cap;
 mod cred;
 #[cfg(feature = "proc")]
 mod exec_loader;
+pub mod executor;
+mod exit_notifier;
 #[cfg(feature = "proc")]
 mod futex;
 mod ktask;
@@ -22,6 +24,7 @@ pub use cap::*;
 pub use cred::*;
 #[cfg(feature = "proc")]
 pub use exec_loader::*;
+pub use exit_notifier::*;
 #[cfg(feature = "proc")]
 pub use futex::*;
 pub use ktask::*;
@@ -31,6 +34,7 @@ pub use task_state::TaskState;
 pub use task_struct::FsStruct;
 pub use task_struct::SharedTask;
 pub use task_struct::ShmAttachment;
+pub use task_struct::TASK_COMM_LEN;
 pub use task_struct::Task as TaskStruct;
 pub use task_struct::TaskExitStatus;
 pub use work_queue::*;
@@ -40,7 +44,7 @@ use alloc::sync::Arc;
 use crate::ipc::shm_detach_segment;
 use crate::mm::{address::VA, memory_space::MemorySpace};
 use crate::sync::SpinLock;
-use crate::uapi::signal::NUM_SIGCHLD;
+use crate::uapi::signal::{NUM_SIGCHLD, SaFlags};
 use crate::{
     kernel::{cpu::current_cpu, schedule},
     vfs::{FDTable, File, FsError},
@@ -95,9 +99,11 @@ pub(crate) fn terminate_task(code: usize) -> ! {
                 cleanup_process_resources_on_exit(leader.clone());
                 exit_process(leader, exit_code);
             } else {
+                exit_notify(&task);
                 TASK_MANAGER.lock().exit_task(task, exit_code);
             }
         } else {
+            exit_notify(&task);
             TASK_MANAGER.lock().exit_task(task, exit_code);
         }
     }
@@ -112,6 +118,7 @@ pub fn task_group_leader(task: &SharedTask) -> Option<SharedTask> {
 }
 
 /// 进程退出时的资源清理（Linux 语义子集）：
+/// - 调用线程组中各任务的退出回调链（健壮列表、记录锁、定时器、共享内存等）
 /// - 释放用户地址空间（页表 + 用户映射）
 /// - 关闭打开文件描述符（包括 socket fd）
 ///
@@ -129,6 +136,9 @@ pub fn cleanup_process_resources_on_exit(task: SharedTask) {
         return;
     }
 
+    // 0) 调用退出回调链：回调可能访问用户内存，需在切换页表前完成。
+    exit_notify_group(&task);
+
     // 1) 先切换到全局内核页表，避免释放“正在使用的 satp”。
     if let Some(kernel_space) = crate::mm::get_global_kernel_space() {
         let _guard = crate::sync::PreemptGuard::new();
@@ -150,16 +160,20 @@ pub fn cleanup_process_resources_on_exit(task: SharedTask) {
         drop(file);
     }
 
-    // 3) 分离 SysV shared memory 映射，更新全局 registry 的 attach 计数。
-    detach_all_shm(task.clone());
-
-    // 4) 释放用户地址空间。
+    // 3) 释放用户地址空间。
     task.lock().memory_space = None;
 }
 
+/// 分离 SysV shared memory 映射的退出回调，在进程第一次持有映射时注册到 leader 上
+pub static SHM_EXIT_NOTIFIER: ExitNotifier = ExitNotifier {
+    name: "shm",
+    priority: EXIT_PRIO_ADDRESS_SPACE,
+    call: |task| detach_all_shm(task.clone()),
+};
+
 /// 分离一个进程持有的所有 SysV shared memory 映射。
 ///
-/// 调用方可以在 exit/execve 清理路径中使用。该函数会先从 Task 中取走
+/// 进程退出时经 [`SHM_EXIT_NOTIFIER`] 调用，execve 时直接调用。该函数会先从 Task 中取走
 /// attachment 元数据，再释放 task 锁后执行 munmap 和 registry 更新，避免
 /// task -> address_space -> shm registry 的嵌套锁长期持有。
 pub fn detach_all_shm(task: SharedTask) {
@@ -229,6 +243,30 @@ pub fn current_memory_space() -> Arc<SpinLock<MemorySpace>> {
         .clone()
 }
 
+/// 通知父进程子进程被停止或继续
+///
+/// 父进程的 SIGCHLD 动作带 `SA_NOCLDSTOP` 时不发送信号，但仍唤醒在 wait4 中
+/// 等待的父进程。调用者须已持有任务管理器的锁。
+/// # 参数：
+/// * `tm`: 已加锁的任务管理器
+/// * `task`: 子进程（线程组 leader）
+pub fn notify_parent_jobctl(tm: &task_manager::TaskManager, task: &SharedTask) {
+    let ppid = task.lock().ppid;
+    let Some(parent) = tm.get_task(ppid) else {
+        return;
+    };
+    let nocldstop = {
+        let p = parent.lock();
+        let action = p.signal_handlers.lock().actions[NUM_SIGCHLD];
+        SaFlags::from_bits_truncate(action.sa_flags as u32).contains(SaFlags::NOCLDSTOP)
+    };
+    if !nocldstop {
+        tm.send_signal(parent.clone(), NUM_SIGCHLD);
+    }
+    let wait_child = parent.lock().wait_child.clone();
+    wait_child.lock().wake_up_one();
+}
+
 /// 通知父任务子任务状态变化
 /// # 参数：
 /// * `task`: 子任务
@@ -3,12 +3,15 @@
 //! 已经采用多任务设计的内核，进程作为任务的一种特殊形式存在。
 //! 故此模块变得相对简单，主要负责适配传统的进程概念与内核任务之间的关系。
 
+use alloc::vec::Vec;
+
 use crate::{
+    ipc::{QueuedSignal, is_job_control_signal, prepare_signal, sig_ignored, sigpending_limit},
     kernel::{
         SharedTask, TASK_MANAGER, TaskExitStatus, TaskManagerTrait, TaskState, notify_parent,
-        wake_up_task,
+        notify_parent_jobctl, wake_up_task,
     },
-    uapi::signal::SignalFlags,
+    uapi::{errno::EINVAL, signal::SignalFlags},
 };
 
 /// 进程退出处理
@@ -35,6 +38,7 @@ pub fn exit_process_with_status(task: SharedTask, status: TaskExitStatus) {
             t.get_task(1).expect("init process not found"),
         )
     };
+    let mut pdeath = Vec::new();
     {
         let init = init_task.lock();
         let mut pchild = init.children.lock();
@@ -42,8 +46,15 @@ pub fn exit_process_with_status(task: SharedTask, status: TaskExitStatus) {
             let mut c = child.lock();
             pchild.push(child.clone());
             c.ppid = init.pid;
+            if c.pdeath_signal != 0 {
+                pdeath.push((child.clone(), c.pdeath_signal as usize));
+            }
         }
     }
+    // PR_SET_PDEATHSIG：在子进程被过继给 init 之后发送，子进程中 getppid 已返回 1
+    for (child, sig) in pdeath {
+        send_signal_process(&child, sig);
+    }
     {
         let leader_tid = task.lock().tid;
         let mut t = TASK_MANAGER.lock();
@@ -65,21 +76,47 @@ pub fn exit_process_with_status(task: SharedTask, status: TaskExitStatus) {
 /// * `task` - 目标进程对应的任务
 /// * `sig` - 要发送的信号编号
 pub fn send_signal_process(task: &SharedTask, sig: usize) {
+    let _ = send_signal_process_info(task, QueuedSignal::kernel(sig));
+}
+
+/// 向进程发送带 siginfo 的信号，信号挂到线程组共享的 pending 上
+/// # 参数：
+/// * `task` - 目标进程对应的任务
+/// * `info` - 信号实例
+/// # 返回值：
+/// 信号编号非法返回 EINVAL，实时信号队列已满返回 EAGAIN
+pub fn send_signal_process_info(task: &SharedTask, info: QueuedSignal) -> Result<(), i32> {
+    let sig = info.signo;
     let Some(flag) = SignalFlags::from_signal_num(sig) else {
-        return;
+        return Err(EINVAL);
     };
 
-    // Insert into the (possibly shared) pending set first.
-    let pid = {
-        let t = task.lock();
-        t.shared_pending.lock().signals.insert(flag);
-        t.pid
+    let pid = task.lock().pid;
+    let candidates = {
+        let tm = TASK_MANAGER.lock();
+        let threads = tm.get_task_cond(|t| t.lock().pid == pid);
+        if is_job_control_signal(sig)
+            && let Some(leader) = prepare_signal(sig, &threads)
+        {
+            notify_parent_jobctl(&tm, &leader);
+        }
+        threads
     };
 
+    // 被忽略的信号在产生时即丢弃（作业控制的副作用已在上面生效）
+    {
+        let t = task.lock();
+        if sig_ignored(&t, sig) {
+            return Ok(());
+        }
+        // Insert into the (possibly shared) pending set.
+        let limit = sigpending_limit(&t);
+        t.shared_pending.lock().enqueue(info, limit)?;
+    }
+
     // Choose one thread in the thread group to wake (Linux will pick a suitable thread).
     // Without this, delivering a process-wide signal to the leader may not wake the thread
     // currently blocked in select/poll/recv, causing signals to be processed late.
-    let candidates = TASK_MANAGER.lock().get_task_cond(|t| t.lock().pid == pid);
     for thr in candidates {
         let should_wake = {
             let t = thr.lock();
@@ -90,4 +127,5 @@ pub fn send_signal_process(task: &SharedTask, sig: usize) {
             break;
         }
     }
+    Ok(())
 }
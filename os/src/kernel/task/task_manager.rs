@@ -9,10 +9,14 @@
 use alloc::collections::btree_map::BTreeMap;
 use alloc::vec::Vec;
 
+use crate::ipc::{
+    QueuedSignal, is_job_control_signal, prepare_signal, sig_ignored, sigpending_limit,
+};
 use crate::kernel::task::SharedTask;
 use crate::kernel::task::tid_allocator::TidAllocator;
-use crate::kernel::{TaskExitStatus, TaskState, exit_task, wake_up_task};
+use crate::kernel::{TaskExitStatus, TaskState, exit_task, notify_parent_jobctl, wake_up_task};
 use crate::sync::SpinLock;
+use crate::uapi::errno::EINVAL;
 use crate::uapi::signal::SignalFlags;
 
 use lazy_static::lazy_static;
@@ -67,6 +71,11 @@ pub trait TaskManagerTrait {
     /// 返回值: 符合条件的任务列表
     fn get_task_cond(&self, cond: impl Fn(&SharedTask) -> bool) -> Vec<SharedTask>;
 
+    /// 按任务 ID 升序遍历所有任务，不分配内存
+    /// 参数:
+    /// * `f`: 对每个任务调用的函数，参数为任务 ID 和任务
+    fn for_each_task(&self, f: impl FnMut(u32, &SharedTask));
+
     /// 获取进程（线程组）内所有线程
     /// 参数：
     /// * `pid`: 进程 ID
@@ -86,6 +95,13 @@ pub trait TaskManagerTrait {
     /// 返回值: 如果任务存在且信号发送成功则返回 true，否则返回 false
     fn send_signal(&self, task: SharedTask, signal: usize) -> bool;
 
+    /// 发送带 siginfo 的信号给指定任务
+    /// 参数：
+    /// * `task`: 目标任务对应的 SharedTask
+    /// * `info`: 信号实例（编号、来源、发送者等）
+    /// 返回值: 成功返回 Ok(())；信号编号非法返回 EINVAL，实时信号队列已满返回 EAGAIN
+    fn send_signal_info(&self, task: SharedTask, info: QueuedSignal) -> Result<(), i32>;
+
     /// 获取所有任务
     /// 返回值: 所有任务的列表
     fn get_all_tasks(&self) -> Vec<SharedTask>;
@@ -95,10 +111,12 @@ pub trait TaskManagerTrait {
     /// 返回值按升序排列，且去重。
     fn list_process_pids_snapshot(&self) -> Vec<u32>;
 
-    #[cfg(test)]
-    /// 获取当前任务数量（仅用于测试）
+    /// 获取当前任务数量（含尚未回收的僵尸任务）
     /// 返回值: 当前任务数量
     fn task_count(&self) -> usize;
+
+    /// 最近一次分配的任务 ID
+    fn last_tid(&self) -> u32;
 }
 
 /// 任务管理器，负责管理所有任务的生命周期和调度
@@ -161,6 +179,12 @@ impl TaskManagerTrait for TaskManager {
         v
     }
 
+    fn for_each_task(&self, mut f: impl FnMut(u32, &SharedTask)) {
+        for (&tid, task) in &self.tasks {
+            f(tid, task);
+        }
+    }
+
     fn get_process_threads(&self, process: SharedTask) -> Vec<SharedTask> {
         let mut v = Vec::new();
         let pid = process.lock().pid;
@@ -177,17 +201,33 @@ impl TaskManagerTrait for TaskManager {
     }
 
     fn send_signal(&self, task: SharedTask, signal: usize) -> bool {
-        if let Some(signal_flag) = SignalFlags::from_signal_num(signal) {
-            let mut t = task.lock();
-            t.pending.signals.insert(signal_flag);
-            if t.state == TaskState::Interruptible {
-                drop(t);
-                wake_up_task(task.clone());
+        self.send_signal_info(task, QueuedSignal::kernel(signal))
+            .is_ok()
+    }
+
+    fn send_signal_info(&self, task: SharedTask, info: QueuedSignal) -> Result<(), i32> {
+        let signal = info.signo;
+        if SignalFlags::from_signal_num(signal).is_none() {
+            return Err(EINVAL);
+        }
+        if is_job_control_signal(signal) {
+            let pid = task.lock().pid;
+            let threads = self.get_task_cond(|t| t.lock().pid == pid);
+            if let Some(leader) = prepare_signal(signal, &threads) {
+                notify_parent_jobctl(self, &leader);
             }
-            true
-        } else {
-            false
         }
+        let mut t = task.lock();
+        if sig_ignored(&t, signal) {
+            return Ok(());
+        }
+        let limit = sigpending_limit(&t);
+        t.pending.enqueue(info, limit)?;
+        if t.state == TaskState::Interruptible {
+            drop(t);
+            wake_up_task(task.clone());
+        }
+        Ok(())
     }
 
     fn get_all_tasks(&self) -> Vec<SharedTask> {
@@ -209,10 +249,13 @@ impl TaskManagerTrait for TaskManager {
         pids
     }
 
-    #[cfg(test)]
     fn task_count(&self) -> usize {
         self.tasks.len()
     }
+
+    fn last_tid(&self) -> u32 {
+        self.tid_allocator.last_allocated()
+    }
 }
 
 #[cfg(test)]
@@ -293,4 +336,38 @@ mod tests {
         kassert!(tm.task_count() == 0);
         kassert!(tm.get_task(tid).is_none());
     });
+
+    // 作业控制信号在产生时互相抵消，SIGCONT 撤销停止请求并记下继续事件
+    test_case!(test_task_manager_send_job_control_signals, {
+        use crate::ipc::JobCtlReport;
+        use crate::uapi::signal::{NUM_SIGCONT, NUM_SIGSTOP, NUM_SIGTSTP};
+
+        let mut tm = TaskManager::new();
+        let tid = tm.allocate_tid();
+        let task = new_dummy_task(tid);
+        tm.add_task(task.clone());
+        let sigstop = SignalFlags::from_signal_num(NUM_SIGSTOP).unwrap();
+        let sigcont = SignalFlags::from_signal_num(NUM_SIGCONT).unwrap();
+
+        kassert!(tm.send_signal(task.clone(), NUM_SIGSTOP));
+        task.lock().jobctl.stop_pending = true;
+
+        // SIGCONT 的默认动作在产生时完成，未屏蔽时不会挂起；屏蔽后才能观察到它被丢弃
+        task.lock().blocked.insert(sigcont);
+        kassert!(tm.send_signal(task.clone(), NUM_SIGCONT));
+        {
+            let t = task.lock();
+            kassert!(!t.pending.signals.contains(sigstop));
+            kassert!(t.pending.signals.contains(sigcont));
+            kassert!(!t.jobctl.stop_pending);
+            kassert!(t.jobctl.report == Some(JobCtlReport::Continued));
+        }
+
+        kassert!(tm.send_signal(task.clone(), NUM_SIGTSTP));
+        kassert!(!task.lock().pending.signals.contains(sigcont));
+
+        task.lock().blocked.remove(sigcont);
+        kassert!(tm.send_signal(task.clone(), NUM_SIGCONT));
+        kassert!(!task.lock().pending.signals.contains(sigcont));
+    });
 }
@@ -12,9 +12,10 @@ use crate::{
         kernel::{context::Context, task::setup_exec_stack_layout},
         task::ExecTlsTemplate,
     },
-    ipc::{ShmSegment, SignalHandlerTable, SignalPending},
+    ipc::{JobCtl, RestartBlock, ShmSegment, SignalHandlerTable, SignalPending},
     kernel::{
-        WaitQueue,
+        ExitNotifierChain, WaitQueue,
+        schedstat::SchedStat,
         task::{forkret, task_state::TaskState},
     },
     mm::{
@@ -37,6 +38,9 @@ use crate::{
 /// 用于在多个地方引用同一个任务实例
 pub type SharedTask = Arc<SpinLock<Task>>;
 
+/// 任务名缓冲区的长度，含结尾的 NUL
+pub const TASK_COMM_LEN: usize = 16;
+
 #[derive(Debug, Clone)]
 pub struct ShmAttachment {
     pub addr: usize,
@@ -91,10 +95,22 @@ pub struct Task {
     pub sched_priority: i32,
     /// fork/clone 时是否将子任务调度属性重置为普通策略。
     pub sched_reset_on_fork: bool,
-    /// fair 调度类的虚拟运行时间。数值越小越应该被调度。
+    /// fair 调度类的加权虚拟运行时间（纳秒）。数值越小越应该被调度。
     pub vruntime: u64,
     /// 累计运行 tick，用于调度统计与调试。
     pub exec_ticks: u64,
+    /// 累计实际运行时间（纳秒）
+    pub sum_exec_runtime: u64,
+    /// 最近一次运行时间记账的调度时钟（纳秒），0 表示尚未被调度运行
+    pub exec_start: u64,
+    /// 运行队列等待、阻塞时间等调度统计，见 /proc/\[pid\]/schedstat
+    pub sched_stat: SchedStat,
+    /// 定时睡眠的唤醒允许推迟的时间（纳秒），见 `prctl(PR_SET_TIMERSLACK)`
+    pub timer_slack_ns: u64,
+    /// `PR_SET_TIMERSLACK` 传 0 时恢复的值，fork 时取父任务当时的 slack
+    pub default_timer_slack_ns: u64,
+    /// I/O 优先级（`ioprio_set` 编码：类别 << 13 | 级别），0 表示按 CPU 调度属性推导
+    pub io_priority: u16,
     /// Linux-ish OOM badness adjustment exposed through /proc/[pid]/oom_score_adj.
     pub oom_score_adj: i32,
     /// 任务当前的状态
@@ -108,6 +124,11 @@ pub struct Task {
     ///
     /// 由 execve/kernel_execve 在切换到新程序前更新。
     pub exe_path: Option<String>,
+    /// 任务名，以 NUL 结尾
+    ///
+    /// 创建时为 `task_<tid>`，fork 时继承，execve 时改为程序文件名，
+    /// 也可由 `prctl(PR_SET_NAME)` 修改。
+    pub comm: [u8; TASK_COMM_LEN],
     /// 父任务的id
     pub ppid: u32,
     /// 任务的进程组id
@@ -128,6 +149,8 @@ pub struct Task {
     pub exit_status: Option<TaskExitStatus>,
     /// 内核栈跟踪器
     kstack_tracker: FrameRangeTracker,
+    /// 内核栈金丝雀，写在内核栈最低地址处，用于发现内核栈溢出
+    stack_canary: u64,
     /// 任务的 TrapFrame 跟踪器
     trap_frame_tracker: FrameTracker,
     /// 信号屏蔽字
@@ -138,10 +161,16 @@ pub struct Task {
     pub shared_pending: Arc<SpinLock<SignalPending>>,
     /// 信号处理动作表
     pub signal_handlers: Arc<SpinLock<SignalHandlerTable>>,
+    /// 作业控制状态（停止请求与待父进程取走的停止/继续事件）
+    pub jobctl: JobCtl,
+    /// 被信号打断的系统调用留给 restart_syscall 的续作信息
+    pub restart_block: Option<RestartBlock>,
     /// 备用信号栈信息
     pub signal_stack: Arc<SpinLock<SignalStack>>,
     /// 退出信号, 当任务退出时发送给父任务的信号
     pub exit_signal: u8,
+    /// 父进程退出时发给本任务的信号，0 表示不发送（`PR_SET_PDEATHSIG`）
+    pub pdeath_signal: u8,
     /// UTS 命名空间
     pub uts_namespace: Arc<SpinLock<UtsNamespace>>,
     /// 资源限制结构体
@@ -156,16 +185,18 @@ pub struct Task {
     // === 权限和凭证 ===
     /// 任务凭证（用户、组、能力）
     pub credential: super::Credential,
-    /// 文件创建掩码
-    pub umask: u32,
 
     // === 文件系统 ===
     /// 文件描述符表
     pub fd_table: Arc<FDTable>,
-    /// 文件系统信息
+    /// 文件系统信息（cwd、根目录和 umask），带 `CLONE_FS` 创建的任务之间共享
     pub fs: Arc<SpinLock<FsStruct>>,
     /// 当前进程附加的 SysV shared memory 段，按 attach 地址索引。
     pub shm_attachments: ShmAttachmentTable,
+
+    // === 退出 ===
+    /// 退出时调用的资源回收回调
+    pub exit_notifiers: ExitNotifierChain,
 }
 
 /// 文件系统信息相关结构体
@@ -175,11 +206,34 @@ pub struct FsStruct {
     pub cwd: Option<Arc<Dentry>>,
     /// 根目录
     pub root: Option<Arc<Dentry>>,
+    /// 文件创建掩码
+    pub umask: u32,
 }
 
 impl FsStruct {
     pub fn new(cwd: Option<Arc<Dentry>>, root: Option<Arc<Dentry>>) -> Self {
-        Self { cwd, root }
+        Self {
+            cwd,
+            root,
+            umask: 0o022,
+        }
+    }
+
+    /// clone 时子任务使用的文件系统信息
+    ///
+    /// `share` 为真（`CLONE_FS`）时与父任务共享同一份，任一方的 chdir、chroot
+    /// 和 umask 对另一方可见；否则复制一份，此后互不影响。
+    pub fn clone_for_child(fs: &Arc<SpinLock<Self>>, share: bool) -> Arc<SpinLock<Self>> {
+        if share {
+            fs.clone()
+        } else {
+            Arc::new(SpinLock::new(fs.lock().clone()))
+        }
+    }
+
+    /// 按 umask 屏蔽 `mode` 中的权限位
+    pub fn apply_umask(&self, mode: u32) -> u32 {
+        mode & !self.umask
     }
 }
 
@@ -315,6 +369,12 @@ impl Task {
         new_fd_table.close_exec();
         self.fd_table = Arc::new(new_fd_table);
 
+        // 捕获的信号恢复为默认动作，忽略的保持忽略；备用信号栈属于旧地址空间，一并取消。
+        // 屏蔽字和待处理信号保持不变
+        let new_handlers = self.signal_handlers.lock().for_exec();
+        self.signal_handlers = Arc::new(SpinLock::new(new_handlers));
+        self.signal_stack = Arc::new(SpinLock::new(SignalStack::default()));
+
         let tf_ptr = self.trap_frame_ptr.load(Ordering::SeqCst);
 
         // 注意：以下拷贝时对sp进行的操作均要求已经可以访问用户栈空间
@@ -426,8 +486,14 @@ impl Task {
     ) -> Self {
         let trap_frame_ptr = trap_frame_tracker.ppn().start_addr().to_va().as_usize();
         let kstack_base = kstack_tracker.end_ppn().start_addr().to_va();
+        // 最低字节清零，使字符串溢出无法恰好写出金丝雀
+        let stack_canary = crate::security::get_random_u64() & !0xff;
+        // SAFETY: 内核栈由 kstack_tracker 独占持有，最低地址处的 8 字节可写
+        unsafe {
+            Self::canary_slot(&kstack_tracker).write_volatile(stack_canary);
+        }
 
-        Task {
+        let mut task = Task {
             context: Context::zero_init(),
             preempt_count: 0,
             priority: 0,
@@ -439,17 +505,25 @@ impl Task {
             sched_reset_on_fork: false,
             vruntime: 0,
             exec_ticks: 0,
+            sum_exec_runtime: 0,
+            exec_start: 0,
+            sched_stat: SchedStat::default(),
+            timer_slack_ns: crate::kernel::DEFAULT_TIMER_SLACK_NS,
+            default_timer_slack_ns: crate::kernel::DEFAULT_TIMER_SLACK_NS,
+            io_priority: 0,
             oom_score_adj: 0,
             state: TaskState::Running,
             tid,
             pid,
             exe_path: None,
+            comm: [0; TASK_COMM_LEN],
             ppid,
             pgid,
             children,
             wait_child: Arc::new(SpinLock::new(WaitQueue::new())),
             kstack_base,
             kstack_tracker,
+            stack_canary,
             trap_frame_tracker,
             trap_frame_ptr: AtomicPtr::new(trap_frame_ptr as *mut TrapFrame),
             memory_space,
@@ -457,20 +531,25 @@ impl Task {
             signal_handlers,
             signal_stack,
             exit_signal,
+            pdeath_signal: 0,
             uts_namespace,
             rlimit,
             blocked,
             pending: SignalPending::empty(),
             shared_pending,
+            jobctl: JobCtl::default(),
+            restart_block: None,
             robust_list: None,
             set_child_tid: None,
             clear_child_tid: None,
             credential: super::Credential::root(),
-            umask: 0o022,
             fd_table,
             fs,
             shm_attachments: Arc::new(SpinLock::new(BTreeMap::new())),
-        }
+            exit_notifiers: ExitNotifierChain::new(),
+        };
+        task.set_comm(alloc::format!("task_{}", tid).as_bytes());
+        task
     }
 
     #[cfg(test)]
@@ -504,9 +583,61 @@ impl Task {
     }
 }
 
+impl Task {
+    /// 任务名，不含结尾的 NUL
+    pub fn comm(&self) -> String {
+        let len = self
+            .comm
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(TASK_COMM_LEN);
+        String::from_utf8_lossy(&self.comm[..len]).into_owned()
+    }
+
+    /// 设置任务名，在第一个 NUL 处截止，超过 `TASK_COMM_LEN - 1` 字节的部分被截断
+    pub fn set_comm(&mut self, name: &[u8]) {
+        let len = name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(name.len())
+            .min(TASK_COMM_LEN - 1);
+        self.comm = [0; TASK_COMM_LEN];
+        self.comm[..len].copy_from_slice(&name[..len]);
+    }
+
+    /// execve 时以程序路径的最后一个分量作为任务名
+    pub fn set_exec_comm(&mut self, path: &str) {
+        self.set_comm(path.rsplit('/').next().unwrap_or(path).as_bytes());
+    }
+
+    /// 金丝雀在内核栈中的位置（栈向下增长，放在最低地址处）
+    fn canary_slot(kstack_tracker: &FrameRangeTracker) -> *mut u64 {
+        kstack_tracker.start_ppn().start_addr().to_va().as_usize() as *mut u64
+    }
+
+    /// 内核栈金丝雀是否完好
+    pub fn stack_canary_intact(&self) -> bool {
+        // SAFETY: 内核栈在任务存活期间一直有效
+        unsafe { Self::canary_slot(&self.kstack_tracker).read_volatile() == self.stack_canary }
+    }
+
+    /// 检查内核栈金丝雀（仅 debug 构建），被破坏说明内核栈已溢出
+    pub fn check_stack_canary(&self) {
+        if cfg!(debug_assertions) && !self.stack_canary_intact() {
+            panic!(
+                "kernel stack overflow detected: tid={}, stack bottom={}",
+                self.tid,
+                crate::log::hashed_ptr(Self::canary_slot(&self.kstack_tracker) as usize)
+            );
+        }
+    }
+}
+
 impl Drop for Task {
     fn drop(&mut self) {
         pr_debug!("Dropping Task {}", self.tid);
+        // 内核栈随后才释放，这里是任务退出后最后一次检查的机会
+        self.check_stack_canary();
     }
 }
 // /// 关于任务的管理信息
@@ -550,6 +681,20 @@ mod tests {
     //     kassert!(t.trap_frame_ptr.load(Ordering::SeqCst) as usize != 0);
     // });
 
+    // 内核栈金丝雀在创建时写入，被覆盖后能检测到
+    test_case!(test_stack_canary, {
+        let t = Task::new_dummy_task(8);
+        kassert!(t.stack_canary_intact());
+        kassert!(t.stack_canary & 0xff == 0);
+
+        let slot = Task::canary_slot(&t.kstack_tracker);
+        unsafe { slot.write_volatile(!t.stack_canary) };
+        kassert!(!t.stack_canary_intact());
+        // 恢复，避免 drop 时检查失败
+        unsafe { slot.write_volatile(t.stack_canary) };
+        kassert!(t.stack_canary_intact());
+    });
+
     // new_dummy_task：应为内核线程，pid=tid，初始状态为 Running
     test_case!(test_dummy_task_basic, {
         let t = Task::new_dummy_task(7);
@@ -560,6 +705,63 @@ mod tests {
         kassert!(matches!(t.state, TaskState::Running));
     });
 
+    // 任务名：默认 task_<tid>，超长时截断为 15 字节，遇 NUL 截止
+    test_case!(test_task_comm, {
+        let mut t = Task::new_dummy_task(9);
+        kassert!(t.comm() == "task_9");
+
+        t.set_comm(b"a-very-long-thread-name");
+        kassert!(t.comm() == "a-very-long-thr");
+        kassert!(t.comm[TASK_COMM_LEN - 1] == 0);
+
+        t.set_comm(b"sh\0garbage");
+        kassert!(t.comm() == "sh");
+
+        t.set_exec_comm("/usr/bin/busybox");
+        kassert!(t.comm() == "busybox");
+    });
+
+    // 不带 CLONE_FS 的 fork 复制 cwd 和 umask，子进程的修改对父进程不可见；
+    // 带 CLONE_FS 的线程共享同一份，修改互相可见
+    test_case!(test_fs_struct_clone_isolation, {
+        use crate::fs::tmpfs::TmpFs;
+        use crate::vfs::FileSystem;
+
+        let tmpfs = TmpFs::new(0);
+        let root = Dentry::new(String::from("/"), tmpfs.root_inode());
+        let sub = Dentry::new(String::from("sub"), tmpfs.root_inode());
+        let parent = Arc::new(SpinLock::new(FsStruct::new(
+            Some(root.clone()),
+            Some(root.clone()),
+        )));
+
+        let child = FsStruct::clone_for_child(&parent, false);
+        kassert!(!Arc::ptr_eq(&parent, &child));
+        {
+            let mut c = child.lock();
+            c.cwd = Some(sub.clone());
+            c.umask = 0o077;
+        }
+        {
+            let p = parent.lock();
+            kassert!(Arc::ptr_eq(p.cwd.as_ref().unwrap(), &root));
+            kassert!(p.umask == 0o022);
+            kassert!(p.apply_umask(0o666) == 0o644);
+        }
+        kassert!(child.lock().apply_umask(0o666) == 0o600);
+
+        let thread = FsStruct::clone_for_child(&parent, true);
+        kassert!(Arc::ptr_eq(&parent, &thread));
+        {
+            let mut t = thread.lock();
+            t.cwd = Some(sub.clone());
+            t.umask = 0o027;
+        }
+        let p = parent.lock();
+        kassert!(Arc::ptr_eq(p.cwd.as_ref().unwrap(), &sub));
+        kassert!(p.umask == 0o027);
+    });
+
     // // is_process 与 is_kernel_thread 区分：人为创建一个“线程” pid!=tid
     // test_case!(test_is_process_vs_thread, {
     //     let kstack_tracker = alloc_contig_frames(2).expect("alloc kstack");
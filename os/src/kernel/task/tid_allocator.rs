@@ -23,6 +23,11 @@ impl TidAllocator {
         self.next_tid
             .fetch_add(1, core::sync::atomic::Ordering::SeqCst)
     }
+
+    /// 最近一次分配的任务ID（尚未分配时为 1，即 init 进程）
+    pub fn last_allocated(&self) -> u32 {
+        self.next_tid.load(core::sync::atomic::Ordering::SeqCst) - 1
+    }
 }
 
 #[cfg(test)]
@@ -50,5 +55,6 @@ mod tests {
         let r = &alloc;
         let a3 = r.allocate();
         kassert!(a3 == 4);
+        kassert!(alloc.last_allocated() == 4);
     });
 }
@@ -1,18 +1,380 @@
 //! 时间相关功能
+//!
+//! 内核维护三种时钟：
+//! - `CLOCK_MONOTONIC`：直接由计时器周期计数换算，开机从 0 开始，永不跳变；
+//! - `CLOCK_BOOTTIME`：包含挂起时间的单调时钟，目前不支持挂起，与 MONOTONIC 相同；
+//! - `CLOCK_REALTIME`：墙上时钟，等于 MONOTONIC 加上 [`REALTIME`] 偏移量。
+//!   启动时从 RTC 初始化，`clock_settime` 调整偏移量并写回 RTC。
+//!
+//! `adjtimex` 设置的频率修正和待补偿偏移记录在 [`NTP`] 中，由时钟中断调用的
+//! [`timekeeping_tick`] 按节拍逐步叠加到 REALTIME 偏移量上，墙上时钟因此不会跳变。
+//!
+//! 节拍频率与 SCHED_RR 时间片记录在 [`TICK_CONFIG`] 中，启动时由 `hz=` 与
+//! `timeslice_ms=` 内核参数设置。
+//!
+//! 所有时钟、RTC 和文件时间戳都是 UTC。`settimeofday` 设置的时区只保存在
+//! [`SYS_TZ`] 中供 `gettimeofday` 返回，内核不据此换算时间。
 
-use crate::{device::RTC_DRIVERS, pr_info, sync::RwLock, vfs::TimeSpec};
+use core::ffi::{c_int, c_long};
+use core::ops::RangeInclusive;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    arch::timer::TICKS_PER_SEC,
+    device::RTC_DRIVERS,
+    pr_info, pr_warn,
+    sync::{SeqLock, SpinLock},
+    uapi::{
+        errno::EINVAL,
+        time::{Timex, adjtimex::*, timezone},
+    },
+    vfs::TimeSpec,
+};
+
+const NSEC_PER_SEC: i64 = 1_000_000_000;
+/// 逐步补偿偏移时的最大速率（ppm），与 Linux adjtime 一致
+const MAX_SLEW_PPM: i64 = 500;
+/// ppm 定点数（16 位小数）换算为比例时的除数
+const SCALED_PPM_DIV: i128 = 1_000_000 << 16;
+
+/// `hz=` 允许的节拍频率
+const HZ_RANGE: RangeInclusive<usize> = 10..=1000;
+/// `timeslice_ms=` 与 sched_rr_timeslice_ms 允许的时间片（毫秒）
+const RR_TIMESLICE_MS_RANGE: RangeInclusive<usize> = 1..=10_000;
+/// SCHED_RR 默认时间片（毫秒），即默认节拍频率下的 4 个节拍
+const DEFAULT_RR_TIMESLICE_MS: usize = 40;
+
+/// 节拍频率与时间片配置
+///
+/// `hz` 只在启动时由 `hz=` 设置，`timer::init` 据此设置周期节拍；
+/// `rr_timeslice_ms` 还可以通过 /proc/sys/kernel/sched_rr_timeslice_ms 在运行时调整，
+/// 调度器在每次切换任务时按当前节拍频率换算为节拍数。
+pub struct TickConfig {
+    hz: AtomicUsize,
+    rr_timeslice_ms: AtomicUsize,
+}
+
+impl TickConfig {
+    const fn new() -> Self {
+        Self {
+            hz: AtomicUsize::new(TICKS_PER_SEC),
+            rr_timeslice_ms: AtomicUsize::new(DEFAULT_RR_TIMESLICE_MS),
+        }
+    }
+
+    /// 每秒的时钟中断次数
+    pub fn hz(&self) -> usize {
+        self.hz.load(Ordering::Relaxed)
+    }
+
+    /// SCHED_RR 时间片（毫秒）
+    pub fn rr_timeslice_ms(&self) -> usize {
+        self.rr_timeslice_ms.load(Ordering::Relaxed)
+    }
+
+    /// 设置 SCHED_RR 时间片，超出 [`RR_TIMESLICE_MS_RANGE`] 时返回 EINVAL
+    pub fn set_rr_timeslice_ms(&self, ms: usize) -> Result<(), c_int> {
+        if !RR_TIMESLICE_MS_RANGE.contains(&ms) {
+            return Err(EINVAL);
+        }
+        self.rr_timeslice_ms.store(ms, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// 把毫秒换算为节拍数（向上取整，至少 1 个节拍）
+    pub fn ms_to_ticks(&self, ms: usize) -> usize {
+        (ms * self.hz()).div_ceil(1000).max(1)
+    }
+
+    /// 一个节拍对应的硬件时钟周期数
+    pub fn tick_cycles(&self) -> usize {
+        (crate::arch::clock_freq() / self.hz()).max(1)
+    }
+
+    /// 标称节拍长度（微秒）
+    fn nominal_tick_usec(&self) -> i64 {
+        1_000_000 / self.hz() as i64
+    }
+}
+
+/// 全局节拍配置
+pub static TICK_CONFIG: TickConfig = TickConfig::new();
+
+/// 从命令行解析 `hz=` 与 `timeslice_ms=`，未给出或无法解析的参数为 `None`
+fn parse_tick_params(cmdline: &str) -> (Option<usize>, Option<usize>) {
+    let param = |key: &str| {
+        cmdline
+            .split_ascii_whitespace()
+            .filter_map(|arg| arg.strip_prefix(key))
+            .next_back()
+            .and_then(|v| v.parse().ok())
+    };
+    (param("hz="), param("timeslice_ms="))
+}
+
+/// 按内核参数设置节拍配置，必须在 `timer::init` 之前调用
+fn init_tick_config() {
+    let (hz, timeslice_ms) = parse_tick_params(&crate::device::CMDLINE.read());
+    if let Some(hz) = hz {
+        if HZ_RANGE.contains(&hz) {
+            TICK_CONFIG.hz.store(hz, Ordering::Relaxed);
+        } else {
+            pr_warn!("hz={} out of range {:?}, ignored", hz, HZ_RANGE);
+        }
+    }
+    if let Some(ms) = timeslice_ms
+        && TICK_CONFIG.set_rr_timeslice_ms(ms).is_err()
+    {
+        pr_warn!(
+            "timeslice_ms={} out of range {:?}, ignored",
+            ms,
+            RR_TIMESLICE_MS_RANGE
+        );
+    }
+    pr_info!(
+        "Tick rate {} Hz, SCHED_RR timeslice {} ms",
+        TICK_CONFIG.hz(),
+        TICK_CONFIG.rr_timeslice_ms()
+    );
+}
 
 lazy_static::lazy_static! {
-    /// 墙上时钟，记录自 1970-01-01 00:00:00 UTC 以来的时间（以秒为单位）
-    /// XXX: 使用锁会不会影响精度？
-    pub static ref REALTIME: RwLock<TimeSpec> = RwLock::new(TimeSpec::zero());
+    /// 墙上时钟相对单调时钟的偏移量（墙上时间 = 单调时间 + 偏移量）
+    ///
+    /// 使用顺序锁：clock_gettime 等读者不获取锁，只有调整时钟时才互斥。
+    pub static ref REALTIME: SeqLock<TimeSpec> = SeqLock::new(TimeSpec::zero());
+    /// 时钟调整状态
+    pub static ref NTP: SpinLock<NtpState> = SpinLock::new(NtpState::new());
+}
+
+/// `tz_minuteswest` 允许的范围，与 Linux 一致
+const TZ_MINUTESWEST_RANGE: RangeInclusive<c_int> = -15 * 60..=15 * 60;
+
+/// settimeofday 设置的系统时区，只由 gettimeofday 原样返回
+pub static SYS_TZ: SpinLock<timezone> = SpinLock::new(timezone {
+    tz_minuteswest: 0,
+    tz_dsttime: 0,
+});
+
+/// 保存系统时区（调用者负责权限检查）
+pub fn set_sys_tz(tz: timezone) -> Result<(), c_int> {
+    if !TZ_MINUTESWEST_RANGE.contains(&tz.tz_minuteswest) {
+        return Err(EINVAL);
+    }
+    *SYS_TZ.lock() = tz;
+    Ok(())
+}
+
+/// 时钟调整（NTP）状态及漂移统计
+#[derive(Debug, Clone, Copy)]
+pub struct NtpState {
+    /// 频率修正（ppm，16 位小数定点）
+    pub freq: i64,
+    /// 节拍长度（微秒），偏离标称值时等效于额外的频率修正
+    pub tick_usec: i64,
+    /// 尚待补偿的时间偏移（纳秒）
+    pub offset_ns: i64,
+    /// 时钟状态（STA_*）
+    pub status: c_int,
+    /// 最大误差（微秒）
+    pub maxerror: i64,
+    /// 估计误差（微秒）
+    pub esterror: i64,
+    /// PLL 时间常数
+    pub constant: i64,
+    /// TAI 与 UTC 的偏移（秒）
+    pub tai: c_int,
+    /// 累计的频率修正量（纳秒）
+    pub freq_adjusted_ns: i64,
+    /// 累计已补偿的偏移量（纳秒）
+    pub slewed_ns: i64,
+    /// 累计跳变量（纳秒）
+    pub stepped_ns: i64,
+    /// 成功修改时钟参数的次数
+    pub adjustments: u64,
+    /// 上次推进时的单调时钟（纳秒）
+    last_update_ns: i64,
+    /// 频率修正除法的余数，避免每个节拍截断造成的累积误差
+    freq_remainder: i128,
+}
+
+impl NtpState {
+    /// 创建初始状态：无修正，时钟未同步
+    pub fn new() -> Self {
+        Self {
+            freq: 0,
+            tick_usec: TICK_CONFIG.nominal_tick_usec(),
+            offset_ns: 0,
+            status: STA_UNSYNC,
+            maxerror: NTP_PHASE_LIMIT,
+            esterror: NTP_PHASE_LIMIT,
+            constant: 2,
+            tai: 0,
+            freq_adjusted_ns: 0,
+            slewed_ns: 0,
+            stepped_ns: 0,
+            adjustments: 0,
+            last_update_ns: 0,
+            freq_remainder: 0,
+        }
+    }
+
+    /// 频率修正与节拍修正之和（ppm，16 位小数定点）
+    pub fn total_freq(&self) -> i64 {
+        let nominal = TICK_CONFIG.nominal_tick_usec();
+        let tick_ppm = (self.tick_usec - nominal) * (1_000_000 << 16) / nominal;
+        self.freq + tick_ppm
+    }
+
+    /// 推进到单调时钟 `now_ns`，返回这段时间内墙上时钟应额外前进的纳秒数
+    pub fn advance(&mut self, now_ns: i64) -> i64 {
+        let elapsed = now_ns - self.last_update_ns;
+        if elapsed <= 0 {
+            return 0;
+        }
+        self.last_update_ns = now_ns;
+
+        let total = elapsed as i128 * self.total_freq() as i128 + self.freq_remainder;
+        let freq_adj = (total / SCALED_PPM_DIV) as i64;
+        self.freq_remainder = total % SCALED_PPM_DIV;
+
+        let max_slew = elapsed * MAX_SLEW_PPM / 1_000_000;
+        let slew = self.offset_ns.clamp(-max_slew, max_slew);
+        self.offset_ns -= slew;
+
+        self.freq_adjusted_ns += freq_adj;
+        self.slewed_ns += slew;
+        freq_adj + slew
+    }
+
+    /// 按 `tx.modes` 更新状态，返回需要立即跳变的纳秒数（ADJ_SETOFFSET）
+    ///
+    /// 参数非法时返回 EINVAL，状态保持不变。
+    pub fn apply(&mut self, tx: &Timex) -> Result<Option<i64>, c_int> {
+        let modes = tx.modes;
+        if modes & ADJ_OFFSET_SINGLESHOT & !ADJ_OFFSET != 0 {
+            // 旧式 adjtime 只允许两种组合
+            if modes != ADJ_OFFSET_SINGLESHOT && modes != ADJ_OFFSET_SS_READ {
+                return Err(EINVAL);
+            }
+            if modes == ADJ_OFFSET_SINGLESHOT {
+                self.offset_ns = (tx.offset as i64).saturating_mul(1000);
+                self.adjustments += 1;
+            }
+            return Ok(None);
+        }
+
+        let nano = if modes & ADJ_NANO != 0 {
+            true
+        } else if modes & ADJ_MICRO != 0 {
+            false
+        } else {
+            self.status & STA_NANO != 0
+        };
+        let hz = TICK_CONFIG.hz() as c_long;
+        let tick_range = 900_000 / hz..=1_100_000 / hz;
+        if modes & ADJ_TICK != 0 && !tick_range.contains(&tx.tick) {
+            return Err(EINVAL);
+        }
+        let step = if modes & ADJ_SETOFFSET != 0 {
+            let frac_limit = if nano { NSEC_PER_SEC } else { 1_000_000 };
+            if !(0..frac_limit).contains(&tx.time.tv_usec) {
+                return Err(EINVAL);
+            }
+            let frac_ns = if nano {
+                tx.time.tv_usec
+            } else {
+                tx.time.tv_usec * 1000
+            };
+            Some(tx.time.tv_sec.saturating_mul(NSEC_PER_SEC) + frac_ns)
+        } else {
+            None
+        };
+
+        if modes & ADJ_NANO != 0 {
+            self.status |= STA_NANO;
+        } else if modes & ADJ_MICRO != 0 {
+            self.status &= !STA_NANO;
+        }
+        if modes & ADJ_STATUS != 0 {
+            self.status = (self.status & STA_RONLY) | (tx.status & !STA_RONLY);
+        }
+        if modes & ADJ_FREQUENCY != 0 {
+            self.freq = tx.freq.clamp(-MAXFREQ_SCALED, MAXFREQ_SCALED);
+        }
+        if modes & ADJ_MAXERROR != 0 {
+            self.maxerror = tx.maxerror.clamp(0, NTP_PHASE_LIMIT);
+        }
+        if modes & ADJ_ESTERROR != 0 {
+            self.esterror = tx.esterror.clamp(0, NTP_PHASE_LIMIT);
+        }
+        if modes & ADJ_TIMECONST != 0 {
+            self.constant = tx.constant.clamp(0, MAXTC);
+        }
+        if modes & ADJ_TAI != 0 && tx.constant >= 0 {
+            self.tai = tx.constant as c_int;
+        }
+        if modes & ADJ_OFFSET != 0 {
+            let offset_ns = if nano {
+                tx.offset
+            } else {
+                tx.offset.saturating_mul(1000)
+            };
+            self.offset_ns = offset_ns.clamp(-MAXPHASE, MAXPHASE);
+        }
+        if modes & ADJ_TICK != 0 {
+            self.tick_usec = tx.tick;
+        }
+        if let Some(step) = step {
+            self.stepped_ns += step;
+        }
+        if modes != 0 {
+            self.adjustments += 1;
+        }
+        Ok(step)
+    }
+
+    /// 把当前状态填回 `tx`（`time` 字段由调用者填写）
+    pub fn fill(&self, tx: &mut Timex) {
+        let nano = self.status & STA_NANO != 0;
+        tx.offset = if nano {
+            self.offset_ns
+        } else {
+            self.offset_ns / 1000
+        };
+        tx.freq = self.freq;
+        tx.maxerror = self.maxerror;
+        tx.esterror = self.esterror;
+        tx.status = self.status;
+        tx.constant = self.constant;
+        tx.precision = 1;
+        tx.tolerance = MAXFREQ_SCALED;
+        tx.tick = self.tick_usec;
+        tx.tai = self.tai;
+    }
+}
+
+impl Default for NtpState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 把有符号纳秒数转换为规范化的 TimeSpec（tv_nsec 总在 [0, 1e9) 内）
+fn timespec_from_ns(ns: i64) -> TimeSpec {
+    TimeSpec::new(ns.div_euclid(NSEC_PER_SEC), ns.rem_euclid(NSEC_PER_SEC))
+}
+
+fn timespec_to_ns(ts: &TimeSpec) -> i64 {
+    ts.tv_sec.saturating_mul(NSEC_PER_SEC) + ts.tv_nsec
 }
 
 /// 初始化时间子系统
 pub fn init() {
-    // 初始化墙上时钟为 0
+    init_tick_config();
+
+    // 没有 RTC 时墙上时钟从 1970-01-01 开始
     pr_info!("Initializing REALTIME clock...");
-    let mut realtime = REALTIME.write();
     let sec = RTC_DRIVERS
         .read()
         .first()
@@ -21,23 +383,178 @@ pub fn init() {
     let mtime = TimeSpec::monotonic_now();
     // 这里减去 mtime 是为简化后续的时间计算
     let time = TimeSpec::new(sec as i64, 0) - mtime;
-    *realtime = time;
+    *REALTIME.write() = time;
     pr_info!(
         "REALTIME clock initialized to {:?} seconds since epoch.",
         time
     );
 }
 
-/// 更新墙上时钟时间
+/// 登记时间相关的统计指标
+pub fn register_metrics() {
+    crate::kernel::kstat::register_probe("uptime_ms", crate::kernel::kstat::Unit::Count, || {
+        crate::arch::get_time_ms() as u64
+    });
+}
+
+/// 更新墙上时钟时间，并同步写回 RTC
+///
+/// 只改变 REALTIME 偏移量，MONOTONIC/BOOTTIME 不受影响。
 /// # 参数:
 /// - `time`: 新的墙上时钟时间
 pub fn update_realtime(time: &TimeSpec) {
-    let mut realtime = REALTIME.write();
-    *realtime = *time - TimeSpec::monotonic_now();
+    {
+        let mut realtime = REALTIME.write();
+        *realtime = *time - TimeSpec::monotonic_now();
+    }
+    if let Some(rtc) = RTC_DRIVERS.read().first() {
+        rtc.write_epoch(time.tv_sec.max(0) as u64);
+    }
 }
 
 /// 获取当前墙上时钟时间
 pub fn realtime_now() -> TimeSpec {
-    let realtime = REALTIME.read();
-    *realtime + TimeSpec::monotonic_now()
+    REALTIME.read() + TimeSpec::monotonic_now()
+}
+
+/// 获取自启动以来经过的时间（包含挂起时间）
+pub fn boottime_now() -> TimeSpec {
+    // 目前不支持挂起，BOOTTIME 与 MONOTONIC 一致
+    TimeSpec::monotonic_now()
+}
+
+/// 时钟中断中调用：把频率修正和待补偿偏移按流逝的时间叠加到墙上时钟
+pub fn timekeeping_tick() {
+    let now = timespec_to_ns(&TimeSpec::monotonic_now());
+    let delta = NTP.lock().advance(now);
+    if delta != 0 {
+        let mut realtime = REALTIME.write();
+        *realtime = *realtime + timespec_from_ns(delta);
+    }
+}
+
+/// adjtimex/clock_adjtime 的实现（调用者负责权限检查）
+///
+/// 成功时返回时钟状态（TIME_OK / TIME_ERROR），并把当前参数写回 `tx`。
+pub fn do_adjtimex(tx: &mut Timex) -> Result<c_int, c_int> {
+    let (step, old_offset_ns) = {
+        let mut ntp = NTP.lock();
+        // 先结算到当前时刻，新的参数只影响之后的时间
+        let now = timespec_to_ns(&TimeSpec::monotonic_now());
+        let delta = ntp.advance(now);
+        if delta != 0 {
+            let mut realtime = REALTIME.write();
+            *realtime = *realtime + timespec_from_ns(delta);
+        }
+        let old_offset_ns = ntp.offset_ns;
+        (ntp.apply(tx)?, old_offset_ns)
+    };
+    if let Some(step) = step {
+        update_realtime(&(realtime_now() + timespec_from_ns(step)));
+    }
+
+    let ntp = *NTP.lock();
+    ntp.fill(tx);
+    if tx.modes & ADJ_OFFSET_SINGLESHOT & !ADJ_OFFSET != 0 {
+        // 旧式 adjtime 返回调整前的剩余偏移
+        tx.offset = old_offset_ns / 1000;
+    }
+    let now = realtime_now();
+    tx.time.tv_sec = now.tv_sec;
+    tx.time.tv_usec = if ntp.status & STA_NANO != 0 {
+        now.tv_nsec
+    } else {
+        now.tv_nsec / 1000
+    };
+    Ok(if ntp.status & STA_UNSYNC != 0 {
+        TIME_ERROR
+    } else {
+        TIME_OK
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_ntp_slew_is_rate_limited, {
+        let mut ntp = NtpState::new();
+        ntp.offset_ns = 1_000_000;
+        // 1 秒内最多补偿 500us
+        kassert!(ntp.advance(NSEC_PER_SEC) == 500_000);
+        kassert!(ntp.offset_ns == 500_000);
+        kassert!(ntp.advance(2 * NSEC_PER_SEC) == 500_000);
+        kassert!(ntp.offset_ns == 0);
+        kassert!(ntp.advance(3 * NSEC_PER_SEC) == 0);
+        kassert!(ntp.slewed_ns == 1_000_000);
+    });
+
+    test_case!(test_ntp_frequency_adjustment, {
+        let mut ntp = NtpState::new();
+        ntp.freq = 10 << 16; // +10ppm
+        let mut total = 0;
+        // 分成 100 个节拍推进，余数累积后总量不丢失
+        for i in 1..=100 {
+            total += ntp.advance(i * NSEC_PER_SEC / 100);
+        }
+        kassert!(total == 10_000);
+        kassert!(ntp.freq_adjusted_ns == 10_000);
+    });
+
+    test_case!(test_ntp_apply_validates_modes, {
+        let mut ntp = NtpState::new();
+        let mut tx = Timex {
+            modes: ADJ_FREQUENCY | ADJ_OFFSET,
+            freq: 1000 << 16,
+            offset: 2_000_000,
+            ..Timex::default()
+        };
+        kassert!(ntp.apply(&tx) == Ok(None));
+        kassert!(ntp.freq == MAXFREQ_SCALED);
+        kassert!(ntp.offset_ns == MAXPHASE);
+
+        tx.modes = ADJ_TICK;
+        tx.tick = 1;
+        kassert!(ntp.apply(&tx) == Err(EINVAL));
+
+        tx.modes = ADJ_OFFSET_SINGLESHOT | ADJ_STATUS;
+        kassert!(ntp.apply(&tx) == Err(EINVAL));
+
+        tx.modes = ADJ_SETOFFSET | ADJ_NANO;
+        tx.time.tv_sec = -1;
+        tx.time.tv_usec = 500_000_000;
+        kassert!(ntp.apply(&tx) == Ok(Some(-500_000_000)));
+        kassert!(ntp.status & STA_NANO != 0);
+    });
+
+    test_case!(test_set_sys_tz, {
+        let old = *SYS_TZ.lock();
+        let tz = |minuteswest| timezone {
+            tz_minuteswest: minuteswest,
+            tz_dsttime: 0,
+        };
+        kassert!(set_sys_tz(tz(16 * 60)) == Err(EINVAL));
+        kassert!(set_sys_tz(tz(-480)) == Ok(()));
+        kassert!(SYS_TZ.lock().tz_minuteswest == -480);
+        *SYS_TZ.lock() = old;
+    });
+
+    test_case!(test_parse_tick_params, {
+        kassert!(parse_tick_params("console=ttyS0") == (None, None));
+        kassert!(parse_tick_params("hz=250 timeslice_ms=20") == (Some(250), Some(20)));
+        // 重复出现时以最后一个为准，无法解析的值忽略
+        kassert!(parse_tick_params("hz=100 hz=1000 timeslice_ms=x") == (Some(1000), None));
+        kassert!(parse_tick_params("nohz=1") == (None, None));
+    });
+
+    test_case!(test_tick_config_conversions, {
+        let config = TickConfig::new();
+        kassert!(config.ms_to_ticks(DEFAULT_RR_TIMESLICE_MS) == 4);
+        kassert!(config.ms_to_ticks(1) == 1);
+        kassert!(config.ms_to_ticks(15) == 2);
+        kassert!(config.set_rr_timeslice_ms(0) == Err(EINVAL));
+        kassert!(config.set_rr_timeslice_ms(100) == Ok(()));
+        kassert!(config.rr_timeslice_ms() == 100);
+    });
 }
@@ -1,10 +1,93 @@
 //! 定时器队列模块
 //!
 //! 该模块实现了一个简单的定时器队列，用于管理和调度定时任务。
+//!
+//! 睡眠任务的唤醒允许推迟不超过其 timer slack（`prctl(PR_SET_TIMERSLACK)`，
+//! realtime 任务为 0）：tickless 空闲只把一次性定时器设到所有条目中最早的
+//! “截止时间 + slack”，到期时唤醒所有截止时间已过的任务，让相近的唤醒共用
+//! 一次时钟中断。效果由 kstat 指标 `timer_wakeups`、`timer_wakeups_coalesced`
+//! （与其它任务在同一次到期处理中被唤醒、没有单独占用时钟事件的次数）和
+//! `timer_oneshot_events`（tickless 空闲设置一次性定时器的次数）体现。
 
 use alloc::{collections::btree_map::BTreeMap, sync::Arc};
 
-use crate::{kernel::SharedTask, sync::SpinLock, vfs::TimeSpec};
+use crate::{
+    kernel::{
+        EXIT_PRIO_KERNEL, ExitNotifier, SharedTask,
+        kstat::{self, Counter, Unit},
+        send_signal_process, wake_up_task,
+    },
+    sync::SpinLock,
+    uapi::sched::{SCHED_FIFO, SCHED_RR},
+    vfs::TimeSpec,
+};
+
+/// 新任务默认的 timer slack（纳秒），与 Linux 相同
+pub const DEFAULT_TIMER_SLACK_NS: u64 = 50_000;
+
+/// 由 TIMER_QUEUE 唤醒的任务数
+static TIMER_WAKEUPS: Counter = Counter::new();
+/// 与其它任务在同一次到期处理中被唤醒的任务数
+static TIMER_WAKEUPS_COALESCED: Counter = Counter::new();
+/// tickless 空闲设置一次性定时器的次数
+pub static TIMER_ONESHOT_EVENTS: Counter = Counter::new();
+
+/// 登记定时器相关的统计指标
+pub fn register_timer_metrics() {
+    kstat::register_counter("timer_wakeups", Unit::Count, &TIMER_WAKEUPS);
+    kstat::register_counter(
+        "timer_wakeups_coalesced",
+        Unit::Count,
+        &TIMER_WAKEUPS_COALESCED,
+    );
+    kstat::register_counter("timer_oneshot_events", Unit::Count, &TIMER_ONESHOT_EVENTS);
+}
+
+/// 任务允许的唤醒推迟量（硬件时钟周期）
+fn task_slack_cycles(task: &SharedTask) -> usize {
+    let slack_ns = {
+        let t = task.lock();
+        if matches!(t.sched_policy, SCHED_FIFO | SCHED_RR) {
+            0
+        } else {
+            t.timer_slack_ns
+        }
+    };
+    (slack_ns as u128 * crate::arch::clock_freq() as u128 / 1_000_000_000) as usize
+}
+
+/// 时钟中断中调用：唤醒所有到期的睡眠任务
+pub fn run_timer_queue(now: usize) {
+    let mut woken = 0u64;
+    while let Some(task) = TIMER_QUEUE.lock().pop_due_task(now) {
+        wake_up_task(task);
+        woken += 1;
+    }
+    if woken > 0 {
+        TIMER_WAKEUPS.add(woken);
+        TIMER_WAKEUPS_COALESCED.add(woken - 1);
+    }
+    crate::kernel::executor::run_async_timers(now);
+}
+
+/// 时钟中断中调用：向到期的间隔定时器所属进程发送信号，周期定时器按间隔重新入队
+pub fn run_itimers(now: usize) {
+    loop {
+        // 先释放 TIMER 的锁，重新入队时还要再取
+        let Some(entry) = TIMER.lock().pop_due_entry(now) else {
+            break;
+        };
+        send_signal_process(&entry.task, entry.sig);
+        if !entry.it_interval.is_zero() {
+            // 间隔不足一个时钟周期时按一个周期计，否则会在同一时刻反复到期
+            let interval = entry
+                .it_interval
+                .into_freq(crate::arch::clock_freq())
+                .max(1);
+            TIMER.lock().push(now + interval, entry);
+        }
+    }
+}
 
 lazy_static::lazy_static! {
     /// 全局等待队列实例
@@ -17,10 +100,17 @@ lazy_static::lazy_static! {
     pub static ref TIMER: SpinLock<TimerEntries> = SpinLock::new(TimerEntries::new());
 }
 
+/// 定时器队列中的一个睡眠任务
+struct TimerQueueEntry {
+    task: SharedTask,
+    /// 允许推迟唤醒的时钟周期数
+    slack: usize,
+}
+
 /// 定时器队列，用于管理定时任务
 pub struct TimerQueue {
-    /// 以触发时间为键，任务为值的有序映射
-    queue: BTreeMap<usize, SharedTask>,
+    /// 以触发时间为键的有序映射
+    queue: BTreeMap<usize, TimerQueueEntry>,
 }
 
 impl TimerQueue {
@@ -31,15 +121,22 @@ impl TimerQueue {
         }
     }
 
-    /// 向队列中添加一个定时任务
+    /// 向队列中添加一个定时任务，唤醒允许推迟任务的 timer slack
     /// # 参数:
     /// - `trigger_time`: 任务触发的时间点
     /// - `task`: 需要执行的任务
-    pub fn push(&mut self, mut trigger_time: usize, task: SharedTask) {
+    pub fn push(&mut self, trigger_time: usize, task: SharedTask) {
+        let slack = task_slack_cycles(&task);
+        self.push_with_slack(trigger_time, slack, task);
+    }
+
+    /// 向队列中添加一个定时任务，唤醒最多可推迟 `slack` 个时钟周期
+    pub fn push_with_slack(&mut self, mut trigger_time: usize, slack: usize, task: SharedTask) {
         while self.queue.contains_key(&trigger_time) {
             trigger_time += 1;
         }
-        self.queue.insert(trigger_time, task);
+        self.queue
+            .insert(trigger_time, TimerQueueEntry { task, slack });
     }
 
     /// 弹出已到期的任务
@@ -51,25 +148,35 @@ impl TimerQueue {
         if let Some((&trigger_time, _)) = self.queue.iter().next()
             && trigger_time <= current_time
         {
-            return self.queue.remove(&trigger_time);
+            return self.queue.remove(&trigger_time).map(|entry| entry.task);
         }
         None
     }
 
+    /// 最迟必须产生时钟中断的时间点：所有条目中最早的“触发时间 + slack”
+    ///
+    /// 在这一时刻处理队列，会一并唤醒触发时间已过的所有任务。
+    pub fn next_expiry(&self) -> Option<usize> {
+        self.queue
+            .iter()
+            .map(|(&time, entry)| time.saturating_add(entry.slack))
+            .min()
+    }
+
     /// 移除指定任务
     /// # 参数:
     /// - `task`: 需要移除的任务
     /// # 返回值:
     /// - 被移除的任务（如果存在）
     pub fn remove_task(&mut self, task: &SharedTask) -> Option<SharedTask> {
-        let key = self.queue.iter().find_map(|(time, t)| {
-            if Arc::ptr_eq(task, t) {
+        let key = self.queue.iter().find_map(|(time, entry)| {
+            if Arc::ptr_eq(task, &entry.task) {
                 Some(*time)
             } else {
                 None
             }
         })?;
-        self.queue.remove(&key)
+        self.queue.remove(&key).map(|entry| entry.task)
     }
 }
 
@@ -118,6 +225,11 @@ impl TimerEntries {
         self.entries.insert(trigger_time, entry);
     }
 
+    /// 最早的触发时间点（集合为空时返回 None）
+    pub fn next_deadline(&self) -> Option<usize> {
+        self.entries.keys().next().copied()
+    }
+
     /// 弹出已到期的定时器条目
     /// # 参数:
     /// - `current_time`: 当前时间点
@@ -155,4 +267,143 @@ impl TimerEntries {
         let key = self.find_entry(task, sig).map(|(time, _)| *time)?;
         self.entries.remove(&key)
     }
+
+    /// 移除与指定任务关联的所有定时器条目
+    /// # 参数:
+    /// - `task`: 目标任务
+    /// # 返回值:
+    /// - 被移除的条目数
+    pub fn remove_task(&mut self, task: &SharedTask) -> usize {
+        let len = self.entries.len();
+        self.entries
+            .retain(|_, entry| !Arc::ptr_eq(task, &entry.task));
+        len - self.entries.len()
+    }
+}
+
+/// 撤销进程间隔定时器的退出回调，进程第一次设置定时器时注册到线程组 leader 上
+///
+/// 定时器条目持有任务的引用，不撤销时进程退出后仍会按周期向它发送信号。
+pub static ITIMER_EXIT_NOTIFIER: ExitNotifier = ExitNotifier {
+    name: "itimer",
+    priority: EXIT_PRIO_KERNEL,
+    call: |task| {
+        TIMER.lock().remove_task(task);
+        crate::kernel::remove_cpu_itimers(task);
+    },
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, kernel::TaskStruct, test_case};
+
+    fn mk_task(tid: u32) -> SharedTask {
+        TaskStruct::new_dummy_task(tid).into_shared()
+    }
+
+    // 一次性定时器设在最早的“截止时间 + slack”，届时截止时间已过的任务一并唤醒
+    test_case!(test_timer_queue_slack_coalescing, {
+        let mut q = TimerQueue::new();
+        let t1 = mk_task(1);
+        let t2 = mk_task(2);
+        let t3 = mk_task(3);
+        q.push_with_slack(100, 50, t1.clone());
+        q.push_with_slack(120, 50, t2.clone());
+        q.push_with_slack(300, 0, t3.clone());
+
+        let expiry = q.next_expiry().unwrap();
+        kassert!(expiry == 150);
+
+        kassert!(Arc::ptr_eq(&q.pop_due_task(expiry).unwrap(), &t1));
+        kassert!(Arc::ptr_eq(&q.pop_due_task(expiry).unwrap(), &t2));
+        kassert!(q.pop_due_task(expiry).is_none());
+        kassert!(q.next_expiry() == Some(300));
+
+        kassert!(Arc::ptr_eq(&q.remove_task(&t3).unwrap(), &t3));
+        kassert!(q.next_expiry().is_none());
+    });
+
+    test_case!(test_timer_entries_remove_task, {
+        let mut timers = TimerEntries::new();
+        let t1 = mk_task(1);
+        let t2 = mk_task(2);
+        timers.push(100, TimerEntry::new(14, t1.clone(), TimeSpec::zero()));
+        timers.push(100, TimerEntry::new(26, t1.clone(), TimeSpec::zero()));
+        timers.push(200, TimerEntry::new(14, t2.clone(), TimeSpec::zero()));
+
+        kassert!(timers.remove_task(&t1) == 2);
+        kassert!(timers.find_entry(&t1, 14).is_none());
+        kassert!(timers.find_entry(&t2, 14).is_some());
+        kassert!(timers.remove_task(&t1) == 0);
+    });
+
+    /// 1 毫秒对应的时钟周期数
+    #[cfg(feature = "virtual_time")]
+    fn ms(n: usize) -> usize {
+        n * crate::arch::clock_freq() / 1000
+    }
+
+    // 与 futex 超时相同：睡眠任务在截止时间到来时恰好被唤醒，不早也不晚
+    #[cfg(feature = "virtual_time")]
+    test_case!(test_timer_queue_wakes_at_deadline, {
+        use crate::kernel::{TaskState, exit_task, vclock};
+        use core::time::Duration;
+
+        let _clock = vclock::freeze();
+        let t = mk_task(61);
+        t.lock().state = TaskState::Interruptible;
+        let deadline = crate::arch::get_time() + ms(10);
+        TIMER_QUEUE.lock().push_with_slack(deadline, 0, t.clone());
+
+        vclock::advance_time(Duration::from_millis(9));
+        kassert!(t.lock().state == TaskState::Interruptible);
+        vclock::advance_time(Duration::from_millis(1));
+        kassert!(t.lock().state == TaskState::Running);
+        kassert!(TIMER_QUEUE.lock().remove_task(&t).is_none());
+
+        // 唤醒时放进了运行队列
+        exit_task(t);
+    });
+
+    // 周期 itimer 在每个周期到期时发送信号并按间隔重新入队
+    #[cfg(feature = "virtual_time")]
+    test_case!(test_itimer_rearms_on_virtual_time, {
+        use crate::kernel::vclock;
+        use crate::uapi::signal::{NUM_SIGALRM, SignalFlags};
+        use core::time::Duration;
+
+        let _clock = vclock::freeze();
+        let t = mk_task(62);
+        let start = crate::arch::get_time();
+        let interval = TimeSpec {
+            tv_sec: 0,
+            tv_nsec: 10_000_000,
+        };
+        TIMER.lock().push(
+            start + ms(10),
+            TimerEntry::new(NUM_SIGALRM, t.clone(), interval),
+        );
+        let pending = |t: &SharedTask| {
+            t.lock()
+                .shared_pending
+                .lock()
+                .signals
+                .contains(SignalFlags::SIGALRM)
+        };
+
+        vclock::advance_time(Duration::from_millis(5));
+        kassert!(!pending(&t));
+        vclock::advance_time(Duration::from_millis(30));
+        kassert!(pending(&t));
+        kassert!(
+            TIMER
+                .lock()
+                .find_entry(&t, NUM_SIGALRM)
+                .map(|(&time, _)| time)
+                == Some(start + ms(40))
+        );
+
+        kassert!(TIMER.lock().remove_task(&t) == 1);
+    });
 }
@@ -0,0 +1,139 @@
+//! 测试用的虚拟时钟
+//!
+//! 启用 `virtual_time` feature 后，`arch::timer::get_time` 读到的不再直接是硬件计数器，
+//! 而是经本模块换算的时间，所有由它派生的时钟（MONOTONIC、REALTIME、调度时钟）随之变化。
+//! 平时虚拟时钟跟随硬件，只多一个偏移量；测试代码调用 [`freeze`] 后时间停止前进，
+//! 只有 [`advance_time`] 能推进它：
+//!
+//! ```ignore
+//! let _clock = vclock::freeze();
+//! TIMER_QUEUE.lock().push(get_time() + timeout, task.clone());
+//! vclock::advance_time(Duration::from_millis(10));
+//! ```
+//!
+//! 推进时按截止时间顺序逐个停在睡眠队列（[`TIMER_QUEUE`]）和间隔定时器（[`TIMER`]）
+//! 的到期点上处理到期项，与时钟中断恰好在截止时间到来时的效果相同，因此 futex 超时、
+//! itimer 的周期重装都可以在测试中立即、确定地复现。调度器的时间片由测试代码在推进后
+//! 自行调用 `update_time_slice` 检查，虚拟时钟不模拟时钟中断的其余工作。
+//!
+//! 解冻后虚拟时钟从冻结时的读数继续跟随硬件，因此推进过的时间不会倒退。
+
+use core::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use crate::kernel::{TIMER, TIMER_QUEUE, run_itimers, run_timer_queue};
+
+/// 是否处于冻结状态
+static FROZEN: AtomicBool = AtomicBool::new(false);
+/// 冻结时的当前时间（时钟周期）
+static FROZEN_NOW: AtomicUsize = AtomicUsize::new(0);
+/// 未冻结时加到硬件计数器上的偏移量（时钟周期）
+static OFFSET: AtomicUsize = AtomicUsize::new(0);
+
+/// 由硬件计数器 `hw` 换算当前的虚拟时间
+///
+/// 由各架构的 `get_time` 调用。
+#[inline]
+pub fn now(hw: usize) -> usize {
+    if FROZEN.load(Ordering::Acquire) {
+        FROZEN_NOW.load(Ordering::Acquire)
+    } else {
+        hw.wrapping_add(OFFSET.load(Ordering::Relaxed))
+    }
+}
+
+/// 冻结虚拟时钟，返回的守卫在析构时解冻
+///
+/// 冻结期间只有 [`advance_time`] 能推进时间。不能嵌套冻结。
+pub fn freeze() -> FrozenClock {
+    assert!(
+        !FROZEN.load(Ordering::Acquire),
+        "vclock: clock is already frozen"
+    );
+    FROZEN_NOW.store(crate::arch::get_time(), Ordering::Release);
+    FROZEN.store(true, Ordering::Release);
+    FrozenClock { _private: () }
+}
+
+/// 冻结虚拟时钟的守卫，见 [`freeze`]
+pub struct FrozenClock {
+    _private: (),
+}
+
+impl Drop for FrozenClock {
+    fn drop(&mut self) {
+        FROZEN.store(false, Ordering::Release);
+        // 从冻结时的读数继续；推进的时间少于实际经过的时间时，时钟向前跳到硬件时间
+        let frozen_now = FROZEN_NOW.load(Ordering::Acquire);
+        let now = crate::arch::get_time();
+        if frozen_now > now {
+            OFFSET.fetch_add(frozen_now - now, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 把冻结的虚拟时钟推进 `duration`，并处理期间到期的睡眠任务和间隔定时器
+///
+/// # Panics
+/// 时钟未冻结时 panic。
+pub fn advance_time(duration: Duration) {
+    assert!(
+        FROZEN.load(Ordering::Acquire),
+        "vclock: advance_time requires a frozen clock"
+    );
+    let cycles = duration.as_nanos() * crate::arch::clock_freq() as u128 / 1_000_000_000;
+    let target = FROZEN_NOW
+        .load(Ordering::Acquire)
+        .saturating_add(cycles.min(usize::MAX as u128) as usize);
+
+    while let Some(deadline) = next_deadline()
+        && deadline <= target
+    {
+        let now = deadline.max(FROZEN_NOW.load(Ordering::Acquire));
+        FROZEN_NOW.store(now, Ordering::Release);
+        expire(now);
+    }
+    FROZEN_NOW.store(target, Ordering::Release);
+    expire(target);
+}
+
+/// 睡眠队列和间隔定时器中最早需要处理的时间点
+fn next_deadline() -> Option<usize> {
+    let sleep = TIMER_QUEUE.lock().next_expiry();
+    let itimer = TIMER.lock().next_deadline();
+    match (sleep, itimer) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// 处理 `now` 时已到期的项，与时钟中断中的处理相同
+fn expire(now: usize) {
+    run_timer_queue(now);
+    run_itimers(now);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_vclock_freeze_and_advance, {
+        let start;
+        {
+            let _clock = freeze();
+            start = crate::arch::get_time();
+            kassert!(crate::arch::get_time() == start);
+
+            let freq = crate::arch::clock_freq();
+            advance_time(Duration::from_millis(10));
+            kassert!(crate::arch::get_time() - start == freq / 100);
+            advance_time(Duration::from_secs(1));
+            kassert!(crate::arch::get_time() - start == freq / 100 + freq);
+        }
+        // 解冻后不会倒退到硬件时间
+        kassert!(crate::arch::get_time() >= start + crate::arch::clock_freq());
+    });
+}
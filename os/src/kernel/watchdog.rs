@@ -0,0 +1,395 @@
+//! 看门狗
+//!
+//! 三层检测，任何一层超时都会把日志尾部写入 pstore 后强制复位：
+//! - 软死锁：watchdogd 内核线程每秒记录一次运行时间，时钟中断发现它超过
+//!   [`SOFTLOCKUP_THRESH_SECS`] 秒没有运行时复位（调度器卡死、内核开中断死循环）；
+//! - 硬死锁：时钟中断记录各 CPU 最近一次节拍的时间，watchdogd 发现某个 CPU
+//!   超过 [`HARDLOCKUP_THRESH_SECS`] 秒没有节拍时复位（该 CPU 关中断死循环）；
+//! - 硬件看门狗：探测到时（如 QEMU 的 i6300esb）由 watchdogd 定期喂狗，
+//!   覆盖所有 CPU 都停止响应、软件检测无法执行的情况。
+//!
+//! `/dev/watchdog` 供用户态守护进程使用，语义与 Linux 看门狗设备相同：
+//! 打开即启动，写入任意数据或 `WDIOC_KEEPALIVE` 喂狗，超时未喂狗则复位；
+//! 关闭前写入魔术字符 'V' 才会停止计时，否则继续计时直到再次打开。
+
+use alloc::sync::Arc;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::arch::address::UA;
+use crate::arch::{Arch, ArchImpl, Platform};
+use crate::device::watchdog::WATCHDOG_DRIVERS;
+use crate::kernel::{
+    TIMER_QUEUE, clock_freq, current_task, kthread_should_stop, num_cpu, sleep_task, yield_task,
+};
+use crate::sync::SpinLock;
+use crate::uapi::watchdog::*;
+use crate::vfs::{Dentry, File, FsError, Inode, InodeMetadata, OpenFlags};
+
+/// watchdogd 的运行周期（毫秒）
+const WATCHDOG_PERIOD_MS: usize = 1000;
+
+/// watchdogd 超过这么多秒没有运行视为软死锁
+pub const SOFTLOCKUP_THRESH_SECS: usize = 20;
+
+/// CPU 超过这么多秒没有时钟节拍视为硬死锁
+///
+/// 须大于 tickless 空闲的最长时长（1 秒）。
+pub const HARDLOCKUP_THRESH_SECS: usize = 10;
+
+/// 硬件看门狗的超时（秒）
+const HW_TIMEOUT_SECS: u32 = 30;
+
+/// `/dev/watchdog` 的默认超时（秒）
+const DEFAULT_USER_TIMEOUT: u32 = 60;
+
+/// `/dev/watchdog` 允许的最长超时（秒）
+const MAX_USER_TIMEOUT: u32 = 3600;
+
+/// 复位原因行的前缀，下次启动时据此判断上次是否由看门狗复位
+const RESET_REASON_PREFIX: &str = "watchdog: ";
+
+/// 软件检测是否生效；watchdogd 启动后打开，suspend-to-idle 期间关闭
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// watchdogd 最近一次运行的硬件时间
+static KTHREAD_STAMP: AtomicUsize = AtomicUsize::new(0);
+crate::percpu! {
+    /// 各 CPU 最近一次时钟节拍的硬件时间，0 表示尚未开始计时
+    static TICK_STAMP: AtomicUsize = AtomicUsize::new(0);
+}
+/// 复位已经开始
+static FIRING: AtomicBool = AtomicBool::new(false);
+
+/// `/dev/watchdog` 的状态
+struct UserWatchdog {
+    /// 设备已被打开（同一时间只允许一个打开者）
+    open: bool,
+    /// 正在计时
+    armed: bool,
+    /// 超时（秒）
+    timeout: u32,
+    /// 到期的硬件时间
+    deadline: usize,
+    /// 最近一次写入包含 'V'，关闭时停止计时
+    expect_close: bool,
+}
+
+impl UserWatchdog {
+    const fn new() -> Self {
+        Self {
+            open: false,
+            armed: false,
+            timeout: DEFAULT_USER_TIMEOUT,
+            deadline: 0,
+            expect_close: false,
+        }
+    }
+
+    fn ping(&mut self, now: usize) {
+        self.deadline = now + self.timeout as usize * clock_freq();
+    }
+}
+
+static USER: SpinLock<UserWatchdog> = SpinLock::new(UserWatchdog::new());
+
+/// 时钟中断调用：记录本 CPU 的节拍，检查 watchdogd 是否还在运行
+pub fn watchdog_tick() {
+    let now = crate::arch::get_time();
+    TICK_STAMP.with(|stamp| stamp.store(now, Ordering::Relaxed));
+    if !ENABLED.load(Ordering::Acquire) {
+        return;
+    }
+    let stalled = now.saturating_sub(KTHREAD_STAMP.load(Ordering::Relaxed));
+    if stalled > SOFTLOCKUP_THRESH_SECS * clock_freq() {
+        fire(format_args!(
+            "soft lockup on CPU{}, watchdogd not scheduled for {}s",
+            crate::arch::cpu_id(),
+            stalled / clock_freq()
+        ));
+    }
+}
+
+/// 所有 CPU 和 watchdogd 的时间戳都记为现在，用于长时间合法地停止节拍之后
+fn touch_all() {
+    let now = crate::arch::get_time();
+    KTHREAD_STAMP.store(now, Ordering::Relaxed);
+    for stamp in TICK_STAMP.iter() {
+        if stamp.load(Ordering::Relaxed) != 0 {
+            stamp.store(now, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 进入 suspend-to-idle 前调用：暂停软件检测并停止硬件看门狗
+pub fn suspend() {
+    ENABLED.store(false, Ordering::Release);
+    for hw in WATCHDOG_DRIVERS.read().iter() {
+        hw.stop();
+    }
+}
+
+/// 从 suspend-to-idle 唤醒后调用
+pub fn resume() {
+    touch_all();
+    for hw in WATCHDOG_DRIVERS.read().iter() {
+        hw.start(HW_TIMEOUT_SECS);
+    }
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// 看门狗到期：记录原因，写入 pstore，强制复位
+fn fire(reason: fmt::Arguments) -> ! {
+    if !FIRING.swap(true, Ordering::SeqCst) {
+        crate::console::emergency_print(format_args!("{}{}\n", RESET_REASON_PREFIX, reason));
+        crate::log::pstore::dump(format_args!("{}{}", RESET_REASON_PREFIX, reason));
+    }
+    ArchImpl::restart()
+}
+
+/// 上一次启动是否以看门狗复位结束
+pub fn last_boot_was_watchdog_reset() -> bool {
+    let Some(record) = crate::log::pstore::last_record() else {
+        return false;
+    };
+    let text = alloc::string::String::from_utf8_lossy(&record);
+    text.lines()
+        .last()
+        .is_some_and(|line| line.starts_with(RESET_REASON_PREFIX))
+}
+
+fn sleep_ms(ms: usize) {
+    let task = current_task();
+    let trigger = crate::arch::get_time() + ms * clock_freq() / 1000;
+    let mut timer_q = TIMER_QUEUE.lock();
+    timer_q.push(trigger, task.clone());
+    sleep_task(task.clone(), true);
+    drop(timer_q);
+    yield_task();
+    TIMER_QUEUE.lock().remove_task(&task);
+}
+
+/// 看门狗内核线程
+///
+/// 被 `kthread_stop` 停止时关闭软件检测和硬件看门狗。
+pub fn watchdogd(_arg: usize) -> i32 {
+    let hw_count = WATCHDOG_DRIVERS.read().len();
+    touch_all();
+    for hw in WATCHDOG_DRIVERS.read().iter() {
+        hw.start(HW_TIMEOUT_SECS);
+    }
+    ENABLED.store(true, Ordering::Release);
+    crate::pr_info!(
+        "[Watchdog] soft lockup {}s, hard lockup {}s, {} hardware watchdog(s)",
+        SOFTLOCKUP_THRESH_SECS,
+        HARDLOCKUP_THRESH_SECS,
+        hw_count
+    );
+
+    while !kthread_should_stop() {
+        let now = crate::arch::get_time();
+        KTHREAD_STAMP.store(now, Ordering::Relaxed);
+
+        if ENABLED.load(Ordering::Acquire) {
+            for (cpu, stamp) in TICK_STAMP.iter().enumerate().take(num_cpu()) {
+                let stamp = stamp.load(Ordering::Relaxed);
+                if stamp != 0 && now.saturating_sub(stamp) > HARDLOCKUP_THRESH_SECS * clock_freq() {
+                    fire(format_args!(
+                        "hard lockup on CPU{}, no timer tick for {}s",
+                        cpu,
+                        (now - stamp) / clock_freq()
+                    ));
+                }
+            }
+            for hw in WATCHDOG_DRIVERS.read().iter() {
+                hw.keepalive();
+            }
+        }
+
+        let expired = {
+            let user = USER.lock();
+            (user.armed && now > user.deadline).then_some(user.timeout)
+        };
+        if let Some(timeout) = expired {
+            fire(format_args!("/dev/watchdog not pinged within {}s", timeout));
+        }
+
+        sleep_ms(WATCHDOG_PERIOD_MS);
+    }
+
+    suspend();
+    crate::pr_info!("[Watchdog] watchdogd stopped");
+    0
+}
+
+/// `/dev/watchdog` 打开后的文件
+pub struct WatchdogFile {
+    dentry: Arc<Dentry>,
+    flags: OpenFlags,
+}
+
+impl WatchdogFile {
+    /// 打开设备并启动计时；已被打开时返回 EBUSY
+    pub fn open(dentry: Arc<Dentry>, flags: OpenFlags) -> Result<Self, FsError> {
+        let mut user = USER.lock();
+        if user.open {
+            return Err(FsError::Busy);
+        }
+        user.open = true;
+        user.armed = true;
+        user.expect_close = false;
+        user.ping(crate::arch::get_time());
+        Ok(Self { dentry, flags })
+    }
+}
+
+impl File for WatchdogFile {
+    fn readable(&self) -> bool {
+        false
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn read(&self, _buf: &mut [u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    /// 任何写入都会喂狗；数据中包含 'V' 时允许关闭时停止
+    fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        if !buf.is_empty() {
+            let mut user = USER.lock();
+            user.expect_close = buf.contains(&b'V');
+            user.ping(crate::arch::get_time());
+        }
+        Ok(buf.len())
+    }
+
+    fn metadata(&self) -> Result<InodeMetadata, FsError> {
+        self.dentry.inode.metadata()
+    }
+
+    fn flags(&self) -> OpenFlags {
+        self.flags
+    }
+
+    fn inode(&self) -> Result<Arc<dyn Inode>, FsError> {
+        Ok(self.dentry.inode.clone())
+    }
+
+    fn dentry(&self) -> Result<Arc<Dentry>, FsError> {
+        Ok(self.dentry.clone())
+    }
+
+    fn ioctl(&self, request: u32, arg: usize) -> Result<isize, FsError> {
+        let read_u32 = || -> Result<u32, FsError> {
+            let mut val = 0u32;
+            unsafe {
+                ArchImpl::copy_from_user(
+                    UA::from_usize(arg),
+                    &mut val as *mut u32 as *mut u8,
+                    core::mem::size_of::<u32>(),
+                )
+            }
+            .map_err(|_| FsError::BadAddress)?;
+            Ok(val)
+        };
+        let write_bytes = |src: *const u8, len: usize| -> Result<isize, FsError> {
+            unsafe { ArchImpl::copy_to_user(src, UA::from_usize(arg), len) }
+                .map_err(|_| FsError::BadAddress)?;
+            Ok(0)
+        };
+        let write_u32 = |val: u32| write_bytes(&val as *const u32 as *const u8, 4);
+
+        match request {
+            WDIOC_GETSUPPORT => {
+                let mut info = WatchdogInfo {
+                    options: WDIOF_SETTIMEOUT | WDIOF_MAGICCLOSE | WDIOF_KEEPALIVEPING,
+                    ..Default::default()
+                };
+                let identity = b"Software Watchdog";
+                info.identity[..identity.len()].copy_from_slice(identity);
+                write_bytes(
+                    &info as *const WatchdogInfo as *const u8,
+                    core::mem::size_of::<WatchdogInfo>(),
+                )
+            }
+            WDIOC_GETSTATUS => write_u32(0),
+            WDIOC_GETBOOTSTATUS => write_u32(if last_boot_was_watchdog_reset() {
+                WDIOF_CARDRESET
+            } else {
+                0
+            }),
+            WDIOC_SETOPTIONS => {
+                let options = read_u32()?;
+                let mut user = USER.lock();
+                if options & WDIOS_DISABLECARD != 0 {
+                    user.armed = false;
+                }
+                if options & WDIOS_ENABLECARD != 0 {
+                    user.armed = true;
+                    user.ping(crate::arch::get_time());
+                }
+                Ok(0)
+            }
+            WDIOC_KEEPALIVE => {
+                USER.lock().ping(crate::arch::get_time());
+                Ok(0)
+            }
+            WDIOC_SETTIMEOUT => {
+                let timeout = read_u32()?;
+                if timeout == 0 || timeout > MAX_USER_TIMEOUT {
+                    return Err(FsError::InvalidArgument);
+                }
+                {
+                    let mut user = USER.lock();
+                    user.timeout = timeout;
+                    user.ping(crate::arch::get_time());
+                }
+                write_u32(timeout)
+            }
+            WDIOC_GETTIMEOUT => write_u32(USER.lock().timeout),
+            _ => Err(FsError::NotTty),
+        }
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+impl Drop for WatchdogFile {
+    fn drop(&mut self) {
+        let mut user = USER.lock();
+        user.open = false;
+        if user.expect_close {
+            user.armed = false;
+        } else if user.armed {
+            crate::pr_crit!("[Watchdog] /dev/watchdog closed unexpectedly, not stopping");
+        }
+        user.expect_close = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_watchdog_ioctl_numbers, {
+        // 与 Linux 的 <linux/watchdog.h> 一致
+        kassert!(WDIOC_GETSUPPORT == 0x8028_5700);
+        kassert!(WDIOC_GETBOOTSTATUS == 0x8004_5702);
+        kassert!(WDIOC_KEEPALIVE == 0x8004_5705);
+        kassert!(WDIOC_SETTIMEOUT == 0xc004_5706);
+        kassert!(WDIOC_GETTIMEOUT == 0x8004_5707);
+    });
+
+    test_case!(test_user_watchdog_ping, {
+        let mut user = UserWatchdog::new();
+        kassert!(!user.armed);
+        user.timeout = 5;
+        user.ping(100);
+        kassert!(user.deadline == 100 + 5 * clock_freq());
+    });
+}
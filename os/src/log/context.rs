@@ -19,24 +19,12 @@ pub(super) struct LogContext {
 /// # 实现状态
 ///
 /// - **时间戳**: 已通过 `arch::timer::get_time()` 实现
-/// - **CPU ID**: 通过 `arch::kernel::cpu::cpu_id()` 实现
-/// - **任务 ID**: 通过当前任务的 tid 字段获取（如果存在任务）
+/// - **CPU ID**/**任务 ID**: 从 per-CPU 的 [`Cpu`](crate::kernel::Cpu) 结构体读取（没有任务时 tid 为 0）
 pub(super) fn collect_context() -> LogContext {
-    // 获取 CPU ID
-    let cpu_id = crate::arch::cpu_id();
-
-    // 尝试获取当前任务的 tid
-    // 注意：在早期启动或中断上下文中可能没有当前任务
-    // 更重要的是：如果当前已经在持有 task lock 的上下文中（例如 wait4），
-    // 再次尝试获取锁会导致死锁。因此这里必须使用 try_lock。
-    let task_id = {
-        let _guard = crate::sync::PreemptGuard::new();
-        crate::kernel::current_cpu()
-            .current_task
-            .as_ref()
-            .and_then(|task| task.try_lock().map(|t| t.tid))
-            .unwrap_or(0)
-    };
+    // 一次借用本 CPU 的 Cpu 结构体读出 CPU ID 和缓存的 tid，借用期间禁用抢占，
+    // 两者不会因迁移而错配。不获取任务锁，因此在已持有 task lock 的上下文中
+    // （例如 wait4）记录日志也不会死锁；早期启动时没有当前任务，tid 为 0
+    let (cpu_id, task_id) = crate::kernel::CPUS.with(|cpu| (cpu.cpu_id, cpu.current_tid));
 
     LogContext {
         cpu_id,
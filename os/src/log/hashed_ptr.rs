@@ -0,0 +1,82 @@
+//! 日志中的内核指针哈希
+//!
+//! 类似 Linux 的 `%p`：默认把内核地址替换为按启动随机密钥计算的哈希值，
+//! 同一次启动中同一地址的哈希保持不变，便于在日志中关联对象，
+//! 又不会泄露真实地址。写 `/proc/sys/kernel/hash_pointers` 为 0 可打印原始地址。
+//!
+//! ```rust
+//! pr_debug!("trap frame at {}", hashed_ptr(tfp as usize));
+//! ```
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// 是否哈希日志中的指针
+static HASH_POINTERS: AtomicBool = AtomicBool::new(true);
+
+/// 哈希密钥；0 表示尚未生成
+static PTR_KEY: AtomicU64 = AtomicU64::new(0);
+
+/// 开启或关闭指针哈希
+pub fn set_hash_pointers(enabled: bool) {
+    HASH_POINTERS.store(enabled, Ordering::Relaxed);
+}
+
+/// 指针哈希是否开启
+pub fn hash_pointers_enabled() -> bool {
+    HASH_POINTERS.load(Ordering::Relaxed)
+}
+
+/// 取本次启动的哈希密钥，首次使用时从内核随机数生成
+fn ptr_key() -> u64 {
+    let key = PTR_KEY.load(Ordering::Relaxed);
+    if key != 0 {
+        return key;
+    }
+    let new_key = crate::security::get_random_u64() | 1;
+    match PTR_KEY.compare_exchange(0, new_key, Ordering::Relaxed, Ordering::Relaxed) {
+        Ok(_) => new_key,
+        Err(existing) => existing,
+    }
+}
+
+/// 带密钥的 64 位混合函数（splitmix64 终结步骤）
+fn keyed_hash(addr: u64, key: u64) -> u64 {
+    let mut x = addr ^ key;
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// 按本次启动的密钥哈希一个地址
+pub fn hash_pointer(addr: usize) -> usize {
+    keyed_hash(addr as u64, ptr_key()) as usize
+}
+
+/// 在日志中打印的内核指针
+///
+/// 哈希开启时打印哈希值，关闭时打印原始地址；空指针总是打印为 0。
+#[derive(Clone, Copy)]
+pub struct HashedPtr(usize);
+
+/// 包装一个要写入日志的内核地址
+pub fn hashed_ptr(addr: usize) -> HashedPtr {
+    HashedPtr(addr)
+}
+
+impl fmt::Display for HashedPtr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = if self.0 != 0 && hash_pointers_enabled() {
+            hash_pointer(self.0)
+        } else {
+            self.0
+        };
+        write!(f, "{:#018x}", value)
+    }
+}
+
+impl fmt::Debug for HashedPtr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
@@ -3,7 +3,7 @@
 //! 该模块将所有日志状态和逻辑封装到一个单独的 `LogCore` 结构体中，
 //! 可以在保持**无锁、零分配**设计的同时，独立实例化用于测试。
 
-use crate::console::Stdout;
+use crate::console::LevelStdout;
 
 use super::buffer::GlobalLogBuffer;
 use super::config::{DEFAULT_CONSOLE_LEVEL, DEFAULT_LOG_LEVEL};
@@ -122,8 +122,8 @@ impl LogCore {
         // 4. 写入缓冲区 (无锁)
         self.buffer.write(&entry);
 
-        // 5. 可选的即时控制台输出
-        if self.is_console_level(level) {
+        // 5. 可选的即时控制台输出（各控制台可以有自己的阈值）
+        if crate::console::level_enabled(level, self._get_console_level()) {
             self.direct_print_entry(&entry);
         }
     }
@@ -230,12 +230,6 @@ impl LogCore {
         level as u8 <= self.global_level.load(Ordering::Acquire)
     }
 
-    /// 检查日志是否应该打印到控制台
-    #[inline(always)]
-    fn is_console_level(&self, level: LogLevel) -> bool {
-        level as u8 <= self.console_level.load(Ordering::Acquire)
-    }
-
     /// 使用 ANSI 颜色直接将日志条目打印到控制台（无堆分配）
     ///
     /// 此方法在早期启动时即可使用，因为它仅使用栈和 core::fmt::Write，
@@ -249,7 +243,11 @@ impl LogCore {
     fn direct_print_entry(&self, entry: &LogEntry) {
         use core::fmt::Write;
 
-        let mut stdout = Stdout;
+        // 各控制台按自己的阈值过滤
+        let mut stdout = LevelStdout {
+            level: entry.level(),
+            default: self._get_console_level(),
+        };
         // 直接格式化输出，不使用堆分配
         // 使用单个 writeln! 调用以确保整个日志条目在一个锁内完成
         let _ = writeln!(
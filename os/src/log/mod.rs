@@ -9,8 +9,10 @@
 //! - [`context`] - 上下文信息收集（CPU ID、任务 ID、时间戳）
 //! - [`log_core`] - 核心日志实现 (LogCore)
 //! - [`entry`] - 日志条目结构和序列化
+//! - [`hashed_ptr`] - 日志中内核指针的哈希打印
 //! - [`level`] - 日志级别定义（从 Emergency 到 Debug）
 //! - [`macros`] - 面向用户的日志宏 (`pr_info!`, `pr_err!`, 等)
+//! - [`pstore`] - panic 时把日志尾部持久化到块设备
 //!
 //! # 设计概览
 //!
@@ -62,14 +64,19 @@ mod buffer;
 mod config;
 mod context;
 mod entry;
+mod hashed_ptr;
 mod level;
 mod log_core;
 pub mod macros;
+pub mod pstore;
 
 pub use config::{
     DEFAULT_CONSOLE_LEVEL, DEFAULT_LOG_LEVEL, GLOBAL_LOG_BUFFER_SIZE, MAX_LOG_MESSAGE_LENGTH,
 };
 pub use entry::LogEntry;
+pub use hashed_ptr::{
+    HashedPtr, hash_pointer, hash_pointers_enabled, hashed_ptr, set_hash_pointers,
+};
 pub use level::LogLevel;
 pub use log_core::format_log_entry;
 
@@ -0,0 +1,264 @@
+//! 持久化崩溃日志 (pstore)
+//!
+//! 内核 panic 或看门狗复位前把日志环形缓冲区的尾部写入块设备末尾的保留区域，
+//! 下次启动后通过 `/sys/fs/pstore/dmesg-blk-0` 读出上一次的崩溃日志。
+//!
+//! # 启用
+//!
+//! 命令行参数 `pstore=<设备名>`（如 `pstore=vdb` 或 `pstore=/dev/vda3`）指定设备，
+//! 使用其最后 [`PSTORE_REGION_SIZE`] 字节。该区域不能被文件系统占用，通常指定
+//! 一块单独的小磁盘或专用分区。未指定时 pstore 不工作。
+//!
+//! # 记录格式
+//!
+//! ```text
+//! 偏移  长度  内容
+//! 0     8     魔数 "CMXPSTOR"
+//! 8     4     正文长度 (小端)
+//! 12    4     正文的 CRC32 (小端)
+//! 16    ...   正文：日志尾部 + panic 信息，纯文本
+//! ```
+//!
+//! 写入整个区域；魔数或 CRC 不匹配时视为没有记录。
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::device::block::BlockDriver;
+use crate::sync::SpinLock;
+use crate::util::crc32::crc32;
+use crate::{pr_info, pr_warn};
+
+use super::{LogEntry, log_reader_index, log_writer_index, peek_log};
+
+/// 保留区域的大小（字节）
+pub const PSTORE_REGION_SIZE: usize = 16 * 1024;
+
+const PSTORE_MAGIC: &[u8; 8] = b"CMXPSTOR";
+const HEADER_SIZE: usize = 16;
+
+/// 记录正文的最大长度
+const MAX_RECORD_LEN: usize = PSTORE_REGION_SIZE - HEADER_SIZE;
+
+/// 已配置的保留区域
+struct PstoreRegion {
+    device: Arc<dyn BlockDriver>,
+    start_block: usize,
+}
+
+static REGION: SpinLock<Option<PstoreRegion>> = SpinLock::new(None);
+/// 启动时从保留区域读出的上一次崩溃日志
+static LAST_RECORD: SpinLock<Option<Vec<u8>>> = SpinLock::new(None);
+/// 防止 panic 嵌套或看门狗与 panic 同时触发时重复写盘
+static DUMPING: AtomicBool = AtomicBool::new(false);
+
+/// 解析命令行中的 `pstore=` 参数，返回块设备名
+///
+/// 接受 `pstore=/dev/vdb` 与 `pstore=vdb` 两种写法。
+pub fn pstore_device_param(cmdline: &str) -> Option<&str> {
+    let value = cmdline
+        .split_ascii_whitespace()
+        .find_map(|arg| arg.strip_prefix("pstore="))?;
+    let name = value.strip_prefix("/dev/").unwrap_or(value);
+    (!name.is_empty()).then_some(name)
+}
+
+/// 按命令行配置保留区域，并读出上一次启动留下的记录
+///
+/// 须在块设备探测完成后调用。
+pub fn init() {
+    let cmdline = crate::device::CMDLINE.read().clone();
+    let Some(name) = pstore_device_param(&cmdline) else {
+        return;
+    };
+    let Some(info) = crate::fs::sysfs::find_block_device(name) else {
+        pr_warn!("[Pstore] pstore=/dev/{} not found", name);
+        return;
+    };
+    let Some(region) = PstoreRegion::new(info.device) else {
+        pr_warn!("[Pstore] /dev/{} cannot hold a pstore region", name);
+        return;
+    };
+
+    if let Some(record) = region.read() {
+        pr_info!(
+            "[Pstore] found {} bytes of crash log from the previous boot",
+            record.len()
+        );
+        *LAST_RECORD.lock() = Some(record);
+    }
+    pr_info!(
+        "[Pstore] using the last {} bytes of /dev/{}",
+        PSTORE_REGION_SIZE,
+        name
+    );
+    *REGION.lock() = Some(region);
+}
+
+/// 上一次启动的崩溃日志
+pub fn last_record() -> Option<Vec<u8>> {
+    LAST_RECORD.lock().clone()
+}
+
+/// 清除上一次的崩溃日志（内存中的副本和盘上的记录）
+pub fn erase() {
+    *LAST_RECORD.lock() = None;
+    if let Some(region) = REGION.lock().as_ref() {
+        region.write(&[]);
+    }
+}
+
+/// panic 处理函数调用：把日志尾部和 panic 信息写入保留区域
+pub fn dump_panic(info: &PanicInfo) {
+    match info.location() {
+        Some(location) => dump(format_args!(
+            "Kernel panic at {}:{}: {}",
+            location.file(),
+            location.line(),
+            info.message()
+        )),
+        None => dump(format_args!("Kernel panic: {}", info.message())),
+    }
+}
+
+/// 把日志尾部和一行原因写入保留区域，用于 panic 和看门狗复位前
+///
+/// 尽力而为：区域被占用、已经写过一次或写盘失败时直接放弃。
+pub fn dump(reason: fmt::Arguments) {
+    if DUMPING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let Some(guard) = REGION.try_lock() else {
+        return;
+    };
+    let Some(region) = guard.as_ref() else {
+        return;
+    };
+
+    let mut text = String::new();
+    let mut index = log_reader_index();
+    let end = log_writer_index();
+    while index < end {
+        if let Some(entry) = peek_log(index) {
+            let _ = write_entry(&mut text, &entry);
+        }
+        index += 1;
+    }
+    let _ = writeln!(text, "{}", reason);
+
+    let bytes = text.as_bytes();
+    region.write(&bytes[bytes.len().saturating_sub(MAX_RECORD_LEN)..]);
+}
+
+/// 不带颜色控制码的日志行，与 `format_log_entry` 的字段一致
+fn write_entry(out: &mut String, entry: &LogEntry) -> fmt::Result {
+    writeln!(
+        out,
+        "{} [{:12}] [CPU{}/T{:3}] {}",
+        entry.level().as_str(),
+        entry.timestamp(),
+        entry.cpu_id(),
+        entry.task_id(),
+        entry.message()
+    )
+}
+
+impl PstoreRegion {
+    fn new(device: Arc<dyn BlockDriver>) -> Option<Self> {
+        let block_size = device.block_size();
+        if block_size == 0 || !PSTORE_REGION_SIZE.is_multiple_of(block_size) {
+            return None;
+        }
+        let start_block = device
+            .total_blocks()
+            .checked_sub(PSTORE_REGION_SIZE / block_size)?;
+        Some(Self {
+            device,
+            start_block,
+        })
+    }
+
+    fn read(&self) -> Option<Vec<u8>> {
+        let mut buf = vec![0u8; PSTORE_REGION_SIZE];
+        if !self.device.read_blocks(self.start_block, &mut buf) {
+            return None;
+        }
+        decode_record(&buf).map(<[u8]>::to_vec)
+    }
+
+    /// 写入记录；`data` 为空时写入无效记录，即清除
+    fn write(&self, data: &[u8]) -> bool {
+        let buf = if data.is_empty() {
+            vec![0u8; PSTORE_REGION_SIZE]
+        } else {
+            encode_record(data)
+        };
+        self.device.write_blocks(self.start_block, &buf) && self.device.flush()
+    }
+}
+
+/// 把正文编码为整个保留区域的内容，超长的正文只保留尾部
+fn encode_record(data: &[u8]) -> Vec<u8> {
+    let data = &data[data.len().saturating_sub(MAX_RECORD_LEN)..];
+    let mut buf = vec![0u8; PSTORE_REGION_SIZE];
+    buf[..8].copy_from_slice(PSTORE_MAGIC);
+    buf[8..12].copy_from_slice(&(data.len() as u32).to_le_bytes());
+    buf[12..16].copy_from_slice(&crc32(data).to_le_bytes());
+    buf[HEADER_SIZE..HEADER_SIZE + data.len()].copy_from_slice(data);
+    buf
+}
+
+/// 校验保留区域的内容，返回记录正文
+fn decode_record(buf: &[u8]) -> Option<&[u8]> {
+    if buf.len() < HEADER_SIZE || &buf[..8] != PSTORE_MAGIC {
+        return None;
+    }
+    let len = u32::from_le_bytes(buf[8..12].try_into().unwrap()) as usize;
+    let crc = u32::from_le_bytes(buf[12..16].try_into().unwrap());
+    let data = buf.get(HEADER_SIZE..HEADER_SIZE.checked_add(len)?)?;
+    (len <= MAX_RECORD_LEN && crc32(data) == crc).then_some(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::block::ram_disk::RamDisk;
+    use crate::{kassert, test_case};
+
+    test_case!(test_pstore_record_roundtrip, {
+        kassert!(crc32(b"123456789") == 0xCBF4_3926);
+
+        let disk = RamDisk::new(64 * 1024, 512, 0);
+        let region = PstoreRegion::new(disk.clone()).unwrap();
+        kassert!(region.start_block == (64 * 1024 - PSTORE_REGION_SIZE) / 512);
+        kassert!(region.read().is_none());
+
+        kassert!(region.write(b"Kernel panic: oops\n"));
+        kassert!(region.read().as_deref() == Some(&b"Kernel panic: oops\n"[..]));
+
+        // 正文被破坏时 CRC 不匹配
+        let mut raw = disk.raw_data();
+        raw[64 * 1024 - PSTORE_REGION_SIZE + HEADER_SIZE] ^= 0xff;
+        kassert!(decode_record(&raw[64 * 1024 - PSTORE_REGION_SIZE..]).is_none());
+
+        // 超长正文只保留尾部
+        let long = vec![b'x'; PSTORE_REGION_SIZE];
+        let encoded = encode_record(&long);
+        kassert!(decode_record(&encoded).map(<[u8]>::len) == Some(MAX_RECORD_LEN));
+
+        kassert!(region.write(&[]));
+        kassert!(region.read().is_none());
+    });
+
+    test_case!(test_pstore_device_param, {
+        kassert!(pstore_device_param("console=ttyS0 pstore=/dev/vdb") == Some("vdb"));
+        kassert!(pstore_device_param("pstore=vda3 root=vda1") == Some("vda3"));
+        kassert!(pstore_device_param("pstore=") == None);
+        kassert!(pstore_device_param("root=vda1") == None);
+    });
+}
@@ -0,0 +1,31 @@
+// os/src/log/tests/hashed_ptr.rs
+//
+// 测试日志指针哈希
+
+use super::*;
+use crate::log::{hash_pointer, hash_pointers_enabled, hashed_ptr, set_hash_pointers};
+use alloc::format;
+
+test_case!(test_hash_pointer_stable, {
+    let addr = 0xffff_ffc0_8020_1000usize;
+    let hashed = hash_pointer(addr);
+
+    // 同一次启动内稳定，且不等于原地址
+    kassert!(hash_pointer(addr) == hashed);
+    kassert!(hashed != addr);
+    kassert!(hash_pointer(addr + 8) != hashed);
+});
+
+test_case!(test_hashed_ptr_format, {
+    let addr = 0xffff_ffc0_8020_1000usize;
+    let was_enabled = hash_pointers_enabled();
+
+    set_hash_pointers(true);
+    kassert!(format!("{}", hashed_ptr(addr)) == format!("{:#018x}", hash_pointer(addr)));
+    kassert!(format!("{}", hashed_ptr(0)) == format!("{:#018x}", 0));
+
+    set_hash_pointers(false);
+    kassert!(format!("{}", hashed_ptr(addr)) == format!("{:#018x}", addr));
+
+    set_hash_pointers(was_enabled);
+});
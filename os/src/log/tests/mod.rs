@@ -20,5 +20,6 @@ mod basic;
 mod byte_counting;
 mod filter;
 mod format;
+mod hashed_ptr;
 mod overflow;
 mod peek;
@@ -74,6 +74,7 @@ fn panic(info: &PanicInfo) -> ! {
     } else {
         crate::console::emergency_print(format_args!("Panicked: {}\n", info.message()));
     }
+    crate::log::pstore::dump_panic(info);
 
     crate::arch::ArchImpl::power_off()
 }
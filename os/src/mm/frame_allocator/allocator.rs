@@ -7,6 +7,7 @@
 use crate::config::PAGE_SIZE;
 use crate::mm::address::{ConvertablePA, PageNum, Ppn, PpnRange, UsizeConvert};
 use crate::sync::SpinLock;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 
 const BITS_PER_WORD: usize = u64::BITS as usize;
@@ -14,6 +15,40 @@ const MAX_MANAGED_PHYS_BYTES: usize = 8 * 1024 * 1024 * 1024;
 const MAX_MANAGED_FRAMES: usize = MAX_MANAGED_PHYS_BYTES / PAGE_SIZE;
 const MAX_BITMAP_WORDS: usize = MAX_MANAGED_FRAMES.div_ceil(BITS_PER_WORD);
 
+/// DMA32 区域的物理地址上限（不包含）
+pub const DMA32_LIMIT: usize = 1 << 32;
+
+/// 物理内存区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zone {
+    /// 物理地址低于 4GiB，只能发出 32 位地址的设备也可以访问
+    Dma32,
+    /// 其余物理内存
+    Normal,
+}
+
+bitflags::bitflags! {
+    /// 帧分配标志
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct AllocFlags: u32 {
+        /// 只从 DMA32 区域分配
+        const DMA32 = 1 << 0;
+    }
+}
+
+impl AllocFlags {
+    /// 按优先顺序列出可以分配的区域
+    ///
+    /// 普通分配优先使用 NORMAL 区域，耗尽后才退回 DMA32，为设备保留低端内存。
+    fn zones(self) -> &'static [Zone] {
+        if self.contains(AllocFlags::DMA32) {
+            &[Zone::Dma32]
+        } else {
+            &[Zone::Normal, Zone::Dma32]
+        }
+    }
+}
+
 /// 物理帧跟踪器。
 /// 实现了 RAII 模式：当此结构体被 drop 时，它所管理的物理页帧会被自动回收。
 #[derive(Debug)]
@@ -103,24 +138,172 @@ pub enum TrackedFrames {
     Single(FrameTracker),
     /// 多个不连续物理帧。
     Multiple(Vec<FrameTracker>),
+    /// 同页合并后由多个只读映射共享的单个物理帧。
+    Merged(Arc<FrameTracker>),
 }
 
 /// 全局物理帧分配器，由自旋锁保护。
 pub static FRAME_ALLOCATOR: SpinLock<FrameAllocator> = SpinLock::new(FrameAllocator::new());
 
+/// 帧分配器最多管理的物理内存区域数。
+pub const MAX_FRAME_REGIONS: usize = 8;
+
+/// 一段连续的受管物理内存。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FrameRegion {
+    /// 区域的起始 Ppn。
+    start: Ppn,
+    /// 区域首帧在位图中的索引。
+    base: usize,
+    /// 区域的帧数。
+    frames: usize,
+}
+
+impl FrameRegion {
+    const EMPTY: Self = FrameRegion {
+        start: Ppn(0),
+        base: 0,
+        frames: 0,
+    };
+
+    /// 区域的帧索引范围 `[base, base + frames)`。
+    fn index_end(&self) -> usize {
+        self.base + self.frames
+    }
+}
+
+/// 受管区域表：把按地址排序的若干区域首尾相接，映射到连续的帧索引空间。
+///
+/// 位图按帧索引组织，区域之间的空洞不占位图空间。
+#[derive(Debug, Clone, Copy)]
+struct FrameRegions {
+    regions: [FrameRegion; MAX_FRAME_REGIONS],
+    len: usize,
+}
+
+impl FrameRegions {
+    const fn new() -> Self {
+        FrameRegions {
+            regions: [FrameRegion::EMPTY; MAX_FRAME_REGIONS],
+            len: 0,
+        }
+    }
+
+    /// 由 `[start, end)` 列表建立区域表，按地址排序。
+    ///
+    /// 空区域被忽略；超出 [`MAX_FRAME_REGIONS`] 个区域或总帧数超过 `capacity` 的部分被截去。
+    fn build(ranges: &[(Ppn, Ppn)], capacity: usize) -> Self {
+        let mut sorted = [(Ppn(0), Ppn(0)); MAX_FRAME_REGIONS];
+        let mut n = 0;
+        for &(start, end) in ranges {
+            if end <= start || n == MAX_FRAME_REGIONS {
+                continue;
+            }
+            let pos = sorted[..n]
+                .iter()
+                .position(|(s, _)| *s > start)
+                .unwrap_or(n);
+            sorted.copy_within(pos..n, pos + 1);
+            sorted[pos] = (start, end);
+            n += 1;
+        }
+
+        let mut table = FrameRegions::new();
+        let mut base = 0;
+        for &(start, end) in &sorted[..n] {
+            // 与前一区域重叠的部分跳过
+            let start = match table.last() {
+                Some(prev) => start.max(prev.start + prev.frames),
+                None => start,
+            };
+            let frames = end
+                .as_usize()
+                .saturating_sub(start.as_usize())
+                .min(capacity - base);
+            if frames == 0 {
+                continue;
+            }
+            table.regions[table.len] = FrameRegion {
+                start,
+                base,
+                frames,
+            };
+            table.len += 1;
+            base += frames;
+        }
+        table
+    }
+
+    fn as_slice(&self) -> &[FrameRegion] {
+        &self.regions[..self.len]
+    }
+
+    fn last(&self) -> Option<&FrameRegion> {
+        self.as_slice().last()
+    }
+
+    /// 所有区域的总帧数。
+    fn total_frames(&self) -> usize {
+        self.last().map_or(0, FrameRegion::index_end)
+    }
+
+    /// 物理地址低于 `limit_ppn` 的帧数；区域按地址排序，这些帧恰好是索引的前缀。
+    fn frames_below(&self, limit_ppn: usize) -> usize {
+        self.as_slice()
+            .iter()
+            .map(|r| limit_ppn.saturating_sub(r.start.as_usize()).min(r.frames))
+            .sum()
+    }
+
+    /// 帧索引对应的 Ppn。
+    fn ppn_of(&self, frame_idx: usize) -> Ppn {
+        let region = self
+            .as_slice()
+            .iter()
+            .find(|r| frame_idx < r.index_end())
+            .expect("frame index out of range");
+        region.start + (frame_idx - region.base)
+    }
+
+    /// Ppn 对应的帧索引，不在任何区域内时返回 `None`。
+    fn index_of(&self, ppn: Ppn) -> Option<usize> {
+        self.as_slice().iter().find_map(|r| {
+            let offset = ppn.as_usize().checked_sub(r.start.as_usize())?;
+            (offset < r.frames).then_some(r.base + offset)
+        })
+    }
+
+    /// 帧索引范围 `[lo, hi)` 与各区域的交集，连续分配不能跨越区域边界。
+    fn segments(
+        &self,
+        lo: usize,
+        hi: usize,
+    ) -> impl Iterator<Item = (FrameRegion, usize, usize)> + '_ {
+        self.as_slice().iter().filter_map(move |r| {
+            let seg_lo = lo.max(r.base);
+            let seg_hi = hi.min(r.index_end());
+            (seg_lo < seg_hi).then_some((*r, seg_lo, seg_hi))
+        })
+    }
+}
+
 /// 物理帧分配器。
-/// 采用位图策略跟踪每个物理帧的分配状态。
+/// 采用位图策略跟踪每个物理帧的分配状态，可管理多个互不相邻的物理内存区域。
 pub struct FrameAllocator {
-    /// 物理帧的起始 Ppn。
-    start: Ppn,
-    /// 物理帧的结束 Ppn (不包含)。
-    end: Ppn,
+    /// 受管区域表。
+    regions: FrameRegions,
     /// 位图数据（每个 bit 表示一个帧：0=空闲，1=已分配）。
     bitmap: [u64; MAX_BITMAP_WORDS],
     /// 总帧数。
     total_frames: usize,
-    /// 已分配帧数。
+    /// DMA32 区域的帧数（帧索引 `[0, dma32_frames)` 属于 DMA32 区域）。
+    dma32_frames: usize,
+    /// 已分配帧数（不含保留帧）。
     allocated_count: usize,
+    /// 保留帧数：范围内但被固件、设备树等占用，不参与分配的帧。
+    reserved_count: usize,
+    /// 保留帧中属于 DMA32 区域的帧数。
+    reserved_dma32: usize,
     /// 上次分配的位置提示，用于加速单帧分配。
     last_alloc_hint: usize,
 }
@@ -130,30 +313,62 @@ impl FrameAllocator {
     /// 创建一个新的帧分配器实例。
     pub const fn new() -> Self {
         FrameAllocator {
-            // 使用 usize::MAX 作为初始值，表示未初始化状态
-            start: Ppn(usize::MAX),
-            end: Ppn(usize::MAX),
+            regions: FrameRegions::new(),
             bitmap: [0; MAX_BITMAP_WORDS],
             total_frames: 0,
+            dma32_frames: 0,
             allocated_count: 0,
+            reserved_count: 0,
+            reserved_dma32: 0,
             last_alloc_hint: 0,
         }
     }
 
-    /// 初始化帧分配器，设置可用的物理内存范围。
+    /// 初始化帧分配器，设置单个可用的物理内存范围。
     pub fn init(&mut self, start: Ppn, end: Ppn) {
-        self.start = start;
-        self.end = end;
-        self.total_frames = end.as_usize() - start.as_usize();
-        assert!(
-            self.total_frames <= MAX_MANAGED_FRAMES,
-            "frame allocator range exceeds bitmap capacity"
-        );
+        self.init_regions(&[(start, end)]);
+    }
+
+    /// 初始化帧分配器，设置若干个可用的物理内存范围 `[start, end)`。
+    ///
+    /// 范围可以无序、互不相邻；超出位图容量的部分不被管理。
+    pub fn init_regions(&mut self, ranges: &[(Ppn, Ppn)]) {
+        self.regions = FrameRegions::build(ranges, MAX_MANAGED_FRAMES);
+        self.total_frames = self.regions.total_frames();
+        self.dma32_frames = self.regions.frames_below(DMA32_LIMIT / PAGE_SIZE);
         self.bitmap.fill(0);
         self.allocated_count = 0;
+        self.reserved_count = 0;
+        self.reserved_dma32 = 0;
         self.last_alloc_hint = 0;
     }
 
+    /// 把 `[start, end)` 中的帧标记为保留，此后不再分配。
+    ///
+    /// 不在受管区域内的部分被忽略，已经保留的帧不重复计数。
+    pub fn reserve(&mut self, start: Ppn, end: Ppn) {
+        let regions = self.regions;
+        for region in regions.as_slice() {
+            let lo = start.as_usize().max(region.start.as_usize());
+            let hi = end.as_usize().min(region.start.as_usize() + region.frames);
+            for ppn in lo..hi.max(lo) {
+                let frame_idx = region.base + (ppn - region.start.as_usize());
+                if self.is_free(frame_idx) {
+                    self.mark_allocated(frame_idx);
+                    self.reserved_count += 1;
+                    if frame_idx < self.dma32_frames {
+                        self.reserved_dma32 += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 受管区域的 `(起始 Ppn, 帧数)` 列表，按地址递增。
+    pub fn regions(&self) -> impl Iterator<Item = (Ppn, usize)> + '_ {
+        self.regions.as_slice().iter().map(|r| (r.start, r.frames))
+    }
+
     #[inline]
     fn bitmap_words(&self) -> usize {
         self.total_frames.div_ceil(BITS_PER_WORD)
@@ -183,33 +398,76 @@ impl FrameAllocator {
         self.bitmap[word_idx] &= !(1u64 << bit_idx);
     }
 
+    /// 区域对应的帧索引范围 `[lo, hi)`。
+    fn zone_range(&self, zone: Zone) -> (usize, usize) {
+        match zone {
+            Zone::Dma32 => (0, self.dma32_frames),
+            Zone::Normal => (self.dma32_frames, self.total_frames),
+        }
+    }
+
+    /// 区域的总帧数（不含保留帧）和空闲帧数。
+    pub fn zone_frames(&self, zone: Zone) -> (usize, usize) {
+        let (lo, hi) = self.zone_range(zone);
+        let free = (lo..hi).filter(|&idx| self.is_free(idx)).count();
+        let reserved = match zone {
+            Zone::Dma32 => self.reserved_dma32,
+            Zone::Normal => self.reserved_count - self.reserved_dma32,
+        };
+        (hi - lo - reserved, free)
+    }
+
     /// 分配一个物理帧。
-    /// 从 last_alloc_hint 开始循环查找第一个空闲位。
     pub fn alloc_frame(&mut self) -> Option<FrameTracker> {
-        let bitmap_words = self.bitmap_words();
-        if bitmap_words == 0 {
+        self.alloc_frame_with(AllocFlags::empty())
+    }
+
+    /// 按分配标志从允许的区域中分配一个物理帧。
+    pub fn alloc_frame_with(&mut self, flags: AllocFlags) -> Option<FrameTracker> {
+        flags.zones().iter().find_map(|&zone| {
+            let (lo, hi) = self.zone_range(zone);
+            self.alloc_frame_in(lo, hi)
+        })
+    }
+
+    /// 在帧索引 `[lo, hi)` 内分配一个物理帧。
+    /// 从 last_alloc_hint 开始循环查找第一个空闲位。
+    fn alloc_frame_in(&mut self, lo: usize, hi: usize) -> Option<FrameTracker> {
+        if lo >= hi {
             return None;
         }
 
-        let start_idx = self.last_alloc_hint;
-        for offset in 0..bitmap_words {
-            let idx = (start_idx + offset) % bitmap_words;
-            let word = self.bitmap[idx];
+        let first_word = lo / BITS_PER_WORD;
+        let words = (hi - 1) / BITS_PER_WORD - first_word + 1;
+        let start_offset = self.last_alloc_hint.wrapping_sub(first_word);
+        let start_offset = if start_offset < words {
+            start_offset
+        } else {
+            0
+        };
+        for offset in 0..words {
+            let idx = first_word + (start_offset + offset) % words;
+            let base = idx * BITS_PER_WORD;
+            // 区域之外的位视为已分配
+            let mut word = self.bitmap[idx];
+            if base < lo {
+                word |= (1u64 << (lo - base)) - 1;
+            }
+            if base + BITS_PER_WORD > hi {
+                word |= u64::MAX << (hi - base);
+            }
             if word == u64::MAX {
                 continue;
             }
 
             let bit_pos = (!word).trailing_zeros() as usize;
-            let frame_idx = idx * BITS_PER_WORD + bit_pos;
-            if frame_idx >= self.total_frames {
-                continue;
-            }
+            let frame_idx = base + bit_pos;
 
             self.mark_allocated(frame_idx);
             self.allocated_count += 1;
             self.last_alloc_hint = idx;
 
-            let ppn = self.start + frame_idx;
+            let ppn = self.regions.ppn_of(frame_idx);
             return Some(FrameTracker::new(ppn));
         }
 
@@ -219,50 +477,35 @@ impl FrameAllocator {
     /// 分配指定数量的物理帧（不保证连续）。
     pub fn alloc_frames(&mut self, num: usize) -> Option<Vec<FrameTracker>> {
         let mut frames = Vec::with_capacity(num);
+        self.alloc_frames_into(&mut frames, num).then_some(frames)
+    }
+
+    /// 分配 `num` 个物理帧追加到 `frames`（不保证连续）。
+    ///
+    /// `frames` 应预留足够容量，避免持有分配器锁时扩展内核堆。
+    /// 失败时返回 `false`，`frames` 中已有的帧由调用者 drop 回收。
+    pub fn alloc_frames_into(&mut self, frames: &mut Vec<FrameTracker>, num: usize) -> bool {
         for _ in 0..num {
-            if let Some(frame) = self.alloc_frame() {
-                frames.push(frame);
-            } else {
-                // 分配失败，需要将已分配的帧回收
-                // 由于 FrameTracker 实现了 Drop，这里直接 drop frames 即可
-                return None;
+            match self.alloc_frame() {
+                Some(frame) => frames.push(frame),
+                None => return false,
             }
         }
-        Some(frames)
+        true
     }
 
     /// 分配指定数量的**连续**物理帧。
     pub fn alloc_contig_frames(&mut self, num: usize) -> Option<FrameRangeTracker> {
-        if num == 0 || num > self.free_frames() {
-            return None;
-        }
-
-        let mut run_len = 0;
-        let mut run_start = 0;
-
-        for frame_idx in 0..self.total_frames {
-            if self.is_free(frame_idx) {
-                if run_len == 0 {
-                    run_start = frame_idx;
-                }
-                run_len += 1;
-
-                if run_len == num {
-                    for i in 0..num {
-                        self.mark_allocated(run_start + i);
-                    }
-                    self.allocated_count += num;
-
-                    let start_ppn = self.start + run_start;
-                    let range = PpnRange::from_start_len(start_ppn, num);
-                    return Some(FrameRangeTracker::new(range));
-                }
-            } else {
-                run_len = 0;
-            }
-        }
+        self.alloc_contig_frames_with(num, AllocFlags::empty())
+    }
 
-        None
+    /// 按分配标志从允许的区域中分配指定数量的**连续**物理帧。
+    pub fn alloc_contig_frames_with(
+        &mut self,
+        num: usize,
+        flags: AllocFlags,
+    ) -> Option<FrameRangeTracker> {
+        self.alloc_contig_frames_aligned_with(num, 1, flags)
     }
 
     /// 分配指定数量的**连续**物理帧，并确保起始地址对齐到 `align_pages` 页的边界。
@@ -270,6 +513,16 @@ impl FrameAllocator {
         &mut self,
         num: usize,
         align_pages: usize,
+    ) -> Option<FrameRangeTracker> {
+        self.alloc_contig_frames_aligned_with(num, align_pages, AllocFlags::empty())
+    }
+
+    /// 按分配标志从允许的区域中分配对齐的连续物理帧。
+    pub fn alloc_contig_frames_aligned_with(
+        &mut self,
+        num: usize,
+        align_pages: usize,
+        flags: AllocFlags,
     ) -> Option<FrameRangeTracker> {
         if num == 0 || num > self.free_frames() {
             return None;
@@ -280,31 +533,47 @@ impl FrameAllocator {
             "Alignment must be power of 2" // 对齐必须是 2 的幂
         );
 
-        let mut frame_idx = 0;
-        while frame_idx < self.total_frames {
-            let aligned_idx = (frame_idx + align_pages - 1) & !(align_pages - 1);
-            if aligned_idx + num > self.total_frames {
+        flags.zones().iter().find_map(|&zone| {
+            let (lo, hi) = self.zone_range(zone);
+            let regions = self.regions;
+            regions
+                .segments(lo, hi)
+                .find_map(|(region, seg_lo, seg_hi)| {
+                    self.alloc_contig_frames_in(num, align_pages, region, seg_lo, seg_hi)
+                })
+        })
+    }
+
+    /// 在同一区域的帧索引 `[lo, hi)` 内分配起始 Ppn 对齐到 `align_pages` 的连续物理帧。
+    fn alloc_contig_frames_in(
+        &mut self,
+        num: usize,
+        align_pages: usize,
+        region: FrameRegion,
+        lo: usize,
+        hi: usize,
+    ) -> Option<FrameRangeTracker> {
+        // 帧索引与 Ppn 在区域内只差一个常量偏移
+        let ppn_offset = region.start.as_usize() - region.base;
+        let mut frame_idx = lo;
+        while frame_idx < hi {
+            let aligned_ppn = (frame_idx + ppn_offset).next_multiple_of(align_pages);
+            let aligned_idx = aligned_ppn - ppn_offset;
+            if aligned_idx + num > hi {
                 break;
             }
 
-            let mut all_free = true;
-            for i in 0..num {
-                if !self.is_free(aligned_idx + i) {
-                    all_free = false;
-                    frame_idx = aligned_idx + i + 1;
-                    break;
-                }
-            }
+            match (0..num).find(|&i| !self.is_free(aligned_idx + i)) {
+                Some(i) => frame_idx = aligned_idx + i + 1,
+                None => {
+                    for i in 0..num {
+                        self.mark_allocated(aligned_idx + i);
+                    }
+                    self.allocated_count += num;
 
-            if all_free {
-                for i in 0..num {
-                    self.mark_allocated(aligned_idx + i);
+                    let range = PpnRange::from_start_len(Ppn(aligned_ppn), num);
+                    return Some(FrameRangeTracker::new(range));
                 }
-                self.allocated_count += num;
-
-                let start_ppn = self.start + aligned_idx;
-                let range = PpnRange::from_start_len(start_ppn, num);
-                return Some(FrameRangeTracker::new(range));
             }
         }
 
@@ -313,14 +582,10 @@ impl FrameAllocator {
 
     /// 回收一个物理帧。
     pub fn dealloc_frame(&mut self, frame: &FrameTracker) {
-        // 检查帧是否在有效范围内
-        debug_assert!(
-            frame.ppn() >= self.start && frame.ppn() < self.end,
-            "dealloc_frame: frame out of range" // 回收帧超出范围
-        );
-
-        let ppn = frame.ppn();
-        let frame_idx = ppn.as_usize() - self.start.as_usize();
+        let frame_idx = self
+            .regions
+            .index_of(frame.ppn())
+            .expect("dealloc_frame: frame out of range"); // 回收帧超出范围
 
         // 检查帧是否已被分配
         debug_assert!(
@@ -334,15 +599,11 @@ impl FrameAllocator {
 
     /// 回收一个连续的物理帧范围。
     pub fn dealloc_contig_frames(&mut self, frame_range: &FrameRangeTracker) {
-        let start = frame_range.start_ppn();
-        let end = frame_range.end_ppn();
-        // 检查范围是否在有效范围内
-        debug_assert!(
-            start >= self.start && end <= self.end,
-            "dealloc_contig_frames: frame range out of range" // 回收帧范围超出范围
-        );
-
-        let start_idx = start.as_usize() - self.start.as_usize();
+        // 连续范围总是分配自同一区域
+        let start_idx = self
+            .regions
+            .index_of(frame_range.start_ppn())
+            .expect("dealloc_contig_frames: frame range out of range"); // 回收帧范围超出范围
         let len = frame_range.len();
 
         for i in 0..len {
@@ -355,9 +616,9 @@ impl FrameAllocator {
         self.allocated_count -= len;
     }
 
-    /// 获取总的物理帧数
+    /// 获取总的物理帧数（所有区域之和，不含保留帧）
     pub fn total_frames(&self) -> usize {
-        self.total_frames
+        self.total_frames - self.reserved_count
     }
 
     /// 获取已分配的帧数
@@ -375,14 +636,20 @@ impl FrameAllocator {
     /// 获取帧分配器的当前状态
     /// # 返回值
     /// - 当前分配指针的 Ppn
-    /// - 物理帧的结束 Ppn (不包含)
+    /// - 最后一个区域的结束 Ppn (不包含)
     /// - 回收栈的长度（位图实现中恒为 0）
     /// - 已分配的帧数
     /// - 空闲的帧数
     pub fn get_stats(&self) -> (usize, usize, usize, usize, usize) {
+        let (start, end) = match (self.regions.as_slice().first(), self.regions.last()) {
+            (Some(first), Some(last)) => {
+                (first.start.as_usize(), last.start.as_usize() + last.frames)
+            }
+            _ => (usize::MAX, usize::MAX),
+        };
         (
-            self.start.as_usize() + self.allocated_count,
-            self.end.as_usize(),
+            start + self.allocated_count,
+            end,
             0,
             self.allocated_frames(),
             self.free_frames(),
@@ -395,3 +662,39 @@ impl Default for FrameAllocator {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_frame_regions_index_mapping, {
+        // 两个不相邻的区域，乱序给出；第三个与第二个重叠
+        let regions = FrameRegions::build(
+            &[
+                (Ppn(0x9_0000), Ppn(0x9_0100)),
+                (Ppn(0x200), Ppn(0x300)),
+                (Ppn(0x9_0080), Ppn(0x9_0180)),
+            ],
+            MAX_MANAGED_FRAMES,
+        );
+        kassert!(regions.len == 3);
+        kassert!(regions.total_frames() == 0x100 + 0x100 + 0x80);
+        kassert!(regions.ppn_of(0) == Ppn(0x200));
+        kassert!(regions.ppn_of(0x100) == Ppn(0x9_0000));
+        kassert!(regions.ppn_of(0x200) == Ppn(0x9_0100));
+        kassert!(regions.index_of(Ppn(0x9_0001)) == Some(0x101));
+        kassert!(regions.index_of(Ppn(0x300)).is_none());
+        kassert!(regions.frames_below(0x1000) == 0x100);
+
+        // 连续分配的候选段不跨越区域边界
+        let mut segments = regions.segments(0xf0, 0x110);
+        kassert!(segments.next().map(|(_, lo, hi)| (lo, hi)) == Some((0xf0, 0x100)));
+        kassert!(segments.next().map(|(_, lo, hi)| (lo, hi)) == Some((0x100, 0x110)));
+        kassert!(segments.next().is_none());
+
+        // 超出容量的部分被截去
+        let capped = FrameRegions::build(&[(Ppn(0), Ppn(100)), (Ppn(200), Ppn(300))], 150);
+        kassert!(capped.total_frames() == 150);
+    });
+}
@@ -7,15 +7,26 @@
 //! - [`FrameTracker`]：用于单个已分配帧的 **RAII** 封装器。
 //! - [`FrameRangeTracker`]：用于已分配帧范围的 **RAII** 封装器。
 //! - [`init_frame_allocator`]：初始化全局帧分配器。
+//! - [`init_frame_allocator_regions`]：以多段不相邻的物理内存初始化全局帧分配器。
+//! - [`reserve_frames`]：保留分配器范围内已被占用的内存。
 //! - [`alloc_frame`]：分配单个帧。
 //! - `alloc_frames`：分配多个（非连续）帧。
 //! - `alloc_contig_frames`：分配多个连续帧。
 //! - `alloc_contig_frames_aligned`：分配带对齐要求的多个连续帧。
+//!
+//! # 区域
+//!
+//! 物理内存按地址分为 [`Zone::Dma32`]（低于 4GiB）和 [`Zone::Normal`] 两个区域。
+//! 普通分配优先使用 NORMAL 区域；`*_with` 系列函数传入 [`AllocFlags::DMA32`]
+//! 时只从 DMA32 区域分配，供只能发出 32 位地址的设备使用。
 
 mod allocator;
 
 use alloc::vec::Vec;
-pub use allocator::{FrameRangeTracker, FrameTracker, TrackedFrames};
+pub use allocator::{
+    AllocFlags, DMA32_LIMIT, FrameRangeTracker, FrameTracker, MAX_FRAME_REGIONS, TrackedFrames,
+    Zone,
+};
 
 use crate::mm::address::{PA, PageNum, Ppn};
 use allocator::FRAME_ALLOCATOR;
@@ -36,13 +47,49 @@ pub fn init_frame_allocator(start_addr: PA, end_addr: PA) {
     allocator.init(start_ppn, end_ppn);
 }
 
+/// 使用若干段不相邻的物理内存初始化全局帧分配器。
+///
+/// 每段为 `[start, end)`，起点向上、终点向下取整到页；空的段被忽略。
+/// 最多管理 [`MAX_FRAME_REGIONS`] 段，各段共用一个位图，统计信息按所有段汇总。
+pub fn init_frame_allocator_regions(ranges: &[(PA, PA)]) {
+    let mut ppns = [(Ppn(0), Ppn(0)); MAX_FRAME_REGIONS];
+    for (slot, &(start, end)) in ppns.iter_mut().zip(ranges) {
+        *slot = (Ppn::from_addr_ceil(start), Ppn::from_addr_floor(end));
+    }
+    FRAME_ALLOCATOR
+        .lock()
+        .init_regions(&ppns[..ranges.len().min(MAX_FRAME_REGIONS)]);
+}
+
+/// 把物理地址范围 `[start_addr, end_addr)` 覆盖到的帧标记为保留，不再分配。
+///
+/// 用于帧分配器范围内被设备树、initramfs 等占用的内存。
+pub fn reserve_frames(start_addr: PA, end_addr: PA) {
+    let start_ppn = Ppn::from_addr_floor(start_addr);
+    let end_ppn = Ppn::from_addr_ceil(end_addr);
+    FRAME_ALLOCATOR.lock().reserve(start_ppn, end_ppn);
+}
+
 /// 分配一个物理帧。
 ///
 /// # 返回
 ///
 /// 如果分配成功，返回 `Some(FrameTracker)`；否则返回 `None`。
+///
+/// 内存不足时先换出匿名页（见 [`crate::mm::swap`]）再重试一次。
 pub fn alloc_frame() -> Option<FrameTracker> {
-    FRAME_ALLOCATOR.lock().alloc_frame()
+    alloc_frame_with(AllocFlags::empty())
+}
+
+/// 按分配标志分配一个物理帧。
+///
+/// 内存不足时先换出匿名页（见 [`crate::mm::swap`]）再重试一次。
+pub fn alloc_frame_with(flags: AllocFlags) -> Option<FrameTracker> {
+    let frame = FRAME_ALLOCATOR.lock().alloc_frame_with(flags);
+    if frame.is_some() || crate::mm::swap::reclaim(crate::mm::swap::SWAP_CLUSTER) == 0 {
+        return frame;
+    }
+    FRAME_ALLOCATOR.lock().alloc_frame_with(flags)
 }
 
 /// 分配多个物理帧（不保证连续）。
@@ -54,8 +101,20 @@ pub fn alloc_frame() -> Option<FrameTracker> {
 /// # 返回
 ///
 /// 如果分配成功，返回 `Some(Vec<FrameTracker>)`；否则返回 `None`。
+///
+/// 内存不足时先换出匿名页（见 [`crate::mm::swap`]）再重试一次。
 pub fn alloc_frames(num: usize) -> Option<Vec<FrameTracker>> {
-    FRAME_ALLOCATOR.lock().alloc_frames(num)
+    // 结果向量在锁外分配：堆扩展时会向帧分配器申请内存
+    // 失败时已分配的帧在释放锁之后 drop 回收
+    let mut frames = Vec::with_capacity(num);
+    let ok = FRAME_ALLOCATOR.lock().alloc_frames_into(&mut frames, num);
+    let frames = ok.then_some(frames);
+    if frames.is_some() || crate::mm::swap::reclaim(num.max(crate::mm::swap::SWAP_CLUSTER)) == 0 {
+        return frames;
+    }
+    let mut frames = Vec::with_capacity(num);
+    let ok = FRAME_ALLOCATOR.lock().alloc_frames_into(&mut frames, num);
+    ok.then_some(frames)
 }
 
 /// 分配指定数量的**连续**物理帧。
@@ -71,6 +130,26 @@ pub fn alloc_contig_frames(num: usize) -> Option<FrameRangeTracker> {
     FRAME_ALLOCATOR.lock().alloc_contig_frames(num)
 }
 
+/// 不等待锁地分配指定数量的**连续**物理帧，供内核堆扩展使用。
+///
+/// 内核堆在持有堆锁时扩展；若本 CPU 正持有帧分配器的锁并在其中分配堆内存，
+/// 等待锁会死锁，因此只做有限次尝试，拿不到锁时返回 `None`。
+pub fn try_alloc_contig_frames(num: usize) -> Option<FrameRangeTracker> {
+    const ATTEMPTS: usize = 1 << 16;
+    for _ in 0..ATTEMPTS {
+        if let Some(mut allocator) = FRAME_ALLOCATOR.try_lock() {
+            return allocator.alloc_contig_frames(num);
+        }
+        core::hint::spin_loop();
+    }
+    None
+}
+
+/// 按分配标志分配指定数量的**连续**物理帧。
+pub fn alloc_contig_frames_with(num: usize, flags: AllocFlags) -> Option<FrameRangeTracker> {
+    FRAME_ALLOCATOR.lock().alloc_contig_frames_with(num, flags)
+}
+
 /// 分配指定数量的**连续**物理帧，并确保起始地址对齐。
 ///
 /// # 参数
@@ -87,6 +166,17 @@ pub fn alloc_contig_frames_aligned(num: usize, align_pages: usize) -> Option<Fra
         .alloc_contig_frames_aligned(num, align_pages)
 }
 
+/// 按分配标志分配对齐的**连续**物理帧。
+pub fn alloc_contig_frames_aligned_with(
+    num: usize,
+    align_pages: usize,
+    flags: AllocFlags,
+) -> Option<FrameRangeTracker> {
+    FRAME_ALLOCATOR
+        .lock()
+        .alloc_contig_frames_aligned_with(num, align_pages, flags)
+}
+
 /// 回收一个物理帧。此函数由 FrameTracker 的 Drop 实现调用。
 fn dealloc_frame(frame: &FrameTracker) {
     FRAME_ALLOCATOR.lock().dealloc_frame(frame);
@@ -120,6 +210,11 @@ pub fn get_free_frames() -> usize {
     FRAME_ALLOCATOR.lock().free_frames()
 }
 
+/// 获取区域的总帧数和空闲帧数
+pub fn get_zone_frames(zone: Zone) -> (usize, usize) {
+    FRAME_ALLOCATOR.lock().zone_frames(zone)
+}
+
 /// 获取帧分配器的当前状态
 ///
 /// # 返回值
@@ -132,6 +227,13 @@ pub fn get_stats() -> (usize, usize, usize, usize, usize) {
     FRAME_ALLOCATOR.lock().get_stats()
 }
 
+/// 同 [`get_stats`]，但帧分配器正被占用时不等待，返回 `None`
+pub fn try_get_stats() -> Option<(usize, usize, usize, usize, usize)> {
+    FRAME_ALLOCATOR
+        .try_lock()
+        .map(|allocator| allocator.get_stats())
+}
+
 #[cfg(test)]
 mod frame_allocator_tests {
     use super::*;
@@ -209,4 +311,13 @@ mod frame_allocator_tests {
         let frames = alloc_frames(100).expect("分配 100 帧");
         kassert!(frames.len() == 100);
     });
+
+    // 7. 区域约束分配测试
+    test_case!(test_dma32_alloc, {
+        let frame = alloc_frame_with(AllocFlags::DMA32).expect("分配失败");
+        kassert!(frame.ppn().start_addr().as_usize() < DMA32_LIMIT);
+
+        let frames = alloc_contig_frames_with(4, AllocFlags::DMA32).expect("分配失败");
+        kassert!(frames.end_ppn().start_addr().as_usize() <= DMA32_LIMIT);
+    });
 }
@@ -5,9 +5,15 @@
 //! # 模块组成
 //!
 //! - [`init_heap`]：初始化全局堆分配器。
+//! - [`register_metrics`]：登记堆大小与扩展情况的统计指标。
+//! - [`heap_bytes`]：内核堆当前的总字节数。
+//! - `heap_usage`：内核堆中仍在使用的字节数和块数，只供测试用例的泄漏检查使用。
 
 #[cfg(feature = "alloc")]
 mod talc_alloc;
 
 #[cfg(feature = "alloc")]
-pub use talc_alloc::init_heap;
+pub use talc_alloc::{heap_bytes, init_heap, register_metrics};
+
+#[cfg(all(feature = "alloc", test))]
+pub use talc_alloc::heap_usage;
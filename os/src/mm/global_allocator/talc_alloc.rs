@@ -7,9 +7,120 @@
 //! - 基于 **talc::Talck** 的全局堆分配器。
 //! - 由链接器符号定义的堆内存区域。
 //! - 用于设置堆的初始化函数。
+//! - 初始区域耗尽时从帧分配器申请连续帧扩展堆，总量不超过
+//!   [`KERNEL_HEAP_MAX_SIZE`](crate::config::KERNEL_HEAP_MAX_SIZE)。
+//!   扩展得到的内存不再归还。
+//! - 包装在 talc 外面、统计在用字节数和分配块数的 [`CountingAllocator`]。
 
+use core::alloc::{GlobalAlloc, Layout};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::config::{KERNEL_HEAP_GROW_CHUNK, KERNEL_HEAP_MAX_SIZE, PAGE_SIZE};
+use crate::kernel::kstat::{self, Counter, Gauge, Unit};
+use crate::mm::address::{ConvertablePA, PageNum};
+use crate::mm::frame_allocator::try_alloc_contig_frames;
 use crate::sync::RawSpinLock;
-use talc::{Span, Talc, Talck};
+use talc::{OomHandler, Span, Talc, Talck};
+
+/// 初始堆区域的字节数
+static HEAP_INITIAL: Gauge = Gauge::new();
+/// 从帧分配器扩展的字节数
+static HEAP_GROWN: Counter = Counter::new();
+/// 扩展次数
+static HEAP_GROW_COUNT: Counter = Counter::new();
+/// 因达到上限或没有连续帧而扩展失败的次数
+static HEAP_GROW_FAILED: Counter = Counter::new();
+/// 已分配且尚未释放的字节数（按请求的大小，不含 talc 的块头和对齐）
+static HEAP_USED: AtomicUsize = AtomicUsize::new(0);
+/// 已分配且尚未释放的块数
+static HEAP_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// 登记内核堆的统计指标
+pub fn register_metrics() {
+    kstat::register_probe("kheap_bytes", Unit::Bytes, heap_bytes);
+    kstat::register_gauge("kheap_initial_bytes", Unit::Bytes, &HEAP_INITIAL);
+    kstat::register_counter("kheap_grown_bytes", Unit::Bytes, &HEAP_GROWN);
+    kstat::register_probe("kheap_limit_bytes", Unit::Bytes, || {
+        KERNEL_HEAP_MAX_SIZE as u64
+    });
+    kstat::register_counter("kheap_grow", Unit::Count, &HEAP_GROW_COUNT);
+    kstat::register_counter("kheap_grow_fail", Unit::Count, &HEAP_GROW_FAILED);
+    kstat::register_probe("kheap_used_bytes", Unit::Bytes, || {
+        heap_usage().bytes as u64
+    });
+    kstat::register_probe("kheap_allocations", Unit::Count, || {
+        heap_usage().allocations as u64
+    });
+}
+
+/// 内核堆当前的总字节数（初始区域加上扩展的部分）
+pub fn heap_bytes() -> u64 {
+    HEAP_INITIAL.get() + HEAP_GROWN.get()
+}
+
+/// 内核堆中仍在使用的内存
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeapUsage {
+    /// 尚未释放的字节数
+    pub bytes: usize,
+    /// 尚未释放的块数
+    pub allocations: usize,
+}
+
+/// 读取内核堆当前的使用量
+///
+/// 两个计数分别原子更新，其它 CPU 正在分配时读到的可能不是同一时刻的值。
+pub fn heap_usage() -> HeapUsage {
+    HeapUsage {
+        bytes: HEAP_USED.load(Ordering::Relaxed),
+        allocations: HEAP_ALLOCATIONS.load(Ordering::Relaxed),
+    }
+}
+
+/// talc 内存不足时的处理：从帧分配器申请连续帧并交给 talc 管理
+pub struct GrowFromFrames;
+
+impl OomHandler for GrowFromFrames {
+    fn handle_oom(talc: &mut Talc<Self>, layout: Layout) -> Result<(), ()> {
+        let Some(bytes) = grow_size(layout, (HEAP_INITIAL.get() + HEAP_GROWN.get()) as usize)
+        else {
+            HEAP_GROW_FAILED.inc();
+            return Err(());
+        };
+        let Some(frames) = try_alloc_contig_frames(bytes / PAGE_SIZE) else {
+            HEAP_GROW_FAILED.inc();
+            return Err(());
+        };
+        let start = frames.start_ppn().start_addr().to_va().as_usize();
+        // 堆内存从此由 talc 管理，不再经 RAII 归还帧分配器
+        core::mem::forget(frames);
+
+        // SAFETY: 这段内存刚从帧分配器取得，经直接映射可读写，且不会再被其他人使用
+        unsafe {
+            talc.claim(Span::new(start as *mut u8, (start + bytes) as *mut u8))?;
+        }
+        HEAP_GROWN.add(bytes as u64);
+        HEAP_GROW_COUNT.inc();
+        Ok(())
+    }
+}
+
+/// 为满足 `layout` 一次扩展的字节数（页对齐）；超过上限时返回 `None`
+///
+/// 至少扩展 [`KERNEL_HEAP_GROW_CHUNK`]，并为 talc 的块头和对齐留出余量。
+fn grow_size(layout: Layout, current: usize) -> Option<usize> {
+    let need = layout
+        .size()
+        .checked_add(layout.align())?
+        .checked_add(PAGE_SIZE)?
+        .next_multiple_of(PAGE_SIZE);
+    let bytes = need.max(KERNEL_HEAP_GROW_CHUNK);
+    let room = KERNEL_HEAP_MAX_SIZE.saturating_sub(current) / PAGE_SIZE * PAGE_SIZE;
+    if need > room {
+        return None;
+    }
+    Some(bytes.min(room))
+}
 
 /// 全局堆分配器实例
 ///
@@ -17,10 +128,54 @@ use talc::{Span, Talc, Talck};
 /// 此锁实现了 `lock_api::RawMutex` 并提供了中断保护，
 /// 以防止当中断处理程序尝试分配内存时发生死锁。
 ///
-/// 初始化时使用一个空范围 (**Span::empty()**)；实际内存将在 `init_heap()` 中声明。
+/// 初始内存在 `init_heap()` 中声明，之后不足时由 [`GrowFromFrames`] 扩展。
 #[global_allocator]
-static ALLOCATOR: Talck<RawSpinLock, talc::ClaimOnOom> =
-    Talc::new(unsafe { talc::ClaimOnOom::new(Span::empty()) }).lock();
+static ALLOCATOR: CountingAllocator = CountingAllocator {
+    talc: Talc::new(GrowFromFrames).lock(),
+};
+
+/// 在 talc 之外统计在用字节数和块数的全局分配器
+///
+/// 计数只在分配成功后增加、释放后减少，供 [`heap_usage`] 和测试用例的泄漏检查使用。
+pub struct CountingAllocator {
+    talc: Talck<RawSpinLock, GrowFromFrames>,
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.talc.alloc(layout) };
+        if !ptr.is_null() {
+            HEAP_USED.fetch_add(layout.size(), Ordering::Relaxed);
+            HEAP_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.talc.dealloc(ptr, layout) };
+        HEAP_USED.fetch_sub(layout.size(), Ordering::Relaxed);
+        HEAP_ALLOCATIONS.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.talc.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            HEAP_USED.fetch_add(layout.size(), Ordering::Relaxed);
+            HEAP_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { self.talc.realloc(ptr, layout, new_size) };
+        // 失败时原来的块保持不变
+        if !new_ptr.is_null() {
+            HEAP_USED.fetch_add(new_size, Ordering::Relaxed);
+            HEAP_USED.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+        new_ptr
+    }
+}
 
 /// 使用链接器脚本中定义的堆内存区域初始化堆分配器
 ///
@@ -52,10 +207,61 @@ pub fn init_heap() {
 
     unsafe {
         ALLOCATOR
+            .talc
             .lock()
             .claim(Span::new(heap_start as *mut u8, heap_end as *mut u8))
             .expect("Failed to initialize heap allocator");
     }
+    HEAP_INITIAL.set(heap_size as u64);
 
     crate::println!("Heap allocator initialized successfully");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_heap_grow_size, {
+        let small = Layout::from_size_align(64, 8).unwrap();
+        kassert!(grow_size(small, 0) == Some(KERNEL_HEAP_GROW_CHUNK));
+
+        // 大于块大小的请求留出块头和对齐的余量
+        let big = Layout::from_size_align(KERNEL_HEAP_GROW_CHUNK, PAGE_SIZE).unwrap();
+        let bytes = grow_size(big, 0).unwrap();
+        kassert!(bytes > KERNEL_HEAP_GROW_CHUNK && bytes.is_multiple_of(PAGE_SIZE));
+
+        // 接近上限时只扩展剩余部分，放不下请求时失败
+        let current = KERNEL_HEAP_MAX_SIZE - 3 * PAGE_SIZE;
+        kassert!(grow_size(small, current) == Some(3 * PAGE_SIZE));
+        kassert!(grow_size(big, current).is_none());
+    });
+
+    test_case!(test_heap_grows_from_frames, {
+        // 一次超过初始堆大小的分配必须从帧分配器扩展
+        let before = HEAP_GROWN.get();
+        let size = crate::config::KERNEL_HEAP_SIZE + PAGE_SIZE;
+        let buf = alloc::vec![0xa5u8; size];
+        kassert!(buf.len() == size && buf[size - 1] == 0xa5);
+        kassert!(HEAP_GROWN.get() >= before + size as u64);
+    });
+
+    test_case!(test_heap_usage_counts_live_allocations, {
+        let before = heap_usage();
+        let mut buf = alloc::vec::Vec::<u8>::with_capacity(100);
+        let boxed = alloc::boxed::Box::new(0u64);
+        let during = heap_usage();
+        kassert!(during.allocations >= before.allocations + 2);
+        kassert!(during.bytes >= before.bytes + 108);
+
+        // realloc 只调整字节数；其它 CPU 可能同时在分配，块数只检查没有减少
+        buf.reserve_exact(300);
+        let grown = heap_usage();
+        kassert!(grown.allocations >= during.allocations);
+        kassert!(grown.bytes >= during.bytes + 200);
+
+        drop(buf);
+        drop(boxed);
+        kassert!(heap_usage() == before);
+    });
+}
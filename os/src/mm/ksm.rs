@@ -0,0 +1,333 @@
+//! 同页合并（KSM）
+//!
+//! 后台任务 `ksmd`（运行在异步执行器上）周期性扫描各进程不可写的私有匿名页（包括由 ELF 载入的代码段和
+//! 只读数据段），把内容相同的页合并为同一个物理帧，由各地址空间以只读方式共同映射。
+//!
+//! # 稳定表与不稳定表
+//!
+//! 已合并的帧按内容哈希记录在稳定表中，表中只保存弱引用，最后一个映射解除后帧随之释放。
+//! 本轮扫描中见过、但尚未找到相同内容的候选页记录在不稳定表中；遇到内容相同的第二个页时，
+//! 前者原地转为合并帧（物理页和页表项不变），后者改为映射到它。不稳定表在每轮完整扫描后清空。
+//!
+//! # 写时复制
+//!
+//! 合并帧只存在于不可写的区域中。mprotect 为区域加上写权限、或内核直接改写页内容之前，
+//! 先为该页复制出私有帧，其他映射者不受影响。
+//!
+//! 通过 `/sys/kernel/mm/ksm/run` 开关，默认关闭。
+
+use alloc::{
+    collections::btree_map::BTreeMap,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::time::Duration;
+
+use crate::config::PAGE_SIZE;
+use crate::kernel::executor::{self, AsyncWaitQueue};
+use crate::mm::address::{PageNum, Ppn, UsizeConvert, Vpn};
+use crate::mm::frame_allocator::FrameTracker;
+use crate::mm::memory_space::MemorySpace;
+use crate::sync::SpinLock;
+
+/// 是否运行 `ksmd`
+static RUN: AtomicBool = AtomicBool::new(false);
+
+/// 每次唤醒扫描的页数
+static PAGES_TO_SCAN: AtomicUsize = AtomicUsize::new(100);
+
+/// 两次扫描之间的睡眠时间（毫秒）
+static SLEEP_MILLISECS: AtomicUsize = AtomicUsize::new(20);
+
+/// 完成的完整扫描轮数
+static FULL_SCANS: AtomicUsize = AtomicUsize::new(0);
+
+/// 是否曾经合并过页
+static MERGED_PAGES: AtomicBool = AtomicBool::new(false);
+
+static STATE: SpinLock<KsmState> = SpinLock::new(KsmState {
+    stable: BTreeMap::new(),
+    unstable: BTreeMap::new(),
+    space_cursor: 0,
+    vpn_cursor: Vpn(0),
+});
+
+/// `run` 为 0 时 `ksmd` 在此等待
+static KSMD_WAIT: AsyncWaitQueue = AsyncWaitQueue::new();
+
+/// 不稳定表中的候选页
+struct UnstableItem {
+    space: Weak<SpinLock<MemorySpace>>,
+    vpn: Vpn,
+}
+
+/// 扫描状态
+struct KsmState {
+    /// 合并帧，按内容哈希索引
+    stable: BTreeMap<u64, Vec<Weak<FrameTracker>>>,
+    /// 本轮扫描见过的候选页，按内容哈希索引
+    unstable: BTreeMap<u64, UnstableItem>,
+    /// 下一个扫描的地址空间在列表中的序号
+    space_cursor: usize,
+    /// 下一个扫描的虚拟页
+    vpn_cursor: Vpn,
+}
+
+impl KsmState {
+    /// 转到下一个地址空间
+    fn next_space(&mut self) {
+        self.space_cursor += 1;
+        self.vpn_cursor = Vpn(0);
+    }
+
+    /// 一轮完整扫描结束：清空不稳定表，丢弃已释放的合并帧
+    fn finish_round(&mut self) {
+        self.unstable.clear();
+        self.stable.retain(|_, frames| {
+            frames.retain(|f| f.strong_count() > 0);
+            !frames.is_empty()
+        });
+        self.space_cursor = 0;
+        self.vpn_cursor = Vpn(0);
+        FULL_SCANS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 稳定表中与物理页 `ppn` 内容相同的合并帧
+    fn stable_match(&self, hash: u64, ppn: Ppn) -> Option<Arc<FrameTracker>> {
+        self.stable
+            .get(&hash)?
+            .iter()
+            .filter_map(Weak::upgrade)
+            .find(|f| f.ppn() != ppn && same_content(f.ppn(), ppn))
+    }
+
+    /// 尝试合并 `space` 中映射到 `ppn` 的候选页 `vpn`，返回是否合并
+    fn merge_one(
+        &mut self,
+        space: &mut MemorySpace,
+        space_ref: &Arc<SpinLock<MemorySpace>>,
+        vpn: Vpn,
+        ppn: Ppn,
+    ) -> bool {
+        let hash = page_hash(ppn);
+        if let Some(frame) = self.stable_match(hash, ppn) {
+            return space.ksm_merge(vpn, &frame);
+        }
+
+        let promoted = match self.unstable.remove(&hash) {
+            Some(item) if core::ptr::eq(item.space.as_ptr(), Arc::as_ptr(space_ref)) => {
+                if item.vpn == vpn {
+                    None
+                } else {
+                    space
+                        .ksm_page(item.vpn)
+                        .filter(|other| same_content(*other, ppn))
+                        .and_then(|_| space.ksm_promote(item.vpn))
+                }
+            }
+            // 另一个地址空间被占用时放弃本次合并，下一轮再试
+            Some(item) => item.space.upgrade().and_then(|other| {
+                let mut other = other.try_lock()?;
+                let promoted = other
+                    .ksm_page(item.vpn)
+                    .filter(|other_ppn| same_content(*other_ppn, ppn))
+                    .and_then(|_| other.ksm_promote(item.vpn));
+                promoted
+            }),
+            None => None,
+        };
+
+        let Some(frame) = promoted else {
+            self.unstable.insert(hash, UnstableItem {
+                space: Arc::downgrade(space_ref),
+                vpn,
+            });
+            return false;
+        };
+        MERGED_PAGES.store(true, Ordering::Release);
+        self.stable
+            .entry(hash)
+            .or_default()
+            .push(Arc::downgrade(&frame));
+        space.ksm_merge(vpn, &frame)
+    }
+}
+
+/// 物理页内容（按 8 字节字访问）
+fn page_words(ppn: Ppn) -> &'static [u64] {
+    unsafe {
+        core::slice::from_raw_parts(
+            crate::arch::pa_to_va(ppn.start_addr()).as_usize() as *const u64,
+            PAGE_SIZE / core::mem::size_of::<u64>(),
+        )
+    }
+}
+
+/// 物理页内容的 FNV-1a 哈希
+fn page_hash(ppn: Ppn) -> u64 {
+    page_words(ppn)
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, word| {
+            (hash ^ word).wrapping_mul(0x100_0000_01b3)
+        })
+}
+
+/// 两个物理页的内容是否相同
+fn same_content(a: Ppn, b: Ppn) -> bool {
+    page_words(a) == page_words(b)
+}
+
+/// 是否可能存在合并页
+pub(crate) fn has_merged_pages() -> bool {
+    MERGED_PAGES.load(Ordering::Acquire)
+}
+
+/// 扫描至多 `budget` 个候选页，返回合并的页数
+///
+/// 从上次停下的位置继续；被占用的地址空间会被跳过。
+pub fn scan(budget: usize) -> usize {
+    let spaces = crate::mm::swap::user_spaces(true);
+    let mut state = STATE.lock();
+    let mut scanned = 0;
+    let mut merged = 0;
+    let mut wrapped = false;
+
+    while scanned < budget {
+        if state.space_cursor >= spaces.len() {
+            if wrapped {
+                break;
+            }
+            wrapped = true;
+            state.finish_round();
+            continue;
+        }
+        let space_ref = &spaces[state.space_cursor];
+        let Some(mut space) = space_ref.try_lock() else {
+            state.next_space();
+            continue;
+        };
+        let candidates = space.ksm_candidates(state.vpn_cursor, budget - scanned);
+        if candidates.is_empty() {
+            state.next_space();
+            continue;
+        }
+        for (vpn, ppn) in candidates {
+            scanned += 1;
+            state.vpn_cursor = Vpn::from_usize(vpn.as_usize() + 1);
+            if state.merge_one(&mut space, space_ref, vpn, ppn) {
+                merged += 1;
+            }
+        }
+    }
+
+    if merged > 0 {
+        crate::pr_debug!("[KSM] Merged {} pages", merged);
+    }
+    merged
+}
+
+/// 合并统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KsmStats {
+    /// 仍在使用的合并帧数
+    pub pages_shared: usize,
+    /// 合并帧被额外映射的次数（即节省的页数）
+    pub pages_sharing: usize,
+    /// 本轮扫描中尚未找到相同内容的候选页数
+    pub pages_unshared: usize,
+    /// 完成的完整扫描轮数
+    pub full_scans: usize,
+}
+
+/// 当前的合并统计
+pub fn stats() -> KsmStats {
+    let state = STATE.lock();
+    let mut stats = KsmStats {
+        pages_shared: 0,
+        pages_sharing: 0,
+        pages_unshared: state.unstable.len(),
+        full_scans: FULL_SCANS.load(Ordering::Relaxed),
+    };
+    for frame in state.stable.values().flatten().filter_map(Weak::upgrade) {
+        // 减去此处 upgrade 得到的引用
+        let mappings = Arc::strong_count(&frame) - 1;
+        if mappings > 0 {
+            stats.pages_shared += 1;
+            stats.pages_sharing += mappings - 1;
+        }
+    }
+    stats
+}
+
+/// `ksmd` 是否在运行
+pub fn is_running() -> bool {
+    RUN.load(Ordering::Acquire)
+}
+
+/// 启动或停止 `ksmd`
+pub fn set_running(run: bool) {
+    RUN.store(run, Ordering::Release);
+    if run {
+        KSMD_WAIT.wake_all();
+    }
+}
+
+/// 每次唤醒扫描的页数
+pub fn pages_to_scan() -> usize {
+    PAGES_TO_SCAN.load(Ordering::Relaxed)
+}
+
+/// 设置每次唤醒扫描的页数
+pub fn set_pages_to_scan(pages: usize) {
+    PAGES_TO_SCAN.store(pages, Ordering::Relaxed);
+}
+
+/// 两次扫描之间的睡眠时间（毫秒）
+pub fn sleep_millisecs() -> usize {
+    SLEEP_MILLISECS.load(Ordering::Relaxed)
+}
+
+/// 设置两次扫描之间的睡眠时间（毫秒）
+pub fn set_sleep_millisecs(ms: usize) {
+    SLEEP_MILLISECS.store(ms, Ordering::Relaxed);
+}
+
+/// 同页合并后台任务
+pub async fn ksmd() {
+    loop {
+        KSMD_WAIT.wait_until(is_running).await;
+        scan(pages_to_scan());
+        executor::sleep(Duration::from_millis(sleep_millisecs() as u64)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mm::frame_allocator::alloc_frame;
+    use crate::{kassert, test_case};
+
+    fn fill(frame: &FrameTracker, byte: u8) {
+        unsafe {
+            core::ptr::write_bytes(
+                crate::arch::pa_to_va(frame.ppn().start_addr()).as_usize() as *mut u8,
+                byte,
+                PAGE_SIZE,
+            );
+        }
+    }
+
+    test_case!(test_ksm_page_compare, {
+        let a = alloc_frame().expect("alloc frame failed");
+        let b = alloc_frame().expect("alloc frame failed");
+        fill(&a, 0x5a);
+        fill(&b, 0x5a);
+        kassert!(page_hash(a.ppn()) == page_hash(b.ppn()));
+        kassert!(same_content(a.ppn(), b.ppn()));
+
+        fill(&b, 0xa5);
+        kassert!(page_hash(a.ppn()) != page_hash(b.ppn()));
+        kassert!(!same_content(a.ppn(), b.ppn()));
+    });
+}
@@ -0,0 +1,480 @@
+//! 启动时的物理内存布局
+//!
+//! 在帧分配器之前从设备树建立物理内存布局：
+//! - `device_type = "memory"` 节点给出 RAM 区域；
+//! - `/memreserve/` 表、`/reserved-memory` 子节点、内核映像、DTB 本身以及
+//!   `/chosen` 中的 initramfs 范围记为保留区域。
+//!
+//! 此时堆尚未初始化，区域保存在定长数组中。设备树没有报告内存时退回到
+//! 平台的编译期 `MEMORY_END`。
+
+use crate::arch::platform::MEMORY_END;
+use crate::config::PAGE_SIZE;
+use crate::device::device_tree::{DTP, FDT};
+use crate::mm::address::VA;
+use crate::sync::SpinLock;
+use core::fmt;
+use core::sync::atomic::Ordering;
+
+/// 最多记录的 RAM 区域数
+const MAX_MEMORY_REGIONS: usize = 8;
+/// 最多记录的保留区域数
+const MAX_RESERVED_REGIONS: usize = 16;
+
+/// LoongArch 经 DMW 窗口访问物理内存，不需要逐页映射；virt 机器报告的 RAM 窗口
+/// 很大，用 4K 页表映射既慢又无意义，把内核管理的范围限制在内核之后 1GiB。
+#[cfg(target_arch = "loongarch64")]
+const MAX_KERNEL_WINDOW_BYTES: usize = 1024 * 1024 * 1024;
+
+/// 物理地址范围 `[start, end)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PhysRange {
+    /// 起始物理地址
+    pub start: usize,
+    /// 结束物理地址（不包含）
+    pub end: usize,
+}
+
+impl PhysRange {
+    /// 创建范围，`end` 小于 `start` 时为空
+    pub const fn new(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end: if end < start { start } else { end },
+        }
+    }
+
+    /// 范围是否为空
+    pub const fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    /// 范围的字节数
+    pub const fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// 两个范围的交集
+    pub fn intersect(&self, other: &PhysRange) -> PhysRange {
+        PhysRange::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// 向内按页对齐
+    pub fn page_align_inward(&self) -> PhysRange {
+        PhysRange::new(
+            self.start.div_ceil(PAGE_SIZE) * PAGE_SIZE,
+            self.end / PAGE_SIZE * PAGE_SIZE,
+        )
+    }
+}
+
+impl fmt::Display for PhysRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:#012x} - {:#012x})", self.start, self.end)
+    }
+}
+
+/// 保留区域的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReserveKind {
+    /// 内核映像（`skernel..ekernel`）
+    Kernel,
+    /// 设备树本身
+    Dtb,
+    /// `/chosen` 中 `linux,initrd-start/end` 给出的 initramfs
+    Initramfs,
+    /// 设备树头部的 `/memreserve/` 表
+    MemReserve,
+    /// `/reserved-memory` 的子节点（如固件占用的内存）
+    ReservedMemory,
+}
+
+impl ReserveKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReserveKind::Kernel => "kernel",
+            ReserveKind::Dtb => "dtb",
+            ReserveKind::Initramfs => "initramfs",
+            ReserveKind::MemReserve => "memreserve",
+            ReserveKind::ReservedMemory => "reserved-memory",
+        }
+    }
+}
+
+/// 物理内存布局：RAM 区域与保留区域，均按起始地址排序
+#[derive(Clone, Copy)]
+pub struct MemoryMap {
+    memory: [PhysRange; MAX_MEMORY_REGIONS],
+    nr_memory: usize,
+    reserved: [(PhysRange, ReserveKind); MAX_RESERVED_REGIONS],
+    nr_reserved: usize,
+}
+
+impl MemoryMap {
+    /// 创建空的内存布局
+    pub const fn new() -> Self {
+        Self {
+            memory: [PhysRange::new(0, 0); MAX_MEMORY_REGIONS],
+            nr_memory: 0,
+            reserved: [(PhysRange::new(0, 0), ReserveKind::Kernel); MAX_RESERVED_REGIONS],
+            nr_reserved: 0,
+        }
+    }
+
+    /// RAM 区域
+    pub fn memory(&self) -> &[PhysRange] {
+        &self.memory[..self.nr_memory]
+    }
+
+    /// 保留区域
+    pub fn reserved(&self) -> &[(PhysRange, ReserveKind)] {
+        &self.reserved[..self.nr_reserved]
+    }
+
+    /// 加入一个 RAM 区域，与已有区域重叠或相邻时合并
+    ///
+    /// 数组已满时丢弃并返回 `false`。
+    pub fn add_memory(&mut self, range: PhysRange) -> bool {
+        if range.is_empty() {
+            return true;
+        }
+        let mut merged = range;
+        let mut kept = 0;
+        for i in 0..self.nr_memory {
+            let region = self.memory[i];
+            if region.start <= merged.end && merged.start <= region.end {
+                merged = PhysRange::new(merged.start.min(region.start), merged.end.max(region.end));
+            } else {
+                self.memory[kept] = region;
+                kept += 1;
+            }
+        }
+        if kept == MAX_MEMORY_REGIONS {
+            return false;
+        }
+        let pos = self.memory[..kept]
+            .iter()
+            .position(|r| r.start > merged.start)
+            .unwrap_or(kept);
+        self.memory.copy_within(pos..kept, pos + 1);
+        self.memory[pos] = merged;
+        self.nr_memory = kept + 1;
+        true
+    }
+
+    /// 加入一个保留区域
+    ///
+    /// 保留区域之间允许重叠。数组已满时丢弃并返回 `false`。
+    pub fn reserve(&mut self, range: PhysRange, kind: ReserveKind) -> bool {
+        if range.is_empty() {
+            return true;
+        }
+        if self.nr_reserved == MAX_RESERVED_REGIONS {
+            return false;
+        }
+        let n = self.nr_reserved;
+        let pos = self.reserved[..n]
+            .iter()
+            .position(|(r, _)| r.start > range.start)
+            .unwrap_or(n);
+        self.reserved.copy_within(pos..n, pos + 1);
+        self.reserved[pos] = (range, kind);
+        self.nr_reserved = n + 1;
+        true
+    }
+
+    /// 包含 `addr` 的 RAM 区域
+    pub fn region_containing(&self, addr: usize) -> Option<PhysRange> {
+        self.memory()
+            .iter()
+            .find(|r| r.start <= addr && addr < r.end)
+            .copied()
+    }
+
+    /// 对 `window` 内去掉保留区域后的每段可用内存调用 `f`，按地址递增
+    pub fn for_each_usable(&self, window: PhysRange, mut f: impl FnMut(PhysRange)) {
+        for region in self.memory() {
+            let region = region.intersect(&window);
+            let mut cursor = region.start;
+            for (reserved, _) in self.reserved() {
+                let reserved = reserved.intersect(&region);
+                if reserved.is_empty() {
+                    continue;
+                }
+                if reserved.start > cursor {
+                    f(PhysRange::new(cursor, reserved.start));
+                }
+                cursor = cursor.max(reserved.end);
+            }
+            if cursor < region.end {
+                f(PhysRange::new(cursor, region.end));
+            }
+        }
+    }
+}
+
+impl Default for MemoryMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static MEMORY_MAP: SpinLock<MemoryMap> = SpinLock::new(MemoryMap::new());
+
+unsafe extern "C" {
+    fn skernel();
+    fn ekernel();
+}
+
+/// 内核映像的物理地址范围
+fn kernel_image() -> PhysRange {
+    // SAFETY: skernel/ekernel 是链接脚本中的内核虚拟地址
+    let (start, end) = unsafe {
+        (
+            crate::arch::va_to_pa(VA::from_usize(skernel as usize)),
+            crate::arch::va_to_pa(VA::from_usize(ekernel as usize)),
+        )
+    };
+    PhysRange::new(start.as_usize(), end.as_usize())
+}
+
+/// 从设备树建立内存布局并记录为全局布局
+///
+/// 必须在帧分配器初始化之前调用，期间不使用堆。
+pub fn init() -> MemoryMap {
+    let mut map = MemoryMap::new();
+    let kernel = kernel_image();
+
+    for node in FDT.all_nodes() {
+        let is_memory = node
+            .property("device_type")
+            .and_then(|p| p.as_str())
+            .is_some_and(|t| t == "memory");
+        if !is_memory {
+            continue;
+        }
+        for region in node.reg().into_iter().flatten() {
+            let start = region.starting_address as usize;
+            let range = PhysRange::new(start, start.saturating_add(region.size.unwrap_or(0)));
+            if !map.add_memory(range) {
+                crate::println!("[MM] too many memory regions, ignoring {}", range);
+            }
+        }
+    }
+    if map.memory().is_empty() {
+        crate::println!(
+            "[MM] no memory node in device tree, using MEMORY_END {:#x}",
+            MEMORY_END
+        );
+        map.add_memory(PhysRange::new(kernel.start, MEMORY_END));
+    }
+
+    let mut reserve = |range: PhysRange, kind: ReserveKind| {
+        if !map.reserve(range, kind) {
+            crate::println!("[MM] too many reserved regions, ignoring {}", range);
+        }
+    };
+    reserve(kernel, ReserveKind::Kernel);
+    let dtb = DTP.load(Ordering::Acquire);
+    reserve(
+        PhysRange::new(dtb, dtb.saturating_add(FDT.total_size())),
+        ReserveKind::Dtb,
+    );
+    for entry in FDT.memory_reservations() {
+        let start = entry.address() as usize;
+        reserve(
+            PhysRange::new(start, start.saturating_add(entry.size())),
+            ReserveKind::MemReserve,
+        );
+    }
+    if let Some(reserved_memory) = FDT.find_node("/reserved-memory") {
+        for child in reserved_memory.children() {
+            for region in child.reg().into_iter().flatten() {
+                let start = region.starting_address as usize;
+                reserve(
+                    PhysRange::new(start, start.saturating_add(region.size.unwrap_or(0))),
+                    ReserveKind::ReservedMemory,
+                );
+            }
+        }
+    }
+    if let Some(initrd) = initrd_range() {
+        reserve(initrd, ReserveKind::Initramfs);
+    }
+
+    *MEMORY_MAP.lock() = map;
+    map
+}
+
+/// `/chosen` 中 `linux,initrd-start` 与 `linux,initrd-end` 给出的范围
+fn initrd_range() -> Option<PhysRange> {
+    let chosen = FDT.find_node("/chosen")?;
+    let start = chosen.property("linux,initrd-start")?.as_usize()?;
+    let end = chosen.property("linux,initrd-end")?.as_usize()?;
+    Some(PhysRange::new(start, end)).filter(|r| !r.is_empty())
+}
+
+/// 全局内存布局
+pub fn memory_map() -> MemoryMap {
+    *MEMORY_MAP.lock()
+}
+
+/// 内核直接映射并交给帧分配器管理的物理范围，按地址递增、页对齐
+#[derive(Debug, Clone, Copy)]
+pub struct KernelWindows {
+    ranges: [PhysRange; MAX_MEMORY_REGIONS],
+    len: usize,
+}
+
+impl KernelWindows {
+    /// 各个范围
+    pub fn as_slice(&self) -> &[PhysRange] {
+        &self.ranges[..self.len]
+    }
+
+    /// `addr` 是否落在某个范围内
+    pub fn contains(&self, addr: usize) -> bool {
+        self.as_slice()
+            .iter()
+            .any(|r| r.start <= addr && addr < r.end)
+    }
+
+    /// 所有范围的总字节数
+    pub fn total_bytes(&self) -> usize {
+        self.as_slice().iter().map(PhysRange::len).sum()
+    }
+}
+
+/// 由内存布局计算内核管理的范围
+///
+/// 内核所在的 RAM 区域从内核映像末尾开始（之前是固件和引导程序），其余区域整体使用。
+/// 按地址顺序累计，总量不超过 `budget` 字节。
+fn compute_kernel_windows(map: &MemoryMap, kernel: PhysRange, budget: usize) -> KernelWindows {
+    let mut windows = KernelWindows {
+        ranges: [PhysRange::new(0, 0); MAX_MEMORY_REGIONS],
+        len: 0,
+    };
+    let mut remaining = budget;
+    for region in map.memory() {
+        let start = if region.start <= kernel.start && kernel.start < region.end {
+            kernel.end
+        } else {
+            region.start
+        };
+        let range = PhysRange::new(start, region.end).page_align_inward();
+        let range = PhysRange::new(range.start, range.start + range.len().min(remaining));
+        if range.is_empty() {
+            continue;
+        }
+        remaining -= range.len();
+        windows.ranges[windows.len] = range;
+        windows.len += 1;
+    }
+    windows
+}
+
+/// 内核直接映射并交给帧分配器管理的物理范围
+///
+/// LoongArch 另有总量上限 `MAX_KERNEL_WINDOW_BYTES`。
+pub fn kernel_windows() -> KernelWindows {
+    #[cfg(target_arch = "loongarch64")]
+    let budget = MAX_KERNEL_WINDOW_BYTES;
+    #[cfg(not(target_arch = "loongarch64"))]
+    let budget = usize::MAX;
+    compute_kernel_windows(&MEMORY_MAP.lock(), kernel_image(), budget)
+}
+
+/// 打印启动时的内存布局摘要
+pub fn print_summary(map: &MemoryMap, windows: &KernelWindows) {
+    crate::println!("[MM] Physical memory map:");
+    for region in map.memory() {
+        crate::println!("[MM]   memory   {} {} MiB", region, region.len() >> 20);
+    }
+    for (region, kind) in map.reserved() {
+        crate::println!("[MM]   reserved {} {}", region, kind.as_str());
+    }
+    let mut usable = 0;
+    for window in windows.as_slice() {
+        map.for_each_usable(*window, |range| {
+            let range = range.page_align_inward();
+            usable += range.len() / PAGE_SIZE;
+            crate::println!("[MM]   usable   {}", range);
+        });
+    }
+    crate::println!(
+        "[MM] {} usable frames ({} MiB) in {} region(s)",
+        usable,
+        (usable * PAGE_SIZE) >> 20,
+        windows.as_slice().len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_memory_map_usable, {
+        let mut map = MemoryMap::new();
+        kassert!(map.add_memory(PhysRange::new(0x8000_0000, 0x8800_0000)));
+        // 重叠与相邻的区域合并
+        kassert!(map.add_memory(PhysRange::new(0x8800_0000, 0x9000_0000)));
+        kassert!(map.add_memory(PhysRange::new(0x1000_0000, 0x2000_0000)));
+        kassert!(
+            map.memory()
+                == [
+                    PhysRange::new(0x1000_0000, 0x2000_0000),
+                    PhysRange::new(0x8000_0000, 0x9000_0000)
+                ]
+        );
+
+        map.reserve(PhysRange::new(0x8f00_0000, 0x9000_0000), ReserveKind::Dtb);
+        map.reserve(
+            PhysRange::new(0x8020_0000, 0x8100_0000),
+            ReserveKind::Kernel,
+        );
+        map.reserve(
+            PhysRange::new(0x8080_0000, 0x8200_0000),
+            ReserveKind::Initramfs,
+        );
+        let mut usable = [PhysRange::default(); 4];
+        let mut n = 0;
+        map.for_each_usable(PhysRange::new(0x8000_0000, usize::MAX), |r| {
+            usable[n] = r;
+            n += 1;
+        });
+        kassert!(n == 2);
+        kassert!(usable[0] == PhysRange::new(0x8000_0000, 0x8020_0000));
+        kassert!(usable[1] == PhysRange::new(0x8200_0000, 0x8f00_0000));
+        kassert!(
+            map.region_containing(0x1800_0000) == Some(PhysRange::new(0x1000_0000, 0x2000_0000))
+        );
+        kassert!(map.region_containing(0x3000_0000).is_none());
+    });
+
+    test_case!(test_kernel_windows, {
+        // 类似 LoongArch virt：低端 256MiB 含内核，高端另有一段
+        let mut map = MemoryMap::new();
+        map.add_memory(PhysRange::new(0, 0x1000_0000));
+        map.add_memory(PhysRange::new(0x9000_0000, 0xb000_0000));
+        let kernel = PhysRange::new(0x20_0000, 0x0123_4567);
+
+        let windows = compute_kernel_windows(&map, kernel, usize::MAX);
+        kassert!(
+            windows.as_slice()
+                == [
+                    PhysRange::new(0x0123_5000, 0x1000_0000),
+                    PhysRange::new(0x9000_0000, 0xb000_0000)
+                ]
+        );
+        kassert!(windows.contains(0x9000_0000));
+        kassert!(!windows.contains(0x1000_0000));
+        kassert!(!windows.contains(0x20_0000));
+
+        // 总量上限按地址顺序分配
+        let budget = 0x1000_0000 - 0x0123_5000 + 0x100_0000;
+        let windows = compute_kernel_windows(&map, kernel, budget);
+        kassert!(windows.as_slice()[1] == PhysRange::new(0x9000_0000, 0x9100_0000));
+        kassert!(windows.total_bytes() == budget);
+    });
+}
@@ -0,0 +1,181 @@
+//! 用户地址空间布局
+//!
+//! 用户 [`MemorySpace`](super::MemorySpace) 装入程序映像后确定一份布局，从低到高依次为：
+//!
+//! ```text
+//! mmap_min_addr
+//! [text_base, heap_base)      程序映像
+//! [heap_base, heap_limit)     brk 堆，最多 MAX_USER_HEAP_SIZE
+//! guard_gap
+//! [mmap_floor, mmap_base)     mmap 区，自顶向下分配
+//! USER_STACK_GUARD_GAP
+//! [stack_limit, stack_top)    用户栈
+//! ```
+//!
+//! 内核选择的 mmap 地址与程序映像、堆和栈之间至少隔开 `guard_gap`，mmap 区耗尽时
+//! 才退回到当前堆顶之上查找；相邻的 mmap 映射之间不留间隔。
+//!
+//! 低于 `/proc/sys/vm/mmap_min_addr` 的地址不能通过 mmap 映射：固定映射返回 EPERM，
+//! 普通 hint 被抬高到该值。这样用户态无法在零页附近布置数据，
+//! 内核里的空指针解引用就不会读到用户控制的内容。
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::config::{
+    DEFAULT_MMAP_MIN_ADDR, MAX_USER_HEAP_SIZE, PAGE_SIZE, USER_GUARD_GAP, USER_STACK_GUARD_GAP,
+    USER_STACK_SIZE, USER_STACK_TOP,
+};
+use crate::util::address::align_down;
+
+static MMAP_MIN_ADDR: AtomicUsize = AtomicUsize::new(DEFAULT_MMAP_MIN_ADDR);
+
+/// 用户 mmap 可使用的最低地址
+pub fn mmap_min_addr() -> usize {
+    MMAP_MIN_ADDR.load(Ordering::Relaxed)
+}
+
+/// 设置用户 mmap 可使用的最低地址，向上取整到页
+pub fn set_mmap_min_addr(addr: usize) {
+    let addr = addr
+        .checked_next_multiple_of(PAGE_SIZE)
+        .unwrap_or(align_down(usize::MAX, PAGE_SIZE));
+    MMAP_MIN_ADDR.store(addr, Ordering::Relaxed);
+}
+
+/// 用户地址空间各区域的基址与上限，地址均按页对齐
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserLayout {
+    /// 程序映像的最低地址
+    pub text_base: usize,
+    /// 堆的起点（brk 的下界），即程序映像的结束地址
+    pub heap_base: usize,
+    /// 堆可增长到的最高地址（不含）
+    pub heap_limit: usize,
+    /// mmap 区的最高地址（不含），自顶向下分配从这里开始
+    pub mmap_base: usize,
+    /// 用户栈的最低地址
+    pub stack_limit: usize,
+    /// 用户栈顶
+    pub stack_top: usize,
+    /// 内核选择的 mmap 地址与关键区域之间保留的间隔
+    pub guard_gap: usize,
+}
+
+impl UserLayout {
+    /// 按程序映像的范围 `[text_base, image_end)` 确定布局
+    pub fn new(text_base: usize, image_end: usize) -> Self {
+        let heap_base = image_end.next_multiple_of(PAGE_SIZE);
+        let stack_limit = align_down(USER_STACK_TOP - USER_STACK_SIZE, PAGE_SIZE);
+        Self {
+            text_base: align_down(text_base.min(heap_base), PAGE_SIZE),
+            heap_base,
+            heap_limit: heap_base
+                .saturating_add(MAX_USER_HEAP_SIZE)
+                .min(stack_limit),
+            mmap_base: stack_limit.saturating_sub(USER_STACK_GUARD_GAP),
+            stack_limit,
+            stack_top: USER_STACK_TOP,
+            guard_gap: USER_GUARD_GAP,
+        }
+    }
+
+    /// mmap 区的最低地址：堆上限之上再留一个间隔
+    pub fn mmap_floor(&self) -> usize {
+        self.heap_limit
+            .saturating_add(self.guard_gap)
+            .max(mmap_min_addr())
+            .min(self.mmap_base)
+    }
+}
+
+/// 已占用的地址区间 `[start, end)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Occupied {
+    pub start: usize,
+    pub end: usize,
+    /// 新映射与该区间两侧至少相隔的字节数
+    pub gap: usize,
+}
+
+/// 在 `[floor, ceiling)` 内自顶向下查找能放下 `size` 字节的最高 `align` 对齐地址
+///
+/// `occupied` 须按起始地址排序且互不重叠；找到的区间与每个已占用区间之间
+/// 至少隔开该区间的 `gap`。
+pub(super) fn find_top_down(
+    occupied: &[Occupied],
+    floor: usize,
+    ceiling: usize,
+    size: usize,
+    align: usize,
+) -> Option<usize> {
+    let fit = |lower: usize, upper: usize| {
+        let end = lower.checked_add(size)?;
+        if end > upper {
+            return None;
+        }
+        let candidate = align_down(upper - size, align);
+        (candidate >= lower).then_some(candidate)
+    };
+
+    let mut upper = ceiling;
+    for area in occupied.iter().rev() {
+        let lower = area.end.saturating_add(area.gap).max(floor);
+        if let Some(addr) = fit(lower, upper) {
+            return Some(addr);
+        }
+        upper = upper.min(area.start.saturating_sub(area.gap));
+        if upper <= floor {
+            return None;
+        }
+    }
+    fit(floor, upper)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    fn occupied(start: usize, end: usize, gap: usize) -> Occupied {
+        Occupied { start, end, gap }
+    }
+
+    test_case!(test_user_layout_regions, {
+        let layout = UserLayout::new(0x10000, 0x12345);
+        kassert!(layout.heap_base == 0x13000);
+        kassert!(layout.heap_limit == 0x13000 + MAX_USER_HEAP_SIZE);
+        kassert!(layout.mmap_floor() == layout.heap_limit + layout.guard_gap);
+        kassert!(layout.mmap_base + USER_STACK_GUARD_GAP == layout.stack_limit);
+        kassert!(layout.stack_limit < layout.stack_top);
+    });
+
+    test_case!(test_find_top_down, {
+        let page = PAGE_SIZE;
+        // 空地址空间：紧贴上限
+        kassert!(find_top_down(&[], 0, 16 * page, 2 * page, page) == Some(14 * page));
+
+        // 相邻的 mmap 映射之间不留间隔
+        let areas = [occupied(12 * page, 16 * page, 0)];
+        kassert!(find_top_down(&areas, 0, 16 * page, 2 * page, page) == Some(10 * page));
+
+        // 与关键区域两侧各隔一个间隔
+        let areas = [
+            occupied(4 * page, 6 * page, page),
+            occupied(10 * page, 12 * page, page),
+        ];
+        kassert!(find_top_down(&areas, 0, 12 * page, 3 * page, page) == Some(0));
+        kassert!(find_top_down(&areas, 0, 12 * page, 2 * page, page) == Some(7 * page));
+        kassert!(find_top_down(&areas, page, 12 * page, 3 * page, page).is_none());
+
+        // 对齐向下取整
+        kassert!(find_top_down(&[], 0, 16 * page, page, 4 * page) == Some(12 * page));
+        kassert!(find_top_down(&[], 0, page, 2 * page, page).is_none());
+    });
+
+    test_case!(test_mmap_min_addr, {
+        let saved = mmap_min_addr();
+        set_mmap_min_addr(1);
+        kassert!(mmap_min_addr() == PAGE_SIZE);
+        set_mmap_min_addr(saved);
+    });
+}
@@ -23,6 +23,7 @@ impl MappingArea {
                 let ppn = match tracked_frame {
                     TrackedFrames::Single(frame) => frame.ppn(),
                     TrackedFrames::Multiple(frames) => frames.first().map(|f| f.ppn()).unwrap(),
+                    TrackedFrames::Merged(frame) => frame.ppn(),
                 };
 
                 let paddr = ppn.start_addr();
@@ -58,6 +59,14 @@ impl MappingArea {
 
                 // buffer[actual_read..] 保持为零（新分配的物理帧默认清零）
             }
+
+            // 可执行映射（如动态库的代码段）读入后同步指令缓存
+            if self.permission.contains(UniversalPTEFlag::EXECUTABLE) {
+                crate::arch::sync_icache_range(
+                    self.vpn_range.start().start_addr(),
+                    self.vpn_range.len() * PAGE_SIZE,
+                );
+            }
         }
         Ok(())
     }
@@ -108,6 +117,7 @@ impl MappingArea {
                     let ppn = match tracked_frame {
                         TrackedFrames::Single(frame) => frame.ppn(),
                         TrackedFrames::Multiple(frames) => frames.first().map(|f| f.ppn()).unwrap(),
+                        TrackedFrames::Merged(frame) => frame.ppn(),
                     };
 
                     let paddr = ppn.start_addr();
@@ -0,0 +1,122 @@
+use super::*;
+
+/// 同页合并
+impl MappingArea {
+    /// 区域中的页是否可以参与同页合并
+    ///
+    /// 只合并不可写的私有匿名页（包括由 ELF 载入的代码段和只读数据段）。
+    pub fn is_mergeable(&self) -> bool {
+        self.map_type == MapType::Framed
+            && self.file.is_none()
+            && self.shared.is_none()
+            && !self.permission.contains(UniversalPTEFlag::WRITEABLE)
+            && self
+                .permission
+                .intersects(UniversalPTEFlag::READABLE | UniversalPTEFlag::EXECUTABLE)
+            && matches!(
+                self.area_type,
+                AreaType::UserText
+                    | AreaType::UserRodata
+                    | AreaType::UserData
+                    | AreaType::UserBss
+                    | AreaType::UserHeap
+                    | AreaType::UserMmap
+            )
+    }
+
+    /// 从 `from` 开始尚未合并的候选页
+    pub(crate) fn ksm_candidates(&self, from: Vpn) -> impl Iterator<Item = (Vpn, Ppn)> + '_ {
+        self.frames
+            .range(from..)
+            .filter_map(|(vpn, tracked)| match tracked {
+                TrackedFrames::Single(frame) => Some((*vpn, frame.ppn())),
+                _ => None,
+            })
+    }
+
+    /// 把 `vpn` 的私有帧转为合并帧（物理页和页表项不变），返回该帧
+    pub(crate) fn ksm_promote(&mut self, vpn: Vpn) -> Option<Arc<FrameTracker>> {
+        let tracked = self.frames.remove(&vpn)?;
+        let TrackedFrames::Single(frame) = tracked else {
+            self.frames.insert(vpn, tracked);
+            return None;
+        };
+        let frame = Arc::new(frame);
+        self.frames
+            .insert(vpn, TrackedFrames::Merged(frame.clone()));
+        Some(frame)
+    }
+
+    /// 把 `vpn` 重新映射到合并帧 `frame` 并释放原来的私有帧
+    pub(crate) fn ksm_merge(
+        &mut self,
+        page_table: &mut ActivePageTableInner,
+        vpn: Vpn,
+        frame: &Arc<FrameTracker>,
+    ) -> Result<(), page_table::PagingError> {
+        let Some(TrackedFrames::Single(old)) = self.frames.get(&vpn) else {
+            return Err(page_table::PagingError::NotMapped);
+        };
+        let old_ppn = old.ppn();
+        self.remap_frame(page_table, vpn, old_ppn, frame.ppn())?;
+        self.frames
+            .insert(vpn, TrackedFrames::Merged(frame.clone()));
+        Ok(())
+    }
+
+    /// 为合并页 `vpn` 复制出私有帧（写时复制），其他映射者不受影响
+    pub(crate) fn ksm_unmerge(
+        &mut self,
+        page_table: &mut ActivePageTableInner,
+        vpn: Vpn,
+    ) -> Result<(), page_table::PagingError> {
+        let Some(TrackedFrames::Merged(merged)) = self.frames.get(&vpn) else {
+            return Ok(());
+        };
+        let merged_ppn = merged.ppn();
+        let frame = alloc_frame().ok_or(page_table::PagingError::FrameAllocFailed)?;
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                crate::arch::pa_to_va(merged_ppn.start_addr()).as_usize() as *const u8,
+                crate::arch::pa_to_va(frame.ppn().start_addr()).as_usize() as *mut u8,
+                PAGE_SIZE,
+            );
+        }
+        self.remap_frame(page_table, vpn, merged_ppn, frame.ppn())?;
+        self.frames.insert(vpn, TrackedFrames::Single(frame));
+        Ok(())
+    }
+
+    /// 为 `range` 内的所有合并页复制出私有帧
+    pub(crate) fn ksm_unmerge_range(
+        &mut self,
+        page_table: &mut ActivePageTableInner,
+        range: VpnRange,
+    ) -> Result<(), page_table::PagingError> {
+        let merged: Vec<Vpn> = self
+            .frames
+            .range(range.start()..range.end())
+            .filter(|(_, tracked)| matches!(tracked, TrackedFrames::Merged(_)))
+            .map(|(vpn, _)| *vpn)
+            .collect();
+        for vpn in merged {
+            self.ksm_unmerge(page_table, vpn)?;
+        }
+        Ok(())
+    }
+
+    /// 把 `vpn` 的页表项从 `old_ppn` 改为 `new_ppn`，同时更新反向映射
+    fn remap_frame(
+        &self,
+        page_table: &mut ActivePageTableInner,
+        vpn: Vpn,
+        old_ppn: Ppn,
+        new_ppn: Ppn,
+    ) -> Result<(), page_table::PagingError> {
+        page_table.unmap_with_batch(vpn, None)?;
+        self.rmap_remove(page_table, vpn, old_ppn);
+        page_table.map_with_batch(vpn, new_ppn, PageSize::Size4K, self.permission, None)?;
+        self.rmap_add(page_table, vpn, new_ppn);
+        Ok(())
+    }
+}
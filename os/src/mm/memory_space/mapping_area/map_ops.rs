@@ -13,6 +13,16 @@ impl MappingArea {
         self.permission = perm;
     }
 
+    /// mprotect 可以授予的最大访问权限
+    pub fn max_permission(&self) -> UniversalPTEFlag {
+        self.max_permission
+    }
+
+    /// 限制 mprotect 可以授予的访问权限（只保留 R/W/X 位）
+    pub fn set_max_permission(&mut self, max: UniversalPTEFlag) {
+        self.max_permission = max & USER_PROT_MASK;
+    }
+
     pub fn map_type(&self) -> MapType {
         self.map_type
     }
@@ -21,6 +31,16 @@ impl MappingArea {
         self.area_type
     }
 
+    /// 文件映射的后备文件，匿名映射返回 `None`
+    pub fn mmap_file(&self) -> Option<&MmapFile> {
+        self.file.as_ref()
+    }
+
+    /// 是否映射到共享物理页（`MAP_SHARED` 匿名映射、共享文件页、SysV shm 等）
+    pub fn is_shared(&self) -> bool {
+        self.shared.is_some()
+    }
+
     /// 已实际映射的页数（仅对 Framed 有意义）
     ///
     /// 注意：Range/VPN/PPN 语义均为左闭右开。
@@ -30,7 +50,7 @@ impl MappingArea {
                 .frames
                 .values()
                 .map(|t| match t {
-                    TrackedFrames::Single(_) => 1,
+                    TrackedFrames::Single(_) | TrackedFrames::Merged(_) => 1,
                     TrackedFrames::Multiple(v) => v.len(),
                 })
                 .sum(),
@@ -44,6 +64,7 @@ impl MappingArea {
         self.frames.get(&vpn).map(|tracked| match tracked {
             TrackedFrames::Single(frame) => frame.ppn(),
             TrackedFrames::Multiple(frames) => frames.first().map(|f| f.ppn()).unwrap(),
+            TrackedFrames::Merged(frame) => frame.ppn(),
         })
     }
 
@@ -59,6 +80,7 @@ impl MappingArea {
             area_type,
             map_type,
             permission,
+            max_permission: USER_PROT_MASK,
             frames: BTreeMap::new(),
             file,
             shared: None,
@@ -69,17 +91,19 @@ impl MappingArea {
     pub fn new_shared(
         vpn_range: VpnRange,
         permission: UniversalPTEFlag,
-        segment: Arc<ShmSegment>,
+        segment: Arc<dyn SharedPages>,
+        page_offset: usize,
     ) -> Self {
         MappingArea {
             vpn_range,
             area_type: AreaType::UserMmap,
             map_type: MapType::Shared,
             permission,
+            max_permission: USER_PROT_MASK,
             frames: BTreeMap::new(),
             file: None,
             shared: Some(segment),
-            shared_page_offset: 0,
+            shared_page_offset: page_offset,
         }
     }
 
@@ -131,6 +155,7 @@ impl MappingArea {
         };
 
         page_table.map_with_batch(vpn, ppn, PageSize::Size4K, self.permission, batch)?;
+        self.rmap_add(page_table, vpn, ppn);
         Ok(())
     }
 
@@ -166,7 +191,29 @@ impl MappingArea {
         if self.map_type == MapType::Reserved {
             return Ok(());
         }
+        // 被换出的页只需释放交换槽
+        if self.map_type == MapType::Framed
+            && crate::mm::swap::has_swapped_pages()
+            && let Some(slot) = page_table.swap_entry(vpn)
+        {
+            page_table.clear_swap_entry(vpn)?;
+            crate::mm::swap::free_slot(slot);
+            return Ok(());
+        }
+        let ppn = match self.map_type {
+            MapType::Framed => self.get_ppn(vpn),
+            MapType::Shared => match page_table.walk(vpn) {
+                Ok((ppn, _, _)) => Some(ppn),
+                // 已被回收解除映射
+                Err(page_table::PagingError::NotMapped) => return Ok(()),
+                Err(e) => return Err(e),
+            },
+            _ => None,
+        };
         page_table.unmap_with_batch(vpn, batch)?;
+        if let Some(ppn) = ppn {
+            self.rmap_remove(page_table, vpn, ppn);
+        }
 
         // 对于帧映射，移除帧跟踪器
         if self.map_type == MapType::Framed {
@@ -1,15 +1,16 @@
-use alloc::{collections::btree_map::BTreeMap, sync::Arc};
+use alloc::{collections::btree_map::BTreeMap, sync::Arc, vec::Vec};
 use core::cmp::min;
 
 use crate::arch::mm::TlbBatchContext;
 use crate::config::PAGE_SIZE;
-use crate::ipc::ShmSegment;
 use crate::mm::address::{PA, PageNum, Ppn, UsizeConvert, Vpn, VpnRange};
-use crate::mm::frame_allocator::{TrackedFrames, alloc_frame};
+use crate::mm::frame_allocator::{FrameTracker, TrackedFrames, alloc_frame, alloc_frames};
+use crate::mm::memory_map::{MemoryMap, memory_map};
 use crate::mm::memory_space::MmapFile;
 use crate::mm::page_table::{
     self, ActivePageTableInner, PageSize, PageTableInner, UniversalPTEFlag,
 };
+use crate::mm::wx_policy::USER_PROT_MASK;
 use crate::uapi::mm::MapFlags;
 use crate::{pr_err, pr_warn};
 
@@ -26,10 +27,118 @@ pub enum MapType {
     /// - mmap(PROT_NONE) 需要“成功占位”但不应该映射可访问页表项
     /// - mprotect(PROT_NONE) 会把原有页表映射解除并转为 Reserved
     Reserved,
-    /// SysV shared memory segment mapping.
+    /// 共享物理页映射（SysV 共享内存段、共享匿名映射、tmpfs/memfd 文件、设备帧缓冲等）
+    ///
+    /// 物理页由 [`SharedPages`] 持有，区域本身不拥有、也不释放这些页。
     Shared,
 }
 
+/// 可被多个地址空间共同映射的物理页集合
+///
+/// 实现者负责物理页的生命周期；映射区域只通过 `Arc` 保持其存活。
+pub trait SharedPages: Send + Sync + core::fmt::Debug {
+    /// 第 `page_idx` 页对应的物理页号，越界时返回 None
+    fn ppn_at(&self, page_idx: usize) -> Option<Ppn>;
+
+    /// 总页数
+    fn page_count(&self) -> usize;
+
+    /// 是否为设备内存（MMIO）
+    ///
+    /// 设备内存以不可缓存方式映射（[`UniversalPTEFlag::DEVICE`]），不记录反向映射，
+    /// 也不参与回收。
+    fn is_device_memory(&self) -> bool {
+        false
+    }
+}
+
+/// `MAP_SHARED | MAP_ANONYMOUS` 映射的物理页
+///
+/// fork 时子进程的映射区域克隆同一个 `Arc`，父子进程看到同一批物理页；
+/// 最后一个映射解除后物理页随之释放。
+#[derive(Debug)]
+pub struct AnonSharedPages {
+    frames: Vec<FrameTracker>,
+}
+
+impl AnonSharedPages {
+    /// 分配 `pages` 个清零的物理页
+    pub fn new(pages: usize) -> Option<Arc<Self>> {
+        let frames = alloc_frames(pages)?;
+        Some(Arc::new(Self { frames }))
+    }
+}
+
+impl SharedPages for AnonSharedPages {
+    fn ppn_at(&self, page_idx: usize) -> Option<Ppn> {
+        self.frames.get(page_idx).map(FrameTracker::ppn)
+    }
+
+    fn page_count(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// 一段物理地址范围，供 `/dev/mem` 和驱动把 MMIO 区域映射到用户空间
+///
+/// 落在 RAM 中的页（内核映像、帧分配器管理的内存、保留内存等）一律拒绝映射，
+/// 只有设备树未报告为内存的地址可以访问。
+pub struct PhysPages {
+    start: Ppn,
+    count: usize,
+    memory_map: MemoryMap,
+}
+
+impl PhysPages {
+    /// 从 `start` 开始的 `count` 个物理页
+    pub fn new(start: Ppn, count: usize) -> Arc<Self> {
+        Arc::new(Self {
+            start,
+            count,
+            memory_map: memory_map(),
+        })
+    }
+
+    /// 整个物理地址空间（`/dev/mem`）
+    pub fn whole() -> Arc<Self> {
+        Self::new(
+            Ppn::from_usize(0),
+            (1usize << ActivePageTableInner::MAX_PA_BITS) / PAGE_SIZE,
+        )
+    }
+}
+
+impl core::fmt::Debug for PhysPages {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PhysPages")
+            .field("start", &self.start)
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+impl SharedPages for PhysPages {
+    fn ppn_at(&self, page_idx: usize) -> Option<Ppn> {
+        if page_idx >= self.count {
+            return None;
+        }
+        let ppn = Ppn::from_usize(self.start.as_usize() + page_idx);
+        let addr = ppn.start_addr().as_usize();
+        self.memory_map
+            .region_containing(addr)
+            .is_none()
+            .then_some(ppn)
+    }
+
+    fn page_count(&self) -> usize {
+        self.count
+    }
+
+    fn is_device_memory(&self) -> bool {
+        true
+    }
+}
+
 /// 内存区域的类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AreaType {
@@ -49,6 +158,36 @@ pub enum AreaType {
     UserMmap,     // 用户 mmap 匿名映射
 }
 
+impl AreaType {
+    /// 是否为用户空间区域
+    pub fn is_user(self) -> bool {
+        matches!(
+            self,
+            AreaType::UserText
+                | AreaType::UserRodata
+                | AreaType::UserData
+                | AreaType::UserBss
+                | AreaType::UserStack
+                | AreaType::UserHeap
+                | AreaType::UserMmap
+        )
+    }
+
+    /// `/proc/[pid]/maps` 等处显示的区域名
+    pub fn label(self) -> &'static str {
+        match self {
+            AreaType::UserText => "[text]",
+            AreaType::UserRodata => "[rodata]",
+            AreaType::UserData => "[data]",
+            AreaType::UserBss => "[bss]",
+            AreaType::UserHeap => "[heap]",
+            AreaType::UserStack => "[stack]",
+            AreaType::UserMmap => "[mmap]",
+            _ => "[kernel]",
+        }
+    }
+}
+
 /// 内存空间中的一个内存映射区域
 #[derive(Debug)]
 pub struct MappingArea {
@@ -64,20 +203,28 @@ pub struct MappingArea {
     /// 此映射区域的权限（使用 UniversalPTEFlag 以提高性能）
     permission: UniversalPTEFlag,
 
+    /// mprotect 可以授予的最大访问权限（R/W/X 位）
+    ///
+    /// 只读打开的文件做 `MAP_SHARED` 映射时不含可写位。
+    max_permission: UniversalPTEFlag,
+
     /// 用于帧映射区域的跟踪帧
     frames: BTreeMap<Vpn, TrackedFrames>,
 
     /// 文件映射信息（如果是文件映射）
     file: Option<MmapFile>,
 
-    /// SysV 共享内存段（如果是共享内存映射）
-    shared: Option<Arc<ShmSegment>>,
+    /// 共享物理页来源（如果是共享映射）
+    shared: Option<Arc<dyn SharedPages>>,
 
     /// `vpn_range.start()` 对应共享段内的页偏移。
     shared_page_offset: usize,
 }
 
 mod file_ops;
+mod ksm_ops;
 mod map_ops;
 mod resize_ops;
+mod rmap_ops;
 mod split_ops;
+mod swap_ops;
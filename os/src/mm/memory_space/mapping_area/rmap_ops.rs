@@ -0,0 +1,119 @@
+use super::*;
+use crate::mm::rmap;
+
+/// 反向映射维护
+impl MappingArea {
+    /// 区域中的页是否记录反向映射（用户空间的帧映射与共享映射，设备内存除外）
+    fn tracks_rmap(&self) -> bool {
+        matches!(self.map_type, MapType::Framed | MapType::Shared)
+            && !self.permission.contains(UniversalPTEFlag::DEVICE)
+            && matches!(
+                self.area_type,
+                AreaType::UserText
+                    | AreaType::UserRodata
+                    | AreaType::UserData
+                    | AreaType::UserBss
+                    | AreaType::UserStack
+                    | AreaType::UserHeap
+                    | AreaType::UserMmap
+            )
+    }
+
+    /// 记录 `vpn` 到 `ppn` 的映射
+    pub(super) fn rmap_add(&self, page_table: &ActivePageTableInner, vpn: Vpn, ppn: Ppn) {
+        if self.tracks_rmap() {
+            rmap::add(ppn, page_table.root_ppn(), vpn);
+        }
+    }
+
+    /// 删除 `vpn` 到 `ppn` 的映射记录
+    pub(super) fn rmap_remove(&self, page_table: &ActivePageTableInner, vpn: Vpn, ppn: Ppn) {
+        if self.tracks_rmap() {
+            rmap::remove(ppn, page_table.root_ppn(), vpn);
+        }
+    }
+
+    /// 删除区域内所有页的映射记录（地址空间销毁时调用）
+    pub(crate) fn rmap_release(&self, page_table: &ActivePageTableInner) {
+        if !self.tracks_rmap() {
+            return;
+        }
+        match self.map_type {
+            MapType::Framed => {
+                for (vpn, tracked) in &self.frames {
+                    match tracked {
+                        TrackedFrames::Single(frame) => {
+                            self.rmap_remove(page_table, *vpn, frame.ppn())
+                        }
+                        TrackedFrames::Merged(frame) => {
+                            self.rmap_remove(page_table, *vpn, frame.ppn())
+                        }
+                        TrackedFrames::Multiple(frames) => frames
+                            .iter()
+                            .for_each(|f| self.rmap_remove(page_table, *vpn, f.ppn())),
+                    }
+                }
+            }
+            _ => {
+                for vpn in self.vpn_range {
+                    if let Ok((ppn, _, _)) = page_table.walk(vpn) {
+                        self.rmap_remove(page_table, vpn, ppn);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 为回收或迁移解除 `vpn` 到 `ppn` 的映射，返回是否成功
+    ///
+    /// 共享页只解除页表项，访问时由 [`Self::fault_in`] 重新映射；私有匿名页写入交换区。
+    pub(crate) fn unmap_for_reclaim(
+        &mut self,
+        page_table: &mut ActivePageTableInner,
+        vpn: Vpn,
+        ppn: Ppn,
+    ) -> bool {
+        match self.map_type {
+            MapType::Shared => {
+                if !page_table
+                    .walk(vpn)
+                    .is_ok_and(|(mapped, _, _)| mapped == ppn)
+                {
+                    return false;
+                }
+                if page_table.unmap_with_batch(vpn, None).is_err() {
+                    return false;
+                }
+                self.rmap_remove(page_table, vpn, ppn);
+                rmap::mark_shared_unmapped();
+                true
+            }
+            MapType::Framed if self.is_swappable() => {
+                self.get_ppn(vpn) == Some(ppn) && self.swap_out_one(page_table, vpn) == Some(true)
+            }
+            _ => false,
+        }
+    }
+
+    /// 重建被换出或被解除映射的页，返回是否建立了映射
+    pub(crate) fn fault_in(
+        &mut self,
+        page_table: &mut ActivePageTableInner,
+        vpn: Vpn,
+    ) -> Result<bool, page_table::PagingError> {
+        match self.map_type {
+            MapType::Framed => self.swap_in_one(page_table, vpn),
+            MapType::Shared
+                if rmap::has_unmapped_shared()
+                    && matches!(
+                        page_table.walk(vpn),
+                        Err(page_table::PagingError::NotMapped)
+                    ) =>
+            {
+                self.map_one_with_batch(page_table, vpn, None)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
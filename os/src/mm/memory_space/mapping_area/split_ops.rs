@@ -9,6 +9,7 @@ impl MappingArea {
             area_type: self.area_type,
             map_type: self.map_type,
             permission: self.permission,
+            max_permission: self.max_permission,
             frames: BTreeMap::new(), // 不克隆帧
             // fork 时复制文件映射信息（MAP_SHARED 和 MAP_PRIVATE 都需要）
             file: self.file.as_ref().map(|f| MmapFile {
@@ -68,6 +69,7 @@ impl MappingArea {
                             self.permission,
                             Some(batch),
                         )?;
+                        new_area.rmap_add(page_table, *vpn, new_ppn);
 
                         new_area
                             .frames
@@ -103,6 +105,7 @@ impl MappingArea {
                                 self.permission,
                                 Some(batch),
                             )?;
+                            new_area.rmap_add(page_table, *vpn, new_ppn);
 
                             new_frames.push(new_frame);
                         }
@@ -111,6 +114,21 @@ impl MappingArea {
                             .frames
                             .insert(*vpn, TrackedFrames::Multiple(new_frames));
                     }
+                    TrackedFrames::Merged(frame) => {
+                        // 合并帧只读，子进程直接共享
+                        page_table.map_with_batch(
+                            *vpn,
+                            frame.ppn(),
+                            PageSize::Size4K,
+                            self.permission,
+                            Some(batch),
+                        )?;
+                        new_area.rmap_add(page_table, *vpn, frame.ppn());
+
+                        new_area
+                            .frames
+                            .insert(*vpn, TrackedFrames::Merged(frame.clone()));
+                    }
                 }
             }
 
@@ -183,6 +201,7 @@ impl MappingArea {
             left_file,
         );
         left_area.shared = self.shared.clone();
+        left_area.max_permission = self.max_permission;
         left_area.shared_page_offset = self.shared_page_offset;
 
         let mut right_area = MappingArea::new(
@@ -193,6 +212,7 @@ impl MappingArea {
             right_file,
         );
         right_area.shared = self.shared.clone();
+        right_area.max_permission = self.max_permission;
         right_area.shared_page_offset = self.shared_page_offset + left_pages;
 
         // 分配帧：遍历原区域的 frames，根据 VPN 分配到左右区域 - 手动迭代并清空
@@ -240,6 +260,8 @@ impl MappingArea {
     ) -> Result<alloc::vec::Vec<Self>, page_table::PagingError> {
         let area_start = self.vpn_range.start();
         let area_end = self.vpn_range.end();
+        // 内存类型属于区域本身，不随访问权限改变
+        let new_perm = new_perm | (self.permission & UniversalPTEFlag::DEVICE);
 
         // 计算需要修改权限的实际范围
         let change_start = core::cmp::max(start_vpn, area_start);
@@ -299,6 +321,7 @@ impl MappingArea {
         };
         if let Some(ref mut l) = left_area {
             l.shared = self.shared.clone();
+            l.max_permission = self.max_permission;
             l.shared_page_offset = self.shared_page_offset;
         }
 
@@ -314,6 +337,7 @@ impl MappingArea {
             middle_file,
         );
         middle_area.shared = self.shared.clone();
+        middle_area.max_permission = self.max_permission;
         middle_area.shared_page_offset = self.shared_page_offset + left_pages;
 
         let mut right_area = if change_end < area_end {
@@ -329,6 +353,7 @@ impl MappingArea {
         };
         if let Some(ref mut r) = right_area {
             r.shared = self.shared.clone();
+            r.max_permission = self.max_permission;
             r.shared_page_offset = self.shared_page_offset + left_pages + middle_pages;
         }
 
@@ -336,9 +361,19 @@ impl MappingArea {
             MapType::Direct => return Err(page_table::PagingError::UnsupportedMapType),
             MapType::Framed => {
                 if wants_mapping {
+                    // 变为可写前先为合并页复制出私有帧
+                    if new_perm.contains(UniversalPTEFlag::WRITEABLE) {
+                        self.ksm_unmerge_range(page_table, middle_range)?;
+                    }
                     // 仅更新 middle_range 的权限（要求为叶子 PTE：必须有 R/W/X）
+                    // 被换出的页在换入时按新权限映射
                     TlbBatchContext::execute(|batch| {
                         for vpn in VpnRange::new(change_start, change_end) {
+                            if !self.frames.contains_key(&vpn)
+                                && page_table.swap_entry(vpn).is_some()
+                            {
+                                continue;
+                            }
                             page_table.update_flags_with_batch(vpn, new_perm, Some(batch))?;
                         }
                         Ok::<(), page_table::PagingError>(())
@@ -394,6 +429,7 @@ impl MappingArea {
                                 middle_area.permission,
                                 Some(batch),
                             )?;
+                            middle_area.rmap_add(page_table, vpn, ppn);
                         }
                         Ok::<(), page_table::PagingError>(())
                     })?;
@@ -407,6 +443,10 @@ impl MappingArea {
                 }
                 TlbBatchContext::execute(|batch| {
                     for vpn in VpnRange::new(change_start, change_end) {
+                        // 被回收解除映射的页在缺页时按新权限重新映射
+                        if page_table.walk(vpn).is_err() {
+                            continue;
+                        }
                         page_table.update_flags_with_batch(vpn, new_perm, Some(batch))?;
                     }
                     Ok::<(), page_table::PagingError>(())
@@ -525,8 +565,10 @@ impl MappingArea {
                 right_file,
             );
             left_area.shared = self.shared.clone();
+            left_area.max_permission = self.max_permission;
             left_area.shared_page_offset = self.shared_page_offset;
             right_area.shared = self.shared.clone();
+            right_area.max_permission = self.max_permission;
             right_area.shared_page_offset = self.shared_page_offset + left_pages + middle_pages;
 
             // 分配 frames - 手动迭代并清空
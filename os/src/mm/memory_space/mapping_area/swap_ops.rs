@@ -0,0 +1,197 @@
+use super::*;
+use crate::mm::swap;
+
+/// 换出与换入
+impl MappingArea {
+    /// 是否可以把页换出到交换区
+    ///
+    /// 只换出私有匿名页；用户栈由内核直接按物理地址写入（参数、信号帧），不参与换出。
+    pub fn is_swappable(&self) -> bool {
+        self.map_type == MapType::Framed
+            && self.file.is_none()
+            && self.shared.is_none()
+            && matches!(
+                self.area_type,
+                AreaType::UserData | AreaType::UserBss | AreaType::UserHeap | AreaType::UserMmap
+            )
+    }
+
+    /// 从 `from` 开始按时钟算法换出至多 `max` 页，返回换出的页数
+    ///
+    /// 访问位置位的页只清除访问位（第二次机会）。`cursor` 更新为下一次扫描的起点。
+    pub(crate) fn reclaim_from(
+        &mut self,
+        page_table: &mut ActivePageTableInner,
+        from: Vpn,
+        max: usize,
+        cursor: &mut Vpn,
+    ) -> usize {
+        let candidates: Vec<Vpn> = self
+            .frames
+            .range(from..)
+            .filter(|(_, tracked)| matches!(tracked, TrackedFrames::Single(_)))
+            .map(|(vpn, _)| *vpn)
+            .collect();
+
+        let mut reclaimed = 0;
+        TlbBatchContext::execute(|batch| {
+            for vpn in candidates {
+                if reclaimed >= max {
+                    break;
+                }
+                *cursor = Vpn::from_usize(vpn.as_usize() + 1);
+                let Ok((_, _, flags)) = page_table.walk(vpn) else {
+                    continue;
+                };
+                if flags.contains(UniversalPTEFlag::ACCESSED) {
+                    let _ = page_table.update_flags_with_batch(vpn, self.permission, Some(batch));
+                    continue;
+                }
+                match self.swap_out_one(page_table, vpn) {
+                    Some(true) => reclaimed += 1,
+                    Some(false) => {}
+                    // 交换区已满
+                    None => break,
+                }
+            }
+        });
+        reclaimed
+    }
+
+    /// 把单页写入交换区并释放物理帧
+    ///
+    /// 返回 None 表示没有空闲交换槽，`Some(false)` 表示该页换出失败但仍驻留内存。
+    pub(super) fn swap_out_one(
+        &mut self,
+        page_table: &mut ActivePageTableInner,
+        vpn: Vpn,
+    ) -> Option<bool> {
+        let Some(TrackedFrames::Single(frame)) = self.frames.get(&vpn) else {
+            return Some(false);
+        };
+        let ppn = frame.ppn();
+        let slot = swap::alloc_slot()?;
+
+        // 先撤销映射再写出，写出期间其他 CPU 无法继续修改该页
+        if page_table.set_swap_entry(vpn, slot).is_err() {
+            swap::free_slot(slot);
+            return Some(false);
+        }
+        let data = unsafe {
+            core::slice::from_raw_parts(
+                crate::arch::pa_to_va(ppn.start_addr()).as_usize() as *const u8,
+                PAGE_SIZE,
+            )
+        };
+        if !swap::write_slot(slot, data) {
+            let _ = page_table.clear_swap_entry(vpn);
+            swap::free_slot(slot);
+            if let Err(e) =
+                page_table.map_with_batch(vpn, ppn, PageSize::Size4K, self.permission, None)
+            {
+                pr_err!(
+                    "[Swap] Failed to restore page {:#x}: {:?}",
+                    vpn.start_addr().as_usize(),
+                    e
+                );
+            }
+            return Some(false);
+        }
+
+        self.rmap_remove(page_table, vpn, ppn);
+        self.frames.remove(&vpn);
+        Some(true)
+    }
+
+    /// 若 `vpn` 被换出则换入；返回是否换入了页
+    pub(crate) fn swap_in_one(
+        &mut self,
+        page_table: &mut ActivePageTableInner,
+        vpn: Vpn,
+    ) -> Result<bool, page_table::PagingError> {
+        let Some(slot) = page_table.swap_entry(vpn) else {
+            return Ok(false);
+        };
+        let frame = alloc_frame().ok_or(page_table::PagingError::FrameAllocFailed)?;
+        let ppn = frame.ppn();
+        let buf = unsafe {
+            core::slice::from_raw_parts_mut(
+                crate::arch::pa_to_va(ppn.start_addr()).as_usize() as *mut u8,
+                PAGE_SIZE,
+            )
+        };
+        if !swap::read_slot(slot, buf) {
+            pr_err!(
+                "[Swap] Failed to read slot {} for page {:#x}",
+                slot,
+                vpn.start_addr().as_usize()
+            );
+            return Err(page_table::PagingError::InvalidAddress);
+        }
+
+        page_table.clear_swap_entry(vpn)?;
+        page_table.map_with_batch(vpn, ppn, PageSize::Size4K, self.permission, None)?;
+        self.rmap_add(page_table, vpn, ppn);
+        self.frames.insert(vpn, TrackedFrames::Single(frame));
+        swap::free_slot(slot);
+        Ok(true)
+    }
+
+    /// 换入区域内所有被换出的页
+    pub(crate) fn swap_in_all(
+        &mut self,
+        page_table: &mut ActivePageTableInner,
+    ) -> Result<(), page_table::PagingError> {
+        for vpn in self.vpn_range {
+            if !self.frames.contains_key(&vpn) {
+                self.swap_in_one(page_table, vpn)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 把父进程中被换出的页复制到子进程（读回到新帧，不占用新的交换槽）
+    pub(crate) fn clone_swapped(
+        &self,
+        page_table: &ActivePageTableInner,
+        child: &mut MappingArea,
+        child_table: &mut ActivePageTableInner,
+    ) -> Result<(), page_table::PagingError> {
+        for vpn in self.vpn_range {
+            if self.frames.contains_key(&vpn) {
+                continue;
+            }
+            let Some(slot) = page_table.swap_entry(vpn) else {
+                continue;
+            };
+            let frame = alloc_frame().ok_or(page_table::PagingError::FrameAllocFailed)?;
+            let ppn = frame.ppn();
+            let buf = unsafe {
+                core::slice::from_raw_parts_mut(
+                    crate::arch::pa_to_va(ppn.start_addr()).as_usize() as *mut u8,
+                    PAGE_SIZE,
+                )
+            };
+            if !swap::read_slot(slot, buf) {
+                return Err(page_table::PagingError::InvalidAddress);
+            }
+            child_table.map_with_batch(vpn, ppn, PageSize::Size4K, self.permission, None)?;
+            child.rmap_add(child_table, vpn, ppn);
+            child.frames.insert(vpn, TrackedFrames::Single(frame));
+        }
+        Ok(())
+    }
+
+    /// 释放区域内被换出页占用的交换槽
+    pub(crate) fn release_swap(&self, page_table: &mut ActivePageTableInner) {
+        for vpn in self.vpn_range {
+            if self.frames.contains_key(&vpn) {
+                continue;
+            }
+            if let Some(slot) = page_table.swap_entry(vpn) {
+                let _ = page_table.clear_swap_entry(vpn);
+                swap::free_slot(slot);
+            }
+        }
+    }
+}
@@ -2,9 +2,11 @@
 //!
 //! 本模块定义了内存空间（Memory Space）的相关结构和功能，
 //! 包括内存空间的创建、管理以及与映射区域（Mapping Area）的交互。
+mod layout;
 pub mod mapping_area;
 mod mmap_file;
 mod space;
 
+pub use layout::{UserLayout, mmap_min_addr, set_mmap_min_addr};
 pub use mmap_file::MmapFile;
 pub use space::*;
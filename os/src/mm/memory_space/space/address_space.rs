@@ -6,7 +6,8 @@ impl MemorySpace {
         Ok(MemorySpace {
             page_table: ActivePageTableInner::new()?,
             areas: Vec::new(),
-            heap_start: None,
+            layout: None,
+            swap_cursor: Vpn::from_usize(0),
         })
     }
 
@@ -46,7 +47,7 @@ impl MemorySpace {
             .iter()
             .find(|a| a.area_type() == AreaType::UserHeap)
             .map(|a| a.vpn_range().end().start_addr())
-            .or_else(|| self.heap_start.map(|vpn| vpn.start_addr()))
+            .or_else(|| self.layout.map(|l| VA::from_usize(l.heap_base)))
     }
 
     /// 创建一个新的用户地址空间，并克隆当前地址空间的内核映射。
@@ -80,11 +81,33 @@ impl MemorySpace {
         Ok(space)
     }
 
-    /// 设置用户堆的起始地址（brk 的下界）。
+    /// 设置用户堆的起始地址（brk 的下界），并据此确定用户地址空间布局。
     ///
-    /// 注意：这里只设置固定的 heap_start，不会创建/扩展 UserHeap 映射区域。
+    /// 应在程序映像装入之后调用，映像中最低的段地址作为布局的 `text_base`。
+    /// 注意：这里不会创建/扩展 UserHeap 映射区域。
     pub fn set_heap_start(&mut self, heap_start: Vpn) {
-        self.heap_start = Some(heap_start);
+        let heap_base = heap_start.start_addr().as_usize();
+        let text_base = self
+            .areas
+            .iter()
+            .filter(|a| {
+                matches!(
+                    a.area_type(),
+                    AreaType::UserText
+                        | AreaType::UserRodata
+                        | AreaType::UserData
+                        | AreaType::UserBss
+                )
+            })
+            .map(|a| a.vpn_range().start().start_addr().as_usize())
+            .min()
+            .unwrap_or(heap_base);
+        self.layout = Some(UserLayout::new(text_base, heap_base));
+    }
+
+    /// 返回用户地址空间布局，内核地址空间和尚未装入程序的空间返回 None
+    pub fn layout(&self) -> Option<&UserLayout> {
+        self.layout.as_ref()
     }
 
     pub(super) fn clone_direct_area(&mut self, area: &MappingArea) -> Result<(), PagingError> {
@@ -128,6 +151,7 @@ impl MemorySpace {
             Some(code),
             None,
         )?;
+        crate::arch::sync_icache_range(VA::from_usize(start), code.len());
 
         Ok(())
     }
@@ -141,6 +165,10 @@ impl MemorySpace {
         let mut written = 0usize;
         while written < bytes.len() {
             let cur_va = va.checked_add(written).ok_or(PagingError::InvalidAddress)?;
+            let vpn = Vpn::from_addr_floor(VA::from_usize(cur_va));
+            self.swap_in(vpn);
+            // 直接改写物理页，不能影响共享合并页的其他映射者
+            self.ksm_unmerge(vpn)?;
             let paddr = self
                 .page_table
                 .translate(VA::from_usize(cur_va))
@@ -171,6 +199,7 @@ impl MemorySpace {
         while written < bytes.len() {
             let cur_va = va.checked_add(written).ok_or(PagingError::InvalidAddress)?;
             let vpn = Vpn::from_addr_floor(VA::from_usize(cur_va));
+            self.swap_in(vpn);
             let (ppn, _page_size, flags) = self.page_table.walk(vpn)?;
             if !flags.contains(UniversalPTEFlag::USER_ACCESSIBLE)
                 || !flags.contains(UniversalPTEFlag::WRITEABLE)
@@ -190,6 +219,32 @@ impl MemorySpace {
         Ok(())
     }
 
+    /// Read bytes from a userspace address only if each source page is user-readable.
+    pub fn read_user_bytes_at(&mut self, va: usize, out: &mut [u8]) -> Result<(), PagingError> {
+        let mut read = 0usize;
+        while read < out.len() {
+            let cur_va = va.checked_add(read).ok_or(PagingError::InvalidAddress)?;
+            let vpn = Vpn::from_addr_floor(VA::from_usize(cur_va));
+            self.swap_in(vpn);
+            let (ppn, _page_size, flags) = self.page_table.walk(vpn)?;
+            if !flags.contains(UniversalPTEFlag::USER_ACCESSIBLE)
+                || !flags.contains(UniversalPTEFlag::READABLE)
+            {
+                return Err(PagingError::PermissionDenied);
+            }
+
+            let page_off = cur_va & (PAGE_SIZE - 1);
+            let take = core::cmp::min(out.len() - read, PAGE_SIZE - page_off);
+            let src = (crate::arch::pa_to_va(ppn.start_addr()).as_usize() + page_off) as *const u8;
+            unsafe {
+                core::ptr::copy_nonoverlapping(src, out[read..].as_mut_ptr(), take);
+            }
+            read += take;
+        }
+
+        Ok(())
+    }
+
     /// 从指定虚拟地址读取字节序列（跨页安全）。
     pub fn read_bytes_at(&self, va: usize, out: &mut [u8]) -> Result<(), PagingError> {
         if out.is_empty() {
@@ -294,14 +349,17 @@ impl MemorySpace {
         Ok(())
     }
 
-    /// 插入 SysV shared memory 映射区域。
+    /// 插入共享物理页映射区域（SysV 共享内存、设备帧缓冲等）。
+    ///
+    /// `page_offset` 为 `vpn_range.start()` 对应的共享页下标。
     pub fn insert_shared_area(
         &mut self,
         vpn_range: VpnRange,
         flags: UniversalPTEFlag,
-        segment: alloc::sync::Arc<crate::ipc::ShmSegment>,
+        segment: alloc::sync::Arc<dyn SharedPages>,
+        page_offset: usize,
     ) -> Result<(), PagingError> {
-        let area = MappingArea::new_shared(vpn_range, flags, segment);
+        let area = MappingArea::new_shared(vpn_range, flags, segment, page_offset);
         self.insert_area(area)?;
         Ok(())
     }
@@ -371,7 +429,7 @@ impl MemorySpace {
     /// - 帧映射是深层复制的
     pub fn clone_for_fork(&self) -> Result<Self, PagingError> {
         let mut new_space = MemorySpace::new()?;
-        new_space.heap_start = self.heap_start;
+        new_space.layout = self.layout;
 
         for area in self.areas.iter() {
             match area.map_type() {
@@ -381,7 +439,14 @@ impl MemorySpace {
                 }
                 MapType::Framed => {
                     // 帧映射：深层复制数据
-                    let new_area = area.clone_with_data(&mut new_space.page_table)?;
+                    let mut new_area = area.clone_with_data(&mut new_space.page_table)?;
+                    if crate::mm::swap::has_swapped_pages() {
+                        area.clone_swapped(
+                            &self.page_table,
+                            &mut new_area,
+                            &mut new_space.page_table,
+                        )?;
+                    }
                     new_space.areas.push(new_area);
                 }
                 MapType::Reserved => {
@@ -420,6 +485,14 @@ impl Drop for MemorySpace {
                 // 继续处理其他区域，不能 panic
             }
         }
+        if crate::mm::swap::has_swapped_pages() {
+            for area in &self.areas {
+                area.release_swap(&mut self.page_table);
+            }
+        }
+        for area in &self.areas {
+            area.rmap_release(&self.page_table);
+        }
         // 其余清理工作由各字段的 Drop 自动完成
     }
 }
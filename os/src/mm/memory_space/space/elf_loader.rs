@@ -80,6 +80,8 @@ impl MemorySpace {
         drop(current_locked);
 
         let mut max_end_vpn = Vpn::from_usize(0);
+        // 可执行段覆盖的范围，加载完成后同步指令缓存
+        let mut text_range: Option<(usize, usize)> = None;
 
         // 1. 解析并映射 ELF 段
         for ph in elf.program_iter() {
@@ -113,19 +115,21 @@ impl MemorySpace {
             };
 
             // 构建权限
-            let mut flags = UniversalPTEFlag::USER_ACCESSIBLE | UniversalPTEFlag::VALID;
-            if ph.flags().is_read() {
-                flags |= UniversalPTEFlag::READABLE;
-            }
-            if ph.flags().is_write() {
-                flags |= UniversalPTEFlag::WRITEABLE;
-            }
-            if ph.flags().is_execute() {
-                flags |= UniversalPTEFlag::EXECUTABLE;
+            let flags = crate::mm::wx_policy::user_pte_flags(
+                ph.flags().is_read(),
+                ph.flags().is_write(),
+                ph.flags().is_execute(),
+            );
+            if !crate::mm::wx_policy::wx_permitted(flags, "from_elf", start_va) {
+                return Err(PagingError::PermissionDenied);
             }
 
             // 确定区域类型
             let area_type = if ph.flags().is_execute() {
+                text_range = Some(match text_range {
+                    Some((start, end)) => (start.min(start_va), end.max(end_va)),
+                    None => (start_va, end_va),
+                });
                 AreaType::UserText
             } else if ph.flags().is_write() {
                 AreaType::UserData
@@ -256,8 +260,13 @@ impl MemorySpace {
             }
         }
 
+        // 代码已写入物理页（重定位可能改写代码段），执行前同步指令缓存
+        if let Some((start, end)) = text_range {
+            crate::arch::sync_icache_range(VA::from_usize(start), end - start);
+        }
+
         // 2. 初始化堆（从 ELF 结束地址开始，页对齐）
-        space.heap_start = Some(max_end_vpn);
+        space.set_heap_start(max_end_vpn);
 
         // 3. 映射用户栈（带保护页）
         let user_stack_bottom =
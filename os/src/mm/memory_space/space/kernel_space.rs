@@ -95,46 +95,28 @@ impl MemorySpace {
             UniversalPTEFlag::kernel_rw(),
         )?;
 
-        // 5. 映射物理内存（从 ekernel 到物理内存末端的直接映射）
+        // 5. 映射物理内存（内核映像之后的各个 RAM 区域的直接映射）
         //
-        // 评测机可能使用较大的 -m（如 1G/4G），QEMU 把 DTB 放在 RAM 顶端（-m 4G 时约
-        // 0xbfe0_0000）。若只映射到编译期 MEMORY_END（128MB），切换页表后解引用 DTB 会缺页，
-        // 且帧分配器也无法管理评测所需的大内存。改用设备树报告的真实 DRAM 末端，并与帧分配器
-        // 范围保持一致（见 mm::init）。
-        let ekernel_paddr = unsafe { crate::arch::va_to_pa(VA::from_usize(ekernel as usize)) };
-
-        let phys_mem_end_paddr = match crate::device::device_tree::dram_info() {
-            Some((dram_start, dram_size)) => dram_start.saturating_add(dram_size),
-            None => MEMORY_END,
-        };
-
-        // LoongArch virt 报告超大 RAM 窗口，用 4K 页映射多 GB 极慢；且 LoongArch 经 DMW 硬件
-        // 直映射访问物理内存（不经页表）。把直映射窗口 cap 在 1GiB，与帧分配器一致。RISC-V 不
-        // 设上限，覆盖全部 DRAM（确保 DTB 也在范围内）。
-        #[cfg(target_arch = "loongarch64")]
-        let phys_mem_end_paddr = {
-            const MAX_KERNEL_DIRECT_MAP_BYTES: usize = 1024 * 1024 * 1024; // 1GiB
-            let cap_end = ekernel_paddr
-                .as_usize()
-                .saturating_add(MAX_KERNEL_DIRECT_MAP_BYTES);
-            phys_mem_end_paddr.min(cap_end)
-        };
-
-        let phys_mem_start_vaddr = crate::arch::pa_to_va(ekernel_paddr);
-        let phys_mem_end_vaddr = crate::arch::pa_to_va(PA::from_usize(phys_mem_end_paddr));
-
-        let phys_mem_start = Vpn::from_addr_ceil(phys_mem_start_vaddr);
-        let phys_mem_end = Vpn::from_addr_floor(phys_mem_end_vaddr);
-        let mut phys_mem_area = MappingArea::new(
-            VpnRange::new(phys_mem_start, phys_mem_end),
-            AreaType::KernelHeap,
-            MapType::Direct,
-            UniversalPTEFlag::kernel_rw(),
-            None, // 内核直接映射，无文件
-        );
-
-        phys_mem_area.map(&mut self.page_table)?;
-        self.areas.push(phys_mem_area);
+        // 范围来自设备树建立的内存布局，与帧分配器一致（见 mm::init）。QEMU 把 DTB 放在
+        // RAM 顶端，也落在这些范围内。
+        let windows = crate::mm::memory_map::kernel_windows();
+        for window in windows.as_slice() {
+            let phys_mem_start_vaddr = crate::arch::pa_to_va(PA::from_usize(window.start));
+            let phys_mem_end_vaddr = crate::arch::pa_to_va(PA::from_usize(window.end));
+
+            let phys_mem_start = Vpn::from_addr_ceil(phys_mem_start_vaddr);
+            let phys_mem_end = Vpn::from_addr_floor(phys_mem_end_vaddr);
+            let mut phys_mem_area = MappingArea::new(
+                VpnRange::new(phys_mem_start, phys_mem_end),
+                AreaType::KernelHeap,
+                MapType::Direct,
+                UniversalPTEFlag::kernel_rw(),
+                None, // 内核直接映射，无文件
+            );
+
+            phys_mem_area.map(&mut self.page_table)?;
+            self.areas.push(phys_mem_area);
+        }
 
         // 确保 DTB 被映射（即使在上面 cap 了物理内存直映射窗口之后）。
         //
@@ -146,8 +128,8 @@ impl MemorySpace {
                 crate::device::device_tree::DTP.load(core::sync::atomic::Ordering::Acquire);
             if dtb_paddr != 0 {
                 let dtb_start = dtb_paddr & !(PAGE_SIZE - 1);
-                // 仅当 DTB 不在（可能被 cap 的）物理内存直映射窗口内时才单独映射。
-                if dtb_start < ekernel_paddr.as_usize() || dtb_start >= phys_mem_end_paddr {
+                // 仅当 DTB 不在物理内存直映射窗口内时才单独映射。
+                if !windows.contains(dtb_start) {
                     let dtb_vaddr = crate::arch::pa_to_va(PA::from_usize(dtb_start));
                     let vpn = Vpn::from_addr_floor(dtb_vaddr);
                     if self.find_area(vpn).is_none() {
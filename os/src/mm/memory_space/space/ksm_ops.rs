@@ -0,0 +1,75 @@
+use alloc::sync::Arc;
+
+use super::*;
+use crate::mm::frame_allocator::FrameTracker;
+
+/// 同页合并相关操作
+impl MemorySpace {
+    /// 按地址顺序收集从 `from` 开始的至多 `max` 个尚未合并的候选页
+    pub(crate) fn ksm_candidates(&self, from: Vpn, max: usize) -> Vec<(Vpn, Ppn)> {
+        let mut areas: Vec<&MappingArea> = self.areas.iter().filter(|a| a.is_mergeable()).collect();
+        areas.sort_by_key(|a| a.vpn_range().start());
+        areas
+            .into_iter()
+            .filter(|a| a.vpn_range().end() > from)
+            .flat_map(|a| a.ksm_candidates(from))
+            .take(max)
+            .collect()
+    }
+
+    /// `vpn` 仍是尚未合并的候选页时返回其物理页号
+    pub(crate) fn ksm_page(&self, vpn: Vpn) -> Option<Ppn> {
+        self.areas
+            .iter()
+            .find(|a| a.vpn_range().contains(vpn) && a.is_mergeable())
+            .and_then(|a| a.ksm_candidates(vpn).next())
+            .filter(|(found, _)| *found == vpn)
+            .map(|(_, ppn)| ppn)
+    }
+
+    /// 把候选页 `vpn` 转为合并帧
+    pub(crate) fn ksm_promote(&mut self, vpn: Vpn) -> Option<Arc<FrameTracker>> {
+        self.areas
+            .iter_mut()
+            .find(|a| a.vpn_range().contains(vpn) && a.is_mergeable())
+            .and_then(|a| a.ksm_promote(vpn))
+    }
+
+    /// 把候选页 `vpn` 合并到 `frame`，返回是否成功
+    pub(crate) fn ksm_merge(&mut self, vpn: Vpn, frame: &Arc<FrameTracker>) -> bool {
+        let MemorySpace {
+            page_table, areas, ..
+        } = self;
+        let Some(area) = areas
+            .iter_mut()
+            .find(|a| a.vpn_range().contains(vpn) && a.is_mergeable())
+        else {
+            return false;
+        };
+        match area.ksm_merge(page_table, vpn, frame) {
+            Ok(()) => true,
+            Err(e) => {
+                pr_warn!(
+                    "[KSM] Failed to merge page {:#x}: {:?}",
+                    vpn.start_addr().as_usize(),
+                    e
+                );
+                false
+            }
+        }
+    }
+
+    /// 若 `vpn` 是合并页则为其复制出私有帧
+    pub(crate) fn ksm_unmerge(&mut self, vpn: Vpn) -> Result<(), PagingError> {
+        if !crate::mm::ksm::has_merged_pages() {
+            return Ok(());
+        }
+        let MemorySpace {
+            page_table, areas, ..
+        } = self;
+        match areas.iter_mut().find(|a| a.vpn_range().contains(vpn)) {
+            Some(area) => area.ksm_unmerge(page_table, vpn),
+            None => Ok(()),
+        }
+    }
+}
@@ -5,27 +5,23 @@ impl MemorySpace {
     ///
     /// # 错误
     /// - 堆未初始化
-    /// - 新的 brk 会超出 MAX_USER_HEAP_SIZE
-    /// - 新的 brk 会与现有区域重叠
+    /// - 新的 brk 会超出布局的堆上限（MAX_USER_HEAP_SIZE）
+    /// - 新的 brk 会与现有区域重叠，或与其间隔不足 `guard_gap`
     pub fn brk(&mut self, new_brk: VA) -> Result<VA, PagingError> {
-        let heap_bottom = self.heap_start.ok_or(PagingError::InvalidAddress)?;
+        let layout = self.layout.ok_or(PagingError::InvalidAddress)?;
+        let heap_bottom = Vpn::from_addr_floor(VA::from_usize(layout.heap_base));
         let new_brk_usize = new_brk.as_usize();
         let new_end_vpn = Vpn::from_addr_ceil(new_brk);
 
         // 边界检查
-        if new_brk_usize < heap_bottom.start_addr().as_usize() {
+        if new_brk_usize < layout.heap_base || new_brk_usize > layout.heap_limit {
             return Err(PagingError::InvalidAddress);
         }
 
-        let heap_size = new_brk_usize - heap_bottom.start_addr().as_usize();
-        if heap_size > MAX_USER_HEAP_SIZE {
-            return Err(PagingError::InvalidAddress);
-        }
-
-        // 检查是否与栈重叠
-        if new_brk_usize >= USER_STACK_TOP - USER_STACK_SIZE {
-            return Err(PagingError::InvalidAddress);
-        }
+        // 扩展后的堆顶之上需要留出保护间隔
+        let guard_end = Vpn::from_addr_ceil(VA::from_usize(
+            new_end_vpn.start_addr().as_usize() + layout.guard_gap,
+        ));
 
         // 查找或创建堆区域
         let heap_area_idx = self
@@ -40,7 +36,7 @@ impl MemorySpace {
             match new_end_vpn.cmp(&old_end) {
                 Ordering::Greater => {
                     // 扩展：检查是否与其他区域冲突
-                    let new_range = VpnRange::new(old_end, new_end_vpn);
+                    let new_range = VpnRange::new(old_end, guard_end);
                     for (i, area) in self.areas.iter().enumerate() {
                         if i != idx && area.vpn_range().overlaps(&new_range) {
                             // 与mmap或其他区域冲突
@@ -72,15 +68,15 @@ impl MemorySpace {
             // 第一次分配堆，创建新区域
             if new_end_vpn > heap_bottom {
                 // 检查是否与现有区域冲突
-                let new_range = VpnRange::new(heap_bottom, new_end_vpn);
+                let guarded = VpnRange::new(heap_bottom, guard_end);
                 for area in &self.areas {
-                    if area.vpn_range().overlaps(&new_range) {
+                    if area.vpn_range().overlaps(&guarded) {
                         return Err(PagingError::AlreadyMapped);
                     }
                 }
 
                 self.insert_framed_area(
-                    new_range,
+                    VpnRange::new(heap_bottom, new_end_vpn),
                     AreaType::UserHeap,
                     UniversalPTEFlag::user_rw(),
                     None,
@@ -94,106 +90,90 @@ impl MemorySpace {
 
     /// 查找足够大的空闲地址区域
     ///
+    /// 在布局的 mmap 区内自顶向下查找，与程序映像、堆和栈之间保留 `guard_gap`；
+    /// mmap 区耗尽时退回到当前堆顶之上查找。
+    ///
     /// # 参数
     /// - `size`: 需要的大小（字节）
     /// - `align`: 对齐要求（字节）
     ///
     /// # 返回值
     /// - `Some(addr)`: 找到的空闲区域起始地址（已对齐）
-    /// - `None`: 没有足够大的空闲区域
+    /// - `None`: 没有足够大的空闲区域，或地址空间尚未确定布局
     pub fn find_free_region(&self, size: usize, align: usize) -> Option<VA> {
-        // 获取堆的起始和结束
-        let heap_start = self.heap_start?.start_addr().as_usize();
-
-        // 获取当前堆的实际结束地址（不包含 mmap 区域）
-        let heap_end = self
-            .areas
-            .iter()
-            .filter(|a| a.area_type() == AreaType::UserHeap)
-            .map(|a| a.vpn_range().end().start_addr().as_usize())
-            .max()
-            .unwrap_or(heap_start);
+        let layout = self.layout?;
+        let occupied = self.occupied_ranges(&layout);
+
+        find_top_down(
+            &occupied,
+            layout.mmap_floor(),
+            layout.mmap_base,
+            size,
+            align,
+        )
+        .or_else(|| {
+            let brk = self.current_brk()?.as_usize();
+            let floor = brk.saturating_add(layout.guard_gap).max(mmap_min_addr());
+            find_top_down(&occupied, floor, layout.mmap_base, size, align)
+        })
+        .map(VA::from_usize)
+    }
 
-        // 栈的底部地址
-        let stack_bottom = USER_STACK_TOP - USER_STACK_SIZE;
+    /// 检查 `[start, start + len)` 能否作为非固定映射的 hint 使用
+    ///
+    /// 区间须位于 `mmap_min_addr` 与 mmap 区上限之间，不与现有区域重叠，
+    /// 且与关键区域之间保留 `guard_gap`。
+    pub fn hint_fits(&self, start: usize, len: usize) -> bool {
+        let Some(end) = start.checked_add(len) else {
+            return false;
+        };
+        let Some(layout) = self.layout else {
+            let range = VpnRange::new(
+                Vpn::from_addr_floor(VA::from_usize(start)),
+                Vpn::from_addr_ceil(VA::from_usize(end)),
+            );
+            return !self.areas.iter().any(|a| a.vpn_range().overlaps(&range));
+        };
+        if start < mmap_min_addr() || end > layout.mmap_base {
+            return false;
+        }
+        let occupied = self.occupied_ranges(&layout);
+        find_top_down(&occupied, start, end, len, PAGE_SIZE) == Some(start)
+    }
 
-        // 预留栈增长空间（建议至少 1MB）
-        const STACK_GUARD_SIZE: usize = 1024 * 1024;
-        let search_limit = stack_bottom.saturating_sub(STACK_GUARD_SIZE);
+    /// 检查区间是否覆盖了不允许被 MAP_FIXED 替换的关键区域
+    ///
+    /// 关键区域包括用户栈、堆、sigreturn trampoline 和内核区域。
+    pub fn overlaps_critical(&self, range: VpnRange) -> bool {
+        let trampoline = Vpn::from_addr_floor(VA::from_usize(USER_SIGRETURN_TRAMPOLINE));
+        self.areas.iter().any(|a| {
+            a.vpn_range().overlaps(&range)
+                && (matches!(a.area_type(), AreaType::UserStack | AreaType::UserHeap)
+                    || !a.area_type().is_user()
+                    || a.vpn_range().contains(trampoline))
+        })
+    }
 
-        // 收集所有用户区域（包括 heap 和 mmap），按起始地址排序
-        let mut user_areas: alloc::vec::Vec<(usize, usize)> = self
+    /// 按起始地址排序的用户区域，及新映射与它们之间需要保持的间隔
+    ///
+    /// 相邻的 mmap 映射之间不留间隔，其余用户区域两侧保留 `guard_gap`。
+    fn occupied_ranges(&self, layout: &UserLayout) -> Vec<Occupied> {
+        let mut occupied: Vec<Occupied> = self
             .areas
             .iter()
-            .filter(|a| {
-                matches!(
-                    a.area_type(),
-                    AreaType::UserHeap
-                        | AreaType::UserMmap
-                        | AreaType::UserStack
-                        | AreaType::UserText
-                        | AreaType::UserRodata
-                        | AreaType::UserData
-                        | AreaType::UserBss
-                )
-            })
-            .map(|a| {
-                let start = a.vpn_range().start().start_addr().as_usize();
-                let end = a.vpn_range().end().start_addr().as_usize();
-                (start, end)
+            .filter(|a| a.area_type().is_user())
+            .map(|a| Occupied {
+                start: a.vpn_range().start().start_addr().as_usize(),
+                end: a.vpn_range().end().start_addr().as_usize(),
+                gap: if a.area_type() == AreaType::UserMmap {
+                    0
+                } else {
+                    layout.guard_gap
+                },
             })
             .collect();
-
-        user_areas.sort_by_key(|&(start, _)| start);
-
-        // Linux 行为更接近 “top-down” 分配：mmap 默认从高地址向低地址找洞，
-        // 以避免与 brk(堆) 的向上增长发生冲突。
-        //
-        // 我们以 search_limit 作为最高可用地址（栈下方保留 guard），在 [heap_end, search_limit) 内自顶向下找洞。
-        if size > search_limit.saturating_sub(heap_end) {
-            return None;
-        }
-
-        let align_down = |addr: usize, align: usize| addr & !(align - 1);
-
-        let mut gap_end = search_limit;
-        for &(area_start, area_end) in user_areas.iter().rev() {
-            // 只关心 [heap_end, search_limit) 内的区域
-            if area_start >= search_limit {
-                continue;
-            }
-
-            let clamped_end = core::cmp::min(area_end, search_limit);
-            if clamped_end < gap_end {
-                // gap = [clamped_end, gap_end)
-                if gap_end >= heap_end + size {
-                    let lowest_ok = core::cmp::max(clamped_end, heap_end);
-                    if gap_end > lowest_ok && gap_end - lowest_ok >= size {
-                        let candidate = align_down(gap_end - size, align);
-                        if candidate >= lowest_ok {
-                            return Some(VA::from_usize(candidate));
-                        }
-                    }
-                }
-            }
-
-            // 下一段 gap 的上界是当前区域的起点（但也不能低于 heap_end）
-            gap_end = core::cmp::max(area_start, heap_end);
-            if gap_end < heap_end + size {
-                break;
-            }
-        }
-
-        // 检查堆顶与最低 VMA 之间（或完全没有 VMA 时）的最后一个 gap：
-        // gap = [heap_end, gap_end)
-        if gap_end >= heap_end + size {
-            let candidate = align_down(gap_end - size, align);
-            if candidate >= heap_end {
-                return Some(VA::from_usize(candidate));
-            }
-        }
-
-        None
+        occupied.sort_by_key(|o| o.start);
+        occupied
     }
 
     /// 映射一个匿名区域（简化的 mmap）
@@ -219,29 +199,10 @@ impl MemorySpace {
                 .ok_or(PagingError::OutOfMemory)?
                 .as_usize()
         } else {
-            // 用户指定地址
-
-            // 检查是否在有效范围内
-            if hint >= USER_STACK_TOP - USER_STACK_SIZE {
-                return Err(PagingError::InvalidAddress);
-            }
-
-            // 将 hint 向下对齐到页边界（Linux 行为）
-            let aligned_hint = hint & !(crate::config::PAGE_SIZE - 1);
+            // 用户指定地址：向下对齐到页边界（Linux 行为），不低于 mmap_min_addr
+            let aligned_hint = (hint & !(crate::config::PAGE_SIZE - 1)).max(mmap_min_addr());
 
-            // 检查对齐后的区域是否可用
-            let vpn_range_check = VpnRange::new(
-                Vpn::from_addr_floor(VA::from_usize(aligned_hint)),
-                Vpn::from_addr_ceil(VA::from_usize(aligned_hint + len)),
-            );
-
-            // 检查是否与现有区域重叠
-            let has_overlap = self
-                .areas
-                .iter()
-                .any(|a| a.vpn_range().overlaps(&vpn_range_check));
-
-            if has_overlap {
+            if !self.hint_fits(aligned_hint, len) {
                 // hint 不可用，尝试查找附近的空闲区域
                 // 注意：这里简化处理，直接查找任意空闲区域
                 // 更好的实现应该优先查找 hint 附近的区域
@@ -372,6 +333,7 @@ impl MemorySpace {
     /// - 范围必须完全在现有映射区域内
     /// - 如果 mprotect 只应用于区域的一部分，会自动分割区域
     /// - 只能修改 Framed 类型的映射区域
+    /// - 超出区域最大权限时返回 `PermissionDenied`
     pub fn mprotect(
         &mut self,
         start: VA,
@@ -429,6 +391,14 @@ impl MemorySpace {
             }
         }
 
+        // 新权限不能超出区域允许的最大权限（如只读文件的共享映射不能改为可写）
+        let requested = prot & crate::mm::wx_policy::USER_PROT_MASK;
+        for &idx in &affected_indices {
+            if !self.areas[idx].max_permission().contains(requested) {
+                return Err(PagingError::PermissionDenied);
+            }
+        }
+
         // 处理每个受影响的区域
         // 从后往前处理，避免索引失效
         affected_indices.reverse();
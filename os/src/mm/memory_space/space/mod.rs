@@ -1,12 +1,10 @@
 use core::cmp::Ordering;
 
-use crate::arch::platform::MEMORY_END;
-use crate::config::{
-    MAX_USER_HEAP_SIZE, PAGE_SIZE, USER_SIGRETURN_TRAMPOLINE, USER_STACK_SIZE, USER_STACK_TOP,
-};
+use crate::config::{PAGE_SIZE, USER_SIGRETURN_TRAMPOLINE, USER_STACK_SIZE, USER_STACK_TOP};
 use crate::mm::address::{PA, PageNum, Ppn, UsizeConvert, VA, Vpn, VpnRange};
-use crate::mm::memory_space::MmapFile;
-use crate::mm::memory_space::mapping_area::{AreaType, MapType, MappingArea};
+use crate::mm::memory_space::layout::{Occupied, find_top_down};
+use crate::mm::memory_space::mapping_area::{AreaType, MapType, MappingArea, SharedPages};
+use crate::mm::memory_space::{MmapFile, UserLayout, mmap_min_addr};
 use crate::mm::page_table::{ActivePageTableInner, PageTableInner, PagingError, UniversalPTEFlag};
 use crate::sync::SpinLock;
 use crate::{pr_err, pr_warn};
@@ -62,14 +60,22 @@ pub struct MemorySpace {
     /// 此内存空间中的映射区域列表
     areas: Vec<MappingArea>,
 
-    /// 堆的起始地址 (brk 系统调用使用，仅限用户空间)
-    /// 注意：这是堆的固定起始位置，真正的堆顶（current brk）存储在 UserHeap 区域的 vpn_range.end 中
-    heap_start: Option<Vpn>,
+    /// 用户地址空间布局（堆、mmap 区和栈的基址与上限），仅限用户空间
+    /// 注意：布局中的 heap_base 是堆的固定起始位置，真正的堆顶（current brk）存储在 UserHeap 区域的 vpn_range.end 中
+    layout: Option<UserLayout>,
+
+    /// 换出扫描的时钟指针（下一次从该页开始扫描）
+    swap_cursor: Vpn,
 }
 
 mod address_space;
 mod elf_loader;
 mod kernel_space;
+mod ksm_ops;
 mod mmap_ops;
+mod swap_ops;
 #[cfg(test)]
 mod tests;
+mod walk_ops;
+
+pub use walk_ops::{DisplayPteFlags, PageState};
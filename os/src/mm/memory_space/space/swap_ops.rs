@@ -0,0 +1,93 @@
+use super::*;
+
+/// 交换相关操作
+impl MemorySpace {
+    /// 若 `vpn` 所在页被换出或被回收解除映射则重新映射，返回是否建立了映射
+    pub fn swap_in(&mut self, vpn: Vpn) -> bool {
+        if !crate::mm::swap::has_swapped_pages() && !crate::mm::rmap::has_unmapped_shared() {
+            return false;
+        }
+        let MemorySpace {
+            page_table, areas, ..
+        } = self;
+        let Some(area) = areas.iter_mut().find(|a| a.vpn_range().contains(vpn)) else {
+            return false;
+        };
+        match area.fault_in(page_table, vpn) {
+            Ok(swapped) => swapped,
+            Err(e) => {
+                pr_warn!(
+                    "[Swap] Failed to swap in page {:#x}: {:?}",
+                    vpn.start_addr().as_usize(),
+                    e
+                );
+                false
+            }
+        }
+    }
+
+    /// 换入所有被换出的页（swapoff），内存不足时返回 false
+    pub fn swap_in_all(&mut self) -> bool {
+        let MemorySpace {
+            page_table, areas, ..
+        } = self;
+        areas
+            .iter_mut()
+            .filter(|a| a.is_swappable())
+            .all(|a| a.swap_in_all(page_table).is_ok())
+    }
+
+    /// 从时钟指针处开始换出至多 `max` 个匿名页，返回换出的页数
+    pub(crate) fn reclaim_pages(&mut self, max: usize) -> usize {
+        let MemorySpace {
+            page_table,
+            areas,
+            swap_cursor,
+            ..
+        } = self;
+        let mut swappable: Vec<&mut MappingArea> =
+            areas.iter_mut().filter(|a| a.is_swappable()).collect();
+        if swappable.is_empty() {
+            return 0;
+        }
+        swappable.sort_by_key(|a| a.vpn_range().start());
+
+        // 从指针所在区域开始，绕回到开头后扫描到指针为止
+        let start = swappable
+            .iter()
+            .position(|a| a.vpn_range().end() > *swap_cursor)
+            .unwrap_or(0);
+        swappable.rotate_left(start);
+
+        let hand = *swap_cursor;
+        let mut reclaimed = 0;
+        let count = swappable.len();
+        for (i, area) in swappable.into_iter().enumerate() {
+            if reclaimed >= max {
+                break;
+            }
+            let from = if i == 0 && area.vpn_range().contains(hand) {
+                hand
+            } else {
+                area.vpn_range().start()
+            };
+            reclaimed += area.reclaim_from(page_table, from, max - reclaimed, swap_cursor);
+            if i + 1 == count && reclaimed < max {
+                // 扫描完一圈，下次从头开始
+                *swap_cursor = Vpn::from_usize(0);
+            }
+        }
+        reclaimed
+    }
+
+    /// 为回收或迁移解除 `vpn` 到物理页 `ppn` 的映射，返回是否成功
+    pub(crate) fn unmap_page(&mut self, vpn: Vpn, ppn: Ppn) -> bool {
+        let MemorySpace {
+            page_table, areas, ..
+        } = self;
+        areas
+            .iter_mut()
+            .find(|a| a.vpn_range().contains(vpn))
+            .is_some_and(|area| area.unmap_for_reclaim(page_table, vpn, ppn))
+    }
+}
@@ -1182,4 +1182,232 @@ mod memory_space_tests {
 
         println!("  mprotect partial single page test passed");
     });
+
+    // 共享匿名映射：fork 后父子进程映射到同一批物理页
+    test_case!(test_shared_anon_survives_fork, {
+        use crate::mm::memory_space::mapping_area::{AnonSharedPages, SharedPages};
+
+        let mut parent = new_memory_space();
+        let vpn_range = VpnRange::new(Vpn::from_usize(0x3000), Vpn::from_usize(0x3002));
+        let pages = AnonSharedPages::new(2).expect("alloc shared pages failed");
+        kassert!(pages.page_count() == 2);
+        parent
+            .insert_shared_area(vpn_range, UniversalPTEFlag::user_rw(), pages, 0)
+            .expect("insert shared area failed");
+
+        let child = parent.clone_for_fork().expect("fork failed");
+        for page in 0..2 {
+            let va = VA::from_usize((0x3000 + page) * PAGE_SIZE);
+            let parent_pa = parent.translate(va);
+            kassert!(parent_pa.is_some());
+            kassert!(child.translate(va) == parent_pa);
+        }
+    });
+
+    // 设备内存：拒绝 RAM，不记录反向映射，mprotect 后仍为设备内存
+    test_case!(test_phys_pages_device_mapping, {
+        use crate::mm::frame_allocator::alloc_frame;
+        use crate::mm::memory_map::memory_map;
+        use crate::mm::memory_space::mapping_area::{PhysPages, SharedPages};
+        use crate::mm::rmap;
+
+        let frame = alloc_frame().expect("alloc frame failed");
+        let ram = PhysPages::new(frame.ppn(), 1);
+        kassert!(ram.is_device_memory());
+        kassert!(ram.ppn_at(0).is_none());
+
+        // 最后一段 RAM 之后的地址不是内存
+        let end = memory_map().memory().last().unwrap().end;
+        let mmio_ppn = Ppn::from_usize(end.div_ceil(PAGE_SIZE));
+        let mmio = PhysPages::new(mmio_ppn, 2);
+        kassert!(mmio.ppn_at(1) == Some(Ppn::from_usize(mmio_ppn.as_usize() + 1)));
+        kassert!(mmio.ppn_at(2).is_none());
+
+        let mut ms = new_memory_space();
+        let vpn_range = VpnRange::new(Vpn::from_usize(0x6000), Vpn::from_usize(0x6002));
+        let flags = UniversalPTEFlag::user_rw() | UniversalPTEFlag::DEVICE;
+        ms.insert_shared_area(vpn_range, flags, mmio, 0)
+            .expect("insert device area failed");
+        let va = vpn_range.start().start_addr();
+        kassert!(ms.translate(va) == Some(mmio_ppn.start_addr()));
+        kassert!(rmap::mapcount(mmio_ppn) == 0);
+
+        ms.mprotect(va, PAGE_SIZE, UniversalPTEFlag::user_read())
+            .expect("mprotect failed");
+        let area = ms.find_area(vpn_range.start()).unwrap();
+        kassert!(area.permission().contains(UniversalPTEFlag::DEVICE));
+    });
+
+    test_case!(test_mprotect_respects_max_permission, {
+        let mut ms = new_memory_space();
+        let vpn_range = VpnRange::new(Vpn::from_usize(0x4000), Vpn::from_usize(0x4002));
+        ms.insert_framed_area(
+            vpn_range,
+            AreaType::UserMmap,
+            UniversalPTEFlag::user_read(),
+            None,
+            None,
+        )
+        .expect("Failed to insert area");
+        ms.find_area_mut(vpn_range.start())
+            .unwrap()
+            .set_max_permission(UniversalPTEFlag::user_rx());
+
+        let start = vpn_range.start().start_addr();
+        // 不能超出最大权限，拆分后的子区域也继承这一限制
+        kassert!(
+            ms.mprotect(start, PAGE_SIZE, UniversalPTEFlag::user_rw())
+                == Err(PagingError::PermissionDenied)
+        );
+        kassert!(
+            ms.mprotect(start, PAGE_SIZE, UniversalPTEFlag::user_rx())
+                .is_ok()
+        );
+        kassert!(
+            ms.mprotect(start, 2 * PAGE_SIZE, UniversalPTEFlag::user_rw())
+                == Err(PagingError::PermissionDenied)
+        );
+    });
+
+    test_case!(test_ksm_merge_and_unmerge, {
+        let mut ms = new_memory_space();
+        let first = VpnRange::new(Vpn::from_usize(0x5000), Vpn::from_usize(0x5001));
+        let second = VpnRange::new(Vpn::from_usize(0x5010), Vpn::from_usize(0x5011));
+        let data = [0x42u8; 64];
+        for range in [first, second] {
+            ms.insert_framed_area(
+                range,
+                AreaType::UserMmap,
+                UniversalPTEFlag::user_read(),
+                Some(&data),
+                None,
+            )
+            .expect("Failed to insert area");
+        }
+
+        let first_ppn = ms
+            .ksm_page(first.start())
+            .expect("first page not mergeable");
+        let second_ppn = ms
+            .ksm_page(second.start())
+            .expect("second page not mergeable");
+        kassert!(first_ppn != second_ppn);
+
+        let frame = ms.ksm_promote(first.start()).expect("promote failed");
+        kassert!(frame.ppn() == first_ppn);
+        kassert!(ms.ksm_merge(second.start(), &frame));
+        let first_va = first.start().start_addr();
+        let second_va = second.start().start_addr();
+        kassert!(ms.translate(second_va) == ms.translate(first_va));
+        // 合并页不再是候选页
+        kassert!(ms.ksm_page(second.start()).is_none());
+
+        // 变为可写时复制出私有帧，内容保持不变
+        ms.mprotect(second_va, PAGE_SIZE, UniversalPTEFlag::user_rw())
+            .expect("mprotect failed");
+        kassert!(ms.translate(second_va) != ms.translate(first_va));
+        kassert!(ms.read_u64_at(second_va.as_usize()) == Ok(0x4242_4242_4242_4242));
+    });
+
+    // 用户地址空间布局：mmap 自顶向下分配并与栈、堆保持间隔
+    test_case!(test_user_layout_allocation, {
+        use crate::config::{USER_STACK_SIZE, USER_STACK_TOP};
+
+        let mut ms = new_memory_space();
+        kassert!(ms.find_free_region(PAGE_SIZE, PAGE_SIZE).is_none());
+
+        ms.set_heap_start(Vpn::from_usize(0x100));
+        let layout = *ms.layout().expect("layout not set");
+        let stack = VpnRange::new(
+            Vpn::from_addr_floor(VA::from_usize(USER_STACK_TOP - USER_STACK_SIZE)),
+            Vpn::from_addr_ceil(VA::from_usize(USER_STACK_TOP)),
+        );
+        ms.insert_reserved_area(
+            stack,
+            AreaType::UserStack,
+            UniversalPTEFlag::user_rw(),
+            None,
+        )
+        .expect("Failed to insert stack");
+
+        // 第一个映射紧贴 mmap 区上限，第二个紧接在它下方
+        let first = ms
+            .find_free_region(2 * PAGE_SIZE, PAGE_SIZE)
+            .expect("no free region")
+            .as_usize();
+        kassert!(first == layout.mmap_base - 2 * PAGE_SIZE);
+        let range = VpnRange::new(
+            Vpn::from_addr_floor(VA::from_usize(first)),
+            Vpn::from_addr_floor(VA::from_usize(first + 2 * PAGE_SIZE)),
+        );
+        ms.insert_reserved_area(range, AreaType::UserMmap, UniversalPTEFlag::user_rw(), None)
+            .expect("Failed to insert mmap area");
+        let second = ms.find_free_region(PAGE_SIZE, PAGE_SIZE);
+        kassert!(second == Some(VA::from_usize(first - PAGE_SIZE)));
+
+        // hint 不能落在栈保护区、堆上方的间隔或 mmap_min_addr 之下
+        kassert!(!ms.hint_fits(layout.mmap_base, PAGE_SIZE));
+        kassert!(!ms.hint_fits(0, PAGE_SIZE));
+        kassert!(ms.hint_fits(layout.heap_base + layout.guard_gap, PAGE_SIZE));
+
+        // 堆创建后，紧贴堆顶的 hint 被拒绝
+        ms.brk(VA::from_usize(layout.heap_base + PAGE_SIZE))
+            .expect("brk failed");
+        kassert!(!ms.hint_fits(layout.heap_base + PAGE_SIZE, PAGE_SIZE));
+        kassert!(ms.hint_fits(layout.heap_base + 2 * PAGE_SIZE, PAGE_SIZE));
+
+        // MAP_FIXED 不能覆盖栈和堆，可以覆盖普通 mmap 映射
+        kassert!(ms.overlaps_critical(stack));
+        kassert!(ms.overlaps_critical(VpnRange::new(
+            Vpn::from_usize(0x100),
+            Vpn::from_usize(0x101)
+        )));
+        kassert!(!ms.overlaps_critical(range));
+    });
+
+    // 页表遍历：驻留页报告物理页号和标志，保留区域没有页表项
+    test_case!(test_walk_pages, {
+        let mut ms = new_memory_space();
+        let framed = VpnRange::new(Vpn::from_usize(0x400), Vpn::from_usize(0x403));
+        ms.insert_framed_area(
+            framed,
+            AreaType::UserData,
+            UniversalPTEFlag::user_rw(),
+            None,
+            None,
+        )
+        .expect("Failed to insert framed area");
+        let reserved = VpnRange::new(Vpn::from_usize(0x500), Vpn::from_usize(0x504));
+        ms.insert_reserved_area(
+            reserved,
+            AreaType::UserMmap,
+            UniversalPTEFlag::user_rw(),
+            None,
+        )
+        .expect("Failed to insert reserved area");
+
+        let mut pages = 0;
+        ms.walk_pages(framed, |run| {
+            pages += run.pages;
+            match run.first {
+                PageState::Present { ppn, flags } => {
+                    kassert!(flags.contains(UniversalPTEFlag::user_rw()));
+                    kassert!(ms.translate(run.start.start_addr()) == Some(ppn.start_addr()));
+                }
+                _ => kassert!(false),
+            }
+        });
+        kassert!(pages == 3);
+
+        let mut runs = 0;
+        ms.walk_pages(reserved, |run| {
+            runs += 1;
+            kassert!(run.first == PageState::NotPresent);
+            kassert!(run.start == reserved.start() && run.end() == reserved.end());
+            kassert!(alloc::format!("{}", run) == "0000000000500000-0000000000504000      4 none");
+        });
+        kassert!(runs == 1);
+
+        kassert!(alloc::format!("{}", DisplayPteFlags(UniversalPTEFlag::user_rx())) == "vr-xu---");
+    });
 }
@@ -0,0 +1,155 @@
+use core::fmt;
+
+use super::*;
+
+/// 单个虚拟页的翻译结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageState {
+    /// 已映射到物理页
+    Present { ppn: Ppn, flags: UniversalPTEFlag },
+    /// 已换出，记录交换槽号
+    Swapped(usize),
+    /// 没有页表项（如 PROT_NONE 区域）
+    NotPresent,
+}
+
+impl PageState {
+    /// `next` 能否作为下一页并入同一段：标志相同且物理页号或交换槽号连续
+    fn continues(&self, next: &PageState) -> bool {
+        match (self, next) {
+            (
+                PageState::Present { ppn, flags },
+                PageState::Present {
+                    ppn: next_ppn,
+                    flags: next_flags,
+                },
+            ) => flags == next_flags && next_ppn.as_usize() == ppn.as_usize() + 1,
+            (PageState::Swapped(slot), PageState::Swapped(next_slot)) => *next_slot == slot + 1,
+            (PageState::NotPresent, PageState::NotPresent) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for PageState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageState::Present { ppn, flags } => {
+                write!(f, "pfn {:#x} {}", ppn.as_usize(), DisplayPteFlags(*flags))
+            }
+            PageState::Swapped(slot) => write!(f, "swap {}", slot),
+            PageState::NotPresent => f.write_str("none"),
+        }
+    }
+}
+
+/// 页表项标志的简写：依次为 `v r w x u g a d`，未设置的位显示为 `-`，设备内存追加 ` dev`
+pub struct DisplayPteFlags(pub UniversalPTEFlag);
+
+impl fmt::Display for DisplayPteFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const BITS: [(UniversalPTEFlag, char); 8] = [
+            (UniversalPTEFlag::VALID, 'v'),
+            (UniversalPTEFlag::READABLE, 'r'),
+            (UniversalPTEFlag::WRITEABLE, 'w'),
+            (UniversalPTEFlag::EXECUTABLE, 'x'),
+            (UniversalPTEFlag::USER_ACCESSIBLE, 'u'),
+            (UniversalPTEFlag::GLOBAL, 'g'),
+            (UniversalPTEFlag::ACCESSED, 'a'),
+            (UniversalPTEFlag::DIRTY, 'd'),
+        ];
+        for (bit, c) in BITS {
+            fmt::Write::write_char(f, if self.0.contains(bit) { c } else { '-' })?;
+        }
+        if self.0.contains(UniversalPTEFlag::DEVICE) {
+            f.write_str(" dev")?;
+        }
+        Ok(())
+    }
+}
+
+/// 一段虚拟地址连续、翻译结果也连续的页
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRun {
+    /// 起始虚拟页
+    pub start: Vpn,
+    /// 页数
+    pub pages: usize,
+    /// 第一页的翻译结果，其后各页的物理页号或交换槽号依次加一
+    pub first: PageState,
+    last: PageState,
+}
+
+impl PageRun {
+    fn new(vpn: Vpn, state: PageState) -> Self {
+        Self {
+            start: vpn,
+            pages: 1,
+            first: state,
+            last: state,
+        }
+    }
+
+    /// 尝试把紧接在本段之后的一页并入本段
+    fn extend(&mut self, vpn: Vpn, state: PageState) -> bool {
+        if vpn.as_usize() != self.start.as_usize() + self.pages || !self.last.continues(&state) {
+            return false;
+        }
+        self.pages += 1;
+        self.last = state;
+        true
+    }
+
+    /// 结束虚拟页（不含）
+    pub fn end(&self) -> Vpn {
+        Vpn::from_usize(self.start.as_usize() + self.pages)
+    }
+}
+
+impl fmt::Display for PageRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:016x}-{:016x} {:>6} {}",
+            self.start.start_addr().as_usize(),
+            self.end().start_addr().as_usize(),
+            self.pages,
+            self.first
+        )
+    }
+}
+
+impl MemorySpace {
+    /// 查询单个虚拟页的翻译结果
+    pub fn page_state(&self, vpn: Vpn) -> PageState {
+        match self.page_table.walk(vpn) {
+            Ok((ppn, _, flags)) => PageState::Present { ppn, flags },
+            Err(_) => self
+                .page_table
+                .swap_entry(vpn)
+                .map_or(PageState::NotPresent, PageState::Swapped),
+        }
+    }
+
+    /// 逐页遍历 `range` 的页表，把结果合并成连续的段依次交给 `f`
+    ///
+    /// 不分配内存，可在 SysRq 等不能分配的上下文中使用。
+    pub fn walk_pages(&self, range: VpnRange, mut f: impl FnMut(&PageRun)) {
+        let mut run: Option<PageRun> = None;
+        for vpn in range.start().as_usize()..range.end().as_usize() {
+            let vpn = Vpn::from_usize(vpn);
+            let state = self.page_state(vpn);
+            if let Some(r) = run.as_mut()
+                && r.extend(vpn, state)
+            {
+                continue;
+            }
+            if let Some(r) = run.replace(PageRun::new(vpn, state)) {
+                f(&r);
+            }
+        }
+        if let Some(r) = run {
+            f(&r);
+        }
+    }
+}
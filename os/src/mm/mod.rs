@@ -8,31 +8,34 @@
 //! - [`address`]：地址和页号抽象。
 //! - [`frame_allocator`]：物理帧分配。
 //! - [`mod@global_allocator`]：全局堆分配器。
+//! - [`ksm`]：同页合并。
+//! - [`memory_map`]：启动时从设备树得到的物理内存布局。
 //! - [`memory_space`]：内存空间管理。
 //! - [`page_table`]：页表抽象和实现（与架构无关）。
+//! - [`rmap`]：用户物理页的反向映射。
+//! - [`swap`]：交换文件与内存回收。
+//! - [`wx_policy`]：用户映射的 W^X 策略。
 
 pub mod address;
 pub mod frame_allocator;
 pub mod global_allocator;
+pub mod ksm;
+pub mod memory_map;
 pub mod memory_space;
 pub mod page_table;
+pub mod rmap;
+pub mod swap;
+pub mod wx_policy;
 
-pub use frame_allocator::init_frame_allocator;
+pub use frame_allocator::init_frame_allocator_regions;
 #[cfg(feature = "alloc")]
 pub use global_allocator::init_heap;
 
-use crate::arch::platform::MEMORY_END;
-use crate::config::PAGE_SIZE;
 use crate::mm::address::PA;
 use crate::mm::address::{Ppn, UsizeConvert};
 use crate::sync::SpinLock;
 use alloc::sync::Arc;
 
-unsafe extern "C" {
-    // 链接器脚本中定义的内核结束地址
-    fn ekernel();
-}
-
 /// 初始化内存管理子系统
 ///
 /// 此函数执行所有内存管理组件的初始化工作：
@@ -45,57 +48,34 @@ unsafe extern "C" {
 pub fn init() -> alloc::sync::Arc<crate::sync::SpinLock<memory_space::MemorySpace>> {
     // 1. 初始化物理帧分配器
 
-    // ekernel 是一个虚拟地址，需要转换为物理地址，以确定可分配物理内存的起始点。
-    let ekernel_paddr =
-        unsafe { crate::arch::va_to_pa(crate::arch::address::VA::from_usize(ekernel as usize)) };
-
-    // 计算页对齐后的物理内存起始地址。
-    // 分配器将管理 [start, end) 范围内的内存。
-    let start = PA::from_usize(ekernel_paddr.as_usize().div_ceil(PAGE_SIZE) * PAGE_SIZE); // 页对齐
-
-    // 物理内存末端：优先采用设备树报告的真实 DRAM 范围，否则回退到编译期 MEMORY_END。
-    //
-    // 评测机通常给 1–2GB（本地复现甚至 -m 4G），QEMU 把 DTB 放在 RAM 顶端。若帧分配器只
-    // 覆盖 128MB（MEMORY_END），既无法管理评测所需的大内存，DTB 也会落在可映射范围之外。
-    // 必须与 kernel_space 的物理内存直映射上限保持一致（见 map_kernel_space）。
-    let dram_end = match crate::device::device_tree::dram_info() {
-        Some((dram_start, dram_size)) => {
-            let e = dram_start.saturating_add(dram_size);
-            crate::println!(
-                "[MM] DRAM from device tree: {:#x} - {:#x} (size {:#x})",
-                dram_start,
-                e,
-                dram_size
-            );
-            e
-        }
-        None => {
-            crate::println!(
-                "[MM] DRAM info unavailable, using MEMORY_END {:#x}",
-                MEMORY_END
-            );
-            MEMORY_END
+    // 从设备树建立内存布局。帧分配器管理内核映像之后的各个 RAM 区域，与 kernel_space
+    // 的物理内存直映射范围一致（见 map_kernel_space）；其中的 DTB、initramfs 和固件
+    // 保留区域不参与分配。
+    let map = memory_map::init();
+    let windows = memory_map::kernel_windows();
+    memory_map::print_summary(&map, &windows);
+
+    let mut ranges = [(PA::from_usize(0), PA::from_usize(0)); frame_allocator::MAX_FRAME_REGIONS];
+    for (slot, window) in ranges.iter_mut().zip(windows.as_slice()) {
+        *slot = (PA::from_usize(window.start), PA::from_usize(window.end));
+    }
+    init_frame_allocator_regions(&ranges);
+    for window in windows.as_slice() {
+        for (range, _) in map.reserved() {
+            let range = range.intersect(window);
+            if !range.is_empty() {
+                frame_allocator::reserve_frames(
+                    PA::from_usize(range.start),
+                    PA::from_usize(range.end),
+                );
+            }
         }
-    };
-
-    // LoongArch virt 在 DTB 中报告超大 RAM 窗口，且 LoongArch 通过 DMW 硬件直映射访问物理
-    // 内存（不经页表）；用 4K 页映射多 GB 既极慢又无意义。把帧分配器范围 cap 在 1GiB，
-    // 与 map_kernel_space 的直映射上限一致。RISC-V 不设上限，覆盖全部 DRAM。
-    let end_usize = {
-        #[cfg(target_arch = "loongarch64")]
-        {
-            const MAX_USABLE_PHYS_BYTES: usize = 1024 * 1024 * 1024; // 1GiB
-            dram_end.min(start.as_usize().saturating_add(MAX_USABLE_PHYS_BYTES))
-        }
-        #[cfg(not(target_arch = "loongarch64"))]
-        {
-            dram_end
-        }
-    };
-    let end = PA::from_usize(end_usize);
-
-    // 初始化物理帧分配器
-    init_frame_allocator(start, end);
+    }
+    crate::println!(
+        "[MM] Zones: DMA32 {} frames, NORMAL {} frames",
+        frame_allocator::get_zone_frames(frame_allocator::Zone::Dma32).0,
+        frame_allocator::get_zone_frames(frame_allocator::Zone::Normal).0
+    );
 
     // 2. 初始化堆分配器
     #[cfg(feature = "alloc")]
@@ -120,6 +100,21 @@ pub fn init() -> alloc::sync::Arc<crate::sync::SpinLock<memory_space::MemorySpac
     space
 }
 
+/// 登记内存管理的统计指标（见 [`crate::kernel::kstat`]）
+pub fn register_metrics() {
+    use crate::kernel::kstat::{Unit, register_probe};
+
+    register_probe("nr_total_pages", Unit::Pages, || {
+        frame_allocator::get_total_frames() as u64
+    });
+    register_probe("nr_free_pages", Unit::Pages, || {
+        frame_allocator::get_free_frames() as u64
+    });
+    #[cfg(feature = "alloc")]
+    global_allocator::register_metrics();
+    swap::register_metrics();
+}
+
 /// 激活指定的地址空间
 ///
 /// 通过将根页表（Page Table Root）的物理页号写入特定的寄存器，
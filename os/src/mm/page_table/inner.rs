@@ -57,4 +57,13 @@ where
     fn update_flags(&mut self, vpn: Vpn, flags: UniversalPTEFlag) -> PagingResult<()>;
 
     fn walk(&self, vpn: Vpn) -> PagingResult<(Ppn, PageSize, UniversalPTEFlag)>;
+
+    /// 把已映射的叶子页表项改写为指向交换槽 `slot` 的交换项，并刷新 TLB
+    fn set_swap_entry(&mut self, vpn: Vpn, slot: usize) -> PagingResult<()>;
+
+    /// 叶子页表项中的交换槽号；该页已映射或未映射时返回 None
+    fn swap_entry(&self, vpn: Vpn) -> Option<usize>;
+
+    /// 清除叶子上的交换项
+    fn clear_swap_entry(&mut self, vpn: Vpn) -> PagingResult<()>;
 }
@@ -20,6 +20,9 @@ bitflags::bitflags! {
             const GLOBAL = 1 << 5;              // 指示该页是否为全局页（Global）
             const ACCESSED = 1 << 6;            // 指示该页是否已被访问
             const DIRTY = 1 << 7;               // 指示该页是否已被写入（修改）
+
+            // ---- 架构相关的扩展标志 ----
+            const DEVICE = 1 << 8;              // 设备内存，不经过缓存（RISC-V 由 PMA 决定，忽略此位）
     }
 }
 
@@ -92,6 +95,11 @@ pub trait PageTableEntry {
     /// 检查页表项是否为空
     fn is_empty(&self) -> bool;
 
+    /// 创建指向交换槽 `slot` 的交换项（有效位为 0，访问时触发缺页）
+    fn new_swap(slot: usize) -> Self;
+    /// 交换项中的交换槽号；不是交换项时返回 None
+    fn swap_slot(&self) -> Option<usize>;
+
     /// 获取页表项中存储的物理页号（Ppn）
     fn ppn(&self) -> Ppn;
     /// 获取页表项的通用标志
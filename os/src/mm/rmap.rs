@@ -0,0 +1,118 @@
+//! 反向映射（rmap）
+//!
+//! 记录每个用户物理页被哪些地址空间的哪个虚拟页映射，供内存回收和页迁移找到引用
+//! 同一物理页的全部页表项。私有匿名页只有一个映射；共享匿名映射、SysV 共享内存、
+//! tmpfs/memfd 文件页等共享页可以同时出现在多个地址空间中。
+//!
+//! 地址空间以根页表的物理页号标识。映射区域在建立和解除用户页映射、fork、换入换出
+//! 时维护本表；地址空间销毁时一并清除它的所有条目。
+
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::mm::address::{Ppn, Vpn};
+use crate::sync::SpinLock;
+
+/// 一个映射：地址空间（根页表 PPN）与虚拟页号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RmapEntry {
+    /// 地址空间根页表的物理页号
+    pub space: Ppn,
+    /// 映射所在的虚拟页号
+    pub vpn: Vpn,
+}
+
+static RMAP: SpinLock<BTreeMap<Ppn, Vec<RmapEntry>>> = SpinLock::new(BTreeMap::new());
+
+/// 是否有共享页被 [`try_to_unmap`] 解除过映射（之后的访问需要通过缺页重建映射）
+static SHARED_UNMAPPED: AtomicBool = AtomicBool::new(false);
+
+/// 记录 `space` 中 `vpn` 映射到物理页 `ppn`
+pub(crate) fn add(ppn: Ppn, space: Ppn, vpn: Vpn) {
+    RMAP.lock()
+        .entry(ppn)
+        .or_default()
+        .push(RmapEntry { space, vpn });
+}
+
+/// 删除 `space` 中 `vpn` 到物理页 `ppn` 的映射记录
+pub(crate) fn remove(ppn: Ppn, space: Ppn, vpn: Vpn) {
+    let mut rmap = RMAP.lock();
+    if let Some(entries) = rmap.get_mut(&ppn) {
+        entries.retain(|e| e.space != space || e.vpn != vpn);
+        if entries.is_empty() {
+            rmap.remove(&ppn);
+        }
+    }
+}
+
+/// 物理页 `ppn` 的全部映射
+pub fn mappings(ppn: Ppn) -> Vec<RmapEntry> {
+    RMAP.lock().get(&ppn).cloned().unwrap_or_default()
+}
+
+/// 物理页 `ppn` 被映射的次数
+pub fn mapcount(ppn: Ppn) -> usize {
+    RMAP.lock().get(&ppn).map_or(0, Vec::len)
+}
+
+pub(crate) fn mark_shared_unmapped() {
+    SHARED_UNMAPPED.store(true, Ordering::Release);
+}
+
+/// 是否可能存在被解除映射、需要在缺页时重建的共享页
+pub(crate) fn has_unmapped_shared() -> bool {
+    SHARED_UNMAPPED.load(Ordering::Acquire)
+}
+
+/// 解除物理页 `ppn` 的全部用户映射
+///
+/// 共享页直接解除页表项，之后的访问通过缺页从共享页来源重新映射；
+/// 私有匿名页写入交换区。持锁中的地址空间会被跳过，返回是否已没有任何映射。
+pub fn try_to_unmap(ppn: Ppn) -> bool {
+    let entries = mappings(ppn);
+    if entries.is_empty() {
+        return true;
+    }
+
+    let spaces = crate::mm::swap::user_spaces(true);
+    for entry in entries {
+        let Some(space) = spaces.iter().find(|s| {
+            s.try_lock()
+                .is_some_and(|guard| guard.root_ppn() == entry.space)
+        }) else {
+            continue;
+        };
+        if let Some(mut guard) = space.try_lock() {
+            guard.unmap_page(entry.vpn, ppn);
+        }
+    }
+    mapcount(ppn) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mm::address::UsizeConvert;
+    use crate::{kassert, test_case};
+
+    test_case!(test_rmap_add_remove, {
+        // 使用不会被真实分配的页号，避免与其他测试冲突
+        let ppn = Ppn::from_usize(usize::MAX >> 20);
+        let space_a = Ppn::from_usize(1);
+        let space_b = Ppn::from_usize(2);
+        add(ppn, space_a, Vpn::from_usize(0x10));
+        add(ppn, space_b, Vpn::from_usize(0x20));
+        kassert!(mapcount(ppn) == 2);
+        kassert!(mappings(ppn).contains(&RmapEntry {
+            space: space_b,
+            vpn: Vpn::from_usize(0x20),
+        }));
+
+        remove(ppn, space_a, Vpn::from_usize(0x10));
+        kassert!(mapcount(ppn) == 1);
+        remove(ppn, space_b, Vpn::from_usize(0x20));
+        kassert!(mapcount(ppn) == 0);
+        kassert!(mappings(ppn).is_empty());
+    });
+}
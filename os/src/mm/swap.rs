@@ -0,0 +1,466 @@
+//! 交换空间
+//!
+//! `swapon` 把文件系统上的交换文件（`mkswap` 生成的 Linux 交换头）登记为交换区，
+//! 交换槽号即交换文件内的页号，第 0 页是交换头，不参与分配。同一时刻只支持一个交换区。
+//!
+//! # 换出
+//!
+//! 物理帧分配失败时 [`reclaim`] 按时钟（second-chance）算法扫描各进程的匿名页：
+//! 访问位置位的页清除访问位后跳过，未被访问的页写入交换槽、释放物理帧，
+//! 页表项改写为交换项（有效位为 0，槽号存放在 PPN 字段）。
+//! LoongArch 没有硬件访问位，时钟算法退化为按地址顺序换出。
+//!
+//! # 换入
+//!
+//! 用户态访问交换项触发缺页，或内核在拷贝用户数据前校验地址时发现交换项，
+//! 由 [`MemorySpace::swap_in`] 读回数据并重新映射。
+
+use alloc::{string::String, sync::Arc, vec, vec::Vec};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::config::PAGE_SIZE;
+use crate::kernel::kstat::{self, Counter, Unit};
+use crate::kernel::{TASK_MANAGER, TaskManagerTrait};
+use crate::mm::address::{PageNum, VA, Vpn};
+use crate::mm::memory_space::MemorySpace;
+use crate::sync::SpinLock;
+use crate::vfs::FsError;
+
+/// 交换头签名，位于第 0 页末尾
+const SWAP_MAGIC: &[u8; 10] = b"SWAPSPACE2";
+/// 交换头信息区（version、last_page、nr_badpages）的偏移
+const SWAP_INFO_OFFSET: usize = 1024;
+/// 坏页列表的偏移
+const SWAP_BADPAGES_OFFSET: usize = 1536;
+
+/// 一次回收的目标页数
+pub const SWAP_CLUSTER: usize = 32;
+
+/// 交换文件的存储后端
+///
+/// 由文件系统在 `swapon` 时创建：预先解析好文件的物理块，读写直接访问块设备，
+/// 不经过文件系统锁，因而可以在内存回收路径上使用。
+pub trait SwapBacking: Send + Sync {
+    /// 交换文件包含的完整页数
+    fn page_count(&self) -> usize;
+
+    /// 读取第 `page` 页到 `buf`（长度为 `PAGE_SIZE`）
+    fn read_page(&self, page: usize, buf: &mut [u8]) -> bool;
+
+    /// 把 `buf`（长度为 `PAGE_SIZE`）写入第 `page` 页
+    fn write_page(&self, page: usize, buf: &[u8]) -> bool;
+}
+
+/// 交换头描述的可用页
+#[derive(Debug, PartialEq, Eq)]
+pub struct SwapHeader {
+    /// 最后一个可用页的页号
+    pub last_page: usize,
+    /// 坏页页号
+    pub bad_pages: Vec<usize>,
+}
+
+/// 解析交换文件第 0 页中的 Linux 交换头（version 1）
+pub fn parse_swap_header(page: &[u8]) -> Option<SwapHeader> {
+    if page.len() < PAGE_SIZE || &page[PAGE_SIZE - SWAP_MAGIC.len()..PAGE_SIZE] != SWAP_MAGIC {
+        return None;
+    }
+    let word = |off: usize| u32::from_le_bytes(page[off..off + 4].try_into().unwrap()) as usize;
+    if word(SWAP_INFO_OFFSET) != 1 {
+        return None;
+    }
+    let last_page = word(SWAP_INFO_OFFSET + 4);
+    let nr_bad = word(SWAP_INFO_OFFSET + 8);
+    if nr_bad > (PAGE_SIZE - SWAP_MAGIC.len() - SWAP_BADPAGES_OFFSET) / 4 {
+        return None;
+    }
+    let bad_pages = (0..nr_bad)
+        .map(|i| word(SWAP_BADPAGES_OFFSET + i * 4))
+        .collect();
+    Some(SwapHeader {
+        last_page,
+        bad_pages,
+    })
+}
+
+/// 当前交换区的统计信息（`/proc/swaps`、`/proc/meminfo`、`sysinfo`）
+#[derive(Debug, Clone)]
+pub struct SwapInfo {
+    /// 交换文件路径
+    pub path: String,
+    /// 可用槽位总数
+    pub total: usize,
+    /// 已使用槽位数
+    pub used: usize,
+}
+
+struct SwapArea {
+    path: String,
+    backing: Arc<dyn SwapBacking>,
+    /// 槽位占用表，下标即交换文件页号；交换头与坏页预先标记为占用
+    used: Vec<bool>,
+    total: usize,
+    inuse: usize,
+    /// 下次分配的起始位置
+    cursor: usize,
+    /// swapoff 进行中，不再分配新槽位
+    draining: bool,
+}
+
+impl SwapArea {
+    fn alloc(&mut self) -> Option<usize> {
+        if self.draining || self.inuse == self.total {
+            return None;
+        }
+        let len = self.used.len();
+        let slot = (0..len)
+            .map(|i| (self.cursor + i) % len)
+            .find(|&i| !self.used[i])?;
+        self.used[slot] = true;
+        self.inuse += 1;
+        self.cursor = slot + 1;
+        Some(slot)
+    }
+
+    fn free(&mut self, slot: usize) {
+        if let Some(used) = self.used.get_mut(slot)
+            && *used
+        {
+            *used = false;
+            self.inuse -= 1;
+        }
+    }
+}
+
+static SWAP_AREA: SpinLock<Option<SwapArea>> = SpinLock::new(None);
+
+/// 已分配的交换槽数，用于在没有页被换出时跳过页表检查
+static SWAPPED_PAGES: AtomicUsize = AtomicUsize::new(0);
+
+/// 防止回收路径重入（回收本身可能再次分配帧）
+static RECLAIMING: AtomicBool = AtomicBool::new(false);
+
+/// 时钟指针：下一轮回收从第几个地址空间开始
+static CLOCK_HAND: AtomicUsize = AtomicUsize::new(0);
+
+/// 写入交换区的页数
+static PSWPOUT: Counter = Counter::new();
+/// 从交换区读回的页数
+static PSWPIN: Counter = Counter::new();
+
+/// 登记交换相关的统计指标
+pub fn register_metrics() {
+    kstat::register_probe("nr_swap_pages", Unit::Pages, || {
+        swap_info().map_or(0, |info| info.total as u64)
+    });
+    kstat::register_probe("nr_swap_free_pages", Unit::Pages, || {
+        swap_info().map_or(0, |info| (info.total - info.used) as u64)
+    });
+    kstat::register_counter("pswpin", Unit::Count, &PSWPIN);
+    kstat::register_counter("pswpout", Unit::Count, &PSWPOUT);
+}
+
+/// 是否有页位于交换区中
+pub fn has_swapped_pages() -> bool {
+    SWAPPED_PAGES.load(Ordering::Acquire) != 0
+}
+
+/// 当前交换区的统计信息
+pub fn swap_info() -> Option<SwapInfo> {
+    SWAP_AREA.lock().as_ref().map(|area| SwapInfo {
+        path: area.path.clone(),
+        total: area.total,
+        used: area.inuse,
+    })
+}
+
+/// 启用交换区，返回可用槽位数
+///
+/// `path` 仅用于 `swapoff` 匹配和 `/proc/swaps` 显示。
+pub fn swapon(path: String, backing: Arc<dyn SwapBacking>) -> Result<usize, FsError> {
+    if SWAP_AREA.lock().is_some() {
+        return Err(FsError::Busy);
+    }
+
+    let mut header = vec![0u8; PAGE_SIZE];
+    if !backing.read_page(0, &mut header) {
+        return Err(FsError::IoError);
+    }
+    let header = parse_swap_header(&header).ok_or(FsError::InvalidArgument)?;
+    let pages = backing.page_count().min(header.last_page.saturating_add(1));
+    if pages < 2 {
+        return Err(FsError::InvalidArgument);
+    }
+
+    let mut used = vec![false; pages];
+    used[0] = true;
+    for bad in header.bad_pages {
+        if let Some(slot) = used.get_mut(bad) {
+            *slot = true;
+        }
+    }
+    let total = used.iter().filter(|u| !**u).count();
+    if total == 0 {
+        return Err(FsError::InvalidArgument);
+    }
+
+    let mut guard = SWAP_AREA.lock();
+    if guard.is_some() {
+        return Err(FsError::Busy);
+    }
+    *guard = Some(SwapArea {
+        path,
+        backing,
+        used,
+        total,
+        inuse: 0,
+        cursor: 1,
+        draining: false,
+    });
+    Ok(total)
+}
+
+/// 停用交换区：先把所有被换出的页换回内存
+pub fn swapoff(path: &str) -> Result<(), FsError> {
+    {
+        let mut guard = SWAP_AREA.lock();
+        let area = guard
+            .as_mut()
+            .filter(|a| a.path == path)
+            .ok_or(FsError::InvalidArgument)?;
+        if area.draining {
+            return Err(FsError::Busy);
+        }
+        area.draining = true;
+    }
+
+    let mut result = Ok(());
+    for space in user_spaces(false) {
+        if !space.lock().swap_in_all() {
+            result = Err(FsError::NoMemory);
+            break;
+        }
+    }
+
+    let mut guard = SWAP_AREA.lock();
+    let area = guard.as_mut().expect("swap area vanished during swapoff");
+    if result.is_ok() && area.inuse != 0 {
+        // 仍有槽位属于已经不在任务列表中的地址空间
+        result = Err(FsError::Busy);
+    }
+    if result.is_err() {
+        area.draining = false;
+        return result;
+    }
+    *guard = None;
+    Ok(())
+}
+
+/// 分配一个交换槽
+///
+/// 回收路径可能在任意上下文中调用，只尝试获取锁。
+pub(crate) fn alloc_slot() -> Option<usize> {
+    let slot = SWAP_AREA.try_lock()?.as_mut()?.alloc()?;
+    SWAPPED_PAGES.fetch_add(1, Ordering::AcqRel);
+    Some(slot)
+}
+
+/// 释放交换槽
+pub(crate) fn free_slot(slot: usize) {
+    if let Some(area) = SWAP_AREA.lock().as_mut() {
+        area.free(slot);
+    }
+    SWAPPED_PAGES.fetch_sub(1, Ordering::AcqRel);
+}
+
+fn backing() -> Option<Arc<dyn SwapBacking>> {
+    SWAP_AREA.lock().as_ref().map(|a| a.backing.clone())
+}
+
+/// 把一页数据写入交换槽
+pub(crate) fn write_slot(slot: usize, data: &[u8]) -> bool {
+    let ok = backing().is_some_and(|b| b.write_page(slot, data));
+    if ok {
+        PSWPOUT.inc();
+    }
+    ok
+}
+
+/// 从交换槽读回一页数据
+pub(crate) fn read_slot(slot: usize, buf: &mut [u8]) -> bool {
+    let ok = backing().is_some_and(|b| b.read_page(slot, buf));
+    if ok {
+        PSWPIN.inc();
+    }
+    ok
+}
+
+/// 复制交换槽（fork 时子进程获得独立的副本）
+pub(crate) fn duplicate_slot(slot: usize) -> Option<usize> {
+    let mut buf = vec![0u8; PAGE_SIZE];
+    if !read_slot(slot, &mut buf) {
+        return None;
+    }
+    let copy = alloc_slot()?;
+    if !write_slot(copy, &buf) {
+        free_slot(copy);
+        return None;
+    }
+    Some(copy)
+}
+
+/// 收集任务列表中的用户地址空间
+///
+/// 回收路径使用 `try_lock`，避免与持有任务或任务管理器锁的调用者死锁。
+pub(crate) fn user_spaces(try_only: bool) -> Vec<Arc<SpinLock<MemorySpace>>> {
+    let tasks = if try_only {
+        match TASK_MANAGER.try_lock() {
+            Some(tm) => tm.get_task_cond(|_| true),
+            None => return Vec::new(),
+        }
+    } else {
+        TASK_MANAGER.lock().get_task_cond(|_| true)
+    };
+
+    let mut spaces: Vec<Arc<SpinLock<MemorySpace>>> = Vec::new();
+    for task in tasks {
+        let task = if try_only {
+            match task.try_lock() {
+                Some(t) => t,
+                None => continue,
+            }
+        } else {
+            task.lock()
+        };
+        if let Some(space) = task.memory_space.as_ref()
+            && !spaces.iter().any(|s| Arc::ptr_eq(s, space))
+        {
+            spaces.push(space.clone());
+        }
+    }
+    spaces
+}
+
+/// 内存不足时换出至多 `target` 个匿名页，返回实际换出的页数
+///
+/// 被调用者持有锁的地址空间会被跳过。
+pub fn reclaim(target: usize) -> usize {
+    if swap_info().is_none_or(|info| info.used == info.total) {
+        return 0;
+    }
+    if RECLAIMING.swap(true, Ordering::Acquire) {
+        return 0;
+    }
+
+    let spaces = user_spaces(true);
+    let mut reclaimed = 0;
+    // 第一轮清除的访问位在第二轮仍未被置位的页会被换出
+    'sweep: for _ in 0..2 {
+        if spaces.is_empty() {
+            break;
+        }
+        let start = CLOCK_HAND.fetch_add(1, Ordering::Relaxed);
+        for i in 0..spaces.len() {
+            let Some(mut space) = spaces[(start + i) % spaces.len()].try_lock() else {
+                continue;
+            };
+            reclaimed += space.reclaim_pages(target - reclaimed);
+            if reclaimed >= target {
+                break 'sweep;
+            }
+        }
+    }
+
+    RECLAIMING.store(false, Ordering::Release);
+    if reclaimed > 0 {
+        crate::pr_debug!("[Swap] Reclaimed {} pages", reclaimed);
+    }
+    reclaimed
+}
+
+/// 处理当前任务在用户地址 `addr` 上的缺页
+///
+/// 该页被换出或被回收解除映射时重新映射并返回 true；返回 false 表示不是回收引起的缺页。
+pub fn handle_page_fault(addr: usize) -> bool {
+    if !has_swapped_pages() && !crate::mm::rmap::has_unmapped_shared() {
+        return false;
+    }
+    let Some(task) = crate::kernel::try_current_task() else {
+        return false;
+    };
+    let Some(space) = task.lock().memory_space.clone() else {
+        return false;
+    };
+    let vpn = Vpn::from_addr_floor(VA::from_usize(addr));
+    space.lock().swap_in(vpn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    fn header(last_page: u32, bad: &[u32]) -> Vec<u8> {
+        let mut page = vec![0u8; PAGE_SIZE];
+        page[PAGE_SIZE - SWAP_MAGIC.len()..].copy_from_slice(SWAP_MAGIC);
+        page[SWAP_INFO_OFFSET..SWAP_INFO_OFFSET + 4].copy_from_slice(&1u32.to_le_bytes());
+        page[SWAP_INFO_OFFSET + 4..SWAP_INFO_OFFSET + 8].copy_from_slice(&last_page.to_le_bytes());
+        page[SWAP_INFO_OFFSET + 8..SWAP_INFO_OFFSET + 12]
+            .copy_from_slice(&(bad.len() as u32).to_le_bytes());
+        for (i, b) in bad.iter().enumerate() {
+            let off = SWAP_BADPAGES_OFFSET + i * 4;
+            page[off..off + 4].copy_from_slice(&b.to_le_bytes());
+        }
+        page
+    }
+
+    test_case!(test_parse_swap_header, {
+        let parsed = parse_swap_header(&header(255, &[3, 7])).unwrap();
+        kassert!(parsed.last_page == 255);
+        kassert!(parsed.bad_pages == vec![3, 7]);
+
+        // 缺少签名或版本不对
+        let mut page = header(255, &[]);
+        page[PAGE_SIZE - 1] = 0;
+        kassert!(parse_swap_header(&page).is_none());
+        let mut page = header(255, &[]);
+        page[SWAP_INFO_OFFSET] = 2;
+        kassert!(parse_swap_header(&page).is_none());
+    });
+
+    test_case!(test_swap_area_alloc, {
+        let mut area = SwapArea {
+            path: String::new(),
+            backing: Arc::new(NullBacking),
+            used: vec![true, false, true, false],
+            total: 2,
+            inuse: 0,
+            cursor: 1,
+            draining: false,
+        };
+        kassert!(area.alloc() == Some(1));
+        kassert!(area.alloc() == Some(3));
+        kassert!(area.alloc().is_none());
+        area.free(1);
+        kassert!(area.alloc() == Some(1));
+        area.draining = true;
+        area.free(3);
+        kassert!(area.alloc().is_none());
+    });
+
+    struct NullBacking;
+
+    impl SwapBacking for NullBacking {
+        fn page_count(&self) -> usize {
+            0
+        }
+
+        fn read_page(&self, _page: usize, _buf: &mut [u8]) -> bool {
+            false
+        }
+
+        fn write_page(&self, _page: usize, _buf: &[u8]) -> bool {
+            false
+        }
+    }
+}
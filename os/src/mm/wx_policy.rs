@@ -0,0 +1,129 @@
+//! W^X（写与执行互斥）策略
+//!
+//! 同时可写又可执行的用户页是代码注入的常见落脚点。`/proc/sys/vm/wx_policy`
+//! 决定如何处理同时请求 `PROT_WRITE | PROT_EXEC` 的 mmap、mprotect 和 ELF 段：
+//! - `0`：放行；
+//! - `1`：放行，但记录一条告警（默认）；
+//! - `2`：拒绝，mmap/mprotect 返回 EACCES，含 W+X 段的 ELF 无法执行。
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::mm::page_table::UniversalPTEFlag;
+use crate::pr_warn;
+
+/// 用户映射的访问权限位
+pub const USER_PROT_MASK: UniversalPTEFlag = UniversalPTEFlag::READABLE
+    .union(UniversalPTEFlag::WRITEABLE)
+    .union(UniversalPTEFlag::EXECUTABLE);
+
+/// W^X 策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum WxPolicy {
+    /// 放行
+    Allow = 0,
+    /// 放行并告警
+    Log = 1,
+    /// 拒绝
+    Deny = 2,
+}
+
+impl WxPolicy {
+    /// 从 sysctl 数值转换
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Allow),
+            1 => Some(Self::Log),
+            2 => Some(Self::Deny),
+            _ => None,
+        }
+    }
+}
+
+static WX_POLICY: AtomicU8 = AtomicU8::new(WxPolicy::Log as u8);
+
+/// 当前的 W^X 策略
+pub fn wx_policy() -> WxPolicy {
+    WxPolicy::from_u8(WX_POLICY.load(Ordering::Relaxed)).unwrap_or(WxPolicy::Log)
+}
+
+/// 设置 W^X 策略
+pub fn set_wx_policy(policy: WxPolicy) {
+    WX_POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+/// 由读/写/执行位构造用户页表标志
+///
+/// RISC-V 不允许只写不读的叶子页表项，因此可写总是隐含可读。
+pub fn user_pte_flags(read: bool, write: bool, exec: bool) -> UniversalPTEFlag {
+    let mut flags = UniversalPTEFlag::USER_ACCESSIBLE | UniversalPTEFlag::VALID;
+    if read || write {
+        flags |= UniversalPTEFlag::READABLE;
+    }
+    if write {
+        flags |= UniversalPTEFlag::WRITEABLE;
+    }
+    if exec {
+        flags |= UniversalPTEFlag::EXECUTABLE;
+    }
+    flags
+}
+
+/// 按当前策略审核一次映射请求，返回是否允许
+///
+/// `source` 标明请求来源（如 `"mprotect"`），与起始地址一起写入告警。
+pub fn wx_permitted(flags: UniversalPTEFlag, source: &str, addr: usize) -> bool {
+    if !flags.contains(UniversalPTEFlag::WRITEABLE | UniversalPTEFlag::EXECUTABLE) {
+        return true;
+    }
+
+    let pid = crate::kernel::try_current_task().map_or(0, |t| t.lock().pid);
+    match wx_policy() {
+        WxPolicy::Allow => true,
+        WxPolicy::Log => {
+            pr_warn!(
+                "[W^X] {}: pid {} maps {:#x} writable and executable",
+                source,
+                pid,
+                addr
+            );
+            true
+        }
+        WxPolicy::Deny => {
+            pr_warn!(
+                "[W^X] {}: refused writable and executable mapping at {:#x} for pid {}",
+                source,
+                addr,
+                pid
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_user_pte_flags, {
+        let rx = user_pte_flags(true, false, true);
+        kassert!(rx == UniversalPTEFlag::user_rx());
+        // 只写隐含可读
+        kassert!(user_pte_flags(false, true, false) == UniversalPTEFlag::user_rw());
+    });
+
+    test_case!(test_wx_policy_deny, {
+        let saved = wx_policy();
+        let rwx = user_pte_flags(true, true, true);
+
+        set_wx_policy(WxPolicy::Deny);
+        kassert!(!wx_permitted(rwx, "test", 0x1000));
+        kassert!(wx_permitted(UniversalPTEFlag::user_rx(), "test", 0x1000));
+
+        set_wx_policy(WxPolicy::Allow);
+        kassert!(wx_permitted(rwx, "test", 0x1000));
+
+        set_wx_policy(saved);
+    });
+}
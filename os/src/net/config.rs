@@ -1,6 +1,7 @@
 use crate::net::interface::{NETWORK_INTERFACE_MANAGER, NetworkInterface};
+use crate::net::route::{ROUTING_TABLE, Route};
 use alloc::string::String;
-use smoltcp::wire::{IpAddress, IpCidr, Ipv4Address};
+use smoltcp::wire::{IpAddress, IpCidr, Ipv4Address, Ipv4Cidr};
 
 /// 网络配置错误
 #[derive(Debug)]
@@ -37,6 +38,31 @@ impl NetworkConfigManager {
         iface
     }
 
+    /// 把出口为 `interface_name` 的默认路由替换为经 `gateway` 的路由，`None` 表示删除
+    fn set_default_route(interface_name: &str, gateway: Option<Ipv4Address>) {
+        let default = Ipv4Cidr::new(Ipv4Address::UNSPECIFIED, 0);
+        let mut table = ROUTING_TABLE.lock();
+        while table.delete(default, None, Some(interface_name)).is_ok() {}
+        if let Some(gateway) = gateway {
+            let _ = table.add(Route::new(
+                default,
+                Some(gateway),
+                String::from(interface_name),
+                0,
+            ));
+        }
+    }
+
+    /// 出口为 `interface_name` 的默认路由的网关
+    fn default_gateway(interface_name: &str) -> Option<Ipv4Address> {
+        ROUTING_TABLE
+            .lock()
+            .routes()
+            .iter()
+            .find(|r| r.dest.prefix_len() == 0 && r.iface == interface_name)
+            .and_then(|r| r.gateway)
+    }
+
     /// 解析点分十进制子网掩码并计算前缀长度
     ///
     /// # 参数
@@ -150,7 +176,7 @@ impl NetworkConfigManager {
                 let loopback_cidr = IpCidr::new(IpAddress::v4(127, 0, 0, 1), 8);
                 interface.add_ip_address(loopback_cidr);
                 crate::println!("Set loopback address: 127.0.0.1/8");
-                interface.set_ipv4_gateway(None);
+                Self::set_default_route(interface.name(), None);
                 crate::println!("No default gateway (loopback-only)");
             } else {
                 // 设置默认IP地址
@@ -165,7 +191,7 @@ impl NetworkConfigManager {
 
                 // 设置默认网关
                 let gateway = Ipv4Address::new(192, 168, 1, 1);
-                interface.set_ipv4_gateway(Some(gateway));
+                Self::set_default_route(interface.name(), Some(gateway));
                 crate::println!("Set default gateway: 192.168.1.1");
             }
 
@@ -183,6 +209,8 @@ impl NetworkConfigManager {
             }
             use crate::net::socket::init_network;
             init_network(smoltcp_iface);
+            // 生成直连路由并把网关路由下发给 smoltcp
+            crate::net::stack::network_stack().sync_interface_config();
             crate::println!("Initialized global network interface");
 
             Ok(())
@@ -222,17 +250,17 @@ impl NetworkConfigManager {
         interface_name: &str,
         gateway: &str,
     ) -> Result<(), NetworkConfigError> {
-        // 先获取接口的Arc，然后释放全局锁
-        let interface = NETWORK_INTERFACE_MANAGER
+        let exists = NETWORK_INTERFACE_MANAGER
             .lock()
             .find_interface_by_name(interface_name)
-            .cloned(); // clone Arc，然后锁自动释放
+            .is_some();
 
-        if let Some(interface) = interface {
+        if exists {
             // 解析网关地址
             match gateway.parse::<Ipv4Address>() {
                 Ok(gateway_ipv4) => {
-                    interface.set_ipv4_gateway(Some(gateway_ipv4));
+                    Self::set_default_route(interface_name, Some(gateway_ipv4));
+                    crate::net::stack::network_stack().sync_interface_config();
                     crate::println!("Set default gateway for {}: {}", interface_name, gateway);
                     Ok(())
                 }
@@ -270,7 +298,7 @@ impl NetworkConfigManager {
             }
 
             // 添加网关信息
-            if let Some(gateway) = interface.ipv4_gateway() {
+            if let Some(gateway) = Self::default_gateway(interface_name) {
                 config.push_str(&alloc::format!("Default Gateway: {}\n", gateway));
             } else {
                 config.push_str("No default gateway configured\n");
@@ -316,7 +344,8 @@ impl NetworkConfigManager {
             interface.add_ip_address(ip_cidr);
 
             // 设置网关
-            interface.set_ipv4_gateway(Some(gateway_address));
+            Self::set_default_route(interface_name, Some(gateway_address));
+            crate::net::stack::network_stack().sync_interface_config();
 
             crate::println!(
                 "Set interface config for {}: IP={}/{}, Gateway={}",
@@ -4,9 +4,12 @@ use crate::sync::SpinLock;
 use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
 use lazy_static::lazy_static;
 use smoltcp::time::Instant;
-use smoltcp::wire::{EthernetAddress, IpCidr, Ipv4Address};
+use smoltcp::wire::{EthernetAddress, IpCidr, Ipv4Address, Ipv4Cidr};
+
+use crate::uapi::ioctl::{IFF_BROADCAST, IFF_LOOPBACK, IFF_MULTICAST, IFF_RUNNING, IFF_UP};
 
 pub use crate::net::stack::SmoltcpInterface;
 
@@ -37,6 +40,24 @@ impl NetworkInterfaceManager {
     pub fn find_interface_by_name(&self, name: &str) -> Option<&Arc<NetworkInterface>> {
         self.interfaces.iter().find(|iface| iface.name() == name)
     }
+
+    /// 查找以 `device` 为底层设备的网络接口
+    pub fn find_interface_by_device(
+        &self,
+        device: &Arc<dyn NetDevice>,
+    ) -> Option<&Arc<NetworkInterface>> {
+        self.interfaces
+            .iter()
+            .find(|iface| Arc::ptr_eq(iface.device(), device))
+    }
+
+    /// 接口索引（从 1 开始，按注册顺序）
+    pub fn interface_index(&self, name: &str) -> Option<usize> {
+        self.interfaces
+            .iter()
+            .position(|iface| iface.name() == name)
+            .map(|pos| pos + 1)
+    }
 }
 
 lazy_static! {
@@ -45,13 +66,123 @@ lazy_static! {
         SpinLock::new(NetworkInterfaceManager::new());
 }
 
+/// 接口收发统计（/proc/net/dev 与 sysfs statistics）
+#[derive(Default)]
+pub struct InterfaceStats {
+    rx_packets: AtomicU64,
+    rx_bytes: AtomicU64,
+    rx_errors: AtomicU64,
+    rx_dropped: AtomicU64,
+    tx_packets: AtomicU64,
+    tx_bytes: AtomicU64,
+    tx_errors: AtomicU64,
+    tx_dropped: AtomicU64,
+}
+
+/// [`InterfaceStats`] 某一时刻的取值
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterfaceStatsSnapshot {
+    pub rx_packets: u64,
+    pub rx_bytes: u64,
+    pub rx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_packets: u64,
+    pub tx_bytes: u64,
+    pub tx_errors: u64,
+    pub tx_dropped: u64,
+}
+
+impl InterfaceStats {
+    /// 记录收到一个 `len` 字节的帧
+    pub fn record_rx(&self, len: usize) {
+        self.rx_packets.fetch_add(1, Ordering::Relaxed);
+        self.rx_bytes.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    /// 记录发出一个 `len` 字节的帧
+    pub fn record_tx(&self, len: usize) {
+        self.tx_packets.fetch_add(1, Ordering::Relaxed);
+        self.tx_bytes.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    /// 记录一次发送失败
+    pub fn record_tx_error(&self) {
+        self.tx_errors.fetch_add(1, Ordering::Relaxed);
+        self.tx_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一个被丢弃的接收帧
+    pub fn record_rx_dropped(&self) {
+        self.rx_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 读取当前计数
+    pub fn snapshot(&self) -> InterfaceStatsSnapshot {
+        InterfaceStatsSnapshot {
+            rx_packets: self.rx_packets.load(Ordering::Relaxed),
+            rx_bytes: self.rx_bytes.load(Ordering::Relaxed),
+            rx_errors: self.rx_errors.load(Ordering::Relaxed),
+            rx_dropped: self.rx_dropped.load(Ordering::Relaxed),
+            tx_packets: self.tx_packets.load(Ordering::Relaxed),
+            tx_bytes: self.tx_bytes.load(Ordering::Relaxed),
+            tx_errors: self.tx_errors.load(Ordering::Relaxed),
+            tx_dropped: self.tx_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// RFC 2863 接口运行状态，见 `/sys/class/net/<dev>/operstate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperState {
+    Unknown,
+    NotPresent,
+    Down,
+    LowerLayerDown,
+    Testing,
+    Dormant,
+    Up,
+}
+
+impl OperState {
+    /// sysfs 中的名字
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::NotPresent => "notpresent",
+            Self::Down => "down",
+            Self::LowerLayerDown => "lowerlayerdown",
+            Self::Testing => "testing",
+            Self::Dormant => "dormant",
+            Self::Up => "up",
+        }
+    }
+
+    /// 按 sysfs 中的名字解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Unknown,
+            Self::NotPresent,
+            Self::Down,
+            Self::LowerLayerDown,
+            Self::Testing,
+            Self::Dormant,
+            Self::Up,
+        ]
+        .into_iter()
+        .find(|state| state.as_str() == name)
+    }
+}
+
 /// 网络接口
 pub struct NetworkInterface {
     name: String,
     mac_address: EthernetAddress,
     device: Arc<dyn NetDevice>,
     ip_addresses: SpinLock<Vec<IpCidr>>,
-    ipv4_gateway: SpinLock<Option<Ipv4Address>>,
+    flags: SpinLock<u32>,
+    /// 覆盖由 IFF_UP 推出的运行状态，用于测试链路异常
+    operstate_override: SpinLock<Option<OperState>>,
+    stats: Arc<InterfaceStats>,
     interrupt_enabled: SpinLock<bool>,
     last_interrupt_time: SpinLock<Instant>,
 }
@@ -60,12 +191,19 @@ impl NetworkInterface {
     /// 创建新的网络接口
     pub fn new(name: String, device: Arc<dyn NetDevice>) -> Self {
         let mac_address = EthernetAddress(device.mac_address());
+        let flags = if name.starts_with("lo") {
+            IFF_UP | IFF_RUNNING | IFF_MULTICAST | IFF_LOOPBACK
+        } else {
+            IFF_UP | IFF_RUNNING | IFF_MULTICAST | IFF_BROADCAST
+        };
         Self {
             name,
             mac_address,
             device,
             ip_addresses: SpinLock::new(Vec::new()),
-            ipv4_gateway: SpinLock::new(None),
+            flags: SpinLock::new(flags),
+            operstate_override: SpinLock::new(None),
+            stats: Arc::new(InterfaceStats::default()),
             interrupt_enabled: SpinLock::new(true),
             last_interrupt_time: SpinLock::new(Instant::from_millis(0)),
         }
@@ -86,6 +224,11 @@ impl NetworkInterface {
         &self.device
     }
 
+    /// 收发统计
+    pub fn stats(&self) -> &Arc<InterfaceStats> {
+        &self.stats
+    }
+
     /// 设置IP地址
     pub fn add_ip_address(&self, ip_cidr: IpCidr) {
         let mut ip_addresses = self.ip_addresses.lock();
@@ -99,14 +242,98 @@ impl NetworkInterface {
         self.ip_addresses.lock().clone()
     }
 
-    /// 设置IPv4网关
-    pub fn set_ipv4_gateway(&self, gateway: Option<Ipv4Address>) {
-        *self.ipv4_gateway.lock() = gateway;
+    /// 主 IPv4 地址
+    ///
+    /// 回环接口取第一个 IPv4 地址，其他接口取第一个非 127/8 的地址
+    /// （真实网卡上同时挂有 127.0.0.1/8）。
+    pub fn primary_ipv4(&self) -> Option<Ipv4Cidr> {
+        let is_loopback = self.is_loopback();
+        self.ip_addresses.lock().iter().find_map(|cidr| match cidr {
+            IpCidr::Ipv4(v4) if is_loopback || !v4.address().is_loopback() => Some(*v4),
+            _ => None,
+        })
+    }
+
+    /// 设置主 IPv4 地址
+    ///
+    /// 与 Linux 的 SIOCSIFADDR 一致，网络掩码重置为地址类别的默认值。
+    pub fn set_primary_ipv4(&self, addr: Ipv4Address) {
+        let prefix = match addr.octets()[0] {
+            0..=127 => 8,
+            128..=191 => 16,
+            _ => 24,
+        };
+        self.replace_primary_ipv4(Ipv4Cidr::new(addr, prefix));
+    }
+
+    /// 设置主 IPv4 地址的前缀长度
+    ///
+    /// 接口尚无 IPv4 地址时返回 `false`。
+    pub fn set_primary_ipv4_prefix(&self, prefix: u8) -> bool {
+        let Some(old) = self.primary_ipv4() else {
+            return false;
+        };
+        self.replace_primary_ipv4(Ipv4Cidr::new(old.address(), prefix));
+        true
+    }
+
+    fn replace_primary_ipv4(&self, new: Ipv4Cidr) {
+        let old = self.primary_ipv4();
+        let mut ip_addresses = self.ip_addresses.lock();
+        match old.and_then(|old| ip_addresses.iter().position(|c| *c == IpCidr::Ipv4(old))) {
+            Some(pos) => ip_addresses[pos] = IpCidr::Ipv4(new),
+            None => ip_addresses.insert(0, IpCidr::Ipv4(new)),
+        }
     }
 
-    /// 获取IPv4网关
-    pub fn ipv4_gateway(&self) -> Option<Ipv4Address> {
-        *self.ipv4_gateway.lock()
+    /// 是否为回环接口
+    pub fn is_loopback(&self) -> bool {
+        self.flags() & IFF_LOOPBACK != 0
+    }
+
+    /// 接口标志（IFF_*）
+    ///
+    /// 运行状态被覆盖时，IFF_RUNNING 只在状态为 `up` 或 `unknown` 时置位。
+    pub fn flags(&self) -> u32 {
+        let flags = *self.flags.lock();
+        match *self.operstate_override.lock() {
+            Some(OperState::Up | OperState::Unknown) if flags & IFF_UP != 0 => flags | IFF_RUNNING,
+            Some(_) => flags & !IFF_RUNNING,
+            None => flags,
+        }
+    }
+
+    /// 设置接口标志，只有 IFF_UP 可由用户修改
+    pub fn set_flags(&self, flags: u32) {
+        let mut cur = self.flags.lock();
+        *cur = (*cur & !IFF_UP) | (flags & IFF_UP);
+        if *cur & IFF_UP != 0 {
+            *cur |= IFF_RUNNING;
+        } else {
+            *cur &= !IFF_RUNNING;
+        }
+    }
+
+    /// 接口是否已启用
+    pub fn is_up(&self) -> bool {
+        self.flags() & IFF_UP != 0
+    }
+
+    /// 运行状态：被覆盖时返回覆盖值，否则按 IFF_UP 为 `up` 或 `down`
+    pub fn operstate(&self) -> OperState {
+        if let Some(state) = *self.operstate_override.lock() {
+            return state;
+        }
+        if self.is_up() {
+            OperState::Up
+        } else {
+            OperState::Down
+        }
+    }
+
+    /// 覆盖运行状态，`None` 恢复为按 IFF_UP 推出
+    pub fn set_operstate_override(&self, state: Option<OperState>) {
+        *self.operstate_override.lock() = state;
     }
 
     /// 启用中断
@@ -138,9 +365,23 @@ impl NetworkInterface {
     ///
     /// 返回一个 SmoltcpInterface 包装器，它拥有 NetDeviceAdapter 和 Interface，
     /// 确保两者有相同的生命周期，避免悬垂指针问题。
+    /// 路由由 [`crate::net::stack::NetworkStack::sync_interface_config`] 从路由表同步。
     pub fn create_smoltcp_interface(&self) -> SmoltcpInterface {
         // 创建包装器（内部会创建 device_adapter 和 interface）
-        let mut smoltcp_iface = SmoltcpInterface::new(self.device.clone(), self.mac_address());
+        // 回环帧不经过网卡，计入 lo 的统计
+        let loopback_stats = NETWORK_INTERFACE_MANAGER
+            .lock()
+            .get_interfaces()
+            .iter()
+            .find(|iface| iface.is_loopback())
+            .map(|iface| iface.stats().clone())
+            .unwrap_or_else(|| self.stats.clone());
+        let mut smoltcp_iface = SmoltcpInterface::new(
+            self.device.clone(),
+            self.mac_address(),
+            self.stats.clone(),
+            loopback_stats,
+        );
 
         // 设置IP地址
         for ip_cidr in self.ip_addresses.lock().iter() {
@@ -149,15 +390,6 @@ impl NetworkInterface {
             });
         }
 
-        // 设置路由
-        if let Some(gateway) = self.ipv4_gateway() {
-            smoltcp_iface
-                .interface_mut()
-                .routes_mut()
-                .add_default_ipv4_route(gateway)
-                .ok();
-        }
-
         smoltcp_iface
     }
 }
@@ -206,31 +438,14 @@ impl crate::device::Driver for NetDriverHandle {
             );
         }
 
+        // 应答设备中断，收包交给 NAPI 轮询
+        if !self.device.handle_interrupt() {
+            return false;
+        }
+
         // 更新最后中断时间
         self.update_interrupt_time();
-
-        // 处理接收中断：尝试接收数据包
-        let mut buffer = [0u8; 2048];
-        match self.device.receive(&mut buffer) {
-            Ok(size) if size > 0 => {
-                crate::pr_debug!(
-                    "Received {} bytes of data on interface {}",
-                    size,
-                    self.name()
-                );
-                // Wake up tasks waiting in ppoll
-                crate::kernel::syscall::io::wake_poll_waiters();
-                true
-            }
-            Err(e) => {
-                crate::pr_debug!("Error receiving data: {:?}", e);
-                true // 仍然返回true表示我们处理了这个中断
-            }
-            _ => {
-                // 没有接收到数据，但可能是发送完成中断等
-                true
-            }
-        }
+        true
     }
 
     fn device_type(&self) -> DeviceType {
@@ -257,3 +472,66 @@ impl crate::device::Driver for NetDriverHandle {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::net::loopback::LoopbackNetDevice;
+    use crate::{kassert, test_case};
+    use alloc::string::ToString;
+    use smoltcp::wire::IpAddress;
+
+    test_case!(test_interface_primary_ipv4, {
+        let iface = NetworkInterface::new("eth9".to_string(), LoopbackNetDevice::new(9));
+        kassert!(iface.primary_ipv4().is_none());
+        kassert!(!iface.set_primary_ipv4_prefix(24));
+
+        // 真实网卡上的 127.0.0.1/8 不是主地址
+        iface.add_ip_address(IpCidr::new(IpAddress::v4(127, 0, 0, 1), 8));
+        kassert!(iface.primary_ipv4().is_none());
+
+        iface.set_primary_ipv4(Ipv4Address::new(10, 0, 2, 15));
+        kassert!(iface.primary_ipv4() == Some(Ipv4Cidr::new(Ipv4Address::new(10, 0, 2, 15), 8)));
+        kassert!(iface.set_primary_ipv4_prefix(24));
+        iface.set_primary_ipv4(Ipv4Address::new(192, 168, 7, 2));
+        kassert!(iface.ip_addresses().len() == 2);
+        kassert!(iface.primary_ipv4() == Some(Ipv4Cidr::new(Ipv4Address::new(192, 168, 7, 2), 24)));
+
+        iface.set_flags(0);
+        kassert!(!iface.is_up());
+        kassert!(iface.flags() & IFF_BROADCAST != 0);
+        iface.set_flags(IFF_UP);
+        kassert!(iface.is_up());
+    });
+
+    test_case!(test_interface_operstate_override, {
+        let iface = NetworkInterface::new("eth9".to_string(), LoopbackNetDevice::new(9));
+        kassert!(iface.operstate() == OperState::Up);
+        iface.set_flags(0);
+        kassert!(iface.operstate() == OperState::Down);
+        iface.set_flags(IFF_UP);
+
+        iface.set_operstate_override(OperState::from_name("lowerlayerdown"));
+        kassert!(iface.operstate() == OperState::LowerLayerDown);
+        kassert!(iface.is_up());
+        kassert!(iface.flags() & IFF_RUNNING == 0);
+
+        iface.set_operstate_override(None);
+        kassert!(iface.flags() & IFF_RUNNING != 0);
+        kassert!(OperState::from_name("sideways").is_none());
+    });
+
+    test_case!(test_interface_stats, {
+        let stats = InterfaceStats::default();
+        stats.record_rx(60);
+        stats.record_rx(1514);
+        stats.record_tx(42);
+        stats.record_tx_error();
+        stats.record_rx_dropped();
+        let snapshot = stats.snapshot();
+        kassert!(snapshot.rx_packets == 2 && snapshot.rx_bytes == 1574);
+        kassert!(snapshot.tx_packets == 1 && snapshot.tx_bytes == 42);
+        kassert!(snapshot.tx_errors == 1 && snapshot.tx_dropped == 1);
+        kassert!(snapshot.rx_dropped == 1 && snapshot.rx_errors == 0);
+    });
+}
@@ -6,6 +6,7 @@ use alloc::{format, sync::Arc};
 
 pub mod config;
 pub mod interface;
+pub mod route;
 pub mod socket;
 pub mod stack;
 pub mod unix_socket;
@@ -59,8 +60,12 @@ pub fn register_net_device(
     interface::NETWORK_INTERFACE_MANAGER
         .lock()
         .add_interface(network_interface.clone());
-    let driver = Arc::new(interface::NetDriverHandle::new(network_interface.clone()));
-    crate::device::register_driver(driver as Arc<dyn crate::device::Driver>);
+    let driver: Arc<dyn crate::device::Driver> =
+        Arc::new(interface::NetDriverHandle::new(network_interface.clone()));
+    crate::device::IRQ_MANAGER
+        .lock()
+        .register_all(driver.clone());
+    crate::device::register_driver(driver);
 
     network_interface
 }
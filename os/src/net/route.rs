@@ -0,0 +1,268 @@
+//! IPv4 路由表
+//!
+//! 路由表按目的前缀组织，查询时做最长前缀匹配，前缀相同时取 metric 较小者。
+//! 表项分两类：
+//! - 直连路由：由已启用接口的地址推出，接口配置变化后由
+//!   [`NetworkStack::sync_interface_config`](crate::net::stack::NetworkStack::sync_interface_config) 重建；
+//! - 静态路由：由 SIOCADDRT / SIOCDELRT 维护。
+//!
+//! 所有接口共用一个 smoltcp 接口，经网关的路由会同步给 smoltcp，本机发出的数据包由
+//! smoltcp 选择下一跳；转发的数据包（见 [`crate::net::stack::forward`]）直接查本表。
+//! `ip_forward` 开关对应 /proc/sys/net/ipv4/ip_forward，默认关闭。
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+use lazy_static::lazy_static;
+use smoltcp::wire::{Ipv4Address, Ipv4Cidr};
+
+use crate::sync::SpinLock;
+use crate::uapi::ioctl::{RTF_GATEWAY, RTF_HOST, RTF_UP};
+
+/// 路由表操作错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteError {
+    /// 已存在相同的路由
+    Exists,
+    /// 没有匹配的路由
+    NotFound,
+}
+
+/// 路由表项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// 目的网络（主机位为 0）
+    pub dest: Ipv4Cidr,
+    /// 下一跳网关，直连网络为 `None`
+    pub gateway: Option<Ipv4Address>,
+    /// 出口接口名
+    pub iface: String,
+    /// 度量值，越小越优先
+    pub metric: u32,
+    /// 是否为由接口地址推出的直连路由
+    pub connected: bool,
+}
+
+impl Route {
+    /// 创建静态路由，目的地址的主机位会被清零
+    pub fn new(dest: Ipv4Cidr, gateway: Option<Ipv4Address>, iface: String, metric: u32) -> Self {
+        Self {
+            dest: dest.network(),
+            gateway,
+            iface,
+            metric,
+            connected: false,
+        }
+    }
+
+    /// 路由标志（RTF_*）
+    pub fn flags(&self) -> u16 {
+        let mut flags = RTF_UP;
+        if self.gateway.is_some() {
+            flags |= RTF_GATEWAY;
+        }
+        if self.dest.prefix_len() == 32 {
+            flags |= RTF_HOST;
+        }
+        flags
+    }
+
+    /// 发往 `dst` 时的下一跳地址
+    pub fn next_hop(&self, dst: Ipv4Address) -> Ipv4Address {
+        self.gateway.unwrap_or(dst)
+    }
+}
+
+/// IPv4 路由表
+pub struct RoutingTable {
+    routes: Vec<Route>,
+    /// 已启用接口上的本机地址
+    local: Vec<Ipv4Address>,
+}
+
+impl RoutingTable {
+    /// 创建空路由表
+    pub const fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            local: Vec::new(),
+        }
+    }
+
+    /// 所有表项
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    /// 添加路由
+    ///
+    /// 目的网络、出口接口和 metric 都相同的路由已存在时返回 [`RouteError::Exists`]。
+    pub fn add(&mut self, route: Route) -> Result<(), RouteError> {
+        if self
+            .routes
+            .iter()
+            .any(|r| r.dest == route.dest && r.iface == route.iface && r.metric == route.metric)
+        {
+            return Err(RouteError::Exists);
+        }
+        self.routes.push(route);
+        Ok(())
+    }
+
+    /// 删除第一条匹配的路由
+    ///
+    /// `gateway` 和 `iface` 为 `None` 时不参与匹配。
+    pub fn delete(
+        &mut self,
+        dest: Ipv4Cidr,
+        gateway: Option<Ipv4Address>,
+        iface: Option<&str>,
+    ) -> Result<Route, RouteError> {
+        let dest = dest.network();
+        let pos = self
+            .routes
+            .iter()
+            .position(|r| {
+                r.dest == dest
+                    && gateway.is_none_or(|gw| r.gateway == Some(gw))
+                    && iface.is_none_or(|name| r.iface == name)
+            })
+            .ok_or(RouteError::NotFound)?;
+        Ok(self.routes.remove(pos))
+    }
+
+    /// 最长前缀匹配
+    pub fn lookup(&self, dst: Ipv4Address) -> Option<&Route> {
+        self.routes
+            .iter()
+            .filter(|r| r.dest.contains_addr(&dst))
+            .min_by_key(|r| (u8::MAX - r.dest.prefix_len(), r.metric))
+    }
+
+    /// 用新的接口地址重建直连路由
+    ///
+    /// `addrs` 为（接口地址，接口名），同一网络只保留第一条。
+    pub fn set_connected(&mut self, addrs: &[(Ipv4Cidr, String)]) {
+        self.routes.retain(|r| !r.connected);
+        self.local.clear();
+        for (cidr, iface) in addrs {
+            if !self.local.contains(&cidr.address()) {
+                self.local.push(cidr.address());
+            }
+            let dest = cidr.network();
+            if self.routes.iter().any(|r| r.connected && r.dest == dest) {
+                continue;
+            }
+            self.routes.push(Route {
+                dest,
+                gateway: None,
+                iface: iface.clone(),
+                metric: 0,
+                connected: true,
+            });
+        }
+    }
+
+    /// `addr` 是否为本机地址
+    pub fn is_local(&self, addr: Ipv4Address) -> bool {
+        addr.is_loopback() || self.local.contains(&addr)
+    }
+}
+
+impl Default for RoutingTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+lazy_static! {
+    /// 全局 IPv4 路由表
+    pub static ref ROUTING_TABLE: SpinLock<RoutingTable> = SpinLock::new(RoutingTable::new());
+}
+
+/// 是否在接口之间转发 IPv4 数据包
+static IP_FORWARD: AtomicBool = AtomicBool::new(false);
+
+/// /proc/sys/net/ipv4/ip_forward 的当前值
+pub fn ip_forward_enabled() -> bool {
+    IP_FORWARD.load(Ordering::Relaxed)
+}
+
+/// 打开或关闭 IPv4 转发
+pub fn set_ip_forward(enabled: bool) {
+    IP_FORWARD.store(enabled, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+    use alloc::string::ToString;
+
+    test_case!(test_routing_table_lookup, {
+        let mut table = RoutingTable::new();
+        table.set_connected(&[
+            (
+                Ipv4Cidr::new(Ipv4Address::new(192, 168, 1, 100), 24),
+                "eth0".to_string(),
+            ),
+            (
+                Ipv4Cidr::new(Ipv4Address::new(10, 0, 0, 1), 8),
+                "eth1".to_string(),
+            ),
+        ]);
+        let default = Route::new(
+            Ipv4Cidr::new(Ipv4Address::UNSPECIFIED, 0),
+            Some(Ipv4Address::new(192, 168, 1, 1)),
+            "eth0".to_string(),
+            0,
+        );
+        kassert!(table.add(default.clone()).is_ok());
+        kassert!(table.add(default) == Err(RouteError::Exists));
+        kassert!(
+            table
+                .add(Route::new(
+                    Ipv4Cidr::new(Ipv4Address::new(10, 1, 2, 3), 16),
+                    Some(Ipv4Address::new(192, 168, 1, 254)),
+                    "eth0".to_string(),
+                    0,
+                ))
+                .is_ok()
+        );
+
+        // 最长前缀优先于直连的 10/8 和默认路由
+        let route = table.lookup(Ipv4Address::new(10, 1, 9, 9)).unwrap();
+        kassert!(route.dest == Ipv4Cidr::new(Ipv4Address::new(10, 1, 0, 0), 16));
+        kassert!(
+            route.next_hop(Ipv4Address::new(10, 1, 9, 9)) == Ipv4Address::new(192, 168, 1, 254)
+        );
+        kassert!(route.flags() == RTF_UP | RTF_GATEWAY);
+        kassert!(table.lookup(Ipv4Address::new(10, 2, 0, 1)).unwrap().iface == "eth1");
+        kassert!(
+            table
+                .lookup(Ipv4Address::new(8, 8, 8, 8))
+                .unwrap()
+                .gateway
+                .is_some()
+        );
+        kassert!(table.is_local(Ipv4Address::new(192, 168, 1, 100)));
+        kassert!(!table.is_local(Ipv4Address::new(192, 168, 1, 1)));
+
+        // 重建直连路由不影响静态路由
+        table.set_connected(&[]);
+        kassert!(table.routes().len() == 2);
+        kassert!(
+            table.delete(
+                Ipv4Cidr::new(Ipv4Address::UNSPECIFIED, 0),
+                None,
+                Some("eth1")
+            ) == Err(RouteError::NotFound)
+        );
+        kassert!(
+            table
+                .delete(Ipv4Cidr::new(Ipv4Address::UNSPECIFIED, 0), None, None)
+                .is_ok()
+        );
+        kassert!(table.lookup(Ipv4Address::new(8, 8, 8, 8)).is_none());
+    });
+}
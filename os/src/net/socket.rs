@@ -4,9 +4,9 @@ use crate::arch::Arch;
 use crate::kernel::{GLOBAL_WORK_QUEUE, WorkItem};
 use crate::net::NetworkError;
 use crate::sync::SpinLock;
-use crate::vfs::{File, FsError, InodeMetadata};
+use crate::vfs::{File, FileMode, FsError, InodeMetadata, InodeType};
 use alloc::collections::VecDeque;
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use lazy_static::lazy_static;
 use smoltcp::iface::SocketHandle as SmoltcpHandle;
 use smoltcp::wire::{IpAddress, IpEndpoint, Ipv4Address};
@@ -15,16 +15,25 @@ use smoltcp::wire::{IpAddress, IpEndpoint, Ipv4Address};
 pub enum SocketHandle {
     Tcp(SmoltcpHandle),
     Udp(SmoltcpHandle),
+    Icmp(SmoltcpHandle),
+    Raw(SmoltcpHandle),
 }
 
 use alloc::collections::BTreeMap;
-use alloc::sync::Arc;
+use alloc::sync::{Arc, Weak};
 
 lazy_static! {
     pub static ref FD_SOCKET_MAP: SpinLock<BTreeMap<(usize, usize), SocketHandle>> =
         SpinLock::new(BTreeMap::new());
+
+    /// Live inet socket files keyed by inode number, for /proc/net/{tcp,udp}.
+    static ref INET_SOCKETS: SpinLock<BTreeMap<usize, Weak<SocketFile>>> =
+        SpinLock::new(BTreeMap::new());
 }
 
+/// Next socket inode number.
+static NEXT_SOCKET_INO: AtomicUsize = AtomicUsize::new(1);
+
 static NETWORK_POLL_PENDING: AtomicBool = AtomicBool::new(false);
 
 use crate::uapi::fcntl::OpenFlags;
@@ -48,11 +57,19 @@ pub struct SocketFile {
     local_endpoint: SpinLock<Option<IpEndpoint>>,
     remote_endpoint: SpinLock<Option<IpEndpoint>>,
     udp_rx_queue: SpinLock<VecDeque<UdpDatagram>>,
+    /// Payload bytes in `udp_rx_queue`, bounded by SO_RCVBUF.
+    udp_rx_bytes: AtomicUsize,
     shutdown_rd: SpinLock<bool>,
     shutdown_wr: SpinLock<bool>,
     flags: SpinLock<OpenFlags>,
     options: SpinLock<SocketOptions>,
     pub(crate) is_listener: SpinLock<bool>,
+    /// A non-blocking connect is in progress; its outcome feeds SO_ERROR.
+    connecting: SpinLock<bool>,
+    /// Pending error reported and cleared by SO_ERROR.
+    error: SpinLock<i32>,
+    ino: usize,
+    uid: u32,
 }
 
 impl SocketFile {
@@ -64,11 +81,16 @@ impl SocketFile {
             local_endpoint: SpinLock::new(None),
             remote_endpoint: SpinLock::new(None),
             udp_rx_queue: SpinLock::new(VecDeque::with_capacity(UDP_RXQ_INITIAL_CAP)),
+            udp_rx_bytes: AtomicUsize::new(0),
             shutdown_rd: SpinLock::new(false),
             shutdown_wr: SpinLock::new(false),
             flags: SpinLock::new(OpenFlags::empty()),
             options: SpinLock::new(SocketOptions::default()),
             is_listener: SpinLock::new(false),
+            connecting: SpinLock::new(false),
+            error: SpinLock::new(0),
+            ino: NEXT_SOCKET_INO.fetch_add(1, Ordering::Relaxed),
+            uid: crate::kernel::current_task().lock().credential.euid,
         }
     }
 
@@ -80,6 +102,21 @@ impl SocketFile {
         *self.is_listener.lock()
     }
 
+    /// Socket inode number.
+    pub fn ino(&self) -> usize {
+        self.ino
+    }
+
+    /// Effective uid of the creator.
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// Bytes queued in the per-fd UDP receive queue.
+    pub(crate) fn udp_queued_bytes(&self) -> usize {
+        self.udp_rx_bytes.load(Ordering::Relaxed)
+    }
+
     pub fn new_with_flags(handle: SocketHandle, flags: OpenFlags) -> Self {
         Self {
             handle: SpinLock::new(Some(handle)),
@@ -88,11 +125,16 @@ impl SocketFile {
             local_endpoint: SpinLock::new(None),
             remote_endpoint: SpinLock::new(None),
             udp_rx_queue: SpinLock::new(VecDeque::with_capacity(UDP_RXQ_INITIAL_CAP)),
+            udp_rx_bytes: AtomicUsize::new(0),
             shutdown_rd: SpinLock::new(false),
             shutdown_wr: SpinLock::new(false),
             flags: SpinLock::new(flags),
             options: SpinLock::new(SocketOptions::default()),
             is_listener: SpinLock::new(false),
+            connecting: SpinLock::new(false),
+            error: SpinLock::new(0),
+            ino: NEXT_SOCKET_INO.fetch_add(1, Ordering::Relaxed),
+            uid: crate::kernel::current_task().lock().credential.euid,
         }
     }
 
@@ -108,8 +150,48 @@ impl SocketFile {
         *self.options.lock()
     }
 
+    /// Store new options and apply those the TCP stack tracks (Nagle, buffer sizes).
     pub fn set_socket_options(&self, opts: SocketOptions) {
         *self.options.lock() = opts;
+        if let Some(SocketHandle::Tcp(h)) = *self.handle.lock() {
+            crate::net::stack::network_stack().tcp_apply_options(h, &opts);
+        }
+    }
+
+    /// Deadline in clock ticks for a blocking call that started at `start`,
+    /// or `None` when the SO_RCVTIMEO / SO_SNDTIMEO timeout is unset.
+    pub fn io_deadline(&self, dir: SockTimeout, start: usize) -> Option<usize> {
+        let opts = self.options.lock();
+        let timeout = match dir {
+            SockTimeout::Recv => opts.recv_timeout,
+            SockTimeout::Send => opts.send_timeout,
+        };
+        if timeout.is_zero() {
+            None
+        } else if timeout.tv_sec < 0 {
+            // Linux turns a negative timeout into an immediate one.
+            Some(start)
+        } else {
+            Some(start + timeout.into_freq(crate::arch::clock_freq()))
+        }
+    }
+
+    pub fn set_connecting(&self, connecting: bool) {
+        *self.connecting.lock() = connecting;
+    }
+
+    pub fn is_connecting(&self) -> bool {
+        *self.connecting.lock()
+    }
+
+    pub fn set_error(&self, errno: i32) {
+        *self.error.lock() = errno;
+    }
+
+    /// Return and clear the pending error (SO_ERROR).
+    pub fn take_error(&self) -> i32 {
+        crate::net::stack::network_stack().update_connect_error(self);
+        core::mem::take(&mut *self.error.lock())
     }
 
     pub fn handle(&self) -> SocketHandle {
@@ -135,6 +217,8 @@ impl SocketFile {
             .any(|h| match (*h, handle) {
                 (SocketHandle::Tcp(a), SocketHandle::Tcp(b)) => a == b,
                 (SocketHandle::Udp(a), SocketHandle::Udp(b)) => a == b,
+                (SocketHandle::Icmp(a), SocketHandle::Icmp(b)) => a == b,
+                (SocketHandle::Raw(a), SocketHandle::Raw(b)) => a == b,
                 _ => false,
             })
     }
@@ -171,10 +255,16 @@ impl SocketFile {
         self.udp_rx_queue.lock().len()
     }
 
+    /// Queue a datagram; it is dropped when SO_RCVBUF is already exhausted.
     pub(crate) fn udp_push(&self, d: UdpDatagram) -> bool {
+        let rcvbuf = self.options.lock().recv_buffer_size;
         let mut q = self.udp_rx_queue.lock();
+        let queued = self.udp_rx_bytes.load(Ordering::Relaxed);
+        if !q.is_empty() && queued + d.len > rcvbuf {
+            return false;
+        }
         if q.len() >= UDP_RXQ_MAX_CAP {
-            let _ = q.pop_front();
+            self.udp_pop_locked(&mut q);
         } else if q.len() == q.capacity() {
             let old_capacity = q.capacity();
             if old_capacity < UDP_RXQ_MAX_CAP {
@@ -184,15 +274,22 @@ impl SocketFile {
                 let _ = q.try_reserve(new_capacity.saturating_sub(old_capacity));
             }
             if q.len() == q.capacity() {
-                let _ = q.pop_front();
+                self.udp_pop_locked(&mut q);
             }
         }
+        self.udp_rx_bytes.fetch_add(d.len, Ordering::Relaxed);
         q.push_back(d);
         true
     }
 
     pub(crate) fn udp_pop(&self) -> Option<UdpDatagram> {
-        self.udp_rx_queue.lock().pop_front()
+        self.udp_pop_locked(&mut self.udp_rx_queue.lock())
+    }
+
+    fn udp_pop_locked(&self, q: &mut VecDeque<UdpDatagram>) -> Option<UdpDatagram> {
+        let d = q.pop_front()?;
+        self.udp_rx_bytes.fetch_sub(d.len, Ordering::Relaxed);
+        Some(d)
     }
 
     pub fn shutdown_read(&self) {
@@ -218,10 +315,115 @@ impl SocketFile {
 
 impl Drop for SocketFile {
     fn drop(&mut self) {
+        INET_SOCKETS.lock().remove(&self.ino);
         crate::net::stack::network_stack().drop_socket_file(self);
     }
 }
 
+/// Make a socket file visible in /proc/net/{tcp,udp}.
+pub fn register_inet_socket(file: &Arc<SocketFile>) {
+    INET_SOCKETS.lock().insert(file.ino(), Arc::downgrade(file));
+}
+
+/// All live registered inet socket files, in inode order.
+pub fn inet_sockets() -> alloc::vec::Vec<Arc<SocketFile>> {
+    INET_SOCKETS
+        .lock()
+        .values()
+        .filter_map(Weak::upgrade)
+        .collect()
+}
+
+/// Whether two bind addresses overlap; the unspecified address covers all.
+pub(crate) fn bind_addrs_overlap(a: IpAddress, b: IpAddress) -> bool {
+    a.is_unspecified() || b.is_unspecified() || a == b
+}
+
+/// Check that `file` may bind to `endpoint` given the other bound sockets.
+///
+/// Follows Linux: two sockets of the same protocol conflict on an overlapping
+/// address and port unless both set SO_REUSEPORT with the same owner, or both
+/// set SO_REUSEADDR and, for TCP, the existing socket is not listening. A TCP
+/// connection still closing after its file was dropped also holds the port
+/// unless the new socket sets SO_REUSEADDR.
+pub fn check_bind_conflict(file: &SocketFile, endpoint: IpEndpoint) -> Result<(), NetworkError> {
+    let is_tcp = matches!(*file.handle.lock(), Some(SocketHandle::Tcp(_)));
+    let opts = file.get_socket_options();
+    for other in inet_sockets() {
+        if core::ptr::eq(Arc::as_ptr(&other), file) {
+            continue;
+        }
+        let same_proto = match *other.handle.lock() {
+            Some(SocketHandle::Tcp(_)) => is_tcp,
+            Some(SocketHandle::Udp(_)) => !is_tcp,
+            _ => false,
+        };
+        if !same_proto {
+            continue;
+        }
+        let Some(bound) = other.get_local_endpoint() else {
+            continue;
+        };
+        if bound.port != endpoint.port || !bind_addrs_overlap(bound.addr, endpoint.addr) {
+            continue;
+        }
+        let other_opts = other.get_socket_options();
+        if opts.reuse_port && other_opts.reuse_port && other.uid() == file.uid() {
+            continue;
+        }
+        if opts.reuse_addr && other_opts.reuse_addr && !(is_tcp && other.is_listener()) {
+            continue;
+        }
+        return Err(NetworkError::AddressInUse);
+    }
+    if is_tcp && !opts.reuse_addr && crate::net::stack::network_stack().tcp_closing_on(endpoint) {
+        return Err(NetworkError::AddressInUse);
+    }
+    Ok(())
+}
+
+/// Which timeout bounds a blocking socket call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SockTimeout {
+    /// SO_RCVTIMEO: recv, read and accept.
+    Recv,
+    /// SO_SNDTIMEO: send, write and connect.
+    Send,
+}
+
+/// Wait after a blocking socket call on `file` returned `EAGAIN`.
+///
+/// `start` is the clock tick at which the call began. Without a timeout the
+/// task yields and retries as before. With one, it sleeps on the poll wait
+/// queue, woken by socket activity or by the timer queue at the deadline.
+/// Returns `-EAGAIN` once the timeout has elapsed and `-EINTR` on a signal.
+pub fn wait_socket_io(
+    file: Arc<dyn File>,
+    task: &crate::kernel::SharedTask,
+    dir: SockTimeout,
+    start: usize,
+) -> Result<(), isize> {
+    let deadline = file
+        .as_any()
+        .downcast_ref::<SocketFile>()
+        .and_then(|sf| sf.io_deadline(dir, start));
+    drop(file);
+    poll_network_and_dispatch();
+    match deadline {
+        Some(deadline) => {
+            if crate::arch::get_time() >= deadline {
+                return Err(-(crate::uapi::errno::EAGAIN as isize));
+            }
+            crate::kernel::syscall::io::sleep_poll_waiter(task, deadline);
+        }
+        None => crate::kernel::yield_task(),
+    }
+    if crate::ipc::signal_interrupts_syscall(task) {
+        return Err(-(crate::uapi::errno::EINTR as isize));
+    }
+    Ok(())
+}
+
 /// Register a socket fd mapping (tid, fd) -> handle
 pub fn register_socket_fd(tid: usize, fd: usize, handle: SocketHandle) {
     FD_SOCKET_MAP.lock().insert((tid, fd), handle);
@@ -232,6 +434,14 @@ pub fn unregister_socket_fd(tid: usize, fd: usize) {
     FD_SOCKET_MAP.lock().remove(&(tid, fd));
 }
 
+/// Whether any socket fd is currently open.
+///
+/// smoltcp is only driven by periodic polls, so idle must keep the tick
+/// running while sockets exist.
+pub fn has_open_sockets() -> bool {
+    !FD_SOCKET_MAP.lock().is_empty()
+}
+
 /// Get socket handle from (tid, fd)
 pub fn get_socket_handle(tid: usize, fd: usize) -> Option<SocketHandle> {
     FD_SOCKET_MAP.lock().get(&(tid, fd)).copied()
@@ -338,7 +548,20 @@ impl File for SocketFile {
     }
 
     fn metadata(&self) -> Result<InodeMetadata, FsError> {
-        Err(FsError::NotSupported)
+        Ok(InodeMetadata {
+            inode_no: self.ino,
+            inode_type: InodeType::Socket,
+            size: 0,
+            mode: FileMode::S_IFSOCK | FileMode::from_bits_truncate(0o777),
+            uid: self.uid,
+            gid: 0,
+            atime: crate::uapi::time::TimeSpec::zero(),
+            mtime: crate::uapi::time::TimeSpec::zero(),
+            ctime: crate::uapi::time::TimeSpec::zero(),
+            nlinks: 1,
+            blocks: 0,
+            rdev: 0,
+        })
     }
 
     fn as_any(&self) -> &dyn core::any::Any {
@@ -360,7 +583,7 @@ impl File for SocketFile {
 }
 
 const AF_INET: u16 = 2;
-const SOCKADDR_IN_SIZE: usize = 16;
+pub(crate) const SOCKADDR_IN_SIZE: usize = 16;
 
 /// Read the sa_family field from a user sockaddr.
 pub fn read_sockaddr_family(addr: *const u8, addrlen: u32) -> Result<u16, NetworkError> {
@@ -491,6 +714,16 @@ pub fn create_udp_socket() -> Result<SocketHandle, NetworkError> {
     crate::net::stack::network_stack().create_udp_socket()
 }
 
+/// Create an ICMP echo ("ping") socket.
+pub fn create_icmp_socket() -> Result<SocketHandle, NetworkError> {
+    crate::net::stack::network_stack().create_icmp_socket()
+}
+
+/// Create a raw IPv4 socket carrying ICMP.
+pub fn create_raw_socket() -> Result<SocketHandle, NetworkError> {
+    crate::net::stack::network_stack().create_raw_socket()
+}
+
 /// Initialize network interface through the stack facade.
 pub fn init_network(smoltcp_iface: crate::net::interface::SmoltcpInterface) {
     crate::net::stack::network_stack().init_network(smoltcp_iface);
@@ -512,6 +745,7 @@ pub fn poll_network_interfaces() {
 fn network_poll_work() {
     NETWORK_POLL_PENDING.store(false, Ordering::Release);
     poll_network_interfaces();
+    crate::device::net::napi::poll_complete();
     crate::kernel::syscall::io::wake_poll_waiters();
 }
 
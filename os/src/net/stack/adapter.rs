@@ -11,8 +11,10 @@ impl SmoltcpInterface {
     pub(crate) fn new(
         device: Arc<dyn crate::device::net::net_device::NetDevice>,
         mac_address: EthernetAddress,
+        stats: Arc<InterfaceStats>,
+        loopback_stats: Arc<InterfaceStats>,
     ) -> Self {
-        let mut device_adapter = NetDeviceAdapter::new(device);
+        let mut device_adapter = NetDeviceAdapter::new(device, stats, loopback_stats);
         let config =
             smoltcp::iface::Config::new(smoltcp::wire::HardwareAddress::Ethernet(mac_address));
         let current_time = crate::arch::get_time_ms() as i64;
@@ -64,11 +66,21 @@ impl SmoltcpInterface {
 pub struct NetDeviceAdapter {
     device: Arc<dyn crate::device::net::net_device::NetDevice>,
     rx_buffer: Vec<u8>,
+    /// 本轮轮询还能从设备收取的数据包数
+    rx_budget: usize,
+    /// 设备所属接口的收发统计
+    stats: Arc<InterfaceStats>,
+    /// 回环帧计入的统计（lo 接口）
+    loopback_stats: Arc<InterfaceStats>,
 }
 
 impl NetDeviceAdapter {
     /// Create a new adapter.
-    pub fn new(device: Arc<dyn crate::device::net::net_device::NetDevice>) -> Self {
+    pub fn new(
+        device: Arc<dyn crate::device::net::net_device::NetDevice>,
+        stats: Arc<InterfaceStats>,
+        loopback_stats: Arc<InterfaceStats>,
+    ) -> Self {
         let rx_len = device
             .mtu()
             .saturating_add(smoltcp::wire::EthernetFrame::<&[u8]>::header_len())
@@ -76,9 +88,22 @@ impl NetDeviceAdapter {
         Self {
             device,
             rx_buffer: alloc::vec![0; rx_len],
+            rx_budget: crate::device::net::napi::NAPI_WEIGHT,
+            stats,
+            loopback_stats,
         }
     }
 
+    /// 设置本轮轮询从设备收取的数据包上限
+    pub fn set_rx_budget(&mut self, budget: usize) {
+        self.rx_budget = budget;
+    }
+
+    /// The underlying net device.
+    pub fn device(&self) -> &Arc<dyn crate::device::net::net_device::NetDevice> {
+        &self.device
+    }
+
     /// Compatibility hook for the old bounded loopback drain path.
     pub fn loopback_queue_len(&self) -> usize {
         loopback_link_len()
@@ -95,6 +120,7 @@ impl smoltcp::phy::Device for NetDeviceAdapter {
     ) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
         if let Some(packet) = dequeue_loopback_frame() {
             if packet.len() > self.rx_buffer.len() {
+                self.loopback_stats.record_rx_dropped();
                 return None;
             }
             self.rx_buffer[..packet.len()].copy_from_slice(&packet);
@@ -104,26 +130,42 @@ impl smoltcp::phy::Device for NetDeviceAdapter {
                 },
                 NetTxToken {
                     device: &self.device,
+                    stats: &self.stats,
+                    loopback_stats: &self.loopback_stats,
                 },
             ));
         }
 
-        match self.device.receive(&mut self.rx_buffer) {
-            Ok(size) if size > 0 => Some((
+        let mac = EthernetAddress(self.device.mac_address());
+        while self.rx_budget > 0 {
+            let size = match self.device.receive(&mut self.rx_buffer) {
+                Ok(size) if size > 0 => size,
+                _ => return None,
+            };
+            self.rx_budget -= 1;
+            self.stats.record_rx(size);
+            if super::forward::forward_frame(mac, &mut self.rx_buffer[..size]) {
+                continue;
+            }
+            return Some((
                 NetRxToken {
                     buffer: &self.rx_buffer[..size],
                 },
                 NetTxToken {
                     device: &self.device,
+                    stats: &self.stats,
+                    loopback_stats: &self.loopback_stats,
                 },
-            )),
-            _ => None,
+            ));
         }
+        None
     }
 
     fn transmit(&mut self, _timestamp: smoltcp::time::Instant) -> Option<Self::TxToken<'_>> {
         Some(NetTxToken {
             device: &self.device,
+            stats: &self.stats,
+            loopback_stats: &self.loopback_stats,
         })
     }
 
@@ -158,6 +200,8 @@ impl smoltcp::phy::RxToken for NetRxToken<'_> {
 /// Transmit token.
 pub struct NetTxToken<'a> {
     device: &'a Arc<dyn crate::device::net::net_device::NetDevice>,
+    stats: &'a Arc<InterfaceStats>,
+    loopback_stats: &'a Arc<InterfaceStats>,
 }
 
 impl smoltcp::phy::TxToken for NetTxToken<'_> {
@@ -168,11 +212,19 @@ impl smoltcp::phy::TxToken for NetTxToken<'_> {
         let mut buffer = alloc::vec![0; len];
         let result = f(&mut buffer);
 
+        // Traffic to 127/8 or to one of our own addresses never leaves the host.
+        let is_local = |at: usize| {
+            buffer[at] == 127 || {
+                let addr =
+                    Ipv4Address::new(buffer[at], buffer[at + 1], buffer[at + 2], buffer[at + 3]);
+                crate::net::route::ROUTING_TABLE.lock().is_local(addr)
+            }
+        };
         let is_loopback = if buffer.len() >= 14 {
             let ethertype = u16::from_be_bytes([buffer[12], buffer[13]]);
             match ethertype {
-                0x0800 if buffer.len() >= 34 => buffer[26] == 127 || buffer[30] == 127,
-                0x0806 if buffer.len() >= 42 => buffer[28] == 127 || buffer[38] == 127,
+                0x0800 if buffer.len() >= 34 => buffer[26] == 127 || is_local(30),
+                0x0806 if buffer.len() >= 42 => buffer[28] == 127 || is_local(38),
                 _ => false,
             }
         } else {
@@ -180,9 +232,13 @@ impl smoltcp::phy::TxToken for NetTxToken<'_> {
         };
 
         if is_loopback {
+            self.loopback_stats.record_tx(buffer.len());
+            self.loopback_stats.record_rx(buffer.len());
             enqueue_loopback_frame(buffer);
+        } else if self.device.send(&buffer).is_ok() {
+            self.stats.record_tx(buffer.len());
         } else {
-            let _ = self.device.send(&buffer);
+            self.stats.record_tx_error();
         }
 
         result
@@ -0,0 +1,257 @@
+//! IPv4 forwarding between interfaces.
+//!
+//! With `ip_forward` on, unicast IPv4 frames sent to one of our MAC addresses
+//! but not to a local address are routed through the kernel routing table and
+//! transmitted on the egress interface without reaching smoltcp. The TTL is
+//! decremented and frames whose TTL would expire are dropped. Next-hop MAC
+//! addresses come from a neighbor table learned by snooping ARP on every
+//! interface; when the next hop is unknown an ARP request is sent and the frame
+//! is dropped, so the sender's retransmission goes through.
+//!
+//! Only the primary interface is driven by smoltcp. Secondary interfaces are
+//! drained here: ARP requests for their own addresses are answered so that
+//! neighbors can use them as a gateway, and frames that are not forwarded are
+//! dropped.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use lazy_static::lazy_static;
+use smoltcp::wire::{
+    ArpOperation, ArpPacket, ArpRepr, EthernetAddress, EthernetFrame, EthernetProtocol,
+    EthernetRepr, Ipv4Address, Ipv4Packet,
+};
+
+use crate::device::net::napi::NAPI_WEIGHT;
+use crate::device::net::net_device::NetDevice;
+use crate::net::interface::{NETWORK_INTERFACE_MANAGER, NetworkInterface};
+use crate::net::route::{ROUTING_TABLE, ip_forward_enabled};
+use crate::sync::SpinLock;
+
+const ETH_HEADER_LEN: usize = 14;
+
+/// Upper bound on learned neighbors; new addresses are ignored once full.
+const MAX_NEIGHBORS: usize = 256;
+
+lazy_static! {
+    /// IPv4 neighbors learned from ARP traffic on any interface.
+    static ref NEIGHBORS: SpinLock<BTreeMap<Ipv4Address, EthernetAddress>> =
+        SpinLock::new(BTreeMap::new());
+}
+
+/// Inspect a frame received on an interface with MAC `ingress_mac`.
+///
+/// ARP frames only feed the neighbor table. Returns `true` when the frame was
+/// forwarded or dropped and must not be delivered locally.
+pub(crate) fn forward_frame(ingress_mac: EthernetAddress, frame: &mut [u8]) -> bool {
+    let Ok(eth) = EthernetFrame::new_checked(&*frame) else {
+        return false;
+    };
+    match eth.ethertype() {
+        EthernetProtocol::Arp => {
+            if let Some(repr) = parse_arp(eth.payload()) {
+                learn_neighbor(&repr);
+            }
+            false
+        }
+        EthernetProtocol::Ipv4 if ip_forward_enabled() && eth.dst_addr() == ingress_mac => {
+            forward_ipv4(frame)
+        }
+        _ => false,
+    }
+}
+
+/// Drain every interface other than the primary one.
+pub(crate) fn poll_secondary_interfaces(primary: &Arc<dyn NetDevice>) {
+    let interfaces = NETWORK_INTERFACE_MANAGER.lock().get_interfaces().to_vec();
+    let mut buf = alloc::vec![0u8; 2048];
+    for iface in interfaces
+        .iter()
+        .filter(|iface| !iface.is_loopback() && !Arc::ptr_eq(iface.device(), primary))
+    {
+        for _ in 0..NAPI_WEIGHT {
+            let len = match iface.device().receive(&mut buf) {
+                Ok(len) if len > 0 => len,
+                _ => break,
+            };
+            iface.stats().record_rx(len);
+            if iface.is_up() {
+                receive_secondary(iface, &mut buf[..len]);
+            } else {
+                iface.stats().record_rx_dropped();
+            }
+        }
+    }
+}
+
+fn receive_secondary(iface: &NetworkInterface, frame: &mut [u8]) {
+    if forward_frame(iface.mac_address(), frame) {
+        return;
+    }
+    let Ok(eth) = EthernetFrame::new_checked(&*frame) else {
+        iface.stats().record_rx_dropped();
+        return;
+    };
+    if eth.ethertype() != EthernetProtocol::Arp {
+        iface.stats().record_rx_dropped();
+        return;
+    }
+    if let Some(ArpRepr::EthernetIpv4 {
+        operation: ArpOperation::Request,
+        source_hardware_addr,
+        source_protocol_addr,
+        target_protocol_addr,
+        ..
+    }) = parse_arp(eth.payload())
+        && iface
+            .primary_ipv4()
+            .is_some_and(|cidr| cidr.address() == target_protocol_addr)
+    {
+        send_arp_reply(
+            iface,
+            target_protocol_addr,
+            source_hardware_addr,
+            source_protocol_addr,
+        );
+    }
+}
+
+fn parse_arp(payload: &[u8]) -> Option<ArpRepr> {
+    let packet = ArpPacket::new_checked(payload).ok()?;
+    ArpRepr::parse(&packet).ok()
+}
+
+fn learn_neighbor(repr: &ArpRepr) {
+    let ArpRepr::EthernetIpv4 {
+        source_hardware_addr,
+        source_protocol_addr,
+        ..
+    } = *repr
+    else {
+        return;
+    };
+    if !source_hardware_addr.is_unicast() || source_protocol_addr.is_unspecified() {
+        return;
+    }
+    let mut neighbors = NEIGHBORS.lock();
+    if neighbors.len() < MAX_NEIGHBORS || neighbors.contains_key(&source_protocol_addr) {
+        neighbors.insert(source_protocol_addr, source_hardware_addr);
+    }
+}
+
+/// Route an IPv4 frame that is not addressed to this host.
+fn forward_ipv4(frame: &mut [u8]) -> bool {
+    let Ok(packet) = Ipv4Packet::new_checked(&frame[ETH_HEADER_LEN..]) else {
+        return false;
+    };
+    let dst = packet.dst_addr();
+    if dst.is_broadcast() || dst.is_multicast() || dst.is_unspecified() {
+        return false;
+    }
+    let route = {
+        let table = ROUTING_TABLE.lock();
+        if table.is_local(dst) {
+            return false;
+        }
+        match table.lookup(dst) {
+            // Directed broadcasts stay on their own network.
+            Some(route) if route.connected && route.dest.broadcast() == Some(dst) => {
+                return false;
+            }
+            route => route.cloned(),
+        }
+    };
+    let Some(route) = route else {
+        crate::pr_debug!("[Net] forward: no route to {}", dst);
+        return true;
+    };
+    if packet.hop_limit() <= 1 {
+        crate::pr_debug!("[Net] forward: ttl exceeded for {}", dst);
+        return true;
+    }
+
+    let Some(egress) = NETWORK_INTERFACE_MANAGER
+        .lock()
+        .find_interface_by_name(&route.iface)
+        .cloned()
+    else {
+        return true;
+    };
+    if egress.is_loopback() || !egress.is_up() {
+        return true;
+    }
+    if frame.len() > egress.device().mtu() + ETH_HEADER_LEN {
+        crate::pr_debug!(
+            "[Net] forward: {} byte frame exceeds {} mtu",
+            frame.len(),
+            egress.name()
+        );
+        return true;
+    }
+
+    let next_hop = route.next_hop(dst);
+    let Some(next_mac) = NEIGHBORS.lock().get(&next_hop).copied() else {
+        send_arp_request(&egress, next_hop);
+        return true;
+    };
+
+    let mut packet = Ipv4Packet::new_unchecked(&mut frame[ETH_HEADER_LEN..]);
+    packet.set_hop_limit(packet.hop_limit() - 1);
+    packet.fill_checksum();
+    let mut eth = EthernetFrame::new_unchecked(&mut *frame);
+    eth.set_dst_addr(next_mac);
+    eth.set_src_addr(egress.mac_address());
+    if egress.device().send(frame).is_ok() {
+        egress.stats().record_tx(frame.len());
+    } else {
+        egress.stats().record_tx_error();
+    }
+    true
+}
+
+/// Broadcast an ARP request for `target_ip` from `iface`.
+fn send_arp_request(iface: &NetworkInterface, target_ip: Ipv4Address) {
+    let Some(cidr) = iface.primary_ipv4() else {
+        return;
+    };
+    send_arp(iface, EthernetAddress::BROADCAST, ArpRepr::EthernetIpv4 {
+        operation: ArpOperation::Request,
+        source_hardware_addr: iface.mac_address(),
+        source_protocol_addr: cidr.address(),
+        target_hardware_addr: EthernetAddress([0; 6]),
+        target_protocol_addr: target_ip,
+    });
+}
+
+/// Answer an ARP request for `source_ip`, one of the addresses of `iface`.
+fn send_arp_reply(
+    iface: &NetworkInterface,
+    source_ip: Ipv4Address,
+    target_mac: EthernetAddress,
+    target_ip: Ipv4Address,
+) {
+    send_arp(iface, target_mac, ArpRepr::EthernetIpv4 {
+        operation: ArpOperation::Reply,
+        source_hardware_addr: iface.mac_address(),
+        source_protocol_addr: source_ip,
+        target_hardware_addr: target_mac,
+        target_protocol_addr: target_ip,
+    });
+}
+
+fn send_arp(iface: &NetworkInterface, dst_mac: EthernetAddress, arp: ArpRepr) {
+    let eth = EthernetRepr {
+        src_addr: iface.mac_address(),
+        dst_addr: dst_mac,
+        ethertype: EthernetProtocol::Arp,
+    };
+    let mut buf: Vec<u8> = alloc::vec![0; eth.buffer_len() + arp.buffer_len()];
+    let mut frame = EthernetFrame::new_unchecked(&mut buf[..]);
+    eth.emit(&mut frame);
+    arp.emit(&mut ArpPacket::new_unchecked(frame.payload_mut()));
+    if iface.device().send(&buf).is_ok() {
+        iface.stats().record_tx(buf.len());
+    } else {
+        iface.stats().record_tx_error();
+    }
+}
@@ -0,0 +1,317 @@
+//! ICMP sockets used by `ping`.
+//!
+//! Echo requests addressed to this host are answered by the smoltcp interface
+//! itself. This module provides the user-facing sockets:
+//! - `SOCK_DGRAM` + `IPPROTO_ICMP` ping sockets. Userspace writes an ICMP echo
+//!   header and payload; the kernel owns the echo identifier, rewrites it on
+//!   send and only delivers replies carrying that identifier.
+//! - `SOCK_RAW` + `IPPROTO_ICMP` sockets (`CAP_NET_RAW`). Userspace writes a
+//!   complete ICMP message, the kernel adds the IPv4 header, and every ICMP
+//!   packet is received with its IPv4 header.
+
+use core::sync::atomic::{AtomicU16, Ordering};
+
+use smoltcp::phy::ChecksumCapabilities;
+use smoltcp::socket::{icmp, raw};
+use smoltcp::wire::{IpProtocol, IpVersion, Ipv4Packet, Ipv4Repr};
+
+use super::*;
+use crate::vfs::FsError;
+
+const ICMP_PACKET_METADATA_CAPACITY: usize = 64;
+const ICMP_BUFFER_SIZE: usize = 64 * 1024;
+const ICMP_RXQ_MAX: usize = 64;
+const ICMP_HEADER_LEN: usize = 8;
+const ICMP_ECHO_REQUEST: u8 = 8;
+const IPV4_HEADER_LEN: usize = 20;
+const RAW_HOP_LIMIT: u8 = 64;
+
+/// Next echo identifier handed out to an unbound ping socket.
+static NEXT_ICMP_IDENT: AtomicU16 = AtomicU16::new(1);
+
+/// Per-socket state of a ping socket.
+#[derive(Default)]
+pub(super) struct IcmpSocketState {
+    /// Echo identifier the socket is bound to.
+    ident: Option<u16>,
+    /// Received echo replies (and ICMP errors), without our own echo requests.
+    rx_queue: VecDeque<(IpAddress, Vec<u8>)>,
+}
+
+/// Receive metadata, receive data, transmit metadata and transmit data storage.
+type PacketStorage<M> = (Vec<M>, Vec<u8>, Vec<M>, Vec<u8>);
+
+fn packet_buffers<M: Clone>(empty: M) -> Result<PacketStorage<M>, NetworkError> {
+    let mut rx_meta = Vec::new();
+    let mut tx_meta = Vec::new();
+    let mut rx_data = Vec::new();
+    let mut tx_data = Vec::new();
+    rx_meta
+        .try_reserve(ICMP_PACKET_METADATA_CAPACITY)
+        .and_then(|_| tx_meta.try_reserve(ICMP_PACKET_METADATA_CAPACITY))
+        .and_then(|_| rx_data.try_reserve(ICMP_BUFFER_SIZE))
+        .and_then(|_| tx_data.try_reserve(ICMP_BUFFER_SIZE))
+        .map_err(|_| NetworkError::NoMemory)?;
+    rx_meta.resize(ICMP_PACKET_METADATA_CAPACITY, empty.clone());
+    tx_meta.resize(ICMP_PACKET_METADATA_CAPACITY, empty);
+    rx_data.resize(ICMP_BUFFER_SIZE, 0);
+    tx_data.resize(ICMP_BUFFER_SIZE, 0);
+    Ok((rx_meta, rx_data, tx_meta, tx_data))
+}
+
+impl NetworkStack {
+    /// Create an unprivileged ICMP echo ("ping") socket.
+    pub fn create_icmp_socket(&self) -> Result<SocketHandle, NetworkError> {
+        let (rx_meta, rx_data, tx_meta, tx_data) = packet_buffers(icmp::PacketMetadata::EMPTY)?;
+        let socket = icmp::Socket::new(
+            icmp::PacketBuffer::new(rx_meta, rx_data),
+            icmp::PacketBuffer::new(tx_meta, tx_data),
+        );
+        let handle = self.socket_set.lock().add(socket);
+        self.icmp_sockets
+            .lock()
+            .insert(handle, IcmpSocketState::default());
+        Ok(SocketHandle::Icmp(handle))
+    }
+
+    /// Create a raw IPv4 socket carrying ICMP.
+    pub fn create_raw_socket(&self) -> Result<SocketHandle, NetworkError> {
+        let (rx_meta, rx_data, tx_meta, tx_data) = packet_buffers(raw::PacketMetadata::EMPTY)?;
+        let socket = raw::Socket::new(
+            IpVersion::Ipv4,
+            IpProtocol::Icmp,
+            raw::PacketBuffer::new(rx_meta, rx_data),
+            raw::PacketBuffer::new(tx_meta, tx_data),
+        );
+        let handle = self.socket_set.lock().add(socket);
+        Ok(SocketHandle::Raw(handle))
+    }
+
+    /// Bind a ping socket to an echo identifier; `0` picks a free one.
+    pub fn icmp_bind(&self, handle: SmoltcpHandle, ident: u16) -> Result<u16, NetworkError> {
+        let mut sockets = self.socket_set.lock();
+        self.icmp_bind_locked(&mut sockets, handle, ident)
+    }
+
+    /// Echo identifier a ping socket is bound to.
+    pub fn icmp_ident(&self, handle: SmoltcpHandle) -> Option<u16> {
+        self.icmp_sockets.lock().get(&handle)?.ident
+    }
+
+    fn icmp_bind_locked(
+        &self,
+        sockets: &mut SocketSet<'static>,
+        handle: SmoltcpHandle,
+        ident: u16,
+    ) -> Result<u16, NetworkError> {
+        let mut states = self.icmp_sockets.lock();
+        if states.get(&handle).is_some_and(|s| s.ident.is_some()) {
+            return Err(NetworkError::InvalidAddress);
+        }
+        let in_use = |id: u16| states.values().any(|s| s.ident == Some(id));
+        let ident = if ident != 0 {
+            if in_use(ident) {
+                return Err(NetworkError::AddressInUse);
+            }
+            ident
+        } else {
+            let mut id = 0;
+            for _ in 0..=u16::MAX {
+                let candidate = NEXT_ICMP_IDENT.fetch_add(1, Ordering::Relaxed);
+                if candidate != 0 && !in_use(candidate) {
+                    id = candidate;
+                    break;
+                }
+            }
+            if id == 0 {
+                return Err(NetworkError::AddressInUse);
+            }
+            id
+        };
+        sockets
+            .get_mut::<icmp::Socket>(handle)
+            .bind(icmp::Endpoint::Ident(ident))
+            .map_err(|_| NetworkError::InvalidAddress)?;
+        states.entry(handle).or_default().ident = Some(ident);
+        Ok(ident)
+    }
+
+    /// Remove a ping socket and its queued replies.
+    pub(super) fn remove_icmp_socket_locked(
+        &self,
+        sockets: &mut SocketSet<'static>,
+        handle: SmoltcpHandle,
+    ) {
+        self.icmp_sockets.lock().remove(&handle);
+        sockets.remove(handle);
+    }
+
+    /// Queue an echo message on a ping socket, binding it first if needed.
+    pub(super) fn icmp_send_locked(
+        &self,
+        sockets: &mut SocketSet<'static>,
+        handle: SmoltcpHandle,
+        buf: &[u8],
+        addr: IpAddress,
+    ) -> Result<usize, FsError> {
+        if buf.len() < ICMP_HEADER_LEN {
+            return Err(FsError::InvalidArgument);
+        }
+        let ident = match self.icmp_ident(handle) {
+            Some(ident) => ident,
+            None => self
+                .icmp_bind_locked(sockets, handle, 0)
+                .map_err(|_| FsError::WouldBlock)?,
+        };
+        let socket = sockets.get_mut::<icmp::Socket>(handle);
+        let packet = socket
+            .send(buf.len(), addr)
+            .map_err(|_| FsError::WouldBlock)?;
+        packet.copy_from_slice(buf);
+        // The kernel owns the identifier of echo requests sent on a ping socket.
+        // smoltcp recomputes the checksum when it emits the message.
+        if packet[0] == ICMP_ECHO_REQUEST {
+            packet[4..6].copy_from_slice(&ident.to_be_bytes());
+        }
+        Ok(buf.len())
+    }
+
+    /// Queue an ICMP message on a raw socket behind a freshly built IPv4 header.
+    pub(super) fn raw_send_locked(
+        &self,
+        sockets: &mut SocketSet<'static>,
+        handle: SmoltcpHandle,
+        buf: &[u8],
+        src: Ipv4Address,
+        dst: Ipv4Address,
+    ) -> Result<usize, FsError> {
+        let repr = Ipv4Repr {
+            src_addr: src,
+            dst_addr: dst,
+            next_header: IpProtocol::Icmp,
+            payload_len: buf.len(),
+            hop_limit: RAW_HOP_LIMIT,
+        };
+        let socket = sockets.get_mut::<raw::Socket>(handle);
+        let packet = socket
+            .send(IPV4_HEADER_LEN + buf.len())
+            .map_err(|_| FsError::WouldBlock)?;
+        repr.emit(
+            &mut Ipv4Packet::new_unchecked(&mut packet[..]),
+            &ChecksumCapabilities::default(),
+        );
+        packet[IPV4_HEADER_LEN..].copy_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    /// Source address for a raw packet sent to `dst`.
+    ///
+    /// Must be called without the socket set held (lock order: interface, then sockets).
+    pub(super) fn raw_source_address(&self, dst: Ipv4Address) -> Option<Ipv4Address> {
+        if dst.is_loopback() {
+            return Some(Ipv4Address::new(127, 0, 0, 1));
+        }
+        let iface_guard = self.net_iface.lock();
+        let wrapper = iface_guard.as_ref()?;
+        let iface = wrapper.interface.lock();
+        iface.get_source_address_ipv4(&dst)
+    }
+
+    /// Move received messages into the ping socket queue, dropping our own echo requests.
+    fn icmp_fill_locked(&self, sockets: &mut SocketSet<'static>, handle: SmoltcpHandle) {
+        let socket = sockets.get_mut::<icmp::Socket>(handle);
+        let mut states = self.icmp_sockets.lock();
+        let Some(state) = states.get_mut(&handle) else {
+            return;
+        };
+        while let Ok((payload, addr)) = socket.recv() {
+            // Requests sent to ourselves over loopback carry our identifier too.
+            if payload.first() == Some(&ICMP_ECHO_REQUEST) {
+                continue;
+            }
+            if state.rx_queue.len() >= ICMP_RXQ_MAX {
+                state.rx_queue.pop_front();
+            }
+            state.rx_queue.push_back((addr, payload.to_vec()));
+        }
+    }
+
+    /// Whether a ping socket has a reply ready.
+    pub(super) fn icmp_readable_locked(
+        &self,
+        sockets: &mut SocketSet<'static>,
+        handle: SmoltcpHandle,
+    ) -> bool {
+        self.icmp_fill_locked(sockets, handle);
+        self.icmp_sockets
+            .lock()
+            .get(&handle)
+            .is_some_and(|s| !s.rx_queue.is_empty())
+    }
+
+    /// Receive one ICMP message (header and payload) from a ping socket.
+    pub(super) fn icmp_recv_locked(
+        &self,
+        sockets: &mut SocketSet<'static>,
+        handle: SmoltcpHandle,
+        buf: &mut [u8],
+    ) -> Result<(usize, IpAddress), FsError> {
+        self.icmp_fill_locked(sockets, handle);
+        let (addr, data) = self
+            .icmp_sockets
+            .lock()
+            .get_mut(&handle)
+            .and_then(|s| s.rx_queue.pop_front())
+            .ok_or(FsError::WouldBlock)?;
+        let n = core::cmp::min(buf.len(), data.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        Ok((n, addr))
+    }
+
+    /// Receive one IPv4 packet, header included, from a raw socket.
+    pub(super) fn raw_recv_locked(
+        &self,
+        sockets: &mut SocketSet<'static>,
+        handle: SmoltcpHandle,
+        buf: &mut [u8],
+    ) -> Result<(usize, IpAddress), FsError> {
+        let packet = sockets
+            .get_mut::<raw::Socket>(handle)
+            .recv()
+            .map_err(|_| FsError::WouldBlock)?;
+        let src = Ipv4Packet::new_checked(packet)
+            .map(|p| IpAddress::Ipv4(p.src_addr()))
+            .unwrap_or(IpAddress::Ipv4(Ipv4Address::UNSPECIFIED));
+        let n = core::cmp::min(buf.len(), packet.len());
+        buf[..n].copy_from_slice(&packet[..n]);
+        Ok((n, src))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_icmp_socket_ident, {
+        let stack = network_stack();
+        let Ok(SocketHandle::Icmp(a)) = stack.create_icmp_socket() else {
+            panic!("create_icmp_socket failed");
+        };
+        let Ok(SocketHandle::Icmp(b)) = stack.create_icmp_socket() else {
+            panic!("create_icmp_socket failed");
+        };
+        kassert!(stack.icmp_ident(a).is_none());
+        let ident = stack.icmp_bind(a, 0).expect("icmp_bind failed");
+        kassert!(ident != 0);
+        kassert!(stack.icmp_ident(a) == Some(ident));
+        // A socket cannot be rebound, and identifiers are not shared.
+        kassert!(stack.icmp_bind(a, 0).is_err());
+        kassert!(stack.icmp_bind(b, ident).is_err());
+        kassert!(stack.icmp_bind(b, 0).is_ok_and(|id| id != ident));
+
+        let mut sockets = stack.socket_set.lock();
+        stack.remove_icmp_socket_locked(&mut sockets, a);
+        stack.remove_icmp_socket_locked(&mut sockets, b);
+    });
+}
@@ -8,15 +8,21 @@ use alloc::sync::Arc;
 use alloc::vec::Vec;
 use lazy_static::lazy_static;
 use smoltcp::iface::{Interface, SocketHandle as SmoltcpHandle, SocketSet};
-use smoltcp::socket::{tcp, udp};
+use smoltcp::socket::{raw, tcp, udp};
 use smoltcp::time::Instant;
 use smoltcp::wire::{EthernetAddress, IpAddress, IpEndpoint, IpListenEndpoint, Ipv4Address};
 
 use super::NetworkError;
+use super::interface::InterfaceStats;
 use super::socket::{self, SocketFile, SocketHandle, UdpDatagram};
+use crate::uapi::socket::SocketOptions;
 
 mod adapter;
+pub(crate) mod forward;
+mod icmp;
+mod sock_info;
 pub use adapter::{NetDeviceAdapter, SmoltcpInterface};
+pub use sock_info::InetSockInfo;
 
 // 256KiB buffers can drive smoltcp's large-window path into a sequence underflow
 // under parallel loopback iperf; 128KiB-1 keeps throughput high with a stable window scale.
@@ -28,6 +34,24 @@ const UDP_TX_BUFFER_SIZE: usize = 256 * 1024;
 const LOOPBACK_WRITE_DRAIN_POLLS: usize = 64;
 const LOOPBACK_FULL_DRAIN_POLLS: usize = 256;
 
+fn new_tcp_socket(rx_size: usize, tx_size: usize) -> Result<tcp::Socket<'static>, NetworkError> {
+    let mut rx_vec = alloc::vec::Vec::new();
+    rx_vec
+        .try_reserve(rx_size)
+        .map_err(|_| NetworkError::NoMemory)?;
+    rx_vec.resize(rx_size, 0);
+
+    let mut tx_vec = alloc::vec::Vec::new();
+    tx_vec
+        .try_reserve(tx_size)
+        .map_err(|_| NetworkError::NoMemory)?;
+    tx_vec.resize(tx_size, 0);
+
+    let rx_buffer = tcp::SocketBuffer::new(rx_vec);
+    let tx_buffer = tcp::SocketBuffer::new(tx_vec);
+    Ok(tcp::Socket::new(rx_buffer, tx_buffer))
+}
+
 pub(crate) fn enqueue_loopback_frame(frame: Vec<u8>) {
     network_stack().enqueue_loopback_frame(frame);
 }
@@ -50,6 +74,7 @@ impl NetIfaceWrapper {
     fn poll_smoltcp(&self, sockets: &SpinLock<SocketSet<'static>>) -> bool {
         let timestamp = smoltcp::time::Instant::from_millis(crate::arch::get_time_ms() as i64);
         let mut dev = self.device.lock();
+        dev.set_rx_budget(crate::device::net::napi::NAPI_WEIGHT);
 
         let queue_len = dev.loopback_queue_len();
         if queue_len > 0 {
@@ -81,6 +106,10 @@ impl NetIfaceWrapper {
         self.device.lock().loopback_queue_len()
     }
 
+    fn primary_device(&self) -> Arc<dyn crate::device::net::net_device::NetDevice> {
+        self.device.lock().device().clone()
+    }
+
     fn with_context<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&mut smoltcp::iface::Context) -> R,
@@ -126,6 +155,7 @@ pub struct NetworkStack {
     loopback_link: SpinLock<VecDeque<Vec<u8>>>,
     udp_ports: SpinLock<BTreeMap<u16, UdpPortEntry>>,
     pending_tcp_close: SpinLock<alloc::vec::Vec<SmoltcpHandle>>,
+    icmp_sockets: SpinLock<BTreeMap<SmoltcpHandle, icmp::IcmpSocketState>>,
 }
 
 impl NetworkStack {
@@ -136,6 +166,7 @@ impl NetworkStack {
             loopback_link: SpinLock::new(VecDeque::new()),
             udp_ports: SpinLock::new(BTreeMap::new()),
             pending_tcp_close: SpinLock::new(alloc::vec::Vec::new()),
+            icmp_sockets: SpinLock::new(BTreeMap::new()),
         }
     }
 
@@ -160,26 +191,145 @@ impl NetworkStack {
         *self.net_iface.lock() = Some(wrapper);
     }
 
+    /// Push interface addresses and routes into smoltcp.
+    ///
+    /// All interfaces share one smoltcp interface, which carries the addresses of
+    /// every interface that is up. The connected routes of the kernel routing
+    /// table are rebuilt from the same addresses, and every gateway route is
+    /// handed to smoltcp so that locally generated packets use the right next hop.
+    pub fn sync_interface_config(&self) {
+        let interfaces = crate::net::interface::NETWORK_INTERFACE_MANAGER
+            .lock()
+            .get_interfaces()
+            .to_vec();
+        let mut cidrs: Vec<smoltcp::wire::IpCidr> = Vec::new();
+        let mut connected = Vec::new();
+        for iface in interfaces.iter().filter(|iface| iface.is_up()) {
+            for cidr in iface.ip_addresses() {
+                if !cidrs.contains(&cidr) {
+                    cidrs.push(cidr);
+                }
+                // 127/8 on a real NIC only exists for smoltcp's benefit.
+                if let smoltcp::wire::IpCidr::Ipv4(v4) = cidr
+                    && (iface.is_loopback() || !v4.address().is_loopback())
+                {
+                    connected.push((v4, alloc::string::String::from(iface.name())));
+                }
+            }
+        }
+        let gateway_routes: Vec<smoltcp::iface::Route> = {
+            let mut table = crate::net::route::ROUTING_TABLE.lock();
+            table.set_connected(&connected);
+            table
+                .routes()
+                .iter()
+                .filter_map(|route| {
+                    Some(smoltcp::iface::Route {
+                        cidr: route.dest.into(),
+                        via_router: route.gateway?.into(),
+                        preferred_until: None,
+                        expires_at: None,
+                    })
+                })
+                .collect()
+        };
+
+        let iface_guard = self.net_iface.lock();
+        let Some(wrapper) = iface_guard.as_ref() else {
+            return;
+        };
+        let mut iface = wrapper.interface.lock();
+        iface.update_ip_addrs(|addrs| {
+            addrs.clear();
+            for cidr in cidrs {
+                if addrs.push(cidr).is_err() {
+                    crate::pr_warn!("[Net] too many interface addresses, dropping {}", cidr);
+                }
+            }
+        });
+        iface.routes_mut().update(|routes| {
+            routes.clear();
+            for route in gateway_routes {
+                if routes.push(route).is_err() {
+                    crate::pr_warn!("[Net] too many gateway routes, dropping {}", route.cidr);
+                }
+            }
+        });
+    }
+
     /// Create a TCP socket in the stack runtime.
     pub fn create_tcp_socket(&self) -> Result<SocketHandle, NetworkError> {
-        let mut rx_vec = alloc::vec::Vec::new();
-        rx_vec
-            .try_reserve(TCP_RX_BUFFER_SIZE)
-            .map_err(|_| NetworkError::NoMemory)?;
-        rx_vec.resize(TCP_RX_BUFFER_SIZE, 0);
+        let socket = new_tcp_socket(TCP_RX_BUFFER_SIZE, TCP_TX_BUFFER_SIZE)?;
+        let handle = self.socket_set.lock().add(socket);
+        Ok(SocketHandle::Tcp(handle))
+    }
 
-        let mut tx_vec = alloc::vec::Vec::new();
-        tx_vec
-            .try_reserve(TCP_TX_BUFFER_SIZE)
-            .map_err(|_| NetworkError::NoMemory)?;
-        tx_vec.resize(TCP_TX_BUFFER_SIZE, 0);
+    /// Apply socket options to a TCP socket.
+    ///
+    /// TCP_NODELAY turns Nagle's algorithm off. SO_RCVBUF / SO_SNDBUF size the
+    /// socket buffers, capped at the defaults; smoltcp cannot resize a live
+    /// buffer, so they only take effect while the socket is still closed, i.e.
+    /// before connect() or listen(), as Linux requires for the window scale.
+    pub fn tcp_apply_options(&self, handle: SmoltcpHandle, opts: &SocketOptions) {
+        let rx_size = opts.recv_buffer_size.min(TCP_RX_BUFFER_SIZE);
+        let tx_size = opts.send_buffer_size.min(TCP_TX_BUFFER_SIZE);
+        let resize = {
+            let mut sockets = self.socket_set.lock();
+            let socket = sockets.get_mut::<tcp::Socket>(handle);
+            socket.set_nagle_enabled(!opts.tcp_nodelay);
+            socket.state() == tcp::State::Closed
+                && (socket.recv_capacity() != rx_size || socket.send_capacity() != tx_size)
+        };
+        if !resize {
+            return;
+        }
+        // Allocate outside the socket set lock, then recheck the state.
+        let Ok(mut socket) = new_tcp_socket(rx_size, tx_size) else {
+            return;
+        };
+        socket.set_nagle_enabled(!opts.tcp_nodelay);
+        let mut sockets = self.socket_set.lock();
+        let old = sockets.get_mut::<tcp::Socket>(handle);
+        if old.state() == tcp::State::Closed {
+            *old = socket;
+        }
+    }
 
-        let rx_buffer = tcp::SocketBuffer::new(rx_vec);
-        let tx_buffer = tcp::SocketBuffer::new(tx_vec);
-        let socket = tcp::Socket::new(rx_buffer, tx_buffer);
+    /// Whether a TCP connection closing without a file still holds `endpoint`.
+    pub fn tcp_closing_on(&self, endpoint: IpEndpoint) -> bool {
+        let sockets = self.socket_set.lock();
+        let pending = self.pending_tcp_close.lock();
+        pending.iter().any(|&h| {
+            sockets
+                .get::<tcp::Socket>(h)
+                .local_endpoint()
+                .is_some_and(|local| {
+                    local.port == endpoint.port
+                        && socket::bind_addrs_overlap(local.addr, endpoint.addr)
+                })
+        })
+    }
 
-        let handle = self.socket_set.lock().add(socket);
-        Ok(SocketHandle::Tcp(handle))
+    /// Record the outcome of a non-blocking connect in the file's SO_ERROR.
+    pub fn update_connect_error(&self, file: &SocketFile) {
+        if !file.is_connecting() {
+            return;
+        }
+        let state = {
+            let sockets = self.socket_set.lock();
+            match *file.handle.lock() {
+                Some(SocketHandle::Tcp(h)) => sockets.get::<tcp::Socket>(h).state(),
+                _ => tcp::State::Closed,
+            }
+        };
+        match state {
+            tcp::State::SynSent | tcp::State::SynReceived => {}
+            tcp::State::Closed => {
+                file.set_connecting(false);
+                file.set_error(crate::uapi::errno::ECONNREFUSED);
+            }
+            _ => file.set_connecting(false),
+        }
     }
 
     /// Create a UDP socket in the stack runtime.
@@ -316,6 +466,15 @@ impl NetworkStack {
                     listen_ep.port,
                 ))
             }
+            SocketHandle::Icmp(h) => {
+                drop(sockets);
+                let ident = self.icmp_ident(h)?;
+                Some(IpEndpoint::new(
+                    IpAddress::Ipv4(Ipv4Address::UNSPECIFIED),
+                    ident,
+                ))
+            }
+            SocketHandle::Raw(_) => None,
         }
     }
 
@@ -324,7 +483,7 @@ impl NetworkStack {
         let sockets = self.socket_set.lock();
         match handle {
             SocketHandle::Tcp(h) => sockets.get::<tcp::Socket>(h).remote_endpoint(),
-            SocketHandle::Udp(_) => None,
+            SocketHandle::Udp(_) | SocketHandle::Icmp(_) | SocketHandle::Raw(_) => None,
         }
     }
 
@@ -333,6 +492,7 @@ impl NetworkStack {
         if let Some(ref wrapper) = *self.net_iface.lock() {
             crate::pr_debug!("poll_network_interfaces: calling poll");
             let smoltcp_changed = wrapper.poll_smoltcp(&self.socket_set);
+            forward::poll_secondary_interfaces(&wrapper.primary_device());
             let udp_changed = {
                 let mut sockets = self.socket_set.lock();
                 self.udp_dispatch_drain_locked(&mut sockets)
@@ -468,7 +628,7 @@ impl NetworkStack {
                         _ => {}
                     }
                 }
-                SocketHandle::Udp(_) => {
+                _ => {
                     q.remove(i);
                     continue;
                 }
@@ -503,7 +663,7 @@ impl NetworkStack {
                         _ => {}
                     }
                 }
-                SocketHandle::Udp(_) => {
+                _ => {
                     q.remove(i);
                     continue;
                 }
@@ -538,7 +698,7 @@ impl NetworkStack {
                         _ => {}
                     }
                 }
-                SocketHandle::Udp(_) => {
+                _ => {
                     q.remove(i);
                     continue;
                 }
@@ -588,15 +748,21 @@ impl NetworkStack {
                         sockets.remove(h);
                     }
                 }
+                SocketHandle::Icmp(h) => {
+                    self.remove_icmp_socket_locked(&mut sockets, h);
+                }
+                SocketHandle::Raw(h) => {
+                    sockets.remove(h);
+                }
             }
         }
         for handle in file.listen_sockets.lock().iter() {
             match handle {
-                SocketHandle::Tcp(h) => {
+                SocketHandle::Tcp(h) | SocketHandle::Udp(h) | SocketHandle::Raw(h) => {
                     sockets.remove(*h);
                 }
-                SocketHandle::Udp(h) => {
-                    sockets.remove(*h);
+                SocketHandle::Icmp(h) => {
+                    self.remove_icmp_socket_locked(&mut sockets, *h);
                 }
             }
         }
@@ -609,6 +775,10 @@ impl NetworkStack {
         buf: &[u8],
         endpoint: IpEndpoint,
     ) -> Result<usize, crate::vfs::FsError> {
+        let raw_src = match (handle, endpoint.addr) {
+            (SocketHandle::Raw(_), IpAddress::Ipv4(dst)) => self.raw_source_address(dst),
+            _ => None,
+        };
         let result = {
             let mut sockets = self.socket_set.lock();
             match handle {
@@ -620,6 +790,13 @@ impl NetworkStack {
                         .map_err(|_| crate::vfs::FsError::WouldBlock)?;
                     Ok(buf.len())
                 }
+                SocketHandle::Icmp(h) => self.icmp_send_locked(&mut sockets, h, buf, endpoint.addr),
+                SocketHandle::Raw(h) => match (raw_src, endpoint.addr) {
+                    (Some(src), IpAddress::Ipv4(dst)) => {
+                        self.raw_send_locked(&mut sockets, h, buf, src, dst)
+                    }
+                    _ => Err(crate::vfs::FsError::InvalidArgument),
+                },
             }
         };
         if result.is_ok() {
@@ -668,6 +845,11 @@ impl NetworkStack {
                 drop(sockets);
                 file.udp_queue_len() > 0
             }
+            Some(SocketHandle::Icmp(h)) => {
+                let mut sockets = sockets;
+                self.icmp_readable_locked(&mut sockets, *h)
+            }
+            Some(SocketHandle::Raw(h)) => sockets.get::<raw::Socket>(*h).can_recv(),
             None => false,
         }
     }
@@ -686,12 +868,18 @@ impl NetworkStack {
                     state,
                     can_send
                 );
-                can_send
+                // A failed non-blocking connect is reported writable, as on Linux,
+                // so that poll() returns and SO_ERROR can be read.
+                can_send || (state == tcp::State::Closed && file.is_connecting())
             }
             Some(SocketHandle::Udp(h)) => {
                 let socket = sockets.get::<udp::Socket>(*h);
                 socket.can_send()
             }
+            Some(SocketHandle::Icmp(h)) => {
+                sockets.get::<smoltcp::socket::icmp::Socket>(*h).can_send()
+            }
+            Some(SocketHandle::Raw(h)) => sockets.get::<raw::Socket>(*h).can_send(),
             None => false,
         }
     }
@@ -765,6 +953,12 @@ impl NetworkStack {
                 buf[..n].copy_from_slice(&d.data[..n]);
                 Ok(n)
             }
+            Some(SocketHandle::Icmp(h)) => {
+                self.icmp_recv_locked(&mut sockets, *h, buf).map(|(n, _)| n)
+            }
+            Some(SocketHandle::Raw(h)) => {
+                self.raw_recv_locked(&mut sockets, *h, buf).map(|(n, _)| n)
+            }
             None => Err(crate::vfs::FsError::InvalidArgument),
         };
         if result.is_ok() {
@@ -815,6 +1009,13 @@ impl NetworkStack {
                         .map_err(|_| crate::vfs::FsError::WouldBlock)?;
                     Ok(buf.len())
                 }
+                Some(handle @ (SocketHandle::Icmp(_) | SocketHandle::Raw(_))) => {
+                    let Some(endpoint) = file.get_remote_endpoint() else {
+                        return Err(crate::vfs::FsError::DestinationAddressRequired);
+                    };
+                    drop(sockets);
+                    return self.socket_sendto(*handle, buf, endpoint);
+                }
                 None => Err(crate::vfs::FsError::InvalidArgument),
             }
         };
@@ -879,6 +1080,18 @@ impl NetworkStack {
                 let _ = socket::write_sockaddr_in_to_buf(&mut addr_buf, d.src);
                 Ok((n, Some(addr_buf)))
             }
+            Some(SocketHandle::Icmp(h)) => {
+                let (n, src) = self.icmp_recv_locked(&mut sockets, *h, buf)?;
+                let mut addr_buf = alloc::vec![0u8; 16];
+                let _ = socket::write_sockaddr_in_to_buf(&mut addr_buf, IpEndpoint::new(src, 0));
+                Ok((n, Some(addr_buf)))
+            }
+            Some(SocketHandle::Raw(h)) => {
+                let (n, src) = self.raw_recv_locked(&mut sockets, *h, buf)?;
+                let mut addr_buf = alloc::vec![0u8; 16];
+                let _ = socket::write_sockaddr_in_to_buf(&mut addr_buf, IpEndpoint::new(src, 0));
+                Ok((n, Some(addr_buf)))
+            }
             None => Err(crate::vfs::FsError::InvalidArgument),
         }
     }
@@ -1025,3 +1238,32 @@ lazy_static! {
 pub fn network_stack() -> &'static NetworkStack {
     &NETWORK_STACK
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_tcp_apply_options, {
+        let stack = network_stack();
+        let Ok(SocketHandle::Tcp(h)) = stack.create_tcp_socket() else {
+            panic!("create_tcp_socket failed");
+        };
+        let opts = SocketOptions {
+            tcp_nodelay: true,
+            recv_buffer_size: 8192,
+            send_buffer_size: 1 << 30,
+            ..SocketOptions::default()
+        };
+        stack.tcp_apply_options(h, &opts);
+        {
+            let sockets = stack.socket_set.lock();
+            let socket = sockets.get::<tcp::Socket>(h);
+            kassert!(!socket.nagle_enabled());
+            kassert!(socket.recv_capacity() == 8192);
+            // Buffers never grow past the defaults.
+            kassert!(socket.send_capacity() == TCP_TX_BUFFER_SIZE);
+        }
+        stack.remove_tcp_socket(h);
+    });
+}
@@ -0,0 +1,187 @@
+//! Socket snapshots for /proc/net/tcp and /proc/net/udp.
+
+use alloc::vec::Vec;
+use smoltcp::iface::{SocketHandle as SmoltcpHandle, SocketSet};
+use smoltcp::socket::{AnySocket, tcp, udp};
+use smoltcp::wire::{IpEndpoint, IpListenEndpoint};
+
+use super::NetworkStack;
+use crate::net::socket::{self, SocketHandle};
+
+/// Linux `TCP_ESTABLISHED`.
+pub const TCP_ESTABLISHED: u8 = 0x01;
+/// Linux `TCP_CLOSE`.
+pub const TCP_CLOSE: u8 = 0x07;
+/// Linux `TCP_LISTEN`.
+pub const TCP_LISTEN: u8 = 0x0A;
+
+/// Linux `TCP_*` state number of a smoltcp TCP state.
+fn tcp_state_code(state: tcp::State) -> u8 {
+    match state {
+        tcp::State::Established => TCP_ESTABLISHED,
+        tcp::State::SynSent => 0x02,
+        tcp::State::SynReceived => 0x03,
+        tcp::State::FinWait1 => 0x04,
+        tcp::State::FinWait2 => 0x05,
+        tcp::State::TimeWait => 0x06,
+        tcp::State::Closed => TCP_CLOSE,
+        tcp::State::CloseWait => 0x08,
+        tcp::State::LastAck => 0x09,
+        tcp::State::Listen => TCP_LISTEN,
+        tcp::State::Closing => 0x0B,
+    }
+}
+
+/// One row of /proc/net/tcp or /proc/net/udp.
+#[derive(Debug, Clone, Copy)]
+pub struct InetSockInfo {
+    /// Bound address and port; an unspecified address is `None`.
+    pub local: IpListenEndpoint,
+    /// Peer, if connected.
+    pub remote: Option<IpEndpoint>,
+    /// Linux `TCP_*` state number.
+    pub state: u8,
+    /// Bytes waiting to be sent.
+    pub tx_queue: usize,
+    /// Bytes waiting to be read.
+    pub rx_queue: usize,
+    /// Effective uid of the owner.
+    pub uid: u32,
+    /// Socket inode number; 0 for sockets no longer owned by a file.
+    pub ino: usize,
+}
+
+impl InetSockInfo {
+    fn is_ipv4(&self) -> bool {
+        self.local
+            .addr
+            .is_none_or(|addr| addr.version() == smoltcp::wire::IpVersion::Ipv4)
+    }
+}
+
+/// Look up a socket without panicking on handles that were already removed.
+fn find_socket<'a, T: AnySocket<'static>>(
+    sockets: &'a SocketSet<'static>,
+    handle: SmoltcpHandle,
+) -> Option<&'a T> {
+    sockets
+        .iter()
+        .find(|(h, _)| *h == handle)
+        .and_then(|(_, socket)| T::downcast(socket))
+}
+
+fn tcp_row(socket: &tcp::Socket, uid: u32, ino: usize) -> InetSockInfo {
+    InetSockInfo {
+        local: socket
+            .local_endpoint()
+            .map(Into::into)
+            .unwrap_or_else(|| socket.listen_endpoint()),
+        remote: socket.remote_endpoint(),
+        state: tcp_state_code(socket.state()),
+        tx_queue: socket.send_queue(),
+        rx_queue: socket.recv_queue(),
+        uid,
+        ino,
+    }
+}
+
+impl NetworkStack {
+    /// Rows of /proc/net/tcp.
+    ///
+    /// A listening file is shown once in `LISTEN`; connections it has
+    /// established but not yet accepted and sockets still closing after their
+    /// file was dropped are shown without an inode, as on Linux.
+    pub fn tcp_sock_info(&self) -> Vec<InetSockInfo> {
+        let files = socket::inet_sockets();
+        let mut rows = Vec::new();
+        {
+            let sockets = self.socket_set.lock();
+            for file in &files {
+                let Some(SocketHandle::Tcp(handle)) = *file.handle.lock() else {
+                    continue;
+                };
+                if file.is_listener() {
+                    let local = file.get_local_endpoint().map(Into::into).or_else(|| {
+                        find_socket::<tcp::Socket>(&sockets, handle).map(|s| s.listen_endpoint())
+                    });
+                    let Some(local) = local else {
+                        continue;
+                    };
+                    let pending: Vec<&tcp::Socket> = file
+                        .listen_sockets
+                        .lock()
+                        .iter()
+                        .filter_map(|h| match *h {
+                            SocketHandle::Tcp(h) => find_socket::<tcp::Socket>(&sockets, h),
+                            _ => None,
+                        })
+                        .filter(|s| s.state() != tcp::State::Listen)
+                        .collect();
+                    rows.push(InetSockInfo {
+                        local,
+                        remote: None,
+                        state: TCP_LISTEN,
+                        tx_queue: 0,
+                        // Like Linux, the accept queue length.
+                        rx_queue: pending.len(),
+                        uid: file.uid(),
+                        ino: file.ino(),
+                    });
+                    rows.extend(pending.into_iter().map(|s| tcp_row(s, file.uid(), 0)));
+                } else if let Some(s) = find_socket::<tcp::Socket>(&sockets, handle) {
+                    // Sockets that were never bound are not listed.
+                    if s.state() != tcp::State::Closed || s.listen_endpoint().port != 0 {
+                        rows.push(tcp_row(s, file.uid(), file.ino()));
+                    }
+                }
+            }
+            for &handle in self.pending_tcp_close.lock().iter() {
+                if let Some(s) = find_socket::<tcp::Socket>(&sockets, handle) {
+                    rows.push(tcp_row(s, 0, 0));
+                }
+            }
+        }
+        rows.retain(InetSockInfo::is_ipv4);
+        rows
+    }
+
+    /// Rows of /proc/net/udp, one per bound socket file.
+    pub fn udp_sock_info(&self) -> Vec<InetSockInfo> {
+        let files = socket::inet_sockets();
+        let mut rows = Vec::new();
+        {
+            let sockets = self.socket_set.lock();
+            for file in &files {
+                let Some(SocketHandle::Udp(handle)) = *file.handle.lock() else {
+                    continue;
+                };
+                let Some(s) = find_socket::<udp::Socket>(&sockets, handle) else {
+                    continue;
+                };
+                let local = file
+                    .get_local_endpoint()
+                    .map(Into::into)
+                    .unwrap_or_else(|| s.endpoint());
+                if local.port == 0 {
+                    continue;
+                }
+                let remote = file.get_remote_endpoint();
+                rows.push(InetSockInfo {
+                    local,
+                    remote,
+                    state: if remote.is_some() {
+                        TCP_ESTABLISHED
+                    } else {
+                        TCP_CLOSE
+                    },
+                    tx_queue: s.send_queue(),
+                    rx_queue: file.udp_queued_bytes(),
+                    uid: file.uid(),
+                    ino: file.ino(),
+                });
+            }
+        }
+        rows.retain(InetSockInfo::is_ipv4);
+        rows
+    }
+}
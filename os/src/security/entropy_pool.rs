@@ -73,7 +73,8 @@ impl EntropyPool for BiogasPoll {
 
     fn try_fill(&mut self, dest: &mut [u8]) -> Result<usize, EntropyError> {
         for byte in dest.iter_mut() {
-            *byte = (self.biogas & 0xFF) as u8;
+            // LCG 的低位周期很短，取最高字节
+            *byte = (self.biogas >> (usize::BITS - 8)) as u8;
             self.biogas = self
                 .biogas
                 .wrapping_mul(6364136223846793005)
@@ -82,8 +83,13 @@ impl EntropyPool for BiogasPoll {
         Ok(dest.len())
     }
 
-    fn add_entropy(&mut self, _data: &[u8], _entropy_bits: usize) {
-        // 简单实现不做任何操作
+    fn add_entropy(&mut self, data: &[u8], _entropy_bits: usize) {
+        // 逐字节折叠进状态，不估计熵
+        for &byte in data {
+            self.biogas = (self.biogas ^ byte as usize)
+                .rotate_left(5)
+                .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        }
     }
 
     fn get_entropy_count(&self) -> usize {
@@ -1,5 +1,7 @@
 //! 安全相关模块
 
 mod entropy_pool;
+mod random;
 
 pub use entropy_pool::*;
+pub use random::*;
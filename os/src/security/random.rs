@@ -0,0 +1,28 @@
+//! 内核随机数
+//!
+//! 全局熵池同时服务 `getrandom(2)` 和内核内部（栈金丝雀、指针哈希密钥等）。
+//! 每次取数前把当前硬件时钟周期混入熵池，使不同启动、不同时刻取到的值不同。
+
+use crate::sync::SpinLock;
+
+use super::{BiogasPoll, EntropyError, EntropyPool};
+
+lazy_static::lazy_static! {
+    /// 全局内核熵池
+    static ref KERNEL_POOL: SpinLock<BiogasPoll> = SpinLock::new(BiogasPoll::new());
+}
+
+/// 用随机字节填充 `dest`
+pub fn get_random_bytes(dest: &mut [u8]) -> Result<(), EntropyError> {
+    let mut pool = KERNEL_POOL.lock();
+    pool.add_entropy(&crate::arch::get_time().to_le_bytes(), 0);
+    pool.try_fill(dest).map(|_| ())
+}
+
+/// 取一个随机 `u64`
+pub fn get_random_u64() -> u64 {
+    let mut bytes = [0u8; 8];
+    // BiogasPoll 总是已播种，不会失败
+    let _ = get_random_bytes(&mut bytes);
+    u64::from_le_bytes(bytes)
+}
@@ -0,0 +1,185 @@
+//! 完成量
+//!
+//! 等待一次性事件（I/O 完成、内核线程进入某个状态等）的阻塞原语。
+//! [`Completion::complete`] 每次允许一个等待者通过，[`Completion::complete_all`]
+//! 之后所有等待者都直接通过，直到 [`Completion::reinit`]。事件先于等待发生也不会丢失。
+//!
+//! 等待者以不可中断的方式睡眠在内部的等待队列上；没有当前任务时（如早期启动）自旋等待。
+
+use core::hint;
+
+use crate::arch::timer::{clock_freq, get_time};
+use crate::kernel::{SharedTask, TIMER_QUEUE, WaitQueue, sleep_task, try_current_task, yield_task};
+use crate::sync::SpinLock;
+
+/// `done` 取该值表示已 `complete_all`
+const COMPLETE_ALL: usize = usize::MAX;
+
+struct CompletionInner {
+    /// 尚未被等待者消耗的完成次数
+    done: usize,
+    waiters: WaitQueue,
+}
+
+/// 完成量
+pub struct Completion {
+    inner: SpinLock<CompletionInner>,
+}
+
+impl Completion {
+    pub fn new() -> Self {
+        Self {
+            inner: SpinLock::new(CompletionInner {
+                done: 0,
+                waiters: WaitQueue::new(),
+            }),
+        }
+    }
+
+    /// 标记完成一次，唤醒一个等待者
+    pub fn complete(&self) {
+        let mut inner = self.inner.lock();
+        if inner.done != COMPLETE_ALL {
+            inner.done += 1;
+        }
+        inner.waiters.wake_up_one();
+    }
+
+    /// 标记永久完成，唤醒所有等待者
+    pub fn complete_all(&self) {
+        let mut inner = self.inner.lock();
+        inner.done = COMPLETE_ALL;
+        inner.waiters.wake_up_all();
+    }
+
+    /// 清除完成状态，以便再次使用
+    pub fn reinit(&self) {
+        self.inner.lock().done = 0;
+    }
+
+    /// 是否已完成（有尚未被消耗的完成次数）
+    pub fn is_done(&self) -> bool {
+        self.inner.lock().done > 0
+    }
+
+    /// 不阻塞地消耗一次完成
+    /// # 返回值
+    /// 已完成返回 `true`，否则返回 `false`
+    pub fn try_wait(&self) -> bool {
+        let mut inner = self.inner.lock();
+        if inner.done == 0 {
+            return false;
+        }
+        if inner.done != COMPLETE_ALL {
+            inner.done -= 1;
+        }
+        true
+    }
+
+    /// 等待完成
+    pub fn wait_for_completion(&self) {
+        let task = try_current_task();
+        while !self.wait_once(task.as_ref(), None) {}
+    }
+
+    /// 最多等待 `timeout_ms` 毫秒
+    /// # 返回值
+    /// 在超时前完成返回 `true`，超时返回 `false`
+    pub fn wait_timeout(&self, timeout_ms: usize) -> bool {
+        let deadline = get_time().saturating_add(timeout_ms * clock_freq() / 1000);
+        let task = try_current_task();
+        loop {
+            if self.try_wait() {
+                return true;
+            }
+            if get_time() >= deadline {
+                return false;
+            }
+            let done = self.wait_once(task.as_ref(), Some(deadline));
+            if let Some(task) = &task {
+                TIMER_QUEUE.lock().remove_task(task);
+            }
+            if done {
+                return true;
+            }
+        }
+    }
+
+    /// 已完成时消耗一次完成并返回 `true`；否则睡眠一次（或自旋一次）后返回 `false`
+    ///
+    /// 检查、入队睡眠和登记 `deadline` 定时器在同一把锁内完成，任务先进入睡眠状态再登记
+    /// 定时器，`complete` 和定时器到期都不会错过唤醒。定时器由调用者在醒来后移除。
+    fn wait_once(&self, task: Option<&SharedTask>, deadline: Option<usize>) -> bool {
+        let mut inner = self.inner.lock();
+        if inner.done > 0 {
+            if inner.done != COMPLETE_ALL {
+                inner.done -= 1;
+            }
+            return true;
+        }
+        match task {
+            Some(task) => {
+                if !inner.waiters.contains(task) {
+                    inner.waiters.add_task(task.clone());
+                }
+                sleep_task(task.clone(), false);
+                if let Some(deadline) = deadline {
+                    TIMER_QUEUE.lock().push(deadline, task.clone());
+                }
+                drop(inner);
+                yield_task();
+                // 被超时等其它原因唤醒时仍在队列中
+                self.inner.lock().waiters.remove_task(task);
+            }
+            None => {
+                drop(inner);
+                hint::spin_loop();
+            }
+        }
+        false
+    }
+}
+
+impl Default for Completion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_completion_counts, {
+        let c = Completion::new();
+        kassert!(!c.is_done());
+        kassert!(!c.try_wait());
+
+        c.complete();
+        c.complete();
+        kassert!(c.try_wait());
+        c.wait_for_completion();
+        kassert!(!c.try_wait());
+    });
+
+    test_case!(test_completion_complete_all, {
+        let c = Completion::new();
+        c.complete_all();
+        for _ in 0..4 {
+            c.wait_for_completion();
+        }
+        kassert!(c.is_done());
+
+        c.reinit();
+        kassert!(!c.is_done());
+        kassert!(!c.wait_timeout(0));
+    });
+
+    test_case!(test_completion_wait_timeout_done, {
+        let c = Completion::new();
+        c.complete();
+        kassert!(c.wait_timeout(0));
+        kassert!(!c.is_done());
+    });
+}
@@ -1,18 +1,24 @@
 //! 同步原语
 //!
 //! 向其它内核模块提供基本的锁和同步原语
-//! 包括自旋锁、睡眠锁、中断保护等
+//! 包括自旋锁、睡眠锁、顺序锁、完成量、中断保护等
+mod completion;
 mod intr_guard;
 mod mutex;
 mod per_cpu;
 mod preempt;
 mod raw_spin_lock;
 mod rwlock;
+mod seqlock;
 mod spin_lock;
 
+pub use completion::*;
 pub use mutex::*;
+#[cfg(test)]
 pub use per_cpu::PerCpu;
-pub use preempt::PreemptGuard;
+pub use per_cpu::{CacheAligned, PerCpuVar};
+pub use preempt::{PreemptGuard, preempt_disabled};
 pub use raw_spin_lock::*;
 pub use rwlock::*;
+pub use seqlock::*;
 pub use spin_lock::*;
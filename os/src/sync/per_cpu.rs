@@ -2,6 +2,10 @@
 //!
 //! 允许每个 CPU 维护独立的数据副本，避免锁竞争。
 //!
+//! - [`PerCpu`]：运行时按 CPU 数量在堆上创建，适合动态创建的数据；
+//! - [`PerCpuVar`]：由 [`percpu!`](crate::percpu) 定义的静态变量，编译期按
+//!   `MAX_CPU_COUNT` 初始化，不依赖堆，启动早期即可使用。安全接口在借用期间禁用抢占。
+//!
 //! # 泛型参数
 //!
 //! * `T` - 每个 CPU 存储的数据类型
@@ -13,15 +17,18 @@ use core::marker::PhantomData;
 
 use crate::arch::ArchImpl;
 use crate::arch::CpuOps;
+use crate::config::MAX_CPU_COUNT;
+use crate::sync::preempt::PreemptGuardGeneric;
 
 const CACHE_LINE_SIZE: usize = 64;
 
 /// 缓存行对齐的包装结构
+#[doc(hidden)]
 #[repr(align(64))]
-struct CacheAligned<T>(UnsafeCell<T>);
+pub struct CacheAligned<T>(UnsafeCell<T>);
 
 impl<T> CacheAligned<T> {
-    fn new(value: T) -> Self {
+    pub const fn new(value: T) -> Self {
         CacheAligned(UnsafeCell::new(value))
     }
 
@@ -106,6 +113,116 @@ impl<T, CPU: CpuOps> PerCpu<T, CPU> {
 unsafe impl<T: Send, CPU: CpuOps> Send for PerCpu<T, CPU> {}
 unsafe impl<T: Send, CPU: CpuOps> Sync for PerCpu<T, CPU> {}
 
+/// 静态 Per-CPU 变量，由 [`percpu!`](crate::percpu) 定义
+///
+/// 每个可能的 CPU（共 `MAX_CPU_COUNT` 个）一份缓存行对齐的副本，按 CPU 号索引。
+pub struct PerCpuVar<T, CPU: CpuOps = ArchImpl> {
+    data: [CacheAligned<T>; MAX_CPU_COUNT],
+    _marker: PhantomData<CPU>,
+}
+
+impl<T, CPU: CpuOps> PerCpuVar<T, CPU> {
+    #[doc(hidden)]
+    pub const fn from_array(data: [CacheAligned<T>; MAX_CPU_COUNT]) -> Self {
+        PerCpuVar {
+            data,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 在禁用抢占的情况下借用当前 CPU 的副本
+    #[inline]
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let _guard = PreemptGuardGeneric::<CPU>::new();
+        f(unsafe { &*self.data[CPU::id()].get() })
+    }
+
+    /// 在禁用抢占的情况下可变借用当前 CPU 的副本
+    ///
+    /// 只防止任务迁移；中断处理程序也访问该副本时，调用者须另行关中断。
+    #[inline]
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let _guard = PreemptGuardGeneric::<CPU>::new();
+        f(unsafe { &mut *self.data[CPU::id()].get() })
+    }
+
+    /// 获取当前 CPU 的副本（可变），不禁用抢占
+    ///
+    /// # Safety
+    ///
+    /// 调用者必须保证借用期间不会迁移到其他 CPU（持有 `PreemptGuard` 或已关中断），
+    /// 且同一 CPU 上没有与之冲突的借用。
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn current_unchecked(&self) -> &mut T {
+        unsafe { &mut *self.data[CPU::id()].get() }
+    }
+
+    /// 获取指定 CPU 的副本（只读），用于跨核访问
+    #[inline]
+    pub fn get_of(&self, cpu_id: usize) -> &T {
+        assert!(cpu_id < MAX_CPU_COUNT, "Invalid CPU ID");
+        unsafe { &*self.data[cpu_id].get() }
+    }
+
+    /// 遍历所有 CPU 的副本（包括尚未上线的 CPU）
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.data.iter().map(|slot| unsafe { &*slot.get() })
+    }
+}
+
+unsafe impl<T: Send, CPU: CpuOps> Send for PerCpuVar<T, CPU> {}
+unsafe impl<T: Send, CPU: CpuOps> Sync for PerCpuVar<T, CPU> {}
+
+/// 定义静态 Per-CPU 变量
+///
+/// 初始值必须能在编译期求值；需要按 CPU 号初始化时写成 `|cpu_id| 表达式`。
+///
+/// ```ignore
+/// percpu! {
+///     /// 本 CPU 的节拍数
+///     static TICKS: AtomicUsize = AtomicUsize::new(0);
+///     pub static CPUS: Cpu = |cpu_id| Cpu::new_with_id(cpu_id);
+/// }
+///
+/// TICKS.with(|ticks| ticks.fetch_add(1, Ordering::Relaxed));
+/// ```
+#[macro_export]
+macro_rules! percpu {
+    () => {};
+    (
+        $(#[$attr:meta])*
+        $vis:vis static $name:ident: $ty:ty = |$id:ident| $init:expr;
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis static $name: $crate::sync::PerCpuVar<$ty> = {
+            const fn init() -> [$crate::sync::CacheAligned<$ty>; $crate::config::MAX_CPU_COUNT] {
+                let mut data = [const {
+                    core::mem::MaybeUninit::<$crate::sync::CacheAligned<$ty>>::uninit()
+                }; $crate::config::MAX_CPU_COUNT];
+                let mut $id = 0;
+                while $id < $crate::config::MAX_CPU_COUNT {
+                    data[$id] = core::mem::MaybeUninit::new($crate::sync::CacheAligned::new($init));
+                    $id += 1;
+                }
+                // SAFETY: 上面的循环初始化了每一个元素
+                unsafe { core::mem::transmute(data) }
+            }
+            $crate::sync::PerCpuVar::from_array(init())
+        };
+        $crate::percpu!($($rest)*);
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis static $name:ident: $ty:ty = $init:expr;
+        $($rest:tt)*
+    ) => {
+        $crate::percpu!($(#[$attr])* $vis static $name: $ty = |_cpu_id| $init;);
+        $crate::percpu!($($rest)*);
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,6 +247,27 @@ mod tests {
         kassert!(per_cpu.get_of(0).load(Ordering::Relaxed) == 43);
     });
 
+    crate::percpu! {
+        static TEST_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static TEST_IDS: usize = |cpu_id| cpu_id * 10;
+    }
+
+    test_case!(test_percpu_var_with, {
+        TEST_COUNTER.with(|counter| counter.fetch_add(1, Ordering::Relaxed));
+        kassert!(TEST_COUNTER.with(|counter| counter.load(Ordering::Relaxed)) == 1);
+
+        // 借用期间禁用抢占
+        kassert!(TEST_COUNTER.with(|_| crate::sync::preempt_disabled()));
+    });
+
+    test_case!(test_percpu_var_init_by_id, {
+        for (cpu_id, value) in TEST_IDS.iter().enumerate() {
+            kassert!(*value == cpu_id * 10);
+            kassert!(*TEST_IDS.get_of(cpu_id) == cpu_id * 10);
+        }
+        kassert!(TEST_IDS.iter().count() == crate::config::MAX_CPU_COUNT);
+    });
+
     test_case!(test_per_cpu_get_mut, {
         let per_cpu: PerCpu<usize> = PerCpu::new(|| 0usize);
         let _guard = PreemptGuard::new();
@@ -3,12 +3,19 @@
 //! 基于原子操作实现自旋锁机制，结合 `IntrGuard` 实现中断保护。
 //! 不可重入 (即不能嵌套调用 RawSpinLock::lock())。
 //!
+//! 锁记录持有者的 CPU 号；等待超过 `STALL_THRESH_SECS` 秒时打印锁地址、
+//! 持有者和等待者的栈回溯（见 [`crate::kernel::stall`]）。
+//!
+//! 测试构建中按 CPU 统计获取锁的次数（[`lock_acquisitions`]），
+//! 用于验证快速路径不获取任何锁。
+//!
 //! # 泛型参数
 //!
 //! * `CPU` - 实现 `CpuOps` 的类型，默认使用 `ArchImpl`
 
 use crate::arch::ArchImpl;
 use crate::arch::CpuOps;
+use crate::kernel::stall::SpinWatch;
 use crate::sync::intr_guard::IntrGuard;
 use core::{
     hint,
@@ -16,9 +23,36 @@ use core::{
     sync::atomic::{AtomicBool, AtomicUsize, Ordering},
 };
 
+/// 每自旋这么多次检查一次等待时间
+const SPIN_CHECK_INTERVAL: usize = 1 << 16;
+
+/// 每个 CPU 成功获取锁的次数（仅测试构建统计）
+#[cfg(test)]
+static ACQUIRE_COUNT: [AtomicUsize; crate::config::MAX_CPU_COUNT] =
+    [const { AtomicUsize::new(0) }; crate::config::MAX_CPU_COUNT];
+
+/// 记录当前 CPU 获取了一次锁；非测试构建为空操作
+#[inline(always)]
+pub(super) fn note_acquire<CPU: CpuOps>() {
+    #[cfg(test)]
+    if let Some(count) = ACQUIRE_COUNT.get(CPU::id()) {
+        count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 当前 CPU 累计获取锁的次数（自旋锁、读写锁，以及基于它们的锁）
+#[cfg(test)]
+pub fn lock_acquisitions() -> usize {
+    ACQUIRE_COUNT
+        .get(ArchImpl::id())
+        .map_or(0, |count| count.load(Ordering::Relaxed))
+}
+
 /// 自旋锁结构体，提供互斥访问临界区的能力。
 pub struct RawSpinLock<CPU: CpuOps = ArchImpl> {
     lock: AtomicBool,
+    /// 持有者的 CPU 号加 1，0 表示未记录
+    owner: AtomicUsize,
     saved_intr_flags: AtomicUsize,
     _marker: PhantomData<CPU>,
 }
@@ -35,25 +69,45 @@ impl<CPU: CpuOps> RawSpinLock<CPU> {
     pub const fn new() -> Self {
         RawSpinLock {
             lock: AtomicBool::new(false),
+            owner: AtomicUsize::new(0),
             saved_intr_flags: AtomicUsize::new(0),
             _marker: PhantomData,
         }
     }
 
     fn acquire(&self) {
+        let mut spins: usize = 0;
+        let mut watch = SpinWatch::new();
         while self
             .lock
             .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
             .is_err()
         {
+            spins = spins.wrapping_add(1);
+            if spins.is_multiple_of(SPIN_CHECK_INTERVAL) {
+                watch.check(self as *const Self as usize, self.owner_cpu());
+            }
             hint::spin_loop();
         }
+        self.owner.store(CPU::id() + 1, Ordering::Relaxed);
+        note_acquire::<CPU>();
     }
 
     fn try_acquire(&self) -> bool {
-        self.lock
+        let acquired = self
+            .lock
             .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
-            .is_ok()
+            .is_ok();
+        if acquired {
+            self.owner.store(CPU::id() + 1, Ordering::Relaxed);
+            note_acquire::<CPU>();
+        }
+        acquired
+    }
+
+    /// 持有者的 CPU 号，未被持有时为 `None`
+    pub fn owner_cpu(&self) -> Option<usize> {
+        self.owner.load(Ordering::Relaxed).checked_sub(1)
     }
 
     /// 尝试获取自旋锁，并返回一个 RAII 保护器。
@@ -87,6 +141,7 @@ impl<CPU: CpuOps> RawSpinLock<CPU> {
 
     /// 仅释放锁标志。
     fn unlock(&self) {
+        self.owner.store(0, Ordering::Relaxed);
         self.lock.store(false, Ordering::Release);
     }
 
@@ -137,7 +192,7 @@ unsafe impl<CPU: CpuOps> lock_api::RawMutex for RawSpinLock<CPU> {
 
     unsafe fn unlock(&self) {
         let flags = self.saved_intr_flags.load(Ordering::Acquire);
-        self.lock.store(false, Ordering::Release);
+        RawSpinLock::unlock(self);
         CPU::restore_interrupt_state(flags);
     }
 }
@@ -166,6 +221,33 @@ mod tests {
         kassert!(!lock.is_locked());
     });
 
+    test_case!(test_raw_spin_lock_owner_cpu, {
+        let lock = RawSpinLock::<ArchImpl>::new();
+        kassert!(lock.owner_cpu().is_none());
+
+        let guard = lock.lock();
+        kassert!(lock.owner_cpu() == Some(ArchImpl::id()));
+        kassert!(lock.try_lock().is_none());
+        kassert!(lock.owner_cpu() == Some(ArchImpl::id()));
+
+        drop(guard);
+        kassert!(lock.owner_cpu().is_none());
+    });
+
+    test_case!(test_raw_spin_lock_counts_acquisitions, {
+        let lock = RawSpinLock::<ArchImpl>::new();
+        let before = lock_acquisitions();
+
+        drop(lock.lock());
+        let guard = lock.try_lock();
+        kassert!(guard.is_some());
+        // 失败的 try_lock 不计数
+        kassert!(lock.try_lock().is_none());
+        drop(guard);
+
+        kassert!(lock_acquisitions() - before == 2);
+    });
+
     test_case!(test_raw_spin_lock_raii_release, {
         let lock = RawSpinLock::<ArchImpl>::new();
 
@@ -10,6 +10,7 @@
 use crate::arch::ArchImpl;
 use crate::arch::CpuOps;
 use crate::sync::intr_guard::IntrGuard;
+use crate::sync::raw_spin_lock::note_acquire;
 use core::{
     cell::UnsafeCell,
     hint,
@@ -68,6 +69,7 @@ impl<T, CPU: CpuOps> RwLock<T, CPU> {
                 .compare_exchange_weak(state, state + 1, Ordering::Acquire, Ordering::Relaxed)
                 .is_ok()
             {
+                note_acquire::<CPU>();
                 return RwLockReadGuard {
                     lock: self,
                     intr_guard,
@@ -86,6 +88,7 @@ impl<T, CPU: CpuOps> RwLock<T, CPU> {
                     .compare_exchange_weak(0, WRITER_BIT, Ordering::Acquire, Ordering::Relaxed)
                     .is_ok()
             {
+                note_acquire::<CPU>();
                 return RwLockWriteGuard {
                     lock: self,
                     intr_guard,
@@ -113,6 +116,7 @@ impl<T, CPU: CpuOps> RwLock<T, CPU> {
             .compare_exchange(state, state + 1, Ordering::Acquire, Ordering::Relaxed)
             .is_ok()
         {
+            note_acquire::<CPU>();
             Some(RwLockReadGuard {
                 lock: self,
                 intr_guard,
@@ -130,6 +134,7 @@ impl<T, CPU: CpuOps> RwLock<T, CPU> {
             .compare_exchange(0, WRITER_BIT, Ordering::Acquire, Ordering::Relaxed)
             .is_ok()
         {
+            note_acquire::<CPU>();
             Some(RwLockWriteGuard {
                 lock: self,
                 intr_guard,
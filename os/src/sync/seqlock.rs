@@ -0,0 +1,138 @@
+//! 顺序锁
+//!
+//! 适用于读多写少、数据可按值复制的场景（如墙上时钟偏移量）。
+//! 读者不获取任何锁，也不关中断：读取前后比较序号，期间有写者则重试；
+//! 写者之间用自旋锁互斥，写入期间序号为奇数。
+//!
+//! 写者持锁时关中断，因此同一 CPU 上的中断处理程序不会在写入中途读取而自旋。
+//!
+//! # 泛型参数
+//!
+//! * `T` - 被保护的数据类型，必须可按值复制
+//! * `CPU` - 实现 `CpuOps` 的类型，默认使用 `ArchImpl`
+
+use core::{
+    cell::UnsafeCell,
+    hint,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicUsize, Ordering, fence},
+};
+
+use crate::arch::ArchImpl;
+use crate::arch::CpuOps;
+use crate::sync::raw_spin_lock::{RawSpinLock, RawSpinLockGuard};
+
+/// 顺序锁：读者无锁重试，写者互斥
+pub struct SeqLock<T: Copy, CPU: CpuOps = ArchImpl> {
+    seq: AtomicUsize,
+    writer: RawSpinLock<CPU>,
+    data: UnsafeCell<T>,
+}
+
+/// 写者的 RAII 保护器，释放时结束本次写入
+pub struct SeqLockWriteGuard<'a, T: Copy, CPU: CpuOps = ArchImpl> {
+    lock: &'a SeqLock<T, CPU>,
+    _raw_guard: RawSpinLockGuard<'a, CPU>,
+}
+
+impl<T: Copy, CPU: CpuOps> SeqLock<T, CPU> {
+    pub const fn new(data: T) -> Self {
+        SeqLock {
+            seq: AtomicUsize::new(0),
+            writer: RawSpinLock::new(),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// 读取数据的一致副本，不获取任何锁
+    #[inline]
+    pub fn read(&self) -> T {
+        loop {
+            let start = self.seq.load(Ordering::Acquire);
+            if start & 1 != 0 {
+                hint::spin_loop();
+                continue;
+            }
+            // 可能与写者并发，读到的值只有在序号未变时才被采用
+            let value = unsafe { core::ptr::read_volatile(self.data.get()) };
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == start {
+                return value;
+            }
+        }
+    }
+
+    /// 开始一次写入
+    pub fn write(&self) -> SeqLockWriteGuard<'_, T, CPU> {
+        let _raw_guard = self.writer.lock();
+        self.seq.fetch_add(1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        SeqLockWriteGuard {
+            lock: self,
+            _raw_guard,
+        }
+    }
+
+    /// 当前序号（仅用于调试/测试）
+    #[cfg(test)]
+    pub fn sequence(&self) -> usize {
+        self.seq.load(Ordering::Relaxed)
+    }
+}
+
+impl<T: Copy, CPU: CpuOps> Deref for SeqLockWriteGuard<'_, T, CPU> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: Copy, CPU: CpuOps> DerefMut for SeqLockWriteGuard<'_, T, CPU> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: Copy, CPU: CpuOps> Drop for SeqLockWriteGuard<'_, T, CPU> {
+    fn drop(&mut self) {
+        // 先让序号回到偶数，随后 _raw_guard 释放写者锁
+        self.lock.seq.fetch_add(1, Ordering::Release);
+    }
+}
+
+unsafe impl<T: Copy + Send, CPU: CpuOps> Send for SeqLock<T, CPU> {}
+unsafe impl<T: Copy + Send, CPU: CpuOps> Sync for SeqLock<T, CPU> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::lock_acquisitions;
+    use crate::{kassert, test_case};
+
+    test_case!(test_seqlock_read_write, {
+        let lock: SeqLock<(u64, u64)> = SeqLock::new((1, 2));
+        kassert!(lock.read() == (1, 2));
+
+        {
+            let mut guard = lock.write();
+            kassert!(lock.sequence() & 1 == 1);
+            *guard = (3, 4);
+        }
+        kassert!(lock.sequence() == 2);
+        kassert!(lock.read() == (3, 4));
+    });
+
+    test_case!(test_seqlock_read_takes_no_lock, {
+        let lock: SeqLock<u64> = SeqLock::new(7);
+        let before = lock_acquisitions();
+        for _ in 0..16 {
+            kassert!(lock.read() == 7);
+        }
+        kassert!(lock_acquisitions() == before);
+
+        *lock.write() = 8;
+        kassert!(lock_acquisitions() == before + 1);
+        kassert!(lock.read() == 8);
+    });
+}
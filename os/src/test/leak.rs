@@ -0,0 +1,216 @@
+//! 测试用例的资源泄漏检查
+//!
+//! `test_case!` 在用例开始前和结束后各取一次 [`Snapshot`]。结束时用例内的局部变量
+//! 都已释放，物理帧或堆上的块仍然比开始时多，就判为泄漏，记一次失败断言。
+//! 有意把状态留在全局结构里的用例（注册全局表、填充缓存等）用
+//! `test_case!(#[retains_state] name, { ... })` 标记，只记录增量，不判失败。
+//!
+//! 内核堆扩展时从帧分配器拿走的帧不再归还，这部分按堆的统计计算，不算作帧泄漏。
+//! 所有用例中增量最大的几个在测试汇总里输出。
+
+use crate::config::PAGE_SIZE;
+use crate::mm::frame_allocator::get_allocated_frames;
+use crate::mm::global_allocator::{heap_bytes, heap_usage};
+use crate::println;
+use crate::sync::SpinLock;
+use crate::test::macros::{FailedAssertion, record_failed_assertion};
+
+/// 汇总中输出的泄漏条目数
+const TOP_LEAKS: usize = 8;
+
+/// 某一时刻的资源占用
+pub struct Snapshot {
+    frames: usize,
+    heap_pages: usize,
+    heap_bytes: usize,
+    heap_allocations: usize,
+}
+
+impl Snapshot {
+    /// 读取当前的帧和堆占用，本身不分配内存
+    pub fn take() -> Self {
+        let heap = heap_usage();
+        Self {
+            frames: get_allocated_frames(),
+            heap_pages: heap_bytes() as usize / PAGE_SIZE,
+            heap_bytes: heap.bytes,
+            heap_allocations: heap.allocations,
+        }
+    }
+
+    /// 从 `before` 到本快照的增量
+    fn delta_since(&self, before: &Snapshot) -> Delta {
+        let diff = |after: usize, before: usize| after as isize - before as isize;
+        Delta {
+            frames: diff(self.frames, before.frames) - diff(self.heap_pages, before.heap_pages),
+            heap_bytes: diff(self.heap_bytes, before.heap_bytes),
+            heap_allocations: diff(self.heap_allocations, before.heap_allocations),
+        }
+    }
+}
+
+/// 一个用例前后的占用变化，负数表示释放了用例开始前就存在的资源
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Delta {
+    /// 帧数变化，不含堆扩展占用的帧
+    pub frames: isize,
+    pub heap_bytes: isize,
+    pub heap_allocations: isize,
+}
+
+impl Delta {
+    /// 是否有帧或堆上的块没有归还
+    ///
+    /// 只看块数：已有的全局容器扩容只增加字节数，不算泄漏。
+    pub fn leaked(&self) -> bool {
+        self.frames > 0 || self.heap_allocations > 0
+    }
+
+    /// 用于排序的泄漏量，按字节计
+    fn weight(&self) -> isize {
+        self.frames.max(0) * PAGE_SIZE as isize + self.heap_bytes.max(0)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct LeakRecord {
+    test: &'static str,
+    delta: Delta,
+    retains_state: bool,
+}
+
+/// 泄漏量最大的若干用例，定长保存以免记录本身分配内存
+static TOP: SpinLock<[Option<LeakRecord>; TOP_LEAKS]> = SpinLock::new([None; TOP_LEAKS]);
+
+/// 记入泄漏排行，排行已满时替换其中最小的一项
+fn record(record: LeakRecord) {
+    let mut top = TOP.lock();
+    let slot = match top.iter().position(Option::is_none) {
+        Some(free) => free,
+        None => {
+            let (min, smallest) = top
+                .iter()
+                .enumerate()
+                .filter_map(|(i, r)| r.map(|r| (i, r.delta.weight())))
+                .min_by_key(|&(_, weight)| weight)
+                .unwrap();
+            if smallest >= record.delta.weight() {
+                return;
+            }
+            min
+        }
+    };
+    top[slot] = Some(record);
+}
+
+/// 用例结束时调用：与开始时的快照比较
+///
+/// 有泄漏时输出增量并记入排行；未标记 `retains_state` 的用例另记一次失败断言。
+pub fn check(
+    test: &'static str,
+    before: &Snapshot,
+    retains_state: bool,
+    file: &'static str,
+    line: u32,
+) {
+    let delta = Snapshot::take().delta_since(before);
+    if !delta.leaked() {
+        return;
+    }
+    println!(
+        "\x1b[{}m[leak] {:+} frames, {:+} heap bytes in {:+} allocations{}\x1b[0m",
+        if retains_state { 33 } else { 31 },
+        delta.frames,
+        delta.heap_bytes,
+        delta.heap_allocations,
+        if retains_state {
+            " (retains state)"
+        } else {
+            ""
+        }
+    );
+    record(LeakRecord {
+        test,
+        delta,
+        retains_state,
+    });
+    if !retains_state {
+        record_failed_assertion(FailedAssertion::new(
+            "no frames or heap blocks leaked",
+            file,
+            line,
+        ));
+    }
+}
+
+/// 在测试汇总中按泄漏量从大到小输出排行
+pub fn print_summary() {
+    let mut top = *TOP.lock();
+    top.sort_unstable_by_key(|r| core::cmp::Reverse(r.map(|r| r.delta.weight())));
+    if top[0].is_none() {
+        return;
+    }
+    println!("\x1b[33m--- Top leak deltas ---\x1b[0m");
+    for r in top.iter().flatten() {
+        println!(
+            "  {}: {:+} frames, {:+} heap bytes in {:+} allocations{}",
+            r.test,
+            r.delta.frames,
+            r.delta.heap_bytes,
+            r.delta.heap_allocations,
+            if r.retains_state {
+                " (retains state)"
+            } else {
+                ""
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    fn snapshot(frames: usize, heap_pages: usize, heap_allocations: usize) -> Snapshot {
+        Snapshot {
+            frames,
+            heap_pages,
+            heap_bytes: heap_allocations * 16,
+            heap_allocations,
+        }
+    }
+
+    test_case!(test_leak_delta, {
+        let before = snapshot(100, 10, 50);
+        kassert!(!snapshot(100, 10, 50).delta_since(&before).leaked());
+        // 释放已有资源不是泄漏
+        kassert!(!snapshot(90, 10, 40).delta_since(&before).leaked());
+
+        let delta = snapshot(103, 10, 52).delta_since(&before);
+        kassert!(delta.leaked());
+        kassert!(
+            delta
+                == Delta {
+                    frames: 3,
+                    heap_bytes: 32,
+                    heap_allocations: 2,
+                }
+        );
+        kassert!(delta.weight() == 3 * PAGE_SIZE as isize + 32);
+
+        // 堆扩展占用的帧不算帧泄漏
+        let grown = snapshot(104, 14, 50).delta_since(&before);
+        kassert!(grown.frames == 0 && !grown.leaked());
+    });
+
+    test_case!(test_leak_snapshot_balanced, {
+        let before = Snapshot::take();
+        let frame = crate::mm::frame_allocator::alloc_frame();
+        let boxed = alloc::boxed::Box::new([0u8; 64]);
+        kassert!(Snapshot::take().delta_since(&before).leaked());
+        drop(boxed);
+        drop(frame);
+        kassert!(Snapshot::take().delta_since(&before) == Delta::default());
+    });
+}
@@ -104,6 +104,10 @@ macro_rules! early_test {
 /// 提供两种语法：
 /// 1. 带环境：`test_case!(test_name, env_variable, { code });`
 /// 2. 不带环境：`test_case!(test_name, { code });`
+/// 3. 保留全局状态：`test_case!(#[retains_state] test_name, { code });`
+///
+/// 不带环境的用例在结束时检查物理帧和堆块是否泄漏（见 [`crate::test::leak`]），
+/// 泄漏记为一次失败断言。有意把状态留在全局结构里的用例用第三种语法，只报告不判失败。
 #[macro_export]
 macro_rules! test_case {
     (
@@ -130,9 +134,23 @@ macro_rules! test_case {
             }
         }
     };
+    (
+        #[retains_state]
+        $func_name:ident,
+        $body:block
+    ) => {
+        $crate::test_case!(@case $func_name, true, $body);
+    };
     (
         $func_name:ident,
         $body:block
+    ) => {
+        $crate::test_case!(@case $func_name, false, $body);
+    };
+    (
+        @case $func_name:ident,
+        $retains_state:literal,
+        $body:block
     ) => {
         #[doc = concat!("Test case: ", stringify!($func_name))]
         #[test_case]
@@ -145,9 +163,17 @@ macro_rules! test_case {
             );
 
             let failed_before = $crate::test::macros::TEST_FAILED.load(core::sync::atomic::Ordering::SeqCst);
+            let leak_snapshot = $crate::test::leak::Snapshot::take();
 
             $body
 
+            $crate::test::leak::check(
+                concat!(module_path!(), "::", stringify!($func_name)),
+                &leak_snapshot,
+                $retains_state,
+                file!(),
+                line!(),
+            );
             let failed_after = $crate::test::macros::TEST_FAILED.load(core::sync::atomic::Ordering::SeqCst);
             let failed_count = failed_after - failed_before;
 
@@ -1,6 +1,9 @@
 mod guard;
+#[cfg(test)]
+pub mod leak;
 pub mod macros;
 pub mod net_test;
+pub mod user_result;
 use crate::arch::{are_interrupts_enabled, disable_interrupts, enable_interrupts};
 
 /// 测试运行器。它由测试框架自动调用，并传入一个包含所有测试的切片。
@@ -28,6 +31,8 @@ pub fn test_runner(tests: &[&dyn Fn()]) {
         failed
     );
 
+    crate::test::leak::print_summary();
+
     if failed > 0 {
         crate::println!("\x1b[91mSome tests failed!\x1b[0m");
     } else {
@@ -0,0 +1,88 @@
+//! 用户态测试结果
+//!
+//! 用户态测试程序（或启动自检）通过 /proc/test-result 报告整体结果，
+//! `reboot(POWER_OFF)` 关机时内核据此调用 `shutdown(failure)`，让模拟器的退出码
+//! 反映用户态测试是否通过。RISC-V 上失败经 SBI SRST 的 SystemFailure 原因传给
+//! QEMU 的 finisher 设备；LoongArch 的 GED 关机寄存器没有失败码，结果只记录在日志中。
+//!
+//! 结果只能由 pass 变为 fail：多个测试程序先后报告时，任何一次失败都会保留下来。
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// 用户态测试的整体结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserTestResult {
+    Pass,
+    Fail,
+}
+
+impl UserTestResult {
+    /// 解析写入 /proc/test-result 的内容，允许末尾的换行和 NUL
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        match core::str::from_utf8(&buf[..end]).ok()?.trim() {
+            "pass" => Some(Self::Pass),
+            "fail" => Some(Self::Fail),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pass => "pass",
+            Self::Fail => "fail",
+        }
+    }
+}
+
+const RESULT_NONE: u8 = 0;
+const RESULT_PASS: u8 = 1;
+const RESULT_FAIL: u8 = 2;
+
+static RESULT: AtomicU8 = AtomicU8::new(RESULT_NONE);
+
+/// 记录一次测试结果
+pub fn report(result: UserTestResult) {
+    match result {
+        // 已经失败时不能被后来的 pass 覆盖
+        UserTestResult::Pass => {
+            let _ = RESULT.compare_exchange(
+                RESULT_NONE,
+                RESULT_PASS,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            );
+        }
+        UserTestResult::Fail => RESULT.store(RESULT_FAIL, Ordering::SeqCst),
+    }
+    crate::pr_info!("test-result: user space reported {}", result.as_str());
+}
+
+/// 当前记录的结果，尚未报告时为 `None`
+pub fn result() -> Option<UserTestResult> {
+    match RESULT.load(Ordering::SeqCst) {
+        RESULT_PASS => Some(UserTestResult::Pass),
+        RESULT_FAIL => Some(UserTestResult::Fail),
+        _ => None,
+    }
+}
+
+/// 关机时是否应报告失败
+pub fn failed() -> bool {
+    result() == Some(UserTestResult::Fail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserTestResult;
+    use crate::{kassert, test_case};
+
+    test_case!(test_parse_user_test_result, {
+        kassert!(UserTestResult::parse(b"pass") == Some(UserTestResult::Pass));
+        kassert!(UserTestResult::parse(b"fail\n\0") == Some(UserTestResult::Fail));
+        kassert!(UserTestResult::parse(b"  pass\n") == Some(UserTestResult::Pass));
+        kassert!(UserTestResult::parse(b"ok").is_none());
+        kassert!(UserTestResult::parse(b"").is_none());
+        kassert!(UserTestResult::parse(&[0xff, 0xfe]).is_none());
+    });
+}
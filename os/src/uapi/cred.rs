@@ -19,3 +19,32 @@ pub const UID_UNCHANGED: u32 = u32::MAX;
 /// 在 setresuid/setresgid 系统调用中，如果参数为此值，表示不改变对应的 ID
 /// 对应 C 中的 (gid_t)-1
 pub const GID_UNCHANGED: u32 = u32::MAX;
+
+/// 附加组列表的最大长度
+///
+/// 与 musl `<limits.h>` 中的 `NGROUPS_MAX` 相同，setgroups 超过该长度返回 `EINVAL`。
+pub const NGROUPS_MAX: usize = 32;
+
+/// capget/capset 接口版本 1（每个能力集 32 位）
+pub const LINUX_CAPABILITY_VERSION_1: u32 = 0x1998_0330;
+/// capget/capset 接口版本 2（已废弃，与版本 3 布局相同）
+pub const LINUX_CAPABILITY_VERSION_2: u32 = 0x2007_1026;
+/// capget/capset 接口版本 3（每个能力集 64 位，分两个 `CapUserData`）
+pub const LINUX_CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+
+/// capget/capset 头部（struct __user_cap_header_struct）
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CapUserHeader {
+    pub version: u32,
+    pub pid: i32,
+}
+
+/// capget/capset 数据（struct __user_cap_data_struct），每项保存 32 个能力位
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CapUserData {
+    pub effective: u32,
+    pub permitted: u32,
+    pub inheritable: u32,
+}
@@ -283,3 +283,13 @@ pub const ERFKILL: i32 = 132;
 
 /// Memory page has hardware error
 pub const EHWPOISON: i32 = 133;
+
+/* 内核内部使用，不会返回给用户态 */
+/// 被信号打断，处理函数设置了 SA_RESTART 时重启，否则返回 EINTR
+pub const ERESTARTSYS: i32 = 512;
+/// 被信号打断后总是重启
+pub const ERESTARTNOINTR: i32 = 513;
+/// 没有执行用户处理函数时重启，否则返回 EINTR
+pub const ERESTARTNOHAND: i32 = 514;
+/// 同 ERESTARTNOHAND，但通过 restart_syscall 按任务的重启块重启
+pub const ERESTART_RESTARTBLOCK: i32 = 516;
@@ -0,0 +1,92 @@
+//! 帧缓冲（fbdev）用户态接口定义
+//!
+//! 与 Linux `include/uapi/linux/fb.h` 保持二进制兼容。
+
+/// 像素格式：紧密排列的像素
+pub const FB_TYPE_PACKED_PIXELS: u32 = 0;
+/// 颜色模式：真彩色
+pub const FB_VISUAL_TRUECOLOR: u32 = 2;
+
+/// 颜色分量在像素中的位置，对应 Linux `struct fb_bitfield`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FbBitfield {
+    /// 分量起始位
+    pub offset: u32,
+    /// 分量位宽
+    pub length: u32,
+    /// 非 0 表示最高位在右侧
+    pub msb_right: u32,
+}
+
+/// 可变屏幕信息，对应 Linux `struct fb_var_screeninfo`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FbVarScreeninfo {
+    /// 可见分辨率
+    pub xres: u32,
+    pub yres: u32,
+    /// 虚拟分辨率
+    pub xres_virtual: u32,
+    pub yres_virtual: u32,
+    /// 可见区域相对虚拟区域的偏移
+    pub xoffset: u32,
+    pub yoffset: u32,
+    /// 每像素位数
+    pub bits_per_pixel: u32,
+    /// 非 0 表示灰度
+    pub grayscale: u32,
+    /// 各颜色分量布局
+    pub red: FbBitfield,
+    pub green: FbBitfield,
+    pub blue: FbBitfield,
+    pub transp: FbBitfield,
+    /// 非标准像素格式
+    pub nonstd: u32,
+    pub activate: u32,
+    /// 屏幕物理尺寸（毫米）
+    pub height: u32,
+    pub width: u32,
+    pub accel_flags: u32,
+    /// 时序参数（虚拟设备全部为 0）
+    pub pixclock: u32,
+    pub left_margin: u32,
+    pub right_margin: u32,
+    pub upper_margin: u32,
+    pub lower_margin: u32,
+    pub hsync_len: u32,
+    pub vsync_len: u32,
+    pub sync: u32,
+    pub vmode: u32,
+    pub rotate: u32,
+    pub colorspace: u32,
+    pub reserved: [u32; 4],
+}
+
+/// 固定屏幕信息，对应 Linux `struct fb_fix_screeninfo`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FbFixScreeninfo {
+    /// 设备标识字符串
+    pub id: [u8; 16],
+    /// 帧缓冲物理起始地址
+    pub smem_start: usize,
+    /// 帧缓冲长度
+    pub smem_len: u32,
+    /// FB_TYPE_*
+    pub type_: u32,
+    pub type_aux: u32,
+    /// FB_VISUAL_*
+    pub visual: u32,
+    pub xpanstep: u16,
+    pub ypanstep: u16,
+    pub ywrapstep: u16,
+    /// 每行字节数
+    pub line_length: u32,
+    /// MMIO 物理起始地址
+    pub mmio_start: usize,
+    pub mmio_len: u32,
+    pub accel: u32,
+    pub capabilities: u16,
+    pub reserved: [u16; 2],
+}
@@ -309,7 +309,8 @@ impl OpenFlags {
 /// 文件偏移量设置模式
 ///
 /// 用于 lseek() 系统调用
-/// 对应 POSIX 的 `SEEK_SET`、`SEEK_CUR`、`SEEK_END`
+/// 对应 POSIX 的 `SEEK_SET`、`SEEK_CUR`、`SEEK_END`，以及 Linux 扩展的
+/// `SEEK_DATA`、`SEEK_HOLE`
 /// 参考：include/uapi/linux/fs.h
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
@@ -322,13 +323,19 @@ pub enum SeekWhence {
 
     /// 从文件末尾计算 (SEEK_END)
     End = 2,
+
+    /// 定位到不小于 offset 的第一个数据位置 (SEEK_DATA)
+    Data = 3,
+
+    /// 定位到不小于 offset 的第一个空洞位置，文件末尾视为空洞 (SEEK_HOLE)
+    Hole = 4,
 }
 
 impl SeekWhence {
     /// 从 i32 转换（用于系统调用参数解析）
     ///
     /// # 参数
-    /// - `value`: 用户空间传入的 whence 值（0/1/2）
+    /// - `value`: 用户空间传入的 whence 值（0~4）
     ///
     /// # 返回值
     /// - `Some(whence)`: 有效的 whence 值
@@ -338,6 +345,8 @@ impl SeekWhence {
             0 => Some(Self::Set),
             1 => Some(Self::Cur),
             2 => Some(Self::End),
+            3 => Some(Self::Data),
+            4 => Some(Self::Hole),
             _ => None,
         }
     }
@@ -352,3 +361,5 @@ impl SeekWhence {
 pub const SEEK_SET: i32 = 0;
 pub const SEEK_CUR: i32 = 1;
 pub const SEEK_END: i32 = 2;
+pub const SEEK_DATA: i32 = 3;
+pub const SEEK_HOLE: i32 = 4;
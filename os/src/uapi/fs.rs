@@ -12,6 +12,21 @@ pub enum FileSystemType {
     /// 魔数来自：include/uapi/linux/magic.h
     Ext4 = 0xEF53,
 
+    /// tmpfs（TMPFS_MAGIC）
+    Tmpfs = 0x0102_1994,
+
+    /// procfs（PROC_SUPER_MAGIC）
+    Proc = 0x9FA0,
+
+    /// sysfs（SYSFS_MAGIC）
+    Sysfs = 0x6265_6572,
+
+    /// FAT/VFAT（MSDOS_SUPER_MAGIC）
+    Msdos = 0x4D44,
+
+    /// 纯内存文件系统（RAMFS_MAGIC），simplefs 使用
+    Ramfs = 0x8584_58F6,
+
     /// 未知或不支持的文件系统
     Unknown = 0,
 }
@@ -27,6 +42,11 @@ impl FileSystemType {
     pub fn from_str(fs_type: &str) -> Self {
         match fs_type {
             "ext4" | "ext3" | "ext2" => Self::Ext4,
+            "tmpfs" => Self::Tmpfs,
+            "proc" => Self::Proc,
+            "sysfs" => Self::Sysfs,
+            "vfat" | "msdos" => Self::Msdos,
+            "simplefs" | "ramfs" => Self::Ramfs,
             _ => Self::Unknown,
         }
     }
@@ -460,6 +480,23 @@ pub const STATX_SIZE: u32 = 0x0000_0200;
 pub const STATX_BLOCKS: u32 = 0x0000_0400;
 pub const STATX_BASIC_STATS: u32 = 0x0000_07ff;
 
+/// d_type：类型未知
+pub const DT_UNKNOWN: u8 = 0;
+/// d_type：命名管道
+pub const DT_FIFO: u8 = 1;
+/// d_type：字符设备
+pub const DT_CHR: u8 = 2;
+/// d_type：目录
+pub const DT_DIR: u8 = 4;
+/// d_type：块设备
+pub const DT_BLK: u8 = 6;
+/// d_type：普通文件
+pub const DT_REG: u8 = 8;
+/// d_type：符号链接
+pub const DT_LNK: u8 = 10;
+/// d_type：套接字
+pub const DT_SOCK: u8 = 12;
+
 /// Linux dirent64 结构
 ///
 /// 用于getdents64系统调用
@@ -480,7 +517,8 @@ pub struct LinuxDirent64 {
 }
 
 impl LinuxDirent64 {
-    const BASE_SIZE: usize = 19;
+    /// 固定部分的长度，即 d_name 在记录中的偏移
+    pub const BASE_SIZE: usize = 19;
 
     /// 计算包含文件名的总长度（8字节对齐）
     pub fn total_len(name: &str) -> usize {
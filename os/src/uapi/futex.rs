@@ -89,3 +89,15 @@ pub struct RobustListHead {
     /// 指向一个正在等待被释放或修复的 futex 锁。
     pub pending: *mut c_void,
 }
+
+/// futex 字中表示有等待者的位
+pub const FUTEX_WAITERS: u32 = 0x8000_0000;
+
+/// futex 字中表示持有者已退出的位
+pub const FUTEX_OWNER_DIED: u32 = 0x4000_0000;
+
+/// futex 字中持有者 TID 所占的位
+pub const FUTEX_TID_MASK: u32 = 0x3fff_ffff;
+
+/// 退出时最多处理的健壮列表项数，防止用户构造环形列表
+pub const ROBUST_LIST_LIMIT: usize = 2048;
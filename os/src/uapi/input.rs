@@ -0,0 +1,74 @@
+//! 输入子系统（evdev）用户态接口定义
+//!
+//! 与 Linux `include/uapi/linux/input.h` 和 `input-event-codes.h` 保持二进制兼容，
+//! `/dev/input/eventN` 的 read 返回的就是 [`InputEvent`] 数组。
+
+use super::time::timeval;
+
+/// evdev 协议版本（`EVIOCGVERSION` 返回值）
+pub const EV_VERSION: i32 = 0x010001;
+
+/// 同步事件
+pub const EV_SYN: u16 = 0x00;
+/// 按键事件
+pub const EV_KEY: u16 = 0x01;
+/// 相对坐标事件（鼠标）
+pub const EV_REL: u16 = 0x02;
+/// 绝对坐标事件（触摸板、数位板）
+pub const EV_ABS: u16 = 0x03;
+/// 杂项事件
+pub const EV_MSC: u16 = 0x04;
+/// LED 事件
+pub const EV_LED: u16 = 0x11;
+/// 重复按键事件
+pub const EV_REP: u16 = 0x14;
+/// 事件类型最大值
+pub const EV_MAX: u16 = 0x1f;
+
+/// 一组事件结束的同步标记
+pub const SYN_REPORT: u16 = 0;
+/// 内核缓冲区溢出，客户端应丢弃到下一个 SYN_REPORT 为止的事件
+pub const SYN_DROPPED: u16 = 3;
+
+/// 总线类型：virtio
+pub const BUS_VIRTUAL: u16 = 0x06;
+
+/// 输入事件，对应 Linux `struct input_event`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    /// 事件时间戳（CLOCK_REALTIME）
+    pub time: timeval,
+    /// 事件类型（EV_*）
+    pub type_: u16,
+    /// 事件编码（KEY_*、REL_*、ABS_* 等）
+    pub code: u16,
+    /// 事件值
+    pub value: i32,
+}
+
+impl InputEvent {
+    /// 构造一个输入事件
+    pub fn new(time: timeval, type_: u16, code: u16, value: i32) -> Self {
+        Self {
+            time,
+            type_,
+            code,
+            value,
+        }
+    }
+}
+
+/// 设备标识，对应 Linux `struct input_id`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InputId {
+    /// 总线类型（BUS_*）
+    pub bustype: u16,
+    /// 厂商 ID
+    pub vendor: u16,
+    /// 产品 ID
+    pub product: u16,
+    /// 版本号
+    pub version: u16,
+}
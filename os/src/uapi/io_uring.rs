@@ -0,0 +1,113 @@
+//! io_uring UAPI
+//!
+//! 参考：include/uapi/linux/io_uring.h。只包含内核实现的子集。
+
+/// `mmap` 偏移：SQ 环（带 `IORING_FEAT_SINGLE_MMAP` 时同时包含 CQ 环）
+pub const IORING_OFF_SQ_RING: u64 = 0;
+/// `mmap` 偏移：CQ 环
+pub const IORING_OFF_CQ_RING: u64 = 0x800_0000;
+/// `mmap` 偏移：SQE 数组
+pub const IORING_OFF_SQES: u64 = 0x1000_0000;
+
+/// io_uring_setup: 使用 `params.cq_entries` 指定的 CQ 大小
+pub const IORING_SETUP_CQSIZE: u32 = 1 << 3;
+/// io_uring_setup: 条目数超过上限时截断而不是返回 EINVAL
+pub const IORING_SETUP_CLAMP: u32 = 1 << 4;
+
+/// SQ/CQ 环的条目数上限
+pub const IORING_MAX_ENTRIES: u32 = 4096;
+/// CQ 环的条目数上限
+pub const IORING_MAX_CQ_ENTRIES: u32 = 2 * IORING_MAX_ENTRIES;
+
+/// SQ 环和 CQ 环可以用一次 `mmap` 映射
+pub const IORING_FEAT_SINGLE_MMAP: u32 = 1 << 0;
+
+/// io_uring_enter: 等待至少 `min_complete` 个完成项
+pub const IORING_ENTER_GETEVENTS: u32 = 1 << 0;
+
+/// 空操作，立即完成
+pub const IORING_OP_NOP: u8 = 0;
+/// accept(2)
+pub const IORING_OP_ACCEPT: u8 = 13;
+/// read(2) / pread(2)
+pub const IORING_OP_READ: u8 = 22;
+/// write(2) / pwrite(2)
+pub const IORING_OP_WRITE: u8 = 23;
+
+/// SQ 环中各字段的偏移 (struct io_sqring_offsets)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IoSqringOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub flags: u32,
+    pub dropped: u32,
+    pub array: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// CQ 环中各字段的偏移 (struct io_cqring_offsets)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IoCqringOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub overflow: u32,
+    pub cqes: u32,
+    pub flags: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// io_uring_setup 的参数 (struct io_uring_params)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IoUringParams {
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+    pub features: u32,
+    pub wq_fd: u32,
+    pub resv: [u32; 3],
+    pub sq_off: IoSqringOffsets,
+    pub cq_off: IoCqringOffsets,
+}
+
+/// 提交队列项 (struct io_uring_sqe)
+///
+/// 各操作复用的联合体字段按本内核支持的操作命名：
+/// - `off`：读写的文件偏移（`u64::MAX` 表示使用并推进当前偏移）；ACCEPT 的 `addrlen` 指针
+/// - `op_flags`：ACCEPT 的 `SOCK_NONBLOCK | SOCK_CLOEXEC`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IoUringSqe {
+    pub opcode: u8,
+    pub flags: u8,
+    pub ioprio: u16,
+    pub fd: i32,
+    pub off: u64,
+    pub addr: u64,
+    pub len: u32,
+    pub op_flags: u32,
+    pub user_data: u64,
+    pub buf_index: u16,
+    pub personality: u16,
+    pub splice_fd_in: i32,
+    pub __pad2: [u64; 2],
+}
+
+/// 完成队列项 (struct io_uring_cqe)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IoUringCqe {
+    pub user_data: u64,
+    pub res: i32,
+    pub flags: u32,
+}
@@ -181,6 +181,12 @@ pub const SIOCGIFINDEX: u32 = 0x8933;
 /// 根据索引获取接口名称（struct ifreq）
 pub const SIOCGIFNAME_BY_INDEX: u32 = 0x8910;
 
+/// 添加路由（struct rtentry）
+pub const SIOCADDRT: u32 = 0x890B;
+
+/// 删除路由（struct rtentry）
+pub const SIOCDELRT: u32 = 0x890C;
+
 // ========== 设备特定 ioctl ==========
 
 /// RTC（实时时钟）设备
@@ -214,6 +220,23 @@ pub struct RtcTime {
     pub tm_isdst: i32,
 }
 
+/// 输入设备（evdev）ioctl 魔数
+pub const EVIOC_TYPE: u32 = b'E' as u32;
+/// 获取 evdev 协议版本（int）
+pub const EVIOCGVERSION: u32 = _IOR(EVIOC_TYPE, 0x01, 4);
+/// 获取设备标识（struct input_id）
+pub const EVIOCGID: u32 = _IOR(EVIOC_TYPE, 0x02, 8);
+/// 获取设备名称：`EVIOCGNAME(len)`，长度编码在请求码中
+pub const EVIOCGNAME_NR: u32 = 0x06;
+/// 获取事件位图：`EVIOCGBIT(ev, len)`，编号为 0x20 + ev
+pub const EVIOCGBIT_NR_BASE: u32 = 0x20;
+
+/// 帧缓冲设备（fbdev）
+pub const FBIOGET_VSCREENINFO: u32 = 0x4600;
+pub const FBIOPUT_VSCREENINFO: u32 = 0x4601;
+pub const FBIOGET_FSCREENINFO: u32 = 0x4602;
+pub const FBIOPAN_DISPLAY: u32 = 0x4606;
+
 /// 块设备
 pub const BLKGETSIZE: u32 = _IO(0x12, 96);
 pub const BLKGETSIZE64: u32 = _IOR(0x12, 114, 8);
@@ -351,7 +374,7 @@ pub union IfreqIfru {
     pub ifru_flags: i16,
     pub ifru_ivalue: i32,
     pub ifru_mtu: i32,
-    pub ifru_map: [u8; 16],
+    pub ifru_map: [u8; 24], // struct ifmap
     pub ifru_slave: [u8; IFNAMSIZ],
     pub ifru_newname: [u8; IFNAMSIZ],
     pub ifru_data: usize, // void*
@@ -371,3 +394,38 @@ pub struct Ifconf {
     pub ifc_len: i32,
     pub ifc_buf: usize, // void* 或 struct ifreq*
 }
+
+/// 接口标志（Linux IFF_*）
+pub const IFF_UP: u32 = 1 << 0; // 接口已启用
+pub const IFF_BROADCAST: u32 = 1 << 1; // 支持广播
+pub const IFF_LOOPBACK: u32 = 1 << 3; // 回环接口
+pub const IFF_RUNNING: u32 = 1 << 6; // 接口正在运行
+pub const IFF_MULTICAST: u32 = 1 << 12; // 支持多播
+
+/// 硬件地址类型（`ifr_hwaddr.sa_family`）
+pub const ARPHRD_ETHER: u16 = 1;
+pub const ARPHRD_LOOPBACK: u16 = 772;
+
+/// 路由表项（用于 SIOCADDRT / SIOCDELRT）
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Rtentry {
+    pub rt_pad1: usize,
+    pub rt_dst: [u8; 16],     // sockaddr
+    pub rt_gateway: [u8; 16], // sockaddr
+    pub rt_genmask: [u8; 16], // sockaddr
+    pub rt_flags: u16,
+    pub rt_pad2: i16,
+    pub rt_pad3: usize,
+    pub rt_pad4: usize,
+    pub rt_metric: i16,
+    pub rt_dev: usize, // char*
+    pub rt_mtu: usize,
+    pub rt_window: usize,
+    pub rt_irtt: u16,
+}
+
+/// 路由标志（Linux RTF_*）
+pub const RTF_UP: u16 = 0x0001; // 路由可用
+pub const RTF_GATEWAY: u16 = 0x0002; // 经网关转发
+pub const RTF_HOST: u16 = 0x0004; // 主机路由
@@ -0,0 +1,49 @@
+//! I/O 优先级常量和定义
+//!
+//! 对应于 Linux 用户空间 API `<linux/ioprio.h>`。优先级值由类别和类别内级别组成：
+//! 高 3 位为类别，低 13 位为级别（0 最高，7 最低）。
+
+/// 类别在优先级值中的位移
+pub const IOPRIO_CLASS_SHIFT: u16 = 13;
+
+/// 类别内级别的掩码
+pub const IOPRIO_PRIO_MASK: u16 = (1 << IOPRIO_CLASS_SHIFT) - 1;
+
+/// 每个类别的级别数
+pub const IOPRIO_NR_LEVELS: u16 = 8;
+
+/// 未设置，按 CPU 调度属性推导
+pub const IOPRIO_CLASS_NONE: u16 = 0;
+
+/// 实时类，总是最先得到服务
+pub const IOPRIO_CLASS_RT: u16 = 1;
+
+/// 尽力而为类，普通任务的默认类别
+pub const IOPRIO_CLASS_BE: u16 = 2;
+
+/// 空闲类，只在没有其他 I/O 时得到服务
+pub const IOPRIO_CLASS_IDLE: u16 = 3;
+
+/// `who` 为线程 ID，0 表示调用线程
+pub const IOPRIO_WHO_PROCESS: i32 = 1;
+
+/// `who` 为进程组 ID
+pub const IOPRIO_WHO_PGRP: i32 = 2;
+
+/// `who` 为用户 ID
+pub const IOPRIO_WHO_USER: i32 = 3;
+
+/// 由类别和级别组成优先级值
+pub const fn ioprio_prio_value(class: u16, level: u16) -> u16 {
+    (class << IOPRIO_CLASS_SHIFT) | (level & IOPRIO_PRIO_MASK)
+}
+
+/// 取出优先级值的类别
+pub const fn ioprio_prio_class(ioprio: u16) -> u16 {
+    ioprio >> IOPRIO_CLASS_SHIFT
+}
+
+/// 取出优先级值的类别内级别
+pub const fn ioprio_prio_level(ioprio: u16) -> u16 {
+    ioprio & IOPRIO_PRIO_MASK
+}
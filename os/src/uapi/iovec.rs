@@ -1,5 +1,8 @@
 //! 向量 I/O 相关类型定义
 
+/// 单次向量 I/O 允许的最大 iovec 个数（对应 POSIX IOV_MAX）
+pub const IOV_MAX: usize = 1024;
+
 /// iovec 结构体（对应 POSIX struct iovec）
 ///
 /// 用于 readv/writev/preadv/pwritev 系统调用
@@ -18,4 +21,13 @@ impl IoVec {
     pub fn is_valid(&self) -> bool {
         !self.iov_base.is_null() && self.iov_len > 0
     }
+
+    /// 计算一组 iovec 的总长度
+    ///
+    /// 总长度超出 `isize::MAX`（ssize_t 无法表示）时返回 `None`。
+    pub fn total_len(iovs: &[IoVec]) -> Option<usize> {
+        iovs.iter()
+            .try_fold(0usize, |acc, vec| acc.checked_add(vec.iov_len))
+            .filter(|&total| total <= isize::MAX as usize)
+    }
 }
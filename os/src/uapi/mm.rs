@@ -87,6 +87,24 @@ impl MapFlags {
     }
 }
 
+bitflags! {
+    /// memfd_create 标志
+    ///
+    /// 参考：include/uapi/linux/memfd.h
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct MemfdFlags: u32 {
+        /// 对返回的 fd 设置 close-on-exec (MFD_CLOEXEC)
+        const CLOEXEC = 0x0001;
+        /// 允许文件封印 (MFD_ALLOW_SEALING)，当前仅接受该标志
+        const ALLOW_SEALING = 0x0002;
+        /// 使用大页 (MFD_HUGETLB)，当前不支持
+        const HUGETLB = 0x0004;
+    }
+}
+
+/// memfd 名称的最大长度（不含 `memfd:` 前缀和结尾 NUL）
+pub const MFD_NAME_MAX_LEN: usize = 249;
+
 /// 映射类型枚举
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MapType {
@@ -5,14 +5,20 @@
 #![allow(dead_code)]
 pub mod cred;
 pub mod errno;
+pub mod fb;
 pub mod fcntl;
 pub mod fs;
 pub mod futex;
+pub mod input;
+pub mod io_uring;
 pub mod ioctl;
+pub mod ioprio;
 pub mod iovec;
 pub mod ipc;
 pub mod log;
 pub mod mm;
+pub mod mqueue;
+pub mod prctl;
 pub mod reboot;
 pub mod resource;
 pub mod sched;
@@ -24,3 +30,5 @@ pub mod time;
 pub mod types;
 pub mod uts_namespace;
 pub mod wait;
+pub mod watchdog;
+pub mod xattr;
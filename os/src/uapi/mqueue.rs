@@ -0,0 +1,58 @@
+//! POSIX 消息队列 UAPI
+//!
+//! 参考：include/uapi/linux/mqueue.h、ipc/mqueue.c
+
+use core::ffi::{c_int, c_long};
+
+/// 消息优先级上限（不含），合法优先级为 `0..MQ_PRIO_MAX`
+pub const MQ_PRIO_MAX: u32 = 32768;
+
+/// 未指定属性时的默认最大消息数 (DFLT_MSGMAX)
+pub const DFLT_MSGMAX: usize = 10;
+/// 未指定属性时的默认单条消息大小 (DFLT_MSGSIZEMAX)
+pub const DFLT_MSGSIZEMAX: usize = 8192;
+/// 每个队列允许的最大消息数 (HARD_MSGMAX 的子集)
+pub const MQ_MAXMSG_LIMIT: usize = 1024;
+/// 单条消息允许的最大字节数
+pub const MQ_MSGSIZE_LIMIT: usize = 1024 * 1024;
+/// 单个队列的消息总字节上限（`mq_maxmsg * mq_msgsize`）
+pub const MQ_BYTES_LIMIT: usize = 16 * 1024 * 1024;
+
+/// 队列名最大长度 (NAME_MAX)
+pub const MQ_NAME_MAX: usize = 255;
+
+/// 消息队列属性 (struct mq_attr)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MqAttr {
+    /// 队列标志：仅 O_NONBLOCK 有意义
+    pub mq_flags: c_long,
+    /// 最大消息数
+    pub mq_maxmsg: c_long,
+    /// 单条消息最大字节数
+    pub mq_msgsize: c_long,
+    /// 当前消息数
+    pub mq_curmsgs: c_long,
+    pub __reserved: [c_long; 4],
+}
+
+/// 通知方式：发送信号
+pub const SIGEV_SIGNAL: c_int = 0;
+/// 通知方式：不通知
+pub const SIGEV_NONE: c_int = 1;
+/// 通知方式：创建线程（由用户态库实现，内核不支持）
+pub const SIGEV_THREAD: c_int = 2;
+
+/// 异步事件通知描述 (struct sigevent)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SigEvent {
+    /// 随通知传递的值 (union sigval)
+    pub sigev_value: usize,
+    /// 通知信号
+    pub sigev_signo: c_int,
+    /// 通知方式
+    pub sigev_notify: c_int,
+    /// 联合体剩余部分 (SIGEV_THREAD_ID 的 tid 等)
+    pub _sigev_un: [c_int; 12],
+}
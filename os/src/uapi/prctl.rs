@@ -0,0 +1,21 @@
+//! prctl 常量和定义
+//!
+//! 对应于 Linux 用户空间 API `<linux/prctl.h>`。
+
+/// 设置父进程退出时发给调用者的信号
+pub const PR_SET_PDEATHSIG: i32 = 1;
+
+/// 读取父进程退出时发给调用者的信号
+pub const PR_GET_PDEATHSIG: i32 = 2;
+
+/// 设置调用线程的名字
+pub const PR_SET_NAME: i32 = 15;
+
+/// 读取调用线程的名字
+pub const PR_GET_NAME: i32 = 16;
+
+/// 设置调用线程的 timer slack（纳秒），0 表示恢复默认值
+pub const PR_SET_TIMERSLACK: i32 = 29;
+
+/// 读取调用线程的 timer slack（纳秒），通过返回值返回
+pub const PR_GET_TIMERSLACK: i32 = 30;
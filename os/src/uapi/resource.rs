@@ -76,6 +76,8 @@ pub mod rlimit_value {
     pub const MEMLOCK_DEFAULT_LIMIT: RlimT = 64 * 1024;
     /// 消息队列的最大字节数默认值：800KB。
     pub const MQ_BYTES_MAX_DEFAULT: RlimT = 819200;
+    /// 每个待处理集合可排队的信号数默认值。
+    pub const SIGPENDING_DEFAULT_LIMIT: RlimT = 1024;
 }
 
 use rlimit_value::*;
@@ -188,7 +190,8 @@ pub const INIT_RLIMITS: [Rlimit; RLIM_NLIMITS] = {
         Rlimit::new(MEMLOCK_DEFAULT_LIMIT, MEMLOCK_DEFAULT_LIMIT);
     limits[id_to_index(ResourceId::As)] = Rlimit::inf();
     limits[id_to_index(ResourceId::Locks)] = Rlimit::inf();
-    limits[id_to_index(ResourceId::Sigpending)] = Rlimit::new(0, 0);
+    limits[id_to_index(ResourceId::Sigpending)] =
+        Rlimit::new(SIGPENDING_DEFAULT_LIMIT, SIGPENDING_DEFAULT_LIMIT);
     limits[id_to_index(ResourceId::Msgqueue)] =
         Rlimit::new(MQ_BYTES_MAX_DEFAULT, MQ_BYTES_MAX_DEFAULT);
     limits[id_to_index(ResourceId::Nice)] = Rlimit::new(0, 0);
@@ -350,6 +350,17 @@ pub const SIG_IGN: isize = 1;
 /// 错误返回值：表示信号系统调用的错误。
 pub const SIG_ERR: isize = -1;
 
+// --- siginfo_t 的 si_code 取值（信号来源） ---
+
+/// 由 kill 发送。
+pub const SI_USER: c_int = 0;
+/// 由内核发送。
+pub const SI_KERNEL: c_int = 0x80;
+/// 由 sigqueue / rt_sigqueueinfo 发送。
+pub const SI_QUEUE: c_int = -1;
+/// 由 tkill / tgkill 发送。
+pub const SI_TKILL: c_int = -6;
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 /// 信号详细信息结构体 (siginfo_t)
@@ -1,5 +1,7 @@
 //! Socket options and constants
 
+use crate::uapi::time::timeval;
+
 // Address families
 pub const AF_UNIX: i32 = 1;
 pub const AF_LOCAL: i32 = AF_UNIX;
@@ -9,17 +11,20 @@ pub const AF_INET: i32 = 2;
 pub const SOL_SOCKET: i32 = 1;
 pub const IPPROTO_TCP: i32 = 6;
 pub const IPPROTO_IP: i32 = 0;
+pub const IPPROTO_ICMP: i32 = 1;
 pub const IPPROTO_IPV6: i32 = 41;
 
 // Socket types and flags
 pub const SOCK_STREAM: i32 = 1;
 pub const SOCK_DGRAM: i32 = 2;
+pub const SOCK_RAW: i32 = 3;
 pub const SOCK_NONBLOCK: i32 = 0x800;
 pub const SOCK_CLOEXEC: i32 = 0x80000;
 pub const SOCK_TYPE_MASK: i32 = 0x0f;
 
 // SOL_SOCKET options
 pub const SO_REUSEADDR: i32 = 2;
+pub const SO_ERROR: i32 = 4;
 pub const SO_DONTROUTE: i32 = 5;
 pub const SO_BROADCAST: i32 = 6;
 pub const SO_KEEPALIVE: i32 = 9;
@@ -32,6 +37,16 @@ pub const SO_RCVLOWAT: i32 = 18;
 pub const SO_SNDLOWAT: i32 = 19;
 pub const SO_RCVTIMEO_OLD: i32 = 20;
 pub const SO_SNDTIMEO_OLD: i32 = 21;
+pub const SO_RCVTIMEO_NEW: i32 = 66;
+pub const SO_SNDTIMEO_NEW: i32 = 67;
+
+// Socket buffer limits, as Linux `SOCK_MIN_SNDBUF` / `SOCK_MIN_RCVBUF` and the
+// defaults of `net.core.{w,r}mem_default`
+pub const SOCK_MIN_SNDBUF: usize = 4608;
+pub const SOCK_MIN_RCVBUF: usize = 2304;
+pub const SOCK_BUF_DEFAULT: usize = 212992;
+/// Largest value accepted by SO_SNDBUF / SO_RCVBUF before doubling
+pub const SOCK_BUF_MAX: u32 = 16 * 1024 * 1024;
 
 // IPPROTO_IP options (subset; enough for common tools/tests)
 pub const IP_TOS: i32 = 1;
@@ -50,6 +65,9 @@ pub const TCP_CONGESTION: i32 = 13;
 pub const IPV6_V6ONLY: i32 = 26;
 
 /// Socket options storage
+///
+/// Buffer sizes hold the doubled value reported by getsockopt, as on Linux.
+/// A zero timeout means blocking calls wait forever.
 #[derive(Clone, Copy, Debug)]
 pub struct SocketOptions {
     pub reuse_addr: bool,
@@ -60,6 +78,8 @@ pub struct SocketOptions {
     pub tcp_maxseg: usize,
     pub send_buffer_size: usize,
     pub recv_buffer_size: usize,
+    pub recv_timeout: timeval,
+    pub send_timeout: timeval,
 }
 
 impl Default for SocketOptions {
@@ -71,8 +91,10 @@ impl Default for SocketOptions {
             tcp_nodelay: false,
             ipv6_v6only: true,
             tcp_maxseg: 1460, // Default MSS for IPv4
-            send_buffer_size: 65536,
-            recv_buffer_size: 65536,
+            send_buffer_size: SOCK_BUF_DEFAULT,
+            recv_buffer_size: SOCK_BUF_DEFAULT,
+            recv_timeout: timeval::zero(),
+            send_timeout: timeval::zero(),
         }
     }
 }
@@ -33,6 +33,9 @@ pub struct SysInfo {
     pub mem_unit: c_uint,
 }
 
+/// `loads` 字段的定点小数位数
+pub const SI_LOAD_SHIFT: u32 = 16;
+
 const _: () = assert!(core::mem::size_of::<SysInfo>() == 112);
 
 impl SysInfo {
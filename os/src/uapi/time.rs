@@ -6,7 +6,7 @@
 #![allow(dead_code)]
 
 use core::{
-    ffi::{c_int, c_long},
+    ffi::{c_int, c_long, c_uint},
     ops::{Add, Sub},
 };
 
@@ -75,9 +75,7 @@ impl TimeSpec {
     /// # 返回值:
     /// - 当前时间的 TimeSpec 结构体
     pub fn now() -> Self {
-        let time = REALTIME.read();
-        let mtime = Self::monotonic_now();
-        mtime + *time
+        Self::monotonic_now() + REALTIME.read()
     }
 
     /// 获取当前单调时钟时间的 TimeSpec。
@@ -386,3 +384,99 @@ pub mod clock_flags {
     /// TIMER_ABSTIME: 将时间解释为绝对时间而非相对时间。
     pub const TIMER_ABSTIME: c_int = 0x01;
 }
+
+/// 内核时钟调整参数，对应 Linux `struct timex`（adjtimex / clock_adjtime）。
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timex {
+    /// 本次调整的模式位（ADJ_*）
+    pub modes: c_uint,
+    /// 时间偏移（微秒，STA_NANO 时为纳秒）
+    pub offset: c_long,
+    /// 频率偏移（ppm，16 位小数定点）
+    pub freq: c_long,
+    /// 最大误差（微秒）
+    pub maxerror: c_long,
+    /// 估计误差（微秒）
+    pub esterror: c_long,
+    /// 时钟状态（STA_*）
+    pub status: c_int,
+    /// PLL 时间常数
+    pub constant: c_long,
+    /// 时钟精度（微秒，只读）
+    pub precision: c_long,
+    /// 最大频率误差（ppm，16 位小数定点，只读）
+    pub tolerance: c_long,
+    /// 当前时间（只读，ADJ_SETOFFSET 时为偏移量）
+    pub time: timeval,
+    /// 每个时钟节拍的微秒数
+    pub tick: c_long,
+    /// PPS 相关字段（未支持，始终为 0）
+    pub ppsfreq: c_long,
+    pub jitter: c_long,
+    pub shift: c_int,
+    pub stabil: c_long,
+    pub jitcnt: c_long,
+    pub calcnt: c_long,
+    pub errcnt: c_long,
+    pub stbcnt: c_long,
+    /// TAI 与 UTC 的偏移（秒）
+    pub tai: c_int,
+    pub _reserved: [c_int; 11],
+}
+
+/// adjtimex 的模式位、状态位和返回值。
+pub mod adjtimex {
+    use super::{c_int, c_long, c_uint};
+
+    /// 设置时间偏移（按 PLL 方式逐步补偿）
+    pub const ADJ_OFFSET: c_uint = 0x0001;
+    /// 设置频率偏移
+    pub const ADJ_FREQUENCY: c_uint = 0x0002;
+    /// 设置最大误差
+    pub const ADJ_MAXERROR: c_uint = 0x0004;
+    /// 设置估计误差
+    pub const ADJ_ESTERROR: c_uint = 0x0008;
+    /// 设置时钟状态
+    pub const ADJ_STATUS: c_uint = 0x0010;
+    /// 设置 PLL 时间常数
+    pub const ADJ_TIMECONST: c_uint = 0x0020;
+    /// 设置 TAI 偏移
+    pub const ADJ_TAI: c_uint = 0x0080;
+    /// 立即把时间跳变 `time` 指定的量
+    pub const ADJ_SETOFFSET: c_uint = 0x0100;
+    /// 时间单位改为微秒
+    pub const ADJ_MICRO: c_uint = 0x1000;
+    /// 时间单位改为纳秒
+    pub const ADJ_NANO: c_uint = 0x2000;
+    /// 设置节拍长度
+    pub const ADJ_TICK: c_uint = 0x4000;
+    /// 旧式 adjtime：按固定速率逐步补偿偏移
+    pub const ADJ_OFFSET_SINGLESHOT: c_uint = 0x8001;
+    /// 旧式 adjtime：只读取剩余偏移
+    pub const ADJ_OFFSET_SS_READ: c_uint = 0xa001;
+
+    /// 启用 PLL
+    pub const STA_PLL: c_int = 0x0001;
+    /// 时钟未同步
+    pub const STA_UNSYNC: c_int = 0x0040;
+    /// 时间单位为纳秒
+    pub const STA_NANO: c_int = 0x2000;
+    /// 只读状态位
+    pub const STA_RONLY: c_int =
+        0x0100 | 0x0200 | 0x0400 | 0x0800 | 0x1000 | 0x2000 | 0x4000 | 0x8000;
+
+    /// 时钟同步
+    pub const TIME_OK: c_int = 0;
+    /// 时钟未同步
+    pub const TIME_ERROR: c_int = 5;
+
+    /// 最大时间偏移（纳秒）
+    pub const MAXPHASE: c_long = 500_000_000;
+    /// 最大频率偏移（ppm，16 位小数定点）
+    pub const MAXFREQ_SCALED: c_long = 500 << 16;
+    /// 最大误差上限（微秒）
+    pub const NTP_PHASE_LIMIT: c_long = 16_000_000;
+    /// PLL 时间常数上限
+    pub const MAXTC: c_long = 10;
+}
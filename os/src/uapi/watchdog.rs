@@ -0,0 +1,52 @@
+//! 看门狗 UAPI
+//!
+//! 参考：include/uapi/linux/watchdog.h。
+
+use crate::uapi::ioctl::{_IOR, _IOWR};
+
+const WATCHDOG_IOCTL_BASE: u32 = b'W' as u32;
+
+/// `WDIOC_GETSUPPORT` 返回的设备信息 (struct watchdog_info)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WatchdogInfo {
+    /// 支持的 `WDIOF_*` 选项
+    pub options: u32,
+    /// 固件版本
+    pub firmware_version: u32,
+    /// 设备名，以 NUL 结尾
+    pub identity: [u8; 32],
+}
+
+/// 获取设备信息
+pub const WDIOC_GETSUPPORT: u32 = _IOR(
+    WATCHDOG_IOCTL_BASE,
+    0,
+    core::mem::size_of::<WatchdogInfo>() as u32,
+);
+/// 获取当前状态
+pub const WDIOC_GETSTATUS: u32 = _IOR(WATCHDOG_IOCTL_BASE, 1, 4);
+/// 获取上次启动的原因
+pub const WDIOC_GETBOOTSTATUS: u32 = _IOR(WATCHDOG_IOCTL_BASE, 2, 4);
+/// 设置选项（`WDIOS_*`）
+pub const WDIOC_SETOPTIONS: u32 = _IOR(WATCHDOG_IOCTL_BASE, 4, 4);
+/// 喂狗
+pub const WDIOC_KEEPALIVE: u32 = _IOR(WATCHDOG_IOCTL_BASE, 5, 4);
+/// 设置超时（秒），返回实际生效的超时
+pub const WDIOC_SETTIMEOUT: u32 = _IOWR(WATCHDOG_IOCTL_BASE, 6, 4);
+/// 获取超时（秒）
+pub const WDIOC_GETTIMEOUT: u32 = _IOR(WATCHDOG_IOCTL_BASE, 7, 4);
+
+/// 上次复位由看门狗引起
+pub const WDIOF_CARDRESET: u32 = 0x0020;
+/// 支持设置超时
+pub const WDIOF_SETTIMEOUT: u32 = 0x0080;
+/// 支持魔术关闭字符 'V'
+pub const WDIOF_MAGICCLOSE: u32 = 0x0100;
+/// 支持 `WDIOC_KEEPALIVE`
+pub const WDIOF_KEEPALIVEPING: u32 = 0x8000;
+
+/// 停止看门狗
+pub const WDIOS_DISABLECARD: u32 = 0x0001;
+/// 启动看门狗
+pub const WDIOS_ENABLECARD: u32 = 0x0002;
@@ -0,0 +1,25 @@
+//! 扩展属性常量和定义
+//!
+//! 对应于 Linux 用户空间 API `<linux/xattr.h>` 和 `<linux/limits.h>`。
+
+use bitflags::bitflags;
+
+/// 属性名的最大长度（不含结尾的 `\0`）
+pub const XATTR_NAME_MAX: usize = 255;
+
+/// 单个属性值的最大长度
+pub const XATTR_SIZE_MAX: usize = 65536;
+
+/// listxattr 返回的名字列表的最大长度
+pub const XATTR_LIST_MAX: usize = 65536;
+
+bitflags! {
+    /// setxattr 的 `flags` 参数
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct XattrFlags: u32 {
+        /// 属性已存在时失败（EEXIST）
+        const CREATE = 1;
+        /// 属性不存在时失败（ENODATA）
+        const REPLACE = 2;
+    }
+}
@@ -0,0 +1,19 @@
+//! CRC-32（IEEE 802.3，与 zlib 相同）
+//!
+//! 按位计算，不使用查找表，只用于少量元数据（pstore 记录、SimpleFS 镜像头）的校验。
+
+/// 计算 `data` 的 CRC-32
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
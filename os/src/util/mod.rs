@@ -1,8 +1,10 @@
 //! 工具函数模块
 #![allow(dead_code)]
 pub mod address;
+pub mod crc32;
 pub mod mem;
 pub mod ring_buffer;
+pub mod sha256;
 pub mod stdio;
 pub mod user_buffer;
 
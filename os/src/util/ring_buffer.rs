@@ -65,6 +65,11 @@ impl RingBuffer {
         Ok(())
     }
 
+    /// 环形缓冲区是否为空
+    pub fn is_empty(&self) -> bool {
+        self.status == BufferStatus::EMPTY
+    }
+
     /// 获取环形缓冲区的可用空间
     pub fn available_space(&self) -> usize {
         match self.status {
@@ -1,7 +1,7 @@
 //! 用户态缓冲区
 //!
 //! 通过 `Arch` trait 的 `copy_from_user`/`copy_to_user` 方法访问用户空间内存，
-//! 不再直接依赖架构特定的 SumGuard。
+//! 这些方法在 [`UserAccessGuard`](crate::arch::uaccess::UserAccessGuard) 保护下完成拷贝。
 
 use alloc::vec::Vec;
 use core::mem::MaybeUninit;
@@ -2,8 +2,13 @@
 //!
 //! 用于处理数据结构之间的转换
 
-use super::{InodeMetadata, InodeType};
-use crate::uapi::fs::{STATX_BASIC_STATS, Stat, Statx, StatxTimestamp};
+use alloc::vec::Vec;
+
+use super::{DirEntry, InodeMetadata, InodeType};
+use crate::uapi::fs::{
+    DT_BLK, DT_CHR, DT_DIR, DT_FIFO, DT_LNK, DT_REG, DT_SOCK, LinuxDirent64, STATX_BASIC_STATS,
+    Stat, Statx, StatxTimestamp,
+};
 use crate::vfs::dev::{encode_linux_dev, major, minor};
 
 /// Stat 结构适配方法
@@ -91,14 +96,57 @@ fn mode_with_type(meta: &InodeMetadata) -> u32 {
 }
 
 /// 将InodeType转换为d_type值
-pub fn inode_type_to_d_type(t: InodeType) -> u8 {
+fn inode_type_to_d_type(t: InodeType) -> u8 {
     match t {
-        InodeType::File => 8,        // DT_REG
-        InodeType::Directory => 4,   // DT_DIR
-        InodeType::Symlink => 10,    // DT_LNK
-        InodeType::CharDevice => 2,  // DT_CHR
-        InodeType::BlockDevice => 6, // DT_BLK
-        InodeType::Fifo => 1,        // DT_FIFO
-        InodeType::Socket => 12,     // DT_SOCK
+        InodeType::File => DT_REG,
+        InodeType::Directory => DT_DIR,
+        InodeType::Symlink => DT_LNK,
+        InodeType::CharDevice => DT_CHR,
+        InodeType::BlockDevice => DT_BLK,
+        InodeType::Fifo => DT_FIFO,
+        InodeType::Socket => DT_SOCK,
+    }
+}
+
+impl LinuxDirent64 {
+    /// 把一条记录（头部、以 NUL 结尾的文件名和对齐填充）追加到 `buf`
+    pub fn encode(&self, name: &str, buf: &mut Vec<u8>) {
+        let start = buf.len();
+        buf.extend_from_slice(&self.d_ino.to_ne_bytes());
+        buf.extend_from_slice(&self.d_off.to_ne_bytes());
+        buf.extend_from_slice(&self.d_reclen.to_ne_bytes());
+        buf.push(self.d_type);
+        buf.extend_from_slice(name.as_bytes());
+        buf.resize(start + self.d_reclen as usize, 0);
+    }
+}
+
+/// 把 `entries[start..]` 依次编码为 `linux_dirent64` 记录追加到 `buf`，总长度不超过 `limit`
+///
+/// 返回编码的条目数，放不下下一条时停止。每条记录的 `d_off` 是下一个条目的下标，
+/// 即读完这条记录后的目录偏移。
+pub fn encode_dirents(
+    entries: &[DirEntry],
+    start: usize,
+    limit: usize,
+    buf: &mut Vec<u8>,
+) -> usize {
+    let mut used = 0;
+    let mut count = 0;
+    for (index, entry) in entries.iter().enumerate().skip(start) {
+        let reclen = LinuxDirent64::total_len(&entry.name);
+        if used + reclen > limit {
+            break;
+        }
+        LinuxDirent64 {
+            d_ino: entry.inode_no as u64,
+            d_off: (index + 1) as i64,
+            d_reclen: reclen as u16,
+            d_type: inode_type_to_d_type(entry.inode_type),
+        }
+        .encode(&entry.name, buf);
+        used += reclen;
+        count += 1;
     }
+    count
 }
@@ -108,18 +108,23 @@
 //! ```
 
 use crate::sync::SpinLock;
+use crate::vfs::FsError;
 use crate::vfs::inode::Inode;
 use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::sync::{Arc, Weak};
 use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
 
 /// 目录项（Dentry）
 ///
 /// 表示路径中的一个组件，缓存文件名到 inode 的映射
 pub struct Dentry {
-    /// 文件名（不含路径）
-    pub name: String,
+    /// 文件名（不含路径），重命名时更新
+    name: SpinLock<String>,
+
+    /// 对应的目录项已被删除（rmdir、unlink 或被 rename 覆盖）
+    deleted: AtomicBool,
 
     /// 关联的 inode
     pub inode: Arc<dyn Inode>,
@@ -139,14 +144,14 @@ pub struct Dentry {
 
 impl fmt::Debug for Dentry {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let parent_name = self.parent().map(|p| p.name.clone());
+        let parent_name = self.parent().map(|p| p.name());
         let child_names = {
             let children = self.children.lock();
             children.keys().cloned().collect::<alloc::vec::Vec<_>>()
         };
 
         f.debug_struct("Dentry")
-            .field("name", &self.name)
+            .field("name", &self.name())
             .field("parent", &parent_name)
             .field("children", &child_names)
             .finish()
@@ -157,7 +162,8 @@ impl Dentry {
     /// 创建新的 dentry
     pub fn new(name: String, inode: Arc<dyn Inode>) -> Arc<Self> {
         let dentry = Arc::new(Self {
-            name,
+            name: SpinLock::new(name),
+            deleted: AtomicBool::new(false),
             inode,
             parent: SpinLock::new(Weak::new()),
             children: SpinLock::new(BTreeMap::new()),
@@ -170,6 +176,26 @@ impl Dentry {
         dentry
     }
 
+    /// 文件名（不含路径）
+    pub fn name(&self) -> String {
+        self.name.lock().clone()
+    }
+
+    /// 不分配内存地借用文件名；名字正被修改（锁被占用）时返回 `None`
+    pub fn try_with_name<R>(&self, f: impl FnOnce(&str) -> R) -> Option<R> {
+        self.name.try_lock().map(|name| f(&name))
+    }
+
+    /// 标记对应的目录项已被删除
+    pub fn mark_deleted(&self) {
+        self.deleted.store(true, Ordering::Release);
+    }
+
+    /// 对应的目录项是否已被删除
+    pub fn is_deleted(&self) -> bool {
+        self.deleted.load(Ordering::Acquire)
+    }
+
     /// 设置父 dentry
     pub fn set_parent(&self, parent: &Arc<Dentry>) {
         *self.parent.lock() = Arc::downgrade(parent);
@@ -188,7 +214,7 @@ impl Dentry {
     /// 添加子 dentry
     pub fn add_child(self: &Arc<Self>, child: Arc<Dentry>) {
         child.set_parent(self);
-        self.children.lock().insert(child.name.clone(), child);
+        self.children.lock().insert(child.name(), child);
     }
 
     /// 删除子 dentry
@@ -196,19 +222,71 @@ impl Dentry {
         self.children.lock().remove(name)
     }
 
+    /// 重命名后把 dentry 移动到 `new_parent` 下的 `new_name`
+    ///
+    /// 仍持有该 dentry 的引用（cwd、打开的文件）随之看到新的路径。
+    pub fn move_to(self: &Arc<Self>, new_parent: &Arc<Dentry>, new_name: &str) {
+        if let Some(old_parent) = self.parent() {
+            let old_name = self.name();
+            let mut children = old_parent.children.lock();
+            if children
+                .get(&old_name)
+                .is_some_and(|child| Arc::ptr_eq(child, self))
+            {
+                children.remove(&old_name);
+            }
+        }
+        *self.name.lock() = String::from(new_name);
+        new_parent.add_child(self.clone());
+    }
+
     /// 获取完整路径（通过向上遍历父节点直到根目录）
     pub fn full_path(&self) -> String {
+        self.path_below(None)
+    }
+
+    /// 渲染绝对路径（对应 Linux 的 `d_path`），用于 `/proc` 中的路径展示
+    ///
+    /// 与 [`full_path`](Self::full_path) 一样跨越挂载点回溯到全局根目录；已删除的
+    /// dentry 追加 ` (deleted)` 后缀。
+    pub fn d_path(&self) -> String {
+        let path = self.full_path();
+        if self.is_deleted() {
+            path + " (deleted)"
+        } else {
+            path
+        }
+    }
+
+    /// 获取相对 `root` 的路径，用于 getcwd
+    ///
+    /// 向上遍历父节点并跨越挂载点，遇到 `root` 即停止，未遇到则一直回溯到全局根目录。
+    /// 已删除的 dentry 返回 `NotFound`。
+    pub fn path_from(&self, root: &Arc<Dentry>) -> Result<String, FsError> {
+        if self.is_deleted() {
+            return Err(FsError::NotFound);
+        }
+        Ok(self.path_below(Some(root)))
+    }
+
+    /// 向上遍历直到全局根目录或 `stop`
+    fn path_below(&self, stop: Option<&Arc<Dentry>>) -> String {
+        let is_stop = |dentry: &Dentry| stop.is_some_and(|stop| core::ptr::eq(&**stop, dentry));
         let mut components = alloc::vec::Vec::new();
-        let mut current_name = self.name.clone();
+        let mut reached = is_stop(self);
+        let mut current_name = self.name();
         let mut current_parent = self.parent();
         let mut current_mounted_on = self.mounted_on.lock().as_ref().and_then(Weak::upgrade);
 
         // 向上遍历到全局根目录。挂载文件系统的 root dentry 名字也是 "/"，
         // 但它的全局路径应继续通过外层挂载点回溯。
-        loop {
+        while !reached {
             if current_name == "/" {
                 if let Some(mount_parent) = current_mounted_on {
-                    current_name = mount_parent.name.clone();
+                    if is_stop(&mount_parent) {
+                        break;
+                    }
+                    current_name = mount_parent.name();
                     current_parent = mount_parent.parent();
                     current_mounted_on = mount_parent
                         .mounted_on
@@ -225,7 +303,8 @@ impl Dentry {
             // 获取父节点
             match current_parent {
                 Some(parent) => {
-                    current_name = parent.name.clone();
+                    reached = is_stop(&parent);
+                    current_name = parent.name();
                     current_parent = parent.parent();
                     current_mounted_on = parent.mounted_on.lock().as_ref().and_then(Weak::upgrade);
                 }
@@ -4,26 +4,38 @@
 
 use crate::device::block::BlockDriver;
 use crate::device::block::partition::{PartitionBlockDevice, discover_partitions};
-use crate::device::{BLK_DRIVERS, Driver, RTC_DRIVERS, SERIAL_DRIVERS};
+use crate::device::block::verity;
+use crate::device::console::{console_driver, find_console, tty_driver};
+use crate::device::{BLK_DRIVERS, Driver, INPUT_DRIVERS, RTC_DRIVERS, SERIAL_DRIVERS};
 use crate::vfs::dev::{major, minor};
 use alloc::format;
 use alloc::sync::Arc;
 
 /// 标准字符设备 major 号
 pub mod chrdev_major {
-    pub const MEM: u32 = 1; // /dev/null, /dev/zero 等
+    pub const MEM: u32 = 1; // /dev/mem, /dev/null, /dev/zero 等
     pub const TTY: u32 = 4; // /dev/tty*, /dev/ttyS*
     pub const CONSOLE: u32 = 5; // /dev/console
     pub const MISC: u32 = 10; // /dev/misc/* (rtc=135)
     pub const INPUT: u32 = 13; // /dev/input/*
+    pub const FB: u32 = 29; // /dev/fb*
 }
 
 /// MISC 设备 minor 号
 pub mod misc_minor {
+    pub const WATCHDOG: u32 = 130;
     pub const CPU_DMA_LATENCY: u32 = 123;
     pub const RTC: u32 = 135;
 }
 
+/// INPUT 设备 minor 号
+pub mod input_minor {
+    /// /dev/input/eventN 的起始 minor（event0 = 64）
+    pub const EVDEV_BASE: u32 = 64;
+    /// evdev 设备数量上限
+    pub const EVDEV_MAX: u32 = 32;
+}
+
 /// 标准块设备 major 号
 pub mod blkdev_major {
     pub const LOOP: u32 = 7; // /dev/loop*
@@ -59,7 +71,7 @@ pub fn get_chrdev_driver(dev: u64) -> Option<Arc<dyn Driver>> {
 
     match maj {
         chrdev_major::MEM => {
-            // 内存设备 (/dev/null, /dev/zero 等)
+            // 内存设备 (/dev/mem, /dev/null, /dev/zero 等)
             // 在 CharDeviceFile 中直接处理，无需驱动
             None
         }
@@ -72,19 +84,22 @@ pub fn get_chrdev_driver(dev: u64) -> Option<Arc<dyn Driver>> {
                     .lock()
                     .get(idx)
                     .map(|d| d.clone() as Arc<dyn Driver>)
+            } else if min < 64 {
+                // 虚拟终端：tty0-tty63 (minor 0-63)，目前只有帧缓冲控制台 tty0
+                find_console(&format!("tty{}", min)).map(|c| tty_driver(&c))
             } else {
-                // 虚拟终端：tty0-tty63 (minor 0-63)
-                // 暂不支持
                 None
             }
         }
         chrdev_major::CONSOLE => {
             // 控制台设备 (minor 1)
-            // 使用第一个串口作为控制台
-            SERIAL_DRIVERS
-                .lock()
-                .first()
-                .map(|d| d.clone() as Arc<dyn Driver>)
+            // 指向 console= 选出的主控制台；控制台尚未初始化时退回到第一个串口
+            console_driver().or_else(|| {
+                SERIAL_DRIVERS
+                    .lock()
+                    .first()
+                    .map(|d| d.clone() as Arc<dyn Driver>)
+            })
         }
         chrdev_major::MISC => {
             // misc 设备
@@ -100,6 +115,23 @@ pub fn get_chrdev_driver(dev: u64) -> Option<Arc<dyn Driver>> {
                 None
             }
         }
+        chrdev_major::INPUT => {
+            // evdev 设备：event0-event31 (minor 64-95)
+            let base = input_minor::EVDEV_BASE;
+            if (base..base + input_minor::EVDEV_MAX).contains(&min) {
+                INPUT_DRIVERS
+                    .read()
+                    .get((min - base) as usize)
+                    .map(|d| d.clone() as Arc<dyn Driver>)
+            } else {
+                None
+            }
+        }
+        chrdev_major::FB => {
+            // 帧缓冲设备 (/dev/fb0)
+            // 在 CharDeviceFile 中直接通过 FRAME_BUFFER 处理，无需驱动
+            None
+        }
         _ => None,
     }
 }
@@ -148,16 +180,22 @@ pub fn get_blkdev_driver(dev: u64) -> Option<Arc<dyn BlockDriver>> {
             let drivers = BLK_DRIVERS.read();
             let disk = drivers.get(disk_idx)?.clone();
 
+            let disk_name = format!("vd{}", (b'a' + disk_idx as u8) as char);
             if partition_number == 0 {
-                return Some(disk);
+                return Some(verity::wrap(&disk_name, disk));
             }
 
             let partition = discover_partitions(&disk)
                 .into_iter()
                 .find(|entry| entry.number == partition_number)?;
-            let name = format!("vd{}{}", (b'a' + disk_idx as u8) as char, partition.number);
-            PartitionBlockDevice::new(disk, name, partition.start_lba, partition.sector_count)
-                .map(|device| device as Arc<dyn BlockDriver>)
+            let name = format!("{}{}", disk_name, partition.number);
+            let device = PartitionBlockDevice::new(
+                disk,
+                name.clone(),
+                partition.start_lba,
+                partition.sector_count,
+            )?;
+            Some(verity::wrap(&name, device))
         }
         blkdev_major::SCSI_DISK => {
             let disk_idx = (min / 16) as usize;
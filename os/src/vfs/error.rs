@@ -16,10 +16,12 @@ pub enum FsError {
 
     // 权限相关
     PermissionDenied, // -EACCES(13): 权限被拒绝
+    NotPermitted,     // -EPERM(1): 不是属主或缺少所需能力
 
     // 文件描述符相关
     BadFileDescriptor, // -EBADF(9): 无效的文件描述符
     TooManyOpenFiles,  // -EMFILE(24): 打开的文件过多
+    FileTableOverflow, // -ENFILE(23): 系统打开的文件总数达到上限
 
     // 参数相关
     InvalidArgument, // -EINVAL(22): 无效参数
@@ -45,11 +47,17 @@ pub enum FsError {
     NotConnected,               // -ENOTCONN(107): 套接字未连接
 
     // 其他
+    Interrupted,     // -EINTR(4): 阻塞等待被信号打断
     NotSupported,    // -ENOTSUP(95): 操作不支持
     NotTty,          // -ENOTTY(25): 非 TTY 设备或不支持该 ioctl
     NotSeekable,     // -ESPIPE(29): 不支持 seek
     TooManyLinks,    // -EMLINK(31): 硬链接过多
     TooManySymlinks, // -ELOOP(40): 符号链接层级过多
+
+    // 扩展属性相关
+    NoData,     // -ENODATA(61): 扩展属性不存在
+    OutOfRange, // -ERANGE(34): 缓冲区太小或属性名长度不合法
+    TooBig,     // -E2BIG(7): 属性值或名字列表超出上限
 }
 
 impl FsError {
@@ -65,6 +73,7 @@ impl FsError {
             FsError::WouldBlock => -EAGAIN as isize,
             FsError::NoMemory => -ENOMEM as isize,
             FsError::PermissionDenied => -EACCES as isize,
+            FsError::NotPermitted => -EPERM as isize,
             FsError::BadAddress => -EFAULT as isize,
             FsError::Busy => -EBUSY as isize,
             FsError::AlreadyExists => -EEXIST as isize,
@@ -74,6 +83,7 @@ impl FsError {
             FsError::IsDirectory => -EISDIR as isize,
             FsError::InvalidArgument => -EINVAL as isize,
             FsError::TooManyOpenFiles => -EMFILE as isize,
+            FsError::FileTableOverflow => -ENFILE as isize,
             FsError::NotTty => -ENOTTY as isize,
             FsError::NoSpace => -ENOSPC as isize,
             FsError::NotSeekable => -ESPIPE as isize,
@@ -83,9 +93,13 @@ impl FsError {
             FsError::NameTooLong => -ENAMETOOLONG as isize,
             FsError::DirectoryNotEmpty => -ENOTEMPTY as isize,
             FsError::TooManySymlinks => -ELOOP as isize,
+            FsError::Interrupted => -EINTR as isize,
             FsError::NotSupported => -EOPNOTSUPP as isize,
             FsError::DestinationAddressRequired => -EDESTADDRREQ as isize,
             FsError::NotConnected => -ENOTCONN as isize,
+            FsError::NoData => -ENODATA as isize,
+            FsError::OutOfRange => -ERANGE as isize,
+            FsError::TooBig => -E2BIG as isize,
         }
     }
 }
@@ -104,5 +118,10 @@ mod tests {
         kassert!(FsError::BadAddress.to_errno() == -crate::uapi::errno::EFAULT as isize);
         kassert!(FsError::NotSeekable.to_errno() == -crate::uapi::errno::ESPIPE as isize);
         kassert!(FsError::NotTty.to_errno() == -crate::uapi::errno::ENOTTY as isize);
+        kassert!(FsError::Interrupted.to_errno() == -crate::uapi::errno::EINTR as isize);
+        kassert!(FsError::NotPermitted.to_errno() == -crate::uapi::errno::EPERM as isize);
+        kassert!(FsError::NoData.to_errno() == -crate::uapi::errno::ENODATA as isize);
+        kassert!(FsError::OutOfRange.to_errno() == -crate::uapi::errno::ERANGE as isize);
+        kassert!(FsError::TooBig.to_errno() == -crate::uapi::errno::E2BIG as isize);
     });
 }
@@ -63,13 +63,8 @@
 //!
 //! ### fork 时
 //!
-//! 父子进程共享整个 FDTable：
-//!
-//! ```text
-//! 父进程 ──┐
-//!          ├──> Arc<FDTable>
-//! 子进程 ──┘
-//! ```
+//! 不带 `CLONE_FILES` 时子进程得到一份 [`FDTable::clone_table`] 复制的表，
+//! 两张表指向同一批 File 对象；带 `CLONE_FILES` 时（线程）共享同一个 `Arc<FDTable>`。
 //!
 //! ### exec 时
 //!
@@ -79,14 +74,25 @@
 //! fd_table.close_exec();  // exec 前调用
 //! ```
 //!
+//! # 分配
+//!
+//! 占用情况记录在两级位图中：一级位图每位对应一个 fd，二级位图每位对应一级位图
+//! 中的一个字，置位表示该字已全满。查找最小空闲 fd 时借助二级位图跳过全满的字，
+//! 默认上限下只需检查常数个字，不再线性扫描文件数组。
+//!
 //! # 并发安全
 //!
-//! FDTable 内部使用 `SpinLock` 保护：
-//! - `files`: `Vec<Option<Arc<dyn File>>>`
-//! - `fd_flags`: `Vec<FdFlags>`
+//! 表内容由读写锁保护。系统调用最频繁的操作是按 fd 取文件（[`FDTable::get`]），
+//! 只取读锁，多个线程的查找互不阻塞；分配、关闭、dup 等修改操作取写锁，
+//! 并在释放锁之后才 drop 被替换下来的文件，File 的析构不会在持锁时运行。
+//!
+//! File 对象本身（如 RegFile 的 offset）使用原子操作，无需额外锁定。
 //!
-//! 多线程访问 FDTable 时会竞争锁，但 File 对象本身（如 RegFile 的 offset）
-//! 使用原子操作，无需额外锁定。
+//! # 全局计数
+//!
+//! 所有表中已安装的文件描述符总数记录在全局计数器中，通过
+//! `/proc/sys/fs/file-nr` 导出，超过 [`FILE_MAX`] 时分配失败（`ENFILE`）。
+//! 与 Linux 统计打开的文件对象不同，这里 dup 出的 fd 各计一次。
 //!
 //! # 使用示例
 //!
@@ -133,8 +139,8 @@
 //! // 现在所有 println! 都会写到文件
 //! ```
 
-use crate::config::DEFAULT_MAX_FDS;
-use crate::sync::SpinLock;
+use crate::config::{DEFAULT_MAX_FDS, FILE_MAX};
+use crate::sync::RwLock;
 use crate::uapi::fcntl::{FdFlags, OpenFlags};
 use crate::vfs::{File, FsError};
 use alloc::sync::Arc;
@@ -144,19 +150,118 @@ use core::{
     sync::atomic::{AtomicUsize, Ordering},
 };
 
+/// 位图中每个字的位数
+const BITS: usize = usize::BITS as usize;
+
+/// 所有 FDTable 中已安装的文件描述符总数
+static NR_OPEN_FDS: AtomicUsize = AtomicUsize::new(0);
+
+/// 全系统已安装的文件描述符总数
+pub fn nr_open_files() -> usize {
+    NR_OPEN_FDS.load(Ordering::Relaxed)
+}
+
+/// 一个已打开的文件描述符
+#[derive(Clone)]
+struct FdEntry {
+    file: Arc<dyn File>,
+    /// FD 标志（FD_CLOEXEC），每个 fd 独立，dup 时不继承
+    flags: FdFlags,
+}
+
+/// 读写锁保护的表内容
+///
+/// 不变式：`open_fds` 的第 fd 位置位当且仅当 `entries[fd]` 为 `Some`；
+/// `full_words` 的第 i 位置位当且仅当 `open_fds[i]` 全满。
+#[derive(Clone, Default)]
+struct FdSlots {
+    entries: Vec<Option<FdEntry>>,
+    /// 一级位图，每位对应一个 fd
+    open_fds: Vec<usize>,
+    /// 二级位图，每位对应 `open_fds` 中的一个字
+    full_words: Vec<usize>,
+}
+
+impl FdSlots {
+    fn get(&self, fd: usize) -> Option<&FdEntry> {
+        self.entries.get(fd).and_then(Option::as_ref)
+    }
+
+    /// 大于等于 `start` 的最小空闲 fd，可能超出 `entries` 的当前长度
+    fn find_free(&self, start: usize) -> usize {
+        let mut word = start / BITS;
+        let Some(&bits) = self.open_fds.get(word) else {
+            return start;
+        };
+        // 起始字中 start 之前的位视为已占用
+        let bits = bits | ((1usize << (start % BITS)) - 1);
+        if bits != usize::MAX {
+            return word * BITS + (!bits).trailing_zeros() as usize;
+        }
+        word += 1;
+
+        loop {
+            let Some(&full) = self.full_words.get(word / BITS) else {
+                return word * BITS;
+            };
+            let full = full | ((1usize << (word % BITS)) - 1);
+            if full != usize::MAX {
+                word = (word / BITS) * BITS + (!full).trailing_zeros() as usize;
+                return match self.open_fds.get(word) {
+                    Some(&bits) => word * BITS + (!bits).trailing_zeros() as usize,
+                    None => word * BITS,
+                };
+            }
+            word = (word / BITS + 1) * BITS;
+        }
+    }
+
+    /// 在 `fd` 处安装，返回被替换的旧项
+    fn install(&mut self, fd: usize, entry: FdEntry) -> Result<Option<FdEntry>, FsError> {
+        if self.get(fd).is_none() {
+            NR_OPEN_FDS
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                    (n < FILE_MAX).then_some(n + 1)
+                })
+                .map_err(|_| FsError::FileTableOverflow)?;
+        }
+        if self.entries.len() <= fd {
+            self.entries.resize(fd + 1, None);
+            self.open_fds.resize(fd / BITS + 1, 0);
+            self.full_words
+                .resize(self.open_fds.len().div_ceil(BITS), 0);
+        }
+        let (word, bit) = (fd / BITS, fd % BITS);
+        self.open_fds[word] |= 1 << bit;
+        if self.open_fds[word] == usize::MAX {
+            self.full_words[word / BITS] |= 1 << (word % BITS);
+        }
+        Ok(self.entries[fd].replace(entry))
+    }
+
+    /// 移除 `fd` 处的项
+    fn remove(&mut self, fd: usize) -> Option<FdEntry> {
+        let entry = self.entries.get_mut(fd)?.take()?;
+        let word = fd / BITS;
+        self.open_fds[word] &= !(1 << (fd % BITS));
+        self.full_words[word / BITS] &= !(1 << (word % BITS));
+        NR_OPEN_FDS.fetch_sub(1, Ordering::Relaxed);
+        Some(entry)
+    }
+
+    /// 已打开的 fd 个数
+    fn count(&self) -> usize {
+        self.open_fds.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
 /// 文件描述符表
 ///
 /// # 并发安全
 ///
-/// 内部使用 `SpinLock` 保护，支持多线程访问。
+/// 内部使用读写锁保护，查找只取读锁，支持多线程并发访问。
 pub struct FDTable {
-    /// 文件描述符数组
-    /// None 表示该 FD 未使用
-    files: SpinLock<Vec<Option<Arc<dyn File>>>>,
-
-    /// 文件描述符标志数组（与 files 索引对应）
-    /// 默认值为 FdFlags::empty()
-    fd_flags: SpinLock<Vec<FdFlags>>,
+    slots: RwLock<FdSlots>,
 
     /// 最大文件描述符数量
     max_fds: AtomicUsize,
@@ -177,22 +282,33 @@ impl FdFlags {
 
 impl fmt::Debug for FDTable {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let files = self.files.lock();
-        let used = files.iter().filter(|slot| slot.is_some()).count();
+        let slots = self.slots.read();
         f.debug_struct("FDTable")
             .field("max_fds", &self.max_fds.load(Ordering::Relaxed))
-            .field("slots", &files.len())
-            .field("used", &used)
+            .field("slots", &slots.entries.len())
+            .field("used", &slots.count())
             .finish()
     }
 }
 
+impl Default for FDTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for FDTable {
+    fn drop(&mut self) {
+        let open = self.slots.read().count();
+        NR_OPEN_FDS.fetch_sub(open, Ordering::Relaxed);
+    }
+}
+
 impl FDTable {
     /// 创建新的文件描述符表
     pub fn new() -> Self {
         Self {
-            files: SpinLock::new(Vec::new()),
-            fd_flags: SpinLock::new(Vec::new()),
+            slots: RwLock::new(FdSlots::default()),
             max_fds: AtomicUsize::new(DEFAULT_MAX_FDS),
         }
     }
@@ -209,19 +325,25 @@ impl FDTable {
     /// - 这不会做任何“按类型”的额外清理（例如 socket fd 的 (tid,fd)->handle 映射），
     ///   需要调用方在 drop 前自行处理。
     pub fn take_all(&self) -> Vec<(usize, Arc<dyn File>)> {
-        let mut files = self.files.lock();
-        let mut fd_flags = self.fd_flags.lock();
+        let mut slots = self.slots.write();
+        (0..slots.entries.len())
+            .filter_map(|fd| slots.remove(fd).map(|entry| (fd, entry.file)))
+            .collect()
+    }
 
-        let mut out = Vec::new();
-        for (fd, slot) in files.iter_mut().enumerate() {
-            if let Some(file) = slot.take() {
-                out.push((fd, file));
+    /// 按 fd 升序遍历已打开的文件，不分配内存
+    ///
+    /// 表正被修改（写锁被占用）时不等待，直接返回 `false`。
+    pub fn try_for_each(&self, mut f: impl FnMut(usize, &Arc<dyn File>, FdFlags)) -> bool {
+        let Some(slots) = self.slots.try_read() else {
+            return false;
+        };
+        for (fd, entry) in slots.entries.iter().enumerate() {
+            if let Some(entry) = entry {
+                f(fd, &entry.file, entry.flags);
             }
         }
-        for f in fd_flags.iter_mut() {
-            *f = FdFlags::empty();
-        }
-        out
+        true
     }
 
     /// 分配一个新的文件描述符（默认无 FD 标志）
@@ -231,27 +353,17 @@ impl FDTable {
 
     /// 分配一个新的文件描述符并指定 FD 标志
     pub fn alloc_with_flags(&self, file: Arc<dyn File>, flags: FdFlags) -> Result<usize, FsError> {
-        let mut files = self.files.lock();
-        let mut fd_flags = self.fd_flags.lock();
-        let max_fds = self.max_fds.load(Ordering::Relaxed);
-
-        // 查找最小可用 FD
-        for (fd, slot) in files.iter_mut().enumerate().take(max_fds) {
-            if slot.is_none() {
-                *slot = Some(file);
-                fd_flags[fd] = flags;
-                return Ok(fd);
-            }
-        }
+        self.alloc_from(0, FdEntry { file, flags })
+    }
 
-        // 如果没有空闲槽位，扩展数组
-        let fd = files.len();
-        if fd >= max_fds {
+    /// 在 >= `min_fd` 的最小空闲位置安装
+    fn alloc_from(&self, min_fd: usize, entry: FdEntry) -> Result<usize, FsError> {
+        let mut slots = self.slots.write();
+        let fd = slots.find_free(min_fd);
+        if fd >= self.max_fds.load(Ordering::Relaxed) {
             return Err(FsError::TooManyOpenFiles);
         }
-
-        files.push(Some(file));
-        fd_flags.push(flags);
+        slots.install(fd, entry)?;
         Ok(fd)
     }
 
@@ -261,61 +373,45 @@ impl FDTable {
     }
 
     /// 在指定的 FD 位置安装文件并指定 FD 标志
+    ///
+    /// `fd` 上原有的文件被替换，在释放锁之后关闭。
     pub fn install_at_with_flags(
         &self,
         fd: usize,
         file: Arc<dyn File>,
         flags: FdFlags,
     ) -> Result<(), FsError> {
-        let mut files = self.files.lock();
-        let mut fd_flags = self.fd_flags.lock();
-        let max_fds = self.max_fds.load(Ordering::Relaxed);
-
-        if fd >= max_fds {
+        if fd >= self.max_fds.load(Ordering::Relaxed) {
             return Err(FsError::InvalidArgument);
         }
-
-        // 扩展数组到指定大小
-        while files.len() <= fd {
-            files.push(None);
-            fd_flags.push(FdFlags::empty());
-        }
-
-        // 替换（旧文件会自动通过 Arc 释放）
-        files[fd] = Some(file);
-        fd_flags[fd] = flags;
+        let old = self.slots.write().install(fd, FdEntry { file, flags })?;
+        drop(old);
         Ok(())
     }
 
     /// 获取文件对象
+    ///
+    /// 只取读锁，与其他线程的查找并发进行。
     pub fn get(&self, fd: usize) -> Result<Arc<dyn File>, FsError> {
-        let files = self.files.lock();
-        files
+        self.slots
+            .read()
             .get(fd)
-            .and_then(|f| f.clone())
+            .map(|entry| entry.file.clone())
             .ok_or(FsError::BadFileDescriptor)
     }
 
     /// 关闭文件描述符
     pub fn close(&self, fd: usize) -> Result<(), FsError> {
-        let mut files = self.files.lock();
-        let mut fd_flags = self.fd_flags.lock();
-
-        if fd >= files.len() || files[fd].is_none() {
-            return Err(FsError::BadFileDescriptor);
-        }
-
-        files[fd] = None;
-        fd_flags[fd] = FdFlags::empty();
-        Ok(())
+        let old = self.slots.write().remove(fd);
+        old.map(drop).ok_or(FsError::BadFileDescriptor)
     }
 
     /// 复制文件描述符
     ///
     /// 返回新的 fd，与 old_fd 指向同一个 `Arc<dyn File>` (共享 offset)。
+    /// 新 fd 不继承 FD_CLOEXEC。
     pub fn dup(&self, old_fd: usize) -> Result<usize, FsError> {
-        let file = self.get(old_fd)?;
-        self.alloc(file)
+        self.dup_from(old_fd, 0, FdFlags::empty())
     }
 
     /// 复制文件描述符，新 fd >= min_fd（F_DUPFD 语义）
@@ -323,84 +419,54 @@ impl FDTable {
     /// 返回新的 fd，与 old_fd 指向同一个 `Arc<dyn File>` (共享 offset)。
     /// 新分配的 fd 是 >= min_fd 的最小未使用文件描述符。
     pub fn dup_from(&self, old_fd: usize, min_fd: usize, flags: FdFlags) -> Result<usize, FsError> {
-        let max_fds = self.max_fds.load(Ordering::Relaxed);
-        if min_fd >= max_fds {
+        if min_fd >= self.max_fds.load(Ordering::Relaxed) {
             return Err(FsError::InvalidArgument);
         }
-
         let file = self.get(old_fd)?;
-        let mut files = self.files.lock();
-        let mut fd_flags = self.fd_flags.lock();
-
-        // 1. 先确保数组至少有 min_fd 个元素
-        while files.len() < min_fd {
-            files.push(None);
-            fd_flags.push(FdFlags::empty());
-        }
-
-        // 2. 从 min_fd 开始查找最小可用 FD
-        let search_end = core::cmp::min(files.len(), max_fds);
-        for fd in min_fd..search_end {
-            if files[fd].is_none() {
-                files[fd] = Some(file);
-                fd_flags[fd] = flags;
-                return Ok(fd);
-            }
-        }
-
-        // 3. 如果没有空闲槽位，在数组末尾分配新的 fd
-        let fd = core::cmp::max(files.len(), min_fd);
-        if fd >= max_fds {
-            return Err(FsError::TooManyOpenFiles);
-        }
-
-        while files.len() <= fd {
-            files.push(None);
-            fd_flags.push(FdFlags::empty());
-        }
-
-        files[fd] = Some(file);
-        fd_flags[fd] = flags;
-        Ok(fd)
+        self.alloc_from(min_fd, FdEntry { file, flags })
     }
 
     /// 复制文件描述符到指定位置
     ///
-    /// 如果 new_fd 已打开，先关闭它。
+    /// 如果 new_fd 已打开，先关闭它。old_fd == new_fd 时只检查 old_fd 是否有效。
     pub fn dup2(&self, old_fd: usize, new_fd: usize) -> Result<usize, FsError> {
-        // 特殊情况：如果两个 FD 相同，直接返回
         if old_fd == new_fd {
-            // 检查 old_fd 是否有效
             self.get(old_fd)?;
             return Ok(new_fd);
         }
-
-        // 调用 dup3，不设置任何标志
         self.dup3(old_fd, new_fd, OpenFlags::empty())
     }
 
     /// 复制文件描述符到指定位置（dup3 语义）
     ///
-    /// 如果 new_fd 已打开，先关闭它。
-    /// 与 dup2 不同，dup3 不允许 old_fd == new_fd。
+    /// 如果 new_fd 已打开，先关闭它；取文件、关闭和安装在同一次写锁内完成，
+    /// 其他线程不会看到 new_fd 暂时空闲。
+    ///
+    /// # 错误
+    /// - `old_fd == new_fd`：`EINVAL`（dup2 在此情况下直接返回）
+    /// - `old_fd` 未打开或 `new_fd` 超出上限：`EBADF`
     ///
     /// # 参数
     /// - `flags`: 可以包含 `O_CLOEXEC`，用于设置新 FD 的 CLOEXEC 标志
     pub fn dup3(&self, old_fd: usize, new_fd: usize, flags: OpenFlags) -> Result<usize, FsError> {
-        // dup3 不允许 old_fd == new_fd
         if old_fd == new_fd {
             return Err(FsError::InvalidArgument);
         }
+        if new_fd >= self.max_fds.load(Ordering::Relaxed) {
+            return Err(FsError::BadFileDescriptor);
+        }
 
-        let file = self.get(old_fd)?;
-
-        // 如果 new_fd 已打开，先关闭它（忽略错误）
-        let _ = self.close(new_fd);
-
-        // 提取 FD 标志
-        let fd_flags = FdFlags::from_open_flags(flags);
-
-        self.install_at_with_flags(new_fd, file, fd_flags)?;
+        let old = {
+            let mut slots = self.slots.write();
+            let file = slots
+                .get(old_fd)
+                .ok_or(FsError::BadFileDescriptor)?
+                .file
+                .clone();
+            let flags = FdFlags::from_open_flags(flags);
+            slots.install(new_fd, FdEntry { file, flags })?
+        };
+        drop(old);
         Ok(new_fd)
     }
 
@@ -409,50 +475,51 @@ impl FDTable {
     /// 所有 `Arc<dyn File>` 引用计数递增，父子进程共享文件对象。
     /// FD 标志也会被复制。
     pub fn clone_table(&self) -> Self {
-        let files = self.files.lock().clone();
-        let fd_flags = self.fd_flags.lock().clone();
+        let slots = self.slots.read().clone();
+        NR_OPEN_FDS.fetch_add(slots.count(), Ordering::Relaxed);
         Self {
-            files: SpinLock::new(files),
-            fd_flags: SpinLock::new(fd_flags),
+            slots: RwLock::new(slots),
             max_fds: AtomicUsize::new(self.max_fds.load(Ordering::Relaxed)),
         }
     }
 
     /// 关闭所有带有 CLOEXEC 标志的文件（用于 exec）
     pub fn close_exec(&self) {
-        let mut files = self.files.lock();
-        let mut fd_flags = self.fd_flags.lock();
-
-        for (slot, flags) in files.iter_mut().zip(fd_flags.iter_mut()) {
-            if flags.contains(FdFlags::CLOEXEC) {
-                *slot = None;
-                *flags = FdFlags::empty();
-            }
-        }
+        let closed: Vec<FdEntry> = {
+            let mut slots = self.slots.write();
+            let fds: Vec<usize> = slots
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, slot)| {
+                    slot.as_ref()
+                        .is_some_and(|entry| entry.flags.contains(FdFlags::CLOEXEC))
+                })
+                .map(|(fd, _)| fd)
+                .collect();
+            fds.into_iter().filter_map(|fd| slots.remove(fd)).collect()
+        };
+        drop(closed);
     }
 
     /// 获取文件描述符标志 (F_GETFD)
     pub fn get_fd_flags(&self, fd: usize) -> Result<FdFlags, FsError> {
-        let files = self.files.lock();
-        let fd_flags = self.fd_flags.lock();
-
-        if fd >= files.len() || files[fd].is_none() {
-            return Err(FsError::BadFileDescriptor);
-        }
-
-        Ok(fd_flags[fd])
+        self.slots
+            .read()
+            .get(fd)
+            .map(|entry| entry.flags)
+            .ok_or(FsError::BadFileDescriptor)
     }
 
     /// 设置文件描述符标志 (F_SETFD)
     pub fn set_fd_flags(&self, fd: usize, flags: FdFlags) -> Result<(), FsError> {
-        let files = self.files.lock();
-        let mut fd_flags = self.fd_flags.lock();
-
-        if fd >= files.len() || files[fd].is_none() {
-            return Err(FsError::BadFileDescriptor);
-        }
-
-        fd_flags[fd] = flags;
+        let mut slots = self.slots.write();
+        let entry = slots
+            .entries
+            .get_mut(fd)
+            .and_then(Option::as_mut)
+            .ok_or(FsError::BadFileDescriptor)?;
+        entry.flags = flags;
         Ok(())
     }
 }
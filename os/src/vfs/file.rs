@@ -82,8 +82,9 @@
 //! println!("文件大小: {}", metadata.size);
 //! ```
 
+use crate::mm::memory_space::mapping_area::SharedPages;
 use crate::uapi::fcntl::{OpenFlags, SeekWhence};
-use crate::vfs::{Dentry, DirEntry, FsError, Inode, InodeMetadata};
+use crate::vfs::{Dentry, DirEntry, FsError, Inode, InodeMetadata, MmapDesc, MmapPolicy};
 use alloc::{sync::Arc, vec::Vec};
 
 /// 文件操作的统一接口
@@ -102,6 +103,21 @@ pub trait File: Send + Sync {
     /// 检查文件是否可写
     fn writable(&self) -> bool;
 
+    /// 检查当前能否不阻塞地读取（可选方法，用于 poll/select 和 io_uring）
+    ///
+    /// 管道、消息队列等在缓冲区为空时返回 `false`；默认与 `readable` 相同，
+    /// 适用于读操作从不阻塞的文件。
+    fn read_ready(&self) -> bool {
+        self.readable()
+    }
+
+    /// 检查当前能否不阻塞地写入（可选方法，用于 poll/select 和 io_uring）
+    ///
+    /// 默认与 `writable` 相同。
+    fn write_ready(&self) -> bool {
+        self.writable()
+    }
+
     /// 从文件读取数据
     ///
     /// RegFile 从当前 offset 读取并更新 offset；
@@ -193,6 +209,30 @@ pub trait File: Send + Sync {
         Err(FsError::NotSupported)
     }
 
+    /// 从当前偏移量按页读取，数据依次交给 `sink`（可选方法，`read` 的快速路径）
+    ///
+    /// 语义同 [`Inode::read_pages`]，成功时推进文件偏移量。
+    /// 默认返回 `NotSupported`，调用者回退到 `read`。
+    fn read_pages(
+        &self,
+        _len: usize,
+        _sink: &mut dyn FnMut(&[u8]) -> Result<(), FsError>,
+    ) -> Result<usize, FsError> {
+        Err(FsError::NotSupported)
+    }
+
+    /// 从指定位置按页读取，不改变文件偏移量（可选方法，`pread64` 的快速路径）
+    ///
+    /// 默认返回 `NotSupported`，调用者回退到 `read_at`。
+    fn read_pages_at(
+        &self,
+        _offset: usize,
+        _len: usize,
+        _sink: &mut dyn FnMut(&[u8]) -> Result<(), FsError>,
+    ) -> Result<usize, FsError> {
+        Err(FsError::NotSupported)
+    }
+
     /// 从指定位置读取数据（可选方法，用于 pread64/preadv）
     ///
     /// 不改变文件偏移量，默认返回 `NotSupported`，适用于非 seekable 文件
@@ -218,6 +258,23 @@ pub trait File: Send + Sync {
         Err(FsError::NotSupported)
     }
 
+    /// 获取可直接映射到用户地址空间的设备内存（可选方法，用于 mmap）
+    ///
+    /// 返回的物理页以共享方式映射，不经过按文件内容拷贝的路径。
+    /// 默认返回 `NotSupported`，仅由帧缓冲等设备文件实现
+    fn mmap_pages(&self) -> Result<Arc<dyn SharedPages>, FsError> {
+        Err(FsError::NotSupported)
+    }
+
+    /// 决定 `desc` 描述的区域如何映射（可选方法，用于 mmap）
+    ///
+    /// 默认直接映射 [`File::mmap_pages`] 返回的物理页；没有可映射的物理页时返回
+    /// `NotSupported`（mmap 返回 ENODEV）。普通文件由 [`RegFile`](crate::vfs::RegFile)
+    /// 转交给 [`Inode::mmap`]。
+    fn mmap(&self, desc: &MmapDesc) -> Result<MmapPolicy, FsError> {
+        MmapPolicy::direct(self.mmap_pages()?, desc)
+    }
+
     /// 获取 Any trait 引用，用于安全的类型转换
     fn as_any(&self) -> &dyn core::any::Any;
 
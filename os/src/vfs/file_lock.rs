@@ -6,6 +6,7 @@
 //! - 同一进程的锁可以合并/覆盖
 //! - 进程退出时自动释放所有锁
 
+use crate::kernel::{EXIT_PRIO_KERNEL, ExitNotifier};
 use crate::sync::SpinLock;
 use crate::uapi::fcntl::{Flock, LockType};
 use crate::vfs::FsError;
@@ -231,3 +232,13 @@ lazy_static::lazy_static! {
 pub fn file_lock_manager() -> &'static FileLockManager {
     &FILE_LOCK_MANAGER
 }
+
+/// 释放进程所有锁的退出回调，进程第一次加锁时注册到线程组 leader 上
+pub static FILE_LOCK_EXIT_NOTIFIER: ExitNotifier = ExitNotifier {
+    name: "file_lock",
+    priority: EXIT_PRIO_KERNEL,
+    call: |task| {
+        let pid = task.lock().pid as i32;
+        file_lock_manager().release_all_locks(pid);
+    },
+};
@@ -1,3 +1,4 @@
+use crate::uapi::fs::FileSystemType;
 use crate::vfs::{FsError, Inode};
 use alloc::sync::Arc;
 
@@ -35,6 +36,9 @@ pub trait FileSystem: Send + Sync {
 /// 文件系统统计信息
 #[derive(Debug, Clone)]
 pub struct StatFs {
+    /// 文件系统类型，statfs 以其魔数作为 `f_type`
+    pub fs_type: FileSystemType,
+
     /// 块大小（单位：字节）
     pub block_size: usize,
 
@@ -164,6 +164,7 @@ impl File for BlockDeviceFile {
             SeekWhence::Set => offset,
             SeekWhence::Cur => current + offset,
             SeekWhence::End => device_size as isize + offset,
+            SeekWhence::Data | SeekWhence::Hole => return Err(FsError::InvalidArgument),
         };
 
         if new_offset < 0 {
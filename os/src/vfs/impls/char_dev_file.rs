@@ -1,9 +1,15 @@
 use crate::device::Driver;
+use crate::device::gpu::{FRAME_BUFFER, FrameBuffer};
+use crate::kernel::{Capabilities, current_task};
+use crate::mm::memory_space::mapping_area::{PhysPages, SharedPages};
 use crate::sync::SpinLock;
+use crate::uapi::input::InputEvent;
 use crate::uapi::ioctl::Termios;
 use crate::vfs::dev::{major, minor};
 use crate::vfs::devno::{chrdev_major, get_chrdev_driver, misc_minor};
-use crate::vfs::{Dentry, File, FsError, Inode, InodeMetadata, OpenFlags, SeekWhence};
+use crate::vfs::{
+    Dentry, File, FsError, Inode, InodeMetadata, MmapDesc, MmapPolicy, OpenFlags, SeekWhence,
+};
 use alloc::sync::Arc;
 
 impl CharDeviceFile {
@@ -89,10 +95,22 @@ impl CharDeviceFile {
         let maj = major(dev);
         let is_builtin_misc =
             maj == chrdev_major::MISC && minor(dev) == misc_minor::CPU_DMA_LATENCY;
-        if driver.is_none() && maj != chrdev_major::MEM && !is_builtin_misc {
+        let is_fb = maj == chrdev_major::FB && FRAME_BUFFER.read().is_some();
+        if driver.is_none() && maj != chrdev_major::MEM && !is_builtin_misc && !is_fb {
             // 既不是内存设备，也找不到驱动
             return Err(FsError::NoDevice);
         }
+        // /dev/mem 可以访问任意设备寄存器，需要 CAP_SYS_RAWIO
+        if maj == chrdev_major::MEM
+            && minor(dev) == 1
+            && !current_task()
+                .lock()
+                .credential
+                .capabilities
+                .has(Capabilities::SYS_RAWIO)
+        {
+            return Err(FsError::NotPermitted);
+        }
 
         Ok(Self {
             dentry,
@@ -124,6 +142,10 @@ impl CharDeviceFile {
                 buf.fill(0);
                 Ok(buf.len())
             }
+            1 => {
+                // /dev/mem: 只支持 mmap
+                Err(FsError::NotSupported)
+            }
             8 | 9 => {
                 // /dev/random, /dev/urandom: 简单实现（使用时间戳）
                 let mut seed = crate::arch::get_ticks() as u32;
@@ -138,6 +160,68 @@ impl CharDeviceFile {
         }
     }
 
+    /// 处理输入设备的读操作
+    ///
+    /// 只返回完整的 `input_event`；缓冲区放不下一个事件时返回 EINVAL，
+    /// 没有事件时返回 EAGAIN，由 syscall 层决定是否阻塞重试。
+    fn input_read(&self, buf: &mut [u8]) -> Result<usize, FsError> {
+        let input = self
+            .driver
+            .as_ref()
+            .and_then(|d| d.as_input())
+            .ok_or(FsError::NoDevice)?;
+        let ev_size = core::mem::size_of::<InputEvent>();
+        if buf.len() < ev_size {
+            return Err(FsError::InvalidArgument);
+        }
+
+        let mut count = 0usize;
+        while count + ev_size <= buf.len() {
+            let Some(event) = input.pop_event() else {
+                break;
+            };
+            // SAFETY: InputEvent 是 repr(C) 的 POD 结构，按字节读取其表示是合法的
+            let bytes = unsafe {
+                core::slice::from_raw_parts(&event as *const InputEvent as *const u8, ev_size)
+            };
+            buf[count..count + ev_size].copy_from_slice(bytes);
+            count += ev_size;
+        }
+
+        if count == 0 {
+            return Err(FsError::WouldBlock);
+        }
+        Ok(count)
+    }
+
+    /// 帧缓冲设备对应的全局帧缓冲
+    fn frame_buffer(&self) -> Result<Arc<FrameBuffer>, FsError> {
+        FRAME_BUFFER.read().clone().ok_or(FsError::NoDevice)
+    }
+
+    /// 处理帧缓冲设备的读操作：按文件偏移读取显存
+    fn fb_read(&self, buf: &mut [u8]) -> Result<usize, FsError> {
+        let fb = self.frame_buffer()?;
+        let mut offset = self.offset.lock();
+        let n = fb.read_at(*offset, buf);
+        *offset += n;
+        Ok(n)
+    }
+
+    /// 处理帧缓冲设备的写操作：按文件偏移写入显存并刷新显示
+    fn fb_write(&self, buf: &[u8]) -> Result<usize, FsError> {
+        let fb = self.frame_buffer()?;
+        let mut offset = self.offset.lock();
+        let n = fb.write_at(*offset, buf);
+        if n == 0 && !buf.is_empty() {
+            return Err(FsError::NoSpace);
+        }
+        *offset += n;
+        drop(offset);
+        fb.flush();
+        Ok(n)
+    }
+
     /// 处理内存设备的写操作
     fn mem_device_write(&self, buf: &[u8]) -> Result<usize, FsError> {
         let min = minor(self.dev);
@@ -146,6 +230,7 @@ impl CharDeviceFile {
                 // /dev/null, /dev/zero: 丢弃所有数据
                 Ok(buf.len())
             }
+            1 => Err(FsError::NotSupported),
             _ => Err(FsError::NoDevice),
         }
     }
@@ -160,6 +245,25 @@ impl File for CharDeviceFile {
         self.flags.writable()
     }
 
+    /// 检查是否有可读数据（用于 poll/select）
+    ///
+    /// 输入设备只有在事件环非空时才可读，串口在接收环非空时可读，
+    /// 其余设备保持原有的“总是可读”语义。
+    fn read_ready(&self) -> bool {
+        if let Some(serial) = self.driver.as_ref().and_then(|d| d.as_serial()) {
+            return self.readable() && serial.rx_ready();
+        }
+        if major(self.dev) == chrdev_major::INPUT {
+            return self.readable()
+                && self
+                    .driver
+                    .as_ref()
+                    .and_then(|d| d.as_input())
+                    .is_some_and(|input| input.has_events());
+        }
+        self.readable()
+    }
+
     fn read(&self, buf: &mut [u8]) -> Result<usize, FsError> {
         if !self.readable() {
             return Err(FsError::PermissionDenied);
@@ -174,6 +278,12 @@ impl File for CharDeviceFile {
         if maj == chrdev_major::MISC && minor(self.dev) == misc_minor::CPU_DMA_LATENCY {
             return Ok(0);
         }
+        if maj == chrdev_major::INPUT {
+            return self.input_read(buf);
+        }
+        if maj == chrdev_major::FB {
+            return self.fb_read(buf);
+        }
 
         // 其他设备：委托给驱动
         if let Some(ref driver) = self.driver {
@@ -222,6 +332,8 @@ impl File for CharDeviceFile {
                         let b = match serial.try_read() {
                             Some(bb) => bb,
                             None => {
+                                // 睡眠到接收中断送来数据；无法睡眠时退回轮询
+                                serial.wait_readable();
                                 core::hint::spin_loop();
                                 continue;
                             }
@@ -261,6 +373,9 @@ impl File for CharDeviceFile {
         if maj == chrdev_major::MISC && minor(self.dev) == misc_minor::CPU_DMA_LATENCY {
             return Ok(buf.len());
         }
+        if maj == chrdev_major::FB {
+            return self.fb_write(buf);
+        }
 
         // 其他设备：委托给驱动
         if let Some(ref driver) = self.driver {
@@ -293,10 +408,26 @@ impl File for CharDeviceFile {
         self.inode.metadata()
     }
 
-    fn lseek(&self, _offset: isize, _whence: SeekWhence) -> Result<usize, FsError> {
+    fn lseek(&self, offset: isize, whence: SeekWhence) -> Result<usize, FsError> {
         // 大多数字符设备不支持 seek
-        // 但某些设备（如 /dev/mem）可能需要
-        Err(FsError::NotSeekable)
+        // 但某些设备（如帧缓冲、/dev/mem）需要
+        if major(self.dev) != chrdev_major::FB {
+            return Err(FsError::NotSeekable);
+        }
+
+        let size = self.frame_buffer()?.size();
+        let mut offset_guard = self.offset.lock();
+        let new_offset = match whence {
+            SeekWhence::Set => offset,
+            SeekWhence::Cur => *offset_guard as isize + offset,
+            SeekWhence::End => size as isize + offset,
+            SeekWhence::Data | SeekWhence::Hole => return Err(FsError::InvalidArgument),
+        };
+        if new_offset < 0 {
+            return Err(FsError::InvalidArgument);
+        }
+        *offset_guard = new_offset as usize;
+        Ok(new_offset as usize)
     }
 
     fn offset(&self) -> usize {
@@ -328,9 +459,41 @@ impl File for CharDeviceFile {
                 // MISC 设备 ioctl (包括 RTC)
                 self.misc_ioctl(request, arg)
             }
+            chrdev_major::INPUT => {
+                // evdev ioctl
+                self.input_ioctl(request, arg)
+            }
+            chrdev_major::FB => {
+                // fbdev ioctl
+                self.fb_ioctl(request, arg)
+            }
             _ => Err(FsError::NotTty),
         }
     }
+
+    fn mmap_pages(&self) -> Result<Arc<dyn SharedPages>, FsError> {
+        match major(self.dev) {
+            chrdev_major::FB => Ok(self.frame_buffer()? as Arc<dyn SharedPages>),
+            // /dev/mem：文件偏移即物理地址
+            chrdev_major::MEM if minor(self.dev) == 1 => {
+                Ok(PhysPages::whole() as Arc<dyn SharedPages>)
+            }
+            _ => self
+                .driver
+                .as_ref()
+                .and_then(|d| d.mmap_region())
+                .ok_or(FsError::NotSupported),
+        }
+    }
+
+    fn mmap(&self, desc: &MmapDesc) -> Result<MmapPolicy, FsError> {
+        // /dev/zero：私有映射为清零的私有页，共享映射为共享匿名内存
+        if major(self.dev) == chrdev_major::MEM && minor(self.dev) == 5 {
+            return Ok(MmapPolicy::Anonymous);
+        }
+        MmapPolicy::direct(self.mmap_pages()?, desc)
+    }
+
     fn as_any(&self) -> &dyn core::any::Any {
         self
     }
@@ -459,4 +622,104 @@ impl CharDeviceFile {
             Err(FsError::NotTty)
         }
     }
+
+    /// 输入设备（evdev）ioctl 处理
+    fn input_ioctl(&self, request: u32, arg: usize) -> Result<isize, FsError> {
+        use crate::uapi::errno::EINVAL;
+        use crate::uapi::input::{EV_MAX, EV_VERSION};
+        use crate::uapi::ioctl::*;
+        use crate::util::user_buffer::{UserBuffer, write_to_user};
+        use alloc::vec::Vec;
+
+        let input = self
+            .driver
+            .as_ref()
+            .and_then(|d| d.as_input())
+            .ok_or(FsError::NoDevice)?;
+        if arg == 0 {
+            return Ok(-EINVAL as isize);
+        }
+
+        match request {
+            EVIOCGVERSION => {
+                write_to_user(arg as *mut i32, EV_VERSION);
+                Ok(0)
+            }
+            EVIOCGID => {
+                write_to_user(arg as *mut crate::uapi::input::InputId, input.input_id());
+                Ok(0)
+            }
+            _ if _IOC_TYPE(request) == EVIOC_TYPE && _IOC_DIR(request) == IOC_READ => {
+                // EVIOCGNAME / EVIOCGBIT 的长度编码在请求码中
+                let nr = _IOC_NR(request);
+                let len = _IOC_SIZE(request) as usize;
+                let data: Vec<u8> = if nr == EVIOCGNAME_NR {
+                    let mut name = input.name().into_bytes();
+                    name.push(0);
+                    name.truncate(len);
+                    name
+                } else if (EVIOCGBIT_NR_BASE..=EVIOCGBIT_NR_BASE + EV_MAX as u32).contains(&nr) {
+                    let mut bits = input.event_bits((nr - EVIOCGBIT_NR_BASE) as u8);
+                    bits.resize(len, 0);
+                    bits
+                } else {
+                    return Err(FsError::NotTty);
+                };
+
+                // SAFETY: arg 是用户提供的长度为 len 的缓冲区，copy_to_user 内部做访问校验，
+                // 且写入长度不超过 data.len() <= len
+                unsafe { UserBuffer::new(arg as *mut u8, len).copy_to_user(&data) };
+                Ok(data.len() as isize)
+            }
+            _ => Err(FsError::NotTty),
+        }
+    }
+
+    /// 帧缓冲设备（fbdev）ioctl 处理
+    ///
+    /// 显示模式由宿主决定，FBIOPUT_VSCREENINFO 只接受与当前模式一致的参数；
+    /// 不支持多缓冲，FBIOPAN_DISPLAY 只接受零偏移并用于触发一次刷新。
+    fn fb_ioctl(&self, request: u32, arg: usize) -> Result<isize, FsError> {
+        use crate::uapi::errno::EINVAL;
+        use crate::uapi::fb::{FbFixScreeninfo, FbVarScreeninfo};
+        use crate::uapi::ioctl::*;
+        use crate::util::user_buffer::{read_from_user, write_to_user};
+
+        let fb = self.frame_buffer()?;
+        if arg == 0 {
+            return Ok(-EINVAL as isize);
+        }
+
+        match request {
+            FBIOGET_VSCREENINFO => {
+                write_to_user(arg as *mut FbVarScreeninfo, fb.var_screeninfo());
+                Ok(0)
+            }
+            FBIOGET_FSCREENINFO => {
+                write_to_user(arg as *mut FbFixScreeninfo, fb.fix_screeninfo());
+                Ok(0)
+            }
+            FBIOPUT_VSCREENINFO => {
+                let req = read_from_user(arg as *const FbVarScreeninfo);
+                let cur = fb.var_screeninfo();
+                if req.xres != cur.xres
+                    || req.yres != cur.yres
+                    || (req.bits_per_pixel != 0 && req.bits_per_pixel != cur.bits_per_pixel)
+                {
+                    return Ok(-EINVAL as isize);
+                }
+                write_to_user(arg as *mut FbVarScreeninfo, cur);
+                Ok(0)
+            }
+            FBIOPAN_DISPLAY => {
+                let req = read_from_user(arg as *const FbVarScreeninfo);
+                if req.xoffset != 0 || req.yoffset != 0 {
+                    return Ok(-EINVAL as isize);
+                }
+                fb.flush();
+                Ok(0)
+            }
+            _ => Err(FsError::NotTty),
+        }
+    }
 }
@@ -1,17 +1,48 @@
 //! 管道文件实现
 //!
 //! 管道是流式单向通信设备，读端和写端分别由两个 [`PipeFile`] 实例表示。
+//!
+//! 命名 FIFO（`mknod S_IFIFO`）按 inode 共享同一个环形缓冲区：
+//! - 阻塞打开时，只读端等待写端出现，只写端等待读端出现；`O_RDWR` 从不阻塞；
+//! - `O_RDONLY | O_NONBLOCK` 立即成功，`O_WRONLY | O_NONBLOCK` 在没有读端时返回 `ENXIO`；
+//! - 两端全部关闭后缓冲区随注册表项一起释放，残留数据被丢弃；
+//! - 打开期间注册表持有 inode，即使目录项已被删除，已打开的两端仍可继续通信。
 
+use crate::kernel::{SharedTask, WaitQueue};
 use crate::sync::SpinLock;
-use crate::vfs::{Dentry, File, FileMode, FsError, InodeMetadata, InodeType, OpenFlags, TimeSpec};
+use crate::vfs::{
+    Dentry, File, FileMode, FsError, Inode, InodeMetadata, InodeType, OpenFlags, TimeSpec,
+};
 use alloc::collections::{BTreeMap, VecDeque};
 use alloc::sync::Arc;
 
+/// 命名 FIFO 注册表项
+struct NamedFifo {
+    /// 两端共享的缓冲区
+    buffer: Arc<SpinLock<PipeRingBuffer>>,
+    /// 持有 inode，保证 FIFO 打开期间注册表键（inode 地址）不会被复用
+    _inode: Arc<dyn Inode>,
+}
+
 lazy_static::lazy_static! {
-    static ref NAMED_FIFO_REGISTRY: SpinLock<BTreeMap<usize, Arc<SpinLock<PipeRingBuffer>>>> =
+    /// inode 地址 -> 命名 FIFO
+    ///
+    /// 锁顺序：先注册表，后缓冲区。
+    static ref NAMED_FIFO_REGISTRY: SpinLock<BTreeMap<usize, NamedFifo>> =
         SpinLock::new(BTreeMap::new());
 }
 
+/// 命名 FIFO 的注册表键
+fn fifo_key(inode: &Arc<dyn Inode>) -> usize {
+    Arc::as_ptr(inode) as *const () as usize
+}
+
+/// 当前仍有端点打开的命名 FIFO 数量
+#[cfg(test)]
+pub(crate) fn named_fifo_count() -> usize {
+    NAMED_FIFO_REGISTRY.lock().len()
+}
+
 /// 管道环形缓冲区
 ///
 /// 容量默认 4KB（POSIX 最小 512 字节）。
@@ -28,6 +59,12 @@ struct PipeRingBuffer {
     ever_had_writer: bool,
     /// 是否曾经打开过读端。命名 FIFO 需要区分“尚无读端”和“读端已关闭”。
     ever_had_reader: bool,
+    /// 读端累计打开次数，阻塞的写端据此判断期间是否来过读端
+    reader_opens: usize,
+    /// 写端累计打开次数，阻塞的读端据此判断期间是否来过写端
+    writer_opens: usize,
+    /// 阻塞打开、等待对端出现的任务，任一端打开时全部唤醒
+    open_waiters: WaitQueue,
 }
 
 impl PipeRingBuffer {
@@ -43,6 +80,17 @@ impl PipeRingBuffer {
             read_end_count: 0,
             ever_had_writer: false,
             ever_had_reader: false,
+            reader_opens: 0,
+            writer_opens: 0,
+            open_waiters: WaitQueue::new(),
+        }
+    }
+
+    /// 阻塞打开等待的对端是否已经出现
+    fn partner_arrived(&self, partner: FifoPartner) -> bool {
+        match partner {
+            FifoPartner::Writer(opens) => self.write_end_count > 0 || self.writer_opens != opens,
+            FifoPartner::Reader(opens) => self.read_end_count > 0 || self.reader_opens != opens,
         }
     }
 
@@ -144,6 +192,17 @@ pub struct PipeFile {
     flags: SpinLock<OpenFlags>,
     /// 异步 I/O 所有者 PID
     owner: SpinLock<Option<i32>>,
+    /// 命名 FIFO 的注册表键；匿名管道为 `None`
+    fifo_key: Option<usize>,
+}
+
+/// 阻塞打开命名 FIFO 时需要等待的对端
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FifoPartner {
+    /// 等待写端：记录开始等待时的写端打开次数
+    Writer(usize),
+    /// 等待读端：记录开始等待时的读端打开次数
+    Reader(usize),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -198,6 +257,7 @@ impl PipeFile {
             end_type: PipeEnd::Read,
             flags: SpinLock::new(OpenFlags::empty()),
             owner: SpinLock::new(None),
+            fifo_key: None,
         };
 
         let write_end = Self {
@@ -205,57 +265,124 @@ impl PipeFile {
             end_type: PipeEnd::Write,
             flags: SpinLock::new(OpenFlags::empty()),
             owner: SpinLock::new(None),
+            fifo_key: None,
         };
 
         (read_end, write_end)
     }
 
     /// 以命名 FIFO 的语义打开一个目录项。
+    ///
+    /// 阻塞打开时睡眠在 FIFO 的等待队列上直到对端出现；等待期间收到信号返回
+    /// [`FsError::Interrupted`]。
     pub fn open_fifo(dentry: Arc<Dentry>, flags: OpenFlags) -> Result<Self, FsError> {
+        let (file, partner) = Self::attach_fifo(&dentry, flags)?;
+        if let Some(partner) = partner {
+            let task = crate::kernel::current_task();
+            while !file.wait_fifo_partner(&task, partner) {
+                if crate::ipc::signal_interrupts_syscall(&task) {
+                    // file 在此被释放，撤销本端的引用计数
+                    return Err(FsError::Interrupted);
+                }
+            }
+        }
+        Ok(file)
+    }
+
+    /// 对端已出现时返回 `true`；否则睡眠一次后返回 `false`
+    ///
+    /// 检查与入队睡眠都在缓冲区锁内完成，对端在两者之间打开也不会错过唤醒。
+    fn wait_fifo_partner(&self, task: &SharedTask, partner: FifoPartner) -> bool {
+        {
+            let mut buf = self.buffer.lock();
+            if buf.partner_arrived(partner) {
+                return true;
+            }
+            buf.open_waiters.add_task(task.clone());
+            let slept = crate::kernel::sleep_task_prepare(task.clone(), true, |t| {
+                crate::ipc::sig_pending(t)
+            });
+            if !slept {
+                buf.open_waiters.remove_task(task);
+                return false;
+            }
+        }
+        crate::kernel::yield_task();
+        // 被信号唤醒时仍在队列中
+        self.buffer.lock().open_waiters.remove_task(task);
+        false
+    }
+
+    /// 登记命名 FIFO 的一个端点，不等待对端
+    ///
+    /// 返回打开的端点，以及阻塞打开时需要等待的对端（非阻塞或 `O_RDWR` 时为 `None`）。
+    pub(crate) fn attach_fifo(
+        dentry: &Arc<Dentry>,
+        flags: OpenFlags,
+    ) -> Result<(Self, Option<FifoPartner>), FsError> {
         let readable = flags.readable();
         let writable = flags.writable();
 
         if !readable && !writable {
             return Err(FsError::InvalidArgument);
         }
+        let nonblock = flags.contains(OpenFlags::O_NONBLOCK);
 
-        let key = Arc::as_ptr(&dentry) as usize;
-        let buffer = {
+        let key = fifo_key(&dentry.inode);
+        let (buffer, partner) = {
             let mut registry = NAMED_FIFO_REGISTRY.lock();
-            registry
-                .entry(key)
-                .or_insert_with(|| Arc::new(SpinLock::new(PipeRingBuffer::new())))
-                .clone()
-        };
-
-        if writable
-            && !readable
-            && flags.contains(OpenFlags::O_NONBLOCK)
-            && buffer.lock().read_end_count == 0
-        {
-            return Err(FsError::NoSuchDeviceOrAddress);
-        }
+            if writable && !readable && nonblock {
+                let has_reader = registry
+                    .get(&key)
+                    .is_some_and(|fifo| fifo.buffer.lock().read_end_count > 0);
+                if !has_reader {
+                    return Err(FsError::NoSuchDeviceOrAddress);
+                }
+            }
 
-        {
-            let mut buf = buffer.lock();
+            let fifo = registry.entry(key).or_insert_with(|| NamedFifo {
+                buffer: Arc::new(SpinLock::new(PipeRingBuffer::new())),
+                _inode: dentry.inode.clone(),
+            });
+            let mut buf = fifo.buffer.lock();
             if readable {
                 buf.read_end_count += 1;
+                buf.reader_opens = buf.reader_opens.wrapping_add(1);
                 buf.ever_had_reader = true;
             }
             if writable {
                 buf.write_end_count += 1;
+                buf.writer_opens = buf.writer_opens.wrapping_add(1);
                 buf.ever_had_writer = true;
             }
-        }
+            buf.open_waiters.wake_up_all();
+            let partner = match (readable, writable) {
+                (true, false) if !nonblock => Some(FifoPartner::Writer(buf.writer_opens)),
+                (false, true) => Some(FifoPartner::Reader(buf.reader_opens)),
+                _ => None,
+            };
+            drop(buf);
+            (fifo.buffer.clone(), partner)
+        };
 
         crate::kernel::syscall::io::wake_poll_waiters();
 
-        Ok(Self {
+        let file = Self {
             buffer,
             end_type: PipeEnd::from_flags(readable, writable),
             flags: SpinLock::new(flags),
             owner: SpinLock::new(None),
-        })
+            fifo_key: Some(key),
+        };
+        let partner = partner.filter(|&p| !file.fifo_partner_arrived(p));
+        Ok((file, partner))
+    }
+
+    /// 阻塞打开等待的对端是否已经出现
+    ///
+    /// 对端打开后又立即关闭也算出现过（与 Linux 相同，按打开次数判断）。
+    pub(crate) fn fifo_partner_arrived(&self, partner: FifoPartner) -> bool {
+        self.buffer.lock().partner_arrived(partner)
     }
 
     /// 设置文件状态标志 (F_SETFL)
@@ -276,14 +403,6 @@ impl PipeFile {
         buffer.set_capacity(new_size)?;
         Ok(buffer.get_capacity())
     }
-
-    pub fn read_ready(&self) -> bool {
-        self.end_type.readable() && self.buffer.lock().can_read_now()
-    }
-
-    pub fn write_ready(&self) -> bool {
-        self.end_type.writable() && self.buffer.lock().can_write_now()
-    }
 }
 
 impl File for PipeFile {
@@ -295,6 +414,14 @@ impl File for PipeFile {
         self.end_type.writable()
     }
 
+    fn read_ready(&self) -> bool {
+        self.end_type.readable() && self.buffer.lock().can_read_now()
+    }
+
+    fn write_ready(&self) -> bool {
+        self.end_type.writable() && self.buffer.lock().can_write_now()
+    }
+
     fn read(&self, buf: &mut [u8]) -> Result<usize, FsError> {
         if !self.end_type.readable() {
             return Err(FsError::InvalidArgument);
@@ -360,6 +487,9 @@ impl File for PipeFile {
 
 impl Drop for PipeFile {
     fn drop(&mut self) {
+        // 命名 FIFO 需要在注册表锁内递减计数，避免与并发的 open 交错
+        let mut registry = self.fifo_key.map(|_| NAMED_FIFO_REGISTRY.lock());
+
         // 减少引用计数
         let mut buf = self.buffer.lock();
         match self.end_type {
@@ -370,7 +500,17 @@ impl Drop for PipeFile {
                 buf.write_end_count -= 1;
             }
         }
+        let unused = buf.read_end_count == 0 && buf.write_end_count == 0;
         drop(buf);
+
+        // 两端都已关闭：释放注册表项，inode 在锁外释放
+        let removed = match (registry.as_mut(), self.fifo_key) {
+            (Some(registry), Some(key)) if unused => registry.remove(&key),
+            _ => None,
+        };
+        drop(registry);
+        drop(removed);
+
         crate::kernel::syscall::io::wake_poll_waiters();
     }
 }
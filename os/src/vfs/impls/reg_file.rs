@@ -1,8 +1,10 @@
 //! 普通文件（Regular File）的 File trait 实现
 
+use crate::mm::memory_space::mapping_area::SharedPages;
 use crate::sync::{Mutex, SpinLock};
 use crate::vfs::{
-    Dentry, DirEntry, File, FsError, Inode, InodeMetadata, InodeType, OpenFlags, SeekWhence,
+    Dentry, DirEntry, File, FsError, Inode, InodeMetadata, InodeType, MmapDesc, MmapPolicy,
+    MountFlags, OpenFlags, SeekWhence, mount_flags,
 };
 use alloc::{sync::Arc, vec::Vec};
 
@@ -34,12 +36,24 @@ pub struct RegFile {
 
     /// Per-open directory snapshot used by getdents64 style iteration.
     dir_entries: SpinLock<Option<Arc<Vec<DirEntry>>>>,
+
+    /// 读取后是否更新 atime：打开时所在挂载点为 noatime（目录还包括 nodiratime）时为 false
+    update_atime: bool,
 }
 
 impl RegFile {
     /// 创建新的 RegFile 实例
     pub fn new(dentry: Arc<Dentry>, flags: OpenFlags) -> Self {
         let inode = dentry.inode.clone();
+        let is_dir = inode
+            .metadata()
+            .is_ok_and(|m| m.inode_type == InodeType::Directory);
+        let no_atime = if is_dir {
+            MountFlags::NO_ATIME | MountFlags::NO_DIRATIME
+        } else {
+            MountFlags::NO_ATIME
+        };
+        let update_atime = !mount_flags(&dentry).intersects(no_atime);
         Self {
             dentry,
             inode,
@@ -47,6 +61,14 @@ impl RegFile {
             flags: SpinLock::new(flags),
             owner: SpinLock::new(None),
             dir_entries: SpinLock::new(None),
+            update_atime,
+        }
+    }
+
+    /// 读取了 `nread` 字节内容（目录为列出了条目）：挂载点允许时更新 atime
+    fn accessed(&self, nread: usize) {
+        if self.update_atime && nread > 0 {
+            self.inode.touch_atime();
         }
     }
 
@@ -91,6 +113,7 @@ impl File for RegFile {
 
         // 调用 inode 的 read_at
         let nread = self.inode.read_at(current_offset, buf)?;
+        self.accessed(nread);
 
         // 更新偏移量
         *offset_guard = current_offset + nread;
@@ -98,6 +121,23 @@ impl File for RegFile {
         Ok(nread)
     }
 
+    fn read_pages(
+        &self,
+        len: usize,
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), FsError>,
+    ) -> Result<usize, FsError> {
+        if !self.readable() {
+            return Err(FsError::PermissionDenied);
+        }
+
+        let mut offset_guard = self.offset.lock();
+        let offset = *offset_guard;
+        let nread = self.inode.read_pages(offset, len, sink)?;
+        self.accessed(nread);
+        *offset_guard += nread;
+        Ok(nread)
+    }
+
     fn write(&self, buf: &[u8]) -> Result<usize, FsError> {
         // 检查权限
         if !self.writable() {
@@ -107,16 +147,14 @@ impl File for RegFile {
         // 获取写入偏移量
         let mut offset_guard = self.offset.lock();
         let flags = *self.flags.lock();
-        let write_offset = if flags.contains(OpenFlags::O_APPEND) {
-            // O_APPEND: 总是写到文件末尾
-            self.inode.metadata()?.size
+        let (write_offset, nwritten) = if flags.contains(OpenFlags::O_APPEND) {
+            // O_APPEND: 由 inode 在自己的锁内取得文件末尾并写入，与其它会话互斥
+            self.inode.append(buf)?
         } else {
-            *offset_guard
+            let offset = *offset_guard;
+            (offset, self.inode.write_at(offset, buf)?)
         };
 
-        // 调用 inode 的 write_at
-        let nwritten = self.inode.write_at(write_offset, buf)?;
-
         // 更新偏移量
         *offset_guard = write_offset + nwritten;
 
@@ -139,6 +177,11 @@ impl File for RegFile {
             SeekWhence::Set => offset,
             SeekWhence::Cur => current + offset,
             SeekWhence::End => file_size + offset,
+            SeekWhence::Data | SeekWhence::Hole => {
+                let found = seek_data_hole(self.inode.as_ref(), offset, whence)?;
+                *offset_guard = found;
+                return Ok(found);
+            }
         };
 
         // 检查偏移量合法性 (不能为负)
@@ -170,17 +213,17 @@ impl File for RegFile {
             return Err(FsError::NotDirectory);
         }
 
-        if let Some(entries) = self.dir_entries.lock().as_ref().cloned() {
-            return Ok(entries);
-        }
-
-        let entries = Arc::new(self.inode.readdir()?);
-        let mut guard = self.dir_entries.lock();
-        if guard.is_none() {
-            *guard = Some(entries);
-        }
-
-        Ok(guard.as_ref().unwrap().clone())
+        let cached = self.dir_entries.lock().as_ref().cloned();
+        let entries = match cached {
+            Some(entries) => entries,
+            None => {
+                let entries = Arc::new(self.inode.readdir()?);
+                let mut guard = self.dir_entries.lock();
+                guard.get_or_insert(entries).clone()
+            }
+        };
+        self.accessed(entries.len());
+        Ok(entries)
     }
 
     fn dentry(&self) -> Result<Arc<Dentry>, FsError> {
@@ -201,14 +244,63 @@ impl File for RegFile {
     }
 
     fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, FsError> {
-        self.inode.read_at(offset, buf)
+        let nread = self.inode.read_at(offset, buf)?;
+        self.accessed(nread);
+        Ok(nread)
+    }
+
+    fn read_pages_at(
+        &self,
+        offset: usize,
+        len: usize,
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), FsError>,
+    ) -> Result<usize, FsError> {
+        let nread = self.inode.read_pages(offset, len, sink)?;
+        self.accessed(nread);
+        Ok(nread)
     }
 
     fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, FsError> {
         self.inode.write_at(offset, buf)
     }
 
+    fn mmap_pages(&self) -> Result<Arc<dyn SharedPages>, FsError> {
+        self.inode.mmap_pages()
+    }
+
+    fn mmap(&self, desc: &MmapDesc) -> Result<MmapPolicy, FsError> {
+        self.inode.mmap(desc)
+    }
+
     fn as_any(&self) -> &dyn core::any::Any {
         self
     }
 }
+
+/// `SEEK_DATA`/`SEEK_HOLE`：按 [`Inode::next_data`] 查找数据段或空洞
+///
+/// 与 Linux 相同，`offset` 为负或不小于文件大小时返回 `ENXIO`；文件末尾视为一个空洞。
+fn seek_data_hole(inode: &dyn Inode, offset: isize, whence: SeekWhence) -> Result<usize, FsError> {
+    let size = inode.metadata()?.size;
+    if offset < 0 || offset as usize >= size {
+        return Err(FsError::NoSuchDeviceOrAddress);
+    }
+    let offset = offset as usize;
+
+    if whence == SeekWhence::Data {
+        return match inode.next_data(offset)? {
+            Some((start, _)) if start < size => Ok(start.max(offset)),
+            _ => Err(FsError::NoSuchDeviceOrAddress),
+        };
+    }
+
+    // 跨过所有与当前位置相接的数据段
+    let mut pos = offset;
+    while pos < size {
+        match inode.next_data(pos)? {
+            Some((start, end)) if start <= pos && end > pos => pos = end,
+            _ => break,
+        }
+    }
+    Ok(pos.min(size))
+}
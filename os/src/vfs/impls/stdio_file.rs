@@ -58,6 +58,7 @@ impl File for StdinFile {
         let log_once = READ_LOG_COUNT.fetch_add(1, Ordering::Relaxed) < 6;
 
         while count < buf.len() {
+            crate::console::wait_for_input();
             let ch_opt = console_getchar();
             let mut ch = match ch_opt {
                 Some(c) => c,
@@ -85,8 +85,11 @@
 
 use core::any::Any;
 
+use crate::mm::memory_space::mapping_area::SharedPages;
+use crate::mm::swap::SwapBacking;
 use crate::uapi::time::TimeSpec;
-use crate::vfs::{Dentry, FsError};
+use crate::uapi::xattr::XattrFlags;
+use crate::vfs::{Dentry, FsError, MmapDesc, MmapPolicy};
 use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::sync::Weak;
@@ -205,6 +208,32 @@ pub trait Inode: Send + Sync + Any {
     /// 向指定偏移量写入数据
     fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, FsError>;
 
+    /// 在文件末尾追加数据（`O_APPEND` 写入），返回写入位置和写入的字节数
+    ///
+    /// 文件大小属于 inode，所有打开同一文件的会话共享：读取大小与写入之间不能插入
+    /// 其它会话的写入或截断，否则并发追加会互相覆盖。默认实现不保证这一点，
+    /// 可被多个会话同时写入的文件系统应在自己的锁内完成两步。
+    fn append(&self, buf: &[u8]) -> Result<(usize, usize), FsError> {
+        let offset = self.metadata()?.size;
+        Ok((offset, self.write_at(offset, buf)?))
+    }
+
+    /// 按页把 `[offset, offset + len)` 的数据依次交给 `sink`（可选方法，读取快速路径）
+    ///
+    /// 数据直接取自页缓存，`sink` 可以把它一次拷贝到最终目的地（如用户缓冲区），
+    /// 省去 `read_at` 所需的中间缓冲区。遇到文件末尾提前结束，返回交给 `sink`
+    /// 的总字节数。`sink` 出错时立即停止：此前已有数据交给 `sink` 则返回已交出的
+    /// 字节数（与 read 遇到用户缓冲区错误时返回已读字节数一致），否则返回该错误。
+    /// 默认返回 `NotSupported`，调用者回退到 `read_at`。
+    fn read_pages(
+        &self,
+        _offset: usize,
+        _len: usize,
+        _sink: &mut dyn FnMut(&[u8]) -> Result<(), FsError>,
+    ) -> Result<usize, FsError> {
+        Err(FsError::NotSupported)
+    }
+
     /// 在目录中查找子项
     ///
     /// 返回子项的 Inode。仅对目录有效。
@@ -240,11 +269,78 @@ pub trait Inode: Send + Sync + Any {
     fn readdir(&self) -> Result<Vec<DirEntry>, FsError>;
 
     /// 截断文件到指定大小
+    ///
+    /// `O_TRUNC` 和 ftruncate 都经由这里修改大小，与 [`Inode::append`] 一样应与其它
+    /// 会话的写入互斥，完成后所有会话立即看到新的大小。
     fn truncate(&self, size: usize) -> Result<(), FsError>;
 
     /// 同步文件数据到存储设备
     fn sync(&self) -> Result<(), FsError>;
 
+    /// 查找 `offset` 处或之后的第一段数据（可选方法，用于 `SEEK_DATA`/`SEEK_HOLE`）
+    ///
+    /// 返回 `Some((start, end))` 表示 `[start, end)` 有数据且 `offset <= start`（或
+    /// `start <= offset < end`）；`None` 表示 `offset` 之后直到文件末尾都是空洞。
+    /// 相邻的数据段不必合并。空洞读出全为零，且不占用存储空间。
+    /// 默认把 `[0, size)` 整体视为数据，适用于不支持稀疏文件的文件系统。
+    fn next_data(&self, offset: usize) -> Result<Option<(usize, usize)>, FsError> {
+        let size = self.metadata()?.size;
+        Ok((offset < size).then_some((offset, size)))
+    }
+
+    /// 获取文件数据页，供 `MAP_SHARED` 直接映射（可选方法）
+    ///
+    /// 只有数据本身驻留在物理页中的文件系统（如 tmpfs）才能实现；
+    /// 默认返回 `NotSupported`。
+    fn mmap_pages(&self) -> Result<Arc<dyn SharedPages>, FsError> {
+        Err(FsError::NotSupported)
+    }
+
+    /// 决定对该文件的 mmap 如何映射（可选方法）
+    ///
+    /// 默认按文件内容拷贝到私有页，`MAP_SHARED` 时回写（页缓存路径）；
+    /// 实现了 [`Inode::mmap_pages`] 的文件系统可以选择直接映射文件页。
+    fn mmap(&self, _desc: &MmapDesc) -> Result<MmapPolicy, FsError> {
+        Ok(MmapPolicy::CopyIn)
+    }
+
+    /// 获取交换文件的存储后端，供 `swapon` 使用（可选方法）
+    ///
+    /// 默认返回 `NotSupported`，表示该文件系统上的文件不能作为交换文件。
+    fn swap_backing(&self) -> Result<Arc<dyn SwapBacking>, FsError> {
+        Err(FsError::NotSupported)
+    }
+
+    /// 读取扩展属性（可选方法）
+    ///
+    /// 名字的合法性和权限由调用者按 [`crate::vfs::xattr`] 的规则检查。
+    /// 属性不存在时返回 `NoData`；默认返回 `NotSupported`。
+    fn getxattr(&self, _name: &str) -> Result<Vec<u8>, FsError> {
+        Err(FsError::NotSupported)
+    }
+
+    /// 设置扩展属性（可选方法）
+    ///
+    /// `flags` 的语义见 [`XattrMap::set`](crate::vfs::xattr::XattrMap::set)，
+    /// 成功时实现同时更新 ctime。默认返回 `NotSupported`。
+    fn setxattr(&self, _name: &str, _value: &[u8], _flags: XattrFlags) -> Result<(), FsError> {
+        Err(FsError::NotSupported)
+    }
+
+    /// 列出所有扩展属性名（可选方法）
+    ///
+    /// 默认返回空列表，与 Linux 上不支持扩展属性的文件系统一致。
+    fn listxattr(&self) -> Result<Vec<String>, FsError> {
+        Ok(Vec::new())
+    }
+
+    /// 删除扩展属性（可选方法）
+    ///
+    /// 属性不存在时返回 `NoData`，成功时实现同时更新 ctime。默认返回 `NotSupported`。
+    fn removexattr(&self, _name: &str) -> Result<(), FsError> {
+        Err(FsError::NotSupported)
+    }
+
     /// 设置 Dentry（可选方法）
     fn set_dentry(&self, _dentry: Weak<Dentry>) {}
 
@@ -264,9 +360,18 @@ pub trait Inode: Send + Sync + Any {
     /// 向下转型为 &dyn Any，用于支持 downcast
     fn as_any(&self) -> &dyn Any;
 
-    /// 设置文件时间戳
+    /// 设置文件时间戳，`None` 表示不改变
+    ///
+    /// 实现同时把 ctime 更新为当前时间，见 [`crate::vfs::timestamps`]。
     fn set_times(&self, atime: Option<TimeSpec>, mtime: Option<TimeSpec>) -> Result<(), FsError>;
 
+    /// 内容被读取：按 atime 策略更新访问时间（可选方法）
+    ///
+    /// `read_at`/`read_pages`/`readdir` 本身不更新 atime，由 VFS 在读取文件内容或
+    /// 列目录后调用本方法，所在挂载点为 noatime（目录还包括 nodiratime）时不调用。
+    /// 默认什么都不做。
+    fn touch_atime(&self) {}
+
     /// 读取符号链接的目标路径
     fn readlink(&self) -> Result<String, FsError>;
 
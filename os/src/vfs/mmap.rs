@@ -0,0 +1,81 @@
+//! 文件映射策略
+//!
+//! mmap 把"映射什么"交给文件决定：系统调用层把请求的区域描述为 [`MmapDesc`]，
+//! 交给 [`File::mmap`](crate::vfs::File::mmap)，文件返回一种 [`MmapPolicy`]，
+//! 系统调用层再按策略建立映射区域：
+//! - 普通文件默认读入文件内容（`MAP_SHARED` 时在 msync/munmap 时回写），
+//!   数据驻留在物理页中的文件系统（tmpfs、memfd）由 [`Inode::mmap`](crate::vfs::Inode::mmap)
+//!   选择直接映射文件页；
+//! - 设备文件直接映射设备内存（帧缓冲、`/dev/mem`、驱动的 MMIO 区域），
+//!   `/dev/zero` 映射为匿名内存；
+//! - 其它文件（管道、socket 等）不支持映射。
+
+use alloc::sync::Arc;
+
+use crate::config::PAGE_SIZE;
+use crate::mm::memory_space::mapping_area::SharedPages;
+use crate::uapi::mm::{MapFlags, ProtFlags};
+use crate::vfs::FsError;
+
+/// 一次 mmap 请求的区域描述
+#[derive(Debug, Clone, Copy)]
+pub struct MmapDesc {
+    /// 文件偏移（页对齐）
+    pub offset: usize,
+    /// 映射长度（字节）
+    pub len: usize,
+    /// 访问权限
+    pub prot: ProtFlags,
+    /// 映射标志
+    pub flags: MapFlags,
+}
+
+impl MmapDesc {
+    /// 映射的第一页在文件中的页下标
+    pub fn first_page(&self) -> usize {
+        self.offset / PAGE_SIZE
+    }
+
+    /// 映射的页数
+    pub fn page_count(&self) -> usize {
+        self.len.div_ceil(PAGE_SIZE)
+    }
+
+    /// 是否为 `MAP_SHARED` 映射
+    pub fn is_shared(&self) -> bool {
+        self.flags.contains(MapFlags::SHARED)
+    }
+}
+
+/// 文件选择的映射方式
+#[derive(Debug)]
+pub enum MmapPolicy {
+    /// 直接映射共享物理页，区域起点对应第 `first_page` 页
+    Direct {
+        pages: Arc<dyn SharedPages>,
+        first_page: usize,
+    },
+    /// 匿名内存，与文件内容无关（`/dev/zero`）
+    Anonymous,
+    /// 按文件内容拷贝到私有页，`MAP_SHARED` 时回写
+    CopyIn,
+}
+
+impl MmapPolicy {
+    /// 直接映射 `pages` 中 `desc` 描述的范围
+    ///
+    /// # 返回
+    /// - `Err(FsError::InvalidArgument)`: 范围超出 `pages`
+    /// - `Err(FsError::NotPermitted)`: 设备内存中有不允许映射的页（如 `/dev/mem` 下的 RAM）
+    pub fn direct(pages: Arc<dyn SharedPages>, desc: &MmapDesc) -> Result<Self, FsError> {
+        let first_page = desc.first_page();
+        let end = first_page
+            .checked_add(desc.page_count())
+            .filter(|&end| end <= pages.page_count())
+            .ok_or(FsError::InvalidArgument)?;
+        if pages.is_device_memory() && (first_page..end).any(|idx| pages.ppn_at(idx).is_none()) {
+            return Err(FsError::NotPermitted);
+        }
+        Ok(Self::Direct { pages, first_page })
+    }
+}
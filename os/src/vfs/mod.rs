@@ -6,6 +6,7 @@
 //!
 //! - [`mod@file`] - 会话层接口定义 (File trait)
 //! - [`inode`] - 存储层接口定义 (Inode trait)
+//! - [`mmap`] - 文件映射策略（File::mmap / Inode::mmap）
 //! - [`dentry`] - 目录项结构和全局缓存
 //! - [`path`] - 路径解析引擎（绝对/相对路径、符号链接）
 //! - [`mount`] - 挂载表管理和挂载点栈
@@ -15,6 +16,8 @@
 //! - [`impls`] - 具体文件类型实现（RegFile、PipeFile 等）
 //! - [`error`] - VFS 错误类型定义
 //! - [`dev`]/[`devno`] - 设备号管理和驱动注册
+//! - [`timestamps`] - atime/mtime/ctime 更新规则
+//! - [`xattr`] - 扩展属性的命名空间、权限和内存存储
 //!
 //! # 设计概览
 //!
@@ -67,7 +70,7 @@
 //!
 //! ## 并发安全
 //!
-//! - **FDTable**：内部使用 `SpinLock` 保护文件描述符数组
+//! - **FDTable**：内部使用读写锁保护文件描述符数组，查找只取读锁
 //! - **DentryCache**：使用 `SpinLock` 保护全局缓存
 //! - **MountTable**：使用 `SpinLock` 保护挂载表
 //! - **FileLockManager**：使用 `SpinLock` 保护文件锁表
@@ -159,20 +162,28 @@ pub mod file_lock;
 pub mod file_system;
 pub mod impls;
 pub mod inode;
+pub mod mmap;
 pub mod mount;
 pub mod page_cache;
 pub mod path;
+pub mod perm;
+pub mod timestamps;
+pub mod xattr;
 
-pub use adapter::inode_type_to_d_type;
+pub use adapter::encode_dirents;
 pub use dentry::{DENTRY_CACHE, Dentry};
 pub use error::FsError;
-pub use fd_table::FDTable;
+pub use fd_table::{FDTable, nr_open_files};
 pub use file::File;
-pub use file_lock::file_lock_manager;
+pub use file_lock::{FILE_LOCK_EXIT_NOTIFIER, file_lock_manager};
 pub use file_system::{FileSystem, StatFs};
 pub use impls::{PipeFile, RegFile, create_stdio_files};
 pub use inode::{DirEntry, FileMode, Inode, InodeMetadata, InodeType};
-pub use mount::{MOUNT_TABLE, MountFlags, get_root_dentry};
+pub use mmap::{MmapDesc, MmapPolicy};
+pub use mount::{
+    MOUNT_TABLE, MountFlags, check_mount_writable, get_root_dentry, mount_flags,
+    parse_mount_options,
+};
 pub use path::{
     normalize_path, split_path, vfs_lookup, vfs_lookup_from, vfs_lookup_no_follow,
     vfs_lookup_no_follow_from,
@@ -180,7 +191,7 @@ pub use path::{
 
 // Re-export UAPI types used by VFS
 pub use crate::uapi::fcntl::{FdFlags, OpenFlags, SeekWhence};
-pub use crate::uapi::fs::{LinuxDirent64, Stat, Statx};
+pub use crate::uapi::fs::{Stat, Statx};
 pub use crate::uapi::time::TimeSpec;
 
 use alloc::{vec, vec::Vec};
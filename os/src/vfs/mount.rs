@@ -73,6 +73,8 @@
 //!         const NO_SUID    = 1 << 2;  // 忽略 SUID/SGID
 //!         const SYNC       = 1 << 3;  // 同步写入
 //!         const NO_DEV     = 1 << 4;  // 禁止设备文件
+//!         const NO_ATIME   = 1 << 5;  // 不更新访问时间
+//!         const NO_DIRATIME = 1 << 6; // 不更新目录访问时间
 //!     }
 //! }
 //! ```
@@ -84,6 +86,20 @@
 //! - **NO_SUID**: 防止特权提升攻击
 //! - **SYNC**: 确保数据持久化（牺牲性能）
 //! - **NO_DEV**: 防止通过设备文件访问硬件
+//! - **NO_ATIME**: 读取文件和列目录不更新 atime
+//! - **NO_DIRATIME**: 列目录不更新目录的 atime
+//!
+//! ## 标志来源与检查
+//!
+//! mount(2) 的 `mountflags` 经 [`MountFlags::from_syscall`] 转换，`data` 中的通用选项
+//! （`ro`、`nosuid`、`nodev`、`noatime` 等）由 [`parse_mount_options`] 解析，
+//! 剩下的选项原样交给具体文件系统。标志随挂载点保存，在以下位置检查：
+//!
+//! - READ_ONLY：创建、删除、重命名、修改元数据和以写方式打开，见 [`check_mount_writable`]；
+//! - NO_ATIME、NO_DIRATIME：`RegFile` 读取或列目录后不调用 `Inode::touch_atime`；
+//! - NO_DEV：打开字符设备和块设备节点返回 EACCES。
+//!
+//! `/proc/mounts` 按挂载点的标志输出选项。
 //!
 //! # MountPoint 结构
 //!
@@ -226,6 +242,7 @@
 //! ```
 
 use crate::sync::SpinLock;
+use crate::uapi::fs::SysMountFlags;
 use crate::vfs::{Dentry, FileSystem, FsError};
 use alloc::collections::BTreeMap;
 use alloc::string::String;
@@ -234,6 +251,7 @@ use alloc::vec::Vec;
 
 /// 挂载标志
 bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct MountFlags: u32 {
         /// 只读挂载
         const READ_ONLY  = 1 << 0;
@@ -249,9 +267,66 @@ bitflags::bitflags! {
 
         /// 禁止设备文件
         const NO_DEV     = 1 << 4;
+
+        /// 不更新访问时间
+        const NO_ATIME   = 1 << 5;
+
+        /// 不更新目录访问时间
+        const NO_DIRATIME = 1 << 6;
     }
 }
 
+impl MountFlags {
+    /// 由 mount(2) 的 `mountflags` 转换，不影响挂载点的标志（如 MS_BIND）被忽略
+    pub fn from_syscall(flags: SysMountFlags) -> Self {
+        const MAP: [(SysMountFlags, MountFlags); 7] = [
+            (SysMountFlags::MS_RDONLY, MountFlags::READ_ONLY),
+            (SysMountFlags::MS_NOEXEC, MountFlags::NO_EXEC),
+            (SysMountFlags::MS_NOSUID, MountFlags::NO_SUID),
+            (SysMountFlags::MS_SYNCHRONOUS, MountFlags::SYNC),
+            (SysMountFlags::MS_NODEV, MountFlags::NO_DEV),
+            (SysMountFlags::MS_NOATIME, MountFlags::NO_ATIME),
+            (SysMountFlags::MS_NODIRATIME, MountFlags::NO_DIRATIME),
+        ];
+        MAP.iter()
+            .filter(|(sys, _)| flags.contains(*sys))
+            .fold(Self::empty(), |acc, (_, flag)| acc | *flag)
+    }
+}
+
+/// 解析 mount(2) `data` 中的通用挂载选项
+///
+/// 选项以逗号分隔，按出现顺序作用于 `flags`，后出现的覆盖先出现的（如 `ro,rw` 为可写）。
+/// 返回更新后的标志和文件系统自己的选项（仍以逗号分隔），后者交给具体文件系统解析。
+pub fn parse_mount_options(data: &str, mut flags: MountFlags) -> (MountFlags, String) {
+    let mut rest = Vec::new();
+    for opt in data.split(',').map(str::trim).filter(|opt| !opt.is_empty()) {
+        let (flag, set) = match opt {
+            "ro" => (MountFlags::READ_ONLY, true),
+            "rw" => (MountFlags::READ_ONLY, false),
+            "nosuid" => (MountFlags::NO_SUID, true),
+            "suid" => (MountFlags::NO_SUID, false),
+            "nodev" => (MountFlags::NO_DEV, true),
+            "dev" => (MountFlags::NO_DEV, false),
+            "noexec" => (MountFlags::NO_EXEC, true),
+            "exec" => (MountFlags::NO_EXEC, false),
+            "sync" => (MountFlags::SYNC, true),
+            "async" => (MountFlags::SYNC, false),
+            "noatime" => (MountFlags::NO_ATIME, true),
+            "atime" | "relatime" | "strictatime" => (MountFlags::NO_ATIME, false),
+            "nodiratime" => (MountFlags::NO_DIRATIME, true),
+            "diratime" => (MountFlags::NO_DIRATIME, false),
+            "defaults" => continue,
+            _ => {
+                rest.push(opt);
+                continue;
+            }
+        };
+        flags.set(flag, set);
+    }
+    (flags, rest.join(","))
+}
+
 /// 挂载点信息
 pub struct MountPoint {
     /// 挂载的文件系统
@@ -425,7 +500,7 @@ impl MountTable {
         let mut best_len = 0;
 
         for (mount_path, stack) in mounts.iter() {
-            if normalized_path.starts_with(mount_path) && mount_path.len() > best_len {
+            if path_is_under(&normalized_path, mount_path) && mount_path.len() > best_len {
                 // 返回栈顶的挂载点（当前可见的）
                 if let Some(mp) = stack.last() {
                     best_match = Some(mp.clone());
@@ -477,11 +552,37 @@ impl MountTable {
     }
 }
 
+/// `path` 是否为 `mount_path` 本身或位于其下（按路径分量比较，`/mnt2` 不在 `/mnt` 下）
+fn path_is_under(path: &str, mount_path: &str) -> bool {
+    match path.strip_prefix(mount_path) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || mount_path.ends_with('/'),
+        None => false,
+    }
+}
+
 // 全局挂载表
 lazy_static::lazy_static! {
     pub static ref MOUNT_TABLE: MountTable = MountTable::new();
 }
 
+/// `dentry` 所在挂载点的标志，找不到挂载点时为空
+pub fn mount_flags(dentry: &Dentry) -> MountFlags {
+    MOUNT_TABLE
+        .find_mount(&dentry.full_path())
+        .map_or(MountFlags::empty(), |mp| mp.flags)
+}
+
+/// 修改 `dentry`（或在其下创建、删除条目）前检查所在挂载点是否可写
+///
+/// # 返回
+/// - `Err(FsError::ReadOnlyFs)`: 挂载点为只读
+pub fn check_mount_writable(dentry: &Dentry) -> Result<(), FsError> {
+    if mount_flags(dentry).contains(MountFlags::READ_ONLY) {
+        return Err(FsError::ReadOnlyFs);
+    }
+    Ok(())
+}
+
 /// 获取根 dentry
 pub fn get_root_dentry() -> Result<Arc<Dentry>, FsError> {
     MOUNT_TABLE
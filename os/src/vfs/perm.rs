@@ -0,0 +1,187 @@
+//! 属主与能力检查
+//!
+//! 修改 inode 属性（属主、权限位、时间戳）前的权限判定，规则与 Linux 相同：
+//! - 改变属主需要 `CAP_CHOWN`；属主可以把属组改成自己所属的任一组；
+//! - 改变权限位、显式设置时间戳需要是属主或拥有 `CAP_FOWNER`；
+//! - 非属组成员且没有 `CAP_FSETID` 时，chmod 会清除 setgid 位。
+//!
+//! 以及按权限位判定读、写、执行（搜索）访问的 [`check_access`]。
+
+use crate::kernel::{Capabilities, Credential, try_current_task};
+use crate::uapi::cred::{GID_UNCHANGED, UID_UNCHANGED};
+use crate::uapi::fs::{R_OK, W_OK, X_OK};
+use crate::vfs::{FileMode, FsError, InodeMetadata, InodeType};
+
+/// 检查调用者对 inode 是否有 `mask`（`R_OK`/`W_OK`/`X_OK` 的组合）表示的访问权限
+///
+/// 属主看属主位，属组是调用者的文件系统组或附加组之一时看属组位，其余看其他人位。`CAP_DAC_OVERRIDE` 绕过读写检查，
+/// 对非目录执行时仍要求至少有一个执行位；`CAP_DAC_READ_SEARCH` 绕过读和目录搜索检查。
+///
+/// # 返回
+/// - `Err(FsError::PermissionDenied)`: 权限位不允许
+pub fn check_access(cred: &Credential, meta: &InodeMetadata, mask: i32) -> Result<(), FsError> {
+    let mask = (mask & (R_OK | W_OK | X_OK)) as u32;
+    let shift = if cred.fsuid == meta.uid {
+        6
+    } else if cred.in_group(meta.gid) {
+        3
+    } else {
+        0
+    };
+    if (meta.mode.bits() >> shift) & mask == mask {
+        return Ok(());
+    }
+
+    let is_dir = meta.inode_type == InodeType::Directory;
+    let any_exec = meta.mode.bits() & 0o111 != 0;
+    if cred.capabilities.has(Capabilities::DAC_OVERRIDE)
+        && (mask & X_OK as u32 == 0 || is_dir || any_exec)
+    {
+        return Ok(());
+    }
+    let search_only = if is_dir { R_OK | X_OK } else { R_OK } as u32;
+    if cred.capabilities.has(Capabilities::DAC_READ_SEARCH) && mask & !search_only == 0 {
+        return Ok(());
+    }
+    Err(FsError::PermissionDenied)
+}
+
+/// 用当前任务的凭证执行 [`check_access`]
+///
+/// 没有当前任务（内核初始化、内核线程直接访问）时总是允许。
+pub fn check_current_access(meta: &InodeMetadata, mask: i32) -> Result<(), FsError> {
+    match try_current_task() {
+        Some(task) => {
+            let cred = task.lock().credential;
+            check_access(&cred, meta, mask)
+        }
+        None => Ok(()),
+    }
+}
+
+/// 调用者是否为 inode 属主或拥有 `CAP_FOWNER`
+pub fn inode_owner_or_capable(cred: &Credential, meta: &InodeMetadata) -> bool {
+    cred.fsuid == meta.uid || cred.capabilities.has(Capabilities::FOWNER)
+}
+
+/// 检查 chown 是否允许
+///
+/// `owner`/`group` 为 `u32::MAX` 表示不改变。
+pub fn check_chown(
+    cred: &Credential,
+    meta: &InodeMetadata,
+    owner: u32,
+    group: u32,
+) -> Result<(), FsError> {
+    if cred.capabilities.has(Capabilities::CHOWN) {
+        return Ok(());
+    }
+    let owner_changes = owner != UID_UNCHANGED && owner != meta.uid;
+    let group_changes = group != GID_UNCHANGED && group != meta.gid;
+    if owner_changes {
+        return Err(FsError::NotPermitted);
+    }
+    if group_changes && (cred.fsuid != meta.uid || !cred.in_group(group)) {
+        return Err(FsError::NotPermitted);
+    }
+    Ok(())
+}
+
+/// 检查 chmod 是否允许，返回实际要写入的权限位
+pub fn check_chmod(
+    cred: &Credential,
+    meta: &InodeMetadata,
+    mode: FileMode,
+) -> Result<FileMode, FsError> {
+    if !inode_owner_or_capable(cred, meta) {
+        return Err(FsError::NotPermitted);
+    }
+    let mut mode = mode;
+    if !cred.in_group(meta.gid) && !cred.capabilities.has(Capabilities::FSETID) {
+        mode.remove(FileMode::S_ISGID);
+    }
+    Ok(mode)
+}
+
+/// 检查显式设置时间戳（非 `UTIME_NOW`）是否允许
+pub fn check_set_times(cred: &Credential, meta: &InodeMetadata) -> Result<(), FsError> {
+    if inode_owner_or_capable(cred, meta) {
+        Ok(())
+    } else {
+        Err(FsError::NotPermitted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::CapabilitySet;
+    use crate::{kassert, test_case};
+
+    fn user(uid: u32, gid: u32) -> Credential {
+        let mut cred = Credential::root();
+        cred.fsuid = uid;
+        cred.fsgid = gid;
+        cred.capabilities = CapabilitySet::empty();
+        cred
+    }
+
+    fn meta(inode_type: InodeType, mode: u32) -> InodeMetadata {
+        let now = crate::uapi::time::TimeSpec::zero();
+        InodeMetadata {
+            inode_no: 1,
+            inode_type,
+            mode: FileMode::from_bits_truncate(mode),
+            uid: 1000,
+            gid: 100,
+            size: 0,
+            atime: now,
+            mtime: now,
+            ctime: now,
+            nlinks: 1,
+            blocks: 0,
+            rdev: 0,
+        }
+    }
+
+    test_case!(test_check_access_classes, {
+        let file = meta(InodeType::File, 0o640);
+        kassert!(check_access(&user(1000, 1000), &file, R_OK | W_OK).is_ok());
+        kassert!(check_access(&user(1001, 100), &file, R_OK).is_ok());
+        kassert!(check_access(&user(1001, 100), &file, W_OK) == Err(FsError::PermissionDenied));
+        kassert!(check_access(&user(1001, 1001), &file, R_OK) == Err(FsError::PermissionDenied));
+
+        // 属主类不回落到属组和其他人位
+        let other_only = meta(InodeType::File, 0o004);
+        kassert!(
+            check_access(&user(1000, 100), &other_only, R_OK) == Err(FsError::PermissionDenied)
+        );
+
+        // 附加组中有文件属组时看属组位
+        let mut member = user(1001, 1001);
+        member.groups = crate::kernel::GroupList::from_slice(&[50, 100]).unwrap();
+        kassert!(check_access(&member, &file, R_OK).is_ok());
+        kassert!(check_access(&member, &file, W_OK) == Err(FsError::PermissionDenied));
+    });
+
+    test_case!(test_check_access_capabilities, {
+        let mut cred = user(1001, 1001);
+        cred.capabilities = CapabilitySet::full();
+        kassert!(check_access(&cred, &meta(InodeType::File, 0o000), R_OK | W_OK).is_ok());
+        // 没有任何执行位的普通文件，CAP_DAC_OVERRIDE 也不能执行
+        kassert!(
+            check_access(&cred, &meta(InodeType::File, 0o600), X_OK)
+                == Err(FsError::PermissionDenied)
+        );
+        kassert!(check_access(&cred, &meta(InodeType::Directory, 0o000), X_OK).is_ok());
+
+        let mut reader = user(1001, 1001);
+        reader.capabilities = CapabilitySet::empty();
+        reader.capabilities.add(Capabilities::DAC_READ_SEARCH);
+        kassert!(check_access(&reader, &meta(InodeType::File, 0o000), R_OK).is_ok());
+        kassert!(
+            check_access(&reader, &meta(InodeType::File, 0o000), W_OK)
+                == Err(FsError::PermissionDenied)
+        );
+    });
+}
@@ -14,7 +14,7 @@ test_case!(test_dentry_create, {
     let root_inode = fs.root_inode();
     let dentry = Dentry::new("test".to_string(), root_inode.clone());
 
-    kassert!(dentry.name == "test");
+    kassert!(dentry.name() == "test");
     kassert!(Arc::ptr_eq(&dentry.inode, &root_inode));
 });
 
@@ -133,3 +133,63 @@ test_case!(test_dentry_overwrite_child, {
     kassert!(found.is_some());
     kassert!(Arc::ptr_eq(&found.unwrap(), &child2));
 });
+
+test_case!(test_dentry_move_updates_descendant_path, {
+    let fs = SimpleFs::new();
+    let root_inode = fs.root_inode();
+
+    let root = Dentry::new("/".to_string(), root_inode.clone());
+    let a = Dentry::new("a".to_string(), root_inode.clone());
+    let b = Dentry::new("b".to_string(), root_inode.clone());
+    let deep = Dentry::new("deep".to_string(), root_inode.clone());
+    root.add_child(a.clone());
+    root.add_child(b.clone());
+    a.add_child(deep.clone());
+    kassert!(deep.path_from(&root).unwrap() == "/a/deep");
+
+    // 祖先目录重命名后，持有的 dentry 看到新路径
+    a.move_to(&b, "renamed");
+    kassert!(root.lookup_child("a").is_none());
+    kassert!(Arc::ptr_eq(&b.lookup_child("renamed").unwrap(), &a));
+    kassert!(deep.path_from(&root).unwrap() == "/b/renamed/deep");
+
+    // 以 a 为根时路径从 a 开始
+    kassert!(deep.path_from(&a).unwrap() == "/deep");
+    kassert!(a.path_from(&a).unwrap() == "/");
+});
+
+test_case!(test_dentry_deleted_path_not_found, {
+    let fs = SimpleFs::new();
+    let root_inode = fs.root_inode();
+
+    let root = Dentry::new("/".to_string(), root_inode.clone());
+    let dir = Dentry::new("gone".to_string(), root_inode.clone());
+    root.add_child(dir.clone());
+    kassert!(!dir.is_deleted());
+
+    dir.mark_deleted();
+    root.remove_child("gone");
+    kassert!(matches!(dir.path_from(&root), Err(FsError::NotFound)));
+});
+
+test_case!(test_dentry_d_path_across_mount, {
+    let fs = SimpleFs::new();
+    let root_inode = fs.root_inode();
+
+    let root = Dentry::new("/".to_string(), root_inode.clone());
+    let mnt = Dentry::new("mnt".to_string(), root_inode.clone());
+    root.add_child(mnt.clone());
+    let mounted_root = Dentry::new("/".to_string(), root_inode.clone());
+    mounted_root.set_mounted_on(&mnt);
+    let file = Dentry::new("log".to_string(), root_inode.clone());
+    mounted_root.add_child(file.clone());
+
+    kassert!(file.d_path() == "/mnt/log");
+    kassert!(file.path_from(&root).unwrap() == "/mnt/log");
+    kassert!(file.path_from(&mounted_root).unwrap() == "/log");
+
+    // 与 Linux 一样，已删除的文件带 " (deleted)" 后缀
+    file.mark_deleted();
+    mounted_root.remove_child("log");
+    kassert!(file.d_path() == "/mnt/log (deleted)");
+});
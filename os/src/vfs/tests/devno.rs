@@ -61,6 +61,21 @@ test_case!(test_get_chrdev_driver, {
     kassert!(driver.is_some() || driver.is_none()); // 取决于是否有注册的驱动
 });
 
+test_case!(test_get_chrdev_driver_input, {
+    // evdev minor 从 64 开始，范围外的 minor 不映射驱动
+    kassert!(get_chrdev_driver(makedev(chrdev_major::INPUT, 0)).is_none());
+    kassert!(
+        get_chrdev_driver(makedev(
+            chrdev_major::INPUT,
+            input_minor::EVDEV_BASE + input_minor::EVDEV_MAX
+        ))
+        .is_none()
+    );
+    let has_input = !crate::device::INPUT_DRIVERS.read().is_empty();
+    let event0 = get_chrdev_driver(makedev(chrdev_major::INPUT, input_minor::EVDEV_BASE));
+    kassert!(event0.is_some() == has_input);
+});
+
 test_case!(test_devno_unique, {
     // 确保不同的 major/minor 组合产生不同的 devno
     let dev1 = makedev(1, 0);
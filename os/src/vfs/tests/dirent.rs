@@ -0,0 +1,71 @@
+use super::super::*;
+use crate::uapi::fs::{DT_DIR, DT_REG, LinuxDirent64};
+use crate::{kassert, test_case};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+fn entry(name: &str, inode_no: usize, inode_type: InodeType) -> DirEntry {
+    DirEntry {
+        name: String::from(name),
+        inode_no,
+        inode_type,
+    }
+}
+
+fn read_u16(buf: &[u8], off: usize) -> u16 {
+    u16::from_ne_bytes([buf[off], buf[off + 1]])
+}
+
+fn read_i64(buf: &[u8], off: usize) -> i64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&buf[off..off + 8]);
+    i64::from_ne_bytes(bytes)
+}
+
+test_case!(test_encode_dirents_layout, {
+    let entries = [
+        entry(".", 1, InodeType::Directory),
+        entry("hello.txt", 7, InodeType::File),
+    ];
+    let mut buf = Vec::new();
+    kassert!(encode_dirents(&entries, 0, 4096, &mut buf) == 2);
+
+    // 每条记录按 8 字节对齐，d_off 是下一个条目的下标
+    let first = read_u16(&buf, 16) as usize;
+    kassert!(first == LinuxDirent64::total_len("."));
+    kassert!(first % 8 == 0);
+    kassert!(read_i64(&buf, 8) == 1);
+    kassert!(buf[18] == DT_DIR);
+
+    let second = read_u16(&buf, first + 16) as usize;
+    kassert!(buf.len() == first + second);
+    kassert!(second % 8 == 0);
+    kassert!(read_i64(&buf, first) == 7);
+    kassert!(read_i64(&buf, first + 8) == 2);
+    kassert!(buf[first + 18] == DT_REG);
+    let name = first + LinuxDirent64::BASE_SIZE;
+    kassert!(&buf[name..name + 9] == b"hello.txt");
+    kassert!(buf[name + 9] == 0);
+});
+
+test_case!(test_encode_dirents_short_buffer, {
+    let entries = [
+        entry("a", 2, InodeType::File),
+        entry("b", 3, InodeType::File),
+    ];
+    let reclen = LinuxDirent64::total_len("a");
+
+    // 只放得下一条，剩下的从偏移 1 继续
+    let mut buf = Vec::new();
+    kassert!(encode_dirents(&entries, 0, reclen + 1, &mut buf) == 1);
+    kassert!(buf.len() == reclen);
+
+    let mut buf = Vec::new();
+    kassert!(encode_dirents(&entries, 1, reclen, &mut buf) == 1);
+    kassert!(read_i64(&buf, 8) == 2);
+
+    // 连一条都放不下
+    let mut buf = Vec::new();
+    kassert!(encode_dirents(&entries, 0, reclen - 1, &mut buf) == 0);
+    kassert!(buf.is_empty());
+});
@@ -1,4 +1,5 @@
 use super::*;
+use crate::config::DEFAULT_MAX_FDS;
 use crate::{kassert, test_case};
 
 // P0 核心功能测试
@@ -196,3 +197,85 @@ test_case!(test_fdtable_alloc_multiple, {
         kassert!(result.is_ok());
     }
 });
+
+test_case!(test_fdtable_lowest_free_across_words, {
+    let fd_table = FDTable::new();
+    fd_table.set_max_fds(200);
+    let fs = create_test_simplefs();
+    let inode = create_test_file_with_content(&fs, "test.txt", b"test").unwrap();
+    let file = create_test_file("test.txt", inode, OpenFlags::O_RDONLY);
+
+    // 占满两个以上的位图字
+    for i in 0..130 {
+        kassert!(fd_table.alloc(file.clone()).unwrap() == i);
+    }
+    fd_table.close(70).unwrap();
+    fd_table.close(5).unwrap();
+
+    // 总是复用最小的空闲 fd
+    kassert!(fd_table.alloc(file.clone()).unwrap() == 5);
+    kassert!(fd_table.alloc(file.clone()).unwrap() == 70);
+    kassert!(fd_table.alloc(file.clone()).unwrap() == 130);
+    kassert!(fd_table.dup_from(0, 64, FdFlags::empty()).unwrap() == 131);
+
+    fd_table.set_max_fds(132);
+    kassert!(matches!(
+        fd_table.alloc(file.clone()),
+        Err(FsError::TooManyOpenFiles)
+    ));
+});
+
+test_case!(test_fdtable_dup3_errors_and_cloexec, {
+    let fd_table = FDTable::new();
+    let fs = create_test_simplefs();
+    let inode = create_test_file_with_content(&fs, "test.txt", b"test").unwrap();
+    let file = create_test_file("test.txt", inode, OpenFlags::O_RDONLY);
+    let fd = fd_table.alloc(file).unwrap();
+
+    // dup3 不允许相同的 fd，dup2 则直接返回
+    kassert!(matches!(
+        fd_table.dup3(fd, fd, OpenFlags::empty()),
+        Err(FsError::InvalidArgument)
+    ));
+    kassert!(fd_table.dup2(fd, fd).unwrap() == fd);
+    kassert!(matches!(
+        fd_table.dup3(fd, DEFAULT_MAX_FDS, OpenFlags::empty()),
+        Err(FsError::BadFileDescriptor)
+    ));
+    kassert!(matches!(
+        fd_table.dup3(9, 10, OpenFlags::empty()),
+        Err(FsError::BadFileDescriptor)
+    ));
+
+    // CLOEXEC 只作用于新 fd，exec 时只关闭带标志的 fd
+    kassert!(fd_table.dup3(fd, 7, OpenFlags::O_CLOEXEC).unwrap() == 7);
+    kassert!(fd_table.get_fd_flags(7).unwrap() == FdFlags::CLOEXEC);
+    kassert!(fd_table.get_fd_flags(fd).unwrap() == FdFlags::empty());
+    fd_table.close_exec();
+    kassert!(fd_table.get(7).is_err());
+    kassert!(fd_table.get(fd).is_ok());
+});
+
+test_case!(test_fdtable_open_file_count, {
+    let fs = create_test_simplefs();
+    let inode = create_test_file_with_content(&fs, "test.txt", b"test").unwrap();
+    let file = create_test_file("test.txt", inode, OpenFlags::O_RDONLY);
+
+    let before = nr_open_files();
+    let fd_table = FDTable::new();
+    let fd = fd_table.alloc(file.clone()).unwrap();
+    fd_table.dup(fd).unwrap();
+    kassert!(nr_open_files() == before + 2);
+
+    // 替换已打开的 fd 不改变计数
+    fd_table.install_at(1, file).unwrap();
+    kassert!(nr_open_files() == before + 2);
+
+    let cloned = fd_table.clone_table();
+    kassert!(nr_open_files() == before + 4);
+    fd_table.close(fd).unwrap();
+    kassert!(nr_open_files() == before + 3);
+    drop(cloned);
+    drop(fd_table);
+    kassert!(nr_open_files() == before);
+});
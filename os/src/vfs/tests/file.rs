@@ -1,5 +1,8 @@
 use super::*;
+use crate::config::PAGE_SIZE;
+use crate::fs::tmpfs::TmpFs;
 use crate::{kassert, test_case};
+use alloc::boxed::Box;
 
 // P0 核心功能测试
 
@@ -119,6 +122,137 @@ test_case!(test_file_append_mode, {
     kassert!(&buf[..] == b"Hello, World!");
 });
 
+/// 并发追加测试中后台线程写入的记录数
+const APPEND_RECORDS: usize = 32;
+
+/// 后台追加线程：通过 `arg` 传入的会话写入 [`APPEND_RECORDS`] 条 `b"line\n"`，
+/// 每写一条让出一次 CPU，返回写入的条数
+fn append_records(arg: usize) -> i32 {
+    // SAFETY: arg 由测试用 Box::into_raw 传入，只在这里取回一次
+    let file = unsafe { Box::from_raw(arg as *mut Arc<dyn File>) };
+    let mut written = 0;
+    while written < APPEND_RECORDS && !crate::kernel::kthread_should_stop() {
+        if file.write(b"line\n").unwrap() != 5 {
+            break;
+        }
+        written += 1;
+        crate::kernel::yield_task();
+    }
+    written as i32
+}
+
+/// 启动后台追加线程，写入会话的所有权交给线程
+fn spawn_appender(writer: Arc<dyn File>) -> crate::kernel::KthreadHandle {
+    let arg = Box::into_raw(Box::new(writer)) as usize;
+    crate::kernel::kthread_run("append_test", append_records, arg)
+}
+
+// 后台线程通过一个会话追加写，另一个会话并发 stat/lseek：看到的大小从不减小，
+// 最终等于写入的总字节数
+test_case!(test_file_size_shared_between_sessions, {
+    let fs = TmpFs::new(0);
+    let inode = fs
+        .root_inode()
+        .create("log", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    let writer = create_test_file(
+        "log",
+        inode.clone(),
+        OpenFlags::O_WRONLY | OpenFlags::O_APPEND,
+    );
+    let reader = create_test_file("log", inode, OpenFlags::O_RDONLY);
+    let total = APPEND_RECORDS * 5;
+
+    let handle = spawn_appender(writer);
+    let mut last = 0;
+    loop {
+        let size = reader.metadata().unwrap().size;
+        let end = reader.lseek(0, SeekWhence::End).unwrap();
+        kassert!(size >= last && end >= size && size % 5 == 0);
+        last = end;
+        if end == total {
+            break;
+        }
+        crate::kernel::yield_task();
+    }
+    kassert!(crate::kernel::kthread_stop(handle) == APPEND_RECORDS as i32);
+    kassert!(reader.metadata().unwrap().size == total);
+
+    let mut buf = [0u8; 5];
+    reader.lseek(0, SeekWhence::Set).unwrap();
+    for _ in 0..APPEND_RECORDS {
+        kassert!(reader.read(&mut buf).unwrap() == 5);
+        kassert!(&buf == b"line\n");
+    }
+    kassert!(reader.read(&mut buf).unwrap() == 0);
+});
+
+// 后台线程和当前任务通过各自的会话交替追加，每条记录都写到当时的末尾，互不覆盖
+test_case!(test_file_append_interleaved_sessions, {
+    let fs = TmpFs::new(0);
+    let inode = fs
+        .root_inode()
+        .create("log", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    let flags = OpenFlags::O_WRONLY | OpenFlags::O_APPEND;
+    let ours = create_test_file("log", inode.clone(), flags);
+    let reader = create_test_file("log", inode.clone(), OpenFlags::O_RDONLY);
+    let total = APPEND_RECORDS * 5 * 2;
+
+    let handle = spawn_appender(create_test_file("log", inode.clone(), flags));
+    let mut last = 0;
+    for _ in 0..APPEND_RECORDS {
+        ours.write(b"LINE\n").unwrap();
+        // O_APPEND 写完后偏移量就是本次写入后的末尾
+        kassert!(ours.offset() >= last + 5);
+        last = ours.offset();
+        let size = reader.lseek(0, SeekWhence::End).unwrap();
+        kassert!(size >= last);
+        crate::kernel::yield_task();
+    }
+    kassert!(crate::kernel::kthread_stop(handle) == APPEND_RECORDS as i32);
+    kassert!(inode.metadata().unwrap().size == total);
+
+    // 两边的记录各自完整，数量各为 APPEND_RECORDS
+    let (mut upper, mut lower) = (0, 0);
+    let mut buf = [0u8; 5];
+    reader.lseek(0, SeekWhence::Set).unwrap();
+    for _ in 0..APPEND_RECORDS * 2 {
+        kassert!(reader.read(&mut buf).unwrap() == 5);
+        match &buf {
+            b"LINE\n" => upper += 1,
+            b"line\n" => lower += 1,
+            _ => kassert!(false),
+        }
+    }
+    kassert!(upper == APPEND_RECORDS && lower == APPEND_RECORDS);
+    kassert!(inode.append(b"!").unwrap() == (total, 1));
+});
+
+// 通过一个会话截断，另一个会话读到的大小和内容随之变化
+test_case!(test_file_truncate_seen_by_other_session, {
+    let fs = TmpFs::new(0);
+    let inode = fs
+        .root_inode()
+        .create("data", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    inode.write_at(0, &[0xaa; PAGE_SIZE + 100]).unwrap();
+    let reader = create_test_file("data", inode.clone(), OpenFlags::O_RDONLY);
+    reader.lseek(10, SeekWhence::Set).unwrap();
+
+    inode.truncate(20).unwrap();
+    kassert!(reader.metadata().unwrap().size == 20);
+    let mut buf = [0u8; 64];
+    kassert!(reader.read(&mut buf).unwrap() == 10);
+
+    // 重新扩展后截断点之后读出零
+    inode.truncate(PAGE_SIZE).unwrap();
+    reader.lseek(0, SeekWhence::Set).unwrap();
+    kassert!(reader.read(&mut buf).unwrap() == 64);
+    kassert!(buf[..20].iter().all(|&b| b == 0xaa));
+    kassert!(buf[20..].iter().all(|&b| b == 0));
+});
+
 test_case!(test_file_readable_check, {
     // 创建文件
     let fs = create_test_simplefs();
@@ -194,6 +328,46 @@ test_case!(test_file_lseek_negative_set, {
     kassert!(matches!(result, Err(FsError::InvalidArgument)));
 });
 
+test_case!(test_file_lseek_data_hole, {
+    // tmpfs 只为写入过的页分配内存，其余为空洞
+    let fs = TmpFs::new(0);
+    let inode = fs
+        .root_inode()
+        .create("sparse", FileMode::from_bits_truncate(0o644))
+        .unwrap();
+    inode.write_at(2 * PAGE_SIZE, b"data").unwrap();
+    inode.truncate(4 * PAGE_SIZE).unwrap();
+    let file = create_test_file("sparse", inode, OpenFlags::O_RDONLY);
+
+    kassert!(file.lseek(0, SeekWhence::Data).unwrap() == 2 * PAGE_SIZE);
+    kassert!(file.offset() == 2 * PAGE_SIZE);
+    kassert!(file.lseek(0, SeekWhence::Hole).unwrap() == 0);
+    kassert!(
+        file.lseek(2 * PAGE_SIZE as isize + 1, SeekWhence::Data)
+            .unwrap()
+            == 2 * PAGE_SIZE + 1
+    );
+    kassert!(
+        file.lseek(2 * PAGE_SIZE as isize, SeekWhence::Hole)
+            .unwrap()
+            == 3 * PAGE_SIZE
+    );
+
+    // 最后一段数据之后没有数据；文件末尾视为空洞
+    kassert!(matches!(
+        file.lseek(3 * PAGE_SIZE as isize, SeekWhence::Data),
+        Err(FsError::NoSuchDeviceOrAddress)
+    ));
+    kassert!(matches!(
+        file.lseek(4 * PAGE_SIZE as isize, SeekWhence::Hole),
+        Err(FsError::NoSuchDeviceOrAddress)
+    ));
+    kassert!(matches!(
+        file.lseek(-1, SeekWhence::Data),
+        Err(FsError::NoSuchDeviceOrAddress)
+    ));
+});
+
 test_case!(test_directory_readdir_cached_until_rewind, {
     let fs = create_test_simplefs();
     let root = fs.root_inode();
@@ -220,3 +394,30 @@ test_case!(test_directory_readdir_cached_until_rewind, {
     kassert!(!Arc::ptr_eq(&first, &refreshed));
     kassert!(refreshed.iter().any(|entry| entry.name == "after.txt"));
 });
+
+test_case!(test_file_read_updates_atime, {
+    use crate::uapi::time::TimeSpec;
+
+    let fs = create_test_simplefs();
+    let inode = create_test_file_with_content(&fs, "atime.txt", b"data").unwrap();
+    let past = TimeSpec {
+        tv_sec: 1_000,
+        tv_nsec: 0,
+    };
+    inode.set_times(Some(past), None).unwrap();
+
+    // inode 的读取本身不更新 atime，经由 File 读取后才更新
+    let mut buf = [0u8; 4];
+    kassert!(inode.read_at(0, &mut buf).unwrap() == 4);
+    kassert!(inode.metadata().unwrap().atime == past);
+    let file = create_test_file("atime.txt", inode.clone(), OpenFlags::O_RDONLY);
+    kassert!(file.read(&mut buf).unwrap() == 4);
+    kassert!(inode.metadata().unwrap().atime > past);
+
+    // 列目录更新目录的 atime
+    let root = fs.root_inode();
+    root.set_times(Some(past), None).unwrap();
+    let dir = create_test_file("/", root.clone(), OpenFlags::O_RDONLY);
+    dir.readdir_cached().unwrap();
+    kassert!(root.metadata().unwrap().atime > past);
+});
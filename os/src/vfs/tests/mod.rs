@@ -52,11 +52,13 @@ pub mod blk_dev_file;
 pub mod char_dev_file;
 pub mod dentry;
 pub mod devno;
+pub mod dirent;
 pub mod fd_table;
 pub mod file;
 pub mod mount;
 pub mod page_cache;
 pub mod path;
+pub mod perm;
 pub mod pipe;
 pub mod stdio;
 pub mod trait_file;
@@ -99,3 +99,39 @@ test_case!(test_umount_root_should_fail, {
     let result = MOUNT_TABLE.umount("/");
     kassert!(result.is_err());
 });
+
+// 挂载选项
+
+test_case!(test_parse_mount_options, {
+    use crate::uapi::fs::SysMountFlags;
+    use crate::vfs::parse_mount_options;
+
+    let flags = MountFlags::from_syscall(SysMountFlags::MS_RDONLY | SysMountFlags::MS_NODEV);
+    kassert!(flags == MountFlags::READ_ONLY | MountFlags::NO_DEV);
+    kassert!(MountFlags::from_syscall(SysMountFlags::MS_BIND).is_empty());
+
+    // 通用选项作用于标志，其余选项留给文件系统
+    let (flags, rest) = parse_mount_options("noatime,hidepid=2,nosuid,rw,gid=10", flags);
+    kassert!(flags == MountFlags::NO_DEV | MountFlags::NO_ATIME | MountFlags::NO_SUID);
+    kassert!(rest == "hidepid=2,gid=10");
+
+    // 后出现的覆盖先出现的
+    let (flags, rest) = parse_mount_options("defaults,ro,relatime,dev", flags);
+    kassert!(flags == MountFlags::READ_ONLY | MountFlags::NO_SUID);
+    kassert!(rest.is_empty());
+});
+
+test_case!(test_find_mount_component_boundary, {
+    let fs = create_test_simplefs();
+    MOUNT_TABLE
+        .mount(fs, "/boundary_test", MountFlags::READ_ONLY, None)
+        .ok();
+
+    let inside = MOUNT_TABLE.find_mount("/boundary_test/file");
+    kassert!(inside.is_some_and(|m| m.flags.contains(MountFlags::READ_ONLY)));
+    // 仅前缀相同的兄弟路径不属于该挂载点
+    let sibling = MOUNT_TABLE.find_mount("/boundary_test2/file");
+    kassert!(sibling.is_none_or(|m| m.mount_path != "/boundary_test"));
+
+    MOUNT_TABLE.umount("/boundary_test").ok();
+});
@@ -203,7 +203,7 @@ test_case!(test_dentry_mount_cache, {
 
     if let Some(mp) = mount_point {
         // 检查挂载点的根 dentry
-        kassert!(mp.root.name == "/");
+        kassert!(mp.root.name() == "/");
 
         // 检查是否有挂载缓存（通过 find_mount 已经验证）
         kassert!(mp.mount_path == "/cache_test");
@@ -244,7 +244,7 @@ test_case!(test_check_mount_point_function, {
     let dentry = vfs_lookup("/mountpoint");
     if let Ok(d) = dentry {
         // 应该返回挂载文件系统的根
-        kassert!(d.name == "/");
+        kassert!(d.name() == "/");
 
         // 继续查找子文件系统中的文件
         let file_lookup = vfs_lookup_from(d, "file_in_child");
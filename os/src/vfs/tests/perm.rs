@@ -0,0 +1,78 @@
+//! 属主与能力检查测试
+
+use crate::kernel::{Capabilities, CapabilitySet, Credential, GroupList};
+use crate::vfs::perm::{check_chmod, check_chown, check_set_times};
+use crate::vfs::{FileMode, FsError, InodeMetadata, InodeType, TimeSpec};
+use crate::{kassert, test_case};
+
+fn metadata(uid: u32, gid: u32) -> InodeMetadata {
+    InodeMetadata {
+        inode_no: 1,
+        inode_type: InodeType::File,
+        mode: FileMode::from_bits_truncate(0o644),
+        uid,
+        gid,
+        size: 0,
+        atime: TimeSpec::zero(),
+        mtime: TimeSpec::zero(),
+        ctime: TimeSpec::zero(),
+        nlinks: 1,
+        blocks: 0,
+        rdev: 0,
+    }
+}
+
+fn user(uid: u32, gid: u32) -> Credential {
+    let mut cred = Credential::root();
+    cred.uid = uid;
+    cred.euid = uid;
+    cred.suid = uid;
+    cred.fsuid = uid;
+    cred.gid = gid;
+    cred.egid = gid;
+    cred.sgid = gid;
+    cred.fsgid = gid;
+    cred.capabilities = CapabilitySet::empty();
+    cred
+}
+
+test_case!(test_chown_requires_cap_chown, {
+    let meta = metadata(1000, 1000);
+    let owner = user(1000, 1000);
+
+    // 属主不能把文件送给别人，但可以改到自己的组
+    kassert!(check_chown(&owner, &meta, 0, u32::MAX) == Err(FsError::NotPermitted));
+    kassert!(check_chown(&owner, &meta, u32::MAX, 1000).is_ok());
+    kassert!(check_chown(&owner, &meta, 1000, 0) == Err(FsError::NotPermitted));
+    kassert!(check_chown(&user(1001, 1000), &meta, u32::MAX, 1000).is_ok());
+    kassert!(check_chown(&user(1001, 1001), &meta, u32::MAX, 1001) == Err(FsError::NotPermitted));
+
+    // 属主可以改到任一附加组，不能改到不属于的组
+    let mut member = owner;
+    member.groups = GroupList::from_slice(&[20]).unwrap();
+    kassert!(check_chown(&member, &meta, u32::MAX, 20).is_ok());
+    kassert!(check_chown(&member, &meta, u32::MAX, 30) == Err(FsError::NotPermitted));
+
+    let mut admin = user(1001, 1001);
+    admin.capabilities.add(Capabilities::CHOWN);
+    kassert!(check_chown(&admin, &meta, 0, 0).is_ok());
+    kassert!(check_chown(&Credential::root(), &meta, 0, 0).is_ok());
+});
+
+test_case!(test_chmod_requires_owner_or_fowner, {
+    let meta = metadata(1000, 1000);
+    let mode = FileMode::from_bits_truncate(0o2755);
+
+    kassert!(check_chmod(&user(1001, 1000), &meta, mode) == Err(FsError::NotPermitted));
+    kassert!(check_chmod(&user(1000, 1000), &meta, mode) == Ok(mode));
+
+    // 不在属组中的属主会丢掉 setgid 位
+    let cleared = check_chmod(&user(1000, 2000), &meta, mode);
+    kassert!(cleared == Ok(FileMode::from_bits_truncate(0o755)));
+
+    let mut fowner = user(1001, 1001);
+    fowner.capabilities.add(Capabilities::FOWNER);
+    kassert!(check_chmod(&fowner, &meta, mode).is_ok());
+    kassert!(check_set_times(&fowner, &meta).is_ok());
+    kassert!(check_set_times(&user(1001, 1001), &meta) == Err(FsError::NotPermitted));
+});
@@ -1,6 +1,7 @@
 use super::*;
 use crate::fs::tmpfs::TmpFs;
 use crate::vfs::PipeFile;
+use crate::vfs::impls::pipe_file::{FifoPartner, named_fifo_count};
 use crate::{kassert, test_case};
 
 // P0 核心功能测试
@@ -174,7 +175,9 @@ test_case!(test_named_fifo_shared_buffer, {
         .unwrap();
     let dentry = Dentry::new(String::from("fifo"), inode);
 
-    let read_end = PipeFile::open_fifo(dentry.clone(), OpenFlags::O_RDONLY).unwrap();
+    // 读端先以非阻塞方式打开，写端随后打开时读端已存在，二者都不会阻塞
+    let read_end =
+        PipeFile::open_fifo(dentry.clone(), OpenFlags::O_RDONLY | OpenFlags::O_NONBLOCK).unwrap();
     let write_end = PipeFile::open_fifo(dentry, OpenFlags::O_WRONLY).unwrap();
 
     let write_file: Arc<dyn File> = Arc::new(write_end);
@@ -202,3 +205,95 @@ test_case!(test_named_fifo_nonblock_write_without_reader, {
     let result = PipeFile::open_fifo(dentry, OpenFlags::O_WRONLY | OpenFlags::O_NONBLOCK);
     kassert!(matches!(result, Err(FsError::NoSuchDeviceOrAddress)));
 });
+
+/// 创建一个命名 FIFO，返回 (文件系统根, 目录项)
+fn make_fifo(name: &str) -> (Arc<dyn Inode>, Arc<Dentry>) {
+    let fs = TmpFs::new(0);
+    let root = fs.root_inode();
+    let inode = root
+        .mknod(
+            name,
+            FileMode::S_IFIFO | FileMode::S_IRUSR | FileMode::S_IWUSR,
+            0,
+        )
+        .unwrap();
+    (root, Dentry::new(String::from(name), inode))
+}
+
+test_case!(test_named_fifo_blocking_open_rendezvous, {
+    // 模拟两个协作任务：任务 A 阻塞打开读端，任务 B 随后打开写端
+    let (_root, dentry) = make_fifo("fifo");
+
+    let (reader, partner) = PipeFile::attach_fifo(&dentry, OpenFlags::O_RDONLY).unwrap();
+    kassert!(matches!(partner, Some(FifoPartner::Writer(_))));
+    let partner = partner.unwrap();
+    kassert!(!reader.fifo_partner_arrived(partner));
+
+    // 读端已存在，写端无需等待
+    let (writer, writer_partner) = PipeFile::attach_fifo(&dentry, OpenFlags::O_WRONLY).unwrap();
+    kassert!(writer_partner.is_none());
+    kassert!(reader.fifo_partner_arrived(partner));
+
+    let reader: Arc<dyn File> = Arc::new(reader);
+    let writer: Arc<dyn File> = Arc::new(writer);
+    kassert!(writer.write(b"ping").unwrap() == 4);
+    let mut buf = [0u8; 4];
+    kassert!(reader.read(&mut buf).unwrap() == 4);
+    kassert!(&buf == b"ping");
+
+    // 写端关闭后读端读到 EOF
+    drop(writer);
+    kassert!(reader.read(&mut buf).unwrap() == 0);
+});
+
+test_case!(test_named_fifo_writer_waits_for_reader, {
+    let (_root, dentry) = make_fifo("fifo");
+
+    let (writer, partner) = PipeFile::attach_fifo(&dentry, OpenFlags::O_WRONLY).unwrap();
+    let partner = partner.unwrap();
+    kassert!(matches!(partner, FifoPartner::Reader(_)));
+    kassert!(!writer.fifo_partner_arrived(partner));
+
+    // 读端短暂出现后又关闭，等待中的写端也应被放行
+    let (reader, _) =
+        PipeFile::attach_fifo(&dentry, OpenFlags::O_RDONLY | OpenFlags::O_NONBLOCK).unwrap();
+    drop(reader);
+    kassert!(writer.fifo_partner_arrived(partner));
+    kassert!(matches!(writer.write(b"x"), Err(FsError::BrokenPipe)));
+});
+
+test_case!(test_named_fifo_rdwr_and_nonblock_read_do_not_wait, {
+    let (_root, dentry) = make_fifo("fifo");
+
+    let (rdwr, partner) = PipeFile::attach_fifo(&dentry, OpenFlags::O_RDWR).unwrap();
+    kassert!(partner.is_none());
+    drop(rdwr);
+
+    let (reader, partner) =
+        PipeFile::attach_fifo(&dentry, OpenFlags::O_RDONLY | OpenFlags::O_NONBLOCK).unwrap();
+    kassert!(partner.is_none());
+    let mut buf = [0u8; 1];
+    kassert!(matches!(reader.read(&mut buf), Err(FsError::WouldBlock)));
+});
+
+test_case!(test_named_fifo_released_after_last_close, {
+    let (root, dentry) = make_fifo("fifo");
+    let before = named_fifo_count();
+
+    let (rdwr, _) = PipeFile::attach_fifo(&dentry, OpenFlags::O_RDWR).unwrap();
+    kassert!(named_fifo_count() == before + 1);
+    kassert!(rdwr.write(b"stale").unwrap() == 5);
+
+    // 删除目录项后，已打开的端点仍可读写
+    root.unlink("fifo").unwrap();
+    let mut buf = [0u8; 2];
+    kassert!(rdwr.read(&mut buf).unwrap() == 2);
+    kassert!(&buf == b"st");
+
+    // 最后一个端点关闭后注册表项释放，残留数据被丢弃
+    drop(rdwr);
+    kassert!(named_fifo_count() == before);
+
+    let (reopened, _) = PipeFile::attach_fifo(&dentry, OpenFlags::O_RDWR).unwrap();
+    kassert!(matches!(reopened.read(&mut buf), Err(FsError::WouldBlock)));
+});
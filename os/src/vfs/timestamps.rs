@@ -0,0 +1,120 @@
+//! 文件时间戳更新规则
+//!
+//! 各文件系统按同一套规则维护 atime/mtime/ctime：
+//! - 读文件内容、列目录：按 atime 策略决定是否更新 atime，见 [`atime_needs_update`]；
+//! - 写入、截断：更新 mtime 和 ctime；
+//! - chmod、chown、link/unlink 等元数据修改：只更新 ctime；
+//! - utimensat/futimens：按参数设置 atime/mtime（`UTIME_OMIT` 不改变，`UTIME_NOW`
+//!   取当前时间），只要有一个被修改，ctime 就更新为当前时间；两个都是
+//!   `UTIME_OMIT` 时什么都不做。
+//!
+//! atime 策略由 `/proc/sys/fs/atime_policy` 设置：
+//! - `0`：从不更新（noatime）；
+//! - `1`：atime 不晚于 mtime 或 ctime、或已超过一天时才更新（relatime，默认）；
+//! - `2`：每次读取都更新（strictatime）。
+//!
+//! inode 的读取方法本身不更新 atime，由 VFS 在读取后调用 `Inode::touch_atime`，
+//! 各文件系统在其中按上述策略判断。以 `noatime` 挂载的文件系统上的读取不受策略影响，
+//! 从不更新 atime，`nodiratime` 对目录同样如此：VFS 不调用 `touch_atime`。
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::uapi::time::TimeSpec;
+
+/// relatime 下 atime 至少多久更新一次（秒）
+const RELATIME_INTERVAL_SECS: i64 = 24 * 60 * 60;
+
+/// atime 更新策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AtimePolicy {
+    /// 从不更新
+    NoAtime = 0,
+    /// 相对更新
+    Relatime = 1,
+    /// 每次读取都更新
+    Strict = 2,
+}
+
+impl AtimePolicy {
+    /// 从 sysctl 数值转换
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::NoAtime),
+            1 => Some(Self::Relatime),
+            2 => Some(Self::Strict),
+            _ => None,
+        }
+    }
+}
+
+static ATIME_POLICY: AtomicU8 = AtomicU8::new(AtimePolicy::Relatime as u8);
+
+/// 当前的 atime 策略
+pub fn atime_policy() -> AtimePolicy {
+    AtimePolicy::from_u8(ATIME_POLICY.load(Ordering::Relaxed)).unwrap_or(AtimePolicy::Relatime)
+}
+
+/// 设置 atime 策略
+pub fn set_atime_policy(policy: AtimePolicy) {
+    ATIME_POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+/// 按 `policy` 判断读取时是否需要把 atime 更新为 `now`
+pub fn atime_update_due(
+    policy: AtimePolicy,
+    atime: TimeSpec,
+    mtime: TimeSpec,
+    ctime: TimeSpec,
+    now: TimeSpec,
+) -> bool {
+    match policy {
+        AtimePolicy::NoAtime => false,
+        AtimePolicy::Strict => atime != now,
+        AtimePolicy::Relatime => {
+            atime <= mtime
+                || atime <= ctime
+                || now.tv_sec.saturating_sub(atime.tv_sec) >= RELATIME_INTERVAL_SECS
+        }
+    }
+}
+
+/// 按当前策略判断读取时是否需要把 atime 更新为 `now`
+pub fn atime_needs_update(
+    atime: TimeSpec,
+    mtime: TimeSpec,
+    ctime: TimeSpec,
+    now: TimeSpec,
+) -> bool {
+    atime_update_due(atime_policy(), atime, mtime, ctime, now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    fn sec(s: i64) -> TimeSpec {
+        TimeSpec {
+            tv_sec: s,
+            tv_nsec: 0,
+        }
+    }
+
+    test_case!(test_atime_update_policy, {
+        // atime 为 100 时，按给定的 mtime、ctime 和当前时间判断
+        let due = |policy, mtime, ctime, now| {
+            atime_update_due(policy, sec(100), sec(mtime), sec(ctime), sec(now))
+        };
+        let day = RELATIME_INTERVAL_SECS;
+        // 读过之后没有修改，一天内不再更新
+        kassert!(!due(AtimePolicy::Relatime, 50, 50, 200));
+        kassert!(due(AtimePolicy::Relatime, 50, 50, 100 + day));
+        // 修改或状态改变不早于上次读取
+        kassert!(due(AtimePolicy::Relatime, 150, 50, 200));
+        kassert!(due(AtimePolicy::Relatime, 50, 100, 200));
+
+        kassert!(due(AtimePolicy::Strict, 50, 50, 101));
+        kassert!(!due(AtimePolicy::NoAtime, 150, 50, 200));
+    });
+}
@@ -0,0 +1,227 @@
+//! 扩展属性
+//!
+//! 属性名形如 `namespace.name`，按命名空间决定权限，规则与 Linux 相同：
+//! - `user.`：只能用于普通文件和目录，读需要文件的读权限，写需要写权限；
+//! - `trusted.`：读写都需要 `CAP_SYS_ADMIN`，没有该能力时 listxattr 不列出；
+//! - `security.`：写需要文件的写权限，供安全模块和文件能力（`security.capability`）使用；
+//! - `system.`（POSIX ACL 等）和其它前缀不支持，返回 `EOPNOTSUPP`。
+//!
+//! 权限在系统调用层由 [`check_xattr_permission`] 检查，文件系统只负责存取，
+//! 可以直接用 [`XattrMap`] 保存属性。
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::kernel::{Capabilities, Credential};
+use crate::uapi::fs::{R_OK, W_OK};
+use crate::uapi::xattr::{XATTR_NAME_MAX, XATTR_SIZE_MAX, XattrFlags};
+use crate::vfs::perm::check_access;
+use crate::vfs::{FsError, InodeMetadata, InodeType};
+
+/// 扩展属性的命名空间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XattrNamespace {
+    User,
+    Trusted,
+    Security,
+}
+
+impl XattrNamespace {
+    /// 按名字前缀解析命名空间
+    ///
+    /// # 返回
+    /// - `Err(FsError::OutOfRange)`: 名字为空或超过 [`XATTR_NAME_MAX`]
+    /// - `Err(FsError::NotSupported)`: 不支持的命名空间，或前缀后没有名字
+    pub fn parse(name: &str) -> Result<Self, FsError> {
+        if name.is_empty() || name.len() > XATTR_NAME_MAX {
+            return Err(FsError::OutOfRange);
+        }
+        let (ns, suffix) = if let Some(suffix) = name.strip_prefix("user.") {
+            (Self::User, suffix)
+        } else if let Some(suffix) = name.strip_prefix("trusted.") {
+            (Self::Trusted, suffix)
+        } else if let Some(suffix) = name.strip_prefix("security.") {
+            (Self::Security, suffix)
+        } else {
+            return Err(FsError::NotSupported);
+        };
+        if suffix.is_empty() {
+            return Err(FsError::NotSupported);
+        }
+        Ok(ns)
+    }
+}
+
+/// 检查读写扩展属性 `name` 是否允许，返回其命名空间
+///
+/// # 返回
+/// - `Err(FsError::NotPermitted)`: `trusted.*` 缺少 `CAP_SYS_ADMIN`，
+///   或在普通文件和目录以外的文件上写 `user.*`
+/// - `Err(FsError::NoData)`: 在普通文件和目录以外的文件上读 `user.*`
+/// - `Err(FsError::PermissionDenied)`: 缺少文件的读写权限
+pub fn check_xattr_permission(
+    cred: &Credential,
+    meta: &InodeMetadata,
+    name: &str,
+    write: bool,
+) -> Result<XattrNamespace, FsError> {
+    let ns = XattrNamespace::parse(name)?;
+    match ns {
+        XattrNamespace::Trusted => {
+            if !cred.capabilities.has(Capabilities::SYS_ADMIN) {
+                return Err(FsError::NotPermitted);
+            }
+            return Ok(ns);
+        }
+        XattrNamespace::User
+            if !matches!(meta.inode_type, InodeType::File | InodeType::Directory) =>
+        {
+            return Err(if write {
+                FsError::NotPermitted
+            } else {
+                FsError::NoData
+            });
+        }
+        _ => {}
+    }
+    check_access(cred, meta, if write { W_OK } else { R_OK })?;
+    Ok(ns)
+}
+
+/// listxattr 是否向调用者列出 `name`
+///
+/// 没有 `CAP_SYS_ADMIN` 时隐藏 `trusted.*`。
+pub fn xattr_listable(cred: &Credential, name: &str) -> bool {
+    !name.starts_with("trusted.") || cred.capabilities.has(Capabilities::SYS_ADMIN)
+}
+
+/// 内存中的扩展属性表
+///
+/// 按名字排序保存属性，实现 setxattr 的 `XATTR_CREATE`/`XATTR_REPLACE` 语义。
+/// 可以限制所有名字和值的总长度，超出时返回 `NoSpace`。
+#[derive(Debug)]
+pub struct XattrMap {
+    entries: BTreeMap<String, Vec<u8>>,
+    /// 名字和值总长度的上限
+    limit: usize,
+    /// 当前名字和值的总长度
+    used: usize,
+}
+
+impl Default for XattrMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XattrMap {
+    /// 创建不限制总长度的属性表
+    pub const fn new() -> Self {
+        Self::with_limit(usize::MAX)
+    }
+
+    /// 创建名字和值总长度不超过 `limit` 字节的属性表
+    pub const fn with_limit(limit: usize) -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            limit,
+            used: 0,
+        }
+    }
+
+    /// 读取属性值
+    ///
+    /// # 返回
+    /// - `Err(FsError::NoData)`: 属性不存在
+    pub fn get(&self, name: &str) -> Result<Vec<u8>, FsError> {
+        self.entries.get(name).cloned().ok_or(FsError::NoData)
+    }
+
+    /// 设置属性值
+    ///
+    /// # 返回
+    /// - `Err(FsError::AlreadyExists)`: 带 `XATTR_CREATE` 且属性已存在
+    /// - `Err(FsError::NoData)`: 带 `XATTR_REPLACE` 且属性不存在
+    /// - `Err(FsError::TooBig)`: 值超过 [`XATTR_SIZE_MAX`]
+    /// - `Err(FsError::NoSpace)`: 超出总长度上限
+    pub fn set(&mut self, name: &str, value: &[u8], flags: XattrFlags) -> Result<(), FsError> {
+        if value.len() > XATTR_SIZE_MAX {
+            return Err(FsError::TooBig);
+        }
+        let old = self.entries.get(name).map(|v| name.len() + v.len());
+        match old {
+            Some(_) if flags.contains(XattrFlags::CREATE) => return Err(FsError::AlreadyExists),
+            None if flags.contains(XattrFlags::REPLACE) => return Err(FsError::NoData),
+            _ => {}
+        }
+        let used = self.used - old.unwrap_or(0) + name.len() + value.len();
+        if used > self.limit {
+            return Err(FsError::NoSpace);
+        }
+        self.entries.insert(String::from(name), value.to_vec());
+        self.used = used;
+        Ok(())
+    }
+
+    /// 删除属性
+    ///
+    /// # 返回
+    /// - `Err(FsError::NoData)`: 属性不存在
+    pub fn remove(&mut self, name: &str) -> Result<(), FsError> {
+        let value = self.entries.remove(name).ok_or(FsError::NoData)?;
+        self.used -= name.len() + value.len();
+        Ok(())
+    }
+
+    /// 按名字顺序列出所有属性名
+    pub fn list(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kassert, test_case};
+
+    test_case!(test_xattr_map_semantics, {
+        let mut map = XattrMap::new();
+        kassert!(map.get("user.a") == Err(FsError::NoData));
+        kassert!(map.set("user.a", b"1", XattrFlags::REPLACE) == Err(FsError::NoData));
+        kassert!(map.set("user.a", b"1", XattrFlags::CREATE).is_ok());
+        kassert!(map.set("user.a", b"2", XattrFlags::CREATE) == Err(FsError::AlreadyExists));
+        kassert!(map.set("user.a", b"22", XattrFlags::REPLACE).is_ok());
+        kassert!(map.get("user.a") == Ok(b"22".to_vec()));
+        kassert!(map.set("user.b", b"", XattrFlags::empty()).is_ok());
+        kassert!(map.list() == ["user.a", "user.b"]);
+
+        let big = alloc::vec![0u8; XATTR_SIZE_MAX + 1];
+        kassert!(map.set("user.c", &big, XattrFlags::empty()) == Err(FsError::TooBig));
+
+        kassert!(map.remove("user.a").is_ok());
+        kassert!(map.remove("user.a") == Err(FsError::NoData));
+        kassert!(map.list() == ["user.b"]);
+
+        // 总长度按名字加值计算，替换时先扣除旧值
+        let mut small = XattrMap::with_limit(16);
+        kassert!(small.set("user.x", b"1234", XattrFlags::empty()).is_ok());
+        kassert!(
+            small
+                .set("user.x", b"12345678", XattrFlags::empty())
+                .is_ok()
+        );
+        kassert!(small.set("user.y", b"1", XattrFlags::empty()) == Err(FsError::NoSpace));
+    });
+
+    test_case!(test_xattr_namespace, {
+        kassert!(XattrNamespace::parse("user.mime") == Ok(XattrNamespace::User));
+        kassert!(XattrNamespace::parse("trusted.x") == Ok(XattrNamespace::Trusted));
+        kassert!(XattrNamespace::parse("security.capability") == Ok(XattrNamespace::Security));
+        kassert!(XattrNamespace::parse("system.posix_acl_access") == Err(FsError::NotSupported));
+        kassert!(XattrNamespace::parse("user.") == Err(FsError::NotSupported));
+        kassert!(XattrNamespace::parse("") == Err(FsError::OutOfRange));
+        let long = "u".repeat(XATTR_NAME_MAX + 1);
+        kassert!(XattrNamespace::parse(&long) == Err(FsError::OutOfRange));
+    });
+}
@@ -0,0 +1,97 @@
+#!/usr/bin/env python3
+"""
+verity 哈希树追加工具
+
+在文件系统镜像之后原地追加 SHA-256 哈希树和超级块，供内核 `verity=` 校验层使用
+（见 os/src/device/block/verity.rs）。
+
+镜像格式：
+- 数据块: 原镜像，按校验块大小补零对齐
+- 哈希块: 顶层在前、叶子层在后；每块存放下一层 block_size/32 个摘要，不足补零
+- 超级块: 512 字节，位于镜像最后
+  "CVERITY1" (8) + 版本 (4) + 块大小 (4) + 数据块数 (8) + 哈希块数 (8) + 根哈希 (32)
+
+完成后打印根哈希，可写入启动参数 `verity=<设备名>,<根哈希>`。
+"""
+
+import argparse
+import hashlib
+import struct
+import sys
+from pathlib import Path
+
+MAGIC = b"CVERITY1"
+VERSION = 1
+SUPERBLOCK_SIZE = 512
+DIGEST_LEN = 32
+
+
+def build_levels(data_blocks: int, block_size: int) -> list:
+    """返回各层哈希块数，叶子层在前"""
+    per_block = block_size // DIGEST_LEN
+    counts = []
+    count = data_blocks
+    while True:
+        count = (count + per_block - 1) // per_block
+        counts.append(count)
+        if count <= 1:
+            return counts
+
+
+def hash_level(blocks: list, block_size: int) -> list:
+    """把一层摘要打包成哈希块"""
+    per_block = block_size // DIGEST_LEN
+    out = []
+    for i in range(0, len(blocks), per_block):
+        block = b"".join(blocks[i:i + per_block])
+        out.append(block.ljust(block_size, b"\0"))
+    return out
+
+
+def main() -> int:
+    parser = argparse.ArgumentParser(description="在镜像后追加 verity 哈希树")
+    parser.add_argument("image", type=Path, help="文件系统镜像（原地修改）")
+    parser.add_argument("--block-size", type=int, default=4096, help="校验块大小，默认 4096")
+    args = parser.parse_args()
+
+    block_size = args.block_size
+    if block_size < 512 or block_size & (block_size - 1):
+        print("Error: block size must be a power of two >= 512", file=sys.stderr)
+        return 1
+
+    data = args.image.read_bytes()
+    if len(data) % block_size:
+        data += b"\0" * (block_size - len(data) % block_size)
+    data_blocks = len(data) // block_size
+    if data_blocks == 0:
+        print("Error: image is empty", file=sys.stderr)
+        return 1
+
+    digests = [
+        hashlib.sha256(data[i:i + block_size]).digest()
+        for i in range(0, len(data), block_size)
+    ]
+    levels = []
+    for _ in build_levels(data_blocks, block_size):
+        blocks = hash_level(digests, block_size)
+        levels.append(blocks)
+        digests = [hashlib.sha256(block).digest() for block in blocks]
+    root_hash = digests[0]
+    hash_blocks = sum(len(level) for level in levels)
+
+    superblock = MAGIC + struct.pack("<IIQQ", VERSION, block_size, data_blocks, hash_blocks)
+    superblock = (superblock + root_hash).ljust(SUPERBLOCK_SIZE, b"\0")
+
+    with args.image.open("wb") as f:
+        f.write(data)
+        for level in reversed(levels):
+            f.write(b"".join(level))
+        f.write(superblock)
+
+    print(f"[Verity] {args.image}: {data_blocks} data blocks, {hash_blocks} hash blocks")
+    print(f"[Verity] root hash: {root_hash.hex()}")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
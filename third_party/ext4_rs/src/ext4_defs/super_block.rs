@@ -195,6 +195,40 @@ impl Ext4Superblock {
         }
     }
 
+    /// Returns the filesystem state flags (`s_state`).
+    pub fn state(&self) -> u16 {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: u16) {
+        self.state = state;
+    }
+
+    /// Returns the behaviour when errors are detected (`s_errors`).
+    pub fn errors(&self) -> u16 {
+        self.errors
+    }
+
+    /// Returns the head of the orphan inode list.
+    pub fn last_orphan(&self) -> u32 {
+        self.last_orphan
+    }
+
+    pub fn set_last_orphan(&mut self, inode: u32) {
+        self.last_orphan = inode;
+    }
+
+    /// Records an error occurrence in the error tracking fields.
+    pub fn record_error(&mut self, time: u32, inode: u32) {
+        self.error_count = self.error_count.saturating_add(1);
+        if self.first_error_time == 0 {
+            self.first_error_time = time;
+            self.first_error_ino = inode;
+        }
+        self.last_error_time = time;
+        self.last_error_ino = inode;
+    }
+
     pub fn decrease_free_inodes_count(&mut self) {
         self.free_inodes_count -= 1;
     }
@@ -76,6 +76,19 @@ impl Ext4 {
         }
     }
 
+    /// Applies `f` to both the in-memory superblock and the on-disk copy.
+    ///
+    /// Allocation paths write back copies of `self.super_block`, so fields
+    /// changed at runtime (state, orphan list head, error info) have to be
+    /// kept in memory as well, while the on-disk counters are left as is.
+    pub fn update_super_block(&mut self, f: impl Fn(&mut Ext4Superblock)) {
+        f(&mut self.super_block);
+        let block = Block::load(self.block_device.clone(), SUPERBLOCK_OFFSET);
+        let mut on_disk: Ext4Superblock = block.read_as();
+        f(&mut on_disk);
+        on_disk.sync_to_disk_with_csum(self.block_device.clone());
+    }
+
     // with dir result search path offset
     pub fn generic_open(
         &self,
@@ -96,6 +96,41 @@ impl Ext4 {
         }
     }
 
+    /// Collect all extents of an inode in logical block order.
+    ///
+    /// Params:
+    /// inode_ref: &Ext4InodeRef - inode reference
+    ///
+    /// Returns:
+    /// `Result<Vec<Ext4Extent>>` - leaf extents; gaps between them are holes
+    pub fn inode_extents(&self, inode_ref: &Ext4InodeRef) -> Result<Vec<Ext4Extent>> {
+        let root_data: &[u8; 60] =
+            unsafe { core::mem::transmute::<&[u32; 15], &[u8; 60]>(&inode_ref.inode.block) };
+        let root = ExtentNode::load_from_data(root_data, true)?;
+
+        let mut extents = Vec::new();
+        self.collect_extents(&root, &mut extents)?;
+        Ok(extents)
+    }
+
+    fn collect_extents(&self, node: &ExtentNode, extents: &mut Vec<Ext4Extent>) -> Result<()> {
+        let entries = node.header.entries_count as usize;
+        if node.header.depth == 0 {
+            extents.extend((0..entries).filter_map(|pos| node.get_extent(pos)));
+            return Ok(());
+        }
+
+        for pos in 0..entries {
+            let index = node.get_index(pos)?;
+            let data = self
+                .block_device
+                .read_offset(index.get_pblock() as usize * BLOCK_SIZE);
+            let child = ExtentNode::load_from_data(&data, false)?;
+            self.collect_extents(&child, extents)?;
+        }
+        Ok(())
+    }
+
     /// Insert an extent into the extent tree.
     pub fn insert_extent(
         &self,
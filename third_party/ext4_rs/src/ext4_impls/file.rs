@@ -2,6 +2,7 @@ use crate::ext4_defs::*;
 use crate::prelude::*;
 use crate::return_errno_with_message;
 use crate::utils::path_check;
+use core::cmp::max;
 // use std::time::{Duration, Instant};
 
 impl Ext4 {
@@ -299,7 +300,6 @@ impl Ext4 {
 
         // start block index
         let mut iblk_idx = iblock_start;
-        let ifile_blocks = (file_size + BLOCK_SIZE as u64 - 1) / BLOCK_SIZE as u64;
 
         // Calculate the unaligned size
         let unaligned = offset % BLOCK_SIZE;
@@ -315,20 +315,36 @@ impl Ext4 {
         // Start bgid for block allocation
         let mut start_bgid = 1;
 
-        // Pre-allocate blocks up to the write end. This keeps extended regions
-        // zero-backed and avoids sparse holes that the current extent code
-        // cannot otherwise distinguish from missing mappings.
-        let existing_file_blocks = ifile_blocks as usize;
-        let blocks_to_allocate = iblock_last.saturating_sub(existing_file_blocks);
+        // Only the unmapped blocks of the written range are allocated. Blocks
+        // outside of it stay as holes, which read back as zeros without
+        // occupying disk space.
+        let mapped_end = match self.get_last_extent(&inode_ref) {
+            Ok(extent) => extent.first_block as usize + extent.get_actual_len() as usize,
+            Err(_) => 0,
+        };
+
+        // Holes below the last extent are mapped one block at a time
+        for lblock in iblock_start..min(iblock_last, mapped_end) {
+            match self.get_pblock_idx(&inode_ref, lblock as u32) {
+                Ok(_) => {}
+                Err(e) if e.error() == Errno::ENOENT => {
+                    self.map_hole_block(&mut inode_ref, lblock as u32)?;
+                    new_blocks += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
 
-        if blocks_to_allocate > 0 {
+        // Blocks past the last extent are appended in one batch
+        let append_start = max(iblock_start, mapped_end);
+        if append_start < iblock_last {
+            let blocks_to_allocate = iblock_last - append_start;
             log::trace!("[Pre-allocation] Allocating {} blocks", blocks_to_allocate);
 
-            // 使用append_inode_pblk_batch进行批量块分配
             let allocated_blocks = self.append_inode_pblk_batch(
                 &mut inode_ref,
                 &mut start_bgid,
-                existing_file_blocks as u32,
+                append_start as u32,
                 blocks_to_allocate,
             )?;
 
@@ -338,7 +354,8 @@ impl Ext4 {
                     .write_offset(*block as usize * BLOCK_SIZE, &zero_block);
             }
 
-            // If we couldn't allocate all blocks, adjust the write size
+            // If we couldn't allocate all blocks, shorten the write to the
+            // last allocated block
             if allocated_blocks.len() < blocks_to_allocate {
                 log::trace!(
                     "[Write] Could only allocate {} out of {} blocks",
@@ -346,22 +363,8 @@ impl Ext4 {
                     blocks_to_allocate
                 );
 
-                // Calculate new write size based on allocated blocks
-                let max_write_size = allocated_blocks.len() * BLOCK_SIZE;
-                let adjusted_write_size = if unaligned > 0 {
-                    // For unaligned writes, we need to account for the unaligned portion
-                    if allocated_blocks.len() > 0 {
-                        let first_block_available = BLOCK_SIZE - unaligned;
-                        let remaining_blocks_available = (allocated_blocks.len() - 1) * BLOCK_SIZE;
-                        first_block_available + remaining_blocks_available
-                    } else {
-                        0
-                    }
-                } else {
-                    max_write_size
-                };
-
-                if adjusted_write_size == 0 {
+                let writable_end = (append_start + allocated_blocks.len()) * BLOCK_SIZE;
+                if writable_end <= offset {
                     log::error!("[Write] No space available for write after block allocation");
                     return return_errno_with_message!(
                         Errno::ENOSPC,
@@ -369,8 +372,7 @@ impl Ext4 {
                     );
                 }
 
-                // Update write size
-                write_buf_len = min(write_buf_len, adjusted_write_size);
+                write_buf_len = min(write_buf_len, writable_end - offset);
                 log::trace!(
                     "[Write] Adjusted write size from {} to {} bytes",
                     write_buf.len(),
@@ -381,26 +383,6 @@ impl Ext4 {
             new_blocks += allocated_blocks.len();
         }
 
-        // Verify we have enough blocks for the write
-        let required_blocks = (write_buf_len + BLOCK_SIZE - 1) / BLOCK_SIZE;
-        let available_blocks = if iblk_idx >= existing_file_blocks {
-            new_blocks
-        } else {
-            existing_file_blocks.saturating_sub(iblk_idx) + new_blocks
-        };
-
-        if available_blocks < required_blocks {
-            log::error!(
-                "[Write] Not enough blocks available: required {}, available {}",
-                required_blocks,
-                available_blocks
-            );
-            return return_errno_with_message!(
-                Errno::ENOSPC,
-                "Not enough blocks available for write"
-            );
-        }
-
         // Unaligned write
         if unaligned > 0 && written < write_buf_len {
             let len = min(write_buf_len, BLOCK_SIZE - unaligned);
@@ -513,32 +495,33 @@ impl Ext4 {
         }
 
         // Update file size if necessary
-        let new_size = match offset.checked_add(written) {
+        let write_end = match offset.checked_add(written) {
             Some(v) => v,
             None => return return_errno_with_message!(Errno::EINVAL, "File size overflow"),
         };
-        if new_size > file_size as usize {
+        if write_end > file_size as usize && write_end > EXT4_MAX_FILE_SIZE as usize {
+            log::error!(
+                "[Write] New file size {} exceeds maximum allowed size",
+                write_end
+            );
+            return return_errno_with_message!(Errno::EFBIG, "File size too large");
+        }
+
+        // Block allocation may have rounded the size up to a block boundary
+        let new_size = max(file_size, write_end as u64);
+        if inode_ref.inode.size() != new_size {
             log::trace!(
                 "[Write] Updating file size from {} to {}",
                 file_size,
                 new_size
             );
 
-            // Verify the new size is valid
-            if new_size > EXT4_MAX_FILE_SIZE as usize {
-                log::error!(
-                    "[Write] New file size {} exceeds maximum allowed size",
-                    new_size
-                );
-                return return_errno_with_message!(Errno::EFBIG, "File size too large");
-            }
-
-            inode_ref.inode.set_size(new_size as u64);
+            inode_ref.inode.set_size(new_size);
             self.write_back_inode(&mut inode_ref);
 
             // Verify file size update
             let verify_inode = self.get_inode_ref(inode);
-            if verify_inode.inode.size() != new_size as u64 {
+            if verify_inode.inode.size() != new_size {
                 log::error!(
                     "[Write] File size update verification failed: expected {}, got {}",
                     new_size,
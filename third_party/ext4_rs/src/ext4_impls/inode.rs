@@ -206,6 +206,36 @@ impl Ext4 {
         Ok(new_block)
     }
 
+    /// Map a single zeroed block at a hole of the inode.
+    ///
+    /// Unlike the append helpers, the inode size is left unchanged.
+    ///
+    /// Params:
+    /// inode_ref: &mut Ext4InodeRef - inode reference
+    /// iblock: Ext4Lblk - logical block id of the hole
+    ///
+    /// Returns:
+    /// `Result<Ext4Fsblk>` - physical block id of the new block
+    pub fn map_hole_block(
+        &self,
+        inode_ref: &mut Ext4InodeRef,
+        iblock: Ext4Lblk,
+    ) -> Result<Ext4Fsblk> {
+        let new_block = self.balloc_alloc_block(inode_ref, None)?;
+        self.block_device
+            .write_offset(new_block as usize * BLOCK_SIZE, &vec![0u8; BLOCK_SIZE]);
+
+        let mut newex: Ext4Extent = Ext4Extent::default();
+        newex.first_block = iblock;
+        newex.store_pblock(new_block);
+        newex.block_count = 1;
+
+        self.insert_extent(inode_ref, &mut newex)?;
+        self.write_back_inode(inode_ref);
+
+        Ok(new_block)
+    }
+
     /// Allocate a new inode
     ///
     /// Params:
@@ -429,7 +459,7 @@ impl Ext4 {
     }
 
     /// Get the last extent in the extent tree
-    fn get_last_extent(&self, inode_ref: &Ext4InodeRef) -> Result<Ext4Extent> {
+    pub(crate) fn get_last_extent(&self, inode_ref: &Ext4InodeRef) -> Result<Ext4Extent> {
         let root_header = inode_ref.inode.root_extent_header();
         if root_header.entries_count == 0 {
             return return_errno_with_message!(Errno::ENOENT, "No extents found");
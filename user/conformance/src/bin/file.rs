@@ -0,0 +1,238 @@
+//! 文件系统调用：open/read/write/lseek、截断、目录、重命名、dup、管道与符号链接
+//!
+//! 在空的当前目录中运行，除标准输入输出外没有打开的文件，新 fd 从 3 开始分配。
+
+#![no_std]
+#![no_main]
+
+#[macro_use]
+#[path = "../rt.rs"]
+mod rt;
+
+use rt::*;
+
+const O_RDONLY: usize = 0;
+const O_WRONLY: usize = 1;
+const O_RDWR: usize = 2;
+const O_CREAT: usize = 0o100;
+const O_EXCL: usize = 0o200;
+const O_TRUNC: usize = 0o1000;
+const O_APPEND: usize = 0o2000;
+const O_DIRECTORY: usize = 0o200000;
+const O_NOFOLLOW: usize = 0o400000;
+const O_CLOEXEC: usize = 0o2000000;
+const AT_REMOVEDIR: usize = 0x200;
+const SEEK_SET: usize = 0;
+const SEEK_CUR: usize = 1;
+const F_OK: usize = 0;
+const F_GETFD: usize = 1;
+
+/// `struct stat`（asm-generic），按 8 字节字段读取
+struct Stat([u64; 16]);
+
+impl Stat {
+    fn of(fd: usize) -> (isize, Self) {
+        let mut st = Stat([0; 16]);
+        let ret = sys!(SYS_FSTAT, fd, addr_mut(&mut st));
+        (ret, st)
+    }
+
+    fn mode(&self) -> isize {
+        (self.0[2] & 0xffff_ffff) as isize
+    }
+
+    fn nlink(&self) -> isize {
+        (self.0[2] >> 32) as isize
+    }
+
+    fn size(&self) -> isize {
+        self.0[6] as isize
+    }
+}
+
+fn open(path: &core::ffi::CStr, flags: usize, mode: usize) -> isize {
+    sys!(SYS_OPENAT, AT_FDCWD, path.as_ptr(), flags, mode)
+}
+
+fn read_write() {
+    check("open_missing", open(c"missing", O_RDONLY, 0));
+    let fd = open(c"f", O_RDWR | O_CREAT | O_EXCL, 0o644);
+    check("open_create", fd);
+    check(
+        "open_excl_exists",
+        open(c"f", O_RDWR | O_CREAT | O_EXCL, 0o644),
+    );
+    let fd = fd as usize;
+
+    let data = b"hello world";
+    check("write", sys!(SYS_WRITE, fd, data.as_ptr(), data.len()));
+    check("lseek_cur", sys!(SYS_LSEEK, fd, 0, SEEK_CUR));
+    check("lseek_negative", sys!(SYS_LSEEK, fd, -1isize, SEEK_SET));
+    check("lseek_bad_whence", sys!(SYS_LSEEK, fd, 0, 99));
+
+    let mut buf = [0u8; 16];
+    check("read_eof", sys!(SYS_READ, fd, buf.as_mut_ptr(), buf.len()));
+    check("pread", sys!(SYS_PREAD64, fd, buf.as_mut_ptr(), 5, 6));
+    check_true("pread_data", &buf[..5] == b"world");
+    // pread 不移动文件偏移
+    check("lseek_after_pread", sys!(SYS_LSEEK, fd, 0, SEEK_CUR));
+
+    let (ret, st) = Stat::of(fd);
+    check("fstat", ret);
+    check("fstat_size", st.size());
+    check_octal("fstat_mode", st.mode());
+    check("fstat_nlink", st.nlink());
+
+    check("ftruncate", sys!(SYS_FTRUNCATE, fd, 5));
+    check("ftruncate_size", Stat::of(fd).1.size());
+    check("ftruncate_negative", sys!(SYS_FTRUNCATE, fd, -1isize));
+
+    // O_APPEND 总是写到当前末尾，与其它 fd 的偏移无关
+    let afd = open(c"f", O_WRONLY | O_APPEND, 0);
+    check("open_append", afd);
+    let afd = afd as usize;
+    check("append_write", sys!(SYS_WRITE, afd, b"!!".as_ptr(), 2));
+    check("append_size", Stat::of(fd).1.size());
+    check(
+        "append_pread",
+        sys!(SYS_PREAD64, fd, buf.as_mut_ptr(), buf.len(), 0),
+    );
+    check_true("append_data", &buf[..7] == b"hello!!");
+    check("read_wronly", sys!(SYS_READ, afd, buf.as_mut_ptr(), 1));
+
+    let tfd = open(c"f", O_RDWR | O_TRUNC, 0);
+    check("open_trunc", tfd);
+    check("open_trunc_size", Stat::of(fd).1.size());
+    check("close_trunc", sys!(SYS_CLOSE, tfd));
+
+    let rfd = open(c"f", O_RDONLY, 0);
+    check("open_rdonly", rfd);
+    check("write_rdonly", sys!(SYS_WRITE, rfd, data.as_ptr(), 1));
+    sys!(SYS_CLOSE, rfd);
+    sys!(SYS_CLOSE, afd);
+
+    check("close", sys!(SYS_CLOSE, fd));
+    check("close_again", sys!(SYS_CLOSE, fd));
+    check("read_bad_fd", sys!(SYS_READ, 99, buf.as_mut_ptr(), 1));
+}
+
+fn directories() {
+    check("mkdir", sys!(SYS_MKDIRAT, AT_FDCWD, c"d".as_ptr(), 0o755));
+    check(
+        "mkdir_exists",
+        sys!(SYS_MKDIRAT, AT_FDCWD, c"d".as_ptr(), 0o755),
+    );
+    check("open_dir_write", open(c"d", O_WRONLY, 0));
+    check("open_through_file", open(c"f/x", O_RDONLY, 0));
+    check("open_file_as_dir", open(c"f", O_RDONLY | O_DIRECTORY, 0));
+    let dfd = open(c"d", O_RDONLY | O_DIRECTORY, 0);
+    check("open_dir", dfd);
+    sys!(SYS_CLOSE, dfd);
+
+    let fd = open(c"d/inner", O_WRONLY | O_CREAT, 0o600);
+    check("create_in_dir", fd);
+    check_octal("create_in_dir_mode", Stat::of(fd as usize).1.mode());
+    sys!(SYS_CLOSE, fd);
+
+    let unlink =
+        |path: &core::ffi::CStr, flags: usize| sys!(SYS_UNLINKAT, AT_FDCWD, path.as_ptr(), flags);
+    check("rmdir_not_empty", unlink(c"d", AT_REMOVEDIR));
+    check("unlink_dir", unlink(c"d", 0));
+    check("unlink_inner", unlink(c"d/inner", 0));
+    check("rmdir", unlink(c"d", AT_REMOVEDIR));
+    check("rmdir_file", unlink(c"f", AT_REMOVEDIR));
+    check("unlink_missing", unlink(c"missing", 0));
+}
+
+fn rename() {
+    let rename = |from: &core::ffi::CStr, to: &core::ffi::CStr| {
+        sys!(
+            SYS_RENAMEAT2,
+            AT_FDCWD,
+            from.as_ptr(),
+            AT_FDCWD,
+            to.as_ptr(),
+            0
+        )
+    };
+    let access = |path: &core::ffi::CStr| sys!(SYS_FACCESSAT, AT_FDCWD, path.as_ptr(), F_OK, 0);
+    check("rename", rename(c"f", c"g"));
+    check("access_old", access(c"f"));
+    check("access_new", access(c"g"));
+    check("rename_missing", rename(c"f", c"h"));
+}
+
+fn descriptors() {
+    let fd = open(c"g", O_RDONLY, 0);
+    check("open_for_dup", fd);
+    check("dup", sys!(SYS_DUP, fd));
+    check("dup3_same", sys!(SYS_DUP3, fd, fd, 0));
+    check("dup3", sys!(SYS_DUP3, fd, 10, 0));
+    check("dup3_getfd", sys!(SYS_FCNTL, 10, F_GETFD));
+    check("dup3_cloexec", sys!(SYS_DUP3, fd, 11, O_CLOEXEC));
+    check("dup3_cloexec_getfd", sys!(SYS_FCNTL, 11, F_GETFD));
+    for fd in [fd as usize, 4, 10, 11] {
+        sys!(SYS_CLOSE, fd);
+    }
+
+    let mut fds = [-1i32; 2];
+    check("pipe2", sys!(SYS_PIPE2, fds.as_mut_ptr(), 0));
+    check("pipe2_read_fd", fds[0] as isize);
+    check("pipe2_write_fd", fds[1] as isize);
+    let (r, w) = (fds[0] as usize, fds[1] as usize);
+    let mut buf = [0u8; 16];
+    check("pipe_write", sys!(SYS_WRITE, w, b"ping".as_ptr(), 4));
+    check("pipe_read", sys!(SYS_READ, r, buf.as_mut_ptr(), buf.len()));
+    check("pipe_lseek", sys!(SYS_LSEEK, r, 0, SEEK_SET));
+    sys!(SYS_CLOSE, w);
+    check(
+        "pipe_read_eof",
+        sys!(SYS_READ, r, buf.as_mut_ptr(), buf.len()),
+    );
+    sys!(SYS_CLOSE, r);
+}
+
+fn symlinks() {
+    check(
+        "symlink",
+        sys!(SYS_SYMLINKAT, c"g".as_ptr(), AT_FDCWD, c"l".as_ptr()),
+    );
+    let mut buf = [0u8; 64];
+    check(
+        "readlink",
+        sys!(
+            SYS_READLINKAT,
+            AT_FDCWD,
+            c"l".as_ptr(),
+            buf.as_mut_ptr(),
+            buf.len()
+        ),
+    );
+    check("open_nofollow", open(c"l", O_RDONLY | O_NOFOLLOW, 0));
+    check(
+        "readlink_not_link",
+        sys!(
+            SYS_READLINKAT,
+            AT_FDCWD,
+            c"g".as_ptr(),
+            buf.as_mut_ptr(),
+            buf.len()
+        ),
+    );
+    check(
+        "unlink_link",
+        sys!(SYS_UNLINKAT, AT_FDCWD, c"l".as_ptr(), 0),
+    );
+    check(
+        "link_target_kept",
+        sys!(SYS_FACCESSAT, AT_FDCWD, c"g".as_ptr(), F_OK, 0),
+    );
+}
+
+fn run() {
+    read_write();
+    directories();
+    rename();
+    descriptors();
+    symlinks();
+}
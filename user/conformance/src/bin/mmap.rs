@@ -0,0 +1,167 @@
+//! 内存系统调用：匿名与文件映射、参数检查、mprotect、munmap、fork 后的共享语义与 brk
+
+#![no_std]
+#![no_main]
+
+#[macro_use]
+#[path = "../rt.rs"]
+mod rt;
+
+use rt::*;
+
+const PROT_NONE: usize = 0;
+const PROT_READ: usize = 1;
+const PROT_WRITE: usize = 2;
+const MAP_SHARED: usize = 0x01;
+const MAP_PRIVATE: usize = 0x02;
+const MAP_FIXED: usize = 0x10;
+const MAP_ANONYMOUS: usize = 0x20;
+const MAP_FIXED_NOREPLACE: usize = 0x100000;
+const O_RDONLY: usize = 0;
+const O_RDWR: usize = 2;
+const O_CREAT: usize = 0o100;
+
+fn mmap(addr: usize, len: usize, prot: usize, flags: usize, fd: isize, offset: usize) -> isize {
+    sys!(SYS_MMAP, addr, len, prot, flags, fd, offset)
+}
+
+fn anon(len: usize, flags: usize) -> isize {
+    mmap(0, len, PROT_READ | PROT_WRITE, flags | MAP_ANONYMOUS, -1, 0)
+}
+
+fn anonymous() {
+    let addr = anon(2 * PAGE_SIZE, MAP_PRIVATE);
+    check_true("mmap_anon", addr > 0 && addr as usize % PAGE_SIZE == 0);
+    let p = addr as *mut u64;
+    // 匿名映射初始为零，第二页同样可写
+    check_true("mmap_anon_zeroed", unsafe { p.read_volatile() } == 0);
+    unsafe { p.add(PAGE_SIZE / 8).write_volatile(0x1234) };
+    check_true(
+        "mmap_anon_written",
+        unsafe { p.add(PAGE_SIZE / 8).read_volatile() } == 0x1234,
+    );
+
+    let addr = addr as usize;
+    check("mprotect", sys!(SYS_MPROTECT, addr, PAGE_SIZE, PROT_READ));
+    check(
+        "mprotect_unaligned",
+        sys!(SYS_MPROTECT, addr + 1, PAGE_SIZE, PROT_READ),
+    );
+    check(
+        "mmap_fixed_noreplace",
+        mmap(
+            addr,
+            PAGE_SIZE,
+            PROT_READ,
+            MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED_NOREPLACE,
+            -1,
+            0,
+        ),
+    );
+    let fixed = mmap(
+        addr,
+        PAGE_SIZE,
+        PROT_NONE,
+        MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED,
+        -1,
+        0,
+    );
+    check_true("mmap_fixed_replace", fixed == addr as isize);
+    check("munmap_unaligned", sys!(SYS_MUNMAP, addr + 1, PAGE_SIZE));
+    check("munmap_zero", sys!(SYS_MUNMAP, addr, 0));
+    check("munmap", sys!(SYS_MUNMAP, addr, 2 * PAGE_SIZE));
+    // 范围内没有映射也不是错误
+    check("munmap_again", sys!(SYS_MUNMAP, addr, 2 * PAGE_SIZE));
+}
+
+fn invalid_arguments() {
+    check("mmap_zero_length", anon(0, MAP_PRIVATE));
+    check("mmap_no_type", anon(PAGE_SIZE, 0));
+    check(
+        "mmap_bad_fd",
+        mmap(0, PAGE_SIZE, PROT_READ, MAP_PRIVATE, -1, 0),
+    );
+    check(
+        "mmap_fixed_unaligned",
+        mmap(
+            0x1000_0001,
+            PAGE_SIZE,
+            PROT_READ,
+            MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED,
+            -1,
+            0,
+        ),
+    );
+}
+
+fn file_mapping() {
+    let fd = sys!(SYS_OPENAT, AT_FDCWD, c"m".as_ptr(), O_RDWR | O_CREAT, 0o644);
+    check("open", fd);
+    let page = [b'x'; PAGE_SIZE];
+    check("write_page", sys!(SYS_WRITE, fd, page.as_ptr(), PAGE_SIZE));
+
+    let addr = mmap(0, PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+    check_true("mmap_file", addr > 0);
+    let p = addr as *mut u8;
+    check_true("mmap_file_data", unsafe { p.read_volatile() } == b'x');
+    // MAP_SHARED 的写入对 read 可见
+    unsafe { p.write_volatile(b'y') };
+    let mut byte = [0u8; 1];
+    sys!(SYS_PREAD64, fd, byte.as_mut_ptr(), 1, 0);
+    check_true("mmap_shared_visible", byte[0] == b'y');
+    sys!(SYS_MUNMAP, addr, PAGE_SIZE);
+
+    check(
+        "mmap_unaligned_offset",
+        mmap(0, PAGE_SIZE, PROT_READ, MAP_PRIVATE, fd, 1),
+    );
+    sys!(SYS_CLOSE, fd);
+
+    let fd = sys!(SYS_OPENAT, AT_FDCWD, c"m".as_ptr(), O_RDONLY, 0);
+    check(
+        "mmap_shared_write_rdonly",
+        mmap(0, PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0),
+    );
+    // 私有映射写入的是副本，不要求 fd 可写
+    let addr = mmap(0, PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_PRIVATE, fd, 0);
+    check_true("mmap_private_rdonly", addr > 0);
+    sys!(SYS_MUNMAP, addr, PAGE_SIZE);
+    sys!(SYS_CLOSE, fd);
+}
+
+/// fork 后子进程写入，父进程观察到的值
+fn value_after_child_write(flags: usize) -> isize {
+    let addr = anon(PAGE_SIZE, flags);
+    let p = addr as *mut u64;
+    let pid = fork();
+    if pid == 0 {
+        unsafe { p.write_volatile(7) };
+        exit(0);
+    }
+    wait(pid, 0);
+    let value = unsafe { p.read_volatile() } as isize;
+    sys!(SYS_MUNMAP, addr, PAGE_SIZE);
+    value
+}
+
+fn fork_sharing() {
+    check("fork_shared_anon", value_after_child_write(MAP_SHARED));
+    check("fork_private_anon", value_after_child_write(MAP_PRIVATE));
+}
+
+fn brk() {
+    let cur = sys!(SYS_BRK, 0);
+    check_true("brk_query", cur > 0);
+    let grown = sys!(SYS_BRK, cur as usize + PAGE_SIZE);
+    check_true("brk_grow", grown == cur + PAGE_SIZE as isize);
+    unsafe { (cur as *mut u8).write_volatile(1) };
+    check_true("brk_shrink", sys!(SYS_BRK, cur) == cur);
+}
+
+fn run() {
+    anonymous();
+    invalid_arguments();
+    file_mapping();
+    fork_sharing();
+    brk();
+}
@@ -0,0 +1,97 @@
+//! 进程系统调用：进程标识、fork/wait4、退出状态、kill、execve 与 umask
+//!
+//! 运行时不能有其它子进程，wait4(-1) 的结果依赖于此。
+
+#![no_std]
+#![no_main]
+
+#[macro_use]
+#[path = "../rt.rs"]
+mod rt;
+
+use rt::*;
+
+const WNOHANG: usize = 1;
+const SIGKILL: usize = 9;
+
+/// 状态字是否表示正常退出
+fn exited(status: i32) -> bool {
+    status & 0x7f == 0
+}
+
+fn exit_code(status: i32) -> isize {
+    ((status >> 8) & 0xff) as isize
+}
+
+fn term_signal(status: i32) -> isize {
+    (status & 0x7f) as isize
+}
+
+fn identity() {
+    let pid = sys!(SYS_GETPID);
+    check_true("getpid_positive", pid > 0);
+    // 单线程进程的 tid 就是 pid
+    check_true("gettid_is_pid", sys!(SYS_GETTID) == pid);
+    check_octal("umask_default", sys!(SYS_UMASK, 0o077));
+    check_octal("umask_set", sys!(SYS_UMASK, 0o022));
+}
+
+fn fork_and_wait() {
+    let parent = sys!(SYS_GETPID);
+    let pid = fork();
+    if pid == 0 {
+        exit(if sys!(SYS_GETPPID) == parent { 42 } else { 1 });
+    }
+    check_true("fork_positive", pid > 0);
+    let (ret, status) = wait(pid, 0);
+    check_true("wait4_pid", ret == pid);
+    check_true("wait4_exited", exited(status));
+    check("wait4_exit_code", exit_code(status));
+    check("wait4_no_children", wait(-1, 0).0);
+    check("wait4_nohang_no_children", wait(-1, WNOHANG).0);
+    check("wait4_not_child", wait(parent, 0).0);
+
+    // 退出码只保留低 8 位
+    let pid = fork();
+    if pid == 0 {
+        exit(300);
+    }
+    check("exit_code_truncated", exit_code(wait(pid, 0).1));
+}
+
+fn kill() {
+    let pid = fork();
+    if pid == 0 {
+        loop {
+            sys!(SYS_SCHED_YIELD);
+        }
+    }
+    // 子进程不会自己退出，WNOHANG 立即返回 0
+    check("wait4_nohang_running", wait(pid, WNOHANG).0);
+    check("kill_child", sys!(SYS_KILL, pid, SIGKILL));
+    let (ret, status) = wait(pid, 0);
+    check_true("killed_reaped", ret == pid);
+    check_true("killed_not_exited", !exited(status));
+    check("killed_signal", term_signal(status));
+
+    let me = sys!(SYS_GETPID);
+    check("kill_probe_self", sys!(SYS_KILL, me, 0));
+    check("kill_probe_missing", sys!(SYS_KILL, 0x3fff_ffff, 0));
+    check("kill_bad_signal", sys!(SYS_KILL, me, 99));
+}
+
+fn exec() {
+    let argv = [c"x".as_ptr(), core::ptr::null()];
+    let envp = [core::ptr::null::<core::ffi::c_char>()];
+    let execve =
+        |path: &core::ffi::CStr| sys!(SYS_EXECVE, path.as_ptr(), argv.as_ptr(), envp.as_ptr());
+    check("execve_missing", execve(c"/no/such/program"));
+    check("execve_directory", execve(c"/"));
+}
+
+fn run() {
+    identity();
+    fork_and_wait();
+    kill();
+    exec();
+}
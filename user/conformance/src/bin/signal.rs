@@ -0,0 +1,190 @@
+//! 信号系统调用：rt_sigaction、投递、屏蔽与挂起、SIG_IGN、SA_RESETHAND 与默认动作
+
+#![no_std]
+#![no_main]
+
+#[macro_use]
+#[path = "../rt.rs"]
+mod rt;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use rt::*;
+
+const SIGKILL: usize = 9;
+const SIGUSR1: usize = 10;
+const SIGUSR2: usize = 12;
+const SIGTERM: usize = 15;
+const SIGSTOP: usize = 19;
+const SIG_BLOCK: usize = 0;
+const SIG_UNBLOCK: usize = 1;
+const SIG_SETMASK: usize = 2;
+const SIG_DFL: usize = 0;
+const SIG_IGN: usize = 1;
+const SA_RESETHAND: usize = 0x8000_0000;
+const SIGSET_SIZE: usize = 8;
+
+/// 内核的 `struct sigaction`
+///
+/// `restorer` 和 `mask` 始终为 0：在没有 `sa_restorer` 字段的架构上 Linux 把第三个字段
+/// 当作 `sa_mask`，两种布局读到的动作相同。
+#[repr(C)]
+#[derive(Default)]
+struct SigAction {
+    handler: usize,
+    flags: usize,
+    restorer: usize,
+    mask: u64,
+}
+
+/// 处理函数被调用的次数
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+/// 处理函数执行期间的信号屏蔽字
+static MASK_IN_HANDLER: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn on_signal(_signo: usize) {
+    let mut mask: u64 = 0;
+    sys!(
+        SYS_RT_SIGPROCMASK,
+        SIG_BLOCK,
+        0,
+        addr_mut(&mut mask),
+        SIGSET_SIZE
+    );
+    MASK_IN_HANDLER.store(mask as usize, Ordering::Relaxed);
+    CALLS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn bit(signo: usize) -> u64 {
+    1 << (signo - 1)
+}
+
+fn sigaction(signo: usize, handler: usize, flags: usize) -> isize {
+    let act = SigAction {
+        handler,
+        flags,
+        ..Default::default()
+    };
+    sys!(SYS_RT_SIGACTION, signo, addr(&act), 0, SIGSET_SIZE)
+}
+
+fn current_handler(signo: usize) -> usize {
+    let mut old = SigAction::default();
+    sys!(SYS_RT_SIGACTION, signo, 0, addr_mut(&mut old), SIGSET_SIZE);
+    old.handler
+}
+
+fn sigprocmask(how: usize, set: u64) -> isize {
+    sys!(SYS_RT_SIGPROCMASK, how, addr(&set), 0, SIGSET_SIZE)
+}
+
+fn raise(signo: usize) -> isize {
+    let pid = sys!(SYS_GETPID);
+    sys!(SYS_KILL, pid, signo)
+}
+
+fn calls() -> isize {
+    CALLS.load(Ordering::Relaxed) as isize
+}
+
+fn delivery() {
+    check("sigaction", sigaction(SIGUSR1, on_signal as usize, 0));
+    check_true(
+        "sigaction_readback",
+        current_handler(SIGUSR1) == on_signal as usize,
+    );
+    check("raise", raise(SIGUSR1));
+    check("handler_calls", calls());
+    // 处理期间正在处理的信号被屏蔽，返回后恢复
+    check_true(
+        "blocked_in_handler",
+        MASK_IN_HANDLER.load(Ordering::Relaxed) as u64 & bit(SIGUSR1) != 0,
+    );
+    let mut mask: u64 = u64::MAX;
+    sys!(
+        SYS_RT_SIGPROCMASK,
+        SIG_BLOCK,
+        0,
+        addr_mut(&mut mask),
+        SIGSET_SIZE
+    );
+    check_true("unblocked_after_return", mask & bit(SIGUSR1) == 0);
+}
+
+fn masking() {
+    check("block", sigprocmask(SIG_BLOCK, bit(SIGUSR1)));
+    check("raise_blocked", raise(SIGUSR1));
+    check("handler_calls_blocked", calls());
+    let mut pending: u64 = 0;
+    check(
+        "sigpending",
+        sys!(SYS_RT_SIGPENDING, addr_mut(&mut pending), SIGSET_SIZE),
+    );
+    check_true("pending_usr1", pending & bit(SIGUSR1) != 0);
+    check("unblock", sigprocmask(SIG_UNBLOCK, bit(SIGUSR1)));
+    check("handler_calls_unblocked", calls());
+
+    // SIGKILL 和 SIGSTOP 不能被屏蔽，设置时被静默去掉
+    check(
+        "block_kill",
+        sigprocmask(SIG_BLOCK, bit(SIGKILL) | bit(SIGSTOP)),
+    );
+    let mut mask: u64 = u64::MAX;
+    sys!(
+        SYS_RT_SIGPROCMASK,
+        SIG_BLOCK,
+        0,
+        addr_mut(&mut mask),
+        SIGSET_SIZE
+    );
+    check_true(
+        "kill_unblockable",
+        mask & (bit(SIGKILL) | bit(SIGSTOP)) == 0,
+    );
+    sigprocmask(SIG_SETMASK, 0);
+
+    check("sigprocmask_bad_how", sigprocmask(3, 0));
+    check(
+        "sigprocmask_bad_size",
+        sys!(SYS_RT_SIGPROCMASK, SIG_BLOCK, 0, 0, 4),
+    );
+}
+
+fn invalid_actions() {
+    check("sigaction_kill", sigaction(SIGKILL, on_signal as usize, 0));
+    check("sigaction_stop", sigaction(SIGSTOP, SIG_IGN, 0));
+    check("sigaction_zero", sigaction(0, SIG_IGN, 0));
+    check("sigaction_too_large", sigaction(65, SIG_IGN, 0));
+    check("kill_bad_signal", raise(65));
+}
+
+fn dispositions() {
+    check("ignore", sigaction(SIGUSR2, SIG_IGN, 0));
+    check("raise_ignored", raise(SIGUSR2));
+    check("ignored_survived", 1);
+
+    let before = calls();
+    check(
+        "resethand",
+        sigaction(SIGUSR1, on_signal as usize, SA_RESETHAND),
+    );
+    check("raise_resethand", raise(SIGUSR1));
+    check("resethand_delivered", calls() - before);
+    check_true("resethand_default", current_handler(SIGUSR1) == SIG_DFL);
+
+    // 子进程对 SIGTERM 采用默认动作，被信号终止
+    let pid = fork();
+    if pid == 0 {
+        raise(SIGTERM);
+        exit(1);
+    }
+    let (_, status) = wait(pid, 0);
+    check("default_term_signal", (status & 0x7f) as isize);
+}
+
+fn run() {
+    delivery();
+    masking();
+    invalid_actions();
+    dispositions();
+}
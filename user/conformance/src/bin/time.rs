@@ -0,0 +1,225 @@
+//! 时间系统调用：时钟读取与精度、睡眠、gettimeofday、times 与 ITIMER_REAL
+
+#![no_std]
+#![no_main]
+
+#[macro_use]
+#[path = "../rt.rs"]
+mod rt;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use rt::*;
+
+const CLOCK_REALTIME: usize = 0;
+const CLOCK_MONOTONIC: usize = 1;
+const TIMER_ABSTIME: usize = 1;
+const ITIMER_REAL: usize = 0;
+const SIGALRM: usize = 14;
+const NSEC_PER_SEC: i64 = 1_000_000_000;
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct TimeSpec {
+    sec: i64,
+    nsec: i64,
+}
+
+impl TimeSpec {
+    fn valid(&self) -> bool {
+        self.sec >= 0 && (0..NSEC_PER_SEC).contains(&self.nsec)
+    }
+
+    fn as_ns(&self) -> i64 {
+        self.sec * NSEC_PER_SEC + self.nsec
+    }
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct TimeVal {
+    sec: i64,
+    usec: i64,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct ITimerVal {
+    interval: TimeVal,
+    value: TimeVal,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct SigAction {
+    handler: usize,
+    flags: usize,
+    restorer: usize,
+    mask: u64,
+}
+
+static ALARMS: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn on_alarm(_signo: usize) {
+    ALARMS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn now(clock: usize) -> TimeSpec {
+    let mut ts = TimeSpec::default();
+    sys!(SYS_CLOCK_GETTIME, clock, addr_mut(&mut ts));
+    ts
+}
+
+fn clocks() {
+    let mut ts = TimeSpec::default();
+    check(
+        "monotonic",
+        sys!(SYS_CLOCK_GETTIME, CLOCK_MONOTONIC, addr_mut(&mut ts)),
+    );
+    check_true("monotonic_valid", ts.valid());
+    check(
+        "realtime",
+        sys!(SYS_CLOCK_GETTIME, CLOCK_REALTIME, addr_mut(&mut ts)),
+    );
+    check_true("realtime_valid", ts.valid() && ts.sec > 0);
+    check(
+        "clock_bad_id",
+        sys!(SYS_CLOCK_GETTIME, 99, addr_mut(&mut ts)),
+    );
+    check("clock_null", sys!(SYS_CLOCK_GETTIME, CLOCK_MONOTONIC, 0));
+
+    check(
+        "getres",
+        sys!(SYS_CLOCK_GETRES, CLOCK_MONOTONIC, addr_mut(&mut ts)),
+    );
+    check_true("getres_valid", ts.sec == 0 && ts.nsec > 0);
+    check(
+        "getres_bad_id",
+        sys!(SYS_CLOCK_GETRES, 99, addr_mut(&mut ts)),
+    );
+
+    let first = now(CLOCK_MONOTONIC).as_ns();
+    let second = now(CLOCK_MONOTONIC).as_ns();
+    check_true("monotonic_nondecreasing", second >= first);
+
+    let mut tv = TimeVal::default();
+    check("gettimeofday", sys!(SYS_GETTIMEOFDAY, addr_mut(&mut tv), 0));
+    check_true(
+        "gettimeofday_valid",
+        tv.sec > 0 && (0..1_000_000).contains(&tv.usec),
+    );
+
+    let mut tms = [0i64; 4];
+    check_true("times", sys!(SYS_TIMES, addr_mut(&mut tms)) >= 0);
+}
+
+fn sleeping() {
+    let req = TimeSpec {
+        sec: 0,
+        nsec: 10_000_000,
+    };
+    let start = now(CLOCK_MONOTONIC).as_ns();
+    check("nanosleep", sys!(SYS_NANOSLEEP, addr(&req), 0));
+    check_true(
+        "nanosleep_elapsed",
+        now(CLOCK_MONOTONIC).as_ns() - start >= req.as_ns(),
+    );
+
+    let bad = |sec: i64, nsec: i64| {
+        let req = TimeSpec { sec, nsec };
+        sys!(SYS_NANOSLEEP, addr(&req), 0)
+    };
+    check("nanosleep_nsec_overflow", bad(0, NSEC_PER_SEC));
+    check("nanosleep_nsec_negative", bad(0, -1));
+    check("nanosleep_sec_negative", bad(-1, 0));
+
+    // 已经过去的绝对时间立即返回
+    let past = TimeSpec { sec: 0, nsec: 1 };
+    check(
+        "clock_nanosleep_past",
+        sys!(
+            SYS_CLOCK_NANOSLEEP,
+            CLOCK_MONOTONIC,
+            TIMER_ABSTIME,
+            addr(&past),
+            0
+        ),
+    );
+    check(
+        "clock_nanosleep_bad_id",
+        sys!(SYS_CLOCK_NANOSLEEP, 99, 0, addr(&req), 0),
+    );
+}
+
+fn interval_timer() {
+    let mut cur = ITimerVal::default();
+    check(
+        "getitimer",
+        sys!(SYS_GETITIMER, ITIMER_REAL, addr_mut(&mut cur)),
+    );
+    check_true(
+        "getitimer_disarmed",
+        cur.value.sec == 0 && cur.value.usec == 0,
+    );
+    check(
+        "getitimer_bad_which",
+        sys!(SYS_GETITIMER, 5, addr_mut(&mut cur)),
+    );
+
+    let act = SigAction {
+        handler: on_alarm as usize,
+        ..Default::default()
+    };
+    sys!(SYS_RT_SIGACTION, SIGALRM, addr(&act), 0, 8);
+
+    // 先设一个较长的定时器读回剩余时间，再改为 10ms 等它到期
+    let long = ITimerVal {
+        value: TimeVal { sec: 5, usec: 0 },
+        ..Default::default()
+    };
+    check(
+        "setitimer",
+        sys!(SYS_SETITIMER, ITIMER_REAL, addr(&long), 0),
+    );
+    sys!(SYS_GETITIMER, ITIMER_REAL, addr_mut(&mut cur));
+    let left = cur.value.sec * 1_000_000 + cur.value.usec;
+    check_true("getitimer_remaining", left > 0 && left <= 5_000_000);
+
+    let short = ITimerVal {
+        value: TimeVal {
+            sec: 0,
+            usec: 10_000,
+        },
+        ..Default::default()
+    };
+    let mut old = ITimerVal::default();
+    check(
+        "setitimer_replace",
+        sys!(SYS_SETITIMER, ITIMER_REAL, addr(&short), addr_mut(&mut old)),
+    );
+    check_true(
+        "setitimer_old_value",
+        old.value.sec > 0 || old.value.usec > 0,
+    );
+
+    let deadline = now(CLOCK_MONOTONIC).as_ns() + NSEC_PER_SEC;
+    while ALARMS.load(Ordering::Relaxed) == 0 && now(CLOCK_MONOTONIC).as_ns() < deadline {
+        sys!(SYS_SCHED_YIELD);
+    }
+    check("alarm_delivered", ALARMS.load(Ordering::Relaxed) as isize);
+    sys!(SYS_GETITIMER, ITIMER_REAL, addr_mut(&mut cur));
+    check_true(
+        "oneshot_disarmed",
+        cur.value.sec == 0 && cur.value.usec == 0,
+    );
+    check(
+        "setitimer_bad_which",
+        sys!(SYS_SETITIMER, 5, addr(&short), 0),
+    );
+}
+
+fn run() {
+    clocks();
+    sleeping();
+    interval_timer();
+}
@@ -0,0 +1,270 @@
+//! 一致性测试程序共用的运行时
+//!
+//! `src/bin/` 下的每个程序以 `#[path = "../rt.rs"] mod rt;` 引入本文件并提供 `fn run()`。
+//! 程序不依赖用户库：可以用 `cargo build` 构建后在 shell 中手动运行，
+//! 也由内核 `build.rs` 直接用 rustc 编译后嵌入内核，由 `kernel::conformance` 运行。
+//!
+//! 每个检查项向标准输出写一行 `名字 = 结果`：非负返回值原样输出，`-errno` 输出为
+//! 错误码的名字（如 `ENOENT`）。运行器把输出与 `expected/<程序名>.out` 逐行比较，
+//! 期望输出按 Linux 的行为编写，因此结果里不能出现 pid、地址、时间这类每次运行都
+//! 不同的值，只能输出由它们推出的确定结论（如 `1` 表示成立）。
+//!
+//! 系统调用号取自 Linux 通用表（asm-generic/unistd.h），RISC-V 与 LoongArch 相同。
+
+#![allow(dead_code)]
+
+use core::arch::{asm, global_asm};
+
+#[cfg(target_arch = "riscv64")]
+global_asm!(".globl _start", "_start:", "call main");
+#[cfg(target_arch = "loongarch64")]
+global_asm!(".globl _start", "_start:", "bl main");
+
+pub const SYS_DUP: usize = 23;
+pub const SYS_DUP3: usize = 24;
+pub const SYS_FCNTL: usize = 25;
+pub const SYS_MKDIRAT: usize = 34;
+pub const SYS_UNLINKAT: usize = 35;
+pub const SYS_SYMLINKAT: usize = 36;
+pub const SYS_FTRUNCATE: usize = 46;
+pub const SYS_FACCESSAT: usize = 48;
+pub const SYS_OPENAT: usize = 56;
+pub const SYS_CLOSE: usize = 57;
+pub const SYS_PIPE2: usize = 59;
+pub const SYS_LSEEK: usize = 62;
+pub const SYS_READ: usize = 63;
+pub const SYS_WRITE: usize = 64;
+pub const SYS_PREAD64: usize = 67;
+pub const SYS_READLINKAT: usize = 78;
+pub const SYS_FSTAT: usize = 80;
+pub const SYS_EXIT_GROUP: usize = 94;
+pub const SYS_NANOSLEEP: usize = 101;
+pub const SYS_GETITIMER: usize = 102;
+pub const SYS_SETITIMER: usize = 103;
+pub const SYS_CLOCK_GETTIME: usize = 113;
+pub const SYS_CLOCK_GETRES: usize = 114;
+pub const SYS_CLOCK_NANOSLEEP: usize = 115;
+pub const SYS_SCHED_YIELD: usize = 124;
+pub const SYS_KILL: usize = 129;
+pub const SYS_RT_SIGACTION: usize = 134;
+pub const SYS_RT_SIGPROCMASK: usize = 135;
+pub const SYS_RT_SIGPENDING: usize = 136;
+pub const SYS_TIMES: usize = 153;
+pub const SYS_UMASK: usize = 166;
+pub const SYS_GETTIMEOFDAY: usize = 169;
+pub const SYS_GETPID: usize = 172;
+pub const SYS_GETPPID: usize = 173;
+pub const SYS_GETTID: usize = 178;
+pub const SYS_BRK: usize = 214;
+pub const SYS_MUNMAP: usize = 215;
+pub const SYS_CLONE: usize = 220;
+pub const SYS_EXECVE: usize = 221;
+pub const SYS_MMAP: usize = 222;
+pub const SYS_MPROTECT: usize = 226;
+pub const SYS_WAIT4: usize = 260;
+pub const SYS_RENAMEAT2: usize = 276;
+
+pub const AT_FDCWD: isize = -100;
+pub const SIGCHLD: usize = 17;
+pub const PAGE_SIZE: usize = 4096;
+
+/// 错误码与名字，按 Linux 通用 errno 编号
+const ERRNO_NAMES: [(isize, &str); 24] = [
+    (1, "EPERM"),
+    (2, "ENOENT"),
+    (3, "ESRCH"),
+    (4, "EINTR"),
+    (5, "EIO"),
+    (9, "EBADF"),
+    (10, "ECHILD"),
+    (11, "EAGAIN"),
+    (12, "ENOMEM"),
+    (13, "EACCES"),
+    (14, "EFAULT"),
+    (17, "EEXIST"),
+    (20, "ENOTDIR"),
+    (21, "EISDIR"),
+    (22, "EINVAL"),
+    (24, "EMFILE"),
+    (28, "ENOSPC"),
+    (29, "ESPIPE"),
+    (32, "EPIPE"),
+    (34, "ERANGE"),
+    (36, "ENAMETOOLONG"),
+    (38, "ENOSYS"),
+    (39, "ENOTEMPTY"),
+    (40, "ELOOP"),
+];
+
+/// 发起系统调用，调用者负责参数中指针的有效性
+#[cfg(target_arch = "riscv64")]
+pub unsafe fn syscall(id: usize, args: [usize; 6]) -> isize {
+    let ret: isize;
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") args[0] => ret,
+            in("a1") args[1],
+            in("a2") args[2],
+            in("a3") args[3],
+            in("a4") args[4],
+            in("a5") args[5],
+            in("a7") id,
+        );
+    }
+    ret
+}
+
+/// 发起系统调用，调用者负责参数中指针的有效性
+#[cfg(target_arch = "loongarch64")]
+pub unsafe fn syscall(id: usize, args: [usize; 6]) -> isize {
+    let ret: isize;
+    unsafe {
+        asm!(
+            "syscall 0",
+            inlateout("$a0") args[0] => ret,
+            in("$a1") args[1],
+            in("$a2") args[2],
+            in("$a3") args[3],
+            in("$a4") args[4],
+            in("$a5") args[5],
+            in("$a7") id,
+        );
+    }
+    ret
+}
+
+/// 把不足 6 个的参数补零
+pub fn pack(args: &[usize]) -> [usize; 6] {
+    let mut packed = [0; 6];
+    packed[..args.len()].copy_from_slice(args);
+    packed
+}
+
+/// 宏：以任意个（不超过 6 个）参数发起系统调用
+/// Usage: sys!(SYS_OPENAT, AT_FDCWD, c"f".as_ptr(), flags, mode)
+macro_rules! sys {
+    ($nr:expr $(, $arg:expr)* $(,)?) => {
+        // Safety: 测试程序自己保证传入的指针有效
+        unsafe { $crate::rt::syscall($nr, $crate::rt::pack(&[$($arg as usize),*])) }
+    };
+}
+
+/// 结构体的地址，作为系统调用参数
+pub fn addr<T>(value: &T) -> usize {
+    value as *const T as usize
+}
+
+/// 可写结构体的地址，作为系统调用参数
+pub fn addr_mut<T>(value: &mut T) -> usize {
+    value as *mut T as usize
+}
+
+/// 退出整个进程
+pub fn exit(code: i32) -> ! {
+    sys!(SYS_EXIT_GROUP, code);
+    loop {}
+}
+
+/// 以 `clone(SIGCHLD)` 创建子进程，相当于 fork
+pub fn fork() -> isize {
+    sys!(SYS_CLONE, SIGCHLD, 0, 0, 0, 0)
+}
+
+/// 等待子进程 `pid`，返回 wait4 的返回值和状态字
+pub fn wait(pid: isize, options: usize) -> (isize, i32) {
+    let mut status: i32 = 0;
+    let ret = sys!(SYS_WAIT4, pid, addr_mut(&mut status), options, 0);
+    (ret, status)
+}
+
+fn errno_name(ret: isize) -> Option<&'static str> {
+    ERRNO_NAMES
+        .iter()
+        .find(|&&(errno, _)| errno == -ret)
+        .map(|&(_, name)| name)
+}
+
+/// 正在拼接的一行输出，整行用一次 write 写出，避免与其它进程的输出交错
+struct Line {
+    buf: [u8; 128],
+    len: usize,
+}
+
+impl Line {
+    fn new(name: &str) -> Self {
+        let mut line = Self {
+            buf: [0; 128],
+            len: 0,
+        };
+        line.push(name.as_bytes());
+        line.push(b" = ");
+        line
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        let n = bytes.len().min(self.buf.len() - 1 - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+        self.len += n;
+    }
+
+    fn push_number(&mut self, value: isize, radix: usize) {
+        if value < 0 {
+            self.push(b"-");
+        }
+        let mut n = value.unsigned_abs();
+        let mut digits = [0u8; 24];
+        let mut pos = digits.len();
+        loop {
+            pos -= 1;
+            digits[pos] = b"0123456789abcdef"[n % radix];
+            n /= radix;
+            if n == 0 {
+                break;
+            }
+        }
+        self.push(&digits[pos..]);
+    }
+
+    fn finish(mut self) {
+        self.buf[self.len] = b'\n';
+        sys!(SYS_WRITE, 1, self.buf.as_ptr(), self.len + 1);
+    }
+}
+
+fn report(name: &str, ret: isize, radix: usize) {
+    let mut line = Line::new(name);
+    match errno_name(ret) {
+        Some(errno) if ret < 0 => line.push(errno.as_bytes()),
+        _ => line.push_number(ret, radix),
+    }
+    line.finish();
+}
+
+/// 输出检查项的结果：非负值为十进制，`-errno` 为错误码名字
+pub fn check(name: &str, ret: isize) {
+    report(name, ret, 10);
+}
+
+/// 与 [`check`] 相同，但非负值以八进制输出，用于文件模式
+pub fn check_octal(name: &str, ret: isize) {
+    report(name, ret, 8);
+}
+
+/// 输出一个结论，成立为 `1`，否则为 `0`
+pub fn check_true(name: &str, cond: bool) {
+    report(name, cond as isize, 10);
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn main() -> ! {
+    crate::run();
+    exit(0)
+}
+
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    let msg = b"panic\n";
+    sys!(SYS_WRITE, 2, msg.as_ptr(), msg.len());
+    exit(101)
+}
@@ -0,0 +1,286 @@
+//! fsck_simplefs - 检查块设备上的 SimpleFS
+//!
+//! 用法：`fsck_simplefs <device>`。检查项与内核 `fs/simple_fs/image.rs` 的 `check` 相同：
+//! 镜像头魔数、版本与校验和，文件头魔数、名称长度与编码、路径分量及其长度、文件类型、
+//! 越界、重复路径，以及以普通文件为父目录的条目。第 1 版与第 2 版镜像都可检查。
+//! 只报告问题，不做修复。
+//!
+//! 退出码沿用 fsck 约定：0 无错误，4 存在未修复的错误，8 操作错误（无法打开或读取设备、
+//! 不是 SimpleFS、条目过多）。
+
+#![no_std]
+#![no_main]
+
+use core::arch::global_asm;
+
+use lib::args::Args;
+use lib::io::{eprint, print, print_usize};
+use lib::{AT_FDCWD, O_RDONLY, SEEK_SET, close, exit, lseek, openat, read};
+
+/// 以下常量与内核 `fs/simple_fs/image.rs` 保持一致
+const IMAGE_MAGIC: &[u8; 8] = b"RAMDISK\0";
+const ENTRY_MAGIC: u32 = 0x46494C45;
+const VERSION_2: u32 = 2;
+const HEADER_SIZE: usize = 32;
+const HEADER_SIZE_V1: usize = 16;
+const ENTRY_HEADER_SIZE: usize = 64;
+const ENTRY_HEADER_SIZE_V1: usize = 32;
+const VERSION_OFFSET: usize = 12;
+const CHECKSUM_OFFSET: usize = 16;
+const NAME_MAX: usize = 255;
+const FILE_TYPE_FILE: u32 = 0;
+const FILE_TYPE_DIR: u32 = 1;
+const NAME_ALIGN: usize = 4;
+const DATA_ALIGN: usize = 512;
+const MAX_PATH_LEN: usize = 4096;
+
+/// 能记录的条目数与路径总长度
+const MAX_ENTRIES: usize = 1024;
+const NAME_POOL_SIZE: usize = 64 * 1024;
+
+const EXIT_OK: i32 = 0;
+const EXIT_UNCORRECTED: i32 = 4;
+const EXIT_OPERATIONAL: i32 = 8;
+
+global_asm!(".globl _start", "_start:", "mv a0, sp", "call main");
+
+/// 已检查过的路径
+struct Seen {
+    pool: [u8; NAME_POOL_SIZE],
+    used: usize,
+    /// (路径在 pool 中的起点, 长度, 是否为目录)
+    entries: [(usize, usize, bool); MAX_ENTRIES],
+    count: usize,
+}
+
+impl Seen {
+    fn get(&self, path: &[u8]) -> Option<bool> {
+        self.entries[..self.count]
+            .iter()
+            .find(|&&(start, len, _)| &self.pool[start..start + len] == path)
+            .map(|&(_, _, is_dir)| is_dir)
+    }
+
+    fn insert(&mut self, path: &[u8], is_dir: bool) -> bool {
+        if self.count == MAX_ENTRIES || self.used + path.len() > NAME_POOL_SIZE {
+            return false;
+        }
+        self.pool[self.used..self.used + path.len()].copy_from_slice(path);
+        self.entries[self.count] = (self.used, path.len(), is_dir);
+        self.used += path.len();
+        self.count += 1;
+        true
+    }
+}
+
+struct Checker {
+    fd: usize,
+    errors: usize,
+}
+
+impl Checker {
+    /// 从 `offset` 处读满 `buf`
+    fn read_exact(&self, offset: usize, buf: &mut [u8]) -> bool {
+        if lseek(self.fd, offset as isize, SEEK_SET) != offset as isize {
+            return false;
+        }
+        unsafe { read(self.fd, buf, buf.len()) == buf.len() as isize }
+    }
+
+    fn error(&mut self, index: usize, what: &[u8], path: &[u8]) {
+        self.errors += 1;
+        print(b"entry ");
+        print_usize(index);
+        print(b": ");
+        print(what);
+        if !path.is_empty() {
+            print(b" '");
+            print(path);
+            print(b"'");
+        }
+        print(b"\n");
+    }
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn main(sp: *const usize) -> ! {
+    // SAFETY: `_start` 传入的是进程的初始栈指针
+    let args = unsafe { Args::from_stack(sp) };
+    let (Some(device), None) = (args.get_cstr(1), args.get(2)) else {
+        eprint(b"usage: fsck_simplefs <device>\n");
+        exit(EXIT_OPERATIONAL)
+    };
+
+    let fd = openat(AT_FDCWD, device.as_ptr(), O_RDONLY, 0);
+    if fd < 0 {
+        fail(device.to_bytes(), b"cannot open device");
+    }
+    let mut checker = Checker {
+        fd: fd as usize,
+        errors: 0,
+    };
+
+    let mut header = [0u8; HEADER_SIZE];
+    if !checker.read_exact(0, &mut header) {
+        fail(device.to_bytes(), b"cannot read image header");
+    }
+    if &header[..8] != IMAGE_MAGIC {
+        fail(device.to_bytes(), b"bad magic, not a SimpleFS image");
+    }
+    let file_count = le32(&header[8..12]) as usize;
+    // 第 1 版的版本字段是保留的 0
+    let (header_size, entry_header_size) = match le32(&header[VERSION_OFFSET..]) {
+        0 => (HEADER_SIZE_V1, ENTRY_HEADER_SIZE_V1),
+        VERSION_2 => {
+            let checksum = le32(&header[CHECKSUM_OFFSET..]);
+            header[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4].fill(0);
+            if crc32(&header) != checksum {
+                fail(device.to_bytes(), b"image header checksum mismatch");
+            }
+            (HEADER_SIZE, ENTRY_HEADER_SIZE)
+        }
+        _ => fail(device.to_bytes(), b"unsupported SimpleFS version"),
+    };
+
+    let mut seen = Seen {
+        pool: [0; NAME_POOL_SIZE],
+        used: 0,
+        entries: [(0, 0, false); MAX_ENTRIES],
+        count: 0,
+    };
+    let (mut files, mut dirs, mut bytes) = (0usize, 0usize, 0usize);
+    let mut name = [0u8; MAX_PATH_LEN];
+    let mut offset = header_size;
+
+    for index in 0..file_count {
+        let mut raw = [0u8; ENTRY_HEADER_SIZE];
+        let raw = &mut raw[..entry_header_size];
+        if !checker.read_exact(offset, raw) || le32(&raw[0..4]) != ENTRY_MAGIC {
+            checker.error(index, b"bad entry header, stopping", b"");
+            break;
+        }
+        let name_len = le32(&raw[4..8]) as usize;
+        let data_len = le32(&raw[8..12]) as usize;
+        let file_type = le32(&raw[12..16]);
+        if name_len == 0 || name_len > MAX_PATH_LEN {
+            checker.error(index, b"bad name length, stopping", b"");
+            break;
+        }
+
+        let name_offset = offset + entry_header_size;
+        let data_offset = name_offset + name_len.next_multiple_of(NAME_ALIGN);
+        let next = data_offset + data_len.next_multiple_of(DATA_ALIGN);
+        let path = &mut name[..name_len];
+        // 读取数据区的最后一个字节，确认条目没有越过设备末尾
+        let mut last = [0u8; 1];
+        if !checker.read_exact(name_offset, path)
+            || (next > data_offset && !checker.read_exact(next - 1, &mut last))
+        {
+            checker.error(index, b"entry extends past end of device, stopping", b"");
+            break;
+        }
+        offset = next;
+        let path = &*path;
+
+        if core::str::from_utf8(path).is_err() {
+            checker.error(index, b"name is not UTF-8", b"");
+            continue;
+        }
+        if path
+            .split(|&b| b == b'/')
+            .any(|c| c.is_empty() || c == b"." || c == b"..")
+        {
+            checker.error(index, b"invalid path", path);
+            continue;
+        }
+        if path.split(|&b| b == b'/').any(|c| c.len() > NAME_MAX) {
+            checker.error(index, b"name longer than 255 bytes", path);
+            continue;
+        }
+        let is_dir = match file_type {
+            FILE_TYPE_FILE => false,
+            FILE_TYPE_DIR => true,
+            _ => {
+                checker.error(index, b"unknown file type", path);
+                continue;
+            }
+        };
+        if is_dir && data_len != 0 {
+            checker.error(index, b"directory has data", path);
+        }
+        if seen.get(path).is_some() {
+            checker.error(index, b"duplicate path", path);
+            continue;
+        }
+        let parent_is_file = path
+            .iter()
+            .enumerate()
+            .filter(|&(_, &b)| b == b'/')
+            .any(|(end, _)| seen.get(&path[..end]) == Some(false));
+        if parent_is_file {
+            checker.error(index, b"parent is a regular file", path);
+        }
+
+        if !seen.insert(path, is_dir) {
+            fail(device.to_bytes(), b"too many entries to check");
+        }
+        if is_dir {
+            dirs += 1;
+        } else {
+            files += 1;
+            bytes += data_len;
+        }
+    }
+    close(checker.fd);
+
+    print(device.to_bytes());
+    print(b": ");
+    print_usize(files);
+    print(b" files, ");
+    print_usize(dirs);
+    print(b" directories, ");
+    print_usize(bytes);
+    print(b" bytes, ");
+    print_usize(checker.errors);
+    print(b" errors\n");
+    exit(if checker.errors == 0 {
+        EXIT_OK
+    } else {
+        EXIT_UNCORRECTED
+    })
+}
+
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// CRC-32（IEEE 802.3），与内核 `util::crc32` 相同
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn fail(device: &[u8], what: &[u8]) -> ! {
+    eprint(b"fsck_simplefs: ");
+    eprint(device);
+    eprint(b": ");
+    eprint(what);
+    eprint(b"\n");
+    exit(EXIT_OPERATIONAL)
+}
+
+#[cfg(not(test))]
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    exit(-1)
+}
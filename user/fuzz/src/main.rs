@@ -0,0 +1,817 @@
+//! 随机系统调用模糊测试程序
+//!
+//! 以对抗性参数反复发起随机系统调用：空指针、野指针、指向内核地址和只读代码段的指针、
+//! 越过映射末尾的缓冲区、超大长度和无效 fd，用来发现系统调用路径上遗漏的用户指针与
+//! 参数检查。无论参数如何，内核都只应返回错误码，不能 panic 或挂起。
+//!
+//! 用法：`fuzz <seed> <calls> [-v]`。种子相同则调用序列相同；`-v` 在每次调用前输出
+//! 调用名和参数，复现时可以看到最后一个发出的调用。内核命令行带 `fuzz` 时由
+//! `kernel::fuzz` 以无特权用户在 tmpfs 沙箱中运行。也可以在 shell 中手动运行，但应以
+//! 普通用户在空目录中运行，程序会在当前目录下随意创建、改名和删除文件。
+//!
+//! 为了让一次运行能够跑完，退出、创建进程、发送信号、修改信号处理和屏蔽字、修改间隔
+//! 定时器和资源限制、调度策略以及 reboot 不在候选表中；内存管理调用只作用于程序预留的
+//! 区域，不会拆掉自己的代码、栈和数据。阻塞的调用由周期性的 SIGALRM 打断。
+//!
+//! 系统调用号取自 Linux 通用表（asm-generic/unistd.h），RISC-V 与 LoongArch 相同。
+
+#![no_std]
+#![no_main]
+
+use core::arch::{asm, global_asm};
+use core::ffi::CStr;
+
+#[cfg(target_arch = "riscv64")]
+global_asm!(".globl _start", "_start:", "mv a0, sp", "call main");
+#[cfg(target_arch = "loongarch64")]
+global_asm!(".globl _start", "_start:", "move $a0, $sp", "bl main");
+
+const SYS_MKDIRAT: usize = 34;
+const SYS_SYMLINKAT: usize = 36;
+const SYS_OPENAT: usize = 56;
+const SYS_CLOSE: usize = 57;
+const SYS_PIPE2: usize = 59;
+const SYS_WRITE: usize = 64;
+const SYS_EXIT_GROUP: usize = 94;
+const SYS_SETITIMER: usize = 103;
+const SYS_RT_SIGACTION: usize = 134;
+const SYS_GETPID: usize = 172;
+const SYS_SOCKET: usize = 198;
+const SYS_MMAP: usize = 222;
+const SYS_MPROTECT: usize = 226;
+
+const AT_FDCWD: isize = -100;
+const O_RDONLY: usize = 0;
+const O_RDWR: usize = 2;
+const O_CREAT: usize = 0o100;
+const O_DIRECTORY: usize = 0o200000;
+const AF_UNIX: usize = 1;
+const AF_INET: usize = 2;
+const SOCK_STREAM: usize = 1;
+const SOCK_DGRAM: usize = 2;
+const PROT_NONE: usize = 0;
+const PROT_READ_WRITE: usize = 0x3;
+const MAP_PRIVATE_ANONYMOUS: usize = 0x22;
+const ITIMER_REAL: usize = 0;
+const SIGALRM: usize = 14;
+const SIG_IGN: usize = 1;
+const PATH_MAX: usize = 4096;
+
+/// 默认动作为终止、但只可能由被测调用的副作用产生的信号，一律忽略
+const IGNORED_SIGNALS: [usize; 18] = [
+    1, 2, 3, 10, 12, 13, 15, 16, 20, 21, 22, 24, 25, 26, 27, 29, 30, 31,
+];
+
+const PAGE_SIZE: usize = 4096;
+/// 作为缓冲区传给内核的数据区，其后紧跟一个不可访问的保护页
+const DATA_SIZE: usize = 4 * PAGE_SIZE;
+/// 内存管理调用的目标区域；实际预留两倍大小，使任何 `地址 + 长度` 都落在预留范围内
+const MAP_SIZE: usize = 16 * PAGE_SIZE;
+/// 记住的最近创建的 fd 个数
+const MAX_FDS: usize = 32;
+/// 每隔这么多次调用重新填充数据区
+const REFILL_INTERVAL: usize = 64;
+/// 每隔这么多次调用关闭所有 fd 并重建初始文件
+const RESET_INTERVAL: usize = 1024;
+/// 重建时关闭的 fd 上界
+const RESET_MAX_FD: usize = 256;
+/// 打断阻塞调用的 SIGALRM 周期
+const ALARM_INTERVAL_US: i64 = 20_000;
+
+/// 用户地址空间之外或不可能被映射的地址
+const WILD_ADDRS: [usize; 8] = [
+    1,
+    PAGE_SIZE - 1,
+    0x0000_0040_0000_0000 - 8,     // 用户地址空间的最后一个字
+    0x0000_0040_0000_0000,         // Sv39 用户地址空间之外
+    0x0000_8000_0000_0000,         // 48 位用户地址空间之外
+    0x9000_0000_0020_0000,         // LoongArch 内核直接映射窗口
+    0xffff_ffc0_8020_0000,         // RISC-V 内核映像
+    usize::MAX & !(PAGE_SIZE - 1), // 最高的页
+];
+
+const LENGTHS: [usize; 15] = [
+    0,
+    1,
+    7,
+    8,
+    16,
+    64,
+    PAGE_SIZE - 1,
+    PAGE_SIZE,
+    PAGE_SIZE + 1,
+    DATA_SIZE,
+    DATA_SIZE + 1,
+    1 << 31,
+    u32::MAX as usize,
+    isize::MAX as usize,
+    usize::MAX,
+];
+
+const INTS: [usize; 12] = [
+    0,
+    1,
+    2,
+    3,
+    -1isize as usize,
+    AT_FDCWD as usize,
+    i32::MAX as usize,
+    i32::MIN as isize as usize,
+    u32::MAX as usize,
+    isize::MAX as usize,
+    1 << 63,
+    PAGE_SIZE,
+];
+
+const BAD_FDS: [isize; 7] = [
+    -1,
+    -2,
+    1023,
+    1024,
+    65536,
+    i32::MAX as isize,
+    i32::MIN as isize,
+];
+
+/// 沙箱中的初始文件名和其它常见路径形式
+const PATHS: [&CStr; 14] = [
+    c"f", c"d", c"d/f", c"d/", c"l", c"/", c".", c"..", c"", c"f/", c"../../f", c"/d/../f", c"p",
+    c"s",
+];
+
+/// 参数的生成方式
+#[derive(Clone, Copy)]
+enum Arg {
+    /// 最近创建的 fd、`AT_FDCWD` 或无效 fd；不会是标准输入输出
+    Fd,
+    /// 指向数据区、内存管理区域、代码段或野地址的指针
+    Ptr,
+    /// 合法路径、超长路径、没有结尾 NUL 的路径或任意指针
+    Path,
+    /// 缓冲区长度
+    Len,
+    /// 内存管理区域中的地址或野地址
+    MapAddr,
+    /// 不会越出内存管理区域预留范围的长度或溢出的长度
+    MapLen,
+    /// 随机位组合
+    Flags,
+    /// 边界整数
+    Int,
+    /// 自身、init、不存在的进程和特殊的 pid 值
+    Pid,
+}
+
+use Arg::*;
+
+/// 候选的系统调用
+struct Call {
+    nr: usize,
+    name: &'static str,
+    args: &'static [Arg],
+    /// 成功时返回新的 fd
+    creates_fd: bool,
+}
+
+const fn call(nr: usize, name: &'static str, args: &'static [Arg]) -> Call {
+    Call {
+        nr,
+        name,
+        args,
+        creates_fd: false,
+    }
+}
+
+const fn call_fd(nr: usize, name: &'static str, args: &'static [Arg]) -> Call {
+    Call {
+        nr,
+        name,
+        args,
+        creates_fd: true,
+    }
+}
+
+const CALLS: &[Call] = &[
+    call(5, "setxattr", &[Path, Path, Ptr, Len, Flags]),
+    call(8, "getxattr", &[Path, Path, Ptr, Len]),
+    call(11, "listxattr", &[Path, Ptr, Len]),
+    call(14, "removexattr", &[Path, Path]),
+    call(17, "getcwd", &[Ptr, Len]),
+    call_fd(23, "dup", &[Fd]),
+    call_fd(24, "dup3", &[Fd, Fd, Flags]),
+    call(25, "fcntl", &[Fd, Int, Int]),
+    call(29, "ioctl", &[Fd, Int, Ptr]),
+    call(33, "mknodat", &[Fd, Path, Flags, Int]),
+    call(34, "mkdirat", &[Fd, Path, Int]),
+    call(35, "unlinkat", &[Fd, Path, Flags]),
+    call(36, "symlinkat", &[Path, Fd, Path]),
+    call(37, "linkat", &[Fd, Path, Fd, Path, Flags]),
+    call(39, "umount2", &[Path, Flags]),
+    call(40, "mount", &[Path, Path, Path, Flags, Ptr]),
+    call(43, "statfs", &[Path, Ptr]),
+    call(46, "ftruncate", &[Fd, Len]),
+    call(48, "faccessat", &[Fd, Path, Int, Flags]),
+    call(49, "chdir", &[Path]),
+    call(50, "fchdir", &[Fd]),
+    call(53, "fchmodat", &[Fd, Path, Int]),
+    call(54, "fchownat", &[Fd, Path, Int, Int, Flags]),
+    call_fd(56, "openat", &[Fd, Path, Flags, Int]),
+    call(57, "close", &[Fd]),
+    call(59, "pipe2", &[Ptr, Flags]),
+    call(61, "getdents64", &[Fd, Ptr, Len]),
+    call(62, "lseek", &[Fd, Int, Int]),
+    call(63, "read", &[Fd, Ptr, Len]),
+    call(64, "write", &[Fd, Ptr, Len]),
+    call(65, "readv", &[Fd, Ptr, Len]),
+    call(66, "writev", &[Fd, Ptr, Len]),
+    call(67, "pread64", &[Fd, Ptr, Len, Int]),
+    call(68, "pwrite64", &[Fd, Ptr, Len, Int]),
+    call(69, "preadv", &[Fd, Ptr, Len, Int]),
+    call(70, "pwritev", &[Fd, Ptr, Len, Int]),
+    call(71, "sendfile", &[Fd, Fd, Ptr, Len]),
+    call(72, "pselect6", &[Int, Ptr, Ptr, Ptr, Ptr, Ptr]),
+    call(73, "ppoll", &[Ptr, Len, Ptr, Ptr]),
+    call(78, "readlinkat", &[Fd, Path, Ptr, Len]),
+    call(79, "newfstatat", &[Fd, Path, Ptr, Flags]),
+    call(80, "fstat", &[Fd, Ptr]),
+    call(82, "fsync", &[Fd]),
+    call(88, "utimensat", &[Fd, Path, Ptr, Flags]),
+    call(90, "capget", &[Ptr, Ptr]),
+    call(91, "capset", &[Ptr, Ptr]),
+    call(96, "set_tid_address", &[Ptr]),
+    call(98, "futex", &[Ptr, Int, Int, Ptr, Ptr, Int]),
+    call(99, "set_robust_list", &[Ptr, Len]),
+    call(100, "get_robust_list", &[Pid, Ptr, Ptr]),
+    call(101, "nanosleep", &[Ptr, Ptr]),
+    call(102, "getitimer", &[Int, Ptr]),
+    call(112, "clock_settime", &[Int, Ptr]),
+    call(113, "clock_gettime", &[Int, Ptr]),
+    call(114, "clock_getres", &[Int, Ptr]),
+    call(115, "clock_nanosleep", &[Int, Flags, Ptr, Ptr]),
+    call(116, "syslog", &[Int, Ptr, Len]),
+    call(121, "sched_getparam", &[Pid, Ptr]),
+    call(122, "sched_setaffinity", &[Pid, Len, Ptr]),
+    call(123, "sched_getaffinity", &[Pid, Len, Ptr]),
+    call(124, "sched_yield", &[]),
+    call(136, "rt_sigpending", &[Ptr, Len]),
+    call(137, "rt_sigtimedwait", &[Ptr, Ptr, Ptr, Len]),
+    call(144, "setgid", &[Int]),
+    call(146, "setuid", &[Int]),
+    call(147, "setresuid", &[Int, Int, Int]),
+    call(148, "getresuid", &[Ptr, Ptr, Ptr]),
+    call(153, "times", &[Ptr]),
+    call(154, "setpgid", &[Pid, Pid]),
+    call(158, "getgroups", &[Int, Ptr]),
+    call(159, "setgroups", &[Len, Ptr]),
+    call(160, "uname", &[Ptr]),
+    call(161, "sethostname", &[Ptr, Len]),
+    call(163, "getrlimit", &[Int, Ptr]),
+    call(165, "getrusage", &[Int, Ptr]),
+    call(166, "umask", &[Int]),
+    call(167, "prctl", &[Int, Ptr, Int, Int, Int]),
+    call(169, "gettimeofday", &[Ptr, Ptr]),
+    call(170, "settimeofday", &[Ptr, Ptr]),
+    call(171, "adjtimex", &[Ptr]),
+    call(179, "sysinfo", &[Ptr]),
+    call_fd(180, "mq_open", &[Path, Flags, Int, Ptr]),
+    call(181, "mq_unlink", &[Path]),
+    call(182, "mq_timedsend", &[Fd, Ptr, Len, Int, Ptr]),
+    call(183, "mq_timedreceive", &[Fd, Ptr, Len, Ptr, Ptr]),
+    call(185, "mq_getsetattr", &[Fd, Ptr, Ptr]),
+    call(194, "shmget", &[Int, Len, Flags]),
+    call(195, "shmctl", &[Int, Int, Ptr]),
+    call(196, "shmat", &[Int, MapAddr, Flags]),
+    call(197, "shmdt", &[MapAddr]),
+    call_fd(198, "socket", &[Int, Int, Int]),
+    call(199, "socketpair", &[Int, Int, Int, Ptr]),
+    call(200, "bind", &[Fd, Ptr, Len]),
+    call(201, "listen", &[Fd, Int]),
+    call_fd(202, "accept", &[Fd, Ptr, Ptr]),
+    call(203, "connect", &[Fd, Ptr, Len]),
+    call(204, "getsockname", &[Fd, Ptr, Ptr]),
+    call(205, "getpeername", &[Fd, Ptr, Ptr]),
+    call(206, "sendto", &[Fd, Ptr, Len, Flags, Ptr, Len]),
+    call(207, "recvfrom", &[Fd, Ptr, Len, Flags, Ptr, Ptr]),
+    call(208, "setsockopt", &[Fd, Int, Int, Ptr, Len]),
+    call(209, "getsockopt", &[Fd, Int, Int, Ptr, Ptr]),
+    call(210, "shutdown", &[Fd, Int]),
+    call(214, "brk", &[Ptr]),
+    call(215, "munmap", &[MapAddr, MapLen]),
+    call(221, "execve", &[Path, Ptr, Ptr]),
+    call(222, "mmap", &[MapAddr, MapLen, Flags, Flags, Fd, Int]),
+    call(224, "swapon", &[Path, Flags]),
+    call(225, "swapoff", &[Path]),
+    call(226, "mprotect", &[MapAddr, MapLen, Flags]),
+    call(228, "mlock", &[MapAddr, MapLen]),
+    call(229, "munlock", &[MapAddr, MapLen]),
+    call(233, "madvise", &[MapAddr, MapLen, Int]),
+    call(236, "get_mempolicy", &[Ptr, Ptr, Len, Ptr, Flags]),
+    call_fd(242, "accept4", &[Fd, Ptr, Ptr, Flags]),
+    call(260, "wait4", &[Pid, Ptr, Flags, Ptr]),
+    call(267, "syncfs", &[Fd]),
+    call(276, "renameat2", &[Fd, Path, Fd, Path, Flags]),
+    call(278, "getrandom", &[Ptr, Len, Flags]),
+    call_fd(279, "memfd_create", &[Path, Flags]),
+    call(291, "statx", &[Fd, Path, Flags, Int, Ptr]),
+    call_fd(425, "io_uring_setup", &[Len, Ptr]),
+    call(426, "io_uring_enter", &[Fd, Len, Len, Flags, Ptr, Len]),
+];
+
+#[repr(C)]
+struct SigAction {
+    handler: usize,
+    flags: usize,
+    restorer: usize,
+    mask: u64,
+}
+
+#[repr(C)]
+struct ITimerVal {
+    interval: [i64; 2],
+    value: [i64; 2],
+}
+
+#[cfg(target_arch = "riscv64")]
+unsafe fn syscall(id: usize, args: [usize; 6]) -> isize {
+    let ret: isize;
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") args[0] => ret,
+            in("a1") args[1],
+            in("a2") args[2],
+            in("a3") args[3],
+            in("a4") args[4],
+            in("a5") args[5],
+            in("a7") id,
+        );
+    }
+    ret
+}
+
+#[cfg(target_arch = "loongarch64")]
+unsafe fn syscall(id: usize, args: [usize; 6]) -> isize {
+    let ret: isize;
+    unsafe {
+        asm!(
+            "syscall 0",
+            inlateout("$a0") args[0] => ret,
+            in("$a1") args[1],
+            in("$a2") args[2],
+            in("$a3") args[3],
+            in("$a4") args[4],
+            in("$a5") args[5],
+            in("$a7") id,
+        );
+    }
+    ret
+}
+
+fn pack(args: &[usize]) -> [usize; 6] {
+    let mut packed = [0; 6];
+    packed[..args.len()].copy_from_slice(args);
+    packed
+}
+
+/// 宏：以任意个（不超过 6 个）参数发起系统调用
+macro_rules! sys {
+    ($nr:expr $(, $arg:expr)* $(,)?) => {
+        // Safety: 初始化代码传入的都是有效指针
+        unsafe { syscall($nr, pack(&[$($arg as usize),*])) }
+    };
+}
+
+fn exit(code: i32) -> ! {
+    sys!(SYS_EXIT_GROUP, code);
+    loop {}
+}
+
+/// 正在拼接的一行输出，整行用一次 write 写到标准输出
+struct Line {
+    buf: [u8; 160],
+    len: usize,
+}
+
+impl Line {
+    fn new() -> Self {
+        Self {
+            buf: [0; 160],
+            len: 0,
+        }
+    }
+
+    fn str(mut self, s: &str) -> Self {
+        let n = s.len().min(self.buf.len() - 1 - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        self
+    }
+
+    fn num(self, mut n: usize, radix: usize) -> Self {
+        let mut digits = [0u8; 24];
+        let mut pos = digits.len();
+        loop {
+            pos -= 1;
+            digits[pos] = b"0123456789abcdef"[n % radix];
+            n /= radix;
+            if n == 0 {
+                break;
+            }
+        }
+        let prefix = if radix == 16 { "0x" } else { "" };
+        // Safety: digits 中只有 ASCII 数字和字母
+        self.str(prefix)
+            .str(unsafe { core::str::from_utf8_unchecked(&digits[pos..]) })
+    }
+
+    fn print(mut self) {
+        self.buf[self.len] = b'\n';
+        sys!(SYS_WRITE, 1, self.buf.as_ptr(), self.len + 1);
+    }
+}
+
+/// xorshift64*，序列只由种子决定
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // 全零状态会一直输出零
+        Self(if seed == 0 {
+            0x9e37_79b9_7f4a_7c15
+        } else {
+            seed
+        })
+    }
+
+    fn next(&mut self) -> usize {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d) as usize
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.next() % n
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+}
+
+struct Fuzzer {
+    rng: Rng,
+    pid: usize,
+    /// 数据区起始地址
+    data: usize,
+    /// 内存管理区域起始地址
+    map: usize,
+    /// 最近创建的 fd，环形覆盖
+    fds: [usize; MAX_FDS],
+    nfds: usize,
+}
+
+impl Fuzzer {
+    fn fd(&mut self) -> usize {
+        match self.rng.below(8) {
+            0..=3 if self.nfds > 0 => self.fds[self.rng.below(self.nfds.min(MAX_FDS))],
+            4 | 5 => AT_FDCWD as usize,
+            _ => self.rng.pick(&BAD_FDS) as usize,
+        }
+    }
+
+    fn ptr(&mut self) -> usize {
+        match self.rng.below(12) {
+            0 => 0,
+            1..=4 => self.data + (self.rng.below(DATA_SIZE) & !7),
+            5 => self.data + self.rng.below(DATA_SIZE),
+            // 数据区末尾，长度稍大就会越过保护页
+            6 => self.data + DATA_SIZE - self.rng.below(64),
+            7 | 8 => self.map + self.rng.below(MAP_SIZE),
+            // 只读的代码段
+            9 => main as *const () as usize,
+            _ => self.rng.pick(&WILD_ADDRS),
+        }
+    }
+
+    fn path(&mut self) -> usize {
+        match self.rng.below(10) {
+            0..=5 => self.rng.pick(&PATHS).as_ptr() as usize,
+            6 => self.long_path(),
+            7 => self.unterminated_path(),
+            _ => self.ptr(),
+        }
+    }
+
+    /// 在数据区开头写一个超过 PATH_MAX 的路径
+    fn long_path(&mut self) -> usize {
+        let buf = self.data as *mut u8;
+        for i in 0..PATH_MAX + 64 {
+            let byte = if i % 8 == 7 { b'/' } else { b'a' };
+            // Safety: 数据区可写，且 PATH_MAX + 65 不超过 DATA_SIZE
+            unsafe { buf.add(i).write_volatile(byte) };
+        }
+        unsafe { buf.add(PATH_MAX + 64).write_volatile(0) };
+        self.data
+    }
+
+    /// 数据区末尾没有 NUL 的字符串，读取会越过保护页
+    fn unterminated_path(&mut self) -> usize {
+        let start = self.data + DATA_SIZE - 64;
+        for i in 0..64 {
+            // Safety: 在数据区范围内
+            unsafe { ((start + i) as *mut u8).write_volatile(b'a') };
+        }
+        start
+    }
+
+    fn len(&mut self) -> usize {
+        match self.rng.below(4) {
+            0 => self.rng.below(256),
+            _ => self.rng.pick(&LENGTHS),
+        }
+    }
+
+    fn map_addr(&mut self) -> usize {
+        match self.rng.below(8) {
+            0 => 0,
+            1 => self.rng.pick(&WILD_ADDRS),
+            2 => self.map + self.rng.below(MAP_SIZE),
+            _ => self.map + self.rng.below(MAP_SIZE / PAGE_SIZE) * PAGE_SIZE,
+        }
+    }
+
+    fn map_len(&mut self) -> usize {
+        match self.rng.below(8) {
+            0 => 0,
+            1 => self.rng.pick(&[
+                1,
+                PAGE_SIZE + 1,
+                isize::MAX as usize,
+                usize::MAX - PAGE_SIZE + 2,
+                1 << 63,
+            ]),
+            _ => (1 + self.rng.below(MAP_SIZE / PAGE_SIZE)) * PAGE_SIZE,
+        }
+    }
+
+    fn flags(&mut self) -> usize {
+        match self.rng.below(4) {
+            0 => 0,
+            1 => 1 << self.rng.below(32),
+            2 => self.rng.next() as u32 as usize,
+            _ => self.rng.below(0x1000),
+        }
+    }
+
+    fn int(&mut self) -> usize {
+        match self.rng.below(4) {
+            0 => self.rng.below(64),
+            1 => self.rng.next(),
+            _ => self.rng.pick(&INTS),
+        }
+    }
+
+    fn pid(&mut self) -> usize {
+        let pid = self.pid as isize;
+        self.rng
+            .pick(&[0, -1, pid, -pid, 1, 0x3fff_ffff, i32::MIN as isize]) as usize
+    }
+
+    fn arg(&mut self, kind: Arg) -> usize {
+        match kind {
+            Fd => self.fd(),
+            Ptr => self.ptr(),
+            Path => self.path(),
+            Len => self.len(),
+            MapAddr => self.map_addr(),
+            MapLen => self.map_len(),
+            Flags => self.flags(),
+            Int => self.int(),
+            Pid => self.pid(),
+        }
+    }
+
+    /// 用指针、长度和边界整数填满数据区，使内核读到的 iovec、msghdr、timespec
+    /// 等结构本身也带有对抗性的内容
+    fn refill(&mut self) {
+        for i in 0..DATA_SIZE / 8 {
+            let word = match self.rng.below(4) {
+                0 => self.ptr(),
+                1 => self.len(),
+                2 => self.int(),
+                _ => self.rng.next(),
+            };
+            // Safety: 在数据区范围内
+            unsafe { (self.data as *mut usize).add(i).write_volatile(word) };
+        }
+    }
+
+    fn track(&mut self, fd: isize) {
+        // 标准输入输出不参与测试
+        if fd > 2 {
+            self.fds[self.nfds % MAX_FDS] = fd as usize;
+            self.nfds += 1;
+        }
+    }
+
+    /// 关闭所有 fd 并在当前目录重建初始文件：普通文件、目录、符号链接、管道和套接字
+    fn reset(&mut self) {
+        for fd in 3..RESET_MAX_FD {
+            sys!(SYS_CLOSE, fd);
+        }
+        self.nfds = 0;
+
+        sys!(SYS_MKDIRAT, AT_FDCWD, c"d".as_ptr(), 0o755);
+        sys!(SYS_SYMLINKAT, c"f".as_ptr(), AT_FDCWD, c"l".as_ptr());
+        for path in [c"f", c"d/f"] {
+            let fd = sys!(SYS_OPENAT, AT_FDCWD, path.as_ptr(), O_RDWR | O_CREAT, 0o644);
+            sys!(SYS_WRITE, fd, path.as_ptr(), path.count_bytes());
+            self.track(fd);
+        }
+        let fd = sys!(
+            SYS_OPENAT,
+            AT_FDCWD,
+            c"d".as_ptr(),
+            O_RDONLY | O_DIRECTORY,
+            0
+        );
+        self.track(fd);
+        let mut pipe = [0i32; 2];
+        if sys!(SYS_PIPE2, pipe.as_mut_ptr(), 0) == 0 {
+            self.track(pipe[0] as isize);
+            self.track(pipe[1] as isize);
+        }
+        let fd = sys!(SYS_SOCKET, AF_UNIX, SOCK_STREAM, 0);
+        self.track(fd);
+        let fd = sys!(SYS_SOCKET, AF_INET, SOCK_DGRAM, 0);
+        self.track(fd);
+    }
+
+    fn step(&mut self, index: usize, verbose: bool) {
+        let call = &CALLS[self.rng.below(CALLS.len())];
+        let mut args = [0usize; 6];
+        for (arg, &kind) in args.iter_mut().zip(call.args) {
+            *arg = self.arg(kind);
+        }
+        if verbose {
+            let mut line = Line::new().num(index, 10).str(" ").str(call.name).str("(");
+            for (i, &arg) in args[..call.args.len()].iter().enumerate() {
+                if i > 0 {
+                    line = line.str(", ");
+                }
+                line = line.num(arg, 16);
+            }
+            line.str(")").print();
+        }
+        // Safety: 被测调用只会通过内核访问这些参数，程序自己的代码、栈和数据不在
+        // 内存管理调用的作用范围内
+        let ret = unsafe { syscall(call.nr, args) };
+        if call.creates_fd {
+            self.track(ret);
+        }
+    }
+}
+
+/// 忽略副作用产生的信号，并让周期性的 SIGALRM 打断阻塞的调用
+fn setup_signals() {
+    extern "C" fn on_alarm(_signo: usize) {}
+
+    let ignore = SigAction {
+        handler: SIG_IGN,
+        flags: 0,
+        restorer: 0,
+        mask: 0,
+    };
+    for signo in IGNORED_SIGNALS {
+        sys!(SYS_RT_SIGACTION, signo, &ignore as *const SigAction, 0, 8);
+    }
+    // 不带 SA_RESTART，被打断的调用返回 EINTR
+    let alarm = SigAction {
+        handler: on_alarm as *const () as usize,
+        ..ignore
+    };
+    sys!(SYS_RT_SIGACTION, SIGALRM, &alarm as *const SigAction, 0, 8);
+    let period = [0, ALARM_INTERVAL_US];
+    let timer = ITimerVal {
+        interval: period,
+        value: period,
+    };
+    sys!(SYS_SETITIMER, ITIMER_REAL, &timer as *const ITimerVal, 0);
+}
+
+/// 映射 `len` 字节并把末尾 `guard` 字节设为不可访问
+fn map_region(len: usize, guard: usize) -> usize {
+    let addr = sys!(
+        SYS_MMAP,
+        0,
+        len,
+        PROT_READ_WRITE,
+        MAP_PRIVATE_ANONYMOUS,
+        -1isize,
+        0
+    );
+    if addr < 0 {
+        Line::new().str("fuzz: mmap failed").print();
+        exit(2);
+    }
+    let addr = addr as usize;
+    if guard > 0 {
+        sys!(SYS_MPROTECT, addr + len - guard, guard, PROT_NONE);
+    }
+    addr
+}
+
+/// 解析十进制或 0x 开头的十六进制数
+fn parse_number(s: &[u8]) -> Option<u64> {
+    let (digits, radix) = match s {
+        [b'0', b'x' | b'X', rest @ ..] => (rest, 16),
+        _ => (s, 10),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let mut value: u64 = 0;
+    for &c in digits {
+        let digit = (c as char).to_digit(radix)?;
+        value = value.checked_mul(radix as u64)?.checked_add(digit as u64)?;
+    }
+    Some(value)
+}
+
+/// 取第 `index` 个命令行参数
+///
+/// # Safety
+/// `sp` 必须是进程的初始栈指针。
+unsafe fn arg(sp: *const usize, index: usize) -> Option<&'static [u8]> {
+    unsafe {
+        if index >= *sp {
+            return None;
+        }
+        let ptr = *sp.add(1 + index) as *const core::ffi::c_char;
+        Some(CStr::from_ptr(ptr).to_bytes())
+    }
+}
+
+fn usage() -> ! {
+    Line::new().str("usage: fuzz <seed> <calls> [-v]").print();
+    exit(2)
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn main(sp: *const usize) -> ! {
+    // Safety: `_start` 传入的是进程的初始栈指针
+    let (seed, calls, verbose) = unsafe {
+        let seed = arg(sp, 1).and_then(parse_number);
+        let calls = arg(sp, 2).and_then(parse_number);
+        let verbose = arg(sp, 3) == Some(&b"-v"[..]);
+        match (seed, calls) {
+            (Some(seed), Some(calls)) => (seed, calls as usize, verbose),
+            _ => usage(),
+        }
+    };
+    Line::new()
+        .str("fuzz: seed ")
+        .num(seed as usize, 16)
+        .str(", ")
+        .num(calls, 10)
+        .str(" calls")
+        .print();
+
+    setup_signals();
+    let data = map_region(DATA_SIZE + PAGE_SIZE, PAGE_SIZE);
+    let map = map_region(2 * MAP_SIZE, 0);
+    let mut fuzzer = Fuzzer {
+        rng: Rng::new(seed),
+        pid: sys!(SYS_GETPID) as usize,
+        data,
+        map,
+        fds: [0; MAX_FDS],
+        nfds: 0,
+    };
+
+    for i in 0..calls {
+        if i % RESET_INTERVAL == 0 {
+            fuzzer.reset();
+        }
+        if i % REFILL_INTERVAL == 0 {
+            fuzzer.refill();
+        }
+        fuzzer.step(i, verbose);
+    }
+
+    Line::new()
+        .str("fuzz: completed ")
+        .num(calls, 10)
+        .str(" calls")
+        .print();
+    exit(0)
+}
+
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    Line::new().str("fuzz: panic").print();
+    exit(101)
+}
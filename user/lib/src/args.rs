@@ -0,0 +1,54 @@
+//! 命令行参数
+//!
+//! 内核按 Linux ABI 把参数放在进程的初始栈上：`sp` 处依次为 argc、argv[0..argc]、NULL、envp。
+//! 需要参数的程序在 `_start` 中把 `sp` 传给 Rust 入口，例如：
+//!
+//! ```text
+//! global_asm!(".globl _start", "_start:", "mv a0, sp", "call main");
+//! ```
+
+use core::ffi::CStr;
+
+/// 进程的命令行参数
+pub struct Args {
+    argc: usize,
+    argv: *const *const u8,
+}
+
+impl Args {
+    /// 从进程入口时的栈指针解析参数
+    ///
+    /// # Safety
+    /// `sp` 必须是内核交给 `_start` 的初始栈指针
+    pub unsafe fn from_stack(sp: *const usize) -> Self {
+        unsafe {
+            Self {
+                argc: *sp,
+                argv: sp.add(1) as *const *const u8,
+            }
+        }
+    }
+
+    /// 参数个数（含程序名）
+    pub fn len(&self) -> usize {
+        self.argc
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.argc == 0
+    }
+
+    /// 第 `index` 个参数，不含结尾的 NUL
+    pub fn get(&self, index: usize) -> Option<&'static [u8]> {
+        self.get_cstr(index).map(CStr::to_bytes)
+    }
+
+    /// 第 `index` 个参数，可直接传给系统调用
+    pub fn get_cstr(&self, index: usize) -> Option<&'static CStr> {
+        if index >= self.argc {
+            return None;
+        }
+        // SAFETY: 内核保证 argv[0..argc] 指向以 NUL 结尾的字符串
+        unsafe { Some(CStr::from_ptr(*self.argv.add(index) as *const _)) }
+    }
+}
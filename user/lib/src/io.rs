@@ -73,3 +73,23 @@ pub fn read_line(buffer: &mut [u8]) -> usize {
     // 返回实际读取的字节总数
     current_pos
 }
+
+/// 打印字符串到标准错误 (文件描述符 2)
+pub fn eprint(s: &[u8]) {
+    unsafe { write(2, s, s.len()) };
+}
+
+/// 以十进制打印无符号整数到标准输出
+pub fn print_usize(mut n: usize) {
+    let mut buf = [0u8; 20];
+    let mut pos = buf.len();
+    loop {
+        pos -= 1;
+        buf[pos] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    print(&buf[pos..]);
+}
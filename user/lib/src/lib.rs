@@ -4,6 +4,7 @@
 
 #![no_std]
 #![allow(non_snake_case)]
+pub mod args;
 pub mod io;
 mod syscall;
 pub mod syscall_numbers;
@@ -95,6 +95,48 @@ pub fn open(path: *const c_char) -> isize {
     syscall!(syscall_numbers::SYS_OPENAT, path)
 }
 
+/// openat 的 dirfd：相对当前工作目录
+pub const AT_FDCWD: isize = -100;
+/// 只读打开
+pub const O_RDONLY: usize = 0;
+/// 读写打开
+pub const O_RDWR: usize = 2;
+
+/// 相对目录打开文件
+/// # 参数
+/// - dirfd: 目录文件描述符，`AT_FDCWD` 表示当前工作目录
+/// - path: 以 NUL 结尾的路径
+/// - flags: 打开标志（`O_RDONLY`、`O_RDWR` 等）
+/// - mode: 创建文件时的权限
+/// # 返回值
+/// 成功时返回文件描述符，失败时返回负的错误码
+pub fn openat(dirfd: isize, path: *const c_char, flags: usize, mode: usize) -> isize {
+    syscall!(syscall_numbers::SYS_OPENAT, dirfd, path, flags, mode)
+}
+
+/// lseek 的 whence：从文件开头计算
+pub const SEEK_SET: usize = 0;
+
+/// 修改文件偏移量
+/// # 参数
+/// - fd: 文件描述符
+/// - offset: 偏移量
+/// - whence: 计算方式（`SEEK_SET` 等）
+/// # 返回值
+/// 成功时返回新的偏移量，失败时返回负的错误码
+pub fn lseek(fd: usize, offset: isize, whence: usize) -> isize {
+    syscall!(syscall_numbers::SYS_LSEEK, fd, offset, whence)
+}
+
+/// 把文件的修改刷到存储设备
+/// # 参数
+/// - fd: 文件描述符
+/// # 返回值
+/// 成功时返回0，失败时返回负的错误码
+pub fn fsync(fd: usize) -> isize {
+    syscall!(syscall_numbers::SYS_FSYNC, fd)
+}
+
 /// 关闭文件
 /// # 参数
 /// - fd: 要关闭的文件描述符
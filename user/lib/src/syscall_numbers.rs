@@ -44,3 +44,5 @@ pub const SYS_READ: usize = 63;
 pub const SYS_WRITE: usize = 64;
 /// fstat - 获取文件状态
 pub const SYS_FSTAT: usize = 80;
+/// fsync - 把文件数据刷到存储设备
+pub const SYS_FSYNC: usize = 82;
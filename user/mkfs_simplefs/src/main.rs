@@ -0,0 +1,90 @@
+//! mkfs_simplefs - 在块设备上创建空的 SimpleFS
+//!
+//! 用法：`mkfs_simplefs <device>`，例如 `mkfs_simplefs /dev/vdb`。
+//! 镜像格式见内核 `fs/simple_fs/image.rs`；空文件系统只有一个 32 字节的第 2 版镜像头，
+//! 之后可用 `mount -t simplefs <device> <dir>` 挂载。
+
+#![no_std]
+#![no_main]
+
+use core::arch::global_asm;
+
+use lib::args::Args;
+use lib::io::{eprint, print};
+use lib::{AT_FDCWD, O_RDWR, close, exit, fsync, openat, write};
+
+/// 以下常量与内核 `fs/simple_fs/image.rs` 保持一致
+const IMAGE_MAGIC: &[u8; 8] = b"RAMDISK\0";
+const VERSION: u32 = 2;
+const HEADER_SIZE: usize = 32;
+const VERSION_OFFSET: usize = 12;
+const CHECKSUM_OFFSET: usize = 16;
+const BLOCK_SIZE: usize = 512;
+
+global_asm!(".globl _start", "_start:", "mv a0, sp", "call main");
+
+#[unsafe(no_mangle)]
+extern "C" fn main(sp: *const usize) -> ! {
+    // SAFETY: `_start` 传入的是进程的初始栈指针
+    let args = unsafe { Args::from_stack(sp) };
+    let (Some(device), None) = (args.get_cstr(1), args.get(2)) else {
+        eprint(b"usage: mkfs_simplefs <device>\n");
+        exit(1)
+    };
+
+    let fd = openat(AT_FDCWD, device.as_ptr(), O_RDWR, 0);
+    if fd < 0 {
+        fail(device.to_bytes(), b"cannot open device");
+    }
+    let fd = fd as usize;
+
+    // 镜像头：魔数 + 文件数量 0 + 版本 + 校验和，其余清零；整块写入，覆盖旧镜像头
+    let mut header = [0u8; BLOCK_SIZE];
+    header[..IMAGE_MAGIC.len()].copy_from_slice(IMAGE_MAGIC);
+    header[VERSION_OFFSET..VERSION_OFFSET + 4].copy_from_slice(&VERSION.to_le_bytes());
+    let checksum = crc32(&header[..HEADER_SIZE]);
+    header[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4].copy_from_slice(&checksum.to_le_bytes());
+    if unsafe { write(fd, &header, header.len()) } != header.len() as isize {
+        fail(device.to_bytes(), b"write failed");
+    }
+    if fsync(fd) < 0 {
+        fail(device.to_bytes(), b"fsync failed");
+    }
+    close(fd);
+
+    print(b"mkfs_simplefs: created empty SimpleFS on ");
+    print(device.to_bytes());
+    print(b"\n");
+    exit(0)
+}
+
+/// CRC-32（IEEE 802.3），与内核 `util::crc32` 相同
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn fail(device: &[u8], what: &[u8]) -> ! {
+    eprint(b"mkfs_simplefs: ");
+    eprint(device);
+    eprint(b": ");
+    eprint(what);
+    eprint(b"\n");
+    exit(1)
+}
+
+#[cfg(not(test))]
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    exit(-1)
+}